@@ -113,6 +113,12 @@ impl BitSet {
     pub fn byte_len(&self) -> usize {
         self.buffer.len()
     }
+
+    /// Returns the number of bytes allocated by this bitset, including
+    /// capacity reserved for future appends
+    pub fn allocated_bytes(&self) -> usize {
+        self.buffer.capacity()
+    }
 }
 
 /// Returns an iterator over set bit positions in increasing order
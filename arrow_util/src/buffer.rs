@@ -0,0 +1,133 @@
+use std::marker::PhantomData;
+use std::mem;
+
+use arrow::buffer::{Buffer, MutableBuffer};
+use arrow::datatypes::ArrowNativeType;
+
+/// A growable array of fixed-width values, like a `Vec<T>`, that is stored
+/// in an arrow-compatible allocation so that it can be converted into an
+/// arrow [`Buffer`] without copying its values
+#[derive(Debug)]
+pub struct TypedBuffer<T> {
+    buffer: MutableBuffer,
+    phantom: PhantomData<T>,
+}
+
+impl<T: ArrowNativeType> Default for TypedBuffer<T> {
+    fn default() -> Self {
+        Self {
+            buffer: MutableBuffer::new(0),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ArrowNativeType> TypedBuffer<T> {
+    /// Creates an empty buffer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a buffer of `len` copies of `value`
+    pub fn new_with_value(len: usize, value: T) -> Self {
+        let mut buffer = Self::new();
+        buffer.resize(len, value);
+        buffer
+    }
+
+    /// Returns the number of values in the buffer
+    pub fn len(&self) -> usize {
+        self.buffer.len() / mem::size_of::<T>()
+    }
+
+    /// Returns true if the buffer holds no values
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of values the buffer can hold without allocating
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity() / mem::size_of::<T>()
+    }
+
+    /// Appends `value`
+    pub fn push(&mut self, value: T) {
+        self.buffer.push(value)
+    }
+
+    /// Grows the buffer to `new_len` values, filling it with `value`
+    ///
+    /// Panics if `new_len` is less than the current length
+    pub fn resize(&mut self, new_len: usize, value: T) {
+        let len = self.len();
+        assert!(new_len >= len, "cannot shrink buffer");
+        self.buffer.reserve((new_len - len) * mem::size_of::<T>());
+        for _ in len..new_len {
+            self.buffer.push(value);
+        }
+    }
+
+    /// Returns the values of the buffer
+    pub fn as_slice(&self) -> &[T] {
+        // Safety: arrow allocations are aligned to more than the width of
+        // any native type, and the buffer only ever holds values of type T
+        let (prefix, values, suffix) = unsafe { self.buffer.as_slice().align_to::<T>() };
+        assert!(prefix.is_empty() && suffix.is_empty());
+        values
+    }
+
+    /// Returns the values of the buffer for modification
+    pub fn as_slice_mut(&mut self) -> &mut [T] {
+        // Safety: see `as_slice`
+        let (prefix, values, suffix) = unsafe { self.buffer.as_slice_mut().align_to_mut::<T>() };
+        assert!(prefix.is_empty() && suffix.is_empty());
+        values
+    }
+
+    /// Returns a copy of the values as an arrow [`Buffer`]
+    pub fn to_arrow(&self) -> Buffer {
+        Buffer::from(self.buffer.as_slice())
+    }
+
+    /// Converts the values into an arrow [`Buffer`] without copying them.
+    /// The buffer keeps the capacity of this buffer
+    pub fn into_arrow(self) -> Buffer {
+        self.buffer.into()
+    }
+}
+
+impl<T: ArrowNativeType> Extend<T> for TypedBuffer<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.buffer
+            .reserve(iter.size_hint().0 * mem::size_of::<T>());
+        for value in iter {
+            self.buffer.push(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_buffer() {
+        let mut buffer = TypedBuffer::new_with_value(3, -1_i32);
+        buffer.push(5);
+        buffer.resize(6, 0);
+        buffer.as_slice_mut()[1] = 7;
+        buffer.extend(vec![8, 9]);
+
+        assert_eq!(buffer.len(), 8);
+        assert!(buffer.capacity() >= 8);
+        assert_eq!(buffer.as_slice(), &[-1, 7, -1, 5, 0, 0, 8, 9]);
+
+        let copy = buffer.to_arrow();
+        let ptr = buffer.as_slice().as_ptr() as *const u8;
+        let arrow = buffer.into_arrow();
+        assert_eq!(copy, arrow);
+        // the values were not copied
+        assert_eq!(arrow.as_ptr(), ptr);
+    }
+}
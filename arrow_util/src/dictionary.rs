@@ -93,6 +93,13 @@ impl<K: AsPrimitive<usize> + FromPrimitive + Zero> StringDictionary<K> {
         self.storage.size() + self.dedup.len() * std::mem::size_of::<K>()
     }
 
+    /// Returns the amount of memory in bytes allocated by this dictionary,
+    /// including capacity reserved for future inserts. Each slot of the
+    /// hash table holds a key and a control byte.
+    pub fn allocated_bytes(&self) -> usize {
+        self.storage.allocated_bytes() + self.dedup.capacity() * (std::mem::size_of::<K>() + 1)
+    }
+
     pub fn values(&self) -> &PackedStringArray<K> {
         &self.storage
     }
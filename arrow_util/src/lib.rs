@@ -2,6 +2,7 @@
 #![allow(clippy::clone_on_ref_ptr)]
 
 pub mod bitset;
+pub mod buffer;
 pub mod dictionary;
 pub mod string;
 pub mod util;
@@ -86,6 +86,12 @@ impl<K: AsPrimitive<usize> + FromPrimitive + Zero> PackedStringArray<K> {
         self.storage.len() + self.offsets.len() * std::mem::size_of::<K>()
     }
 
+    /// Return the amount of memory in bytes allocated by this array,
+    /// including capacity reserved for future appends
+    pub fn allocated_bytes(&self) -> usize {
+        self.storage.capacity() + self.offsets.capacity() * std::mem::size_of::<K>()
+    }
+
     pub fn into_inner(self) -> (Vec<K>, String) {
         (self.offsets, self.storage)
     }
@@ -160,4 +166,15 @@ mod tests {
         assert_eq!(array.get(9).unwrap(), "");
         assert_eq!(array.get(3).unwrap(), "");
     }
+
+    #[test]
+    fn test_allocated_bytes() {
+        let mut array = PackedStringArray::<i32>::new();
+        assert!(array.allocated_bytes() >= array.size());
+
+        for _ in 0..100 {
+            array.append("hello");
+            assert!(array.allocated_bytes() >= array.size());
+        }
+    }
 }
@@ -0,0 +1,386 @@
+//! Generates synthetic line protocol, and the entries built from it, for
+//! load tests and benchmarks.
+//!
+//! Every measurement contains one series per combination of tag values, and
+//! each series receives `samples` points. The tag values of consecutive lines
+//! vary the first tag key fastest, so the default configuration produces the
+//! same shape of data as `inch` with three tag keys:
+//!
+//! ```text
+//! m0,tag0=value0,tag1=value0,tag2=value0 v0=0.33 1618246883097384000
+//! m0,tag0=value1,tag1=value0,tag2=value0 v0=0.72 1618246883100984000
+//! ...
+//! m0,tag0=value9,tag1=value99,tag2=value999 v0=0.06 1618250483093784000
+//! ```
+#![deny(broken_intra_doc_links, rust_2018_idioms)]
+#![warn(
+    missing_copy_implementations,
+    missing_debug_implementations,
+    clippy::explicit_iter_loop,
+    clippy::use_self,
+    clippy::clone_on_ref_ptr
+)]
+
+use std::{fmt::Write, str::FromStr};
+
+use data_types::{
+    database_rules::{PartitionTemplate, ShardConfig, TemplatePart},
+    timestamp::Precision,
+};
+use entry::{lines_to_sharded_entries, Entry};
+use influxdb_line_protocol::parse_lines;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use snafu::{ensure, ResultExt, Snafu};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("At least one measurement must be generated"))]
+    NoMeasurements,
+
+    #[snafu(display("At least one field type must be specified"))]
+    NoFields,
+
+    #[snafu(display("The cardinality of tag{} must be greater than zero", index))]
+    ZeroCardinality { index: usize },
+
+    #[snafu(display(
+        "Invalid field type '{}', expected one of i64, u64, f64, bool or string",
+        name
+    ))]
+    InvalidFieldType { name: String },
+
+    #[snafu(display("Error parsing generated line protocol: {}", source))]
+    ParsingLineProtocol {
+        source: influxdb_line_protocol::Error,
+    },
+
+    #[snafu(display("Error converting generated line protocol to entries: {}", source))]
+    ConvertingToEntries { source: entry::Error },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The type of the values generated for a field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Integer,
+    UInteger,
+    Float,
+    Boolean,
+    String,
+}
+
+impl FromStr for FieldType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "i64" => Ok(Self::Integer),
+            "u64" => Ok(Self::UInteger),
+            "f64" => Ok(Self::Float),
+            "bool" => Ok(Self::Boolean),
+            "string" => Ok(Self::String),
+            _ => InvalidFieldType { name: s }.fail(),
+        }
+    }
+}
+
+/// Describes the data to generate
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The number of measurements, named `m0`, `m1`, ...
+    pub measurements: usize,
+
+    /// The number of distinct values of each tag key, named `tag0`, `tag1`,
+    /// ... The number of series in each measurement is the product of these
+    pub tag_cardinalities: Vec<usize>,
+
+    /// The type of each field, named `v0`, `v1`, ...
+    pub field_types: Vec<FieldType>,
+
+    /// The number of points written to each series
+    pub samples: usize,
+
+    /// The timestamp of the first line, in nanoseconds since the epoch
+    pub start_time: i64,
+
+    /// The nanoseconds between the timestamps of consecutive lines
+    pub spacing: i64,
+
+    /// The seed of the random field values, the same seed always generates
+    /// the same data
+    pub seed: u64,
+}
+
+impl Default for Config {
+    /// One measurement with one million series over an hour, the shape of the
+    /// data used by the benchmarks
+    fn default() -> Self {
+        Self {
+            measurements: 1,
+            tag_cardinalities: vec![10, 100, 1000],
+            field_types: vec![FieldType::Float],
+            samples: 1,
+            start_time: 1_618_246_883_097_384_000,
+            spacing: 3_600_000,
+            seed: 0,
+        }
+    }
+}
+
+impl Config {
+    /// Returns an error if the configuration does not describe valid line
+    /// protocol
+    pub fn validate(&self) -> Result<()> {
+        ensure!(self.measurements > 0, NoMeasurements);
+        ensure!(!self.field_types.is_empty(), NoFields);
+        if let Some(index) = self.tag_cardinalities.iter().position(|c| *c == 0) {
+            return ZeroCardinality { index }.fail();
+        }
+        Ok(())
+    }
+
+    /// The number of series in each measurement
+    pub fn series(&self) -> usize {
+        self.tag_cardinalities.iter().product()
+    }
+
+    /// The total number of lines generated
+    pub fn line_count(&self) -> usize {
+        self.samples * self.measurements * self.series()
+    }
+
+    /// Returns an iterator over the generated lines, without trailing newlines
+    pub fn lines(&self) -> Result<Lines<'_>> {
+        self.validate()?;
+        Ok(Lines {
+            config: self,
+            rng: StdRng::seed_from_u64(self.seed),
+            line: 0,
+        })
+    }
+
+    /// Returns all the generated lines as a single string
+    pub fn line_protocol(&self) -> Result<String> {
+        let mut lp = String::new();
+        for line in self.lines()? {
+            lp.push_str(&line);
+            lp.push('\n');
+        }
+        Ok(lp)
+    }
+
+    /// Converts the generated lines to entries of at most `lines_per_entry`
+    /// lines each, partitioned by the hour of their timestamp
+    pub fn entries(&self, lines_per_entry: usize) -> Result<Vec<Entry>> {
+        let partitioner =
+            PartitionTemplate::new(vec![TemplatePart::TimeFormat("%Y-%m-%dT%H".to_string())]);
+
+        let mut lines = self.lines()?.peekable();
+        let mut entries = vec![];
+        while lines.peek().is_some() {
+            let lp = lines
+                .by_ref()
+                .take(lines_per_entry.max(1))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let parsed = parse_lines(&lp)
+                .collect::<Result<Vec<_>, _>>()
+                .context(ParsingLineProtocol)?;
+
+            let sharded_entries = lines_to_sharded_entries(
+                &parsed,
+                self.start_time,
+                Precision::Nanoseconds,
+                None as Option<&ShardConfig>,
+                &partitioner,
+            )
+            .context(ConvertingToEntries)?;
+            entries.extend(sharded_entries.into_iter().map(|sharded| sharded.entry));
+        }
+
+        Ok(entries)
+    }
+}
+
+/// An iterator over the lines described by a [`Config`]
+#[derive(Debug)]
+pub struct Lines<'a> {
+    config: &'a Config,
+    rng: StdRng,
+    line: usize,
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.line >= self.config.line_count() {
+            return None;
+        }
+
+        let series = self.config.series();
+        let measurement = (self.line / series) % self.config.measurements;
+        let mut tag_values = self.line % series;
+
+        let mut line = format!("m{}", measurement);
+        for (index, cardinality) in self.config.tag_cardinalities.iter().enumerate() {
+            write!(line, ",tag{}=value{}", index, tag_values % cardinality).unwrap();
+            tag_values /= cardinality;
+        }
+
+        for (index, field_type) in self.config.field_types.iter().enumerate() {
+            let separator = if index == 0 { ' ' } else { ',' };
+            write!(line, "{}v{}=", separator, index).unwrap();
+            match field_type {
+                FieldType::Integer => write!(line, "{}i", self.rng.gen_range(-1000..1000)),
+                FieldType::UInteger => write!(line, "{}u", self.rng.gen_range(0..1000)),
+                FieldType::Float => write!(line, "{:.2}", self.rng.gen::<f64>()),
+                FieldType::Boolean => write!(line, "{}", self.rng.gen::<bool>()),
+                FieldType::String => {
+                    write!(line, "\"value{}\"", self.rng.gen_range(0..1000))
+                }
+            }
+            .unwrap();
+        }
+
+        let timestamp = self.config.start_time + self.line as i64 * self.config.spacing;
+        write!(line, " {}", timestamp).unwrap();
+
+        self.line += 1;
+        Some(line)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.config.line_count() - self.line;
+        (remaining, Some(remaining))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use influxdb_line_protocol::FieldValue;
+
+    use super::*;
+
+    fn small_config() -> Config {
+        Config {
+            measurements: 2,
+            tag_cardinalities: vec![2, 3],
+            field_types: vec![
+                FieldType::Integer,
+                FieldType::UInteger,
+                FieldType::Float,
+                FieldType::Boolean,
+                FieldType::String,
+            ],
+            samples: 2,
+            start_time: 100,
+            spacing: 10,
+            seed: 7,
+        }
+    }
+
+    #[test]
+    fn generates_configured_shape() {
+        let config = small_config();
+        let lp = config.line_protocol().unwrap();
+        let lines: Vec<_> = parse_lines(&lp).map(|l| l.unwrap()).collect();
+        assert_eq!(lines.len(), 24);
+        assert_eq!(config.line_count(), 24);
+
+        assert_eq!(
+            lp.lines().next().unwrap().split(' ').next().unwrap(),
+            "m0,tag0=value0,tag1=value0"
+        );
+
+        let series: BTreeSet<_> = lines.iter().map(|l| l.series.to_string()).collect();
+        assert_eq!(series.len(), 12);
+
+        let timestamps: Vec<_> = lines.iter().map(|l| l.timestamp.unwrap()).collect();
+        let expected: Vec<_> = (0..24).map(|i| 100 + i * 10).collect();
+        assert_eq!(timestamps, expected);
+
+        for line in &lines {
+            let types: Vec<_> = line
+                .field_set
+                .iter()
+                .map(|(_, value)| match value {
+                    FieldValue::I64(_) => FieldType::Integer,
+                    FieldValue::U64(_) => FieldType::UInteger,
+                    FieldValue::F64(_) => FieldType::Float,
+                    FieldValue::Boolean(_) => FieldType::Boolean,
+                    FieldValue::String(_) => FieldType::String,
+                })
+                .collect();
+            assert_eq!(types, config.field_types);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_data() {
+        let config = small_config();
+        assert_eq!(
+            config.line_protocol().unwrap(),
+            config.line_protocol().unwrap()
+        );
+
+        let other = Config {
+            seed: 8,
+            ..small_config()
+        };
+        assert_ne!(
+            config.line_protocol().unwrap(),
+            other.line_protocol().unwrap()
+        );
+    }
+
+    #[test]
+    fn entries() {
+        let config = Config {
+            spacing: 60_000_000_000,
+            ..small_config()
+        };
+
+        // 24 lines a minute apart fall into the same hour, so each batch of
+        // lines becomes a single entry
+        let entries = config.entries(5).unwrap();
+        assert_eq!(entries.len(), 5);
+
+        let mut rows = 0;
+        for entry in &entries {
+            for write in entry.partition_writes().unwrap() {
+                assert_eq!(write.key(), "1970-01-01T00");
+                for batch in write.table_batches() {
+                    rows += batch.row_count();
+                }
+            }
+        }
+        assert_eq!(rows, 24);
+    }
+
+    #[test]
+    fn invalid_config() {
+        let config = Config {
+            field_types: vec![],
+            ..small_config()
+        };
+        assert!(matches!(config.validate(), Err(Error::NoFields)));
+
+        let config = Config {
+            tag_cardinalities: vec![1, 0],
+            ..small_config()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(Error::ZeroCardinality { index: 1 })
+        ));
+
+        assert!(matches!(
+            "i32".parse::<FieldType>(),
+            Err(Error::InvalidFieldType { .. })
+        ));
+    }
+}
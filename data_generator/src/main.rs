@@ -0,0 +1,107 @@
+//! Writes synthetic line protocol or entries for load tests.
+//!
+//! ```text
+//! # 10 measurements of 100 series with an integer and a float field
+//! data_generator --measurements 10 --tag-cardinalities 10,10 --field-types i64,f64 > data.lp
+//!
+//! # The same data as one entry per file in `entries/`
+//! data_generator --measurements 10 --tag-cardinalities 10,10 --field-types i64,f64 \
+//!     --format entry --output entries
+//! ```
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use data_generator::{Config, FieldType};
+use structopt::StructOpt;
+
+/// Generates synthetic line protocol, or the entries built from it
+#[derive(Debug, StructOpt)]
+#[structopt(name = "data_generator")]
+struct Args {
+    /// The number of measurements
+    #[structopt(long, default_value = "1")]
+    measurements: usize,
+
+    /// The comma separated cardinalities of the tag keys of each measurement
+    #[structopt(long, use_delimiter = true, default_value = "10,100,1000")]
+    tag_cardinalities: Vec<usize>,
+
+    /// The comma separated types of the fields of each measurement, each one
+    /// of i64, u64, f64, bool or string
+    #[structopt(long, use_delimiter = true, default_value = "f64")]
+    field_types: Vec<FieldType>,
+
+    /// The number of points written to each series
+    #[structopt(long, default_value = "1")]
+    samples: usize,
+
+    /// The timestamp of the first line in nanoseconds since the epoch
+    #[structopt(long, default_value = "1618246883097384000")]
+    start_time: i64,
+
+    /// The nanoseconds between the timestamps of consecutive lines
+    #[structopt(long, default_value = "3600000")]
+    spacing: i64,
+
+    /// The seed of the random field values
+    #[structopt(long, default_value = "0")]
+    seed: u64,
+
+    /// Write line protocol (lp) or flatbuffers entries (entry)
+    #[structopt(long, default_value = "lp", possible_values = &["lp", "entry"])]
+    format: String,
+
+    /// The number of lines in each entry
+    #[structopt(long, default_value = "10000")]
+    lines_per_entry: usize,
+
+    /// The file to write the line protocol to, or the directory to write one
+    /// file per entry to. Line protocol is written to stdout if not specified
+    #[structopt(long, short, parse(from_os_str))]
+    output: Option<PathBuf>,
+}
+
+fn main() {
+    if let Err(e) = run(Args::from_args()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config {
+        measurements: args.measurements,
+        tag_cardinalities: args.tag_cardinalities,
+        field_types: args.field_types,
+        samples: args.samples,
+        start_time: args.start_time,
+        spacing: args.spacing,
+        seed: args.seed,
+    };
+
+    if args.format == "entry" {
+        let dir = args.output.ok_or("--output is required to write entries")?;
+        fs::create_dir_all(&dir)?;
+
+        let entries = config.entries(args.lines_per_entry)?;
+        for (i, entry) in entries.iter().enumerate() {
+            fs::write(dir.join(format!("{:08}.entry", i)), entry.data())?;
+        }
+        eprintln!("wrote {} entries to {}", entries.len(), dir.display());
+        return Ok(());
+    }
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(fs::File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+    for line in config.lines()? {
+        writeln!(out, "{}", line)?;
+    }
+    out.flush()?;
+
+    Ok(())
+}
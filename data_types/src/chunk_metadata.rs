@@ -36,6 +36,70 @@ impl ChunkStorage {
     }
 }
 
+/// A lifecycle state a chunk can transition into
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+pub enum ChunkLifecycleState {
+    /// The chunk was created in the Mutable Buffer and is open for writes
+    Open,
+
+    /// The chunk was closed for writes
+    Closed,
+
+    /// The chunk started moving to the Read Buffer
+    Moving,
+
+    /// The chunk was moved to the Read Buffer
+    Moved,
+
+    /// The chunk started being written to object storage
+    Persisting,
+
+    /// The chunk was written to object storage
+    Persisted,
+
+    /// The chunk was unloaded from the Read Buffer and now only exists in
+    /// object storage
+    Unloaded,
+
+    /// The chunk was dropped
+    Dropped,
+}
+
+impl ChunkLifecycleState {
+    /// Return a str representation of this lifecycle state
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "Open",
+            Self::Closed => "Closed",
+            Self::Moving => "Moving",
+            Self::Moved => "Moved",
+            Self::Persisting => "Persisting",
+            Self::Persisted => "Persisted",
+            Self::Unloaded => "Unloaded",
+            Self::Dropped => "Dropped",
+        }
+    }
+}
+
+/// Notification that a chunk transitioned into a new lifecycle state
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ChunkEvent {
+    /// The partition key of the chunk
+    pub partition_key: Arc<str>,
+
+    /// The table of the chunk
+    pub table_name: Arc<str>,
+
+    /// The id of the chunk
+    pub id: u32,
+
+    /// The state the chunk transitioned into
+    pub state: ChunkLifecycleState,
+
+    /// Time of the transition
+    pub time: DateTime<Utc>,
+}
+
 /// Represents metadata about the physical storage of a chunk in a
 /// database.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
@@ -55,6 +119,12 @@ pub struct ChunkSummary {
     /// The total estimated size of this chunk, in bytes
     pub estimated_bytes: usize,
 
+    /// The memory allocated by this chunk, in bytes. For chunks in the open
+    /// mutable buffer this includes the spare capacity of their
+    /// dictionaries and column builders, for all other chunks it is the
+    /// same as `estimated_bytes`
+    pub allocated_bytes: usize,
+
     /// The total number of rows in this chunk
     pub row_count: usize,
 
@@ -70,6 +140,30 @@ pub struct ChunkSummary {
     /// Time at which this chunk was marked as closed. Note this is
     /// not the same as the timestamps on the data itself
     pub time_closed: Option<DateTime<Utc>>,
+
+    /// Storage statistics of the read buffer representation of this
+    /// chunk, if it has one
+    pub read_buffer: Option<ReadBufferChunkSummary>,
+}
+
+/// Represents metadata about the read buffer representation of a chunk
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub struct ReadBufferChunkSummary {
+    /// The total number of rows in the read buffer chunk
+    pub row_count: u64,
+
+    /// The total number of row groups in the read buffer chunk
+    pub row_groups: usize,
+
+    /// The total estimated size of the compressed (encoded) data, in bytes
+    pub compressed_bytes: usize,
+
+    /// The estimated size of the data if it was stored uncompressed and
+    /// contiguously, including NULL values, in bytes
+    pub raw_bytes: usize,
+
+    /// Most recent time at which the chunk was queried, if ever
+    pub time_of_last_access: Option<DateTime<Utc>>,
 }
 
 /// Represents metadata about the physical storage of a column in a chunk
@@ -80,6 +174,18 @@ pub struct ChunkColumnSummary {
 
     /// Estimated size, in bytes, consumed by this column.
     pub estimated_bytes: usize,
+
+    /// The encoding(s) used to store this column, if known. Columns of
+    /// read buffer chunks may be encoded differently in each row group,
+    /// in which case this is a comma separated list.
+    pub encoding: Option<String>,
+
+    /// Estimated size, in bytes, of this column if its values were stored
+    /// contiguously and uncompressed, if known.
+    pub raw_bytes: Option<usize>,
+
+    /// The number of distinct values in this column, if known.
+    pub cardinality: Option<u64>,
 }
 
 /// Contains additional per-column details about physical storage of a chunk
@@ -108,10 +214,12 @@ impl ChunkSummary {
             id,
             storage,
             estimated_bytes,
+            allocated_bytes: estimated_bytes,
             row_count,
             time_of_first_write: None,
             time_of_last_write: None,
             time_closed: None,
+            read_buffer: None,
         }
     }
 }
@@ -1,6 +1,10 @@
 use crate::{consistent_hasher::ConsistentHasher, server_id::ServerId, DatabaseName};
-use chrono::{TimeZone, Utc};
+use chrono::{
+    format::{Item, StrftimeItems},
+    TimeZone, Utc,
+};
 use influxdb_line_protocol::ParsedLine;
+use once_cell::sync::OnceCell;
 use regex::Regex;
 use snafu::{OptionExt, Snafu};
 use std::num::NonZeroU64;
@@ -25,6 +29,12 @@ pub enum Error {
 
     #[snafu(display("No shards defined"))]
     NoShardsDefined,
+
+    #[snafu(display("Invalid tag predicate '{}': {}", predicate, reason))]
+    InvalidTagPredicate { predicate: String, reason: String },
+
+    #[snafu(display("Invalid partition template '{}': {}", template, reason))]
+    InvalidPartitionTemplate { template: String, reason: String },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -51,8 +61,35 @@ pub struct DatabaseRules {
     /// Defaults to 500 seconds.
     pub worker_cleanup_avg_sleep: Duration,
 
-    /// An optional connection string to a write buffer.
+    /// An optional connection string to a write buffer: the address of a
+    /// Kafka cluster, or `file://` followed by the directory of a local
+    /// write-ahead log.
     pub write_buffer_connection_string: Option<String>,
+
+    /// An optional config to asynchronously replicate the entries written
+    /// to this database to peer servers.
+    pub replication_config: Option<ReplicationConfig>,
+
+    /// Limits on the schema and amount of data of this database
+    pub quotas: Quotas,
+
+    /// Limits on the size and shape of each written line
+    pub line_limits: LineLimits,
+
+    /// How to handle writes whose column types conflict with the types of
+    /// the columns already stored
+    pub schema_conflict_policy: SchemaConflictPolicy,
+
+    /// Numeric field types that may be converted to other numeric field
+    /// types when they conflict, which takes precedence over
+    /// `schema_conflict_policy`
+    pub type_coercion_rules: Vec<TypeCoercionRule>,
+
+    /// Tables that are periodically aggregated into other tables
+    pub rollup_rules: Vec<RollupRule>,
+
+    /// Queries that are run on a schedule, writing their result into a table
+    pub continuous_queries: Vec<ContinuousQuery>,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -84,6 +121,13 @@ impl DatabaseRules {
             routing_rules: None,
             worker_cleanup_avg_sleep: Duration::from_secs(500),
             write_buffer_connection_string: None,
+            replication_config: None,
+            quotas: Default::default(),
+            line_limits: Default::default(),
+            schema_conflict_policy: Default::default(),
+            type_coercion_rules: vec![],
+            rollup_rules: vec![],
+            continuous_queries: vec![],
         }
     }
 
@@ -120,8 +164,37 @@ pub struct LifecycleRules {
     /// Once a chunk of data within a partition reaches this number of bytes
     /// writes outside its keyspace will be directed to a new chunk and this
     /// chunk will be compacted to the read buffer as soon as possible
+    ///
+    /// The size of a chunk in the mutable buffer is the memory it allocated,
+    /// including the spare capacity of its dictionaries and column builders
+    ///
+    /// Checked on every write and by the lifecycle policy, so that lowering
+    /// the threshold also rolls over chunks that are no longer written to
     pub mutable_size_threshold: Option<NonZeroUsize>,
 
+    /// A chunk of data within a partition that received its first write
+    /// this many seconds ago will be frozen and compacted, even if it is
+    /// still receiving writes. The lifecycle policy rolls it over right
+    /// away, so that further writes are directed to a new chunk while it
+    /// waits to be compacted
+    pub mutable_max_age_seconds: Option<NonZeroU32>,
+
+    /// Once a chunk of data within a partition reaches this number of rows
+    /// writes will be directed to a new chunk and this chunk will be
+    /// compacted to the read buffer as soon as possible
+    ///
+    /// Checked like [`mutable_size_threshold`](Self::mutable_size_threshold)
+    pub mutable_row_threshold: Option<NonZeroUsize>,
+
+    /// Share the memory for tag values between the open chunks of all
+    /// partitions, instead of storing a copy of them in every chunk
+    pub mutable_shared_tag_values: bool,
+
+    /// A chunk in the read buffer is only persisted once its first write is
+    /// at least this many seconds old, so that late arriving data can still
+    /// be compacted with it. Only used if [`persist`](Self::persist) is set
+    pub persist_age_threshold_seconds: Option<NonZeroU32>,
+
     /// Once the total amount of buffered data in memory reaches this size start
     /// dropping data from memory based on the [`sort_order`](Self::sort_order)
     pub buffer_size_soft: Option<NonZeroUsize>,
@@ -236,33 +309,94 @@ pub enum ColumnValue {
 
 /// `PartitionTemplate` is used to compute the partition key of each row that
 /// gets written. It can consist of the table name, a column name and its value,
-/// a formatted time, or a string column and regex captures of its value. For
-/// columns that do not appear in the input row, a blank value is output.
+/// a formatted time, a string column and regex captures of its value, or a
+/// template string combining time formats and column values. For columns that
+/// do not appear in the input row, a blank value is output.
 ///
 /// The key is constructed in order of the template parts; thus ordering changes
 /// what partition key is generated.
-#[derive(Debug, Default, Eq, PartialEq, Clone)]
+///
+/// The time formats of the parts are parsed once, the first time a partition
+/// key is computed, and reused for every following row.
+#[derive(Default, Clone)]
 pub struct PartitionTemplate {
-    pub parts: Vec<TemplatePart>,
+    parts: Vec<TemplatePart>,
+    parsed: OnceCell<Vec<ParsedPart>>,
+}
+
+impl PartitionTemplate {
+    pub fn new(parts: Vec<TemplatePart>) -> Self {
+        Self {
+            parts,
+            parsed: OnceCell::new(),
+        }
+    }
+
+    /// The parts the partition key is constructed from
+    pub fn parts(&self) -> &[TemplatePart] {
+        &self.parts
+    }
+
+    pub fn into_parts(self) -> Vec<TemplatePart> {
+        self.parts
+    }
+}
+
+impl std::fmt::Debug for PartitionTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PartitionTemplate")
+            .field("parts", &self.parts)
+            .finish()
+    }
+}
+
+impl PartialEq for PartitionTemplate {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts == other.parts
+    }
 }
 
+impl Eq for PartitionTemplate {}
+
 impl Partitioner for PartitionTemplate {
     fn partition_key(&self, line: &ParsedLine<'_>, default_time: i64) -> Result<String> {
+        let parsed = self.parsed.get_or_try_init(|| {
+            self.parts
+                .iter()
+                .map(TemplatePart::parse)
+                .collect::<Result<_>>()
+        })?;
+
         let parts: Vec<_> = self
             .parts
             .iter()
-            .map(|p| match p {
-                TemplatePart::Table => line.series.measurement.to_string(),
-                TemplatePart::Column(column) => match line.tag_value(&column) {
+            .zip(parsed)
+            .map(|(part, parsed)| match (part, parsed) {
+                (TemplatePart::Table, _) => line.series.measurement.to_string(),
+                (TemplatePart::Column(column), _) => match column_value(line, column) {
                     Some(v) => format!("{}_{}", column, v),
-                    None => match line.field_value(&column) {
-                        Some(v) => format!("{}_{}", column, v),
-                        None => "".to_string(),
-                    },
+                    None => "".to_string(),
                 },
-                TemplatePart::TimeFormat(format) => {
+                (_, ParsedPart::TimeFormat(items)) => {
+                    let nanos = line.timestamp.unwrap_or(default_time);
+                    Utc.timestamp_nanos(nanos)
+                        .format_with_items(items.iter())
+                        .to_string()
+                }
+                (_, ParsedPart::Template(segments)) => {
                     let nanos = line.timestamp.unwrap_or(default_time);
-                    Utc.timestamp_nanos(nanos).format(&format).to_string()
+                    let time = Utc.timestamp_nanos(nanos);
+                    segments
+                        .iter()
+                        .map(|segment| match segment {
+                            TemplateSegment::Time(items) => {
+                                time.format_with_items(items.iter()).to_string()
+                            }
+                            TemplateSegment::Column(column) => {
+                                column_value(line, column).unwrap_or_default()
+                            }
+                        })
+                        .collect()
                 }
                 _ => unimplemented!(),
             })
@@ -272,6 +406,83 @@ impl Partitioner for PartitionTemplate {
     }
 }
 
+/// Returns the value of the tag or field named `column`, if the line has one
+fn column_value(line: &ParsedLine<'_>, column: &str) -> Option<String> {
+    match line.tag_value(column) {
+        Some(v) => Some(v.to_string()),
+        None => line.field_value(column).map(ToString::to_string),
+    }
+}
+
+/// The time formats of a [`TemplatePart`], parsed
+#[derive(Debug, Clone)]
+enum ParsedPart {
+    /// A part without time formats
+    Other,
+    /// A parsed [`TemplatePart::TimeFormat`]
+    TimeFormat(Vec<Item<'static>>),
+    /// A parsed [`TemplatePart::Template`]
+    Template(Vec<TemplateSegment>),
+}
+
+/// A piece of a [`TemplatePart::Template`]
+#[derive(Debug, PartialEq, Clone)]
+enum TemplateSegment {
+    /// Text that is formatted as a `strftime` pattern on the "time" column
+    Time(Vec<Item<'static>>),
+    /// A `<column>` placeholder
+    Column(String),
+}
+
+/// Parses a `strftime` format, returning `None` if it is invalid
+fn parse_time_format(format: &str) -> Option<Vec<Item<'static>>> {
+    StrftimeItems::new(format)
+        .map(|item| match item {
+            Item::Literal(s) => Some(Item::OwnedLiteral(s.into())),
+            Item::OwnedLiteral(s) => Some(Item::OwnedLiteral(s)),
+            Item::Space(s) => Some(Item::OwnedSpace(s.into())),
+            Item::OwnedSpace(s) => Some(Item::OwnedSpace(s)),
+            Item::Numeric(numeric, pad) => Some(Item::Numeric(numeric, pad)),
+            Item::Fixed(fixed) => Some(Item::Fixed(fixed)),
+            Item::Error => None,
+        })
+        .collect()
+}
+
+/// Splits a template string into its time formats and column placeholders
+fn parse_template(template: &str) -> Result<Vec<TemplateSegment>> {
+    let invalid = |reason: &str| Error::InvalidPartitionTemplate {
+        template: template.to_string(),
+        reason: reason.to_string(),
+    };
+    let time = |format| {
+        parse_time_format(format)
+            .map(TemplateSegment::Time)
+            .ok_or_else(|| invalid("invalid time format"))
+    };
+
+    let mut segments = vec![];
+    let mut rest = template;
+    while let Some(start) = rest.find('<') {
+        if start > 0 {
+            segments.push(time(&rest[..start])?);
+        }
+
+        let end = rest.find('>').ok_or_else(|| invalid("unterminated '<'"))?;
+        let column = rest[start + 1..end].trim();
+        if column.is_empty() || column.contains('<') {
+            return Err(invalid("expected a column name between '<' and '>'"));
+        }
+        segments.push(TemplateSegment::Column(column.to_string()));
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(time(rest)?);
+    }
+
+    Ok(segments)
+}
+
 /// `TemplatePart` specifies what part of a row should be used to compute this
 /// part of a partition key.
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -290,6 +501,33 @@ pub enum TemplatePart {
     RegexCapture(RegexCapture),
     /// Applies a `strftime` pattern to some column other than "time"
     StrftimeColumn(StrftimeColumn),
+    /// A template combining `strftime` formats of the "time" column with the
+    /// values of columns, named in angle brackets.
+    ///
+    /// For example, a template of "%Y-%m-%d/region=<region>" will produce
+    /// partition key parts such as "2021-03-14/region=west"
+    Template(String),
+}
+
+impl TemplatePart {
+    /// Checks that the part can be used to compute partition keys
+    pub fn validate(&self) -> Result<()> {
+        self.parse().map(|_| ())
+    }
+
+    /// Parses the time formats of the part
+    fn parse(&self) -> Result<ParsedPart> {
+        Ok(match self {
+            Self::TimeFormat(format) => ParsedPart::TimeFormat(parse_time_format(format).context(
+                InvalidPartitionTemplate {
+                    template: format.as_str(),
+                    reason: "invalid time format",
+                },
+            )?),
+            Self::Template(template) => ParsedPart::Template(parse_template(template)?),
+            _ => ParsedPart::Other,
+        })
+    }
 }
 
 /// `RegexCapture` is for pulling parts of a string column into the partition
@@ -322,6 +560,224 @@ pub struct RoutingConfig {
     pub target: NodeGroup,
 }
 
+/// The default maximum number of entries queued for a replication target
+pub const DEFAULT_REPLICATION_QUEUE_SIZE: usize = 10_000;
+
+/// A replication config lists the peer servers that receive a copy of every
+/// entry written to a database, e.g. to keep a warm standby that can serve
+/// queries if this server fails. Entries are shipped in the background after
+/// they have been written locally, so a write does not wait for the peers.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ReplicationConfig {
+    /// The servers that each receive every entry
+    pub targets: NodeGroup,
+
+    /// The maximum number of entries queued for a target that cannot keep
+    /// up or is unreachable. Further entries are dropped for that target
+    /// until the queue drains, and the target never receives their writes.
+    ///
+    /// The queues are held in memory only, so the entries queued when the
+    /// server stops are lost as well.
+    pub queue_size: NonZeroUsize,
+}
+
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        Self {
+            targets: Default::default(),
+            queue_size: NonZeroUsize::new(DEFAULT_REPLICATION_QUEUE_SIZE).unwrap(),
+        }
+    }
+}
+
+/// Quotas limit the schema and the amount of data of a database, so that a
+/// runaway client cannot exhaust the resources of a server shared with other
+/// databases. Writes that would exceed a quota are rejected; the data
+/// already in the database is not affected when a quota is lowered.
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct Quotas {
+    /// The maximum number of tables
+    pub max_tables: Option<NonZeroUsize>,
+
+    /// The maximum number of columns of each table, including tags, fields
+    /// and the time column
+    pub max_columns_per_table: Option<NonZeroUsize>,
+
+    /// The maximum number of rows in the chunks of the database
+    pub max_rows: Option<NonZeroUsize>,
+}
+
+impl Quotas {
+    /// Returns true if none of the quotas is set
+    pub fn is_unlimited(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Limits on the lines of a write, checked when the lines are converted to
+/// entries. Unlike [`Quotas`] they do not depend on the data already in the
+/// database, so a write containing a line that exceeds a limit is rejected
+/// before any of it is buffered.
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct LineLimits {
+    /// The maximum length of a line, in bytes of its canonical line protocol
+    /// representation
+    pub max_line_length: Option<NonZeroUsize>,
+
+    /// The maximum number of fields of a line
+    pub max_fields_per_line: Option<NonZeroUsize>,
+
+    /// The maximum number of distinct columns, including tags, fields and
+    /// the time column, that the lines of a write have for each table
+    pub max_columns_per_table: Option<NonZeroUsize>,
+
+    /// The maximum length of a table name, in bytes
+    pub max_table_name_length: Option<NonZeroUsize>,
+}
+
+impl LineLimits {
+    /// Returns true if none of the limits is set
+    pub fn is_unlimited(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// How to handle a write that has a value of a different type than the
+/// column it is written to, e.g. an integer written to a float field
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum SchemaConflictPolicy {
+    /// Reject the whole table batch of the write
+    Reject,
+
+    /// Drop the rows that have a value in a conflicting column and write
+    /// the others
+    DropLines,
+
+    /// Convert numeric field values to the type of the column if that is
+    /// lossless for every value, e.g. integers to floats of the same value,
+    /// and otherwise reject the table batch
+    Coerce,
+}
+
+impl Default for SchemaConflictPolicy {
+    fn default() -> Self {
+        Self::Reject
+    }
+}
+
+/// Allows the values of fields of type `from` to be converted to type `to`
+/// when the types conflict, e.g. integers to be widened to floats. Only
+/// the numeric types `I64`, `U64` and `F64` can be coerced.
+///
+/// Values written to an existing column of type `to` are converted to it,
+/// even if some precision is lost, unless a value can't be represented at
+/// all, such as a negative integer as an unsigned integer. A column of an
+/// open chunk of type `from` is converted to type `to` when values of type
+/// `to` are written to it, if `to` is `F64`. Chunks storing the column as
+/// either type are queried and compacted together as type `to`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct TypeCoercionRule {
+    /// The type of the values that are converted
+    pub from: ColumnType,
+
+    /// The type the values are converted to
+    pub to: ColumnType,
+}
+
+impl TypeCoercionRule {
+    /// Returns true if the rule converts between two different numeric
+    /// types
+    pub fn is_valid(&self) -> bool {
+        let numeric =
+            |t: &ColumnType| matches!(t, ColumnType::I64 | ColumnType::U64 | ColumnType::F64);
+        numeric(&self.from) && numeric(&self.to) && self.from != self.to
+    }
+}
+
+/// A rollup rule periodically aggregates the rows of a source table into
+/// fixed time windows, e.g. 10 second samples into 5 minute means, and
+/// writes one row per series and window into a target table.
+///
+/// A window is aggregated once `delay` has passed since its end, so rows
+/// arriving later than that are not reflected in the target table.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct RollupRule {
+    /// The table whose rows are aggregated
+    pub source_table: String,
+
+    /// The table the aggregated rows are written to
+    pub target_table: String,
+
+    /// The width of the time windows
+    pub window: Duration,
+
+    /// How the field values within a window are aggregated
+    pub aggregate: RollupAggregate,
+
+    /// How long to wait after the end of a window before aggregating it
+    pub delay: Duration,
+}
+
+/// The aggregate a [`RollupRule`] applies to every field of the source table
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum RollupAggregate {
+    Mean,
+    Min,
+    Max,
+    Sum,
+    Count,
+}
+
+impl Default for RollupAggregate {
+    fn default() -> Self {
+        Self::Mean
+    }
+}
+
+/// A continuous query runs a SQL query against the database on a schedule
+/// and writes the rows of its result into a table of the same database.
+///
+/// The `time` column of the result is written as the timestamp, the columns
+/// with a string type as tags and all other columns as fields. Rows without
+/// a time are written with the time the run was scheduled at.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ContinuousQuery {
+    /// The name of the query, unique within the database
+    pub name: String,
+
+    /// The SQL query to run
+    pub query: String,
+
+    /// The table the result is written to
+    pub target_table: String,
+
+    /// When to run the query
+    pub schedule: Schedule,
+}
+
+/// A schedule that runs a task at every multiple of `every` since the epoch,
+/// shifted by `offset`. For example, every hour at 5 minutes past the hour is
+/// `every` one hour and `offset` 5 minutes.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Schedule {
+    /// The interval between two runs, must not be zero
+    pub every: Duration,
+
+    /// The shift of the runs from the multiples of `every`, less than `every`
+    pub offset: Duration,
+}
+
+impl Schedule {
+    /// Returns the latest time the task is scheduled at that is not after
+    /// `now`, both in nanoseconds since the epoch
+    pub fn last_run(&self, now: i64) -> i64 {
+        let every = self.every.as_nanos().clamp(1, i64::MAX as u128) as i64;
+        let offset = (self.offset.as_nanos() % every as u128) as i64;
+
+        now.saturating_sub(offset).div_euclid(every) * every + offset
+    }
+}
+
 /// ShardId maps to a nodegroup that holds the the shard.
 pub type ShardId = u32;
 pub const NO_SHARD_CONFIG: Option<&ShardConfig> = None;
@@ -434,13 +890,14 @@ pub struct HashRing {
 }
 
 /// A matcher is used to match routing rules or subscriptions on a row-by-row
-/// (or line) basis.
+/// (or line) basis. A line matches if it satisfies all the conditions that are
+/// provided; a matcher without any condition matches no line.
 #[derive(Debug, Clone, Default)]
 pub struct Matcher {
     /// if provided, match if the table name matches against the regex
     pub table_name_regex: Option<Regex>,
-    // paul: what should we use for predicate matching here against a single row/line?
-    pub predicate: Option<String>,
+    /// if provided, match if the tags of the line satisfy the predicate
+    pub predicate: Option<TagPredicate>,
 }
 
 impl PartialEq for Matcher {
@@ -455,19 +912,83 @@ impl Eq for Matcher {}
 
 impl Matcher {
     fn match_line(&self, line: &ParsedLine<'_>) -> bool {
-        let table_name_matches = if let Some(table_name_regex) = &self.table_name_regex {
-            table_name_regex.is_match(line.series.measurement.as_str())
-        } else {
-            false
-        };
+        if self.table_name_regex.is_none() && self.predicate.is_none() {
+            return false;
+        }
 
-        let predicate_matches = if self.predicate.is_some() {
-            unimplemented!("predicates not implemented yet")
-        } else {
-            false
-        };
+        let table_name_matches = self
+            .table_name_regex
+            .as_ref()
+            .map_or(true, |re| re.is_match(line.series.measurement.as_str()));
+
+        let predicate_matches = self
+            .predicate
+            .as_ref()
+            .map_or(true, |predicate| predicate.match_line(line));
+
+        table_name_matches && predicate_matches
+    }
+}
+
+/// A predicate on the tags of a line: a conjunction of tag equality
+/// comparisons such as `region = 'us-west' and host = 'a'`. Values may be
+/// single or double quoted, or bare if they don't contain whitespace.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct TagPredicate {
+    /// (tag key, tag value) pairs that must all be present on a line
+    pub tags: Vec<(String, String)>,
+}
 
-        table_name_matches || predicate_matches
+impl TagPredicate {
+    fn match_line(&self, line: &ParsedLine<'_>) -> bool {
+        self.tags.iter().all(|(key, value)| {
+            line.tag_value(key)
+                .map_or(false, |tag_value| tag_value.as_str() == value)
+        })
+    }
+}
+
+impl std::str::FromStr for TagPredicate {
+    type Err = Error;
+
+    fn from_str(predicate: &str) -> Result<Self> {
+        let invalid = |reason: &str| InvalidTagPredicate { predicate, reason }.fail();
+
+        let conjunction = Regex::new(r"(?i)\s+and\s+").expect("valid regex");
+        let mut tags = vec![];
+        for comparison in conjunction.split(predicate.trim()) {
+            let (key, value) = match comparison.find('=') {
+                Some(idx) => (comparison[..idx].trim(), comparison[idx + 1..].trim()),
+                None => return invalid("expected comparisons of the form `tag = 'value'`"),
+            };
+
+            let value = match value.chars().next() {
+                Some(quote @ '\'') | Some(quote @ '"') => match value[1..].strip_suffix(quote) {
+                    Some(unquoted) => unquoted,
+                    None => return invalid("unterminated quoted value"),
+                },
+                _ => value,
+            };
+
+            if key.is_empty() || value.is_empty() {
+                return invalid("tag keys and values must not be empty");
+            }
+            tags.push((key.to_string(), value.to_string()));
+        }
+
+        Ok(Self { tags })
+    }
+}
+
+impl std::fmt::Display for TagPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (key, value)) in self.tags.iter().enumerate() {
+            if i > 0 {
+                write!(f, " and ")?;
+            }
+            write!(f, "{} = '{}'", key, value)?;
+        }
+        Ok(())
     }
 }
 
@@ -486,9 +1007,7 @@ mod tests {
 
     #[test]
     fn partition_key_with_table() {
-        let template = PartitionTemplate {
-            parts: vec![TemplatePart::Table],
-        };
+        let template = PartitionTemplate::new(vec![TemplatePart::Table]);
 
         let line = parse_line("cpu foo=1 10");
         assert_eq!(
@@ -501,9 +1020,7 @@ mod tests {
 
     #[test]
     fn partition_key_with_int_field() {
-        let template = PartitionTemplate {
-            parts: vec![TemplatePart::Column("foo".to_string())],
-        };
+        let template = PartitionTemplate::new(vec![TemplatePart::Column("foo".to_string())]);
 
         let line = parse_line("cpu foo=1 10");
         assert_eq!(
@@ -516,9 +1033,7 @@ mod tests {
 
     #[test]
     fn partition_key_with_float_field() {
-        let template = PartitionTemplate {
-            parts: vec![TemplatePart::Column("foo".to_string())],
-        };
+        let template = PartitionTemplate::new(vec![TemplatePart::Column("foo".to_string())]);
 
         let line = parse_line("cpu foo=1.1 10");
         assert_eq!(
@@ -531,9 +1046,7 @@ mod tests {
 
     #[test]
     fn partition_key_with_string_field() {
-        let template = PartitionTemplate {
-            parts: vec![TemplatePart::Column("foo".to_string())],
-        };
+        let template = PartitionTemplate::new(vec![TemplatePart::Column("foo".to_string())]);
 
         let line = parse_line("cpu foo=\"asdf\" 10");
         assert_eq!(
@@ -546,9 +1059,7 @@ mod tests {
 
     #[test]
     fn partition_key_with_bool_field() {
-        let template = PartitionTemplate {
-            parts: vec![TemplatePart::Column("bar".to_string())],
-        };
+        let template = PartitionTemplate::new(vec![TemplatePart::Column("bar".to_string())]);
 
         let line = parse_line("cpu bar=true 10");
         assert_eq!(
@@ -561,9 +1072,7 @@ mod tests {
 
     #[test]
     fn partition_key_with_tag_column() {
-        let template = PartitionTemplate {
-            parts: vec![TemplatePart::Column("region".to_string())],
-        };
+        let template = PartitionTemplate::new(vec![TemplatePart::Column("region".to_string())]);
 
         let line = parse_line("cpu,region=west usage_user=23.2 10");
         assert_eq!(
@@ -576,9 +1085,7 @@ mod tests {
 
     #[test]
     fn partition_key_with_missing_column() {
-        let template = PartitionTemplate {
-            parts: vec![TemplatePart::Column("not_here".to_string())],
-        };
+        let template = PartitionTemplate::new(vec![TemplatePart::Column("not_here".to_string())]);
 
         let line = parse_line("cpu,foo=asdf bar=true 10");
         assert_eq!(
@@ -591,9 +1098,9 @@ mod tests {
 
     #[test]
     fn partition_key_with_time() {
-        let template = PartitionTemplate {
-            parts: vec![TemplatePart::TimeFormat("%Y-%m-%d %H:%M:%S".to_string())],
-        };
+        let template = PartitionTemplate::new(vec![TemplatePart::TimeFormat(
+            "%Y-%m-%d %H:%M:%S".to_string(),
+        )]);
 
         let line = parse_line("cpu,foo=asdf bar=true 1602338097000000000");
         assert_eq!(
@@ -607,9 +1114,8 @@ mod tests {
     #[test]
     fn partition_key_with_default_time() {
         let format_string = "%Y-%m-%d %H:%M:%S";
-        let template = PartitionTemplate {
-            parts: vec![TemplatePart::TimeFormat(format_string.to_string())],
-        };
+        let template =
+            PartitionTemplate::new(vec![TemplatePart::TimeFormat(format_string.to_string())]);
 
         let default_time = Utc::now();
         let line = parse_line("cpu,foo=asdf bar=true");
@@ -621,16 +1127,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn partition_key_parses_time_format_once() {
+        let template =
+            PartitionTemplate::new(vec![TemplatePart::TimeFormat("%Y-%m-%d".to_string())]);
+        assert!(template.parsed.get().is_none());
+
+        let line = parse_line("cpu,foo=asdf bar=true 1602338097000000000");
+        assert_eq!(
+            "2020-10-10",
+            template
+                .partition_key(&line, ARBITRARY_DEFAULT_TIME)
+                .unwrap()
+        );
+        assert!(template.parsed.get().is_some());
+
+        // clones of the template keep the parsed format
+        let template = template.clone();
+        assert!(template.parsed.get().is_some());
+        assert_eq!(
+            "2020-10-10",
+            template
+                .partition_key(&line, ARBITRARY_DEFAULT_TIME)
+                .unwrap()
+        );
+
+        let template = PartitionTemplate::new(vec![TemplatePart::TimeFormat("%Q".to_string())]);
+        let err = template
+            .partition_key(&line, ARBITRARY_DEFAULT_TIME)
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidPartitionTemplate { .. }));
+    }
+
     #[test]
     fn partition_key_with_many_parts() {
-        let template = PartitionTemplate {
-            parts: vec![
-                TemplatePart::Table,
-                TemplatePart::Column("region".to_string()),
-                TemplatePart::Column("usage_system".to_string()),
-                TemplatePart::TimeFormat("%Y-%m-%d %H:%M:%S".to_string()),
-            ],
-        };
+        let template = PartitionTemplate::new(vec![
+            TemplatePart::Table,
+            TemplatePart::Column("region".to_string()),
+            TemplatePart::Column("usage_system".to_string()),
+            TemplatePart::TimeFormat("%Y-%m-%d %H:%M:%S".to_string()),
+        ]);
 
         let line = parse_line(
             "cpu,host=a,region=west usage_user=22.1,usage_system=53.1 1602338097000000000",
@@ -643,6 +1179,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn partition_key_with_template() {
+        let template = PartitionTemplate::new(vec![TemplatePart::Template(
+            "%Y-%m-%d/region=<region>/<usage_system>".to_string(),
+        )]);
+
+        let line = parse_line(
+            "cpu,host=a,region=west usage_user=22.1,usage_system=53.1 1602338097000000000",
+        );
+        assert_eq!(
+            "2020-10-10/region=west/53.1",
+            template
+                .partition_key(&line, ARBITRARY_DEFAULT_TIME)
+                .unwrap()
+        );
+
+        // Missing columns are blank, and tag values are never interpreted as time formats
+        let line = parse_line("cpu,host=%Y bar=true 1602338097000000000");
+        assert_eq!(
+            "2020-10-10/region=/",
+            template
+                .partition_key(&line, ARBITRARY_DEFAULT_TIME)
+                .unwrap()
+        );
+        let template =
+            PartitionTemplate::new(vec![TemplatePart::Template("<host>-%H".to_string())]);
+        assert_eq!(
+            "%Y-13",
+            template
+                .partition_key(&line, ARBITRARY_DEFAULT_TIME)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_template() {
+        assert_eq!(
+            parse_template("%Y-%m-%d/region=<region>").unwrap(),
+            vec![
+                TemplateSegment::Time(parse_time_format("%Y-%m-%d/region=").unwrap()),
+                TemplateSegment::Column("region".to_string())
+            ]
+        );
+        assert_eq!(
+            parse_template("<a><b>").unwrap(),
+            vec![
+                TemplateSegment::Column("a".to_string()),
+                TemplateSegment::Column("b".to_string())
+            ]
+        );
+        assert!(parse_template("").unwrap().is_empty());
+
+        for template in &["%Y-<region", "<>", "<a<b>", "%Q"] {
+            let err = TemplatePart::Template(template.to_string())
+                .validate()
+                .unwrap_err();
+            assert!(
+                matches!(err, Error::InvalidPartitionTemplate { .. }),
+                "{}: {}",
+                template,
+                err
+            );
+        }
+    }
+
     #[test]
     #[allow(clippy::trivial_regex)]
     fn test_sharder() {
@@ -721,6 +1322,67 @@ mod tests {
         assert_eq!(sharded_line, 637504);
     }
 
+    #[test]
+    fn test_sharder_tag_predicate() {
+        let shard_config = ShardConfig {
+            specific_targets: vec![
+                MatcherToShard {
+                    matcher: Matcher {
+                        table_name_regex: Some(Regex::new("^cpu$").unwrap()),
+                        predicate: Some("region = 'west'".parse().unwrap()),
+                    },
+                    shard: 1,
+                },
+                MatcherToShard {
+                    matcher: Matcher {
+                        table_name_regex: None,
+                        predicate: Some("region=east AND host = \"a\"".parse().unwrap()),
+                    },
+                    shard: 2,
+                },
+            ],
+            hash_ring: Some(HashRing {
+                table_name: true,
+                shards: ConsistentHasher::new(&[3]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let shard = |lp| shard_config.shard(&parse_line(lp)).unwrap();
+        assert_eq!(shard("cpu,region=west,host=a f=1 10"), 1);
+        assert_eq!(shard("mem,region=west,host=a f=1 10"), 3);
+        assert_eq!(shard("mem,region=east,host=a f=1 10"), 2);
+        assert_eq!(shard("cpu,region=east,host=b f=1 10"), 3);
+        assert_eq!(shard("cpu f=1 10"), 3);
+    }
+
+    #[test]
+    fn test_tag_predicate() {
+        let predicate: TagPredicate = " region = 'us west'  and host=\"a\" ".parse().unwrap();
+        assert_eq!(
+            predicate.tags,
+            vec![
+                ("region".to_string(), "us west".to_string()),
+                ("host".to_string(), "a".to_string())
+            ]
+        );
+        assert_eq!(predicate.to_string(), "region = 'us west' and host = 'a'");
+        assert_eq!(
+            predicate.to_string().parse::<TagPredicate>().unwrap(),
+            predicate
+        );
+
+        for invalid in &["", "region", "region = 'west", "= a", "region = ''"] {
+            let err = invalid.parse::<TagPredicate>().unwrap_err();
+            assert!(
+                matches!(err, Error::InvalidTagPredicate { .. }),
+                "{}",
+                invalid
+            );
+        }
+    }
+
     #[test]
     fn test_sharder_no_shards() {
         let shard_config = ShardConfig {
@@ -741,6 +1403,25 @@ mod tests {
         assert!(matches!(err, Error::NoShardsDefined));
     }
 
+    #[test]
+    fn test_schedule_last_run() {
+        let schedule = Schedule {
+            every: Duration::from_nanos(10),
+            offset: Duration::from_nanos(3),
+        };
+        assert_eq!(schedule.last_run(3), 3);
+        assert_eq!(schedule.last_run(12), 3);
+        assert_eq!(schedule.last_run(13), 13);
+        assert_eq!(schedule.last_run(2), -7);
+
+        // offsets larger than the interval wrap around
+        let schedule = Schedule {
+            every: Duration::from_nanos(10),
+            offset: Duration::from_nanos(23),
+        };
+        assert_eq!(schedule.last_run(12), 3);
+    }
+
     fn parsed_lines(lp: &str) -> Vec<ParsedLine<'_>> {
         parse_lines(lp).map(|l| l.unwrap()).collect()
     }
@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+use crate::timestamp::TimestampRange;
+
+/// Specifies the rows of a table that are to be deleted: all rows within
+/// the time range whose tags have the given values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeletePredicate {
+    /// The table to delete rows from
+    pub table_name: String,
+
+    /// Only rows with a timestamp within this range are deleted
+    pub range: TimestampRange,
+
+    /// Only rows where every one of these tags has the given value are
+    /// deleted. Rows without a value for one of the tags are kept.
+    pub tags: BTreeMap<String, String>,
+}
+
+impl DeletePredicate {
+    /// Returns true if a row with the timestamp `time` and the value
+    /// returned by `tag_value` for each tag column matches this predicate
+    pub fn matches<'a>(
+        &self,
+        time: i64,
+        mut tag_value: impl FnMut(&str) -> Option<&'a str>,
+    ) -> bool {
+        self.range.contains(time)
+            && self
+                .tags
+                .iter()
+                .all(|(tag, value)| tag_value(tag) == Some(value.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let predicate = DeletePredicate {
+            table_name: "cpu".to_string(),
+            range: TimestampRange::new(100, 200),
+            tags: vec![("host".to_string(), "a".to_string())]
+                .into_iter()
+                .collect(),
+        };
+
+        let host_a = |tag: &str| (tag == "host").then(|| "a");
+        let host_b = |tag: &str| (tag == "host").then(|| "b");
+        let no_host = |_: &str| None;
+
+        assert!(predicate.matches(100, host_a));
+        assert!(predicate.matches(199, host_a));
+        assert!(!predicate.matches(99, host_a));
+        assert!(!predicate.matches(200, host_a));
+        assert!(!predicate.matches(150, host_b));
+        assert!(!predicate.matches(150, no_host));
+
+        let predicate = DeletePredicate {
+            tags: Default::default(),
+            ..predicate
+        };
+        assert!(predicate.matches(150, host_b));
+        assert!(predicate.matches(150, no_host));
+    }
+}
@@ -25,6 +25,13 @@ pub enum Job {
         table_name: String,
         chunk_id: u32,
     },
+
+    /// Compact the persisted chunks of a table within a partition
+    CompactChunks {
+        db_name: String,
+        partition_key: String,
+        table_name: String,
+    },
 }
 
 impl Job {
@@ -34,6 +41,7 @@ impl Job {
             Self::Dummy { .. } => None,
             Self::CloseChunk { db_name, .. } => Some(db_name),
             Self::WriteChunk { db_name, .. } => Some(db_name),
+            Self::CompactChunks { db_name, .. } => Some(db_name),
         }
     }
 
@@ -43,6 +51,7 @@ impl Job {
             Self::Dummy { .. } => None,
             Self::CloseChunk { partition_key, .. } => Some(partition_key),
             Self::WriteChunk { partition_key, .. } => Some(partition_key),
+            Self::CompactChunks { partition_key, .. } => Some(partition_key),
         }
     }
 
@@ -52,6 +61,7 @@ impl Job {
             Self::Dummy { .. } => None,
             Self::CloseChunk { chunk_id, .. } => Some(*chunk_id),
             Self::WriteChunk { chunk_id, .. } => Some(*chunk_id),
+            Self::CompactChunks { .. } => None,
         }
     }
 
@@ -61,6 +71,7 @@ impl Job {
             Self::Dummy { .. } => "Dummy Job, for testing",
             Self::CloseChunk { .. } => "Loading chunk to ReadBuffer",
             Self::WriteChunk { .. } => "Writing chunk to Object Storage",
+            Self::CompactChunks { .. } => "Compacting chunks in Object Storage",
         }
     }
 }
@@ -5,8 +5,11 @@ use std::{borrow::Cow, cmp::Ordering, mem};
 
 use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
+use std::collections::BTreeMap;
 use std::num::NonZeroU64;
 
+use crate::chunk_metadata::{ChunkStorage, ChunkSummary};
+
 /// Describes the aggregated (across all chunks) summary
 /// statistics for each column in each table in a partition
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
@@ -60,6 +63,85 @@ impl PartitionSummary {
     }
 }
 
+/// Describes how the data of each table in a partition is stored
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PartitionStorageSummary {
+    /// The identifier for the partition, the partition key computed from
+    /// PartitionRules
+    pub key: String,
+
+    /// The tables in this partition, sorted by name
+    pub tables: Vec<TableStorageSummary>,
+}
+
+impl PartitionStorageSummary {
+    /// Create a storage summary from the summaries of the partition's chunks,
+    /// each paired with the size in bytes of its parquet file if the chunk
+    /// has been persisted.
+    pub fn from_chunk_summaries(
+        key: impl Into<String>,
+        chunks: impl IntoIterator<Item = (ChunkSummary, Option<usize>)>,
+    ) -> Self {
+        let mut tables: BTreeMap<String, TableStorageSummary> = BTreeMap::new();
+
+        for (chunk, object_store_bytes) in chunks {
+            let name = chunk.table_name.to_string();
+            let table = tables
+                .entry(name.clone())
+                .or_insert_with(|| TableStorageSummary {
+                    name,
+                    ..Default::default()
+                });
+
+            match chunk.storage {
+                ChunkStorage::OpenMutableBuffer | ChunkStorage::ClosedMutableBuffer => {
+                    table.mutable_buffer_chunks += 1
+                }
+                ChunkStorage::ReadBuffer => table.read_buffer_chunks += 1,
+                ChunkStorage::ReadBufferAndObjectStore => {
+                    table.read_buffer_chunks += 1;
+                    table.object_store_chunks += 1;
+                }
+                ChunkStorage::ObjectStoreOnly => table.object_store_chunks += 1,
+            }
+
+            table.row_count += chunk.row_count;
+            table.memory_bytes += chunk.estimated_bytes;
+            table.object_store_bytes += object_store_bytes.unwrap_or(0);
+        }
+
+        Self {
+            key: key.into(),
+            tables: tables.into_iter().map(|(_, table)| table).collect(),
+        }
+    }
+}
+
+/// Describes how the data of a table within a partition is stored
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TableStorageSummary {
+    /// Table name
+    pub name: String,
+
+    /// Number of chunks in the mutable buffer, open or closed
+    pub mutable_buffer_chunks: usize,
+
+    /// Number of chunks loaded in the read buffer
+    pub read_buffer_chunks: usize,
+
+    /// Number of chunks persisted to object store
+    pub object_store_chunks: usize,
+
+    /// Total number of rows across all chunks
+    pub row_count: usize,
+
+    /// Estimated memory consumed by all chunks, in bytes
+    pub memory_bytes: usize,
+
+    /// Total size of the persisted parquet files, in bytes
+    pub object_store_bytes: usize,
+}
+
 /// Describes the (unaggregated) summary statistics for
 /// each column in each table in each chunk of a Partition.
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
@@ -559,6 +641,70 @@ impl IsNan for f64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn partition_storage_summary() {
+        let chunk = |table_name: &str, id, storage, estimated_bytes, row_count| {
+            ChunkSummary::new_without_timestamps(
+                Arc::from("p1"),
+                Arc::from(table_name),
+                id,
+                storage,
+                estimated_bytes,
+                row_count,
+            )
+        };
+
+        let summary = PartitionStorageSummary::from_chunk_summaries(
+            "p1",
+            vec![
+                (
+                    chunk("mem", 0, ChunkStorage::OpenMutableBuffer, 100, 2),
+                    None,
+                ),
+                (
+                    chunk("cpu", 0, ChunkStorage::ObjectStoreOnly, 10, 5),
+                    Some(700),
+                ),
+                (
+                    chunk("cpu", 1, ChunkStorage::ReadBufferAndObjectStore, 50, 3),
+                    Some(300),
+                ),
+                (chunk("cpu", 2, ChunkStorage::ReadBuffer, 40, 4), None),
+                (
+                    chunk("cpu", 3, ChunkStorage::ClosedMutableBuffer, 80, 1),
+                    None,
+                ),
+            ],
+        );
+
+        let expected = PartitionStorageSummary {
+            key: "p1".to_string(),
+            tables: vec![
+                TableStorageSummary {
+                    name: "cpu".to_string(),
+                    mutable_buffer_chunks: 1,
+                    read_buffer_chunks: 2,
+                    object_store_chunks: 2,
+                    row_count: 13,
+                    memory_bytes: 180,
+                    object_store_bytes: 1000,
+                },
+                TableStorageSummary {
+                    name: "mem".to_string(),
+                    mutable_buffer_chunks: 1,
+                    read_buffer_chunks: 0,
+                    object_store_chunks: 0,
+                    row_count: 2,
+                    memory_bytes: 100,
+                    object_store_bytes: 0,
+                },
+            ],
+        };
+
+        assert_eq!(summary, expected);
+    }
 
     #[test]
     fn statistics_update() {
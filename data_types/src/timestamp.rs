@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 /// Specifies a continuous range of nanosecond timestamps. Timestamp
 /// predicates are so common and critical to performance of timeseries
 /// databases in general, and IOx in particular, that they are handled
@@ -35,10 +37,88 @@ impl TimestampRange {
     }
 }
 
+/// The unit of the timestamps of written line protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Precision {
+    #[serde(rename = "ns", alias = "n")]
+    Nanoseconds,
+    #[serde(rename = "us", alias = "u")]
+    Microseconds,
+    #[serde(rename = "ms")]
+    Milliseconds,
+    #[serde(rename = "s")]
+    Seconds,
+}
+
+impl Default for Precision {
+    fn default() -> Self {
+        Self::Nanoseconds
+    }
+}
+
+impl Precision {
+    /// Converts a timestamp in this precision to nanoseconds since the epoch,
+    /// returning `None` if it does not fit
+    pub fn to_nanos(self, timestamp: i64) -> Option<i64> {
+        let multiplier = match self {
+            Self::Nanoseconds => 1,
+            Self::Microseconds => 1_000,
+            Self::Milliseconds => 1_000_000,
+            Self::Seconds => 1_000_000_000,
+        };
+        timestamp.checked_mul(multiplier)
+    }
+}
+
+impl std::fmt::Display for Precision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nanoseconds => write!(f, "ns"),
+            Self::Microseconds => write!(f, "us"),
+            Self::Milliseconds => write!(f, "ms"),
+            Self::Seconds => write!(f, "s"),
+        }
+    }
+}
+
+impl std::str::FromStr for Precision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ns" | "n" => Ok(Self::Nanoseconds),
+            "us" | "u" => Ok(Self::Microseconds),
+            "ms" => Ok(Self::Milliseconds),
+            "s" => Ok(Self::Seconds),
+            _ => Err(format!(
+                "unknown precision {:?}, expected one of 'ns', 'us', 'ms' or 's'",
+                s
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_precision_to_nanos() {
+        assert_eq!(Precision::Nanoseconds.to_nanos(5), Some(5));
+        assert_eq!(Precision::Microseconds.to_nanos(5), Some(5_000));
+        assert_eq!(Precision::Milliseconds.to_nanos(-5), Some(-5_000_000));
+        assert_eq!(Precision::Seconds.to_nanos(5), Some(5_000_000_000));
+        assert_eq!(Precision::Seconds.to_nanos(i64::MAX / 100), None);
+
+        for precision in &["ns", "us", "ms", "s"] {
+            assert_eq!(
+                &precision.parse::<Precision>().unwrap().to_string(),
+                precision
+            );
+        }
+        assert!("h".parse::<Precision>().is_err());
+    }
+
     #[test]
     fn test_timestamp_range_contains() {
         let range = TimestampRange::new(100, 200);
@@ -4,6 +4,8 @@
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
+use futures::{stream::BoxStream, StreamExt};
+
 use datafusion::{
     arrow::{datatypes::SchemaRef, error::Result as ArrowResult, record_batch::RecordBatch},
     logical_plan::{binary_expr, col, lit, Expr, Operator},
@@ -153,6 +155,51 @@ impl futures::Stream for MemoryStream {
     }
 }
 
+/// A RecordBatchStream created from a stream of RecordBatches, such as one
+/// computing its batches in an `async` block
+pub struct AdapterStream {
+    schema: SchemaRef,
+    inner: BoxStream<'static, ArrowResult<RecordBatch>>,
+}
+
+impl AdapterStream {
+    /// Create new stream of the batches of `inner`, which have `schema`
+    pub fn new(
+        schema: SchemaRef,
+        inner: impl futures::Stream<Item = ArrowResult<RecordBatch>> + Send + 'static,
+    ) -> Self {
+        Self {
+            schema,
+            inner: inner.boxed(),
+        }
+    }
+}
+
+impl std::fmt::Debug for AdapterStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdapterStream")
+            .field("schema", &self.schema)
+            .finish()
+    }
+}
+
+impl RecordBatchStream for AdapterStream {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+}
+
+impl futures::Stream for AdapterStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,16 +1,22 @@
 //! This module contains helper code for building `Entry` from line protocol and the
 //! `DatabaseRules` configuration.
 
-use std::{collections::BTreeMap, convert::TryFrom, fmt::Formatter, num::NonZeroU64};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    fmt::Formatter,
+    num::NonZeroU64,
+};
 
 use chrono::Utc;
 use flatbuffers::{FlatBufferBuilder, Follow, ForwardsUOffset, Vector, VectorIter, WIPOffset};
 use ouroboros::self_referencing;
-use snafu::{OptionExt, ResultExt, Snafu};
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
 
 use data_types::{
-    database_rules::{Error as DataError, Partitioner, ShardId, Sharder},
+    database_rules::{Error as DataError, LineLimits, Partitioner, ShardId, Sharder},
     server_id::ServerId,
+    timestamp::Precision,
 };
 use influxdb_line_protocol::{FieldValue, ParsedLine};
 use internal_types::schema::{InfluxColumnType, InfluxFieldType, TIME_COLUMN_NAME};
@@ -39,8 +45,77 @@ pub enum Error {
         source: ColumnError,
     },
 
+    #[snafu(display(
+        "timestamp {} on line {} is out of range for precision {}",
+        timestamp,
+        line_number,
+        precision
+    ))]
+    TimestampOutOfRange {
+        line_number: usize,
+        timestamp: i64,
+        precision: Precision,
+    },
+
     #[snafu(display("invalid flatbuffers: field {} is required", field))]
     FlatbufferFieldMissing { field: String },
+
+    #[snafu(display("row {} of table {} has no fields", row_number, table))]
+    RowFieldsMissing { table: String, row_number: usize },
+
+    #[snafu(display(
+        "line {} is {} bytes long, exceeding the limit of {}",
+        line_number,
+        length,
+        max
+    ))]
+    LineTooLong {
+        line_number: usize,
+        length: usize,
+        max: usize,
+    },
+
+    #[snafu(display(
+        "line {} of table {} has {} fields, exceeding the limit of {}",
+        line_number,
+        table,
+        fields,
+        max
+    ))]
+    TooManyFields {
+        table: String,
+        line_number: usize,
+        fields: usize,
+        max: usize,
+    },
+
+    #[snafu(display(
+        "line {} adds column {} to table {}, exceeding the limit of {} columns per table",
+        line_number,
+        column,
+        table,
+        max
+    ))]
+    TooManyColumns {
+        table: String,
+        column: String,
+        line_number: usize,
+        max: usize,
+    },
+
+    #[snafu(display(
+        "line {} has table name {} of {} bytes, exceeding the limit of {}",
+        line_number,
+        table,
+        length,
+        max
+    ))]
+    TableNameTooLong {
+        table: String,
+        line_number: usize,
+        length: usize,
+        max: usize,
+    },
 }
 
 #[derive(Debug, Snafu)]
@@ -56,13 +131,25 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 type ColumnResult<T, E = ColumnError> = std::result::Result<T, E>;
 
 /// Converts parsed line protocol into a collection of ShardedEntry with the
-/// underlying flatbuffers bytes generated.
+/// underlying flatbuffers bytes generated. The timestamps of `lines` are in
+/// `precision` and converted to nanoseconds, `default_time` already is in
+/// nanoseconds.
 pub fn lines_to_sharded_entries(
     lines: &[ParsedLine<'_>],
     default_time: i64,
+    precision: Precision,
     sharder: Option<&impl Sharder>,
     partitioner: &impl Partitioner,
 ) -> Result<Vec<ShardedEntry>> {
+    let scaled_lines;
+    let lines = match precision {
+        Precision::Nanoseconds => lines,
+        _ => {
+            scaled_lines = scale_timestamps(lines, precision)?;
+            &scaled_lines
+        }
+    };
+
     let mut sharded_lines = BTreeMap::new();
 
     for line in lines {
@@ -93,6 +180,109 @@ pub fn lines_to_sharded_entries(
     Ok(sharded_entries)
 }
 
+/// Returns copies of `lines` with their timestamps converted from `precision`
+/// to nanoseconds
+fn scale_timestamps<'a>(
+    lines: &[ParsedLine<'a>],
+    precision: Precision,
+) -> Result<Vec<ParsedLine<'a>>> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            let mut line = line.clone();
+            if let Some(timestamp) = line.timestamp {
+                let nanos = precision.to_nanos(timestamp).context(TimestampOutOfRange {
+                    line_number: idx + 1,
+                    timestamp,
+                    precision,
+                })?;
+                line.timestamp = Some(nanos);
+            }
+            Ok(line)
+        })
+        .collect()
+}
+
+/// Checks `lines` against `limits`, returning an error for the first line
+/// that exceeds one of them. Line numbers in the errors start at 1.
+pub fn validate_lines(lines: &[ParsedLine<'_>], limits: &LineLimits) -> Result<()> {
+    if limits.is_unlimited() {
+        return Ok(());
+    }
+
+    let mut table_columns: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+
+    for (idx, line) in lines.iter().enumerate() {
+        let line_number = idx + 1;
+        let table = line.series.measurement.as_str();
+
+        if let Some(max) = limits.max_table_name_length {
+            let length = table.len();
+            ensure!(
+                length <= max.get(),
+                TableNameTooLong {
+                    table,
+                    line_number,
+                    length,
+                    max: max.get(),
+                }
+            );
+        }
+
+        if let Some(max) = limits.max_fields_per_line {
+            let fields = line.field_set.len();
+            ensure!(
+                fields <= max.get(),
+                TooManyFields {
+                    table,
+                    line_number,
+                    fields,
+                    max: max.get(),
+                }
+            );
+        }
+
+        if let Some(max) = limits.max_line_length {
+            let length = line.to_string().len();
+            ensure!(
+                length <= max.get(),
+                LineTooLong {
+                    line_number,
+                    length,
+                    max: max.get(),
+                }
+            );
+        }
+
+        if let Some(max) = limits.max_columns_per_table {
+            let columns = table_columns.entry(table).or_default();
+            let tags = line.series.tag_set.iter().flatten().map(|(k, _)| k);
+            let fields = line.field_set.iter().map(|(k, _)| k);
+            let names = tags
+                .chain(fields)
+                .map(|k| k.as_str())
+                .chain(std::iter::once(TIME_COLUMN_NAME));
+
+            for column in names {
+                if columns.insert(column) {
+                    ensure!(
+                        columns.len() <= max.get(),
+                        TooManyColumns {
+                            table,
+                            column,
+                            line_number,
+                            max: max.get(),
+                        }
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn build_sharded_entry(
     shard_id: Option<ShardId>,
     partitions: BTreeMap<String, BTreeMap<&str, Vec<&ParsedLine<'_>>>>,
@@ -106,7 +296,18 @@ fn build_sharded_entry(
             build_partition_write(&mut fbb, partition_key, tables, default_time)
         })
         .collect::<Result<Vec<_>>>()?;
-    let partition_writes = fbb.create_vector(&partition_writes);
+    let entry = finish_entry(fbb, &partition_writes);
+
+    Ok(ShardedEntry { shard_id, entry })
+}
+
+/// Builds an entry consisting of a write operation of `partition_writes`,
+/// which have been created in `fbb`
+fn finish_entry<'a>(
+    mut fbb: FlatBufferBuilder<'a>,
+    partition_writes: &[WIPOffset<entry_fb::PartitionWrite<'a>>],
+) -> Entry {
+    let partition_writes = fbb.create_vector(partition_writes);
 
     let write_operations = entry_fb::WriteOperations::create(
         &mut fbb,
@@ -125,10 +326,7 @@ fn build_sharded_entry(
     fbb.finish(entry, None);
 
     let (mut data, idx) = fbb.collapse();
-    let entry = Entry::try_from(data.split_off(idx))
-        .expect("Flatbuffer data just constructed should be valid");
-
-    Ok(ShardedEntry { shard_id, entry })
+    Entry::try_from(data.split_off(idx)).expect("Flatbuffer data just constructed should be valid")
 }
 
 fn build_partition_write<'a>(
@@ -278,6 +476,263 @@ fn build_table_write_batch<'a>(
     ))
 }
 
+/// Builds an [`Entry`] from rows that are added programmatically, so that
+/// clients can write data without formatting and re-parsing line protocol.
+///
+/// ```
+/// use entry::EntryBuilder;
+///
+/// let mut builder = EntryBuilder::new();
+/// builder
+///     .table("cpu")
+///     .tag("host", "a")
+///     .field("usage_system", 23.5)
+///     .timestamp(100);
+/// builder
+///     .table("mem")
+///     .tag("host", "a")
+///     .field("free", 1024_i64);
+///
+/// // The row of `mem` is assigned the default time of 200
+/// let entry = builder.build("2021-07-01", 200).unwrap();
+///
+/// let partition_writes = entry.partition_writes().unwrap();
+/// assert_eq!(partition_writes[0].key(), "2021-07-01");
+/// assert_eq!(partition_writes[0].table_batches().len(), 2);
+/// ```
+#[derive(Debug, Default)]
+pub struct EntryBuilder {
+    /// The rows of each table, in the order they were added
+    tables: BTreeMap<String, Vec<Row>>,
+}
+
+impl EntryBuilder {
+    /// Creates a builder without any rows
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a row to `table` and returns a builder for its values
+    pub fn table(&mut self, table: impl Into<String>) -> RowBuilder<'_> {
+        let rows = self.tables.entry(table.into()).or_default();
+        rows.push(Row::default());
+        RowBuilder {
+            row: rows.last_mut().expect("row just added"),
+        }
+    }
+
+    /// Returns true if no row has been added
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Builds an entry with a single partition write for `partition_key`
+    /// containing all rows added so far. Rows without a timestamp are
+    /// assigned `default_time`, in nanoseconds since the epoch. Fails if a
+    /// row has no fields or if a column has values of different types
+    pub fn build(&self, partition_key: &str, default_time: i64) -> Result<Entry> {
+        let mut fbb = FlatBufferBuilder::new_with_capacity(1024);
+
+        let table_batches = self
+            .tables
+            .iter()
+            .map(|(table_name, rows)| {
+                build_rows_write_batch(&mut fbb, table_name, rows, default_time)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let table_batches = fbb.create_vector(&table_batches);
+
+        let partition_key = fbb.create_string(partition_key);
+        let partition_write = entry_fb::PartitionWrite::create(
+            &mut fbb,
+            &entry_fb::PartitionWriteArgs {
+                key: Some(partition_key),
+                table_batches: Some(table_batches),
+            },
+        );
+
+        Ok(finish_entry(fbb, &[partition_write]))
+    }
+}
+
+/// Builds the values of a row added to an [`EntryBuilder`]. A tag or field
+/// that is set twice keeps the last value
+#[derive(Debug)]
+pub struct RowBuilder<'a> {
+    row: &'a mut Row,
+}
+
+impl<'a> RowBuilder<'a> {
+    /// Sets the tag `key` to `value`
+    pub fn tag(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let key = key.into();
+        let value = value.into();
+        match self.row.tags.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.row.tags.push((key, value)),
+        }
+        self
+    }
+
+    /// Sets the field `key` to `value`
+    pub fn field(self, key: impl Into<String>, value: impl Into<EntryFieldValue>) -> Self {
+        let key = key.into();
+        let value = value.into();
+        match self.row.fields.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.row.fields.push((key, value)),
+        }
+        self
+    }
+
+    /// Sets the timestamp, in nanoseconds since the epoch
+    pub fn timestamp(self, timestamp: i64) -> Self {
+        self.row.timestamp = Some(timestamp);
+        self
+    }
+}
+
+/// The value of a field of a row added to an [`EntryBuilder`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryFieldValue {
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    String(String),
+    Bool(bool),
+}
+
+impl From<i64> for EntryFieldValue {
+    fn from(value: i64) -> Self {
+        Self::I64(value)
+    }
+}
+
+impl From<u64> for EntryFieldValue {
+    fn from(value: u64) -> Self {
+        Self::U64(value)
+    }
+}
+
+impl From<f64> for EntryFieldValue {
+    fn from(value: f64) -> Self {
+        Self::F64(value)
+    }
+}
+
+impl From<String> for EntryFieldValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for EntryFieldValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<bool> for EntryFieldValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+/// A row added to an [`EntryBuilder`]
+#[derive(Debug, Default)]
+struct Row {
+    tags: Vec<(String, String)>,
+    fields: Vec<(String, EntryFieldValue)>,
+    timestamp: Option<i64>,
+}
+
+fn build_rows_write_batch<'a>(
+    fbb: &mut FlatBufferBuilder<'a>,
+    table_name: &str,
+    rows: &'a [Row],
+    default_time: i64,
+) -> Result<flatbuffers::WIPOffset<entry_fb::TableWriteBatch<'a>>> {
+    let mut columns = BTreeMap::new();
+    for (i, row) in rows.iter().enumerate() {
+        let row_number = i + 1;
+        ensure!(
+            !row.fields.is_empty(),
+            RowFieldsMissing {
+                table: table_name,
+                row_number,
+            }
+        );
+
+        for (key, value) in &row.tags {
+            let builder = columns
+                .entry(key.as_str())
+                .or_insert_with(ColumnBuilder::new_tag_column);
+            builder.null_to_row(row_number);
+            builder.push_tag(value).context(TableColumnTypeMismatch {
+                table: table_name,
+                column: key,
+                line_number: i,
+            })?;
+        }
+
+        for (key, value) in &row.fields {
+            let new_column = match value {
+                EntryFieldValue::I64(_) => ColumnBuilder::new_i64_column,
+                EntryFieldValue::U64(_) => ColumnBuilder::new_u64_column,
+                EntryFieldValue::F64(_) => ColumnBuilder::new_f64_column,
+                EntryFieldValue::String(_) => ColumnBuilder::new_string_column,
+                EntryFieldValue::Bool(_) => ColumnBuilder::new_bool_column,
+            };
+            let builder = columns.entry(key.as_str()).or_insert_with(new_column);
+            builder.null_to_row(row_number);
+
+            let pushed = match value {
+                EntryFieldValue::I64(v) => builder.push_i64(*v),
+                EntryFieldValue::U64(v) => builder.push_u64(*v),
+                EntryFieldValue::F64(v) => builder.push_f64(*v),
+                EntryFieldValue::String(v) => builder.push_string(v),
+                EntryFieldValue::Bool(v) => builder.push_bool(*v),
+            };
+            pushed.context(TableColumnTypeMismatch {
+                table: table_name,
+                column: key,
+                line_number: i,
+            })?;
+        }
+
+        let builder = columns
+            .entry(TIME_COLUMN_NAME)
+            .or_insert_with(ColumnBuilder::new_time_column);
+        builder
+            .push_time(row.timestamp.unwrap_or(default_time))
+            .context(TableColumnTypeMismatch {
+                table: table_name,
+                column: TIME_COLUMN_NAME,
+                line_number: i,
+            })?;
+
+        for b in columns.values_mut() {
+            b.null_to_row(row_number + 1);
+        }
+    }
+
+    let columns = columns
+        .into_iter()
+        .map(|(column_name, builder)| builder.build_flatbuffer(fbb, column_name))
+        .collect::<Vec<_>>();
+    let columns = fbb.create_vector(&columns);
+
+    let table_name = fbb.create_string(table_name);
+
+    Ok(entry_fb::TableWriteBatch::create(
+        fbb,
+        &entry_fb::TableWriteBatchArgs {
+            name: Some(table_name),
+            columns: Some(columns),
+        },
+    ))
+}
+
 /// Holds a shard id to the associated entry. If there is no ShardId, then
 /// everything goes to the same place. This means a single entry will be
 /// generated from a batch of line protocol.
@@ -1266,6 +1721,7 @@ pub mod test_helpers {
         lines_to_sharded_entries(
             &lines,
             default_time,
+            Precision::Nanoseconds,
             sharder(1).as_ref(),
             &hour_partitioner(),
         )
@@ -1275,10 +1731,11 @@ pub mod test_helpers {
         .entry
     }
 
-    /// Converts the line protocol to a collection of `Entry` with a single
-    /// shard and a single partition, which is useful for testing when `lp` is
-    /// large. Batches are sized according to LP_BATCH_SIZE.
-    pub fn lp_to_entries(lp: &str) -> Vec<Entry> {
+    /// Converts the line protocol with timestamps in `precision` to a
+    /// collection of `Entry` with a single shard and a single partition, which
+    /// is useful for testing when `lp` is large. Batches are sized according
+    /// to LP_BATCH_SIZE.
+    pub fn lp_to_entries(lp: &str, precision: Precision) -> Vec<Entry> {
         let lines: Vec<_> = parse_lines(&lp).map(|l| l.unwrap()).collect();
 
         let default_time = Utc::now().timestamp_nanos();
@@ -1289,6 +1746,7 @@ pub mod test_helpers {
                 lines_to_sharded_entries(
                     batch,
                     default_time,
+                    precision,
                     sharder(1).as_ref(),
                     &hour_partitioner(),
                 )
@@ -1383,6 +1841,8 @@ pub mod test_helpers {
 
 #[cfg(test)]
 mod tests {
+    use std::num::NonZeroUsize;
+
     use data_types::database_rules::NO_SHARD_CONFIG;
     use influxdb_line_protocol::parse_lines;
 
@@ -1404,6 +1864,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             sharder(2).as_ref(),
             &partitioner(1),
         )
@@ -1414,6 +1875,68 @@ mod tests {
         assert_eq!(sharded_entries[1].shard_id, Some(1));
     }
 
+    #[test]
+    fn validates_lines() {
+        let lp = vec![
+            "cpu,host=a,region=west user=23.1,system=66.1 123",
+            "cpu,host=b user=23.1,idle=1.5 124",
+            "memory,host=a used=23432 123",
+        ]
+        .join("\n");
+        let lines: Vec<_> = parse_lines(&lp).map(|l| l.unwrap()).collect();
+
+        validate_lines(&lines, &LineLimits::default()).unwrap();
+
+        let limits = LineLimits {
+            max_line_length: NonZeroUsize::new(48),
+            max_fields_per_line: NonZeroUsize::new(2),
+            max_columns_per_table: NonZeroUsize::new(6),
+            max_table_name_length: NonZeroUsize::new(6),
+        };
+        validate_lines(&lines, &limits).unwrap();
+
+        let limits = LineLimits {
+            max_line_length: NonZeroUsize::new(47),
+            ..Default::default()
+        };
+        let err = validate_lines(&lines, &limits).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1 is 48 bytes long, exceeding the limit of 47"
+        );
+
+        let limits = LineLimits {
+            max_fields_per_line: NonZeroUsize::new(1),
+            ..Default::default()
+        };
+        let err = validate_lines(&lines, &limits).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1 of table cpu has 2 fields, exceeding the limit of 1"
+        );
+
+        // host, region, user, system, time and idle
+        let limits = LineLimits {
+            max_columns_per_table: NonZeroUsize::new(5),
+            ..Default::default()
+        };
+        let err = validate_lines(&lines, &limits).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2 adds column idle to table cpu, exceeding the limit of 5 columns per table"
+        );
+
+        let limits = LineLimits {
+            max_table_name_length: NonZeroUsize::new(5),
+            ..Default::default()
+        };
+        let err = validate_lines(&lines, &limits).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 3 has table name memory of 6 bytes, exceeding the limit of 5"
+        );
+    }
+
     #[test]
     fn no_shard_config() {
         let lp = vec![
@@ -1427,6 +1950,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             NO_SHARD_CONFIG,
             &partitioner(1),
         )
@@ -1449,6 +1973,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             sharder(1).as_ref(),
             &partitioner(2),
         )
@@ -1475,6 +2000,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             sharder(1).as_ref(),
             &partitioner(1),
         )
@@ -1497,6 +2023,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             sharder(1).as_ref(),
             &partitioner(1),
         )
@@ -1544,6 +2071,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             sharder(1).as_ref(),
             &partitioner(1),
         )
@@ -1620,6 +2148,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             sharder(1).as_ref(),
             &partitioner(1),
         )
@@ -1694,6 +2223,78 @@ mod tests {
         assert_eq!(&values, &[None, Some(23.2), None]);
     }
 
+    #[test]
+    fn entry_builder_matches_line_protocol() {
+        let lp = vec![
+            "a,host=a val=23i 983",
+            "a,host=a,region=west val2=23.2 2343",
+            "a val=21i,bool=true,string=\"hello\",unsigned=7u",
+            "b,host=b val=1i 10",
+        ]
+        .join("\n");
+        let lines: Vec<_> = parse_lines(&lp).map(|l| l.unwrap()).collect();
+        let expected = lines_to_sharded_entries(
+            &lines,
+            ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
+            NO_SHARD_CONFIG,
+            &partitioner(1),
+        )
+        .unwrap()
+        .pop()
+        .unwrap()
+        .entry;
+
+        let mut builder = EntryBuilder::new();
+        assert!(builder.is_empty());
+        builder
+            .table("a")
+            .tag("host", "a")
+            .field("val", 23_i64)
+            .timestamp(983);
+        builder
+            .table("b")
+            .tag("host", "b")
+            .field("val", 1_i64)
+            .timestamp(10);
+        builder
+            .table("a")
+            .tag("host", "a")
+            .tag("region", "west")
+            .field("val2", 23.2)
+            .timestamp(2343);
+        builder
+            .table("a")
+            .field("val", 20_i64)
+            .field("bool", true)
+            .field("string", "hello")
+            .field("unsigned", 7_u64)
+            // overwrites the previous value
+            .field("val", 21_i64);
+        assert!(!builder.is_empty());
+
+        let entry = builder.build("key_0", ARBITRARY_DEFAULT_TIME).unwrap();
+        assert_eq!(entry, expected);
+    }
+
+    #[test]
+    fn entry_builder_errors() {
+        let mut builder = EntryBuilder::new();
+        builder.table("a").field("val", 1_i64);
+        builder.table("a").tag("host", "a");
+        let err = builder.build("key", ARBITRARY_DEFAULT_TIME).unwrap_err();
+        assert_eq!(err.to_string(), "row 2 of table a has no fields");
+
+        let mut builder = EntryBuilder::new();
+        builder.table("a").field("val", 1_i64);
+        builder.table("a").field("val", 1.5);
+        let err = builder.build("key", ARBITRARY_DEFAULT_TIME).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "table a has column val type mismatch: expected i64 but got f64 with new data on line 1"
+        );
+    }
+
     #[test]
     fn null_mask_builder() {
         let mut m = NullMaskBuilder::new();
@@ -1750,6 +2351,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             sharder(1).as_ref(),
             &partitioner(1),
         )
@@ -1786,6 +2388,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             sharder(1).as_ref(),
             &partitioner(1),
         )
@@ -1835,6 +2438,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             sharder(1).as_ref(),
             &partitioner(1),
         )
@@ -1876,9 +2480,14 @@ mod tests {
 
         let default_time = Utc::now().timestamp_nanos();
 
-        let sharded_entries =
-            lines_to_sharded_entries(&lines, default_time, sharder(1).as_ref(), &partitioner(1))
-                .unwrap();
+        let sharded_entries = lines_to_sharded_entries(
+            &lines,
+            default_time,
+            Precision::Nanoseconds,
+            sharder(1).as_ref(),
+            &partitioner(1),
+        )
+        .unwrap();
 
         let partition_writes = sharded_entries
             .first()
@@ -1914,9 +2523,14 @@ mod tests {
         let hour_partitioner = hour_partitioner();
 
         // Extract the partition key the partitioned write was assigned
-        let sharded_entries =
-            lines_to_sharded_entries(&lines, default_time, sharder(1).as_ref(), &hour_partitioner)
-                .unwrap();
+        let sharded_entries = lines_to_sharded_entries(
+            &lines,
+            default_time,
+            Precision::Nanoseconds,
+            sharder(1).as_ref(),
+            &hour_partitioner,
+        )
+        .unwrap();
         let partition_writes = sharded_entries
             .first()
             .unwrap()
@@ -1950,6 +2564,59 @@ mod tests {
         assert_eq!(point_key, assigned_partition_key);
     }
 
+    #[test]
+    fn timestamp_precision() {
+        let lp = vec!["a val=1i", "a val=2i 1617286224"].join("\n");
+        let lines: Vec<_> = parse_lines(&lp).map(|l| l.unwrap()).collect();
+
+        let sharded_entries = lines_to_sharded_entries(
+            &lines,
+            ARBITRARY_DEFAULT_TIME,
+            Precision::Seconds,
+            sharder(1).as_ref(),
+            &hour_partitioner(),
+        )
+        .unwrap();
+
+        let partition_writes = sharded_entries
+            .first()
+            .unwrap()
+            .entry
+            .partition_writes()
+            .unwrap();
+        let keys: Vec<_> = partition_writes.iter().map(|w| w.key()).collect();
+        assert_eq!(keys, vec!["1970-01-01T00", "2021-04-01T14"]);
+
+        let table_batches = partition_writes[1].table_batches();
+        let columns = table_batches[0].columns();
+        let col = columns.get(0).unwrap();
+        assert_eq!(col.name(), TIME_COLUMN_NAME);
+        let values = col.values().i64_values().unwrap();
+        assert_eq!(values, vec![Some(1_617_286_224_000_000_000)]);
+
+        // The default time already is in nanoseconds
+        let table_batches = partition_writes[0].table_batches();
+        let columns = table_batches[0].columns();
+        let col = columns.get(0).unwrap();
+        let values = col.values().i64_values().unwrap();
+        assert_eq!(values, vec![Some(ARBITRARY_DEFAULT_TIME)]);
+
+        let lp = "a val=1i 1\na val=2i 9223372036854775";
+        let lines: Vec<_> = parse_lines(&lp).map(|l| l.unwrap()).collect();
+        let err = lines_to_sharded_entries(
+            &lines,
+            ARBITRARY_DEFAULT_TIME,
+            Precision::Seconds,
+            sharder(1).as_ref(),
+            &hour_partitioner(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "timestamp 9223372036854775 on line 2 is out of range for precision s"
+        );
+    }
+
     #[test]
     fn field_type_conflict() {
         let lp = vec!["a val=1i 1", "a val=2.1 123"].join("\n");
@@ -1958,6 +2625,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             sharder(1).as_ref(),
             &partitioner(1),
         );
@@ -1973,6 +2641,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             sharder(1).as_ref(),
             &partitioner(1),
         );
@@ -0,0 +1,189 @@
+//! Generates the seed corpus of the fuzz targets: line protocol covering the
+//! syntax of measurements, tags, fields and timestamps for `line_protocol`,
+//! and the entries built from it for `entry`.
+//!
+//! Run with `cargo run --example generate_corpus [<number of inputs>]` in the
+//! `fuzz` directory, which writes the inputs to `corpus/<target>/`.
+use std::{fs, path::Path};
+
+use data_types::timestamp::Precision;
+use entry::{
+    lines_to_sharded_entries,
+    test_helpers::{hour_partitioner, sharder},
+};
+use influxdb_line_protocol::parse_lines;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+/// The number of inputs generated for each target if not specified
+const DEFAULT_INPUTS: usize = 200;
+
+/// Names containing the characters that have to be escaped in measurements,
+/// tag keys and values or field keys
+const NAMES: &[&str] = &[
+    "cpu",
+    "h2o_temperature",
+    "disk usage",
+    "a,b",
+    "key=value",
+    "back\\slash",
+    "ünïcödé",
+    "#not_a_comment",
+    "time",
+];
+
+/// String field values containing the characters that have to be escaped
+const STRINGS: &[&str] = &[
+    "",
+    "hello",
+    "with space",
+    "quote\"d",
+    "back\\slash",
+    "new\nline",
+];
+
+fn main() {
+    let inputs = std::env::args()
+        .nth(1)
+        .map(|n| n.parse().expect("number of inputs must be an integer"))
+        .unwrap_or(DEFAULT_INPUTS);
+
+    let lp_dir = Path::new("corpus/line_protocol");
+    let entry_dir = Path::new("corpus/entry");
+    fs::create_dir_all(lp_dir).expect("creating corpus directory");
+    fs::create_dir_all(entry_dir).expect("creating corpus directory");
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut entries = 0;
+    for i in 0..inputs {
+        let lp = lines(&mut rng);
+        fs::write(lp_dir.join(format!("{:05}", i)), &lp).expect("writing corpus");
+
+        let lines: Vec<_> = parse_lines(&lp).filter_map(Result::ok).collect();
+        if let Ok(sharded_entries) = lines_to_sharded_entries(
+            &lines,
+            0,
+            Precision::Nanoseconds,
+            sharder(1).as_ref(),
+            &hour_partitioner(),
+        ) {
+            for sharded_entry in sharded_entries {
+                fs::write(
+                    entry_dir.join(format!("{:05}", entries)),
+                    sharded_entry.entry.data(),
+                )
+                .expect("writing corpus");
+                entries += 1;
+            }
+        }
+    }
+
+    println!(
+        "wrote {} line protocol inputs and {} entries",
+        inputs, entries
+    );
+}
+
+/// Returns a few lines of line protocol, mostly valid ones, separated by
+/// newlines and the occasional comment or blank line
+fn lines(rng: &mut StdRng) -> String {
+    let count = rng.gen_range(1..=5);
+    let mut lines = Vec::with_capacity(count);
+    for _ in 0..count {
+        match rng.gen_range(0..10) {
+            0 => lines.push("# a comment".to_string()),
+            1 => lines.push(String::new()),
+            _ => lines.push(line(rng)),
+        }
+    }
+
+    // Some inputs use the same measurement with conflicting field types
+    if rng.gen_bool(0.2) {
+        lines.push("conflict value=1i".to_string());
+        lines.push("conflict value=1.5".to_string());
+    }
+
+    lines.join(if rng.gen_bool(0.1) { "\r\n" } else { "\n" })
+}
+
+/// Returns a single line with random tags, fields and timestamp
+fn line(rng: &mut StdRng) -> String {
+    let mut line = escape(name(rng), &[',', ' ']);
+
+    for _ in 0..rng.gen_range(0..=3) {
+        line.push(',');
+        line.push_str(&escape(name(rng), &[',', '=', ' ']));
+        line.push('=');
+        line.push_str(&escape(name(rng), &[',', '=', ' ']));
+    }
+
+    line.push(' ');
+    let fields: Vec<_> = (0..rng.gen_range(1..=4))
+        .map(|_| {
+            format!(
+                "{}={}",
+                escape(name(rng), &[',', '=', ' ']),
+                field_value(rng)
+            )
+        })
+        .collect();
+    line.push_str(&fields.join(","));
+
+    if rng.gen_bool(0.8) {
+        let timestamp: i64 = match rng.gen_range(0..4) {
+            0 => 0,
+            1 => i64::MAX,
+            2 => i64::MIN,
+            _ => rng.gen_range(0..1_700_000_000_000_000_000),
+        };
+        line.push_str(&format!(" {}", timestamp));
+    }
+
+    line
+}
+
+fn name(rng: &mut StdRng) -> &'static str {
+    *NAMES.choose(rng).expect("names are not empty")
+}
+
+/// Returns a field value of a random type, including the edge cases of
+/// each type
+fn field_value(rng: &mut StdRng) -> String {
+    match rng.gen_range(0..5) {
+        0 => {
+            let value = *[0, -1, i64::MAX, i64::MIN, rng.gen()]
+                .choose(rng)
+                .expect("values are not empty");
+            format!("{}i", value)
+        }
+        1 => {
+            let value = *[0, u64::MAX, rng.gen()]
+                .choose(rng)
+                .expect("values are not empty");
+            format!("{}u", value)
+        }
+        2 => ["0", "-1.5", "1e10", "1.7976931348623157e308", "3"]
+            .choose(rng)
+            .expect("values are not empty")
+            .to_string(),
+        3 => ["t", "T", "true", "True", "TRUE", "f", "F", "false", "False"]
+            .choose(rng)
+            .expect("values are not empty")
+            .to_string(),
+        _ => {
+            let value = STRINGS.choose(rng).expect("strings are not empty");
+            format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+    }
+}
+
+/// Escapes the `special` characters of `name` with a backslash
+fn escape(name: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,30 @@
+//! Decodes arbitrary input as an entry and writes it into mutable buffer
+//! chunks, as done for the entries written over gRPC or read from a write
+//! buffer
+#![no_main]
+use std::convert::TryFrom;
+
+use entry::Entry;
+use libfuzzer_sys::fuzz_target;
+use mutable_buffer::chunk::{Chunk, ChunkMetrics};
+
+fuzz_target!(|data: &[u8]| {
+    let entry = match Entry::try_from(data.to_vec()) {
+        Ok(entry) => entry,
+        Err(_) => return,
+    };
+
+    for partition_write in entry.partition_writes().unwrap_or_default() {
+        let _ = partition_write.key();
+
+        for table_batch in partition_write.table_batches() {
+            let mut chunk = Chunk::new(table_batch.name(), ChunkMetrics::new_unregistered());
+            if chunk
+                .validate_table_batch(&table_batch, Default::default(), &[])
+                .is_ok()
+            {
+                let _ = chunk.write_table_batch(0, 0, table_batch, Default::default(), &[]);
+            }
+        }
+    }
+});
@@ -0,0 +1,42 @@
+//! Parses arbitrary input as line protocol and converts the lines that parse
+//! into entries, as done for the line protocol written over HTTP
+#![no_main]
+use std::convert::TryFrom;
+
+use data_types::timestamp::Precision;
+use entry::{
+    lines_to_sharded_entries,
+    test_helpers::{hour_partitioner, sharder},
+    Entry,
+};
+use influxdb_line_protocol::parse_lines;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let lp = match std::str::from_utf8(data) {
+        Ok(lp) => lp,
+        Err(_) => return,
+    };
+
+    let lines: Vec<_> = parse_lines(lp).filter_map(Result::ok).collect();
+    for line in &lines {
+        let _ = line.to_string();
+    }
+
+    let sharded_entries = match lines_to_sharded_entries(
+        &lines,
+        0,
+        Precision::Nanoseconds,
+        sharder(1).as_ref(),
+        &hour_partitioner(),
+    ) {
+        Ok(sharded_entries) => sharded_entries,
+        Err(_) => return,
+    };
+
+    // The entries built from valid lines must be valid themselves
+    for sharded_entry in sharded_entries {
+        let data = sharded_entry.entry.data().to_vec();
+        Entry::try_from(data).expect("entry built from line protocol is invalid");
+    }
+});
@@ -1,6 +1,8 @@
-use crate::google::{FieldViolation, FromField};
+use crate::google::{FieldViolation, FromField, FromFieldOpt};
 use crate::influxdata::iox::management::v1 as management;
-use data_types::chunk_metadata::{ChunkStorage, ChunkSummary};
+use data_types::chunk_metadata::{
+    ChunkEvent, ChunkLifecycleState, ChunkStorage, ChunkSummary, ReadBufferChunkSummary,
+};
 use std::convert::{TryFrom, TryInto};
 use std::sync::Arc;
 
@@ -13,16 +15,19 @@ impl From<ChunkSummary> for management::Chunk {
             id,
             storage,
             estimated_bytes,
+            allocated_bytes,
             row_count,
             time_of_first_write,
             time_of_last_write,
             time_closed,
+            read_buffer,
         } = summary;
 
         let storage: management::ChunkStorage = storage.into();
         let storage = storage.into(); // convert to i32
 
         let estimated_bytes = estimated_bytes as u64;
+        let allocated_bytes = allocated_bytes as u64;
         let row_count = row_count as u64;
 
         let partition_key = partition_key.to_string();
@@ -31,6 +36,7 @@ impl From<ChunkSummary> for management::Chunk {
         let time_of_first_write = time_of_first_write.map(|t| t.into());
         let time_of_last_write = time_of_last_write.map(|t| t.into());
         let time_closed = time_closed.map(|t| t.into());
+        let read_buffer = read_buffer.map(|r| r.into());
 
         Self {
             partition_key,
@@ -38,10 +44,32 @@ impl From<ChunkSummary> for management::Chunk {
             id,
             storage,
             estimated_bytes,
+            allocated_bytes,
             row_count,
             time_of_first_write,
             time_of_last_write,
             time_closed,
+            read_buffer,
+        }
+    }
+}
+
+impl From<ReadBufferChunkSummary> for management::ReadBufferChunkSummary {
+    fn from(summary: ReadBufferChunkSummary) -> Self {
+        let ReadBufferChunkSummary {
+            row_count,
+            row_groups,
+            compressed_bytes,
+            raw_bytes,
+            time_of_last_access,
+        } = summary;
+
+        Self {
+            row_count,
+            row_groups: row_groups as u64,
+            compressed_bytes: compressed_bytes as u64,
+            raw_bytes: raw_bytes as u64,
+            time_of_last_access: time_of_last_access.map(|t| t.into()),
         }
     }
 }
@@ -93,16 +121,20 @@ impl TryFrom<management::Chunk> for ChunkSummary {
                 description: "Timestamp must be positive".to_string(),
             })?;
 
+        let read_buffer = proto.read_buffer.optional("read_buffer")?;
+
         let management::Chunk {
             partition_key,
             table_name,
             id,
             estimated_bytes,
+            allocated_bytes,
             row_count,
             ..
         } = proto;
 
         let estimated_bytes = estimated_bytes as usize;
+        let allocated_bytes = allocated_bytes as usize;
         let row_count = row_count as usize;
         let partition_key = Arc::from(partition_key.as_str());
         let table_name = Arc::from(table_name.as_str());
@@ -113,10 +145,35 @@ impl TryFrom<management::Chunk> for ChunkSummary {
             id,
             storage,
             estimated_bytes,
+            allocated_bytes,
             row_count,
             time_of_first_write,
             time_of_last_write,
             time_closed,
+            read_buffer,
+        })
+    }
+}
+
+impl TryFrom<management::ReadBufferChunkSummary> for ReadBufferChunkSummary {
+    type Error = FieldViolation;
+
+    fn try_from(proto: management::ReadBufferChunkSummary) -> Result<Self, Self::Error> {
+        let time_of_last_access = proto
+            .time_of_last_access
+            .map(TryInto::try_into)
+            .transpose()
+            .map_err(|_| FieldViolation {
+                field: "time_of_last_access".to_string(),
+                description: "Timestamp must be positive".to_string(),
+            })?;
+
+        Ok(Self {
+            row_count: proto.row_count,
+            row_groups: proto.row_groups as usize,
+            compressed_bytes: proto.compressed_bytes as usize,
+            raw_bytes: proto.raw_bytes as usize,
+            time_of_last_access,
         })
     }
 }
@@ -138,6 +195,96 @@ impl TryFrom<management::ChunkStorage> for ChunkStorage {
     }
 }
 
+/// Conversion code to management API chunk event structure
+impl From<ChunkEvent> for management::ChunkEvent {
+    fn from(event: ChunkEvent) -> Self {
+        let ChunkEvent {
+            partition_key,
+            table_name,
+            id,
+            state,
+            time,
+        } = event;
+
+        let state: management::ChunkLifecycleState = state.into();
+
+        Self {
+            partition_key: partition_key.to_string(),
+            table_name: table_name.to_string(),
+            id,
+            state: state.into(), // convert to i32
+            time: Some(time.into()),
+        }
+    }
+}
+
+impl From<ChunkLifecycleState> for management::ChunkLifecycleState {
+    fn from(state: ChunkLifecycleState) -> Self {
+        match state {
+            ChunkLifecycleState::Open => Self::Open,
+            ChunkLifecycleState::Closed => Self::Closed,
+            ChunkLifecycleState::Moving => Self::Moving,
+            ChunkLifecycleState::Moved => Self::Moved,
+            ChunkLifecycleState::Persisting => Self::Persisting,
+            ChunkLifecycleState::Persisted => Self::Persisted,
+            ChunkLifecycleState::Unloaded => Self::Unloaded,
+            ChunkLifecycleState::Dropped => Self::Dropped,
+        }
+    }
+}
+
+/// Conversion code from management API chunk event structure
+impl TryFrom<management::ChunkEvent> for ChunkEvent {
+    type Error = FieldViolation;
+
+    fn try_from(proto: management::ChunkEvent) -> Result<Self, Self::Error> {
+        // Use prost enum conversion
+        let state = proto.state().scope("state")?;
+
+        let time = proto
+            .time
+            .ok_or_else(|| FieldViolation::required("time"))?
+            .try_into()
+            .map_err(|_| FieldViolation {
+                field: "time".to_string(),
+                description: "Timestamp must be positive".to_string(),
+            })?;
+
+        let management::ChunkEvent {
+            partition_key,
+            table_name,
+            id,
+            ..
+        } = proto;
+
+        Ok(Self {
+            partition_key: Arc::from(partition_key.as_str()),
+            table_name: Arc::from(table_name.as_str()),
+            id,
+            state,
+            time,
+        })
+    }
+}
+
+impl TryFrom<management::ChunkLifecycleState> for ChunkLifecycleState {
+    type Error = FieldViolation;
+
+    fn try_from(proto: management::ChunkLifecycleState) -> Result<Self, Self::Error> {
+        match proto {
+            management::ChunkLifecycleState::Open => Ok(Self::Open),
+            management::ChunkLifecycleState::Closed => Ok(Self::Closed),
+            management::ChunkLifecycleState::Moving => Ok(Self::Moving),
+            management::ChunkLifecycleState::Moved => Ok(Self::Moved),
+            management::ChunkLifecycleState::Persisting => Ok(Self::Persisting),
+            management::ChunkLifecycleState::Persisted => Ok(Self::Persisted),
+            management::ChunkLifecycleState::Unloaded => Ok(Self::Unloaded),
+            management::ChunkLifecycleState::Dropped => Ok(Self::Dropped),
+            management::ChunkLifecycleState::Unspecified => Err(FieldViolation::required("")),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -149,11 +296,13 @@ mod test {
             table_name: "bar".to_string(),
             id: 42,
             estimated_bytes: 1234,
+            allocated_bytes: 1234,
             row_count: 321,
             storage: management::ChunkStorage::ObjectStoreOnly.into(),
             time_of_first_write: None,
             time_of_last_write: None,
             time_closed: None,
+            read_buffer: None,
         };
 
         let summary = ChunkSummary::try_from(proto).expect("conversion successful");
@@ -162,11 +311,13 @@ mod test {
             table_name: Arc::from("bar"),
             id: 42,
             estimated_bytes: 1234,
+            allocated_bytes: 1234,
             row_count: 321,
             storage: ChunkStorage::ObjectStoreOnly,
             time_of_first_write: None,
             time_of_last_write: None,
             time_closed: None,
+            read_buffer: None,
         };
 
         assert_eq!(
@@ -183,11 +334,13 @@ mod test {
             table_name: Arc::from("bar"),
             id: 42,
             estimated_bytes: 1234,
+            allocated_bytes: 1234,
             row_count: 321,
             storage: ChunkStorage::ObjectStoreOnly,
             time_of_first_write: None,
             time_of_last_write: None,
             time_closed: None,
+            read_buffer: None,
         };
 
         let proto = management::Chunk::try_from(summary).expect("conversion successful");
@@ -197,11 +350,13 @@ mod test {
             table_name: "bar".to_string(),
             id: 42,
             estimated_bytes: 1234,
+            allocated_bytes: 1234,
             row_count: 321,
             storage: management::ChunkStorage::ObjectStoreOnly.into(),
             time_of_first_write: None,
             time_of_last_write: None,
             time_closed: None,
+            read_buffer: None,
         };
 
         assert_eq!(
@@ -210,4 +365,71 @@ mod test {
             proto, expected
         );
     }
+
+    #[test]
+    fn read_buffer_summary_round_trip() {
+        let proto = management::ReadBufferChunkSummary {
+            row_count: 321,
+            row_groups: 2,
+            compressed_bytes: 1234,
+            raw_bytes: 4321,
+            time_of_last_access: Some(crate::google::protobuf::Timestamp {
+                seconds: 10,
+                nanos: 20,
+            }),
+        };
+
+        let summary =
+            ReadBufferChunkSummary::try_from(proto.clone()).expect("conversion successful");
+        assert_eq!(summary.row_count, 321);
+        assert_eq!(summary.row_groups, 2);
+        assert_eq!(summary.compressed_bytes, 1234);
+        assert_eq!(summary.raw_bytes, 4321);
+        assert_eq!(
+            summary.time_of_last_access.unwrap().timestamp_nanos(),
+            10_000_000_020
+        );
+
+        assert_eq!(management::ReadBufferChunkSummary::from(summary), proto);
+    }
+
+    #[test]
+    fn chunk_event_round_trip() {
+        let proto = management::ChunkEvent {
+            partition_key: "foo".to_string(),
+            table_name: "bar".to_string(),
+            id: 42,
+            state: management::ChunkLifecycleState::Moved.into(),
+            time: Some(crate::google::protobuf::Timestamp {
+                seconds: 10,
+                nanos: 20,
+            }),
+        };
+
+        let event = ChunkEvent::try_from(proto.clone()).expect("conversion successful");
+        assert_eq!(event.partition_key.as_ref(), "foo");
+        assert_eq!(event.table_name.as_ref(), "bar");
+        assert_eq!(event.id, 42);
+        assert_eq!(event.state, ChunkLifecycleState::Moved);
+        assert_eq!(event.time.timestamp_nanos(), 10_000_000_020);
+
+        assert_eq!(management::ChunkEvent::from(event), proto);
+    }
+
+    #[test]
+    fn chunk_event_missing_state() {
+        let proto = management::ChunkEvent {
+            partition_key: "foo".to_string(),
+            table_name: "bar".to_string(),
+            id: 42,
+            state: management::ChunkLifecycleState::Unspecified.into(),
+            time: Some(crate::google::protobuf::Timestamp {
+                seconds: 10,
+                nanos: 20,
+            }),
+        };
+
+        let err = ChunkEvent::try_from(proto).unwrap_err();
+        assert_eq!(err.field, "state");
+    }
 }
@@ -1,18 +1,26 @@
+use std::collections::HashSet;
 use std::convert::{TryFrom, TryInto};
+use std::num::NonZeroUsize;
 use std::time::Duration;
 
 use thiserror::Error;
 
 use data_types::database_rules::{
-    ColumnType, ColumnValue, DatabaseRules, Order, RoutingConfig, RoutingRules,
+    ColumnType, ColumnValue, DatabaseRules, LineLimits, Order, Quotas, ReplicationConfig,
+    RoutingConfig, RoutingRules, SchemaConflictPolicy, TypeCoercionRule,
+    DEFAULT_REPLICATION_QUEUE_SIZE,
 };
 use data_types::DatabaseName;
 
-use crate::google::{FieldViolation, FieldViolationExt, FromFieldOpt, FromFieldString};
+use crate::google::{
+    FieldViolation, FieldViolationExt, FromField, FromFieldOpt, FromFieldString, FromFieldVec,
+};
 use crate::influxdata::iox::management::v1 as management;
 
+mod continuous_query;
 mod lifecycle;
 mod partition;
+mod rollup;
 mod shard;
 
 impl From<DatabaseRules> for management::DatabaseRules {
@@ -26,6 +34,23 @@ impl From<DatabaseRules> for management::DatabaseRules {
             write_buffer_connection_string: rules
                 .write_buffer_connection_string
                 .unwrap_or_default(),
+            replication_config: rules.replication_config.map(Into::into),
+            quotas: Some(rules.quotas.into()),
+            line_limits: Some(rules.line_limits.into()),
+            schema_conflict_policy: management::SchemaConflictPolicy::from(
+                rules.schema_conflict_policy,
+            ) as _,
+            type_coercion_rules: rules
+                .type_coercion_rules
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            rollup_rules: rules.rollup_rules.into_iter().map(Into::into).collect(),
+            continuous_queries: rules
+                .continuous_queries
+                .into_iter()
+                .map(Into::into)
+                .collect(),
         }
     }
 }
@@ -58,6 +83,29 @@ impl TryFrom<management::DatabaseRules> for DatabaseRules {
 
         let write_buffer_connection_string = proto.write_buffer_connection_string.optional();
 
+        let replication_config = proto.replication_config.optional("replication_config")?;
+
+        let quotas = proto.quotas.map(Into::into).unwrap_or_default();
+
+        let line_limits = proto.line_limits.map(Into::into).unwrap_or_default();
+
+        let schema_conflict_policy = proto.schema_conflict_policy().into();
+
+        let type_coercion_rules = proto.type_coercion_rules.vec_field("type_coercion_rules")?;
+
+        let rollup_rules = proto.rollup_rules.vec_field("rollup_rules")?;
+
+        let continuous_queries = proto.continuous_queries.vec_field("continuous_queries")?;
+        let mut names = HashSet::new();
+        for query in &continuous_queries {
+            if !names.insert(query.name.as_str()) {
+                return Err(FieldViolation {
+                    field: "continuous_queries".to_string(),
+                    description: format!("Duplicate query name {}", query.name),
+                });
+            }
+        }
+
         Ok(Self {
             name,
             partition_template,
@@ -65,6 +113,13 @@ impl TryFrom<management::DatabaseRules> for DatabaseRules {
             routing_rules,
             worker_cleanup_avg_sleep,
             write_buffer_connection_string,
+            replication_config,
+            quotas,
+            line_limits,
+            schema_conflict_policy,
+            type_coercion_rules,
+            rollup_rules,
+            continuous_queries,
         })
     }
 }
@@ -115,6 +170,135 @@ impl TryFrom<management::RoutingConfig> for RoutingConfig {
     }
 }
 
+impl From<Quotas> for management::Quotas {
+    fn from(quotas: Quotas) -> Self {
+        let limit = |quota: Option<NonZeroUsize>| quota.map_or(0, |q| q.get() as u64);
+
+        Self {
+            max_tables: limit(quotas.max_tables),
+            max_columns_per_table: limit(quotas.max_columns_per_table),
+            max_rows: limit(quotas.max_rows),
+        }
+    }
+}
+
+impl From<management::Quotas> for Quotas {
+    fn from(proto: management::Quotas) -> Self {
+        let limit = |quota: u64| NonZeroUsize::new(quota as usize);
+
+        Self {
+            max_tables: limit(proto.max_tables),
+            max_columns_per_table: limit(proto.max_columns_per_table),
+            max_rows: limit(proto.max_rows),
+        }
+    }
+}
+
+impl From<LineLimits> for management::LineLimits {
+    fn from(limits: LineLimits) -> Self {
+        let limit = |limit: Option<NonZeroUsize>| limit.map_or(0, |l| l.get() as u64);
+
+        Self {
+            max_line_length: limit(limits.max_line_length),
+            max_fields_per_line: limit(limits.max_fields_per_line),
+            max_columns_per_table: limit(limits.max_columns_per_table),
+            max_table_name_length: limit(limits.max_table_name_length),
+        }
+    }
+}
+
+impl From<management::LineLimits> for LineLimits {
+    fn from(proto: management::LineLimits) -> Self {
+        let limit = |limit: u64| NonZeroUsize::new(limit as usize);
+
+        Self {
+            max_line_length: limit(proto.max_line_length),
+            max_fields_per_line: limit(proto.max_fields_per_line),
+            max_columns_per_table: limit(proto.max_columns_per_table),
+            max_table_name_length: limit(proto.max_table_name_length),
+        }
+    }
+}
+
+impl From<SchemaConflictPolicy> for management::SchemaConflictPolicy {
+    fn from(policy: SchemaConflictPolicy) -> Self {
+        match policy {
+            SchemaConflictPolicy::Reject => Self::Reject,
+            SchemaConflictPolicy::DropLines => Self::DropLines,
+            SchemaConflictPolicy::Coerce => Self::Coerce,
+        }
+    }
+}
+
+impl From<management::SchemaConflictPolicy> for SchemaConflictPolicy {
+    fn from(proto: management::SchemaConflictPolicy) -> Self {
+        match proto {
+            management::SchemaConflictPolicy::Unspecified => Self::default(),
+            management::SchemaConflictPolicy::Reject => Self::Reject,
+            management::SchemaConflictPolicy::DropLines => Self::DropLines,
+            management::SchemaConflictPolicy::Coerce => Self::Coerce,
+        }
+    }
+}
+
+impl From<TypeCoercionRule> for management::TypeCoercionRule {
+    fn from(rule: TypeCoercionRule) -> Self {
+        let from: management::ColumnType = rule.from.into();
+        let to: management::ColumnType = rule.to.into();
+
+        Self {
+            from: from as _,
+            to: to as _,
+        }
+    }
+}
+
+impl TryFrom<management::TypeCoercionRule> for TypeCoercionRule {
+    type Error = FieldViolation;
+
+    fn try_from(proto: management::TypeCoercionRule) -> Result<Self, Self::Error> {
+        let rule = Self {
+            from: proto.from().scope("from")?,
+            to: proto.to().scope("to")?,
+        };
+
+        if !rule.is_valid() {
+            return Err(FieldViolation {
+                field: "".to_string(),
+                description: format!(
+                    "Cannot convert {:?} to {:?}, only different numeric types can be converted",
+                    rule.from, rule.to
+                ),
+            });
+        }
+
+        Ok(rule)
+    }
+}
+
+impl From<ReplicationConfig> for management::ReplicationConfig {
+    fn from(replication_config: ReplicationConfig) -> Self {
+        Self {
+            targets: Some(replication_config.targets.into()),
+            queue_size: replication_config.queue_size.get() as u64,
+        }
+    }
+}
+
+impl TryFrom<management::ReplicationConfig> for ReplicationConfig {
+    type Error = FieldViolation;
+
+    fn try_from(proto: management::ReplicationConfig) -> Result<Self, Self::Error> {
+        let queue_size = NonZeroUsize::new(proto.queue_size as usize)
+            .unwrap_or_else(|| NonZeroUsize::new(DEFAULT_REPLICATION_QUEUE_SIZE).unwrap());
+
+        Ok(Self {
+            targets: proto.targets.required("targets")?,
+            queue_size,
+        })
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum DecodeError {
     #[error("failed to decode protobuf: {0}")]
@@ -232,7 +416,7 @@ mod tests {
         assert_eq!(rules.name.as_str(), protobuf.name.as_str());
         assert_eq!(protobuf.name, back.name);
 
-        assert_eq!(rules.partition_template.parts.len(), 0);
+        assert_eq!(rules.partition_template.parts().len(), 0);
 
         // These will be defaulted as optionality not preserved on non-protobuf
         // DatabaseRules
@@ -241,5 +425,144 @@ mod tests {
 
         // These should be none as preserved on non-protobuf DatabaseRules
         assert!(back.routing_rules.is_none());
+        assert!(back.replication_config.is_none());
+    }
+
+    #[test]
+    fn test_replication_config() {
+        let protobuf = management::ReplicationConfig {
+            targets: Some(management::NodeGroup {
+                nodes: vec![management::node_group::Node { id: 2 }],
+            }),
+            queue_size: 0,
+        };
+
+        let config: ReplicationConfig = protobuf.try_into().unwrap();
+        assert_eq!(config.targets.len(), 1);
+        assert_eq!(config.queue_size.get(), DEFAULT_REPLICATION_QUEUE_SIZE);
+
+        let back: management::ReplicationConfig = config.into();
+        assert_eq!(back.queue_size, DEFAULT_REPLICATION_QUEUE_SIZE as u64);
+
+        let err =
+            ReplicationConfig::try_from(management::ReplicationConfig::default()).unwrap_err();
+        assert_eq!(err.field, "targets");
+    }
+
+    #[test]
+    fn test_quotas() {
+        let protobuf = management::Quotas {
+            max_tables: 10,
+            max_columns_per_table: 0,
+            max_rows: 1000,
+        };
+
+        let quotas: Quotas = protobuf.clone().into();
+        assert_eq!(quotas.max_tables, NonZeroUsize::new(10));
+        assert_eq!(quotas.max_columns_per_table, None);
+        assert_eq!(quotas.max_rows, NonZeroUsize::new(1000));
+
+        let back: management::Quotas = quotas.into();
+        assert_eq!(back, protobuf);
+
+        let quotas: Quotas = management::Quotas::default().into();
+        assert!(quotas.is_unlimited());
+    }
+
+    #[test]
+    fn test_line_limits() {
+        let protobuf = management::LineLimits {
+            max_line_length: 1024,
+            max_fields_per_line: 0,
+            max_columns_per_table: 50,
+            max_table_name_length: 64,
+        };
+
+        let limits: LineLimits = protobuf.clone().into();
+        assert_eq!(limits.max_line_length, NonZeroUsize::new(1024));
+        assert_eq!(limits.max_fields_per_line, None);
+        assert_eq!(limits.max_columns_per_table, NonZeroUsize::new(50));
+        assert_eq!(limits.max_table_name_length, NonZeroUsize::new(64));
+
+        let back: management::LineLimits = limits.into();
+        assert_eq!(back, protobuf);
+
+        let limits: LineLimits = management::LineLimits::default().into();
+        assert!(limits.is_unlimited());
+    }
+
+    #[test]
+    fn test_schema_conflict_policy() {
+        let protobuf = management::DatabaseRules {
+            name: "database".to_string(),
+            schema_conflict_policy: management::SchemaConflictPolicy::DropLines as _,
+            ..Default::default()
+        };
+
+        let rules: DatabaseRules = protobuf.try_into().unwrap();
+        assert_eq!(
+            rules.schema_conflict_policy,
+            SchemaConflictPolicy::DropLines
+        );
+
+        let back: management::DatabaseRules = rules.into();
+        assert_eq!(
+            back.schema_conflict_policy(),
+            management::SchemaConflictPolicy::DropLines
+        );
+
+        // Unknown values fall back to the default
+        let protobuf = management::DatabaseRules {
+            name: "database".to_string(),
+            schema_conflict_policy: 42,
+            ..Default::default()
+        };
+        let rules: DatabaseRules = protobuf.try_into().unwrap();
+        assert_eq!(rules.schema_conflict_policy, SchemaConflictPolicy::Reject);
+    }
+
+    #[test]
+    fn test_type_coercion_rules() {
+        let protobuf = management::DatabaseRules {
+            name: "database".to_string(),
+            type_coercion_rules: vec![management::TypeCoercionRule {
+                from: management::ColumnType::I64 as _,
+                to: management::ColumnType::F64 as _,
+            }],
+            ..Default::default()
+        };
+
+        let rules: DatabaseRules = protobuf.clone().try_into().unwrap();
+        assert_eq!(
+            rules.type_coercion_rules,
+            vec![TypeCoercionRule {
+                from: ColumnType::I64,
+                to: ColumnType::F64
+            }]
+        );
+
+        let back: management::DatabaseRules = rules.into();
+        assert_eq!(back.type_coercion_rules, protobuf.type_coercion_rules);
+
+        // Only different numeric types can be converted
+        for (from, to) in &[
+            (management::ColumnType::String, management::ColumnType::F64),
+            (management::ColumnType::I64, management::ColumnType::I64),
+            (
+                management::ColumnType::Unspecified,
+                management::ColumnType::F64,
+            ),
+        ] {
+            let protobuf = management::DatabaseRules {
+                name: "database".to_string(),
+                type_coercion_rules: vec![management::TypeCoercionRule {
+                    from: *from as _,
+                    to: *to as _,
+                }],
+                ..Default::default()
+            };
+            let res: Result<DatabaseRules, _> = protobuf.try_into();
+            assert!(res.unwrap_err().field.starts_with("type_coercion_rules.0"));
+        }
     }
 }
@@ -0,0 +1,127 @@
+use std::convert::{TryFrom, TryInto};
+use std::time::Duration;
+
+use data_types::database_rules::{ContinuousQuery, Schedule};
+
+use crate::google::{FieldViolation, FieldViolationExt, FromFieldString};
+use crate::influxdata::iox::management::v1 as management;
+
+impl From<ContinuousQuery> for management::ContinuousQuery {
+    fn from(query: ContinuousQuery) -> Self {
+        Self {
+            name: query.name,
+            query: query.query,
+            target_table: query.target_table,
+            every: Some(query.schedule.every.into()),
+            offset: Some(query.schedule.offset.into()),
+        }
+    }
+}
+
+impl TryFrom<management::ContinuousQuery> for ContinuousQuery {
+    type Error = FieldViolation;
+
+    fn try_from(proto: management::ContinuousQuery) -> Result<Self, Self::Error> {
+        let every: Duration = match proto.every {
+            Some(d) => d.try_into().field("every")?,
+            None => return Err(FieldViolation::required("every")),
+        };
+        if every.as_nanos() == 0 {
+            return Err(FieldViolation {
+                field: "every".to_string(),
+                description: "Must not be zero".to_string(),
+            });
+        }
+
+        let offset: Duration = match proto.offset {
+            Some(d) => d.try_into().field("offset")?,
+            None => Duration::from_secs(0),
+        };
+        if offset >= every {
+            return Err(FieldViolation {
+                field: "offset".to_string(),
+                description: "Must be less than every".to_string(),
+            });
+        }
+
+        Ok(Self {
+            name: proto.name.required("name")?,
+            query: proto.query.required("query")?,
+            target_table: proto.target_table.required("target_table")?,
+            schedule: Schedule { every, offset },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use data_types::database_rules::DatabaseRules;
+
+    fn query() -> management::ContinuousQuery {
+        management::ContinuousQuery {
+            name: "hourly".to_string(),
+            query: "select count(*) as count from cpu".to_string(),
+            target_table: "cpu_count".to_string(),
+            every: Some(Duration::from_secs(3600).into()),
+            offset: Some(Duration::from_secs(300).into()),
+        }
+    }
+
+    #[test]
+    fn test_continuous_queries() {
+        let protobuf = management::DatabaseRules {
+            name: "database".to_string(),
+            continuous_queries: vec![query()],
+            ..Default::default()
+        };
+
+        let rules: DatabaseRules = protobuf.clone().try_into().unwrap();
+        assert_eq!(
+            rules.continuous_queries,
+            vec![ContinuousQuery {
+                name: "hourly".to_string(),
+                query: "select count(*) as count from cpu".to_string(),
+                target_table: "cpu_count".to_string(),
+                schedule: Schedule {
+                    every: Duration::from_secs(3600),
+                    offset: Duration::from_secs(300),
+                },
+            }]
+        );
+
+        let back: management::DatabaseRules = rules.into();
+        assert_eq!(back.continuous_queries, protobuf.continuous_queries);
+    }
+
+    #[test]
+    fn test_continuous_query_invalid() {
+        let err = ContinuousQuery::try_from(management::ContinuousQuery {
+            name: "".to_string(),
+            ..query()
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "name");
+
+        let err = ContinuousQuery::try_from(management::ContinuousQuery {
+            query: "".to_string(),
+            ..query()
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "query");
+
+        let err = ContinuousQuery::try_from(management::ContinuousQuery {
+            every: None,
+            ..query()
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "every");
+
+        let err = ContinuousQuery::try_from(management::ContinuousQuery {
+            offset: Some(Duration::from_secs(3600).into()),
+            ..query()
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "offset");
+    }
+}
@@ -22,6 +22,19 @@ impl From<LifecycleRules> for management::LifecycleRules {
                 .mutable_size_threshold
                 .map(|x| x.get() as u64)
                 .unwrap_or_default(),
+            mutable_max_age_seconds: config
+                .mutable_max_age_seconds
+                .map(Into::into)
+                .unwrap_or_default(),
+            mutable_row_threshold: config
+                .mutable_row_threshold
+                .map(|x| x.get() as u64)
+                .unwrap_or_default(),
+            mutable_shared_tag_values: config.mutable_shared_tag_values,
+            persist_age_threshold_seconds: config
+                .persist_age_threshold_seconds
+                .map(Into::into)
+                .unwrap_or_default(),
             buffer_size_soft: config
                 .buffer_size_soft
                 .map(|x| x.get() as u64)
@@ -47,6 +60,10 @@ impl TryFrom<management::LifecycleRules> for LifecycleRules {
             mutable_linger_seconds: proto.mutable_linger_seconds.try_into().ok(),
             mutable_minimum_age_seconds: proto.mutable_minimum_age_seconds.try_into().ok(),
             mutable_size_threshold: (proto.mutable_size_threshold as usize).try_into().ok(),
+            mutable_max_age_seconds: proto.mutable_max_age_seconds.try_into().ok(),
+            mutable_row_threshold: (proto.mutable_row_threshold as usize).try_into().ok(),
+            mutable_shared_tag_values: proto.mutable_shared_tag_values,
+            persist_age_threshold_seconds: proto.persist_age_threshold_seconds.try_into().ok(),
             buffer_size_soft: (proto.buffer_size_soft as usize).try_into().ok(),
             buffer_size_hard: (proto.buffer_size_hard as usize).try_into().ok(),
             sort_order: proto.sort_order.optional("sort_order")?.unwrap_or_default(),
@@ -134,6 +151,10 @@ mod tests {
             mutable_linger_seconds: 123,
             mutable_minimum_age_seconds: 5345,
             mutable_size_threshold: 232,
+            mutable_max_age_seconds: 600,
+            mutable_row_threshold: 1000,
+            mutable_shared_tag_values: true,
+            persist_age_threshold_seconds: 1800,
             buffer_size_soft: 353,
             buffer_size_hard: 232,
             sort_order: None,
@@ -159,6 +180,18 @@ mod tests {
             config.mutable_size_threshold.unwrap().get(),
             protobuf.mutable_size_threshold as usize
         );
+        assert_eq!(
+            config.mutable_max_age_seconds.unwrap().get(),
+            protobuf.mutable_max_age_seconds
+        );
+        assert_eq!(
+            config.mutable_row_threshold.unwrap().get(),
+            protobuf.mutable_row_threshold as usize
+        );
+        assert_eq!(
+            config.persist_age_threshold_seconds.unwrap().get(),
+            protobuf.persist_age_threshold_seconds
+        );
         assert_eq!(
             config.buffer_size_soft.unwrap().get(),
             protobuf.buffer_size_soft as usize
@@ -167,6 +200,10 @@ mod tests {
             config.buffer_size_hard.unwrap().get(),
             protobuf.buffer_size_hard as usize
         );
+        assert_eq!(
+            config.mutable_shared_tag_values,
+            protobuf.mutable_shared_tag_values
+        );
         assert_eq!(config.drop_non_persisted, protobuf.drop_non_persisted);
         assert_eq!(config.immutable, protobuf.immutable);
 
@@ -176,6 +213,19 @@ mod tests {
             protobuf.mutable_minimum_age_seconds
         );
         assert_eq!(back.mutable_size_threshold, protobuf.mutable_size_threshold);
+        assert_eq!(
+            back.mutable_max_age_seconds,
+            protobuf.mutable_max_age_seconds
+        );
+        assert_eq!(back.mutable_row_threshold, protobuf.mutable_row_threshold);
+        assert_eq!(
+            back.mutable_shared_tag_values,
+            protobuf.mutable_shared_tag_values
+        );
+        assert_eq!(
+            back.persist_age_threshold_seconds,
+            protobuf.persist_age_threshold_seconds
+        );
         assert_eq!(back.buffer_size_soft, protobuf.buffer_size_soft);
         assert_eq!(back.buffer_size_hard, protobuf.buffer_size_hard);
         assert_eq!(back.drop_non_persisted, protobuf.drop_non_persisted);
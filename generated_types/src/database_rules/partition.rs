@@ -9,7 +9,7 @@ use crate::influxdata::iox::management::v1 as management;
 impl From<PartitionTemplate> for management::PartitionTemplate {
     fn from(pt: PartitionTemplate) -> Self {
         Self {
-            parts: pt.parts.into_iter().map(Into::into).collect(),
+            parts: pt.into_parts().into_iter().map(Into::into).collect(),
         }
     }
 }
@@ -19,7 +19,7 @@ impl TryFrom<management::PartitionTemplate> for PartitionTemplate {
 
     fn try_from(proto: management::PartitionTemplate) -> Result<Self, Self::Error> {
         let parts = proto.parts.vec_field("parts")?;
-        Ok(Self { parts })
+        Ok(Self::new(parts))
     }
 }
 
@@ -40,6 +40,7 @@ impl From<TemplatePart> for management::partition_template::part::Part {
                 Self::StrfTime(ColumnFormat { column, format })
             }
             TemplatePart::TimeFormat(format) => Self::Time(format),
+            TemplatePart::Template(template) => Self::Template(template),
         }
     }
 }
@@ -64,6 +65,14 @@ impl TryFrom<management::partition_template::part::Part> for TemplatePart {
                 })
             }
             Part::Time(format) => Self::TimeFormat(format.required("time")?),
+            Part::Template(template) => {
+                let part = Self::Template(template.required("template")?);
+                part.validate().map_err(|e| FieldViolation {
+                    field: "template".to_string(),
+                    description: e.to_string(),
+                })?;
+                part
+            }
         })
     }
 }
@@ -101,7 +110,7 @@ mod tests {
         let rules: DatabaseRules = protobuf.clone().try_into().unwrap();
         let back: management::DatabaseRules = rules.clone().into();
 
-        assert_eq!(rules.partition_template.parts.len(), 0);
+        assert_eq!(rules.partition_template.parts().len(), 0);
         assert_eq!(protobuf.partition_template, back.partition_template);
     }
 
@@ -147,7 +156,7 @@ mod tests {
         let back: management::PartitionTemplate = pt.clone().into();
 
         assert_eq!(
-            pt.parts,
+            pt.parts(),
             vec![
                 TemplatePart::TimeFormat("time".to_string()),
                 TemplatePart::Table,
@@ -160,6 +169,39 @@ mod tests {
         assert_eq!(protobuf, back);
     }
 
+    #[test]
+    fn test_partition_template_string() {
+        use management::partition_template::part::Part;
+
+        let protobuf = management::PartitionTemplate {
+            parts: vec![management::partition_template::Part {
+                part: Some(Part::Template("%Y-%m-%d/region=<region>".to_string())),
+            }],
+        };
+
+        let pt: PartitionTemplate = protobuf.clone().try_into().unwrap();
+        let back: management::PartitionTemplate = pt.clone().into();
+
+        assert_eq!(
+            pt.parts(),
+            vec![TemplatePart::Template(
+                "%Y-%m-%d/region=<region>".to_string()
+            )]
+        );
+        assert_eq!(protobuf, back);
+
+        let protobuf = management::PartitionTemplate {
+            parts: vec![management::partition_template::Part {
+                part: Some(Part::Template("%Y-<region".to_string())),
+            }],
+        };
+
+        let res: Result<PartitionTemplate, _> = protobuf.try_into();
+        let err = res.expect_err("expected failure");
+
+        assert_eq!(&err.field, "parts.0.part.template");
+    }
+
     #[test]
     fn test_partition_template_empty() {
         use management::partition_template::part::{ColumnFormat, Part};
@@ -0,0 +1,173 @@
+use std::convert::{TryFrom, TryInto};
+use std::time::Duration;
+
+use data_types::database_rules::{RollupAggregate, RollupRule};
+
+use crate::google::{FieldViolation, FieldViolationExt, FromFieldString};
+use crate::influxdata::iox::management::v1 as management;
+
+impl From<RollupRule> for management::RollupRule {
+    fn from(rule: RollupRule) -> Self {
+        Self {
+            source_table: rule.source_table,
+            target_table: rule.target_table,
+            window: Some(rule.window.into()),
+            aggregate: management::RollupAggregate::from(rule.aggregate) as _,
+            delay: Some(rule.delay.into()),
+        }
+    }
+}
+
+impl TryFrom<management::RollupRule> for RollupRule {
+    type Error = FieldViolation;
+
+    fn try_from(proto: management::RollupRule) -> Result<Self, Self::Error> {
+        let aggregate = proto.aggregate().into();
+
+        let source_table = proto.source_table.required("source_table")?;
+        let target_table = proto.target_table.required("target_table")?;
+        if source_table == target_table {
+            return Err(FieldViolation {
+                field: "target_table".to_string(),
+                description: "Must differ from the source table".to_string(),
+            });
+        }
+
+        let window: Duration = match proto.window {
+            Some(d) => d.try_into().field("window")?,
+            None => return Err(FieldViolation::required("window")),
+        };
+        if window.as_nanos() == 0 {
+            return Err(FieldViolation {
+                field: "window".to_string(),
+                description: "Must not be zero".to_string(),
+            });
+        }
+
+        let delay = match proto.delay {
+            Some(d) => d.try_into().field("delay")?,
+            None => Duration::from_secs(0),
+        };
+
+        Ok(Self {
+            source_table,
+            target_table,
+            window,
+            aggregate,
+            delay,
+        })
+    }
+}
+
+impl From<RollupAggregate> for management::RollupAggregate {
+    fn from(aggregate: RollupAggregate) -> Self {
+        match aggregate {
+            RollupAggregate::Mean => Self::Mean,
+            RollupAggregate::Min => Self::Min,
+            RollupAggregate::Max => Self::Max,
+            RollupAggregate::Sum => Self::Sum,
+            RollupAggregate::Count => Self::Count,
+        }
+    }
+}
+
+impl From<management::RollupAggregate> for RollupAggregate {
+    fn from(proto: management::RollupAggregate) -> Self {
+        match proto {
+            management::RollupAggregate::Unspecified => Self::default(),
+            management::RollupAggregate::Mean => Self::Mean,
+            management::RollupAggregate::Min => Self::Min,
+            management::RollupAggregate::Max => Self::Max,
+            management::RollupAggregate::Sum => Self::Sum,
+            management::RollupAggregate::Count => Self::Count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use data_types::database_rules::DatabaseRules;
+
+    fn rule() -> management::RollupRule {
+        management::RollupRule {
+            source_table: "cpu".to_string(),
+            target_table: "cpu_5m".to_string(),
+            window: Some(Duration::from_secs(300).into()),
+            aggregate: management::RollupAggregate::Max as _,
+            delay: None,
+        }
+    }
+
+    #[test]
+    fn test_rollup_rules() {
+        let protobuf = management::DatabaseRules {
+            name: "database".to_string(),
+            rollup_rules: vec![rule()],
+            ..Default::default()
+        };
+
+        let rules: DatabaseRules = protobuf.try_into().unwrap();
+        assert_eq!(
+            rules.rollup_rules,
+            vec![RollupRule {
+                source_table: "cpu".to_string(),
+                target_table: "cpu_5m".to_string(),
+                window: Duration::from_secs(300),
+                aggregate: RollupAggregate::Max,
+                delay: Duration::from_secs(0),
+            }]
+        );
+
+        let back: management::DatabaseRules = rules.into();
+        assert_eq!(
+            back.rollup_rules,
+            vec![management::RollupRule {
+                delay: Some(Duration::from_secs(0).into()),
+                ..rule()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_rollup_rule_default_aggregate() {
+        let protobuf = management::RollupRule {
+            aggregate: 0,
+            ..rule()
+        };
+        let rule: RollupRule = protobuf.try_into().unwrap();
+        assert_eq!(rule.aggregate, RollupAggregate::Mean);
+    }
+
+    #[test]
+    fn test_rollup_rule_invalid() {
+        let err = RollupRule::try_from(management::RollupRule {
+            target_table: "cpu".to_string(),
+            ..rule()
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "target_table");
+
+        let err = RollupRule::try_from(management::RollupRule {
+            source_table: "".to_string(),
+            ..rule()
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "source_table");
+
+        let err = RollupRule::try_from(management::RollupRule {
+            window: None,
+            ..rule()
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "window");
+
+        let err = RollupRule::try_from(management::RollupRule {
+            window: Some(Duration::from_secs(0).into()),
+            ..rule()
+        })
+        .unwrap_err();
+        assert_eq!(err.field, "window");
+        assert_eq!(err.description, "Must not be zero");
+    }
+}
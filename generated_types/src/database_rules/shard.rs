@@ -165,7 +165,7 @@ impl From<Matcher> for management::Matcher {
                 .table_name_regex
                 .map(|r| r.to_string())
                 .unwrap_or_default(),
-            predicate: matcher.predicate.unwrap_or_default(),
+            predicate: matcher.predicate.map(|p| p.to_string()).unwrap_or_default(),
         }
     }
 }
@@ -181,10 +181,16 @@ impl TryFrom<management::Matcher> for Matcher {
                 description: e.to_string(),
             })?),
         };
-        let predicate = match proto.predicate {
-            p if p.is_empty() => None,
-            p => Some(p),
-        };
+        let predicate =
+            match &proto.predicate as &str {
+                "" => None,
+                p => Some(p.parse().map_err(|e: data_types::database_rules::Error| {
+                    FieldViolation {
+                        field: "predicate".to_string(),
+                        description: e.to_string(),
+                    }
+                })?),
+            };
 
         Ok(Self {
             table_name_regex,
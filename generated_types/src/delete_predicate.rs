@@ -0,0 +1,104 @@
+use crate::google::FieldViolation;
+use crate::influxdata::iox::management::v1 as management;
+use data_types::{delete_predicate::DeletePredicate, timestamp::TimestampRange};
+use std::convert::TryFrom;
+
+/// Conversion code to management API delete predicate structure
+impl From<DeletePredicate> for management::DeletePredicate {
+    fn from(predicate: DeletePredicate) -> Self {
+        let DeletePredicate {
+            table_name,
+            range,
+            tags,
+        } = predicate;
+
+        Self {
+            table_name,
+            start_time: range.start,
+            stop_time: range.end,
+            tags: tags.into_iter().collect(),
+        }
+    }
+}
+
+/// Conversion code from management API delete predicate structure
+impl TryFrom<management::DeletePredicate> for DeletePredicate {
+    type Error = FieldViolation;
+
+    fn try_from(proto: management::DeletePredicate) -> Result<Self, Self::Error> {
+        let management::DeletePredicate {
+            table_name,
+            start_time,
+            stop_time,
+            tags,
+        } = proto;
+
+        if table_name.is_empty() {
+            return Err(FieldViolation::required("table_name"));
+        }
+
+        if stop_time <= start_time {
+            return Err(FieldViolation {
+                field: "stop_time".to_string(),
+                description: "Time range must not be empty".to_string(),
+            });
+        }
+
+        Ok(Self {
+            table_name,
+            range: TimestampRange::new(start_time, stop_time),
+            tags: tags.into_iter().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_proto_to_predicate() {
+        let proto = management::DeletePredicate {
+            table_name: "cpu".to_string(),
+            start_time: 10,
+            stop_time: 20,
+            tags: vec![("host".to_string(), "a".to_string())]
+                .into_iter()
+                .collect(),
+        };
+
+        let predicate = DeletePredicate::try_from(proto.clone()).expect("conversion successful");
+        let expected = DeletePredicate {
+            table_name: "cpu".to_string(),
+            range: TimestampRange::new(10, 20),
+            tags: vec![("host".to_string(), "a".to_string())]
+                .into_iter()
+                .collect(),
+        };
+        assert_eq!(predicate, expected);
+
+        let back: management::DeletePredicate = predicate.into();
+        assert_eq!(back, proto);
+    }
+
+    #[test]
+    fn invalid_proto_to_predicate() {
+        let proto = management::DeletePredicate {
+            table_name: "".to_string(),
+            start_time: 10,
+            stop_time: 20,
+            tags: Default::default(),
+        };
+        let err = DeletePredicate::try_from(proto).unwrap_err();
+        assert_eq!(err.field, "table_name");
+
+        let proto = management::DeletePredicate {
+            table_name: "cpu".to_string(),
+            start_time: 20,
+            stop_time: 20,
+            tags: Default::default(),
+        };
+        let err = DeletePredicate::try_from(proto).unwrap_err();
+        assert_eq!(err.field, "stop_time");
+    }
+}
@@ -30,6 +30,15 @@ impl From<Job> for management::operation_metadata::Job {
                 table_name,
                 chunk_id,
             }),
+            Job::CompactChunks {
+                db_name,
+                partition_key,
+                table_name,
+            } => Self::CompactChunks(management::CompactChunks {
+                db_name,
+                partition_key,
+                table_name,
+            }),
         }
     }
 }
@@ -61,6 +70,15 @@ impl From<management::operation_metadata::Job> for Job {
                 table_name,
                 chunk_id,
             },
+            Job::CompactChunks(management::CompactChunks {
+                db_name,
+                partition_key,
+                table_name,
+            }) => Self::CompactChunks {
+                db_name,
+                partition_key,
+                table_name,
+            },
         }
     }
 }
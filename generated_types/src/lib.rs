@@ -118,8 +118,11 @@ pub use influxdata::platform::storage::*;
 
 pub mod chunk;
 pub mod database_rules;
+pub mod delete_predicate;
 pub mod google;
 pub mod job;
+pub mod partition;
+pub mod write;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,103 @@
+use crate::influxdata::iox::management::v1 as management;
+use data_types::partition_metadata::{PartitionStorageSummary, TableStorageSummary};
+
+/// Conversion code to management API partition structure
+impl From<PartitionStorageSummary> for management::Partition {
+    fn from(summary: PartitionStorageSummary) -> Self {
+        let PartitionStorageSummary { key, tables } = summary;
+
+        Self {
+            key,
+            tables: tables.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<TableStorageSummary> for management::PartitionTable {
+    fn from(summary: TableStorageSummary) -> Self {
+        let TableStorageSummary {
+            name,
+            mutable_buffer_chunks,
+            read_buffer_chunks,
+            object_store_chunks,
+            row_count,
+            memory_bytes,
+            object_store_bytes,
+        } = summary;
+
+        Self {
+            table_name: name,
+            mutable_buffer_chunks: mutable_buffer_chunks as u64,
+            read_buffer_chunks: read_buffer_chunks as u64,
+            object_store_chunks: object_store_chunks as u64,
+            row_count: row_count as u64,
+            memory_bytes: memory_bytes as u64,
+            object_store_bytes: object_store_bytes as u64,
+        }
+    }
+}
+
+/// Conversion code from management API partition structure
+impl From<management::Partition> for PartitionStorageSummary {
+    fn from(proto: management::Partition) -> Self {
+        let management::Partition { key, tables } = proto;
+
+        Self {
+            key,
+            tables: tables.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<management::PartitionTable> for TableStorageSummary {
+    fn from(proto: management::PartitionTable) -> Self {
+        let management::PartitionTable {
+            table_name,
+            mutable_buffer_chunks,
+            read_buffer_chunks,
+            object_store_chunks,
+            row_count,
+            memory_bytes,
+            object_store_bytes,
+        } = proto;
+
+        Self {
+            name: table_name,
+            mutable_buffer_chunks: mutable_buffer_chunks as usize,
+            read_buffer_chunks: read_buffer_chunks as usize,
+            object_store_chunks: object_store_chunks as usize,
+            row_count: row_count as usize,
+            memory_bytes: memory_bytes as usize,
+            object_store_bytes: object_store_bytes as usize,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn valid_proto_round_trip() {
+        let summary = PartitionStorageSummary {
+            key: "foo".to_string(),
+            tables: vec![TableStorageSummary {
+                name: "bar".to_string(),
+                mutable_buffer_chunks: 1,
+                read_buffer_chunks: 2,
+                object_store_chunks: 3,
+                row_count: 4,
+                memory_bytes: 5,
+                object_store_bytes: 6,
+            }],
+        };
+
+        let proto = management::Partition::from(summary.clone());
+        assert_eq!(proto.key, "foo");
+        assert_eq!(proto.tables.len(), 1);
+        assert_eq!(proto.tables[0].table_name, "bar");
+        assert_eq!(proto.tables[0].object_store_bytes, 6);
+
+        assert_eq!(PartitionStorageSummary::from(proto), summary);
+    }
+}
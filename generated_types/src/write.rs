@@ -0,0 +1,44 @@
+use crate::influxdata::iox::write::v1 as write;
+use data_types::timestamp::Precision;
+
+impl From<Precision> for write::Precision {
+    fn from(precision: Precision) -> Self {
+        match precision {
+            Precision::Nanoseconds => Self::Nanoseconds,
+            Precision::Microseconds => Self::Microseconds,
+            Precision::Milliseconds => Self::Milliseconds,
+            Precision::Seconds => Self::Seconds,
+        }
+    }
+}
+
+impl From<write::Precision> for Precision {
+    fn from(proto: write::Precision) -> Self {
+        match proto {
+            write::Precision::Unspecified => Self::default(),
+            write::Precision::Nanoseconds => Self::Nanoseconds,
+            write::Precision::Microseconds => Self::Microseconds,
+            write::Precision::Milliseconds => Self::Milliseconds,
+            write::Precision::Seconds => Self::Seconds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precision() {
+        let protobuf = write::WriteRequest {
+            precision: write::Precision::Seconds as _,
+            ..Default::default()
+        };
+        let precision: Precision = protobuf.precision().into();
+        assert_eq!(precision, Precision::Seconds);
+        assert_eq!(write::Precision::from(precision), write::Precision::Seconds);
+
+        let precision: Precision = write::WriteRequest::default().precision().into();
+        assert_eq!(precision, Precision::Nanoseconds);
+    }
+}
@@ -0,0 +1,370 @@
+//! Traits describing the surface of the gRPC clients.
+//!
+//! The concrete clients in this crate implement these traits, as does the
+//! in-memory [`MockClient`](crate::mock::MockClient) (behind the `mock`
+//! feature), so that application code can be written against the traits and
+//! unit tested without a running IOx server.
+use std::num::NonZeroU32;
+
+use async_trait::async_trait;
+
+use ::generated_types::google::longrunning::Operation;
+
+use crate::management::{self, generated_types::*, *};
+use crate::write::{self, WriteError};
+
+/// The management API surface
+#[async_trait]
+pub trait ManagementApi: Send {
+    /// Set the server's ID.
+    async fn update_server_id(&mut self, id: u32) -> Result<(), UpdateServerIdError>;
+
+    /// Get the server's ID.
+    async fn get_server_id(&mut self) -> Result<NonZeroU32, GetServerIdError>;
+
+    /// Check if databases are loaded and ready for read and write.
+    async fn get_server_status(&mut self) -> Result<ServerStatus, GetServerStatusError>;
+
+    /// Set serving readiness.
+    async fn set_serving_readiness(&mut self, ready: bool) -> Result<(), SetServingReadinessError>;
+
+    /// Creates a new IOx database.
+    async fn create_database(&mut self, rules: DatabaseRules) -> Result<(), CreateDatabaseError>;
+
+    /// Updates the configuration for a database.
+    async fn update_database(
+        &mut self,
+        rules: DatabaseRules,
+    ) -> Result<DatabaseRules, UpdateDatabaseError>;
+
+    /// Deletes a database, optionally preserving its data in object storage.
+    async fn delete_database(
+        &mut self,
+        db_name: &str,
+        preserve_data: bool,
+    ) -> Result<(), DeleteDatabaseError>;
+
+    /// Backs up a database to the object store path prefix `destination`.
+    async fn backup_database(
+        &mut self,
+        db_name: &str,
+        destination: &str,
+    ) -> Result<u64, BackupDatabaseError>;
+
+    /// Restores a database from the backup at `source`.
+    async fn restore_database(
+        &mut self,
+        db_name: &str,
+        source: &str,
+    ) -> Result<u64, RestoreDatabaseError>;
+
+    /// Restores a deleted database whose data has not been purged yet.
+    async fn restore_deleted_database(
+        &mut self,
+        db_name: &str,
+    ) -> Result<(), RestoreDeletedDatabaseError>;
+
+    /// Creates database `target` with the rules, and optionally the
+    /// persisted data, of database `source`.
+    async fn clone_database(
+        &mut self,
+        source: &str,
+        target: &str,
+        with_data: bool,
+    ) -> Result<u64, CloneDatabaseError>;
+
+    /// List databases.
+    async fn list_databases(&mut self) -> Result<Vec<String>, ListDatabaseError>;
+
+    /// Get database configuration
+    async fn get_database(&mut self, name: &str) -> Result<DatabaseRules, GetDatabaseError>;
+
+    /// List chunks in a database.
+    async fn list_chunks(&mut self, db_name: &str) -> Result<Vec<Chunk>, ListChunksError>;
+
+    /// List remotes.
+    async fn list_remotes(&mut self) -> Result<Vec<Remote>, ListRemotesError>;
+
+    /// Update remote
+    async fn update_remote(
+        &mut self,
+        id: u32,
+        connection_string: &str,
+    ) -> Result<(), UpdateRemoteError>;
+
+    /// Delete remote
+    async fn delete_remote(&mut self, id: u32) -> Result<(), UpdateRemoteError>;
+
+    /// List all partitions of the database
+    async fn list_partitions(
+        &mut self,
+        db_name: &str,
+    ) -> Result<Vec<Partition>, ListPartitionsError>;
+
+    /// Get details about a specific partition
+    async fn get_partition(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+    ) -> Result<Partition, GetPartitionError>;
+
+    /// List chunks in a partition
+    async fn list_partition_chunks(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+    ) -> Result<Vec<Chunk>, ListPartitionChunksError>;
+
+    /// Create a new chunk in a partittion
+    async fn new_partition_chunk(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+        table_name: &str,
+    ) -> Result<(), NewPartitionChunkError>;
+
+    /// Closes the specified chunk in the specified partition and
+    /// begins it moving to the read buffer.
+    async fn close_partition_chunk(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+        table_name: &str,
+        chunk_id: u32,
+    ) -> Result<Operation, ClosePartitionChunkError>;
+
+    /// Writes the specified chunk, which must be in the read buffer, to
+    /// object storage.
+    async fn persist_partition_chunk(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+        table_name: &str,
+        chunk_id: u32,
+    ) -> Result<Operation, PersistPartitionChunkError>;
+
+    /// Drops the specified partition and all of its chunks.
+    async fn drop_partition(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+        delete_persisted: bool,
+    ) -> Result<(), DropPartitionError>;
+}
+
+/// The write API surface
+#[async_trait]
+pub trait WriteApi: Send {
+    /// Write the line protocol formatted data in `lp_data` to
+    /// database `db_name`. Returns the number of lines which were parsed
+    /// and written to the database
+    async fn write(&mut self, db_name: &str, lp_data: &str) -> Result<usize, WriteError>;
+
+    /// Write a flatbuffer encoded Entry to database `db_name`.
+    async fn write_entry(&mut self, db_name: &str, entry: &[u8]) -> Result<(), WriteError>;
+}
+
+/// The query API surface
+#[cfg(feature = "flight")]
+#[async_trait]
+pub trait FlightApi: Send {
+    /// Query the given database with the given SQL query and collect all
+    /// resulting `RecordBatch`es
+    async fn query(
+        &mut self,
+        database_name: &str,
+        sql_query: &str,
+    ) -> Result<Vec<arrow::record_batch::RecordBatch>, crate::flight::Error>;
+}
+
+#[async_trait]
+impl ManagementApi for management::Client {
+    async fn update_server_id(&mut self, id: u32) -> Result<(), UpdateServerIdError> {
+        self.update_server_id(id).await
+    }
+
+    async fn get_server_id(&mut self) -> Result<NonZeroU32, GetServerIdError> {
+        self.get_server_id().await
+    }
+
+    async fn get_server_status(&mut self) -> Result<ServerStatus, GetServerStatusError> {
+        self.get_server_status().await
+    }
+
+    async fn set_serving_readiness(&mut self, ready: bool) -> Result<(), SetServingReadinessError> {
+        self.set_serving_readiness(ready).await
+    }
+
+    async fn create_database(&mut self, rules: DatabaseRules) -> Result<(), CreateDatabaseError> {
+        self.create_database(rules).await
+    }
+
+    async fn update_database(
+        &mut self,
+        rules: DatabaseRules,
+    ) -> Result<DatabaseRules, UpdateDatabaseError> {
+        self.update_database(rules).await
+    }
+
+    async fn delete_database(
+        &mut self,
+        db_name: &str,
+        preserve_data: bool,
+    ) -> Result<(), DeleteDatabaseError> {
+        self.delete_database(db_name, preserve_data).await
+    }
+
+    async fn backup_database(
+        &mut self,
+        db_name: &str,
+        destination: &str,
+    ) -> Result<u64, BackupDatabaseError> {
+        self.backup_database(db_name, destination).await
+    }
+
+    async fn restore_database(
+        &mut self,
+        db_name: &str,
+        source: &str,
+    ) -> Result<u64, RestoreDatabaseError> {
+        self.restore_database(db_name, source).await
+    }
+
+    async fn restore_deleted_database(
+        &mut self,
+        db_name: &str,
+    ) -> Result<(), RestoreDeletedDatabaseError> {
+        self.restore_deleted_database(db_name).await
+    }
+
+    async fn clone_database(
+        &mut self,
+        source: &str,
+        target: &str,
+        with_data: bool,
+    ) -> Result<u64, CloneDatabaseError> {
+        self.clone_database(source, target, with_data).await
+    }
+
+    async fn list_databases(&mut self) -> Result<Vec<String>, ListDatabaseError> {
+        self.list_databases().await
+    }
+
+    async fn get_database(&mut self, name: &str) -> Result<DatabaseRules, GetDatabaseError> {
+        self.get_database(name).await
+    }
+
+    async fn list_chunks(&mut self, db_name: &str) -> Result<Vec<Chunk>, ListChunksError> {
+        self.list_chunks(db_name).await
+    }
+
+    async fn list_remotes(&mut self) -> Result<Vec<Remote>, ListRemotesError> {
+        self.list_remotes().await
+    }
+
+    async fn update_remote(
+        &mut self,
+        id: u32,
+        connection_string: &str,
+    ) -> Result<(), UpdateRemoteError> {
+        self.update_remote(id, connection_string).await
+    }
+
+    async fn delete_remote(&mut self, id: u32) -> Result<(), UpdateRemoteError> {
+        self.delete_remote(id).await
+    }
+
+    async fn list_partitions(
+        &mut self,
+        db_name: &str,
+    ) -> Result<Vec<Partition>, ListPartitionsError> {
+        self.list_partitions(db_name).await
+    }
+
+    async fn get_partition(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+    ) -> Result<Partition, GetPartitionError> {
+        self.get_partition(db_name, partition_key).await
+    }
+
+    async fn list_partition_chunks(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+    ) -> Result<Vec<Chunk>, ListPartitionChunksError> {
+        self.list_partition_chunks(db_name, partition_key).await
+    }
+
+    async fn new_partition_chunk(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+        table_name: &str,
+    ) -> Result<(), NewPartitionChunkError> {
+        self.new_partition_chunk(db_name, partition_key, table_name)
+            .await
+    }
+
+    async fn close_partition_chunk(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+        table_name: &str,
+        chunk_id: u32,
+    ) -> Result<Operation, ClosePartitionChunkError> {
+        self.close_partition_chunk(db_name, partition_key, table_name, chunk_id)
+            .await
+    }
+
+    async fn persist_partition_chunk(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+        table_name: &str,
+        chunk_id: u32,
+    ) -> Result<Operation, PersistPartitionChunkError> {
+        self.persist_partition_chunk(db_name, partition_key, table_name, chunk_id)
+            .await
+    }
+
+    async fn drop_partition(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+        delete_persisted: bool,
+    ) -> Result<(), DropPartitionError> {
+        self.drop_partition(db_name, partition_key, delete_persisted)
+            .await
+    }
+}
+
+#[async_trait]
+impl WriteApi for write::Client {
+    async fn write(&mut self, db_name: &str, lp_data: &str) -> Result<usize, WriteError> {
+        self.write(db_name, lp_data).await
+    }
+
+    async fn write_entry(&mut self, db_name: &str, entry: &[u8]) -> Result<(), WriteError> {
+        self.write_entry(db_name, entry).await
+    }
+}
+
+#[cfg(feature = "flight")]
+#[async_trait]
+impl FlightApi for crate::flight::Client {
+    async fn query(
+        &mut self,
+        database_name: &str,
+        sql_query: &str,
+    ) -> Result<Vec<arrow::record_batch::RecordBatch>, crate::flight::Error> {
+        let mut query_results = self.perform_query(database_name, sql_query).await?;
+
+        let mut batches = vec![];
+        while let Some(batch) = query_results.next().await? {
+            batches.push(batch);
+        }
+        Ok(batches)
+    }
+}
@@ -0,0 +1,92 @@
+//! Blocking (synchronous) wrappers around the async gRPC clients.
+//!
+//! Each [`Connection`] owns a single-threaded tokio runtime that is used to
+//! drive the underlying async client, so callers do not need to set up an
+//! async runtime themselves.
+//!
+//! ```no_run
+//! use influxdb_iox_client::{blocking::{Connection, management::Client}, connection::Builder};
+//!
+//! let connection = Connection::new(Builder::default(), "http://127.0.0.1:8082")
+//!     .expect("connection must succeed");
+//!
+//! let mut client = Client::new(connection);
+//! let databases = client.list_databases().expect("listing databases failed");
+//! ```
+use std::{convert::TryInto, future::Future, sync::Arc};
+
+use http::{uri::InvalidUri, Uri};
+use thiserror::Error;
+use tokio::runtime::Runtime;
+
+use crate::connection::Builder;
+
+/// Blocking client for health checking API
+pub mod health;
+
+/// Blocking client for management API
+pub mod management;
+
+/// Blocking client for write API
+pub mod write;
+
+/// Blocking client for long running operations API
+pub mod operations;
+
+#[cfg(feature = "flight")]
+/// Blocking client for query API (based on Arrow flight)
+pub mod flight;
+
+/// Errors returned when constructing a blocking [`Connection`]
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Error creating the runtime used to drive the client
+    #[error("Error creating runtime: {}", .0)]
+    RuntimeError(#[from] std::io::Error),
+
+    /// Error establishing the connection
+    #[error("{}", .0)]
+    ConnectionError(#[from] crate::connection::Error),
+}
+
+/// Result type for blocking connections
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A connection to an IOx server along with the runtime that drives it.
+///
+/// Cloning a `Connection` is cheap and shares both the underlying channel and
+/// the runtime.
+#[derive(Debug, Clone)]
+pub struct Connection {
+    runtime: Arc<Runtime>,
+    inner: crate::connection::Connection,
+}
+
+impl Connection {
+    /// Connect to `dst` using the settings from `builder`
+    pub fn new<D>(builder: Builder, dst: D) -> Result<Self>
+    where
+        D: TryInto<Uri, Error = InvalidUri>,
+    {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+
+        let inner = runtime.block_on(builder.build(dst))?;
+
+        Ok(Self {
+            runtime: Arc::new(runtime),
+            inner,
+        })
+    }
+
+    /// Returns the underlying async connection
+    pub fn inner(&self) -> &crate::connection::Connection {
+        &self.inner
+    }
+
+    /// Run `future` to completion on this connection's runtime
+    pub(crate) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
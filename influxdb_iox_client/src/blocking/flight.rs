@@ -0,0 +1,70 @@
+use arrow::record_batch::RecordBatch;
+
+use super::Connection;
+use crate::flight::Error;
+
+/// A blocking IOx Arrow Flight gRPC API client.
+///
+/// See [`crate::flight::Client`] for details.
+#[derive(Debug)]
+pub struct Client {
+    connection: Connection,
+    inner: crate::flight::Client,
+}
+
+impl Client {
+    /// Creates a new client with the provided connection
+    pub fn new(connection: Connection) -> Self {
+        let inner = crate::flight::Client::new(connection.inner().clone());
+        Self { connection, inner }
+    }
+
+    /// Query the given database with the given SQL query, and return a
+    /// [`PerformQuery`] instance that yields Arrow `RecordBatch` results.
+    pub fn perform_query(
+        &mut self,
+        database_name: impl Into<String>,
+        sql_query: impl Into<String>,
+    ) -> Result<PerformQuery, Error> {
+        let inner = self
+            .connection
+            .block_on(self.inner.perform_query(database_name, sql_query))?;
+
+        Ok(PerformQuery {
+            connection: self.connection.clone(),
+            inner,
+        })
+    }
+
+    /// Query the given database with the given SQL query and collect all
+    /// resulting `RecordBatch`es.
+    pub fn query_all(
+        &mut self,
+        database_name: impl Into<String>,
+        sql_query: impl Into<String>,
+    ) -> Result<Vec<RecordBatch>, Error> {
+        let mut query_results = self.perform_query(database_name, sql_query)?;
+
+        let mut batches = vec![];
+        while let Some(batch) = query_results.next()? {
+            batches.push(batch);
+        }
+        Ok(batches)
+    }
+}
+
+/// Blocking counterpart of [`crate::flight::PerformQuery`]
+#[derive(Debug)]
+pub struct PerformQuery {
+    connection: Connection,
+    inner: crate::flight::PerformQuery,
+}
+
+impl PerformQuery {
+    /// Returns the next `RecordBatch` available for this query, or `None` if
+    /// there are no further results available.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<RecordBatch>, Error> {
+        self.connection.block_on(self.inner.next())
+    }
+}
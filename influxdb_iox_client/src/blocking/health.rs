@@ -0,0 +1,29 @@
+use super::Connection;
+use crate::health::Result;
+
+/// A blocking client for the gRPC health checking API.
+///
+/// See [`crate::health::Client`] for details.
+#[derive(Debug)]
+pub struct Client {
+    connection: Connection,
+    inner: crate::health::Client,
+}
+
+impl Client {
+    /// Creates a new client with the provided connection
+    pub fn new(connection: Connection) -> Self {
+        let inner = crate::health::Client::new(connection.inner().clone());
+        Self { connection, inner }
+    }
+
+    /// Returns `Ok()` if the corresponding service is serving
+    pub fn check(&mut self, service: impl Into<String>) -> Result<()> {
+        self.connection.block_on(self.inner.check(service))
+    }
+
+    /// Returns `Ok()` if the storage service is serving
+    pub fn check_storage(&mut self) -> Result<()> {
+        self.connection.block_on(self.inner.check_storage())
+    }
+}
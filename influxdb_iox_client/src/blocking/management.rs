@@ -0,0 +1,250 @@
+use std::num::NonZeroU32;
+
+use ::generated_types::google::longrunning::Operation;
+
+use super::Connection;
+use crate::management::{generated_types::*, *};
+
+/// A blocking IOx Management API client.
+///
+/// See [`crate::management::Client`] for details.
+#[derive(Debug, Clone)]
+pub struct Client {
+    connection: Connection,
+    inner: crate::management::Client,
+}
+
+impl Client {
+    /// Creates a new client with the provided connection
+    pub fn new(connection: Connection) -> Self {
+        let inner = crate::management::Client::new(connection.inner().clone());
+        Self { connection, inner }
+    }
+
+    /// Set the server's ID.
+    pub fn update_server_id(&mut self, id: u32) -> Result<(), UpdateServerIdError> {
+        self.connection.block_on(self.inner.update_server_id(id))
+    }
+
+    /// Get the server's ID.
+    pub fn get_server_id(&mut self) -> Result<NonZeroU32, GetServerIdError> {
+        self.connection.block_on(self.inner.get_server_id())
+    }
+
+    /// Check if databases are loaded and ready for read and write.
+    pub fn get_server_status(&mut self) -> Result<ServerStatus, GetServerStatusError> {
+        self.connection.block_on(self.inner.get_server_status())
+    }
+
+    /// Set serving readiness.
+    pub fn set_serving_readiness(&mut self, ready: bool) -> Result<(), SetServingReadinessError> {
+        self.connection
+            .block_on(self.inner.set_serving_readiness(ready))
+    }
+
+    /// Creates a new IOx database.
+    pub fn create_database(&mut self, rules: DatabaseRules) -> Result<(), CreateDatabaseError> {
+        self.connection.block_on(self.inner.create_database(rules))
+    }
+
+    /// Updates the configuration for a database.
+    pub fn update_database(
+        &mut self,
+        rules: DatabaseRules,
+    ) -> Result<DatabaseRules, UpdateDatabaseError> {
+        self.connection.block_on(self.inner.update_database(rules))
+    }
+
+    /// Deletes a database, optionally preserving its data in object storage.
+    pub fn delete_database(
+        &mut self,
+        db_name: impl Into<String>,
+        preserve_data: bool,
+    ) -> Result<(), DeleteDatabaseError> {
+        self.connection
+            .block_on(self.inner.delete_database(db_name, preserve_data))
+    }
+
+    /// Backs up a database to the object store path prefix `destination`.
+    pub fn backup_database(
+        &mut self,
+        db_name: impl Into<String>,
+        destination: impl Into<String>,
+    ) -> Result<u64, BackupDatabaseError> {
+        self.connection
+            .block_on(self.inner.backup_database(db_name, destination))
+    }
+
+    /// Restores a database from the backup at `source`.
+    pub fn restore_database(
+        &mut self,
+        db_name: impl Into<String>,
+        source: impl Into<String>,
+    ) -> Result<u64, RestoreDatabaseError> {
+        self.connection
+            .block_on(self.inner.restore_database(db_name, source))
+    }
+
+    /// Restores a deleted database whose data has not been purged yet.
+    pub fn restore_deleted_database(
+        &mut self,
+        db_name: impl Into<String>,
+    ) -> Result<(), RestoreDeletedDatabaseError> {
+        self.connection
+            .block_on(self.inner.restore_deleted_database(db_name))
+    }
+
+    /// Creates database `target` with the rules, and optionally the
+    /// persisted data, of database `source`.
+    pub fn clone_database(
+        &mut self,
+        source: impl Into<String>,
+        target: impl Into<String>,
+        with_data: bool,
+    ) -> Result<u64, CloneDatabaseError> {
+        self.connection
+            .block_on(self.inner.clone_database(source, target, with_data))
+    }
+
+    /// List databases.
+    pub fn list_databases(&mut self) -> Result<Vec<String>, ListDatabaseError> {
+        self.connection.block_on(self.inner.list_databases())
+    }
+
+    /// Get database configuration
+    pub fn get_database(
+        &mut self,
+        name: impl Into<String>,
+    ) -> Result<DatabaseRules, GetDatabaseError> {
+        self.connection.block_on(self.inner.get_database(name))
+    }
+
+    /// List chunks in a database.
+    pub fn list_chunks(
+        &mut self,
+        db_name: impl Into<String>,
+    ) -> Result<Vec<Chunk>, ListChunksError> {
+        self.connection.block_on(self.inner.list_chunks(db_name))
+    }
+
+    /// List remotes.
+    pub fn list_remotes(&mut self) -> Result<Vec<Remote>, ListRemotesError> {
+        self.connection.block_on(self.inner.list_remotes())
+    }
+
+    /// Update remote
+    pub fn update_remote(
+        &mut self,
+        id: u32,
+        connection_string: impl Into<String>,
+    ) -> Result<(), UpdateRemoteError> {
+        self.connection
+            .block_on(self.inner.update_remote(id, connection_string))
+    }
+
+    /// Delete remote
+    pub fn delete_remote(&mut self, id: u32) -> Result<(), UpdateRemoteError> {
+        self.connection.block_on(self.inner.delete_remote(id))
+    }
+
+    /// List all partitions of the database
+    pub fn list_partitions(
+        &mut self,
+        db_name: impl Into<String>,
+    ) -> Result<Vec<Partition>, ListPartitionsError> {
+        self.connection
+            .block_on(self.inner.list_partitions(db_name))
+    }
+
+    /// Get details about a specific partition
+    pub fn get_partition(
+        &mut self,
+        db_name: impl Into<String>,
+        partition_key: impl Into<String>,
+    ) -> Result<Partition, GetPartitionError> {
+        self.connection
+            .block_on(self.inner.get_partition(db_name, partition_key))
+    }
+
+    /// List chunks in a partition
+    pub fn list_partition_chunks(
+        &mut self,
+        db_name: impl Into<String>,
+        partition_key: impl Into<String>,
+    ) -> Result<Vec<Chunk>, ListPartitionChunksError> {
+        self.connection
+            .block_on(self.inner.list_partition_chunks(db_name, partition_key))
+    }
+
+    /// Create a new chunk in a partittion
+    pub fn new_partition_chunk(
+        &mut self,
+        db_name: impl Into<String>,
+        partition_key: impl Into<String>,
+        table_name: impl Into<String>,
+    ) -> Result<(), NewPartitionChunkError> {
+        self.connection.block_on(
+            self.inner
+                .new_partition_chunk(db_name, partition_key, table_name),
+        )
+    }
+
+    /// Creates a dummy job that for each value of the nanos field
+    /// spawns a task that sleeps for that number of nanoseconds before
+    /// returning
+    pub fn create_dummy_job(&mut self, nanos: Vec<u64>) -> Result<Operation, CreateDummyJobError> {
+        self.connection.block_on(self.inner.create_dummy_job(nanos))
+    }
+
+    /// Closes the specified chunk in the specified partition and
+    /// begins it moving to the read buffer.
+    ///
+    /// Returns the job tracking the data's movement
+    pub fn close_partition_chunk(
+        &mut self,
+        db_name: impl Into<String>,
+        partition_key: impl Into<String>,
+        table_name: impl Into<String>,
+        chunk_id: u32,
+    ) -> Result<Operation, ClosePartitionChunkError> {
+        self.connection.block_on(self.inner.close_partition_chunk(
+            db_name,
+            partition_key,
+            table_name,
+            chunk_id,
+        ))
+    }
+
+    /// Writes the specified chunk, which must be in the read buffer, to
+    /// object storage.
+    ///
+    /// Returns the job tracking the write
+    pub fn persist_partition_chunk(
+        &mut self,
+        db_name: impl Into<String>,
+        partition_key: impl Into<String>,
+        table_name: impl Into<String>,
+        chunk_id: u32,
+    ) -> Result<Operation, PersistPartitionChunkError> {
+        self.connection.block_on(self.inner.persist_partition_chunk(
+            db_name,
+            partition_key,
+            table_name,
+            chunk_id,
+        ))
+    }
+
+    /// Drops the specified partition and all of its chunks.
+    pub fn drop_partition(
+        &mut self,
+        db_name: impl Into<String>,
+        partition_key: impl Into<String>,
+        delete_persisted: bool,
+    ) -> Result<(), DropPartitionError> {
+        self.connection.block_on(self.inner.drop_partition(
+            db_name,
+            partition_key,
+            delete_persisted,
+        ))
+    }
+}
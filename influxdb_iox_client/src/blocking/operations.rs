@@ -0,0 +1,50 @@
+use super::Connection;
+use crate::operations::{generated_types::Operation, ClientOperation, Result};
+
+/// A blocking IOx Long Running Operations API client.
+///
+/// See [`crate::operations::Client`] for details.
+#[derive(Debug, Clone)]
+pub struct Client {
+    connection: Connection,
+    inner: crate::operations::Client,
+}
+
+impl Client {
+    /// Creates a new client with the provided connection
+    pub fn new(connection: Connection) -> Self {
+        let inner = crate::operations::Client::new(connection.inner().clone());
+        Self { connection, inner }
+    }
+
+    /// Get information of all client operation
+    pub fn list_operations(&mut self) -> Result<Vec<ClientOperation>> {
+        self.connection.block_on(self.inner.list_operations())
+    }
+
+    /// Get information about a specific operation
+    pub fn get_operation(&mut self, id: usize) -> Result<Operation> {
+        self.connection.block_on(self.inner.get_operation(id))
+    }
+
+    /// Cancel a given operation
+    pub fn cancel_operation(&mut self, id: usize) -> Result<()> {
+        self.connection.block_on(self.inner.cancel_operation(id))
+    }
+
+    /// Waits until an operation completes, or the timeout expires, and
+    /// returns the latest operation metadata
+    pub fn wait_operation(
+        &mut self,
+        id: usize,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<Operation> {
+        self.connection
+            .block_on(self.inner.wait_operation(id, timeout))
+    }
+
+    /// Return the Client Operation
+    pub fn client_operation(&mut self, id: usize) -> Result<ClientOperation> {
+        self.connection.block_on(self.inner.client_operation(id))
+    }
+}
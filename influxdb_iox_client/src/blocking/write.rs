@@ -0,0 +1,64 @@
+use super::Connection;
+use crate::write::{generated_types::Precision, WriteError};
+
+/// A blocking IOx Write API client.
+///
+/// See [`crate::write::Client`] for details.
+#[derive(Debug, Clone)]
+pub struct Client {
+    connection: Connection,
+    inner: crate::write::Client,
+}
+
+impl Client {
+    /// Creates a new client with the provided connection
+    pub fn new(connection: Connection) -> Self {
+        let inner = crate::write::Client::new(connection.inner().clone());
+        Self { connection, inner }
+    }
+
+    /// Write the line protocol formatted data in `lp_data` to
+    /// database `name`. Returns the number of lines which were parsed
+    /// and written to the database
+    pub fn write(
+        &mut self,
+        db_name: impl Into<String>,
+        lp_data: impl Into<String>,
+    ) -> Result<usize, WriteError> {
+        self.connection.block_on(self.inner.write(db_name, lp_data))
+    }
+
+    /// Like [`write`](Self::write), but the timestamps in `lp_data` are in
+    /// `precision` rather than nanoseconds
+    pub fn write_with_precision(
+        &mut self,
+        db_name: impl Into<String>,
+        lp_data: impl Into<String>,
+        precision: Precision,
+    ) -> Result<usize, WriteError> {
+        self.connection
+            .block_on(self.inner.write_with_precision(db_name, lp_data, precision))
+    }
+
+    /// Write the line protocol formatted data in `lp_data` to
+    /// database `name` only if all of the lines can be written. Returns the
+    /// number of lines written
+    pub fn write_all_or_nothing(
+        &mut self,
+        db_name: impl Into<String>,
+        lp_data: impl Into<String>,
+    ) -> Result<usize, WriteError> {
+        self.connection
+            .block_on(self.inner.write_all_or_nothing(db_name, lp_data))
+    }
+
+    /// Write an Entry to database `name`.
+    pub fn write_entry(
+        &mut self,
+        db_name: impl Into<String>,
+        entry: impl Into<Vec<u8>>,
+    ) -> Result<(), WriteError> {
+        self.connection
+            .block_on(self.inner.write_entry(db_name, entry))
+    }
+}
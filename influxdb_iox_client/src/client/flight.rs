@@ -1,4 +1,4 @@
-use std::{convert::TryFrom, sync::Arc};
+use std::{collections::BTreeMap, convert::TryFrom, sync::Arc};
 
 use futures_util::stream::StreamExt;
 use serde::Serialize;
@@ -97,7 +97,113 @@ impl Client {
         database_name: impl Into<String>,
         sql_query: impl Into<String>,
     ) -> Result<PerformQuery, Error> {
-        PerformQuery::new(self, database_name.into(), sql_query.into()).await
+        PerformQuery::new(self, database_name.into(), sql_query.into(), None).await
+    }
+
+    /// Query the given database with the given SQL query, after binding
+    /// `params` to the parameters of the query, and return a
+    /// [`PerformQuery`] instance that streams Arrow `RecordBatch` results.
+    ///
+    /// ```rust,no_run
+    /// # async fn example(client: &mut influxdb_iox_client::flight::Client) {
+    /// use influxdb_iox_client::flight::QueryParams;
+    ///
+    /// let query_results = client
+    ///     .perform_query_with_params(
+    ///         "my_database",
+    ///         "select * from cpu_load where host = $1 and usage > $2",
+    ///         QueryParams::positional(vec!["server01".into(), 0.5.into()]),
+    ///     )
+    ///     .await
+    ///     .expect("query request should work");
+    /// # }
+    /// ```
+    pub async fn perform_query_with_params(
+        &mut self,
+        database_name: impl Into<String>,
+        sql_query: impl Into<String>,
+        params: QueryParams,
+    ) -> Result<PerformQuery, Error> {
+        PerformQuery::new(self, database_name.into(), sql_query.into(), Some(params)).await
+    }
+}
+
+/// A value bound to a parameter of a SQL query
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ParamValue {
+    /// SQL `NULL`
+    Null,
+    /// A boolean
+    Boolean(bool),
+    /// A signed integer
+    Integer(i64),
+    /// A floating point number, which must be finite
+    Float(f64),
+    /// A string
+    String(String),
+}
+
+impl From<bool> for ParamValue {
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+impl From<i64> for ParamValue {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<f64> for ParamValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<&str> for ParamValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for ParamValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+/// The values bound to the parameters of a SQL query, which the server
+/// substitutes for the parameters without interpolating them into the
+/// text of the query
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum QueryParams {
+    /// The values of the parameters `$1`, `$2`, ...
+    Positional(Vec<ParamValue>),
+    /// The values of named parameters, such as `$host`
+    Named(BTreeMap<String, ParamValue>),
+}
+
+impl QueryParams {
+    /// Binds `values` to the parameters `$1`, `$2`, ...
+    pub fn positional(values: Vec<ParamValue>) -> Self {
+        Self::Positional(values)
+    }
+
+    /// Binds each value to the parameter `$name`, where `name` is its name
+    pub fn named<I, S>(values: I) -> Self
+    where
+        I: IntoIterator<Item = (S, ParamValue)>,
+        S: Into<String>,
+    {
+        Self::Named(
+            values
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        )
     }
 }
 
@@ -106,6 +212,8 @@ impl Client {
 struct ReadInfo {
     database_name: String,
     sql_query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    params: Option<QueryParams>,
 }
 
 /// A struct that manages the stream of Arrow `RecordBatch` results from an
@@ -123,10 +231,12 @@ impl PerformQuery {
         flight: &mut Client,
         database_name: String,
         sql_query: String,
+        params: Option<QueryParams>,
     ) -> Result<Self, Error> {
         let query = ReadInfo {
             database_name,
             sql_query,
+            params,
         };
 
         let t = Ticket {
@@ -146,6 +256,11 @@ impl PerformQuery {
         })
     }
 
+    /// Returns the schema of the query results
+    pub fn schema(&self) -> Arc<Schema> {
+        Arc::clone(&self.schema)
+    }
+
     /// Returns the next `RecordBatch` available for this query, or `None` if
     /// there are no further results available.
     pub async fn next(&mut self) -> Result<Option<RecordBatch>, Error> {
@@ -7,6 +7,7 @@ use ::generated_types::google::longrunning::Operation;
 
 use std::convert::TryInto;
 use std::num::NonZeroU32;
+use std::ops::Range;
 
 /// Re-export generated_types
 pub mod generated_types {
@@ -89,6 +90,114 @@ pub enum UpdateDatabaseError {
     ServerError(tonic::Status),
 }
 
+/// Errors returned by Client::delete_database
+#[derive(Debug, Error)]
+pub enum DeleteDatabaseError {
+    /// Server ID is not set
+    #[error("Server ID not set")]
+    NoServerId,
+
+    /// Database not found
+    #[error("Database not found")]
+    DatabaseNotFound,
+
+    /// Server returned an invalid argument error
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    InvalidArgument(tonic::Status),
+
+    /// Server indicated that it is not (yet) available
+    #[error("Server unavailable: {}", .0.message())]
+    Unavailable(tonic::Status),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by Client::backup_database
+#[derive(Debug, Error)]
+pub enum BackupDatabaseError {
+    /// Server ID is not set
+    #[error("Server ID not set")]
+    NoServerId,
+
+    /// Database not found
+    #[error("Database not found")]
+    DatabaseNotFound,
+
+    /// The destination already contains a backup of the database
+    #[error("Backup already exists")]
+    BackupAlreadyExists,
+
+    /// Server returned an invalid argument error
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    InvalidArgument(tonic::Status),
+
+    /// Server indicated that it is not (yet) available
+    #[error("Server unavailable: {}", .0.message())]
+    Unavailable(tonic::Status),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by Client::restore_database
+#[derive(Debug, Error)]
+pub enum RestoreDatabaseError {
+    /// Server ID is not set
+    #[error("Server ID not set")]
+    NoServerId,
+
+    /// The database or its data already exists
+    #[error("Database already exists")]
+    DatabaseAlreadyExists,
+
+    /// No backup of the database found at the source
+    #[error("Backup not found")]
+    BackupNotFound,
+
+    /// Server returned an invalid argument error
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    InvalidArgument(tonic::Status),
+
+    /// Server indicated that it is not (yet) available
+    #[error("Server unavailable: {}", .0.message())]
+    Unavailable(tonic::Status),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by Client::restore_deleted_database
+#[derive(Debug, Error)]
+pub enum RestoreDeletedDatabaseError {
+    /// Server ID is not set
+    #[error("Server ID not set")]
+    NoServerId,
+
+    /// No deleted database with this name found
+    #[error("Deleted database not found")]
+    DatabaseNotFound,
+
+    /// The database is not deleted
+    #[error("Database already exists")]
+    DatabaseAlreadyExists,
+
+    /// Server returned an invalid argument error
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    InvalidArgument(tonic::Status),
+
+    /// Server indicated that it is not (yet) available
+    #[error("Server unavailable: {}", .0.message())]
+    Unavailable(tonic::Status),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
 /// Errors returned by Client::list_databases
 #[derive(Debug, Error)]
 pub enum ListDatabaseError {
@@ -125,6 +234,86 @@ pub enum GetDatabaseError {
     ServerError(tonic::Status),
 }
 
+/// Errors returned by Client::clone_database
+#[derive(Debug, Error)]
+pub enum CloneDatabaseError {
+    /// Server ID is not set
+    #[error("Server ID not set")]
+    NoServerId,
+
+    /// Source database not found
+    #[error("Database not found")]
+    DatabaseNotFound,
+
+    /// The target database or its data already exists
+    #[error("Database already exists")]
+    DatabaseAlreadyExists,
+
+    /// Server returned an invalid argument error
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    InvalidArgument(tonic::Status),
+
+    /// Server indicated that it is not (yet) available
+    #[error("Server unavailable: {}", .0.message())]
+    Unavailable(tonic::Status),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by Client::export_database
+#[derive(Debug, Error)]
+pub enum ExportDatabaseError {
+    /// Server ID is not set
+    #[error("Server ID not set")]
+    NoServerId,
+
+    /// Database not found
+    #[error("Database not found")]
+    DatabaseNotFound,
+
+    /// The destination is not empty
+    #[error("Export destination is not empty")]
+    DestinationNotEmpty,
+
+    /// Server returned an invalid argument error
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    InvalidArgument(tonic::Status),
+
+    /// Server indicated that it is not (yet) available
+    #[error("Server unavailable: {}", .0.message())]
+    Unavailable(tonic::Status),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by Client::import_tsm
+#[derive(Debug, Error)]
+pub enum ImportTsmError {
+    /// Server ID is not set
+    #[error("Server ID not set")]
+    NoServerId,
+
+    /// Database not found
+    #[error("Database not found")]
+    DatabaseNotFound,
+
+    /// A file could not be read or imported
+    #[error("Invalid TSM file: {}", .0.message())]
+    InvalidFile(tonic::Status),
+
+    /// Server indicated that it is not (yet) available
+    #[error("Server unavailable: {}", .0.message())]
+    Unavailable(tonic::Status),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
 /// Errors returned by Client::list_chunks
 #[derive(Debug, Error)]
 pub enum ListChunksError {
@@ -137,6 +326,32 @@ pub enum ListChunksError {
     ServerError(tonic::Status),
 }
 
+/// Errors returned by Client::watch_chunks and [`ChunkEventStream::next`]
+#[derive(Debug, Error)]
+pub enum WatchChunksError {
+    /// Database not found
+    #[error("Database not found")]
+    DatabaseNotFound,
+
+    /// Server indicated that it is not (yet) available
+    #[error("Server unavailable: {}", .0.message())]
+    Unavailable(tonic::Status),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+impl From<tonic::Status> for WatchChunksError {
+    fn from(status: tonic::Status) -> Self {
+        match status.code() {
+            tonic::Code::NotFound => Self::DatabaseNotFound,
+            tonic::Code::Unavailable => Self::Unavailable(status),
+            _ => Self::ServerError(status),
+        }
+    }
+}
+
 /// Errors returned by Client::list_remotes
 #[derive(Debug, Error)]
 pub enum ListRemotesError {
@@ -249,6 +464,150 @@ pub enum ClosePartitionChunkError {
     ServerError(tonic::Status),
 }
 
+/// Errors returned by Client::persist_partition_chunk
+#[derive(Debug, Error)]
+pub enum PersistPartitionChunkError {
+    /// Database not found
+    #[error("Database not found")]
+    DatabaseNotFound,
+
+    /// Response contained no payload
+    #[error("Server returned an empty response")]
+    EmptyResponse,
+
+    /// Server indicated that it is not (yet) available
+    #[error("Server unavailable: {}", .0.message())]
+    Unavailable(tonic::Status),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by Client::compact_partition
+#[derive(Debug, Error)]
+pub enum CompactPartitionError {
+    /// Database not found
+    #[error("Database not found")]
+    DatabaseNotFound,
+
+    /// Response contained no payload
+    #[error("Server returned an empty response")]
+    EmptyResponse,
+
+    /// Server indicated that it is not (yet) available
+    #[error("Server unavailable: {}", .0.message())]
+    Unavailable(tonic::Status),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by Client::drop_partition
+#[derive(Debug, Error)]
+pub enum DropPartitionError {
+    /// Database or partition not found
+    #[error("{}", .0)]
+    NotFound(String),
+
+    /// Server indicated that it is not (yet) available
+    #[error("Server unavailable: {}", .0.message())]
+    Unavailable(tonic::Status),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by [`Client::delete`]
+#[derive(Debug, Error)]
+pub enum DeleteError {
+    /// Database not found
+    #[error("{}", .0)]
+    NotFound(String),
+
+    /// The predicate is invalid
+    #[error("Invalid predicate: {}", .0)]
+    InvalidPredicate(String),
+
+    /// Server indicated that it is not (yet) available
+    #[error("Server unavailable: {}", .0.message())]
+    Unavailable(tonic::Status),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by [`Client::create_continuous_query`]
+#[derive(Debug, Error)]
+pub enum CreateContinuousQueryError {
+    /// Database not found
+    #[error("{}", .0)]
+    DatabaseNotFound(String),
+
+    /// A query with the same name already exists
+    #[error("{}", .0)]
+    AlreadyExists(String),
+
+    /// The query is invalid
+    #[error("Invalid query: {}", .0)]
+    InvalidQuery(String),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by [`Client::list_continuous_queries`]
+#[derive(Debug, Error)]
+pub enum ListContinuousQueriesError {
+    /// Database not found
+    #[error("{}", .0)]
+    DatabaseNotFound(String),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by [`Client::delete_continuous_query`]
+#[derive(Debug, Error)]
+pub enum DeleteContinuousQueryError {
+    /// Database or query not found
+    #[error("{}", .0)]
+    NotFound(String),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by [`Client::list_queries`]
+#[derive(Debug, Error)]
+pub enum ListQueriesError {
+    /// Database not found
+    #[error("{}", .0)]
+    DatabaseNotFound(String),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by [`Client::cancel_query`]
+#[derive(Debug, Error)]
+pub enum CancelQueryError {
+    /// Database or query not found
+    #[error("{}", .0)]
+    NotFound(String),
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
 /// Errors returned by [`Client::get_server_status`]
 #[derive(Debug, Error)]
 pub enum GetServerStatusError {
@@ -261,6 +620,42 @@ pub enum GetServerStatusError {
     ServerError(tonic::Status),
 }
 
+/// Errors returned by Client::create_token
+#[derive(Debug, Error)]
+pub enum CreateTokenError {
+    /// Response contained no payload
+    #[error("Server returned an empty response")]
+    EmptyResponse,
+
+    /// Authorization is not enabled on the server
+    #[error("Authorization is not enabled on the server")]
+    AuthorizationDisabled,
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by Client::list_tokens
+#[derive(Debug, Error)]
+pub enum ListTokensError {
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
+/// Errors returned by Client::revoke_token
+#[derive(Debug, Error)]
+pub enum RevokeTokenError {
+    /// Token not found
+    #[error("Token not found")]
+    TokenNotFound,
+
+    /// Client received an unexpected error from the server
+    #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
+    ServerError(tonic::Status),
+}
+
 /// An IOx Management API client.
 ///
 /// This client wraps the underlying `tonic` generated client with a
@@ -298,7 +693,7 @@ pub struct Client {
 
 impl Client {
     /// Creates a new client with the provided connection
-    pub fn new(channel: tonic::transport::Channel) -> Self {
+    pub fn new(channel: Connection) -> Self {
         Self {
             inner: ManagementServiceClient::new(channel),
         }
@@ -333,70 +728,273 @@ impl Client {
         Ok(id)
     }
 
-    /// Check if databases are loaded and ready for read and write.
-    pub async fn get_server_status(&mut self) -> Result<ServerStatus, GetServerStatusError> {
+    /// Check if databases are loaded and ready for read and write.
+    pub async fn get_server_status(&mut self) -> Result<ServerStatus, GetServerStatusError> {
+        let response = self
+            .inner
+            .get_server_status(GetServerStatusRequest {})
+            .await
+            .map_err(GetServerStatusError::ServerError)?;
+
+        let server_status = response
+            .into_inner()
+            .server_status
+            .ok_or(GetServerStatusError::EmptyResponse)?;
+        Ok(server_status)
+    }
+
+    /// Set serving readiness.
+    pub async fn set_serving_readiness(
+        &mut self,
+        ready: bool,
+    ) -> Result<(), SetServingReadinessError> {
+        self.inner
+            .set_serving_readiness(SetServingReadinessRequest { ready })
+            .await
+            .map_err(SetServingReadinessError::ServerError)?;
+        Ok(())
+    }
+
+    /// Creates a new IOx database.
+    pub async fn create_database(
+        &mut self,
+        rules: DatabaseRules,
+    ) -> Result<(), CreateDatabaseError> {
+        self.inner
+            .create_database(CreateDatabaseRequest { rules: Some(rules) })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::AlreadyExists => CreateDatabaseError::DatabaseAlreadyExists,
+                tonic::Code::FailedPrecondition => CreateDatabaseError::NoServerId,
+                tonic::Code::InvalidArgument => CreateDatabaseError::InvalidArgument(status),
+                tonic::Code::Unavailable => CreateDatabaseError::Unavailable(status),
+                _ => CreateDatabaseError::ServerError(status),
+            })?;
+
+        Ok(())
+    }
+
+    /// Updates the configuration for a database.
+    pub async fn update_database(
+        &mut self,
+        rules: DatabaseRules,
+    ) -> Result<DatabaseRules, UpdateDatabaseError> {
+        let response = self
+            .inner
+            .update_database(UpdateDatabaseRequest { rules: Some(rules) })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => UpdateDatabaseError::DatabaseNotFound,
+                tonic::Code::FailedPrecondition => UpdateDatabaseError::NoServerId,
+                tonic::Code::InvalidArgument => UpdateDatabaseError::InvalidArgument(status),
+                tonic::Code::Unavailable => UpdateDatabaseError::Unavailable(status),
+                _ => UpdateDatabaseError::ServerError(status),
+            })?;
+
+        Ok(response.into_inner().rules.unwrap())
+    }
+
+    /// Deletes a database, detaching it from the server. Its data in object
+    /// storage is kept for later re-attachment if `preserve_data` is set,
+    /// otherwise it can be restored with
+    /// [`restore_deleted_database`](Self::restore_deleted_database) until it
+    /// is purged.
+    pub async fn delete_database(
+        &mut self,
+        db_name: impl Into<String>,
+        preserve_data: bool,
+    ) -> Result<(), DeleteDatabaseError> {
+        let db_name = db_name.into();
+
+        self.inner
+            .delete_database(DeleteDatabaseRequest {
+                db_name,
+                preserve_data,
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => DeleteDatabaseError::DatabaseNotFound,
+                tonic::Code::FailedPrecondition => DeleteDatabaseError::NoServerId,
+                tonic::Code::InvalidArgument => DeleteDatabaseError::InvalidArgument(status),
+                tonic::Code::Unavailable => DeleteDatabaseError::Unavailable(status),
+                _ => DeleteDatabaseError::ServerError(status),
+            })?;
+
+        Ok(())
+    }
+
+    /// Copies a point-in-time snapshot of a database to the object store
+    /// path prefix `destination`. Returns the number of files copied.
+    pub async fn backup_database(
+        &mut self,
+        db_name: impl Into<String>,
+        destination: impl Into<String>,
+    ) -> Result<u64, BackupDatabaseError> {
+        let db_name = db_name.into();
+        let destination = destination.into();
+
+        let response = self
+            .inner
+            .backup_database(BackupDatabaseRequest {
+                db_name,
+                destination,
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => BackupDatabaseError::DatabaseNotFound,
+                tonic::Code::AlreadyExists => BackupDatabaseError::BackupAlreadyExists,
+                tonic::Code::FailedPrecondition => BackupDatabaseError::NoServerId,
+                tonic::Code::InvalidArgument => BackupDatabaseError::InvalidArgument(status),
+                tonic::Code::Unavailable => BackupDatabaseError::Unavailable(status),
+                _ => BackupDatabaseError::ServerError(status),
+            })?;
+
+        Ok(response.into_inner().files)
+    }
+
+    /// Restores a database from a backup at the object store path prefix
+    /// `source` and loads it. Returns the number of files copied.
+    pub async fn restore_database(
+        &mut self,
+        db_name: impl Into<String>,
+        source: impl Into<String>,
+    ) -> Result<u64, RestoreDatabaseError> {
+        let db_name = db_name.into();
+        let source = source.into();
+
         let response = self
             .inner
-            .get_server_status(GetServerStatusRequest {})
+            .restore_database(RestoreDatabaseRequest { db_name, source })
             .await
-            .map_err(GetServerStatusError::ServerError)?;
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => RestoreDatabaseError::BackupNotFound,
+                tonic::Code::AlreadyExists => RestoreDatabaseError::DatabaseAlreadyExists,
+                tonic::Code::FailedPrecondition => RestoreDatabaseError::NoServerId,
+                tonic::Code::InvalidArgument => RestoreDatabaseError::InvalidArgument(status),
+                tonic::Code::Unavailable => RestoreDatabaseError::Unavailable(status),
+                _ => RestoreDatabaseError::ServerError(status),
+            })?;
 
-        let server_status = response
-            .into_inner()
-            .server_status
-            .ok_or(GetServerStatusError::EmptyResponse)?;
-        Ok(server_status)
+        Ok(response.into_inner().files)
     }
 
-    /// Set serving readiness.
-    pub async fn set_serving_readiness(
+    /// Restores a deleted database whose data has not been purged yet and
+    /// loads it.
+    pub async fn restore_deleted_database(
         &mut self,
-        ready: bool,
-    ) -> Result<(), SetServingReadinessError> {
+        db_name: impl Into<String>,
+    ) -> Result<(), RestoreDeletedDatabaseError> {
+        let db_name = db_name.into();
+
         self.inner
-            .set_serving_readiness(SetServingReadinessRequest { ready })
+            .restore_deleted_database(RestoreDeletedDatabaseRequest { db_name })
             .await
-            .map_err(SetServingReadinessError::ServerError)?;
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => RestoreDeletedDatabaseError::DatabaseNotFound,
+                tonic::Code::AlreadyExists => RestoreDeletedDatabaseError::DatabaseAlreadyExists,
+                tonic::Code::FailedPrecondition => RestoreDeletedDatabaseError::NoServerId,
+                tonic::Code::InvalidArgument => {
+                    RestoreDeletedDatabaseError::InvalidArgument(status)
+                }
+                tonic::Code::Unavailable => RestoreDeletedDatabaseError::Unavailable(status),
+                _ => RestoreDeletedDatabaseError::ServerError(status),
+            })?;
+
         Ok(())
     }
 
-    /// Creates a new IOx database.
-    pub async fn create_database(
+    /// Creates database `target` with the rules of database `source`. With
+    /// `with_data` the persisted data of `source` is copied too. Returns the
+    /// number of parquet files copied.
+    pub async fn clone_database(
         &mut self,
-        rules: DatabaseRules,
-    ) -> Result<(), CreateDatabaseError> {
-        self.inner
-            .create_database(CreateDatabaseRequest { rules: Some(rules) })
+        source: impl Into<String>,
+        target: impl Into<String>,
+        with_data: bool,
+    ) -> Result<u64, CloneDatabaseError> {
+        let source_db_name = source.into();
+        let target_db_name = target.into();
+
+        let response = self
+            .inner
+            .clone_database(CloneDatabaseRequest {
+                source_db_name,
+                target_db_name,
+                with_data,
+            })
             .await
             .map_err(|status| match status.code() {
-                tonic::Code::AlreadyExists => CreateDatabaseError::DatabaseAlreadyExists,
-                tonic::Code::FailedPrecondition => CreateDatabaseError::NoServerId,
-                tonic::Code::InvalidArgument => CreateDatabaseError::InvalidArgument(status),
-                tonic::Code::Unavailable => CreateDatabaseError::Unavailable(status),
-                _ => CreateDatabaseError::ServerError(status),
+                tonic::Code::NotFound => CloneDatabaseError::DatabaseNotFound,
+                tonic::Code::AlreadyExists => CloneDatabaseError::DatabaseAlreadyExists,
+                tonic::Code::FailedPrecondition => CloneDatabaseError::NoServerId,
+                tonic::Code::InvalidArgument => CloneDatabaseError::InvalidArgument(status),
+                tonic::Code::Unavailable => CloneDatabaseError::Unavailable(status),
+                _ => CloneDatabaseError::ServerError(status),
             })?;
 
-        Ok(())
+        Ok(response.into_inner().files)
     }
 
-    /// Updates the configuration for a database.
-    pub async fn update_database(
+    /// Writes the data of a database with a timestamp within `range`, or all
+    /// data without a range, as a hive-style partitioned directory of parquet
+    /// files to `destination`: `file://` followed by a directory on the
+    /// server's local filesystem, or else an object store path prefix.
+    /// Returns the number of files written.
+    pub async fn export_database(
         &mut self,
-        rules: DatabaseRules,
-    ) -> Result<DatabaseRules, UpdateDatabaseError> {
+        db_name: impl Into<String>,
+        destination: impl Into<String>,
+        range: Option<Range<i64>>,
+    ) -> Result<u64, ExportDatabaseError> {
+        let db_name = db_name.into();
+        let destination = destination.into();
+        let range = range.map(|range| export_database_request::TimeRange {
+            start_time: range.start,
+            stop_time: range.end,
+        });
+
         let response = self
             .inner
-            .update_database(UpdateDatabaseRequest { rules: Some(rules) })
+            .export_database(ExportDatabaseRequest {
+                db_name,
+                destination,
+                range,
+            })
             .await
             .map_err(|status| match status.code() {
-                tonic::Code::NotFound => UpdateDatabaseError::DatabaseNotFound,
-                tonic::Code::FailedPrecondition => UpdateDatabaseError::NoServerId,
-                tonic::Code::InvalidArgument => UpdateDatabaseError::InvalidArgument(status),
-                tonic::Code::Unavailable => UpdateDatabaseError::Unavailable(status),
-                _ => UpdateDatabaseError::ServerError(status),
+                tonic::Code::NotFound => ExportDatabaseError::DatabaseNotFound,
+                tonic::Code::AlreadyExists => ExportDatabaseError::DestinationNotEmpty,
+                tonic::Code::FailedPrecondition => ExportDatabaseError::NoServerId,
+                tonic::Code::InvalidArgument => ExportDatabaseError::InvalidArgument(status),
+                tonic::Code::Unavailable => ExportDatabaseError::Unavailable(status),
+                _ => ExportDatabaseError::ServerError(status),
             })?;
 
-        Ok(response.into_inner().rules.unwrap())
+        Ok(response.into_inner().files)
+    }
+
+    /// Imports the TSM files at `paths` on the local filesystem of the server
+    /// into a database. Returns the number of rows imported.
+    pub async fn import_tsm(
+        &mut self,
+        db_name: impl Into<String>,
+        paths: Vec<String>,
+    ) -> Result<u64, ImportTsmError> {
+        let db_name = db_name.into();
+
+        let response = self
+            .inner
+            .import_tsm(ImportTsmRequest { db_name, paths })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => ImportTsmError::DatabaseNotFound,
+                tonic::Code::FailedPrecondition => ImportTsmError::NoServerId,
+                tonic::Code::InvalidArgument => ImportTsmError::InvalidFile(status),
+                tonic::Code::Unavailable => ImportTsmError::Unavailable(status),
+                _ => ImportTsmError::ServerError(status),
+            })?;
+
+        Ok(response.into_inner().rows)
     }
 
     /// List databases.
@@ -453,6 +1051,24 @@ impl Client {
         Ok(response.into_inner().chunks)
     }
 
+    /// Subscribe to the lifecycle state transitions of all chunks in a
+    /// database. Only transitions happening after the call are reported.
+    pub async fn watch_chunks(
+        &mut self,
+        db_name: impl Into<String>,
+    ) -> Result<ChunkEventStream, WatchChunksError> {
+        let db_name = db_name.into();
+
+        let response = self
+            .inner
+            .watch_chunks(WatchChunksRequest { db_name })
+            .await?;
+
+        Ok(ChunkEventStream {
+            inner: response.into_inner(),
+        })
+    }
+
     /// List remotes.
     pub async fn list_remotes(&mut self) -> Result<Vec<generated_types::Remote>, ListRemotesError> {
         let response = self
@@ -490,6 +1106,52 @@ impl Client {
         Ok(())
     }
 
+    /// Create a token granting `permissions`. Returns the token and the
+    /// secret clients present to the server, which cannot be retrieved later.
+    pub async fn create_token(
+        &mut self,
+        description: impl Into<String>,
+        permissions: Vec<Permission>,
+    ) -> Result<(Token, String), CreateTokenError> {
+        let response = self
+            .inner
+            .create_token(CreateTokenRequest {
+                description: description.into(),
+                permissions,
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::FailedPrecondition => CreateTokenError::AuthorizationDisabled,
+                _ => CreateTokenError::ServerError(status),
+            })?
+            .into_inner();
+
+        let token = response.token.ok_or(CreateTokenError::EmptyResponse)?;
+        Ok((token, response.secret))
+    }
+
+    /// List the tokens that have not been revoked
+    pub async fn list_tokens(&mut self) -> Result<Vec<Token>, ListTokensError> {
+        let response = self
+            .inner
+            .list_tokens(ListTokensRequest {})
+            .await
+            .map_err(ListTokensError::ServerError)?;
+        Ok(response.into_inner().tokens)
+    }
+
+    /// Revoke the token with the given id
+    pub async fn revoke_token(&mut self, id: u64) -> Result<(), RevokeTokenError> {
+        self.inner
+            .revoke_token(RevokeTokenRequest { id })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => RevokeTokenError::TokenNotFound,
+                _ => RevokeTokenError::ServerError(status),
+            })?;
+        Ok(())
+    }
+
     /// List all partitions of the database
     pub async fn list_partitions(
         &mut self,
@@ -644,4 +1306,261 @@ impl Client {
             .operation
             .ok_or(ClosePartitionChunkError::EmptyResponse)?)
     }
+
+    /// Writes the specified chunk, which must be in the read buffer, to
+    /// object storage.
+    ///
+    /// Returns the job tracking the write
+    pub async fn persist_partition_chunk(
+        &mut self,
+        db_name: impl Into<String>,
+        partition_key: impl Into<String>,
+        table_name: impl Into<String>,
+        chunk_id: u32,
+    ) -> Result<Operation, PersistPartitionChunkError> {
+        let db_name = db_name.into();
+        let partition_key = partition_key.into();
+        let table_name = table_name.into();
+
+        let response = self
+            .inner
+            .persist_partition_chunk(PersistPartitionChunkRequest {
+                db_name,
+                partition_key,
+                table_name,
+                chunk_id,
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => PersistPartitionChunkError::DatabaseNotFound,
+                tonic::Code::Unavailable => PersistPartitionChunkError::Unavailable(status),
+                _ => PersistPartitionChunkError::ServerError(status),
+            })?;
+
+        Ok(response
+            .into_inner()
+            .operation
+            .ok_or(PersistPartitionChunkError::EmptyResponse)?)
+    }
+
+    /// Compacts the persisted chunks of the specified table within a
+    /// partition into a single chunk.
+    ///
+    /// Returns the job tracking the compaction
+    pub async fn compact_partition(
+        &mut self,
+        db_name: impl Into<String>,
+        partition_key: impl Into<String>,
+        table_name: impl Into<String>,
+    ) -> Result<Operation, CompactPartitionError> {
+        let db_name = db_name.into();
+        let partition_key = partition_key.into();
+        let table_name = table_name.into();
+
+        let response = self
+            .inner
+            .compact_partition(CompactPartitionRequest {
+                db_name,
+                partition_key,
+                table_name,
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => CompactPartitionError::DatabaseNotFound,
+                tonic::Code::Unavailable => CompactPartitionError::Unavailable(status),
+                _ => CompactPartitionError::ServerError(status),
+            })?;
+
+        Ok(response
+            .into_inner()
+            .operation
+            .ok_or(CompactPartitionError::EmptyResponse)?)
+    }
+
+    /// Drops the specified partition and all of its chunks.
+    ///
+    /// If `delete_persisted` is true, the partition's parquet files are also
+    /// removed from the preserved catalog, otherwise they will be reloaded
+    /// when the server restarts.
+    pub async fn drop_partition(
+        &mut self,
+        db_name: impl Into<String>,
+        partition_key: impl Into<String>,
+        delete_persisted: bool,
+    ) -> Result<(), DropPartitionError> {
+        let db_name = db_name.into();
+        let partition_key = partition_key.into();
+
+        self.inner
+            .drop_partition(DropPartitionRequest {
+                db_name,
+                partition_key,
+                delete_persisted,
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => DropPartitionError::NotFound(status.message().to_string()),
+                tonic::Code::Unavailable => DropPartitionError::Unavailable(status),
+                _ => DropPartitionError::ServerError(status),
+            })?;
+
+        Ok(())
+    }
+
+    /// Deletes the rows of a table matching `predicate`.
+    ///
+    /// The rows are masked at query time right away and removed from the
+    /// parquet files of chunks persisted later on.
+    pub async fn delete(
+        &mut self,
+        db_name: impl Into<String>,
+        predicate: DeletePredicate,
+    ) -> Result<(), DeleteError> {
+        let db_name = db_name.into();
+
+        self.inner
+            .delete(DeleteRequest {
+                db_name,
+                predicate: Some(predicate),
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => DeleteError::NotFound(status.message().to_string()),
+                tonic::Code::InvalidArgument => {
+                    DeleteError::InvalidPredicate(status.message().to_string())
+                }
+                tonic::Code::Unavailable => DeleteError::Unavailable(status),
+                _ => DeleteError::ServerError(status),
+            })?;
+
+        Ok(())
+    }
+
+    /// Creates a continuous query, which runs a SQL query on a schedule and
+    /// writes its result into a table of the same database
+    pub async fn create_continuous_query(
+        &mut self,
+        db_name: impl Into<String>,
+        query: ContinuousQuery,
+    ) -> Result<(), CreateContinuousQueryError> {
+        self.inner
+            .create_continuous_query(CreateContinuousQueryRequest {
+                db_name: db_name.into(),
+                query: Some(query),
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => {
+                    CreateContinuousQueryError::DatabaseNotFound(status.message().to_string())
+                }
+                tonic::Code::AlreadyExists => {
+                    CreateContinuousQueryError::AlreadyExists(status.message().to_string())
+                }
+                tonic::Code::InvalidArgument => {
+                    CreateContinuousQueryError::InvalidQuery(status.message().to_string())
+                }
+                _ => CreateContinuousQueryError::ServerError(status),
+            })?;
+
+        Ok(())
+    }
+
+    /// Lists the continuous queries of a database
+    pub async fn list_continuous_queries(
+        &mut self,
+        db_name: impl Into<String>,
+    ) -> Result<Vec<ContinuousQuery>, ListContinuousQueriesError> {
+        let response = self
+            .inner
+            .list_continuous_queries(ListContinuousQueriesRequest {
+                db_name: db_name.into(),
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => {
+                    ListContinuousQueriesError::DatabaseNotFound(status.message().to_string())
+                }
+                _ => ListContinuousQueriesError::ServerError(status),
+            })?;
+
+        Ok(response.into_inner().queries)
+    }
+
+    /// Deletes a continuous query
+    pub async fn delete_continuous_query(
+        &mut self,
+        db_name: impl Into<String>,
+        name: impl Into<String>,
+    ) -> Result<(), DeleteContinuousQueryError> {
+        self.inner
+            .delete_continuous_query(DeleteContinuousQueryRequest {
+                db_name: db_name.into(),
+                name: name.into(),
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => {
+                    DeleteContinuousQueryError::NotFound(status.message().to_string())
+                }
+                _ => DeleteContinuousQueryError::ServerError(status),
+            })?;
+
+        Ok(())
+    }
+
+    /// Lists the queries running against a database
+    pub async fn list_queries(
+        &mut self,
+        db_name: impl Into<String>,
+    ) -> Result<Vec<RunningQuery>, ListQueriesError> {
+        let response = self
+            .inner
+            .list_queries(ListQueriesRequest {
+                db_name: db_name.into(),
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => {
+                    ListQueriesError::DatabaseNotFound(status.message().to_string())
+                }
+                _ => ListQueriesError::ServerError(status),
+            })?;
+
+        Ok(response.into_inner().queries)
+    }
+
+    /// Cancels a query running against a database
+    pub async fn cancel_query(
+        &mut self,
+        db_name: impl Into<String>,
+        id: impl Into<String>,
+    ) -> Result<(), CancelQueryError> {
+        self.inner
+            .cancel_query(CancelQueryRequest {
+                db_name: db_name.into(),
+                id: id.into(),
+            })
+            .await
+            .map_err(|status| match status.code() {
+                tonic::Code::NotFound => CancelQueryError::NotFound(status.message().to_string()),
+                _ => CancelQueryError::ServerError(status),
+            })?;
+
+        Ok(())
+    }
+}
+
+/// A stream of chunk lifecycle events. Created by calling the `watch_chunks`
+/// method on a management [`Client`].
+#[derive(Debug)]
+pub struct ChunkEventStream {
+    inner: tonic::Streaming<ChunkEvent>,
+}
+
+impl ChunkEventStream {
+    /// Waits for the next chunk event, or returns `None` if the server closed
+    /// the stream
+    pub async fn next(&mut self) -> Result<Option<ChunkEvent>, WatchChunksError> {
+        Ok(self.inner.message().await?)
+    }
 }
@@ -15,6 +15,29 @@ pub enum WriteError {
     /// Client received an unexpected error from the server
     #[error("Unexpected server error: {}: {}", .0.code(), .0.message())]
     ServerError(tonic::Status),
+
+    /// Some lines were not written, e.g. because they could not be parsed
+    /// or conflict with the schema of the database. The other lines were
+    /// written
+    #[error(
+        "Partial write: {} lines written, {} lines rejected",
+        .lines_written,
+        .rejected_lines.len()
+    )]
+    PartialWrite {
+        /// The number of lines written
+        lines_written: usize,
+        /// The line number and reason of each line that was not written
+        rejected_lines: Vec<RejectedLine>,
+    },
+
+    /// Some lines could not be written by an all-or-nothing write, so none
+    /// of the lines were written
+    #[error("Write rejected: {} lines rejected", .rejected_lines.len())]
+    RejectedWrite {
+        /// The line number and reason of each line that could not be written
+        rejected_lines: Vec<RejectedLine>,
+    },
 }
 
 /// An IOx Write API client.
@@ -48,7 +71,7 @@ pub struct Client {
 
 impl Client {
     /// Creates a new client with the provided connection
-    pub fn new(channel: tonic::transport::Channel) -> Self {
+    pub fn new(channel: Connection) -> Self {
         Self {
             inner: WriteServiceClient::new(channel),
         }
@@ -56,23 +79,82 @@ impl Client {
 
     /// Write the [LineProtocol] formatted data in `lp_data` to
     /// database `name`. Returns the number of lines which were parsed
-    /// and written to the database
+    /// and written to the database, or [`WriteError::PartialWrite`] if
+    /// some of the lines were not written
     ///
     /// [LineProtocol]: https://docs.influxdata.com/influxdb/v2.0/reference/syntax/line-protocol/#data-types-and-format
     pub async fn write(
         &mut self,
         db_name: impl Into<String>,
         lp_data: impl Into<String>,
+    ) -> Result<usize, WriteError> {
+        self.write_with_precision(db_name, lp_data, Precision::Nanoseconds)
+            .await
+    }
+
+    /// Like [`write`](Self::write), but the timestamps in `lp_data` are in
+    /// `precision` rather than nanoseconds
+    pub async fn write_with_precision(
+        &mut self,
+        db_name: impl Into<String>,
+        lp_data: impl Into<String>,
+        precision: Precision,
     ) -> Result<usize, WriteError> {
         let db_name = db_name.into();
         let lp_data = lp_data.into();
         let response = self
             .inner
-            .write(WriteRequest { db_name, lp_data })
+            .write(WriteRequest {
+                db_name,
+                lp_data,
+                all_or_nothing: false,
+                precision: precision as _,
+            })
             .await
-            .map_err(WriteError::ServerError)?;
+            .map_err(WriteError::ServerError)?
+            .into_inner();
 
-        Ok(response.into_inner().lines_written as usize)
+        let lines_written = response.lines_written as usize;
+        match response.rejected_lines.is_empty() {
+            true => Ok(lines_written),
+            false => Err(WriteError::PartialWrite {
+                lines_written,
+                rejected_lines: response.rejected_lines,
+            }),
+        }
+    }
+
+    /// Write the [LineProtocol] formatted data in `lp_data` to
+    /// database `name` only if all of the lines can be written. Returns the
+    /// number of lines written, or [`WriteError::RejectedWrite`] with the
+    /// lines that could not be written, in which case nothing was written
+    ///
+    /// [LineProtocol]: https://docs.influxdata.com/influxdb/v2.0/reference/syntax/line-protocol/#data-types-and-format
+    pub async fn write_all_or_nothing(
+        &mut self,
+        db_name: impl Into<String>,
+        lp_data: impl Into<String>,
+    ) -> Result<usize, WriteError> {
+        let db_name = db_name.into();
+        let lp_data = lp_data.into();
+        let response = self
+            .inner
+            .write(WriteRequest {
+                db_name,
+                lp_data,
+                all_or_nothing: true,
+                precision: Precision::Nanoseconds as _,
+            })
+            .await
+            .map_err(WriteError::ServerError)?
+            .into_inner();
+
+        match response.rejected_lines.is_empty() {
+            true => Ok(response.lines_written as usize),
+            false => Err(WriteError::RejectedWrite {
+                rejected_lines: response.rejected_lines,
+            }),
+        }
     }
 
     /// Write an [Entry] to database `name`.
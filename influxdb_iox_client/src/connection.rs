@@ -1,11 +1,21 @@
-use http::{uri::InvalidUri, Uri};
+use http::{
+    header::{HeaderName, HeaderValue, InvalidHeaderValue, AUTHORIZATION},
+    uri::InvalidUri,
+    Request, Uri,
+};
 use std::convert::TryInto;
+use std::sync::Arc;
+use std::task::{Context, Poll};
 use std::time::Duration;
 use thiserror::Error;
-use tonic::transport::Endpoint;
+use tonic::transport::{Channel, Endpoint};
+use tower::Service;
+
+#[cfg(all(feature = "tls-rustls", feature = "tls-native"))]
+compile_error!("features `tls-rustls` and `tls-native` are mutually exclusive");
 
 /// The connection type used for clients
-pub type Connection = tonic::transport::Channel;
+pub type Connection = SetRequestHeadersService<Channel>;
 
 /// The default User-Agent header sent by the HTTP client.
 pub const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
@@ -24,6 +34,15 @@ pub enum Error {
     /// Client received an unexpected error from the server
     #[error("Invalid URI: {}", .0)]
     InvalidUri(#[from] InvalidUri),
+
+    /// A header configured on the builder has an invalid value
+    #[error("Invalid header value: {}", .0)]
+    InvalidHeaderValue(#[from] InvalidHeaderValue),
+
+    /// Error configuring the native TLS connector
+    #[cfg(feature = "tls-native")]
+    #[error("TLS error: {}", .0)]
+    TlsError(#[from] native_tls::Error),
 }
 
 /// Result type for the ConnectionBuilder
@@ -32,6 +51,9 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 /// A builder that produces a connection that can be used with any of the gRPC
 /// clients
 ///
+/// Connections to `https://` URLs require one of the `tls-rustls` or
+/// `tls-native` features to be enabled, which select the TLS implementation.
+///
 /// ```no_run
 /// #[tokio::main]
 /// # async fn main() {
@@ -48,9 +70,10 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 /// let client = Client::new(connection);
 /// # }
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Builder {
     user_agent: String,
+    headers: Vec<(HeaderName, String)>,
     connect_timeout: Duration,
     timeout: Duration,
 }
@@ -59,6 +82,7 @@ impl std::default::Default for Builder {
     fn default() -> Self {
         Self {
             user_agent: USER_AGENT.into(),
+            headers: Default::default(),
             connect_timeout: DEFAULT_CONNECT_TIMEOUT,
             timeout: DEFAULT_TIMEOUT,
         }
@@ -71,10 +95,23 @@ impl Builder {
     where
         D: TryInto<Uri, Error = InvalidUri>,
     {
+        let headers = self
+            .headers
+            .into_iter()
+            .map(|(name, value)| Ok((name, HeaderValue::from_str(&value)?)))
+            .collect::<Result<Vec<_>>>()?;
+
         let endpoint = Endpoint::from(dst.try_into()?)
             .user_agent(self.user_agent)?
             .timeout(self.timeout);
 
+        #[cfg(feature = "tls-rustls")]
+        let endpoint = if endpoint.uri().scheme() == Some(&http::uri::Scheme::HTTPS) {
+            endpoint.tls_config(tonic::transport::ClientTlsConfig::new())?
+        } else {
+            endpoint
+        };
+
         // Manually construct connector to workaround https://github.com/hyperium/tonic/issues/498
         let mut connector = hyper::client::HttpConnector::new();
         connector.set_connect_timeout(Some(self.connect_timeout));
@@ -84,7 +121,16 @@ impl Builder {
         connector.set_nodelay(true);
         connector.set_keepalive(None);
 
-        Ok(endpoint.connect_with_connector(connector).await?)
+        #[cfg(feature = "tls-native")]
+        let connector = {
+            let tls = native_tls::TlsConnector::builder()
+                .request_alpns(&["h2"])
+                .build()?;
+            hyper_tls::HttpsConnector::from((connector, tls.into()))
+        };
+
+        let channel = endpoint.connect_with_connector(connector).await?;
+        Ok(SetRequestHeadersService::new(channel, headers))
     }
 
     /// Set the `User-Agent` header sent by this client.
@@ -95,6 +141,20 @@ impl Builder {
         }
     }
 
+    /// Set a header sent with every request made over the connection.
+    pub fn header(mut self, name: HeaderName, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    /// Authenticate every request made over the connection with `token`, as
+    /// created by the management API of an IOx server that has authorization
+    /// enabled.
+    pub fn token(self, token: impl AsRef<str>) -> Self {
+        let value = format!("Token {}", token.as_ref());
+        self.header(AUTHORIZATION, value)
+    }
+
     /// Sets the maximum duration of time the client will wait for the IOx
     /// server to accept the TCP connection before aborting the request.
     ///
@@ -121,3 +181,42 @@ impl Builder {
         Self { timeout, ..self }
     }
 }
+
+/// A [`Service`] that adds a fixed set of headers to every request before
+/// passing it to the wrapped service
+#[derive(Debug, Clone)]
+pub struct SetRequestHeadersService<S> {
+    service: S,
+    headers: Arc<Vec<(HeaderName, HeaderValue)>>,
+}
+
+impl<S> SetRequestHeadersService<S> {
+    /// Wrap `service` so that `headers` are set on every request.
+    pub fn new(service: S, headers: Vec<(HeaderName, HeaderValue)>) -> Self {
+        Self {
+            service,
+            headers: Arc::new(headers),
+        }
+    }
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for SetRequestHeadersService<S>
+where
+    S: Service<Request<ReqBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        let headers = request.headers_mut();
+        for (name, value) in self.headers.iter() {
+            headers.insert(name.clone(), value.clone());
+        }
+        self.service.call(request)
+    }
+}
@@ -8,13 +8,43 @@ use arrow::{
     self, csv::WriterBuilder, error::ArrowError, json::ArrayWriter, record_batch::RecordBatch,
 };
 
+mod export;
+mod influx_v1;
+mod json_lines;
+mod pretty;
+mod streaming;
+mod timestamp;
+
+pub use export::write_arrow_ipc;
+#[cfg(feature = "parquet")]
+pub use export::write_parquet;
+pub use influx_v1::batches_to_influx_v1_json;
+pub use pretty::PrettyOptions;
+pub use streaming::StreamingFormatter;
+pub use timestamp::{TimestampFormat, TimestampPrecision};
+
 /// Error type for results formatting
 #[derive(Debug, Error)]
 pub enum Error {
     /// Unknown formatting type
-    #[error("Unknown format type: {}. Expected one of 'pretty', 'csv' or 'json'", .0)]
+    #[error(
+        "Unknown format type: {}. Expected one of 'pretty', 'csv', 'json', 'jsonl' or 'influxv1'",
+        .0
+    )]
     Invalid(String),
 
+    /// Unknown timestamp format
+    #[error(
+        "Unknown timestamp format: {}. Expected one of 'rfc3339', 'rfc3339-s', \
+         'rfc3339-ms', 'rfc3339-us', 'rfc3339-ns' or 'nanos'",
+        .0
+    )]
+    InvalidTimestampFormat(String),
+
+    /// Error converting timestamps
+    #[error("Arrow timestamp conversion error: {}", .0)]
+    TimestampArrow(ArrowError),
+
     /// Error pretty printing
     #[error("Arrow pretty printing error: {}", .0)]
     PrettyArrow(ArrowError),
@@ -27,6 +57,27 @@ pub enum Error {
     #[error("Arrow json printing error: {}", .0)]
     JsonArrow(ArrowError),
 
+    /// Error during JSON lines conversion
+    #[error("Arrow json lines printing error: {}", .0)]
+    JsonLinesArrow(ArrowError),
+
+    /// Error during InfluxDB 1.x JSON conversion
+    #[error("Arrow InfluxDB 1.x json printing error: {}", .0)]
+    InfluxV1Arrow(ArrowError),
+
+    /// Error writing Arrow IPC output
+    #[error("Arrow IPC writing error: {}", .0)]
+    IpcArrow(ArrowError),
+
+    /// Error writing parquet output
+    #[cfg(feature = "parquet")]
+    #[error("Parquet writing error: {}", .0)]
+    Parquet(parquet::errors::ParquetError),
+
+    /// Error writing output
+    #[error("I/O error writing output: {}", .0)]
+    Io(std::io::Error),
+
     /// Error converting CSV output to utf-8
     #[error("Error converting CSV output to UTF-8: {}", .0)]
     CsvUtf8(std::string::FromUtf8Error),
@@ -37,6 +88,9 @@ pub enum Error {
 }
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// The `strftime` format used to render timestamps as RFC3339 (UTC)
+pub const RFC3339_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.9fZ";
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 /// Requested output format for the query endpoint
 pub enum QueryOutputFormat {
@@ -46,6 +100,10 @@ pub enum QueryOutputFormat {
     Csv,
     /// Arrow JSON format
     Json,
+    /// Newline delimited JSON, one object per row
+    JsonLines,
+    /// The JSON layout of InfluxDB 1.x query responses
+    InfluxV1,
 }
 
 impl Display for QueryOutputFormat {
@@ -54,6 +112,8 @@ impl Display for QueryOutputFormat {
             QueryOutputFormat::Pretty => write!(f, "pretty"),
             QueryOutputFormat::Csv => write!(f, "csv"),
             QueryOutputFormat::Json => write!(f, "json"),
+            QueryOutputFormat::JsonLines => write!(f, "jsonl"),
+            QueryOutputFormat::InfluxV1 => write!(f, "influxv1"),
         }
     }
 }
@@ -72,6 +132,8 @@ impl FromStr for QueryOutputFormat {
             "pretty" => Ok(Self::Pretty),
             "csv" => Ok(Self::Csv),
             "json" => Ok(Self::Json),
+            "jsonl" | "ndjson" => Ok(Self::JsonLines),
+            "influxv1" => Ok(Self::InfluxV1),
             _ => Err(Error::Invalid(s.to_string())),
         }
     }
@@ -84,6 +146,8 @@ impl QueryOutputFormat {
             Self::Pretty => "text/plain",
             Self::Csv => "text/csv",
             Self::Json => "application/json",
+            Self::JsonLines => "application/x-ndjson",
+            Self::InfluxV1 => "application/json",
         }
     }
 }
@@ -92,7 +156,7 @@ impl QueryOutputFormat {
     /// Format the [`RecordBatch`]es into a String in one of the
     /// following formats:
     ///
-    /// Pretty:
+    /// Pretty (see [`PrettyOptions`] for more control over the output):
     /// ```text
     /// +----------------+--------------+-------+-----------------+------------+
     /// | bottom_degrees | location     | state | surface_degrees | time       |
@@ -101,10 +165,10 @@ impl QueryOutputFormat {
     /// +----------------+--------------+-------+-----------------+------------+
     /// ```
     ///
-    /// CSV:
+    /// CSV (see [`CsvOptions`] for more control over the output):
     /// ```text
     /// bottom_degrees,location,state,surface_degrees,time
-    /// 50.4,santa_monica,CA,65.2,1568756160
+    /// 50.4,santa_monica,CA,65.2,2019-09-17T21:36:00.000000000Z
     /// ```
     ///
     /// JSON:
@@ -116,31 +180,123 @@ impl QueryOutputFormat {
     ///  {"location":"Boston","state":"MA","surface_degrees":50.2,"time":1568756160}
     /// ]
     /// ```
+    ///
+    /// JSON lines:
+    /// ```text
+    /// {"bottom_degrees":50.4,"location":"santa_monica","state":"CA","surface_degrees":65.2,"time":"2019-09-17T21:36:00.000000000Z"}
+    /// {"bottom_degrees":null,"location":"Boston","state":"MA","surface_degrees":50.2,"time":"2019-09-17T21:36:00.000000000Z"}
+    /// ```
+    ///
+    /// InfluxDB 1.x JSON (see [`batches_to_influx_v1_json`] to set the series
+    /// name):
+    /// ```text
+    /// {"results":[{"statement_id":0,"series":[{"columns":["time","bottom_degrees","location","state","surface_degrees"],"values":[["2019-09-17T21:36:00.000000000Z",50.4,"santa_monica","CA",65.2]]}]}]}
+    /// ```
     pub fn format(&self, batches: &[RecordBatch]) -> Result<String> {
         match self {
             Self::Pretty => batches_to_pretty(&batches),
             Self::Csv => batches_to_csv(&batches),
             Self::Json => batches_to_json(&batches),
+            Self::JsonLines => {
+                json_lines::batches_to_json_lines(&batches).map_err(Error::JsonLinesArrow)
+            }
+            Self::InfluxV1 => {
+                batches_to_influx_v1_json(&batches, None).map_err(Error::InfluxV1Arrow)
+            }
         }
     }
+
+    /// Format the [`RecordBatch`]es like [`format`](Self::format), with the
+    /// timestamp columns rendered using `timestamp_format`
+    pub fn format_with_timestamp_format(
+        &self,
+        batches: &[RecordBatch],
+        timestamp_format: TimestampFormat,
+    ) -> Result<String> {
+        let batches = batches
+            .iter()
+            .map(|batch| timestamp_format.convert(batch))
+            .collect::<Result<Vec<_>>>()?;
+        self.format(&batches)
+    }
 }
 
 fn batches_to_pretty(batches: &[RecordBatch]) -> Result<String> {
-    arrow::util::pretty::pretty_format_batches(batches).map_err(Error::PrettyArrow)
+    PrettyOptions::default().format(batches)
 }
 
 fn batches_to_csv(batches: &[RecordBatch]) -> Result<String> {
-    let mut bytes = vec![];
+    CsvOptions::default().format(batches)
+}
 
-    {
-        let mut writer = WriterBuilder::new().has_headers(true).build(&mut bytes);
+/// Options for formatting [`RecordBatch`]es as CSV.
+///
+/// Timestamps are always rendered as RFC3339 in UTC.
+///
+/// ```
+/// # use influxdb_iox_client::format::CsvOptions;
+/// let options = CsvOptions::default().with_delimiter(b'\t').with_header(false);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CsvOptions {
+    delimiter: u8,
+    header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            header: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Set the field delimiter (defaults to `,`)
+    pub fn with_delimiter(self, delimiter: u8) -> Self {
+        Self { delimiter, ..self }
+    }
 
-        for batch in batches {
-            writer.write(batch).map_err(Error::CsvArrow)?;
+    /// Set whether a header row with the column names is written (defaults
+    /// to `true`)
+    pub fn with_header(self, header: bool) -> Self {
+        Self { header, ..self }
+    }
+
+    /// Format the [`RecordBatch`]es as CSV using these options
+    pub fn format(&self, batches: &[RecordBatch]) -> Result<String> {
+        let mut bytes = vec![];
+
+        {
+            let mut writer = self.writer(&mut bytes, self.header);
+
+            for batch in batches {
+                writer.write(batch).map_err(Error::CsvArrow)?;
+            }
         }
+        let csv = String::from_utf8(bytes).map_err(Error::CsvUtf8)?;
+        Ok(csv)
+    }
+
+    /// Create a CSV writer using these options, overriding whether a header
+    /// row is written
+    pub(crate) fn writer<W: std::io::Write>(
+        &self,
+        writer: W,
+        header: bool,
+    ) -> arrow::csv::Writer<W> {
+        WriterBuilder::new()
+            .has_headers(header)
+            .with_delimiter(self.delimiter)
+            .with_timestamp_format(RFC3339_TIMESTAMP_FORMAT.to_string())
+            .build(writer)
+    }
+
+    /// Whether a header row is written
+    pub(crate) fn header(&self) -> bool {
+        self.header
     }
-    let csv = String::from_utf8(bytes).map_err(Error::CsvUtf8)?;
-    Ok(csv)
 }
 
 fn batches_to_json(batches: &[RecordBatch]) -> Result<String> {
@@ -162,6 +318,86 @@ fn batches_to_json(batches: &[RecordBatch]) -> Result<String> {
 mod tests {
     use super::*;
 
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Float64Array, StringArray, TimestampNanosecondArray},
+        datatypes::{DataType, Field, Schema, TimeUnit},
+    };
+
+    fn test_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("region", DataType::Utf8, true),
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("user", DataType::Float64, true),
+        ]));
+
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec![Some("west"), None])),
+                Arc::new(TimestampNanosecondArray::from(vec![
+                    100,
+                    1568756160000000000,
+                ])),
+                Arc::new(Float64Array::from(vec![Some(23.2), None])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_csv() {
+        let batches = vec![test_batch()];
+
+        assert_eq!(
+            QueryOutputFormat::Csv.format(&batches).unwrap(),
+            "region,time,user\n\
+             west,1970-01-01T00:00:00.000000100Z,23.2\n\
+             ,2019-09-17T21:36:00.000000000Z,\n"
+        );
+
+        assert_eq!(
+            CsvOptions::default()
+                .with_delimiter(b'\t')
+                .with_header(false)
+                .format(&batches)
+                .unwrap(),
+            "west\t1970-01-01T00:00:00.000000100Z\t23.2\n\
+             \t2019-09-17T21:36:00.000000000Z\t\n"
+        );
+    }
+
+    #[test]
+    fn test_timestamp_format() {
+        let batches = vec![test_batch()];
+
+        assert_eq!(
+            QueryOutputFormat::Csv
+                .format_with_timestamp_format(&batches, TimestampFormat::Nanoseconds)
+                .unwrap(),
+            "region,time,user\n\
+             west,100,23.2\n\
+             ,1568756160000000000,\n"
+        );
+
+        assert_eq!(
+            QueryOutputFormat::Csv
+                .format_with_timestamp_format(
+                    &batches,
+                    TimestampFormat::Rfc3339(TimestampPrecision::Seconds)
+                )
+                .unwrap(),
+            "region,time,user\n\
+             west,1970-01-01T00:00:00Z,23.2\n\
+             ,2019-09-17T21:36:00Z,\n"
+        );
+    }
+
     #[test]
     fn test_from_str() {
         assert_eq!(
@@ -191,9 +427,23 @@ mod tests {
             QueryOutputFormat::Json
         );
 
+        assert_eq!(
+            QueryOutputFormat::from_str("jsonl").unwrap(),
+            QueryOutputFormat::JsonLines
+        );
+        assert_eq!(
+            QueryOutputFormat::from_str("ndjson").unwrap(),
+            QueryOutputFormat::JsonLines
+        );
+
+        assert_eq!(
+            QueryOutputFormat::from_str("InfluxV1").unwrap(),
+            QueryOutputFormat::InfluxV1
+        );
+
         assert_eq!(
             QueryOutputFormat::from_str("un").unwrap_err().to_string(),
-            "Unknown format type: un. Expected one of 'pretty', 'csv' or 'json'"
+            "Unknown format type: un. Expected one of 'pretty', 'csv', 'json', 'jsonl' or 'influxv1'"
         );
     }
 
@@ -213,5 +463,15 @@ mod tests {
             QueryOutputFormat::from_str(&QueryOutputFormat::Json.to_string()).unwrap(),
             QueryOutputFormat::Json
         );
+
+        assert_eq!(
+            QueryOutputFormat::from_str(&QueryOutputFormat::JsonLines.to_string()).unwrap(),
+            QueryOutputFormat::JsonLines
+        );
+
+        assert_eq!(
+            QueryOutputFormat::from_str(&QueryOutputFormat::InfluxV1.to_string()).unwrap(),
+            QueryOutputFormat::InfluxV1
+        );
     }
 }
@@ -0,0 +1,134 @@
+//! Lossless binary export of query results
+
+use std::io::Write;
+
+use arrow::{datatypes::SchemaRef, ipc::writer::StreamWriter, record_batch::RecordBatch};
+
+use super::{Error, Result};
+
+/// Write `batches` to `writer` as an [Arrow IPC stream].
+///
+/// `schema` is written even if there are no batches so that the output is
+/// always a valid stream.
+///
+/// [Arrow IPC stream]: https://arrow.apache.org/docs/format/Columnar.html#ipc-streaming-format
+pub fn write_arrow_ipc<W: Write>(
+    schema: SchemaRef,
+    batches: &[RecordBatch],
+    writer: W,
+) -> Result<()> {
+    let mut writer = StreamWriter::try_new(writer, &schema).map_err(Error::IpcArrow)?;
+    for batch in batches {
+        writer.write(batch).map_err(Error::IpcArrow)?;
+    }
+    writer.finish().map_err(Error::IpcArrow)
+}
+
+/// Write `batches` to `writer` as a parquet file.
+///
+/// The parquet writer requires a seekable output, so the file is assembled in
+/// memory before being written to `writer`.
+#[cfg(feature = "parquet")]
+pub fn write_parquet<W: Write>(
+    schema: SchemaRef,
+    batches: &[RecordBatch],
+    mut writer: W,
+) -> Result<()> {
+    use parquet::{arrow::ArrowWriter, file::writer::InMemoryWriteableCursor};
+
+    let cursor = InMemoryWriteableCursor::default();
+    {
+        let mut parquet_writer =
+            ArrowWriter::try_new(cursor.clone(), schema, None).map_err(Error::Parquet)?;
+        for batch in batches {
+            parquet_writer.write(batch).map_err(Error::Parquet)?;
+        }
+        parquet_writer.close().map_err(Error::Parquet)?;
+    } // drop the reference to the cursor held by the parquet writer
+
+    writer.write_all(&cursor.data()).map_err(Error::Io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Float64Array, StringArray},
+        datatypes::{DataType, Field, Schema},
+        ipc::reader::StreamReader,
+    };
+
+    fn test_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("region", DataType::Utf8, true),
+            Field::new("user", DataType::Float64, true),
+        ]));
+
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec![Some("west"), None])),
+                Arc::new(Float64Array::from(vec![Some(23.2), Some(21.0)])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_arrow_ipc_roundtrip() {
+        let batch = test_batch();
+
+        let mut bytes = vec![];
+        write_arrow_ipc(batch.schema(), &[batch.clone()], &mut bytes).unwrap();
+
+        let reader = StreamReader::try_new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.schema(), batch.schema());
+
+        let batches: Vec<_> = reader.map(|b| b.unwrap()).collect();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(batches[0].column(0).data(), batch.column(0).data());
+        assert_eq!(batches[0].column(1).data(), batch.column(1).data());
+    }
+
+    #[test]
+    fn test_arrow_ipc_empty() {
+        let schema = test_batch().schema();
+
+        let mut bytes = vec![];
+        write_arrow_ipc(Arc::clone(&schema), &[], &mut bytes).unwrap();
+
+        let reader = StreamReader::try_new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.schema(), schema);
+        assert_eq!(reader.count(), 0);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_roundtrip() {
+        use parquet::{
+            arrow::{ArrowReader, ParquetFileArrowReader},
+            file::serialized_reader::{SerializedFileReader, SliceableCursor},
+        };
+
+        let batch = test_batch();
+
+        let mut bytes = vec![];
+        write_parquet(batch.schema(), &[batch.clone()], &mut bytes).unwrap();
+
+        let file_reader = SerializedFileReader::new(SliceableCursor::new(bytes)).unwrap();
+        let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
+        let batches: Vec<_> = arrow_reader
+            .get_record_reader(1024)
+            .unwrap()
+            .map(|b| b.unwrap())
+            .collect();
+
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(batches[0].schema().fields(), batch.schema().fields());
+    }
+}
@@ -0,0 +1,160 @@
+//! Output in the JSON layout returned by the InfluxDB 1.x `/query` API
+
+use arrow::{datatypes::Schema, error::ArrowError, record_batch::RecordBatch};
+use serde_json::Value;
+
+use super::json_lines::json_value;
+
+/// The response for a query that returned no rows
+pub(crate) const EMPTY_RESPONSE: &str = r#"{"results":[{"statement_id":0}]}"#;
+
+/// Closes the response opened by [`series_prefix`]
+pub(crate) const SERIES_SUFFIX: &str = "]}]}]}";
+
+/// Format `batches` as a single series in an InfluxDB 1.x query response:
+///
+/// ```text
+/// {"results":[{"statement_id":0,"series":[{"name":"cpu","columns":["time","region","user"],"values":[["1970-01-01T00:00:00.000000100Z","west",23.2]]}]}]}
+/// ```
+///
+/// As in InfluxDB 1.x, the `time` column (if any) is always the first column
+/// and the series `name` is omitted if not provided.
+pub fn batches_to_influx_v1_json(
+    batches: &[RecordBatch],
+    series_name: Option<&str>,
+) -> Result<String, ArrowError> {
+    let mut out = String::new();
+    let mut rows_written = 0;
+
+    for batch in batches {
+        if batch.num_rows() == 0 {
+            continue;
+        }
+
+        let schema = batch.schema();
+        if rows_written == 0 {
+            out.push_str(&series_prefix(&schema, series_name));
+        }
+        rows_written += write_rows(batch, &column_order(&schema), rows_written == 0, &mut out)?;
+    }
+
+    match rows_written {
+        0 => out.push_str(EMPTY_RESPONSE),
+        _ => out.push_str(SERIES_SUFFIX),
+    }
+
+    Ok(out)
+}
+
+/// Returns the column indexes of `schema` in output order, with `time` first
+pub(crate) fn column_order(schema: &Schema) -> Vec<usize> {
+    let mut order: Vec<_> = (0..schema.fields().len()).collect();
+    if let Ok(time_index) = schema.index_of("time") {
+        order.remove(time_index);
+        order.insert(0, time_index);
+    }
+    order
+}
+
+/// Opens the response with the series description, up to the start of the
+/// `values` array
+pub(crate) fn series_prefix(schema: &Schema, series_name: Option<&str>) -> String {
+    let columns: Vec<_> = column_order(schema)
+        .into_iter()
+        .map(|i| Value::from(schema.field(i).name().as_str()))
+        .collect();
+
+    let name = match series_name {
+        Some(name) => format!(r#""name":{},"#, Value::from(name)),
+        None => String::new(),
+    };
+
+    format!(
+        r#"{{"results":[{{"statement_id":0,"series":[{{{}"columns":{},"values":["#,
+        name,
+        Value::Array(columns)
+    )
+}
+
+/// Append the rows of `batch` as JSON arrays to `out`, returning the number of
+/// rows written. If `first` is false the rows are preceded by a separator.
+pub(crate) fn write_rows(
+    batch: &RecordBatch,
+    order: &[usize],
+    first: bool,
+    out: &mut String,
+) -> Result<usize, ArrowError> {
+    for row in 0..batch.num_rows() {
+        if row > 0 || !first {
+            out.push(',');
+        }
+
+        let values = order
+            .iter()
+            .map(|i| json_value(batch.column(*i), row))
+            .collect::<Result<Vec<_>, _>>()?;
+        out.push_str(&Value::Array(values).to_string());
+    }
+
+    Ok(batch.num_rows())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Float64Array, StringArray, TimestampNanosecondArray},
+        datatypes::{DataType, Field, TimeUnit},
+    };
+
+    fn test_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("region", DataType::Utf8, true),
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("user", DataType::Float64, true),
+        ]));
+
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec![Some("west"), None])),
+                Arc::new(TimestampNanosecondArray::from(vec![100, 200])),
+                Arc::new(Float64Array::from(vec![Some(23.2), None])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_influx_v1_json() {
+        let batch = test_batch();
+
+        let expected = concat!(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"cpu","columns":["time","region","user"],"values":["#,
+            r#"["1970-01-01T00:00:00.000000100Z","west",23.2],"#,
+            r#"["1970-01-01T00:00:00.000000200Z",null,null],"#,
+            r#"["1970-01-01T00:00:00.000000100Z","west",23.2],"#,
+            r#"["1970-01-01T00:00:00.000000200Z",null,null]"#,
+            r#"]}]}]}"#
+        );
+        assert_eq!(
+            batches_to_influx_v1_json(&[batch.clone(), batch], Some("cpu")).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_influx_v1_json_empty() {
+        assert_eq!(
+            batches_to_influx_v1_json(&[], None).unwrap(),
+            r#"{"results":[{"statement_id":0}]}"#
+        );
+    }
+}
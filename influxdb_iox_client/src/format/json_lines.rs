@@ -0,0 +1,181 @@
+//! Newline delimited JSON output, one object per row
+
+use arrow::{
+    array::*, datatypes::*, error::ArrowError, record_batch::RecordBatch,
+    util::display::array_value_to_string,
+};
+use serde_json::{Map, Number, Value};
+
+use super::RFC3339_TIMESTAMP_FORMAT;
+
+/// Format `batches` as newline delimited JSON.
+///
+/// Unlike the arrow JSON writer, NULL values are written as explicit `null`s,
+/// timestamps are rendered as RFC3339 strings and dictionary encoded columns
+/// are written as their decoded values.
+pub(crate) fn batches_to_json_lines(batches: &[RecordBatch]) -> Result<String, ArrowError> {
+    let mut out = String::new();
+
+    for batch in batches {
+        let schema = batch.schema();
+
+        for row in 0..batch.num_rows() {
+            let mut object = Map::with_capacity(batch.num_columns());
+            for (field, column) in schema.fields().iter().zip(batch.columns()) {
+                object.insert(field.name().clone(), json_value(column, row)?);
+            }
+
+            out.push_str(&Value::Object(object).to_string());
+            out.push('\n');
+        }
+    }
+
+    Ok(out)
+}
+
+macro_rules! primitive_value {
+    ($array:expr, $row:expr, $ARRAY_TYPE:ty) => {{
+        let array = $array.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+        Value::from(array.value($row))
+    }};
+}
+
+macro_rules! timestamp_value {
+    ($array:expr, $row:expr, $ARRAY_TYPE:ty) => {{
+        let array = $array.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+        array
+            .value_as_datetime($row)
+            .map(|dt| Value::from(dt.format(RFC3339_TIMESTAMP_FORMAT).to_string()))
+            .unwrap_or(Value::Null)
+    }};
+}
+
+macro_rules! dictionary_value {
+    ($array:expr, $row:expr, $KEY_TYPE:ty) => {{
+        let array = $array
+            .as_any()
+            .downcast_ref::<DictionaryArray<$KEY_TYPE>>()
+            .unwrap();
+        let key = array.keys().value($row) as usize;
+        json_value(array.values(), key)?
+    }};
+}
+
+/// Convert the value at `row` in `array` to a JSON value
+pub(crate) fn json_value(array: &ArrayRef, row: usize) -> Result<Value, ArrowError> {
+    if array.is_null(row) {
+        return Ok(Value::Null);
+    }
+
+    let value = match array.data_type() {
+        DataType::Boolean => primitive_value!(array, row, BooleanArray),
+        DataType::Int8 => primitive_value!(array, row, Int8Array),
+        DataType::Int16 => primitive_value!(array, row, Int16Array),
+        DataType::Int32 => primitive_value!(array, row, Int32Array),
+        DataType::Int64 => primitive_value!(array, row, Int64Array),
+        DataType::UInt8 => primitive_value!(array, row, UInt8Array),
+        DataType::UInt16 => primitive_value!(array, row, UInt16Array),
+        DataType::UInt32 => primitive_value!(array, row, UInt32Array),
+        DataType::UInt64 => primitive_value!(array, row, UInt64Array),
+        DataType::Float32 => {
+            let array = array.as_any().downcast_ref::<Float32Array>().unwrap();
+            float_value(array.value(row) as f64)
+        }
+        DataType::Float64 => {
+            let array = array.as_any().downcast_ref::<Float64Array>().unwrap();
+            float_value(array.value(row))
+        }
+        DataType::Utf8 => primitive_value!(array, row, StringArray),
+        DataType::LargeUtf8 => primitive_value!(array, row, LargeStringArray),
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            timestamp_value!(array, row, TimestampSecondArray)
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            timestamp_value!(array, row, TimestampMillisecondArray)
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            timestamp_value!(array, row, TimestampMicrosecondArray)
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            timestamp_value!(array, row, TimestampNanosecondArray)
+        }
+        DataType::Dictionary(key_type, _) => match key_type.as_ref() {
+            DataType::Int8 => dictionary_value!(array, row, Int8Type),
+            DataType::Int16 => dictionary_value!(array, row, Int16Type),
+            DataType::Int32 => dictionary_value!(array, row, Int32Type),
+            DataType::Int64 => dictionary_value!(array, row, Int64Type),
+            DataType::UInt8 => dictionary_value!(array, row, UInt8Type),
+            DataType::UInt16 => dictionary_value!(array, row, UInt16Type),
+            DataType::UInt32 => dictionary_value!(array, row, UInt32Type),
+            DataType::UInt64 => dictionary_value!(array, row, UInt64Type),
+            _ => Value::from(array_value_to_string(array, row)?),
+        },
+        _ => Value::from(array_value_to_string(array, row)?),
+    };
+
+    Ok(value)
+}
+
+/// JSON has no representation for NaN or infinity, so they are written as
+/// `null`
+fn float_value(v: f64) -> Value {
+    Number::from_f64(v)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    #[test]
+    fn test_json_lines() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "region",
+                DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+                true,
+            ),
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("user", DataType::Float64, true),
+            Field::new("active", DataType::Boolean, true),
+        ]));
+
+        let region: DictionaryArray<Int32Type> =
+            vec![Some("west"), None, Some("west")].into_iter().collect();
+
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(region),
+                Arc::new(TimestampNanosecondArray::from(vec![100, 200, 300])),
+                Arc::new(Float64Array::from(vec![Some(23.2), None, Some(f64::NAN)])),
+                Arc::new(BooleanArray::from(vec![Some(true), Some(false), None])),
+            ],
+        )
+        .unwrap();
+
+        let output = batches_to_json_lines(&[batch]).unwrap();
+        let rows: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(
+            rows,
+            vec![
+                json!({"region": "west", "time": "1970-01-01T00:00:00.000000100Z", "user": 23.2, "active": true}),
+                json!({"region": null, "time": "1970-01-01T00:00:00.000000200Z", "user": null, "active": false}),
+                json!({"region": "west", "time": "1970-01-01T00:00:00.000000300Z", "user": null, "active": null}),
+            ]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Pretty printed tables with configurable rendering
+
+use arrow::{record_batch::RecordBatch, util::display::array_value_to_string};
+use prettytable::{format, Cell, Row, Table};
+
+use super::{Error, Result};
+
+/// Options for formatting [`RecordBatch`]es as a pretty printed table.
+///
+/// The default options produce the same output as the arrow pretty printer.
+///
+/// ```
+/// # use influxdb_iox_client::format::PrettyOptions;
+/// let options = PrettyOptions::default()
+///     .with_null("NULL")
+///     .with_max_width(Some(20))
+///     .with_max_rows(Some(100));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrettyOptions {
+    null: String,
+    max_width: Option<usize>,
+    max_rows: Option<usize>,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        Self {
+            null: String::new(),
+            max_width: None,
+            max_rows: None,
+        }
+    }
+}
+
+impl PrettyOptions {
+    /// Set the text used to render NULL values (defaults to an empty cell)
+    pub fn with_null(self, null: impl Into<String>) -> Self {
+        Self {
+            null: null.into(),
+            ..self
+        }
+    }
+
+    /// Set the maximum width in characters of a cell. Longer values are
+    /// truncated and end with an ellipsis. `None` means unlimited.
+    pub fn with_max_width(self, max_width: Option<usize>) -> Self {
+        Self { max_width, ..self }
+    }
+
+    /// Set the maximum number of rows rendered. If there are more rows, a
+    /// footer with the number of omitted rows is added below the table.
+    /// `None` means unlimited.
+    pub fn with_max_rows(self, max_rows: Option<usize>) -> Self {
+        Self { max_rows, ..self }
+    }
+
+    /// Format the [`RecordBatch`]es as a table using these options
+    pub fn format(&self, batches: &[RecordBatch]) -> Result<String> {
+        let total_rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+        let (mut output, rendered_rows) = self.render(batches, true, self.max_rows)?;
+        output.push_str(&more_rows_footer(total_rows - rendered_rows));
+        Ok(output)
+    }
+
+    /// Render at most `max_rows` rows of `batches` as a table, optionally with
+    /// a header row. Returns the table and the number of rows rendered.
+    pub(crate) fn render(
+        &self,
+        batches: &[RecordBatch],
+        header: bool,
+        max_rows: Option<usize>,
+    ) -> Result<(String, usize)> {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+
+        if batches.is_empty() {
+            return Ok((table.to_string(), 0));
+        }
+
+        if header {
+            let schema = batches[0].schema();
+            let titles = schema
+                .fields()
+                .iter()
+                .map(|field| Cell::new(&self.truncate(field.name())))
+                .collect();
+            table.set_titles(Row::new(titles));
+        }
+
+        let max_rows = max_rows.unwrap_or(usize::MAX);
+
+        let mut rendered_rows = 0;
+        'outer: for batch in batches {
+            for row in 0..batch.num_rows() {
+                if rendered_rows >= max_rows {
+                    break 'outer;
+                }
+
+                let cells = batch
+                    .columns()
+                    .iter()
+                    .map(|column| {
+                        let value = if column.is_null(row) {
+                            self.null.clone()
+                        } else {
+                            array_value_to_string(column, row).map_err(Error::PrettyArrow)?
+                        };
+                        Ok(Cell::new(&self.truncate(&value)))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                table.add_row(Row::new(cells));
+                rendered_rows += 1;
+            }
+        }
+
+        Ok((table.to_string(), rendered_rows))
+    }
+
+    /// The maximum number of rows to render, if any
+    pub(crate) fn max_rows(&self) -> Option<usize> {
+        self.max_rows
+    }
+
+    /// Truncate `value` to the maximum cell width, if any
+    fn truncate(&self, value: &str) -> String {
+        match self.max_width {
+            Some(max_width) if value.chars().count() > max_width => {
+                let mut truncated: String =
+                    value.chars().take(max_width.saturating_sub(1)).collect();
+                truncated.push('…');
+                truncated
+            }
+            _ => value.to_string(),
+        }
+    }
+}
+
+/// The footer added below a table when `omitted_rows` rows were not rendered
+pub(crate) fn more_rows_footer(omitted_rows: usize) -> String {
+    match omitted_rows {
+        0 => String::new(),
+        1 => "… 1 more row\n".to_string(),
+        n => format!("… {} more rows\n", n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Float64Array, StringArray},
+        datatypes::{DataType, Field, Schema},
+    };
+
+    fn test_batches() -> Vec<RecordBatch> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("location", DataType::Utf8, true),
+            Field::new("temp", DataType::Float64, true),
+        ]));
+
+        let batch = |locations: Vec<Option<&str>>, temps: Vec<Option<f64>>| {
+            RecordBatch::try_new(
+                Arc::clone(&schema),
+                vec![
+                    Arc::new(StringArray::from(locations)),
+                    Arc::new(Float64Array::from(temps)),
+                ],
+            )
+            .unwrap()
+        };
+
+        vec![
+            batch(
+                vec![Some("santa_monica"), None],
+                vec![Some(65.2), Some(50.4)],
+            ),
+            batch(vec![Some("Boston")], vec![None]),
+        ]
+    }
+
+    #[test]
+    fn test_default_matches_arrow() {
+        let batches = test_batches();
+
+        assert_eq!(
+            PrettyOptions::default().format(&batches).unwrap(),
+            arrow::util::pretty::pretty_format_batches(&batches).unwrap()
+        );
+        assert_eq!(
+            PrettyOptions::default().format(&[]).unwrap(),
+            arrow::util::pretty::pretty_format_batches(&[]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_options() {
+        let batches = test_batches();
+
+        let output = PrettyOptions::default()
+            .with_null("NULL")
+            .with_max_width(Some(6))
+            .with_max_rows(Some(2))
+            .format(&batches)
+            .unwrap();
+
+        let expected = vec![
+            "+--------+------+",
+            "| locat… | temp |",
+            "+--------+------+",
+            "| santa… | 65.2 |",
+            "| NULL   | 50.4 |",
+            "+--------+------+",
+            "… 1 more row",
+        ];
+        assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+
+        let output = PrettyOptions::default()
+            .with_null("NULL")
+            .with_max_rows(Some(0))
+            .format(&batches)
+            .unwrap();
+        assert!(output.ends_with("… 3 more rows\n"), "{}", output);
+    }
+}
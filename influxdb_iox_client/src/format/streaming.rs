@@ -0,0 +1,324 @@
+//! Incremental output of query results
+
+use std::io::Write;
+
+use arrow::{json::writer::record_batches_to_json_rows, record_batch::RecordBatch};
+use serde_json::Value;
+
+use super::{
+    influx_v1, json_lines, pretty::more_rows_footer, CsvOptions, Error, PrettyOptions,
+    QueryOutputFormat, Result,
+};
+
+/// Writes [`RecordBatch`]es to `writer` as they arrive rather than buffering
+/// the entire result, so that memory use is bounded by the size of a single
+/// batch.
+///
+/// The output is the same as [`QueryOutputFormat::format`] on all the
+/// batches, except for the pretty format, where each batch is rendered with
+/// its own column widths (the header is only written once).
+///
+/// ```
+/// # use influxdb_iox_client::format::{QueryOutputFormat, StreamingFormatter};
+/// let mut formatter = StreamingFormatter::new(QueryOutputFormat::Csv, std::io::stdout());
+/// // call `formatter.write(&batch)` for each batch received
+/// formatter.finish().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct StreamingFormatter<W: Write> {
+    format: QueryOutputFormat,
+    pretty_options: PrettyOptions,
+    csv_options: CsvOptions,
+    timestamp_format: Option<TimestampFormat>,
+    writer: W,
+    /// Whether any output has been written
+    started: bool,
+    rows_written: usize,
+    rows_omitted: usize,
+}
+
+impl<W: Write> StreamingFormatter<W> {
+    /// Create a new formatter writing `format` to `writer`
+    pub fn new(format: QueryOutputFormat, writer: W) -> Self {
+        Self {
+            format,
+            pretty_options: Default::default(),
+            csv_options: Default::default(),
+            timestamp_format: None,
+            writer,
+            started: false,
+            rows_written: 0,
+            rows_omitted: 0,
+        }
+    }
+
+    /// Set the options used for the pretty format
+    pub fn with_pretty_options(self, pretty_options: PrettyOptions) -> Self {
+        Self {
+            pretty_options,
+            ..self
+        }
+    }
+
+    /// Set the options used for the CSV format
+    pub fn with_csv_options(self, csv_options: CsvOptions) -> Self {
+        Self {
+            csv_options,
+            ..self
+        }
+    }
+
+    /// Convert timestamp columns using `timestamp_format` before formatting
+    /// each batch
+    pub fn with_timestamp_format(self, timestamp_format: TimestampFormat) -> Self {
+        Self {
+            timestamp_format: Some(timestamp_format),
+            ..self
+        }
+    }
+
+    /// Returns a mutable reference to the underlying writer, e.g. to drain
+    /// the output written so far
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Format `batch` and write it to the output
+    pub fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let converted;
+        let batch = match &self.timestamp_format {
+            Some(timestamp_format) => {
+                converted = timestamp_format.convert(batch)?;
+                &converted
+            }
+            None => batch,
+        };
+        let batches = std::slice::from_ref(batch);
+
+        match self.format {
+            QueryOutputFormat::Pretty => {
+                if self.started && batch.num_rows() == 0 {
+                    return Ok(());
+                }
+
+                let max_rows = self
+                    .pretty_options
+                    .max_rows()
+                    .map(|max_rows| max_rows.saturating_sub(self.rows_written));
+                if max_rows == Some(0) {
+                    self.rows_omitted += batch.num_rows();
+                    return Ok(());
+                }
+
+                let (table, rendered_rows) =
+                    self.pretty_options
+                        .render(batches, !self.started, max_rows)?;
+                self.rows_written += rendered_rows;
+                self.rows_omitted += batch.num_rows() - rendered_rows;
+
+                // Continue the previous table by dropping the top border, as
+                // the previous table's bottom border takes its place
+                let table = match self.started {
+                    true => table.splitn(2, '\n').nth(1).unwrap_or_default(),
+                    false => table.as_str(),
+                };
+                self.writer.write_all(table.as_bytes()).map_err(Error::Io)?;
+            }
+            QueryOutputFormat::Csv => {
+                let header = self.csv_options.header() && !self.started;
+                let mut writer = self.csv_options.writer(&mut self.writer, header);
+                writer.write(batch).map_err(Error::CsvArrow)?;
+            }
+            QueryOutputFormat::Json => {
+                for row in record_batches_to_json_rows(batches) {
+                    let separator: &[u8] = match self.rows_written {
+                        0 => b"[",
+                        _ => b",",
+                    };
+                    self.writer.write_all(separator).map_err(Error::Io)?;
+                    serde_json::to_writer(&mut self.writer, &Value::Object(row))
+                        .map_err(|e| Error::Io(e.into()))?;
+                    self.rows_written += 1;
+                }
+            }
+            QueryOutputFormat::JsonLines => {
+                let json =
+                    json_lines::batches_to_json_lines(batches).map_err(Error::JsonLinesArrow)?;
+                self.writer.write_all(json.as_bytes()).map_err(Error::Io)?;
+            }
+            QueryOutputFormat::InfluxV1 => {
+                if batch.num_rows() == 0 {
+                    return Ok(());
+                }
+
+                let schema = batch.schema();
+                let mut json = String::new();
+                if self.rows_written == 0 {
+                    json.push_str(&influx_v1::series_prefix(&schema, None));
+                }
+                let order = influx_v1::column_order(&schema);
+                self.rows_written +=
+                    influx_v1::write_rows(batch, &order, self.rows_written == 0, &mut json)
+                        .map_err(Error::InfluxV1Arrow)?;
+
+                self.writer.write_all(json.as_bytes()).map_err(Error::Io)?;
+            }
+        }
+
+        self.started = true;
+        self.writer.flush().map_err(Error::Io)
+    }
+
+    /// Write any trailing output, flush and return the underlying writer
+    pub fn finish(mut self) -> Result<W> {
+        match self.format {
+            QueryOutputFormat::Pretty => {
+                let trailer = match self.started {
+                    true => more_rows_footer(self.rows_omitted),
+                    false => self.pretty_options.format(&[])?,
+                };
+                self.writer
+                    .write_all(trailer.as_bytes())
+                    .map_err(Error::Io)?;
+            }
+            QueryOutputFormat::Json => {
+                let trailer: &[u8] = match self.rows_written {
+                    0 => b"[]",
+                    _ => b"]",
+                };
+                self.writer.write_all(trailer).map_err(Error::Io)?;
+            }
+            QueryOutputFormat::InfluxV1 => {
+                let trailer = match self.rows_written {
+                    0 => influx_v1::EMPTY_RESPONSE,
+                    _ => influx_v1::SERIES_SUFFIX,
+                };
+                self.writer
+                    .write_all(trailer.as_bytes())
+                    .map_err(Error::Io)?;
+            }
+            QueryOutputFormat::Csv | QueryOutputFormat::JsonLines => {}
+        }
+
+        self.writer.flush().map_err(Error::Io)?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Float64Array, StringArray},
+        datatypes::{DataType, Field, Schema},
+    };
+
+    fn test_batches() -> Vec<RecordBatch> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("region", DataType::Utf8, true),
+            Field::new("user", DataType::Float64, true),
+        ]));
+
+        let batch = |regions: Vec<Option<&str>>, users: Vec<Option<f64>>| {
+            RecordBatch::try_new(
+                Arc::clone(&schema),
+                vec![
+                    Arc::new(StringArray::from(regions)),
+                    Arc::new(Float64Array::from(users)),
+                ],
+            )
+            .unwrap()
+        };
+
+        vec![
+            batch(vec![Some("west"), None], vec![Some(23.2), Some(21.0)]),
+            batch(vec![], vec![]),
+            batch(vec![Some("east")], vec![None]),
+        ]
+    }
+
+    fn stream(format: QueryOutputFormat, batches: &[RecordBatch]) -> String {
+        let mut formatter = StreamingFormatter::new(format, vec![]);
+        for batch in batches {
+            formatter.write(batch).unwrap();
+        }
+        String::from_utf8(formatter.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_matches_buffered() {
+        let batches = test_batches();
+
+        for format in &[
+            QueryOutputFormat::Csv,
+            QueryOutputFormat::Json,
+            QueryOutputFormat::JsonLines,
+            QueryOutputFormat::InfluxV1,
+        ] {
+            assert_eq!(
+                stream(*format, &batches),
+                format.format(&batches).unwrap(),
+                "format: {}",
+                format
+            );
+            assert_eq!(
+                stream(*format, &[]),
+                format.format(&[]).unwrap(),
+                "format: {}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn test_pretty() {
+        let batches = test_batches();
+
+        let expected = vec![
+            "+--------+------+",
+            "| region | user |",
+            "+--------+------+",
+            "| west   | 23.2 |",
+            "|        | 21   |",
+            "+--------+------+",
+            "| east |  |",
+            "+------+--+",
+        ];
+        assert_eq!(
+            stream(QueryOutputFormat::Pretty, &batches)
+                .lines()
+                .collect::<Vec<_>>(),
+            expected
+        );
+
+        assert_eq!(
+            stream(QueryOutputFormat::Pretty, &[]),
+            QueryOutputFormat::Pretty.format(&[]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pretty_max_rows() {
+        let batches = test_batches();
+
+        let mut formatter = StreamingFormatter::new(QueryOutputFormat::Pretty, vec![])
+            .with_pretty_options(PrettyOptions::default().with_max_rows(Some(1)));
+        for batch in &batches {
+            formatter.write(batch).unwrap();
+        }
+        let output = String::from_utf8(formatter.finish().unwrap()).unwrap();
+
+        let expected = vec![
+            "+--------+------+",
+            "| region | user |",
+            "+--------+------+",
+            "| west   | 23.2 |",
+            "+--------+------+",
+            "… 2 more rows",
+        ];
+        assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,253 @@
+//! Rendering of timestamp columns
+
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, StringArray, TimestampNanosecondArray},
+    compute::cast,
+    datatypes::{DataType, Field, Schema, TimeUnit},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+
+use super::{Error, Result};
+
+/// The number of fractional second digits used when rendering timestamps as
+/// RFC3339
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimestampPrecision {
+    /// No fractional seconds
+    Seconds,
+    /// 3 fractional digits
+    Milliseconds,
+    /// 6 fractional digits
+    Microseconds,
+    /// 9 fractional digits
+    Nanoseconds,
+}
+
+impl TimestampPrecision {
+    /// The `strftime` format rendering timestamps as RFC3339 (UTC) with this
+    /// precision
+    fn strftime_format(&self) -> &'static str {
+        match self {
+            Self::Seconds => "%Y-%m-%dT%H:%M:%SZ",
+            Self::Milliseconds => "%Y-%m-%dT%H:%M:%S%.3fZ",
+            Self::Microseconds => "%Y-%m-%dT%H:%M:%S%.6fZ",
+            Self::Nanoseconds => super::RFC3339_TIMESTAMP_FORMAT,
+        }
+    }
+}
+
+/// How timestamp columns are rendered by the text formatters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimestampFormat {
+    /// RFC3339 in UTC, e.g. `2019-09-17T21:36:00.000Z`
+    Rfc3339(TimestampPrecision),
+    /// Integer nanoseconds since the epoch, e.g. `1568756160000000000`
+    Nanoseconds,
+}
+
+impl Default for TimestampFormat {
+    fn default() -> Self {
+        Self::Rfc3339(TimestampPrecision::Nanoseconds)
+    }
+}
+
+impl Display for TimestampFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rfc3339(TimestampPrecision::Seconds) => write!(f, "rfc3339-s"),
+            Self::Rfc3339(TimestampPrecision::Milliseconds) => write!(f, "rfc3339-ms"),
+            Self::Rfc3339(TimestampPrecision::Microseconds) => write!(f, "rfc3339-us"),
+            Self::Rfc3339(TimestampPrecision::Nanoseconds) => write!(f, "rfc3339"),
+            Self::Nanoseconds => write!(f, "nanos"),
+        }
+    }
+}
+
+impl FromStr for TimestampFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "rfc3339" | "rfc3339-ns" => Ok(Self::Rfc3339(TimestampPrecision::Nanoseconds)),
+            "rfc3339-us" => Ok(Self::Rfc3339(TimestampPrecision::Microseconds)),
+            "rfc3339-ms" => Ok(Self::Rfc3339(TimestampPrecision::Milliseconds)),
+            "rfc3339-s" => Ok(Self::Rfc3339(TimestampPrecision::Seconds)),
+            "nanos" => Ok(Self::Nanoseconds),
+            _ => Err(Error::InvalidTimestampFormat(s.to_string())),
+        }
+    }
+}
+
+impl TimestampFormat {
+    /// Return a copy of `batch` with all timestamp columns converted to
+    /// strings (for RFC3339) or 64 bit integers (for nanoseconds), so that
+    /// they are rendered the same way by all the text formatters.
+    pub fn convert(&self, batch: &RecordBatch) -> Result<RecordBatch> {
+        self.try_convert(batch).map_err(Error::TimestampArrow)
+    }
+
+    fn try_convert(&self, batch: &RecordBatch) -> Result<RecordBatch, ArrowError> {
+        let schema = batch.schema();
+        let mut fields = Vec::with_capacity(schema.fields().len());
+        let mut columns = Vec::with_capacity(batch.num_columns());
+
+        for (field, column) in schema.fields().iter().zip(batch.columns()) {
+            match field.data_type() {
+                DataType::Timestamp(_, _) => {
+                    let column = self.convert_column(column)?;
+                    fields.push(Field::new(
+                        field.name(),
+                        column.data_type().clone(),
+                        field.is_nullable(),
+                    ));
+                    columns.push(column);
+                }
+                _ => {
+                    fields.push(field.clone());
+                    columns.push(Arc::clone(column));
+                }
+            }
+        }
+
+        let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+        RecordBatch::try_new(Arc::new(schema), columns)
+    }
+
+    fn convert_column(&self, column: &ArrayRef) -> Result<ArrayRef, ArrowError> {
+        let nanos = cast(column, &DataType::Timestamp(TimeUnit::Nanosecond, None))?;
+
+        match self {
+            Self::Nanoseconds => cast(&nanos, &DataType::Int64),
+            Self::Rfc3339(precision) => {
+                let nanos = nanos
+                    .as_any()
+                    .downcast_ref::<TimestampNanosecondArray>()
+                    .expect("cast to nanosecond timestamps");
+                let format = precision.strftime_format();
+
+                let strings: StringArray = (0..nanos.len())
+                    .map(|i| {
+                        if nanos.is_null(i) {
+                            return None;
+                        }
+                        nanos
+                            .value_as_datetime(i)
+                            .map(|dt| dt.format(format).to_string())
+                    })
+                    .collect();
+                Ok(Arc::new(strings))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow::array::{Float64Array, Int64Array};
+
+    fn test_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                true,
+            ),
+            Field::new("user", DataType::Float64, true),
+        ]));
+
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(TimestampNanosecondArray::from(vec![
+                    Some(1568756160123456789),
+                    None,
+                ])),
+                Arc::new(Float64Array::from(vec![Some(23.2), None])),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn time_strings(batch: &RecordBatch) -> Vec<Option<String>> {
+        let array = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        (0..array.len())
+            .map(|i| match array.is_null(i) {
+                true => None,
+                false => Some(array.value(i).to_string()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        let batch = test_batch();
+
+        let cases = vec![
+            (TimestampPrecision::Seconds, "2019-09-17T21:36:00Z"),
+            (TimestampPrecision::Milliseconds, "2019-09-17T21:36:00.123Z"),
+            (
+                TimestampPrecision::Microseconds,
+                "2019-09-17T21:36:00.123456Z",
+            ),
+            (
+                TimestampPrecision::Nanoseconds,
+                "2019-09-17T21:36:00.123456789Z",
+            ),
+        ];
+
+        for (precision, expected) in cases {
+            let converted = TimestampFormat::Rfc3339(precision).convert(&batch).unwrap();
+            assert_eq!(
+                time_strings(&converted),
+                vec![Some(expected.to_string()), None]
+            );
+            // other columns are untouched
+            assert_eq!(converted.column(1).data(), batch.column(1).data());
+        }
+    }
+
+    #[test]
+    fn test_nanoseconds() {
+        let converted = TimestampFormat::Nanoseconds.convert(&test_batch()).unwrap();
+
+        assert_eq!(converted.schema().field(0).data_type(), &DataType::Int64);
+        let times = converted
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(times.value(0), 1568756160123456789);
+        assert!(times.is_null(1));
+    }
+
+    #[test]
+    fn test_from_str_roundtrip() {
+        for format in &[
+            TimestampFormat::Rfc3339(TimestampPrecision::Seconds),
+            TimestampFormat::Rfc3339(TimestampPrecision::Milliseconds),
+            TimestampFormat::Rfc3339(TimestampPrecision::Microseconds),
+            TimestampFormat::Rfc3339(TimestampPrecision::Nanoseconds),
+            TimestampFormat::Nanoseconds,
+        ] {
+            assert_eq!(
+                TimestampFormat::from_str(&format.to_string()).unwrap(),
+                *format
+            );
+        }
+
+        assert_eq!(
+            TimestampFormat::from_str("unix").unwrap_err().to_string(),
+            "Unknown timestamp format: unix. Expected one of 'rfc3339', 'rfc3339-s', \
+             'rfc3339-ms', 'rfc3339-us', 'rfc3339-ns' or 'nanos'"
+        );
+    }
+}
@@ -17,6 +17,9 @@ pub use generated_types::{protobuf_type_url, protobuf_type_url_eq};
 
 pub use client::*;
 
+/// Traits abstracting over the gRPC clients
+pub mod api;
+
 /// Builder for constructing connections for use with the various gRPC clients
 pub mod connection;
 
@@ -24,4 +27,12 @@ pub mod connection;
 /// Output formatting utilities
 pub mod format;
 
+#[cfg(feature = "mock")]
+/// In-memory implementation of the client traits for testing
+pub mod mock;
+
+#[cfg(feature = "blocking")]
+/// Blocking wrappers around the async clients
+pub mod blocking;
+
 mod client;
@@ -0,0 +1,873 @@
+//! An in-memory implementation of the client traits in [`crate::api`] for use
+//! in unit tests.
+//!
+//! ```
+//! # #[tokio::main]
+//! # async fn main() {
+//! use influxdb_iox_client::{api::WriteApi, mock::MockClient};
+//!
+//! let mut client = MockClient::new();
+//! client.add_database("bananas");
+//!
+//! client.write("bananas", "cpu,region=west user=23.2 100").await.unwrap();
+//! assert_eq!(client.written_lines("bananas"), vec!["cpu,region=west user=23.2 100"]);
+//! # }
+//! ```
+use std::{
+    collections::BTreeMap,
+    num::NonZeroU32,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use async_trait::async_trait;
+
+use ::generated_types::google::longrunning::Operation;
+
+use crate::api::{ManagementApi, WriteApi};
+use crate::management::{generated_types::*, *};
+use crate::write::WriteError;
+
+#[derive(Debug, Default, Clone)]
+struct MockDatabase {
+    rules: DatabaseRules,
+    lines: Vec<String>,
+    entries: Vec<Vec<u8>>,
+    chunks: Vec<Chunk>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    server_id: Option<NonZeroU32>,
+    ready: bool,
+    databases: BTreeMap<String, MockDatabase>,
+    /// Deleted databases that can still be restored
+    deleted: BTreeMap<String, MockDatabase>,
+    /// Backups keyed by (location, database name)
+    backups: BTreeMap<(String, String), MockDatabase>,
+    remotes: BTreeMap<u32, String>,
+    next_operation_id: usize,
+    #[cfg(feature = "flight")]
+    query_results: BTreeMap<(String, String), Vec<arrow::record_batch::RecordBatch>>,
+}
+
+/// An in-memory stand-in for an IOx server.
+///
+/// Clones share the same state, so a test can hand one clone to the code
+/// under test and use another to set up and inspect the server state.
+#[derive(Debug, Clone, Default)]
+pub struct MockClient {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockClient {
+    /// Create a new mock with server ID 1 and no databases
+    pub fn new() -> Self {
+        let client = Self::default();
+        {
+            let mut state = client.state();
+            state.server_id = NonZeroU32::new(1);
+            state.ready = true;
+        }
+        client
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().expect("mock state poisoned")
+    }
+
+    /// Create a database named `name` with default rules
+    pub fn add_database(&self, name: impl Into<String>) {
+        let name = name.into();
+        let rules = DatabaseRules {
+            name: name.clone(),
+            ..Default::default()
+        };
+        self.state().databases.insert(
+            name,
+            MockDatabase {
+                rules,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Add `chunk` to the database `db_name`, which must exist
+    pub fn add_chunk(&self, db_name: &str, chunk: Chunk) {
+        self.state()
+            .databases
+            .get_mut(db_name)
+            .expect("database not found")
+            .chunks
+            .push(chunk)
+    }
+
+    /// Return the line protocol lines written to `db_name`
+    pub fn written_lines(&self, db_name: &str) -> Vec<String> {
+        self.state()
+            .databases
+            .get(db_name)
+            .map(|db| db.lines.clone())
+            .unwrap_or_default()
+    }
+
+    /// Return the encoded entries written to `db_name`
+    pub fn written_entries(&self, db_name: &str) -> Vec<Vec<u8>> {
+        self.state()
+            .databases
+            .get(db_name)
+            .map(|db| db.entries.clone())
+            .unwrap_or_default()
+    }
+
+    /// Return whether the mock is currently marked as serving
+    pub fn is_ready(&self) -> bool {
+        self.state().ready
+    }
+
+    /// Set the results returned by a query for `sql_query` against
+    /// `database_name`
+    #[cfg(feature = "flight")]
+    pub fn add_query_results(
+        &self,
+        database_name: impl Into<String>,
+        sql_query: impl Into<String>,
+        batches: Vec<arrow::record_batch::RecordBatch>,
+    ) {
+        self.state()
+            .query_results
+            .insert((database_name.into(), sql_query.into()), batches);
+    }
+
+    fn next_operation(&self) -> Operation {
+        let mut state = self.state();
+        let id = state.next_operation_id;
+        state.next_operation_id += 1;
+
+        Operation {
+            name: id.to_string(),
+            done: true,
+            ..Default::default()
+        }
+    }
+}
+
+fn not_found(what: &str, name: &str) -> tonic::Status {
+    tonic::Status::not_found(format!("{} not found: {}", what, name))
+}
+
+#[async_trait]
+impl ManagementApi for MockClient {
+    async fn update_server_id(&mut self, id: u32) -> Result<(), UpdateServerIdError> {
+        let id = NonZeroU32::new(id).ok_or_else(|| {
+            UpdateServerIdError::ServerError(tonic::Status::invalid_argument("invalid server id"))
+        })?;
+        self.state().server_id = Some(id);
+        Ok(())
+    }
+
+    async fn get_server_id(&mut self) -> Result<NonZeroU32, GetServerIdError> {
+        self.state().server_id.ok_or(GetServerIdError::NoServerId)
+    }
+
+    async fn get_server_status(&mut self) -> Result<ServerStatus, GetServerStatusError> {
+        let state = self.state();
+        Ok(ServerStatus {
+            initialized: state.server_id.is_some(),
+            error: None,
+            database_statuses: state
+                .databases
+                .keys()
+                .map(|db_name| DatabaseStatus {
+                    db_name: db_name.clone(),
+                    error: None,
+                })
+                .collect(),
+        })
+    }
+
+    async fn set_serving_readiness(&mut self, ready: bool) -> Result<(), SetServingReadinessError> {
+        self.state().ready = ready;
+        Ok(())
+    }
+
+    async fn create_database(&mut self, rules: DatabaseRules) -> Result<(), CreateDatabaseError> {
+        let mut state = self.state();
+        if state.server_id.is_none() {
+            return Err(CreateDatabaseError::NoServerId);
+        }
+        if state.databases.contains_key(&rules.name) || state.deleted.contains_key(&rules.name) {
+            return Err(CreateDatabaseError::DatabaseAlreadyExists);
+        }
+        state.databases.insert(
+            rules.name.clone(),
+            MockDatabase {
+                rules,
+                ..Default::default()
+            },
+        );
+        Ok(())
+    }
+
+    async fn update_database(
+        &mut self,
+        rules: DatabaseRules,
+    ) -> Result<DatabaseRules, UpdateDatabaseError> {
+        let mut state = self.state();
+        let db = state
+            .databases
+            .get_mut(&rules.name)
+            .ok_or(UpdateDatabaseError::DatabaseNotFound)?;
+        db.rules = rules.clone();
+        Ok(rules)
+    }
+
+    async fn delete_database(
+        &mut self,
+        db_name: &str,
+        preserve_data: bool,
+    ) -> Result<(), DeleteDatabaseError> {
+        let mut state = self.state();
+        if state.server_id.is_none() {
+            return Err(DeleteDatabaseError::NoServerId);
+        }
+        let db = state
+            .databases
+            .remove(db_name)
+            .ok_or(DeleteDatabaseError::DatabaseNotFound)?;
+        if !preserve_data {
+            state.deleted.insert(db_name.to_string(), db);
+        }
+        Ok(())
+    }
+
+    async fn backup_database(
+        &mut self,
+        db_name: &str,
+        destination: &str,
+    ) -> Result<u64, BackupDatabaseError> {
+        let mut state = self.state();
+        if state.server_id.is_none() {
+            return Err(BackupDatabaseError::NoServerId);
+        }
+        let db = state
+            .databases
+            .get(db_name)
+            .cloned()
+            .ok_or(BackupDatabaseError::DatabaseNotFound)?;
+        let key = (destination.to_string(), db_name.to_string());
+        if state.backups.contains_key(&key) {
+            return Err(BackupDatabaseError::BackupAlreadyExists);
+        }
+        // the rules plus one file per chunk
+        let files = 1 + db.chunks.len() as u64;
+        state.backups.insert(key, db);
+        Ok(files)
+    }
+
+    async fn restore_database(
+        &mut self,
+        db_name: &str,
+        source: &str,
+    ) -> Result<u64, RestoreDatabaseError> {
+        let mut state = self.state();
+        if state.server_id.is_none() {
+            return Err(RestoreDatabaseError::NoServerId);
+        }
+        if state.databases.contains_key(db_name) {
+            return Err(RestoreDatabaseError::DatabaseAlreadyExists);
+        }
+        let db = state
+            .backups
+            .get(&(source.to_string(), db_name.to_string()))
+            .cloned()
+            .ok_or(RestoreDatabaseError::BackupNotFound)?;
+        let files = 1 + db.chunks.len() as u64;
+        // the backup replaces the data of a deleted database
+        state.deleted.remove(db_name);
+        state.databases.insert(db_name.to_string(), db);
+        Ok(files)
+    }
+
+    async fn restore_deleted_database(
+        &mut self,
+        db_name: &str,
+    ) -> Result<(), RestoreDeletedDatabaseError> {
+        let mut state = self.state();
+        if state.server_id.is_none() {
+            return Err(RestoreDeletedDatabaseError::NoServerId);
+        }
+        if state.databases.contains_key(db_name) {
+            return Err(RestoreDeletedDatabaseError::DatabaseAlreadyExists);
+        }
+        let db = state
+            .deleted
+            .remove(db_name)
+            .ok_or(RestoreDeletedDatabaseError::DatabaseNotFound)?;
+        state.databases.insert(db_name.to_string(), db);
+        Ok(())
+    }
+
+    async fn clone_database(
+        &mut self,
+        source: &str,
+        target: &str,
+        with_data: bool,
+    ) -> Result<u64, CloneDatabaseError> {
+        let mut state = self.state();
+        if state.server_id.is_none() {
+            return Err(CloneDatabaseError::NoServerId);
+        }
+        let source = state
+            .databases
+            .get(source)
+            .cloned()
+            .ok_or(CloneDatabaseError::DatabaseNotFound)?;
+        if state.databases.contains_key(target) {
+            return Err(CloneDatabaseError::DatabaseAlreadyExists);
+        }
+
+        let mut db = if with_data {
+            source
+        } else {
+            MockDatabase {
+                rules: source.rules,
+                ..Default::default()
+            }
+        };
+        db.rules.name = target.to_string();
+        // one file per chunk
+        let files = db.chunks.len() as u64;
+        state.databases.insert(target.to_string(), db);
+        Ok(files)
+    }
+
+    async fn list_databases(&mut self) -> Result<Vec<String>, ListDatabaseError> {
+        Ok(self.state().databases.keys().cloned().collect())
+    }
+
+    async fn get_database(&mut self, name: &str) -> Result<DatabaseRules, GetDatabaseError> {
+        self.state()
+            .databases
+            .get(name)
+            .map(|db| db.rules.clone())
+            .ok_or(GetDatabaseError::DatabaseNotFound)
+    }
+
+    async fn list_chunks(&mut self, db_name: &str) -> Result<Vec<Chunk>, ListChunksError> {
+        self.state()
+            .databases
+            .get(db_name)
+            .map(|db| db.chunks.clone())
+            .ok_or_else(|| ListChunksError::ServerError(not_found("Database", db_name)))
+    }
+
+    async fn list_remotes(&mut self) -> Result<Vec<Remote>, ListRemotesError> {
+        Ok(self
+            .state()
+            .remotes
+            .iter()
+            .map(|(id, connection_string)| Remote {
+                id: *id,
+                connection_string: connection_string.clone(),
+            })
+            .collect())
+    }
+
+    async fn update_remote(
+        &mut self,
+        id: u32,
+        connection_string: &str,
+    ) -> Result<(), UpdateRemoteError> {
+        self.state()
+            .remotes
+            .insert(id, connection_string.to_string());
+        Ok(())
+    }
+
+    async fn delete_remote(&mut self, id: u32) -> Result<(), UpdateRemoteError> {
+        self.state()
+            .remotes
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| UpdateRemoteError::ServerError(not_found("Remote", &id.to_string())))
+    }
+
+    async fn list_partitions(
+        &mut self,
+        db_name: &str,
+    ) -> Result<Vec<Partition>, ListPartitionsError> {
+        let state = self.state();
+        let db = state
+            .databases
+            .get(db_name)
+            .ok_or(ListPartitionsError::DatabaseNotFound)?;
+
+        let mut keys: Vec<_> = db.chunks.iter().map(|c| c.partition_key.clone()).collect();
+        keys.sort();
+        keys.dedup();
+
+        Ok(keys
+            .into_iter()
+            .map(|key| Partition {
+                key,
+                ..Default::default()
+            })
+            .collect())
+    }
+
+    async fn get_partition(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+    ) -> Result<Partition, GetPartitionError> {
+        let state = self.state();
+        let db = state
+            .databases
+            .get(db_name)
+            .ok_or(GetPartitionError::DatabaseNotFound)?;
+
+        let mut tables: BTreeMap<&str, PartitionTable> = BTreeMap::new();
+        for chunk in db
+            .chunks
+            .iter()
+            .filter(|c| c.partition_key == partition_key)
+        {
+            let table = tables
+                .entry(&chunk.table_name)
+                .or_insert_with(|| PartitionTable {
+                    table_name: chunk.table_name.clone(),
+                    ..Default::default()
+                });
+
+            match chunk.storage() {
+                ChunkStorage::OpenMutableBuffer | ChunkStorage::ClosedMutableBuffer => {
+                    table.mutable_buffer_chunks += 1
+                }
+                ChunkStorage::ReadBuffer => table.read_buffer_chunks += 1,
+                ChunkStorage::ReadBufferAndObjectStore => {
+                    table.read_buffer_chunks += 1;
+                    table.object_store_chunks += 1;
+                }
+                ChunkStorage::ObjectStoreOnly => table.object_store_chunks += 1,
+                ChunkStorage::Unspecified => {}
+            }
+            table.row_count += chunk.row_count;
+            table.memory_bytes += chunk.estimated_bytes;
+        }
+
+        if tables.is_empty() {
+            return Err(GetPartitionError::PartitionNotFound);
+        }
+
+        Ok(Partition {
+            key: partition_key.to_string(),
+            tables: tables.into_iter().map(|(_, table)| table).collect(),
+        })
+    }
+
+    async fn list_partition_chunks(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+    ) -> Result<Vec<Chunk>, ListPartitionChunksError> {
+        let state = self.state();
+        let db = state
+            .databases
+            .get(db_name)
+            .ok_or_else(|| ListPartitionChunksError::ServerError(not_found("Database", db_name)))?;
+
+        Ok(db
+            .chunks
+            .iter()
+            .filter(|c| c.partition_key == partition_key)
+            .cloned()
+            .collect())
+    }
+
+    async fn new_partition_chunk(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+        table_name: &str,
+    ) -> Result<(), NewPartitionChunkError> {
+        let mut state = self.state();
+        let db = state.databases.get_mut(db_name).ok_or_else(|| {
+            NewPartitionChunkError::NotFound(format!("Database not found: {}", db_name))
+        })?;
+
+        let id = db.chunks.iter().map(|c| c.id + 1).max().unwrap_or(0);
+        db.chunks.push(Chunk {
+            partition_key: partition_key.to_string(),
+            table_name: table_name.to_string(),
+            id,
+            storage: ChunkStorage::OpenMutableBuffer.into(),
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    async fn close_partition_chunk(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+        table_name: &str,
+        chunk_id: u32,
+    ) -> Result<Operation, ClosePartitionChunkError> {
+        {
+            let mut state = self.state();
+            let db = state
+                .databases
+                .get_mut(db_name)
+                .ok_or(ClosePartitionChunkError::DatabaseNotFound)?;
+
+            let chunk = db
+                .chunks
+                .iter_mut()
+                .find(|c| {
+                    c.partition_key == partition_key
+                        && c.table_name == table_name
+                        && c.id == chunk_id
+                })
+                .ok_or_else(|| {
+                    ClosePartitionChunkError::ServerError(not_found("Chunk", &chunk_id.to_string()))
+                })?;
+            chunk.storage = ChunkStorage::ReadBuffer.into();
+        }
+
+        Ok(self.next_operation())
+    }
+
+    async fn persist_partition_chunk(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+        table_name: &str,
+        chunk_id: u32,
+    ) -> Result<Operation, PersistPartitionChunkError> {
+        {
+            let mut state = self.state();
+            let db = state
+                .databases
+                .get_mut(db_name)
+                .ok_or(PersistPartitionChunkError::DatabaseNotFound)?;
+
+            let chunk = db
+                .chunks
+                .iter_mut()
+                .find(|c| {
+                    c.partition_key == partition_key
+                        && c.table_name == table_name
+                        && c.id == chunk_id
+                })
+                .ok_or_else(|| {
+                    PersistPartitionChunkError::ServerError(not_found(
+                        "Chunk",
+                        &chunk_id.to_string(),
+                    ))
+                })?;
+            chunk.storage = ChunkStorage::ReadBufferAndObjectStore.into();
+        }
+
+        Ok(self.next_operation())
+    }
+
+    async fn drop_partition(
+        &mut self,
+        db_name: &str,
+        partition_key: &str,
+        _delete_persisted: bool,
+    ) -> Result<(), DropPartitionError> {
+        let mut state = self.state();
+        let db = state.databases.get_mut(db_name).ok_or_else(|| {
+            DropPartitionError::NotFound(format!("Database not found: {}", db_name))
+        })?;
+
+        if !db.chunks.iter().any(|c| c.partition_key == partition_key) {
+            return Err(DropPartitionError::NotFound(format!(
+                "Partition not found: {}",
+                partition_key
+            )));
+        }
+        db.chunks.retain(|c| c.partition_key != partition_key);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl WriteApi for MockClient {
+    async fn write(&mut self, db_name: &str, lp_data: &str) -> Result<usize, WriteError> {
+        let mut state = self.state();
+        let db = state
+            .databases
+            .get_mut(db_name)
+            .ok_or_else(|| WriteError::ServerError(not_found("Database", db_name)))?;
+
+        let lines: Vec<_> = lp_data
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(ToString::to_string)
+            .collect();
+        let lines_written = lines.len();
+        db.lines.extend(lines);
+
+        Ok(lines_written)
+    }
+
+    async fn write_entry(&mut self, db_name: &str, entry: &[u8]) -> Result<(), WriteError> {
+        let mut state = self.state();
+        let db = state
+            .databases
+            .get_mut(db_name)
+            .ok_or_else(|| WriteError::ServerError(not_found("Database", db_name)))?;
+        db.entries.push(entry.to_vec());
+        Ok(())
+    }
+}
+
+#[cfg(feature = "flight")]
+#[async_trait]
+impl crate::api::FlightApi for MockClient {
+    async fn query(
+        &mut self,
+        database_name: &str,
+        sql_query: &str,
+    ) -> Result<Vec<arrow::record_batch::RecordBatch>, crate::flight::Error> {
+        let state = self.state();
+        if !state.databases.contains_key(database_name) {
+            return Err(not_found("Database", database_name).into());
+        }
+
+        Ok(state
+            .query_results
+            .get(&(database_name.to_string(), sql_query.to_string()))
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_databases() {
+        let mut client = MockClient::new();
+
+        client
+            .create_database(DatabaseRules {
+                name: "bananas".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let err = client
+            .create_database(DatabaseRules {
+                name: "bananas".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CreateDatabaseError::DatabaseAlreadyExists));
+
+        assert_eq!(client.list_databases().await.unwrap(), vec!["bananas"]);
+        assert_eq!(
+            client.get_database("bananas").await.unwrap().name,
+            "bananas"
+        );
+
+        let err = client.get_database("apples").await.unwrap_err();
+        assert!(matches!(err, GetDatabaseError::DatabaseNotFound));
+
+        client.delete_database("bananas", false).await.unwrap();
+        assert!(client.list_databases().await.unwrap().is_empty());
+
+        let err = client.delete_database("bananas", false).await.unwrap_err();
+        assert!(matches!(err, DeleteDatabaseError::DatabaseNotFound));
+
+        client.restore_deleted_database("bananas").await.unwrap();
+        assert_eq!(client.list_databases().await.unwrap(), vec!["bananas"]);
+
+        let err = client
+            .restore_deleted_database("bananas")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            RestoreDeletedDatabaseError::DatabaseAlreadyExists
+        ));
+
+        client.delete_database("bananas", true).await.unwrap();
+        let err = client
+            .restore_deleted_database("bananas")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RestoreDeletedDatabaseError::DatabaseNotFound));
+    }
+
+    #[tokio::test]
+    async fn test_backup_restore() {
+        let mut client = MockClient::new();
+        client.add_database("bananas");
+        client.write("bananas", "cpu bar=1 10").await.unwrap();
+
+        assert_eq!(
+            client
+                .backup_database("bananas", "backups/1")
+                .await
+                .unwrap(),
+            1
+        );
+        let err = client
+            .backup_database("bananas", "backups/1")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BackupDatabaseError::BackupAlreadyExists));
+        let err = client
+            .backup_database("apples", "backups/1")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BackupDatabaseError::DatabaseNotFound));
+
+        let err = client
+            .restore_database("bananas", "backups/1")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RestoreDatabaseError::DatabaseAlreadyExists));
+
+        client.delete_database("bananas", false).await.unwrap();
+        let err = client
+            .restore_database("bananas", "backups/2")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RestoreDatabaseError::BackupNotFound));
+
+        client
+            .restore_database("bananas", "backups/1")
+            .await
+            .unwrap();
+        assert_eq!(client.written_lines("bananas"), vec!["cpu bar=1 10"]);
+    }
+
+    #[tokio::test]
+    async fn test_clone() {
+        let mut client = MockClient::new();
+        client.add_database("bananas");
+        client.write("bananas", "cpu bar=1 10").await.unwrap();
+
+        client
+            .clone_database("bananas", "rules_only", false)
+            .await
+            .unwrap();
+        assert!(client.written_lines("rules_only").is_empty());
+        assert_eq!(
+            client.get_database("rules_only").await.unwrap().name,
+            "rules_only"
+        );
+
+        client
+            .clone_database("bananas", "with_data", true)
+            .await
+            .unwrap();
+        assert_eq!(client.written_lines("with_data"), vec!["cpu bar=1 10"]);
+
+        let err = client
+            .clone_database("bananas", "with_data", true)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CloneDatabaseError::DatabaseAlreadyExists));
+        let err = client
+            .clone_database("apples", "pears", true)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CloneDatabaseError::DatabaseNotFound));
+    }
+
+    #[tokio::test]
+    async fn test_write() {
+        let client = MockClient::new();
+        client.add_database("bananas");
+
+        // writes through a clone are visible to the original
+        let mut writer = client.clone();
+        let lines_written = writer
+            .write(
+                "bananas",
+                "cpu,region=west user=23.2 100\n\ncpu,region=east user=2 200",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(lines_written, 2);
+        assert_eq!(
+            client.written_lines("bananas"),
+            vec![
+                "cpu,region=west user=23.2 100",
+                "cpu,region=east user=2 200"
+            ]
+        );
+
+        writer.write("apples", "cpu user=1 1").await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_chunks() {
+        let mut client = MockClient::new();
+        client.add_database("bananas");
+
+        client
+            .new_partition_chunk("bananas", "part", "cpu")
+            .await
+            .unwrap();
+
+        let chunks = client
+            .list_partition_chunks("bananas", "part")
+            .await
+            .unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].storage(), ChunkStorage::OpenMutableBuffer);
+
+        client
+            .close_partition_chunk("bananas", "part", "cpu", chunks[0].id)
+            .await
+            .unwrap();
+
+        let chunks = client.list_chunks("bananas").await.unwrap();
+        assert_eq!(chunks[0].storage(), ChunkStorage::ReadBuffer);
+
+        client
+            .persist_partition_chunk("bananas", "part", "cpu", chunks[0].id)
+            .await
+            .unwrap();
+
+        let chunks = client.list_chunks("bananas").await.unwrap();
+        assert_eq!(chunks[0].storage(), ChunkStorage::ReadBufferAndObjectStore);
+
+        let partitions = client.list_partitions("bananas").await.unwrap();
+        assert_eq!(
+            partitions,
+            vec![Partition {
+                key: "part".to_string(),
+                ..Default::default()
+            }]
+        );
+
+        let partition = client.get_partition("bananas", "part").await.unwrap();
+        assert_eq!(partition.tables.len(), 1);
+        assert_eq!(partition.tables[0].table_name, "cpu");
+        assert_eq!(partition.tables[0].read_buffer_chunks, 1);
+
+        client
+            .drop_partition("bananas", "part", true)
+            .await
+            .unwrap();
+        assert!(client.list_partitions("bananas").await.unwrap().is_empty());
+
+        let err = client
+            .drop_partition("bananas", "part", true)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DropPartitionError::NotFound(_)));
+    }
+}
@@ -35,6 +35,7 @@ use std::{
     collections::{btree_map::Entry, BTreeMap},
     fmt,
     ops::Deref,
+    str,
 };
 
 #[derive(Debug, Snafu)]
@@ -148,7 +149,7 @@ impl nom::error::ParseError<&str> for Error {
 ///
 /// assert_eq!(timestamp, Some(1590488773254420000));
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParsedLine<'a> {
     pub series: Series<'a>,
     pub field_set: FieldSet<'a>,
@@ -230,7 +231,7 @@ impl<'a> Display for ParsedLine<'a> {
 
 /// Represents the identifier of a series (measurement, tagset) for
 /// line protocol data
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Series<'a> {
     raw_input: &'a str,
     pub measurement: EscapedStr<'a>,
@@ -472,13 +473,85 @@ impl PartialEq<String> for EscapedStr<'_> {
 }
 
 pub fn parse_lines(input: &str) -> impl Iterator<Item = Result<ParsedLine<'_>>> {
-    split_lines(input).filter_map(|line| {
+    parse_lines_with_location(input).map(|(_, line)| line)
+}
+
+/// The maximum length, in bytes, of [`LineLocation::snippet`]
+pub const SNIPPET_LENGTH: usize = 64;
+
+/// The location of a line in the input of [`parse_lines_with_location`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineLocation<'a> {
+    /// The number of the line in the input, starting at 1. Empty lines,
+    /// comments and newlines in quoted field values are counted, so this is
+    /// the number an editor shows for the line
+    pub line_number: usize,
+
+    /// The offset of the start of the line from the start of the input, in
+    /// bytes
+    pub byte_offset: usize,
+
+    /// The text of the line
+    pub text: &'a str,
+}
+
+impl LineLocation<'_> {
+    /// Returns the start of the line, truncated to at most
+    /// [`SNIPPET_LENGTH`] bytes followed by `...` if it is longer
+    pub fn snippet(&self) -> String {
+        if self.text.len() <= SNIPPET_LENGTH {
+            return self.text.to_string();
+        }
+
+        let mut end = SNIPPET_LENGTH;
+        while !self.text.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}...", &self.text[..end])
+    }
+}
+
+impl Display for LineLocation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {} (byte offset {}): '{}'",
+            self.line_number,
+            self.byte_offset,
+            self.snippet()
+        )
+    }
+}
+
+/// Like [`parse_lines`], but also returns the location of each line in
+/// `input`, so that errors can be reported in terms of the original input
+pub fn parse_lines_with_location(
+    input: &str,
+) -> impl Iterator<Item = (LineLocation<'_>, Result<ParsedLine<'_>>)> {
+    // The number of the line starting at `counted`
+    let mut line_number = 1;
+    let mut counted = 0;
+
+    split_lines(input).filter_map(move |line| {
         let i = trim_leading(line);
 
         if i.is_empty() {
             return None;
         }
 
+        let byte_offset = i.as_ptr() as usize - input.as_ptr() as usize;
+        line_number += input.as_bytes()[counted..byte_offset]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count();
+        counted = byte_offset;
+
+        let location = LineLocation {
+            line_number,
+            byte_offset,
+            text: i,
+        };
+
         let res = match parse_line(i) {
             Ok((remaining, line)) => {
                 // should have parsed the whole input line, if any
@@ -486,24 +559,193 @@ pub fn parse_lines(input: &str) -> impl Iterator<Item = Result<ParsedLine<'_>>>
                 // corresponding Go logic:
                 // https://github.com/influxdata/influxdb/blob/217eddc87e14a79b01d0c22994fc139f530094a2/models/points_parser.go#L259-L266
                 if !remaining.is_empty() {
-                    Some(Err(Error::CannotParseEntireLine {
+                    Err(Error::CannotParseEntireLine {
                         trailing_content: String::from(remaining),
-                    }))
+                    })
                 } else {
-                    Some(Ok(line))
+                    Ok(line)
                 }
             }
-            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Some(Err(e)),
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(e),
             Err(nom::Err::Incomplete(_)) => unreachable!("Cannot have incomplete data"), // Only streaming parsers have this
         };
 
-        if let Some(Err(r)) = &res {
-            debug!("Error parsing line: '{}'. Error was {:?}", line, r);
+        if let Err(r) = &res {
+            debug!("Error parsing {}. Error was {:?}", location, r);
         }
-        res
+        Some((location, res))
     })
 }
 
+/// Splits line protocol that is received in chunks, e.g. from a network
+/// stream, into batches of complete lines, so that large inputs can be
+/// parsed and written incrementally instead of being held in memory as a
+/// whole.
+///
+/// ```
+/// use influxdb_line_protocol::LineSplitter;
+///
+/// let mut splitter = LineSplitter::new();
+/// splitter.push(b"cpu usage=1 1\ncpu usa");
+///
+/// let batch = splitter.take_complete().unwrap().unwrap();
+/// assert_eq!(batch.text(), "cpu usage=1 1\n");
+///
+/// splitter.push(b"ge=2 2");
+/// let batch = splitter.finish().unwrap().unwrap();
+/// assert_eq!(batch.text(), "cpu usage=2 2");
+///
+/// let (location, line) = batch.parse().next().unwrap();
+/// assert_eq!(location.line_number, 2);
+/// assert!(line.is_ok());
+/// ```
+#[derive(Debug)]
+pub struct LineSplitter {
+    /// Data received but not yet returned in a batch
+    buffer: Vec<u8>,
+
+    /// The number of bytes at the start of `buffer` that have been scanned
+    /// for the ends of lines, always at a character boundary
+    scanned: usize,
+
+    /// The end of the last complete line in `buffer`, or 0 if there is none
+    complete: usize,
+
+    /// The state of the scan at the end of the scanned bytes
+    scanner: LineScanner,
+
+    /// The line number of the start of `buffer` in the whole input
+    line_number: usize,
+
+    /// The offset of the start of `buffer` from the start of the whole
+    /// input, in bytes
+    byte_offset: usize,
+}
+
+impl Default for LineSplitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineSplitter {
+    /// Creates a splitter for an input starting at line 1
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            scanned: 0,
+            complete: 0,
+            scanner: LineScanner::default(),
+            line_number: 1,
+            byte_offset: 0,
+        }
+    }
+
+    /// Appends the next chunk of the input
+    pub fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Returns the number of bytes received but not yet returned in a batch
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Removes all complete lines from the buffer and returns them, or
+    /// `None` if no line is complete yet. Fails if the data is not valid
+    /// UTF-8
+    ///
+    /// Only the data pushed since the last call is scanned, so that a long
+    /// line received in many chunks is not scanned again for each of them.
+    pub fn take_complete(&mut self) -> Result<Option<LineBatch>, str::Utf8Error> {
+        let unscanned = &self.buffer[self.scanned..];
+        let text = match str::from_utf8(unscanned) {
+            Ok(text) => text,
+            // The buffer may end in the middle of a character
+            Err(e) if e.error_len().is_none() => {
+                str::from_utf8(&unscanned[..e.valid_up_to()]).expect("valid prefix")
+            }
+            Err(e) => return Err(e),
+        };
+
+        // Every line but the last is terminated by a newline, the last one
+        // may continue in the next chunk
+        for (i, c) in text.char_indices() {
+            if self.scanner.is_line_end(c) {
+                self.complete = self.scanned + i + 1;
+            }
+        }
+        self.scanned += text.len();
+
+        if self.complete == 0 {
+            return Ok(None);
+        }
+        let rest = self.buffer.split_off(self.complete);
+        let data = std::mem::replace(&mut self.buffer, rest);
+        self.scanned -= self.complete;
+        self.complete = 0;
+        Ok(Some(self.batch(data)))
+    }
+
+    /// Returns the remaining data as the last batch, or `None` if there is
+    /// none. Fails if the data is not valid UTF-8
+    pub fn finish(mut self) -> Result<Option<LineBatch>, str::Utf8Error> {
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+        str::from_utf8(&self.buffer)?;
+
+        let data = std::mem::take(&mut self.buffer);
+        Ok(Some(self.batch(data)))
+    }
+
+    fn batch(&mut self, data: Vec<u8>) -> LineBatch {
+        let text = String::from_utf8(data).expect("checked to be valid UTF-8");
+        let batch = LineBatch {
+            line_number: self.line_number,
+            byte_offset: self.byte_offset,
+            text,
+        };
+
+        self.line_number += batch.text.bytes().filter(|&b| b == b'\n').count();
+        self.byte_offset += batch.text.len();
+        batch
+    }
+}
+
+/// Complete lines of an input split by a [`LineSplitter`]
+#[derive(Debug)]
+pub struct LineBatch {
+    /// The line number of the start of `text` in the whole input
+    line_number: usize,
+
+    /// The offset of the start of `text` from the start of the whole input,
+    /// in bytes
+    byte_offset: usize,
+
+    text: String,
+}
+
+impl LineBatch {
+    /// Returns the line protocol of the batch
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Like [`parse_lines_with_location`], but the locations are relative to
+    /// the whole input rather than to the batch
+    pub fn parse(&self) -> impl Iterator<Item = (LineLocation<'_>, Result<ParsedLine<'_>>)> {
+        let line_number = self.line_number;
+        let byte_offset = self.byte_offset;
+
+        parse_lines_with_location(&self.text).map(move |(mut location, line)| {
+            location.line_number += line_number - 1;
+            location.byte_offset += byte_offset;
+            (location, line)
+        })
+    }
+}
+
 /// Split `input` into invidividual lines to be parsed, based on the
 /// rules of the Line Protocol format.
 ///
@@ -515,60 +757,69 @@ pub fn parse_lines(input: &str) -> impl Iterator<Item = Result<ParsedLine<'_>>>
 /// we can be more sure of the compatibility of the rust parser and
 /// the canonical Go parser.
 fn split_lines(input: &str) -> impl Iterator<Item = &str> {
-    // NB: This is ported as closely as possibly from the original Go code:
-    let mut quoted = false;
-    let mut fields = false;
+    let mut scanner = LineScanner::default();
+    input.split(move |c| scanner.is_line_end(c))
+}
+
+/// The state of [`split_lines`] between the characters of its input, so
+/// that input received in chunks can be split without scanning it again
+#[derive(Debug, Default)]
+struct LineScanner {
+    quoted: bool,
+    fields: bool,
 
     // tracks how many '=' and commas we've seen
     // this duplicates some of the functionality in scanFields
-    let mut equals = 0;
-    let mut commas = 0;
+    equals: usize,
+    commas: usize,
+
+    in_escape: bool,
+}
+
+impl LineScanner {
+    /// Returns true if `c`, the next character of the input, ends a line
+    fn is_line_end(&mut self, c: char) -> bool {
+        // NB: This is ported as closely as possibly from the original Go code:
 
-    let mut in_escape = false;
-    input.split(move |c| {
         // skip past escaped characters
-        if in_escape {
-            in_escape = false;
+        if self.in_escape {
+            self.in_escape = false;
             return false;
         }
 
         if c == '\\' {
-            in_escape = true;
+            self.in_escape = true;
             return false;
         }
 
         if c == ' ' {
-            fields = true;
+            self.fields = true;
             return false;
         }
 
         // If we see a double quote, makes sure it is not escaped
-        if fields {
-            if !quoted && c == '=' {
-                equals += 1;
+        if self.fields {
+            if !self.quoted && c == '=' {
+                self.equals += 1;
                 return false;
-            } else if !quoted && c == ',' {
-                commas += 1;
+            } else if !self.quoted && c == ',' {
+                self.commas += 1;
                 return false;
-            } else if c == '"' && equals > commas {
-                quoted = !quoted;
+            } else if c == '"' && self.equals > self.commas {
+                self.quoted = !self.quoted;
                 return false;
             }
         }
 
-        if c == '\n' && !quoted {
+        if c == '\n' && !self.quoted {
             // reset all the state -- we found a line
-            quoted = false;
-            fields = false;
-            equals = 0;
-            commas = 0;
-            assert!(!in_escape);
-            in_escape = false;
+            assert!(!self.in_escape);
+            *self = Self::default();
             return true;
         }
 
         false
-    })
+    }
 }
 
 fn parse_line(i: &str) -> IResult<&str, ParsedLine<'_>> {
@@ -1732,6 +1983,106 @@ bar value2=2i 123"#;
         assert!(vals.is_empty());
     }
 
+    #[test]
+    fn parse_lines_reports_location() {
+        let input = "# comment\ncpu a=1i 1\n\n  cpu a=\"multi\nline\" 2\ncpu a= 3\n";
+        let lines: Vec<_> = parse_lines_with_location(input).collect();
+
+        let locations: Vec<_> = lines
+            .iter()
+            .map(|(location, _)| (location.line_number, location.byte_offset))
+            .collect();
+        assert_eq!(locations, vec![(2, 10), (4, 24), (6, 45)]);
+
+        assert!(lines[0].1.is_ok());
+        assert!(lines[1].1.is_ok());
+        assert!(lines[2].1.is_err());
+        assert_eq!(lines[2].0.text, "cpu a= 3");
+        assert_eq!(
+            lines[2].0.to_string(),
+            "line 6 (byte offset 45): 'cpu a= 3'"
+        );
+    }
+
+    #[test]
+    fn location_snippet_is_truncated() {
+        let text = format!("cpu a=\"{}\"", "é".repeat(40));
+        let location = LineLocation {
+            line_number: 1,
+            byte_offset: 0,
+            text: &text,
+        };
+
+        // "é" is two bytes long, so the snippet ends before the 64th byte
+        let snippet = location.snippet();
+        assert_eq!(snippet, format!("cpu a=\"{}...", "é".repeat(28)));
+    }
+
+    #[test]
+    fn split_lines_in_chunks() {
+        let input = "cpu a=1i 1\n# comment\ncpu a=\"multi\nline é\" 2\ncpu a= 3\ncpu a=4i 4";
+
+        // Feed the input in chunks of every size, splitting characters and
+        // quoted newlines
+        for chunk_size in 1..input.len() {
+            let mut splitter = LineSplitter::new();
+            let mut batches = vec![];
+            for chunk in input.as_bytes().chunks(chunk_size) {
+                splitter.push(chunk);
+                batches.extend(splitter.take_complete().unwrap());
+            }
+            batches.extend(splitter.finish().unwrap());
+
+            let text: String = batches.iter().map(|b| b.text()).collect();
+            assert_eq!(text, input);
+
+            let lines: Vec<_> = batches
+                .iter()
+                .flat_map(|batch| {
+                    batch.parse().map(|(location, line)| {
+                        (location.line_number, location.byte_offset, line.is_ok())
+                    })
+                })
+                .collect();
+            assert_eq!(
+                lines,
+                vec![(1, 0, true), (3, 21, true), (5, 45, false), (6, 54, true)],
+                "chunk size {}",
+                chunk_size
+            );
+        }
+    }
+
+    #[test]
+    fn split_lines_scans_chunks_once() {
+        let mut splitter = LineSplitter::new();
+        splitter.push(b"cpu a=\"x\n");
+        assert!(splitter.take_complete().unwrap().is_none());
+        assert_eq!(splitter.scanned, 9);
+
+        // The scan continues inside the quoted string
+        splitter.push(b"y\" 1\ncpu");
+        let batch = splitter.take_complete().unwrap().unwrap();
+        assert_eq!(batch.text(), "cpu a=\"x\ny\" 1\n");
+        assert_eq!(splitter.scanned, 3);
+        assert_eq!(splitter.buffered(), 3);
+    }
+
+    #[test]
+    fn split_lines_invalid_utf8() {
+        let mut splitter = LineSplitter::new();
+        splitter.push(b"cpu a=1i 1\n");
+        // An incomplete character is kept until the next chunk
+        splitter.push(&[b'c', 0xc3]);
+        let batch = splitter.take_complete().unwrap().unwrap();
+        assert_eq!(batch.text(), "cpu a=1i 1\n");
+        assert_eq!(splitter.buffered(), 2);
+
+        splitter.push(&[0xff]);
+        splitter.take_complete().unwrap_err();
+        splitter.finish().unwrap_err();
+    }
+
     #[test]
     fn parse_multiple_whitespace_between_elements_is_allowed() {
         let input = "  measurement  a=1i  123  ";
@@ -155,6 +155,120 @@ fn parse_tsm_key_internal(key: &[u8]) -> Result<ParsedTsmKey, DataError> {
     })
 }
 
+/// The delimiter between the series key and the field key of a TSM index key
+const FIELD_KEY_DELIMITER: &[u8] = b"#!~#";
+
+/// parses the the measurement, field key and tag set from a tsm index key
+/// written by InfluxDB 1.x, which has no org and bucket ids and stores the
+/// series key as in line protocol:
+///
+/// <measurement>,<tag_key>=<tag_value>,...#!~#<field_key>
+///
+/// For example:
+/// cpu,host=a,region=us\ west#!~#usage_user
+///
+///    measurement = "cpu"
+///    tags = [("host", "a"), ("region", "us west")]
+///    field = "usage_user"
+pub fn parse_tsm_v1_key(key: &[u8]) -> Result<ParsedTsmKey, Error> {
+    parse_tsm_v1_key_internal(key).context(ParsingTsmKey {
+        key: String::from_utf8_lossy(key),
+    })
+}
+
+fn parse_tsm_v1_key_internal(key: &[u8]) -> Result<ParsedTsmKey, DataError> {
+    // like InfluxDB, split at the first delimiter as neither part escapes it
+    let delimiter = key
+        .windows(FIELD_KEY_DELIMITER.len())
+        .position(|w| w == FIELD_KEY_DELIMITER)
+        .context(NoFieldKey)?;
+    let (series_key, field_key) = (
+        &key[..delimiter],
+        &key[delimiter + FIELD_KEY_DELIMITER.len()..],
+    );
+
+    let field_key =
+        String::from_utf8(field_key.to_vec()).map_err(|e| DataError::ParsingFieldKey {
+            details: e.to_string(),
+        })?;
+    if field_key.is_empty() {
+        return NoFieldKey.fail();
+    }
+
+    let mut parts = split_unescaped(series_key, b',').into_iter();
+    let measurement = unescape(parts.next().unwrap_or_default()).map_err(|description| {
+        DataError::ParsingTsmTagValue {
+            tag_key: "Measurement".to_string(),
+            description,
+        }
+    })?;
+    if measurement.is_empty() {
+        return NoMeasurement.fail();
+    }
+
+    let tagset = parts
+        .map(|tag| {
+            let mut tag = split_unescaped(tag, b'=').into_iter();
+            let (tag_key, tag_value) = match (tag.next(), tag.next(), tag.next()) {
+                (Some(tag_key), Some(tag_value), None) => (tag_key, tag_value),
+                _ => {
+                    return ParsingTsmTagKey {
+                        description: "expected exactly one unescaped '='",
+                    }
+                    .fail()
+                }
+            };
+            let tag_key = unescape(tag_key)
+                .map_err(|description| DataError::ParsingTsmTagKey { description })?;
+            let tag_value =
+                unescape(tag_value).map_err(|description| DataError::ParsingTsmTagValue {
+                    tag_key: tag_key.clone(),
+                    description,
+                })?;
+            Ok((tag_key, tag_value))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ParsedTsmKey {
+        measurement,
+        tagset,
+        field_key,
+    })
+}
+
+/// Splits `data` at every occurrence of `separator` not preceded by a `\`
+fn split_unescaped(data: &[u8], separator: u8) -> Vec<&[u8]> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, &b) in data.iter().enumerate() {
+        match b {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            _ if b == separator => {
+                parts.push(&data[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&data[start..]);
+    parts
+}
+
+/// Removes the `\` escaping `,`, `=` and ` ` from a part of a series key
+fn unescape(data: &[u8]) -> Result<String, String> {
+    let mut unescaped = Vec::with_capacity(data.len());
+    let mut iter = data.iter().copied().peekable();
+    while let Some(b) = iter.next() {
+        match (b, iter.peek()) {
+            (b'\\', Some(b',')) | (b'\\', Some(b'=')) | (b'\\', Some(b' ')) => {}
+            _ => unescaped.push(b),
+        }
+    }
+    String::from_utf8(unescaped).map_err(|e| e.to_string())
+}
+
 /// Parses the field value stored in a TSM field key into a field name.
 /// fields are stored on the series keys in TSM indexes as follows:
 ///
@@ -499,6 +613,41 @@ fn parse_tsm_tag_value(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_tsm_v1_key() {
+        let parsed = parse_tsm_v1_key(br#"cpu,host=a,region=us\ west#!~#usage user"#).unwrap();
+        assert_eq!(parsed.measurement, "cpu");
+        assert_eq!(
+            parsed.tagset,
+            vec![
+                ("host".to_string(), "a".to_string()),
+                ("region".to_string(), "us west".to_string())
+            ]
+        );
+        assert_eq!(parsed.field_key, "usage user");
+
+        let parsed = parse_tsm_v1_key(br#"my\,cpu,k\=ey=v\,al#!~#f#!~#x"#).unwrap();
+        assert_eq!(parsed.measurement, "my,cpu");
+        assert_eq!(
+            parsed.tagset,
+            vec![("k=ey".to_string(), "v,al".to_string())]
+        );
+        assert_eq!(parsed.field_key, "f#!~#x");
+
+        let parsed = parse_tsm_v1_key(b"cpu#!~#usage").unwrap();
+        assert_eq!(parsed.measurement, "cpu");
+        assert!(parsed.tagset.is_empty());
+
+        let err = parse_tsm_v1_key(b"cpu,host=a").unwrap_err();
+        assert!(err.to_string().contains("No field key"), "{}", err);
+
+        let err = parse_tsm_v1_key(b",host=a#!~#usage").unwrap_err();
+        assert!(err.to_string().contains("No measurement"), "{}", err);
+
+        let err = parse_tsm_v1_key(b"cpu,host#!~#usage").unwrap_err();
+        assert!(err.to_string().contains("exactly one"), "{}", err);
+    }
+
     #[test]
     fn test_parse_tsm_field_key_value() {
         // test the operation of parse_tsm_field_key_value
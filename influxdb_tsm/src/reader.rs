@@ -220,11 +220,24 @@ impl IndexEntry {
         InfluxId::from_be_bytes(buf)
     }
 
+    /// Parses the key of this entry, written either by InfluxDB >= 2.x or by
+    /// InfluxDB 1.x
     pub fn parse_key(&self) -> Result<ParsedTsmKey, TsmError> {
-        key::parse_tsm_key(&self.key).map_err(|e| TsmError {
+        let parsed = if self.is_v1_key() {
+            key::parse_tsm_v1_key(&self.key)
+        } else {
+            key::parse_tsm_key(&self.key)
+        };
+        parsed.map_err(|e| TsmError {
             description: e.to_string(),
         })
     }
+
+    /// Returns true if the key has no org and bucket ids followed by the
+    /// measurement tag, as written by InfluxDB >= 2.x
+    fn is_v1_key(&self) -> bool {
+        self.key.get(16..19) != Some(&b",\x00="[..])
+    }
 }
 
 /// A BlockDecoder is capable of decoding a block definition into block data
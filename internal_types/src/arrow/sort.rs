@@ -43,48 +43,61 @@ pub fn estimate_cardinality(array: &DictionaryArray<Int32Type>) -> usize {
 /// Sorts rows lexicographically with respect to the tag columns in increasing
 /// order of cardinality and finally with respect to time
 pub fn sort_record_batch(batch: RecordBatch) -> Result<RecordBatch> {
+    sort_record_batch_with_key(batch).map(|(sorted, _)| sorted)
+}
+
+/// Sorts rows the same way as [`sort_record_batch`], additionally returning
+/// the names of the columns the rows are sorted by, in order
+pub fn sort_record_batch_with_key(batch: RecordBatch) -> Result<(RecordBatch, Vec<String>)> {
     let schema: Schema = batch.schema().try_into().context(InvalidSchema)?;
 
     let mut tag_cardinalities = schema
         .iter()
         .enumerate()
-        .filter_map(|(idx, (column_type, _))| match column_type {
-            Some(InfluxColumnType::Tag) => Some(idx),
+        .filter_map(|(idx, (column_type, field))| match column_type {
+            Some(InfluxColumnType::Tag) => Some((idx, field)),
             _ => None,
         })
-        .map(|idx| {
+        .map(|(idx, field)| {
             let column = batch.column(idx);
             let dictionary = column
                 .as_any()
                 .downcast_ref()
                 .ok_or(Error::InvalidTagColumn { column: idx })?;
-            Ok((Arc::clone(column), estimate_cardinality(dictionary)))
+            Ok((field, Arc::clone(column), estimate_cardinality(dictionary)))
         })
         .collect::<Result<Vec<_>>>()?;
 
-    tag_cardinalities.sort_by_key(|x| x.1);
+    tag_cardinalities.sort_by_key(|x| x.2);
 
-    let mut sort_columns: Vec<_> = tag_cardinalities
+    let mut key_columns: Vec<_> = tag_cardinalities
         .into_iter()
-        .map(|(column, _)| SortColumn {
-            values: column,
-            options: None,
-        })
+        .map(|(field, column, _)| (field, column))
         .collect();
 
-    sort_columns.extend(
+    key_columns.extend(
         schema
             .iter()
             .enumerate()
-            .filter_map(|(idx, (column_type, _))| match column_type {
-                Some(InfluxColumnType::Timestamp) => Some(SortColumn {
-                    values: Arc::clone(batch.column(idx)),
-                    options: None,
-                }),
+            .filter_map(|(idx, (column_type, field))| match column_type {
+                Some(InfluxColumnType::Timestamp) => Some((field, Arc::clone(batch.column(idx)))),
                 _ => None,
             }),
     );
 
+    let sort_key = key_columns
+        .iter()
+        .map(|(field, _)| field.name().to_string())
+        .collect();
+
+    let sort_columns: Vec<_> = key_columns
+        .into_iter()
+        .map(|(_, column)| SortColumn {
+            values: column,
+            options: None,
+        })
+        .collect();
+
     let indices = lexsort_to_indices(&sort_columns, None)?;
 
     let columns = batch
@@ -93,9 +106,10 @@ pub fn sort_record_batch(batch: RecordBatch) -> Result<RecordBatch> {
         .map(|column| Ok(take(column.as_ref(), &indices, None)?))
         .collect::<Result<Vec<_>>>()?;
 
-    // TODO: Record sort order in schema
+    let sorted =
+        RecordBatch::try_new(schema.as_arrow(), columns).expect("failed to recreated sorted batch");
 
-    Ok(RecordBatch::try_new(schema.as_arrow(), columns).expect("failed to recreated sorted batch"))
+    Ok((sorted, sort_key))
 }
 
 #[cfg(test)]
@@ -153,9 +167,10 @@ mod tests {
         )
         .unwrap();
 
-        let sorted = sort_record_batch(batch).unwrap();
+        let (sorted, sort_key) = sort_record_batch_with_key(batch).unwrap();
 
         // Expects to be sorted first by tag2, then tag1, then time
+        assert_eq!(sort_key, vec!["tag2", "tag1", "time"]);
         assert_batches_eq!(
             &[
                 "+----------+-------+-------------------------------+------+",
@@ -1,4 +1,4 @@
-use super::{InfluxColumnType, Schema};
+use super::{InfluxColumnType, InfluxFieldType, Schema};
 use crate::schema::builder::set_field_metadata;
 use crate::schema::MEASUREMENT_METADATA_KEY;
 use arrow::datatypes::{DataType as ArrowDataType, Field, Schema as ArrowSchema};
@@ -79,10 +79,17 @@ fn nullable_to_str(nullability: bool) -> &'static str {
 ///
 /// 2. The measurement names must be consistent: one or both can be
 ///    `None`, or they can both be `Some(name`)
+///
+/// 3. Fields of different types can be merged if a coercion allows the
+///    values of one type to be converted to the other, see
+///    [`with_coercions`](Self::with_coercions)
 #[derive(Debug, Default)]
 pub struct SchemaMerger {
     /// Maps column names to their definition
     fields: HashMap<String, (Field, Option<InfluxColumnType>)>,
+    /// Pairs of field types, the first of which may be converted to the
+    /// second
+    coercions: Vec<(InfluxFieldType, InfluxFieldType)>,
     /// The measurement name if any
     measurement: Option<String>,
     /// If the builder has been consumed
@@ -94,6 +101,13 @@ impl SchemaMerger {
         Self::default()
     }
 
+    /// Allows fields of the first type of each pair to be merged with
+    /// fields of the second type, resulting in a field of the second type
+    pub fn with_coercions(mut self, coercions: Vec<(InfluxFieldType, InfluxFieldType)>) -> Self {
+        self.coercions = coercions;
+        self
+    }
+
     /// Appends the schema to the merged schema being built,
     /// validating that no columns are added.
     pub fn merge(&mut self, other: &Schema) -> Result<&mut Self> {
@@ -134,8 +148,27 @@ impl SchemaMerger {
                 let field = Field::new(field_name, field.data_type().clone(), field.is_nullable());
                 vacant.insert(field_name.clone(), (field, column_type));
             }
-            RawEntryMut::Occupied(occupied) => {
-                let (existing_field, existing_column_type) = occupied.get();
+            RawEntryMut::Occupied(mut occupied) => {
+                let (existing_field, existing_column_type) = occupied.get_mut();
+
+                if let (
+                    Some(InfluxColumnType::Field(existing_type)),
+                    Some(InfluxColumnType::Field(new_type)),
+                ) = (*existing_column_type, column_type)
+                {
+                    if self.coercions.contains(&(new_type, existing_type)) {
+                        return Ok(self);
+                    }
+                    if self.coercions.contains(&(existing_type, new_type)) {
+                        *existing_field = Field::new(
+                            field_name,
+                            field.data_type().clone(),
+                            existing_field.is_nullable(),
+                        );
+                        *existing_column_type = column_type;
+                        return Ok(self);
+                    }
+                }
 
                 // for now, insist the types are exactly the same
                 // (e.g. None and Some(..) don't match). We could
@@ -388,6 +421,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_merge_coerced_field_types() {
+        let int_schema = SchemaBuilder::new()
+            .influx_field("the_field", Integer)
+            .build()
+            .unwrap();
+        let float_schema = SchemaBuilder::new()
+            .influx_field("the_field", InfluxFieldType::Float)
+            .build()
+            .unwrap();
+
+        // the merged field has the type the values are converted to,
+        // whichever schema comes first
+        for (schema1, schema2) in &[(&int_schema, &float_schema), (&float_schema, &int_schema)] {
+            let merged_schema = SchemaMerger::new()
+                .with_coercions(vec![(Integer, InfluxFieldType::Float)])
+                .merge(schema1)
+                .unwrap()
+                .merge(schema2)
+                .unwrap()
+                .build();
+
+            assert_eq!(merged_schema, float_schema);
+        }
+
+        // fields can't be merged without a coercion in that direction
+        let merged_schema_error = SchemaMerger::new()
+            .with_coercions(vec![(InfluxFieldType::Float, InfluxFieldType::UInteger)])
+            .merge(&int_schema)
+            .unwrap()
+            .merge(&float_schema)
+            .unwrap_err();
+
+        assert!(matches!(
+            merged_schema_error,
+            Error::TryMergeBadColumnType { .. }
+        ));
+    }
+
     #[test]
     fn test_merge_incompatible_data_types() {
         // same field name with different type
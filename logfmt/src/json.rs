@@ -0,0 +1,182 @@
+use chrono::{SecondsFormat, Utc};
+use observability_deps::{
+    opentelemetry::trace::TraceContextExt,
+    tracing::{
+        self,
+        field::{Field, Visit},
+        subscriber::Interest,
+        Id, Level, Subscriber,
+    },
+    tracing_subscriber::{fmt::MakeWriter, layer::Context, registry::LookupSpan, Layer},
+};
+use serde_json::{Map, Value};
+use std::io::Write;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Implements a `tracing_subscriber::Layer` which writes one JSON object per
+/// line for each event, suitable for log ingestion by Loki, ELK and the like.
+///
+/// Every object has the same top-level fields:
+///
+/// * `time`: the RFC 3339 timestamp of the event
+/// * `level`: one of `trace`, `debug`, `info`, `warn` or `error`
+/// * `target`: the target of the event, usually its module path
+/// * `message`: the message of the event, if any
+/// * `span`: the name of the innermost span the event occurred in, if any
+/// * `trace_id`: the OpenTelemetry trace id of that span, if traces are
+///   exported
+///
+/// followed by the fields of the spans the event occurred in, from the
+/// outermost to the innermost, and the fields of the event itself. So for
+/// example the `db_name` of a span is included in all of the events within
+/// it, and a field of an event overrides a field of the same name of its
+/// spans.
+pub struct JsonLayer<W: MakeWriter> {
+    writer: W,
+}
+
+impl<W: MakeWriter> JsonLayer<W> {
+    /// Create a new JSON Layer to pass into tracing_subscriber
+    ///
+    /// Like [`LogFmtLayer`](crate::LogFmtLayer) this layer does not do any
+    /// filtering for levels itself.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+/// The fields recorded for a span, kept in the extensions of the span
+struct SpanFields(Map<String, Value>);
+
+impl<S, W> Layer<S> for JsonLayer<W>
+where
+    W: MakeWriter + 'static,
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn register_callsite(
+        &self,
+        _metadata: &'static tracing::Metadata<'static>,
+    ) -> tracing::subscriber::Interest {
+        Interest::always()
+    }
+
+    fn new_span(&self, attrs: &tracing::span::Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let span = ctx.span(id).expect("span should exist");
+        let mut visitor = JsonVisitor::default();
+        attrs.record(&mut visitor);
+        span.extensions_mut().insert(SpanFields(visitor.0));
+    }
+
+    fn on_record(&self, id: &Id, values: &tracing::span::Record<'_>, ctx: Context<'_, S>) {
+        let span = ctx.span(id).expect("span should exist");
+        let mut extensions = span.extensions_mut();
+        if let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() {
+            let mut visitor = JsonVisitor(std::mem::take(fields));
+            values.record(&mut visitor);
+            *fields = visitor.0;
+        }
+    }
+
+    fn max_level_hint(&self) -> Option<tracing::metadata::LevelFilter> {
+        None
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+
+        let mut object = Map::new();
+        object.insert(
+            "time".to_string(),
+            Utc::now()
+                .to_rfc3339_opts(SecondsFormat::Nanos, true)
+                .into(),
+        );
+        object.insert("level".to_string(), level_str(metadata.level()).into());
+        object.insert("target".to_string(), metadata.target().into());
+
+        let mut visitor = JsonVisitor::default();
+        if let Some(span) = ctx.lookup_current() {
+            object.insert("span".to_string(), span.name().into());
+            if let Some(trace_id) = trace_id() {
+                object.insert("trace_id".to_string(), trace_id.into());
+            }
+
+            for span in span.from_root().chain(std::iter::once(span)) {
+                if let Some(SpanFields(fields)) = span.extensions().get::<SpanFields>() {
+                    visitor
+                        .0
+                        .extend(fields.iter().map(|(k, v)| (k.clone(), v.clone())));
+                }
+            }
+        }
+        event.record(&mut visitor);
+
+        // fields never replace the fields common to all events
+        for (name, value) in visitor.0 {
+            object.entry(name).or_insert(value);
+        }
+
+        if let (Some(file), Some(line)) = (metadata.file(), metadata.line()) {
+            object
+                .entry("location")
+                .or_insert_with(|| format!("{}:{}", file, line).into());
+        }
+
+        // write the whole line at once to avoid interleaving with other lines
+        let mut line = serde_json::to_vec(&object).expect("JSON objects can be serialized");
+        line.push(b'\n');
+        self.writer.make_writer().write_all(&line).ok();
+    }
+}
+
+/// Returns the OpenTelemetry trace id of the current span as a hex string,
+/// if the OpenTelemetry layer is installed and the span is part of a trace
+fn trace_id() -> Option<String> {
+    let context = tracing::Span::current().context();
+    let span_context = context.span().span_context();
+    span_context
+        .is_valid()
+        .then(|| format!("{:032x}", span_context.trace_id().to_u128()))
+}
+
+fn level_str(level: &Level) -> &'static str {
+    match *level {
+        Level::TRACE => "trace",
+        Level::DEBUG => "debug",
+        Level::INFO => "info",
+        Level::WARN => "warn",
+        Level::ERROR => "error",
+    }
+}
+
+/// Records the fields of an event or span as JSON values
+#[derive(Default)]
+struct JsonVisitor(Map<String, Value>);
+
+impl Visit for JsonVisitor {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.0
+            .insert(field.name().to_string(), value.to_string().into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+}
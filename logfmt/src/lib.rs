@@ -12,6 +12,9 @@ use observability_deps::{
 use std::borrow::Cow;
 use std::{io::Write, time::SystemTime};
 
+mod json;
+pub use json::JsonLayer;
+
 /// Implements a `tracing_subscriber::Layer` which generates
 /// [logfmt] formatted log entries, suitable for log ingestion
 ///
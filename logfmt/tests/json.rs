@@ -0,0 +1,219 @@
+// Note that this needs to be an integration test (and separate from the
+// logfmt one) because the tracing structures are global, and once you set a
+// logging subscriber you can't undo that
+
+use logfmt::JsonLayer;
+use observability_deps::{
+    opentelemetry::{sdk::trace::TracerProvider, trace::TracerProvider as _},
+    tracing::{info, span, warn, Level},
+    tracing_subscriber::{self, fmt::MakeWriter, prelude::*},
+};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::io::{self, Cursor, Write};
+
+#[test]
+fn event_fields() {
+    let capture = CapturedWriter::new();
+
+    info!(
+        event_name = "foo bar",
+        bar = 1,
+        frr = false,
+        "This is an info message"
+    );
+    warn!("This is a warn message");
+
+    let lines = capture.to_json();
+    assert_eq!(
+        lines,
+        vec![
+            json!({
+                "level": "info",
+                "target": "json",
+                "message": "This is an info message",
+                "event_name": "foo bar",
+                "bar": 1,
+                "frr": false,
+            }),
+            json!({
+                "level": "warn",
+                "target": "json",
+                "message": "This is a warn message",
+            }),
+        ]
+    );
+}
+
+#[test]
+fn span_fields() {
+    let capture = CapturedWriter::new();
+
+    let span1 = span!(Level::INFO, "db_worker", db_name = "mydb", foo = "bar");
+    let _enter1 = span1.enter();
+    {
+        let span2 = span!(Level::INFO, "compact", foo = "baz", chunk_id = 3);
+        let _enter2 = span2.enter();
+        info!(chunk_id = 4, "info message in span 2");
+    }
+    info!("info message in span 1");
+
+    let mut lines = capture.to_json();
+
+    // spans are traced, so have a trace id
+    for line in &mut lines {
+        let trace_id = line
+            .as_object_mut()
+            .unwrap()
+            .remove("trace_id")
+            .expect("trace id of span");
+        let trace_id = trace_id.as_str().unwrap();
+        assert_eq!(trace_id.len(), 32);
+        assert!(trace_id.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    assert_eq!(
+        lines,
+        vec![
+            json!({
+                "level": "info",
+                "target": "json",
+                "message": "info message in span 2",
+                "span": "compact",
+                "db_name": "mydb",
+                "foo": "baz",
+                "chunk_id": 4,
+            }),
+            json!({
+                "level": "info",
+                "target": "json",
+                "message": "info message in span 1",
+                "span": "db_worker",
+                "db_name": "mydb",
+                "foo": "bar",
+            }),
+        ]
+    );
+}
+
+#[test]
+fn span_record() {
+    let capture = CapturedWriter::new();
+
+    let span = span!(Level::INFO, "my_span", foo = "bar", rows = 0);
+    let _enter = span.enter();
+    span.record("rows", &42);
+    info!("info message in span");
+
+    let mut lines = capture.to_json();
+    lines[0].as_object_mut().unwrap().remove("trace_id");
+
+    assert_eq!(
+        lines,
+        vec![json!({
+            "level": "info",
+            "target": "json",
+            "message": "info message in span",
+            "span": "my_span",
+            "foo": "bar",
+            "rows": 42,
+        })]
+    );
+}
+
+// Each thread has a local collection of lines that is captured to
+// This is needed because the rust test framework runs the
+// tests potentially using multiple threads but there is a single
+// global logger.
+thread_local! {
+    static LOG_LINES: Mutex<Cursor<Vec<u8>>> = Mutex::new(Cursor::new(Vec::new()));
+}
+
+// The tracer provider must outlive the tracers it creates, or their spans
+// have no trace id
+static TRACER_PROVIDER: Lazy<TracerProvider> = Lazy::new(|| TracerProvider::builder().build());
+
+// Since we can only setup logging once, we need to have global to
+// use it among test cases
+static GLOBAL_WRITER: Lazy<Mutex<CapturedWriter>> = Lazy::new(|| {
+    let capture = CapturedWriter::default();
+    let tracer = TRACER_PROVIDER.get_tracer("json", None);
+    tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .with(JsonLayer::new(capture.clone()))
+        .init();
+    Mutex::new(capture)
+});
+
+// This thing captures log lines
+#[derive(Default, Clone)]
+struct CapturedWriter {
+    // all state is held in the LOG_LINES thread local variable
+}
+
+impl CapturedWriter {
+    fn new() -> Self {
+        let global_writer = GLOBAL_WRITER.lock();
+        global_writer.clone().clear()
+    }
+
+    /// Clear all thread local state
+    fn clear(self) -> Self {
+        LOG_LINES.with(|lines| {
+            let mut cursor = lines.lock();
+            cursor.get_mut().clear()
+        });
+        self
+    }
+
+    /// Parses the captured lines, checking and removing the time and location
+    /// of each
+    fn to_json(&self) -> Vec<Value> {
+        LOG_LINES.with(|lines| {
+            let cursor = lines.lock();
+            let bytes: Vec<u8> = cursor.get_ref().clone();
+            String::from_utf8(bytes)
+                .expect("valid utf8")
+                .lines()
+                .map(|line| {
+                    let mut value: Value = serde_json::from_str(line).expect("valid JSON");
+                    let object = value.as_object_mut().expect("JSON object");
+
+                    let time = object.remove("time").expect("time of event");
+                    chrono::DateTime::parse_from_rfc3339(time.as_str().unwrap())
+                        .expect("RFC 3339 time");
+
+                    let location = object.remove("location").expect("location of event");
+                    assert!(location.as_str().unwrap().contains("json.rs:"));
+
+                    value
+                })
+                .collect()
+        })
+    }
+}
+
+impl Write for CapturedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        LOG_LINES.with(|lines| {
+            let mut cursor = lines.lock();
+            cursor.write(buf)
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        LOG_LINES.with(|lines| {
+            let mut cursor = lines.lock();
+            cursor.flush()
+        })
+    }
+}
+
+impl MakeWriter for CapturedWriter {
+    type Writer = Self;
+
+    fn make_writer(&self) -> Self::Writer {
+        self.clone()
+    }
+}
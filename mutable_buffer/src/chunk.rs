@@ -4,12 +4,17 @@ use arrow::record_batch::RecordBatch;
 use parking_lot::Mutex;
 use snafu::{ResultExt, Snafu};
 
-use data_types::partition_metadata::TableSummary;
+use data_types::{
+    database_rules::{SchemaConflictPolicy, TypeCoercionRule},
+    delete_predicate::DeletePredicate,
+    partition_metadata::TableSummary,
+};
 use entry::TableBatch;
 use internal_types::selection::Selection;
 use metrics::GaugeValue;
 
 use crate::chunk::snapshot::ChunkSnapshot;
+use crate::interner::StringInterner;
 use crate::table::Table;
 
 pub mod snapshot;
@@ -35,6 +40,9 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 pub struct ChunkMetrics {
     /// keep track of memory used by chunk
     memory_bytes: GaugeValue,
+
+    /// keep track of memory allocated by chunk, including spare capacity
+    allocated_bytes: GaugeValue,
 }
 
 impl ChunkMetrics {
@@ -45,11 +53,19 @@ impl ChunkMetrics {
     pub fn new_unregistered() -> Self {
         Self {
             memory_bytes: GaugeValue::new_unregistered(),
+            allocated_bytes: GaugeValue::new_unregistered(),
         }
     }
 
-    pub fn new(_metrics: &metrics::Domain, memory_bytes: GaugeValue) -> Self {
-        Self { memory_bytes }
+    pub fn new(
+        _metrics: &metrics::Domain,
+        memory_bytes: GaugeValue,
+        allocated_bytes: GaugeValue,
+    ) -> Self {
+        Self {
+            memory_bytes,
+            allocated_bytes,
+        }
     }
 }
 
@@ -75,8 +91,19 @@ pub struct Chunk {
 
 impl Chunk {
     pub fn new(table_name: impl AsRef<str>, metrics: ChunkMetrics) -> Self {
+        Self::new_with_interner(table_name, metrics, None)
+    }
+
+    /// Creates a chunk that shares the values of its tag columns with other
+    /// chunks through `interner`, if any, instead of storing its own copy
+    /// of them
+    pub fn new_with_interner(
+        table_name: impl AsRef<str>,
+        metrics: ChunkMetrics,
+        interner: Option<Arc<StringInterner>>,
+    ) -> Self {
         let table_name = Arc::from(table_name.as_ref());
-        let table = Table::new(Arc::clone(&table_name));
+        let table = Table::new(Arc::clone(&table_name), interner);
 
         let mut chunk = Self {
             table_name,
@@ -85,10 +112,13 @@ impl Chunk {
             snapshot: Mutex::new(None),
         };
         chunk.metrics.memory_bytes.set(chunk.size());
+        chunk.metrics.allocated_bytes.set(chunk.allocated_bytes());
         chunk
     }
 
-    /// Write the contents of a [`TableBatch`] into this Chunk.
+    /// Write the contents of a [`TableBatch`] into this Chunk, handling
+    /// columns whose type conflicts with the existing columns according to
+    /// `coercions` or else `policy`.
     ///
     /// Panics if the batch specifies a different name for the table in this Chunk
     pub fn write_table_batch(
@@ -96,6 +126,8 @@ impl Chunk {
         sequencer_id: u32,
         sequence_number: u64,
         batch: TableBatch<'_>,
+        policy: SchemaConflictPolicy,
+        coercions: &[TypeCoercionRule],
     ) -> Result<()> {
         let table_name = batch.name();
         assert_eq!(
@@ -106,7 +138,7 @@ impl Chunk {
 
         let columns = batch.columns();
         self.table
-            .write_columns(sequencer_id, sequence_number, columns)
+            .write_columns(sequencer_id, sequence_number, columns, policy, coercions)
             .context(TableWrite { table_name })?;
 
         // Invalidate chunk snapshot
@@ -116,10 +148,51 @@ impl Chunk {
             .expect("concurrent readers/writers to MBChunk") = None;
 
         self.metrics.memory_bytes.set(self.size());
+        self.metrics.allocated_bytes.set(self.allocated_bytes());
 
         Ok(())
     }
 
+    /// Checks that the contents of a [`TableBatch`] can be written into this
+    /// Chunk with [`write_table_batch`](Self::write_table_batch) without a
+    /// schema conflict that `coercions` and `policy` reject
+    pub fn validate_table_batch(
+        &self,
+        batch: &TableBatch<'_>,
+        policy: SchemaConflictPolicy,
+        coercions: &[TypeCoercionRule],
+    ) -> Result<()> {
+        let table_name = batch.name();
+        self.table
+            .validate_columns(&batch.columns(), policy, coercions)
+            .context(TableWrite { table_name })?;
+
+        Ok(())
+    }
+
+    /// Masks the rows written so far that match `predicate`, so that they
+    /// are no longer part of the snapshots of this chunk, and returns their
+    /// number. Rows written later on are not affected by the predicate.
+    ///
+    /// Note: the summary statistics of the chunk still include the rows
+    pub fn delete(&mut self, predicate: &DeletePredicate) -> usize {
+        let deleted = self.table.delete(predicate);
+        if deleted == 0 {
+            return 0;
+        }
+
+        // Invalidate chunk snapshot
+        *self
+            .snapshot
+            .try_lock()
+            .expect("concurrent readers/writers to MBChunk") = None;
+
+        self.metrics.memory_bytes.set(self.size());
+        self.metrics.allocated_bytes.set(self.allocated_bytes());
+
+        deleted
+    }
+
     /// Returns a queryable snapshot of this chunk
     #[cfg(not(feature = "nocache"))]
     pub fn snapshot(&self) -> Arc<ChunkSnapshot> {
@@ -145,6 +218,31 @@ impl Chunk {
         ))
     }
 
+    /// Returns a queryable snapshot of this chunk for when it is closed and
+    /// no longer written to. Unlike [`snapshot`](Self::snapshot) the data of
+    /// the numeric and tag columns is moved into the snapshot instead of
+    /// being copied, which leaves this chunk empty
+    pub fn take_snapshot(&mut self) -> Arc<ChunkSnapshot> {
+        let table = std::mem::replace(
+            &mut self.table,
+            Table::new(Arc::clone(&self.table_name), None),
+        );
+
+        let cached = self.snapshot.get_mut().take();
+        let snapshot = match cached {
+            Some(snapshot) => snapshot,
+            None => Arc::new(ChunkSnapshot::from_table(
+                table,
+                Arc::clone(&self.table_name),
+                self.metrics.memory_bytes.clone_empty(),
+            )),
+        };
+
+        self.metrics.memory_bytes.set(self.size());
+        self.metrics.allocated_bytes.set(self.allocated_bytes());
+        snapshot
+    }
+
     /// Return the name of the table in this chunk
     pub fn table_name(&self) -> &Arc<str> {
         &self.table_name
@@ -180,6 +278,15 @@ impl Chunk {
         self.table.size() + self.table_name.len()
     }
 
+    /// Return the memory allocated by the chunk, in bytes, including the
+    /// dictionaries and the capacity of the column buffers reserved for
+    /// future writes. This is at least [`size`](Self::size).
+    ///
+    /// Note: This does not include the size of any cached ChunkSnapshot
+    pub fn allocated_bytes(&self) -> usize {
+        self.table.allocated_bytes() + self.table_name.len()
+    }
+
     /// Returns an iterator over (column_name, estimated_size) for all
     /// columns in this chunk.
     pub fn column_sizes(&self) -> impl Iterator<Item = (&str, usize)> + '_ {
@@ -216,7 +323,7 @@ pub mod test_helpers {
             );
 
             for batch in table_batches {
-                chunk.write_table_batch(1, 5, batch)?;
+                chunk.write_table_batch(1, 5, batch, SchemaConflictPolicy::Reject, &[])?;
             }
         }
 
@@ -229,7 +336,10 @@ mod tests {
     use std::num::NonZeroU64;
 
     use arrow_util::assert_batches_eq;
-    use data_types::partition_metadata::{ColumnSummary, InfluxDbType, StatValues, Statistics};
+    use data_types::{
+        partition_metadata::{ColumnSummary, InfluxDbType, StatValues, Statistics},
+        timestamp::TimestampRange,
+    };
 
     use super::test_helpers::write_lp_to_chunk;
     use super::*;
@@ -348,6 +458,57 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_write_repeated_values_and_nulls() {
+        let mut chunk = Chunk::new("cpu", ChunkMetrics::new_unregistered());
+        let lp = vec![
+            "cpu,host=a val=1 1",
+            "cpu,host=a val=2 2",
+            "cpu val=3 3",
+            "cpu,host=a val=4 4",
+            "cpu,host=b ok=true 5",
+        ]
+        .join("\n");
+        write_lp_to_chunk(&lp, &mut chunk).unwrap();
+
+        assert_batches_eq!(
+            vec![
+                "+------+------+-------------------------------+-----+",
+                "| host | ok   | time                          | val |",
+                "+------+------+-------------------------------+-----+",
+                "| a    |      | 1970-01-01 00:00:00.000000001 | 1   |",
+                "| a    |      | 1970-01-01 00:00:00.000000002 | 2   |",
+                "|      |      | 1970-01-01 00:00:00.000000003 | 3   |",
+                "| a    |      | 1970-01-01 00:00:00.000000004 | 4   |",
+                "| b    | true | 1970-01-01 00:00:00.000000005 |     |",
+                "+------+------+-------------------------------+-----+",
+            ],
+            &chunk_to_batches(&chunk)
+        );
+
+        let summary = chunk.table_summary();
+        let host = summary.column("host").unwrap();
+        assert_eq!(
+            host.stats,
+            Statistics::String(StatValues {
+                min: Some("a".to_string()),
+                max: Some("b".to_string()),
+                count: 4,
+                distinct_count: Some(NonZeroU64::new(3).unwrap())
+            })
+        );
+        let val = summary.column("val").unwrap();
+        assert_eq!(
+            val.stats,
+            Statistics::F64(StatValues {
+                min: Some(1.),
+                max: Some(4.),
+                count: 4,
+                distinct_count: None
+            })
+        );
+    }
+
     #[test]
     #[cfg(not(feature = "nocache"))]
     fn test_snapshot() {
@@ -368,6 +529,153 @@ mod tests {
         assert_eq!(Arc::as_ptr(&s3), Arc::as_ptr(&s4));
     }
 
+    #[test]
+    fn test_take_snapshot() {
+        let memory = GaugeValue::new_unregistered();
+        let metrics = ChunkMetrics {
+            memory_bytes: memory.clone_empty(),
+            allocated_bytes: GaugeValue::new_unregistered(),
+        };
+        let mut chunk = Chunk::new("cpu", metrics);
+
+        let lp = vec![
+            "cpu,host=a val=23,count=1i,flag=true,msg=\"hi\" 1",
+            "cpu,host=b val=2,count=2i 2",
+        ]
+        .join("\n");
+        write_lp_to_chunk(&lp, &mut chunk).unwrap();
+
+        let expected = vec![
+            "+-------+------+------+-----+-------------------------------+-----+",
+            "| count | flag | host | msg | time                          | val |",
+            "+-------+------+------+-----+-------------------------------+-----+",
+            "| 1     | true | a    | hi  | 1970-01-01 00:00:00.000000001 | 23  |",
+            "| 2     |      | b    |     | 1970-01-01 00:00:00.000000002 | 2   |",
+            "+-------+------+------+-----+-------------------------------+-----+",
+        ];
+        let summary = chunk.table_summary();
+
+        let snapshot = chunk.take_snapshot();
+        assert_batches_eq!(&expected, &[snapshot.read_filter(Selection::All).unwrap()]);
+        assert_eq!(snapshot.table_summary(), summary);
+
+        // the data was moved into the snapshot
+        assert_eq!(chunk.rows(), 0);
+        assert_eq!(memory.get_total(), snapshot.size() + chunk.size());
+    }
+
+    #[test]
+    fn test_delete() {
+        let mut chunk = Chunk::new("cpu", ChunkMetrics::new_unregistered());
+
+        let lp = vec![
+            "cpu,host=a val=1 10",
+            "cpu,host=b val=2 10",
+            "cpu,host=a val=3 20",
+            "cpu val=4 10",
+        ]
+        .join("\n");
+        write_lp_to_chunk(&lp, &mut chunk).unwrap();
+        let snapshot = chunk.snapshot();
+
+        let predicate = DeletePredicate {
+            table_name: "cpu".to_string(),
+            range: TimestampRange::new(0, 15),
+            tags: vec![("host".to_string(), "a".to_string())]
+                .into_iter()
+                .collect(),
+        };
+        assert_eq!(chunk.delete(&predicate), 1);
+        // the rows are only masked once
+        assert_eq!(chunk.delete(&predicate), 0);
+
+        // later writes are not masked
+        write_lp_to_chunk("cpu,host=a val=5 10", &mut chunk).unwrap();
+
+        let expected = vec![
+            "+------+-------------------------------+-----+",
+            "| host | time                          | val |",
+            "+------+-------------------------------+-----+",
+            "| b    | 1970-01-01 00:00:00.000000010 | 2   |",
+            "| a    | 1970-01-01 00:00:00.000000020 | 3   |",
+            "|      | 1970-01-01 00:00:00.000000010 | 4   |",
+            "| a    | 1970-01-01 00:00:00.000000010 | 5   |",
+            "+------+-------------------------------+-----+",
+        ];
+        // the cached snapshot was invalidated
+        assert!(!Arc::ptr_eq(&snapshot, &chunk.snapshot()));
+        assert_batches_eq!(
+            &expected,
+            &[chunk.snapshot().read_filter(Selection::All).unwrap()]
+        );
+        assert_eq!(chunk.rows(), 5);
+
+        // the rows stay masked when the data is moved into a snapshot
+        chunk.snapshot.get_mut().take();
+        assert_batches_eq!(
+            &expected,
+            &[chunk.take_snapshot().read_filter(Selection::All).unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_allocated_bytes() {
+        let allocated = GaugeValue::new_unregistered();
+        let metrics = ChunkMetrics {
+            memory_bytes: GaugeValue::new_unregistered(),
+            allocated_bytes: allocated.clone_empty(),
+        };
+        let mut chunk = Chunk::new("cpu", metrics);
+
+        for i in 0..100 {
+            let lp = format!("cpu,host=host{} val=23,msg=\"hello\",ok=true {}", i % 3, i);
+            write_lp_to_chunk(&lp, &mut chunk).unwrap();
+
+            // the spare capacity of the columns is accounted for
+            assert!(chunk.allocated_bytes() >= chunk.size());
+            assert_eq!(allocated.get_total(), chunk.allocated_bytes());
+        }
+    }
+
+    #[test]
+    fn test_shared_tag_values() {
+        let interner = Arc::new(StringInterner::new());
+        let new_chunk = || {
+            Chunk::new_with_interner(
+                "cpu",
+                ChunkMetrics::new_unregistered(),
+                Some(Arc::clone(&interner)),
+            )
+        };
+
+        let mut chunk1 = new_chunk();
+        write_lp_to_chunk("cpu,host=a,region=west val=1 1", &mut chunk1).unwrap();
+        write_lp_to_chunk("cpu,host=b val=2 2", &mut chunk1).unwrap();
+
+        let mut chunk2 = new_chunk();
+        write_lp_to_chunk("cpu,host=b,region=east val=3 3", &mut chunk2).unwrap();
+        assert_eq!(interner.len(), 4);
+
+        assert_batches_eq!(
+            vec![
+                "+------+--------+-------------------------------+-----+",
+                "| host | region | time                          | val |",
+                "+------+--------+-------------------------------+-----+",
+                "| a    | west   | 1970-01-01 00:00:00.000000001 | 1   |",
+                "| b    |        | 1970-01-01 00:00:00.000000002 | 2   |",
+                "+------+--------+-------------------------------+-----+",
+            ],
+            &chunk_to_batches(&chunk1)
+        );
+
+        // "b" is still used by chunk2
+        std::mem::drop(chunk1);
+        assert_eq!(interner.len(), 2);
+
+        std::mem::drop(chunk2);
+        assert!(interner.is_empty());
+    }
+
     fn chunk_to_batches(chunk: &Chunk) -> Vec<RecordBatch> {
         let mut batches = vec![];
         chunk.table_to_arrow(&mut batches, Selection::All).unwrap();
@@ -18,6 +18,7 @@ use internal_types::schema::{Schema, TIME_COLUMN_NAME};
 use internal_types::selection::Selection;
 
 use super::Chunk;
+use crate::table::Table;
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -67,6 +68,35 @@ impl ChunkSnapshot {
         s
     }
 
+    /// Creates a snapshot of `table`, moving its data into the snapshot
+    /// where possible instead of copying it
+    pub(crate) fn from_table(
+        table: Table,
+        table_name: Arc<str>,
+        memory: metrics::GaugeValue,
+    ) -> Self {
+        let schema = table
+            .schema(Selection::All)
+            .log_if_error("ChunkSnapshot getting table schema")
+            .unwrap();
+        let stats = table.stats();
+
+        let batch = table
+            .into_arrow()
+            .log_if_error("ChunkSnapshot converting table to arrow")
+            .unwrap();
+
+        let mut s = Self {
+            schema: Arc::new(schema),
+            batch,
+            table_name,
+            stats,
+            memory,
+        };
+        s.memory.set(s.size());
+        s
+    }
+
     /// returns true if there is no data in this snapshot
     pub fn is_empty(&self) -> bool {
         self.batch.num_rows() == 0
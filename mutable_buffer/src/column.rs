@@ -6,17 +6,21 @@ use arrow::{
         Array, ArrayDataBuilder, ArrayRef, BooleanArray, DictionaryArray, Float64Array, Int64Array,
         TimestampNanosecondArray, UInt64Array,
     },
-    datatypes::{DataType, Int32Type},
+    buffer::Buffer,
+    datatypes::{ArrowNativeType, DataType, Int32Type},
 };
 use snafu::{ensure, Snafu};
 
 use arrow_util::bitset::{iter_set_positions, BitSet};
+use arrow_util::buffer::TypedBuffer;
 use arrow_util::string::PackedStringArray;
+use data_types::database_rules::{ColumnType, TypeCoercionRule};
 use data_types::partition_metadata::{IsNan, StatValues, Statistics};
 use entry::Column as EntryColumn;
 use internal_types::schema::{InfluxColumnType, InfluxFieldType, TIME_DATA_TYPE};
 
 use crate::dictionary::{Dictionary, DID, INVALID_DID};
+use crate::interner::StringInterner;
 use std::convert::TryInto;
 
 #[derive(Debug, Snafu)]
@@ -51,16 +55,22 @@ pub struct Column {
 
 #[derive(Debug)]
 pub enum ColumnData {
-    F64(Vec<f64>, StatValues<f64>),
-    I64(Vec<i64>, StatValues<i64>),
-    U64(Vec<u64>, StatValues<u64>),
+    F64(TypedBuffer<f64>, StatValues<f64>),
+    I64(TypedBuffer<i64>, StatValues<i64>),
+    U64(TypedBuffer<u64>, StatValues<u64>),
     String(PackedStringArray<i32>, StatValues<String>),
     Bool(BitSet, StatValues<bool>),
-    Tag(Vec<DID>, Dictionary, StatValues<String>),
+    Tag(TypedBuffer<DID>, Dictionary, StatValues<String>),
 }
 
 impl Column {
-    pub fn new(row_count: usize, column_type: InfluxColumnType) -> Self {
+    /// Creates a column of `row_count` nulls. The values of tag columns
+    /// are shared with other chunks through `interner`, if any
+    pub fn new(
+        row_count: usize,
+        column_type: InfluxColumnType,
+        interner: Option<&Arc<StringInterner>>,
+    ) -> Self {
         let mut valid = BitSet::new();
         valid.append_unset(row_count);
 
@@ -70,22 +80,27 @@ impl Column {
                 data.append_unset(row_count);
                 ColumnData::Bool(data, StatValues::default())
             }
-            InfluxColumnType::Field(InfluxFieldType::UInteger) => {
-                ColumnData::U64(vec![0; row_count], StatValues::default())
-            }
-            InfluxColumnType::Field(InfluxFieldType::Float) => {
-                ColumnData::F64(vec![0.0; row_count], StatValues::default())
-            }
+            InfluxColumnType::Field(InfluxFieldType::UInteger) => ColumnData::U64(
+                TypedBuffer::new_with_value(row_count, 0),
+                StatValues::default(),
+            ),
+            InfluxColumnType::Field(InfluxFieldType::Float) => ColumnData::F64(
+                TypedBuffer::new_with_value(row_count, 0.0),
+                StatValues::default(),
+            ),
             InfluxColumnType::Field(InfluxFieldType::Integer) | InfluxColumnType::Timestamp => {
-                ColumnData::I64(vec![0; row_count], StatValues::default())
+                ColumnData::I64(
+                    TypedBuffer::new_with_value(row_count, 0),
+                    StatValues::default(),
+                )
             }
             InfluxColumnType::Field(InfluxFieldType::String) => ColumnData::String(
                 PackedStringArray::new_empty(row_count),
                 StatValues::default(),
             ),
             InfluxColumnType::Tag => ColumnData::Tag(
-                vec![INVALID_DID; row_count],
-                Default::default(),
+                TypedBuffer::new_with_value(row_count, INVALID_DID),
+                Dictionary::new(interner),
                 StatValues::default(),
             ),
         };
@@ -111,19 +126,147 @@ impl Column {
         Ok(())
     }
 
+    /// Returns true if the values of `entry` have a different numeric field
+    /// type than this column, and every one of them can be converted to the
+    /// type of this column without loss
+    pub fn can_coerce(&self, entry: &EntryColumn<'_>) -> bool {
+        self.can_represent(entry, true)
+    }
+
+    /// Returns true if one of `coercions` allows the values of `entry` to be
+    /// converted to the type of this column, and every one of them can be
+    /// represented by it. Unlike [`can_coerce`](Self::can_coerce), integers
+    /// may lose precision when converted to floats
+    pub fn can_convert(&self, entry: &EntryColumn<'_>, coercions: &[TypeCoercionRule]) -> bool {
+        coercions.iter().any(|rule| {
+            field_type(&rule.from) == entry.influx_type()
+                && field_type(&rule.to) == self.influx_type
+        }) && self.can_represent(entry, false)
+    }
+
+    /// Returns true if this is an integer field column, the values of `entry`
+    /// are floats and one of `coercions` allows the column to be widened to
+    /// floats, see [`widen_to_float`](Self::widen_to_float)
+    pub fn can_widen(&self, entry: &EntryColumn<'_>, coercions: &[TypeCoercionRule]) -> bool {
+        let float = InfluxColumnType::Field(InfluxFieldType::Float);
+        matches!(
+            self.influx_type,
+            InfluxColumnType::Field(InfluxFieldType::Integer)
+                | InfluxColumnType::Field(InfluxFieldType::UInteger)
+        ) && entry.influx_type() == float
+            && coercions.iter().any(|rule| {
+                field_type(&rule.from) == self.influx_type && field_type(&rule.to) == float
+            })
+    }
+
+    /// Returns true if the values of `entry` have a different numeric field
+    /// type than this column, and every one of them can be represented by the
+    /// type of this column. If `exact` is true, integers converted to floats
+    /// must not lose precision
+    fn can_represent(&self, entry: &EntryColumn<'_>, exact: bool) -> bool {
+        // Integers with a larger magnitude can't be represented exactly by a f64
+        const MAX_EXACT_F64: u64 = 1 << f64::MANTISSA_DIGITS;
+
+        let inner = entry.inner();
+        let i64_values = || inner.values_as_i64values().and_then(|v| v.values());
+        let u64_values = || inner.values_as_u64values().and_then(|v| v.values());
+
+        match (self.influx_type, entry.influx_type()) {
+            (
+                InfluxColumnType::Field(InfluxFieldType::Float),
+                InfluxColumnType::Field(InfluxFieldType::Integer),
+            ) => i64_values().map_or(false, |values| {
+                !exact
+                    || values
+                        .iter()
+                        .all(|v| (-(MAX_EXACT_F64 as i64)..=MAX_EXACT_F64 as i64).contains(&v))
+            }),
+            (
+                InfluxColumnType::Field(InfluxFieldType::Float),
+                InfluxColumnType::Field(InfluxFieldType::UInteger),
+            ) => u64_values().map_or(false, |values| {
+                !exact || values.iter().all(|v| v <= MAX_EXACT_F64)
+            }),
+            (
+                InfluxColumnType::Field(InfluxFieldType::Integer),
+                InfluxColumnType::Field(InfluxFieldType::UInteger),
+            ) => u64_values().map_or(false, |values| values.iter().all(|v| v <= i64::MAX as u64)),
+            (
+                InfluxColumnType::Field(InfluxFieldType::UInteger),
+                InfluxColumnType::Field(InfluxFieldType::Integer),
+            ) => i64_values().map_or(false, |values| values.iter().all(|v| v >= 0)),
+            _ => false,
+        }
+    }
+
+    /// Converts the values of an integer field column to floats, so that
+    /// float values can be appended to it. The distinct count is discarded,
+    /// as distinct integers may convert to the same float
+    pub fn widen_to_float(&mut self) {
+        fn widen<T: Copy>(
+            values: &TypedBuffer<T>,
+            stats: &StatValues<T>,
+            f: impl Fn(T) -> f64,
+        ) -> ColumnData {
+            let mut data = TypedBuffer::new();
+            data.extend(values.as_slice().iter().map(|v| f(*v)));
+            let stats = StatValues {
+                min: stats.min.map(&f),
+                max: stats.max.map(&f),
+                count: stats.count,
+                distinct_count: None,
+            };
+            ColumnData::F64(data, stats)
+        }
+
+        let data = match &self.data {
+            ColumnData::I64(values, stats) if self.influx_type != InfluxColumnType::Timestamp => {
+                widen(values, stats, |v| v as f64)
+            }
+            ColumnData::U64(values, stats) => widen(values, stats, |v| v as f64),
+            _ => return,
+        };
+
+        self.data = data;
+        self.influx_type = InfluxColumnType::Field(InfluxFieldType::Float);
+    }
+
     pub fn influx_type(&self) -> InfluxColumnType {
         self.influx_type
     }
 
-    pub fn append(&mut self, entry: &EntryColumn<'_>) -> Result<()> {
-        self.validate_schema(entry)?;
+    /// Appends the values of `entry` to this column. If `rows` is given, only
+    /// the rows for which it is true are appended. Values of a different type
+    /// are converted if [`can_coerce`](Self::can_coerce) or
+    /// [`can_convert`](Self::can_convert) allows it, and the column is
+    /// widened to floats first if [`can_widen`](Self::can_widen) allows it
+    pub fn append(
+        &mut self,
+        entry: &EntryColumn<'_>,
+        rows: Option<&[bool]>,
+        coercions: &[TypeCoercionRule],
+    ) -> Result<()> {
+        if entry.influx_type() != self.influx_type {
+            if self.can_widen(entry, coercions) {
+                self.widen_to_float();
+            } else if !self.can_coerce(entry) && !self.can_convert(entry, coercions) {
+                self.validate_schema(entry)?;
+            }
+        }
 
-        let row_count = entry.row_count;
-        if row_count == 0 {
+        if entry.row_count == 0 {
             return Ok(());
         }
 
         let mask = construct_valid_mask(entry)?;
+        let (row_count, mask, selected) = match rows {
+            Some(rows) => {
+                let (row_count, mask, selected) = select_rows(&mask, rows);
+                (row_count, mask, Some(selected))
+            }
+            None => (entry.row_count, mask, None),
+        };
+        let selected = selected.as_deref();
 
         match &mut self.data {
             ColumnData::Bool(col_data, stats) => {
@@ -138,52 +281,60 @@ impl Column {
                 col_data.append_unset(row_count);
 
                 let initial_non_null_count = stats.count;
+                let mut to_add = 0;
 
-                for (idx, value) in iter_set_positions(&mask).zip(entry_data) {
+                for (idx, value) in
+                    iter_set_positions(&mask).zip(select(entry_data.iter(), selected))
+                {
                     stats.update(value);
+                    to_add += 1;
 
                     if *value {
                         col_data.set(data_offset + idx);
                     }
                 }
-                assert_eq!(
-                    stats.count - initial_non_null_count,
-                    entry_data.len() as u64
-                );
-            }
-            ColumnData::U64(col_data, stats) => {
-                let entry_data = entry
-                    .inner()
-                    .values_as_u64values()
-                    .expect("invalid flatbuffer")
-                    .values()
-                    .expect("invalid payload")
-                    .into_iter();
-
-                handle_write(row_count, &mask, entry_data, col_data, stats);
-            }
-            ColumnData::F64(col_data, stats) => {
-                let entry_data = entry
-                    .inner()
-                    .values_as_f64values()
-                    .expect("invalid flatbuffer")
-                    .values()
-                    .expect("invalid payload")
-                    .into_iter();
-
-                handle_write(row_count, &mask, entry_data, col_data, stats);
-            }
-            ColumnData::I64(col_data, stats) => {
-                let entry_data = entry
-                    .inner()
-                    .values_as_i64values()
-                    .expect("invalid flatbuffer")
-                    .values()
-                    .expect("invalid payload")
-                    .into_iter();
-
-                handle_write(row_count, &mask, entry_data, col_data, stats);
+                assert_eq!(stats.count - initial_non_null_count, to_add);
             }
+            ColumnData::U64(col_data, stats) => match entry.influx_type() {
+                InfluxColumnType::Field(InfluxFieldType::Integer) => {
+                    let entry_data = i64_values(entry).map(|v| v as u64);
+                    handle_write(row_count, &mask, entry_data, selected, col_data, stats);
+                }
+                _ => {
+                    let entry_data = u64_values(entry);
+                    handle_write(row_count, &mask, entry_data, selected, col_data, stats);
+                }
+            },
+            ColumnData::F64(col_data, stats) => match entry.influx_type() {
+                InfluxColumnType::Field(InfluxFieldType::Integer) => {
+                    let entry_data = i64_values(entry).map(|v| v as f64);
+                    handle_write(row_count, &mask, entry_data, selected, col_data, stats);
+                }
+                InfluxColumnType::Field(InfluxFieldType::UInteger) => {
+                    let entry_data = u64_values(entry).map(|v| v as f64);
+                    handle_write(row_count, &mask, entry_data, selected, col_data, stats);
+                }
+                _ => {
+                    let entry_data = entry
+                        .inner()
+                        .values_as_f64values()
+                        .expect("invalid flatbuffer")
+                        .values()
+                        .expect("invalid payload")
+                        .into_iter();
+                    handle_write(row_count, &mask, entry_data, selected, col_data, stats);
+                }
+            },
+            ColumnData::I64(col_data, stats) => match entry.influx_type() {
+                InfluxColumnType::Field(InfluxFieldType::UInteger) => {
+                    let entry_data = u64_values(entry).map(|v| v as i64);
+                    handle_write(row_count, &mask, entry_data, selected, col_data, stats);
+                }
+                _ => {
+                    let entry_data = i64_values(entry);
+                    handle_write(row_count, &mask, entry_data, selected, col_data, stats);
+                }
+            },
             ColumnData::String(col_data, stats) => {
                 let entry_data = entry
                     .inner()
@@ -194,12 +345,14 @@ impl Column {
 
                 let data_offset = col_data.len();
                 let initial_non_null_count = stats.count;
-                let to_add = entry_data.len();
+                let mut to_add = 0;
 
-                for (str, idx) in entry_data.iter().zip(iter_set_positions(&mask)) {
+                for (str, idx) in select(entry_data.iter(), selected).zip(iter_set_positions(&mask))
+                {
                     col_data.extend(data_offset + idx - col_data.len());
                     stats.update(str);
                     col_data.append(str);
+                    to_add += 1;
                 }
 
                 col_data.extend(data_offset + row_count - col_data.len());
@@ -214,22 +367,42 @@ impl Column {
                     .values()
                     .expect("invalid payload");
 
-                let data_offset = col_data.len();
-                col_data.resize(data_offset + row_count, INVALID_DID);
-
                 let initial_non_null_count = stats.count;
-                let to_add = entry_data.len();
-
-                for (idx, value) in iter_set_positions(&mask).zip(entry_data) {
-                    stats.update(value);
-                    col_data[data_offset + idx] = dictionary.lookup_value_or_insert(value);
-                }
+                let mut values = select(entry_data.iter(), selected);
+
+                // Consecutive rows usually belong to the same series, so the
+                // dictionary and the min/max statistics are only consulted
+                // when the value changes
+                let mut last: Option<(&str, DID)> = None;
+                let ids = (0..row_count).map(|idx| {
+                    if !is_set(&mask, idx) {
+                        return INVALID_DID;
+                    }
+                    let value = values.next().expect("fewer values than valid rows");
+                    match last {
+                        Some((last_value, id)) if last_value == value => {
+                            stats.count += 1;
+                            id
+                        }
+                        _ => {
+                            stats.update(value);
+                            let id = dictionary.lookup_value_or_insert(value);
+                            last = Some((value, id));
+                            id
+                        }
+                    }
+                });
+                col_data.extend(ids);
 
-                assert_eq!(stats.count - initial_non_null_count, to_add as u64);
+                assert!(values.next().is_none(), "more values than valid rows");
+                assert_eq!(
+                    stats.count - initial_non_null_count,
+                    count_set(&mask, row_count) as u64
+                );
             }
         };
 
-        self.valid.append_bits(entry.row_count, &mask);
+        self.valid.append_bits(row_count, &mask);
         Ok(())
     }
 
@@ -255,6 +428,26 @@ impl Column {
         self.valid.len()
     }
 
+    /// Returns the value of row `idx` of a tag column, or None if the row is
+    /// null or this is not a tag column
+    pub fn tag_value(&self, idx: usize) -> Option<&str> {
+        match &self.data {
+            ColumnData::Tag(data, dictionary, _) if self.valid.get(idx) => {
+                dictionary.lookup_id(data.as_slice()[idx])
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the value of row `idx` of an integer or timestamp column, or
+    /// None if the row is null or this is a column of another type
+    pub fn i64_value(&self, idx: usize) -> Option<i64> {
+        match &self.data {
+            ColumnData::I64(data, _) if self.valid.get(idx) => Some(data.as_slice()[idx]),
+            _ => None,
+        }
+    }
+
     pub fn stats(&self) -> Statistics {
         match &self.data {
             ColumnData::F64(_, stats) => Statistics::F64(stats.clone()),
@@ -263,7 +456,7 @@ impl Column {
             ColumnData::Bool(_, stats) => Statistics::Bool(stats.clone()),
             ColumnData::String(_, stats) => Statistics::String(stats.clone()),
             ColumnData::Tag(keys, dictionary, stats) => {
-                let mut distinct_count = dictionary.values().len() as u64;
+                let mut distinct_count = dictionary.len() as u64;
                 if keys.len() as u64 != stats.count {
                     // Column contains NULLs
                     distinct_count += 1;
@@ -296,30 +489,87 @@ impl Column {
         data_size + self.valid.byte_len()
     }
 
+    /// The memory allocated for the data in the column, including capacity
+    /// reserved for future writes and, unlike [`size`](Self::size), the
+    /// min/max strings of the statistics of tag columns
+    pub fn allocated_bytes(&self) -> usize {
+        let data_size = match &self.data {
+            ColumnData::F64(v, stats) => {
+                mem::size_of::<f64>() * v.capacity() + mem::size_of_val(&stats)
+            }
+            ColumnData::I64(v, stats) => {
+                mem::size_of::<i64>() * v.capacity() + mem::size_of_val(&stats)
+            }
+            ColumnData::U64(v, stats) => {
+                mem::size_of::<u64>() * v.capacity() + mem::size_of_val(&stats)
+            }
+            ColumnData::Bool(v, stats) => v.allocated_bytes() + mem::size_of_val(&stats),
+            ColumnData::Tag(v, dictionary, stats) => {
+                mem::size_of::<DID>() * v.capacity()
+                    + dictionary.allocated_bytes()
+                    + mem::size_of_val(&stats)
+                    + stats.string_size()
+            }
+            ColumnData::String(v, stats) => {
+                v.allocated_bytes() + mem::size_of_val(&stats) + stats.string_size()
+            }
+        };
+        data_size + self.valid.allocated_bytes()
+    }
+
+    /// Returns the data of the column as an arrow array, copying its values
     pub fn to_arrow(&self) -> Result<ArrayRef> {
+        let values = match &self.data {
+            ColumnData::F64(data, _) => data.to_arrow(),
+            ColumnData::I64(data, _) => data.to_arrow(),
+            ColumnData::U64(data, _) => data.to_arrow(),
+            ColumnData::Bool(data, _) => data.to_arrow(),
+            ColumnData::Tag(data, _, _) => data.to_arrow(),
+            ColumnData::String(data, _) => return Ok(Arc::new(data.to_arrow())),
+        };
+        self.build_arrow(values)
+    }
+
+    /// Converts the column into an arrow array. Unlike
+    /// [`to_arrow`](Self::to_arrow) the values of numeric and tag columns are
+    /// moved into the array instead of being copied
+    pub fn into_arrow(mut self) -> Result<ArrayRef> {
+        let values = match &mut self.data {
+            ColumnData::F64(data, _) => mem::take(data).into_arrow(),
+            ColumnData::I64(data, _) => mem::take(data).into_arrow(),
+            ColumnData::U64(data, _) => mem::take(data).into_arrow(),
+            ColumnData::Tag(data, _, _) => mem::take(data).into_arrow(),
+            ColumnData::Bool(_, _) | ColumnData::String(_, _) => return self.to_arrow(),
+        };
+        self.build_arrow(values)
+    }
+
+    /// Builds the arrow array of the column around the buffer of its values
+    fn build_arrow(&self, values: Buffer) -> Result<ArrayRef> {
         let nulls = self.valid.to_arrow();
+        let len = self.len();
         let data: ArrayRef = match &self.data {
-            ColumnData::F64(data, _) => {
+            ColumnData::F64(_, _) => {
                 let data = ArrayDataBuilder::new(DataType::Float64)
-                    .len(data.len())
-                    .add_buffer(data.iter().cloned().collect())
+                    .len(len)
+                    .add_buffer(values)
                     .null_bit_buffer(nulls)
                     .build();
                 Arc::new(Float64Array::from(data))
             }
-            ColumnData::I64(data, _) => match self.influx_type {
+            ColumnData::I64(_, _) => match self.influx_type {
                 InfluxColumnType::Timestamp => {
                     let data = ArrayDataBuilder::new(TIME_DATA_TYPE())
-                        .len(data.len())
-                        .add_buffer(data.iter().cloned().collect())
+                        .len(len)
+                        .add_buffer(values)
                         .null_bit_buffer(nulls)
                         .build();
                     Arc::new(TimestampNanosecondArray::from(data))
                 }
                 InfluxColumnType::Field(InfluxFieldType::Integer) => {
                     let data = ArrayDataBuilder::new(DataType::Int64)
-                        .len(data.len())
-                        .add_buffer(data.iter().cloned().collect())
+                        .len(len)
+                        .add_buffer(values)
                         .null_bit_buffer(nulls)
                         .build();
 
@@ -327,32 +577,32 @@ impl Column {
                 }
                 _ => unreachable!(),
             },
-            ColumnData::U64(data, _) => {
+            ColumnData::U64(_, _) => {
                 let data = ArrayDataBuilder::new(DataType::UInt64)
-                    .len(data.len())
-                    .add_buffer(data.iter().cloned().collect())
+                    .len(len)
+                    .add_buffer(values)
                     .null_bit_buffer(nulls)
                     .build();
                 Arc::new(UInt64Array::from(data))
             }
-            ColumnData::String(data, _) => Arc::new(data.to_arrow()),
-            ColumnData::Bool(data, _) => {
+            ColumnData::String(_, _) => unreachable!("string columns have no values buffer"),
+            ColumnData::Bool(_, _) => {
                 let data = ArrayDataBuilder::new(DataType::Boolean)
-                    .len(data.len())
-                    .add_buffer(data.to_arrow())
+                    .len(len)
+                    .add_buffer(values)
                     .null_bit_buffer(nulls)
                     .build();
                 Arc::new(BooleanArray::from(data))
             }
-            ColumnData::Tag(data, dictionary, _) => {
-                let dictionary = dictionary.values().to_arrow();
+            ColumnData::Tag(_, dictionary, _) => {
+                let dictionary = dictionary.to_arrow();
 
                 let data = ArrayDataBuilder::new(DataType::Dictionary(
                     Box::new(DataType::Int32),
                     Box::new(DataType::Utf8),
                 ))
-                .len(data.len())
-                .add_buffer(data.iter().cloned().collect())
+                .len(len)
+                .add_buffer(values)
                 .null_bit_buffer(nulls)
                 .add_child_data(dictionary.data().clone())
                 .build();
@@ -399,27 +649,145 @@ fn construct_valid_mask(column: &EntryColumn<'_>) -> Result<Vec<u8>> {
     }
 }
 
-/// Writes entry data into a column based on the valid mask
+/// Returns whether each row of `column` has a value
+pub(crate) fn valid_rows(column: &EntryColumn<'_>) -> Result<Vec<bool>> {
+    let mask = construct_valid_mask(column)?;
+    Ok((0..column.row_count)
+        .map(|idx| is_set(&mask, idx))
+        .collect())
+}
+
+/// Returns true if the bit of row `idx` is set in the valid mask
+#[inline]
+fn is_set(valid_mask: &[u8], idx: usize) -> bool {
+    valid_mask[idx >> 3] & (1 << (idx & 7)) != 0
+}
+
+/// Returns the number of rows with a value among the first `row_count` rows
+/// of the valid mask
+fn count_set(valid_mask: &[u8], row_count: usize) -> usize {
+    let full_bytes = row_count >> 3;
+    let remainder = row_count & 7;
+    let full: u32 = valid_mask[..full_bytes]
+        .iter()
+        .map(|b| b.count_ones())
+        .sum();
+    let partial = match remainder {
+        0 => 0,
+        _ => (valid_mask[full_bytes] & ((1 << remainder) - 1)).count_ones(),
+    };
+    (full + partial) as usize
+}
+
+/// Restricts the valid mask of a column to the rows for which `rows` is
+/// true. Returns the number of selected rows, their valid mask, and whether
+/// each of the column's values belongs to a selected row
+fn select_rows(valid_mask: &[u8], rows: &[bool]) -> (usize, Vec<u8>, Vec<bool>) {
+    let row_count = rows.iter().filter(|keep| **keep).count();
+    let mut selected_mask = vec![0; (row_count + 7) >> 3];
+    let mut selected_values = Vec::with_capacity(rows.len());
+
+    let mut row = 0;
+    for (idx, keep) in rows.iter().enumerate() {
+        let valid = is_set(valid_mask, idx);
+        if valid {
+            selected_values.push(*keep);
+        }
+        if *keep {
+            if valid {
+                selected_mask[row >> 3] |= 1 << (row & 7);
+            }
+            row += 1;
+        }
+    }
+
+    (row_count, selected_mask, selected_values)
+}
+
+/// Returns the values for which `selected` is true, or all values if it is
+/// `None`
+fn select<'a, I>(values: I, selected: Option<&'a [bool]>) -> impl Iterator<Item = I::Item> + 'a
+where
+    I: Iterator + 'a,
+{
+    values
+        .enumerate()
+        .filter(move |(idx, _)| selected.map_or(true, |selected| selected[*idx]))
+        .map(|(_, value)| value)
+}
+
+/// Returns the [`InfluxColumnType`] of a field column of type `column_type`
+fn field_type(column_type: &ColumnType) -> InfluxColumnType {
+    InfluxColumnType::Field(match column_type {
+        ColumnType::I64 => InfluxFieldType::Integer,
+        ColumnType::U64 => InfluxFieldType::UInteger,
+        ColumnType::F64 => InfluxFieldType::Float,
+        ColumnType::String => InfluxFieldType::String,
+        ColumnType::Bool => InfluxFieldType::Boolean,
+    })
+}
+
+fn i64_values<'a>(entry: &EntryColumn<'a>) -> impl Iterator<Item = i64> + 'a {
+    entry
+        .inner()
+        .values_as_i64values()
+        .expect("invalid flatbuffer")
+        .values()
+        .expect("invalid payload")
+        .into_iter()
+}
+
+fn u64_values<'a>(entry: &EntryColumn<'a>) -> impl Iterator<Item = u64> + 'a {
+    entry
+        .inner()
+        .values_as_u64values()
+        .expect("invalid flatbuffer")
+        .values()
+        .expect("invalid payload")
+        .into_iter()
+}
+
+/// Writes the selected entry data into a column based on the valid mask
+///
+/// The values are appended to the column in a single pass, and the
+/// statistics are then updated from the appended slice
 fn handle_write<T, E>(
     row_count: usize,
     valid_mask: &[u8],
     entry_data: E,
-    col_data: &mut Vec<T>,
+    selected: Option<&[bool]>,
+    col_data: &mut TypedBuffer<T>,
     stats: &mut StatValues<T>,
 ) where
-    T: Clone + Default + PartialOrd + IsNan,
-    E: Iterator<Item = T> + ExactSizeIterator,
+    T: ArrowNativeType + PartialOrd + IsNan,
+    E: Iterator<Item = T>,
 {
     let data_offset = col_data.len();
-    col_data.resize(data_offset + row_count, Default::default());
-
     let initial_non_null_count = stats.count;
-    let to_add = entry_data.len();
+    let valid_count = count_set(valid_mask, row_count);
+
+    if valid_count == row_count {
+        // No nulls: the values can be appended as they are
+        col_data.extend(select(entry_data, selected));
+        assert_eq!(col_data.len(), data_offset + row_count);
 
-    for (idx, value) in iter_set_positions(valid_mask).zip(entry_data) {
-        stats.update(&value);
-        col_data[data_offset + idx] = value;
+        for value in &col_data.as_slice()[data_offset..] {
+            stats.update(value);
+        }
+    } else {
+        let mut values = select(entry_data, selected);
+        col_data.extend((0..row_count).map(|idx| match is_set(valid_mask, idx) {
+            true => values.next().expect("fewer values than valid rows"),
+            false => T::default(),
+        }));
+        assert!(values.next().is_none(), "more values than valid rows");
+
+        for (idx, value) in col_data.as_slice()[data_offset..].iter().enumerate() {
+            if is_set(valid_mask, idx) {
+                stats.update(value);
+            }
+        }
     }
 
-    assert_eq!(stats.count - initial_non_null_count, to_add as u64);
+    assert_eq!(stats.count - initial_non_null_count, valid_count as u64);
 }
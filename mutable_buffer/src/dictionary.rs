@@ -1,3 +1,11 @@
+use std::{convert::TryFrom, mem, sync::Arc};
+
+use arrow::array::StringArray;
+use arrow_util::dictionary::StringDictionary;
+use hashbrown::HashMap;
+
+use crate::interner::StringInterner;
+
 /// A "dictionary ID" (DID) is a compact numeric representation of an interned
 /// string in the dictionary. The same string always maps the same DID.
 ///
@@ -8,4 +16,139 @@
 pub type DID = i32;
 pub const INVALID_DID: DID = -1;
 
-pub type Dictionary = arrow_util::dictionary::StringDictionary<DID>;
+/// The dictionary of the values of a tag column
+#[derive(Debug)]
+pub enum Dictionary {
+    /// The column stores its own copy of the strings
+    Owned(StringDictionary<DID>),
+
+    /// The strings are shared with other chunks through a [`StringInterner`]
+    Shared(SharedDictionary),
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        Self::Owned(Default::default())
+    }
+}
+
+impl Dictionary {
+    /// Creates a dictionary that shares its strings through `interner`, if any
+    pub fn new(interner: Option<&Arc<StringInterner>>) -> Self {
+        match interner {
+            Some(interner) => Self::Shared(SharedDictionary::new(Arc::clone(interner))),
+            None => Self::default(),
+        }
+    }
+
+    /// Returns the id corresponding to value, adding an entry for the
+    /// id if it is not yet present in the dictionary.
+    pub fn lookup_value_or_insert(&mut self, value: &str) -> DID {
+        match self {
+            Self::Owned(dictionary) => dictionary.lookup_value_or_insert(value),
+            Self::Shared(dictionary) => dictionary.lookup_value_or_insert(value),
+        }
+    }
+
+    /// Returns the value corresponding to `id`, if any
+    pub fn lookup_id(&self, id: DID) -> Option<&str> {
+        match self {
+            Self::Owned(dictionary) => dictionary.lookup_id(id),
+            Self::Shared(dictionary) => dictionary
+                .values
+                .get(usize::try_from(id).ok()?)
+                .map(AsRef::as_ref),
+        }
+    }
+
+    /// Returns the number of distinct values in the dictionary
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Owned(dictionary) => dictionary.values().len(),
+            Self::Shared(dictionary) => dictionary.values.len(),
+        }
+    }
+
+    /// Returns the memory used by the dictionary, in bytes. Shared strings
+    /// are accounted for by the [`StringInterner`] instead.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Owned(dictionary) => dictionary.size(),
+            Self::Shared(dictionary) => {
+                dictionary.values.len() * mem::size_of::<Arc<str>>()
+                    + dictionary.ids.len() * (mem::size_of::<Arc<str>>() + mem::size_of::<DID>())
+            }
+        }
+    }
+
+    /// Returns the memory allocated by the dictionary, in bytes, including
+    /// capacity reserved for future inserts
+    pub fn allocated_bytes(&self) -> usize {
+        match self {
+            Self::Owned(dictionary) => dictionary.allocated_bytes(),
+            Self::Shared(dictionary) => {
+                dictionary.values.capacity() * mem::size_of::<Arc<str>>()
+                    + dictionary.ids.capacity()
+                        * (mem::size_of::<Arc<str>>() + mem::size_of::<DID>() + 1)
+            }
+        }
+    }
+
+    /// Returns the values of the dictionary, indexed by their DID
+    pub fn to_arrow(&self) -> StringArray {
+        match self {
+            Self::Owned(dictionary) => dictionary.values().to_arrow(),
+            Self::Shared(dictionary) => StringArray::from(
+                dictionary
+                    .values
+                    .iter()
+                    .map(AsRef::as_ref)
+                    .collect::<Vec<&str>>(),
+            ),
+        }
+    }
+}
+
+/// A dictionary whose strings are owned by a [`StringInterner`], which are
+/// released once the dictionary is dropped
+#[derive(Debug)]
+pub struct SharedDictionary {
+    interner: Arc<StringInterner>,
+    /// The values of the dictionary, indexed by their DID
+    values: Vec<Arc<str>>,
+    /// Used to provide a lookup from string value to DID
+    ids: HashMap<Arc<str>, DID>,
+}
+
+impl SharedDictionary {
+    fn new(interner: Arc<StringInterner>) -> Self {
+        Self {
+            interner,
+            values: Default::default(),
+            ids: Default::default(),
+        }
+    }
+
+    fn lookup_value_or_insert(&mut self, value: &str) -> DID {
+        if let Some(id) = self.ids.get(value) {
+            return *id;
+        }
+
+        let string = self.interner.intern(value);
+        let id = self.values.len() as DID;
+        self.values.push(Arc::clone(&string));
+        self.ids.insert(string, id);
+        id
+    }
+}
+
+impl Drop for SharedDictionary {
+    fn drop(&mut self) {
+        // drop the references of the lookup first, so that the interner can
+        // tell whether other chunks still refer to a value
+        self.ids.clear();
+        for value in self.values.drain(..) {
+            self.interner.release(value);
+        }
+    }
+}
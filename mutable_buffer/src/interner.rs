@@ -0,0 +1,103 @@
+//! Contains a [`StringInterner`] that lets the open chunks of a database
+//! share the memory for tag values that repeat across chunks and
+//! partitions, instead of storing a copy of them in every chunk.
+
+use std::{mem, sync::Arc};
+
+use hashbrown::HashSet;
+use parking_lot::Mutex;
+
+/// A set of reference counted strings shared between chunks. A string is
+/// kept for as long as a chunk refers to it.
+#[derive(Debug, Default)]
+pub struct StringInterner {
+    state: Mutex<InternerState>,
+}
+
+#[derive(Debug, Default)]
+struct InternerState {
+    strings: HashSet<Arc<str>>,
+    /// The total length of the strings
+    bytes: usize,
+}
+
+impl StringInterner {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the shared copy of `value`, adding it if no chunk refers to
+    /// it yet. The returned string must be given back with
+    /// [`release`](Self::release) once it is no longer used.
+    pub fn intern(&self, value: &str) -> Arc<str> {
+        let mut state = self.state.lock();
+        if let Some(string) = state.strings.get(value) {
+            return Arc::clone(string);
+        }
+
+        let string: Arc<str> = Arc::from(value);
+        state.bytes += string.len();
+        state.strings.insert(Arc::clone(&string));
+        string
+    }
+
+    /// Gives back a string returned by [`intern`](Self::intern), removing it
+    /// from the interner if this was the last reference outside of it
+    pub fn release(&self, value: Arc<str>) {
+        let mut state = self.state.lock();
+        // the interner holds one reference, `value` the other
+        if Arc::strong_count(&value) == 2 && state.strings.remove(&value) {
+            state.bytes -= value.len();
+        }
+    }
+
+    /// Returns the number of strings in the interner
+    pub fn len(&self) -> usize {
+        self.state.lock().strings.len()
+    }
+
+    /// Returns true if no chunk refers to a string of the interner
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the memory used by the strings, in bytes
+    pub fn size(&self) -> usize {
+        let state = self.state.lock();
+        state.bytes + state.strings.capacity() * mem::size_of::<Arc<str>>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern_and_release() {
+        let interner = StringInterner::new();
+        assert!(interner.is_empty());
+
+        let a1 = interner.intern("a");
+        let a2 = interner.intern("a");
+        let b = interner.intern("bb");
+        assert!(Arc::ptr_eq(&a1, &a2));
+        assert_eq!(interner.len(), 2);
+        assert!(interner.size() >= 3);
+
+        // still referenced by a2
+        interner.release(a1);
+        assert_eq!(interner.len(), 2);
+
+        interner.release(a2);
+        assert_eq!(interner.len(), 1);
+
+        interner.release(b);
+        assert!(interner.is_empty());
+
+        // strings are added again once they are interned after being removed
+        let a = interner.intern("a");
+        assert_eq!(interner.len(), 1);
+        interner.release(a);
+        assert!(interner.is_empty());
+    }
+}
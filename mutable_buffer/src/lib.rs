@@ -46,7 +46,9 @@
 //! Note: Strings in the mutable buffer are dictionary encoded (via
 //! string interning) to reduce memory usage. This dictionary encoding
 //! is done on a per-Chunk basis, so that as soon as the chunk is
-//! closed the corresponding dictionary also becomes immutable
+//! closed the corresponding dictionary also becomes immutable. The
+//! strings of tag values may optionally be shared between chunks via a
+//! [`StringInterner`](interner::StringInterner)
 
 #![deny(broken_intra_doc_links, rust_2018_idioms)]
 #![warn(
@@ -60,4 +62,5 @@
 pub mod chunk;
 mod column;
 mod dictionary;
+pub mod interner;
 mod table;
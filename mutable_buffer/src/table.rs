@@ -1,17 +1,23 @@
-use std::sync::Arc;
+use std::{mem, sync::Arc};
 
-use arrow::record_batch::RecordBatch;
+use arrow::{array::BooleanArray, compute::filter_record_batch, record_batch::RecordBatch};
 use hashbrown::HashMap;
 use snafu::{ensure, OptionExt, ResultExt, Snafu};
 
-use data_types::partition_metadata::{ColumnSummary, InfluxDbType};
+use arrow_util::bitset::BitSet;
+use data_types::{
+    database_rules::{SchemaConflictPolicy, TypeCoercionRule},
+    delete_predicate::DeletePredicate,
+    partition_metadata::{ColumnSummary, InfluxDbType},
+};
 use internal_types::{
-    schema::{builder::SchemaBuilder, InfluxColumnType, Schema},
+    schema::{builder::SchemaBuilder, InfluxColumnType, Schema, TIME_COLUMN_NAME},
     selection::Selection,
 };
 
 use crate::column;
 use crate::column::Column;
+use crate::interner::StringInterner;
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -48,13 +54,23 @@ pub struct Table {
 
     /// Map of column id from the chunk dictionary to the column
     pub columns: HashMap<String, Column>,
+
+    /// Shares the values of tag columns with other chunks, if set
+    interner: Option<Arc<StringInterner>>,
+
+    /// The rows removed by delete predicates, which are masked when the
+    /// table is converted to arrow. Rows written after the last delete
+    /// may be beyond its length.
+    deleted: BitSet,
 }
 
 impl Table {
-    pub fn new(table_name: Arc<str>) -> Self {
+    pub fn new(table_name: Arc<str>, interner: Option<Arc<StringInterner>>) -> Self {
         Self {
             table_name,
             columns: Default::default(),
+            interner,
+            deleted: BitSet::new(),
         }
     }
 
@@ -70,7 +86,21 @@ impl Table {
     /// that the space taken for the tag string values is represented in the
     /// dictionary size in the chunk that holds the table.
     pub fn size(&self) -> usize {
-        self.columns.iter().map(|(k, v)| k.len() + v.size()).sum()
+        let columns: usize = self.columns.iter().map(|(k, v)| k.len() + v.size()).sum();
+        columns + self.deleted.byte_len()
+    }
+
+    /// The memory allocated for the data in the table, in bytes, including
+    /// capacity reserved for future writes
+    pub fn allocated_bytes(&self) -> usize {
+        let columns = self.columns.capacity() * mem::size_of::<(String, Column)>();
+        columns
+            + self.deleted.allocated_bytes()
+            + self
+                .columns
+                .iter()
+                .map(|(k, v)| k.capacity() + v.allocated_bytes())
+                .sum::<usize>()
     }
 
     /// Returns an iterator over (column_name, estimated_size) for each column
@@ -85,20 +115,20 @@ impl Table {
         self.columns.get(column).context(ColumnNotFound { column })
     }
 
-    /// Validates the schema of the passed in columns, then adds their values to
-    /// the associated columns in the table and updates summary statistics.
-    pub fn write_columns(
-        &mut self,
-        _sequencer_id: u32,
-        _sequence_number: u64,
-        columns: Vec<entry::Column<'_>>,
-    ) -> Result<()> {
-        let row_count_before_insert = self.row_count();
+    /// Validates the schema of the passed in columns against the existing
+    /// columns, handling conflicting types according to `coercions` or else
+    /// `policy`. Returns the names of the conflicting columns whose lines are
+    /// to be dropped
+    pub fn validate_columns<'a>(
+        &self,
+        columns: &'a [entry::Column<'_>],
+        policy: SchemaConflictPolicy,
+        coercions: &[TypeCoercionRule],
+    ) -> Result<Vec<&'a str>> {
         let additional_rows = columns.first().map(|x| x.row_count).unwrap_or_default();
-        let final_row_count = row_count_before_insert + additional_rows;
 
-        // get the column ids and validate schema for those that already exist
-        columns.iter().try_for_each(|column| {
+        let mut conflicting = Vec::new();
+        for column in columns {
             ensure!(
                 column.row_count == additional_rows,
                 IncorrectRowCount {
@@ -109,15 +139,73 @@ impl Table {
             );
 
             if let Some(c) = self.columns.get(column.name()) {
-                c.validate_schema(&column).context(ColumnError {
+                let validated = c.validate_schema(&column).context(ColumnError {
                     column: column.name(),
-                })?;
+                });
+
+                match (validated, policy) {
+                    (Ok(()), _) => {}
+                    (Err(_), _)
+                        if c.can_convert(&column, coercions) || c.can_widen(&column, coercions) => {
+                    }
+                    (Err(_), SchemaConflictPolicy::DropLines) => conflicting.push(column.name()),
+                    (Err(_), SchemaConflictPolicy::Coerce) if c.can_coerce(&column) => {}
+                    (Err(e), _) => return Err(e),
+                }
             }
+        }
 
-            Ok(())
-        })?;
+        Ok(conflicting)
+    }
 
-        for fb_column in columns {
+    /// Validates the schema of the passed in columns, then adds their values to
+    /// the associated columns in the table and updates summary statistics.
+    ///
+    /// Columns whose type conflicts with the existing column of the same name
+    /// are handled according to `coercions` or else `policy`
+    pub fn write_columns(
+        &mut self,
+        _sequencer_id: u32,
+        _sequence_number: u64,
+        columns: Vec<entry::Column<'_>>,
+        policy: SchemaConflictPolicy,
+        coercions: &[TypeCoercionRule],
+    ) -> Result<()> {
+        let row_count_before_insert = self.row_count();
+        let additional_rows = columns.first().map(|x| x.row_count).unwrap_or_default();
+
+        let conflicting = self.validate_columns(&columns, policy, coercions)?;
+
+        // the rows that have no value in any of the conflicting columns
+        let rows = match conflicting.is_empty() {
+            true => None,
+            false => {
+                let mut rows = vec![true; additional_rows];
+                for column in columns.iter().filter(|c| conflicting.contains(&c.name())) {
+                    let valid = column::valid_rows(column).context(ColumnError {
+                        column: column.name(),
+                    })?;
+                    for (keep, valid) in rows.iter_mut().zip(valid) {
+                        *keep &= !valid;
+                    }
+                }
+                Some(rows)
+            }
+        };
+
+        let final_row_count = row_count_before_insert
+            + rows.as_ref().map_or(additional_rows, |rows| {
+                rows.iter().filter(|keep| **keep).count()
+            });
+        if final_row_count == row_count_before_insert {
+            return Ok(());
+        }
+
+        let interner = self.interner.as_ref();
+        for fb_column in &columns {
+            if conflicting.contains(&fb_column.name()) {
+                continue;
+            }
             let influx_type = fb_column.influx_type();
 
             let column = self
@@ -127,14 +215,16 @@ impl Table {
                 .or_insert_with(|| {
                     (
                         fb_column.name().to_string(),
-                        Column::new(row_count_before_insert, influx_type),
+                        Column::new(row_count_before_insert, influx_type, interner),
                     )
                 })
                 .1;
 
-            column.append(&fb_column).context(ColumnError {
-                column: fb_column.name(),
-            })?;
+            column
+                .append(&fb_column, rows.as_deref(), coercions)
+                .context(ColumnError {
+                    column: fb_column.name(),
+                })?;
 
             assert_eq!(column.len(), final_row_count);
         }
@@ -146,6 +236,57 @@ impl Table {
         Ok(())
     }
 
+    /// Masks the rows written so far that match `predicate`, returning the
+    /// number of rows that were not masked before. Rows written later on are
+    /// not affected by the predicate.
+    pub fn delete(&mut self, predicate: &DeletePredicate) -> usize {
+        let time = match self.columns.get(TIME_COLUMN_NAME) {
+            Some(time) => time,
+            None => return 0,
+        };
+
+        let deleted: Vec<_> = (0..self.row_count())
+            .filter(|&row| !self.is_deleted(row))
+            .filter(|&row| {
+                time.i64_value(row).map_or(false, |time| {
+                    predicate.matches(time, |tag| {
+                        self.columns
+                            .get(tag)
+                            .and_then(|column| column.tag_value(row))
+                    })
+                })
+            })
+            .collect();
+
+        if let Some(last) = deleted.last() {
+            if self.deleted.len() <= *last {
+                self.deleted.append_unset(last + 1 - self.deleted.len());
+            }
+        }
+        for row in &deleted {
+            self.deleted.set(*row);
+        }
+        deleted.len()
+    }
+
+    /// Returns true if `row` was masked by a delete predicate
+    fn is_deleted(&self, row: usize) -> bool {
+        row < self.deleted.len() && self.deleted.get(row)
+    }
+
+    /// Returns `batch`, which holds the rows of this table, without the rows
+    /// masked by delete predicates
+    fn remove_deleted_rows(&self, batch: RecordBatch) -> Result<RecordBatch> {
+        if self.deleted.is_empty() {
+            return Ok(batch);
+        }
+
+        let keep: BooleanArray = (0..batch.num_rows())
+            .map(|row| Some(!self.is_deleted(row)))
+            .collect();
+        filter_record_batch(&batch, &keep).context(ArrowError {})
+    }
+
     /// Converts this table to an arrow record batch
     ///
     /// If Selection::All the returned columns are sorted by name
@@ -165,7 +306,32 @@ impl Table {
             })
             .collect::<Result<Vec<_>>>()?;
 
-        RecordBatch::try_new(schema.into(), columns).context(ArrowError {})
+        let batch = RecordBatch::try_new(schema.into(), columns).context(ArrowError {})?;
+        self.remove_deleted_rows(batch)
+    }
+
+    /// Converts this table into an arrow record batch of all its columns,
+    /// sorted by name, moving the data of the columns into the record batch
+    /// where possible instead of copying it. The data is copied if rows were
+    /// masked by a delete predicate
+    pub fn into_arrow(mut self) -> Result<RecordBatch> {
+        let schema = self.schema(Selection::All)?;
+        let columns = schema
+            .iter()
+            .map(|(_, field)| {
+                let column = self
+                    .columns
+                    .remove(field.name())
+                    .expect("schema contains non-existent column");
+
+                column.into_arrow().context(ColumnError {
+                    column: field.name(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let batch = RecordBatch::try_new(schema.into(), columns).context(ArrowError {})?;
+        self.remove_deleted_rows(batch)
     }
 
     /// Returns the schema for a given selection
@@ -221,6 +387,8 @@ impl Table {
 mod tests {
     use arrow::datatypes::DataType as ArrowDataType;
 
+    use arrow_util::assert_batches_eq;
+    use data_types::database_rules::ColumnType;
     use entry::test_helpers::lp_to_entry;
     use internal_types::schema::{InfluxColumnType, InfluxFieldType};
 
@@ -228,7 +396,7 @@ mod tests {
 
     #[test]
     fn table_size() {
-        let mut table = Table::new(Arc::from("table_name"));
+        let mut table = Table::new(Arc::from("table_name"), None);
 
         let lp_lines = vec![
             "h2o,state=MA,city=Boston temp=70.4 100",
@@ -250,7 +418,7 @@ mod tests {
 
     #[test]
     fn test_to_arrow_schema_all() {
-        let mut table = Table::new(Arc::from("table_name"));
+        let mut table = Table::new(Arc::from("table_name"), None);
 
         let lp_lines = vec![
             "h2o,state=MA,city=Boston float_field=70.4,int_field=8i,uint_field=42u,bool_field=t,string_field=\"foo\" 100",
@@ -281,7 +449,7 @@ mod tests {
 
     #[test]
     fn test_to_arrow_schema_subset() {
-        let mut table = Table::new(Arc::from("table_name"));
+        let mut table = Table::new(Arc::from("table_name"), None);
 
         let lp_lines = vec!["h2o,state=MA,city=Boston float_field=70.4 100"];
 
@@ -303,7 +471,7 @@ mod tests {
 
     #[test]
     fn write_columns_validates_schema() {
-        let mut table = Table::new(Arc::from("foo"));
+        let mut table = Table::new(Arc::from("foo"), None);
         let sequencer_id = 1;
         let sequence_number = 5;
 
@@ -322,6 +490,8 @@ mod tests {
                     .first()
                     .unwrap()
                     .columns(),
+                SchemaConflictPolicy::Reject,
+                &[],
             )
             .unwrap();
 
@@ -340,6 +510,8 @@ mod tests {
                     .first()
                     .unwrap()
                     .columns(),
+                SchemaConflictPolicy::Reject,
+                &[],
             )
             .err()
             .unwrap();
@@ -373,6 +545,8 @@ mod tests {
                     .first()
                     .unwrap()
                     .columns(),
+                SchemaConflictPolicy::Reject,
+                &[],
             )
             .err()
             .unwrap();
@@ -406,6 +580,8 @@ mod tests {
                     .first()
                     .unwrap()
                     .columns(),
+                SchemaConflictPolicy::Reject,
+                &[],
             )
             .err()
             .unwrap();
@@ -439,6 +615,8 @@ mod tests {
                     .first()
                     .unwrap()
                     .columns(),
+                SchemaConflictPolicy::Reject,
+                &[],
             )
             .err()
             .unwrap();
@@ -472,6 +650,8 @@ mod tests {
                     .first()
                     .unwrap()
                     .columns(),
+                SchemaConflictPolicy::Reject,
+                &[],
             )
             .err()
             .unwrap();
@@ -505,6 +685,8 @@ mod tests {
                     .first()
                     .unwrap()
                     .columns(),
+                SchemaConflictPolicy::Reject,
+                &[],
             )
             .err()
             .unwrap();
@@ -536,7 +718,196 @@ mod tests {
             .unwrap()
             .table_batches()
         {
-            table.write_columns(1, 5, batch.columns()).unwrap();
+            table
+                .write_columns(1, 5, batch.columns(), SchemaConflictPolicy::Reject, &[])
+                .unwrap();
+        }
+    }
+
+    /// Insert the line protocol lines in `lp_lines` into this table, handling
+    /// schema conflicts according to `coercions` or else `policy`
+    fn try_write_lines_with_policy(
+        table: &mut Table,
+        lp_lines: Vec<&str>,
+        policy: SchemaConflictPolicy,
+        coercions: &[TypeCoercionRule],
+    ) -> Result<()> {
+        let lp_data = lp_lines.join("\n");
+        let entry = lp_to_entry(&lp_data);
+
+        let partition_writes = entry.partition_writes().unwrap();
+        let batch = partition_writes.first().unwrap().table_batches().remove(0);
+        table.write_columns(1, 5, batch.columns(), policy, coercions)
+    }
+
+    #[test]
+    fn write_columns_drop_lines() {
+        let mut table = Table::new(Arc::from("foo"), None);
+        write_lines_to_table(&mut table, vec!["foo,t1=a fv=1.0,iv=1i 1"]);
+
+        try_write_lines_with_policy(
+            &mut table,
+            vec![
+                "foo,t1=b fv=2.0 2",
+                "foo,t1=c fv=3.0,iv=3u 3",
+                "foo,t1=d fv=4.0 4",
+            ],
+            SchemaConflictPolicy::DropLines,
+            &[],
+        )
+        .unwrap();
+
+        // Only the lines without a value of a conflicting type are written
+        let batch = table
+            .to_arrow(Selection::Some(&["t1", "fv", "iv"]))
+            .unwrap();
+        assert_batches_eq!(
+            &[
+                "+----+----+----+",
+                "| t1 | fv | iv |",
+                "+----+----+----+",
+                "| a  | 1  | 1  |",
+                "| b  | 2  |    |",
+                "| d  | 4  |    |",
+                "+----+----+----+",
+            ],
+            &[batch]
+        );
+
+        // Nothing is written if every line conflicts
+        try_write_lines_with_policy(
+            &mut table,
+            vec!["foo,t1=f fv=true 6"],
+            SchemaConflictPolicy::DropLines,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(table.row_count(), 3);
+    }
+
+    #[test]
+    fn write_columns_coerce() {
+        let mut table = Table::new(Arc::from("foo"), None);
+        write_lines_to_table(&mut table, vec!["foo fv=1.5,iv=1i,uv=1u 1"]);
+
+        for lines in vec![vec!["foo fv=2i,iv=2u,uv=2i 2"], vec!["foo fv=3u 3"]] {
+            try_write_lines_with_policy(&mut table, lines, SchemaConflictPolicy::Coerce, &[])
+                .unwrap();
+        }
+
+        let batch = table
+            .to_arrow(Selection::Some(&["fv", "iv", "uv"]))
+            .unwrap();
+        assert_batches_eq!(
+            &[
+                "+-----+----+----+",
+                "| fv  | iv | uv |",
+                "+-----+----+----+",
+                "| 1.5 | 1  | 1  |",
+                "| 2   | 2  | 2  |",
+                "| 3   |    |    |",
+                "+-----+----+----+",
+            ],
+            &[batch]
+        );
+
+        // Conversions that would lose information are rejected
+        for line in &[
+            "foo uv=-1i 4",
+            "foo fv=9007199254740993i 4",
+            "foo iv=\"four\" 4",
+        ] {
+            let err = try_write_lines_with_policy(
+                &mut table,
+                vec![line],
+                SchemaConflictPolicy::Coerce,
+                &[],
+            )
+            .unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    Error::ColumnError {
+                        source: column::Error::TypeMismatch { .. },
+                        ..
+                    }
+                ),
+                "didn't match returned error: {:?}",
+                err
+            );
+        }
+        assert_eq!(table.row_count(), 3);
+    }
+
+    #[test]
+    fn write_columns_type_coercion_rules() {
+        let coercions = vec![
+            TypeCoercionRule {
+                from: ColumnType::I64,
+                to: ColumnType::F64,
+            },
+            TypeCoercionRule {
+                from: ColumnType::I64,
+                to: ColumnType::U64,
+            },
+        ];
+
+        let mut table = Table::new(Arc::from("foo"), None);
+        write_lines_to_table(&mut table, vec!["foo fv=1.5,iv=1i,uv=1u 1"]);
+
+        // Integers are written to float and unsigned columns, even if they
+        // lose precision, and integer columns are widened to floats
+        try_write_lines_with_policy(
+            &mut table,
+            vec!["foo fv=9007199254740993i,iv=2.5,uv=2i 2"],
+            SchemaConflictPolicy::Reject,
+            &coercions,
+        )
+        .unwrap();
+
+        let schema = table.schema(Selection::Some(&["iv"])).unwrap();
+        assert_eq!(
+            schema.field(0).0,
+            Some(InfluxColumnType::Field(InfluxFieldType::Float))
+        );
+
+        let batch = table
+            .to_arrow(Selection::Some(&["fv", "iv", "uv"]))
+            .unwrap();
+        assert_batches_eq!(
+            &[
+                "+------------------+-----+----+",
+                "| fv               | iv  | uv |",
+                "+------------------+-----+----+",
+                "| 1.5              | 1   | 1  |",
+                "| 9007199254740992 | 2.5 | 2  |",
+                "+------------------+-----+----+",
+            ],
+            &[batch]
+        );
+
+        // Conversions without a rule, or of values that can't be
+        // represented, are handled according to the policy
+        for line in &["foo uv=-1i 3", "foo uv=3.5 3"] {
+            let err = try_write_lines_with_policy(
+                &mut table,
+                vec![line],
+                SchemaConflictPolicy::Reject,
+                &coercions,
+            )
+            .unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    Error::ColumnError {
+                        source: column::Error::TypeMismatch { .. },
+                        ..
+                    }
+                ),
+                "didn't match returned error: {:?}",
+                err
+            );
         }
+        assert_eq!(table.row_count(), 2);
     }
 }
@@ -37,6 +37,7 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use chrono::{DateTime, Utc};
 use futures::{stream::BoxStream, Stream, StreamExt, TryFutureExt, TryStreamExt};
+use observability_deps::tracing;
 use snafu::{ResultExt, Snafu};
 use std::io;
 
@@ -154,6 +155,7 @@ impl ObjectStoreApi for ObjectStore {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip(self, location, bytes), fields(location = %location.display()))]
     async fn put<S>(&self, location: &Self::Path, bytes: S, length: Option<usize>) -> Result<()>
     where
         S: Stream<Item = io::Result<Bytes>> + Send + Sync + 'static,
@@ -186,6 +188,7 @@ impl ObjectStoreApi for ObjectStore {
         Ok(())
     }
 
+    #[tracing::instrument(level = "debug", skip(self, location), fields(location = %location.display()))]
     async fn get(&self, location: &Self::Path) -> Result<BoxStream<'static, Result<Bytes>>> {
         use ObjectStoreIntegration::*;
         Ok(match (&self.0, location) {
@@ -214,6 +217,7 @@ impl ObjectStoreApi for ObjectStore {
         })
     }
 
+    #[tracing::instrument(level = "debug", skip(self, location), fields(location = %location.display()))]
     async fn delete(&self, location: &Self::Path) -> Result<()> {
         use ObjectStoreIntegration::*;
         match (&self.0, location) {
@@ -235,6 +239,7 @@ impl ObjectStoreApi for ObjectStore {
         Ok(())
     }
 
+    #[tracing::instrument(level = "debug", skip(self, prefix), fields(prefix = ?prefix.map(|p| p.display())))]
     async fn list<'a>(
         &'a self,
         prefix: Option<&'a Self::Path>,
@@ -324,6 +329,7 @@ impl ObjectStoreApi for ObjectStore {
         })
     }
 
+    #[tracing::instrument(level = "debug", skip(self, prefix), fields(prefix = %prefix.display()))]
     async fn list_with_delimiter(&self, prefix: &Self::Path) -> Result<ListResult<Self::Path>> {
         use ObjectStoreIntegration::*;
         match (&self.0, prefix) {
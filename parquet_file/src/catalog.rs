@@ -14,7 +14,9 @@ use std::{
 use crate::metadata::{parquet_metadata_to_thrift, thrift_to_parquet_metadata};
 use bytes::Bytes;
 use chrono::{DateTime, Utc};
-use data_types::server_id::ServerId;
+use data_types::{
+    delete_predicate::DeletePredicate, server_id::ServerId, timestamp::TimestampRange,
+};
 use futures::TryStreamExt;
 use generated_types::influxdata::iox::catalog::v1 as proto;
 use object_store::{
@@ -156,6 +158,15 @@ pub enum Error {
         path: DirsAndFileName,
     },
 
+    #[snafu(
+        display("Cannot read IOx metadata from {:?}: {}", path, source),
+        visibility(pub)
+    )]
+    IoxMetadataReadFailed {
+        source: crate::metadata::Error,
+        path: DirsAndFileName,
+    },
+
     #[snafu(
         display("Catalog state failure when processing {:?}: {}", path, source),
         visibility(pub)
@@ -216,6 +227,17 @@ pub trait CatalogState {
 
     /// Remove parquet file from state.
     fn remove(&self, path: DirsAndFileName) -> Result<()>;
+
+    /// Mask the rows matching the delete predicate in the given parquet files.
+    ///
+    /// If `sequence_number` is set, the predicate also applies to the rows of the write buffer
+    /// entries before it that are replayed, which were not persisted when it was added.
+    fn delete_predicate(
+        &self,
+        predicate: Arc<DeletePredicate>,
+        paths: Vec<DirsAndFileName>,
+        sequence_number: Option<u64>,
+    ) -> Result<()>;
 }
 
 /// Find last transaction-start-timestamp.
@@ -683,6 +705,54 @@ fn unparse_dirs_and_filename(path: &DirsAndFileName) -> proto::Path {
     }
 }
 
+/// Parse [`DeletePredicate`], the paths it applies to and the sequence number of the write buffer
+/// entries it applies to from protobuf.
+fn parse_delete_predicate(
+    proto: &proto::AddDeletePredicate,
+) -> Result<(DeletePredicate, Vec<DirsAndFileName>, Option<u64>)> {
+    let predicate = DeletePredicate {
+        table_name: proto.table_name.clone(),
+        range: TimestampRange {
+            start: proto.start_time,
+            end: proto.stop_time,
+        },
+        tags: proto
+            .tags
+            .iter()
+            .map(|(tag, value)| (tag.clone(), value.clone()))
+            .collect(),
+    };
+    let paths = proto
+        .paths
+        .iter()
+        .map(|path| parse_dirs_and_filename(&Some(path.clone())))
+        .collect::<Result<_>>()?;
+    let sequence_number = Some(proto.sequence_number).filter(|n| *n > 0);
+
+    Ok((predicate, paths, sequence_number))
+}
+
+/// Store [`DeletePredicate`], the paths it applies to and the sequence number of the write buffer
+/// entries it applies to as protobuf.
+fn unparse_delete_predicate(
+    predicate: &DeletePredicate,
+    paths: &[DirsAndFileName],
+    sequence_number: Option<u64>,
+) -> proto::AddDeletePredicate {
+    proto::AddDeletePredicate {
+        table_name: predicate.table_name.clone(),
+        start_time: predicate.range.start,
+        stop_time: predicate.range.end,
+        tags: predicate
+            .tags
+            .iter()
+            .map(|(tag, value)| (tag.clone(), value.clone()))
+            .collect(),
+        paths: paths.iter().map(unparse_dirs_and_filename).collect(),
+        sequence_number: sequence_number.unwrap_or_default(),
+    }
+}
+
 /// Parse timestamp from protobuf.
 fn parse_timestamp(
     ts: &Option<generated_types::google::protobuf::Timestamp>,
@@ -782,6 +852,10 @@ where
                 let path = parse_dirs_and_filename(&a.path)?;
                 state.remove(path)?;
             }
+            proto::transaction::action::Action::AddDeletePredicate(a) => {
+                let (predicate, paths, sequence_number) = parse_delete_predicate(a)?;
+                state.delete_predicate(Arc::new(predicate), paths, sequence_number)?;
+            }
         };
         Ok(())
     }
@@ -1016,6 +1090,33 @@ where
                 &self.catalog.db_name,
             )
     }
+
+    /// Mask the rows matching the delete predicate in the given parquet files.
+    ///
+    /// If `sequence_number` is set, the predicate also masks the rows of the write buffer entries
+    /// before it when these are replayed, as their rows are not persisted yet.
+    ///
+    /// Referencing files that do not exist or were already removed will result in an error.
+    pub fn add_delete_predicate(
+        &mut self,
+        predicate: &DeletePredicate,
+        paths: &[DirsAndFileName],
+        sequence_number: Option<u64>,
+    ) -> Result<()> {
+        self.transaction
+            .as_mut()
+            .expect("transaction handle w/o transaction?!")
+            .handle_action_and_record(
+                proto::transaction::action::Action::AddDeletePredicate(unparse_delete_predicate(
+                    predicate,
+                    paths,
+                    sequence_number,
+                )),
+                &self.catalog.object_store,
+                self.catalog.server_id,
+                &self.catalog.db_name,
+            )
+    }
 }
 
 impl<'c, S> Debug for TransactionHandle<'c, S>
@@ -1041,6 +1142,152 @@ where
     }
 }
 
+/// Debug view of the preserved catalog of a database, as found in object store.
+///
+/// Unlike [`PreservedCatalog::load`] this does not stop at the first broken transaction: every transaction file is
+/// read on its own and failures are recorded next to the file, which is what you want when debugging broken catalogs.
+#[derive(Debug)]
+pub struct CatalogDump {
+    /// All transaction files, sorted by revision counter and UUID.
+    pub transactions: Vec<TransactionDump>,
+}
+
+/// Debug view of a single transaction file.
+#[derive(Debug)]
+pub struct TransactionDump {
+    /// Location of the transaction file.
+    pub path: Path,
+
+    /// Revision counter as encoded in the path.
+    pub revision_counter: u64,
+
+    /// Transaction UUID as encoded in the path.
+    pub uuid: Uuid,
+
+    /// Decoded file content or the error that occurred while reading it.
+    pub content: Result<TransactionContentDump>,
+}
+
+/// Decoded content of a transaction file.
+#[derive(Debug)]
+pub struct TransactionContentDump {
+    /// Format version of the file.
+    pub version: u32,
+
+    /// UUID of the previous transaction, if any.
+    pub previous_uuid: Option<Uuid>,
+
+    /// Time when the transaction was started.
+    pub start_timestamp: Result<DateTime<Utc>>,
+
+    /// Actions recorded in the transaction.
+    pub actions: Vec<ActionDump>,
+}
+
+/// Decoded action of a transaction.
+#[derive(Debug)]
+pub enum ActionDump {
+    /// Upgrade to a newer, unknown format.
+    Upgrade { format: String },
+
+    /// Parquet file added to the catalog.
+    AddParquet {
+        path: Result<DirsAndFileName>,
+        /// Number of rows according to the parquet metadata.
+        num_rows: Result<i64>,
+    },
+
+    /// Parquet file removed from the catalog.
+    RemoveParquet { path: Result<DirsAndFileName> },
+
+    /// Rows of parquet files and of unpersisted write buffer entries masked by a delete predicate.
+    AddDeletePredicate {
+        predicate: Result<(DeletePredicate, Vec<DirsAndFileName>, Option<u64>)>,
+    },
+}
+
+impl CatalogDump {
+    /// Read all transaction files of the given database without checking their consistency.
+    pub async fn load(
+        object_store: &ObjectStore,
+        server_id: ServerId,
+        db_name: &str,
+    ) -> Result<Self> {
+        let mut files = list_transaction_files(object_store, server_id, db_name).await?;
+        files.sort_by_key(|(_path, revision_counter, uuid)| (*revision_counter, *uuid));
+
+        let mut transactions = Vec::with_capacity(files.len());
+        for (path, revision_counter, uuid) in files {
+            let content = load_transaction_proto(object_store, &path)
+                .await
+                .map(TransactionContentDump::from_proto);
+            transactions.push(TransactionDump {
+                path,
+                revision_counter,
+                uuid,
+                content,
+            });
+        }
+
+        Ok(Self { transactions })
+    }
+
+    /// Parquet files tracked by the catalog after replaying all readable transactions in order.
+    pub fn parquet_files(&self) -> Vec<DirsAndFileName> {
+        let mut files: Vec<DirsAndFileName> = vec![];
+        for transaction in &self.transactions {
+            let actions = match &transaction.content {
+                Ok(content) => &content.actions,
+                Err(_) => continue,
+            };
+            for action in actions {
+                match action {
+                    ActionDump::AddParquet { path: Ok(path), .. } => files.push(path.clone()),
+                    ActionDump::RemoveParquet { path: Ok(path) } => files.retain(|f| f != path),
+                    _ => {}
+                }
+            }
+        }
+        files
+    }
+}
+
+impl TransactionContentDump {
+    fn from_proto(proto: proto::Transaction) -> Self {
+        let actions = proto
+            .actions
+            .iter()
+            .filter_map(|action| action.action.as_ref())
+            .map(|action| match action {
+                proto::transaction::action::Action::Upgrade(u) => ActionDump::Upgrade {
+                    format: u.format.clone(),
+                },
+                proto::transaction::action::Action::AddParquet(a) => ActionDump::AddParquet {
+                    path: parse_dirs_and_filename(&a.path),
+                    num_rows: thrift_to_parquet_metadata(&a.metadata)
+                        .context(MetadataDecodingFailed)
+                        .map(|md| md.file_metadata().num_rows()),
+                },
+                proto::transaction::action::Action::RemoveParquet(a) => ActionDump::RemoveParquet {
+                    path: parse_dirs_and_filename(&a.path),
+                },
+                proto::transaction::action::Action::AddDeletePredicate(a) => {
+                    ActionDump::AddDeletePredicate {
+                        predicate: parse_delete_predicate(a),
+                    }
+                }
+            })
+            .collect();
+
+        Self {
+            version: proto.version,
+            previous_uuid: parse_uuid(&proto.previous_uuid).ok().flatten(),
+            start_timestamp: parse_timestamp(&proto.start_timestamp),
+            actions,
+        }
+    }
+}
+
 pub mod test_helpers {
     use super::*;
     use std::{cell::RefCell, ops::Deref};
@@ -1050,6 +1297,9 @@ pub mod test_helpers {
     pub struct TestCatalogStateInner {
         /// Map of all parquet files that are currently registered.
         pub parquet_files: HashMap<DirsAndFileName, ParquetMetaData>,
+
+        /// Delete predicates recorded for the registered parquet files.
+        pub delete_predicates: HashMap<DirsAndFileName, Vec<Arc<DeletePredicate>>>,
     }
 
     /// In-memory catalog state, for testing.
@@ -1066,6 +1316,7 @@ pub mod test_helpers {
             Self {
                 inner: RefCell::new(TestCatalogStateInner {
                     parquet_files: HashMap::new(),
+                    delete_predicates: HashMap::new(),
                 }),
             }
         }
@@ -1096,7 +1347,8 @@ pub mod test_helpers {
         }
 
         fn remove(&self, path: DirsAndFileName) -> Result<()> {
-            match self.inner.borrow_mut().parquet_files.entry(path) {
+            let mut inner = self.inner.borrow_mut();
+            match inner.parquet_files.entry(path.clone()) {
                 Occupied(o) => {
                     o.remove();
                 }
@@ -1104,6 +1356,28 @@ pub mod test_helpers {
                     return Err(Error::ParquetFileDoesNotExist { path: v.into_key() });
                 }
             }
+            inner.delete_predicates.remove(&path);
+
+            Ok(())
+        }
+
+        fn delete_predicate(
+            &self,
+            predicate: Arc<DeletePredicate>,
+            paths: Vec<DirsAndFileName>,
+            _sequence_number: Option<u64>,
+        ) -> Result<()> {
+            let mut inner = self.inner.borrow_mut();
+            for path in paths {
+                if !inner.parquet_files.contains_key(&path) {
+                    return Err(Error::ParquetFileDoesNotExist { path });
+                }
+                inner
+                    .delete_predicates
+                    .entry(path)
+                    .or_default()
+                    .push(Arc::clone(&predicate));
+            }
 
             Ok(())
         }
@@ -2118,6 +2392,152 @@ mod tests {
         assert_eq!(files, vec![path.display()]);
     }
 
+    #[tokio::test]
+    async fn test_dump() {
+        let object_store = make_object_store();
+        let server_id = make_server_id();
+        let db_name = "db1";
+
+        let catalog = PreservedCatalog::<TestCatalogState>::new_empty(
+            Arc::clone(&object_store),
+            server_id,
+            db_name.to_string(),
+            (),
+        )
+        .await
+        .unwrap();
+        let (_, metadata) = make_metadata(&object_store, "foo", 1).await;
+        {
+            let mut t = catalog.open_transaction().await;
+            t.add_parquet(&parsed_path!("test1"), &metadata).unwrap();
+            t.add_parquet(&parsed_path!(["sub1"], "test2"), &metadata)
+                .unwrap();
+            t.commit().await.unwrap();
+        }
+        {
+            let mut t = catalog.open_transaction().await;
+            t.remove_parquet(&parsed_path!("test1")).unwrap();
+            t.commit().await.unwrap();
+        }
+        break_catalog_with_weird_version(&catalog).await;
+
+        let dump = CatalogDump::load(&object_store, server_id, db_name)
+            .await
+            .unwrap();
+        assert_eq!(dump.transactions.len(), 3);
+        assert_eq!(
+            dump.transactions
+                .iter()
+                .map(|t| t.revision_counter)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+
+        let contents: Vec<_> = dump
+            .transactions
+            .iter()
+            .map(|t| t.content.as_ref().unwrap())
+            .collect();
+        assert!(contents[0].actions.is_empty());
+        assert_eq!(contents[0].previous_uuid, None);
+        assert_eq!(contents[1].previous_uuid, Some(dump.transactions[0].uuid));
+        assert!(contents[1].start_timestamp.is_ok());
+        assert_eq!(contents[1].actions.len(), 2);
+        match &contents[1].actions[0] {
+            ActionDump::AddParquet { path, num_rows } => {
+                assert_eq!(path.as_ref().unwrap(), &parsed_path!("test1"));
+                assert_eq!(
+                    *num_rows.as_ref().unwrap(),
+                    metadata.file_metadata().num_rows()
+                );
+            }
+            action => panic!("unexpected action: {:?}", action),
+        }
+
+        // broken transactions are still dumped
+        assert_eq!(contents[2].version, 42);
+
+        assert_eq!(dump.parquet_files(), vec![parsed_path!(["sub1"], "test2")]);
+    }
+
+    #[tokio::test]
+    async fn test_delete_predicate() {
+        let object_store = make_object_store();
+        let server_id = make_server_id();
+        let db_name = "db1";
+
+        let catalog = PreservedCatalog::<TestCatalogState>::new_empty(
+            Arc::clone(&object_store),
+            server_id,
+            db_name.to_string(),
+            (),
+        )
+        .await
+        .unwrap();
+        let (_, metadata) = make_metadata(&object_store, "foo", 1).await;
+        let predicate = DeletePredicate {
+            table_name: "table1".to_string(),
+            range: TimestampRange::new(10, 20),
+            tags: vec![("tag1".to_string(), "foo".to_string())]
+                .into_iter()
+                .collect(),
+        };
+        {
+            let mut t = catalog.open_transaction().await;
+            t.add_parquet(&parsed_path!("test1"), &metadata).unwrap();
+            t.add_parquet(&parsed_path!("test2"), &metadata).unwrap();
+            t.add_delete_predicate(&predicate, &[parsed_path!("test1")], Some(42))
+                .unwrap();
+
+            // unknown files are rejected
+            let err = t
+                .add_delete_predicate(&predicate, &[parsed_path!("test3")], None)
+                .unwrap_err();
+            assert!(matches!(err, Error::ParquetFileDoesNotExist { .. }));
+
+            t.commit().await.unwrap();
+        }
+
+        let expected: HashMap<_, _> =
+            vec![(parsed_path!("test1"), vec![Arc::new(predicate.clone())])]
+                .into_iter()
+                .collect();
+        assert_eq!(catalog.state().inner.borrow().delete_predicates, expected);
+
+        // predicates are restored when loading the catalog
+        let catalog = PreservedCatalog::<TestCatalogState>::load(
+            Arc::clone(&object_store),
+            server_id,
+            db_name.to_string(),
+            (),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(catalog.state().inner.borrow().delete_predicates, expected);
+
+        let dump = CatalogDump::load(&object_store, server_id, db_name)
+            .await
+            .unwrap();
+        match &dump.transactions[1].content.as_ref().unwrap().actions[2] {
+            ActionDump::AddDeletePredicate { predicate: p } => {
+                assert_eq!(
+                    p.as_ref().unwrap(),
+                    &(predicate.clone(), vec![parsed_path!("test1")], Some(42))
+                );
+            }
+            action => panic!("unexpected action: {:?}", action),
+        }
+
+        // predicates of removed files are forgotten
+        {
+            let mut t = catalog.open_transaction().await;
+            t.remove_parquet(&parsed_path!("test1")).unwrap();
+            t.commit().await.unwrap();
+        }
+        assert!(catalog.state().inner.borrow().delete_predicates.is_empty());
+    }
+
     #[tokio::test]
     async fn test_transaction_handle_revision_counter() {
         let object_store = make_object_store();
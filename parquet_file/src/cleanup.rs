@@ -5,7 +5,7 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use data_types::server_id::ServerId;
+use data_types::{delete_predicate::DeletePredicate, server_id::ServerId};
 use futures::TryStreamExt;
 use object_store::{
     path::{parsed::DirsAndFileName, ObjectStorePath},
@@ -38,6 +38,9 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 /// Delete all unreferenced parquet files.
 ///
+/// Files that were removed from the catalog, e.g. because the chunks they back were dropped or compacted, are no
+/// longer referenced and will be deleted as well.
+///
 /// This will hold the transaction lock while the list of files is being gathered. To limit the time the lock is held
 /// use `max_files` which will limit the number of files to delete in this cleanup round.
 pub async fn cleanup_unreferenced_parquet_files<S>(
@@ -55,7 +58,7 @@ where
     let server_id = catalog.server_id();
     let db_name = catalog.db_name();
     let all_known = {
-        // replay catalog transactions to track ALL (even dropped) files that are referenced
+        // replay catalog transactions to track the files that are still referenced
         let catalog = PreservedCatalog::<TracerCatalogState>::load(
             Arc::clone(&store),
             server_id,
@@ -119,7 +122,7 @@ where
     Ok(())
 }
 
-/// Catalog state that traces all used parquet files.
+/// Catalog state that traces all referenced parquet files.
 struct TracerCatalogState {
     files: Mutex<HashSet<DirsAndFileName>>,
 }
@@ -152,8 +155,18 @@ impl CatalogState for TracerCatalogState {
         Ok(())
     }
 
-    fn remove(&self, _path: DirsAndFileName) -> crate::catalog::Result<()> {
-        // Do NOT remove the file since we still need it for time travel
+    fn remove(&self, path: DirsAndFileName) -> crate::catalog::Result<()> {
+        self.files.lock().expect("lock poissened?").remove(&path);
+        Ok(())
+    }
+
+    fn delete_predicate(
+        &self,
+        _predicate: Arc<DeletePredicate>,
+        _paths: Vec<DirsAndFileName>,
+        _sequence_number: Option<u64>,
+    ) -> crate::catalog::Result<()> {
+        // masked rows do not affect which files are referenced
         Ok(())
     }
 }
@@ -219,11 +232,11 @@ mod tests {
             transaction.add_parquet(&path.clone().into(), &md).unwrap();
             paths_keep.push(path.display());
 
-            // another ordinary tracked parquet file that was added and removed => keep (for time travel)
+            // another ordinary tracked parquet file that was added and removed => delete
             let (path, md) = make_metadata(&object_store, "foo", 2).await;
             transaction.add_parquet(&path.clone().into(), &md).unwrap();
             transaction.remove_parquet(&path.clone().into()).unwrap();
-            paths_keep.push(path.display());
+            paths_delete.push(path.display());
 
             // not a parquet file => keep
             let mut path: DirsAndFileName = path.into();
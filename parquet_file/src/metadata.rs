@@ -88,6 +88,7 @@
 //! [Thrift Compact Protocol]: https://github.com/apache/thrift/blob/master/doc/specs/thrift-compact-protocol.md
 use std::{convert::TryInto, sync::Arc};
 
+use chrono::{DateTime, Utc};
 use data_types::partition_metadata::{
     ColumnSummary, InfluxDbType, StatValues, Statistics, TableSummary,
 };
@@ -219,6 +220,23 @@ pub struct IoxMetadata {
 
     /// UUID of the transaction during which the Parquet file was created.
     pub transaction_uuid: Uuid,
+
+    /// Time at which the first data was written into the chunk(s) stored in
+    /// the Parquet file, if known.
+    #[serde(default)]
+    pub time_of_first_write: Option<DateTime<Utc>>,
+
+    /// Time at which the last data was written into the chunk(s) stored in
+    /// the Parquet file, if known. The data of overlapping chunks is
+    /// deduplicated in the order of this time.
+    #[serde(default)]
+    pub time_of_last_write: Option<DateTime<Utc>>,
+
+    /// Sequence number of the oldest write buffer entry whose data was not
+    /// persisted when the Parquet file was created, if the database has a
+    /// write buffer. Entries before it don't need to be replayed.
+    #[serde(default)]
+    pub min_unpersisted_sequence_number: Option<u64>,
 }
 
 /// Read parquet metadata from a parquet file.
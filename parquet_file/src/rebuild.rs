@@ -585,6 +585,9 @@ mod tests {
         let metadata = IoxMetadata {
             transaction_revision_counter,
             transaction_uuid,
+            time_of_first_write: None,
+            time_of_last_write: None,
+            min_unpersisted_sequence_number: None,
         };
         let stream: SendableRecordBatchStream = Box::pin(MemoryStream::new(record_batches));
         let (path, parquet_md) = storage
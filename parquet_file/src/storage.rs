@@ -27,7 +27,9 @@ use parquet::{
         writer::TryClone,
     },
 };
-use query::predicate::Predicate;
+use query::{
+    func::regex::regex_match_range_expr, predicate::Predicate, pruning::in_list_to_binary_exprs,
+};
 
 use bytes::Bytes;
 use data_types::server_id::ServerId;
@@ -285,6 +287,25 @@ impl Storage {
         mem_writer.into_inner().context(WritingToMemWriter)
     }
 
+    /// Convert the given RecordBatches to the bytes of a parquet file without
+    /// IOx metadata, to be read by tools other than IOx
+    pub fn record_batches_to_parquet_bytes(
+        schema: SchemaRef,
+        batches: &[RecordBatch],
+    ) -> Result<Vec<u8>> {
+        let mem_writer = MemWriter::default();
+        {
+            let mut writer = ArrowWriter::try_new(mem_writer.clone(), schema, None)
+                .context(OpeningParquetWriter)?;
+            for batch in batches {
+                writer.write(batch).context(WritingParquetToMemory)?;
+            }
+            writer.close().context(ClosingParquetWriter)?;
+        } // drop the reference to the MemWriter that the SerializedFileWriter has
+
+        mem_writer.into_inner().context(WritingToMemWriter)
+    }
+
     /// Put the given vector of bytes to the specified location
     pub async fn to_object_store(
         &self,
@@ -417,8 +438,18 @@ impl Storage {
                 .collect(),
         ));
 
-        // pushdown predicate, if any
-        let predicate = predicate.filter_expr();
+        // pushdown predicate, if any, including the ranges of values implied
+        // by regex matches and the equalities implied by IN lists so that row
+        // groups can be pruned using statistics
+        let predicate = predicate.filter_expr().map(|filter_expr| {
+            predicate
+                .exprs
+                .iter()
+                .filter_map(|expr| {
+                    regex_match_range_expr(expr).or_else(|| in_list_to_binary_exprs(expr))
+                })
+                .fold(filter_expr, Expr::and)
+        });
 
         let (tx, rx) = tokio::sync::mpsc::channel(2);
 
@@ -529,6 +560,9 @@ mod tests {
         let metadata = IoxMetadata {
             transaction_revision_counter: 42,
             transaction_uuid: Uuid::new_v4(),
+            time_of_first_write: None,
+            time_of_last_write: None,
+            min_unpersisted_sequence_number: None,
         };
 
         // create parquet file
@@ -656,6 +690,9 @@ mod tests {
         let metadata = IoxMetadata {
             transaction_revision_counter: 42,
             transaction_uuid: Uuid::new_v4(),
+            time_of_first_write: None,
+            time_of_last_write: None,
+            min_unpersisted_sequence_number: None,
         };
 
         let (path, _) = storage
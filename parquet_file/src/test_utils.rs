@@ -145,6 +145,9 @@ pub async fn make_chunk_given_record_batch(
     let metadata = IoxMetadata {
         transaction_revision_counter: 0,
         transaction_uuid: Uuid::nil(),
+        time_of_first_write: None,
+        time_of_last_write: None,
+        min_unpersisted_sequence_number: None,
     };
     let (path, _metadata) = storage
         .write_to_object_store(
@@ -1,16 +1,25 @@
 //! This module handles the manipulation / execution of storage
 //! plans. This is currently implemented using DataFusion, and this
 //! interface abstracts away many of the details
+mod admission;
+mod analyze;
 pub(crate) mod context;
 mod counters;
 pub mod field;
 pub mod fieldlist;
+mod gapfill;
+mod memory;
+mod result_cache;
 mod schema_pivot;
 pub mod seriesset;
 pub mod stringset;
 mod task;
+mod topk;
+pub use admission::{Error as AdmissionError, QueryConcurrencyLimits};
 pub use context::{DEFAULT_CATALOG, DEFAULT_SCHEMA};
 use futures::{future, Future};
+pub use gapfill::{make_gap_fill, FillStrategy, GapFillParams, MAX_GAP_FILL_WINDOWS};
+pub use result_cache::QueryResultCache;
 
 use std::sync::Arc;
 
@@ -34,7 +43,10 @@ use crate::plan::{
     stringset::StringSetPlan,
 };
 
-use self::task::{DedicatedExecutor, Error as ExecutorError};
+use self::{
+    admission::QueryAdmission,
+    task::{DedicatedExecutor, Error as ExecutorError},
+};
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -97,6 +109,17 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 pub struct Executor {
     counters: Arc<ExecutionCounters>,
     exec: DedicatedExecutor,
+    /// The memory, in bytes, each query may use for sorts and aggregations
+    query_memory_limit: Option<usize>,
+    /// The limits on the queries each database may run at the same time
+    query_concurrency_limits: Option<QueryConcurrencyLimits>,
+    /// Bounds the queries of the database this executor runs queries for
+    admission: Option<Arc<QueryAdmission>>,
+    /// The size, in bytes, of the results each database may cache
+    query_result_cache_size: Option<usize>,
+    /// Caches the results of the queries of the database this executor
+    /// runs queries for
+    result_cache: Option<Arc<QueryResultCache>>,
 }
 
 impl Executor {
@@ -108,9 +131,62 @@ impl Executor {
         Self {
             exec,
             counters: Arc::new(ExecutionCounters::default()),
+            query_memory_limit: None,
+            query_concurrency_limits: None,
+            admission: None,
+            query_result_cache_size: None,
+            result_cache: None,
         }
     }
 
+    /// Limit the memory each query may use for buffering the data of sorts
+    /// and aggregations to `limit` bytes. Queries exceeding their limit fail
+    /// with an error.
+    pub fn with_query_memory_limit(mut self, limit: usize) -> Self {
+        self.query_memory_limit = Some(limit);
+        self
+    }
+
+    /// Limit the queries each database may run at the same time to
+    /// `limits`, see [`for_database`](Self::for_database)
+    pub fn with_query_concurrency_limits(mut self, limits: QueryConcurrencyLimits) -> Self {
+        self.query_concurrency_limits = Some(limits);
+        self
+    }
+
+    /// Cache up to `size` bytes of the results of the queries of each
+    /// database, see [`for_database`](Self::for_database)
+    pub fn with_query_result_cache_size(mut self, size: usize) -> Self {
+        self.query_result_cache_size = Some(size);
+        self
+    }
+
+    /// Returns an executor for the queries of a single database. It runs
+    /// them on the same thread pool as this executor, but with their own
+    /// concurrency limits so that the queries of one database cannot
+    /// starve the queries of the others.
+    pub fn for_database(&self) -> Self {
+        Self {
+            counters: Arc::clone(&self.counters),
+            exec: self.exec.clone(),
+            query_memory_limit: self.query_memory_limit,
+            query_concurrency_limits: self.query_concurrency_limits,
+            admission: self
+                .query_concurrency_limits
+                .map(|limits| Arc::new(QueryAdmission::new(limits))),
+            query_result_cache_size: self.query_result_cache_size,
+            result_cache: self
+                .query_result_cache_size
+                .map(|size| Arc::new(QueryResultCache::new(size))),
+        }
+    }
+
+    /// Returns the cache of the results of the queries of the database
+    /// this executor runs queries for, if result caching is enabled
+    pub fn result_cache(&self) -> Option<&Arc<QueryResultCache>> {
+        self.result_cache.as_ref()
+    }
+
     /// Executes this plan and returns the resulting set of strings
     pub async fn to_string_set(&self, plan: StringSetPlan) -> Result<StringSetRef> {
         match plan {
@@ -247,7 +323,12 @@ impl Executor {
 
     /// Create a new execution context, suitable for executing a new query
     pub fn new_context(&self) -> IOxExecutionContext {
-        IOxExecutionContext::new(self.exec.clone(), Arc::clone(&self.counters))
+        IOxExecutionContext::new(
+            self.exec.clone(),
+            Arc::clone(&self.counters),
+            self.query_memory_limit,
+            self.admission.clone(),
+        )
     }
 
     /// plans and runs the plans in parallel and collects the results
@@ -483,6 +564,69 @@ mod tests {
         assert_eq!(results, to_set(&["f1", "f2"]));
     }
 
+    #[tokio::test]
+    async fn executor_query_memory_limit() {
+        let batch = RecordBatch::try_from_iter_with_nullable(vec![(
+            "a",
+            to_string_array(&["foo", "bar", "baz"]),
+            true,
+        )])
+        .expect("created new record batch");
+        let sort = |batch: &RecordBatch| {
+            LogicalPlanBuilder::scan_memory(vec![vec![batch.clone()]], batch.schema(), None)
+                .unwrap()
+                .sort(vec![datafusion::logical_plan::col("a").sort(true, true)])
+                .unwrap()
+                .build()
+                .unwrap()
+        };
+
+        // within the limit
+        let executor = Executor::new(1).with_query_memory_limit(1_000_000);
+        let results = executor.run_logical_plan(sort(&batch)).await.unwrap();
+        assert_eq!(results.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+
+        // the sort exceeds the limit
+        let executor = Executor::new(1).with_query_memory_limit(10);
+        let err = executor
+            .run_logical_plan(sort(&batch))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("Query exceeded its memory limit of 10 bytes"),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn executor_limits_concurrent_queries_per_database() {
+        let executor = Executor::new(1).with_query_concurrency_limits(QueryConcurrencyLimits {
+            max_concurrent: 1,
+            max_queued: 0,
+            queue_timeout: std::time::Duration::from_secs(10),
+        });
+        let db1 = executor.for_database();
+        let db2 = executor.for_database();
+
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Utf8, true)]));
+        let plan = make_plan(schema, vec![]);
+
+        // the stream of the first query holds the only slot of db1
+        let ctx = db1.new_context();
+        let stream = ctx.execute(ctx.prepare_plan(&plan).unwrap()).await.unwrap();
+
+        let err = db1.run_logical_plan(plan.clone()).await.unwrap_err();
+        assert!(err.to_string().contains("Too many queries"), "{}", err);
+
+        // other databases are not affected
+        db2.run_logical_plan(plan.clone()).await.unwrap();
+
+        drop(stream);
+        db1.run_logical_plan(plan).await.unwrap();
+    }
+
     /// return a set for testing
     fn to_set(strs: &[&str]) -> StringSetRef {
         StringSetRef::new(strs.iter().map(|s| s.to_string()).collect::<StringSet>())
@@ -0,0 +1,256 @@
+//! This module contains the admission control of the queries of a
+//! database.
+//!
+//! All databases share the thread pool of the [`Executor`], so without a
+//! limit a burst of queries against one database can take all its threads
+//! and delay the queries of every other database. A [`QueryAdmission`]
+//! bounds how many plans of a database execute at the same time. Plans
+//! over the limit wait in a bounded queue for their turn, and fail once the
+//! queue is full or they have waited for too long.
+//!
+//! [`Executor`]: super::Executor
+
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use arrow::{datatypes::SchemaRef, error::Result as ArrowResult, record_batch::RecordBatch};
+use datafusion::physical_plan::{RecordBatchStream, SendableRecordBatchStream};
+use futures::Stream;
+use snafu::Snafu;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display(
+        "Too many queries: {} queries are already waiting for one of the {} query slots of the database",
+        max_queued,
+        max_concurrent
+    ))]
+    QueueFull {
+        max_concurrent: usize,
+        max_queued: usize,
+    },
+
+    #[snafu(display(
+        "Query timed out after waiting {:?} for one of the {} query slots of the database",
+        timeout,
+        max_concurrent
+    ))]
+    QueueTimeout {
+        max_concurrent: usize,
+        timeout: Duration,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The limits on the queries of a single database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryConcurrencyLimits {
+    /// How many query plans may execute at the same time
+    pub max_concurrent: usize,
+    /// How many query plans may wait for their turn to execute
+    pub max_queued: usize,
+    /// How long a query plan may wait for its turn before it fails
+    pub queue_timeout: Duration,
+}
+
+/// Admits the query plans of a database for execution within its
+/// [`QueryConcurrencyLimits`]
+#[derive(Debug)]
+pub(crate) struct QueryAdmission {
+    limits: QueryConcurrencyLimits,
+    slots: Arc<Semaphore>,
+    queued: AtomicUsize,
+}
+
+impl QueryAdmission {
+    pub fn new(limits: QueryConcurrencyLimits) -> Self {
+        Self {
+            limits,
+            slots: Arc::new(Semaphore::new(limits.max_concurrent)),
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    /// Waits for a query slot, which is released when the returned
+    /// permit is dropped
+    pub async fn admit(&self) -> Result<OwnedSemaphorePermit> {
+        if let Ok(permit) = Arc::clone(&self.slots).try_acquire_owned() {
+            return Ok(permit);
+        }
+
+        let QueryConcurrencyLimits {
+            max_concurrent,
+            max_queued,
+            queue_timeout,
+        } = self.limits;
+
+        // Leaves the queue when dropped, also if the query is cancelled
+        // while it waits
+        let queued = Queued::enter(&self.queued);
+        if queued.position >= max_queued {
+            return QueueFull {
+                max_concurrent,
+                max_queued,
+            }
+            .fail();
+        }
+
+        let permit =
+            tokio::time::timeout(queue_timeout, Arc::clone(&self.slots).acquire_owned()).await;
+
+        match permit {
+            Ok(permit) => Ok(permit.expect("query slots are never closed")),
+            Err(_) => QueueTimeout {
+                max_concurrent,
+                timeout: queue_timeout,
+            }
+            .fail(),
+        }
+    }
+}
+
+/// A place in the queue of a [`QueryAdmission`], left when dropped
+struct Queued<'a> {
+    queued: &'a AtomicUsize,
+    /// The number of queries that were waiting before this one
+    position: usize,
+}
+
+impl<'a> Queued<'a> {
+    fn enter(queued: &'a AtomicUsize) -> Self {
+        let position = queued.fetch_add(1, Ordering::SeqCst);
+        Self { queued, position }
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Holds the query slot of a plan until the stream of its results is
+/// dropped
+pub(crate) struct AdmittedStream {
+    inner: SendableRecordBatchStream,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl AdmittedStream {
+    pub fn new(inner: SendableRecordBatchStream, permit: OwnedSemaphorePermit) -> Self {
+        Self {
+            inner,
+            _permit: permit,
+        }
+    }
+}
+
+impl Stream for AdmittedStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl RecordBatchStream for AdmittedStream {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn admission(max_concurrent: usize, max_queued: usize) -> Arc<QueryAdmission> {
+        Arc::new(QueryAdmission::new(QueryConcurrencyLimits {
+            max_concurrent,
+            max_queued,
+            queue_timeout: Duration::from_secs(10),
+        }))
+    }
+
+    #[tokio::test]
+    async fn admits_queued_query_when_slot_is_released() {
+        let admission = admission(1, 1);
+        let permit = admission.admit().await.unwrap();
+
+        let queued = tokio::spawn({
+            let admission = Arc::clone(&admission);
+            async move { admission.admit().await.map(|_| ()) }
+        });
+        while admission.queued.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        drop(permit);
+        queued.await.unwrap().unwrap();
+        assert_eq!(admission.queued.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn leaves_queue_when_waiting_query_is_dropped() {
+        let admission = admission(1, 1);
+        let permit = admission.admit().await.unwrap();
+
+        let queued = tokio::spawn({
+            let admission = Arc::clone(&admission);
+            async move { admission.admit().await.map(|_| ()) }
+        });
+        while admission.queued.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        queued.abort();
+        assert!(queued.await.unwrap_err().is_cancelled());
+        assert_eq!(admission.queued.load(Ordering::SeqCst), 0);
+
+        // The place in the queue is free again
+        let queued = tokio::spawn({
+            let admission = Arc::clone(&admission);
+            async move { admission.admit().await.map(|_| ()) }
+        });
+        while admission.queued.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        drop(permit);
+        queued.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_query_when_queue_is_full() {
+        let admission = admission(1, 0);
+        let _permit = admission.admit().await.unwrap();
+
+        let err = admission.admit().await.unwrap_err();
+        assert!(matches!(err, Error::QueueFull { .. }), "{}", err);
+        assert_eq!(
+            err.to_string(),
+            "Too many queries: 0 queries are already waiting for one of the 1 query slots of the database"
+        );
+    }
+
+    #[tokio::test]
+    async fn times_out_waiting_query() {
+        let admission = Arc::new(QueryAdmission::new(QueryConcurrencyLimits {
+            max_concurrent: 1,
+            max_queued: 1,
+            queue_timeout: Duration::from_millis(1),
+        }));
+        let _permit = admission.admit().await.unwrap();
+
+        let err = admission.admit().await.unwrap_err();
+        assert!(matches!(err, Error::QueueTimeout { .. }), "{}", err);
+        assert_eq!(admission.queued.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,328 @@
+//! This module contains the implementation of `EXPLAIN ANALYZE`, which runs
+//! a query and reports the physical plan annotated with the number of rows
+//! each node produced and the time spent producing them.
+//!
+//! The DataFusion version in use only supports `EXPLAIN [VERBOSE]`, so the
+//! `ANALYZE` keyword is handled by IOx before the rest of the statement is
+//! planned by DataFusion.
+
+use std::{
+    any::Any,
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use arrow::{
+    array::StringArray,
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    error::Result as ArrowResult,
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use datafusion::{
+    error::{DataFusionError as Error, Result},
+    physical_plan::{
+        collect, common::SizedRecordBatchStream, displayable, DisplayFormatType, ExecutionPlan,
+        Partitioning, RecordBatchStream, SendableRecordBatchStream,
+    },
+};
+use futures::Stream;
+
+/// Returns the statement following `EXPLAIN ANALYZE` if `sql` starts with
+/// these keywords (in any case), or `None` otherwise
+pub(crate) fn strip_explain_analyze(sql: &str) -> Option<&str> {
+    let rest = strip_keyword(sql.trim_start(), "EXPLAIN")?;
+    strip_keyword(rest, "ANALYZE")
+}
+
+/// Strips `keyword` and the whitespace following it from the start of `sql`
+fn strip_keyword<'a>(sql: &'a str, keyword: &str) -> Option<&'a str> {
+    let len = keyword.len();
+    let rest = sql.get(len..)?;
+    (sql[..len].eq_ignore_ascii_case(keyword) && rest.starts_with(char::is_whitespace))
+        .then(|| rest.trim_start())
+}
+
+/// Runs its input plan to completion and produces the plan annotated with
+/// the metrics collected while running it, followed by a summary:
+///
+/// ```text
+/// +---------------+----------------------------------------------------------+
+/// | plan_type     | plan                                                     |
+/// +---------------+----------------------------------------------------------+
+/// | analyzed_plan | ProjectionExec: expr=[...], metrics=[output_rows=2, ...] |
+/// |               |   IOxReadFilterNode: ..., metrics=[output_rows=2, ...]   |
+/// | summary       | output_rows=2, elapsed=1.2ms                             |
+/// +---------------+----------------------------------------------------------+
+/// ```
+///
+/// The elapsed time of a node includes the time spent in its inputs.
+#[derive(Debug)]
+pub(crate) struct AnalyzeExec {
+    input: Arc<dyn ExecutionPlan>,
+    schema: SchemaRef,
+}
+
+impl AnalyzeExec {
+    pub fn new(input: Arc<dyn ExecutionPlan>) -> Self {
+        // Same columns as the output of DataFusion's `EXPLAIN`
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("plan_type", DataType::Utf8, false),
+            Field::new("plan", DataType::Utf8, false),
+        ]));
+
+        Self { input, schema }
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for AnalyzeExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![Arc::clone(&self.input)]
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(Self::new(Arc::clone(&children[0])))),
+            _ => Err(Error::Internal(
+                "AnalyzeExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(Error::Internal(format!(
+                "AnalyzeExec invalid partition {}",
+                partition
+            )));
+        }
+
+        let plan = instrument(Arc::clone(&self.input))?;
+        let start = Instant::now();
+        let batches = collect(Arc::clone(&plan)).await?;
+        let elapsed = start.elapsed();
+        let output_rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+
+        let plan_types = StringArray::from(vec!["analyzed_plan", "summary"]);
+        let plans = StringArray::from(vec![
+            displayable(plan.as_ref()).indent().to_string(),
+            format!("output_rows={}, elapsed={:?}", output_rows, elapsed),
+        ]);
+        let batch =
+            RecordBatch::try_new(self.schema(), vec![Arc::new(plan_types), Arc::new(plans)])?;
+
+        Ok(Box::pin(SizedRecordBatchStream::new(
+            self.schema(),
+            vec![Arc::new(batch)],
+        )))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default => write!(f, "AnalyzeExec"),
+        }
+    }
+}
+
+/// Returns a copy of `plan` where every node records the metrics displayed
+/// by [`AnalyzeExec`]
+fn instrument(plan: Arc<dyn ExecutionPlan>) -> Result<Arc<dyn ExecutionPlan>> {
+    let children = plan
+        .children()
+        .into_iter()
+        .map(instrument)
+        .collect::<Result<Vec<_>>>()?;
+
+    let plan = if children.is_empty() {
+        plan
+    } else {
+        plan.with_new_children(children)?
+    };
+
+    Ok(Arc::new(MetricsExec {
+        input: plan,
+        metrics: Default::default(),
+    }))
+}
+
+/// The metrics recorded for a node of the plan, across all its partitions
+#[derive(Debug, Default)]
+struct Metrics {
+    output_rows: AtomicUsize,
+    elapsed_nanos: AtomicU64,
+}
+
+/// Wraps a node of the plan and counts the rows it produces and the time
+/// spent producing them. It is transparent to the plan display: its
+/// children are the children of the wrapped node.
+#[derive(Debug)]
+struct MetricsExec {
+    input: Arc<dyn ExecutionPlan>,
+    metrics: Arc<Metrics>,
+}
+
+#[async_trait]
+impl ExecutionPlan for MetricsExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        self.input.children()
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(Self {
+            input: self.input.with_new_children(children)?,
+            metrics: Arc::clone(&self.metrics),
+        }))
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        let start = Instant::now();
+        let input = self.input.execute(partition).await?;
+        self.metrics
+            .elapsed_nanos
+            .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+
+        Ok(Box::pin(MetricsStream {
+            input,
+            metrics: Arc::clone(&self.metrics),
+        }))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.input.fmt_as(t, f)?;
+        write!(
+            f,
+            ", metrics=[output_rows={}, elapsed={:?}]",
+            self.metrics.output_rows.load(Ordering::Relaxed),
+            Duration::from_nanos(self.metrics.elapsed_nanos.load(Ordering::Relaxed))
+        )
+    }
+}
+
+/// Records the metrics of a stream produced by [`MetricsExec`]
+struct MetricsStream {
+    input: SendableRecordBatchStream,
+    metrics: Arc<Metrics>,
+}
+
+impl Stream for MetricsStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let start = Instant::now();
+        let poll = self.input.as_mut().poll_next(cx);
+        self.metrics
+            .elapsed_nanos
+            .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+
+        if let Poll::Ready(Some(Ok(batch))) = &poll {
+            self.metrics
+                .output_rows
+                .fetch_add(batch.num_rows(), Ordering::Relaxed);
+        }
+        poll
+    }
+}
+
+impl RecordBatchStream for MetricsStream {
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int64Array;
+    use datafusion::physical_plan::memory::MemoryExec;
+
+    #[test]
+    fn strip_explain_analyze_prefix() {
+        assert_eq!(
+            strip_explain_analyze("EXPLAIN ANALYZE SELECT * from cpu"),
+            Some("SELECT * from cpu")
+        );
+        assert_eq!(
+            strip_explain_analyze("  explain\n  analyze\tSELECT 1"),
+            Some("SELECT 1")
+        );
+        assert_eq!(strip_explain_analyze("EXPLAIN SELECT 1"), None);
+        assert_eq!(strip_explain_analyze("EXPLAIN ANALYZED SELECT 1"), None);
+        assert_eq!(strip_explain_analyze("SELECT * from explain"), None);
+        assert_eq!(strip_explain_analyze("EXPLAIN"), None);
+    }
+
+    #[tokio::test]
+    async fn analyze_counts_rows() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+        let batch = |values: Vec<i64>| {
+            RecordBatch::try_new(
+                Arc::clone(&schema),
+                vec![Arc::new(Int64Array::from(values))],
+            )
+            .unwrap()
+        };
+        let input = MemoryExec::try_new(
+            &[vec![batch(vec![1, 2])], vec![batch(vec![3, 4, 5])]],
+            Arc::clone(&schema),
+            None,
+        )
+        .unwrap();
+
+        let analyze = Arc::new(AnalyzeExec::new(Arc::new(input)));
+        let batches = collect(analyze).await.unwrap();
+        assert_eq!(batches.len(), 1);
+
+        let plans = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert!(
+            plans.value(0).contains("metrics=[output_rows=5, elapsed="),
+            "unexpected plan: {}",
+            plans.value(0)
+        );
+        assert!(
+            plans.value(1).starts_with("output_rows=5, elapsed="),
+            "unexpected summary: {}",
+            plans.value(1)
+        );
+    }
+}
@@ -16,9 +16,25 @@ use datafusion::{
     prelude::*,
 };
 
-use crate::exec::schema_pivot::{SchemaPivotExec, SchemaPivotNode};
+use crate::{
+    exec::{
+        admission::{AdmittedStream, QueryAdmission},
+        analyze::{strip_explain_analyze, AnalyzeExec},
+        gapfill::{GapFillExec, GapFillNode},
+        memory::{limit_memory, MemoryBudget},
+        schema_pivot::{SchemaPivotExec, SchemaPivotNode},
+        topk::plan_top_k,
+    },
+    func::{
+        selectors::{plan_sql_selectors, register_sql_selectors},
+        window::date_bin_udf,
+    },
+    provider::{merge_sorted_partitions, plan_metadata_aggregates},
+    PartitionChunk,
+};
 
-use observability_deps::tracing::debug;
+use observability_deps::tracing::{debug, info_span, Instrument};
+use tokio::sync::OwnedSemaphorePermit;
 
 // Reuse DataFusion error and Result types for this module
 pub use datafusion::error::{DataFusionError as Error, Result};
@@ -42,7 +58,8 @@ impl QueryPlanner for IOxQueryPlanner {
         logical_plan: &LogicalPlan,
         ctx_state: &ExecutionContextState,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        // Teach the default physical planner how to plan SchemaPivot nodes.
+        // Teach the default physical planner how to plan SchemaPivot and
+        // GapFill nodes.
         let physical_planner =
             DefaultPhysicalPlanner::with_extension_planners(vec![Arc::new(IOxExtensionPlanner {})]);
         // Delegate most work of physical planning to the default physical planner
@@ -61,17 +78,24 @@ impl ExtensionPlanner for IOxExtensionPlanner {
         inputs: &[Arc<dyn ExecutionPlan>],
         _ctx_state: &ExecutionContextState,
     ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
-        node.as_any()
-            .downcast_ref::<SchemaPivotNode>()
-            .map(|schema_pivot| {
-                assert_eq!(inputs.len(), 1, "Inconsistent number of inputs");
-                let execution_plan = Arc::new(SchemaPivotExec::new(
-                    Arc::clone(&inputs[0]),
-                    schema_pivot.schema().as_ref().clone().into(),
-                ));
-                Ok(execution_plan as _)
-            })
-            .transpose()
+        let any = node.as_any();
+        let execution_plan = if let Some(schema_pivot) = any.downcast_ref::<SchemaPivotNode>() {
+            assert_eq!(inputs.len(), 1, "Inconsistent number of inputs");
+            Some(Arc::new(SchemaPivotExec::new(
+                Arc::clone(&inputs[0]),
+                schema_pivot.schema().as_ref().clone().into(),
+            )) as _)
+        } else if let Some(gap_fill) = any.downcast_ref::<GapFillNode>() {
+            assert_eq!(inputs.len(), 1, "Inconsistent number of inputs");
+            let input = match inputs[0].output_partitioning().partition_count() {
+                1 => Arc::clone(&inputs[0]),
+                _ => Arc::new(MergeExec::new(Arc::clone(&inputs[0]))) as _,
+            };
+            Some(Arc::new(GapFillExec::new(input, gap_fill.params().clone())) as _)
+        } else {
+            None
+        };
+        Ok(execution_plan)
     }
 }
 
@@ -96,6 +120,13 @@ pub struct IOxExecutionContext {
     /// dedicated tokio runtime to run them so that other requests
     /// can be handled.
     exec: DedicatedExecutor,
+
+    /// The memory, in bytes, each plan prepared by this context may use
+    /// for sorts and aggregations
+    memory_limit: Option<usize>,
+
+    /// Bounds how many plans of the database execute at the same time
+    admission: Option<Arc<QueryAdmission>>,
 }
 
 impl fmt::Debug for IOxExecutionContext {
@@ -103,6 +134,8 @@ impl fmt::Debug for IOxExecutionContext {
         f.debug_struct("IOxExecutionContext")
             .field("counters", &self.counters)
             .field("inner", &"<DataFusion ExecutionContext>")
+            .field("memory_limit", &self.memory_limit)
+            .field("admission", &self.admission)
             .finish()
     }
 }
@@ -112,7 +145,12 @@ impl IOxExecutionContext {
     ///
     /// The config is created with a default catalog and schema, but this
     /// can be overridden at a later date
-    pub fn new(exec: DedicatedExecutor, counters: Arc<ExecutionCounters>) -> Self {
+    pub fn new(
+        exec: DedicatedExecutor,
+        counters: Arc<ExecutionCounters>,
+        memory_limit: Option<usize>,
+        admission: Option<Arc<QueryAdmission>>,
+    ) -> Self {
         const BATCH_SIZE: usize = 1000;
 
         // TBD: Should we be reusing an execution context across all executions?
@@ -123,12 +161,16 @@ impl IOxExecutionContext {
             .with_default_catalog_and_schema(DEFAULT_CATALOG, DEFAULT_SCHEMA)
             .with_query_planner(Arc::new(IOxQueryPlanner {}));
 
-        let inner = ExecutionContext::with_config(config);
+        let mut inner = ExecutionContext::with_config(config);
+        register_sql_selectors(&mut inner);
+        inner.register_udf(date_bin_udf());
 
         Self {
             counters,
             inner,
             exec,
+            memory_limit,
+            admission,
         }
     }
 
@@ -144,20 +186,90 @@ impl IOxExecutionContext {
 
     /// Prepare a SQL statement for execution. This assumes that any
     /// tables referenced in the SQL have been registered with this context
+    ///
+    /// In addition to the SQL supported by DataFusion, `EXPLAIN ANALYZE
+    /// <statement>` runs the statement and returns its plan annotated with
+    /// runtime metrics, the selector functions such as
+    /// `selector_first_value(column, time)` can be called, and
+    /// `date_bin(stride, time)` truncates timestamps to windows so that
+    /// queries can `GROUP BY date_bin('1m', time)`.
     pub fn prepare_sql(&mut self, sql: &str) -> Result<Arc<dyn ExecutionPlan>> {
+        self.prepare_sql_with_rewrite(sql, &Ok, &Ok)
+    }
+
+    /// Prepare a SQL statement for execution like
+    /// [`prepare_sql`](Self::prepare_sql), answering aggregates such as
+    /// `count(*)` over tables made of chunks of type `C` from the
+    /// statistics of the chunks where possible, and merging the data of
+    /// chunks known to be sorted instead of sorting it again
+    pub fn prepare_sql_for_chunks<C: PartitionChunk + 'static>(
+        &mut self,
+        sql: &str,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        self.prepare_sql_for_chunks_with_rewrite::<C>(sql, &Ok)
+    }
+
+    /// Prepare a SQL statement for execution like
+    /// [`prepare_sql_for_chunks`](Self::prepare_sql_for_chunks), then
+    /// transforming its logical plan with `rewrite` before it is optimized
+    pub fn prepare_sql_for_chunks_with_rewrite<C: PartitionChunk + 'static>(
+        &mut self,
+        sql: &str,
+        rewrite: &dyn Fn(LogicalPlan) -> Result<LogicalPlan>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        self.prepare_sql_with_rewrite(
+            sql,
+            &|plan| rewrite(plan_metadata_aggregates::<C>(plan)?),
+            &merge_sorted_partitions::<C>,
+        )
+    }
+
+    /// Prepare a SQL statement for execution, transforming its logical
+    /// plan with `rewrite` before it is optimized and its physical plan
+    /// with `rewrite_physical`
+    fn prepare_sql_with_rewrite(
+        &mut self,
+        sql: &str,
+        rewrite: &dyn Fn(LogicalPlan) -> Result<LogicalPlan>,
+        rewrite_physical: &dyn Fn(Arc<dyn ExecutionPlan>) -> Result<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
         debug!(text=%sql, "SQL");
-        let logical_plan = self.inner.sql(sql)?.to_logical_plan();
-        self.prepare_plan(&logical_plan)
+        if let Some(sql) = strip_explain_analyze(sql) {
+            let plan = self.prepare_sql_with_rewrite(sql, rewrite, rewrite_physical)?;
+            return Ok(Arc::new(AnalyzeExec::new(plan)));
+        }
+
+        let logical_plan = info_span!("plan_sql")
+            .in_scope(|| self.inner.sql(sql))?
+            .to_logical_plan();
+        let logical_plan = plan_sql_selectors(logical_plan)?;
+        let logical_plan = rewrite(logical_plan)?;
+        self.prepare_plan_with_rewrite(&logical_plan, rewrite_physical)
     }
 
     /// Prepare (optimize + plan) a pre-created logical plan for execution
     pub fn prepare_plan(&self, plan: &LogicalPlan) -> Result<Arc<dyn ExecutionPlan>> {
+        self.prepare_plan_with_rewrite(plan, &Ok)
+    }
+
+    /// Prepare a pre-created logical plan for execution, transforming
+    /// its physical plan with `rewrite_physical`
+    fn prepare_plan_with_rewrite(
+        &self,
+        plan: &LogicalPlan,
+        rewrite_physical: &dyn Fn(Arc<dyn ExecutionPlan>) -> Result<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
         debug!(text=%plan.display_indent_schema(), "initial plan");
 
-        let plan = self.inner.optimize(&plan)?;
+        let plan = info_span!("optimize_plan").in_scope(|| self.inner.optimize(&plan))?;
         debug!(text=%plan.display_indent_schema(), graphviz=%plan.display_graphviz(), "optimized plan");
 
         let physical_plan = self.inner.create_physical_plan(&plan)?;
+        let physical_plan = plan_top_k(rewrite_physical(physical_plan)?)?;
+        let physical_plan = match self.memory_limit {
+            Some(limit) => limit_memory(physical_plan, &Arc::new(MemoryBudget::new(limit)))?,
+            None => physical_plan,
+        };
 
         debug!(text=%displayable(physical_plan.as_ref()).indent(), "optimized physical plan");
         Ok(physical_plan)
@@ -166,6 +278,7 @@ impl IOxExecutionContext {
     /// Executes the logical plan using DataFusion on a separate
     /// thread pool and produces RecordBatches
     pub async fn collect(&self, physical_plan: Arc<dyn ExecutionPlan>) -> Result<Vec<RecordBatch>> {
+        let _permit = self.admit().await?;
         self.counters.inc_plans_run();
 
         debug!(
@@ -173,9 +286,13 @@ impl IOxExecutionContext {
             displayable(physical_plan.as_ref()).indent()
         );
 
-        self.exec.spawn(collect(physical_plan)).await.map_err(|e| {
-            Error::Execution(format!("Error running IOxExecutionContext::collect: {}", e))
-        })?
+        let span = info_span!("collect");
+        self.exec
+            .spawn(collect(physical_plan).instrument(span))
+            .await
+            .map_err(|e| {
+                Error::Execution(format!("Error running IOxExecutionContext::collect: {}", e))
+            })?
     }
 
     /// Executes the physical plan and produces a RecordBatchStream to stream
@@ -184,21 +301,45 @@ impl IOxExecutionContext {
         &self,
         physical_plan: Arc<dyn ExecutionPlan>,
     ) -> Result<SendableRecordBatchStream> {
-        self.exec
-            .spawn(async move {
-                if physical_plan.output_partitioning().partition_count() <= 1 {
-                    physical_plan.execute(0).await
-                } else {
-                    // merge into a single partition
-                    let plan = MergeExec::new(physical_plan);
-                    // MergeExec must produce a single partition
-                    assert_eq!(1, plan.output_partitioning().partition_count());
-                    plan.execute(0).await
+        let permit = self.admit().await?;
+        let span = info_span!("execute");
+        let stream = self
+            .exec
+            .spawn(
+                async move {
+                    if physical_plan.output_partitioning().partition_count() <= 1 {
+                        physical_plan.execute(0).await
+                    } else {
+                        // merge into a single partition
+                        let plan = MergeExec::new(physical_plan);
+                        // MergeExec must produce a single partition
+                        assert_eq!(1, plan.output_partitioning().partition_count());
+                        plan.execute(0).await
+                    }
                 }
-            })
+                .instrument(span),
+            )
             .await
             .map_err(|e| {
                 Error::Execution(format!("Error running IOxExecutionContext::execute: {}", e))
-            })?
+            })??;
+
+        Ok(match permit {
+            Some(permit) => Box::pin(AdmittedStream::new(stream, permit)),
+            None => stream,
+        })
+    }
+
+    /// Waits until the database has a free query slot for a plan, if the
+    /// number of plans it executes at the same time is limited
+    async fn admit(&self) -> Result<Option<OwnedSemaphorePermit>> {
+        match &self.admission {
+            Some(admission) => admission
+                .admit()
+                .await
+                .map(Some)
+                .map_err(|e| Error::Execution(e.to_string())),
+            None => Ok(None),
+        }
     }
 }
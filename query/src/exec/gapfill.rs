@@ -0,0 +1,819 @@
+//! This module contains code for the "GapFill" DataFusion extension
+//! plan node
+//!
+//! A GapFill node takes the output of an aggregate grouped by time
+//! windows (see `date_bin`), such as
+//!
+//!  region | time | user
+//! --------+------+------
+//!  west   | 0    | 1.0
+//!  west   | 20   | 3.0
+//!
+//! And adds a row for each window in the requested range that has no
+//! data. With windows of 10 and linear interpolation the above becomes
+//!
+//!  region | time | user
+//! --------+------+------
+//!  west   | 0    | 1.0
+//!  west   | 10   | 2.0
+//!  west   | 20   | 3.0
+//!
+//! The gaps of each series (distinct values of the group columns) are
+//! filled separately.
+
+use std::{
+    any::Any,
+    cmp::Ordering,
+    fmt::{self, Debug},
+    iter,
+    ops::Range,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+
+use arrow::{
+    array::{
+        ord::build_compare, Array, ArrayRef, Float64Array, Int64Array, PrimitiveArray,
+        TimestampNanosecondArray, UInt32Array, UInt64Array,
+    },
+    compute::{cast, concat, take},
+    datatypes::{ArrowPrimitiveType, DataType, SchemaRef, TimeUnit},
+    error::Result as ArrowResult,
+    record_batch::RecordBatch,
+};
+use datafusion::{
+    error::{DataFusionError as Error, Result},
+    logical_plan::{
+        self, DFSchemaRef, Expr, LogicalPlan, LogicalPlanBuilder, UserDefinedLogicalNode,
+    },
+    physical_plan::{
+        common::{self, SizedRecordBatchStream},
+        DisplayFormatType, Distribution, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+    },
+};
+
+use crate::{
+    func::window::{Duration, Window},
+    group_by::WindowDuration,
+};
+
+/// How the value columns of the rows added for missing windows are filled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillStrategy {
+    /// Fill with nulls
+    Null,
+
+    /// Fill with the values of the previous row of the series
+    Previous,
+
+    /// Linearly interpolate between the rows of the series before and after
+    /// the gap. Columns that are not numeric are filled with nulls.
+    Linear,
+}
+
+/// The maximum number of windows between the `start` and `stop` of a
+/// [`GapFillParams`], each of which may add a row to every series
+pub const MAX_GAP_FILL_WINDOWS: i128 = 100_000;
+
+/// Nanoseconds in the shortest month, to bound the number of windows of a
+/// stride in months
+const MIN_MONTH_NANOS: i128 = 28 * 24 * 60 * 60 * 1_000_000_000;
+
+/// Describes the gaps to fill in the output of a plan grouped by time
+/// windows
+#[derive(Debug, Clone, PartialEq)]
+pub struct GapFillParams {
+    /// The column containing the start of each window
+    pub time_column: String,
+
+    /// The columns identifying each series. The gaps of each series are
+    /// filled separately.
+    pub group_columns: Vec<String>,
+
+    /// The width of the windows
+    pub stride: WindowDuration,
+
+    /// Windows are added to each series from the one containing `start`
+    /// up to (but excluding) `stop`, in nanoseconds since the epoch
+    pub start: i64,
+    pub stop: i64,
+
+    /// How the value columns of the added rows are filled
+    pub fill: FillStrategy,
+}
+
+/// Creates a plan that fills the gaps in the output of `input`, a plan
+/// grouped by time windows, as described by `params`.
+pub fn make_gap_fill(input: LogicalPlan, params: GapFillParams) -> Result<LogicalPlan> {
+    for name in params
+        .group_columns
+        .iter()
+        .chain(iter::once(&params.time_column))
+    {
+        input.schema().field_with_unqualified_name(name)?;
+    }
+
+    let positive = match params.stride {
+        WindowDuration::Fixed { nanoseconds } => nanoseconds > 0,
+        WindowDuration::Variable { months, negative } => months > 0 && !negative,
+    };
+    if !positive {
+        return Err(Error::Plan(format!(
+            "GapFill stride must be positive, got {:?}",
+            params.stride
+        )));
+    }
+
+    // Filling a long range with a short stride would add more rows to each
+    // series than can be held in memory
+    let stride_nanos = match params.stride {
+        WindowDuration::Fixed { nanoseconds } => nanoseconds as i128,
+        WindowDuration::Variable { months, .. } => months as i128 * MIN_MONTH_NANOS,
+    };
+    let windows = (params.stop as i128 - params.start as i128) / stride_nanos + 1;
+    if windows > MAX_GAP_FILL_WINDOWS {
+        return Err(Error::Plan(format!(
+            "GapFill range [{}, {}) spans {} windows of {:?}, more than the maximum of {}",
+            params.start, params.stop, windows, params.stride, MAX_GAP_FILL_WINDOWS
+        )));
+    }
+
+    // GapFillExec relies on the rows of each series being adjacent and
+    // sorted by time
+    let sort_exprs = params
+        .group_columns
+        .iter()
+        .map(|name| logical_plan::col(name).sort(true, false))
+        .chain(iter::once(
+            logical_plan::col(&params.time_column).sort(true, true),
+        ))
+        .collect();
+    let input = LogicalPlanBuilder::from(&input).sort(sort_exprs)?.build()?;
+
+    let node = Arc::new(GapFillNode::new(input, params));
+    Ok(LogicalPlan::Extension { node })
+}
+
+/// Implements the GapFill operation described in make_gap_fill
+pub struct GapFillNode {
+    input: LogicalPlan,
+    params: GapFillParams,
+    // these expressions represent what columns are "used" by this
+    // node (in this case all of them) -- columns that are not used
+    // are optimzied away by datafusion.
+    exprs: Vec<Expr>,
+}
+
+impl GapFillNode {
+    pub fn new(input: LogicalPlan, params: GapFillParams) -> Self {
+        let exprs = input
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| logical_plan::col(field.name()))
+            .collect::<Vec<_>>();
+
+        Self {
+            input,
+            params,
+            exprs,
+        }
+    }
+
+    pub fn params(&self) -> &GapFillParams {
+        &self.params
+    }
+}
+
+impl Debug for GapFillNode {
+    /// Use explain format for the Debug format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_for_explain(f)
+    }
+}
+
+impl UserDefinedLogicalNode for GapFillNode {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    /// GapFill only adds rows, so its schema is that of its input
+    fn schema(&self) -> &DFSchemaRef {
+        self.input.schema()
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        self.exprs.clone()
+    }
+
+    /// For example: `GapFill: time_column=time, group_columns=[region], ...`
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GapFill: ")?;
+        fmt_params(&self.params, f)
+    }
+
+    fn from_template(
+        &self,
+        exprs: &[Expr],
+        inputs: &[LogicalPlan],
+    ) -> Arc<dyn UserDefinedLogicalNode + Send + Sync> {
+        assert_eq!(inputs.len(), 1, "GapFill: input sizes inconistent");
+        assert_eq!(
+            exprs.len(),
+            self.exprs.len(),
+            "GapFill: expression sizes inconistent"
+        );
+        Arc::new(Self::new(inputs[0].clone(), self.params.clone()))
+    }
+}
+
+fn fmt_params(params: &GapFillParams, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+        f,
+        "time_column={}, group_columns=[{}], stride={:?}, range=[{}, {}), fill={:?}",
+        params.time_column,
+        params.group_columns.join(", "),
+        params.stride,
+        params.start,
+        params.stop,
+        params.fill
+    )
+}
+
+// ------ The implementation of GapFill code follows -----
+
+/// Physical operator that implements the GapFill operation. Its input
+/// must be a single partition sorted by the group columns and then time.
+pub struct GapFillExec {
+    input: Arc<dyn ExecutionPlan>,
+    params: GapFillParams,
+}
+
+impl GapFillExec {
+    pub fn new(input: Arc<dyn ExecutionPlan>, params: GapFillParams) -> Self {
+        Self { input, params }
+    }
+}
+
+impl Debug for GapFillExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GapFillExec")
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for GapFillExec {
+    fn as_any(&self) -> &(dyn std::any::Any + 'static) {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn required_child_distribution(&self) -> Distribution {
+        Distribution::SinglePartition
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![Arc::clone(&self.input)]
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(Self {
+                input: Arc::clone(&children[0]),
+                params: self.params.clone(),
+            })),
+            _ => Err(Error::Internal(
+                "GapFillExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    /// Execute one partition and return an iterator over RecordBatch
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(Error::Internal(format!(
+                "GapFillExec invalid partition {}",
+                partition
+            )));
+        }
+
+        if self.input.output_partitioning().partition_count() != 1 {
+            return Err(Error::Internal(
+                "GapFillExec requires a single input partition".to_string(),
+            ));
+        }
+
+        // The rows of a series may span several batches, so read them all
+        let input_batches = common::collect(self.input.execute(0).await?).await?;
+
+        let batches = gap_fill(&self.params, self.schema(), &input_batches)?
+            .into_iter()
+            .map(Arc::new)
+            .collect();
+
+        Ok(Box::pin(SizedRecordBatchStream::new(
+            self.schema(),
+            batches,
+        )))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "GapFillExec: ")?;
+                fmt_params(&self.params, f)
+            }
+        }
+    }
+}
+
+/// A row of the output of GapFillExec
+#[derive(Debug, Clone, Copy)]
+enum OutputRow {
+    /// The input row at this index
+    Input(usize),
+
+    /// A row added for the missing window starting at `time`. `series` is
+    /// the index of an input row of the same series, and `prev` and `next`
+    /// the indexes of the input rows of the series around the gap.
+    Fill {
+        time: i64,
+        series: usize,
+        prev: Option<usize>,
+        next: Option<usize>,
+    },
+}
+
+/// Fills the gaps in `batches`, which are sorted by the group columns and
+/// then time.
+fn gap_fill(
+    params: &GapFillParams,
+    schema: SchemaRef,
+    batches: &[RecordBatch],
+) -> Result<Vec<RecordBatch>> {
+    let num_rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+    if num_rows == 0 {
+        return Ok(vec![]);
+    }
+
+    let time_idx = schema.index_of(&params.time_column)?;
+    let timezone = match schema.field(time_idx).data_type() {
+        DataType::Timestamp(TimeUnit::Nanosecond, timezone) => timezone.clone(),
+        data_type => {
+            return Err(Error::Plan(format!(
+                "GapFill time column {} must be a nanosecond timestamp, got {:?}",
+                params.time_column, data_type
+            )))
+        }
+    };
+    let group_idxs = params
+        .group_columns
+        .iter()
+        .map(|name| schema.index_of(name))
+        .collect::<ArrowResult<Vec<_>>>()?;
+
+    // Concatenate the batches column by column. Dictionaries are unpacked
+    // as concatenating them is not supported.
+    let columns = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let data_type = value_type(field.data_type());
+            let arrays = batches
+                .iter()
+                .map(|batch| cast(batch.column(i), &data_type))
+                .collect::<ArrowResult<Vec<_>>>()?;
+            let arrays: Vec<&dyn Array> = arrays.iter().map(|array| array.as_ref()).collect();
+            concat(&arrays)
+        })
+        .collect::<ArrowResult<Vec<_>>>()?;
+
+    let times = columns[time_idx]
+        .as_any()
+        .downcast_ref::<TimestampNanosecondArray>()
+        .expect("cast of time failed");
+
+    let comparators = group_idxs
+        .iter()
+        .map(|&i| build_compare(columns[i].as_ref(), columns[i].as_ref()))
+        .collect::<ArrowResult<Vec<_>>>()?;
+    let same_series = |a: usize, b: usize| {
+        group_idxs.iter().zip(&comparators).all(|(&i, compare)| {
+            let column = &columns[i];
+            match (column.is_valid(a), column.is_valid(b)) {
+                (true, true) => compare(a, b) == Ordering::Equal,
+                (false, false) => true,
+                _ => false,
+            }
+        })
+    };
+
+    let every = Duration::from(&params.stride);
+    let window = Window::new(every, every, Duration::from_nsecs(0));
+
+    let mut rows = Vec::with_capacity(num_rows);
+    let mut series_start = 0;
+    while series_start < num_rows {
+        let mut series_end = series_start + 1;
+        while series_end < num_rows && same_series(series_start, series_end) {
+            series_end += 1;
+        }
+        fill_series(&window, params, times, series_start..series_end, &mut rows);
+        series_start = series_end;
+    }
+
+    let output = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let column = &columns[i];
+            let array = if i == time_idx {
+                let values = rows
+                    .iter()
+                    .map(|row| match *row {
+                        OutputRow::Input(idx) => times.is_valid(idx).then(|| times.value(idx)),
+                        OutputRow::Fill { time, .. } => Some(time),
+                    })
+                    .collect();
+                Arc::new(TimestampNanosecondArray::from_opt_vec(
+                    values,
+                    timezone.clone(),
+                )) as ArrayRef
+            } else if group_idxs.contains(&i) {
+                take_rows(column, &rows, |row| match *row {
+                    OutputRow::Input(idx) => Some(idx),
+                    OutputRow::Fill { series, .. } => Some(series),
+                })?
+            } else {
+                match params.fill {
+                    FillStrategy::Null => take_rows(column, &rows, |row| match *row {
+                        OutputRow::Input(idx) => Some(idx),
+                        OutputRow::Fill { .. } => None,
+                    })?,
+                    FillStrategy::Previous => take_rows(column, &rows, |row| match *row {
+                        OutputRow::Input(idx) => Some(idx),
+                        OutputRow::Fill { prev, .. } => prev,
+                    })?,
+                    FillStrategy::Linear => interpolate(column, times, &rows)?,
+                }
+            };
+            cast(&array, field.data_type())
+        })
+        .collect::<ArrowResult<Vec<_>>>()?;
+
+    Ok(vec![RecordBatch::try_new(schema, output)?])
+}
+
+/// Appends the rows of the series at `series` in the input, and rows for
+/// the windows it is missing, to `rows` in time order
+fn fill_series(
+    window: &Window,
+    params: &GapFillParams,
+    times: &TimestampNanosecondArray,
+    series: Range<usize>,
+    rows: &mut Vec<OutputRow>,
+) {
+    let mut prev = None;
+    let mut row = series.start;
+    let mut bin = window.get_earliest_bounds(params.start).start;
+
+    while row < series.end || bin < params.stop {
+        let next_is_input = row < series.end
+            && (times.is_null(row) || bin >= params.stop || times.value(row) <= bin);
+
+        if next_is_input {
+            if times.is_valid(row) {
+                if times.value(row) == bin {
+                    bin = window.get_earliest_bounds(bin).stop;
+                }
+                prev = Some(row);
+            }
+            rows.push(OutputRow::Input(row));
+            row += 1;
+        } else {
+            rows.push(OutputRow::Fill {
+                time: bin,
+                series: series.start,
+                prev,
+                next: (row < series.end).then(|| row),
+            });
+            bin = window.get_earliest_bounds(bin).stop;
+        }
+    }
+}
+
+/// Returns the rows of `column` selected by `index` for each output row,
+/// or null where it returns `None`
+fn take_rows(
+    column: &ArrayRef,
+    rows: &[OutputRow],
+    index: impl Fn(&OutputRow) -> Option<usize>,
+) -> ArrowResult<ArrayRef> {
+    let indices: UInt32Array = rows
+        .iter()
+        .map(|row| index(row).map(|idx| idx as u32))
+        .collect();
+    take(column.as_ref(), &indices, None)
+}
+
+/// Returns the output rows of `column` with the values of the added rows
+/// linearly interpolated between the input rows around each gap
+fn interpolate(
+    column: &ArrayRef,
+    times: &TimestampNanosecondArray,
+    rows: &[OutputRow],
+) -> ArrowResult<ArrayRef> {
+    let array: ArrayRef = match column.data_type() {
+        DataType::Float64 => Arc::new(interpolate_values(
+            column.as_any().downcast_ref::<Float64Array>().unwrap(),
+            times,
+            rows,
+            |a, b, num, den| a + (b - a) * (num as f64 / den as f64),
+        )),
+        DataType::Int64 => Arc::new(interpolate_values(
+            column.as_any().downcast_ref::<Int64Array>().unwrap(),
+            times,
+            rows,
+            |a, b, num, den| {
+                (a as i128 + (b as i128 - a as i128) * num as i128 / den as i128) as i64
+            },
+        )),
+        DataType::UInt64 => Arc::new(interpolate_values(
+            column.as_any().downcast_ref::<UInt64Array>().unwrap(),
+            times,
+            rows,
+            |a, b, num, den| {
+                (a as i128 + (b as i128 - a as i128) * num as i128 / den as i128) as u64
+            },
+        )),
+        // only numbers can be interpolated
+        _ => take_rows(column, rows, |row| match *row {
+            OutputRow::Input(idx) => Some(idx),
+            OutputRow::Fill { .. } => None,
+        })?,
+    };
+    Ok(array)
+}
+
+/// Interpolates the added rows using `f(a, b, num, den)`, which returns the
+/// value `num / den` of the way from `a` to `b`
+fn interpolate_values<T, F>(
+    values: &PrimitiveArray<T>,
+    times: &TimestampNanosecondArray,
+    rows: &[OutputRow],
+    f: F,
+) -> PrimitiveArray<T>
+where
+    T: ArrowPrimitiveType,
+    F: Fn(T::Native, T::Native, i64, i64) -> T::Native,
+{
+    rows.iter()
+        .map(|row| match *row {
+            OutputRow::Input(idx) => values.is_valid(idx).then(|| values.value(idx)),
+            OutputRow::Fill {
+                time,
+                prev: Some(prev),
+                next: Some(next),
+                ..
+            } => {
+                let valid = values.is_valid(prev)
+                    && values.is_valid(next)
+                    && times.is_valid(prev)
+                    && times.is_valid(next);
+                valid.then(|| {
+                    let (prev_time, next_time) = (times.value(prev), times.value(next));
+                    f(
+                        values.value(prev),
+                        values.value(next),
+                        time - prev_time,
+                        next_time - prev_time,
+                    )
+                })
+            }
+            // there is nothing to interpolate from before the first row of a
+            // series or after its last
+            OutputRow::Fill { .. } => None,
+        })
+        .collect()
+}
+
+/// Returns the type the values of a column of type `data_type` are
+/// processed as
+fn value_type(data_type: &DataType) -> DataType {
+    match data_type {
+        DataType::Dictionary(_, value_type) => value_type.as_ref().clone(),
+        data_type => data_type.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::Executor;
+    use arrow::{
+        array::StringArray,
+        datatypes::{Field, Schema},
+    };
+    use arrow_util::assert_batches_eq;
+
+    #[tokio::test]
+    async fn gap_fill_null() {
+        let output = run_gap_fill(FillStrategy::Null, 0, 50).await;
+
+        let expected = vec![
+            "+--------+-------------------------------+------+-------+",
+            "| region | time                          | user | count |",
+            "+--------+-------------------------------+------+-------+",
+            "| east   | 1970-01-01 00:00:00           |      |       |",
+            "| east   | 1970-01-01 00:00:00.000000010 | 1    | 1     |",
+            "| east   | 1970-01-01 00:00:00.000000020 |      |       |",
+            "| east   | 1970-01-01 00:00:00.000000030 |      |       |",
+            "| east   | 1970-01-01 00:00:00.000000040 | 4    | 4     |",
+            "| west   | 1970-01-01 00:00:00           |      |       |",
+            "| west   | 1970-01-01 00:00:00.000000010 |      |       |",
+            "| west   | 1970-01-01 00:00:00.000000020 | 2    | 2     |",
+            "| west   | 1970-01-01 00:00:00.000000030 |      |       |",
+            "| west   | 1970-01-01 00:00:00.000000040 |      |       |",
+            "+--------+-------------------------------+------+-------+",
+        ];
+        assert_batches_eq!(&expected, &output);
+    }
+
+    #[tokio::test]
+    async fn gap_fill_previous() {
+        let output = run_gap_fill(FillStrategy::Previous, 0, 50).await;
+
+        let expected = vec![
+            "+--------+-------------------------------+------+-------+",
+            "| region | time                          | user | count |",
+            "+--------+-------------------------------+------+-------+",
+            "| east   | 1970-01-01 00:00:00           |      |       |",
+            "| east   | 1970-01-01 00:00:00.000000010 | 1    | 1     |",
+            "| east   | 1970-01-01 00:00:00.000000020 | 1    | 1     |",
+            "| east   | 1970-01-01 00:00:00.000000030 | 1    | 1     |",
+            "| east   | 1970-01-01 00:00:00.000000040 | 4    | 4     |",
+            "| west   | 1970-01-01 00:00:00           |      |       |",
+            "| west   | 1970-01-01 00:00:00.000000010 |      |       |",
+            "| west   | 1970-01-01 00:00:00.000000020 | 2    | 2     |",
+            "| west   | 1970-01-01 00:00:00.000000030 | 2    | 2     |",
+            "| west   | 1970-01-01 00:00:00.000000040 | 2    | 2     |",
+            "+--------+-------------------------------+------+-------+",
+        ];
+        assert_batches_eq!(&expected, &output);
+    }
+
+    #[tokio::test]
+    async fn gap_fill_linear() {
+        let output = run_gap_fill(FillStrategy::Linear, 0, 50).await;
+
+        let expected = vec![
+            "+--------+-------------------------------+------+-------+",
+            "| region | time                          | user | count |",
+            "+--------+-------------------------------+------+-------+",
+            "| east   | 1970-01-01 00:00:00           |      |       |",
+            "| east   | 1970-01-01 00:00:00.000000010 | 1    | 1     |",
+            "| east   | 1970-01-01 00:00:00.000000020 | 2    | 2     |",
+            "| east   | 1970-01-01 00:00:00.000000030 | 3    | 3     |",
+            "| east   | 1970-01-01 00:00:00.000000040 | 4    | 4     |",
+            "| west   | 1970-01-01 00:00:00           |      |       |",
+            "| west   | 1970-01-01 00:00:00.000000010 |      |       |",
+            "| west   | 1970-01-01 00:00:00.000000020 | 2    | 2     |",
+            "| west   | 1970-01-01 00:00:00.000000030 |      |       |",
+            "| west   | 1970-01-01 00:00:00.000000040 |      |       |",
+            "+--------+-------------------------------+------+-------+",
+        ];
+        assert_batches_eq!(&expected, &output);
+    }
+
+    #[tokio::test]
+    async fn gap_fill_keeps_rows_outside_range() {
+        let output = run_gap_fill(FillStrategy::Null, 15, 35).await;
+
+        let expected = vec![
+            "+--------+-------------------------------+------+-------+",
+            "| region | time                          | user | count |",
+            "+--------+-------------------------------+------+-------+",
+            "| east   | 1970-01-01 00:00:00.000000010 | 1    | 1     |",
+            "| east   | 1970-01-01 00:00:00.000000020 |      |       |",
+            "| east   | 1970-01-01 00:00:00.000000030 |      |       |",
+            "| east   | 1970-01-01 00:00:00.000000040 | 4    | 4     |",
+            "| west   | 1970-01-01 00:00:00.000000010 |      |       |",
+            "| west   | 1970-01-01 00:00:00.000000020 | 2    | 2     |",
+            "| west   | 1970-01-01 00:00:00.000000030 |      |       |",
+            "+--------+-------------------------------+------+-------+",
+        ];
+        assert_batches_eq!(&expected, &output);
+    }
+
+    #[test]
+    fn gap_fill_invalid_params() {
+        let plan = make_plan();
+
+        let mut params = make_params(FillStrategy::Null, 0, 50);
+        params.time_column = "foo".to_string();
+        let err = make_gap_fill(plan.clone(), params).unwrap_err();
+        assert!(err.to_string().contains("foo"), "{}", err);
+
+        let mut params = make_params(FillStrategy::Null, 0, 50);
+        params.stride = WindowDuration::from_nanoseconds(0);
+        let err = make_gap_fill(plan.clone(), params).unwrap_err();
+        assert!(
+            err.to_string().contains("GapFill stride must be positive"),
+            "{}",
+            err
+        );
+
+        let params = make_params(FillStrategy::Null, i64::MIN, i64::MAX);
+        let err = make_gap_fill(plan.clone(), params).unwrap_err();
+        assert!(
+            err.to_string().contains("more than the maximum of 100000"),
+            "{}",
+            err
+        );
+
+        // Strides in months are counted as months of 28 days, so there are
+        // never too many of them
+        let mut params = make_params(FillStrategy::Null, i64::MIN, i64::MAX);
+        params.stride = WindowDuration::from_months(1, false);
+        make_gap_fill(plan, params).unwrap();
+    }
+
+    /// Fills the gaps of the test data in windows of 10ns from `start` to
+    /// `stop`
+    async fn run_gap_fill(fill: FillStrategy, start: i64, stop: i64) -> Vec<RecordBatch> {
+        let plan = make_gap_fill(make_plan(), make_params(fill, start, stop)).unwrap();
+
+        Executor::new(1).run_logical_plan(plan).await.unwrap()
+    }
+
+    fn make_params(fill: FillStrategy, start: i64, stop: i64) -> GapFillParams {
+        GapFillParams {
+            time_column: "time".to_string(),
+            group_columns: vec!["region".to_string()],
+            stride: WindowDuration::from_nanoseconds(10),
+            start,
+            stop,
+            fill,
+        }
+    }
+
+    /// Returns a plan reading windowed data, out of order and split across
+    /// batches
+    fn make_plan() -> LogicalPlan {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("region", DataType::Utf8, true),
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                true,
+            ),
+            Field::new("user", DataType::Float64, true),
+            Field::new("count", DataType::Int64, true),
+        ]));
+
+        let make_batch = |region: Vec<&str>, time: Vec<i64>, user: Vec<f64>, count: Vec<i64>| {
+            RecordBatch::try_new(
+                Arc::clone(&schema),
+                vec![
+                    Arc::new(StringArray::from(region)),
+                    Arc::new(TimestampNanosecondArray::from_vec(time, None)),
+                    Arc::new(Float64Array::from(user)),
+                    Arc::new(Int64Array::from(count)),
+                ],
+            )
+            .unwrap()
+        };
+
+        let batches = vec![
+            make_batch(
+                vec!["east", "west"],
+                vec![40, 20],
+                vec![4.0, 2.0],
+                vec![4, 2],
+            ),
+            make_batch(vec!["east"], vec![10], vec![1.0], vec![1]),
+        ];
+
+        LogicalPlanBuilder::scan_memory(vec![batches], schema, None)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+}
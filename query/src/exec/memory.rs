@@ -0,0 +1,293 @@
+//! This module contains the per-query memory limit of the [`Executor`].
+//!
+//! The DataFusion version in use doesn't track the memory it allocates, so
+//! the data buffered by the memory intensive operators is accounted for by
+//! wrapping them in a [`MemoryLimitExec`] when the plan is prepared:
+//!
+//! * sorts buffer their entire input, so the input is charged
+//! * aggregations hold a hash table of all groups, so the groups they output
+//!   are charged
+//!
+//! The memory charged is released when the query completes. Once a query
+//! exceeds its limit it fails instead of growing further; spilling to disk is
+//! not supported by these operators.
+//!
+//! [`Executor`]: super::Executor
+
+use std::{
+    any::Any,
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use arrow::{
+    datatypes::SchemaRef,
+    error::{ArrowError, Result as ArrowResult},
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use datafusion::{
+    error::Result,
+    physical_plan::{
+        hash_aggregate::HashAggregateExec, sort::SortExec, DisplayFormatType, ExecutionPlan,
+        Partitioning, RecordBatchStream, SendableRecordBatchStream,
+    },
+};
+use futures::Stream;
+use snafu::Snafu;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display(
+        "Query exceeded its memory limit of {} bytes while buffering data for {}",
+        limit,
+        operator
+    ))]
+    MemoryLimitExceeded { limit: usize, operator: String },
+}
+
+/// The memory a single query may use for buffering data
+#[derive(Debug)]
+pub(crate) struct MemoryBudget {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl MemoryBudget {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// Charges `bytes` buffered by `operator` to the query, failing if the
+    /// query is now over its limit
+    fn charge(&self, bytes: usize, operator: &str) -> std::result::Result<(), Error> {
+        let used = self.used.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        if used > self.limit {
+            return MemoryLimitExceeded {
+                limit: self.limit,
+                operator,
+            }
+            .fail();
+        }
+        Ok(())
+    }
+}
+
+/// Returns `plan` with the data buffered by its sorts and aggregations
+/// charged to `budget`
+pub(crate) fn limit_memory(
+    plan: Arc<dyn ExecutionPlan>,
+    budget: &Arc<MemoryBudget>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let children = plan
+        .children()
+        .into_iter()
+        .map(|child| limit_memory(child, budget))
+        .collect::<Result<Vec<_>>>()?;
+
+    let any = plan.as_any();
+    if any.downcast_ref::<SortExec>().is_some() {
+        let children = children
+            .into_iter()
+            .map(|child| MemoryLimitExec::new(child, "SortExec", budget))
+            .collect();
+        plan.with_new_children(children)
+    } else if any.downcast_ref::<HashAggregateExec>().is_some() {
+        let plan = plan.with_new_children(children)?;
+        Ok(MemoryLimitExec::new(plan, "HashAggregateExec", budget))
+    } else if children.is_empty() {
+        Ok(plan)
+    } else {
+        plan.with_new_children(children)
+    }
+}
+
+/// Charges the record batches produced by its input to a [`MemoryBudget`]
+#[derive(Debug)]
+struct MemoryLimitExec {
+    input: Arc<dyn ExecutionPlan>,
+    /// The operator that buffers the data
+    operator: &'static str,
+    budget: Arc<MemoryBudget>,
+}
+
+impl MemoryLimitExec {
+    fn new(
+        input: Arc<dyn ExecutionPlan>,
+        operator: &'static str,
+        budget: &Arc<MemoryBudget>,
+    ) -> Arc<dyn ExecutionPlan> {
+        Arc::new(Self {
+            input,
+            operator,
+            budget: Arc::clone(budget),
+        })
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for MemoryLimitExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![Arc::clone(&self.input)]
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Self::new(
+                Arc::clone(&children[0]),
+                self.operator,
+                &self.budget,
+            )),
+            _ => Err(datafusion::error::DataFusionError::Internal(
+                "MemoryLimitExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        Ok(Box::pin(MemoryLimitStream {
+            input: self.input.execute(partition).await?,
+            operator: self.operator,
+            budget: Arc::clone(&self.budget),
+        }))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default => write!(
+                f,
+                "MemoryLimitExec: operator={}, limit={}",
+                self.operator, self.budget.limit
+            ),
+        }
+    }
+}
+
+struct MemoryLimitStream {
+    input: SendableRecordBatchStream,
+    operator: &'static str,
+    budget: Arc<MemoryBudget>,
+}
+
+impl Stream for MemoryLimitStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.input.as_mut().poll_next(cx) {
+            Poll::Ready(Some(Ok(batch))) => {
+                let bytes = batch
+                    .columns()
+                    .iter()
+                    .map(|column| column.get_array_memory_size())
+                    .sum();
+
+                let result = self
+                    .budget
+                    .charge(bytes, self.operator)
+                    .map(|_| batch)
+                    .map_err(|e| ArrowError::ExternalError(Box::new(e)));
+                Poll::Ready(Some(result))
+            }
+            poll => poll,
+        }
+    }
+}
+
+impl RecordBatchStream for MemoryLimitStream {
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::{
+        array::Int64Array,
+        datatypes::{DataType, Field, Schema},
+    };
+    use datafusion::physical_plan::{
+        collect,
+        expressions::{col, PhysicalSortExpr},
+        memory::MemoryExec,
+    };
+
+    fn sort_plan(num_rows: i64) -> Arc<dyn ExecutionPlan> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![Arc::new(Int64Array::from(
+                (0..num_rows).collect::<Vec<_>>(),
+            ))],
+        )
+        .unwrap();
+        let input = MemoryExec::try_new(&[vec![batch]], Arc::clone(&schema), None).unwrap();
+
+        let sort_expr = PhysicalSortExpr {
+            expr: col("a"),
+            options: Default::default(),
+        };
+        Arc::new(SortExec::try_new(vec![sort_expr], Arc::new(input)).unwrap())
+    }
+
+    #[tokio::test]
+    async fn sort_within_limit() {
+        let budget = Arc::new(MemoryBudget::new(1_000_000));
+        let plan = limit_memory(sort_plan(10), &budget).unwrap();
+
+        let batches = collect(plan).await.unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 10);
+        assert!(budget.used.load(Ordering::Relaxed) > 0);
+    }
+
+    #[tokio::test]
+    async fn sort_exceeds_limit() {
+        let budget = Arc::new(MemoryBudget::new(100));
+        let plan = limit_memory(sort_plan(1000), &budget).unwrap();
+
+        let err = collect(plan).await.unwrap_err().to_string();
+        assert!(
+            err.contains(
+                "Query exceeded its memory limit of 100 bytes while buffering data for SortExec"
+            ),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn charge() {
+        let budget = MemoryBudget::new(10);
+        budget.charge(6, "SortExec").unwrap();
+        budget.charge(4, "SortExec").unwrap();
+
+        let err = budget.charge(1, "HashAggregateExec").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Query exceeded its memory limit of 10 bytes while buffering data for HashAggregateExec"
+        );
+    }
+}
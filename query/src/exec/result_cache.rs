@@ -0,0 +1,334 @@
+//! This module contains the [`QueryResultCache`], which keeps the results
+//! of recent queries of a database in memory so that repeated identical
+//! queries, such as those of a dashboard that is refreshed every few
+//! seconds, are answered without running them again.
+//!
+//! Results are cached for a version of the data of the database, such as
+//! a fingerprint of its chunks, and are discarded once the data changes.
+
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use arrow::{datatypes::SchemaRef, error::Result as ArrowResult, record_batch::RecordBatch};
+use datafusion::physical_plan::{RecordBatchStream, SendableRecordBatchStream};
+use datafusion_util::MemoryStream;
+use futures::Stream;
+use parking_lot::Mutex;
+
+/// Functions whose results differ between runs of the same query over the
+/// same data, and tables that are not made of the chunks of the database
+const UNCACHEABLE: &[&str] = &[
+    "now(",
+    "random(",
+    "system.",
+    "information_schema",
+    "explain",
+];
+
+/// An in-memory cache of the results of the queries of a database, holding
+/// up to a number of bytes of results and evicting the least recently
+/// used results first
+#[derive(Debug)]
+pub struct QueryResultCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    /// The total size of the entries
+    bytes: usize,
+    /// Incremented on every use of an entry, to find the least recently
+    /// used one
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    version: u64,
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+    bytes: usize,
+    last_used: u64,
+}
+
+impl QueryResultCache {
+    /// Creates a cache that holds up to `capacity` bytes of results
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Default::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the key the results of `query` are cached under, which is
+    /// the query with its whitespace normalized, or `None` if its results
+    /// must not be cached, e.g. because it calls `now()`
+    pub fn key(query: &str) -> Option<String> {
+        let lowercase = query.to_lowercase();
+        if UNCACHEABLE.iter().any(|s| lowercase.contains(s)) {
+            return None;
+        }
+
+        // collapse whitespace outside of quotes
+        let mut key = String::with_capacity(query.len());
+        let mut quote = None;
+        let mut whitespace = false;
+        for c in query.trim().trim_end_matches(';').trim_end().chars() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '\'' || c == '"' => quote = Some(c),
+                None if c.is_whitespace() => {
+                    whitespace = true;
+                    continue;
+                }
+                None => {}
+            }
+            if whitespace {
+                key.push(' ');
+                whitespace = false;
+            }
+            key.push(c);
+        }
+        Some(key)
+    }
+
+    /// Returns the cached results of the query `key`, if they were cached
+    /// for `version` of the data
+    pub fn get(&self, key: &str, version: u64) -> Option<SendableRecordBatchStream> {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+
+        let stale = match state.entries.get_mut(key) {
+            Some(entry) if entry.version == version => {
+                state.clock += 1;
+                entry.last_used = state.clock;
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Some(Box::pin(MemoryStream::new_with_schema(
+                    entry.batches.clone(),
+                    Arc::clone(&entry.schema),
+                )));
+            }
+            Some(_) => true,
+            None => false,
+        };
+
+        if stale {
+            let entry = state.entries.remove(key).expect("entry exists");
+            state.bytes -= entry.bytes;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        None
+    }
+
+    /// Returns a stream of the results of `stream`, which caches them as
+    /// the results of the query `key` for `version` of the data once they
+    /// were all read. Results larger than the cache are not cached.
+    pub fn fill(
+        self: &Arc<Self>,
+        key: String,
+        version: u64,
+        stream: SendableRecordBatchStream,
+    ) -> SendableRecordBatchStream {
+        Box::pin(CachingStream {
+            inner: stream,
+            cache: Arc::clone(self),
+            key,
+            version,
+            batches: Some(vec![]),
+            bytes: 0,
+        })
+    }
+
+    fn insert(&self, key: String, entry: CacheEntry) {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+
+        state.clock += 1;
+        let entry = CacheEntry {
+            last_used: state.clock,
+            ..entry
+        };
+        state.bytes += entry.bytes;
+        if let Some(replaced) = state.entries.insert(key, entry) {
+            state.bytes -= replaced.bytes;
+        }
+
+        while state.bytes > self.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+                .expect("entries exist while they use bytes");
+            let entry = state.entries.remove(&oldest).expect("entry exists");
+            state.bytes -= entry.bytes;
+        }
+    }
+
+    /// The number of lookups that were answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// The number of lookups that were not answered from the cache
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// The size of the cached results, in bytes
+    pub fn bytes(&self) -> usize {
+        self.state.lock().bytes
+    }
+}
+
+/// Passes the results of a query through, and caches them once the query
+/// completes successfully
+struct CachingStream {
+    inner: SendableRecordBatchStream,
+    cache: Arc<QueryResultCache>,
+    key: String,
+    version: u64,
+    /// The results read so far, until they no longer fit into the cache
+    batches: Option<Vec<RecordBatch>>,
+    bytes: usize,
+}
+
+impl Stream for CachingStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let result = this.inner.as_mut().poll_next(cx);
+        match &result {
+            Poll::Ready(Some(Ok(batch))) => {
+                this.bytes += batch
+                    .columns()
+                    .iter()
+                    .map(|column| column.get_array_memory_size())
+                    .sum::<usize>();
+                if this.bytes > this.cache.capacity {
+                    this.batches = None;
+                } else if let Some(batches) = &mut this.batches {
+                    batches.push(batch.clone());
+                }
+            }
+            Poll::Ready(Some(Err(_))) => this.batches = None,
+            Poll::Ready(None) => {
+                if let Some(batches) = this.batches.take() {
+                    this.cache.insert(
+                        std::mem::take(&mut this.key),
+                        CacheEntry {
+                            version: this.version,
+                            schema: this.inner.schema(),
+                            batches,
+                            bytes: this.bytes,
+                            last_used: 0,
+                        },
+                    );
+                }
+            }
+            Poll::Pending => {}
+        }
+        result
+    }
+}
+
+impl RecordBatchStream for CachingStream {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::{
+        array::Int64Array,
+        datatypes::{DataType, Field, Schema},
+    };
+    use datafusion::physical_plan::common::collect;
+
+    fn batch(values: Vec<i64>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(values))]).unwrap()
+    }
+
+    fn batch_bytes(batch: &RecordBatch) -> usize {
+        batch
+            .columns()
+            .iter()
+            .map(|column| column.get_array_memory_size())
+            .sum()
+    }
+
+    async fn run(cache: &Arc<QueryResultCache>, key: &str, version: u64, values: Vec<i64>) {
+        let batch = batch(values);
+        let stream = Box::pin(MemoryStream::new(vec![batch.clone()]));
+        let results = collect(cache.fill(key.to_string(), version, stream))
+            .await
+            .unwrap();
+        assert_eq!(results, vec![batch]);
+    }
+
+    #[test]
+    fn normalize_keys() {
+        assert_eq!(
+            QueryResultCache::key("  SELECT *\n  FROM cpu   WHERE host = 'a  b' ;").unwrap(),
+            "SELECT * FROM cpu WHERE host = 'a  b'"
+        );
+        assert!(QueryResultCache::key("select * from cpu where time > NOW() - 1h").is_none());
+        assert!(QueryResultCache::key("select * from system.chunks").is_none());
+    }
+
+    #[tokio::test]
+    async fn cache_results_per_version() {
+        let cache = Arc::new(QueryResultCache::new(1_000_000));
+
+        assert!(cache.get("q", 1).is_none());
+        run(&cache, "q", 1, vec![1, 2, 3]).await;
+
+        let results = collect(cache.get("q", 1).unwrap()).await.unwrap();
+        assert_eq!(results, vec![batch(vec![1, 2, 3])]);
+        assert_eq!(cache.hits(), 1);
+
+        // the data changed, so the results are discarded
+        assert!(cache.get("q", 2).is_none());
+        assert_eq!(cache.misses(), 2);
+        assert_eq!(cache.bytes(), 0);
+    }
+
+    #[tokio::test]
+    async fn evict_least_recently_used_results() {
+        let bytes = batch_bytes(&batch(vec![1, 2, 3]));
+        let cache = Arc::new(QueryResultCache::new(2 * bytes));
+
+        run(&cache, "q1", 1, vec![1, 2, 3]).await;
+        run(&cache, "q2", 1, vec![4, 5, 6]).await;
+        assert!(cache.get("q1", 1).is_some());
+
+        run(&cache, "q3", 1, vec![7, 8, 9]).await;
+        assert_eq!(cache.bytes(), 2 * bytes);
+        assert!(cache.get("q1", 1).is_some());
+        assert!(cache.get("q2", 1).is_none());
+        assert!(cache.get("q3", 1).is_some());
+
+        // results larger than the cache are not cached
+        run(&cache, "q4", 1, (0..100).collect()).await;
+        assert!(cache.get("q4", 1).is_none());
+        assert_eq!(cache.bytes(), 2 * bytes);
+    }
+}
@@ -8,7 +8,7 @@ use tracker::{TaskRegistration, TrackedFutureExt};
 
 use futures::Future;
 
-use observability_deps::tracing::warn;
+use observability_deps::tracing::{debug, warn, Instrument, Span};
 
 /// The type of thing that the dedicated executor runs
 type Task = Pin<Box<dyn Future<Output = ()> + Send>>;
@@ -106,17 +106,34 @@ impl DedicatedExecutor {
     ///
     /// Currently all tasks are added to the tokio executor
     /// immediately and compete for the threadpool's resources.
+    ///
+    /// If the returned `Receiver` is dropped before the task completes
+    /// (e.g. because the client that issued a query disconnected), the
+    /// task is cancelled rather than left running to completion.
+    ///
+    /// The task runs in the current tracing span, so that the spans it
+    /// creates are part of the trace of the request that spawned it.
     pub fn spawn<T>(&self, task: T) -> Receiver<T::Output>
     where
         T: Future + Send + 'static,
         T::Output: Send + 'static,
     {
-        let (tx, rx) = tokio::sync::oneshot::channel();
+        let (mut tx, rx) = tokio::sync::oneshot::channel();
+        let task = task.instrument(Span::current());
 
         let job = Box::pin(async move {
-            let task_output = task.await;
-            if tx.send(task_output).is_err() {
-                warn!("Spawned task output ignored: receiver dropped")
+            let task_output = tokio::select! {
+                task_output = task => Some(task_output),
+                _ = tx.closed() => None,
+            };
+
+            match task_output {
+                Some(task_output) => {
+                    if tx.send(task_output).is_err() {
+                        warn!("Spawned task output ignored: receiver dropped")
+                    }
+                }
+                None => debug!("Spawned task cancelled: receiver dropped"),
             }
         });
 
@@ -285,6 +302,27 @@ mod tests {
         dedicated_task.await.unwrap_err();
     }
 
+    #[tokio::test]
+    async fn dropped_receiver_cancels_task() {
+        let exec = DedicatedExecutor::new("Test DedicatedExecutor", 1);
+
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let (cancelled_tx, cancelled_rx) = tokio::sync::oneshot::channel::<()>();
+        let dedicated_task = exec.spawn(async move {
+            // dropped when the task is cancelled, as it never completes
+            let _cancelled_tx = cancelled_tx;
+            started_tx.send(()).unwrap();
+            futures::future::pending::<()>().await
+        });
+
+        started_rx.await.unwrap();
+        std::mem::drop(dedicated_task);
+
+        // the sender is dropped without sending
+        cancelled_rx.await.unwrap_err();
+        exec.join();
+    }
+
     #[tokio::test]
     async fn executor_shutdown_while_task_running() {
         let barrier = Arc::new(Barrier::new(2));
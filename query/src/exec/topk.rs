@@ -0,0 +1,361 @@
+//! This module contains the [`TopKExec`] operator, which answers queries
+//! that sort their results and only keep the first rows, such as
+//!
+//! ```sql
+//! SELECT host, usage_user FROM cpu ORDER BY usage_user DESC LIMIT 10
+//! ```
+//!
+//! DataFusion plans these as a `GlobalLimitExec` over a `SortExec`, which
+//! buffers and sorts the entire result set to return a handful of rows. A
+//! [`TopKExec`] instead keeps only the first `k` rows seen so far, so its
+//! memory is bounded by `k` and the size of an input batch.
+
+use std::{fmt, sync::Arc};
+
+use arrow::{
+    array::ArrayRef,
+    compute::{cast, concat, lexsort_to_indices, take},
+    datatypes::{Field, Schema, SchemaRef},
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use datafusion::{
+    error::{DataFusionError, Result},
+    physical_plan::{
+        common::SizedRecordBatchStream, expressions::PhysicalSortExpr, limit::GlobalLimitExec,
+        sort::SortExec, DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+    },
+};
+use futures::StreamExt;
+
+use crate::provider::value_type;
+
+/// Returns `plan` with the limits of sorted data computed by a [`TopKExec`]
+/// instead of sorting all the rows
+pub(crate) fn plan_top_k(plan: Arc<dyn ExecutionPlan>) -> Result<Arc<dyn ExecutionPlan>> {
+    let children = plan
+        .children()
+        .into_iter()
+        .map(plan_top_k)
+        .collect::<Result<Vec<_>>>()?;
+    let plan = if children.is_empty() {
+        plan
+    } else {
+        plan.with_new_children(children)?
+    };
+
+    let limit = match plan.as_any().downcast_ref::<GlobalLimitExec>() {
+        Some(limit) => limit,
+        None => return Ok(plan),
+    };
+    match limit.input().as_any().downcast_ref::<SortExec>() {
+        Some(sort) => Ok(Arc::new(TopKExec::new(
+            Arc::clone(sort.input()),
+            sort.expr().to_vec(),
+            limit.limit(),
+        ))),
+        None => Ok(plan),
+    }
+}
+
+/// Outputs the first `k` rows of its input, in the order of `expr`, as a
+/// single partition
+#[derive(Debug)]
+pub(crate) struct TopKExec {
+    input: Arc<dyn ExecutionPlan>,
+    expr: Vec<PhysicalSortExpr>,
+    k: usize,
+}
+
+impl TopKExec {
+    pub fn new(input: Arc<dyn ExecutionPlan>, expr: Vec<PhysicalSortExpr>, k: usize) -> Self {
+        Self { input, expr, k }
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for TopKExec {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![Arc::clone(&self.input)]
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(Self::new(
+                Arc::clone(&children[0]),
+                self.expr.clone(),
+                self.k,
+            ))),
+            _ => Err(DataFusionError::Internal(
+                "TopKExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "TopKExec invalid partition {}",
+                partition
+            )));
+        }
+
+        let mut top_k = TopK::new(self.schema(), &self.expr, self.k);
+        for input_partition in 0..self.input.output_partitioning().partition_count() {
+            let mut stream = self.input.execute(input_partition).await?;
+            while let Some(batch) = stream.next().await {
+                top_k.insert(&batch?)?;
+            }
+        }
+
+        let batches = top_k.finish()?.into_iter().map(Arc::new).collect();
+        Ok(Box::pin(SizedRecordBatchStream::new(
+            self.schema(),
+            batches,
+        )))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                let expr: Vec<String> = self.expr.iter().map(|e| e.to_string()).collect();
+                write!(f, "TopKExec: k={}, expr=[{}]", self.k, expr.join(","))
+            }
+        }
+    }
+}
+
+/// The first `k` rows of the batches inserted so far
+struct TopK<'a> {
+    schema: SchemaRef,
+    /// `schema` with dictionaries replaced by their values, as the rows are
+    /// kept in
+    value_schema: SchemaRef,
+    expr: &'a [PhysicalSortExpr],
+    k: usize,
+    rows: Option<RecordBatch>,
+}
+
+impl<'a> TopK<'a> {
+    fn new(schema: SchemaRef, expr: &'a [PhysicalSortExpr], k: usize) -> Self {
+        let fields = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                Field::new(
+                    field.name(),
+                    value_type(field.data_type()),
+                    field.is_nullable(),
+                )
+            })
+            .collect();
+
+        Self {
+            schema,
+            value_schema: Arc::new(Schema::new(fields)),
+            expr,
+            k,
+            rows: None,
+        }
+    }
+
+    /// Keeps the rows of `batch` that are among the first `k`
+    fn insert(&mut self, batch: &RecordBatch) -> Result<()> {
+        if batch.num_rows() == 0 || self.k == 0 {
+            return Ok(());
+        }
+
+        // Concatenate the rows kept so far with the batch, column by column.
+        // Dictionaries are concatenated as their values as the dictionaries
+        // of the batches differ.
+        let columns = self
+            .value_schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                let column = cast(batch.column(idx), field.data_type())?;
+                Ok(match &self.rows {
+                    Some(rows) => concat(&[rows.column(idx).as_ref(), column.as_ref()])?,
+                    None => column,
+                })
+            })
+            .collect::<Result<Vec<ArrayRef>>>()?;
+        let rows = RecordBatch::try_new(Arc::clone(&self.value_schema), columns)?;
+
+        let sort_columns = self
+            .expr
+            .iter()
+            .map(|expr| expr.evaluate_to_sort_column(&rows))
+            .collect::<Result<Vec<_>>>()?;
+        let indices = lexsort_to_indices(&sort_columns, Some(self.k))?;
+
+        let columns = rows
+            .columns()
+            .iter()
+            .map(|column| take(column.as_ref(), &indices, None))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.rows = Some(RecordBatch::try_new(
+            Arc::clone(&self.value_schema),
+            columns,
+        )?);
+        Ok(())
+    }
+
+    /// Returns the first `k` rows, in order, if there are any
+    fn finish(self) -> Result<Option<RecordBatch>> {
+        let rows = match self.rows {
+            Some(rows) => rows,
+            None => return Ok(None),
+        };
+
+        let columns = rows
+            .columns()
+            .iter()
+            .zip(self.schema.fields())
+            .map(|(column, field)| cast(column, field.data_type()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Some(RecordBatch::try_new(self.schema, columns)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::Executor;
+    use arrow::{
+        array::{DictionaryArray, Float64Array},
+        datatypes::{DataType, Int32Type},
+    };
+    use arrow_util::assert_batches_eq;
+    use datafusion::{
+        logical_plan::{col, LogicalPlanBuilder},
+        physical_plan::{collect, displayable, expressions, memory::MemoryExec},
+    };
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new(
+                "host",
+                DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+                true,
+            ),
+            Field::new("usage", DataType::Float64, true),
+        ]))
+    }
+
+    fn make_batch(hosts: Vec<&str>, usage: Vec<Option<f64>>) -> RecordBatch {
+        RecordBatch::try_new(
+            schema(),
+            vec![
+                Arc::new(hosts.into_iter().collect::<DictionaryArray<Int32Type>>()),
+                Arc::new(Float64Array::from(usage)),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn partitions() -> Vec<Vec<RecordBatch>> {
+        vec![
+            vec![
+                make_batch(vec!["a", "b"], vec![Some(1.0), Some(5.0)]),
+                make_batch(vec!["c"], vec![None]),
+            ],
+            vec![make_batch(
+                vec!["d", "e", "f"],
+                vec![Some(3.0), Some(7.0), Some(2.0)],
+            )],
+        ]
+    }
+
+    #[tokio::test]
+    async fn top_k_of_partitions() {
+        let input = Arc::new(MemoryExec::try_new(&partitions(), schema(), None).unwrap());
+        let expr = vec![PhysicalSortExpr {
+            expr: expressions::col("usage"),
+            options: arrow::compute::SortOptions {
+                descending: true,
+                nulls_first: false,
+            },
+        }];
+        let exec = Arc::new(TopKExec::new(input, expr, 3));
+
+        let plan = displayable(exec.as_ref()).indent().to_string();
+        assert_eq!(
+            plan.lines().next().unwrap(),
+            "TopKExec: k=3, expr=[usage DESC NULLS LAST]"
+        );
+
+        let results = collect(exec).await.unwrap();
+        assert_eq!(results[0].schema(), schema());
+        let expected = vec![
+            "+------+-------+",
+            "| host | usage |",
+            "+------+-------+",
+            "| e    | 7     |",
+            "| b    | 5     |",
+            "| d    | 3     |",
+            "+------+-------+",
+        ];
+        assert_batches_eq!(&expected, &results);
+    }
+
+    #[tokio::test]
+    async fn top_k_of_no_rows() {
+        let input = Arc::new(MemoryExec::try_new(&[vec![]], schema(), None).unwrap());
+        let expr = vec![PhysicalSortExpr {
+            expr: expressions::col("usage"),
+            options: Default::default(),
+        }];
+        let results = collect(Arc::new(TopKExec::new(input, expr, 3)))
+            .await
+            .unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn sort_and_limit_planned_as_top_k() {
+        let plan = LogicalPlanBuilder::scan_memory(partitions(), schema(), None)
+            .unwrap()
+            .sort(vec![col("usage").sort(true, true)])
+            .unwrap()
+            .limit(2)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let ctx = Executor::new(1).new_context();
+        let physical_plan = ctx.prepare_plan(&plan).unwrap();
+        assert!(
+            physical_plan.as_any().is::<TopKExec>(),
+            "{}",
+            displayable(physical_plan.as_ref()).indent()
+        );
+
+        let results = ctx.collect(physical_plan).await.unwrap();
+        let expected = vec![
+            "+------+-------+",
+            "| host | usage |",
+            "+------+-------+",
+            "| c    |       |",
+            "| a    | 1     |",
+            "+------+-------+",
+        ];
+        assert_batches_eq!(&expected, &results);
+    }
+}
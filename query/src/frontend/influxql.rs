@@ -0,0 +1,565 @@
+//! This module contains a frontend for a subset of InfluxQL, to ease the
+//! migration of InfluxDB 1.x users. It plans `SELECT` statements with
+//!
+//! * fields, tags, `*` or the aggregates `count`, `sum`, `mean`, `min`,
+//!   `max`, `first` and `last` of fields,
+//! * `WHERE` comparisons of tags, fields and `time`, with timestamps such
+//!   as `now() - 1h` or `'2021-04-01T00:00:00Z'`,
+//! * `GROUP BY time(<interval>)` and tags, `fill(null|previous|linear|none)`,
+//! * `ORDER BY time [ASC|DESC]` and `LIMIT`
+//!
+//! by translating them into the equivalent SQL. The results are a single
+//! table, rather than a series for every set of tags: tags that are
+//! grouped by are output as columns.
+//!
+//! As in InfluxQL, the windows of `GROUP BY time` without any rows are
+//! filled with nulls unless another `fill` is given. SQL cannot express
+//! this, so the plan of the SQL is wrapped in a GapFill node, which
+//! requires the `WHERE` clause to bound `time` from below. Its upper bound
+//! defaults to `now()`.
+
+use std::sync::Arc;
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use datafusion::{
+    catalog::catalog::CatalogProvider,
+    error::Result as DataFusionResult,
+    logical_plan::{col, LogicalPlan, LogicalPlanBuilder},
+    physical_plan::ExecutionPlan,
+};
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
+
+use crate::{
+    exec::{make_gap_fill, Executor, FillStrategy, GapFillParams},
+    frontend::sql::SqlQueryPlanner,
+    func::window::DATE_BIN_UDF_NAME,
+    group_by::WindowDuration,
+    Database,
+};
+
+pub mod parser;
+
+use parser::{
+    AggregateFunction, ComparisonOp, Condition, FieldExpr, Fill, Literal, SelectStatement,
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error parsing InfluxQL query: {}", source))]
+    Parsing { source: parser::Error },
+
+    #[snafu(display("Error planning InfluxQL query: {}", message))]
+    Unsupported { message: String },
+
+    #[snafu(display("Error planning InfluxQL query as SQL '{}': {}", sql, source))]
+    Planning {
+        sql: String,
+        source: super::sql::Error,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// This struct can create plans for running InfluxQL queries against
+/// databases
+#[derive(Debug, Default)]
+pub struct InfluxQlQueryPlanner {}
+
+impl InfluxQlQueryPlanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Plan an InfluxQL query against the data in `database`, and return a
+    /// DataFusion physical execution plan. The plan can then be
+    /// executed using `executor` in a streaming fashion.
+    pub fn query<D: CatalogProvider + Database + 'static>(
+        &self,
+        database: Arc<D>,
+        query: &str,
+        executor: &Executor,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let Translation { sql, gap_fill } = translate(query, Utc::now().timestamp_nanos())?;
+        let planner = SqlQueryPlanner::new();
+        let plan = match gap_fill {
+            Some(gap_fill) => {
+                planner.query_with_rewrite(database, &sql, &|plan| gap_fill.plan(plan), executor)
+            }
+            None => planner.query(database, &sql, executor),
+        };
+        plan.context(Planning { sql })
+    }
+}
+
+/// An InfluxQL query translated into SQL
+#[derive(Debug, PartialEq)]
+struct Translation {
+    sql: String,
+    /// The gaps to fill in the results of `sql`, if any
+    gap_fill: Option<GapFill>,
+}
+
+/// Fills the windows of a `GROUP BY time` query that have no rows. The
+/// results are ordered and limited after the gaps are filled, so that the
+/// added rows are included.
+#[derive(Debug, PartialEq)]
+struct GapFill {
+    params: GapFillParams,
+    descending: bool,
+    limit: Option<u64>,
+}
+
+impl GapFill {
+    /// Wraps `plan`, the logical plan of the translated SQL, in the plan
+    /// that fills its gaps
+    fn plan(&self, plan: LogicalPlan) -> DataFusionResult<LogicalPlan> {
+        let plan = make_gap_fill(plan, self.params.clone())?;
+        let mut builder = LogicalPlanBuilder::from(&plan);
+        // The gap filled rows are ordered by series and ascending time
+        if self.descending {
+            let sort_exprs = self
+                .params
+                .group_columns
+                .iter()
+                .map(|name| col(name).sort(true, false))
+                .chain(std::iter::once(
+                    col(&self.params.time_column).sort(false, false),
+                ))
+                .collect();
+            builder = builder.sort(sort_exprs)?;
+        }
+        if let Some(limit) = self.limit {
+            builder = builder.limit(limit as usize)?;
+        }
+        builder.build()
+    }
+}
+
+/// Translates the InfluxQL `query` into SQL, where `now()` is `now`
+/// nanoseconds since the epoch
+fn translate(query: &str, now: i64) -> Result<Translation> {
+    let statement = parser::parse_select(query, now).context(Parsing)?;
+    let SelectStatement {
+        fields,
+        measurement,
+        condition,
+        group_by_time,
+        group_by_tags,
+        fill,
+        descending,
+        limit,
+    } = statement;
+
+    let num_aggregates = fields
+        .iter()
+        .filter(|field| matches!(field.expr, FieldExpr::Aggregate { .. }))
+        .count();
+    let aggregate = num_aggregates > 0;
+    ensure!(
+        !aggregate || num_aggregates == fields.len(),
+        Unsupported {
+            message: "mixing aggregate and non-aggregate fields is not supported"
+        }
+    );
+    ensure!(
+        aggregate || group_by_time.is_none(),
+        Unsupported {
+            message: "GROUP BY time requires at least one aggregate function"
+        }
+    );
+    // InfluxQL limits the rows of each series, not of the whole result
+    ensure!(
+        limit.is_none() || group_by_tags.is_empty(),
+        Unsupported {
+            message: "LIMIT is not supported with GROUP BY tags"
+        }
+    );
+
+    let time = quote_ident("time")?;
+    let window =
+        group_by_time.map(|width| format!("{}('{}ns', {})", DATE_BIN_UDF_NAME, width, time));
+
+    let mut projection = vec![];
+    let mut names = vec![];
+    let wildcard = fields
+        .iter()
+        .any(|field| matches!(field.expr, FieldExpr::Wildcard));
+    if !wildcard {
+        match &window {
+            Some(window) => projection.push(format!("{} AS {}", window, time)),
+            None if !aggregate => projection.push(time.clone()),
+            None => {}
+        }
+        for tag in &group_by_tags {
+            let selected = fields
+                .iter()
+                .any(|field| matches!(&field.expr, FieldExpr::Column(column) if column == tag));
+            if !selected {
+                projection.push(quote_ident(tag)?);
+            }
+        }
+    }
+
+    for field in &fields {
+        let (expr, default_name) = match &field.expr {
+            FieldExpr::Wildcard => ("*".to_string(), None),
+            FieldExpr::Column(column) => (quote_ident(column)?, None),
+            FieldExpr::Aggregate { function, column } => {
+                let column = quote_ident(column)?;
+                let expr = match function {
+                    AggregateFunction::Count => format!("count({})", column),
+                    AggregateFunction::Sum => format!("sum({})", column),
+                    AggregateFunction::Mean => format!("avg({})", column),
+                    AggregateFunction::Min => format!("min({})", column),
+                    AggregateFunction::Max => format!("max({})", column),
+                    AggregateFunction::First => {
+                        format!("selector_first_value({}, {})", column, time)
+                    }
+                    AggregateFunction::Last => {
+                        format!("selector_last_value({}, {})", column, time)
+                    }
+                };
+                (expr, Some(function.name()))
+            }
+        };
+
+        match field.alias.as_deref().or(default_name) {
+            // Like InfluxQL, name the outputs of the same function `mean`,
+            // `mean_1`, ...
+            Some(name) => {
+                let mut unique_name = name.to_string();
+                let mut suffix = 0;
+                while names.contains(&unique_name) {
+                    suffix += 1;
+                    unique_name = format!("{}_{}", name, suffix);
+                }
+                projection.push(format!("{} AS {}", expr, quote_ident(&unique_name)?));
+                names.push(unique_name);
+            }
+            None => projection.push(expr),
+        }
+    }
+
+    let mut sql = format!(
+        "SELECT {} FROM {}",
+        projection.join(", "),
+        quote_ident(&measurement)?
+    );
+
+    if let Some(condition) = &condition {
+        sql.push_str(" WHERE ");
+        push_condition(&mut sql, condition)?;
+    }
+
+    let tags = group_by_tags
+        .iter()
+        .map(|tag| quote_ident(tag))
+        .collect::<Result<Vec<_>>>()?;
+    if aggregate {
+        let group_by: Vec<_> = window.iter().cloned().chain(tags.iter().cloned()).collect();
+        if !group_by.is_empty() {
+            sql.push_str(&format!(" GROUP BY {}", group_by.join(", ")));
+        }
+    }
+
+    let gap_fill = match (group_by_time, fill_strategy(fill)) {
+        (Some(width), Some(fill)) => {
+            let (start, stop) = condition.as_ref().map(time_range).unwrap_or((None, None));
+            let start = start.context(Unsupported {
+                message: "filling the windows of GROUP BY time requires a lower bound on time \
+                          in the WHERE clause, or fill(none)",
+            })?;
+            Some(GapFill {
+                params: GapFillParams {
+                    time_column: "time".to_string(),
+                    group_columns: group_by_tags.clone(),
+                    stride: WindowDuration::from_nanoseconds(width),
+                    start,
+                    stop: stop.unwrap_or(now),
+                    fill,
+                },
+                descending,
+                limit,
+            })
+        }
+        _ => None,
+    };
+    if gap_fill.is_some() {
+        return Ok(Translation { sql, gap_fill });
+    }
+
+    let mut order_by = tags;
+    if !aggregate || window.is_some() {
+        order_by.push(if descending {
+            format!("{} DESC", time)
+        } else {
+            time
+        });
+    }
+    if !order_by.is_empty() {
+        sql.push_str(&format!(" ORDER BY {}", order_by.join(", ")));
+    }
+
+    if let Some(limit) = limit {
+        sql.push_str(&format!(" LIMIT {}", limit));
+    }
+
+    Ok(Translation {
+        sql,
+        gap_fill: None,
+    })
+}
+
+/// Returns how the windows of a `GROUP BY time` query are filled, or `None`
+/// if windows without rows are left out
+fn fill_strategy(fill: Fill) -> Option<FillStrategy> {
+    match fill {
+        Fill::None => None,
+        Fill::Null => Some(FillStrategy::Null),
+        Fill::Previous => Some(FillStrategy::Previous),
+        Fill::Linear => Some(FillStrategy::Linear),
+    }
+}
+
+/// Returns the start (inclusive) and stop (exclusive) of the times selected
+/// by `condition`, in nanoseconds since the epoch, where it bounds them
+fn time_range(condition: &Condition) -> (Option<i64>, Option<i64>) {
+    match condition {
+        Condition::And(left, right) => {
+            let (left_start, left_stop) = time_range(left);
+            let (right_start, right_stop) = time_range(right);
+            let start = match (left_start, right_start) {
+                (Some(left), Some(right)) => Some(left.max(right)),
+                (start, None) | (None, start) => start,
+            };
+            let stop = match (left_stop, right_stop) {
+                (Some(left), Some(right)) => Some(left.min(right)),
+                (stop, None) | (None, stop) => stop,
+            };
+            (start, stop)
+        }
+        Condition::Or(left, right) => {
+            let (left_start, left_stop) = time_range(left);
+            let (right_start, right_stop) = time_range(right);
+            let start = match (left_start, right_start) {
+                (Some(left), Some(right)) => Some(left.min(right)),
+                _ => None,
+            };
+            let stop = match (left_stop, right_stop) {
+                (Some(left), Some(right)) => Some(left.max(right)),
+                _ => None,
+            };
+            (start, stop)
+        }
+        Condition::Comparison { .. } => (None, None),
+        Condition::Time { op, timestamp } => {
+            let timestamp = *timestamp;
+            match op {
+                ComparisonOp::Eq => (Some(timestamp), Some(timestamp.saturating_add(1))),
+                ComparisonOp::NotEq => (None, None),
+                ComparisonOp::Lt => (None, Some(timestamp)),
+                ComparisonOp::LtEq => (None, Some(timestamp.saturating_add(1))),
+                ComparisonOp::Gt => (Some(timestamp.saturating_add(1)), None),
+                ComparisonOp::GtEq => (Some(timestamp), None),
+            }
+        }
+    }
+}
+
+/// Appends the SQL of `condition` to `sql`
+fn push_condition(sql: &mut String, condition: &Condition) -> Result<()> {
+    match condition {
+        Condition::And(left, right) | Condition::Or(left, right) => {
+            let op = match condition {
+                Condition::And(..) => "AND",
+                _ => "OR",
+            };
+            sql.push('(');
+            push_condition(sql, left)?;
+            sql.push_str(&format!(" {} ", op));
+            push_condition(sql, right)?;
+            sql.push(')');
+        }
+        Condition::Comparison { column, op, value } => {
+            let value = match value {
+                Literal::String(value) => format!("'{}'", value.replace('\'', "''")),
+                Literal::Integer(value) => value.to_string(),
+                Literal::Float(value) => format!("{:?}", value),
+                Literal::Boolean(value) => value.to_string(),
+            };
+            sql.push_str(&format!(
+                "{} {} {}",
+                quote_ident(column)?,
+                comparison_op(*op),
+                value
+            ));
+        }
+        Condition::Time { op, timestamp } => {
+            let timestamp = Utc
+                .timestamp_nanos(*timestamp)
+                .to_rfc3339_opts(SecondsFormat::Nanos, true);
+            sql.push_str(&format!(
+                "{} {} to_timestamp('{}')",
+                quote_ident("time")?,
+                comparison_op(*op),
+                timestamp
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn comparison_op(op: ComparisonOp) -> &'static str {
+    match op {
+        ComparisonOp::Eq => "=",
+        ComparisonOp::NotEq => "!=",
+        ComparisonOp::Lt => "<",
+        ComparisonOp::LtEq => "<=",
+        ComparisonOp::Gt => ">",
+        ComparisonOp::GtEq => ">=",
+    }
+}
+
+/// Returns `name` as a quoted SQL identifier
+fn quote_ident(name: &str) -> Result<String> {
+    ensure!(
+        !name.contains('"'),
+        Unsupported {
+            message: format!("identifiers containing '\"' are not supported: {}", name)
+        }
+    );
+    Ok(format!("\"{}\"", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_617_286_224_000_000_000; // 2021-04-01T14:10:24Z
+
+    #[test]
+    fn raw_query_to_sql() {
+        let cases = vec![
+            ("SELECT * FROM cpu", r#"SELECT * FROM "cpu" ORDER BY "time""#),
+            (
+                "SELECT usage_user AS usage, host FROM cpu WHERE host = 'a''s' OR usage_user > 1.0 \
+                 ORDER BY time DESC LIMIT 5",
+                r#"SELECT "time", "usage_user" AS "usage", "host" FROM "cpu" WHERE ("host" = 'a''s' OR "usage_user" > 1.0) ORDER BY "time" DESC LIMIT 5"#,
+            ),
+            (
+                "SELECT usage_user FROM cpu WHERE time >= now() - 1h AND time < 250 GROUP BY host",
+                r#"SELECT "time", "host", "usage_user" FROM "cpu" WHERE ("time" >= to_timestamp('2021-04-01T13:10:24.000000000Z') AND "time" < to_timestamp('1970-01-01T00:00:00.000000250Z')) ORDER BY "host", "time""#,
+            ),
+        ];
+
+        for (influxql, expected) in cases {
+            let expected = Translation {
+                sql: expected.to_string(),
+                gap_fill: None,
+            };
+            assert_eq!(translate(influxql, NOW).unwrap(), expected, "{}", influxql);
+        }
+    }
+
+    #[test]
+    fn aggregate_query_to_sql() {
+        let cases = vec![
+            (
+                "SELECT count(usage_user) FROM cpu",
+                r#"SELECT count("usage_user") AS "count" FROM "cpu""#,
+            ),
+            (
+                "SELECT mean(usage_user), mean(usage_system), last(usage_user) AS latest FROM cpu \
+                 GROUP BY time(1m), host fill(none)",
+                r#"SELECT date_bin('60000000000ns', "time") AS "time", "host", avg("usage_user") AS "mean", avg("usage_system") AS "mean_1", selector_last_value("usage_user", "time") AS "latest" FROM "cpu" GROUP BY date_bin('60000000000ns', "time"), "host" ORDER BY "host", "time""#,
+            ),
+            (
+                "SELECT max(usage_user) FROM cpu GROUP BY host",
+                r#"SELECT "host", max("usage_user") AS "max" FROM "cpu" GROUP BY "host" ORDER BY "host""#,
+            ),
+        ];
+
+        for (influxql, expected) in cases {
+            let expected = Translation {
+                sql: expected.to_string(),
+                gap_fill: None,
+            };
+            assert_eq!(translate(influxql, NOW).unwrap(), expected, "{}", influxql);
+        }
+    }
+
+    #[test]
+    fn gap_filled_query_to_sql() {
+        let translation = translate(
+            "SELECT mean(usage_user) FROM cpu WHERE time >= now() - 1h \
+             GROUP BY time(1m), host ORDER BY time DESC",
+            NOW,
+        )
+        .unwrap();
+        let expected = Translation {
+            sql: r#"SELECT date_bin('60000000000ns', "time") AS "time", "host", avg("usage_user") AS "mean" FROM "cpu" WHERE "time" >= to_timestamp('2021-04-01T13:10:24.000000000Z') GROUP BY date_bin('60000000000ns', "time"), "host""#.to_string(),
+            gap_fill: Some(GapFill {
+                params: GapFillParams {
+                    time_column: "time".to_string(),
+                    group_columns: vec!["host".to_string()],
+                    stride: WindowDuration::from_nanoseconds(60_000_000_000),
+                    start: NOW - 3_600_000_000_000,
+                    stop: NOW,
+                    fill: FillStrategy::Null,
+                },
+                descending: true,
+                limit: None,
+            }),
+        };
+        assert_eq!(translation, expected);
+
+        let translation = translate(
+            "SELECT max(usage_user) FROM cpu WHERE (time > 100 AND time <= 200 OR time = 150) \
+             AND host = 'a' GROUP BY time(10ns) fill(linear) LIMIT 5",
+            NOW,
+        )
+        .unwrap();
+        let gap_fill = translation.gap_fill.unwrap();
+        assert_eq!(gap_fill.params.group_columns, Vec::<String>::new());
+        assert_eq!(gap_fill.params.start, 101);
+        assert_eq!(gap_fill.params.stop, 201);
+        assert_eq!(gap_fill.params.fill, FillStrategy::Linear);
+        assert_eq!(gap_fill.limit, Some(5));
+    }
+
+    #[test]
+    fn unsupported_queries() {
+        let cases = vec![
+            (
+                "SELECT mean(usage_user), host FROM cpu",
+                "Error planning InfluxQL query: mixing aggregate and non-aggregate fields is not supported",
+            ),
+            (
+                "SELECT usage_user FROM cpu GROUP BY time(1m)",
+                "Error planning InfluxQL query: GROUP BY time requires at least one aggregate function",
+            ),
+            (
+                "SELECT usage_user FROM cpu GROUP BY host LIMIT 1",
+                "Error planning InfluxQL query: LIMIT is not supported with GROUP BY tags",
+            ),
+            (
+                "SELECT mean(usage_user) FROM cpu WHERE time < now() GROUP BY time(1m)",
+                "Error planning InfluxQL query: filling the windows of GROUP BY time requires a \
+                 lower bound on time in the WHERE clause, or fill(none)",
+            ),
+            (
+                "SELECT usage_user FROM cpu WHERE",
+                "Error parsing InfluxQL query: Syntax error at position 32: expected an identifier",
+            ),
+        ];
+
+        for (influxql, expected) in cases {
+            assert_eq!(
+                translate(influxql, NOW).unwrap_err().to_string(),
+                expected,
+                "{}",
+                influxql
+            );
+        }
+    }
+}
@@ -0,0 +1,949 @@
+//! A parser for the subset of InfluxQL `SELECT` statements that IOx can
+//! answer, such as
+//!
+//! ```text
+//! SELECT mean(usage_user) FROM cpu WHERE time > now() - 1h GROUP BY time(1m), host
+//! ```
+//!
+//! Keywords and function names are case insensitive. Timestamps in the
+//! `WHERE` clause, including `now()`, are resolved to nanoseconds since
+//! the epoch while parsing.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use snafu::Snafu;
+
+#[derive(Debug, Snafu, PartialEq)]
+pub enum Error {
+    #[snafu(display("Syntax error at position {}: {}", position, message))]
+    Syntax { position: usize, message: String },
+
+    #[snafu(display("{} is not supported", feature))]
+    Unsupported { feature: String },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A parsed InfluxQL `SELECT` statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectStatement {
+    pub fields: Vec<Field>,
+    pub measurement: String,
+    pub condition: Option<Condition>,
+    /// The width of the `GROUP BY time(..)` windows, in nanoseconds
+    pub group_by_time: Option<i64>,
+    pub group_by_tags: Vec<String>,
+    /// How the `GROUP BY time(..)` windows without any rows are filled
+    pub fill: Fill,
+    /// True if the results are ordered by descending time
+    pub descending: bool,
+    pub limit: Option<u64>,
+}
+
+/// An expression in the field list of a `SELECT`, and its alias
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub expr: FieldExpr,
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldExpr {
+    /// `*`, all the columns of the measurement
+    Wildcard,
+    /// The values of a tag or field
+    Column(String),
+    /// An aggregate of the values of a field
+    Aggregate {
+        function: AggregateFunction,
+        column: String,
+    },
+}
+
+/// The InfluxQL aggregate and selector functions that are supported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Mean,
+    Min,
+    Max,
+    First,
+    Last,
+}
+
+impl AggregateFunction {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "count" => Self::Count,
+            "sum" => Self::Sum,
+            "mean" => Self::Mean,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "first" => Self::First,
+            "last" => Self::Last,
+            _ => return None,
+        })
+    }
+
+    /// The InfluxQL name of the function, which is also the default name of
+    /// its output column
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Count => "count",
+            Self::Sum => "sum",
+            Self::Mean => "mean",
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::First => "first",
+            Self::Last => "last",
+        }
+    }
+}
+
+/// The options of the `fill(..)` clause that are supported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fill {
+    /// Leave windows without rows out of the results
+    None,
+    /// Output nulls for windows without rows, the default
+    Null,
+    /// Output the values of the previous window
+    Previous,
+    /// Linearly interpolate between the windows before and after
+    Linear,
+}
+
+impl Default for Fill {
+    fn default() -> Self {
+        Self::Null
+    }
+}
+
+/// The condition of a `WHERE` clause
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    /// A comparison of a tag or field with a literal
+    Comparison {
+        column: String,
+        op: ComparisonOp,
+        value: Literal,
+    },
+    /// A comparison of the `time` column with a timestamp, in nanoseconds
+    /// since the epoch
+    Time {
+        op: ComparisonOp,
+        timestamp: i64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+}
+
+/// Parses `query`, a single InfluxQL `SELECT` statement. `now` is the
+/// time `now()` refers to, in nanoseconds since the epoch
+pub fn parse_select(query: &str, now: i64) -> Result<SelectStatement> {
+    let tokens = tokenize(query)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        end: query.len(),
+        now,
+    };
+    parser.select_statement()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// A keyword or unquoted identifier
+    Word(String),
+    QuotedIdent(String),
+    String(String),
+    Integer(i64),
+    Float(f64),
+    /// A duration literal such as `10m`, in nanoseconds
+    Duration(i64),
+    Comma,
+    Dot,
+    LParen,
+    RParen,
+    Star,
+    Plus,
+    Minus,
+    Semicolon,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    RegexMatch,
+    RegexNotMatch,
+    Regex(String),
+}
+
+/// Splits `query` into tokens and their byte positions
+fn tokenize(query: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens = vec![];
+    let mut chars = query.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let token = match c {
+            '\'' | '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, escaped)) => value.push(escaped),
+                            None => break,
+                        },
+                        Some((_, end)) if end == c => break,
+                        Some((_, other)) => value.push(other),
+                        None => {
+                            return Syntax {
+                                position,
+                                message: "unterminated quoted string",
+                            }
+                            .fail()
+                        }
+                    }
+                }
+                if c == '"' {
+                    Token::QuotedIdent(value)
+                } else {
+                    Token::String(value)
+                }
+            }
+            '/' => {
+                chars.next();
+                let mut pattern = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) if chars.peek().map(|&(_, c)| c) == Some('/') => {
+                            pattern.push('/');
+                            chars.next();
+                        }
+                        Some((_, '/')) => break,
+                        Some((_, other)) => pattern.push(other),
+                        None => {
+                            return Syntax {
+                                position,
+                                message: "unterminated regular expression",
+                            }
+                            .fail()
+                        }
+                    }
+                }
+                Token::Regex(pattern)
+            }
+            c if c.is_ascii_digit() => {
+                let mut number = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_ascii_digit() || c == '.' {
+                        number.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let mut unit = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_alphabetic() {
+                        unit.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                number_token(position, &number, &unit)?
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut word = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        word.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                Token::Word(word)
+            }
+            _ => {
+                chars.next();
+                let next = chars.peek().map(|&(_, c)| c);
+                let (token, two_chars) = match (c, next) {
+                    ('=', Some('~')) => (Token::RegexMatch, true),
+                    ('!', Some('~')) => (Token::RegexNotMatch, true),
+                    ('!', Some('=')) => (Token::NotEq, true),
+                    ('<', Some('>')) => (Token::NotEq, true),
+                    ('<', Some('=')) => (Token::LtEq, true),
+                    ('>', Some('=')) => (Token::GtEq, true),
+                    ('=', _) => (Token::Eq, false),
+                    ('<', _) => (Token::Lt, false),
+                    ('>', _) => (Token::Gt, false),
+                    (',', _) => (Token::Comma, false),
+                    ('.', _) => (Token::Dot, false),
+                    ('(', _) => (Token::LParen, false),
+                    (')', _) => (Token::RParen, false),
+                    ('*', _) => (Token::Star, false),
+                    ('+', _) => (Token::Plus, false),
+                    ('-', _) => (Token::Minus, false),
+                    (';', _) => (Token::Semicolon, false),
+                    _ => {
+                        return Syntax {
+                            position,
+                            message: format!("unexpected character '{}'", c),
+                        }
+                        .fail()
+                    }
+                };
+                if two_chars {
+                    chars.next();
+                }
+                token
+            }
+        };
+        tokens.push((position, token));
+    }
+
+    Ok(tokens)
+}
+
+/// Returns the token of a number literal, which is a duration if it is
+/// followed by a unit
+fn number_token(position: usize, number: &str, unit: &str) -> Result<Token> {
+    let invalid = || Error::Syntax {
+        position,
+        message: format!("invalid number '{}{}'", number, unit),
+    };
+
+    if unit.is_empty() {
+        return if number.contains('.') {
+            number.parse().map(Token::Float).map_err(|_| invalid())
+        } else {
+            number.parse().map(Token::Integer).map_err(|_| invalid())
+        };
+    }
+
+    let nanos_per_unit: i64 = match unit {
+        "ns" => 1,
+        "u" | "µ" => 1_000,
+        "ms" => 1_000_000,
+        "s" => 1_000_000_000,
+        "m" => 60 * 1_000_000_000,
+        "h" => 60 * 60 * 1_000_000_000,
+        "d" => 24 * 60 * 60 * 1_000_000_000,
+        "w" => 7 * 24 * 60 * 60 * 1_000_000_000,
+        _ => {
+            return Syntax {
+                position,
+                message: format!("invalid duration unit '{}'", unit),
+            }
+            .fail()
+        }
+    };
+    number
+        .parse::<i64>()
+        .ok()
+        .and_then(|value| value.checked_mul(nanos_per_unit))
+        .map(Token::Duration)
+        .ok_or_else(invalid)
+}
+
+/// Parses a timestamp string literal, such as `'2021-04-01T14:10:24Z'`
+/// or `'2021-04-01'`, to nanoseconds since the epoch. Timestamps without
+/// an offset are in UTC
+fn parse_timestamp(s: &str) -> Option<i64> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(s) {
+        return Some(timestamp.timestamp_nanos());
+    }
+    for format in &["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(timestamp) = NaiveDateTime::parse_from_str(s, format) {
+            return Some(timestamp.timestamp_nanos());
+        }
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .map(|date| date.and_hms(0, 0, 0).timestamp_nanos())
+}
+
+/// The keywords of the supported statements
+const KEYWORDS: &[&str] = &[
+    "AND", "AS", "ASC", "BY", "DESC", "FILL", "FROM", "GROUP", "LIMIT", "OFFSET", "OR", "ORDER",
+    "SELECT", "SLIMIT", "SOFFSET", "TZ", "WHERE",
+];
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    /// The length of the query, reported as the position of its end
+    end: usize,
+    now: i64,
+}
+
+impl Parser {
+    fn select_statement(&mut self) -> Result<SelectStatement> {
+        self.expect_keyword("SELECT")?;
+        let fields = self.fields()?;
+        self.expect_keyword("FROM")?;
+        let measurement = self.measurement()?;
+
+        let condition = if self.eat_keyword("WHERE") {
+            Some(self.condition()?)
+        } else {
+            None
+        };
+
+        let mut group_by_time = None;
+        let mut group_by_tags = vec![];
+        let mut fill = Fill::default();
+        if self.eat_keyword("GROUP") {
+            self.expect_keyword("BY")?;
+            loop {
+                if self.peek_keyword("TIME") && self.peek_nth(1) == Some(&Token::LParen) {
+                    self.pos += 2;
+                    let position = self.position();
+                    match self.next() {
+                        Some(Token::Duration(width)) if width > 0 => group_by_time = Some(width),
+                        _ => return self.syntax_error(position, "expected a duration"),
+                    }
+                    if self.eat(&Token::Comma) {
+                        return Unsupported {
+                            feature: "an offset for GROUP BY time",
+                        }
+                        .fail();
+                    }
+                    self.expect(&Token::RParen, "')'")?;
+                } else if self.eat(&Token::Star) {
+                    return Unsupported {
+                        feature: "GROUP BY *",
+                    }
+                    .fail();
+                } else {
+                    group_by_tags.push(self.identifier()?);
+                }
+                if !self.eat(&Token::Comma) {
+                    break;
+                }
+            }
+
+            if self.eat_keyword("FILL") {
+                self.expect(&Token::LParen, "'('")?;
+                let position = self.position();
+                let option = match self.next() {
+                    Some(Token::Word(option)) => option,
+                    Some(Token::Integer(value)) => value.to_string(),
+                    Some(Token::Float(value)) => value.to_string(),
+                    _ => return self.syntax_error(position, "expected a fill option"),
+                };
+                fill = match option.to_ascii_lowercase().as_str() {
+                    "none" => Fill::None,
+                    "null" => Fill::Null,
+                    "previous" => Fill::Previous,
+                    "linear" => Fill::Linear,
+                    _ => {
+                        return Unsupported {
+                            feature: format!("fill({})", option),
+                        }
+                        .fail()
+                    }
+                };
+                self.expect(&Token::RParen, "')'")?;
+            }
+        }
+
+        let mut descending = false;
+        if self.eat_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            self.expect_keyword("TIME")?;
+            if self.eat_keyword("DESC") {
+                descending = true;
+            } else {
+                self.eat_keyword("ASC");
+            }
+        }
+
+        let limit = if self.eat_keyword("LIMIT") {
+            let position = self.position();
+            match self.next() {
+                Some(Token::Integer(limit)) if limit >= 0 => Some(limit as u64),
+                _ => return self.syntax_error(position, "expected a number of rows"),
+            }
+        } else {
+            None
+        };
+
+        for feature in &["OFFSET", "SLIMIT", "SOFFSET", "TZ"] {
+            if self.peek_keyword(feature) {
+                return Unsupported { feature: *feature }.fail();
+            }
+        }
+
+        self.eat(&Token::Semicolon);
+        if self.pos < self.tokens.len() {
+            let position = self.position();
+            return self.syntax_error(position, "expected the end of the statement");
+        }
+
+        Ok(SelectStatement {
+            fields,
+            measurement,
+            condition,
+            group_by_time,
+            group_by_tags,
+            fill,
+            descending,
+            limit,
+        })
+    }
+
+    fn fields(&mut self) -> Result<Vec<Field>> {
+        let mut fields = vec![];
+        loop {
+            let expr = if self.eat(&Token::Star) {
+                FieldExpr::Wildcard
+            } else if self.peek_nth(1) == Some(&Token::LParen) {
+                let name = self.identifier()?;
+                let function =
+                    AggregateFunction::from_name(&name).ok_or_else(|| Error::Unsupported {
+                        feature: format!("the function '{}'", name),
+                    })?;
+                self.expect(&Token::LParen, "'('")?;
+                if self.eat(&Token::Star) {
+                    return Unsupported {
+                        feature: format!("{}(*)", function.name()),
+                    }
+                    .fail();
+                }
+                let column = self.identifier()?;
+                self.expect(&Token::RParen, "')'")?;
+                FieldExpr::Aggregate { function, column }
+            } else {
+                FieldExpr::Column(self.identifier()?)
+            };
+
+            let alias = if self.eat_keyword("AS") {
+                Some(self.identifier()?)
+            } else {
+                None
+            };
+            fields.push(Field { expr, alias });
+
+            if !self.eat(&Token::Comma) {
+                return Ok(fields);
+            }
+        }
+    }
+
+    /// Parses a measurement name, which may be qualified with a database
+    /// and retention policy that are ignored
+    fn measurement(&mut self) -> Result<String> {
+        let mut measurement = self.identifier()?;
+        for _ in 0..2 {
+            if !self.eat(&Token::Dot) {
+                break;
+            }
+            measurement = self.identifier()?;
+        }
+        Ok(measurement)
+    }
+
+    fn condition(&mut self) -> Result<Condition> {
+        let mut condition = self.and_condition()?;
+        while self.eat_keyword("OR") {
+            let right = self.and_condition()?;
+            condition = Condition::Or(Box::new(condition), Box::new(right));
+        }
+        Ok(condition)
+    }
+
+    fn and_condition(&mut self) -> Result<Condition> {
+        let mut condition = self.comparison()?;
+        while self.eat_keyword("AND") {
+            let right = self.comparison()?;
+            condition = Condition::And(Box::new(condition), Box::new(right));
+        }
+        Ok(condition)
+    }
+
+    fn comparison(&mut self) -> Result<Condition> {
+        if self.eat(&Token::LParen) {
+            let condition = self.condition()?;
+            self.expect(&Token::RParen, "')'")?;
+            return Ok(condition);
+        }
+
+        let column = self.identifier()?;
+        let position = self.position();
+        let op = match self.next() {
+            Some(Token::Eq) => ComparisonOp::Eq,
+            Some(Token::NotEq) => ComparisonOp::NotEq,
+            Some(Token::Lt) => ComparisonOp::Lt,
+            Some(Token::LtEq) => ComparisonOp::LtEq,
+            Some(Token::Gt) => ComparisonOp::Gt,
+            Some(Token::GtEq) => ComparisonOp::GtEq,
+            Some(Token::RegexMatch) | Some(Token::RegexNotMatch) => {
+                return Unsupported {
+                    feature: "regular expression matching",
+                }
+                .fail()
+            }
+            _ => return self.syntax_error(position, "expected a comparison operator"),
+        };
+
+        if column.eq_ignore_ascii_case("time") {
+            let timestamp = self.time_expr()?;
+            return Ok(Condition::Time { op, timestamp });
+        }
+
+        let position = self.position();
+        let negate = self.eat(&Token::Minus);
+        let value = match self.next() {
+            Some(Token::String(value)) if !negate => Literal::String(value),
+            Some(Token::Integer(value)) => Literal::Integer(if negate { -value } else { value }),
+            Some(Token::Float(value)) => Literal::Float(if negate { -value } else { value }),
+            Some(Token::Word(word)) if !negate && word.eq_ignore_ascii_case("true") => {
+                Literal::Boolean(true)
+            }
+            Some(Token::Word(word)) if !negate && word.eq_ignore_ascii_case("false") => {
+                Literal::Boolean(false)
+            }
+            _ => return self.syntax_error(position, "expected a literal"),
+        };
+        Ok(Condition::Comparison { column, op, value })
+    }
+
+    /// Parses a sum of timestamps and durations, such as `now() - 1h`
+    fn time_expr(&mut self) -> Result<i64> {
+        let mut timestamp = self.time_term()?;
+        loop {
+            let negate = if self.eat(&Token::Plus) {
+                false
+            } else if self.eat(&Token::Minus) {
+                true
+            } else {
+                return Ok(timestamp);
+            };
+
+            let position = self.position();
+            let term = self.time_term()?;
+            let sum = if negate {
+                timestamp.checked_sub(term)
+            } else {
+                timestamp.checked_add(term)
+            };
+            timestamp = match sum {
+                Some(sum) => sum,
+                None => return self.syntax_error(position, "time is out of range"),
+            };
+        }
+    }
+
+    fn time_term(&mut self) -> Result<i64> {
+        let position = self.position();
+        match self.next() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("now") => {
+                self.expect(&Token::LParen, "'('")?;
+                self.expect(&Token::RParen, "')'")?;
+                Ok(self.now)
+            }
+            Some(Token::String(value)) => match parse_timestamp(&value) {
+                Some(timestamp) => Ok(timestamp),
+                None => self.syntax_error(position, format!("invalid timestamp '{}'", value)),
+            },
+            Some(Token::Integer(value)) | Some(Token::Duration(value)) => Ok(value),
+            _ => self.syntax_error(position, "expected a time"),
+        }
+    }
+
+    /// Parses an unquoted or quoted identifier. Keywords must be quoted to
+    /// be used as identifiers
+    fn identifier(&mut self) -> Result<String> {
+        let position = self.position();
+        match self.next() {
+            Some(Token::Word(word))
+                if !KEYWORDS
+                    .iter()
+                    .any(|keyword| word.eq_ignore_ascii_case(keyword)) =>
+            {
+                Ok(word)
+            }
+            Some(Token::QuotedIdent(ident)) => Ok(ident),
+            _ => self.syntax_error(position, "expected an identifier"),
+        }
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n).map(|(_, token)| token)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek_nth(0), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(_, token)| token.clone());
+        self.pos += 1;
+        token
+    }
+
+    /// The position of the next token in the query
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|(position, _)| *position)
+            .unwrap_or(self.end)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.peek_nth(0) == Some(token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &Token, description: &str) -> Result<()> {
+        let position = self.position();
+        if self.eat(token) {
+            Ok(())
+        } else {
+            self.syntax_error(position, format!("expected {}", description))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        let position = self.position();
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            self.syntax_error(position, format!("expected {}", keyword))
+        }
+    }
+
+    fn syntax_error<T>(&self, position: usize, message: impl Into<String>) -> Result<T> {
+        Syntax {
+            position,
+            message: message.into(),
+        }
+        .fail()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_617_286_224_000_000_000; // 2021-04-01T14:10:24Z
+    const MINUTE: i64 = 60_000_000_000;
+
+    fn parse(query: &str) -> SelectStatement {
+        parse_select(query, NOW).unwrap()
+    }
+
+    #[test]
+    fn parse_raw_select() {
+        let statement = parse(r#"select usage_user AS "user", host FROM "telegraf"."autogen".cpu"#);
+        assert_eq!(
+            statement,
+            SelectStatement {
+                fields: vec![
+                    Field {
+                        expr: FieldExpr::Column("usage_user".into()),
+                        alias: Some("user".into()),
+                    },
+                    Field {
+                        expr: FieldExpr::Column("host".into()),
+                        alias: None,
+                    },
+                ],
+                measurement: "cpu".into(),
+                condition: None,
+                group_by_time: None,
+                group_by_tags: vec![],
+                fill: Fill::Null,
+                descending: false,
+                limit: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_aggregate_select() {
+        let statement = parse(
+            "SELECT MEAN(usage_user), max(usage_user) FROM cpu \
+             WHERE time >= now() - 1h GROUP BY time(10m), host fill(none) \
+             ORDER BY time DESC LIMIT 10;",
+        );
+        assert_eq!(
+            statement.fields,
+            vec![
+                Field {
+                    expr: FieldExpr::Aggregate {
+                        function: AggregateFunction::Mean,
+                        column: "usage_user".into()
+                    },
+                    alias: None,
+                },
+                Field {
+                    expr: FieldExpr::Aggregate {
+                        function: AggregateFunction::Max,
+                        column: "usage_user".into()
+                    },
+                    alias: None,
+                },
+            ]
+        );
+        assert_eq!(
+            statement.condition,
+            Some(Condition::Time {
+                op: ComparisonOp::GtEq,
+                timestamp: NOW - 60 * MINUTE
+            })
+        );
+        assert_eq!(statement.group_by_time, Some(10 * MINUTE));
+        assert_eq!(statement.group_by_tags, vec!["host".to_string()]);
+        assert_eq!(statement.fill, Fill::None);
+        assert!(statement.descending);
+        assert_eq!(statement.limit, Some(10));
+    }
+
+    #[test]
+    fn parse_fill() {
+        let cases = vec![
+            ("", Fill::Null),
+            (" fill(NULL)", Fill::Null),
+            (" fill(previous)", Fill::Previous),
+            (" fill(linear)", Fill::Linear),
+            (" fill(none)", Fill::None),
+        ];
+
+        for (clause, expected) in cases {
+            let query = format!("SELECT mean(a) FROM cpu GROUP BY time(1m){}", clause);
+            assert_eq!(parse(&query).fill, expected, "query: {}", query);
+        }
+    }
+
+    #[test]
+    fn parse_conditions() {
+        let statement = parse(
+            "SELECT * FROM cpu WHERE (host = 'a' OR host <> 'b\\'c') AND usage > -1.5 \
+             AND time < '2021-04-01T14:10:24Z' + 1m AND time > '2021-04-01'",
+        );
+
+        let comparison = |column: &str, op, value| Condition::Comparison {
+            column: column.into(),
+            op,
+            value,
+        };
+        let and = |left, right| Condition::And(Box::new(left), Box::new(right));
+        let expected = and(
+            and(
+                and(
+                    Condition::Or(
+                        Box::new(comparison(
+                            "host",
+                            ComparisonOp::Eq,
+                            Literal::String("a".into()),
+                        )),
+                        Box::new(comparison(
+                            "host",
+                            ComparisonOp::NotEq,
+                            Literal::String("b'c".into()),
+                        )),
+                    ),
+                    comparison("usage", ComparisonOp::Gt, Literal::Float(-1.5)),
+                ),
+                Condition::Time {
+                    op: ComparisonOp::Lt,
+                    timestamp: NOW + MINUTE,
+                },
+            ),
+            Condition::Time {
+                op: ComparisonOp::Gt,
+                timestamp: 1_617_235_200_000_000_000,
+            },
+        );
+        assert_eq!(statement.condition, Some(expected));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let cases = vec![
+            (
+                "SELECT FROM cpu",
+                "Syntax error at position 7: expected an identifier",
+            ),
+            (
+                "SELECT a FROM cpu WHERE",
+                "Syntax error at position 23: expected an identifier",
+            ),
+            (
+                "SELECT a FROM cpu LIMIT 1 foo",
+                "Syntax error at position 26: expected the end of the statement",
+            ),
+            (
+                "SELECT a FROM cpu WHERE time > 'yesterday'",
+                "Syntax error at position 31: invalid timestamp 'yesterday'",
+            ),
+            (
+                "SELECT a FROM cpu GROUP BY time(1y)",
+                "Syntax error at position 32: invalid duration unit 'y'",
+            ),
+            (
+                "SELECT a FROM 'cpu",
+                "Syntax error at position 14: unterminated quoted string",
+            ),
+            (
+                "SELECT percentile(a, 90) FROM cpu",
+                "the function 'percentile' is not supported",
+            ),
+            ("SELECT count(*) FROM cpu", "count(*) is not supported"),
+            (
+                "SELECT a FROM cpu WHERE host =~ /a/",
+                "regular expression matching is not supported",
+            ),
+            (
+                "SELECT a FROM cpu GROUP BY time(1m) fill(0)",
+                "fill(0) is not supported",
+            ),
+            ("SELECT a FROM cpu SLIMIT 1", "SLIMIT is not supported"),
+        ];
+
+        for (query, expected) in cases {
+            let err = parse_select(query, NOW).unwrap_err();
+            assert_eq!(err.to_string(), expected, "query: {}", query);
+        }
+    }
+}
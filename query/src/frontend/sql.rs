@@ -1,14 +1,29 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, fmt, sync::Arc};
 
 use snafu::{ResultExt, Snafu};
 
-use crate::exec::{context::DEFAULT_CATALOG, Executor};
+use crate::{
+    exec::{context::DEFAULT_CATALOG, Executor},
+    Database,
+};
 use datafusion::{
-    catalog::catalog::CatalogProvider, error::DataFusionError, physical_plan::ExecutionPlan,
+    catalog::catalog::CatalogProvider,
+    error::{DataFusionError, Result as DataFusionResult},
+    logical_plan::LogicalPlan,
+    physical_plan::ExecutionPlan,
 };
 
+pub mod catalog;
+pub mod params;
+
+use catalog::CrossDatabaseCatalog;
+use params::QueryParams;
+
 #[derive(Debug, Snafu)]
 pub enum Error {
+    #[snafu(display("Error binding query parameters: {}", source))]
+    BindingParams { source: params::Error },
+
     #[snafu(display("Error preparing query {}", source))]
     Preparing { source: crate::exec::context::Error },
 
@@ -67,25 +82,87 @@ pub enum Error {
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 /// This struct can create plans for running SQL queries against databases
-#[derive(Debug, Default)]
-pub struct SqlQueryPlanner {}
+#[derive(Default)]
+pub struct SqlQueryPlanner {
+    /// Databases, other than the one queried, whose tables queries may
+    /// reference as `<database>.<table>`
+    other_databases: BTreeMap<String, Arc<dyn CatalogProvider>>,
+}
+
+impl fmt::Debug for SqlQueryPlanner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SqlQueryPlanner")
+            .field(
+                "other_databases",
+                &self.other_databases.keys().collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
 
 impl SqlQueryPlanner {
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Allow queries to reference the tables of `databases`, by name, as
+    /// `<database>.<table>`, in addition to the tables of the database
+    /// they are planned against
+    pub fn with_other_databases(
+        mut self,
+        databases: impl IntoIterator<Item = (String, Arc<dyn CatalogProvider>)>,
+    ) -> Self {
+        self.other_databases.extend(databases);
+        self
+    }
+
     /// Plan a SQL query against the data in `database`, and return a
     /// DataFusion physical execution plan. The plan can then be
     /// executed using `executor` in a streaming fashion.
-    pub fn query<D: CatalogProvider + 'static>(
+    ///
+    /// Simple aggregates, such as `count(*)`, are answered from the
+    /// statistics of the chunks of `database` where possible.
+    pub fn query<D: CatalogProvider + Database + 'static>(
+        &self,
+        database: Arc<D>,
+        query: &str,
+        executor: &Executor,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        self.query_with_rewrite(database, query, &Ok, executor)
+    }
+
+    /// Plan a SQL query like [`query`](Self::query), transforming its
+    /// logical plan with `rewrite` before it is optimized
+    pub(crate) fn query_with_rewrite<D: CatalogProvider + Database + 'static>(
         &self,
         database: Arc<D>,
         query: &str,
+        rewrite: &dyn Fn(LogicalPlan) -> DataFusionResult<LogicalPlan>,
         executor: &Executor,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         let mut ctx = executor.new_context();
-        ctx.inner_mut().register_catalog(DEFAULT_CATALOG, database);
-        ctx.prepare_sql(query).context(Preparing)
+        if self.other_databases.is_empty() {
+            ctx.inner_mut().register_catalog(DEFAULT_CATALOG, database);
+        } else {
+            let catalog = CrossDatabaseCatalog::new(database, self.other_databases.clone());
+            ctx.inner_mut()
+                .register_catalog(DEFAULT_CATALOG, Arc::new(catalog));
+        }
+        ctx.prepare_sql_for_chunks_with_rewrite::<D::Chunk>(query, rewrite)
+            .context(Preparing)
+    }
+
+    /// Plan a SQL query like [`query`](Self::query), after binding the
+    /// values of `params` to the parameters of the query, such as `$1` or
+    /// `$name`
+    pub fn query_with_params<D: CatalogProvider + Database + 'static>(
+        &self,
+        database: Arc<D>,
+        query: &str,
+        params: &QueryParams,
+        executor: &Executor,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let query = params::bind(query, params).context(BindingParams)?;
+        self.query(database, &query, executor)
     }
 }
@@ -0,0 +1,100 @@
+//! This module contains the catalog SQL queries are planned against when
+//! they may reference the tables of other databases as
+//! `<database>.<table>`, such as
+//!
+//! ```sql
+//! SELECT * FROM cpu UNION ALL SELECT * FROM db2.cpu
+//! ```
+
+use std::{any::Any, collections::BTreeMap, fmt, sync::Arc};
+
+use datafusion::catalog::{catalog::CatalogProvider, schema::SchemaProvider};
+
+use crate::exec::context::DEFAULT_SCHEMA;
+
+/// A catalog with the schemas of a database, such as `iox` and `system`,
+/// and a schema for each of the other databases it was created with,
+/// named after the database, with the tables of its `iox` schema. The
+/// schemas of the database win over other databases of the same name.
+pub struct CrossDatabaseCatalog {
+    database: Arc<dyn CatalogProvider>,
+    others: BTreeMap<String, Arc<dyn CatalogProvider>>,
+}
+
+impl CrossDatabaseCatalog {
+    pub fn new(
+        database: Arc<dyn CatalogProvider>,
+        others: BTreeMap<String, Arc<dyn CatalogProvider>>,
+    ) -> Self {
+        Self { database, others }
+    }
+}
+
+impl fmt::Debug for CrossDatabaseCatalog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CrossDatabaseCatalog")
+            .field("others", &self.others.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl CatalogProvider for CrossDatabaseCatalog {
+    fn as_any(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    fn schema_names(&self) -> Vec<String> {
+        let mut names = self.database.schema_names();
+        for name in self.others.keys() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    fn schema(&self, name: &str) -> Option<Arc<dyn SchemaProvider>> {
+        self.database.schema(name).or_else(|| {
+            self.others
+                .get(name)
+                .and_then(|database| database.schema(DEFAULT_SCHEMA))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::catalog::{catalog::MemoryCatalogProvider, schema::MemorySchemaProvider};
+
+    fn database(schemas: &[&str]) -> Arc<dyn CatalogProvider> {
+        let catalog = MemoryCatalogProvider::new();
+        for name in schemas {
+            catalog.register_schema(*name, Arc::new(MemorySchemaProvider::new()));
+        }
+        Arc::new(catalog)
+    }
+
+    #[test]
+    fn schemas_of_other_databases() {
+        let others = vec![
+            ("db2".to_string(), database(&[DEFAULT_SCHEMA, "system"])),
+            ("system".to_string(), database(&[DEFAULT_SCHEMA])),
+            ("db3".to_string(), database(&["system"])),
+        ];
+        let catalog = CrossDatabaseCatalog::new(
+            database(&[DEFAULT_SCHEMA, "system"]),
+            others.into_iter().collect(),
+        );
+
+        let mut names = catalog.schema_names();
+        names.sort();
+        assert_eq!(names, vec!["db2", "db3", DEFAULT_SCHEMA, "system"]);
+
+        assert!(catalog.schema(DEFAULT_SCHEMA).is_some());
+        assert!(catalog.schema("db2").is_some());
+        // other databases are only exposed with their tables
+        assert!(catalog.schema("db3").is_none());
+        assert!(catalog.schema("db4").is_none());
+    }
+}
@@ -0,0 +1,234 @@
+//! This module binds values to the parameters of SQL queries, such as
+//!
+//! ```sql
+//! SELECT * FROM cpu WHERE host = $1 AND usage_user > $threshold
+//! ```
+//!
+//! Each parameter outside of string literals, quoted identifiers and
+//! comments is replaced by the SQL literal of its value, so that
+//! applications never interpolate values into the text of a query.
+
+use std::collections::BTreeMap;
+
+use snafu::{ensure, OptionExt, Snafu};
+
+#[derive(Debug, Snafu, PartialEq)]
+pub enum Error {
+    #[snafu(display("No value was bound to the query parameter ${}", name))]
+    MissingParam { name: String },
+
+    #[snafu(display(
+        "Cannot bind {} to the query parameter ${}: only finite numbers are supported",
+        value,
+        name
+    ))]
+    NonFiniteFloat { name: String, value: f64 },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A value bound to a parameter of a SQL query
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamValue {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+/// The values bound to the parameters of a SQL query. `$1`, `$2`, ...
+/// refer to the `positional` values, in order, and `$name` refers to the
+/// `named` value `name`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryParams {
+    pub positional: Vec<ParamValue>,
+    pub named: BTreeMap<String, ParamValue>,
+}
+
+/// Returns `sql` with its parameters replaced by the SQL literals of the
+/// values bound to them in `params`
+pub fn bind(sql: &str, params: &QueryParams) -> Result<String> {
+    let mut bound = String::with_capacity(sql.len());
+    let mut chars = sql.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                // quotes are escaped by doubling them, which reads as two
+                // adjacent quoted strings here
+                let end = chars
+                    .find(|&(_, end)| end == c)
+                    .map(|(end, _)| end + 1)
+                    .unwrap_or_else(|| sql.len());
+                bound.push_str(&sql[start..end]);
+            }
+            '-' if chars.peek().map(|&(_, c)| c) == Some('-') => {
+                let end = chars
+                    .find(|&(_, end)| end == '\n')
+                    .map(|(end, _)| end + 1)
+                    .unwrap_or_else(|| sql.len());
+                bound.push_str(&sql[start..end]);
+            }
+            '/' if chars.peek().map(|&(_, c)| c) == Some('*') => {
+                chars.next();
+                let mut previous = ' ';
+                let end = chars
+                    .find(|&(_, c)| {
+                        let end = previous == '*' && c == '/';
+                        previous = c;
+                        end
+                    })
+                    .map(|(end, _)| end + 1)
+                    .unwrap_or_else(|| sql.len());
+                bound.push_str(&sql[start..end]);
+            }
+            '$' => {
+                let name_start = start + 1;
+                let mut name_end = name_start;
+                while let Some(&(idx, c)) = chars.peek() {
+                    let valid = if name_end == name_start {
+                        c.is_ascii_alphanumeric() || c == '_'
+                    } else if sql[name_start..name_end].starts_with(|c: char| c.is_ascii_digit()) {
+                        c.is_ascii_digit()
+                    } else {
+                        c.is_ascii_alphanumeric() || c == '_'
+                    };
+                    if !valid {
+                        break;
+                    }
+                    name_end = idx + c.len_utf8();
+                    chars.next();
+                }
+
+                let name = &sql[name_start..name_end];
+                if name.is_empty() {
+                    bound.push('$');
+                } else {
+                    bound.push_str(&literal(name, lookup(name, params)?)?);
+                }
+            }
+            c => bound.push(c),
+        }
+    }
+
+    Ok(bound)
+}
+
+/// Returns the value bound to the parameter `$name`
+fn lookup<'a>(name: &str, params: &'a QueryParams) -> Result<&'a ParamValue> {
+    let value = match name.parse::<usize>() {
+        Ok(position) => position
+            .checked_sub(1)
+            .and_then(|idx| params.positional.get(idx)),
+        Err(_) => params.named.get(name),
+    };
+    value.context(MissingParam { name })
+}
+
+/// Returns the SQL literal of `value`, bound to the parameter `$name`
+fn literal(name: &str, value: &ParamValue) -> Result<String> {
+    Ok(match value {
+        ParamValue::Null => "NULL".to_string(),
+        ParamValue::Boolean(value) => value.to_string(),
+        // negative numbers are parenthesized so that they cannot form a
+        // comment, such as `1-$1` bound to `1--2`
+        ParamValue::Integer(value) if *value < 0 => format!("({})", value),
+        ParamValue::Integer(value) => value.to_string(),
+        ParamValue::Float(value) => {
+            ensure!(
+                value.is_finite(),
+                NonFiniteFloat {
+                    name,
+                    value: *value
+                }
+            );
+            if value.is_sign_negative() {
+                format!("({:?})", value)
+            } else {
+                format!("{:?}", value)
+            }
+        }
+        ParamValue::String(value) => format!("'{}'", value.replace('\'', "''")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> QueryParams {
+        QueryParams {
+            positional: vec![
+                ParamValue::String("it's".to_string()),
+                ParamValue::Integer(-2),
+                ParamValue::Float(1.0),
+                ParamValue::Boolean(true),
+                ParamValue::Null,
+            ],
+            named: vec![("host_1".to_string(), ParamValue::Float(-0.5))]
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn bind_params() {
+        let cases = vec![
+            (
+                "SELECT * FROM cpu WHERE host = $1 AND usage > 1-$2",
+                "SELECT * FROM cpu WHERE host = 'it''s' AND usage > 1-(-2)",
+            ),
+            (
+                "SELECT $3, $4, $5, $host_1 FROM cpu",
+                "SELECT 1.0, true, NULL, (-0.5) FROM cpu",
+            ),
+            (
+                "SELECT '$1', \"$2\" FROM cpu -- $3\nWHERE x = /* $4 */ $1",
+                "SELECT '$1', \"$2\" FROM cpu -- $3\nWHERE x = /* $4 */ 'it''s'",
+            ),
+            (
+                "SELECT 'it''s $1', $ FROM cpu",
+                "SELECT 'it''s $1', $ FROM cpu",
+            ),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(bind(sql, &params()).unwrap(), expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn bind_missing_params() {
+        let params = params();
+        for (sql, name) in &[
+            ("SELECT $0", "0"),
+            ("SELECT $6", "6"),
+            ("SELECT $10a", "10"),
+            ("SELECT $host", "host"),
+        ] {
+            let err = bind(sql, &params).unwrap_err();
+            assert_eq!(
+                err,
+                Error::MissingParam {
+                    name: name.to_string()
+                },
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn bind_non_finite_float() {
+        let params = QueryParams {
+            positional: vec![ParamValue::Float(f64::NAN)],
+            ..Default::default()
+        };
+        let err = bind("SELECT $1", &params).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot bind NaN to the query parameter $1: only finite numbers are supported"
+        );
+    }
+}
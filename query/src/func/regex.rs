@@ -6,8 +6,9 @@ use arrow::{
 };
 use datafusion::{
     error::DataFusionError,
-    logical_plan::{create_udf, Expr},
+    logical_plan::{col, create_udf, lit, Expr},
     physical_plan::functions::make_scalar_function,
+    scalar::ScalarValue,
 };
 
 /// The name of the regex_match UDF given to DataFusion.
@@ -26,12 +27,18 @@ pub const REGEX_NOT_MATCH_UDF_NAME: &str = "RegexNotMatch";
 /// This UDF is designed to support the regex operator that can be pushed down
 /// via the InfluxRPC API.
 ///
+/// The pattern is passed to the UDF as a second, literal argument so that it
+/// can be recovered from the expression with [`regex_match_parts`].
 pub fn regex_match_expr(input: Expr, pattern: String, matches: bool) -> Expr {
+    let pattern_arg = pattern.clone();
+
     // N.B., this function does not utilise the Arrow regexp compute kernel because
     // in order to act as a filter it needs to return a boolean array of comparison
     // results, not an array of strings as the regex compute kernel does.
     let func = move |args: &[ArrayRef]| {
-        assert_eq!(args.len(), 1); // only works over a single column at a time.
+        // only works over a single column at a time; the second argument is
+        // the literal pattern, which was captured when the UDF was created.
+        assert_eq!(args.len(), 2);
 
         let input_arr = &args[0].as_any().downcast_ref::<StringArray>().unwrap();
 
@@ -66,12 +73,88 @@ pub fn regex_match_expr(input: Expr, pattern: String, matches: bool) -> Expr {
 
     let udf = create_udf(
         udf_name,
-        vec![DataType::Utf8],
+        vec![DataType::Utf8, DataType::Utf8],
         Arc::new(DataType::Boolean),
         func,
     );
 
-    udf.call(vec![input])
+    udf.call(vec![input, lit(pattern_arg)])
+}
+
+/// If `expr` is a regex match expression on a column created by
+/// [`regex_match_expr`], returns the name of the column, the pattern and
+/// whether values must match (`=~`) or not match (`!~`) the pattern.
+pub fn regex_match_parts(expr: &Expr) -> Option<(&str, &str, bool)> {
+    match expr {
+        Expr::ScalarUDF { fun, args } => {
+            let matches = match fun.name.as_str() {
+                REGEX_MATCH_UDF_NAME => true,
+                REGEX_NOT_MATCH_UDF_NAME => false,
+                _ => return None,
+            };
+
+            match args.as_slice() {
+                [Expr::Column(column), Expr::Literal(ScalarValue::Utf8(Some(pattern)))] => {
+                    Some((column.as_str(), pattern.as_str(), matches))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// If `expr` is a regex match expression created by [`regex_match_expr`]
+/// whose pattern requires values to start with a literal prefix, returns an
+/// equivalent range of values using comparisons only, e.g.
+/// `host >= 'server' AND host < 'servet'` for `host =~ /^server[0-9]+/`.
+///
+/// The returned expression is implied by `expr`, so it can be used to prune
+/// data using the minimum and maximum values of the column.
+pub fn regex_match_range_expr(expr: &Expr) -> Option<Expr> {
+    let (column, pattern, matches) = regex_match_parts(expr)?;
+    if !matches {
+        return None;
+    }
+
+    let lower = literal_prefix(pattern)?;
+    // the prefix only contains ASCII characters, so incrementing the last
+    // character gives the smallest string after all strings with the prefix
+    let mut upper = lower.clone();
+    let last = upper.pop()?;
+    upper.push((last as u8 + 1) as char);
+
+    Some(
+        col(column)
+            .gt_eq(lit(lower))
+            .and(col(column).lt(lit(upper))),
+    )
+}
+
+/// Returns the literal characters every value matching `pattern` must start
+/// with, if any.
+fn literal_prefix(pattern: &str) -> Option<String> {
+    // alternations can match values with different prefixes
+    if pattern.contains('|') {
+        return None;
+    }
+
+    let rest = pattern.strip_prefix('^')?;
+    let mut prefix: String = rest
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '_' | '-' | ',' | ':'))
+        .collect();
+
+    // a quantifier that allows zero repetitions makes the last character
+    // optional
+    if matches!(
+        rest[prefix.len()..].chars().next(),
+        Some('?') | Some('*') | Some('{')
+    ) {
+        prefix.pop();
+    }
+
+    (!prefix.is_empty()).then(|| prefix)
 }
 
 #[cfg(test)]
@@ -85,7 +168,7 @@ mod test {
     use datafusion::{
         datasource::MemTable,
         error::DataFusionError,
-        logical_plan::{col, Expr},
+        logical_plan::{col, lit, Expr},
         prelude::ExecutionContext,
     };
     use std::iter::FromIterator;
@@ -170,6 +253,58 @@ mod test {
         }
     }
 
+    #[test]
+    fn regex_match_parts() {
+        let expr = super::regex_match_expr(col("words"), "^a.*".to_string(), true);
+        assert_eq!(
+            super::regex_match_parts(&expr),
+            Some(("words", "^a.*", true))
+        );
+
+        let expr = super::regex_match_expr(col("words"), "^a.*".to_string(), false);
+        assert_eq!(
+            super::regex_match_parts(&expr),
+            Some(("words", "^a.*", false))
+        );
+
+        // only directly on a column
+        let expr = super::regex_match_expr(col("words").alias("w"), "^a.*".to_string(), true);
+        assert_eq!(super::regex_match_parts(&expr), None);
+
+        assert_eq!(super::regex_match_parts(&col("words").eq(lit("a"))), None);
+    }
+
+    #[test]
+    fn regex_match_range_expr() {
+        let range = |pattern: &str, matches: bool| {
+            super::regex_match_range_expr(&super::regex_match_expr(
+                col("host"),
+                pattern.to_string(),
+                matches,
+            ))
+        };
+
+        assert_eq!(
+            range("^server[0-9]+", true),
+            Some(
+                col("host")
+                    .gt_eq(lit("server"))
+                    .and(col("host").lt(lit("servet")))
+            )
+        );
+        assert_eq!(
+            range("^ab?c", true),
+            Some(col("host").gt_eq(lit("a")).and(col("host").lt(lit("b"))))
+        );
+
+        // not anchored, no literal prefix or not matching
+        assert_eq!(range("server", true), None);
+        assert_eq!(range("^(?i)server", true), None);
+        assert_eq!(range("^a?", true), None);
+        assert_eq!(range("^server|^client", true), None);
+        assert_eq!(range("^server", false), None);
+    }
+
     #[tokio::test]
     async fn regex_match_expr_invalid_regex() {
         // an invalid regex pattern
@@ -14,11 +14,22 @@
 //! This module implements a workaround of "do the aggregation twice
 //! with two distinct functions" to get something working. It should
 //! should be removed when DataFusion / Arrow has proper support
+//!
+//! The selector functions are also available in SQL, where each part of
+//! the (value, time) pair is requested separately:
+//!
+//! ```sql
+//! SELECT selector_last_value(usage, time), selector_last_time(usage, time)
+//! FROM cpu GROUP BY host
+//! ```
 use std::{fmt::Debug, sync::Arc};
 
 use arrow::{array::ArrayRef, datatypes::DataType};
 use datafusion::{
     error::{DataFusionError, Result as DataFusionResult},
+    execution::context::ExecutionContext,
+    logical_plan::{DFSchema, Expr, LogicalPlan},
+    optimizer::utils::from_plan,
     physical_plan::{
         aggregates::{AccumulatorFunctionImplementation, StateTypeFunction},
         functions::{ReturnTypeFunction, Signature},
@@ -158,6 +169,170 @@ pub fn selector_max(data_type: &DataType, output: SelectorOutput) -> AggregateUD
     }
 }
 
+/// Creates one of the selector functions above for the type of its input
+type SelectorFactory = fn(&DataType, SelectorOutput) -> AggregateUDF;
+
+/// The names of the selector functions available in SQL
+const SQL_SELECTORS: [&str; 8] = [
+    "selector_first_value",
+    "selector_first_time",
+    "selector_last_value",
+    "selector_last_time",
+    "selector_min_value",
+    "selector_min_time",
+    "selector_max_value",
+    "selector_max_time",
+];
+
+/// Returns the factory and output of the selector function called `name`
+/// in SQL
+fn sql_selector(name: &str) -> Option<(SelectorFactory, SelectorOutput)> {
+    let selector: (SelectorFactory, SelectorOutput) = match name {
+        "selector_first_value" => (selector_first, SelectorOutput::Value),
+        "selector_first_time" => (selector_first, SelectorOutput::Time),
+        "selector_last_value" => (selector_last, SelectorOutput::Value),
+        "selector_last_time" => (selector_last, SelectorOutput::Time),
+        "selector_min_value" => (selector_min, SelectorOutput::Value),
+        "selector_min_time" => (selector_min, SelectorOutput::Time),
+        "selector_max_value" => (selector_max, SelectorOutput::Value),
+        "selector_max_time" => (selector_max, SelectorOutput::Time),
+        _ => return None,
+    };
+    Some(selector)
+}
+
+/// Registers the selector functions with `ctx` so that they can be used in
+/// SQL.
+///
+/// The implementation of a selector depends on the type of its input,
+/// which is not known when DataFusion resolves the function by name. The
+/// registered functions are therefore placeholders that must be replaced
+/// by [`plan_sql_selectors`] before the plan is executed.
+pub fn register_sql_selectors(ctx: &mut ExecutionContext) {
+    for name in SQL_SELECTORS.iter() {
+        let (_, output) = sql_selector(name).expect("known selector");
+        ctx.register_udaf(untyped_selector(name, output));
+    }
+}
+
+/// Returns the placeholder for the selector function `name` registered for
+/// SQL, which can be planned but not executed
+fn untyped_selector(name: &'static str, output: SelectorOutput) -> AggregateUDF {
+    let return_type_func: ReturnTypeFunction =
+        Arc::new(move |arg_types| Ok(Arc::new(output.return_type(&arg_types[0]))));
+
+    let state_type_factory: StateTypeFunction = Arc::new(|_| Ok(Arc::new(vec![])));
+
+    let factory: AccumulatorFunctionImplementation = Arc::new(move || {
+        Err(DataFusionError::Internal(format!(
+            "{} was not planned for the type of its input",
+            name
+        )))
+    });
+
+    AggregateUDF::new(
+        name,
+        &Signature::Any(2),
+        &return_type_func,
+        &factory,
+        &state_type_factory,
+    )
+}
+
+/// Replaces the selector functions called in SQL with the implementation
+/// for the type of their input
+pub fn plan_sql_selectors(plan: LogicalPlan) -> DataFusionResult<LogicalPlan> {
+    Ok(typed_selectors(&plan)?.unwrap_or(plan))
+}
+
+/// Returns a copy of `plan` with typed selectors, or `None` if `plan`
+/// calls no selector
+fn typed_selectors(plan: &LogicalPlan) -> DataFusionResult<Option<LogicalPlan>> {
+    let new_inputs = plan
+        .inputs()
+        .into_iter()
+        .map(typed_selectors)
+        .collect::<DataFusionResult<Vec<_>>>()?;
+    let inputs_changed = new_inputs.iter().any(Option::is_some);
+
+    if let LogicalPlan::Aggregate {
+        input,
+        group_expr,
+        aggr_expr,
+        schema,
+    } = plan
+    {
+        let input = match new_inputs.into_iter().next().flatten() {
+            Some(new_input) => Arc::new(new_input),
+            None => Arc::clone(input),
+        };
+
+        let new_aggr_expr = aggr_expr
+            .iter()
+            .map(|expr| typed_selector(expr, input.schema()))
+            .collect::<DataFusionResult<Vec<_>>>()?;
+
+        if !inputs_changed && new_aggr_expr.iter().all(Option::is_none) {
+            return Ok(None);
+        }
+
+        let aggr_expr = new_aggr_expr
+            .into_iter()
+            .zip(aggr_expr)
+            .map(|(new_expr, expr)| new_expr.unwrap_or_else(|| expr.clone()))
+            .collect();
+
+        return Ok(Some(LogicalPlan::Aggregate {
+            input,
+            group_expr: group_expr.clone(),
+            aggr_expr,
+            schema: Arc::clone(schema),
+        }));
+    }
+
+    if !inputs_changed {
+        return Ok(None);
+    }
+
+    let inputs = new_inputs
+        .into_iter()
+        .zip(plan.inputs())
+        .map(|(new_input, input)| new_input.unwrap_or_else(|| input.clone()))
+        .collect::<Vec<_>>();
+    from_plan(plan, &plan.expressions(), &inputs).map(Some)
+}
+
+/// Returns the typed selector for an aggregate expression that calls a SQL
+/// selector, or `None` if it calls some other function
+fn typed_selector(expr: &Expr, schema: &DFSchema) -> DataFusionResult<Option<Expr>> {
+    match expr {
+        Expr::Alias(inner, name) => {
+            Ok(typed_selector(inner, schema)?
+                .map(|inner| Expr::Alias(Box::new(inner), name.clone())))
+        }
+        Expr::AggregateUDF { fun, args } => {
+            let (factory, output) = match sql_selector(&fun.name) {
+                Some(selector) => selector,
+                None => return Ok(None),
+            };
+
+            let data_type = args[0].get_type(schema)?;
+            match data_type {
+                DataType::Float64 | DataType::Int64 | DataType::Utf8 | DataType::Boolean => {}
+                _ => {
+                    return Err(DataFusionError::Plan(format!(
+                        "{} is not supported for {:?}",
+                        fun.name, data_type
+                    )))
+                }
+            }
+
+            Ok(Some(factory(&data_type, output).call(args.clone())))
+        }
+        _ => Ok(None),
+    }
+}
+
 /// Implements the logic of the specific selector function (this is a
 /// cutdown version of the Accumulator DataFusion trait, to allow
 /// sharing between implementations)
@@ -309,7 +484,11 @@ mod test {
         record_batch::RecordBatch,
         util::pretty::pretty_format_batches,
     };
-    use datafusion::{datasource::MemTable, logical_plan::Expr, prelude::*};
+    use datafusion::{
+        datasource::MemTable,
+        physical_plan::{collect, ExecutionPlan},
+        prelude::*,
+    };
     use internal_types::schema::TIME_DATA_TIMEZONE;
 
     use super::*;
@@ -595,7 +774,50 @@ mod test {
         }
     }
 
-    /// Run a plan against the following input table as "t"
+    #[tokio::test]
+    async fn test_sql_selectors() {
+        let plan = plan_sql(
+            "SELECT selector_min_value(f64_value, time) as min_value, \
+             selector_min_time(f64_value, time) as min_time, \
+             selector_max_value(i64_value, time) as max_value, \
+             selector_max_time(i64_value, time) as max_time \
+             FROM t",
+        )
+        .unwrap();
+
+        let expected = vec![
+            "+-----------+----------------------------+-----------+----------------------------+",
+            "| min_value | min_time                   | max_value | max_time                   |",
+            "+-----------+----------------------------+-----------+----------------------------+",
+            "| 1         | 1970-01-01 00:00:00.000004 | 50        | 1970-01-01 00:00:00.000005 |",
+            "+-----------+----------------------------+-----------+----------------------------+",
+            "",
+        ];
+        let actual: Vec<String> = pretty_format_batches(&collect(plan).await.unwrap())
+            .unwrap()
+            .split('\n')
+            .map(|s| s.to_owned())
+            .collect();
+
+        assert_eq!(
+            expected, actual,
+            "\n\nEXPECTED:\n{:#?}\nACTUAL:\n{:#?}\n",
+            expected, actual
+        );
+    }
+
+    #[test]
+    fn test_sql_selector_unsupported_type() {
+        let err = plan_sql("SELECT selector_first_value(time, time) FROM t").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("selector_first_value is not supported for Timestamp"),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    /// Returns a context with the following input table registered as "t"
     ///
     /// +-----------+-----------+--------------+------------+----------------------------+,
     /// | f64_value | i64_value | string_value | bool_value | time
@@ -610,7 +832,7 @@ mod test {
     /// | 3         | 30        | three        | false      | 1970-01-01
     /// 00:00:00.000006 |,
     /// +-----------+-----------+--------------+------------+----------------------------+,
-    async fn run_plan(aggs: Vec<Expr>) -> Vec<String> {
+    fn make_context() -> ExecutionContext {
         // define a schema for input
         // (value) and timestamp
         let schema = Arc::new(Schema::new(vec![
@@ -685,6 +907,13 @@ mod test {
         .unwrap();
         let mut ctx = ExecutionContext::new();
         ctx.register_table("t", Arc::new(provider)).unwrap();
+        ctx
+    }
+
+    /// Runs an aggregate with `aggs` over the table described on
+    /// [`make_context`]
+    async fn run_plan(aggs: Vec<Expr>) -> Vec<String> {
+        let ctx = make_context();
 
         let df = ctx.table("t").unwrap();
         let df = df.aggregate(vec![], aggs).unwrap();
@@ -698,4 +927,16 @@ mod test {
             .map(|s| s.to_owned())
             .collect()
     }
+
+    /// Plans `sql` against the table described on [`make_context`], with
+    /// the selector functions available
+    fn plan_sql(sql: &str) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        let mut ctx = make_context();
+        register_sql_selectors(&mut ctx);
+
+        let plan = ctx.create_logical_plan(sql)?;
+        let plan = plan_sql_selectors(plan)?;
+        let plan = ctx.optimize(&plan)?;
+        ctx.create_physical_plan(&plan)
+    }
 }
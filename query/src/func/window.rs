@@ -1,12 +1,19 @@
 mod internal;
 
 pub use internal::{Duration, Window};
-use internal_types::schema::TIME_DATA_TYPE;
+use internal_types::schema::{TIME_DATA_TIMEZONE, TIME_DATA_TYPE};
 
 use std::{iter::FromIterator, sync::Arc};
 
-use arrow::array::{ArrayRef, TimestampNanosecondArray};
-use datafusion::{logical_plan::Expr, physical_plan::functions::make_scalar_function, prelude::*};
+use arrow::{
+    array::{ArrayRef, StringArray, TimestampNanosecondArray},
+    datatypes::DataType,
+};
+use datafusion::{
+    logical_plan::{lit, Expr},
+    physical_plan::{functions::make_scalar_function, udf::ScalarUDF},
+    prelude::*,
+};
 
 use crate::group_by::WindowDuration;
 
@@ -86,11 +93,161 @@ pub fn make_window_bound_expr(
     udf.call(vec![time_arg])
 }
 
+/// The name of the `date_bin` UDF given to DataFusion.
+pub const DATE_BIN_UDF_NAME: &str = "date_bin";
+
+/// This is the implementation of the `date_bin` user defined function,
+/// which truncates each timestamp to the start of the window of width
+/// `stride` that contains it:
+///
+/// ```text
+/// date_bin(stride, time) -> start of window
+/// ```
+///
+/// `stride` is a duration literal such as `'1m'`, `'1h30m'` or `'1mo'`, so
+/// that `GROUP BY date_bin('1m', time)` groups rows into one minute
+/// windows.
+fn date_bin(args: &[ArrayRef]) -> Result<ArrayRef> {
+    // this is guaranteed by DataFusion based on the function's signature.
+    assert_eq!(args.len(), 2);
+
+    let strides = args[0]
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .expect("cast of stride failed");
+
+    let time = args[1]
+        .as_any()
+        .downcast_ref::<TimestampNanosecondArray>()
+        .expect("cast of time failed");
+
+    // The stride is almost always a constant that DataFusion passes as an
+    // array, so only parse it again when it changes
+    let mut current: Option<(&str, internal::Window)> = None;
+
+    let values = strides
+        .iter()
+        .zip(time.iter())
+        .map(|(stride, ts)| {
+            let (stride, ts) = match (stride, ts) {
+                (Some(stride), Some(ts)) => (stride, ts),
+                _ => return Ok(None),
+            };
+
+            let window = match current {
+                Some((current_stride, window)) if current_stride == stride => window,
+                _ => {
+                    let every = parse_stride(stride).map_err(|e| {
+                        Error::Execution(format!("Invalid date_bin stride '{}': {}", stride, e))
+                    })?;
+                    let every = internal::Duration::from(&every);
+                    let window =
+                        internal::Window::new(every, every, internal::Duration::from_nsecs(0));
+                    current = Some((stride, window));
+                    window
+                }
+            };
+
+            Ok(Some(window.get_earliest_bounds(ts).start))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let array = TimestampNanosecondArray::from_opt_vec(values, TIME_DATA_TIMEZONE());
+    Ok(Arc::new(array) as ArrayRef)
+}
+
+/// Parses a duration literal made of one or more `<integer><unit>` pairs,
+/// such as `10s` or `1h30m`. The supported units are `ns`, `us` (or `µs`),
+/// `ms`, `s`, `m`, `h`, `d`, `w`, `mo` and `y`.
+///
+/// Months and years vary in length, so they may not be combined with the
+/// other units.
+fn parse_stride(s: &str) -> std::result::Result<WindowDuration, String> {
+    const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
+    let mut nanoseconds: i64 = 0;
+    let mut months: i64 = 0;
+
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return Err("empty duration".to_string());
+    }
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or_else(|| rest.len());
+        if digits == 0 {
+            return Err(format!("expected a number at '{}'", rest));
+        }
+        let value: i64 = rest[..digits].parse().map_err(|e| format!("{}", e))?;
+        rest = &rest[digits..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or_else(|| rest.len());
+        let unit = &rest[..unit_len];
+        rest = &rest[unit_len..];
+
+        let (nanos_per_unit, months_per_unit) = match unit {
+            "ns" => (1, 0),
+            "us" | "µs" => (1_000, 0),
+            "ms" => (1_000_000, 0),
+            "s" => (NANOS_PER_SECOND, 0),
+            "m" => (60 * NANOS_PER_SECOND, 0),
+            "h" => (60 * 60 * NANOS_PER_SECOND, 0),
+            "d" => (24 * 60 * 60 * NANOS_PER_SECOND, 0),
+            "w" => (7 * 24 * 60 * 60 * NANOS_PER_SECOND, 0),
+            "mo" => (0, 1),
+            "y" => (0, 12),
+            "" => return Err(format!("missing unit after '{}'", value)),
+            _ => return Err(format!("unknown unit '{}'", unit)),
+        };
+
+        let overflow = || "duration is too large".to_string();
+        nanoseconds = value
+            .checked_mul(nanos_per_unit)
+            .and_then(|v| nanoseconds.checked_add(v))
+            .ok_or_else(overflow)?;
+        months = value
+            .checked_mul(months_per_unit)
+            .and_then(|v| months.checked_add(v))
+            .ok_or_else(overflow)?;
+    }
+
+    match (months, nanoseconds) {
+        (0, 0) => Err("duration must be positive".to_string()),
+        (0, nanoseconds) => Ok(WindowDuration::from_nanoseconds(nanoseconds)),
+        (months, 0) => Ok(WindowDuration::from_months(months, false)),
+        _ => Err("months and years cannot be combined with other units".to_string()),
+    }
+}
+
+/// Returns the `date_bin` function so that it can be registered with a
+/// DataFusion `ExecutionContext` and used from SQL
+pub fn date_bin_udf() -> ScalarUDF {
+    create_udf(
+        DATE_BIN_UDF_NAME,
+        vec![DataType::Utf8, TIME_DATA_TYPE()], // argument types
+        Arc::new(TIME_DATA_TYPE()),             // return type
+        make_scalar_function(date_bin),
+    )
+}
+
+/// Create a DataFusion `Expr` that invokes `date_bin` to truncate
+/// `time_arg` to the start of the window of width `stride` containing it,
+/// for use by plan builders that group by time
+pub fn make_date_bin_expr(time_arg: Expr, stride: &WindowDuration) -> Expr {
+    let stride = match stride {
+        WindowDuration::Fixed { nanoseconds } => format!("{}ns", nanoseconds),
+        WindowDuration::Variable { months, .. } => format!("{}mo", months),
+    };
+
+    date_bin_udf().call(vec![lit(stride), time_arg])
+}
+
 #[cfg(test)]
 mod tests {
-    use arrow::array::TimestampNanosecondArray;
-    use internal_types::schema::TIME_DATA_TIMEZONE;
-
     use super::*;
 
     #[test]
@@ -116,4 +273,121 @@ mod tests {
             expected_array, bounds_array,
         );
     }
+
+    #[test]
+    fn test_date_bin() {
+        let strides: ArrayRef = Arc::new(StringArray::from(vec![
+            Some("100ns"),
+            Some("100ns"),
+            Some("100ns"),
+            None,
+            Some("100ns"),
+            Some("1mo"),
+        ]));
+        let input: ArrayRef = Arc::new(TimestampNanosecondArray::from_opt_vec(
+            vec![
+                Some(150),
+                None,
+                Some(250),
+                Some(300),
+                Some(-50),
+                // 2021-02-15T00:00:00Z
+                Some(1_613_347_200_000_000_000),
+            ],
+            TIME_DATA_TIMEZONE(),
+        ));
+
+        let bins_array = date_bin(&[strides, input]).unwrap();
+
+        let expected_array: ArrayRef = Arc::new(TimestampNanosecondArray::from_opt_vec(
+            vec![
+                Some(100),
+                None,
+                Some(200),
+                None,
+                Some(-100),
+                // 2021-02-01T00:00:00Z
+                Some(1_612_137_600_000_000_000),
+            ],
+            TIME_DATA_TIMEZONE(),
+        ));
+
+        assert_eq!(
+            &expected_array, &bins_array,
+            "Expected:\n{:?}\nActual:\n{:?}",
+            expected_array, bins_array,
+        );
+    }
+
+    #[test]
+    fn test_date_bin_invalid_stride() {
+        let strides: ArrayRef = Arc::new(StringArray::from(vec!["1x"]));
+        let input: ArrayRef = Arc::new(TimestampNanosecondArray::from_opt_vec(
+            vec![Some(100)],
+            TIME_DATA_TIMEZONE(),
+        ));
+
+        let err = date_bin(&[strides, input]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Execution error: Invalid date_bin stride '1x': unknown unit 'x'"
+        );
+    }
+
+    #[test]
+    fn test_parse_stride() {
+        let cases = vec![
+            ("1ns", WindowDuration::from_nanoseconds(1)),
+            ("10us", WindowDuration::from_nanoseconds(10_000)),
+            ("10µs", WindowDuration::from_nanoseconds(10_000)),
+            ("5ms", WindowDuration::from_nanoseconds(5_000_000)),
+            ("1m", WindowDuration::from_nanoseconds(60_000_000_000)),
+            ("1h30m", WindowDuration::from_nanoseconds(5_400_000_000_000)),
+            ("1w", WindowDuration::from_nanoseconds(604_800_000_000_000)),
+            ("3mo", WindowDuration::from_months(3, false)),
+            ("1y2mo", WindowDuration::from_months(14, false)),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(parse_stride(input).unwrap(), expected, "input: {}", input);
+        }
+
+        let errors = vec![
+            ("", "empty duration"),
+            ("m", "expected a number at 'm'"),
+            ("10", "missing unit after '10'"),
+            ("10x", "unknown unit 'x'"),
+            ("0s", "duration must be positive"),
+            (
+                "1mo1d",
+                "months and years cannot be combined with other units",
+            ),
+            (
+                "100000000000000000000s",
+                "number too large to fit in target type",
+            ),
+            ("10000000000d", "duration is too large"),
+        ];
+
+        for (input, expected) in errors {
+            assert_eq!(
+                parse_stride(input).unwrap_err(),
+                expected,
+                "input: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_make_date_bin_expr() {
+        let expr = make_date_bin_expr(col("time"), &WindowDuration::from_nanoseconds(60));
+        match expr {
+            Expr::ScalarUDF { fun, args } => {
+                assert_eq!(fun.name, DATE_BIN_UDF_NAME);
+                assert_eq!(args, vec![lit("60ns"), col("time")]);
+            }
+            _ => panic!("unexpected expression {:?}", expr),
+        }
+    }
 }
@@ -7,6 +7,7 @@
 )]
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use data_types::chunk_metadata::ChunkSummary;
 use datafusion::physical_plan::SendableRecordBatchStream;
 use exec::{stringset::StringSet, Executor};
@@ -70,6 +71,16 @@ pub trait PartitionChunk: Prunable + Debug + Send + Sync {
     /// key" within itself
     fn may_contain_pk_duplicates(&self) -> bool;
 
+    /// Returns true if rows may have been deleted from the chunk, in
+    /// which case its summary may describe rows that are no longer
+    /// visible to queries
+    fn has_delete_predicates(&self) -> bool;
+
+    /// Returns the time of the last write to the data of this chunk,
+    /// if known. The data of overlapping chunks is deduplicated in
+    /// the order of this time, the data written last winning
+    fn time_of_last_write(&self) -> Option<DateTime<Utc>>;
+
     /// Returns the result of applying the `predicate` to the chunk
     /// using an efficient, but inexact method, based on metadata.
     ///
@@ -104,6 +115,11 @@ pub trait PartitionChunk: Prunable + Debug + Send + Sync {
     /// selection refers to columns that do not exist.
     fn table_schema(&self, selection: Selection<'_>) -> Result<Schema, Self::Error>;
 
+    /// Returns the columns the rows returned by `read_filter` are
+    /// sorted by, in order, or None if the order of the rows is not
+    /// known
+    fn sort_key(&self) -> Option<&[String]>;
+
     /// Provides access to raw `PartitionChunk` data as an
     /// asynchronous stream of `RecordBatch`es filtered by a *required*
     /// predicate. Note that not all chunks can evaluate all types of
@@ -11,7 +11,7 @@ use std::{
 use data_types::timestamp::TimestampRange;
 use datafusion::{
     error::DataFusionError,
-    logical_plan::{col, Expr, Operator},
+    logical_plan::{col, lit, Expr, Operator},
     optimizer::utils,
 };
 use datafusion_util::{make_range_expr, AndExprBuilder};
@@ -231,6 +231,18 @@ impl PredicateBuilder {
         self
     }
 
+    /// Adds an expression that is true if any of `exprs` is true, e.g.
+    /// `tag = 'a' OR tag = 'b'`, to the list of general purpose predicates.
+    /// No rows pass an empty disjunction.
+    pub fn add_disjunction(self, exprs: impl IntoIterator<Item = Expr>) -> Self {
+        let mut exprs = exprs.into_iter();
+        let expr = match exprs.next() {
+            Some(first) => exprs.fold(first, Expr::or),
+            None => lit(false),
+        };
+        self.add_expr(expr)
+    }
+
     /// Builds a regex matching expression from the provided column name and
     /// pattern. Values not matching the regex will be filtered out.
     pub fn build_regex_match_expr(self, column: &str, pattern: impl Into<String>) -> Self {
@@ -249,6 +261,22 @@ impl PredicateBuilder {
         self
     }
 
+    /// Builds a `column IN (values)` expression. Rows whose `column` is equal
+    /// to any of `values` will be returned.
+    pub fn build_in_list_expr(
+        mut self,
+        column: &str,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        let expr = Expr::InList {
+            expr: Box::new(col(column)),
+            list: values.into_iter().map(|value| lit(value.into())).collect(),
+            negated: false,
+        };
+        self.inner.exprs.push(expr);
+        self
+    }
+
     /// Adds an optional table name restriction to the existing list
     pub fn table_option(self, table: Option<String>) -> Self {
         if let Some(table) = table {
@@ -327,7 +355,8 @@ impl PredicateBuilder {
             .iter()
             .for_each(|expr| Self::split_members(expr, &mut exprs));
 
-        // Only keep single_column and primitive binary expressions
+        // Only keep single_column and primitive binary, regex match or IN
+        // list expressions, or disjunctions of them
         let mut pushdown_exprs: Vec<Expr> = vec![];
         let exprs_result = exprs
             .into_iter()
@@ -335,7 +364,7 @@ impl PredicateBuilder {
                 let mut columns = HashSet::new();
                 utils::expr_to_column_names(&expr, &mut columns)?;
 
-                if columns.len() == 1 && Self::primitive_binary_expr(&expr) {
+                if columns.len() == 1 && Self::pushdown_expr(&expr) {
                     pushdown_exprs.push(expr);
                 }
                 Ok(())
@@ -370,6 +399,31 @@ impl PredicateBuilder {
         }
     }
 
+    /// Return true if the given expression is a primitive binary expression,
+    /// a regex match on a column, an IN list of literals, or a disjunction of
+    /// such expressions
+    fn pushdown_expr(expr: &Expr) -> bool {
+        match expr {
+            Expr::BinaryExpr {
+                left,
+                op: Operator::Or,
+                right,
+            } => Self::pushdown_expr(left) && Self::pushdown_expr(right),
+            Expr::InList {
+                expr,
+                list,
+                negated: false,
+            } => {
+                matches!(expr.as_ref(), Expr::Column(_))
+                    && list.iter().all(|value| matches!(value, Expr::Literal(_)))
+            }
+            expr => {
+                Self::primitive_binary_expr(expr)
+                    || crate::func::regex::regex_match_parts(expr).is_some()
+            }
+        }
+    }
+
     /// Return true if the given expression is in a primitive binary in the form: `column op constant`
     // and op must be a comparison one
     pub fn primitive_binary_expr(expr: &Expr) -> bool {
@@ -502,6 +556,117 @@ mod tests {
         assert_eq!(predicate.exprs[7], lit(5).eq(col("city")));
     }
 
+    #[test]
+    fn test_pushdown_disjunctions() {
+        let filters = vec![
+            // state = CA OR state = MA OR state < B
+            col("state")
+                .eq(lit("CA"))
+                .or(col("state").eq(lit("MA")))
+                .or(col("state").lt(lit("B"))),
+            // city = Boston OR state = MA --> won't be pushed down
+            col("city").eq(lit("Boston")).or(col("state").eq(lit("MA"))),
+            // city = Boston OR city is null --> won't be pushed down
+            col("city").eq(lit("Boston")).or(col("city").is_null()),
+        ];
+
+        let predicate = PredicateBuilder::default()
+            .add_pushdown_exprs(&filters)
+            .build();
+
+        assert_eq!(predicate.exprs, vec![filters[0].clone()]);
+    }
+
+    #[test]
+    fn test_pushdown_in_list() {
+        let in_list = |expr: Expr, list: Vec<Expr>, negated: bool| Expr::InList {
+            expr: Box::new(expr),
+            list,
+            negated,
+        };
+        let filters = vec![
+            // state IN (CA, MA)
+            in_list(col("state"), vec![lit("CA"), lit("MA")], false),
+            // state NOT IN (CA, MA) --> won't be pushed down
+            in_list(col("state"), vec![lit("CA"), lit("MA")], true),
+            // state IN (city) --> won't be pushed down
+            in_list(col("state"), vec![col("city")], false),
+        ];
+
+        let predicate = PredicateBuilder::default()
+            .add_pushdown_exprs(&filters)
+            .build();
+
+        assert_eq!(predicate.exprs, vec![filters[0].clone()]);
+    }
+
+    #[test]
+    fn test_build_in_list_expr() {
+        let predicate = PredicateBuilder::default()
+            .build_in_list_expr("state", vec!["CA", "MA"])
+            .build();
+
+        assert_eq!(
+            predicate.exprs,
+            vec![Expr::InList {
+                expr: Box::new(col("state")),
+                list: vec![lit("CA"), lit("MA")],
+                negated: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_add_disjunction() {
+        let predicate = PredicateBuilder::default()
+            .add_disjunction(vec![
+                col("state").eq(lit("CA")),
+                col("state").eq(lit("MA")),
+                col("state").eq(lit("NY")),
+            ])
+            .add_disjunction(vec![col("city").eq(lit("Boston"))])
+            .add_disjunction(vec![])
+            .build();
+
+        assert_eq!(
+            predicate.exprs,
+            vec![
+                col("state")
+                    .eq(lit("CA"))
+                    .or(col("state").eq(lit("MA")))
+                    .or(col("state").eq(lit("NY"))),
+                col("city").eq(lit("Boston")),
+                lit(false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pushdown_regex_predicates() {
+        use crate::func::regex::{regex_match_expr, regex_match_parts};
+
+        let filters = vec![
+            // state =~ /^C/ AND city !~ /ton$/
+            regex_match_expr(col("state"), "^C".into(), true).and(regex_match_expr(
+                col("city"),
+                "ton$".into(),
+                false,
+            )),
+            // state || city =~ /^C/ --> won't be pushed down
+            regex_match_expr(col("state") + col("city"), "^C".into(), true),
+        ];
+
+        let predicate = PredicateBuilder::default()
+            .add_pushdown_exprs(&filters)
+            .build();
+
+        let parts: Vec<_> = predicate.exprs.iter().map(regex_match_parts).collect();
+        assert_eq!(
+            parts,
+            vec![Some(("state", "^C", true)), Some(("city", "ton$", false))]
+        );
+    }
+
     #[test]
     fn predicate_display_ts() {
         // TODO make this a doc example?
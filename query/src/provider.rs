@@ -1,8 +1,11 @@
 //! Implementation of a DataFusion `TableProvider` in terms of `PartitionChunk`s
 
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
-use arrow::{datatypes::SchemaRef as ArrowSchemaRef, error::ArrowError};
+use arrow::{
+    datatypes::{Schema as ArrowSchema, SchemaRef as ArrowSchemaRef},
+    error::ArrowError,
+};
 use datafusion::{
     datasource::{
         datasource::{Statistics, TableProviderFilterPushDown},
@@ -10,10 +13,14 @@ use datafusion::{
     },
     error::{DataFusionError, Result as DataFusionResult},
     logical_plan::Expr,
-    physical_plan::ExecutionPlan,
+    optimizer::utils::expr_to_column_names,
+    physical_plan::{union::UnionExec, ExecutionPlan},
+};
+use internal_types::{
+    schema::{merge::SchemaMerger, InfluxFieldType, Schema, TIME_COLUMN_NAME},
+    selection::Selection,
 };
-use internal_types::schema::{merge::SchemaMerger, Schema};
-use observability_deps::tracing::debug;
+use observability_deps::tracing::{debug, info_span};
 
 use crate::{
     duplicate::group_potential_duplicates,
@@ -25,8 +32,14 @@ use crate::{
 use snafu::{ResultExt, Snafu};
 
 mod adapter;
+mod deduplicate;
+mod merge;
+mod metadata;
 mod physical;
-use self::physical::IOxReadFilterNode;
+pub(crate) use self::deduplicate::value_type;
+pub(crate) use self::merge::merge_sorted_partitions;
+pub use self::metadata::plan_metadata_aggregates;
+use self::{deduplicate::DeduplicateExec, merge::SortedMergeExec, physical::IOxReadFilterNode};
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -100,6 +113,15 @@ impl<C: PartitionChunk> ProviderBuilder<C> {
         }
     }
 
+    /// Allows chunks to store a field with the first type of each pair of
+    /// `coercions` while others store it with the second type, which it
+    /// is read as. Must be called before any chunk is added
+    pub fn with_coercions(mut self, coercions: Vec<(InfluxFieldType, InfluxFieldType)>) -> Self {
+        assert!(self.chunks.is_empty(), "chunks already added");
+        self.schema_merger = std::mem::take(&mut self.schema_merger).with_coercions(coercions);
+        self
+    }
+
     /// Add a new chunk to this provider
     pub fn add_chunk(&mut self, chunk: Arc<C>, chunk_table_schema: Schema) -> Result<&mut Self> {
         self.schema_merger
@@ -226,21 +248,35 @@ impl<C: PartitionChunk + 'static> TableProvider for ChunkTableProvider<C> {
         // metadata using the pushed down predicate (e.g. in SQL).
         let chunks: Vec<Arc<C>> = self.chunks.to_vec();
         let num_initial_chunks = chunks.len();
-        let chunks = self.chunk_pruner.prune_chunks(chunks, &predicate);
+        let chunks = info_span!("prune_chunks", table_name = %self.table_name, num_initial_chunks)
+            .in_scope(|| self.chunk_pruner.prune_chunks(chunks, &predicate));
         debug!(%predicate, num_initial_chunks, num_final_chunks=chunks.len(), "pruned with pushed down predicates");
 
+        // Remember which chunks were pruned so that EXPLAIN can show them
+        let pruned_chunk_ids: Vec<u32> = self
+            .chunks
+            .iter()
+            .map(|chunk| chunk.id())
+            .filter(|id| !chunks.iter().any(|chunk| chunk.id() == *id))
+            .collect();
+
         // Figure out the schema of the requested output
         let scan_schema = project_schema(self.arrow_schema(), projection);
 
         let mut deduplicate = Deduplicater::new();
         let plan = deduplicate.build_scan_plan(
             Arc::clone(&self.table_name),
+            &self.iox_schema,
             scan_schema,
             chunks,
             predicate,
         )?;
 
-        Ok(plan)
+        if pruned_chunk_ids.is_empty() {
+            Ok(plan)
+        } else {
+            with_pruned_chunk_ids::<C>(plan, &pruned_chunk_ids)
+        }
     }
 
     fn statistics(&self) -> Statistics {
@@ -280,13 +316,10 @@ impl<C: PartitionChunk + 'static> Deduplicater<C> {
 
     /// The IOx scan process needs to deduplicate data if there are duplicates. Hence it will look
     /// like this. In this example, there are 4 chunks.
-    ///  . Chunks 1 and 2 overlap and need to get deduplicated. This includes these main steps:
-    ///     i. Read/scan/steam the chunk: IOxReadFilterNode.
-    ///     ii. Sort each chunk if they are not sorted yet: SortExec.
-    ///     iii. Merge the sorted chunks into one stream: SortPreservingMergeExc.
-    ///     iv. Deduplicate the sorted stream: DeduplicateExec
-    ///  . Chunk 3 does not overlap with others but has duplicates in it self, hence it only needs to get
-    ///      sorted if needed, then deduplicated.
+    ///  . Chunks 1 and 2 overlap and need to get deduplicated. Their data is read in the order
+    ///      it was written, one chunk after the other, and deduplicated: DeduplicateExec.
+    ///  . Chunk 3 does not overlap with others but has duplicates in it self, hence it only needs
+    ///      to get deduplicated.
     ///  . Chunk 4 neither overlaps with other chunks nor has duplicates in itself, hence it does not
     ///      need any extra besides chunk reading.
     /// The final UnionExec on top is to union the streams below. If there is only one stream, UnionExec
@@ -306,29 +339,15 @@ impl<C: PartitionChunk + 'static> Deduplicater<C> {
     ///               └─────────────────┘                └─────────────────┘   │    (Chunk 4)    │
     ///                        ▲                                  ▲            └─────────────────┘
     ///                        │                                  │
-    ///            ┌───────────────────────┐                      │
-    ///            │SortPreservingMergeExec│                      │
-    ///            └───────────────────────┘                      │
-    ///                       ▲                                   |
-    ///                       │                                   |
-    ///           ┌───────────┴───────────┐                       │
-    ///           │                       │                       │
-    ///  ┌─────────────────┐     ┌─────────────────┐    ┌─────────────────┐
-    ///  │    SortExec     │     │    SortExec     │    │    SortExec     │
-    ///  │   (optional)    │     │   (optional)    │    │   (optional)    │
-    ///  └─────────────────┘     └─────────────────┘    └─────────────────┘
-    ///           ▲                       ▲                      ▲
-    ///           │                       │                      │
-    ///           │                       │                      │
-    ///  ┌─────────────────┐     ┌─────────────────┐    ┌─────────────────┐
-    ///  │IOxReadFilterNode│     │IOxReadFilterNode│    │IOxReadFilterNode│
-    ///  │    (Chunk 1)    │     │    (Chunk 2)    │    │    (Chunk 3)    │
-    ///  └─────────────────┘     └─────────────────┘    └─────────────────┘
+    ///               ┌─────────────────┐                ┌─────────────────┐
+    ///               │IOxReadFilterNode│                │IOxReadFilterNode│
+    ///               │ (Chunks 1 and 2)│                │    (Chunk 3)    │
+    ///               └─────────────────┘                └─────────────────┘
     ///```
-
     fn build_scan_plan(
         &mut self,
         table_name: Arc<str>,
+        table_schema: &Schema,
         schema: ArrowSchemaRef,
         chunks: Vec<Arc<C>>,
         predicate: Predicate,
@@ -336,15 +355,6 @@ impl<C: PartitionChunk + 'static> Deduplicater<C> {
         // find overlapped chunks and put them into the right group
         self.split_overlapped_chunks(chunks.to_vec())?;
 
-        // TEMP until the rest of this module's code is complete:
-        // merge all plans into the same
-        self.no_duplicates_chunks
-            .append(&mut self.in_chunk_duplicates_chunks);
-        for mut group in &mut self.overlapped_chunks_set {
-            self.no_duplicates_chunks.append(&mut group);
-        }
-        self.overlapped_chunks_set.clear();
-
         // Building plans
         let mut plans = vec![];
         if self.no_duplicates() {
@@ -361,6 +371,7 @@ impl<C: PartitionChunk + 'static> Deduplicater<C> {
             for overlapped_chunks in self.overlapped_chunks_set.to_vec() {
                 plans.push(Self::build_deduplicate_plan_for_overlapped_chunks(
                     Arc::clone(&table_name),
+                    table_schema,
                     Arc::clone(&schema),
                     overlapped_chunks.to_owned(),
                     predicate.clone(),
@@ -371,6 +382,7 @@ impl<C: PartitionChunk + 'static> Deduplicater<C> {
             for chunk_with_duplicates in self.in_chunk_duplicates_chunks.to_vec() {
                 plans.push(Self::build_deduplicate_plan_for_chunk_with_duplicates(
                     Arc::clone(&table_name),
+                    table_schema,
                     Arc::clone(&schema),
                     chunk_with_duplicates.to_owned(),
                     predicate.clone(),
@@ -388,16 +400,11 @@ impl<C: PartitionChunk + 'static> Deduplicater<C> {
             }
         }
 
-        let final_plan = plans.remove(0);
-
-        // TODO
-        // There are still plan, add UnionExec
-        if !plans.is_empty() {
-            // final_plan = union_plan
-            panic!("Unexpected error: There should be only one output for scan plan, but there were: {:#?}", plans);
+        if plans.len() == 1 {
+            Ok(plans.remove(0))
+        } else {
+            Ok(Arc::new(UnionExec::new(plans)))
         }
-
-        Ok(final_plan)
     }
 
     /// discover overlaps and split them into three groups:
@@ -435,39 +442,24 @@ impl<C: PartitionChunk + 'static> Deduplicater<C> {
     ///               └─────────────────┘
     ///                        ▲
     ///                        │
-    ///            ┌───────────────────────┐
-    ///            │SortPreservingMergeExec│
-    ///            └───────────────────────┘
-    ///                       ▲
-    ///                       │
-    ///           ┌───────────┴───────────┐
-    ///           │                       │
-    ///  ┌─────────────────┐        ┌─────────────────┐
-    ///  │    SortExec     │ ...    │    SortExec     │
-    ///  │   (optional)    │        │   (optional)    │
-    ///  └─────────────────┘        └─────────────────┘
-    ///           ▲                          ▲
-    ///           │          ...             │
-    ///           │                          │
-    ///  ┌─────────────────┐        ┌─────────────────┐
-    ///  │IOxReadFilterNode│        │IOxReadFilterNode│
-    ///  │    (Chunk 1)    │ ...    │    (Chunk n)    │
-    ///  └─────────────────┘        └─────────────────┘
+    ///               ┌─────────────────┐
+    ///               │IOxReadFilterNode│
+    ///               │ (Chunks 1 .. n) │
+    ///               └─────────────────┘
     ///```
+    ///
+    /// The chunks are read in the order of the last write to their data for
+    /// the data written last to win. Chunk ids can't be used for this as
+    /// compaction creates a chunk with a new id for older data.
     fn build_deduplicate_plan_for_overlapped_chunks(
         table_name: Arc<str>,
+        table_schema: &Schema,
         schema: ArrowSchemaRef,
-        chunks: Vec<Arc<C>>, // These chunks are identified overlapped
+        mut chunks: Vec<Arc<C>>, // These chunks are identified overlapped
         predicate: Predicate,
     ) -> Arc<dyn ExecutionPlan> {
-        // TODO
-        // Currently return just like there are no overlaps, no duplicates
-        Arc::new(IOxReadFilterNode::new(
-            Arc::clone(&table_name),
-            schema,
-            chunks,
-            predicate,
-        ))
+        chunks.sort_by_key(|chunk| (chunk.time_of_last_write(), chunk.id()));
+        Self::build_deduplicate_plan(table_name, table_schema, schema, chunks, predicate)
     }
 
     /// Return deduplicate plan for a given chunk with duplicates
@@ -479,31 +471,94 @@ impl<C: PartitionChunk + 'static> Deduplicater<C> {
     ///                        ▲
     ///                        │
     ///                ┌─────────────────┐
-    ///                │    SortExec     │
-    ///                │   (optional)    │
-    ///                └─────────────────┘
-    ///                          ▲
-    ///                          │
-    ///                          │
-    ///                ┌─────────────────┐
     ///                │IOxReadFilterNode│
     ///                │    (Chunk)      │
     ///                └─────────────────┘
     ///```
     fn build_deduplicate_plan_for_chunk_with_duplicates(
         table_name: Arc<str>,
+        table_schema: &Schema,
         schema: ArrowSchemaRef,
         chunk: Arc<C>, // This chunk is identified having duplicates
         predicate: Predicate,
     ) -> Arc<dyn ExecutionPlan> {
-        //     // TODO
-        //     // Currently return just like there are no overlaps, no duplicates
-        Arc::new(IOxReadFilterNode::new(
-            Arc::clone(&table_name),
-            schema,
-            vec![chunk],
+        Self::build_deduplicate_plan(table_name, table_schema, schema, vec![chunk], predicate)
+    }
+
+    /// Return a DeduplicateExec reading `chunks` one after the other
+    ///
+    /// The primary key columns are read even if they are not part of
+    /// `schema`, and only the predicates on primary key columns are pushed
+    /// into the scan: filtering on fields before deduplicating could
+    /// return a row that was overwritten by a filtered out one.
+    ///
+    /// If all the chunks are sorted by the same columns, they are read
+    /// through a SortedMergeExec so that the deduplicated rows are sorted
+    /// by these columns as well.
+    fn build_deduplicate_plan(
+        table_name: Arc<str>,
+        table_schema: &Schema,
+        schema: ArrowSchemaRef,
+        chunks: Vec<Arc<C>>,
+        predicate: Predicate,
+    ) -> Arc<dyn ExecutionPlan> {
+        let tag_columns: Vec<String> = table_schema
+            .tags_iter()
+            .map(|field| field.name().to_string())
+            .collect();
+
+        let mut scan_fields = schema.fields().to_vec();
+        for field in table_schema.tags_iter().chain(table_schema.time_iter()) {
+            if schema.index_of(field.name()).is_err() {
+                scan_fields.push(field.clone());
+            }
+        }
+        let scan_schema = Arc::new(ArrowSchema::new(scan_fields));
+
+        let primary_key: HashSet<String> = table_schema
+            .tags_iter()
+            .chain(table_schema.time_iter())
+            .map(|field| field.name().to_string())
+            .collect();
+        let mut predicate = predicate;
+        predicate.exprs.retain(|expr| {
+            let mut columns = HashSet::new();
+            expr_to_column_names(expr, &mut columns).is_ok() && columns.is_subset(&primary_key)
+        });
+
+        let sort_key = common_sort_key(&chunks);
+        let multiple_chunks = chunks.len() > 1;
+
+        // The tags a chunk doesn't have are null in all its rows, so the
+        // rows of a primary key are next to each other if the chunks are
+        // sorted by all the tags they have and the time
+        let primary_key_sorted = sort_key.as_ref().map_or(false, |sort_key| {
+            sort_key.iter().any(|name| name == TIME_COLUMN_NAME)
+                && chunks.iter().all(|chunk| {
+                    chunk.table_schema(Selection::All).map_or(false, |schema| {
+                        schema
+                            .tags_iter()
+                            .all(|field| sort_key.contains(field.name()))
+                    })
+                })
+        });
+
+        let mut input: Arc<dyn ExecutionPlan> = Arc::new(IOxReadFilterNode::new(
+            table_name,
+            scan_schema,
+            chunks,
             predicate,
-        ))
+        ));
+        if let Some(sort_key) = &sort_key {
+            if multiple_chunks {
+                input = Arc::new(SortedMergeExec::new(input, sort_key.clone()));
+            }
+        }
+
+        Arc::new(
+            DeduplicateExec::new(input, tag_columns, sort_key, schema)
+                .with_primary_key_sorted(primary_key_sorted),
+        )
     }
 
     /// Return the simplest IOx scan plan of a given chunk which is IOxReadFilterNode
@@ -549,6 +604,41 @@ impl<C: PartitionChunk + 'static> Deduplicater<C> {
     }
 }
 
+/// Returns the columns all of `chunks` are sorted by, if they are all
+/// sorted by the same columns
+fn common_sort_key<C: PartitionChunk>(chunks: &[Arc<C>]) -> Option<Vec<String>> {
+    let sort_key = chunks.first()?.sort_key()?;
+    chunks
+        .iter()
+        .all(|chunk| chunk.sort_key() == Some(sort_key))
+        .then(|| sort_key.to_vec())
+}
+
+/// Returns `plan` with `pruned_chunk_ids` recorded on all its
+/// `IOxReadFilterNode`s
+fn with_pruned_chunk_ids<C: PartitionChunk + 'static>(
+    plan: Arc<dyn ExecutionPlan>,
+    pruned_chunk_ids: &[u32],
+) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+    if let Some(node) = plan.as_any().downcast_ref::<IOxReadFilterNode<C>>() {
+        return Ok(Arc::new(
+            node.with_pruned_chunk_ids(pruned_chunk_ids.to_vec()),
+        ));
+    }
+
+    let children = plan
+        .children()
+        .into_iter()
+        .map(|child| with_pruned_chunk_ids::<C>(child, pruned_chunk_ids))
+        .collect::<DataFusionResult<Vec<_>>>()?;
+
+    if children.is_empty() {
+        Ok(plan)
+    } else {
+        plan.with_new_children(children)
+    }
+}
+
 #[derive(Debug)]
 /// A pruner that does not do pruning (suitable if no additional pruning is possible)
 struct NoOpPruner {}
@@ -561,6 +651,7 @@ impl<C: PartitionChunk> ChunkPruner<C> for NoOpPruner {
 #[cfg(test)]
 mod test {
     use crate::test::TestChunk;
+    use datafusion::physical_plan::displayable;
 
     use super::*;
 
@@ -599,6 +690,122 @@ mod test {
         assert_eq!(chunk_ids(&deduplicator.no_duplicates_chunks), "1");
     }
 
+    #[test]
+    fn scan_reports_pruned_chunks() {
+        let c1 = Arc::new(TestChunk::new(1).with_tag_column_with_stats("t", "tag1", "a", "b"));
+        let c2 = Arc::new(TestChunk::new(2).with_tag_column_with_stats("t", "tag1", "c", "d"));
+
+        let mut builder = ProviderBuilder::new("t");
+        builder.add_pruner(Arc::new(PruneChunk(2)));
+        for chunk in vec![c1, c2] {
+            let schema = chunk.table_schema(Selection::All).unwrap();
+            builder.add_chunk(chunk, schema).unwrap();
+        }
+        let provider = builder.build().unwrap();
+
+        let plan = provider.scan(&None, 1000, &[], None).unwrap();
+        assert_eq!(
+            displayable(plan.as_ref()).indent().to_string(),
+            "IOxReadFilterNode: table_name=t, chunks=1 predicate=Predicate pruned_chunks=[2]\n"
+        );
+    }
+
+    #[test]
+    fn scan_deduplicates_chunks() {
+        // c1: no overlaps
+        let c1 = Arc::new(TestChunk::new(1).with_tag_column_with_stats("t", "tag1", "a", "b"));
+
+        // c2 and c3 overlap, c3 is read before c2 as it was created first
+        let c2 = Arc::new(TestChunk::new(3).with_tag_column_with_stats("t", "tag1", "c", "d"));
+        let c3 = Arc::new(TestChunk::new(2).with_tag_column_with_stats("t", "tag1", "c", "d"));
+
+        // c4: self overlap
+        let c4 = Arc::new(
+            TestChunk::new(4)
+                .with_tag_column_with_stats("t", "tag1", "e", "f")
+                .with_may_contain_pk_duplicates(true),
+        );
+
+        let mut builder = ProviderBuilder::new("t");
+        builder.add_no_op_pruner();
+        for chunk in vec![c1, c2, c3, c4] {
+            let schema = chunk.table_schema(Selection::All).unwrap();
+            builder.add_chunk(chunk, schema).unwrap();
+        }
+        let provider = builder.build().unwrap();
+
+        let plan = provider.scan(&None, 1000, &[], None).unwrap();
+        let expected = "ExecutionPlan(PlaceHolder)\
+            \n  DeduplicateExec: tags=[tag1]\
+            \n    IOxReadFilterNode: table_name=t, chunks=2 predicate=Predicate\
+            \n  DeduplicateExec: tags=[tag1]\
+            \n    IOxReadFilterNode: table_name=t, chunks=1 predicate=Predicate\
+            \n  IOxReadFilterNode: table_name=t, chunks=1 predicate=Predicate\n";
+        assert_eq!(displayable(plan.as_ref()).indent().to_string(), expected);
+
+        let union = plan.children();
+        let read_overlapped = union[0].children();
+        let read_overlapped = read_overlapped[0]
+            .as_any()
+            .downcast_ref::<IOxReadFilterNode<TestChunk>>()
+            .unwrap();
+        assert_eq!(chunk_ids(read_overlapped.chunks()), "2, 3");
+    }
+
+    #[test]
+    fn scan_merges_sorted_chunks() {
+        // c1 and c2 overlap and are sorted by the same columns
+        let c1 = Arc::new(
+            TestChunk::new(1)
+                .with_tag_column_with_stats("t", "tag1", "a", "b")
+                .with_sort_key(&["tag1", "time"]),
+        );
+        let c2 = Arc::new(
+            TestChunk::new(2)
+                .with_tag_column_with_stats("t", "tag1", "a", "b")
+                .with_sort_key(&["tag1", "time"]),
+        );
+
+        // c3 and c4 overlap but are sorted differently
+        let c3 = Arc::new(
+            TestChunk::new(3)
+                .with_tag_column_with_stats("t", "tag1", "c", "d")
+                .with_sort_key(&["tag1", "time"]),
+        );
+        let c4 = Arc::new(TestChunk::new(4).with_tag_column_with_stats("t", "tag1", "c", "d"));
+
+        let mut builder = ProviderBuilder::new("t");
+        builder.add_no_op_pruner();
+        for chunk in vec![c1, c2, c3, c4] {
+            let schema = chunk.table_schema(Selection::All).unwrap();
+            builder.add_chunk(chunk, schema).unwrap();
+        }
+        let provider = builder.build().unwrap();
+
+        let plan = provider.scan(&None, 1000, &[], None).unwrap();
+        let expected = "ExecutionPlan(PlaceHolder)\
+            \n  DeduplicateExec: tags=[tag1] sort_key=[tag1, time] streaming\
+            \n    SortedMergeExec: sort_columns=[tag1, time]\
+            \n      IOxReadFilterNode: table_name=t, chunks=2 predicate=Predicate\
+            \n  DeduplicateExec: tags=[tag1]\
+            \n    IOxReadFilterNode: table_name=t, chunks=2 predicate=Predicate\n";
+        assert_eq!(displayable(plan.as_ref()).indent().to_string(), expected);
+    }
+
+    /// Prunes the chunk with the given id
+    #[derive(Debug)]
+    struct PruneChunk(u32);
+
+    impl ChunkPruner<TestChunk> for PruneChunk {
+        fn prune_chunks(
+            &self,
+            chunks: Vec<Arc<TestChunk>>,
+            _predicate: &Predicate,
+        ) -> Vec<Arc<TestChunk>> {
+            chunks.into_iter().filter(|c| c.id() != self.0).collect()
+        }
+    }
+
     fn chunk_ids(group: &[Arc<TestChunk>]) -> String {
         let ids = group.iter().map(|c| c.id().to_string()).collect::<Vec<_>>();
         ids.join(", ")
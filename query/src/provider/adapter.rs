@@ -6,6 +6,7 @@ use std::task::{Context, Poll};
 
 use arrow::{
     array::new_null_array,
+    compute::cast,
     datatypes::{DataType, SchemaRef},
     error::Result as ArrowResult,
     record_batch::RecordBatch,
@@ -46,6 +47,10 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 ///
 /// For example:
 ///
+/// Numeric columns of the input are cast to the numeric type of the output
+/// column, if they differ, as chunks may store a column with a type that
+/// a type coercion rule of the database converts to another type.
+///
 /// If a table had schema with Cols A, B, and C, but the chunk (input)
 /// stream only produced record batches with columns A and C, this
 /// stream would append a column of B / nulls to each record batch
@@ -110,7 +115,16 @@ impl SchemaAdapterStream {
                     .map(|(idx, _)| idx);
 
                 if let Some(input_field_index) = input_field_index {
-                    ColumnMapping::FromInput(input_field_index)
+                    let input_type = input_schema.field(input_field_index).data_type();
+                    let output_type = output_field.data_type();
+                    if input_type != output_type
+                        && is_numeric(input_type)
+                        && is_numeric(output_type)
+                    {
+                        ColumnMapping::CastInput(input_field_index, output_type.clone())
+                    } else {
+                        ColumnMapping::FromInput(input_field_index)
+                    }
                 } else {
                     ColumnMapping::MakeNull(output_field.data_type().clone())
                 }
@@ -152,6 +166,7 @@ impl SchemaAdapterStream {
                         .fail();
                     }
                 }
+                ColumnMapping::CastInput(_, _) => {}
                 ColumnMapping::MakeNull(data_type) => {
                     let output_field = output_schema.field(output_index);
                     if data_type != output_field.data_type() {
@@ -179,10 +194,17 @@ impl SchemaAdapterStream {
             .mappings
             .iter()
             .map(|mapping| match mapping {
-                ColumnMapping::FromInput(input_index) => Arc::clone(&batch.column(*input_index)),
-                ColumnMapping::MakeNull(data_type) => new_null_array(data_type, batch.num_rows()),
+                ColumnMapping::FromInput(input_index) => {
+                    Ok(Arc::clone(&batch.column(*input_index)))
+                }
+                ColumnMapping::CastInput(input_index, data_type) => {
+                    cast(batch.column(*input_index), data_type)
+                }
+                ColumnMapping::MakeNull(data_type) => {
+                    Ok(new_null_array(data_type, batch.num_rows()))
+                }
             })
-            .collect::<Vec<_>>();
+            .collect::<ArrowResult<Vec<_>>>()?;
 
         RecordBatch::try_new(Arc::clone(&self.output_schema), output_columns)
     }
@@ -216,17 +238,28 @@ impl Stream for SchemaAdapterStream {
 enum ColumnMapping {
     /// Output column is found at <index> column of the input schema
     FromInput(usize),
+    /// Output column is the <index> column of the input schema, cast to the
+    /// specified numeric type
+    CastInput(usize, DataType),
     /// Output colum should be synthesized with nulls of the specified type
     MakeNull(DataType),
 }
 
+/// Returns true for the types of numeric field columns
+fn is_numeric(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Int64 | DataType::UInt64 | DataType::Float64
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
     use super::*;
     use arrow::{
-        array::{ArrayRef, Int32Array, StringArray},
+        array::{ArrayRef, Int32Array, Int64Array, StringArray},
         datatypes::{Field, Schema},
         record_batch::RecordBatch,
     };
@@ -353,6 +386,27 @@ mod tests {
 
     // input has different column types than desired output
 
+    #[tokio::test]
+    async fn input_has_different_numeric_type() {
+        let a: ArrayRef = Arc::new(Int64Array::from(vec![1, 2, 3]));
+        let batch = RecordBatch::try_from_iter(vec![("a", a)]).unwrap();
+
+        // integers are cast to the float type of the output
+        let output_schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Float64, true)]));
+        let input_stream = SizedRecordBatchStream::new(batch.schema(), vec![Arc::new(batch)]);
+        let adapter_stream =
+            SchemaAdapterStream::try_new(Box::pin(input_stream), output_schema).unwrap();
+
+        let output = collect(Box::pin(adapter_stream))
+            .await
+            .expect("Running plan");
+        assert_eq!(output[0].column(0).data_type(), &DataType::Float64);
+        let expected = vec![
+            "+---+", "| a |", "+---+", "| 1 |", "| 2 |", "| 3 |", "+---+",
+        ];
+        assert_batches_eq!(&expected, &output);
+    }
+
     fn make_batch() -> RecordBatch {
         let col_a = Arc::new(Int32Array::from(vec![1, 2, 3]));
         let col_b = Arc::new(Int32Array::from(vec![4, 5, 6]));
@@ -0,0 +1,590 @@
+//! Implementation of a DataFusion PhysicalPlan node that removes the rows
+//! with duplicated primary keys from the data of one or more chunks
+
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, StringArray, TimestampNanosecondArray, UInt32Array},
+    compute::{cast, concat, take},
+    datatypes::{DataType, SchemaRef},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use datafusion::{
+    error::{DataFusionError, Result},
+    physical_plan::{
+        common::{self, SizedRecordBatchStream},
+        DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+    },
+};
+use datafusion_util::AdapterStream;
+use futures::StreamExt;
+use internal_types::schema::TIME_COLUMN_NAME;
+
+/// Deduplicates the rows of all partitions of its input, which must be
+/// ordered the way their data was written (oldest first), such as the
+/// chunks of an `IOxReadFilterNode`.
+///
+/// The rows with the same tag values and timestamp are merged into one,
+/// with the value of each field written last, the way writing to an
+/// existing point only updates the fields the write has values for. The
+/// input has to contain all the tag columns and the time column, but
+/// only the columns of `schema` are output.
+///
+/// The rows are output in the order their primary key first appears in
+/// the input, so if the input is sorted by `sort_key` so is the output.
+///
+/// If the input is a single partition sorted by all the columns of the
+/// primary key, the rows of each primary key are next to each other and
+/// are deduplicated batch by batch as they are read. Otherwise all the
+/// input is read before deduplicating it.
+#[derive(Debug)]
+pub(crate) struct DeduplicateExec {
+    input: Arc<dyn ExecutionPlan>,
+    /// The tag columns of the primary key
+    tag_columns: Vec<String>,
+    /// The columns the input is sorted by, if it is sorted
+    sort_key: Option<Vec<String>>,
+    /// If the rows of each primary key are next to each other in the input
+    primary_key_sorted: bool,
+    /// The output schema, a subset of the columns of the input
+    schema: SchemaRef,
+}
+
+impl DeduplicateExec {
+    pub fn new(
+        input: Arc<dyn ExecutionPlan>,
+        tag_columns: Vec<String>,
+        sort_key: Option<Vec<String>>,
+        schema: SchemaRef,
+    ) -> Self {
+        Self {
+            input,
+            tag_columns,
+            sort_key,
+            primary_key_sorted: false,
+            schema,
+        }
+    }
+
+    /// Marks the input as sorted by all the columns of the primary key, the
+    /// tag columns that are not null and the time column
+    pub fn with_primary_key_sorted(mut self, primary_key_sorted: bool) -> Self {
+        self.primary_key_sorted = primary_key_sorted;
+        self
+    }
+
+    /// The columns the output is sorted by, if it is sorted
+    pub fn sort_key(&self) -> Option<&[String]> {
+        self.sort_key.as_deref()
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for DeduplicateExec {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![Arc::clone(&self.input)]
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(Self {
+                input: Arc::clone(&children[0]),
+                tag_columns: self.tag_columns.clone(),
+                sort_key: self.sort_key.clone(),
+                primary_key_sorted: self.primary_key_sorted,
+                schema: Arc::clone(&self.schema),
+            })),
+            _ => Err(DataFusionError::Internal(
+                "DeduplicateExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "DeduplicateExec invalid partition {}",
+                partition
+            )));
+        }
+
+        let deduplicator = Deduplicator {
+            input_schema: self.input.schema(),
+            tag_columns: self.tag_columns.clone(),
+            schema: self.schema(),
+        };
+
+        let num_partitions = self.input.output_partitioning().partition_count();
+        if self.primary_key_sorted && num_partitions == 1 {
+            let input = self.input.execute(0).await?;
+            return Ok(Box::pin(deduplicator.into_stream(input)));
+        }
+
+        // Read the input partitions one after the other so that the rows
+        // stay in the order they were written
+        let mut input_batches = vec![];
+        for input_partition in 0..num_partitions {
+            let stream = self.input.execute(input_partition).await?;
+            input_batches.extend(common::collect(stream).await?);
+        }
+
+        let batches = match deduplicator.deduplicate(&input_batches)? {
+            Some(batch) => vec![Arc::new(batch)],
+            None => vec![],
+        };
+
+        Ok(Box::pin(SizedRecordBatchStream::new(
+            self.schema(),
+            batches,
+        )))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "DeduplicateExec: tags=[{}]", self.tag_columns.join(", "))?;
+                if let Some(sort_key) = &self.sort_key {
+                    write!(f, " sort_key=[{}]", sort_key.join(", "))?;
+                }
+                if self.primary_key_sorted {
+                    write!(f, " streaming")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Deduplicates batches of the input of a `DeduplicateExec`
+#[derive(Debug)]
+struct Deduplicator {
+    input_schema: SchemaRef,
+    /// The tag columns of the primary key
+    tag_columns: Vec<String>,
+    /// The output schema
+    schema: SchemaRef,
+}
+
+impl Deduplicator {
+    /// Returns a row for each primary key of `batches`, or None if there
+    /// are no rows
+    fn deduplicate(&self, batches: &[RecordBatch]) -> Result<Option<RecordBatch>> {
+        let num_rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+        if num_rows == 0 {
+            return Ok(None);
+        }
+
+        let (row_keys, num_keys) = self.primary_keys(batches)?;
+
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let column = self.concat_column(batches, field.name())?;
+                let indices = last_non_null_rows(column.as_ref(), &row_keys, num_keys);
+                let column = take(column.as_ref(), &indices, None)?;
+                Ok(cast(&column, field.data_type())?)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Some(RecordBatch::try_new(
+            Arc::clone(&self.schema),
+            columns,
+        )?))
+    }
+
+    /// Numbers the primary keys of the rows of `batches`, see `primary_keys`
+    fn primary_keys(&self, batches: &[RecordBatch]) -> Result<(Vec<usize>, usize)> {
+        let tags = self
+            .tag_columns
+            .iter()
+            .map(|name| self.concat_column(batches, name))
+            .collect::<Result<Vec<_>>>()?;
+        let time = self.concat_column(batches, TIME_COLUMN_NAME)?;
+
+        primary_keys(&tags, &time)
+    }
+
+    /// Concatenates the column `name` of `batches`. Tags are concatenated
+    /// as plain strings as the dictionaries of the batches differ.
+    fn concat_column(&self, batches: &[RecordBatch], name: &str) -> Result<ArrayRef> {
+        let idx = self.input_schema.index_of(name)?;
+        let data_type = value_type(self.input_schema.field(idx).data_type());
+        let arrays = batches
+            .iter()
+            .map(|batch| cast(batch.column(idx), &data_type))
+            .collect::<Result<Vec<_>, _>>()?;
+        let arrays: Vec<&dyn Array> = arrays.iter().map(|array| array.as_ref()).collect();
+        Ok(concat(&arrays)?)
+    }
+
+    /// Returns the deduplicated rows of `input`, in which the rows of each
+    /// primary key are next to each other, batch by batch
+    ///
+    /// The rows of the last primary key of an input batch may continue in
+    /// the next batch, so they are held back until the next batch is read.
+    fn into_stream(self, input: SendableRecordBatchStream) -> AdapterStream {
+        let schema = Arc::clone(&self.schema);
+        let state = DeduplicateState {
+            deduplicator: self,
+            input,
+            pending: vec![],
+        };
+
+        // Stop at the first error, or once the input is exhausted
+        let stream = futures::stream::unfold(Some(state), |state| async move {
+            let mut state = state?;
+            match state.next_batch().await {
+                Ok(Some(batch)) => Some((Ok(batch), Some(state))),
+                Ok(None) => None,
+                Err(e) => Some((Err(ArrowError::ExternalError(Box::new(e))), None)),
+            }
+        });
+
+        AdapterStream::new(schema, stream)
+    }
+}
+
+/// The state of a streaming `DeduplicateExec`
+struct DeduplicateState {
+    deduplicator: Deduplicator,
+    input: SendableRecordBatchStream,
+    /// The input rows read so far that may have the same primary key as
+    /// rows of the next input batch
+    pending: Vec<RecordBatch>,
+}
+
+impl DeduplicateState {
+    /// Returns the deduplicated rows of the next input batch and the
+    /// pending rows before it, or None once all rows have been output
+    async fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        loop {
+            let batch = match self.input.next().await {
+                Some(batch) => batch?,
+                None => {
+                    let pending = std::mem::take(&mut self.pending);
+                    return self.deduplicator.deduplicate(&pending);
+                }
+            };
+
+            let num_rows = batch.num_rows();
+            if num_rows == 0 {
+                continue;
+            }
+
+            // Find where the rows of the last primary key of the batch start
+            let (row_keys, _) = self.deduplicator.primary_keys(&[batch.clone()])?;
+            let last_key = row_keys[num_rows - 1];
+            let last_key_start = row_keys
+                .iter()
+                .rposition(|key| *key != last_key)
+                .map_or(0, |row| row + 1);
+
+            if last_key_start == 0 {
+                // the whole batch may continue in the next one
+                self.pending.push(batch);
+                continue;
+            }
+
+            let mut batches = std::mem::take(&mut self.pending);
+            batches.push(slice(&batch, 0, last_key_start)?);
+            self.pending
+                .push(slice(&batch, last_key_start, num_rows - last_key_start)?);
+
+            if let Some(batch) = self.deduplicator.deduplicate(&batches)? {
+                return Ok(Some(batch));
+            }
+        }
+    }
+}
+
+/// Returns `length` rows of `batch` starting at `offset`
+fn slice(batch: &RecordBatch, offset: usize, length: usize) -> Result<RecordBatch> {
+    let columns = batch
+        .columns()
+        .iter()
+        .map(|column| column.slice(offset, length))
+        .collect();
+    Ok(RecordBatch::try_new(batch.schema(), columns)?)
+}
+
+/// Returns the type the values of a column of type `data_type` are
+/// deduplicated, merged and sorted as: dictionaries as their values
+pub(crate) fn value_type(data_type: &DataType) -> DataType {
+    match data_type {
+        DataType::Dictionary(_, value_type) => value_type.as_ref().clone(),
+        data_type => data_type.clone(),
+    }
+}
+
+/// Numbers the distinct combinations of tag values and timestamp in the
+/// order they first appear, returning the number of each row and how
+/// many there are
+fn primary_keys(tags: &[ArrayRef], time: &ArrayRef) -> Result<(Vec<usize>, usize)> {
+    let tags = tags
+        .iter()
+        .map(|tag| {
+            tag.as_any().downcast_ref::<StringArray>().ok_or_else(|| {
+                DataFusionError::Internal("DeduplicateExec tags must be strings".to_string())
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let time = time
+        .as_any()
+        .downcast_ref::<TimestampNanosecondArray>()
+        .ok_or_else(|| {
+            DataFusionError::Internal(
+                "DeduplicateExec time must be a nanosecond timestamp".to_string(),
+            )
+        })?;
+
+    let mut keys: HashMap<(Vec<Option<&str>>, Option<i64>), usize> = HashMap::new();
+    let row_keys: Vec<usize> = (0..time.len())
+        .map(|row| {
+            let tag_values = tags
+                .iter()
+                .map(|tag| {
+                    if tag.is_null(row) {
+                        None
+                    } else {
+                        Some(tag.value(row))
+                    }
+                })
+                .collect();
+            let time = if time.is_null(row) {
+                None
+            } else {
+                Some(time.value(row))
+            };
+            let num_keys = keys.len();
+            *keys.entry((tag_values, time)).or_insert(num_keys)
+        })
+        .collect();
+
+    Ok((row_keys, keys.len()))
+}
+
+/// Returns, for each primary key, the index of the last row with a value
+/// in `column`, or null if all its rows are null
+fn last_non_null_rows(column: &dyn Array, row_keys: &[usize], num_keys: usize) -> UInt32Array {
+    let mut indices = vec![None; num_keys];
+    for (row, &key) in row_keys.iter().enumerate() {
+        if column.is_valid(row) {
+            indices[key] = Some(row as u32);
+        }
+    }
+    indices.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{DictionaryArray, Float64Array},
+        datatypes::{Field, Int32Type, Schema, TimeUnit},
+    };
+    use arrow_util::assert_batches_eq;
+    use datafusion::physical_plan::{collect, memory::MemoryExec};
+
+    use super::*;
+
+    fn input_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new(
+                "host",
+                DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+                true,
+            ),
+            Field::new("usage", DataType::Float64, true),
+            Field::new(
+                TIME_COLUMN_NAME,
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+        ]))
+    }
+
+    fn make_batch(
+        hosts: Vec<Option<&str>>,
+        usage: Vec<Option<f64>>,
+        time: Vec<i64>,
+    ) -> RecordBatch {
+        RecordBatch::try_new(
+            input_schema(),
+            vec![
+                Arc::new(hosts.into_iter().collect::<DictionaryArray<Int32Type>>()),
+                Arc::new(Float64Array::from(usage)),
+                Arc::new(TimestampNanosecondArray::from(time)),
+            ],
+        )
+        .unwrap()
+    }
+
+    async fn run(partitions: Vec<Vec<RecordBatch>>, schema: SchemaRef) -> Vec<RecordBatch> {
+        let input = Arc::new(MemoryExec::try_new(&partitions, input_schema(), None).unwrap());
+        let exec = Arc::new(DeduplicateExec::new(
+            input,
+            vec!["host".into()],
+            None,
+            schema,
+        ));
+        collect(exec).await.unwrap()
+    }
+
+    async fn run_sorted(batches: Vec<RecordBatch>) -> Vec<RecordBatch> {
+        let input = Arc::new(MemoryExec::try_new(&[batches], input_schema(), None).unwrap());
+        let exec = Arc::new(
+            DeduplicateExec::new(
+                input,
+                vec!["host".into()],
+                Some(vec!["host".into(), TIME_COLUMN_NAME.into()]),
+                input_schema(),
+            )
+            .with_primary_key_sorted(true),
+        );
+        collect(exec).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn deduplicate_across_partitions() {
+        let older = make_batch(
+            vec![Some("a"), Some("b"), Some("a")],
+            vec![Some(1.0), Some(2.0), Some(3.0)],
+            vec![10, 10, 20],
+        );
+        let newer = make_batch(
+            vec![Some("b"), Some("c"), None],
+            vec![Some(20.0), Some(30.0), Some(40.0)],
+            vec![10, 10, 10],
+        );
+
+        let results = run(vec![vec![older], vec![newer]], input_schema()).await;
+
+        let expected = vec![
+            "+------+-------+-------------------------------+",
+            "| host | usage | time                          |",
+            "+------+-------+-------------------------------+",
+            "| a    | 1     | 1970-01-01 00:00:00.000000010 |",
+            "| b    | 20    | 1970-01-01 00:00:00.000000010 |",
+            "| a    | 3     | 1970-01-01 00:00:00.000000020 |",
+            "| c    | 30    | 1970-01-01 00:00:00.000000010 |",
+            "|      | 40    | 1970-01-01 00:00:00.000000010 |",
+            "+------+-------+-------------------------------+",
+        ];
+        assert_batches_eq!(&expected, &results);
+    }
+
+    #[tokio::test]
+    async fn deduplicate_within_partition() {
+        let batch_1 = make_batch(
+            vec![Some("a"), Some("a")],
+            vec![Some(1.0), Some(2.0)],
+            vec![10, 10],
+        );
+        let batch_2 = make_batch(vec![Some("a"), None], vec![None, Some(4.0)], vec![10, 10]);
+
+        let results = run(vec![vec![batch_1, batch_2]], input_schema()).await;
+
+        // null values don't overwrite earlier writes
+        let expected = vec![
+            "+------+-------+-------------------------------+",
+            "| host | usage | time                          |",
+            "+------+-------+-------------------------------+",
+            "| a    | 2     | 1970-01-01 00:00:00.000000010 |",
+            "|      | 4     | 1970-01-01 00:00:00.000000010 |",
+            "+------+-------+-------------------------------+",
+        ];
+        assert_batches_eq!(&expected, &results);
+    }
+
+    #[tokio::test]
+    async fn deduplicate_projected() {
+        let batch = make_batch(
+            vec![Some("a"), Some("b"), Some("a")],
+            vec![Some(1.0), Some(2.0), Some(3.0)],
+            vec![10, 10, 10],
+        );
+        let schema = Arc::new(Schema::new(vec![input_schema().field(1).clone()]));
+
+        let results = run(vec![vec![batch]], schema).await;
+
+        let expected = vec![
+            "+-------+",
+            "| usage |",
+            "+-------+",
+            "| 3     |",
+            "| 2     |",
+            "+-------+",
+        ];
+        assert_batches_eq!(&expected, &results);
+    }
+
+    #[tokio::test]
+    async fn deduplicate_sorted_streaming() {
+        // the rows of host b at time 10 are split across three batches
+        let batch_1 = make_batch(
+            vec![None, Some("a"), Some("b")],
+            vec![Some(1.0), Some(2.0), Some(3.0)],
+            vec![10, 10, 10],
+        );
+        let batch_2 = make_batch(vec![Some("b")], vec![None], vec![10]);
+        let batch_3 = make_batch(
+            vec![Some("b"), Some("b"), Some("c")],
+            vec![Some(4.0), Some(5.0), Some(6.0)],
+            vec![10, 20, 10],
+        );
+
+        let results = run_sorted(vec![batch_1, batch_2, batch_3]).await;
+
+        // the rows are output as soon as no later batch can have their
+        // primary key
+        assert_eq!(
+            results
+                .iter()
+                .map(|batch| batch.num_rows())
+                .collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        let expected = vec![
+            "+------+-------+-------------------------------+",
+            "| host | usage | time                          |",
+            "+------+-------+-------------------------------+",
+            "|      | 1     | 1970-01-01 00:00:00.000000010 |",
+            "| a    | 2     | 1970-01-01 00:00:00.000000010 |",
+            "| b    | 4     | 1970-01-01 00:00:00.000000010 |",
+            "| b    | 5     | 1970-01-01 00:00:00.000000020 |",
+            "| c    | 6     | 1970-01-01 00:00:00.000000010 |",
+            "+------+-------+-------------------------------+",
+        ];
+        assert_batches_eq!(&expected, &results);
+    }
+
+    #[tokio::test]
+    async fn deduplicate_sorted_streaming_empty() {
+        let results = run_sorted(vec![]).await;
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn deduplicate_empty() {
+        let results = run(vec![vec![], vec![]], input_schema()).await;
+        assert!(results.is_empty());
+    }
+}
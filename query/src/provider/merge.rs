@@ -0,0 +1,624 @@
+//! Implementation of a DataFusion PhysicalPlan node that merges partitions
+//! that are already sorted, and of the pass that uses it in place of sorts
+
+use std::{cmp::Ordering, fmt, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, Int64Array, StringArray, UInt32Array},
+    compute::{cast, concat, take},
+    datatypes::{DataType, SchemaRef},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use datafusion::{
+    error::{DataFusionError, Result},
+    physical_plan::{
+        expressions::{Column, PhysicalSortExpr},
+        filter::FilterExec,
+        merge::MergeExec,
+        projection::ProjectionExec,
+        sort::SortExec,
+        union::UnionExec,
+        DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+    },
+};
+
+use datafusion_util::AdapterStream;
+use futures::StreamExt;
+
+use crate::PartitionChunk;
+
+use super::{
+    deduplicate::{value_type, DeduplicateExec},
+    physical::IOxReadFilterNode,
+};
+
+/// Merges the partitions of its input, each of which must already be
+/// sorted by `sort_columns` (ascending, nulls first), into a single sorted
+/// partition.
+///
+/// Merging costs a comparison per input partition and output row instead
+/// of the full sort a `SortExec` does, and only holds the current batch of
+/// each input partition in memory. Rows that compare equal are output in
+/// the order of their partitions, so the order in which the data of the
+/// chunks of an `IOxReadFilterNode` was written is kept.
+///
+/// The sort columns have to be tags, or the time or other integer columns.
+#[derive(Debug)]
+pub(crate) struct SortedMergeExec {
+    input: Arc<dyn ExecutionPlan>,
+    sort_columns: Vec<String>,
+}
+
+impl SortedMergeExec {
+    pub fn new(input: Arc<dyn ExecutionPlan>, sort_columns: Vec<String>) -> Self {
+        Self {
+            input,
+            sort_columns,
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for SortedMergeExec {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![Arc::clone(&self.input)]
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(Self::new(
+                Arc::clone(&children[0]),
+                self.sort_columns.clone(),
+            ))),
+            _ => Err(DataFusionError::Internal(
+                "SortedMergeExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "SortedMergeExec invalid partition {}",
+                partition
+            )));
+        }
+
+        let schema = self.schema();
+        let sort_indices = self
+            .sort_columns
+            .iter()
+            .map(|name| schema.index_of(name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut inputs = vec![];
+        for input_partition in 0..self.input.output_partitioning().partition_count() {
+            inputs.push(self.input.execute(input_partition).await?);
+        }
+
+        let merger = Merger {
+            schema: Arc::clone(&schema),
+            sort_indices,
+            cursors: inputs.iter().map(|_| None).collect(),
+            inputs,
+            started: false,
+        };
+
+        // Stop at the first error
+        let stream = futures::stream::unfold(Some(merger), |merger| async move {
+            let mut merger = merger?;
+            match merger.next_batch().await {
+                Ok(Some(batch)) => Some((Ok(batch), Some(merger))),
+                Ok(None) => None,
+                Err(e) => Some((Err(ArrowError::ExternalError(Box::new(e))), None)),
+            }
+        });
+
+        Ok(Box::pin(AdapterStream::new(schema, stream)))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(
+                    f,
+                    "SortedMergeExec: sort_columns=[{}]",
+                    self.sort_columns.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// The number of rows of the batches output by `SortedMergeExec`
+const OUTPUT_BATCH_SIZE: usize = 8192;
+
+/// The state of a `SortedMergeExec` stream, holding the next unmerged row of
+/// each input partition
+struct Merger {
+    schema: SchemaRef,
+    /// The indices of the sort columns
+    sort_indices: Vec<usize>,
+    inputs: Vec<SendableRecordBatchStream>,
+    /// The batch of each input partition its next row is in, or None once
+    /// all the rows of the partition have been merged
+    cursors: Vec<Option<Cursor>>,
+    /// If the first batch of each input partition has been read
+    started: bool,
+}
+
+/// The next unmerged row of an input partition
+struct Cursor {
+    batch: RecordBatch,
+    /// The sort columns of `batch`
+    sort_columns: Vec<SortColumn>,
+    row: usize,
+}
+
+impl Merger {
+    /// Returns the next up to OUTPUT_BATCH_SIZE rows in sorted order, or
+    /// None once all rows have been merged
+    async fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        if !self.started {
+            for partition in 0..self.inputs.len() {
+                self.advance(partition).await?;
+            }
+            self.started = true;
+        }
+
+        // The rows to output, as the index of a batch of `batches` and a row
+        // of the batch
+        let mut batches: Vec<RecordBatch> = vec![];
+        let mut batch_indices: Vec<Option<usize>> = vec![None; self.cursors.len()];
+        let mut rows = vec![];
+
+        while rows.len() < OUTPUT_BATCH_SIZE {
+            // Take the smallest next row of the partitions, the one of the
+            // first partition if several are equal
+            let mut next: Option<usize> = None;
+            for (partition, cursor) in self.cursors.iter().enumerate() {
+                let cursor = match cursor {
+                    Some(cursor) => cursor,
+                    None => continue,
+                };
+                next = match next {
+                    Some(smallest)
+                        if self.cursors[smallest]
+                            .as_ref()
+                            .expect("smallest is a cursor")
+                            .compare(cursor)
+                            != Ordering::Greater =>
+                    {
+                        Some(smallest)
+                    }
+                    _ => Some(partition),
+                };
+            }
+            let partition = match next {
+                Some(partition) => partition,
+                None => break,
+            };
+
+            let cursor = self.cursors[partition].as_mut().expect("next is a cursor");
+            let batch_index = *batch_indices[partition].get_or_insert_with(|| {
+                batches.push(cursor.batch.clone());
+                batches.len() - 1
+            });
+            rows.push((batch_index, cursor.row));
+
+            cursor.row += 1;
+            if cursor.row == cursor.batch.num_rows() {
+                batch_indices[partition] = None;
+                self.advance(partition).await?;
+            }
+        }
+
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        // Concatenate the batches column by column, and take the output
+        // rows. Dictionaries are concatenated as their values as the
+        // dictionaries of the batches differ.
+        let mut offsets = Vec::with_capacity(batches.len());
+        let mut num_rows = 0;
+        for batch in &batches {
+            offsets.push(num_rows);
+            num_rows += batch.num_rows();
+        }
+        let indices: UInt32Array = rows
+            .iter()
+            .map(|(batch_index, row)| Some((offsets[*batch_index] + row) as u32))
+            .collect();
+
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                let data_type = value_type(field.data_type());
+                let arrays = batches
+                    .iter()
+                    .map(|batch| cast(batch.column(idx), &data_type))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let arrays: Vec<&dyn Array> = arrays.iter().map(|array| array.as_ref()).collect();
+                let column = take(concat(&arrays)?.as_ref(), &indices, None)?;
+                Ok(cast(&column, field.data_type())?)
+            })
+            .collect::<Result<Vec<ArrayRef>>>()?;
+
+        Ok(Some(RecordBatch::try_new(
+            Arc::clone(&self.schema),
+            columns,
+        )?))
+    }
+
+    /// Moves the cursor of `partition` to the next non-empty batch of the
+    /// partition, if any
+    async fn advance(&mut self, partition: usize) -> Result<()> {
+        self.cursors[partition] = None;
+        while let Some(batch) = self.inputs[partition].next().await {
+            let batch = batch?;
+            if batch.num_rows() == 0 {
+                continue;
+            }
+            let sort_columns = self
+                .sort_indices
+                .iter()
+                .map(|idx| SortColumn::try_new(batch.column(*idx)))
+                .collect::<Result<Vec<_>>>()?;
+            self.cursors[partition] = Some(Cursor {
+                batch,
+                sort_columns,
+                row: 0,
+            });
+            break;
+        }
+        Ok(())
+    }
+}
+
+impl Cursor {
+    /// Compares the next row of this cursor with the one of `other`
+    fn compare(&self, other: &Self) -> Ordering {
+        for (left, right) in self.sort_columns.iter().zip(&other.sort_columns) {
+            let ordering = left.compare(self.row, right, other.row);
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+/// The values of a sort column, which is either a tag, compared as strings,
+/// or the time or another integer column
+enum SortColumn {
+    Utf8(ArrayRef),
+    Int64(ArrayRef),
+}
+
+impl SortColumn {
+    fn try_new(array: &ArrayRef) -> Result<Self> {
+        match value_type(array.data_type()) {
+            DataType::Utf8 => Ok(Self::Utf8(cast(array, &DataType::Utf8)?)),
+            DataType::Timestamp(_, _) | DataType::Int64 | DataType::Int32 => {
+                Ok(Self::Int64(cast(array, &DataType::Int64)?))
+            }
+            data_type => Err(DataFusionError::Internal(format!(
+                "SortedMergeExec can't merge columns of type {:?}",
+                data_type
+            ))),
+        }
+    }
+
+    /// Compares the value of `row` with the value of `other_row` of
+    /// `other`, nulls first
+    fn compare(&self, row: usize, other: &Self, other_row: usize) -> Ordering {
+        match (self, other) {
+            (Self::Utf8(left), Self::Utf8(right)) => {
+                compare_values::<StringArray, _>(left, row, right, other_row, |l, r| {
+                    l.value(row).cmp(r.value(other_row))
+                })
+            }
+            (Self::Int64(left), Self::Int64(right)) => {
+                compare_values::<Int64Array, _>(left, row, right, other_row, |l, r| {
+                    l.value(row).cmp(&r.value(other_row))
+                })
+            }
+            // the same column has the same type in all partitions
+            (Self::Utf8(_), Self::Int64(_)) => Ordering::Less,
+            (Self::Int64(_), Self::Utf8(_)) => Ordering::Greater,
+        }
+    }
+}
+
+/// Compares two values of arrays of type `A` using `compare` unless any of
+/// them is null, nulls sorting first
+fn compare_values<A: Array + 'static, F: Fn(&A, &A) -> Ordering>(
+    left: &ArrayRef,
+    row: usize,
+    right: &ArrayRef,
+    other_row: usize,
+    compare: F,
+) -> Ordering {
+    match (left.is_valid(row), right.is_valid(other_row)) {
+        (true, true) => {
+            let left = left.as_any().downcast_ref::<A>().expect("cast in try_new");
+            let right = right.as_any().downcast_ref::<A>().expect("cast in try_new");
+            compare(left, right)
+        }
+        (left_valid, right_valid) => left_valid.cmp(&right_valid),
+    }
+}
+
+/// Returns `plan` with the sorts of data whose partitions are each already
+/// sorted, such as the data of chunks of type `C` that have the same sort
+/// key, replaced by a [`SortedMergeExec`]
+pub(crate) fn merge_sorted_partitions<C: PartitionChunk + 'static>(
+    plan: Arc<dyn ExecutionPlan>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let children = plan
+        .children()
+        .into_iter()
+        .map(merge_sorted_partitions::<C>)
+        .collect::<Result<Vec<_>>>()?;
+    let plan = if children.is_empty() {
+        plan
+    } else {
+        plan.with_new_children(children)?
+    };
+
+    let sort_columns = match plan.as_any().downcast_ref::<SortExec>() {
+        Some(sort) => sort_columns(sort.expr()),
+        None => None,
+    };
+    let sort_columns = match sort_columns {
+        Some(sort_columns) => sort_columns,
+        None => return Ok(plan),
+    };
+
+    // The input of a sort is usually merged into a single partition
+    // first, in no particular order
+    let input = Arc::clone(&plan.children()[0]);
+    let input = if input.as_any().is::<MergeExec>() {
+        Arc::clone(&input.children()[0])
+    } else {
+        input
+    };
+
+    if partitions_sorted_by::<C>(input.as_ref(), &sort_columns) {
+        Ok(Arc::new(SortedMergeExec::new(input, sort_columns)))
+    } else {
+        Ok(plan)
+    }
+}
+
+/// Returns the names of the columns sorted by `sort_exprs`, if they
+/// only sort columns in the order `SortedMergeExec` merges them
+fn sort_columns(sort_exprs: &[PhysicalSortExpr]) -> Option<Vec<String>> {
+    sort_exprs
+        .iter()
+        .map(|sort_expr| {
+            let column = sort_expr.expr.as_any().downcast_ref::<Column>()?;
+            if sort_expr.options.descending || !sort_expr.options.nulls_first {
+                return None;
+            }
+            Some(column.name().to_string())
+        })
+        .collect()
+}
+
+/// Returns true if each partition of the output of `plan` is known to be
+/// sorted by `sort_columns`
+fn partitions_sorted_by<C: PartitionChunk + 'static>(
+    plan: &dyn ExecutionPlan,
+    sort_columns: &[String],
+) -> bool {
+    let any = plan.as_any();
+    if let Some(scan) = any.downcast_ref::<IOxReadFilterNode<C>>() {
+        scan.chunks()
+            .iter()
+            .all(|chunk| is_sort_key_prefix(sort_columns, chunk.sort_key()))
+    } else if let Some(deduplicate) = any.downcast_ref::<DeduplicateExec>() {
+        is_sort_key_prefix(sort_columns, deduplicate.sort_key())
+    } else if let Some(merge) = any.downcast_ref::<SortedMergeExec>() {
+        is_sort_key_prefix(sort_columns, Some(&merge.sort_columns))
+    } else if any.is::<UnionExec>() || any.is::<FilterExec>() {
+        plan.children()
+            .iter()
+            .all(|child| partitions_sorted_by::<C>(child.as_ref(), sort_columns))
+    } else if let Some(projection) = any.downcast_ref::<ProjectionExec>() {
+        // the sorted columns have to be output as they are
+        let passed_through = sort_columns.iter().all(|name| {
+            projection.expr().iter().any(|(expr, alias)| {
+                alias == name
+                    && expr
+                        .as_any()
+                        .downcast_ref::<Column>()
+                        .map_or(false, |column| column.name() == name)
+            })
+        });
+        passed_through && partitions_sorted_by::<C>(plan.children()[0].as_ref(), sort_columns)
+    } else {
+        false
+    }
+}
+
+/// Returns true if data sorted by `sort_key` is sorted by `sort_columns`
+fn is_sort_key_prefix(sort_columns: &[String], sort_key: Option<&[String]>) -> bool {
+    match sort_key {
+        Some(sort_key) => {
+            sort_columns.len() <= sort_key.len()
+                && sort_columns.iter().zip(sort_key).all(|(a, b)| a == b)
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{DictionaryArray, Float64Array, TimestampNanosecondArray},
+        datatypes::{DataType, Field, Int32Type, Schema, TimeUnit},
+    };
+    use arrow_util::assert_batches_eq;
+    use datafusion::physical_plan::{collect, displayable, expressions::col, memory::MemoryExec};
+    use internal_types::schema::TIME_COLUMN_NAME;
+
+    use crate::{predicate::Predicate, test::TestChunk};
+
+    use super::*;
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new(
+                "host",
+                DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+                true,
+            ),
+            Field::new("usage", DataType::Float64, true),
+            Field::new(
+                TIME_COLUMN_NAME,
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+        ]))
+    }
+
+    fn make_batch(
+        hosts: Vec<Option<&str>>,
+        usage: Vec<Option<f64>>,
+        time: Vec<i64>,
+    ) -> RecordBatch {
+        RecordBatch::try_new(
+            schema(),
+            vec![
+                Arc::new(hosts.into_iter().collect::<DictionaryArray<Int32Type>>()),
+                Arc::new(Float64Array::from(usage)),
+                Arc::new(TimestampNanosecondArray::from(time)),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn merge_sorted_partitions_in_order() {
+        let partition_1 = vec![
+            make_batch(
+                vec![None, Some("a")],
+                vec![Some(1.0), Some(2.0)],
+                vec![10, 10],
+            ),
+            make_batch(vec![Some("c")], vec![Some(3.0)], vec![10]),
+        ];
+        let partition_2 = vec![make_batch(
+            vec![None, Some("a"), Some("b")],
+            vec![Some(10.0), Some(20.0), None],
+            vec![20, 10, 30],
+        )];
+
+        let input =
+            Arc::new(MemoryExec::try_new(&[partition_1, partition_2], schema(), None).unwrap());
+        let exec = Arc::new(SortedMergeExec::new(
+            input,
+            vec!["host".into(), TIME_COLUMN_NAME.into()],
+        ));
+        let results = collect(exec).await.unwrap();
+
+        // the row of the first partition comes first when equal
+        let expected = vec![
+            "+------+-------+-------------------------------+",
+            "| host | usage | time                          |",
+            "+------+-------+-------------------------------+",
+            "|      | 1     | 1970-01-01 00:00:00.000000010 |",
+            "|      | 10    | 1970-01-01 00:00:00.000000020 |",
+            "| a    | 2     | 1970-01-01 00:00:00.000000010 |",
+            "| a    | 20    | 1970-01-01 00:00:00.000000010 |",
+            "| b    |       | 1970-01-01 00:00:00.000000030 |",
+            "| c    | 3     | 1970-01-01 00:00:00.000000010 |",
+            "+------+-------+-------------------------------+",
+        ];
+        assert_batches_eq!(&expected, &results);
+    }
+
+    #[tokio::test]
+    async fn merge_empty() {
+        let input = Arc::new(MemoryExec::try_new(&[vec![], vec![]], schema(), None).unwrap());
+        let exec = Arc::new(SortedMergeExec::new(input, vec!["host".into()]));
+        let results = collect(exec).await.unwrap();
+        assert!(results.is_empty());
+    }
+
+    fn sort_plan(chunks: Vec<TestChunk>, sort_columns: &[&str]) -> Arc<dyn ExecutionPlan> {
+        let chunks: Vec<_> = chunks.into_iter().map(Arc::new).collect();
+        let scan = Arc::new(IOxReadFilterNode::new(
+            Arc::from("t"),
+            schema(),
+            chunks,
+            Predicate::default(),
+        ));
+        let sort_exprs = sort_columns
+            .iter()
+            .map(|name| PhysicalSortExpr {
+                expr: col(name),
+                options: Default::default(),
+            })
+            .collect();
+        let input = Arc::new(MergeExec::new(scan));
+        Arc::new(SortExec::try_new(sort_exprs, input).unwrap())
+    }
+
+    fn plan_to_string(plan: Arc<dyn ExecutionPlan>) -> String {
+        let plan = merge_sorted_partitions::<TestChunk>(plan).unwrap();
+        displayable(plan.as_ref()).indent().to_string()
+    }
+
+    #[test]
+    fn sort_of_sorted_chunks_is_merged() {
+        let chunks = vec![
+            TestChunk::new(1).with_sort_key(&["host", "time"]),
+            TestChunk::new(2).with_sort_key(&["host", "time"]),
+        ];
+
+        assert_eq!(
+            plan_to_string(sort_plan(chunks, &["host"])),
+            "SortedMergeExec: sort_columns=[host]\
+             \n  IOxReadFilterNode: table_name=t, chunks=2 predicate=Predicate\n"
+        );
+    }
+
+    #[test]
+    fn sort_of_unsorted_chunks_is_kept() {
+        // the sort columns aren't a prefix of the sort key
+        let chunks = vec![TestChunk::new(1).with_sort_key(&["host", "time"])];
+        let plan = plan_to_string(sort_plan(chunks, &["time"]));
+        assert!(plan.starts_with("SortExec"), "{}", plan);
+
+        // one of the chunks isn't known to be sorted
+        let chunks = vec![
+            TestChunk::new(1).with_sort_key(&["host", "time"]),
+            TestChunk::new(2),
+        ];
+        let plan = plan_to_string(sort_plan(chunks, &["host"]));
+        assert!(plan.starts_with("SortExec"), "{}", plan);
+    }
+}
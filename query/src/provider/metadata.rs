@@ -0,0 +1,582 @@
+//! Answers simple aggregates, such as `SELECT count(*), min(time),
+//! max(time) FROM t`, from the statistics of chunks rather than by
+//! scanning their data
+
+use std::sync::Arc;
+
+use arrow::{compute::cast, datatypes::Schema as ArrowSchema, record_batch::RecordBatch};
+use chrono::DateTime;
+use data_types::partition_metadata::Statistics;
+use datafusion::{
+    error::Result as DataFusionResult,
+    logical_plan::{col, when, DFSchemaRef, Expr, LogicalPlan, LogicalPlanBuilder, Operator},
+    optimizer::utils::from_plan,
+    physical_plan::aggregates::AggregateFunction,
+    scalar::ScalarValue,
+};
+use internal_types::schema::TIME_COLUMN_NAME;
+use observability_deps::tracing::debug;
+
+use crate::{predicate::PredicateBuilder, pruning::Prunable, PartitionChunk};
+
+use super::{ChunkTableProvider, Deduplicater};
+
+/// Replaces the aggregates of `plan` that can be computed from the
+/// statistics of its chunks with their result.
+///
+/// This is possible for aggregates without `GROUP BY` that only compute
+/// `count(*)`, `min(time)` and `max(time)` of a table, filtered at most by
+/// a time range. Chunks that may contain duplicates or deleted rows, or
+/// that are only partly within the time range, are still scanned, and the
+/// aggregates of their rows combined with those answered from statistics.
+pub fn plan_metadata_aggregates<C: PartitionChunk + 'static>(
+    plan: LogicalPlan,
+) -> DataFusionResult<LogicalPlan> {
+    Ok(metadata_aggregates::<C>(&plan)?.unwrap_or(plan))
+}
+
+/// Returns a copy of `plan` with aggregates answered from statistics, or
+/// `None` if none of them can be
+fn metadata_aggregates<C: PartitionChunk + 'static>(
+    plan: &LogicalPlan,
+) -> DataFusionResult<Option<LogicalPlan>> {
+    if let LogicalPlan::Aggregate {
+        input,
+        group_expr,
+        aggr_expr,
+        schema,
+    } = plan
+    {
+        if group_expr.is_empty() {
+            if let Some(new_plan) = answer_aggregate::<C>(input, aggr_expr, schema)? {
+                return Ok(Some(new_plan));
+            }
+        }
+    }
+
+    let new_inputs = plan
+        .inputs()
+        .into_iter()
+        .map(metadata_aggregates::<C>)
+        .collect::<DataFusionResult<Vec<_>>>()?;
+
+    if new_inputs.iter().all(Option::is_none) {
+        return Ok(None);
+    }
+
+    let inputs = new_inputs
+        .into_iter()
+        .zip(plan.inputs())
+        .map(|(new_input, input)| new_input.unwrap_or_else(|| input.clone()))
+        .collect::<Vec<_>>();
+    from_plan(plan, &plan.expressions(), &inputs).map(Some)
+}
+
+/// Returns a plan that computes `aggr_expr` over `input` using chunk
+/// statistics, or `None` if that is not possible
+fn answer_aggregate<C: PartitionChunk + 'static>(
+    input: &LogicalPlan,
+    aggr_expr: &[Expr],
+    schema: &DFSchemaRef,
+) -> DataFusionResult<Option<LogicalPlan>> {
+    let aggregates = match aggr_expr
+        .iter()
+        .map(MetadataAggregate::try_new)
+        .collect::<Option<Vec<_>>>()
+    {
+        Some(aggregates) => aggregates,
+        None => return Ok(None),
+    };
+
+    let (filter, scan) = match input {
+        LogicalPlan::Filter { predicate, input } => (Some(predicate), input.as_ref()),
+        input => (None, input),
+    };
+
+    let (table_name, source, projection) = match scan {
+        LogicalPlan::TableScan {
+            table_name,
+            source,
+            projection,
+            limit: None,
+            ..
+        } => (table_name, source, projection),
+        _ => return Ok(None),
+    };
+
+    let provider = match source.as_any().downcast_ref::<ChunkTableProvider<C>>() {
+        Some(provider) => provider,
+        None => return Ok(None),
+    };
+
+    let mut exprs = vec![];
+    if let Some(filter) = filter {
+        PredicateBuilder::split_members(filter, &mut exprs);
+    }
+    let range = match exprs
+        .iter()
+        .try_fold(TimeRange::default(), |range, expr| range.restrict(expr))
+    {
+        Some(range) => range,
+        None => return Ok(None),
+    };
+
+    let predicate = PredicateBuilder::default()
+        .add_pushdown_exprs(&exprs)
+        .build();
+    let chunks = provider
+        .chunk_pruner
+        .prune_chunks(provider.chunks.to_vec(), &predicate);
+    let num_chunks = chunks.len();
+
+    // Only chunks that neither overlap other chunks nor contain duplicates
+    // themselves can be answered from their statistics
+    let mut deduplicater = Deduplicater::new();
+    deduplicater.split_overlapped_chunks(chunks)?;
+
+    let mut scan_chunks = deduplicater.in_chunk_duplicates_chunks;
+    scan_chunks.extend(deduplicater.overlapped_chunks_set.into_iter().flatten());
+
+    let mut totals = Totals::default();
+    for chunk in deduplicater.no_duplicates_chunks {
+        let stats = match chunk.summary().column(TIME_COLUMN_NAME).map(|c| &c.stats) {
+            Some(Statistics::I64(stats)) => stats,
+            _ => {
+                scan_chunks.push(chunk);
+                continue;
+            }
+        };
+
+        let (min, max) = match (stats.min, stats.max) {
+            (Some(min), Some(max)) => (min, max),
+            // the chunk has no rows
+            _ => continue,
+        };
+
+        if !range.overlaps(min, max) {
+            continue;
+        }
+
+        if chunk.has_delete_predicates() || !range.contains(min, max) {
+            scan_chunks.push(chunk);
+            continue;
+        }
+
+        totals.add(stats.count, min, max);
+    }
+
+    if totals.count == 0 {
+        // nothing to gain over scanning all chunks
+        return Ok(None);
+    }
+
+    debug!(
+        %table_name,
+        num_chunks,
+        num_scanned_chunks = scan_chunks.len(),
+        "Answering aggregate from chunk statistics"
+    );
+
+    if scan_chunks.is_empty() {
+        let arrow_schema: ArrowSchema = schema.as_ref().clone().into();
+        let columns = aggregates
+            .iter()
+            .zip(arrow_schema.fields())
+            .map(|(aggregate, field)| cast(&aggregate.value(&totals).to_array(), field.data_type()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let batch = RecordBatch::try_new(Arc::new(arrow_schema), columns)?;
+        let batch_schema = batch.schema();
+
+        let plan =
+            LogicalPlanBuilder::scan_memory(vec![vec![batch]], batch_schema, None)?.build()?;
+        return Ok(Some(plan));
+    }
+
+    // Aggregate the rows of the chunks that must be scanned, and then
+    // combine the results with the totals from statistics
+    let scan_provider = ChunkTableProvider {
+        table_name: Arc::clone(&provider.table_name),
+        iox_schema: provider.iox_schema.clone(),
+        chunk_pruner: Arc::clone(&provider.chunk_pruner),
+        chunks: scan_chunks,
+    };
+
+    let mut builder =
+        LogicalPlanBuilder::scan(table_name, Arc::new(scan_provider), projection.clone())?;
+    if let Some(filter) = filter {
+        builder = builder.filter(filter.clone())?;
+    }
+    let scanned = builder.aggregate(vec![], aggr_expr.to_vec())?.build()?;
+
+    let combined = aggregates
+        .iter()
+        .zip(schema.fields())
+        .map(|(aggregate, field)| {
+            Ok(aggregate
+                .combine(col(field.name()), &totals)?
+                .alias(field.name()))
+        })
+        .collect::<DataFusionResult<Vec<_>>>()?;
+
+    let plan = LogicalPlanBuilder::from(&scanned)
+        .project(combined)?
+        .build()?;
+    Ok(Some(plan))
+}
+
+/// An aggregate that can be computed from chunk statistics
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MetadataAggregate {
+    /// `count(*)` or `count(time)`
+    Count,
+    /// `min(time)`
+    MinTime,
+    /// `max(time)`
+    MaxTime,
+}
+
+impl MetadataAggregate {
+    /// Returns the aggregate computed by `expr`, if it can be computed from
+    /// statistics
+    fn try_new(expr: &Expr) -> Option<Self> {
+        match expr {
+            Expr::Alias(inner, _) => Self::try_new(inner),
+            Expr::AggregateFunction {
+                fun,
+                args,
+                distinct: false,
+            } => match (fun, args.as_slice()) {
+                (AggregateFunction::Count, [Expr::Literal(value)]) if !value.is_null() => {
+                    Some(Self::Count)
+                }
+                (AggregateFunction::Count, [Expr::Column(name)]) if name == TIME_COLUMN_NAME => {
+                    Some(Self::Count)
+                }
+                (AggregateFunction::Min, [Expr::Column(name)]) if name == TIME_COLUMN_NAME => {
+                    Some(Self::MinTime)
+                }
+                (AggregateFunction::Max, [Expr::Column(name)]) if name == TIME_COLUMN_NAME => {
+                    Some(Self::MaxTime)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The value of the aggregate over the chunks described by `totals`
+    fn value(&self, totals: &Totals) -> ScalarValue {
+        match self {
+            Self::Count => ScalarValue::UInt64(Some(totals.count)),
+            Self::MinTime => ScalarValue::TimestampNanosecond(totals.min),
+            Self::MaxTime => ScalarValue::TimestampNanosecond(totals.max),
+        }
+    }
+
+    /// Returns an expression combining `scanned`, the value of the
+    /// aggregate over the rows that were scanned, with `totals`
+    fn combine(&self, scanned: Expr, totals: &Totals) -> DataFusionResult<Expr> {
+        let value = Expr::Literal(self.value(totals));
+        match self {
+            Self::Count => Ok(scanned + value),
+            Self::MinTime => when(
+                scanned
+                    .clone()
+                    .is_null()
+                    .or(scanned.clone().gt(value.clone())),
+                value,
+            )
+            .otherwise(scanned),
+            Self::MaxTime => when(
+                scanned
+                    .clone()
+                    .is_null()
+                    .or(scanned.clone().lt(value.clone())),
+                value,
+            )
+            .otherwise(scanned),
+        }
+    }
+}
+
+/// The count, min and max times of the chunks answered from statistics
+#[derive(Debug, Default)]
+struct Totals {
+    count: u64,
+    min: Option<i64>,
+    max: Option<i64>,
+}
+
+impl Totals {
+    fn add(&mut self, count: u64, min: i64, max: i64) {
+        self.count += count;
+        self.min = Some(self.min.map_or(min, |m| m.min(min)));
+        self.max = Some(self.max.map_or(max, |m| m.max(max)));
+    }
+}
+
+/// The inclusive range of times `[first, last]` selected by a filter
+#[derive(Debug, Clone, Copy)]
+struct TimeRange {
+    first: i64,
+    last: i64,
+}
+
+impl Default for TimeRange {
+    fn default() -> Self {
+        Self {
+            first: i64::MIN,
+            last: i64::MAX,
+        }
+    }
+}
+
+impl TimeRange {
+    /// Restricts the range to the times selected by `expr`, or returns
+    /// `None` if `expr` does not compare the time column with a constant
+    fn restrict(self, expr: &Expr) -> Option<Self> {
+        let (op, value) = match expr {
+            Expr::BinaryExpr { left, op, right } => match (left.as_ref(), right.as_ref()) {
+                (Expr::Column(name), Expr::Literal(value)) if name == TIME_COLUMN_NAME => {
+                    (*op, value)
+                }
+                (Expr::Literal(value), Expr::Column(name)) if name == TIME_COLUMN_NAME => {
+                    (swap_operands(*op)?, value)
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        let value = match value {
+            ScalarValue::TimestampNanosecond(Some(value)) => *value,
+            ScalarValue::Utf8(Some(value)) => {
+                DateTime::parse_from_rfc3339(value).ok()?.timestamp_nanos()
+            }
+            _ => return None,
+        };
+
+        let Self { first, last } = self;
+        let (first, last) = match op {
+            Operator::Eq => (first.max(value), last.min(value)),
+            Operator::Gt => (first.max(value.checked_add(1)?), last),
+            Operator::GtEq => (first.max(value), last),
+            Operator::Lt => (first, last.min(value.checked_sub(1)?)),
+            Operator::LtEq => (first, last.min(value)),
+            _ => return None,
+        };
+        Some(Self { first, last })
+    }
+
+    /// Returns true if some time in `[min, max]` is within the range
+    fn overlaps(&self, min: i64, max: i64) -> bool {
+        min <= self.last && max >= self.first
+    }
+
+    /// Returns true if all times in `[min, max]` are within the range
+    fn contains(&self, min: i64, max: i64) -> bool {
+        min >= self.first && max <= self.last
+    }
+}
+
+/// Returns the operator `op2` such that `a op b` is `b op2 a`
+fn swap_operands(op: Operator) -> Option<Operator> {
+    match op {
+        Operator::Eq => Some(Operator::Eq),
+        Operator::Gt => Some(Operator::Lt),
+        Operator::GtEq => Some(Operator::LtEq),
+        Operator::Lt => Some(Operator::Gt),
+        Operator::LtEq => Some(Operator::GtEq),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{exec::Executor, provider::ProviderBuilder, test::TestChunk};
+    use arrow_util::assert_batches_eq;
+    use datafusion::logical_plan::{count, lit, max, min};
+    use internal_types::selection::Selection;
+
+    #[tokio::test]
+    async fn answer_from_statistics() {
+        let plan = make_plan(
+            vec![make_chunk(1, 100, 200, 3), make_chunk(2, 300, 400, 2)],
+            None,
+        );
+        let plan = plan_metadata_aggregates::<TestChunk>(plan).unwrap();
+
+        assert!(scanned_chunk_ids(&plan).is_empty());
+
+        let batches = Executor::new(1).run_logical_plan(plan).await.unwrap();
+        let expected = vec![
+            "+-----------------+-------------------------------+-------------------------------+",
+            "| COUNT(UInt8(1)) | MIN(time)                     | MAX(time)                     |",
+            "+-----------------+-------------------------------+-------------------------------+",
+            "| 5               | 1970-01-01 00:00:00.000000100 | 1970-01-01 00:00:00.000000400 |",
+            "+-----------------+-------------------------------+-------------------------------+",
+        ];
+        assert_batches_eq!(&expected, &batches);
+    }
+
+    #[test]
+    fn scan_chunks_with_duplicates_or_deletes() {
+        let chunks = vec![
+            make_chunk(1, 100, 200, 3),
+            make_chunk(2, 300, 400, 2).with_may_contain_pk_duplicates(true),
+            make_chunk(3, 500, 600, 2).with_delete_predicates(true),
+        ];
+        let plan = plan_metadata_aggregates::<TestChunk>(make_plan(chunks, None)).unwrap();
+
+        assert_eq!(scanned_chunk_ids(&plan), vec![2, 3]);
+    }
+
+    #[test]
+    fn scan_overlapping_chunks() {
+        let chunks = vec![
+            make_chunk(1, 100, 200, 3),
+            make_chunk(2, 300, 400, 2),
+            make_chunk(3, 350, 450, 2),
+        ];
+        let plan = plan_metadata_aggregates::<TestChunk>(make_plan(chunks, None)).unwrap();
+
+        assert_eq!(scanned_chunk_ids(&plan), vec![2, 3]);
+    }
+
+    #[test]
+    fn scan_chunks_partly_in_time_range() {
+        let chunks = vec![
+            make_chunk(1, 100, 200, 3),
+            make_chunk(2, 300, 400, 2),
+            make_chunk(3, 500, 600, 2),
+        ];
+        // chunk 1 is entirely within the range, chunk 2 partly and chunk
+        // 3 not at all
+        let filter = col("time")
+            .gt_eq(timestamp(100))
+            .and(timestamp(350).gt(col("time")));
+        let plan = plan_metadata_aggregates::<TestChunk>(make_plan(chunks, Some(filter))).unwrap();
+
+        assert_eq!(scanned_chunk_ids(&plan), vec![2]);
+    }
+
+    #[test]
+    fn unsupported_queries_are_not_rewritten() {
+        let chunks = || vec![make_chunk(1, 100, 200, 3)];
+
+        // filter on something other than time
+        let filter = col("tag").eq(lit("foo"));
+        let plan =
+            plan_metadata_aggregates::<TestChunk>(make_plan(chunks(), Some(filter))).unwrap();
+        assert_eq!(scanned_chunk_ids(&plan), vec![1]);
+
+        // group by
+        let plan = scan(chunks())
+            .aggregate(vec![col("tag")], vec![count(lit(1u8))])
+            .unwrap()
+            .build()
+            .unwrap();
+        let plan = plan_metadata_aggregates::<TestChunk>(plan).unwrap();
+        assert_eq!(scanned_chunk_ids(&plan), vec![1]);
+
+        // aggregate of another column
+        let plan = scan(chunks())
+            .aggregate(vec![], vec![min(col("tag"))])
+            .unwrap()
+            .build()
+            .unwrap();
+        let plan = plan_metadata_aggregates::<TestChunk>(plan).unwrap();
+        assert_eq!(scanned_chunk_ids(&plan), vec![1]);
+    }
+
+    #[test]
+    fn time_range() {
+        let range = |exprs: Vec<Expr>| {
+            exprs
+                .iter()
+                .try_fold(TimeRange::default(), |range, expr| range.restrict(expr))
+                .map(|range| (range.first, range.last))
+        };
+
+        assert_eq!(range(vec![]), Some((i64::MIN, i64::MAX)));
+        assert_eq!(
+            range(vec![
+                col("time").gt(timestamp(10)),
+                col("time").lt_eq(timestamp(20))
+            ]),
+            Some((11, 20))
+        );
+        assert_eq!(
+            range(vec![
+                timestamp(10).lt_eq(col("time")),
+                timestamp(20).gt(col("time"))
+            ]),
+            Some((10, 19))
+        );
+        assert_eq!(range(vec![col("time").eq(timestamp(10))]), Some((10, 10)));
+        assert_eq!(
+            range(vec![col("time").gt(lit("1970-01-01T00:00:00.000000010Z"))]),
+            Some((11, i64::MAX))
+        );
+        assert_eq!(range(vec![col("time").not_eq(timestamp(10))]), None);
+        assert_eq!(range(vec![col("tag").gt(timestamp(10))]), None);
+    }
+
+    fn timestamp(v: i64) -> Expr {
+        Expr::Literal(ScalarValue::TimestampNanosecond(Some(v)))
+    }
+
+    fn make_chunk(id: u32, min: i64, max: i64, count: u64) -> TestChunk {
+        TestChunk::new(id)
+            .with_tag_column_with_stats("t", "tag", "a", "b")
+            .with_time_column_with_stats("t", min, max, count)
+    }
+
+    /// Returns a builder scanning a table made of `chunks`
+    fn scan(chunks: Vec<TestChunk>) -> LogicalPlanBuilder {
+        let mut builder = ProviderBuilder::new("t");
+        builder.add_no_op_pruner();
+        for chunk in chunks {
+            let schema = chunk.table_schema(Selection::All).unwrap();
+            builder.add_chunk(Arc::new(chunk), schema).unwrap();
+        }
+        let provider = builder.build().unwrap();
+
+        LogicalPlanBuilder::scan("t", Arc::new(provider), None).unwrap()
+    }
+
+    /// Returns a plan computing `count(*)`, `min(time)` and `max(time)`
+    /// over `chunks`
+    fn make_plan(chunks: Vec<TestChunk>, filter: Option<Expr>) -> LogicalPlan {
+        let mut builder = scan(chunks);
+        if let Some(filter) = filter {
+            builder = builder.filter(filter).unwrap();
+        }
+        builder
+            .aggregate(
+                vec![],
+                vec![count(lit(1u8)), min(col("time")), max(col("time"))],
+            )
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    /// Returns the ids of the chunks scanned by `plan`
+    fn scanned_chunk_ids(plan: &LogicalPlan) -> Vec<u32> {
+        let mut ids = match plan {
+            LogicalPlan::TableScan { source, .. } => source
+                .as_any()
+                .downcast_ref::<ChunkTableProvider<TestChunk>>()
+                .map(|provider| provider.chunks.iter().map(|chunk| chunk.id()).collect())
+                .unwrap_or_default(),
+            _ => vec![],
+        };
+        for input in plan.inputs() {
+            ids.extend(scanned_chunk_ids(input));
+        }
+        ids.sort_unstable();
+        ids
+    }
+}
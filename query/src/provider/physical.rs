@@ -1,13 +1,23 @@
 //! Implementation of a DataFusion PhysicalPlan node across partition chunks
 
-use std::{fmt, sync::Arc};
+use std::{
+    fmt,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
 
-use arrow::datatypes::SchemaRef;
+use arrow::{datatypes::SchemaRef, error::Result as ArrowResult, record_batch::RecordBatch};
 use datafusion::{
     error::DataFusionError,
-    physical_plan::{DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream},
+    physical_plan::{
+        DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
+        SendableRecordBatchStream,
+    },
 };
+use futures::Stream;
 use internal_types::{schema::Schema, selection::Selection};
+use observability_deps::tracing::{info_span, Span};
 
 use crate::{predicate::Predicate, PartitionChunk};
 
@@ -24,6 +34,14 @@ pub(crate) struct IOxReadFilterNode<C: PartitionChunk + 'static> {
     schema: SchemaRef,
     chunks: Vec<Arc<C>>,
     predicate: Predicate,
+    /// The ids of the chunks of the table that were pruned from the scan
+    /// because their statistics show no row can pass the predicate
+    pruned_chunk_ids: Vec<u32>,
+    /// The span of the query that planned this scan, which is the parent of
+    /// the per chunk scan spans. It is captured when planning because
+    /// DataFusion reads the partitions on tasks that don't carry the span
+    /// of the query.
+    span: Span,
 }
 
 impl<C: PartitionChunk + 'static> IOxReadFilterNode<C> {
@@ -38,8 +56,27 @@ impl<C: PartitionChunk + 'static> IOxReadFilterNode<C> {
             schema,
             chunks,
             predicate,
+            pruned_chunk_ids: vec![],
+            span: Span::current(),
         }
     }
+
+    /// Returns a copy of this node that reports `pruned_chunk_ids` as pruned
+    pub fn with_pruned_chunk_ids(&self, pruned_chunk_ids: Vec<u32>) -> Self {
+        Self {
+            table_name: Arc::clone(&self.table_name),
+            schema: Arc::clone(&self.schema),
+            chunks: self.chunks.to_vec(),
+            predicate: self.predicate.clone(),
+            pruned_chunk_ids,
+            span: self.span.clone(),
+        }
+    }
+
+    /// The chunks read by this node, one per output partition
+    pub fn chunks(&self) -> &[Arc<C>] {
+        &self.chunks
+    }
 }
 
 #[async_trait]
@@ -76,6 +113,8 @@ impl<C: PartitionChunk + 'static> ExecutionPlan for IOxReadFilterNode<C> {
             schema: Arc::clone(&self.schema),
             chunks,
             predicate: self.predicate.clone(),
+            pruned_chunk_ids: self.pruned_chunk_ids.clone(),
+            span: self.span.clone(),
         };
 
         Ok(Arc::new(new_self))
@@ -103,19 +142,31 @@ impl<C: PartitionChunk + 'static> ExecutionPlan for IOxReadFilterNode<C> {
         let selection_cols = restrict_selection(selection_cols, &chunk_table_schema);
         let selection = Selection::Some(&selection_cols);
 
-        let stream = chunk.read_filter(&self.predicate, selection).map_err(|e| {
-            DataFusionError::Execution(format!(
-                "Error creating scan for table {} chunk {}: {}",
-                self.table_name,
-                chunk.id(),
-                e
-            ))
-        })?;
+        let span = info_span!(
+            parent: &self.span,
+            "read_filter",
+            table_name = %self.table_name,
+            chunk_id = chunk.id()
+        );
+
+        let stream = span
+            .in_scope(|| chunk.read_filter(&self.predicate, selection))
+            .map_err(|e| {
+                DataFusionError::Execution(format!(
+                    "Error creating scan for table {} chunk {}: {}",
+                    self.table_name,
+                    chunk.id(),
+                    e
+                ))
+            })?;
 
         let adapter = SchemaAdapterStream::try_new(stream, Arc::clone(&self.schema))
             .map_err(|e| DataFusionError::Internal(e.to_string()))?;
 
-        Ok(Box::pin(adapter))
+        Ok(Box::pin(InstrumentedStream {
+            inner: Box::pin(adapter),
+            span,
+        }))
     }
 
     fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -127,12 +178,38 @@ impl<C: PartitionChunk + 'static> ExecutionPlan for IOxReadFilterNode<C> {
                     self.table_name,
                     self.chunks.len(),
                     self.predicate,
-                )
+                )?;
+                if !self.pruned_chunk_ids.is_empty() {
+                    write!(f, " pruned_chunks={:?}", self.pruned_chunk_ids)?;
+                }
+                Ok(())
             }
         }
     }
 }
 
+/// Reads the batches of a chunk within the span of its scan
+struct InstrumentedStream {
+    inner: SendableRecordBatchStream,
+    span: Span,
+}
+
+impl Stream for InstrumentedStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let span = self.span.clone();
+        let _entered = span.enter();
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl RecordBatchStream for InstrumentedStream {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
+
 /// Removes any columns that are not present in schema, returning a possibly
 /// restricted set of columns
 fn restrict_selection<'a>(
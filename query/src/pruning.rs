@@ -1,10 +1,13 @@
 //! Implementation of statistics based pruning
 use std::sync::Arc;
 
-use arrow::{array::ArrayRef, datatypes::SchemaRef};
+use arrow::{
+    array::ArrayRef,
+    datatypes::{DataType, Schema, SchemaRef},
+};
 use data_types::partition_metadata::{ColumnSummary, Statistics, TableSummary};
 use datafusion::{
-    logical_plan::Expr,
+    logical_plan::{binary_expr, lit, Expr, Operator},
     physical_optimizer::pruning::{PruningPredicate, PruningStatistics},
     scalar::ScalarValue,
 };
@@ -91,39 +94,188 @@ where
     P: Prunable,
     O: PruningObserver<Observed = P>,
 {
-    trace!(?filter_expr, schema=?chunk.schema(), "creating pruning predicate");
-
-    let pruning_predicate = match PruningPredicate::try_new(filter_expr, chunk.schema()) {
-        Ok(p) => p,
-        Err(e) => {
-            observer.could_not_prune_chunk(chunk, "Can not create pruning predicate");
-            trace!(%e, ?filter_expr, "Can not create pruning predicate");
-            return true;
+    match could_pass(chunk, filter_expr) {
+        Ok(true) => true,
+        Ok(false) => {
+            observer.was_pruned(chunk);
+            false
         }
-    };
-
-    let statistics = PrunableStats {
-        summary: chunk.summary(),
-    };
+        Err(reason) => {
+            observer.could_not_prune_chunk(chunk, reason);
+            true
+        }
+    }
+}
 
-    match pruning_predicate.prune(&statistics) {
-        Ok(results) => {
-            // Boolean array for each row in stats, false if the
-            // stats could not pass the predicate
-            let must_keep = results[0]; // 0 as PrunableStats returns a single row
-            if !must_keep {
-                observer.was_pruned(chunk)
+/// Returns `Ok(false)` if the statistics of the chunk show that no rows can
+/// pass `expr`, or the reason why that couldn't be determined.
+///
+/// Conjunctions and disjunctions are evaluated term by term: for `a OR b`
+/// the chunk can only be pruned if neither `a` nor `b` can be true, while
+/// for `a AND b` it is enough that one of them can't be true, even if the
+/// other one can't be evaluated.
+///
+/// Negations are rewritten into equivalent expressions without a `NOT` where
+/// possible. Negated terms that can't be evaluated using the statistics,
+/// such as `NOT LIKE`, never prune the chunk.
+fn could_pass<P>(chunk: &P, expr: &Expr) -> Result<bool, &'static str>
+where
+    P: Prunable,
+{
+    match expr {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        } => match (could_pass(chunk, left), could_pass(chunk, right)) {
+            (Ok(false), _) | (_, Ok(false)) => Ok(false),
+            (Err(reason), _) | (_, Err(reason)) => Err(reason),
+            (Ok(true), Ok(true)) => Ok(true),
+        },
+        Expr::BinaryExpr {
+            left,
+            op: Operator::Or,
+            right,
+        } => match (could_pass(chunk, left), could_pass(chunk, right)) {
+            (Ok(true), _) | (_, Ok(true)) => Ok(true),
+            (Err(reason), _) | (_, Err(reason)) => Err(reason),
+            (Ok(false), Ok(false)) => Ok(false),
+        },
+        Expr::Not(expr) => match negate(expr, &chunk.schema()) {
+            Some(negated) => could_pass(chunk, &negated),
+            None => Ok(true),
+        },
+        Expr::BinaryExpr {
+            left,
+            op: Operator::NotEq,
+            right,
+        } => {
+            // NaN values are not included in the statistics, but are not
+            // equal to any value
+            let schema = chunk.schema();
+            if is_float(left, &schema) || is_float(right, &schema) {
+                return Ok(true);
             }
-            must_keep
+
+            // `a != b` is `a < b OR a > b` for totally ordered types
+            let (left, right) = (left.as_ref().clone(), right.as_ref().clone());
+            could_pass(chunk, &left.clone().lt(right.clone()).or(left.gt(right)))
         }
-        Err(e) => {
-            observer.could_not_prune_chunk(chunk, "Can not evaluate pruning predicate");
-            trace!(%e, ?filter_expr, "Can not evauate pruning predicate");
-            true
+        Expr::BinaryExpr {
+            op: Operator::NotLike,
+            ..
+        } => Ok(true),
+        Expr::InList { .. } => could_pass(chunk, &in_list_to_binary_exprs(expr).unwrap()),
+        expr => could_pass_term(chunk, expr),
+    }
+}
+
+/// Rewrites `a IN (x, y)` into `a = x OR a = y` and `a NOT IN (x, y)` into
+/// `a != x AND a != y`, which can be evaluated against statistics. Returns
+/// `None` if `expr` isn't an IN list.
+pub fn in_list_to_binary_exprs(expr: &Expr) -> Option<Expr> {
+    match expr {
+        Expr::InList {
+            expr,
+            list,
+            negated,
+        } => {
+            let (op, combine, empty): (_, fn(Expr, Expr) -> Expr, _) = if *negated {
+                (Operator::NotEq, Expr::and, true)
+            } else {
+                (Operator::Eq, Expr::or, false)
+            };
+
+            let mut exprs = list
+                .iter()
+                .map(|value| binary_expr(expr.as_ref().clone(), op, value.clone()));
+            Some(match exprs.next() {
+                Some(first) => exprs.fold(first, combine),
+                None => lit(empty),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Returns an expression without a `NOT` at the top that is true exactly
+/// when `expr` is false, if there is one.
+///
+/// As `NOT NULL` is `NULL`, inverting comparisons and De Morgan's laws also
+/// hold for NULL values.
+fn negate(expr: &Expr, schema: &Schema) -> Option<Expr> {
+    let not = |expr: &Expr| Expr::Not(Box::new(expr.clone()));
+
+    match expr {
+        Expr::Not(expr) => Some(expr.as_ref().clone()),
+        // a boolean column
+        Expr::Column(_) => Some(expr.clone().eq(lit(false))),
+        Expr::BinaryExpr { left, op, right } => {
+            let op = match op {
+                Operator::And => return Some(not(left).or(not(right))),
+                Operator::Or => return Some(not(left).and(not(right))),
+                Operator::Eq => Operator::NotEq,
+                Operator::NotEq => Operator::Eq,
+                Operator::Like => Operator::NotLike,
+                Operator::NotLike => Operator::Like,
+                // NaN is neither smaller, equal nor larger than other values,
+                // so for floating point values `NOT a < b` isn't `a >= b`
+                _ if is_float(left, schema) || is_float(right, schema) => return None,
+                Operator::Lt => Operator::GtEq,
+                Operator::LtEq => Operator::Gt,
+                Operator::Gt => Operator::LtEq,
+                Operator::GtEq => Operator::Lt,
+                _ => return None,
+            };
+            Some(binary_expr(
+                left.as_ref().clone(),
+                op,
+                right.as_ref().clone(),
+            ))
         }
+        _ => None,
     }
 }
 
+/// Returns true if `expr` is a floating point column or literal
+fn is_float(expr: &Expr, schema: &Schema) -> bool {
+    match expr {
+        Expr::Column(name) => schema
+            .field_with_name(name)
+            .map(|field| matches!(field.data_type(), DataType::Float32 | DataType::Float64))
+            .unwrap_or(false),
+        Expr::Literal(ScalarValue::Float32(_)) | Expr::Literal(ScalarValue::Float64(_)) => true,
+        _ => false,
+    }
+}
+
+/// Evaluates a single term of a predicate against the statistics of the
+/// chunk using a DataFusion [`PruningPredicate`]
+fn could_pass_term<P>(chunk: &P, expr: &Expr) -> Result<bool, &'static str>
+where
+    P: Prunable,
+{
+    trace!(?expr, schema=?chunk.schema(), "creating pruning predicate");
+
+    let pruning_predicate = PruningPredicate::try_new(expr, chunk.schema()).map_err(|e| {
+        trace!(%e, ?expr, "Can not create pruning predicate");
+        "Can not create pruning predicate"
+    })?;
+
+    let statistics = PrunableStats {
+        summary: chunk.summary(),
+    };
+
+    let results = pruning_predicate.prune(&statistics).map_err(|e| {
+        trace!(%e, ?expr, "Can not evauate pruning predicate");
+        "Can not evaluate pruning predicate"
+    })?;
+
+    // Boolean array for each row in stats, false if the stats could not pass
+    // the predicate. 0 as PrunableStats returns a single row
+    Ok(results[0])
+}
+
 // struct to implement pruning
 struct PrunableStats<'a> {
     summary: &'a TableSummary,
@@ -576,6 +728,273 @@ mod test {
         assert_eq!(names(&pruned), vec!["chunk1", "chunk4", "chunk6"]);
     }
 
+    #[test]
+    fn test_pruned_or() {
+        test_helpers::maybe_start_logging();
+        // column1 < 10 OR column1 > 100 where
+        //   c1: column1 [0, 5] --> not pruned (first term in range)
+        //   c2: column1 [20, 50] --> pruned (neither term in range)
+        //   c3: column1 [200, 300] --> not pruned (second term in range)
+        //   c4: column1 [0, 1000] --> not pruned (both terms in range)
+
+        let observer = TestObserver::new();
+        let c1 = Arc::new(TestPrunable::new("chunk1").with_i64_column("column1", Some(0), Some(5)));
+
+        let c2 =
+            Arc::new(TestPrunable::new("chunk2").with_i64_column("column1", Some(20), Some(50)));
+
+        let c3 =
+            Arc::new(TestPrunable::new("chunk3").with_i64_column("column1", Some(200), Some(300)));
+
+        let c4 =
+            Arc::new(TestPrunable::new("chunk4").with_i64_column("column1", Some(0), Some(1000)));
+
+        let predicate = PredicateBuilder::new()
+            .add_expr(col("column1").lt(lit(10)).or(col("column1").gt(lit(100))))
+            .build();
+
+        let pruned = prune_chunks(&observer, vec![c1, c2, c3, c4], &predicate);
+
+        assert_eq!(observer.events(), vec!["chunk2: Pruned"]);
+        assert_eq!(names(&pruned), vec!["chunk1", "chunk3", "chunk4"]);
+    }
+
+    #[test]
+    fn test_pruned_or_different_schema() {
+        test_helpers::maybe_start_logging();
+        // (column1 > 100 OR column2 > 100) AND column2 < 1000 where
+        //   c1: column1 [0, 10], column2 [0, 10] --> pruned (neither disjunct in range)
+        //   c2: column1 [0, 10], column2 [0, 200] --> not pruned (column2 in range)
+        //   c3: column2 [0, 10] --> not pruned (no stats for column1)
+        //   c4: column2 [2000, 3000] --> pruned (column2 out of range, column1 ignored)
+
+        let observer = TestObserver::new();
+        let c1 = Arc::new(
+            TestPrunable::new("chunk1")
+                .with_i64_column("column1", Some(0), Some(10))
+                .with_i64_column("column2", Some(0), Some(10)),
+        );
+
+        let c2 = Arc::new(
+            TestPrunable::new("chunk2")
+                .with_i64_column("column1", Some(0), Some(10))
+                .with_i64_column("column2", Some(0), Some(200)),
+        );
+
+        let c3 =
+            Arc::new(TestPrunable::new("chunk3").with_i64_column("column2", Some(0), Some(10)));
+
+        let c4 = Arc::new(TestPrunable::new("chunk4").with_i64_column(
+            "column2",
+            Some(2000),
+            Some(3000),
+        ));
+
+        let predicate = PredicateBuilder::new()
+            .add_expr(
+                col("column1")
+                    .gt(lit(100))
+                    .or(col("column2").gt(lit(100)))
+                    .and(col("column2").lt(lit(1000))),
+            )
+            .build();
+
+        let pruned = prune_chunks(&observer, vec![c1, c2, c3, c4], &predicate);
+
+        assert_eq!(
+            observer.events(),
+            vec![
+                "chunk1: Pruned",
+                "chunk3: Could not prune chunk: Can not evaluate pruning predicate",
+                "chunk4: Pruned",
+            ]
+        );
+        assert_eq!(names(&pruned), vec!["chunk2", "chunk3"]);
+    }
+
+    #[test]
+    fn test_pruned_not_eq() {
+        test_helpers::maybe_start_logging();
+        // column1 != 10 where
+        //   c1: column1 [10, 10] --> pruned (all values are 10)
+        //   c2: column1 [0, 10] --> not pruned
+        //   c3: column1 [10, Null] --> not pruned (max not known)
+        //
+        // column2 != 10.0 where
+        //   c4: column2 [10.0, 10.0] --> not pruned (could contain NaN)
+
+        let observer = TestObserver::new();
+        let c1 =
+            Arc::new(TestPrunable::new("chunk1").with_i64_column("column1", Some(10), Some(10)));
+
+        let c2 =
+            Arc::new(TestPrunable::new("chunk2").with_i64_column("column1", Some(0), Some(10)));
+
+        let c3 = Arc::new(TestPrunable::new("chunk3").with_i64_column("column1", Some(10), None));
+
+        let predicate = PredicateBuilder::new()
+            .add_expr(col("column1").not_eq(lit(10)))
+            .build();
+
+        let pruned = prune_chunks(&observer, vec![c1, c2, c3], &predicate);
+
+        assert_eq!(observer.events(), vec!["chunk1: Pruned"]);
+        assert_eq!(names(&pruned), vec!["chunk2", "chunk3"]);
+
+        let observer = TestObserver::new();
+        let c4 = Arc::new(TestPrunable::new("chunk4").with_f64_column(
+            "column2",
+            Some(10.0),
+            Some(10.0),
+        ));
+
+        let predicate = PredicateBuilder::new()
+            .add_expr(col("column2").not_eq(lit(10.0)))
+            .build();
+
+        let pruned = prune_chunks(&observer, vec![c4], &predicate);
+
+        assert!(observer.events().is_empty());
+        assert_eq!(names(&pruned), vec!["chunk4"]);
+    }
+
+    #[test]
+    fn test_pruned_not() {
+        test_helpers::maybe_start_logging();
+        // NOT (column1 < 10 OR column2 > 100) where
+        //   c1: column1 [0, 5], column2 [0, 5] --> pruned (column1 < 10)
+        //   c2: column1 [20, 30], column2 [0, 5] --> not pruned
+        //   c3: column1 [20, 30], column2 [200, 300] --> pruned (column2 > 100)
+        //
+        // NOT (column3 < 10.0) where
+        //   c4: column3 [0.0, 5.0] --> not pruned (could contain NaN)
+
+        let observer = TestObserver::new();
+        let c1 = Arc::new(
+            TestPrunable::new("chunk1")
+                .with_i64_column("column1", Some(0), Some(5))
+                .with_i64_column("column2", Some(0), Some(5)),
+        );
+
+        let c2 = Arc::new(
+            TestPrunable::new("chunk2")
+                .with_i64_column("column1", Some(20), Some(30))
+                .with_i64_column("column2", Some(0), Some(5)),
+        );
+
+        let c3 = Arc::new(
+            TestPrunable::new("chunk3")
+                .with_i64_column("column1", Some(20), Some(30))
+                .with_i64_column("column2", Some(200), Some(300)),
+        );
+
+        let predicate = PredicateBuilder::new()
+            .add_expr(Expr::Not(Box::new(
+                col("column1").lt(lit(10)).or(col("column2").gt(lit(100))),
+            )))
+            .build();
+
+        let pruned = prune_chunks(&observer, vec![c1, c2, c3], &predicate);
+
+        assert_eq!(observer.events(), vec!["chunk1: Pruned", "chunk3: Pruned"]);
+        assert_eq!(names(&pruned), vec!["chunk2"]);
+
+        let observer = TestObserver::new();
+        let c4 =
+            Arc::new(TestPrunable::new("chunk4").with_f64_column("column3", Some(0.0), Some(5.0)));
+
+        let predicate = PredicateBuilder::new()
+            .add_expr(Expr::Not(Box::new(col("column3").lt(lit(10.0)))))
+            .build();
+
+        let pruned = prune_chunks(&observer, vec![c4], &predicate);
+
+        assert!(observer.events().is_empty());
+        assert_eq!(names(&pruned), vec!["chunk4"]);
+    }
+
+    #[test]
+    fn test_pruned_in_list() {
+        test_helpers::maybe_start_logging();
+        // column1 IN ("a", "z") where
+        //   c1: column1 ["b", "q"] --> pruned
+        //   c2: column1 ["a", "q"] --> not pruned
+        //
+        // column1 NOT IN ("a", "z") where
+        //   c3: column1 ["a", "a"] --> pruned
+        //   c4: column1 ["a", "q"] --> not pruned
+
+        let in_list = |negated| Expr::InList {
+            expr: Box::new(col("column1")),
+            list: vec![lit("a"), lit("z")],
+            negated,
+        };
+
+        let observer = TestObserver::new();
+        let c1 = Arc::new(TestPrunable::new("chunk1").with_string_column(
+            "column1",
+            Some("b"),
+            Some("q"),
+        ));
+
+        let c2 = Arc::new(TestPrunable::new("chunk2").with_string_column(
+            "column1",
+            Some("a"),
+            Some("q"),
+        ));
+
+        let predicate = PredicateBuilder::new().add_expr(in_list(false)).build();
+
+        let pruned = prune_chunks(&observer, vec![c1, c2], &predicate);
+
+        assert_eq!(observer.events(), vec!["chunk1: Pruned"]);
+        assert_eq!(names(&pruned), vec!["chunk2"]);
+
+        let observer = TestObserver::new();
+        let c3 = Arc::new(TestPrunable::new("chunk3").with_string_column(
+            "column1",
+            Some("a"),
+            Some("a"),
+        ));
+
+        let c4 = Arc::new(TestPrunable::new("chunk4").with_string_column(
+            "column1",
+            Some("a"),
+            Some("q"),
+        ));
+
+        let predicate = PredicateBuilder::new().add_expr(in_list(true)).build();
+
+        let pruned = prune_chunks(&observer, vec![c3, c4], &predicate);
+
+        assert_eq!(observer.events(), vec!["chunk3: Pruned"]);
+        assert_eq!(names(&pruned), vec!["chunk4"]);
+    }
+
+    #[test]
+    fn test_pruned_not_like() {
+        test_helpers::maybe_start_logging();
+        // column1 NOT LIKE 'a%' and NOT (column1 LIKE 'a%') where
+        //   c1: column1 ["a", "q"] --> not pruned
+
+        let observer = TestObserver::new();
+        let c1 = Arc::new(TestPrunable::new("chunk1").with_string_column(
+            "column1",
+            Some("a"),
+            Some("q"),
+        ));
+
+        let predicate = PredicateBuilder::new()
+            .add_expr(binary_expr(col("column1"), Operator::NotLike, lit("a%")))
+            .add_expr(Expr::Not(Box::new(col("column1").like(lit("a%")))))
+            .build();
+
+        let pruned = prune_chunks(&observer, vec![c1], &predicate);
+
+        assert!(observer.events().is_empty());
+        assert_eq!(names(&pruned), vec!["chunk1"]);
+    }
+
     #[test]
     fn test_pruned_incompatible_types() {
         test_helpers::maybe_start_logging();
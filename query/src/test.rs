@@ -21,11 +21,14 @@ use crate::{
 use crate::{exec::Executor, pruning::Prunable};
 
 use internal_types::{
-    schema::{builder::SchemaBuilder, merge::SchemaMerger, InfluxColumnType, Schema},
+    schema::{
+        builder::SchemaBuilder, merge::SchemaMerger, InfluxColumnType, Schema, TIME_COLUMN_NAME,
+    },
     selection::Selection,
 };
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use parking_lot::Mutex;
 use snafu::{OptionExt, Snafu};
 use std::{collections::BTreeMap, sync::Arc};
@@ -122,6 +125,12 @@ pub struct TestChunk {
     /// Set the flag if this chunk might contain duplicates
     may_contain_pk_duplicates: bool,
 
+    /// Set the flag if rows may have been deleted from this chunk
+    has_delete_predicates: bool,
+
+    /// Time of the last write to this chunk, if any
+    time_of_last_write: Option<DateTime<Utc>>,
+
     /// A copy of the captured predicates passed
     predicates: Mutex<Vec<Predicate>>,
 
@@ -131,6 +140,9 @@ pub struct TestChunk {
     /// Schema of the table
     table_schema: Option<Schema>,
 
+    /// The columns the table data is sorted by, if any
+    sort_key: Option<Vec<String>>,
+
     /// RecordBatches that are returned on each request
     table_data: Vec<Arc<RecordBatch>>,
 
@@ -185,6 +197,24 @@ impl TestChunk {
         self
     }
 
+    /// Set the `has_delete_predicates` flag
+    pub fn with_delete_predicates(mut self, v: bool) -> Self {
+        self.has_delete_predicates = v;
+        self
+    }
+
+    /// Set the time of the last write to this chunk
+    pub fn with_time_of_last_write(mut self, time_of_last_write: DateTime<Utc>) -> Self {
+        self.time_of_last_write = Some(time_of_last_write);
+        self
+    }
+
+    /// Record that the table data is sorted by the columns of `sort_key`
+    pub fn with_sort_key(mut self, sort_key: &[&str]) -> Self {
+        self.sort_key = Some(sort_key.iter().map(|s| s.to_string()).collect());
+        self
+    }
+
     /// Register an tag column with the test chunk
     pub fn with_tag_column(
         self,
@@ -244,6 +274,37 @@ impl TestChunk {
         self.add_schema_to_table(table_name, new_column_schema)
     }
 
+    /// Register a timestamp column with the test chunk, with `count`
+    /// timestamps between `min` and `max`
+    pub fn with_time_column_with_stats(
+        self,
+        table_name: impl Into<String>,
+        min: i64,
+        max: i64,
+        count: u64,
+    ) -> Self {
+        let mut new_self = self.with_time_column(table_name);
+
+        // Now, find the time column summary and update the stats
+        let column_summary: &mut ColumnSummary = new_self
+            .table_summary
+            .as_mut()
+            .expect("had table summary")
+            .columns
+            .iter_mut()
+            .find(|c| c.name == TIME_COLUMN_NAME)
+            .expect("had column");
+
+        column_summary.stats = Statistics::I64(StatValues {
+            min: Some(min),
+            max: Some(max),
+            count,
+            ..Default::default()
+        });
+
+        new_self
+    }
+
     /// Register an int field column with the test chunk
     pub fn with_int_field_column(
         self,
@@ -407,6 +468,14 @@ impl PartitionChunk for TestChunk {
         self.may_contain_pk_duplicates
     }
 
+    fn has_delete_predicates(&self) -> bool {
+        self.has_delete_predicates
+    }
+
+    fn time_of_last_write(&self) -> Option<DateTime<Utc>> {
+        self.time_of_last_write
+    }
+
     fn read_filter(
         &self,
         predicate: &Predicate,
@@ -459,6 +528,10 @@ impl PartitionChunk for TestChunk {
         })
     }
 
+    fn sort_key(&self) -> Option<&[String]> {
+        self.sort_key.as_deref()
+    }
+
     fn column_values(
         &self,
         _column_name: &str,
@@ -0,0 +1,119 @@
+//! Tests of the InfluxQL frontend, which plans InfluxQL queries as SQL
+
+use super::scenarios::*;
+use arrow::record_batch::RecordBatch;
+use arrow_util::assert_batches_sorted_eq;
+use query::frontend::influxql::InfluxQlQueryPlanner;
+use std::sync::Arc;
+
+/// runs the InfluxQL query and compares it to the expected output
+macro_rules! run_influxql_test_case {
+    ($DB_SETUP:expr, $INFLUXQL:expr, $EXPECTED_LINES:expr) => {
+        test_helpers::maybe_start_logging();
+        let influxql = $INFLUXQL.to_string();
+        for scenario in $DB_SETUP.make().await {
+            let DbScenario {
+                scenario_name, db, ..
+            } = scenario;
+            let db = Arc::new(db);
+
+            println!("Running scenario '{}'", scenario_name);
+            println!("InfluxQL: '{:#?}'", influxql);
+            let planner = InfluxQlQueryPlanner::default();
+            let executor = db.executor();
+
+            let physical_plan = planner
+                .query(db, &influxql, executor.as_ref())
+                .expect("built plan successfully");
+
+            let results: Vec<RecordBatch> =
+                executor.collect(physical_plan).await.expect("Running plan");
+
+            assert_batches_sorted_eq!($EXPECTED_LINES, &results);
+        }
+    };
+}
+
+#[tokio::test]
+async fn influxql_select_fields() {
+    let expected = vec![
+        "+-------------------------------+------+",
+        "| time                          | user |",
+        "+-------------------------------+------+",
+        "| 1970-01-01 00:00:00.000000100 | 23.2 |",
+        "| 1970-01-01 00:00:00.000000150 | 21   |",
+        "+-------------------------------+------+",
+    ];
+    run_influxql_test_case!(TwoMeasurements {}, "SELECT user FROM cpu", &expected);
+}
+
+#[tokio::test]
+async fn influxql_select_with_where() {
+    let expected = vec![
+        "+-------------------------------+------+",
+        "| time                          | user |",
+        "+-------------------------------+------+",
+        "| 1970-01-01 00:00:00.000000150 | 21   |",
+        "+-------------------------------+------+",
+    ];
+    run_influxql_test_case!(
+        TwoMeasurements {},
+        "SELECT user FROM cpu WHERE region = 'west' AND time > 120",
+        &expected
+    );
+}
+
+#[tokio::test]
+async fn influxql_select_group_by_time_and_tag() {
+    let expected = vec![
+        "+-------------------------------+--------+-------+------+",
+        "| time                          | region | count | max  |",
+        "+-------------------------------+--------+-------+------+",
+        "| 1970-01-01 00:00:00.000000100 | west   | 1     | 23.2 |",
+        "| 1970-01-01 00:00:00.000000150 | west   | 1     | 21   |",
+        "+-------------------------------+--------+-------+------+",
+    ];
+    run_influxql_test_case!(
+        TwoMeasurements {},
+        "SELECT count(user), max(user) FROM cpu GROUP BY time(50ns), region fill(none)",
+        &expected
+    );
+}
+
+#[tokio::test]
+async fn influxql_select_group_by_time_fill() {
+    let expected = vec![
+        "+-------------------------------+------+",
+        "| time                          | max  |",
+        "+-------------------------------+------+",
+        "| 1970-01-01 00:00:00           |      |",
+        "| 1970-01-01 00:00:00.000000050 |      |",
+        "| 1970-01-01 00:00:00.000000100 | 23.2 |",
+        "| 1970-01-01 00:00:00.000000150 | 21   |",
+        "| 1970-01-01 00:00:00.000000200 |      |",
+        "+-------------------------------+------+",
+    ];
+    run_influxql_test_case!(
+        TwoMeasurements {},
+        "SELECT max(user) FROM cpu WHERE time >= 0 AND time < 250 GROUP BY time(50ns)",
+        &expected
+    );
+
+    let expected = vec![
+        "+-------------------------------+------+",
+        "| time                          | max  |",
+        "+-------------------------------+------+",
+        "| 1970-01-01 00:00:00           |      |",
+        "| 1970-01-01 00:00:00.000000050 |      |",
+        "| 1970-01-01 00:00:00.000000100 | 23.2 |",
+        "| 1970-01-01 00:00:00.000000150 | 21   |",
+        "| 1970-01-01 00:00:00.000000200 | 21   |",
+        "+-------------------------------+------+",
+    ];
+    run_influxql_test_case!(
+        TwoMeasurements {},
+        "SELECT max(user) FROM cpu WHERE time >= 0 AND time < 250 GROUP BY time(50ns) \
+         fill(previous)",
+        &expected
+    );
+}
@@ -163,7 +163,7 @@ impl DbSetup for MeasurementForWindowAggregateMonths {
 
         let db = make_db().await.db;
         let data = lp_lines.join("\n");
-        write_lp(&db, &data);
+        write_lp(&db, &data).await;
         let scenario1 = DbScenario {
             scenario_name: "Data in 4 partitions, open chunks of mutable buffer".into(),
             db,
@@ -171,7 +171,7 @@ impl DbSetup for MeasurementForWindowAggregateMonths {
 
         let db = make_db().await.db;
         let data = lp_lines.join("\n");
-        write_lp(&db, &data);
+        write_lp(&db, &data).await;
         db.rollover_partition("2020-03-01T00", "h2o").await.unwrap();
         db.rollover_partition("2020-03-02T00", "h2o").await.unwrap();
         let scenario2 = DbScenario {
@@ -183,7 +183,7 @@ impl DbSetup for MeasurementForWindowAggregateMonths {
 
         let db = make_db().await.db;
         let data = lp_lines.join("\n");
-        write_lp(&db, &data);
+        write_lp(&db, &data).await;
         // roll over and load chunks into both RUB and OS
         rollover_and_load(&db, "2020-03-01T00", "h2o").await;
         rollover_and_load(&db, "2020-03-02T00", "h2o").await;
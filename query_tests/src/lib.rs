@@ -7,6 +7,8 @@
 
 // Actual tests
 
+#[cfg(test)]
+pub mod influxql;
 #[cfg(test)]
 pub mod influxrpc;
 #[cfg(test)]
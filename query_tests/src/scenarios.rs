@@ -57,7 +57,7 @@ impl DbSetup for NoData {
         //
         let db = make_db().await.db;
         let data = "cpu,region=west user=23.2 100";
-        write_lp(&db, data);
+        write_lp(&db, data).await;
         // move data out of open chunk
         assert_eq!(
             db.rollover_partition(partition_key, table_name)
@@ -95,7 +95,7 @@ impl DbSetup for NoData {
         //
         let db = make_db().await.db;
         let data = "cpu,region=west user=23.2 100";
-        write_lp(&db, data);
+        write_lp(&db, data).await;
         // move data out of open chunk
         assert_eq!(
             db.rollover_partition(partition_key, table_name)
@@ -285,7 +285,7 @@ impl DbSetup for TwoMeasurementsManyFieldsOneChunk {
             "o2,state=CA temp=79.0 300",
         ];
 
-        write_lp(&db, &lp_lines.join("\n"));
+        write_lp(&db, &lp_lines.join("\n")).await;
         vec![DbScenario {
             scenario_name: "Data in open chunk of mutable buffer".into(),
             db,
@@ -307,7 +307,7 @@ impl DbSetup for TwoMeasurementsManyFieldsTwoChunks {
             "h2o,state=MA,city=Boston temp=70.4 50",
             "h2o,state=MA,city=Boston other_temp=70.4 250",
         ];
-        write_lp(&db, &lp_lines.join("\n"));
+        write_lp(&db, &lp_lines.join("\n")).await;
         db.rollover_partition(partition_key, "h2o").await.unwrap();
         db.load_chunk_to_read_buffer(partition_key, "h2o", 0, &Default::default())
             .await
@@ -318,7 +318,7 @@ impl DbSetup for TwoMeasurementsManyFieldsTwoChunks {
             "o2,state=MA,city=Boston temp=53.4,reading=51 50",
             "o2,state=CA temp=79.0 300",
         ];
-        write_lp(&db, &lp_lines.join("\n"));
+        write_lp(&db, &lp_lines.join("\n")).await;
 
         vec![DbScenario {
             scenario_name: "Data in open chunk of mutable buffer and read buffer".into(),
@@ -346,7 +346,7 @@ impl DbSetup for OneMeasurementThreeChunksWithDuplicates {
             "h2o,state=MA,city=Boston max_temp=75.4 250",
             "h2o,state=MA,city=Andover max_temp=69.2, 250",
         ];
-        write_lp(&db, &lp_lines.join("\n"));
+        write_lp(&db, &lp_lines.join("\n")).await;
         db.rollover_partition(partition_key, "h2o").await.unwrap();
         db.load_chunk_to_read_buffer(partition_key, "h2o", 0, &Default::default())
             .await
@@ -363,7 +363,7 @@ impl DbSetup for OneMeasurementThreeChunksWithDuplicates {
             "h2o,state=CA,city=SJ min_temp=78.5,max_temp=88.0 300",
             "h2o,state=CA,city=SJ min_temp=75.5,max_temp=84.08 350",
         ];
-        write_lp(&db, &lp_lines.join("\n"));
+        write_lp(&db, &lp_lines.join("\n")).await;
         db.rollover_partition(partition_key, "h2o").await.unwrap();
         db.load_chunk_to_read_buffer(partition_key, "h2o", 1, &Default::default())
             .await
@@ -380,7 +380,7 @@ impl DbSetup for OneMeasurementThreeChunksWithDuplicates {
             "h2o,state=CA,city=SJ min_temp=77.0,max_temp=90.7 450",
             "h2o,state=CA,city=SJ min_temp=69.5,max_temp=88.2 500",
         ];
-        write_lp(&db, &lp_lines.join("\n"));
+        write_lp(&db, &lp_lines.join("\n")).await;
         db.rollover_partition(partition_key, "h2o").await.unwrap();
         db.load_chunk_to_read_buffer(partition_key, "h2o", 2, &Default::default())
             .await
@@ -397,7 +397,7 @@ impl DbSetup for OneMeasurementThreeChunksWithDuplicates {
             "h2o,state=CA,city=SJ min_temp=69.5,max_temp=89.2 650",
             "h2o,state=CA,city=SJ min_temp=75.5,max_temp=84.08 700",
         ];
-        write_lp(&db, &lp_lines.join("\n"));
+        write_lp(&db, &lp_lines.join("\n")).await;
         db.rollover_partition(partition_key, "h2o").await.unwrap();
         db.load_chunk_to_read_buffer(partition_key, "h2o", 3, &Default::default())
             .await
@@ -428,7 +428,8 @@ impl DbSetup for TwoMeasurementsManyFieldsLifecycle {
                 "h2o,state=MA,city=Boston other_temp=70.4 250",
             ]
             .join("\n"),
-        );
+        )
+        .await;
 
         // Use a background task to do the work note when I used
         // TaskTracker::join, it ended up hanging for reasons I don't
@@ -443,7 +444,8 @@ impl DbSetup for TwoMeasurementsManyFieldsLifecycle {
         write_lp(
             &db,
             &vec!["h2o,state=CA,city=Boston other_temp=72.4 350"].join("\n"),
-        );
+        )
+        .await;
 
         let job = db.write_chunk_to_object_store_in_background(
             partition_key.to_string(),
@@ -503,7 +505,7 @@ impl DbSetup for EndToEndTest {
         let lp_data = lp_lines.join("\n");
 
         let db = make_db().await.db;
-        write_lp(&db, &lp_data);
+        write_lp(&db, &lp_data).await;
 
         let scenario1 = DbScenario {
             scenario_name: "Data in open chunk of mutable buffer".into(),
@@ -516,10 +518,10 @@ impl DbSetup for EndToEndTest {
 /// This function loads one chunk of lp data into different scenarios that simulates
 /// the data life cycle.
 ///
-pub(crate) async fn make_one_chunk_scenarios(partition_key: &str, data: &str) -> Vec<DbScenario> {
+pub async fn make_one_chunk_scenarios(partition_key: &str, data: &str) -> Vec<DbScenario> {
     // Scenario 1: One open chunk in MUB
     let db = make_db().await.db;
-    write_lp(&db, data);
+    write_lp(&db, data).await;
     let scenario1 = DbScenario {
         scenario_name: "Data in open chunk of mutable buffer".into(),
         db,
@@ -527,7 +529,7 @@ pub(crate) async fn make_one_chunk_scenarios(partition_key: &str, data: &str) ->
 
     // Scenario 2: One closed chunk in MUB
     let db = make_db().await.db;
-    let table_names = write_lp(&db, data);
+    let table_names = write_lp(&db, data).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
@@ -540,7 +542,7 @@ pub(crate) async fn make_one_chunk_scenarios(partition_key: &str, data: &str) ->
 
     // Scenario 3: One closed chunk in RUB
     let db = make_db().await.db;
-    let table_names = write_lp(&db, data);
+    let table_names = write_lp(&db, data).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
@@ -556,7 +558,7 @@ pub(crate) async fn make_one_chunk_scenarios(partition_key: &str, data: &str) ->
 
     // Scenario 4: One closed chunk in both RUb and OS
     let db = make_db().await.db;
-    let table_names = write_lp(&db, data);
+    let table_names = write_lp(&db, data).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
@@ -576,7 +578,7 @@ pub(crate) async fn make_one_chunk_scenarios(partition_key: &str, data: &str) ->
 
     // Scenario 5: One closed chunk in OS only
     let db = make_db().await.db;
-    let table_names = write_lp(&db, data);
+    let table_names = write_lp(&db, data).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
@@ -611,8 +613,8 @@ pub async fn make_two_chunk_scenarios(
     data2: &str,
 ) -> Vec<DbScenario> {
     let db = make_db().await.db;
-    write_lp(&db, data1);
-    write_lp(&db, data2);
+    write_lp(&db, data1).await;
+    write_lp(&db, data2).await;
     let scenario1 = DbScenario {
         scenario_name: "Data in single open chunk of mutable buffer".into(),
         db,
@@ -620,13 +622,13 @@ pub async fn make_two_chunk_scenarios(
 
     // spread across 2 mutable buffer chunks
     let db = make_db().await.db;
-    let table_names = write_lp(&db, data1);
+    let table_names = write_lp(&db, data1).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
             .unwrap();
     }
-    write_lp(&db, data2);
+    write_lp(&db, data2).await;
     let scenario2 = DbScenario {
         scenario_name: "Data in one open chunk and one closed chunk of mutable buffer".into(),
         db,
@@ -634,7 +636,7 @@ pub async fn make_two_chunk_scenarios(
 
     // spread across 1 mutable buffer, 1 read buffer chunks
     let db = make_db().await.db;
-    let table_names = write_lp(&db, data1);
+    let table_names = write_lp(&db, data1).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
@@ -643,7 +645,7 @@ pub async fn make_two_chunk_scenarios(
             .await
             .unwrap();
     }
-    write_lp(&db, data2);
+    write_lp(&db, data2).await;
     let scenario3 = DbScenario {
         scenario_name: "Data in open chunk of mutable buffer, and one chunk of read buffer".into(),
         db,
@@ -651,13 +653,13 @@ pub async fn make_two_chunk_scenarios(
 
     // in 2 read buffer chunks
     let db = make_db().await.db;
-    let table_names = write_lp(&db, data1);
+    let table_names = write_lp(&db, data1).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
             .unwrap();
     }
-    let table_names = write_lp(&db, data2);
+    let table_names = write_lp(&db, data2).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
@@ -678,13 +680,13 @@ pub async fn make_two_chunk_scenarios(
 
     // in 2 read buffer chunks that also loaded into object store
     let db = make_db().await.db;
-    let table_names = write_lp(&db, data1);
+    let table_names = write_lp(&db, data1).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
             .unwrap();
     }
-    let table_names = write_lp(&db, data2);
+    let table_names = write_lp(&db, data2).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
@@ -713,13 +715,13 @@ pub async fn make_two_chunk_scenarios(
 
     // Scenario 6: Two closed chunk in OS only
     let db = make_db().await.db;
-    let table_names = write_lp(&db, data1);
+    let table_names = write_lp(&db, data1).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
             .unwrap();
     }
-    let table_names = write_lp(&db, data2);
+    let table_names = write_lp(&db, data2).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
@@ -777,7 +779,7 @@ pub(crate) async fn make_one_rub_or_parquet_chunk_scenario(
 ) -> Vec<DbScenario> {
     // Scenario 1: One closed chunk in RUB
     let db = make_db().await.db;
-    let table_names = write_lp(&db, data);
+    let table_names = write_lp(&db, data).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
@@ -793,7 +795,7 @@ pub(crate) async fn make_one_rub_or_parquet_chunk_scenario(
 
     // Scenario 2: One closed chunk in Parquet only
     let db = make_db().await.db;
-    let table_names = write_lp(&db, data);
+    let table_names = write_lp(&db, data).await;
     for table_name in &table_names {
         db.rollover_partition(partition_key, &table_name)
             .await
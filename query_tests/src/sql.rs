@@ -146,6 +146,87 @@ async fn sql_select_from_cpu_group() {
     );
 }
 
+#[tokio::test]
+async fn sql_select_count_min_max_time() {
+    // these may be answered from chunk statistics, which must give the
+    // same results as scanning the chunks
+    let expected = vec![
+        "+-------+-------------------------------+-------------------------------+",
+        "| count | min_time                      | max_time                      |",
+        "+-------+-------------------------------+-------------------------------+",
+        "| 2     | 1970-01-01 00:00:00.000000100 | 1970-01-01 00:00:00.000000150 |",
+        "+-------+-------------------------------+-------------------------------+",
+    ];
+    run_sql_test_case!(
+        TwoMeasurements {},
+        "SELECT count(*) as count, min(time) as min_time, max(time) as max_time from cpu",
+        &expected
+    );
+
+    let expected = vec![
+        "+-------+-------------------------------+-------------------------------+",
+        "| count | min_time                      | max_time                      |",
+        "+-------+-------------------------------+-------------------------------+",
+        "| 1     | 1970-01-01 00:00:00.000000150 | 1970-01-01 00:00:00.000000150 |",
+        "+-------+-------------------------------+-------------------------------+",
+    ];
+    run_sql_test_case!(
+        TwoMeasurements {},
+        "SELECT count(*) as count, min(time) as min_time, max(time) as max_time from cpu \
+         where time > '1970-01-01T00:00:00.000000120Z'",
+        &expected
+    );
+}
+
+#[tokio::test]
+async fn sql_select_from_cpu_selectors() {
+    let expected = vec![
+        "+--------+-----------+-------------------------------+",
+        "| region | last_user | last_time                     |",
+        "+--------+-----------+-------------------------------+",
+        "| west   | 21        | 1970-01-01 00:00:00.000000150 |",
+        "+--------+-----------+-------------------------------+",
+    ];
+    run_sql_test_case!(
+        TwoMeasurements {},
+        "SELECT region, selector_last_value(user, time) as last_user, \
+         selector_last_time(user, time) as last_time from cpu group by region",
+        &expected
+    );
+}
+
+#[tokio::test]
+async fn sql_select_from_cpu_group_by_date_bin() {
+    let expected = vec![
+        "+-------------------------------+-------+----------+",
+        "| bin                           | count | max_user |",
+        "+-------------------------------+-------+----------+",
+        "| 1970-01-01 00:00:00.000000100 | 1     | 23.2     |",
+        "| 1970-01-01 00:00:00.000000150 | 1     | 21       |",
+        "+-------------------------------+-------+----------+",
+    ];
+    run_sql_test_case!(
+        TwoMeasurements {},
+        "SELECT date_bin('50ns', time) as bin, count(*) as count, max(user) as max_user \
+         from cpu group by date_bin('50ns', time) order by bin",
+        &expected
+    );
+
+    let expected = vec![
+        "+-------------------------------+-------+----------+",
+        "| bin                           | count | max_user |",
+        "+-------------------------------+-------+----------+",
+        "| 1970-01-01 00:00:00.000000100 | 2     | 23.2     |",
+        "+-------------------------------+-------+----------+",
+    ];
+    run_sql_test_case!(
+        TwoMeasurements {},
+        "SELECT date_bin('100ns', time) as bin, count(*) as count, max(user) as max_user \
+         from cpu group by date_bin('100ns', time)",
+        &expected
+    );
+}
+
 #[tokio::test]
 async fn sql_select_from_disk() {
     let expected = vec![
@@ -344,24 +425,24 @@ async fn sql_select_from_system_chunk_columns() {
     // with different chunk configurations.
 
     let expected = vec![
-        "+---------------+----------+------------+-------------+-------------------+-------+-----------+-----------+-----------------+",
-        "| partition_key | chunk_id | table_name | column_name | storage           | count | min_value | max_value | estimated_bytes |",
-        "+---------------+----------+------------+-------------+-------------------+-------+-----------+-----------+-----------------+",
-        "| 1970-01-01T00 | 0        | h2o        | city        | ReadBuffer        | 2     | Boston    | Boston    | 247             |",
-        "| 1970-01-01T00 | 0        | h2o        | other_temp  | ReadBuffer        | 1     | 70.4      | 70.4      | 369             |",
-        "| 1970-01-01T00 | 0        | h2o        | state       | ReadBuffer        | 2     | MA        | MA        | 235             |",
-        "| 1970-01-01T00 | 0        | h2o        | temp        | ReadBuffer        | 1     | 70.4      | 70.4      | 369             |",
-        "| 1970-01-01T00 | 0        | h2o        | time        | ReadBuffer        | 2     | 50        | 250       | 51              |",
-        "| 1970-01-01T00 | 0        | o2         | city        | OpenMutableBuffer | 1     | Boston    | Boston    | 35              |",
-        "| 1970-01-01T00 | 0        | o2         | reading     | OpenMutableBuffer | 1     | 51        | 51        | 25              |",
-        "| 1970-01-01T00 | 0        | o2         | state       | OpenMutableBuffer | 2     | CA        | MA        | 41              |",
-        "| 1970-01-01T00 | 0        | o2         | temp        | OpenMutableBuffer | 2     | 53.4      | 79        | 25              |",
-        "| 1970-01-01T00 | 0        | o2         | time        | OpenMutableBuffer | 2     | 50        | 300       | 25              |",
-        "| 1970-01-01T00 | 1        | h2o        | city        | OpenMutableBuffer | 1     | Boston    | Boston    | 31              |",
-        "| 1970-01-01T00 | 1        | h2o        | other_temp  | OpenMutableBuffer | 1     | 72.4      | 72.4      | 17              |",
-        "| 1970-01-01T00 | 1        | h2o        | state       | OpenMutableBuffer | 1     | CA        | CA        | 27              |",
-        "| 1970-01-01T00 | 1        | h2o        | time        | OpenMutableBuffer | 1     | 350       | 350       | 17              |",
-        "+---------------+----------+------------+-------------+-------------------+-------+-----------+-----------+-----------------+",
+        "+---------------+----------+------------+-------------+-------------------+-------+-----------+-----------+-----------------+-------------+-----------+-------------+",
+        "| partition_key | chunk_id | table_name | column_name | storage           | count | min_value | max_value | estimated_bytes | encoding    | raw_bytes | cardinality |",
+        "+---------------+----------+------------+-------------+-------------------+-------+-----------+-----------+-----------------+-------------+-----------+-------------+",
+        "| 1970-01-01T00 | 0        | h2o        | city        | ReadBuffer        | 2     | Boston    | Boston    | 247             | RLE         | 84        | 1           |",
+        "| 1970-01-01T00 | 0        | h2o        | other_temp  | ReadBuffer        | 1     | 70.4      | 70.4      | 369             | FIXEDN      | 40        |             |",
+        "| 1970-01-01T00 | 0        | h2o        | state       | ReadBuffer        | 2     | MA        | MA        | 235             | RLE         | 76        | 1           |",
+        "| 1970-01-01T00 | 0        | h2o        | temp        | ReadBuffer        | 1     | 70.4      | 70.4      | 369             | FIXEDN      | 40        |             |",
+        "| 1970-01-01T00 | 0        | h2o        | time        | ReadBuffer        | 2     | 50        | 250       | 51              | BT_U8-FIXED | 40        |             |",
+        "| 1970-01-01T00 | 0        | o2         | city        | OpenMutableBuffer | 1     | Boston    | Boston    | 35              |             |           |             |",
+        "| 1970-01-01T00 | 0        | o2         | reading     | OpenMutableBuffer | 1     | 51        | 51        | 25              |             |           |             |",
+        "| 1970-01-01T00 | 0        | o2         | state       | OpenMutableBuffer | 2     | CA        | MA        | 41              |             |           |             |",
+        "| 1970-01-01T00 | 0        | o2         | temp        | OpenMutableBuffer | 2     | 53.4      | 79        | 25              |             |           |             |",
+        "| 1970-01-01T00 | 0        | o2         | time        | OpenMutableBuffer | 2     | 50        | 300       | 25              |             |           |             |",
+        "| 1970-01-01T00 | 1        | h2o        | city        | OpenMutableBuffer | 1     | Boston    | Boston    | 31              |             |           |             |",
+        "| 1970-01-01T00 | 1        | h2o        | other_temp  | OpenMutableBuffer | 1     | 72.4      | 72.4      | 17              |             |           |             |",
+        "| 1970-01-01T00 | 1        | h2o        | state       | OpenMutableBuffer | 1     | CA        | CA        | 27              |             |           |             |",
+        "| 1970-01-01T00 | 1        | h2o        | time        | OpenMutableBuffer | 1     | 350       | 350       | 17              |             |           |             |",
+        "+---------------+----------+------------+-------------+-------------------+-------+-----------+-----------+-----------------+-------------+-----------+-------------+",
     ];
     run_sql_test_case!(
         TwoMeasurementsManyFieldsTwoChunks {},
@@ -457,6 +538,38 @@ async fn sql_predicate_pushdown_correctness_1() {
     );
 }
 
+#[tokio::test]
+async fn sql_select_top_k() {
+    let expected = vec![
+        "+----------+--------+-------+",
+        "| town     | system | count |",
+        "+----------+--------+-------+",
+        "| andover  | 5      | 40000 |",
+        "| lawrence | 6      | 872   |",
+        "| reading  | 6      | 632   |",
+        "+----------+--------+-------+",
+    ];
+    run_sql_test_case!(
+        TwoMeasurementsPredicatePushDown {},
+        "SELECT town, system, count from restaurant ORDER BY count DESC, time DESC LIMIT 3",
+        &expected
+    );
+
+    let expected = vec![
+        "+-----------+-------+",
+        "| town      | count |",
+        "+-----------+-------+",
+        "| bedford   | 189   |",
+        "| lexington | 372   |",
+        "+-----------+-------+",
+    ];
+    run_sql_test_case!(
+        TwoMeasurementsPredicatePushDown {},
+        "SELECT town, count from restaurant ORDER BY count LIMIT 2",
+        &expected
+    );
+}
+
 #[tokio::test]
 async fn sql_predicate_pushdown_correctness_2() {
     // Test 2: One push-down expression: count > 200
@@ -829,19 +942,19 @@ async fn sql_predicate_pushdown_explain_3() {
     "| plan_type                               | plan                                                                        |",
     "+-----------------------------------------+-----------------------------------------------------------------------------+",
     "| logical_plan                            | Projection: #count, #system, #time, #town                                   |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\")             |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\")           |",
     "|                                         |     TableScan: restaurant projection=None                                   |",
     "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                   |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\")             |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\")           |",
     "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                     |",
     "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                   |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\")             |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\")           |",
     "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                     |",
     "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                   |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\")             |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\")           |",
     "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                     |",
     "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                   |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\")             |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\")           |",
     "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                     |",
     "| physical_plan                           | ProjectionExec: expr=[count, system, time, town]                            |",
     "|                                         |   FilterExec: CAST(count AS Int64) > 200 AND CAST(town AS Utf8) != tewsbury |",
@@ -864,19 +977,19 @@ async fn sql_predicate_pushdown_explain_4() {
     "| plan_type                               | plan                                                                                                                                         |",
     "+-----------------------------------------+----------------------------------------------------------------------------------------------------------------------------------------------+",
     "| logical_plan                            | Projection: #count, #system, #time, #town                                                                                                    |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\")                         |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\")                     |",
     "|                                         |     TableScan: restaurant projection=None                                                                                                    |",
     "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                                                                    |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\")                         |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\")                     |",
     "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                      |",
     "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                                                                    |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\")                         |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\")                     |",
     "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                      |",
     "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                                                                    |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\")                         |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\")                     |",
     "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                      |",
     "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                                                                    |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\")                         |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\")                     |",
     "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                      |",
     "| physical_plan                           | ProjectionExec: expr=[count, system, time, town]                                                                                             |",
     "|                                         |   FilterExec: CAST(count AS Int64) > 200 AND CAST(town AS Utf8) != tewsbury AND system = CAST(5 AS Float64) OR CAST(town AS Utf8) = lawrence |",
@@ -898,19 +1011,19 @@ async fn sql_predicate_pushdown_explain_5() {
     "| plan_type                               | plan                                                                                                                                                                          |",
     "+-----------------------------------------+-------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+",
     "| logical_plan                            | Projection: #count, #system, #time, #town                                                                                                                                     |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\") And #count Lt Int64(40000)                               |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\") And #count Lt Int64(40000)                           |",
     "|                                         |     TableScan: restaurant projection=None                                                                                                                                     |",
     "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                                                                                                     |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\") And #count Lt Int64(40000)                               |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\") And #count Lt Int64(40000)                           |",
     "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                                                       |",
     "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                                                                                                     |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\") And #count Lt Int64(40000)                               |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\") And #count Lt Int64(40000)                           |",
     "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                                                       |",
     "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                                                                                                     |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\") And #count Lt Int64(40000)                               |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\") And #count Lt Int64(40000)                           |",
     "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                                                       |",
     "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                                                                                                     |",
-    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\") And #count Lt Int64(40000)                               |",
+    "|                                         |   Filter: #count Gt Int64(200) And #town NotEq Utf8(\"tewsbury\") And #system Eq Int64(5) Or #town Eq Utf8(\"lawrence\") And #count Lt Int64(40000)                           |",
     "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                                                       |",
     "| physical_plan                           | ProjectionExec: expr=[count, system, time, town]                                                                                                                              |",
     "|                                         |   FilterExec: CAST(count AS Int64) > 200 AND CAST(town AS Utf8) != tewsbury AND system = CAST(5 AS Float64) OR CAST(town AS Utf8) = lawrence AND CAST(count AS Int64) < 40000 |",
@@ -1030,28 +1143,28 @@ async fn sql_predicate_pushdown_explain_8() {
 async fn sql_predicate_pushdown_explain_9() {
     // Test 9: three push-down expression: system > 5.0 and town != 'tewsbury' and system < 7.0
     let expected = vec![
-    "+-----------------------------------------+--------------------------------------------------------------------------------------------+",
-    "| plan_type                               | plan                                                                                       |",
-    "+-----------------------------------------+--------------------------------------------------------------------------------------------+",
-    "| logical_plan                            | Projection: #count, #system, #time, #town                                                  |",
+    "+-----------------------------------------+----------------------------------------------------------------------------------------------+",
+    "| plan_type                               | plan                                                                                         |",
+    "+-----------------------------------------+----------------------------------------------------------------------------------------------+",
+    "| logical_plan                            | Projection: #count, #system, #time, #town                                                    |",
     "|                                         |   Filter: #system Gt Float64(5) And #town NotEq Utf8(\"tewsbury\") And Float64(7) Gt #system |",
-    "|                                         |     TableScan: restaurant projection=None                                                  |",
-    "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                  |",
+    "|                                         |     TableScan: restaurant projection=None                                                    |",
+    "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                    |",
     "|                                         |   Filter: #system Gt Float64(5) And #town NotEq Utf8(\"tewsbury\") And Float64(7) Gt #system |",
-    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                    |",
-    "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                  |",
+    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                      |",
+    "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                    |",
     "|                                         |   Filter: #system Gt Float64(5) And #town NotEq Utf8(\"tewsbury\") And Float64(7) Gt #system |",
-    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                    |",
-    "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                  |",
+    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                      |",
+    "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                    |",
     "|                                         |   Filter: #system Gt Float64(5) And #town NotEq Utf8(\"tewsbury\") And Float64(7) Gt #system |",
-    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                    |",
-    "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                  |",
+    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                      |",
+    "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                    |",
     "|                                         |   Filter: #system Gt Float64(5) And #town NotEq Utf8(\"tewsbury\") And Float64(7) Gt #system |",
-    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                    |",
-    "| physical_plan                           | ProjectionExec: expr=[count, system, time, town]                                           |",
-    "|                                         |   FilterExec: system > 5 AND CAST(town AS Utf8) != tewsbury AND 7 > system                 |",
-    "|                                         |     IOxReadFilterNode: table_name=restaurant, chunks=1 predicate=Predicate                 |",
-    "+-----------------------------------------+--------------------------------------------------------------------------------------------+",
+    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                      |",
+    "| physical_plan                           | ProjectionExec: expr=[count, system, time, town]                                             |",
+    "|                                         |   FilterExec: system > 5 AND CAST(town AS Utf8) != tewsbury AND 7 > system                   |",
+    "|                                         |     IOxReadFilterNode: table_name=restaurant, chunks=1 predicate=Predicate                   |",
+    "+-----------------------------------------+----------------------------------------------------------------------------------------------+",
     ];
     run_sql_explain_test_case!(
         TwoMeasurementsPredicatePushDown {},
@@ -1065,28 +1178,28 @@ async fn sql_predicate_pushdown_explain_10() {
     // Test 10: three push-down expression: system > 5.0 and town != 'tewsbury' and system < 7.0
     // even though there are more expressions,(count = 632 or town = 'reading'), in the filter
     let expected = vec![
-    "+-----------------------------------------+-------------------------------------------------------------------------------------------------------------------------------------------------+",
-    "| plan_type                               | plan                                                                                                                                            |",
-    "+-----------------------------------------+-------------------------------------------------------------------------------------------------------------------------------------------------+",
-    "| logical_plan                            | Projection: #count, #system, #time, #town                                                                                                       |",
+    "+-----------------------------------------+-----------------------------------------------------------------------------------------------------------------------------------------------------+",
+    "| plan_type                               | plan                                                                                                                                                |",
+    "+-----------------------------------------+-----------------------------------------------------------------------------------------------------------------------------------------------------+",
+    "| logical_plan                            | Projection: #count, #system, #time, #town                                                                                                           |",
     "|                                         |   Filter: #system Gt Float64(5) And Utf8(\"tewsbury\") NotEq #town And #system Lt Float64(7) And #count Eq Int64(632) Or #town Eq Utf8(\"reading\") |",
-    "|                                         |     TableScan: restaurant projection=None                                                                                                       |",
-    "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                                                                       |",
+    "|                                         |     TableScan: restaurant projection=None                                                                                                           |",
+    "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                                                                           |",
     "|                                         |   Filter: #system Gt Float64(5) And Utf8(\"tewsbury\") NotEq #town And #system Lt Float64(7) And #count Eq Int64(632) Or #town Eq Utf8(\"reading\") |",
-    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                         |",
-    "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                                                                       |",
+    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                             |",
+    "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                                                                           |",
     "|                                         |   Filter: #system Gt Float64(5) And Utf8(\"tewsbury\") NotEq #town And #system Lt Float64(7) And #count Eq Int64(632) Or #town Eq Utf8(\"reading\") |",
-    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                         |",
-    "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                                                                       |",
+    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                             |",
+    "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                                                                           |",
     "|                                         |   Filter: #system Gt Float64(5) And Utf8(\"tewsbury\") NotEq #town And #system Lt Float64(7) And #count Eq Int64(632) Or #town Eq Utf8(\"reading\") |",
-    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                         |",
-    "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                                                                       |",
+    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                             |",
+    "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                                                                           |",
     "|                                         |   Filter: #system Gt Float64(5) And Utf8(\"tewsbury\") NotEq #town And #system Lt Float64(7) And #count Eq Int64(632) Or #town Eq Utf8(\"reading\") |",
-    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                         |",
-    "| physical_plan                           | ProjectionExec: expr=[count, system, time, town]                                                                                                |",
-    "|                                         |   FilterExec: system > 5 AND tewsbury != CAST(town AS Utf8) AND system < 7 AND CAST(count AS Int64) = 632 OR CAST(town AS Utf8) = reading       |",
-    "|                                         |     IOxReadFilterNode: table_name=restaurant, chunks=1 predicate=Predicate                                                                      |",
-    "+-----------------------------------------+-------------------------------------------------------------------------------------------------------------------------------------------------+",
+    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                             |",
+    "| physical_plan                           | ProjectionExec: expr=[count, system, time, town]                                                                                                    |",
+    "|                                         |   FilterExec: system > 5 AND tewsbury != CAST(town AS Utf8) AND system < 7 AND CAST(count AS Int64) = 632 OR CAST(town AS Utf8) = reading           |",
+    "|                                         |     IOxReadFilterNode: table_name=restaurant, chunks=1 predicate=Predicate                                                                          |",
+    "+-----------------------------------------+-----------------------------------------------------------------------------------------------------------------------------------------------------+",
     ];
     run_sql_explain_test_case!(
         TwoMeasurementsPredicatePushDown {},
@@ -1100,28 +1213,28 @@ async fn sql_predicate_pushdown_explain_11() {
     // Test 11: four push-down expression: system > 5.0 and town != 'tewsbury' and system < 7.0 and
     // time > to_timestamp('1970-01-01T00:00:00.000000120+00:00') rewritten to time GT INT(130)
     let expected = vec![
-    "+-----------------------------------------+-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+",
-    "| plan_type                               | plan                                                                                                                                                                                                                  |",
-    "+-----------------------------------------+-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+",
-    "| logical_plan                            | Projection: #count, #system, #time, #town                                                                                                                                                                             |",
+    "+-----------------------------------------+-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+",
+    "| plan_type                               | plan                                                                                                                                                                                                                        |",
+    "+-----------------------------------------+-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+",
+    "| logical_plan                            | Projection: #count, #system, #time, #town                                                                                                                                                                                   |",
     "|                                         |   Filter: Float64(5) Lt #system And #town NotEq Utf8(\"tewsbury\") And #system Lt Float64(7) And #count Eq Int64(632) Or #town Eq Utf8(\"reading\") And #time Gt totimestamp(Utf8(\"1970-01-01T00:00:00.000000130+00:00\")) |",
-    "|                                         |     TableScan: restaurant projection=None                                                                                                                                                                             |",
-    "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                                                                                                                                             |",
+    "|                                         |     TableScan: restaurant projection=None                                                                                                                                                                                   |",
+    "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                                                                                                                                                   |",
     "|                                         |   Filter: Float64(5) Lt #system And #town NotEq Utf8(\"tewsbury\") And #system Lt Float64(7) And #count Eq Int64(632) Or #town Eq Utf8(\"reading\") And #time Gt totimestamp(Utf8(\"1970-01-01T00:00:00.000000130+00:00\")) |",
-    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                                                                                               |",
-    "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                                                                                                                                             |",
+    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                                                                                                     |",
+    "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                                                                                                                                                   |",
     "|                                         |   Filter: Float64(5) Lt #system And #town NotEq Utf8(\"tewsbury\") And #system Lt Float64(7) And #count Eq Int64(632) Or #town Eq Utf8(\"reading\") And #time Gt totimestamp(Utf8(\"1970-01-01T00:00:00.000000130+00:00\")) |",
-    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                                                                                               |",
-    "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                                                                                                                                             |",
+    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                                                                                                     |",
+    "| logical_plan after projection_push_down | Projection: #count, #system, #time, #town                                                                                                                                                                                   |",
     "|                                         |   Filter: Float64(5) Lt #system And #town NotEq Utf8(\"tewsbury\") And #system Lt Float64(7) And #count Eq Int64(632) Or #town Eq Utf8(\"reading\") And #time Gt totimestamp(Utf8(\"1970-01-01T00:00:00.000000130+00:00\")) |",
-    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                                                                                               |",
-    "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                                                                                                                                             |",
+    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                                                                                                     |",
+    "| logical_plan after simplify_expressions | Projection: #count, #system, #time, #town                                                                                                                                                                                   |",
     "|                                         |   Filter: Float64(5) Lt #system And #town NotEq Utf8(\"tewsbury\") And #system Lt Float64(7) And #count Eq Int64(632) Or #town Eq Utf8(\"reading\") And #time Gt totimestamp(Utf8(\"1970-01-01T00:00:00.000000130+00:00\")) |",
-    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                                                                                               |",
-    "| physical_plan                           | ProjectionExec: expr=[count, system, time, town]                                                                                                                                                                      |",
-    "|                                         |   FilterExec: 5 < system AND CAST(town AS Utf8) != tewsbury AND system < 7 AND CAST(count AS Int64) = 632 OR CAST(town AS Utf8) = reading AND time > totimestamp(1970-01-01T00:00:00.000000130+00:00)                 |",
-    "|                                         |     IOxReadFilterNode: table_name=restaurant, chunks=1 predicate=Predicate                                                                                                                                            |",
-    "+-----------------------------------------+-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+",
+    "|                                         |     TableScan: restaurant projection=Some([0, 1, 2, 3])                                                                                                                                                                     |",
+    "| physical_plan                           | ProjectionExec: expr=[count, system, time, town]                                                                                                                                                                            |",
+    "|                                         |   FilterExec: 5 < system AND CAST(town AS Utf8) != tewsbury AND system < 7 AND CAST(count AS Int64) = 632 OR CAST(town AS Utf8) = reading AND time > totimestamp(1970-01-01T00:00:00.000000130+00:00)                       |",
+    "|                                         |     IOxReadFilterNode: table_name=restaurant, chunks=1 predicate=Predicate                                                                                                                                                  |",
+    "+-----------------------------------------+-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------+",
     ];
     run_sql_explain_test_case!(
         TwoMeasurementsPredicatePushDown {},
@@ -1132,7 +1245,6 @@ async fn sql_predicate_pushdown_explain_11() {
 
 #[tokio::test]
 async fn sql_deduplicate_1() {
-    // This current expected is wrong because deduplicate is not available yet
     let sql =
         "select time, state, city, min_temp, max_temp, area from h2o order by time, state, city";
     let expected = vec![
@@ -1140,19 +1252,15 @@ async fn sql_deduplicate_1() {
         "| time                          | state | city    | min_temp | max_temp | area |",
         "+-------------------------------+-------+---------+----------+----------+------+",
         "| 1970-01-01 00:00:00.000000050 | MA    | Boston  | 70.4     |          |      |",
-        "| 1970-01-01 00:00:00.000000150 | MA    | Bedford |          | 78.75    | 742  |",
-        "| 1970-01-01 00:00:00.000000150 | MA    | Bedford | 71.59    |          |      |", // duplicate
+        "| 1970-01-01 00:00:00.000000150 | MA    | Bedford | 71.59    | 78.75    | 742  |",
         "| 1970-01-01 00:00:00.000000250 | MA    | Andover |          | 69.2     |      |",
-        "| 1970-01-01 00:00:00.000000250 | MA    | Boston  |          | 75.4     |      |",
-        "| 1970-01-01 00:00:00.000000250 | MA    | Boston  | 65.4     |          |      |", // duplicate
+        "| 1970-01-01 00:00:00.000000250 | MA    | Boston  | 65.4     | 75.4     |      |",
         "| 1970-01-01 00:00:00.000000250 | MA    | Reading | 53.4     |          |      |",
         "| 1970-01-01 00:00:00.000000300 | CA    | SF      | 79       | 87.2     | 500  |",
-        "| 1970-01-01 00:00:00.000000300 | CA    | SJ      | 78.5     | 88       |      |", // duplicate
+        "| 1970-01-01 00:00:00.000000300 | CA    | SJ      | 78.5     | 88       |      |",
         "| 1970-01-01 00:00:00.000000350 | CA    | SJ      | 75.5     | 84.08    |      |",
-        "| 1970-01-01 00:00:00.000000400 | MA    | Bedford |          | 80.75    | 742  |",
-        "| 1970-01-01 00:00:00.000000400 | MA    | Bedford | 65.22    |          | 750  |", // duplicate
+        "| 1970-01-01 00:00:00.000000400 | MA    | Bedford | 65.22    | 80.75    | 750  |",
         "| 1970-01-01 00:00:00.000000400 | MA    | Boston  | 65.4     | 82.67    |      |",
-        "| 1970-01-01 00:00:00.000000400 | MA    | Boston  | 68.4     |          |      |", // duplicate
         "| 1970-01-01 00:00:00.000000450 | CA    | SJ      | 77       | 90.7     |      |",
         "| 1970-01-01 00:00:00.000000500 | CA    | SJ      | 69.5     | 88.2     |      |",
         "| 1970-01-01 00:00:00.000000600 | MA    | Bedford |          | 88.75    | 742  |",
@@ -1170,28 +1278,33 @@ async fn sql_deduplicate_1() {
 async fn sql_deduplicate_2() {
     // Plan with order by
     let expected = vec![
-        "+-----------------------------------------+----------------------------------------------------------------------------+",
-        "| plan_type                               | plan                                                                       |",
-        "+-----------------------------------------+----------------------------------------------------------------------------+",
-        "| logical_plan                            | Sort: #time ASC NULLS FIRST, #state ASC NULLS FIRST, #city ASC NULLS FIRST |",
-        "|                                         |   Projection: #time, #state, #city, #min_temp, #max_temp, #area            |",
-        "|                                         |     TableScan: h2o projection=None                                         |",
-        "| logical_plan after projection_push_down | Sort: #time ASC NULLS FIRST, #state ASC NULLS FIRST, #city ASC NULLS FIRST |",
-        "|                                         |   Projection: #time, #state, #city, #min_temp, #max_temp, #area            |",
-        "|                                         |     TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])                     |",
-        "| logical_plan after simplify_expressions | Sort: #time ASC NULLS FIRST, #state ASC NULLS FIRST, #city ASC NULLS FIRST |",
-        "|                                         |   Projection: #time, #state, #city, #min_temp, #max_temp, #area            |",
-        "|                                         |     TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])                     |",
-        "| logical_plan after projection_push_down | Sort: #time ASC NULLS FIRST, #state ASC NULLS FIRST, #city ASC NULLS FIRST |",
-        "|                                         |   Projection: #time, #state, #city, #min_temp, #max_temp, #area            |",
-        "|                                         |     TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])                     |",
-        "| logical_plan after simplify_expressions | Sort: #time ASC NULLS FIRST, #state ASC NULLS FIRST, #city ASC NULLS FIRST |",
-        "|                                         |   Projection: #time, #state, #city, #min_temp, #max_temp, #area            |",
-        "|                                         |     TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])                     |",
-        "| physical_plan                           | SortExec: [time ASC,state ASC,city ASC]                                    |",
-        "|                                         |   ProjectionExec: expr=[time, state, city, min_temp, max_temp, area]       |",
-        "|                                         |     IOxReadFilterNode: table_name=h2o, chunks=4 predicate=Predicate        |",
-        "+-----------------------------------------+----------------------------------------------------------------------------+",
+        "+-----------------------------------------+----------------------------------------------------------------------------------+",
+        "| plan_type                               | plan                                                                             |",
+        "+-----------------------------------------+----------------------------------------------------------------------------------+",
+        "| logical_plan                            | Sort: #time ASC NULLS FIRST, #state ASC NULLS FIRST, #city ASC NULLS FIRST       |",
+        "|                                         |   Projection: #time, #state, #city, #min_temp, #max_temp, #area                  |",
+        "|                                         |     TableScan: h2o projection=None                                               |",
+        "| logical_plan after projection_push_down | Sort: #time ASC NULLS FIRST, #state ASC NULLS FIRST, #city ASC NULLS FIRST       |",
+        "|                                         |   Projection: #time, #state, #city, #min_temp, #max_temp, #area                  |",
+        "|                                         |     TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])                           |",
+        "| logical_plan after simplify_expressions | Sort: #time ASC NULLS FIRST, #state ASC NULLS FIRST, #city ASC NULLS FIRST       |",
+        "|                                         |   Projection: #time, #state, #city, #min_temp, #max_temp, #area                  |",
+        "|                                         |     TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])                           |",
+        "| logical_plan after projection_push_down | Sort: #time ASC NULLS FIRST, #state ASC NULLS FIRST, #city ASC NULLS FIRST       |",
+        "|                                         |   Projection: #time, #state, #city, #min_temp, #max_temp, #area                  |",
+        "|                                         |     TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])                           |",
+        "| logical_plan after simplify_expressions | Sort: #time ASC NULLS FIRST, #state ASC NULLS FIRST, #city ASC NULLS FIRST       |",
+        "|                                         |   Projection: #time, #state, #city, #min_temp, #max_temp, #area                  |",
+        "|                                         |     TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])                           |",
+        "| physical_plan                           | SortExec: [time ASC,state ASC,city ASC]                                          |",
+        "|                                         |   ProjectionExec: expr=[time, state, city, min_temp, max_temp, area]             |",
+        "|                                         |     ExecutionPlan(PlaceHolder)                                                   |",
+        "|                                         |       DeduplicateExec: tags=[city, state] sort_key=[state, city, time] streaming |",
+        "|                                         |         SortedMergeExec: sort_columns=[state, city, time]                        |",
+        "|                                         |           IOxReadFilterNode: table_name=h2o, chunks=2 predicate=Predicate        |",
+        "|                                         |       IOxReadFilterNode: table_name=h2o, chunks=1 predicate=Predicate            |",
+        "|                                         |       IOxReadFilterNode: table_name=h2o, chunks=1 predicate=Predicate            |",
+        "+-----------------------------------------+----------------------------------------------------------------------------------+",
     ];
     let sql = "explain verbose select time, state, city, min_temp, max_temp, area from h2o order by time, state, city";
     run_sql_explain_test_case!(OneMeasurementThreeChunksWithDuplicates {}, sql, &expected);
@@ -1201,22 +1314,27 @@ async fn sql_deduplicate_2() {
 async fn sql_deduplicate_3() {
     // plan without order by
     let expected = vec![
-    "+-----------------------------------------+--------------------------------------------------------------------+",
-    "| plan_type                               | plan                                                               |",
-    "+-----------------------------------------+--------------------------------------------------------------------+",
-    "| logical_plan                            | Projection: #time, #state, #city, #min_temp, #max_temp, #area      |",
-    "|                                         |   TableScan: h2o projection=None                                   |",
-    "| logical_plan after projection_push_down | Projection: #time, #state, #city, #min_temp, #max_temp, #area      |",
-    "|                                         |   TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])               |",
-    "| logical_plan after simplify_expressions | Projection: #time, #state, #city, #min_temp, #max_temp, #area      |",
-    "|                                         |   TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])               |",
-    "| logical_plan after projection_push_down | Projection: #time, #state, #city, #min_temp, #max_temp, #area      |",
-    "|                                         |   TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])               |",
-    "| logical_plan after simplify_expressions | Projection: #time, #state, #city, #min_temp, #max_temp, #area      |",
-    "|                                         |   TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])               |",
-    "| physical_plan                           | ProjectionExec: expr=[time, state, city, min_temp, max_temp, area] |",
-    "|                                         |   IOxReadFilterNode: table_name=h2o, chunks=4 predicate=Predicate  |",
-    "+-----------------------------------------+--------------------------------------------------------------------+",
+    "+-----------------------------------------+--------------------------------------------------------------------------------+",
+    "| plan_type                               | plan                                                                           |",
+    "+-----------------------------------------+--------------------------------------------------------------------------------+",
+    "| logical_plan                            | Projection: #time, #state, #city, #min_temp, #max_temp, #area                  |",
+    "|                                         |   TableScan: h2o projection=None                                               |",
+    "| logical_plan after projection_push_down | Projection: #time, #state, #city, #min_temp, #max_temp, #area                  |",
+    "|                                         |   TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])                           |",
+    "| logical_plan after simplify_expressions | Projection: #time, #state, #city, #min_temp, #max_temp, #area                  |",
+    "|                                         |   TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])                           |",
+    "| logical_plan after projection_push_down | Projection: #time, #state, #city, #min_temp, #max_temp, #area                  |",
+    "|                                         |   TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])                           |",
+    "| logical_plan after simplify_expressions | Projection: #time, #state, #city, #min_temp, #max_temp, #area                  |",
+    "|                                         |   TableScan: h2o projection=Some([0, 1, 2, 3, 4, 5])                           |",
+    "| physical_plan                           | ProjectionExec: expr=[time, state, city, min_temp, max_temp, area]             |",
+    "|                                         |   ExecutionPlan(PlaceHolder)                                                   |",
+    "|                                         |     DeduplicateExec: tags=[city, state] sort_key=[state, city, time] streaming |",
+    "|                                         |       SortedMergeExec: sort_columns=[state, city, time]                        |",
+    "|                                         |         IOxReadFilterNode: table_name=h2o, chunks=2 predicate=Predicate        |",
+    "|                                         |     IOxReadFilterNode: table_name=h2o, chunks=1 predicate=Predicate            |",
+    "|                                         |     IOxReadFilterNode: table_name=h2o, chunks=1 predicate=Predicate            |",
+    "+-----------------------------------------+--------------------------------------------------------------------------------+",
     ];
     let sql = "explain verbose select time, state, city, min_temp, max_temp, area from h2o";
     run_sql_explain_test_case!(OneMeasurementThreeChunksWithDuplicates {}, sql, &expected);
@@ -1228,40 +1346,87 @@ async fn sql_deduplicate_4() {
     let sql =
         "EXPLAIN VERBOSE select state as name from h2o UNION ALL select city as name from h2o";
     let expected = vec![
-    "+-----------------------------------------+---------------------------------------------------------------------+",
-    "| plan_type                               | plan                                                                |",
-    "+-----------------------------------------+---------------------------------------------------------------------+",
-    "| logical_plan                            | Union                                                               |",
-    "|                                         |   Projection: #state AS name                                        |",
-    "|                                         |     TableScan: h2o projection=None                                  |",
-    "|                                         |   Projection: #city AS name                                         |",
-    "|                                         |     TableScan: h2o projection=None                                  |",
-    "| logical_plan after projection_push_down | Union                                                               |",
-    "|                                         |   Projection: #state AS name                                        |",
-    "|                                         |     TableScan: h2o projection=Some([4])                             |",
-    "|                                         |   Projection: #city AS name                                         |",
-    "|                                         |     TableScan: h2o projection=Some([1])                             |",
-    "| logical_plan after simplify_expressions | Union                                                               |",
-    "|                                         |   Projection: #state AS name                                        |",
-    "|                                         |     TableScan: h2o projection=Some([4])                             |",
-    "|                                         |   Projection: #city AS name                                         |",
-    "|                                         |     TableScan: h2o projection=Some([1])                             |",
-    "| logical_plan after projection_push_down | Union                                                               |",
-    "|                                         |   Projection: #state AS name                                        |",
-    "|                                         |     TableScan: h2o projection=Some([4])                             |",
-    "|                                         |   Projection: #city AS name                                         |",
-    "|                                         |     TableScan: h2o projection=Some([1])                             |",
-    "| logical_plan after simplify_expressions | Union                                                               |",
-    "|                                         |   Projection: #state AS name                                        |",
-    "|                                         |     TableScan: h2o projection=Some([4])                             |",
-    "|                                         |   Projection: #city AS name                                         |",
-    "|                                         |     TableScan: h2o projection=Some([1])                             |",
-    "| physical_plan                           | ExecutionPlan(PlaceHolder)                                          |",
-    "|                                         |   ProjectionExec: expr=[state as name]                              |",
-    "|                                         |     IOxReadFilterNode: table_name=h2o, chunks=4 predicate=Predicate |",
-    "|                                         |   ProjectionExec: expr=[city as name]                               |",
-    "|                                         |     IOxReadFilterNode: table_name=h2o, chunks=4 predicate=Predicate |",
-    "+-----------------------------------------+---------------------------------------------------------------------+",
+    "+-----------------------------------------+----------------------------------------------------------------------------------+",
+    "| plan_type                               | plan                                                                             |",
+    "+-----------------------------------------+----------------------------------------------------------------------------------+",
+    "| logical_plan                            | Union                                                                            |",
+    "|                                         |   Projection: #state AS name                                                     |",
+    "|                                         |     TableScan: h2o projection=None                                               |",
+    "|                                         |   Projection: #city AS name                                                      |",
+    "|                                         |     TableScan: h2o projection=None                                               |",
+    "| logical_plan after projection_push_down | Union                                                                            |",
+    "|                                         |   Projection: #state AS name                                                     |",
+    "|                                         |     TableScan: h2o projection=Some([4])                                          |",
+    "|                                         |   Projection: #city AS name                                                      |",
+    "|                                         |     TableScan: h2o projection=Some([1])                                          |",
+    "| logical_plan after simplify_expressions | Union                                                                            |",
+    "|                                         |   Projection: #state AS name                                                     |",
+    "|                                         |     TableScan: h2o projection=Some([4])                                          |",
+    "|                                         |   Projection: #city AS name                                                      |",
+    "|                                         |     TableScan: h2o projection=Some([1])                                          |",
+    "| logical_plan after projection_push_down | Union                                                                            |",
+    "|                                         |   Projection: #state AS name                                                     |",
+    "|                                         |     TableScan: h2o projection=Some([4])                                          |",
+    "|                                         |   Projection: #city AS name                                                      |",
+    "|                                         |     TableScan: h2o projection=Some([1])                                          |",
+    "| logical_plan after simplify_expressions | Union                                                                            |",
+    "|                                         |   Projection: #state AS name                                                     |",
+    "|                                         |     TableScan: h2o projection=Some([4])                                          |",
+    "|                                         |   Projection: #city AS name                                                      |",
+    "|                                         |     TableScan: h2o projection=Some([1])                                          |",
+    "| physical_plan                           | ExecutionPlan(PlaceHolder)                                                       |",
+    "|                                         |   ProjectionExec: expr=[state as name]                                           |",
+    "|                                         |     ExecutionPlan(PlaceHolder)                                                   |",
+    "|                                         |       DeduplicateExec: tags=[city, state] sort_key=[state, city, time] streaming |",
+    "|                                         |         SortedMergeExec: sort_columns=[state, city, time]                        |",
+    "|                                         |           IOxReadFilterNode: table_name=h2o, chunks=2 predicate=Predicate        |",
+    "|                                         |       IOxReadFilterNode: table_name=h2o, chunks=1 predicate=Predicate            |",
+    "|                                         |       IOxReadFilterNode: table_name=h2o, chunks=1 predicate=Predicate            |",
+    "|                                         |   ProjectionExec: expr=[city as name]                                            |",
+    "|                                         |     ExecutionPlan(PlaceHolder)                                                   |",
+    "|                                         |       DeduplicateExec: tags=[city, state] sort_key=[state, city, time] streaming |",
+    "|                                         |         SortedMergeExec: sort_columns=[state, city, time]                        |",
+    "|                                         |           IOxReadFilterNode: table_name=h2o, chunks=2 predicate=Predicate        |",
+    "|                                         |       IOxReadFilterNode: table_name=h2o, chunks=1 predicate=Predicate            |",
+    "|                                         |       IOxReadFilterNode: table_name=h2o, chunks=1 predicate=Predicate            |",
+    "+-----------------------------------------+----------------------------------------------------------------------------------+",
     ];
     run_sql_explain_test_case!(OneMeasurementThreeChunksWithDuplicates {}, sql, &expected);
 }
+
+#[tokio::test]
+async fn sql_explain_analyze() {
+    test_helpers::maybe_start_logging();
+    let scenarios = TwoMeasurements {}.make().await;
+    for scenario in scenarios {
+        let DbScenario {
+            scenario_name, db, ..
+        } = scenario;
+        let db = Arc::new(db);
+
+        println!("Running scenario '{}'", scenario_name);
+        let planner = SqlQueryPlanner::default();
+        let executor = db.executor();
+
+        let physical_plan = planner
+            .query(db, "EXPLAIN ANALYZE SELECT * from cpu", executor.as_ref())
+            .expect("built plan successfully");
+
+        let results: Vec<RecordBatch> =
+            executor.collect(physical_plan).await.expect("Running plan");
+
+        // the elapsed times vary from run to run, so only check the rows
+        let rows = arrow::util::pretty::pretty_format_batches(&results).unwrap();
+        assert!(rows.contains("| analyzed_plan |"), "{}", rows);
+        assert!(
+            rows.contains("ProjectionExec: expr=[region, time, user], metrics=[output_rows=2,"),
+            "{}",
+            rows
+        );
+        assert!(
+            rows.contains("| summary       | output_rows=2, elapsed="),
+            "{}",
+            rows
+        );
+    }
+}
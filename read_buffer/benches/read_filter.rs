@@ -49,7 +49,7 @@ fn read_filter_no_pred_vary_proj(c: &mut Criterion, chunk: &Chunk) {
             |b, _| {
                 b.iter(|| {
                     let result = chunk
-                        .read_filter("table", Predicate::default(), projection)
+                        .read_filter("table", Predicate::default(), projection, vec![])
                         .unwrap();
                     let rbs = result.collect::<Vec<_>>();
                     assert_eq!(rbs.len(), 1);
@@ -86,7 +86,7 @@ fn read_filter_with_pred_vary_proj(c: &mut Criterion, chunk: &Chunk) {
             |b, _| {
                 b.iter(|| {
                     let result = chunk
-                        .read_filter("table", predicate.clone(), Selection::All)
+                        .read_filter("table", predicate.clone(), Selection::All, vec![])
                         .unwrap();
                     let rbs = result.collect::<Vec<_>>();
                     assert_eq!(rbs.len(), 1);
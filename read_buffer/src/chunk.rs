@@ -3,12 +3,16 @@ use std::{
     convert::TryFrom,
 };
 
+use chrono::{DateTime, Utc};
 use metrics::{Gauge, GaugeValue, KeyValue};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use snafu::{OptionExt, ResultExt, Snafu};
 
 use arrow::record_batch::RecordBatch;
-use data_types::{chunk_metadata::ChunkColumnSummary, partition_metadata::TableSummary};
+use data_types::{
+    chunk_metadata::{ChunkColumnSummary, ReadBufferChunkSummary},
+    partition_metadata::TableSummary,
+};
 use internal_types::{schema::builder::Error as SchemaError, schema::Schema, selection::Selection};
 use observability_deps::tracing::info;
 
@@ -73,6 +77,9 @@ pub struct Chunk {
     //               call. Whilst this execution will probably be in the order of micro-seconds
     //               I plan to improve this situation in due course.
     pub(crate) chunk_data: RwLock<TableData>,
+
+    // The most recent time at which data in the chunk was queried.
+    time_of_last_access: Mutex<Option<DateTime<Utc>>>,
 }
 
 /// Tie data and meta-data together so that they can be wrapped in RWLock.
@@ -126,6 +133,7 @@ impl Chunk {
         Self {
             chunk_data: RwLock::new(TableData::default()),
             metrics,
+            time_of_last_access: Mutex::new(None),
         }
     }
 
@@ -140,6 +148,7 @@ impl Chunk {
                 data: vec![(table.name().to_owned(), table)].into_iter().collect(),
             }),
             metrics,
+            time_of_last_access: Mutex::new(None),
         }
     }
 
@@ -183,10 +192,33 @@ impl Chunk {
     }
 
     /// The total number of row groups in all tables in this chunk.
-    pub(crate) fn row_groups(&self) -> usize {
+    pub fn row_groups(&self) -> usize {
         self.chunk_data.read().row_groups
     }
 
+    /// The most recent time at which data in this chunk was queried, if
+    /// it has been queried at all.
+    pub fn time_of_last_access(&self) -> Option<DateTime<Utc>> {
+        *self.time_of_last_access.lock()
+    }
+
+    /// Returns storage statistics for this chunk.
+    pub fn storage_summary(&self) -> ReadBufferChunkSummary {
+        let chunk_data = self.chunk_data.read();
+        ReadBufferChunkSummary {
+            row_count: chunk_data.rows,
+            row_groups: chunk_data.row_groups,
+            compressed_bytes: Self::base_size() + chunk_data.size(),
+            raw_bytes: chunk_data.size_raw(true),
+            time_of_last_access: self.time_of_last_access(),
+        }
+    }
+
+    // Records that data in the chunk is being queried.
+    fn record_access(&self) {
+        *self.time_of_last_access.lock() = Some(Utc::now());
+    }
+
     /// The total number of tables in this chunk.
     pub(crate) fn tables(&self) -> usize {
         self.chunk_data.read().data.len()
@@ -220,6 +252,8 @@ impl Chunk {
         // update the meta-data for this chunk with contents of row group.
         chunk_data.rows += row_group.rows() as u64;
         chunk_data.row_groups += 1;
+        self.metrics
+            .update_row_statistics(row_group.rows() as usize);
 
         match chunk_data.data.entry(table_name.clone()) {
             Entry::Occupied(mut table_entry) => {
@@ -280,6 +314,8 @@ impl Chunk {
         // update the meta-data for this chunk with contents of row group.
         chunk_data.rows += row_group.rows() as u64;
         chunk_data.row_groups += 1;
+        self.metrics
+            .update_row_statistics(row_group.rows() as usize);
 
         // track new row group statistics to update column-based metrics.
         let storage_statistics = row_group.column_storage_statistics();
@@ -319,6 +355,8 @@ impl Chunk {
         if let Some(table) = chunk_data.data.remove(name) {
             chunk_data.rows -= table.rows();
             chunk_data.row_groups -= table.row_groups();
+            self.metrics
+                .remove_row_statistics(table.rows() as usize, table.row_groups());
         }
     }
 
@@ -328,7 +366,9 @@ impl Chunk {
 
     /// Returns selected data for the specified columns in the provided table.
     ///
-    /// Results may be filtered by conjunctive predicates.
+    /// Results may be filtered by conjunctive predicates. Rows satisfying any
+    /// of the `negated_predicates`, such as the rows removed by deletes, are
+    /// excluded from the results without being materialised.
     /// The `ReadBuffer` will optimally prune columns and tables to improve
     /// execution where possible.
     ///
@@ -344,7 +384,10 @@ impl Chunk {
         table_name: &str,
         predicate: Predicate,
         select_columns: Selection<'_>,
+        negated_predicates: Vec<Predicate>,
     ) -> Result<table::ReadFilterResults, Error> {
+        self.record_access();
+
         // read lock on chunk.
         let chunk_data = self.chunk_data.read();
 
@@ -353,7 +396,7 @@ impl Chunk {
             .get(table_name)
             .context(TableNotFound { table_name })?;
 
-        Ok(table.read_filter(&select_columns, &predicate))
+        Ok(table.read_filter(&select_columns, &predicate, &negated_predicates))
     }
 
     /// Returns an iterable collection of data in group columns and aggregate
@@ -371,6 +414,8 @@ impl Chunk {
         group_columns: &Selection<'_>,
         aggregates: &[(ColumnName<'_>, AggregateType)],
     ) -> Result<table::ReadAggregateResults> {
+        self.record_access();
+
         // read lock on chunk.
         let chunk_data = self.chunk_data.read();
 
@@ -544,6 +589,7 @@ impl Chunk {
         only_columns: Selection<'_>,
         dst: BTreeSet<String>,
     ) -> Result<BTreeSet<String>> {
+        self.record_access();
         let chunk_data = self.chunk_data.read();
 
         // TODO(edd): same potential contention as `table_names` but I'm ok
@@ -587,6 +633,7 @@ impl Chunk {
             Selection::Some(columns) => columns,
         };
 
+        self.record_access();
         let chunk_data = self.chunk_data.read();
 
         // TODO(edd): same potential contention as `table_names` but I'm ok
@@ -628,6 +675,12 @@ pub struct ChunkMetrics {
     // columns, further segmented by nullness. It is a building block for
     // tracking a measure of overall compression.
     column_raw_bytes_total: metrics::Gauge,
+
+    // This metric tracks the total number of rows stored in read buffer.
+    rows_total: Gauge,
+
+    // This metric tracks the total number of row groups in read buffer.
+    row_groups_total: Gauge,
 }
 
 impl ChunkMetrics {
@@ -654,6 +707,16 @@ impl ChunkMetrics {
                 Some("bytes"),
                 "The number of bytes used by all columns if they were uncompressed in the Read Buffer",
             ),
+            rows_total: domain.register_gauge_metric(
+                "row",
+                Some("total"),
+                "The number of rows within the Read Buffer",
+            ),
+            row_groups_total: domain.register_gauge_metric(
+                "row_group",
+                Some("total"),
+                "The number of row groups within the Read Buffer",
+            ),
         }
     }
 
@@ -668,9 +731,23 @@ impl ChunkMetrics {
             column_values_total: Gauge::new_unregistered(),
             column_bytes_total: Gauge::new_unregistered(),
             column_raw_bytes_total: Gauge::new_unregistered(),
+            rows_total: Gauge::new_unregistered(),
+            row_groups_total: Gauge::new_unregistered(),
         }
     }
 
+    // Updates row statistics for a row group added to the Read Buffer.
+    fn update_row_statistics(&mut self, rows: usize) {
+        self.rows_total.inc(rows, &[]);
+        self.row_groups_total.inc(1, &[]);
+    }
+
+    // Updates row statistics for a table removed from the Read Buffer.
+    fn remove_row_statistics(&mut self, rows: usize, row_groups: usize) {
+        self.rows_total.decr(rows, &[]);
+        self.row_groups_total.decr(row_groups, &[]);
+    }
+
     // Updates column storage statistics for the Read Buffer.
     fn update_column_storage_statistics(&mut self, statistics: &[Statistics]) {
         for stat in statistics {
@@ -1016,6 +1093,12 @@ mod test {
         r#"read_buffer_column_values{db="mydb",encoding="FIXEDN",log_data_type="bool",null="true"} 0"#,
         r#"read_buffer_column_values{db="mydb",encoding="RLE",log_data_type="string",null="false"} 9"#,
         r#"read_buffer_column_values{db="mydb",encoding="RLE",log_data_type="string",null="true"} 0"#,
+        "# HELP read_buffer_row_group_total The number of row groups within the Read Buffer",
+        "# TYPE read_buffer_row_group_total gauge",
+        r#"read_buffer_row_group_total{db="mydb"} 3"#,
+        "# HELP read_buffer_row_total The number of rows within the Read Buffer",
+        "# TYPE read_buffer_row_total gauge",
+        r#"read_buffer_row_total{db="mydb"} 9"#,
         "",
             ]
             .join("\n")
@@ -1064,6 +1147,12 @@ mod test {
                 r#"read_buffer_column_values{db="mydb",encoding="FIXEDN",log_data_type="bool",null="true"} 0"#,
                 r#"read_buffer_column_values{db="mydb",encoding="RLE",log_data_type="string",null="false"} 0"#,
                 r#"read_buffer_column_values{db="mydb",encoding="RLE",log_data_type="string",null="true"} 0"#,
+                "# HELP read_buffer_row_group_total The number of row groups within the Read Buffer",
+                "# TYPE read_buffer_row_group_total gauge",
+                r#"read_buffer_row_group_total{db="mydb"} 0"#,
+                "# HELP read_buffer_row_total The number of rows within the Read Buffer",
+                "# TYPE read_buffer_row_total gauge",
+                r#"read_buffer_row_total{db="mydb"} 0"#,
                 "",
             ]
             .join("\n")
@@ -1124,6 +1213,30 @@ mod test {
         assert!(!chunk.has_table("b_table"));
     }
 
+    #[test]
+    fn storage_summary() {
+        let mut chunk = Chunk::new(ChunkMetrics::new_unregistered());
+        chunk.upsert_table("a_table", gen_recordbatch());
+        chunk.upsert_table("a_table", gen_recordbatch());
+
+        let summary = chunk.storage_summary();
+        assert_eq!(summary.row_count, 6);
+        assert_eq!(summary.row_groups, 2);
+        assert_eq!(summary.compressed_bytes, chunk.size());
+        assert_eq!(summary.raw_bytes, chunk.size_raw(true));
+        assert!(summary.time_of_last_access.is_none());
+
+        let before_access = Utc::now();
+        chunk
+            .read_filter("a_table", Predicate::default(), Selection::All, vec![])
+            .unwrap();
+        let after_access = Utc::now();
+
+        let time_of_last_access = chunk.storage_summary().time_of_last_access.unwrap();
+        assert!(before_access <= time_of_last_access);
+        assert!(time_of_last_access <= after_access);
+    }
+
     #[test]
     fn table_summaries() {
         let mut chunk = Chunk::new(ChunkMetrics::new_unregistered());
@@ -1277,7 +1390,7 @@ mod test {
             Predicate::with_time_range(&[BinaryExpr::from(("env", "=", "us-west"))], 100, 205); // filter on time
 
         let mut itr = chunk
-            .read_filter("Coolverine", predicate, Selection::All)
+            .read_filter("Coolverine", predicate, Selection::All, vec![])
             .unwrap();
 
         let exp_env_values = Values::Dictionary(vec![0], vec![Some("us-west")]);
@@ -388,6 +388,12 @@ impl Column {
         value: &Value<'_>,
         dst: RowIDs,
     ) -> RowIDsOption {
+        // Regular expressions only apply to string columns, so no rows in any
+        // other column can match them.
+        if op.is_regex() && !matches!(self, Self::String(_, _)) {
+            return RowIDsOption::None(dst);
+        }
+
         // If we can get an answer using only the meta-data on the column then
         // return that answer.
         match self.evaluate_predicate_on_meta(&op, &value) {
@@ -412,6 +418,34 @@ impl Column {
         RowIDsOption::Some(row_ids)
     }
 
+    /// Determine the set of row ids containing any of `values`, i.e., that
+    /// satisfy `column IN (values)`.
+    pub fn row_ids_filter_in(&self, values: &[&str], dst: RowIDs) -> RowIDsOption {
+        let row_ids = match &self {
+            Self::String(meta, data) => {
+                // Only values within the range of the column need to be
+                // looked up.
+                let values = values
+                    .iter()
+                    .filter(|v| meta.might_contain_value(**v))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if values.is_empty() {
+                    return RowIDsOption::None(dst);
+                }
+
+                data.row_ids_in(&values, dst)
+            }
+            // Only string columns can contain string values.
+            _ => return RowIDsOption::None(dst),
+        };
+
+        if row_ids.is_empty() {
+            return RowIDsOption::None(row_ids);
+        }
+        RowIDsOption::Some(row_ids)
+    }
+
     /// Determine the set of row ids that satisfy both of the predicates.
     ///
     /// Note: this method is a special case for common range-based predicates
@@ -504,6 +538,14 @@ impl Column {
                     return PredicateMatch::All; // all rows are going to match.
                 }
             }
+
+            // A regular expression can't be evaluated against the range of
+            // values in the column, but a column containing only NULL values
+            // can't match any rows.
+            cmp::Operator::RegexMatch | cmp::Operator::RegexNotMatch => {}
+
+            // IN lists are evaluated by `row_ids_filter_in`.
+            cmp::Operator::In => unreachable!("IN lists are filtered via row_ids_filter_in"),
         }
 
         if self.predicate_matches_no_values(&op, &value) {
@@ -820,6 +862,10 @@ impl<T: PartialOrd + std::fmt::Debug> MetaData<T> {
                 cmp::Operator::LT => range.1 < u,
                 // all values in column <= v
                 cmp::Operator::LTE => range.1 <= u,
+                // can't be determined from the range
+                cmp::Operator::RegexMatch | cmp::Operator::RegexNotMatch => false,
+                // IN lists are evaluated by `row_ids_filter_in`
+                cmp::Operator::In => false,
             },
             None => false, // only null values in column.
         }
@@ -846,6 +892,10 @@ impl<T: PartialOrd + std::fmt::Debug> MetaData<T> {
                 cmp::Operator::LT => range.0 >= u,
                 // min value in column is `> v` so no values can be `<= v`
                 cmp::Operator::LTE => range.0 > u,
+                // can't be determined from the range
+                cmp::Operator::RegexMatch | cmp::Operator::RegexNotMatch => false,
+                // IN lists are evaluated by `row_ids_filter_in`
+                cmp::Operator::In => false,
             },
             None => true, // only null values in column so no values satisfy `v`
         }
@@ -1348,6 +1398,14 @@ impl RowIDs {
         };
     }
 
+    /// Removes the row ids in `other` from `self`.
+    pub fn difference(&mut self, other: &Self) {
+        match (self, other) {
+            (Self::Bitmap(inner), Self::Bitmap(other)) => inner.andnot_inplace(other),
+            (_, _) => unimplemented!("currently unsupported"),
+        };
+    }
+
     pub fn union(&mut self, other: &Self) {
         match (self, other) {
             (Self::Bitmap(inner), Self::Bitmap(other)) => inner.or_inplace(other),
@@ -1852,6 +1910,92 @@ mod test {
         assert_eq!(dst_buffer.to_vec(), vec![0, 2, 6]);
     }
 
+    #[test]
+    fn row_ids_filter_in() {
+        let input = &[
+            Some("Badlands"),
+            None,
+            Some("Racing in the Street"),
+            Some("Streets of Fire"),
+            None,
+            Some("Darkness on the Edge of Town"),
+        ];
+
+        let col = Column::from(&input[..]);
+        let row_ids = col.row_ids_filter_in(
+            &["Badlands", "Streets of Fire", "Born to Run"],
+            RowIDs::new_bitmap(),
+        );
+        match row_ids {
+            RowIDsOption::Some(dst) => assert_eq!(dst.to_vec(), vec![0, 3]),
+            _ => panic!("expected some rows"),
+        }
+
+        // values outside the range of the column
+        let row_ids =
+            col.row_ids_filter_in(&["Adam Raised a Cain", "The River"], RowIDs::new_bitmap());
+        assert!(matches!(row_ids, RowIDsOption::None(_)));
+
+        // values within the range but not in the column
+        let row_ids = col.row_ids_filter_in(&["Prove It All Night"], RowIDs::new_bitmap());
+        assert!(matches!(row_ids, RowIDsOption::None(_)));
+
+        // string values are never in non-string columns
+        let col = Column::from(&[10_i64, 20, 30][..]);
+        let row_ids = col.row_ids_filter_in(&["10"], RowIDs::new_bitmap());
+        assert!(matches!(row_ids, RowIDsOption::None(_)));
+    }
+
+    #[test]
+    fn row_ids_filter_regex() {
+        let input = &[
+            Some("Badlands"),
+            None,
+            Some("Racing in the Street"),
+            Some("Streets of Fire"),
+            None,
+            Some("Darkness on the Edge of Town"),
+        ];
+
+        let col = Column::from(&input[..]);
+        let row_ids = col.row_ids_filter(
+            &cmp::Operator::RegexMatch,
+            &Value::from("(?i)street"),
+            RowIDs::new_bitmap(),
+        );
+        match row_ids {
+            RowIDsOption::Some(dst) => assert_eq!(dst.to_vec(), vec![2, 3]),
+            _ => panic!("expected some rows"),
+        }
+
+        // NULL values don't match either operator
+        let row_ids = col.row_ids_filter(
+            &cmp::Operator::RegexNotMatch,
+            &Value::from("(?i)street"),
+            RowIDs::new_bitmap(),
+        );
+        match row_ids {
+            RowIDsOption::Some(dst) => assert_eq!(dst.to_vec(), vec![0, 5]),
+            _ => panic!("expected some rows"),
+        }
+
+        let row_ids = col.row_ids_filter(
+            &cmp::Operator::RegexMatch,
+            &Value::from("^Nebraska$"),
+            RowIDs::new_bitmap(),
+        );
+        assert!(matches!(row_ids, RowIDsOption::None(_)));
+
+        // regular expressions never match non-string columns
+        let col = Column::from(&[10_i64, 20, 30][..]);
+        let row_ids = col.row_ids_filter(
+            &cmp::Operator::RegexMatch,
+            &Value::from(".*"),
+            RowIDs::new_bitmap(),
+        );
+        assert!(matches!(row_ids, RowIDsOption::None(_)));
+    }
+
     #[test]
     fn row_ids_filter_int() {
         let input = &[100_i64, 200, 300, 2, 200, 22, 30];
@@ -10,6 +10,20 @@ pub enum Operator {
     GTE,
     LT,
     LTE,
+    // The value matches a regular expression.
+    RegexMatch,
+    // The value does not match a regular expression.
+    RegexNotMatch,
+    // The value is equal to one of a list of values.
+    In,
+}
+
+impl Operator {
+    /// Returns true if the operator compares values against a regular
+    /// expression rather than a literal value.
+    pub fn is_regex(&self) -> bool {
+        matches!(self, Self::RegexMatch | Self::RegexNotMatch)
+    }
 }
 
 impl Display for Operator {
@@ -24,6 +38,9 @@ impl Display for Operator {
                 Self::GTE => ">=",
                 Self::LT => "<",
                 Self::LTE => "<=",
+                Self::RegexMatch => "=~",
+                Self::RegexNotMatch => "!~",
+                Self::In => "IN",
             }
         )
     }
@@ -40,6 +57,8 @@ impl TryFrom<&str> for Operator {
             ">=" => Ok(Self::GTE),
             "<" => Ok(Self::LT),
             "<=" => Ok(Self::LTE),
+            "=~" => Ok(Self::RegexMatch),
+            "!~" => Ok(Self::RegexNotMatch),
             v => Err(format!("unknown operator {:?}", v)),
         }
     }
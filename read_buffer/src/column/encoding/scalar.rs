@@ -1,3 +1,4 @@
+pub mod delta;
 pub mod fixed;
 pub mod fixed_null;
 pub mod rle;
@@ -8,6 +9,7 @@ use std::{fmt::Debug, fmt::Display};
 
 use crate::column::{cmp, RowIDs};
 
+pub use delta::Delta;
 pub use fixed::Fixed;
 pub use fixed_null::FixedNull;
 pub use rle::RLE;
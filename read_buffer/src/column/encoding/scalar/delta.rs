@@ -0,0 +1,401 @@
+//! An encoding for non-nullable `i64` values that change by a constant amount
+//! for long stretches, such as the timestamps of regularly sampled series.
+//!
+//! The values are stored as runs, each of which holds its first value and the
+//! difference between consecutive values within it. A run ends wherever the
+//! difference between two consecutive values changes, that is wherever the
+//! delta-of-delta is non-zero, so a column of evenly spaced timestamps is
+//! stored as a single run regardless of its number of rows.
+use either::Either;
+use std::mem::size_of;
+
+use super::ScalarEncoding;
+use crate::column::{cmp, RowIDs};
+
+pub const ENCODING_NAME: &str = "DELTA";
+
+/// The values of the rows `start_row..` up to the start of the next run are
+/// `first`, `first + delta`, `first + 2 * delta`, ...
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Run {
+    start_row: u32,
+    first: i64,
+    delta: i64,
+}
+
+impl Run {
+    // The value of the row `row_id` in the run.
+    //
+    // Wrapping arithmetic is fine because every value of the run is a value
+    // that was encoded, so the result always fits into an `i64`.
+    fn value(&self, row_id: u32) -> i64 {
+        let offset = (row_id - self.start_row) as i64;
+        self.first.wrapping_add(self.delta.wrapping_mul(offset))
+    }
+}
+
+/// A Delta encoding stores runs of values with a constant difference between
+/// consecutive values. Delta encodings do not support NULL values, and are
+/// suitable for columns of timestamps too large to be byte trimmed.
+#[derive(Debug, Default)]
+pub struct Delta {
+    runs: Vec<Run>,
+    num_rows: u32,
+}
+
+impl std::fmt::Display for Delta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] rows: {:?}, runs: {:?}, size: {}",
+            self.name(),
+            self.num_rows,
+            self.runs.len(),
+            self.size()
+        )
+    }
+}
+
+impl Delta {
+    /// Encodes the values of the provided iterator.
+    pub fn new_from_iter(values: impl Iterator<Item = i64>) -> Self {
+        let mut enc = Self::default();
+
+        // the last value and the number of rows of the current run
+        let mut prev = 0_i64;
+        let mut run_len = 0_u32;
+        for v in values {
+            match enc.runs.last_mut() {
+                Some(run) if run_len == 1 => match v.checked_sub(prev) {
+                    Some(delta) => {
+                        run.delta = delta;
+                        run_len += 1;
+                    }
+                    None => run_len = 0,
+                },
+                Some(run) if prev.checked_add(run.delta) == Some(v) => run_len += 1,
+                _ => run_len = 0,
+            }
+
+            if run_len == 0 {
+                enc.runs.push(Run {
+                    start_row: enc.num_rows,
+                    first: v,
+                    delta: 0,
+                });
+                run_len = 1;
+            }
+            prev = v;
+            enc.num_rows += 1;
+        }
+
+        enc
+    }
+
+    /// The number of runs the values are stored in.
+    pub fn num_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    // The index of the run holding the row `row_id`.
+    fn run_index(&self, row_id: u32) -> usize {
+        match self.runs.binary_search_by_key(&row_id, |run| run.start_row) {
+            Ok(idx) => idx,
+            Err(idx) => idx - 1,
+        }
+    }
+
+    // The rows `[from, to)` covered by the run at index `idx`.
+    fn run_rows(&self, idx: usize) -> (u32, u32) {
+        let to = self
+            .runs
+            .get(idx + 1)
+            .map_or(self.num_rows, |next| next.start_row);
+        (self.runs[idx].start_row, to)
+    }
+
+    // Iterates over all values of the encoding in row order.
+    fn iter(&self) -> impl Iterator<Item = i64> + '_ {
+        (0..self.runs.len()).flat_map(move |idx| {
+            let run = self.runs[idx];
+            let (from, to) = self.run_rows(idx);
+            (from..to).map(move |row_id| run.value(row_id))
+        })
+    }
+
+    // Handles finding all rows whose value satisfies `predicate`. Ranges of
+    // matching rows are collected up and added in bulk to the bitmap.
+    fn row_ids_matching(&self, predicate: impl Fn(i64) -> bool, mut dst: RowIDs) -> RowIDs {
+        dst.clear();
+
+        let mut found: Option<u32> = None;
+        for (idx, run) in self.runs.iter().enumerate() {
+            let (from, to) = self.run_rows(idx);
+
+            // all values of the run are equal, so only one needs checking
+            if run.delta == 0 {
+                match (predicate(run.first), found) {
+                    (true, None) => found = Some(from),
+                    (false, Some(start)) => {
+                        dst.add_range(start, from);
+                        found = None;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            let mut v = run.first;
+            for row_id in from..to {
+                match (predicate(v), found) {
+                    (true, None) => found = Some(row_id),
+                    (false, Some(start)) => {
+                        dst.add_range(start, row_id);
+                        found = None;
+                    }
+                    _ => {}
+                }
+                v = v.wrapping_add(run.delta);
+            }
+        }
+
+        // add any remaining range.
+        if let Some(start) = found {
+            dst.add_range(start, self.num_rows);
+        }
+        dst
+    }
+}
+
+// Determines if `v op value` holds for one of the operators supported by
+// scalar encodings.
+fn compare(v: i64, op: &cmp::Operator, value: i64) -> bool {
+    match op {
+        cmp::Operator::Equal => v == value,
+        cmp::Operator::NotEqual => v != value,
+        cmp::Operator::GT => v > value,
+        cmp::Operator::GTE => v >= value,
+        cmp::Operator::LT => v < value,
+        cmp::Operator::LTE => v <= value,
+        op => panic!("unsupported operator provided: {:?}", op),
+    }
+}
+
+impl ScalarEncoding<i64> for Delta {
+    fn name(&self) -> &'static str {
+        ENCODING_NAME
+    }
+
+    fn num_rows(&self) -> u32 {
+        self.num_rows
+    }
+
+    /// Encoded data size including `Self` - an "accurate" estimation.
+    fn size(&self) -> usize {
+        size_of::<Self>() + (size_of::<Run>() * self.runs.len())
+    }
+
+    fn size_raw(&self, _: bool) -> usize {
+        size_of::<Vec<i64>>() + (size_of::<i64>() * self.num_rows as usize)
+    }
+
+    fn null_count(&self) -> u32 {
+        0 // this encoding never contains NULL values
+    }
+
+    fn has_any_non_null_value(&self) -> bool {
+        self.num_rows > 0 // this encoding never contains NULL values
+    }
+
+    fn has_non_null_value(&self, _: &[u32]) -> bool {
+        self.num_rows > 0 // this encoding never contains NULL values
+    }
+
+    fn value(&self, row_id: u32) -> Option<i64> {
+        assert!(row_id < self.num_rows, "row_id {} out of bounds", row_id);
+        Some(self.runs[self.run_index(row_id)].value(row_id))
+    }
+
+    fn values(&self, row_ids: &[u32]) -> Either<Vec<i64>, Vec<Option<i64>>> {
+        Either::Left(
+            row_ids
+                .iter()
+                .map(|&row_id| self.value(row_id).unwrap())
+                .collect(),
+        )
+    }
+
+    fn all_values(&self) -> Either<Vec<i64>, Vec<Option<i64>>> {
+        let mut dst = Vec::with_capacity(self.num_rows as usize);
+        dst.extend(self.iter());
+        Either::Left(dst)
+    }
+
+    fn count(&self, row_ids: &[u32]) -> u32 {
+        row_ids.len() as u32
+    }
+
+    fn sum(&self, row_ids: &[u32]) -> Option<i64> {
+        let mut result = 0_i64;
+        for &row_id in row_ids {
+            result += self.value(row_id).unwrap();
+        }
+        Some(result)
+    }
+
+    fn min(&self, row_ids: &[u32]) -> Option<i64> {
+        row_ids
+            .iter()
+            .map(|&row_id| self.value(row_id).unwrap())
+            .min()
+    }
+
+    fn max(&self, row_ids: &[u32]) -> Option<i64> {
+        row_ids
+            .iter()
+            .map(|&row_id| self.value(row_id).unwrap())
+            .max()
+    }
+
+    fn row_ids_filter(&self, value: i64, op: &cmp::Operator, dst: RowIDs) -> RowIDs {
+        self.row_ids_matching(|v| compare(v, op, value), dst)
+    }
+
+    fn row_ids_filter_range(
+        &self,
+        left: (i64, &cmp::Operator),
+        right: (i64, &cmp::Operator),
+        dst: RowIDs,
+    ) -> RowIDs {
+        match (&left.1, &right.1) {
+            (cmp::Operator::GT, cmp::Operator::LT)
+            | (cmp::Operator::GT, cmp::Operator::LTE)
+            | (cmp::Operator::GTE, cmp::Operator::LT)
+            | (cmp::Operator::GTE, cmp::Operator::LTE)
+            | (cmp::Operator::LT, cmp::Operator::GT)
+            | (cmp::Operator::LT, cmp::Operator::GTE)
+            | (cmp::Operator::LTE, cmp::Operator::GT)
+            | (cmp::Operator::LTE, cmp::Operator::GTE) => self.row_ids_matching(
+                |v| compare(v, left.1, left.0) && compare(v, right.1, right.0),
+                dst,
+            ),
+
+            (a, b) => panic!("unsupported operators provided: ({:?}, {:?})", a, b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::cmp::Operator;
+    use super::*;
+
+    // Timestamps sampled every 10s, with a gap, a change of interval and a
+    // duplicate value.
+    fn timestamps() -> Vec<i64> {
+        let base = 1_600_000_000_000_000_000_i64;
+        let second = 1_000_000_000;
+        let mut values: Vec<i64> = (0..10).map(|i| base + i * 10 * second).collect();
+        values.extend((0..5).map(|i| base + 500 * second + i * 60 * second));
+        values.push(base + 740 * second);
+        values
+    }
+
+    #[test]
+    fn new_from_iter() {
+        let values = timestamps();
+        let enc = Delta::new_from_iter(values.iter().cloned());
+
+        assert_eq!(enc.num_rows(), 16);
+        // the regular intervals are stored as one run each
+        assert_eq!(enc.num_runs(), 3);
+        assert_eq!(enc.all_values().unwrap_left(), values);
+
+        for (row_id, v) in values.iter().enumerate() {
+            assert_eq!(enc.value(row_id as u32), Some(*v));
+        }
+
+        assert_eq!(Delta::new_from_iter(std::iter::empty()).num_runs(), 0);
+        assert_eq!(
+            Delta::new_from_iter(vec![3, 3, 3].into_iter()).num_runs(),
+            1
+        );
+    }
+
+    #[test]
+    fn new_from_iter_overflow() {
+        let values = vec![i64::MIN, i64::MAX, i64::MIN, 0, i64::MAX];
+        let enc = Delta::new_from_iter(values.iter().cloned());
+        assert_eq!(enc.all_values().unwrap_left(), values);
+        assert_eq!(enc.values(&[1, 4]).unwrap_left(), vec![i64::MAX, i64::MAX]);
+    }
+
+    #[test]
+    fn size() {
+        let enc = Delta::new_from_iter(0..10_000);
+        assert_eq!(enc.num_runs(), 1);
+        assert!(enc.size() < 100);
+        assert_eq!(enc.size_raw(true), 24 + 80_000);
+    }
+
+    #[test]
+    fn aggregates() {
+        let values = timestamps();
+        let enc = Delta::new_from_iter(values.iter().cloned());
+
+        let row_ids = &[0, 9, 10, 15];
+        assert_eq!(
+            enc.values(row_ids).unwrap_left(),
+            row_ids
+                .iter()
+                .map(|&i| values[i as usize])
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(enc.min(row_ids), Some(values[0]));
+        assert_eq!(enc.max(row_ids), Some(values[15]));
+        assert_eq!(enc.count(row_ids), 4);
+
+        let enc = Delta::new_from_iter(vec![1, 2, 3, 10, 10].into_iter());
+        assert_eq!(enc.sum(&[0, 2, 3, 4]), Some(24));
+    }
+
+    #[test]
+    fn row_ids_filter() {
+        let enc = Delta::new_from_iter(vec![1, 3, 5, 7, 7, 7, 4, 1].into_iter());
+
+        let cases = vec![
+            (7, Operator::Equal, vec![3, 4, 5]),
+            (7, Operator::NotEqual, vec![0, 1, 2, 6, 7]),
+            (4, Operator::GT, vec![2, 3, 4, 5]),
+            (4, Operator::GTE, vec![2, 3, 4, 5, 6]),
+            (4, Operator::LT, vec![0, 1, 7]),
+            (4, Operator::LTE, vec![0, 1, 6, 7]),
+            (8, Operator::Equal, vec![]),
+        ];
+
+        for (value, op, expected) in cases {
+            let dst = enc.row_ids_filter(value, &op, RowIDs::new_vector());
+            assert_eq!(dst.unwrap_vector(), &expected, "{:?} {}", op, value);
+        }
+    }
+
+    #[test]
+    fn row_ids_filter_range() {
+        let values = timestamps();
+        let enc = Delta::new_from_iter(values.iter().cloned());
+
+        let dst = enc.row_ids_filter_range(
+            (values[2], &Operator::GTE),
+            (values[11], &Operator::LT),
+            RowIDs::new_vector(),
+        );
+        assert_eq!(dst.unwrap_vector(), &(2..11).collect::<Vec<u32>>());
+
+        let dst = enc.row_ids_filter_range(
+            (values[15], &Operator::LTE),
+            (values[0], &Operator::GT),
+            RowIDs::new_vector(),
+        );
+        assert_eq!(dst.unwrap_vector(), &(1..16).collect::<Vec<u32>>());
+    }
+}
@@ -379,6 +379,7 @@ where
             cmp::Operator::LT | cmp::Operator::LTE | cmp::Operator::GT | cmp::Operator::GTE => {
                 self.row_ids_cmp(value, op, dst)
             }
+            op => unreachable!("{:?} is an invalid operator", op),
         }
     }
 
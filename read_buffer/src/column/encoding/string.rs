@@ -89,6 +89,15 @@ impl Encoding {
         }
     }
 
+    /// Populates the provided destination container with the row ids for rows
+    /// containing any of `values`.
+    pub fn row_ids_in(&self, values: &[&str], dst: RowIDs) -> RowIDs {
+        match self {
+            Self::RLE(enc) => enc.row_ids_in(values, dst),
+            Self::Plain(enc) => enc.row_ids_in(values, dst),
+        }
+    }
+
     /// Populates the provided destination container with the row ids for rows
     /// that null.
     fn row_ids_null(&self, dst: RowIDs) -> RowIDs {
@@ -478,6 +487,71 @@ mod test {
         assert_eq!(ids, RowIDs::Vector(vec![0, 1, 2]), "{}", name);
     }
 
+    #[test]
+    fn row_ids_in() {
+        let encodings = vec![
+            Encoding::RLE(RLE::default()),
+            Encoding::Plain(Dictionary::default()),
+        ];
+
+        for mut enc in encodings {
+            let name = enc.debug_name();
+            enc.push_additional(Some("east".to_string()), 3); // 0, 1, 2
+            enc.push_additional(Some("north".to_string()), 1); // 3
+            enc.push_additional(Some("east".to_string()), 2); // 4, 5
+            enc.push_none(); // 6
+            enc.push_additional(Some("south".to_string()), 2); // 7, 8
+
+            let ids = enc.row_ids_in(&["north", "south"], RowIDs::new_vector());
+            assert_eq!(ids, RowIDs::Vector(vec![3, 7, 8]), "{}", name);
+
+            let ids = enc.row_ids_in(&["east", "west"], RowIDs::new_vector());
+            assert_eq!(ids, RowIDs::Vector(vec![0, 1, 2, 4, 5]), "{}", name);
+
+            let ids = enc.row_ids_in(&["west", ""], RowIDs::new_vector());
+            assert!(ids.is_empty(), "{}", name);
+
+            let ids = enc.row_ids_in(&[], RowIDs::new_vector());
+            assert!(ids.is_empty(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn row_ids_filter_regex() {
+        let encodings = vec![
+            Encoding::RLE(RLE::default()),
+            Encoding::Plain(Dictionary::default()),
+        ];
+
+        for mut enc in encodings {
+            let name = enc.debug_name();
+            enc.push_additional(Some("east".to_string()), 3); // 0, 1, 2
+            enc.push_additional(Some("north".to_string()), 1); // 3
+            enc.push_additional(Some("east".to_string()), 2); // 4, 5
+            enc.push_none(); // 6
+            enc.push_additional(Some("south".to_string()), 2); // 7, 8
+
+            let ids =
+                enc.row_ids_filter(&"^(e|n)", &cmp::Operator::RegexMatch, RowIDs::new_vector());
+            assert_eq!(ids, RowIDs::Vector(vec![0, 1, 2, 3, 4, 5]), "{}", name);
+
+            let ids = enc.row_ids_filter(&"th$", &cmp::Operator::RegexMatch, RowIDs::new_vector());
+            assert_eq!(ids, RowIDs::Vector(vec![3, 7, 8]), "{}", name);
+
+            let ids = enc.row_ids_filter(&"west", &cmp::Operator::RegexMatch, RowIDs::new_vector());
+            assert!(ids.is_empty(), "{}", name);
+
+            // NULL values never match either operator.
+            let ids =
+                enc.row_ids_filter(&"th$", &cmp::Operator::RegexNotMatch, RowIDs::new_vector());
+            assert_eq!(ids, RowIDs::Vector(vec![0, 1, 2, 4, 5]), "{}", name);
+
+            let ids =
+                enc.row_ids_filter(&".*", &cmp::Operator::RegexNotMatch, RowIDs::new_vector());
+            assert!(ids.is_empty(), "{}", name);
+        }
+    }
+
     #[test]
     fn row_ids_filter_cmp() {
         let encodings = vec![
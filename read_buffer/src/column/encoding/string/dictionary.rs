@@ -12,6 +12,7 @@ use std::convert::From;
 use std::mem::size_of;
 
 use arrow::array::{Array, StringArray};
+use regex::Regex;
 
 use super::NULL_ID;
 use crate::column::{cmp, RowIDs};
@@ -211,7 +212,66 @@ impl Dictionary {
             cmp::Operator::LT | cmp::Operator::LTE | cmp::Operator::GT | cmp::Operator::GTE => {
                 self.row_ids_cmp(value, op, dst)
             }
+            cmp::Operator::RegexMatch | cmp::Operator::RegexNotMatch => {
+                self.row_ids_regex(value, op, dst)
+            }
+            cmp::Operator::In => unreachable!("IN lists are filtered via row_ids_in"),
+        }
+    }
+
+    /// Populates the provided destination container with the row ids of the
+    /// rows containing any of `values`. Each value is looked up in the
+    /// dictionary once and the column is scanned a single time.
+    pub fn row_ids_in(&self, values: &[&str], mut dst: RowIDs) -> RowIDs {
+        dst.clear();
+
+        let mut matching_ids = vec![false; self.entries.len()];
+        let mut found = false;
+        for value in values {
+            if let Ok(encoded_id) = self.encoded_id(Some(*value)) {
+                matching_ids[encoded_id as usize] = true;
+                found = true;
+            }
+        }
+
+        if !found {
+            return dst;
+        }
+
+        for (i, next) in self.encoded_data.iter().enumerate() {
+            if matching_ids[*next as usize] {
+                dst.add(i as u32);
+            }
         }
+
+        dst
+    }
+
+    // Finds row ids based on =~ or !~ operator. The pattern is matched
+    // against each distinct value in the dictionary only once. NULL values
+    // never satisfy either operator.
+    fn row_ids_regex(&self, pattern: &str, op: &cmp::Operator, mut dst: RowIDs) -> RowIDs {
+        dst.clear();
+
+        let regex = Regex::new(pattern).expect("regex pattern validated by predicate");
+        let matches = matches!(op, cmp::Operator::RegexMatch);
+
+        let matching_ids = self
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                Some(value) => regex.is_match(value) == matches,
+                None => false,
+            })
+            .collect::<Vec<_>>();
+
+        for (i, next) in self.encoded_data.iter().enumerate() {
+            if matching_ids[*next as usize] {
+                dst.add(i as u32);
+            }
+        }
+
+        dst
     }
 
     // Finds row ids based on = or != operator.
@@ -6,6 +6,7 @@ use std::mem::size_of;
 use croaring::Bitmap;
 
 use arrow::array::{Array, StringArray};
+use regex::Regex;
 
 use super::NULL_ID;
 use crate::column::{cmp, RowIDs};
@@ -287,7 +288,48 @@ impl RLE {
             cmp::Operator::LT | cmp::Operator::LTE | cmp::Operator::GT | cmp::Operator::GTE => {
                 self.row_ids_cmp(value, op, dst)
             }
+            cmp::Operator::RegexMatch | cmp::Operator::RegexNotMatch => {
+                self.row_ids_regex(value, op, dst)
+            }
+            cmp::Operator::In => unreachable!("IN lists are filtered via row_ids_in"),
+        }
+    }
+
+    /// Populates the provided destination container with the row ids of the
+    /// rows containing any of `values`, using the row ids stored for each
+    /// distinct value.
+    pub fn row_ids_in(&self, values: &[&str], mut dst: RowIDs) -> RowIDs {
+        dst.clear();
+
+        for value in values {
+            if let Some(encoded_id) = self.lookup_entry(value) {
+                dst.union(self.index_row_ids.get(&encoded_id).unwrap());
+            }
+        }
+
+        dst
+    }
+
+    // Finds row ids based on =~ or !~ operator. The pattern is matched
+    // against each distinct value in the column only once. NULL values never
+    // satisfy either operator.
+    fn row_ids_regex(&self, pattern: &str, op: &cmp::Operator, mut dst: RowIDs) -> RowIDs {
+        dst.clear();
+
+        let regex = Regex::new(pattern).expect("regex pattern validated by predicate");
+        let matches = matches!(op, cmp::Operator::RegexMatch);
+
+        for (id, entry) in self.index_entries.iter().enumerate() {
+            if id as u32 == NULL_ID || regex.is_match(entry) != matches {
+                continue;
+            }
+
+            if let Some(ids) = self.index_row_ids.get(&(id as u32)) {
+                dst.union(ids);
+            }
         }
+
+        dst
     }
 
     // Finds row ids based on = or != operator.
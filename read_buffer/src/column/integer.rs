@@ -11,7 +11,7 @@ use super::encoding::scalar::{
     transcoders::{ByteTrimmer, NoOpTranscoder, Transcoder},
     ScalarEncoding,
 };
-use super::encoding::{scalar::rle, scalar::Fixed, scalar::FixedNull};
+use super::encoding::{scalar::rle, scalar::Delta, scalar::Fixed, scalar::FixedNull};
 use super::{cmp, Statistics};
 use crate::column::{RowIDs, Scalar, Value, Values};
 
@@ -268,10 +268,23 @@ fn should_rle_from_iter<T: PartialOrd>(len: usize, iter: impl Iterator<Item = Op
         >= MIN_RLE_SIZE_REDUCTION
 }
 
+// Applies a heuristic to decide whether the input data should be encoded as
+// runs of values with a constant difference, rather than using the run-length
+// or fixed encoding it would otherwise be stored in.
+fn should_delta_from(arr: &[i64], delta: &Delta, rle: bool) -> bool {
+    let base_size = match rle {
+        true => rle::estimate_rle_size(arr.iter().map(Some)),
+        false => arr.len() * size_of::<i64>(),
+    };
+    (base_size as f64 - delta.size() as f64) / base_size as f64 >= MIN_RLE_SIZE_REDUCTION
+}
+
 /// Converts a slice of i64 values into an IntegerEncoding.
 ///
 /// The most compact physical type needed to store the columnar values is
-/// determined, and a `Fixed` encoding is used for storage.
+/// determined, and a `Fixed` encoding is used for storage. Values that can't
+/// be byte trimmed, such as timestamps, are stored in a `Delta` encoding
+/// instead if they mostly change by a constant amount.
 ///
 /// #Panics
 ///
@@ -379,7 +392,10 @@ impl From<&[i64]> for IntegerEncoding {
             }
             // otherwise, encode with the same physical type (i64)
             (_, _) => {
-                let enc: Box<dyn ScalarEncoding<i64>> = if rle {
+                let delta = Delta::new_from_iter(arr.iter().cloned());
+                let enc: Box<dyn ScalarEncoding<i64>> = if should_delta_from(arr, &delta, rle) {
+                    Box::new(delta)
+                } else if rle {
                     Box::new(RLE::new_from_iter(
                         arr.to_vec().into_iter(),
                         NoOpTranscoder {},
@@ -836,6 +852,13 @@ mod test {
                 // RLE i64
                 "RLE",
             ),
+            (
+                (0..1000)
+                    .map(|i| 1_600_000_000_000_000_000 + i * 10_000_000_000)
+                    .collect::<Vec<i64>>(),
+                // regularly spaced timestamps
+                "DELTA",
+            ),
         ];
 
         for (case, name) in cases.into_iter() {
@@ -315,6 +315,14 @@ impl StringEncoding {
         }
     }
 
+    /// Returns the row ids containing any of the provided values.
+    pub fn row_ids_in(&self, values: &[&str], dst: RowIDs) -> RowIDs {
+        match &self {
+            Self::RleDictionary(c) => c.row_ids_in(values, dst),
+            Self::Dictionary(c) => c.row_ids_in(values, dst),
+        }
+    }
+
     /// The lexicographic minimum non-null value at the rows specified, or the
     /// NULL value if the column only contains NULL values at the provided row
     /// ids.
@@ -145,12 +145,14 @@ impl RowGroup {
         base_size + self.meta.size()
     }
 
-    /// Returns an iterator of (column_name, estimated_size) for all
-    /// columns in this row_group
-    pub fn column_sizes(&self) -> impl Iterator<Item = (&str, usize)> + '_ {
+    /// Returns an iterator of (column_name, estimated_size, storage_stats)
+    /// for all columns in this row_group
+    pub(crate) fn column_sizes(
+        &self,
+    ) -> impl Iterator<Item = (&str, usize, column::Statistics)> + '_ {
         self.all_columns_by_name.iter().map(move |(name, idx)| {
             let column = &self.columns[*idx];
-            (name.as_str(), column.size())
+            (name.as_str(), column.size(), column.storage_stats())
         })
     }
 
@@ -244,7 +246,11 @@ impl RowGroup {
     //
 
     /// Returns a set of materialised column values that optionally satisfy a
-    /// predicate.
+    /// predicate, excluding the rows that satisfy any of the
+    /// `negated_predicates`.
+    ///
+    /// Both kinds of predicate are evaluated on the encoded column data, so
+    /// only the values of the remaining rows are materialised.
     ///
     /// TODO(edd): this should probably return an Option and the caller can
     /// filter None results.
@@ -252,6 +258,7 @@ impl RowGroup {
         &self,
         columns: &[ColumnName<'_>],
         predicates: &Predicate,
+        negated_predicates: &[Predicate],
     ) -> ReadFilterResult<'_> {
         let select_columns = self.meta.schema_for_column_names(&columns);
         assert_eq!(select_columns.len(), columns.len());
@@ -263,6 +270,7 @@ impl RowGroup {
 
         // apply predicates to determine candidate rows.
         let row_ids = self.row_ids_from_predicate(predicates);
+        let row_ids = self.remove_row_ids_from_negated_predicates(row_ids, negated_predicates);
         let col_data = self.materialise_rows(&schema, row_ids);
         ReadFilterResult {
             schema,
@@ -359,7 +367,15 @@ impl RowGroup {
             // the buffer should be returned to the caller so it can be re-used
             // on other columns. Each call to `row_ids_filter` returns the
             // buffer back enabling it to be re-used.
-            match col.row_ids_filter(&expr.op, &expr.literal_as_value(), dst) {
+            let row_ids = match expr.literal() {
+                Literal::StringList(values) => {
+                    let values = values.iter().map(String::as_str).collect::<Vec<_>>();
+                    col.row_ids_filter_in(&values, dst)
+                }
+                _ => col.row_ids_filter(&expr.op, &expr.literal_as_value(), dst),
+            };
+
+            match row_ids {
                 // No rows will be returned for the `RowGroup` because this
                 // column does not match any rows.
                 RowIDsOption::None(_dst) => return RowIDsOption::None(_dst),
@@ -390,6 +406,54 @@ impl RowGroup {
         RowIDsOption::Some(result_row_ids)
     }
 
+    // Removes the row ids satisfying any of the negated predicates from the
+    // provided candidate row ids.
+    fn remove_row_ids_from_negated_predicates(
+        &self,
+        row_ids: RowIDsOption,
+        negated_predicates: &[Predicate],
+    ) -> RowIDsOption {
+        if matches!(row_ids, RowIDsOption::None(_)) {
+            return row_ids;
+        }
+
+        let mut negated_row_ids = RowIDs::new_bitmap();
+        for predicate in negated_predicates {
+            // Skip predicates that can't match any rows, including ones on
+            // columns the row group doesn't have.
+            if !self.could_satisfy_conjunctive_binary_expressions(predicate.iter()) {
+                continue;
+            }
+
+            match self.row_ids_from_predicate(predicate) {
+                RowIDsOption::None(_) => {}
+                RowIDsOption::Some(row_ids) => negated_row_ids.union(&row_ids),
+                // every row is excluded
+                RowIDsOption::All(dst) => return RowIDsOption::None(dst),
+            }
+        }
+
+        if negated_row_ids.is_empty() {
+            return row_ids;
+        }
+
+        let mut row_ids = match row_ids {
+            RowIDsOption::Some(row_ids) => row_ids,
+            RowIDsOption::All(mut dst) => {
+                dst.clear();
+                dst.add_range(0, self.rows());
+                dst
+            }
+            RowIDsOption::None(_) => unreachable!("handled above"),
+        };
+        row_ids.difference(&negated_row_ids);
+
+        if row_ids.is_empty() {
+            return RowIDsOption::None(row_ids);
+        }
+        RowIDsOption::Some(row_ids)
+    }
+
     // An optimised function for applying two comparison predicates to a time
     // column at once.
     fn row_ids_from_time_range(&self, time_range: &[BinaryExpr], dst: RowIDs) -> RowIDsOption {
@@ -1282,6 +1346,8 @@ pub enum Literal {
     Unsigned(u64),
     Float(f64),
     Boolean(bool),
+    // A list of values, only used with the `IN` operator.
+    StringList(Vec<String>),
 }
 
 impl<'a> TryFrom<&DFScalarValue> for Literal {
@@ -1336,6 +1402,36 @@ impl BinaryExpr {
         }
     }
 
+    /// Creates an expression that is satisfied by the values of a string
+    /// column that match (`=~`), or don't match (`!~`), the regular
+    /// expression `pattern`. NULL values never satisfy the expression.
+    pub fn regex_match(
+        column_name: impl Into<String>,
+        pattern: impl Into<String>,
+        matches: bool,
+    ) -> Result<Self, String> {
+        let pattern = pattern.into();
+        regex::Regex::new(&pattern)
+            .map_err(|e| format!("invalid regex pattern {:?}: {}", pattern, e))?;
+
+        let op = if matches {
+            Operator::RegexMatch
+        } else {
+            Operator::RegexNotMatch
+        };
+        Ok(Self::new(column_name, op, Literal::String(pattern)))
+    }
+
+    /// Creates an expression that is satisfied by the values of a string
+    /// column equal to any of `values`, i.e., `column IN (values)`.
+    pub fn in_list(
+        column_name: impl Into<String>,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+        Self::new(column_name, Operator::In, Literal::StringList(values))
+    }
+
     pub fn column(&self) -> ColumnName<'_> {
         self.col.as_str()
     }
@@ -1355,6 +1451,7 @@ impl BinaryExpr {
             Literal::Unsigned(v) => Value::Scalar(Scalar::U64(*v)),
             Literal::Float(v) => Value::Scalar(Scalar::F64(*v)),
             Literal::Boolean(v) => Value::Boolean(*v),
+            Literal::StringList(_) => panic!("a list literal has no single value"),
         }
     }
 }
@@ -1437,6 +1534,23 @@ impl TryFrom<&DfExpr> for BinaryExpr {
                     }
                 }
             }
+            DfExpr::InList {
+                expr,
+                list,
+                negated: false,
+            } => match &**expr {
+                DfExpr::Column(name) => {
+                    let values = list
+                        .iter()
+                        .map(|value| match value {
+                            DfExpr::Literal(DFScalarValue::Utf8(Some(value))) => Ok(value.clone()),
+                            _ => Err(format!("unsupported IN list value {:?}", value)),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok(Self::in_list(name, values))
+                }
+                _ => Err(format!("unsupported IN list expression {:?}", expr)),
+            },
             _ => return Err(format!("unsupported expression type {:?}", df_expr)),
         }
     }
@@ -1577,11 +1691,20 @@ impl MetaData {
     // no rows in the `RowGroup` would ever match the expression.
     //
     pub fn column_could_satisfy_binary_expr(&self, expr: &BinaryExpr) -> bool {
-        let (column_min, column_max) = match self.columns.get(expr.column()) {
-            Some(schema) => &schema.range,
+        let (schema, (column_min, column_max)) = match self.columns.get(expr.column()) {
+            Some(schema) => (schema, &schema.range),
             None => return false, // column doesn't exist.
         };
 
+        // An IN list could be satisfied if the column range covers any of its
+        // values.
+        if let Literal::StringList(values) = expr.literal() {
+            return values.iter().any(|v| {
+                let value = &Value::String(v);
+                column_min <= value && column_max >= value
+            });
+        }
+
         let (op, value) = (expr.op(), &expr.literal_as_value());
         match op {
             // If the column range covers the value then it could contain that
@@ -1607,6 +1730,14 @@ impl MetaData {
             // if the column min is at least as small as value then the column
             // could contain the value.
             Operator::LTE => column_min <= value,
+
+            // regular expressions can only be satisfied by string columns,
+            // and can't be evaluated against the column range.
+            Operator::RegexMatch | Operator::RegexNotMatch => {
+                matches!(schema.logical_data_type, LogicalDataType::String)
+            }
+
+            Operator::In => unreachable!("IN lists are handled above"),
         }
     }
 
@@ -2482,12 +2613,50 @@ POST
 west,2
 east,3
 west,4
+",
+            ),
+            (
+                vec!["region", "method", "time"],
+                Predicate::with_time_range(
+                    &[BinaryExpr::regex_match("method", "^P", true).unwrap()],
+                    0,
+                    6,
+                ),
+                "region,method,time
+west,POST,2
+east,POST,3
+west,POST,4
+south,PUT,5
+",
+            ),
+            (
+                vec!["region", "time"],
+                Predicate::with_time_range(
+                    &[BinaryExpr::regex_match("region", "st$", false).unwrap()],
+                    0,
+                    7,
+                ),
+                "region,time
+south,5
+north,6
+",
+            ),
+            (
+                vec!["region", "method", "time"],
+                Predicate::with_time_range(
+                    &[BinaryExpr::in_list("region", vec!["north", "east", "up"])],
+                    0,
+                    7,
+                ),
+                "region,method,time
+east,POST,3
+north,GET,6
 ",
             ),
         ];
 
         for (cols, predicates, expected) in cases {
-            let results = row_group.read_filter(&cols, &predicates);
+            let results = row_group.read_filter(&cols, &predicates, &[]);
             assert_eq!(format!("{:?}", &results), expected);
         }
 
@@ -2495,6 +2664,88 @@ west,4
         let results = row_group.read_filter(
             &["method", "region", "time"],
             &Predicate::with_time_range(&[], -19, 1),
+            &[],
+        );
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn read_filter_negated_predicates() {
+        let mut columns = vec![];
+        let tc = ColumnType::Time(Column::from(&[1_i64, 2, 3, 4, 5, 6][..]));
+        columns.push(("time".to_string(), tc));
+
+        let rc = ColumnType::Tag(Column::from(
+            &["west", "west", "east", "west", "south", "north"][..],
+        ));
+        columns.push(("region".to_string(), rc));
+
+        let mc = ColumnType::Tag(Column::from(
+            &["GET", "POST", "POST", "POST", "PUT", "GET"][..],
+        ));
+        columns.push(("method".to_string(), mc));
+
+        let row_group = RowGroup::new(6, columns);
+
+        let cases = vec![
+            (
+                Predicate::default(),
+                vec![Predicate::with_time_range(
+                    &[BinaryExpr::from(("region", "=", "west"))],
+                    0,
+                    4,
+                )],
+                "region,method,time
+east,POST,3
+west,POST,4
+south,PUT,5
+north,GET,6
+",
+            ),
+            (
+                Predicate::new(vec![BinaryExpr::from(("method", "=", "POST"))]),
+                vec![
+                    Predicate::new(vec![BinaryExpr::from(("region", "=", "east"))]),
+                    Predicate::with_time_range(&[], 4, 7),
+                ],
+                "region,method,time
+west,POST,2
+",
+            ),
+            (
+                // predicates on columns the row group doesn't have exclude
+                // no rows
+                Predicate::with_time_range(&[], 5, 7),
+                vec![Predicate::new(vec![BinaryExpr::from(("host", "=", "a"))])],
+                "region,method,time
+south,PUT,5
+north,GET,6
+",
+            ),
+        ];
+
+        for (predicate, negated_predicates, expected) in cases {
+            let results = row_group.read_filter(
+                &["region", "method", "time"],
+                &predicate,
+                &negated_predicates,
+            );
+            assert_eq!(format!("{:?}", &results), expected);
+        }
+
+        // all candidate rows excluded
+        let results = row_group.read_filter(
+            &["region", "method", "time"],
+            &Predicate::new(vec![BinaryExpr::from(("method", "=", "GET"))]),
+            &[Predicate::with_time_range(&[], 0, 10)],
+        );
+        assert!(results.is_empty());
+
+        // a negated predicate without expressions excludes every row
+        let results = row_group.read_filter(
+            &["region", "method", "time"],
+            &Predicate::default(),
+            &[Predicate::default()],
         );
         assert!(results.is_empty());
     }
@@ -2543,7 +2794,7 @@ POST,west,2
         ];
 
         for (cols, predicates, expected) in cases {
-            let results = row_group.read_filter(&cols, &predicates);
+            let results = row_group.read_filter(&cols, &predicates, &[]);
             assert_eq!(format!("{:?}", &results), expected);
         }
     }
@@ -2822,6 +3073,9 @@ west,POST,304,101,203
             (("region", "<=", "north"), true), // region column might contain something ≤ "north"
             (("region", "<=", "south"), true), // region column might contain something ≤ "south"
             (("region", "<=", "abc"), false),  // region column can't contain something ≤ "abc"
+            (("region", "=~", "^zoo"), true),  // region column might match a regex
+            (("region", "!~", "^w"), true),    // region column might not match a regex
+            (("time", "=~", ".*"), false),     // time column can't match a regex
         ];
 
         for ((col, op, value), exp) in cases {
@@ -2834,6 +3088,24 @@ west,POST,304,101,203
                 predicate
             );
         }
+
+        let cases = vec![
+            (("region", vec!["abc", "west"]), true), // region column does contain "west"
+            (("region", vec!["abc", "zoo"]), false), // region column can't contain either
+            (("region", vec![]), false),             // nothing to contain
+            (("time", vec!["west"]), false),         // time column can't contain strings
+        ];
+
+        for ((col, values), exp) in cases {
+            let predicate = Predicate::new(vec![BinaryExpr::in_list(col, values)]);
+
+            assert_eq!(
+                row_group.could_satisfy_conjunctive_binary_expressions(predicate.iter()),
+                exp,
+                "{:?} failed",
+                predicate
+            );
+        }
     }
 
     #[test]
@@ -144,24 +144,42 @@ impl Table {
         base_size + self.table_data.read().meta.size()
     }
 
-    /// The estimated size for each column in this table.
+    /// The estimated size, encoding(s), uncompressed size and cardinality
+    /// for each column in this table.
     pub fn column_sizes(&self) -> Vec<ChunkColumnSummary> {
-        self.table_data
-            .read()
+        let table_data = self.table_data.read();
+        table_data
             .data
             .iter()
             .flat_map(|rg| rg.column_sizes())
             // combine statistics for columns across row groups
-            .fold(BTreeMap::new(), |mut map, (name, estimated_bytes)| {
-                let entry = map.entry(name).or_insert(0);
-                *entry += estimated_bytes;
-                map
-            })
+            .fold(
+                BTreeMap::new(),
+                |mut map, (name, estimated_bytes, stats)| {
+                    let entry = map.entry(name).or_insert_with(|| (0, 0, BTreeSet::new()));
+                    entry.0 += estimated_bytes;
+                    entry.1 += stats.raw_bytes;
+                    entry.2.insert(stats.enc_type);
+                    map
+                },
+            )
             // Now turn into Vec<ChunkColumnSummary>
             .into_iter()
-            .map(|(name, estimated_bytes)| ChunkColumnSummary {
-                name: name.into(),
-                estimated_bytes,
+            .map(|(name, (estimated_bytes, raw_bytes, encodings))| {
+                let cardinality = table_data
+                    .meta
+                    .columns
+                    .get(name)
+                    .and_then(|meta| meta.distinct_count)
+                    .map(|count| count.get());
+
+                ChunkColumnSummary {
+                    name: name.into(),
+                    estimated_bytes,
+                    encoding: Some(encodings.into_iter().collect::<Vec<_>>().join(",")),
+                    raw_bytes: Some(raw_bytes),
+                    cardinality,
+                }
             })
             .collect()
     }
@@ -275,11 +293,13 @@ impl Table {
     /// Results may be filtered by (currently only) conjunctive (AND)
     /// predicates, but can be ranged by time, which should be represented
     /// as nanoseconds since the epoch. Results are included if they satisfy
-    /// the predicate and fall with the [min, max) time range domain.
+    /// the predicate and fall with the [min, max) time range domain, and do
+    /// not satisfy any of the `negated_predicates`.
     pub fn read_filter<'a>(
         &'a self,
         columns: &Selection<'_>,
         predicate: &Predicate,
+        negated_predicates: &[Predicate],
     ) -> ReadFilterResults {
         // identify row groups where time range and predicates match could match
         // the predicate. Get a snapshot of those and the meta-data.
@@ -296,6 +316,7 @@ impl Table {
         // TODO(edd): I think I can remove `predicates` from the results
         ReadFilterResults {
             predicate: predicate.clone(),
+            negated_predicates: negated_predicates.to_vec(),
             schema,
             row_groups,
         }
@@ -779,6 +800,9 @@ pub struct ReadFilterResults {
     // TODO(edd): encapsulate this into a single executor function that just
     // executes on the next row group.
     predicate: Predicate,
+
+    // Rows satisfying any of these predicates are excluded from the results.
+    negated_predicates: Vec<Predicate>,
 }
 
 impl ReadFilterResults {
@@ -807,7 +831,9 @@ impl ReadFilterResults {
 
         self.row_groups
             .iter()
-            .map(|row_group| row_group.read_filter(select_columns, &self.predicate))
+            .map(|row_group| {
+                row_group.read_filter(select_columns, &self.predicate, &self.negated_predicates)
+            })
             .filter(|result| !result.is_empty())
             .collect()
     }
@@ -829,6 +855,7 @@ impl Iterator for ReadFilterResults {
                 .map(|name| name.as_str())
                 .collect::<Vec<_>>(),
             &self.predicate,
+            &self.negated_predicates,
         );
 
         if result.is_empty() {
@@ -1094,15 +1121,22 @@ mod test {
         let rg = RowGroup::new(6, columns);
         table.add_row_group(rg);
 
-        // expect only a single entry for each column, in name order
+        // expect only a single entry for each column, in name order, with the
+        // encodings of both row groups
         let expected = vec![
             ChunkColumnSummary {
                 name: "count".into(),
                 estimated_bytes: 110,
+                encoding: Some("BT_U16-FIXED,BT_U8-FIXED".into()),
+                raw_bytes: Some(120),
+                cardinality: None,
             },
             ChunkColumnSummary {
                 name: "time".into(),
                 estimated_bytes: 107,
+                encoding: Some("BT_U8-FIXED".into()),
+                raw_bytes: Some(120),
+                cardinality: None,
             },
         ];
         assert_eq!(table.column_sizes(), expected);
@@ -1232,7 +1266,11 @@ mod test {
 
         // Get all the results
         let predicate = Predicate::with_time_range(&[], 1, 31);
-        let results = table.read_filter(&Selection::Some(&["time", "count", "region"]), &predicate);
+        let results = table.read_filter(
+            &Selection::Some(&["time", "count", "region"]),
+            &predicate,
+            &[],
+        );
 
         // check the column types
         let exp_schema = ResultSchema {
@@ -1278,7 +1316,7 @@ mod test {
             Predicate::with_time_range(&[BinaryExpr::from(("region", "!=", "south"))], 1, 25);
 
         // Apply a predicate `WHERE "region" != "south"`
-        let results = table.read_filter(&Selection::Some(&["time", "region"]), &predicate);
+        let results = table.read_filter(&Selection::Some(&["time", "region"]), &predicate, &[]);
 
         let exp_schema = ResultSchema {
             select_columns: vec![
@@ -0,0 +1,333 @@
+//! Token based authorization of the read, write and management APIs.
+//!
+//! Authorization is enabled by configuring an admin token, which grants access
+//! to everything including the management API. The admin creates tokens that
+//! grant read and/or write access to individual databases to the teams sharing
+//! the server. Tokens are held in memory and have to be created again after a
+//! restart.
+use std::collections::BTreeMap;
+
+use parking_lot::RwLock;
+use rand::{distributions::Alphanumeric, Rng};
+use snafu::{ensure, OptionExt, Snafu};
+use subtle::ConstantTimeEq;
+
+/// Length of the secrets of the created tokens
+const SECRET_LENGTH: usize = 32;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("no authorization token provided"))]
+    MissingToken,
+
+    #[snafu(display("invalid authorization token"))]
+    InvalidToken,
+
+    #[snafu(display("token does not grant {} access to database {}", scope, db_name))]
+    PermissionDenied { db_name: String, scope: Scope },
+
+    #[snafu(display("token does not grant access to the management API"))]
+    AdminRequired,
+
+    #[snafu(display("token not found: {}", id))]
+    TokenNotFound { id: u64 },
+}
+
+impl Error {
+    /// Returns true if the request presented a valid token that doesn't grant
+    /// the access required, as opposed to a missing or unknown token
+    pub fn is_permission_denied(&self) -> bool {
+        matches!(self, Self::PermissionDenied { .. } | Self::AdminRequired)
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The kind of access to a database granted by a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Scope {
+    /// Query the database
+    Read,
+    /// Write to the database
+    Write,
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Write => write!(f, "write"),
+        }
+    }
+}
+
+/// Access to one database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permission {
+    pub db_name: String,
+    pub scope: Scope,
+}
+
+/// A token created with [`Authorizer::create_token`]. The secret presented by
+/// clients is only returned when the token is created
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub id: u64,
+    pub description: String,
+    pub permissions: Vec<Permission>,
+}
+
+impl Token {
+    fn allows(&self, db_name: &str, scope: Scope) -> bool {
+        self.permissions
+            .iter()
+            .any(|p| p.db_name == db_name && p.scope == scope)
+    }
+}
+
+#[derive(Default)]
+struct Tokens {
+    next_id: u64,
+    by_secret: BTreeMap<String, Token>,
+}
+
+impl Tokens {
+    /// Returns the token with the secret `secret`. All the secrets are
+    /// compared, rather than looking the secret up in the map, so that the
+    /// time taken does not depend on which secrets it is close to
+    fn get(&self, secret: &str) -> Option<&Token> {
+        self.by_secret
+            .iter()
+            .fold(None, |found, (token_secret, token)| {
+                if secret_eq(token_secret, secret) {
+                    Some(token)
+                } else {
+                    found
+                }
+            })
+    }
+}
+
+/// Compares two secrets in constant time, so that the time taken to reject
+/// a token does not reveal how much of a secret it matches
+fn secret_eq(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+/// Validates the tokens presented with API requests
+pub struct Authorizer {
+    admin_token: Option<String>,
+    tokens: RwLock<Tokens>,
+}
+
+// The secrets must not end up in logs
+impl std::fmt::Debug for Authorizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Authorizer")
+            .field("enabled", &self.is_enabled())
+            .field("tokens", &self.tokens.read().by_secret.len())
+            .finish()
+    }
+}
+
+impl Authorizer {
+    /// Create an authorizer. If `admin_token` is `None` authorization is
+    /// disabled and every request is allowed
+    pub fn new(admin_token: Option<String>) -> Self {
+        Self {
+            admin_token,
+            tokens: Default::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.admin_token.is_some()
+    }
+
+    /// Creates a token granting `permissions`, returning it with its secret
+    pub fn create_token(
+        &self,
+        description: impl Into<String>,
+        permissions: Vec<Permission>,
+    ) -> (Token, String) {
+        let secret: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(SECRET_LENGTH)
+            .map(char::from)
+            .collect();
+
+        let mut tokens = self.tokens.write();
+        tokens.next_id += 1;
+        let token = Token {
+            id: tokens.next_id,
+            description: description.into(),
+            permissions,
+        };
+        tokens.by_secret.insert(secret.clone(), token.clone());
+
+        (token, secret)
+    }
+
+    /// Revokes the token with the given id, after which requests presenting
+    /// its secret are rejected
+    pub fn revoke_token(&self, id: u64) -> Result<()> {
+        let mut tokens = self.tokens.write();
+        let len = tokens.by_secret.len();
+        tokens.by_secret.retain(|_, token| token.id != id);
+        ensure!(tokens.by_secret.len() < len, TokenNotFound { id });
+        Ok(())
+    }
+
+    /// Returns the tokens that have not been revoked, ordered by id
+    pub fn tokens(&self) -> Vec<Token> {
+        let mut tokens: Vec<_> = self.tokens.read().by_secret.values().cloned().collect();
+        tokens.sort_by_key(|token| token.id);
+        tokens
+    }
+
+    /// Checks that `token` grants `scope` access to the database `db_name`
+    pub fn authorize(&self, token: Option<&str>, db_name: &str, scope: Scope) -> Result<()> {
+        let admin_token = match &self.admin_token {
+            Some(admin_token) => admin_token,
+            None => return Ok(()),
+        };
+        let token = token.context(MissingToken)?;
+        if secret_eq(token, admin_token) {
+            return Ok(());
+        }
+
+        let tokens = self.tokens.read();
+        let token = tokens.get(token).context(InvalidToken)?;
+        ensure!(
+            token.allows(db_name, scope),
+            PermissionDenied { db_name, scope }
+        );
+        Ok(())
+    }
+
+    /// Checks that `token` grants access to the management API, which is
+    /// reserved to the admin token
+    pub fn authorize_admin(&self, token: Option<&str>) -> Result<()> {
+        let admin_token = match &self.admin_token {
+            Some(admin_token) => admin_token,
+            None => return Ok(()),
+        };
+        let token = token.context(MissingToken)?;
+        if secret_eq(token, admin_token) {
+            return Ok(());
+        }
+
+        ensure!(self.tokens.read().get(token).is_some(), InvalidToken);
+        AdminRequired.fail()
+    }
+}
+
+/// Extracts the token from the value of an `Authorization` header, using
+/// either the `Token` scheme of InfluxDB 2.x or the `Bearer` scheme
+pub fn token_from_header(value: &str) -> Option<&str> {
+    let value = value.trim();
+    value
+        .strip_prefix("Token ")
+        .or_else(|| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permission(db_name: &str, scope: Scope) -> Permission {
+        Permission {
+            db_name: db_name.to_string(),
+            scope,
+        }
+    }
+
+    #[test]
+    fn disabled() {
+        let auth = Authorizer::new(None);
+        assert!(!auth.is_enabled());
+
+        auth.authorize(None, "db", Scope::Write).unwrap();
+        auth.authorize(Some("whatever"), "db", Scope::Read).unwrap();
+        auth.authorize_admin(None).unwrap();
+    }
+
+    #[test]
+    fn admin() {
+        let auth = Authorizer::new(Some("admin".to_string()));
+        assert!(auth.is_enabled());
+
+        auth.authorize(Some("admin"), "db", Scope::Write).unwrap();
+        auth.authorize_admin(Some("admin")).unwrap();
+
+        let err = auth.authorize_admin(None).unwrap_err();
+        assert!(matches!(err, Error::MissingToken));
+        let err = auth.authorize_admin(Some("nimda")).unwrap_err();
+        assert!(matches!(err, Error::InvalidToken));
+        assert!(!err.is_permission_denied());
+    }
+
+    #[test]
+    fn tokens() {
+        let auth = Authorizer::new(Some("admin".to_string()));
+
+        let (reader, reader_secret) =
+            auth.create_token("team a", vec![permission("a", Scope::Read)]);
+        let (writer, writer_secret) = auth.create_token(
+            "team b",
+            vec![permission("a", Scope::Write), permission("b", Scope::Write)],
+        );
+        assert_ne!(reader.id, writer.id);
+        assert_ne!(reader_secret, writer_secret);
+        assert_eq!(reader_secret.len(), SECRET_LENGTH);
+        assert_eq!(auth.tokens(), vec![reader.clone(), writer.clone()]);
+
+        auth.authorize(Some(&reader_secret), "a", Scope::Read)
+            .unwrap();
+        auth.authorize(Some(&writer_secret), "b", Scope::Write)
+            .unwrap();
+
+        let err = auth
+            .authorize(Some(&reader_secret), "a", Scope::Write)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "token does not grant write access to database a"
+        );
+        let err = auth
+            .authorize(Some(&writer_secret), "b", Scope::Read)
+            .unwrap_err();
+        assert!(err.is_permission_denied());
+
+        let err = auth.authorize_admin(Some(&writer_secret)).unwrap_err();
+        assert!(matches!(err, Error::AdminRequired));
+
+        auth.revoke_token(reader.id).unwrap();
+        assert_eq!(auth.tokens(), vec![writer]);
+        let err = auth
+            .authorize(Some(&reader_secret), "a", Scope::Read)
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidToken));
+
+        let err = auth.revoke_token(reader.id).unwrap_err();
+        assert!(matches!(err, Error::TokenNotFound { .. }));
+    }
+
+    #[test]
+    fn compare_secrets() {
+        assert!(secret_eq("admin", "admin"));
+        assert!(!secret_eq("admin", "nimda"));
+        assert!(!secret_eq("admin", "admin2"));
+        assert!(!secret_eq("", "admin"));
+    }
+
+    #[test]
+    fn header() {
+        assert_eq!(token_from_header("Token abc"), Some("abc"));
+        assert_eq!(token_from_header("Bearer abc "), Some("abc"));
+        assert_eq!(token_from_header("Token "), None);
+        assert_eq!(token_from_header("Basic abc"), None);
+    }
+}
@@ -12,7 +12,7 @@ use query::exec::Executor;
 /// This module contains code for managing the configuration of the server.
 use crate::{
     db::{catalog::Catalog, Db},
-    write_buffer::KafkaBuffer,
+    write_buffer::{FileBuffer, WriteBuffer, WriteBufferError, FILE_BUFFER_SCHEME},
     Error, JobRegistry, Result,
 };
 use observability_deps::tracing::{self, error, info, warn, Instrument};
@@ -21,6 +21,14 @@ use tokio_util::sync::CancellationToken;
 
 pub(crate) const DB_RULES_FILE_NAME: &str = "rules.pb";
 
+/// Name of the file flagging a database as deleted, holding the time it was
+/// deleted at
+pub(crate) const DB_DELETED_FILE_NAME: &str = "deleted";
+
+/// Name of the file, at the root of the object store, holding the ID of the
+/// server using it
+pub(crate) const SERVER_ID_FILE_NAME: &str = "server_id";
+
 /// The Config tracks the configuration of databases and their rules along
 /// with host groups for replication. It is used as an in-memory structure
 /// that can be loaded incrementally from object storage.
@@ -70,9 +78,41 @@ impl Config {
             });
         }
 
+        // A write buffer connection string referring to a local directory creates a write-ahead
+        // log in that directory, any other connection string is taken to be the address of a
+        // Kafka cluster, which requires the `kafka` feature.
+        let write_buffer: Option<Arc<dyn WriteBuffer>> = rules
+            .write_buffer_connection_string
+            .as_ref()
+            .map(|conn| -> Result<Arc<dyn WriteBuffer>, WriteBufferError> {
+                Ok(match conn.strip_prefix(FILE_BUFFER_SCHEME) {
+                    Some(dir) => Arc::new(FileBuffer::new(dir, rules.name.as_str())?),
+                    #[cfg(feature = "kafka")]
+                    None => Arc::new(crate::write_buffer::KafkaBuffer::new(
+                        conn,
+                        rules.name.to_string(),
+                    )?),
+                    #[cfg(not(feature = "kafka"))]
+                    None => {
+                        return Err(format!(
+                            "cannot connect to Kafka at {}: the server was built without the \
+                             kafka feature",
+                            conn
+                        )
+                        .into())
+                    }
+                })
+            })
+            .transpose()
+            .map_err(|source| Error::CreatingWriteBuffer {
+                db_name: rules.name.to_string(),
+                source,
+            })?;
+
         state.reservations.insert(rules.name.clone());
         Ok(CreateDatabaseHandle {
             rules: Some(rules),
+            write_buffer,
             config: &self,
         })
     }
@@ -82,6 +122,28 @@ impl Config {
         state.databases.get(name).map(|x| Arc::clone(&x.db))
     }
 
+    /// Removes the database from the config and waits for its background
+    /// worker to shut down. The database stops accepting writes and queries
+    /// as soon as this returns; its object store data is left untouched.
+    pub(crate) async fn remove_db(&self, name: &DatabaseName<'_>) -> Result<()> {
+        let mut db_state = self
+            .state
+            .write()
+            .expect("mutex poisoned")
+            .databases
+            .remove(name)
+            .ok_or_else(|| Error::DatabaseNotFound {
+                db_name: name.to_string(),
+            })?;
+
+        db_state.shutdown.cancel();
+        if let Some(handle) = db_state.join() {
+            let _ = handle.await;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn db_names_sorted(&self) -> Vec<DatabaseName<'static>> {
         let state = self.state.read().expect("mutex poisoned");
         state.databases.keys().cloned().collect()
@@ -139,6 +201,7 @@ impl Config {
         object_store: Arc<ObjectStore>,
         exec: Arc<Executor>,
         preserved_catalog: PreservedCatalog<Catalog>,
+        write_buffer: Option<Arc<dyn WriteBuffer>>,
     ) {
         let mut state = self.state.write().expect("mutex poisoned");
         let name = state
@@ -151,15 +214,6 @@ impl Config {
             return;
         }
 
-        // Right now, `KafkaBuffer` is the only production implementation of the `WriteBuffer`
-        // trait, so always use `KafkaBuffer` when there is a write buffer connection string
-        // specified. If/when there are other kinds of write buffers, additional configuration will
-        // be needed to determine what kind of write buffer to use here.
-        let write_buffer = rules
-            .write_buffer_connection_string
-            .as_ref()
-            .map(|conn| Arc::new(KafkaBuffer::new(conn)) as _);
-
         let db = Arc::new(Db::new(
             rules,
             server_id,
@@ -178,7 +232,7 @@ impl Config {
         let handle = Some(tokio::spawn(async move {
             db_captured
                 .background_worker(shutdown_captured)
-                .instrument(tracing::info_span!("db_worker", database=%name_captured))
+                .instrument(tracing::info_span!("db_worker", db_name=%name_captured))
                 .await
         }));
 
@@ -318,6 +372,7 @@ pub(crate) struct CreateDatabaseHandle<'a> {
     /// Partial moves aren't supported on structures that implement Drop
     /// so use Option to allow taking DatabaseRules out in `commit`
     rules: Option<DatabaseRules>,
+    write_buffer: Option<Arc<dyn WriteBuffer>>,
     config: &'a Config,
 }
 
@@ -335,6 +390,7 @@ impl<'a> CreateDatabaseHandle<'a> {
             object_store,
             exec,
             preserved_catalog,
+            self.write_buffer.take(),
         )
     }
 
@@ -457,6 +513,54 @@ mod test {
         assert!(token.is_cancelled());
     }
 
+    #[tokio::test]
+    async fn remove_db() {
+        let name = DatabaseName::new("foo").unwrap();
+        let metric_registry = Arc::new(metrics::MetricRegistry::new());
+        let config = Config::new(
+            Arc::new(JobRegistry::new()),
+            Arc::clone(&metric_registry),
+            None,
+        );
+
+        let err = config.remove_db(&name).await.unwrap_err();
+        assert!(matches!(err, Error::DatabaseNotFound { .. }));
+
+        let db_reservation = config.create_db(DatabaseRules::new(name.clone())).unwrap();
+        let server_id = ServerId::try_from(1).unwrap();
+        let store = Arc::new(ObjectStore::new_in_memory(InMemory::new()));
+        let exec = Arc::new(Executor::new(1));
+        let preserved_catalog = load_or_create_preserved_catalog(
+            &name,
+            Arc::clone(&store),
+            server_id,
+            config.metrics_registry(),
+        )
+        .await
+        .unwrap();
+        db_reservation.commit(server_id, store, exec, preserved_catalog);
+
+        let token = config
+            .state
+            .read()
+            .expect("lock poisoned")
+            .databases
+            .get(&name)
+            .unwrap()
+            .shutdown
+            .clone();
+
+        config.remove_db(&name).await.unwrap();
+        assert!(token.is_cancelled());
+        assert!(config.db(&name).is_none());
+        assert!(config.db_names_sorted().is_empty());
+
+        // The name can be reused once removed
+        config.create_db(DatabaseRules::new(name)).unwrap();
+
+        config.drain().await
+    }
+
     #[test]
     fn object_store_path_for_database_config() {
         let storage = ObjectStore::new_in_memory(InMemory::new());
@@ -3,64 +3,90 @@
 
 use self::access::QueryCatalogAccess;
 use self::catalog::TableNameFilter;
-use super::{write_buffer::WriteBuffer, JobRegistry};
+use super::{memory::QueryMemory, queries::RunningQueries, write_buffer::WriteBuffer, JobRegistry};
 use arrow::datatypes::SchemaRef as ArrowSchemaRef;
 use async_trait::async_trait;
-use catalog::{chunk::Chunk as CatalogChunk, Catalog};
+use catalog::{
+    chunk::{Chunk as CatalogChunk, ChunkStage},
+    Catalog,
+};
 pub(crate) use chunk::DbChunk;
+use continuous_query::ContinuousQueryScheduler;
 use data_types::{
-    chunk_metadata::ChunkSummary,
-    database_rules::DatabaseRules,
+    chunk_metadata::{ChunkEvent, ChunkStorage, ChunkSummary},
+    database_rules::{ColumnType, DatabaseRules, Quotas, SchemaConflictPolicy, TypeCoercionRule},
+    delete_predicate::DeletePredicate,
     job::Job,
-    partition_metadata::{PartitionSummary, TableSummary},
+    partition_metadata::{PartitionStorageSummary, PartitionSummary, TableSummary},
     server_id::ServerId,
 };
 use datafusion::{
     catalog::{catalog::CatalogProvider, schema::SchemaProvider},
     physical_plan::SendableRecordBatchStream,
 };
-use entry::{Entry, SequencedEntry};
-use internal_types::{arrow::sort::sort_record_batch, selection::Selection};
+use datafusion_util::MemoryStream;
+use entry::{Entry, PartitionWrite, SequencedEntry};
+use futures::{Future, TryStreamExt};
+use internal_types::{schema::InfluxFieldType, selection::Selection};
 use lifecycle::LifecycleManager;
-use metrics::{KeyValue, MetricRegistry};
-use mutable_buffer::chunk::{
-    Chunk as MutableBufferChunk, ChunkMetrics as MutableBufferChunkMetrics,
+use metrics::{KeyValue, MetricObserverBuilder, MetricRegistry};
+use mutable_buffer::{
+    chunk::{Chunk as MutableBufferChunk, ChunkMetrics as MutableBufferChunkMetrics},
+    interner::StringInterner,
+};
+use object_store::{
+    path::{parsed::DirsAndFileName, ObjectStorePath, Path},
+    ObjectStore, ObjectStoreApi,
 };
-use object_store::{path::parsed::DirsAndFileName, ObjectStore};
-use observability_deps::tracing::{debug, error, info};
-use parking_lot::RwLock;
+use observability_deps::tracing::{self, debug, error, info};
+use parking_lot::{Mutex, RwLock};
 use parquet_file::{
     catalog::{CatalogParquetInfo, CatalogState, PreservedCatalog},
     chunk::{Chunk as ParquetChunk, ChunkMetrics as ParquetChunkMetrics},
     cleanup::cleanup_unreferenced_parquet_files,
     metadata::{
+        read_iox_metadata_from_parquet_metadata, read_parquet_metadata_from_file,
         read_schema_from_parquet_metadata, read_statistics_from_parquet_metadata, IoxMetadata,
     },
     storage::Storage,
 };
-use query::{exec::Executor, predicate::Predicate, Database};
+use query::{
+    exec::{Executor, QueryResultCache},
+    predicate::Predicate,
+    Database,
+};
 use rand_distr::{Distribution, Poisson};
 use read_buffer::{Chunk as ReadBufferChunk, ChunkMetrics as ReadBufferChunkMetrics};
-use snafu::{ResultExt, Snafu};
+use rollup::RollupManager;
+use snafu::{OptionExt, ResultExt, Snafu};
 use std::{
     any::Any,
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet},
+    hash::{Hash, Hasher},
     num::NonZeroUsize,
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
     time::Duration,
 };
+use tokio::sync::{broadcast, Semaphore};
 use tracker::{TaskRegistration, TaskTracker, TrackedFutureExt};
 
 pub mod access;
 pub mod catalog;
 mod chunk;
+mod compact;
+mod continuous_query;
+pub mod export;
 mod lifecycle;
+mod lines;
 pub mod pred;
 mod process_clock;
+mod rollup;
 mod streams;
 mod system_tables;
+pub mod tsm;
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -78,6 +104,12 @@ pub enum Error {
         source: catalog::Error,
     },
 
+    #[snafu(display("Can not drop partition {} from catalog: {}", partition_key, source))]
+    DroppingPartition {
+        partition_key: String,
+        source: catalog::Error,
+    },
+
     #[snafu(display(
         "Can not rollover partition {}:{} : {}",
         partition_key,
@@ -90,6 +122,12 @@ pub enum Error {
         source: catalog::Error,
     },
 
+    #[snafu(display("Can not delete rows from table {}: {}", table_name, source))]
+    DeletingRows {
+        table_name: String,
+        source: catalog::Error,
+    },
+
     #[snafu(display(
         "Can not drop chunk {}:{}:{} which has an in-progress lifecycle action {}. Wait for this to complete",
         partition_key,
@@ -158,6 +196,58 @@ pub enum Error {
         chunk_id: u32,
     },
 
+    #[snafu(display(
+        "Can not compact chunks of partition {}:{} : {}",
+        partition_key,
+        table_name,
+        source
+    ))]
+    CompactingPartition {
+        partition_key: String,
+        table_name: String,
+        source: catalog::Error,
+    },
+
+    #[snafu(display(
+        "Error reading chunk {}:{}:{} for compaction: {}",
+        partition_key,
+        table_name,
+        chunk_id,
+        source
+    ))]
+    ReadingCompactedChunk {
+        partition_key: String,
+        table_name: String,
+        chunk_id: u32,
+        source: parquet_file::chunk::Error,
+    },
+
+    #[snafu(display(
+        "Error collecting data of chunk {}:{}:{} for compaction: {}",
+        partition_key,
+        table_name,
+        chunk_id,
+        source
+    ))]
+    CollectingCompactedChunk {
+        partition_key: String,
+        table_name: String,
+        chunk_id: u32,
+        source: arrow::error::ArrowError,
+    },
+
+    #[snafu(display(
+        "Error merging chunks of partition {}:{} : {}",
+        partition_key,
+        table_name,
+        source
+    ))]
+    MergingChunks {
+        partition_key: String,
+        table_name: String,
+        source: compact::Error,
+    },
+
     #[snafu(display("Error writing to object store: {}", source))]
     WritingToObjectStore {
         source: parquet_file::storage::Error,
@@ -174,9 +264,42 @@ pub enum Error {
     #[snafu(display("Cannot write to this database: no mutable buffer configured"))]
     DatabaseNotWriteable {},
 
+    #[snafu(display(
+        "Cannot write to or persist this database: its write buffer could not be replayed"
+    ))]
+    WriteBufferNotReplayed {},
+
     #[snafu(display("Hard buffer size limit reached"))]
     HardLimitReached {},
 
+    #[snafu(display("Schema conflict writing to partition {}: {}", partition_key, source))]
+    SchemaConflict {
+        partition_key: String,
+        source: mutable_buffer::chunk::Error,
+    },
+
+    #[snafu(display("Table quota exceeded: the database is limited to {} tables", limit))]
+    TableQuotaExceeded { limit: usize },
+
+    #[snafu(display(
+        "Column quota exceeded: table {} is limited to {} columns",
+        table_name,
+        limit
+    ))]
+    ColumnQuotaExceeded { table_name: String, limit: usize },
+
+    #[snafu(display(
+        "Row quota exceeded: the database is limited to {} rows, writing {} rows to the {} stored",
+        limit,
+        rows,
+        stored
+    ))]
+    RowQuotaExceeded {
+        limit: usize,
+        rows: usize,
+        stored: usize,
+    },
+
     #[snafu(display("Can not write entry {}:{} : {}", partition_key, chunk_id, source))]
     WriteEntry {
         partition_key: String,
@@ -199,13 +322,32 @@ pub enum Error {
     #[snafu(display("Error building sequenced entry: {}", source))]
     SequencedEntryError { source: entry::SequencedEntryError },
 
+    #[snafu(display("Error listing object store: {}", source))]
+    ListingObjectStore { source: object_store::Error },
+
     #[snafu(display("Error while handling transaction on preserved catalog: {}", source))]
     TransactionError {
         source: parquet_file::catalog::Error,
     },
+
+    #[snafu(display("Error reading parquet file {}: {}", path, source))]
+    ReadingParquetFile {
+        path: String,
+        source: object_store::Error,
+    },
+
+    #[snafu(display("Error reading metadata of parquet file {}: {}", path, source))]
+    ReadingParquetMetadata {
+        path: String,
+        source: parquet_file::metadata::Error,
+    },
 }
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// Maximum number of writes through the write buffer that can be in flight at
+/// the same time
+const MAX_CONCURRENT_WRITES: u32 = 1 << 16;
+
 /// This is the main IOx Database object. It is the root object of any
 /// specific InfluxDB IOx instance
 ///
@@ -263,7 +405,7 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 /// outside of the Db
 #[derive(Debug)]
 pub struct Db {
-    pub rules: RwLock<DatabaseRules>,
+    pub rules: Arc<RwLock<DatabaseRules>>,
 
     pub server_id: ServerId, // this is also the Query Server ID
 
@@ -304,11 +446,47 @@ pub struct Db {
     /// Number of iterations of the worker cleanup loop for this Db
     worker_iterations_cleanup: AtomicUsize,
 
+    /// Set by `flush` to stop the lifecycle policy from starting new actions.
+    /// Held while the policy starts them, so that these are registered as
+    /// jobs once it is set.
+    lifecycle_stopped: Mutex<bool>,
+
     /// Metric labels
     metric_labels: Vec<KeyValue>,
 
     /// Optionally buffer writes
     write_buffer: Option<Arc<dyn WriteBuffer>>,
+
+    /// Permits for writes through the write buffer, one for each write in
+    /// flight. There are none until the write buffer has been replayed, and
+    /// all of them are taken to wait for the writes in flight. Closed if the
+    /// write buffer could not be replayed.
+    write_permits: Semaphore,
+
+    /// Whether the write buffer has been replayed
+    replayed: AtomicBool,
+
+    /// Sequence number following the last entry stored in the mutable buffer
+    next_sequence_number: AtomicU64,
+
+    /// The usage of the quotas, once counted for a write with quotas. Writes
+    /// are admitted and written while holding this lock, so that concurrent
+    /// writes cannot exceed a quota together.
+    quota_usage: Mutex<Option<QuotaUsage>>,
+
+    /// Incremented when rows are added to or removed from the chunks other
+    /// than by writes, which makes a counted quota usage outdated
+    quota_usage_generation: AtomicU64,
+
+    /// Memory held by the queries against this database
+    query_memory: QueryMemory,
+
+    /// The queries running against this database
+    running_queries: RunningQueries,
+
+    /// Shares tag values between the open chunks of the mutable buffer if
+    /// the `mutable_shared_tag_values` lifecycle rule is set
+    string_interner: Arc<StringInterner>,
 }
 
 /// Load preserved catalog state from store.
@@ -393,6 +571,45 @@ pub async fn load_or_create_preserved_catalog(
     }
 }
 
+/// Adds parquet files that already exist in the data directory of the
+/// catalog's database to the preserved catalog in a single transaction. Once
+/// the catalog is used by a [`Db`] their data shows up as object store only
+/// chunks.
+pub async fn import_parquet_files(
+    preserved_catalog: &PreservedCatalog<Catalog>,
+    paths: &[Path],
+) -> Result<()> {
+    let object_store = preserved_catalog.object_store();
+
+    let mut files = Vec::with_capacity(paths.len());
+    for path in paths {
+        let data = object_store
+            .get(path)
+            .await
+            .context(ReadingParquetFile {
+                path: path.display(),
+            })?
+            .map_ok(|bytes| bytes.to_vec())
+            .try_concat()
+            .await
+            .context(ReadingParquetFile {
+                path: path.display(),
+            })?;
+        let metadata = read_parquet_metadata_from_file(data).context(ReadingParquetMetadata {
+            path: path.display(),
+        })?;
+        files.push((DirsAndFileName::from(path.clone()), metadata));
+    }
+
+    let mut transaction = preserved_catalog.open_transaction().await;
+    for (path, metadata) in &files {
+        transaction
+            .add_parquet(path, metadata)
+            .context(TransactionError)?;
+    }
+    transaction.commit().await.context(TransactionError)
+}
+
 impl Db {
     pub fn new(
         rules: DatabaseRules,
@@ -405,7 +622,7 @@ impl Db {
     ) -> Self {
         let db_name = rules.name.clone();
 
-        let rules = RwLock::new(rules);
+        let rules = Arc::new(RwLock::new(rules));
         let server_id = server_id;
         let store = Arc::clone(&object_store);
         let metrics_registry = Arc::clone(&preserved_catalog.state().metrics_registry);
@@ -413,6 +630,7 @@ impl Db {
 
         let catalog_access = QueryCatalogAccess::new(
             &db_name,
+            Arc::clone(&rules),
             preserved_catalog.state(),
             Arc::clone(&jobs),
             Arc::clone(&metrics_registry),
@@ -422,6 +640,60 @@ impl Db {
 
         let process_clock = process_clock::ProcessClock::new();
 
+        // The queries of each database are limited on their own
+        let exec = Arc::new(exec.for_database());
+
+        if let Some(cache) = exec.result_cache() {
+            let cache = Arc::clone(cache);
+            let domain =
+                metrics_registry.register_domain_with_labels("query_cache", metric_labels.clone());
+            domain.register_observer(None, &[], move |observer: MetricObserverBuilder<'_>| {
+                let lookups = Arc::clone(&cache);
+                observer.register_counter_u64(
+                    "lookups",
+                    None,
+                    "Lookups of query results in the cache",
+                    move |observer| {
+                        observer.observe(lookups.hits(), &[KeyValue::new("result", "hit")]);
+                        observer.observe(lookups.misses(), &[KeyValue::new("result", "miss")]);
+                    },
+                );
+                observer.register_gauge_u64(
+                    "size",
+                    Some("bytes"),
+                    "Size of the cached query results",
+                    move |observer| observer.observe(cache.bytes() as u64, &[]),
+                );
+            });
+        }
+
+        // Writes wait for the write buffer to be replayed, if it is
+        let write_permits = if write_buffer.is_some() && !rules.read().lifecycle_rules.immutable {
+            Semaphore::new(0)
+        } else {
+            Semaphore::new(MAX_CONCURRENT_WRITES as usize)
+        };
+
+        let string_interner = Arc::new(StringInterner::new());
+        let interner = Arc::clone(&string_interner);
+        let domain =
+            metrics_registry.register_domain_with_labels("string_interner", metric_labels.clone());
+        domain.register_observer(None, &[], move |observer: MetricObserverBuilder<'_>| {
+            let strings = Arc::clone(&interner);
+            observer.register_gauge_u64(
+                "strings",
+                None,
+                "Tag values shared between the open chunks of the mutable buffer",
+                move |observer| observer.observe(strings.len() as u64, &[]),
+            );
+            observer.register_gauge_u64(
+                "size",
+                Some("bytes"),
+                "Memory used by the tag values shared between open chunks",
+                move |observer| observer.observe(interner.size() as u64, &[]),
+            );
+        });
+
         Self {
             rules,
             server_id,
@@ -434,8 +706,17 @@ impl Db {
             process_clock,
             worker_iterations_lifecycle: AtomicUsize::new(0),
             worker_iterations_cleanup: AtomicUsize::new(0),
+            lifecycle_stopped: Mutex::new(false),
             metric_labels,
             write_buffer,
+            write_permits,
+            replayed: AtomicBool::new(false),
+            next_sequence_number: AtomicU64::new(0),
+            quota_usage: Default::default(),
+            quota_usage_generation: AtomicU64::new(0),
+            query_memory: Default::default(),
+            running_queries: Default::default(),
+            string_interner,
         }
     }
 
@@ -444,8 +725,92 @@ impl Db {
         Arc::clone(&self.exec)
     }
 
+    /// Returns the accounting of the memory held by queries against this
+    /// database
+    pub fn query_memory(&self) -> &QueryMemory {
+        &self.query_memory
+    }
+
+    /// Returns the queries running against this database
+    pub fn running_queries(&self) -> &RunningQueries {
+        &self.running_queries
+    }
+
+    /// Returns the memory used by this database: its chunks in the mutable
+    /// buffer and read buffer, the tag values shared between its open
+    /// chunks, and the results of running and cached queries
+    pub fn memory_used(&self) -> usize {
+        let cached = self
+            .exec
+            .result_cache()
+            .map(|cache| cache.bytes())
+            .unwrap_or(0);
+        self.preserved_catalog.state().metrics().memory().total()
+            + self.string_interner.size()
+            + self.query_memory.used()
+            + cached
+    }
+
+    /// Returns a version of the chunks of this database, which changes
+    /// whenever chunks are created, written to, moved between storages,
+    /// deleted from or dropped
+    fn chunks_version(&self) -> u64 {
+        self.preserved_catalog
+            .state()
+            .chunks()
+            .into_iter()
+            .map(|chunk| {
+                let chunk = chunk.read();
+                let summary = chunk.summary();
+
+                let mut hasher = DefaultHasher::new();
+                summary.partition_key.hash(&mut hasher);
+                summary.table_name.hash(&mut hasher);
+                summary.id.hash(&mut hasher);
+                summary.storage.as_str().hash(&mut hasher);
+                summary.row_count.hash(&mut hasher);
+                summary.time_of_last_write.hash(&mut hasher);
+                chunk.delete_predicates().len().hash(&mut hasher);
+                hasher.finish()
+            })
+            // the chunks are in no particular order
+            .fold(0, u64::wrapping_add)
+    }
+
+    /// Returns the results of `query`, from the query result cache if the
+    /// same query was run on the same chunks before, or otherwise by running
+    /// `execute`. `query` must identify the query and all of its inputs,
+    /// such as the values of its parameters and the `others` databases it
+    /// may read from besides this one.
+    pub async fn query_with_cache<F, Fut, E>(
+        &self,
+        query: &str,
+        others: &[Arc<Self>],
+        execute: F,
+    ) -> Result<SendableRecordBatchStream, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<SendableRecordBatchStream, E>>,
+    {
+        let (cache, key) = match (self.exec.result_cache(), QueryResultCache::key(query)) {
+            (Some(cache), Some(key)) => (cache, key),
+            _ => return execute().await,
+        };
+
+        // the results are cached for the chunks before running the query, so
+        // that writes while it runs invalidate them
+        let version = others.iter().fold(self.chunks_version(), |version, db| {
+            version.wrapping_add(db.chunks_version())
+        });
+        if let Some(results) = cache.get(&key, version) {
+            return Ok(results);
+        }
+        Ok(cache.fill(key, version, execute().await?))
+    }
+
     /// Rolls over the active chunk in the database's specified
     /// partition. Returns the previously open (now closed) Chunk if there was any.
+    #[tracing::instrument(skip(self))]
     pub async fn rollover_partition(
         &self,
         partition_key: &str,
@@ -480,7 +845,131 @@ impl Db {
         }
     }
 
+    /// Closes chunk `chunk_id` of table `table_name` in partition
+    /// `partition_key` if it is still open, so that the next write to the
+    /// table creates a new chunk
+    #[tracing::instrument(skip(self))]
+    pub fn rollover_chunk(
+        &self,
+        partition_key: &str,
+        table_name: &str,
+        chunk_id: u32,
+    ) -> Result<()> {
+        let partition = self
+            .preserved_catalog
+            .state()
+            .valid_partition(partition_key)
+            .context(RollingOverPartition {
+                partition_key,
+                table_name,
+            })?;
+
+        let partition = partition.write();
+        let chunk = partition
+            .chunk(table_name, chunk_id)
+            .context(RollingOverPartition {
+                partition_key,
+                table_name,
+            })?;
+        let mut chunk = chunk.write();
+        chunk.freeze().context(RollingOverPartition {
+            partition_key,
+            table_name,
+        })
+    }
+
+    /// Deletes the rows matching `predicate` from its table
+    ///
+    /// The matching rows of the open chunks of the table are masked in the
+    /// mutable buffer, so that later writes to these chunks are not
+    /// affected. The predicate is added to every other chunk of the table,
+    /// masking the matching rows at query time, and recorded in the
+    /// preserved catalog for the persisted chunks so that the rows stay
+    /// masked after a restart. Chunks persisted later on have the rows
+    /// removed from their parquet files.
+    ///
+    /// If there is a write buffer, the predicate is recorded in the
+    /// preserved catalog with the sequence number of the next entry, and
+    /// applied again to the entries before it that are replayed after a
+    /// restart, as their rows are not persisted yet.
+    #[tracing::instrument(skip(self))]
+    pub async fn delete(&self, predicate: Arc<DeletePredicate>) -> Result<()> {
+        let table_name = predicate.table_name.as_str();
+        debug!(%table_name, ?predicate, "deleting rows");
+
+        // Holding the transaction serializes the delete with persisting and
+        // compacting chunks: they either see the predicate when recording
+        // their new files, or have done so before and are recorded here.
+        let mut transaction = self.preserved_catalog.open_transaction().await;
+
+        // Wait for the writes in flight and hold back new ones, so that the
+        // predicate applies to exactly the entries before the sequence number
+        let (_permits, sequence_number) = match self.write_buffer {
+            Some(_) => {
+                let permits = self
+                    .write_permits
+                    .acquire_many(MAX_CONCURRENT_WRITES)
+                    .await
+                    .ok()
+                    .context(WriteBufferNotReplayed)?;
+                let sequence_number = self.next_sequence_number.load(Ordering::SeqCst);
+                (Some(permits), Some(sequence_number))
+            }
+            None => (None, None),
+        };
+
+        let paths = self.delete_from_chunks(&predicate)?;
+
+        if paths.is_empty() && sequence_number.is_none() {
+            transaction.abort();
+        } else {
+            // adds the predicate to the persisted chunks and the entries
+            // that are replayed
+            transaction
+                .add_delete_predicate(&predicate, &paths, sequence_number)
+                .context(TransactionError)?;
+            transaction.commit().await.context(TransactionError)?;
+        }
+
+        Ok(())
+    }
+
+    /// Masks the rows matching `predicate` in the chunks of its table that
+    /// are not persisted, returning the paths of the parquet files of the
+    /// persisted ones
+    fn delete_from_chunks(&self, predicate: &Arc<DeletePredicate>) -> Result<Vec<DirsAndFileName>> {
+        let table_name = predicate.table_name.as_str();
+
+        let mut paths: Vec<DirsAndFileName> = vec![];
+        for partition in self.preserved_catalog.state().partitions() {
+            let partition = partition.write();
+            for chunk in partition.chunks() {
+                let mut chunk = chunk.write();
+                if chunk.table_name().as_ref() != table_name {
+                    continue;
+                }
+
+                match chunk.stage() {
+                    ChunkStage::Open { .. } => {
+                        let mb_chunk = chunk
+                            .mutable_buffer()
+                            .context(DeletingRows { table_name })?;
+                        mb_chunk.delete(predicate);
+                    }
+                    ChunkStage::Persisted { parquet, .. } => {
+                        paths.push(parquet.table_path().into())
+                    }
+                    _ => chunk.add_delete_predicate(Arc::clone(predicate)),
+                }
+            }
+        }
+        self.outdate_quota_usage();
+
+        Ok(paths)
+    }
+
     /// Drops the specified chunk from the catalog and all storage systems
+    #[tracing::instrument(skip(self))]
     pub fn drop_chunk(&self, partition_key: &str, table_name: &str, chunk_id: u32) -> Result<()> {
         debug!(%partition_key, %table_name, %chunk_id, "dropping chunk");
 
@@ -533,7 +1022,76 @@ impl Db {
                 partition_key,
                 table_name,
                 chunk_id,
-            })
+            })?;
+        self.outdate_quota_usage();
+
+        Ok(())
+    }
+
+    /// Drops the specified partition, and all of its chunks, from the
+    /// catalog and all in-memory storage systems
+    ///
+    /// If `delete_persisted` is true, the parquet files of any persisted
+    /// chunks are also removed from the preserved catalog within a single
+    /// transaction, after which the cleanup worker deletes them from object
+    /// store. Otherwise these files remain referenced by the preserved
+    /// catalog and the chunks will be reloaded on restart.
+    #[tracing::instrument(skip(self))]
+    pub async fn drop_partition(&self, partition_key: &str, delete_persisted: bool) -> Result<()> {
+        debug!(%partition_key, %delete_persisted, "dropping partition");
+
+        let partition = self
+            .preserved_catalog
+            .state()
+            .valid_partition(partition_key)
+            .context(DroppingPartition { partition_key })?;
+
+        // refuse to drop partitions with chunks that are actively being
+        // moved, and find the files of all persisted chunks
+        let paths = {
+            let partition = partition.read();
+            let mut paths = vec![];
+
+            for chunk in partition.chunks() {
+                let chunk = chunk.read();
+
+                if let Some(lifecycle_action) = chunk.lifecycle_action() {
+                    return DropMovingChunk {
+                        partition_key,
+                        table_name: &*chunk.table_name(),
+                        chunk_id: chunk.id(),
+                        action: lifecycle_action.metadata().name(),
+                    }
+                    .fail();
+                }
+
+                if let ChunkStage::Persisted { parquet, .. } = chunk.stage() {
+                    paths.push(parquet.table_path());
+                }
+            }
+
+            paths
+        };
+
+        if delete_persisted && !paths.is_empty() {
+            let mut transaction = self.preserved_catalog.open_transaction().await;
+            for path in paths {
+                transaction
+                    .remove_parquet(&path.into())
+                    .context(TransactionError)?;
+            }
+            transaction.commit().await.context(TransactionError)?;
+        }
+
+        self.preserved_catalog
+            .state()
+            .drop_partition(partition_key)
+            .context(DroppingPartition { partition_key })?;
+        self.outdate_quota_usage();
+
+        debug!(%partition_key, "partition dropped");
+
+        Ok(())
     }
 
     /// Copies a chunk in the Closed state into the ReadBuffer from
@@ -546,6 +1104,7 @@ impl Db {
     /// but the process may take a long time
     ///
     /// Returns a handle to the newly loaded chunk in the read buffer
+    #[tracing::instrument(skip(self, tracker))]
     pub async fn load_chunk_to_read_buffer(
         &self,
         partition_key: &str,
@@ -611,7 +1170,11 @@ impl Db {
             // implemented (and tested) just panic
             .expect("Loading chunk to mutable buffer");
 
-        let sorted = sort_record_batch(batch).expect("failed to sort");
+        // the rows of the mutable buffer are in the order they were written,
+        // so deduplicating them here means no later stage of the chunk
+        // contains duplicates
+        let (sorted, sort_key) =
+            compact::merge_record_batches(&[batch], vec![]).expect("failed to deduplicate");
         rb_chunk.upsert_table(&table_summary.name, sorted);
 
         // Relock the chunk again (nothing else should have been able
@@ -619,11 +1182,15 @@ impl Db {
         let mut chunk = chunk.write();
 
         // update the catalog to say we are done processing
-        chunk.set_moved(Arc::new(rb_chunk)).context(LoadingChunk {
-            partition_key,
-            table_name,
-            chunk_id,
-        })?;
+        chunk
+            .set_moved(Arc::new(rb_chunk), sort_key)
+            .context(LoadingChunk {
+                partition_key,
+                table_name,
+                chunk_id,
+            })?;
+        // deduplicating may have removed rows
+        self.outdate_quota_usage();
 
         debug!(%partition_key, %table_name, %chunk_id, "chunk marked MOVED. loading complete");
 
@@ -632,7 +1199,7 @@ impl Db {
 
     /// Write given table of a given chunk to object store.
     /// The writing only happen if that chunk already in read buffer
-
+    #[tracing::instrument(skip(self, tracker))]
     pub async fn write_chunk_to_object_store(
         &self,
         partition_key: &str,
@@ -664,7 +1231,7 @@ impl Db {
 
         // update the catalog to say we are processing this chunk and
         // then drop the lock while we do the work
-        let (rb_chunk, table_summary) = {
+        let (rb_chunk, table_summary, delete_predicates, time_of_first_write, time_of_last_write) = {
             let mut chunk = chunk.write();
 
             let rb_chunk =
@@ -676,7 +1243,13 @@ impl Db {
                         chunk_id,
                     })?;
 
-            (rb_chunk, chunk.table_summary())
+            (
+                rb_chunk,
+                chunk.table_summary(),
+                chunk.delete_predicates().to_vec(),
+                chunk.time_of_first_write(),
+                chunk.time_of_last_write(),
+            )
         };
 
         debug!(%partition_key, %table_name, %chunk_id, "chunk marked WRITING , loading tables into object store");
@@ -695,7 +1268,7 @@ impl Db {
 
         // Get RecordBatchStream of data from the read buffer chunk
         let read_results = rb_chunk
-            .read_filter(table_name, predicate, Selection::All)
+            .read_filter(table_name, predicate, Selection::All, vec![])
             .context(ReadBufferChunkError {
                 table_name,
                 chunk_id,
@@ -713,14 +1286,27 @@ impl Db {
             Arc::clone(&arrow_schema),
         ));
 
+        // Don't write deleted rows
+        let stream: SendableRecordBatchStream = Box::pin(streams::DeletedRowsFilterStream::new(
+            stream,
+            arrow_schema,
+            delete_predicates.clone(),
+        ));
+
         // catalog-level transaction for preseveration layer
         {
             let mut transaction = self.preserved_catalog.open_transaction().await;
 
+            let min_unpersisted_sequence_number =
+                self.min_unpersisted_sequence_number(Some(&chunk)).await?;
+
             // Write this table data into the object store
             let metadata = IoxMetadata {
                 transaction_revision_counter: transaction.revision_counter(),
                 transaction_uuid: transaction.uuid(),
+                time_of_first_write,
+                time_of_last_write,
+                min_unpersisted_sequence_number,
             };
             let (path, parquet_metadata) = storage
                 .write_to_object_store(
@@ -733,10 +1319,22 @@ impl Db {
                 .await
                 .context(WritingToObjectStore)?;
 
+            let path = path.into();
             transaction
-                .add_parquet(&path.into(), &parquet_metadata)
+                .add_parquet(&path, &parquet_metadata)
                 .context(TransactionError)?;
+
+            // deletes that happened while writing are not reflected in the file
+            let late_predicates = new_delete_predicates(&chunk.read(), &delete_predicates);
+            for predicate in late_predicates {
+                transaction
+                    .add_delete_predicate(&predicate, &[path.clone()], None)
+                    .context(TransactionError)?;
+            }
             transaction.commit().await.context(TransactionError)?;
+
+            self.write_buffer_persisted(min_unpersisted_sequence_number)
+                .await;
         }
 
         // We know this chunk is ParquetFile type
@@ -744,22 +1342,229 @@ impl Db {
         Ok(DbChunk::parquet_file_snapshot(&chunk))
     }
 
-    /// Unload chunk from read buffer but keep it in object store
-    pub async fn unload_read_buffer(
+    /// Compacts the persisted chunks of a table within a partition into a
+    /// single chunk
+    ///
+    /// The data of the chunks is merged into one parquet file, keeping only
+    /// the last written row for each combination of tag values and timestamp,
+    /// and sorted. Within a single transaction the new file is then added to
+    /// the preserved catalog and the files of the compacted chunks removed,
+    /// after which the cleanup worker deletes these from object store.
+    ///
+    /// Only the chunks written before all the other chunks of the table are
+    /// compacted, as the compacted chunk takes their place in the order the
+    /// data of overlapping chunks is deduplicated in.
+    ///
+    /// Returns the compacted chunk, or `None` if there were fewer than two
+    /// such persisted chunks without an in-progress lifecycle action
+    #[tracing::instrument(skip(self, tracker))]
+    pub async fn compact_partition(
         &self,
         partition_key: &str,
         table_name: &str,
-        chunk_id: u32,
-    ) -> Result<Arc<DbChunk>> {
-        debug!(%partition_key, %table_name, %chunk_id, "unloading chunk from read buffer");
+        tracker: &TaskRegistration,
+    ) -> Result<Option<Arc<DbChunk>>> {
+        let partition = self
+            .preserved_catalog
+            .state()
+            .valid_partition(partition_key)
+            .context(CompactingPartition {
+                partition_key,
+                table_name,
+            })?;
 
-        // Get the chunk from the catalog
-        let chunk = {
-            let partition = self
-                .preserved_catalog
-                .state()
-                .valid_partition(partition_key)
-                .context(UnloadingChunkFromReadBuffer {
+        // find the oldest persisted chunks, in the order their data was
+        // written, and mark them as being compacted
+        let (chunk_id, parquet_chunks, time_of_first_write, time_of_last_write) = {
+            let mut partition = partition.write();
+
+            let mut chunks: Vec<_> = partition
+                .chunks()
+                .filter(|chunk| chunk.read().table_name().as_ref() == table_name)
+                .cloned()
+                .collect();
+            chunks.sort_by_cached_key(|chunk| {
+                let chunk = chunk.read();
+                (chunk.time_of_last_write(), chunk.id())
+            });
+
+            let chunks: Vec<_> = chunks
+                .into_iter()
+                .take_while(|chunk| {
+                    let chunk = chunk.read();
+                    chunk.lifecycle_action().is_none()
+                        && matches!(chunk.stage(), ChunkStage::Persisted { .. })
+                })
+                .collect();
+
+            if chunks.len() < 2 {
+                debug!(%partition_key, %table_name, chunks=chunks.len(), "not enough persisted chunks to compact");
+                return Ok(None);
+            }
+
+            let time_of_first_write = chunks
+                .iter()
+                .filter_map(|chunk| chunk.read().time_of_first_write())
+                .min();
+            let time_of_last_write = chunks
+                .iter()
+                .filter_map(|chunk| chunk.read().time_of_last_write())
+                .max();
+
+            let parquet_chunks = chunks
+                .into_iter()
+                .map(|chunk| {
+                    let mut guard = chunk.write();
+                    let parquet = guard.set_compacting(tracker)?;
+                    let delete_predicates = guard.delete_predicates().to_vec();
+                    drop(guard);
+                    Ok((chunk, parquet, delete_predicates))
+                })
+                .collect::<Result<Vec<_>, catalog::Error>>()
+                .context(CompactingPartition {
+                    partition_key,
+                    table_name,
+                })?;
+
+            let chunk_id = partition.reserve_chunk_id(table_name);
+
+            (
+                chunk_id,
+                parquet_chunks,
+                time_of_first_write,
+                time_of_last_write,
+            )
+        };
+
+        debug!(%partition_key, %table_name, %chunk_id, chunks=parquet_chunks.len(), "compacting chunks");
+
+        let mut batches = vec![];
+        for (chunk, parquet, delete_predicates) in &parquet_chunks {
+            let compacted_id = chunk.read().id();
+            let stream = parquet
+                .read_filter(&Predicate::default(), Selection::All)
+                .context(ReadingCompactedChunk {
+                    partition_key,
+                    table_name,
+                    chunk_id: compacted_id,
+                })?;
+            let schema = stream.schema();
+            let stream =
+                streams::DeletedRowsFilterStream::new(stream, schema, delete_predicates.clone());
+            let chunk_batches: Vec<_> =
+                stream
+                    .try_collect()
+                    .await
+                    .context(CollectingCompactedChunk {
+                        partition_key,
+                        table_name,
+                        chunk_id: compacted_id,
+                    })?;
+            batches.extend(chunk_batches);
+        }
+
+        let coercions = field_type_coercions(&self.rules.read().type_coercion_rules);
+        let (batch, _) =
+            compact::merge_record_batches(&batches, coercions).context(MergingChunks {
+                partition_key,
+                table_name,
+            })?;
+        let stream: SendableRecordBatchStream = Box::pin(MemoryStream::new(vec![batch]));
+
+        let storage = Storage::new(
+            Arc::clone(&self.store),
+            self.server_id,
+            self.rules.read().name.to_string(),
+        );
+
+        // swap the files within a single catalog-level transaction
+        {
+            let mut transaction = self.preserved_catalog.open_transaction().await;
+
+            let min_unpersisted_sequence_number =
+                self.min_unpersisted_sequence_number(None).await?;
+
+            let metadata = IoxMetadata {
+                transaction_revision_counter: transaction.revision_counter(),
+                transaction_uuid: transaction.uuid(),
+                time_of_first_write,
+                time_of_last_write,
+                min_unpersisted_sequence_number,
+            };
+            let (path, parquet_metadata) = storage
+                .write_to_object_store(
+                    partition_key.to_string(),
+                    chunk_id,
+                    table_name.to_string(),
+                    stream,
+                    metadata,
+                )
+                .await
+                .context(WritingToObjectStore)?;
+
+            let path = path.into();
+            transaction
+                .add_parquet(&path, &parquet_metadata)
+                .context(TransactionError)?;
+
+            // deletes that happened while compacting are not reflected in
+            // the new file
+            let mut late_predicates: Vec<Arc<DeletePredicate>> = vec![];
+            for (chunk, _, delete_predicates) in &parquet_chunks {
+                for predicate in new_delete_predicates(&chunk.read(), delete_predicates) {
+                    if !late_predicates.contains(&predicate) {
+                        late_predicates.push(predicate);
+                    }
+                }
+            }
+            for predicate in late_predicates {
+                transaction
+                    .add_delete_predicate(&predicate, &[path.clone()], None)
+                    .context(TransactionError)?;
+            }
+
+            for (_, parquet, _) in &parquet_chunks {
+                transaction
+                    .remove_parquet(&parquet.table_path().into())
+                    .context(TransactionError)?;
+            }
+            transaction.commit().await.context(TransactionError)?;
+            self.outdate_quota_usage();
+
+            self.write_buffer_persisted(min_unpersisted_sequence_number)
+                .await;
+        }
+
+        debug!(%partition_key, %table_name, %chunk_id, "chunks compacted");
+
+        let chunk = partition
+            .read()
+            .chunk(table_name, chunk_id)
+            .context(CompactingPartition {
+                partition_key,
+                table_name,
+            })?;
+        let chunk = chunk.read();
+        Ok(Some(DbChunk::parquet_file_snapshot(&chunk)))
+    }
+
+    /// Unload chunk from read buffer but keep it in object store
+    #[tracing::instrument(skip(self))]
+    pub async fn unload_read_buffer(
+        &self,
+        partition_key: &str,
+        table_name: &str,
+        chunk_id: u32,
+    ) -> Result<Arc<DbChunk>> {
+        debug!(%partition_key, %table_name, %chunk_id, "unloading chunk from read buffer");
+
+        // Get the chunk from the catalog
+        let chunk = {
+            let partition = self
+                .preserved_catalog
+                .state()
+                .valid_partition(partition_key)
+                .context(UnloadingChunkFromReadBuffer {
                     partition_key,
                     table_name,
                     chunk_id,
@@ -879,6 +1684,100 @@ impl Db {
         tracker
     }
 
+    /// Spawns a task to compact the persisted chunks of a table within a
+    /// partition, see [`compact_partition`](Self::compact_partition)
+    pub fn compact_partition_in_background(
+        self: &Arc<Self>,
+        partition_key: String,
+        table_name: String,
+    ) -> TaskTracker<Job> {
+        let name = self.rules.read().name.clone();
+        let (tracker, registration) = self.jobs.register(Job::CompactChunks {
+            db_name: name.to_string(),
+            partition_key: partition_key.clone(),
+            table_name: table_name.clone(),
+        });
+
+        let captured_registration = registration.clone();
+        let captured_db = Arc::clone(&self);
+        let task = async move {
+            debug!(%name, %partition_key, %table_name, "background task compacting chunks");
+            let result = captured_db
+                .compact_partition(&partition_key, &table_name, &captured_registration)
+                .await;
+
+            if let Err(e) = result {
+                info!(?e, %name, %partition_key, %table_name, "background task error compacting chunks");
+                return Err(e);
+            }
+
+            debug!(%name, %partition_key, %table_name, "background task completed compacting chunks");
+
+            Ok(())
+        };
+
+        tokio::spawn(task.track(registration));
+
+        tracker
+    }
+
+    /// Prepares the database for the server to shut down: stops the
+    /// lifecycle policy and waits for the chunk moves and writes it started.
+    /// If `persist` is set, then closes the open chunks and writes all chunks
+    /// that are not yet in object store to it, through the read buffer.
+    ///
+    /// Without `persist`, the chunks are left as they are, as the data of
+    /// chunks that are not persisted is lost on exit anyway.
+    ///
+    /// Every chunk is attempted, returning the first error encountered
+    pub async fn flush(&self, persist: bool) -> Result<()> {
+        *self.lifecycle_stopped.lock() = true;
+
+        let db_name = self.rules.read().name.to_string();
+        for job in self.jobs.running() {
+            if job.metadata().db_name() == Some(db_name.as_str()) {
+                job.join().await;
+            }
+        }
+
+        if !persist {
+            return Ok(());
+        }
+
+        let mut res = Ok(());
+
+        for chunk in self.preserved_catalog.state().chunk_summaries() {
+            if let Err(e) = self.flush_chunk(&chunk).await {
+                error!(partition_key=%chunk.partition_key, table_name=%chunk.table_name, chunk_id=chunk.id, %e, "cannot flush chunk");
+                res = res.and(Err(e));
+            }
+        }
+
+        res
+    }
+
+    async fn flush_chunk(&self, chunk: &ChunkSummary) -> Result<()> {
+        let (partition_key, table_name, chunk_id) =
+            (&*chunk.partition_key, &*chunk.table_name, chunk.id);
+        let registration = TaskRegistration::new();
+
+        let mut storage = chunk.storage;
+        if storage == ChunkStorage::OpenMutableBuffer {
+            self.rollover_partition(partition_key, table_name).await?;
+            storage = ChunkStorage::ClosedMutableBuffer;
+        }
+        if storage == ChunkStorage::ClosedMutableBuffer {
+            self.load_chunk_to_read_buffer(partition_key, table_name, chunk_id, &registration)
+                .await?;
+            storage = ChunkStorage::ReadBuffer;
+        }
+        if storage == ChunkStorage::ReadBuffer {
+            self.write_chunk_to_object_store(partition_key, table_name, chunk_id, &registration)
+                .await?;
+        }
+        Ok(())
+    }
+
     /// Return chunk summary information for all chunks in the specified
     /// partition across all storage systems
     pub fn partition_chunk_summaries(&self, partition_key: &str) -> Vec<ChunkSummary> {
@@ -904,6 +1803,76 @@ impl Db {
             })
     }
 
+    /// Returns a receiver of all chunk lifecycle transitions in this
+    /// database from now on. Slow receivers skip the oldest events
+    pub fn subscribe_chunk_events(&self) -> broadcast::Receiver<ChunkEvent> {
+        self.preserved_catalog
+            .state()
+            .metrics()
+            .subscribe_chunk_events()
+    }
+
+    /// Return a summary of how the data of each table in the specified
+    /// partition is stored, or `None` if there is no such partition
+    pub async fn partition_storage_summary(
+        &self,
+        partition_key: &str,
+    ) -> Result<Option<PartitionStorageSummary>> {
+        let chunks: Vec<_> = match self.preserved_catalog.state().partition(partition_key) {
+            Some(partition) => {
+                let partition = partition.read();
+                partition
+                    .chunks()
+                    .map(|chunk| {
+                        let chunk = chunk.read();
+                        let path = match chunk.stage() {
+                            ChunkStage::Persisted { parquet, .. } => Some(parquet.table_path()),
+                            _ => None,
+                        };
+                        (chunk.summary(), path)
+                    })
+                    .collect()
+            }
+            None => return Ok(None),
+        };
+
+        let mut summaries = Vec::with_capacity(chunks.len());
+        for (summary, path) in chunks {
+            let object_store_bytes = match path {
+                Some(path) => Some(self.object_size(&path).await?),
+                None => None,
+            };
+            summaries.push((summary, object_store_bytes));
+        }
+
+        Ok(Some(PartitionStorageSummary::from_chunk_summaries(
+            partition_key,
+            summaries,
+        )))
+    }
+
+    /// Return the size in bytes of the object at `path` in object store
+    async fn object_size(&self, path: &Path) -> Result<usize> {
+        let path = DirsAndFileName::from(path.clone());
+
+        let mut dir = path.clone();
+        dir.file_name = None;
+        let dir = self.store.path_from_dirs_and_filename(dir);
+
+        let listing = self
+            .store
+            .list_with_delimiter(&dir)
+            .await
+            .context(ListingObjectStore)?;
+
+        Ok(listing
+            .objects
+            .into_iter()
+            .filter(|meta| DirsAndFileName::from(meta.location.clone()) == path)
+            .map(|meta| meta.size)
+            .sum())
+    }
+
     /// Return table summary information for the given chunk in the specified
     /// partition
     pub fn table_summary(
@@ -938,16 +1907,59 @@ impl Db {
     ) {
         info!("started background worker");
 
+        tokio::select! {
+            replayed = self.replay_write_buffer() => match replayed {
+                Ok(0) => {}
+                Ok(entries) => info!(entries, "replayed write buffer"),
+                Err(e) => {
+                    // The lifecycle policy must not persist data and drop write buffer entries
+                    // that were not replayed
+                    error!(%e, "error replaying write buffer, stopping background worker");
+                    return;
+                }
+            },
+            _ = shutdown.cancelled() => {}
+        }
+
         tokio::join!(
             // lifecycle manager loop
             async {
                 let mut lifecycle_manager = LifecycleManager::new(Arc::clone(&self));
 
                 while !shutdown.is_cancelled() {
-                    self.worker_iterations_lifecycle
-                        .fetch_add(1, Ordering::Relaxed);
+                    let next_check = {
+                        let stopped = self.lifecycle_stopped.lock();
+                        if *stopped {
+                            break;
+                        }
+                        self.worker_iterations_lifecycle
+                            .fetch_add(1, Ordering::Relaxed);
+                        lifecycle_manager.check_for_work()
+                    };
+                    tokio::select! {
+                        _ = next_check => {},
+                        _ = shutdown.cancelled() => break,
+                    }
+                }
+            },
+            // rollup loop
+            async {
+                let mut rollup_manager = RollupManager::new(Arc::clone(&self));
+
+                while !shutdown.is_cancelled() {
+                    tokio::select! {
+                        _ = rollup_manager.check_for_work() => {},
+                        _ = shutdown.cancelled() => break,
+                    }
+                }
+            },
+            // continuous query loop
+            async {
+                let mut scheduler = ContinuousQueryScheduler::new(Arc::clone(&self));
+
+                while !shutdown.is_cancelled() {
                     tokio::select! {
-                        _ = lifecycle_manager.check_for_work() => {},
+                        _ = scheduler.check_for_work() => {},
                         _ = shutdown.cancelled() => break,
                     }
                 }
@@ -981,8 +1993,135 @@ impl Db {
         info!("finished background worker");
     }
 
+    /// Applies the entries stored in the write buffer, if one is configured, to the mutable
+    /// buffer so that writes accepted before a restart are not lost, skipping the entries whose
+    /// data the preserved catalog records as persisted. Returns the number of entries replayed.
+    ///
+    /// Writes wait for the replay to finish. Entries that cannot be written are logged and
+    /// skipped, but if the write buffer cannot be read, the database neither accepts writes
+    /// nor persists data afterwards, failing with [`Error::WriteBufferNotReplayed`].
+    /// The write buffer is only replayed once, later calls return 0.
+    pub async fn replay_write_buffer(&self) -> Result<usize> {
+        let write_buffer = match self.write_buffer.as_ref() {
+            Some(write_buffer) if !self.rules.read().lifecycle_rules.immutable => write_buffer,
+            _ => return Ok(0),
+        };
+        if self.replayed.swap(true, Ordering::SeqCst) {
+            return Ok(0);
+        }
+
+        let from = self
+            .preserved_catalog
+            .state()
+            .min_unpersisted_sequence_number();
+        self.next_sequence_number.fetch_max(from, Ordering::SeqCst);
+
+        // The rows deleted before the restart are deleted again once the entries written
+        // before the delete are replayed
+        let mut deletes = self
+            .preserved_catalog
+            .state()
+            .unpersisted_delete_predicates(from)
+            .into_iter()
+            .peekable();
+        let delete = |predicate: &Arc<DeletePredicate>| {
+            if let Err(e) = self.delete_from_chunks(predicate) {
+                error!(%e, ?predicate, "error deleting rows of replayed write buffer entries")
+            }
+        };
+
+        // Entries that cannot be written, e.g. because they conflict with a schema changed since,
+        // are logged and skipped rather than losing the rest of the write buffer
+        let replayed = async {
+            let mut entries = write_buffer.replay(from);
+            let mut replayed = 0;
+            while let Some(sequenced_entry) = entries.try_next().await.context(WriteBufferError)? {
+                let sequence_number = sequenced_entry.sequence().number;
+                while let Some((predicate, _)) =
+                    deletes.next_if(|(_, before)| *before <= sequence_number)
+                {
+                    delete(&predicate);
+                }
+                match self.write_sequenced_entry(Arc::new(sequenced_entry), false) {
+                    Ok(()) => replayed += 1,
+                    Err(e) => {
+                        error!(%e, sequence_number, "error replaying write buffer entry, skipping it")
+                    }
+                }
+            }
+            for (predicate, _) in deletes {
+                delete(&predicate);
+            }
+            Ok(replayed)
+        }
+        .await;
+
+        match &replayed {
+            Ok(_) => self
+                .write_permits
+                .add_permits(MAX_CONCURRENT_WRITES as usize),
+            Err(_) => self.write_permits.close(),
+        }
+        replayed
+    }
+
+    /// Returns the sequence number of the oldest write buffer entry whose data is only held by
+    /// the chunks in memory, not counting the chunk `persisting`, to be recorded in the parquet
+    /// files written. Returns `None` if there is no write buffer, and fails if the write buffer
+    /// could not be replayed.
+    async fn min_unpersisted_sequence_number(
+        &self,
+        persisting: Option<&Arc<tracker::RwLock<CatalogChunk>>>,
+    ) -> Result<Option<u64>> {
+        if self.write_buffer.is_none() {
+            return Ok(None);
+        }
+
+        // Wait for the writes in flight, whose entries may be stored in the write buffer but
+        // not yet in a chunk
+        let _permits = self
+            .write_permits
+            .acquire_many(MAX_CONCURRENT_WRITES)
+            .await
+            .ok()
+            .context(WriteBufferNotReplayed)?;
+
+        let min = self
+            .preserved_catalog
+            .state()
+            .partitions()
+            .flat_map(|partition| partition.read().chunks().cloned().collect::<Vec<_>>())
+            .filter(|chunk| !persisting.map_or(false, |persisting| Arc::ptr_eq(chunk, persisting)))
+            .filter_map(|chunk| {
+                let chunk = chunk.read();
+                match chunk.stage() {
+                    ChunkStage::Persisted { .. } => None,
+                    _ => chunk.min_sequence_number(),
+                }
+            })
+            .min();
+
+        Ok(Some(min.unwrap_or_else(|| {
+            self.next_sequence_number.load(Ordering::SeqCst)
+        })))
+    }
+
+    /// Lets the write buffer drop the entries before `min_unpersisted_sequence_number`, once a
+    /// transaction recording it is committed to the preserved catalog. Failing to do so only
+    /// keeps entries around longer than needed, so errors are logged rather than returned.
+    async fn write_buffer_persisted(&self, min_unpersisted_sequence_number: Option<u64>) {
+        if let (Some(write_buffer), Some(sequence_number)) =
+            (self.write_buffer.as_ref(), min_unpersisted_sequence_number)
+        {
+            if let Err(e) = write_buffer.persisted(sequence_number).await {
+                error!(%e, sequence_number, "error removing persisted entries from the write buffer");
+            }
+        }
+    }
+
     /// Stores an entry based on the configuration.
-    pub fn store_entry(&self, entry: Entry) -> Result<()> {
+    #[tracing::instrument(level = "debug", skip(self, entry), fields(bytes = entry.data().len()))]
+    pub async fn store_entry(&self, entry: Entry) -> Result<()> {
         let immutable = {
             let rules = self.rules.read();
             rules.lifecycle_rules.immutable
@@ -993,13 +2132,28 @@ impl Db {
                 // If only the write buffer is configured, this is passing the data through to
                 // the write buffer, and it's not an error. We ignore the returned metadata; it
                 // will get picked up when data is read from the write buffer.
-                let _ = write_buffer.store_entry(&entry).context(WriteBufferError)?;
+                let _ = write_buffer
+                    .store_entry(&entry)
+                    .await
+                    .context(WriteBufferError)?;
                 Ok(())
             }
             (Some(write_buffer), false) => {
+                // Wait for the write buffer to be replayed, and hold the permit until the entry
+                // is in the mutable buffer so that it is not missed when persisting
+                let _permit = self
+                    .write_permits
+                    .acquire()
+                    .await
+                    .ok()
+                    .context(WriteBufferNotReplayed)?;
+
                 // If using both write buffer and mutable buffer, we want to wait for the write
                 // buffer to return success before adding the entry to the mutable buffer.
-                let sequence = write_buffer.store_entry(&entry).context(WriteBufferError)?;
+                let sequence = write_buffer
+                    .store_entry(&entry)
+                    .await
+                    .context(WriteBufferError)?;
                 let sequenced_entry = Arc::new(
                     SequencedEntry::new_from_sequence(sequence, entry)
                         .context(SequencedEntryError)?,
@@ -1031,12 +2185,29 @@ impl Db {
 
     /// Given a `SequencedEntry`, if the mutable buffer is configured, the `SequencedEntry` is then
     /// written into the mutable buffer.
+    #[tracing::instrument(level = "debug", skip(self, sequenced_entry))]
     pub fn store_sequenced_entry(&self, sequenced_entry: Arc<SequencedEntry>) -> Result<()> {
+        self.write_sequenced_entry(sequenced_entry, true)
+    }
+
+    /// Writes `sequenced_entry` into the mutable buffer. Unless `admit` is set, the entry is
+    /// written without checking the hard memory limit, the quotas and the schema conflict
+    /// policy, as done for entries replayed from the write buffer, which were admitted before.
+    fn write_sequenced_entry(
+        &self,
+        sequenced_entry: Arc<SequencedEntry>,
+        admit: bool,
+    ) -> Result<()> {
         // Get all needed database rule values, then release the lock
         let rules = self.rules.read();
         let mutable_size_threshold = rules.lifecycle_rules.mutable_size_threshold;
+        let mutable_row_threshold = rules.lifecycle_rules.mutable_row_threshold;
+        let shared_tag_values = rules.lifecycle_rules.mutable_shared_tag_values;
         let immutable = rules.lifecycle_rules.immutable;
         let buffer_size_hard = rules.lifecycle_rules.buffer_size_hard;
+        let quotas = rules.quotas.clone();
+        let schema_conflict_policy = rules.schema_conflict_policy;
+        let type_coercion_rules = rules.type_coercion_rules.clone();
         std::mem::drop(rules);
 
         // We may have gotten here through `store_entry`, in which case this is checking the
@@ -1045,10 +2216,23 @@ impl Db {
         if immutable {
             return DatabaseNotWriteable {}.fail();
         }
-        if let Some(hard_limit) = buffer_size_hard {
-            if self.preserved_catalog.state().metrics().memory().total() > hard_limit.get() {
-                return HardLimitReached {}.fail();
+        let mut quota_usage = self.quota_usage.lock();
+        if admit {
+            if let Some(hard_limit) = buffer_size_hard {
+                if self.memory_used() > hard_limit.get() {
+                    return HardLimitReached {}.fail();
+                }
             }
+            let partition_writes = sequenced_entry.partition_writes().unwrap_or_default();
+            if !quotas.is_unlimited() {
+                self.counted_quota_usage(&mut quota_usage)
+                    .check(&quotas, &partition_writes)?;
+            }
+            self.check_schema(
+                &partition_writes,
+                schema_conflict_policy,
+                &type_coercion_rules,
+            )?;
         }
 
         // TODO: Direct writes to closing chunks
@@ -1068,39 +2252,49 @@ impl Db {
                         Some(chunk) => {
                             let mut chunk = chunk.write();
                             chunk.record_write();
+                            chunk.record_sequence_number(sequenced_entry.sequence().number);
                             let chunk_id = chunk.id();
 
                             let mb_chunk =
                                 chunk.mutable_buffer().expect("cannot mutate open chunk");
 
+                            let rows = mb_chunk.rows();
                             mb_chunk
                                 .write_table_batch(
                                     sequenced_entry.sequence().id,
                                     sequenced_entry.sequence().number,
                                     table_batch,
+                                    schema_conflict_policy,
+                                    &type_coercion_rules,
                                 )
                                 .context(WriteEntry {
                                     partition_key,
                                     chunk_id,
                                 })?;
+                            if let Some(usage) = quota_usage.as_mut() {
+                                usage.record(mb_chunk, mb_chunk.rows() - rows);
+                            }
 
-                            check_chunk_closed(&mut *chunk, mutable_size_threshold);
+                            check_chunk_closed(
+                                &mut *chunk,
+                                mutable_size_threshold,
+                                mutable_row_threshold,
+                            );
                         }
                         None => {
                             let metrics = self.metrics_registry.register_domain_with_labels(
                                 "mutable_buffer",
                                 self.metric_labels.clone(),
                             );
-                            let mut mb_chunk = MutableBufferChunk::new(
+                            let catalog_metrics = self.preserved_catalog.state().metrics();
+                            let mut mb_chunk = MutableBufferChunk::new_with_interner(
                                 table_batch.name(),
                                 MutableBufferChunkMetrics::new(
                                     &metrics,
-                                    self.preserved_catalog
-                                        .state()
-                                        .metrics()
-                                        .memory()
-                                        .mutable_buffer(),
+                                    catalog_metrics.memory().mutable_buffer(),
+                                    catalog_metrics.memory().mutable_buffer_allocated(),
                                 ),
+                                shared_tag_values.then(|| Arc::clone(&self.string_interner)),
                             );
 
                             mb_chunk
@@ -1108,40 +2302,306 @@ impl Db {
                                     sequenced_entry.sequence().id,
                                     sequenced_entry.sequence().number,
                                     table_batch,
+                                    schema_conflict_policy,
+                                    &type_coercion_rules,
                                 )
                                 .context(WriteEntryInitial { partition_key })?;
+                            if let Some(usage) = quota_usage.as_mut() {
+                                usage.record(&mb_chunk, mb_chunk.rows());
+                            }
 
                             let new_chunk = partition
                                 .create_open_chunk(mb_chunk)
                                 .context(OpenEntry { partition_key })?;
+                            let mut new_chunk = new_chunk.write();
+                            new_chunk.record_sequence_number(sequenced_entry.sequence().number);
 
-                            check_chunk_closed(&mut *new_chunk.write(), mutable_size_threshold);
+                            check_chunk_closed(
+                                &mut *new_chunk,
+                                mutable_size_threshold,
+                                mutable_row_threshold,
+                            );
                         }
                     };
                 }
             }
         }
 
+        self.next_sequence_number
+            .fetch_max(sequenced_entry.sequence().number + 1, Ordering::SeqCst);
+
         Ok(())
     }
-}
 
-/// Check if the given chunk should be closed based on the the MutableBuffer size threshold.
-fn check_chunk_closed(chunk: &mut CatalogChunk, mutable_size_threshold: Option<NonZeroUsize>) {
-    if let Some(threshold) = mutable_size_threshold {
-        if let Ok(mb_chunk) = chunk.mutable_buffer() {
-            let size = mb_chunk.size();
+    /// Returns the error storing `entry` would currently fail with, without
+    /// storing it. Unlike [`store_entry`](Self::store_entry), rows that
+    /// conflict with the schema are an error even if the database is
+    /// configured to drop them, so that an entry passing validation would be
+    /// written as a whole
+    pub fn validate_entry(&self, entry: &Entry) -> Result<()> {
+        let rules = self.rules.read();
+        let immutable = rules.lifecycle_rules.immutable;
+        let buffer_size_hard = rules.lifecycle_rules.buffer_size_hard;
+        let quotas = rules.quotas.clone();
+        let schema_conflict_policy = match rules.schema_conflict_policy {
+            SchemaConflictPolicy::DropLines => SchemaConflictPolicy::Reject,
+            policy => policy,
+        };
+        let type_coercion_rules = rules.type_coercion_rules.clone();
+        std::mem::drop(rules);
 
-            if size > threshold.get() {
-                chunk.freeze().expect("cannot close open chunk");
+        if immutable && self.write_buffer.is_none() {
+            return DatabaseNotWriteable {}.fail();
+        }
+        if let Some(hard_limit) = buffer_size_hard {
+            if self.memory_used() > hard_limit.get() {
+                return HardLimitReached {}.fail();
             }
         }
+
+        let partition_writes = entry.partition_writes().unwrap_or_default();
+        if !quotas.is_unlimited() {
+            self.counted_quota_usage(&mut self.quota_usage.lock())
+                .check(&quotas, &partition_writes)?;
+        }
+        self.check_schema(
+            &partition_writes,
+            schema_conflict_policy,
+            &type_coercion_rules,
+        )
     }
-}
 
-#[async_trait]
-/// Convenience implementation of `Database` so the rest of the code
-/// can just use Db as a `Database` even though the implementation
+    /// Rejects the entry of `partition_writes` as a whole if one of its table
+    /// batches conflicts with the schema of the open chunk it would be written
+    /// to, so that a rejected entry leaves no partial write behind
+    fn check_schema(
+        &self,
+        partition_writes: &[PartitionWrite<'_>],
+        policy: SchemaConflictPolicy,
+        coercions: &[TypeCoercionRule],
+    ) -> Result<()> {
+        for write in partition_writes {
+            let partition_key = write.key();
+            let partition = match self.preserved_catalog.state().partition(partition_key) {
+                Some(partition) => partition,
+                None => continue,
+            };
+            let partition = partition.read();
+
+            for table_batch in write.table_batches() {
+                let chunk = match partition.open_chunk(table_batch.name()).ok().flatten() {
+                    Some(chunk) => chunk,
+                    None => continue,
+                };
+                let chunk = chunk.read();
+
+                if let ChunkStage::Open { mb_chunk, .. } = chunk.stage() {
+                    mb_chunk
+                        .validate_table_batch(&table_batch, policy, coercions)
+                        .context(SchemaConflict { partition_key })?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the quota usage held in `usage`, after counting it from the
+    /// chunks if it has not been counted yet or is outdated
+    fn counted_quota_usage<'a>(&self, usage: &'a mut Option<QuotaUsage>) -> &'a mut QuotaUsage {
+        let generation = self.quota_usage_generation.load(Ordering::SeqCst);
+        if usage
+            .as_ref()
+            .map_or(true, |usage| usage.generation != generation)
+        {
+            let catalog = self.preserved_catalog.state();
+            *usage = Some(QuotaUsage::count(&catalog, generation));
+        }
+        usage.as_mut().expect("quota usage counted")
+    }
+
+    /// Makes the counted quota usage outdated. Must be called after rows are
+    /// added to or removed from the chunks other than by writes, e.g. when
+    /// they are dropped or deduplicated.
+    fn outdate_quota_usage(&self) {
+        self.quota_usage_generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// The tables, columns and rows of a database that count towards its quotas
+#[derive(Debug, Default)]
+struct QuotaUsage {
+    /// The columns of each table
+    tables: BTreeMap<String, BTreeSet<String>>,
+
+    /// The number of rows stored
+    rows: usize,
+
+    /// The `quota_usage_generation` of the database the usage was counted at
+    generation: u64,
+}
+
+impl QuotaUsage {
+    /// Counts the usage of the chunks of `catalog`
+    fn count(catalog: &Catalog, generation: u64) -> Self {
+        let mut usage = Self {
+            generation,
+            ..Default::default()
+        };
+        for chunk in catalog.chunks() {
+            let summary = chunk.read().table_summary();
+            usage.rows += summary.count() as usize;
+            usage
+                .tables
+                .entry(summary.name.clone())
+                .or_default()
+                .extend(summary.columns.iter().map(|c| c.name.clone()));
+        }
+        usage
+    }
+
+    /// Rejects the entry of `partition_writes` as a whole if writing it would
+    /// exceed one of `quotas`. Tables and columns that already exist are never
+    /// rejected, so that lowering a quota does not prevent writes to the
+    /// existing schema
+    fn check(&self, quotas: &Quotas, partition_writes: &[PartitionWrite<'_>]) -> Result<()> {
+        // The columns the entry adds to each table it writes to
+        let mut added: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let mut added_tables = 0;
+        let mut rows = 0;
+        for write in partition_writes {
+            for table_batch in write.table_batches() {
+                rows += table_batch.row_count();
+
+                let table_name = table_batch.name();
+                let existing = self.tables.get(table_name);
+                if existing.is_none() && !added.contains_key(table_name) {
+                    if let Some(limit) = quotas.max_tables {
+                        if self.tables.len() + added_tables >= limit.get() {
+                            return TableQuotaExceeded { limit: limit.get() }.fail();
+                        }
+                    }
+                    added_tables += 1;
+                }
+                let columns = added.entry(table_name.to_string()).or_default();
+
+                let added_columns = columns.len();
+                for column in table_batch.columns() {
+                    if !existing.map_or(false, |existing| existing.contains(column.name())) {
+                        columns.insert(column.name().to_string());
+                    }
+                }
+                if let Some(limit) = quotas.max_columns_per_table {
+                    let num_columns = existing.map_or(0, BTreeSet::len) + columns.len();
+                    if columns.len() > added_columns && num_columns > limit.get() {
+                        return ColumnQuotaExceeded {
+                            table_name,
+                            limit: limit.get(),
+                        }
+                        .fail();
+                    }
+                }
+            }
+        }
+
+        if let Some(limit) = quotas.max_rows {
+            if self.rows + rows > limit.get() {
+                return RowQuotaExceeded {
+                    limit: limit.get(),
+                    rows,
+                    stored: self.rows,
+                }
+                .fail();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the columns of `chunk`, an open chunk that a write added `rows`
+    /// rows to
+    fn record(&mut self, chunk: &MutableBufferChunk, rows: usize) {
+        let columns = self
+            .tables
+            .entry(chunk.table_name().to_string())
+            .or_default();
+        for (name, _) in chunk.column_sizes() {
+            if !columns.contains(name) {
+                columns.insert(name.to_string());
+            }
+        }
+        self.rows += rows;
+    }
+}
+
+/// Check if the given chunk should be closed based on the the MutableBuffer size and row
+/// thresholds.
+///
+/// The size threshold is checked against the memory allocated by the chunk, which includes
+/// the spare capacity of its dictionaries and column builders, so that a chunk receiving many
+/// writes is closed before its actual memory usage exceeds the threshold.
+fn check_chunk_closed(
+    chunk: &mut CatalogChunk,
+    mutable_size_threshold: Option<NonZeroUsize>,
+    mutable_row_threshold: Option<NonZeroUsize>,
+) {
+    if let Ok(mb_chunk) = chunk.mutable_buffer() {
+        let allocated_bytes = mb_chunk.allocated_bytes();
+        let rows = mb_chunk.rows();
+        let exceeds_size = mutable_size_threshold.map_or(false, |t| allocated_bytes > t.get());
+        let exceeds_rows = mutable_row_threshold.map_or(false, |t| rows >= t.get());
+
+        if exceeds_size || exceeds_rows {
+            debug!(
+                partition_key=%chunk.key(),
+                table_name=%chunk.table_name(),
+                chunk_id=chunk.id(),
+                allocated_bytes,
+                rows,
+                exceeds_size,
+                exceeds_rows,
+                "closing mutable buffer chunk"
+            );
+            chunk.freeze().expect("cannot close open chunk");
+        }
+    }
+}
+
+/// Returns the delete predicates of `chunk` that are not in `known`
+fn new_delete_predicates(
+    chunk: &CatalogChunk,
+    known: &[Arc<DeletePredicate>],
+) -> Vec<Arc<DeletePredicate>> {
+    chunk
+        .delete_predicates()
+        .iter()
+        .filter(|predicate| !known.contains(predicate))
+        .cloned()
+        .collect()
+}
+
+/// Returns the pairs of field types that `rules` allow to be converted, for
+/// merging the schemas of chunks that store a column with different types
+pub(crate) fn field_type_coercions(
+    rules: &[TypeCoercionRule],
+) -> Vec<(InfluxFieldType, InfluxFieldType)> {
+    let field_type = |column_type: &ColumnType| match column_type {
+        ColumnType::I64 => InfluxFieldType::Integer,
+        ColumnType::U64 => InfluxFieldType::UInteger,
+        ColumnType::F64 => InfluxFieldType::Float,
+        ColumnType::String => InfluxFieldType::String,
+        ColumnType::Bool => InfluxFieldType::Boolean,
+    };
+
+    rules
+        .iter()
+        .map(|rule| (field_type(&rule.from), field_type(&rule.to)))
+        .collect()
+}
+
+#[async_trait]
+/// Convenience implementation of `Database` so the rest of the code
+/// can just use Db as a `Database` even though the implementation
 /// lives in `catalog_access`
 impl Database for Db {
     type Error = Error;
@@ -1205,7 +2665,8 @@ impl CatalogState for Catalog {
         info: CatalogParquetInfo,
     ) -> parquet_file::catalog::Result<()> {
         use parquet_file::catalog::{
-            CatalogStateFailure, PathParseFailed, SchemaReadFailed, StatisticsReadFailed,
+            CatalogStateFailure, IoxMetadataReadFailed, PathParseFailed, SchemaReadFailed,
+            StatisticsReadFailed,
         };
 
         // extract all relevant bits for the in-memory catalog
@@ -1226,6 +2687,14 @@ impl CatalogState for Catalog {
                     path: info.path.clone(),
                 },
             )?;
+        let iox_metadata = read_iox_metadata_from_parquet_metadata(&info.metadata).context(
+            IoxMetadataReadFailed {
+                path: info.path.clone(),
+            },
+        )?;
+        if let Some(sequence_number) = iox_metadata.min_unpersisted_sequence_number {
+            self.record_min_unpersisted_sequence_number(sequence_number);
+        }
 
         // Create a parquet chunk for this chunk
         let metrics = self
@@ -1270,7 +2739,12 @@ impl CatalogState for Catalog {
                 drop(partition_guard);
                 let mut partition_guard = partition.write();
                 partition_guard
-                    .create_object_store_only_chunk(chunk_id, parquet_chunk)
+                    .create_object_store_only_chunk(
+                        chunk_id,
+                        parquet_chunk,
+                        iox_metadata.time_of_first_write,
+                        iox_metadata.time_of_last_write,
+                    )
                     .map_err(|e| Box::new(e) as _)
                     .context(CatalogStateFailure { path: info.path })?;
                 debug!(%partition_key, %table_name, %chunk_id, "recovered chunk from persisted catalog");
@@ -1287,19 +2761,89 @@ impl CatalogState for Catalog {
         Ok(())
     }
 
-    fn remove(&self, _path: DirsAndFileName) -> parquet_file::catalog::Result<()> {
-        unimplemented!("parquet files cannot be removed from the catalog for now")
+    fn remove(&self, path: DirsAndFileName) -> parquet_file::catalog::Result<()> {
+        use parquet_file::catalog::CatalogStateFailure;
+
+        // find and drop the persisted chunk(s) backed by this file
+        let mut removed = false;
+        for partition in self.partitions() {
+            let mut partition = partition.write();
+
+            let chunks: Vec<_> = partition
+                .chunks()
+                .filter_map(|chunk| {
+                    let chunk = chunk.read();
+                    match chunk.stage() {
+                        ChunkStage::Persisted { parquet, .. }
+                            if DirsAndFileName::from(parquet.table_path()) == path =>
+                        {
+                            Some((chunk.table_name(), chunk.id()))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect();
+
+            for (table_name, chunk_id) in chunks {
+                partition
+                    .drop_chunk(table_name.to_string(), chunk_id)
+                    .map_err(|e| Box::new(e) as _)
+                    .context(CatalogStateFailure { path: path.clone() })?;
+                removed = true;
+            }
+        }
+
+        if removed {
+            Ok(())
+        } else {
+            Err(parquet_file::catalog::Error::ParquetFileDoesNotExist { path })
+        }
+    }
+
+    fn delete_predicate(
+        &self,
+        predicate: Arc<DeletePredicate>,
+        paths: Vec<DirsAndFileName>,
+        sequence_number: Option<u64>,
+    ) -> parquet_file::catalog::Result<()> {
+        if let Some(sequence_number) = sequence_number {
+            self.record_unpersisted_delete_predicate(Arc::clone(&predicate), sequence_number);
+        }
+
+        // find the persisted chunks backed by these files
+        let mut remaining: BTreeSet<_> = paths.into_iter().collect();
+        for partition in self.partitions() {
+            let partition = partition.read();
+            for chunk in partition.chunks() {
+                let mut chunk = chunk.write();
+                let path = match chunk.stage() {
+                    ChunkStage::Persisted { parquet, .. } => {
+                        DirsAndFileName::from(parquet.table_path())
+                    }
+                    _ => continue,
+                };
+                if remaining.remove(&path) {
+                    chunk.add_delete_predicate(Arc::clone(&predicate));
+                }
+            }
+        }
+
+        match remaining.into_iter().next() {
+            Some(path) => Err(parquet_file::catalog::Error::ParquetFileDoesNotExist { path }),
+            None => Ok(()),
+        }
     }
 }
 
 pub mod test_helpers {
     use super::*;
+    use data_types::timestamp::Precision;
     use entry::test_helpers::lp_to_entries;
     use std::collections::HashSet;
 
     /// Try to write lineprotocol data and return all tables that where written.
-    pub fn try_write_lp(db: &Db, lp: &str) -> Result<Vec<String>> {
-        let entries = lp_to_entries(lp);
+    pub async fn try_write_lp(db: &Db, lp: &str) -> Result<Vec<String>> {
+        let entries = lp_to_entries(lp, Precision::Nanoseconds);
 
         let mut tables = HashSet::new();
         for entry in &entries {
@@ -1312,9 +2856,9 @@ pub mod test_helpers {
             }
         }
 
-        entries
-            .into_iter()
-            .try_for_each(|entry| db.store_entry(entry))?;
+        for entry in entries {
+            db.store_entry(entry).await?;
+        }
 
         let mut tables: Vec<_> = tables.into_iter().collect();
         tables.sort();
@@ -1322,8 +2866,8 @@ pub mod test_helpers {
     }
 
     /// Same was [`try_write_lp`](try_write_lp) but will panic on failure.
-    pub fn write_lp(db: &Db, lp: &str) -> Vec<String> {
-        try_write_lp(db, lp).unwrap()
+    pub async fn write_lp(db: &Db, lp: &str) -> Vec<String> {
+        try_write_lp(db, lp).await.unwrap()
     }
 }
 
@@ -1344,9 +2888,10 @@ mod tests {
     use bytes::Bytes;
     use chrono::Utc;
     use data_types::{
-        chunk_metadata::ChunkStorage,
+        chunk_metadata::{ChunkLifecycleState, ChunkStorage},
         database_rules::{Order, Sort, SortOrder},
         partition_metadata::{ColumnSummary, InfluxDbType, StatValues, Statistics, TableSummary},
+        timestamp::TimestampRange,
     };
     use entry::test_helpers::lp_to_entry;
     use futures::{stream, StreamExt, TryStreamExt};
@@ -1379,7 +2924,7 @@ mod tests {
         let db = make_db().await.db;
         db.rules.write().lifecycle_rules.immutable = true;
         let entry = lp_to_entry("cpu bar=1 10");
-        let res = db.store_entry(entry);
+        let res = db.store_entry(entry).await;
         assert_contains!(
             res.unwrap_err().to_string(),
             "Cannot write to this database: no mutable buffer configured"
@@ -1400,7 +2945,7 @@ mod tests {
         test_db.rules.write().lifecycle_rules.immutable = true;
 
         let entry = lp_to_entry("cpu bar=1 10");
-        test_db.store_entry(entry).unwrap();
+        test_db.store_entry(entry).await.unwrap();
 
         assert_eq!(write_buffer.entries.lock().unwrap().len(), 1);
     }
@@ -1415,9 +2960,10 @@ mod tests {
             .build()
             .await
             .db;
+        test_db.replay_write_buffer().await.unwrap();
 
         let entry = lp_to_entry("cpu bar=1 10");
-        test_db.store_entry(entry).unwrap();
+        test_db.store_entry(entry).await.unwrap();
 
         assert_eq!(write_buffer.entries.lock().unwrap().len(), 1);
 
@@ -1434,11 +2980,271 @@ mod tests {
         assert_batches_eq!(expected, &batches);
     }
 
+    #[tokio::test]
+    async fn replay_write_buffer() {
+        // Entries stored in the write buffer before a restart should be applied to the mutable
+        // buffer of the new database
+        let write_buffer = Arc::new(MockBuffer::default());
+        let test_db = TestDb::builder()
+            .write_buffer(Arc::clone(&write_buffer) as _)
+            .build()
+            .await
+            .db;
+        test_db.replay_write_buffer().await.unwrap();
+        test_db
+            .store_entry(lp_to_entry("cpu bar=1 10"))
+            .await
+            .unwrap();
+        test_db
+            .store_entry(lp_to_entry("cpu bar=2 20"))
+            .await
+            .unwrap();
+
+        let restarted_buffer = Arc::new(MockBuffer {
+            entries: Arc::clone(&write_buffer.entries),
+        });
+        let restarted_db = TestDb::builder()
+            .write_buffer(restarted_buffer as _)
+            .build()
+            .await
+            .db;
+        assert_eq!(restarted_db.replay_write_buffer().await.unwrap(), 2);
+
+        let batches = run_query(Arc::new(restarted_db), "select * from cpu").await;
+        let expected = vec![
+            "+-----+-------------------------------+",
+            "| bar | time                          |",
+            "+-----+-------------------------------+",
+            "| 1   | 1970-01-01 00:00:00.000000010 |",
+            "| 2   | 1970-01-01 00:00:00.000000020 |",
+            "+-----+-------------------------------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        // Databases that only forward writes to the write buffer do not replay it
+        let immutable_db = TestDb::builder()
+            .write_buffer(write_buffer as _)
+            .build()
+            .await
+            .db;
+        immutable_db.rules.write().lifecycle_rules.immutable = true;
+        assert_eq!(immutable_db.replay_write_buffer().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn writes_wait_for_write_buffer_replay() {
+        let write_buffer = Arc::new(MockBuffer::default());
+        let db = Arc::new(
+            TestDb::builder()
+                .write_buffer(Arc::clone(&write_buffer) as _)
+                .build()
+                .await
+                .db,
+        );
+
+        let write = {
+            let db = Arc::clone(&db);
+            tokio::spawn(async move { db.store_entry(lp_to_entry("cpu bar=1 10")).await })
+        };
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(write_buffer.entries.lock().unwrap().is_empty());
+
+        assert_eq!(db.replay_write_buffer().await.unwrap(), 0);
+        write.await.unwrap().unwrap();
+        assert_eq!(write_buffer.entries.lock().unwrap().len(), 1);
+
+        // The write buffer is only replayed once
+        assert_eq!(db.replay_write_buffer().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn replay_write_buffer_skips_persisted_entries() {
+        let object_store = Arc::new(ObjectStore::new_in_memory(InMemory::new()));
+        let write_buffer = Arc::new(MockBuffer::default());
+        let db = TestDb::builder()
+            .object_store(Arc::clone(&object_store))
+            .write_buffer(Arc::clone(&write_buffer) as _)
+            .build()
+            .await
+            .db;
+        db.replay_write_buffer().await.unwrap();
+
+        db.store_entry(lp_to_entry("cpu bar=1 10")).await.unwrap();
+        db.store_entry(lp_to_entry("cpu bar=2 20")).await.unwrap();
+        let partition_key = "1970-01-01T00";
+        let chunk = db
+            .rollover_partition(partition_key, "cpu")
+            .await
+            .unwrap()
+            .unwrap();
+        db.store_entry(lp_to_entry("cpu bar=3 30")).await.unwrap();
+
+        db.load_chunk_to_read_buffer(partition_key, "cpu", chunk.id(), &Default::default())
+            .await
+            .unwrap();
+        db.write_chunk_to_object_store(partition_key, "cpu", chunk.id(), &Default::default())
+            .await
+            .unwrap();
+        drop(db);
+
+        // Only the entry written into the open chunk is replayed, the others
+        // are loaded from the preserved catalog
+        let restarted_db = TestDb::builder()
+            .object_store(object_store)
+            .write_buffer(Arc::new(MockBuffer {
+                entries: Arc::clone(&write_buffer.entries),
+            }) as _)
+            .build()
+            .await
+            .db;
+        assert_eq!(restarted_db.replay_write_buffer().await.unwrap(), 1);
+
+        let batches = run_query(Arc::new(restarted_db), "select * from cpu").await;
+        let expected = vec![
+            "+-----+-------------------------------+",
+            "| bar | time                          |",
+            "+-----+-------------------------------+",
+            "| 1   | 1970-01-01 00:00:00.000000010 |",
+            "| 2   | 1970-01-01 00:00:00.000000020 |",
+            "| 3   | 1970-01-01 00:00:00.000000030 |",
+            "+-----+-------------------------------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn replay_write_buffer_deletes_rows() {
+        let object_store = Arc::new(ObjectStore::new_in_memory(InMemory::new()));
+        let write_buffer = Arc::new(MockBuffer::default());
+        let db = TestDb::builder()
+            .object_store(Arc::clone(&object_store))
+            .write_buffer(Arc::clone(&write_buffer) as _)
+            .build()
+            .await
+            .db;
+        db.replay_write_buffer().await.unwrap();
+
+        db.store_entry(lp_to_entry("cpu,host=a bar=1 10\ncpu,host=b bar=2 10"))
+            .await
+            .unwrap();
+        let predicate = DeletePredicate {
+            table_name: "cpu".to_string(),
+            range: TimestampRange::new(0, 25),
+            tags: vec![("host".to_string(), "a".to_string())]
+                .into_iter()
+                .collect(),
+        };
+        db.delete(Arc::new(predicate)).await.unwrap();
+        db.store_entry(lp_to_entry("cpu,host=a bar=3 20"))
+            .await
+            .unwrap();
+        drop(db);
+
+        // The rows deleted from the mutable buffer stay deleted when the entries are replayed,
+        // but the rows written after the delete are kept
+        let restarted_db = TestDb::builder()
+            .object_store(object_store)
+            .write_buffer(Arc::new(MockBuffer {
+                entries: Arc::clone(&write_buffer.entries),
+            }) as _)
+            .build()
+            .await
+            .db;
+        assert_eq!(restarted_db.replay_write_buffer().await.unwrap(), 2);
+
+        let batches = run_query(Arc::new(restarted_db), "select * from cpu").await;
+        let expected = vec![
+            "+-----+------+-------------------------------+",
+            "| bar | host | time                          |",
+            "+-----+------+-------------------------------+",
+            "| 2   | b    | 1970-01-01 00:00:00.000000010 |",
+            "| 3   | a    | 1970-01-01 00:00:00.000000020 |",
+            "+-----+------+-------------------------------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn replay_write_buffer_skips_admission_checks() {
+        let write_buffer = Arc::new(MockBuffer::default());
+        let db = TestDb::builder()
+            .write_buffer(Arc::clone(&write_buffer) as _)
+            .build()
+            .await
+            .db;
+        db.replay_write_buffer().await.unwrap();
+        db.store_entry(lp_to_entry("cpu bar=1 10")).await.unwrap();
+        db.store_entry(lp_to_entry("cpu bar=2 20")).await.unwrap();
+
+        // Entries admitted before the restart are replayed even if they exceed the quotas
+        // and hard limit configured since
+        let restarted_db = TestDb::builder()
+            .write_buffer(Arc::new(MockBuffer {
+                entries: Arc::clone(&write_buffer.entries),
+            }) as _)
+            .build()
+            .await
+            .db;
+        {
+            let mut rules = restarted_db.rules.write();
+            rules.quotas.max_rows = Some(NonZeroUsize::new(1).unwrap());
+            rules.lifecycle_rules.buffer_size_hard = Some(NonZeroUsize::new(1).unwrap());
+        }
+        assert_eq!(restarted_db.replay_write_buffer().await.unwrap(), 2);
+
+        // New writes are checked again
+        let err = restarted_db
+            .store_entry(lp_to_entry("cpu bar=3 30"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, super::Error::HardLimitReached {}));
+    }
+
+    /// A write buffer whose entries cannot be read
+    #[derive(Debug)]
+    struct UnreadableBuffer;
+
+    #[async_trait]
+    impl WriteBuffer for UnreadableBuffer {
+        async fn store_entry(
+            &self,
+            _entry: &entry::Entry,
+        ) -> Result<entry::Sequence, crate::write_buffer::WriteBufferError> {
+            Ok(entry::Sequence { id: 0, number: 0 })
+        }
+
+        fn replay(
+            &self,
+            _from_sequence_number: u64,
+        ) -> futures::stream::BoxStream<
+            'static,
+            Result<SequencedEntry, crate::write_buffer::WriteBufferError>,
+        > {
+            stream::once(async { Err("corrupt write buffer".into()) }).boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn unreadable_write_buffer_stops_writes() {
+        let db = TestDb::builder()
+            .write_buffer(Arc::new(UnreadableBuffer) as _)
+            .build()
+            .await
+            .db;
+        db.replay_write_buffer().await.unwrap_err();
+
+        let err = db
+            .store_entry(lp_to_entry("cpu bar=1 10"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, super::Error::WriteBufferNotReplayed {}));
+    }
+
     #[tokio::test]
     async fn read_write() {
         // This test also exercises the path without a write buffer.
         let db = Arc::new(make_db().await.db);
-        write_lp(&db, "cpu bar=1 10");
+        write_lp(&db, "cpu bar=1 10").await;
 
         let batches = run_query(db, "select * from cpu").await;
 
@@ -1472,7 +3278,7 @@ mod tests {
         let test_db = make_db().await;
         let db = Arc::new(test_db.db);
 
-        write_lp(db.as_ref(), "cpu bar=1 10");
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
 
         // A chunk has been opened
         test_db
@@ -1491,7 +3297,7 @@ mod tests {
         catalog_chunk_size_bytes_metric_eq(&test_db.metric_registry, "mutable_buffer", 44).unwrap();
 
         // write into same chunk again.
-        write_lp(db.as_ref(), "cpu bar=2 10");
+        write_lp(db.as_ref(), "cpu bar=2 10").await;
 
         // verify chunk size updated
         catalog_chunk_size_bytes_metric_eq(&test_db.metric_registry, "mutable_buffer", 60).unwrap();
@@ -1602,7 +3408,7 @@ mod tests {
     #[tokio::test]
     async fn write_with_rollover() {
         let db = Arc::new(make_db().await.db);
-        write_lp(db.as_ref(), "cpu bar=1 10");
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
         assert_eq!(vec!["1970-01-01T00"], db.partition_keys().unwrap());
 
         let mb_chunk = db
@@ -1623,7 +3429,7 @@ mod tests {
         assert_batches_sorted_eq!(expected, &batches);
 
         // add new data
-        write_lp(db.as_ref(), "cpu bar=2 20");
+        write_lp(db.as_ref(), "cpu bar=2 20").await;
         let expected = vec![
             "+-----+-------------------------------+",
             "| bar | time                          |",
@@ -1660,7 +3466,7 @@ mod tests {
             "cpu,core=one user=10.0 11",
         ];
 
-        write_lp(db.as_ref(), &lines.join("\n"));
+        write_lp(db.as_ref(), &lines.join("\n")).await;
         assert_eq!(vec!["1970-01-01T00"], db.partition_keys().unwrap());
 
         let mb_chunk = db
@@ -1689,8 +3495,8 @@ mod tests {
         let test_db = make_db().await;
         let db = Arc::new(test_db.db);
 
-        write_lp(db.as_ref(), "cpu bar=1 10");
-        write_lp(db.as_ref(), "cpu bar=2 20");
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
+        write_lp(db.as_ref(), "cpu bar=2 20").await;
 
         let partition_key = "1970-01-01T00";
         let mb_chunk = db
@@ -1775,12 +3581,12 @@ mod tests {
         let test_db = make_db().await;
         let db = Arc::new(test_db.db);
 
-        write_lp(db.as_ref(), "cpu,tag1=cupcakes bar=1 10");
-        write_lp(db.as_ref(), "cpu,tag1=asfd,tag2=foo bar=2 20");
-        write_lp(db.as_ref(), "cpu,tag1=bingo,tag2=foo bar=2 10");
-        write_lp(db.as_ref(), "cpu,tag1=bongo,tag2=a bar=2 20");
-        write_lp(db.as_ref(), "cpu,tag1=bongo,tag2=a bar=2 10");
-        write_lp(db.as_ref(), "cpu,tag2=a bar=3 5");
+        write_lp(db.as_ref(), "cpu,tag1=cupcakes bar=1 10").await;
+        write_lp(db.as_ref(), "cpu,tag1=asfd,tag2=foo bar=2 20").await;
+        write_lp(db.as_ref(), "cpu,tag1=bingo,tag2=foo bar=2 10").await;
+        write_lp(db.as_ref(), "cpu,tag1=bongo,tag2=a bar=2 20").await;
+        write_lp(db.as_ref(), "cpu,tag1=bongo,tag2=a bar=2 10").await;
+        write_lp(db.as_ref(), "cpu,tag2=a bar=3 5").await;
 
         let partition_key = "1970-01-01T00";
         let mb_chunk = db
@@ -1890,8 +3696,8 @@ mod tests {
         let db = Arc::new(test_db.db);
 
         // Write some line protocols in Mutable buffer of the DB
-        write_lp(db.as_ref(), "cpu bar=1 10");
-        write_lp(db.as_ref(), "cpu bar=2 20");
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
+        write_lp(db.as_ref(), "cpu bar=2 20").await;
 
         //Now mark the MB chunk close
         let partition_key = "1970-01-01T00";
@@ -1989,8 +3795,8 @@ mod tests {
         let db = Arc::new(test_db.db);
 
         // Write some line protocols in Mutable buffer of the DB
-        write_lp(db.as_ref(), "cpu bar=1 10");
-        write_lp(db.as_ref(), "cpu bar=2 20");
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
+        write_lp(db.as_ref(), "cpu bar=2 20").await;
 
         // Now mark the MB chunk close
         let partition_key = "1970-01-01T00";
@@ -2101,7 +3907,7 @@ mod tests {
         let before_create = Utc::now();
 
         let partition_key = "1970-01-01T00";
-        write_lp(&db, "cpu bar=1 10");
+        write_lp(&db, "cpu bar=1 10").await;
         let after_write = Utc::now();
 
         let last_write_prev = {
@@ -2118,7 +3924,7 @@ mod tests {
             partition.last_write_at()
         };
 
-        write_lp(&db, "cpu bar=1 20");
+        write_lp(&db, "cpu bar=1 20").await;
         {
             let partition = db
                 .preserved_catalog
@@ -2136,7 +3942,7 @@ mod tests {
         let db = Arc::new(make_db().await.db);
 
         // Given data loaded into two chunks
-        write_lp(&db, "cpu bar=1 10");
+        write_lp(&db, "cpu bar=1 10").await;
         let after_data_load = Utc::now();
 
         // When the chunk is rolled over
@@ -2178,8 +3984,8 @@ mod tests {
         db.rules.write().lifecycle_rules.mutable_size_threshold =
             Some(NonZeroUsize::new(2).unwrap());
 
-        write_lp(&db, "cpu bar=1 10");
-        write_lp(&db, "cpu bar=1 20");
+        write_lp(&db, "cpu bar=1 10").await;
+        write_lp(&db, "cpu bar=1 20").await;
 
         let partitions = db.preserved_catalog.state().partition_keys();
         assert_eq!(partitions.len(), 1);
@@ -2209,13 +4015,51 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn rollover_chunk() {
+        let db = Arc::new(make_db().await.db);
+        write_lp(&db, "cpu bar=1 10").await;
+
+        db.rollover_chunk("1970-01-01T00", "cpu", 0).unwrap();
+        // Closed chunks are left as they are
+        db.rollover_chunk("1970-01-01T00", "cpu", 0).unwrap();
+        assert!(db.rollover_chunk("1970-01-01T00", "cpu", 1).is_err());
+
+        write_lp(&db, "cpu bar=1 20").await;
+
+        let chunks = db.partition_chunk_summaries("1970-01-01T00");
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].id, 0);
+        assert_eq!(chunks[0].storage, ChunkStorage::ClosedMutableBuffer);
+        assert_eq!(chunks[1].id, 1);
+        assert_eq!(chunks[1].storage, ChunkStorage::OpenMutableBuffer);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_closing_rows() {
+        let db = Arc::new(make_db().await.db);
+        db.rules.write().lifecycle_rules.mutable_row_threshold =
+            Some(NonZeroUsize::new(2).unwrap());
+
+        write_lp(&db, "cpu bar=1 10").await;
+        write_lp(&db, "cpu bar=1 20").await;
+        write_lp(&db, "cpu bar=1 30").await;
+
+        let chunks = db.partition_chunk_summaries("1970-01-01T00");
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].storage, ChunkStorage::ClosedMutableBuffer);
+        assert_eq!(chunks[0].row_count, 2);
+        assert_eq!(chunks[1].storage, ChunkStorage::OpenMutableBuffer);
+        assert_eq!(chunks[1].row_count, 1);
+    }
+
     #[tokio::test]
     async fn chunks_sorted_by_times() {
         let db = Arc::new(make_db().await.db);
-        write_lp(&db, "cpu val=1 1");
-        write_lp(&db, "mem val=2 400000000000001");
-        write_lp(&db, "cpu val=1 2");
-        write_lp(&db, "mem val=2 400000000000002");
+        write_lp(&db, "cpu val=1 1").await;
+        write_lp(&db, "mem val=2 400000000000001").await;
+        write_lp(&db, "cpu val=1 2").await;
+        write_lp(&db, "mem val=2 400000000000002").await;
 
         let sort_rules = SortOrder {
             order: Order::Desc,
@@ -2247,8 +4091,8 @@ mod tests {
         let db = Arc::new(make_db().await.db);
         let partition_key = "1970-01-01T00";
 
-        write_lp(&db, "cpu bar=1 10");
-        write_lp(&db, "cpu bar=1 20");
+        write_lp(&db, "cpu bar=1 10").await;
+        write_lp(&db, "cpu bar=1 20").await;
 
         assert_eq!(mutable_chunk_ids(&db, partition_key), vec![0]);
         assert_eq!(
@@ -2266,7 +4110,7 @@ mod tests {
 
         // add a new chunk in mutable buffer, and move chunk1 (but
         // not chunk 0) to read buffer
-        write_lp(&db, "cpu bar=1 30");
+        write_lp(&db, "cpu bar=1 30").await;
         let mb_chunk = db
             .rollover_partition("1970-01-01T00", "cpu")
             .await
@@ -2276,7 +4120,7 @@ mod tests {
             .await
             .unwrap();
 
-        write_lp(&db, "cpu bar=1 40");
+        write_lp(&db, "cpu bar=1 40").await;
 
         assert_eq!(mutable_chunk_ids(&db, partition_key), vec![0, 2]);
         assert_eq!(read_buffer_chunk_ids(&db, partition_key), vec![1]);
@@ -2315,11 +4159,11 @@ mod tests {
         // Test that chunk id listing is hooked up
         let db = Arc::new(make_db().await.db);
 
-        write_lp(&db, "cpu bar=1 1");
+        write_lp(&db, "cpu bar=1 1").await;
         db.rollover_partition("1970-01-01T00", "cpu").await.unwrap();
 
         // write into a separate partitiion
-        write_lp(&db, "cpu bar=1,baz2,frob=3 400000000000000");
+        write_lp(&db, "cpu bar=1,baz2,frob=3 400000000000000").await;
 
         print!("Partitions: {:?}", db.partition_keys().unwrap());
 
@@ -2360,14 +4204,89 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn partition_chunk_summaries_timestamp() {
-        let db = Arc::new(make_db().await.db);
-        let start = Utc::now();
-        write_lp(&db, "cpu bar=1 1");
-        let after_first_write = Utc::now();
-        write_lp(&db, "cpu bar=2 2");
-        db.rollover_partition("1970-01-01T00", "cpu").await.unwrap();
-        let after_close = Utc::now();
+    async fn chunk_allocated_bytes() {
+        let test_db = make_db().await;
+        let db = Arc::new(test_db.db);
+
+        write_lp(&db, "cpu,tag=a bar=1 1").await;
+        write_lp(&db, "cpu,tag=b bar=2 2").await;
+
+        let summaries = db.chunk_summaries().unwrap();
+        assert_eq!(summaries.len(), 1);
+        let summary = &summaries[0];
+        assert_eq!(summary.storage, ChunkStorage::OpenMutableBuffer);
+        assert!(summary.allocated_bytes >= summary.estimated_bytes);
+
+        let allocated = || {
+            db.preserved_catalog
+                .state()
+                .metrics()
+                .memory()
+                .mutable_buffer_allocated()
+                .get_total()
+        };
+        assert_eq!(allocated(), summary.allocated_bytes);
+
+        db.rollover_partition("1970-01-01T00", "cpu").await.unwrap();
+
+        let summaries = db.chunk_summaries().unwrap();
+        let summary = &summaries[0];
+        assert_eq!(summary.storage, ChunkStorage::ClosedMutableBuffer);
+        assert_eq!(summary.allocated_bytes, summary.estimated_bytes);
+        assert_eq!(allocated(), 0);
+    }
+
+    #[tokio::test]
+    async fn shared_tag_values() {
+        let test_db = make_db().await;
+        let db = Arc::new(test_db.db);
+        db.rules.write().lifecycle_rules.mutable_shared_tag_values = true;
+
+        write_lp(&db, "cpu,host=a bar=1 10").await;
+        write_lp(&db, "cpu,host=a,region=west bar=2 400000000000000").await;
+
+        let strings_eq = |v: f64| {
+            test_db
+                .metric_registry
+                .has_metric_family("string_interner_strings")
+                .with_labels(&[("db_name", "placeholder"), ("svr_id", "1")])
+                .gauge()
+                .eq(v)
+                .unwrap()
+        };
+        strings_eq(2.0);
+        assert!(db.memory_used() >= db.string_interner.size());
+
+        let batches = run_query(Arc::clone(&db), "select * from cpu order by time").await;
+        assert_batches_eq!(
+            &[
+                "+-----+------+--------+-------------------------------+",
+                "| bar | host | region | time                          |",
+                "+-----+------+--------+-------------------------------+",
+                "| 1   | a    |        | 1970-01-01 00:00:00.000000010 |",
+                "| 2   | a    | west   | 1970-01-05 15:06:40           |",
+                "+-----+------+--------+-------------------------------+",
+            ],
+            &batches
+        );
+
+        // "a" is still used by the open chunk of the other partition
+        db.rollover_partition("1970-01-05T15", "cpu").await.unwrap();
+        strings_eq(1.0);
+
+        db.rollover_partition("1970-01-01T00", "cpu").await.unwrap();
+        strings_eq(0.0);
+    }
+
+    #[tokio::test]
+    async fn partition_chunk_summaries_timestamp() {
+        let db = Arc::new(make_db().await.db);
+        let start = Utc::now();
+        write_lp(&db, "cpu bar=1 1").await;
+        let after_first_write = Utc::now();
+        write_lp(&db, "cpu bar=2 2").await;
+        db.rollover_partition("1970-01-01T00", "cpu").await.unwrap();
+        let after_close = Utc::now();
 
         let mut chunk_summaries = db.chunk_summaries().unwrap();
 
@@ -2409,17 +4328,47 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn read_buffer_chunk_summary() {
+        let db = Arc::new(make_db().await.db);
+        write_lp(&db, "cpu bar=1 1").await;
+        write_lp(&db, "cpu bar=2 2").await;
+
+        let summaries = db.partition_chunk_summaries("1970-01-01T00");
+        assert!(summaries[0].read_buffer.is_none());
+
+        db.rollover_partition("1970-01-01T00", "cpu").await.unwrap();
+        db.load_chunk_to_read_buffer("1970-01-01T00", "cpu", 0, &Default::default())
+            .await
+            .unwrap();
+
+        let summaries = db.partition_chunk_summaries("1970-01-01T00");
+        let read_buffer = summaries[0].read_buffer.as_ref().unwrap();
+        assert_eq!(read_buffer.row_count, 2);
+        assert_eq!(read_buffer.row_groups, 1);
+        assert_eq!(read_buffer.compressed_bytes, summaries[0].estimated_bytes);
+        assert!(read_buffer.raw_bytes > 0);
+        assert!(read_buffer.time_of_last_access.is_none());
+
+        let before_query = Utc::now();
+        run_query(Arc::clone(&db), "select * from cpu").await;
+
+        let summaries = db.partition_chunk_summaries("1970-01-01T00");
+        let read_buffer = summaries[0].read_buffer.as_ref().unwrap();
+        assert!(read_buffer.time_of_last_access.unwrap() >= before_query);
+    }
+
     #[tokio::test]
     async fn chunk_summaries() {
         // Test that chunk id listing is hooked up
         let db = Arc::new(make_db().await.db);
 
         // get three chunks: one open, one closed in mb and one close in rb
-        write_lp(&db, "cpu bar=1 1");
+        write_lp(&db, "cpu bar=1 1").await;
         db.rollover_partition("1970-01-01T00", "cpu").await.unwrap();
 
-        write_lp(&db, "cpu bar=1,baz=2 2");
-        write_lp(&db, "cpu bar=1,baz=2,frob=3 400000000000000");
+        write_lp(&db, "cpu bar=1,baz=2 2").await;
+        write_lp(&db, "cpu bar=1,baz=2,frob=3 400000000000000").await;
 
         print!("Partitions: {:?}", db.partition_keys().unwrap());
 
@@ -2434,7 +4383,7 @@ mod tests {
         print!("Partitions2: {:?}", db.partition_keys().unwrap());
 
         db.rollover_partition("1970-01-05T15", "cpu").await.unwrap();
-        write_lp(&db, "cpu bar=1,baz=3,blargh=3 400000000000000");
+        write_lp(&db, "cpu bar=1,baz=3,blargh=3 400000000000000").await;
 
         let chunk_summaries = db.chunk_summaries().expect("expected summary to return");
         let chunk_summaries = normalize_summaries(chunk_summaries);
@@ -2514,15 +4463,15 @@ mod tests {
         // Test that chunk id listing is hooked up
         let db = Arc::new(make_db().await.db);
 
-        write_lp(&db, "cpu bar=1 1");
+        write_lp(&db, "cpu bar=1 1").await;
         let chunk_id = db
             .rollover_partition("1970-01-01T00", "cpu")
             .await
             .unwrap()
             .unwrap()
             .id();
-        write_lp(&db, "cpu bar=2,baz=3.0 2");
-        write_lp(&db, "mem foo=1 1");
+        write_lp(&db, "cpu bar=2,baz=3.0 2").await;
+        write_lp(&db, "mem foo=1 1").await;
 
         // load a chunk to the read buffer
         db.load_chunk_to_read_buffer("1970-01-01T00", "cpu", chunk_id, &Default::default())
@@ -2535,8 +4484,8 @@ mod tests {
             .unwrap();
 
         // write into a separate partition
-        write_lp(&db, "cpu bar=1 400000000000000");
-        write_lp(&db, "mem frob=3 400000000000001");
+        write_lp(&db, "cpu bar=1 400000000000000").await;
+        write_lp(&db, "mem frob=3 400000000000001").await;
 
         print!("Partitions: {:?}", db.partition_keys().unwrap());
 
@@ -2697,8 +4646,8 @@ mod tests {
         let db = Arc::new(make_db().await.db);
 
         // create MB partition
-        write_lp(db.as_ref(), "cpu bar=1 10");
-        write_lp(db.as_ref(), "cpu bar=2 20");
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
+        write_lp(db.as_ref(), "cpu bar=2 20").await;
 
         // MB => RB
         let partition_key = "1970-01-01T00";
@@ -2746,15 +4695,234 @@ mod tests {
         db.rules.write().lifecycle_rules.buffer_size_hard = Some(NonZeroUsize::new(10).unwrap());
 
         // inserting first line does not trigger hard buffer limit
-        write_lp(db.as_ref(), "cpu bar=1 10");
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
 
         // but second line will
         assert!(matches!(
-            try_write_lp(db.as_ref(), "cpu bar=2 20"),
+            try_write_lp(db.as_ref(), "cpu bar=2 20").await,
             Err(super::Error::HardLimitReached {})
         ));
     }
 
+    #[tokio::test]
+    async fn write_schema_conflict_policy() {
+        let db = Arc::new(make_db().await.db);
+        write_lp(db.as_ref(), "cpu bar=1.5 10").await;
+
+        // Conflicting writes are rejected by default, including the other
+        // tables of the same entry
+        assert!(matches!(
+            try_write_lp(db.as_ref(), "mem free=1 20\ncpu bar=2i 20").await,
+            Err(super::Error::SchemaConflict { .. })
+        ));
+        assert_eq!(db.preserved_catalog.state().table_names(), vec!["cpu"]);
+
+        db.rules.write().schema_conflict_policy = SchemaConflictPolicy::Coerce;
+        write_lp(db.as_ref(), "cpu bar=2i 20").await;
+
+        db.rules.write().schema_conflict_policy = SchemaConflictPolicy::DropLines;
+        write_lp(
+            db.as_ref(),
+            "cpu,region=west bar=true 30\ncpu,region=east baz=3 30",
+        )
+        .await;
+
+        let expected = vec![
+            "+-----+-----+--------+-------------------------------+",
+            "| bar | baz | region | time                          |",
+            "+-----+-----+--------+-------------------------------+",
+            "|     | 3   | east   | 1970-01-01 00:00:00.000000030 |",
+            "| 1.5 |     |        | 1970-01-01 00:00:00.000000010 |",
+            "| 2   |     |        | 1970-01-01 00:00:00.000000020 |",
+            "+-----+-----+--------+-------------------------------+",
+        ];
+        let batches = run_query(db, "select * from cpu").await;
+        assert_batches_sorted_eq!(expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn write_type_coercion_rules() {
+        let db = Arc::new(make_db().await.db);
+        db.rules.write().type_coercion_rules = vec![TypeCoercionRule {
+            from: ColumnType::I64,
+            to: ColumnType::F64,
+        }];
+
+        // The integer column of the open chunk is widened to floats
+        write_lp(db.as_ref(), "cpu bar=1i 10").await;
+        write_lp(db.as_ref(), "cpu bar=1.5 20").await;
+        db.rollover_partition("1970-01-01T00", "cpu").await.unwrap();
+
+        // The next chunk stores integers, which are queried as floats
+        write_lp(db.as_ref(), "cpu bar=9007199254740993i 30").await;
+        let chunks = db.chunks(&Default::default());
+        assert_eq!(chunks.len(), 2);
+
+        let expected = vec![
+            "+------------------+-------------------------------+",
+            "| bar              | time                          |",
+            "+------------------+-------------------------------+",
+            "| 1                | 1970-01-01 00:00:00.000000010 |",
+            "| 1.5              | 1970-01-01 00:00:00.000000020 |",
+            "| 9007199254740992 | 1970-01-01 00:00:00.000000030 |",
+            "+------------------+-------------------------------+",
+        ];
+        let batches = run_query(Arc::clone(&db), "select * from cpu").await;
+        assert_batches_sorted_eq!(expected, &batches);
+
+        // Without the rule, values of the other type are rejected
+        db.rules.write().type_coercion_rules = vec![];
+        assert!(matches!(
+            try_write_lp(db.as_ref(), "cpu bar=2.5 40").await,
+            Err(super::Error::SchemaConflict { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn write_quotas() {
+        let db = Arc::new(make_db().await.db);
+        db.rules.write().quotas = Quotas {
+            max_tables: NonZeroUsize::new(2),
+            max_columns_per_table: NonZeroUsize::new(3),
+            max_rows: NonZeroUsize::new(5),
+        };
+
+        write_lp(db.as_ref(), "cpu,region=west user=1 10\nmem free=2 10").await;
+
+        let err = try_write_lp(db.as_ref(), "disk bytes=3 10")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, super::Error::TableQuotaExceeded { limit: 2 }));
+
+        let err = try_write_lp(db.as_ref(), "cpu,region=west user=1,system=2 20")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            super::Error::ColumnQuotaExceeded { limit: 3, .. }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Column quota exceeded: table cpu is limited to 3 columns"
+        );
+
+        // Rejected writes are not stored
+        assert_eq!(
+            db.preserved_catalog.state().table_names(),
+            vec!["cpu", "mem"]
+        );
+
+        // Writes to the existing schema are accepted until the row quota is reached
+        write_lp(db.as_ref(), "cpu,region=east user=3 20\nmem free=4 20").await;
+        let err = try_write_lp(db.as_ref(), "cpu,region=west user=5 30\nmem free=6 30")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            super::Error::RowQuotaExceeded {
+                limit: 5,
+                rows: 2,
+                stored: 4
+            }
+        ));
+        write_lp(db.as_ref(), "mem free=6 30").await;
+
+        // Dropping a chunk makes room for new rows
+        db.drop_chunk("1970-01-01T00", "mem", 0).unwrap();
+        write_lp(db.as_ref(), "cpu,region=west user=5 30\nmem free=7 40").await;
+    }
+
+    #[tokio::test]
+    async fn write_hard_limit_query_memory() {
+        let db = Arc::new(make_db().await.db);
+        db.rules.write().lifecycle_rules.buffer_size_hard =
+            Some(NonZeroUsize::new(1_000_000).unwrap());
+
+        // Memory held by queries counts against the limit
+        let reservation = db.query_memory().reserve(1_000_001);
+        assert!(db.memory_used() > 1_000_000);
+        assert!(matches!(
+            try_write_lp(db.as_ref(), "cpu bar=1 10").await,
+            Err(super::Error::HardLimitReached {})
+        ));
+
+        // Writes are accepted again once the query is done
+        std::mem::drop(reservation);
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
+    }
+
+    #[tokio::test]
+    async fn query_result_cache() {
+        let test_db = TestDb::builder()
+            .query_result_cache_size(1_000_000)
+            .build()
+            .await;
+        let db = Arc::new(test_db.db);
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
+
+        let executions = AtomicUsize::new(0);
+        let query = |sql: &'static str| {
+            let db = Arc::clone(&db);
+            let executions = &executions;
+            async move {
+                let stream = db
+                    .query_with_cache(sql, &[], || async {
+                        executions.fetch_add(1, Ordering::SeqCst);
+                        let executor = db.executor();
+                        let physical_plan = SqlQueryPlanner::default()
+                            .query(Arc::clone(&db), sql, &executor)
+                            .unwrap();
+                        executor.new_context().execute(physical_plan).await
+                    })
+                    .await
+                    .unwrap();
+                stream.try_collect::<Vec<_>>().await.unwrap()
+            }
+        };
+
+        let expected = vec![
+            "+-----+-------------------------------+",
+            "| bar | time                          |",
+            "+-----+-------------------------------+",
+            "| 1   | 1970-01-01 00:00:00.000000010 |",
+            "+-----+-------------------------------+",
+        ];
+        assert_batches_eq!(expected, &query("select * from cpu").await);
+        assert_batches_eq!(expected, &query("SELECT *\n  FROM cpu;").await);
+        assert_eq!(executions.load(Ordering::SeqCst), 1);
+
+        // writes invalidate the cached results
+        write_lp(db.as_ref(), "cpu bar=2 20").await;
+        let batches = query("SELECT * FROM cpu").await;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+        assert_eq!(executions.load(Ordering::SeqCst), 2);
+
+        // as do lifecycle transitions
+        db.rollover_partition("1970-01-01T00", "cpu").await.unwrap();
+        query("SELECT * FROM cpu").await;
+        assert_eq!(executions.load(Ordering::SeqCst), 3);
+
+        // queries with results that change over time are never cached
+        query("SELECT count(*) FROM cpu WHERE time < now()").await;
+        query("SELECT count(*) FROM cpu WHERE time < now()").await;
+        assert_eq!(executions.load(Ordering::SeqCst), 5);
+
+        for (result, count) in &[("hit", 1.0), ("miss", 3.0)] {
+            test_db
+                .metric_registry
+                .has_metric_family("query_cache_lookups_total")
+                .with_labels(&[
+                    ("db_name", "placeholder"),
+                    ("result", result),
+                    ("svr_id", "1"),
+                ])
+                .counter()
+                .eq(*count)
+                .unwrap();
+        }
+        assert!(db.memory_used() > db.preserved_catalog.state().metrics().memory().total());
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn lock_tracker_metrics() {
         let object_store = Arc::new(ObjectStore::new_in_memory(InMemory::new()));
@@ -2800,7 +4968,7 @@ mod tests {
             .eq(0.)
             .unwrap();
 
-        write_lp(db.as_ref(), "cpu bar=1 10");
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
 
         test_db
             .metric_registry
@@ -3067,7 +5235,7 @@ mod tests {
         }
 
         // ==================== check: DB still writable ====================
-        write_lp(db.as_ref(), "cpu bar=1 10");
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
     }
 
     #[tokio::test]
@@ -3089,7 +5257,7 @@ mod tests {
         // create the following chunks:
         //   0: ReadBuffer + Parquet
         //   1: only Parquet
-        //   2: dropped (not in current catalog but parquet file still present for time travel)
+        //   2: dropped (not in current catalog but parquet file still referenced by the preserved catalog)
         let mut paths_keep = vec![];
         for i in 0..3i8 {
             let (partition_key, table_name, chunk_id) = create_parquet_chunk(db.as_ref()).await;
@@ -3169,8 +5337,498 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn flush_stops_lifecycle() {
+        let test_db = TestDb::builder().build().await;
+        let db = Arc::new(test_db.db);
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
+
+        // Without persisting, chunks are not moved to the read buffer
+        db.flush(false).await.unwrap();
+        let chunks = db.chunk_summaries().unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].storage, ChunkStorage::OpenMutableBuffer);
+
+        let shutdown: CancellationToken = Default::default();
+        let join_handle = tokio::spawn({
+            let db = Arc::clone(&db);
+            let shutdown = shutdown.clone();
+            async move { db.background_worker(shutdown).await }
+        });
+        while db.worker_iterations_cleanup() == 0 {
+            tokio::task::yield_now().await;
+        }
+        shutdown.cancel();
+        join_handle.await.unwrap();
+
+        // The lifecycle policy didn't run again
+        assert_eq!(db.worker_iterations_lifecycle(), 0);
+
+        db.flush(true).await.unwrap();
+        let chunks = db.chunk_summaries().unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].storage, ChunkStorage::ReadBufferAndObjectStore);
+    }
+
+    #[tokio::test]
+    async fn partition_storage_summary() {
+        let test_db = TestDb::builder().build().await;
+        let db = Arc::new(test_db.db);
+
+        assert!(db
+            .partition_storage_summary("1970-01-01T00")
+            .await
+            .unwrap()
+            .is_none());
+
+        // one persisted chunk and one open chunk for cpu, one open chunk for mem
+        let (partition_key, _, _) = create_parquet_chunk(db.as_ref()).await;
+        write_lp(db.as_ref(), "cpu bar=2 20\ncpu bar=3 30").await;
+        write_lp(db.as_ref(), "mem foo=1 10").await;
+
+        let summary = db
+            .partition_storage_summary(&partition_key)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(summary.key, partition_key);
+        assert_eq!(summary.tables.len(), 2);
+
+        let cpu = &summary.tables[0];
+        assert_eq!(cpu.name, "cpu");
+        assert_eq!(cpu.mutable_buffer_chunks, 1);
+        assert_eq!(cpu.read_buffer_chunks, 1);
+        assert_eq!(cpu.object_store_chunks, 1);
+        assert_eq!(cpu.row_count, 3);
+        assert!(cpu.memory_bytes > 0);
+
+        assert!(cpu.object_store_bytes > 0);
+
+        let mem = &summary.tables[1];
+        assert_eq!(mem.name, "mem");
+        assert_eq!(mem.mutable_buffer_chunks, 1);
+        assert_eq!(mem.read_buffer_chunks, 0);
+        assert_eq!(mem.object_store_chunks, 0);
+        assert_eq!(mem.row_count, 1);
+        assert_eq!(mem.object_store_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn drop_partition() {
+        // Test that dropping a partition keeps its persisted chunks unless asked otherwise
+
+        // ==================== setup ====================
+        let object_store = Arc::new(ObjectStore::new_in_memory(InMemory::new()));
+        let server_id = ServerId::try_from(1).unwrap();
+        let db_name = "drop_partition_test";
+
+        let test_db = TestDb::builder()
+            .object_store(Arc::clone(&object_store))
+            .server_id(server_id)
+            .db_name(db_name)
+            .build()
+            .await;
+        let db = Arc::new(test_db.db);
+
+        // ==================== do: persist a chunk and write more data ====================
+        let (partition_key, table_name, chunk_id) = create_parquet_chunk(db.as_ref()).await;
+        write_lp(db.as_ref(), "cpu bar=2 20").await;
+        assert_eq!(read_parquet_file_chunk_ids(&db, &partition_key), vec![0]);
+        assert_eq!(mutable_chunk_ids(&db, &partition_key), vec![1]);
+
+        // ==================== do: drop partition from memory only ====================
+        db.drop_partition(&partition_key, false).await.unwrap();
+        assert!(db.partition_keys().unwrap().is_empty());
+
+        let err = db.drop_partition(&partition_key, false).await.unwrap_err();
+        assert_contains!(err.to_string(), "unknown partition: 1970-01-01T00");
+
+        // ==================== check: persisted chunk is reloaded ====================
+        drop(db);
+        let test_db = TestDb::builder()
+            .object_store(Arc::clone(&object_store))
+            .server_id(server_id)
+            .db_name(db_name)
+            .build()
+            .await;
+        let db = Arc::new(test_db.db);
+
+        assert_eq!(db.partition_keys().unwrap(), vec![partition_key.clone()]);
+        assert!(db
+            .table_summary(&partition_key, &table_name, chunk_id)
+            .is_some());
+
+        // ==================== do: drop partition including persisted files ====================
+        db.drop_partition(&partition_key, true).await.unwrap();
+        assert!(db.partition_keys().unwrap().is_empty());
+
+        // ==================== check: nothing is reloaded ====================
+        drop(db);
+        let test_db = TestDb::builder()
+            .object_store(Arc::clone(&object_store))
+            .server_id(server_id)
+            .db_name(db_name)
+            .build()
+            .await;
+        let db = Arc::new(test_db.db);
+
+        assert!(db.partition_keys().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn compact_partition() {
+        // Test that persisted chunks are merged into a single deduplicated chunk
+
+        // ==================== setup ====================
+        let object_store = Arc::new(ObjectStore::new_in_memory(InMemory::new()));
+        let server_id = ServerId::try_from(1).unwrap();
+        let db_name = "compact_partition_test";
+
+        let test_db = TestDb::builder()
+            .object_store(Arc::clone(&object_store))
+            .server_id(server_id)
+            .db_name(db_name)
+            .build()
+            .await;
+        let db = Arc::new(test_db.db);
+
+        // ==================== do: persist two chunks overwriting a row ====================
+        let (partition_key, table_name, _) = create_parquet_chunk(db.as_ref()).await;
+
+        // nothing to compact with a single persisted chunk
+        assert!(db
+            .compact_partition(&partition_key, &table_name, &Default::default())
+            .await
+            .unwrap()
+            .is_none());
+
+        write_lp(db.as_ref(), "cpu bar=2 10\ncpu bar=3 20").await;
+        let chunk_id = db
+            .rollover_partition(&partition_key, &table_name)
+            .await
+            .unwrap()
+            .unwrap()
+            .id();
+        db.load_chunk_to_read_buffer(&partition_key, &table_name, chunk_id, &Default::default())
+            .await
+            .unwrap();
+        db.write_chunk_to_object_store(&partition_key, &table_name, chunk_id, &Default::default())
+            .await
+            .unwrap();
+        assert_eq!(read_parquet_file_chunk_ids(&db, &partition_key), vec![0, 1]);
+
+        let files_before = get_object_store_files(&object_store).await;
+
+        // ==================== do: compact ====================
+        let chunk = db
+            .compact_partition(&partition_key, &table_name, &Default::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk.id(), 2);
+        assert_eq!(read_parquet_file_chunk_ids(&db, &partition_key), vec![2]);
+
+        let expected = vec![
+            "+-----+-------------------------------+",
+            "| bar | time                          |",
+            "+-----+-------------------------------+",
+            "| 2   | 1970-01-01 00:00:00.000000010 |",
+            "| 3   | 1970-01-01 00:00:00.000000020 |",
+            "+-----+-------------------------------+",
+        ];
+        let batches = run_query(Arc::clone(&db), "select * from cpu").await;
+        assert_batches_sorted_eq!(&expected, &batches);
+
+        // ==================== check: cleanup deletes the superseded files ====================
+        cleanup_unreferenced_parquet_files(&db.preserved_catalog, 1_000)
+            .await
+            .unwrap();
+        let files_after = get_object_store_files(&object_store).await;
+        let compacted_path = chunk.object_store_path().unwrap().display();
+        assert!(files_after.contains(&compacted_path));
+        for path in files_before.iter().filter(|p| p.ends_with(".parquet")) {
+            assert!(!files_after.contains(path));
+        }
+
+        // ==================== check: only the compacted chunk is reloaded ====================
+        drop(db);
+        let test_db = TestDb::builder()
+            .object_store(Arc::clone(&object_store))
+            .server_id(server_id)
+            .db_name(db_name)
+            .build()
+            .await;
+        let db = Arc::new(test_db.db);
+
+        assert_eq!(read_parquet_file_chunk_ids(&db, &partition_key), vec![2]);
+        let batches = run_query(db, "select * from cpu").await;
+        assert_batches_sorted_eq!(&expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn compact_partition_keeps_newer_writes() {
+        // Test that compacted data doesn't override the newer data of chunks
+        // created before the compacted chunk
+
+        // ==================== setup ====================
+        let object_store = Arc::new(ObjectStore::new_in_memory(InMemory::new()));
+        let test_db = TestDb::builder()
+            .object_store(Arc::clone(&object_store))
+            .server_id(ServerId::try_from(1).unwrap())
+            .db_name("compact_partition_keeps_newer_writes_test")
+            .build()
+            .await;
+        let db = Arc::new(test_db.db);
+
+        // ==================== do: two persisted chunks and a newer open one ====================
+        let (partition_key, table_name, _) = create_parquet_chunk(db.as_ref()).await;
+        write_lp(db.as_ref(), "cpu bar=2 10").await;
+        let chunk_id = db
+            .rollover_partition(&partition_key, &table_name)
+            .await
+            .unwrap()
+            .unwrap()
+            .id();
+        db.load_chunk_to_read_buffer(&partition_key, &table_name, chunk_id, &Default::default())
+            .await
+            .unwrap();
+        db.write_chunk_to_object_store(&partition_key, &table_name, chunk_id, &Default::default())
+            .await
+            .unwrap();
+
+        write_lp(db.as_ref(), "cpu bar=3 10").await;
+
+        // ==================== do: compact ====================
+        let chunk = db
+            .compact_partition(&partition_key, &table_name, &Default::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk.id(), 3);
+
+        // ==================== check: the open chunk still wins ====================
+        let expected = vec![
+            "+-----+-------------------------------+",
+            "| bar | time                          |",
+            "+-----+-------------------------------+",
+            "| 3   | 1970-01-01 00:00:00.000000010 |",
+            "+-----+-------------------------------+",
+        ];
+        let batches = run_query(Arc::clone(&db), "select * from cpu").await;
+        assert_batches_sorted_eq!(&expected, &batches);
+
+        // ==================== do: persist a chunk newer than an unpersisted one ====================
+        let open_id = db
+            .rollover_partition(&partition_key, &table_name)
+            .await
+            .unwrap()
+            .unwrap()
+            .id();
+        assert_eq!(open_id, 2);
+        db.load_chunk_to_read_buffer(&partition_key, &table_name, open_id, &Default::default())
+            .await
+            .unwrap();
+
+        write_lp(db.as_ref(), "cpu bar=4 10").await;
+        let chunk_id = db
+            .rollover_partition(&partition_key, &table_name)
+            .await
+            .unwrap()
+            .unwrap()
+            .id();
+        db.load_chunk_to_read_buffer(&partition_key, &table_name, chunk_id, &Default::default())
+            .await
+            .unwrap();
+        db.write_chunk_to_object_store(&partition_key, &table_name, chunk_id, &Default::default())
+            .await
+            .unwrap();
+
+        // ==================== check: it isn't compacted with the older persisted chunk ====================
+        assert!(db
+            .compact_partition(&partition_key, &table_name, &Default::default())
+            .await
+            .unwrap()
+            .is_none());
+
+        let expected = vec![
+            "+-----+-------------------------------+",
+            "| bar | time                          |",
+            "+-----+-------------------------------+",
+            "| 4   | 1970-01-01 00:00:00.000000010 |",
+            "+-----+-------------------------------+",
+        ];
+        let batches = run_query(Arc::clone(&db), "select * from cpu").await;
+        assert_batches_sorted_eq!(&expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn delete_rows() {
+        // Test that deleted rows are masked in all chunks, but later writes are not
+
+        // ==================== setup ====================
+        let object_store = Arc::new(ObjectStore::new_in_memory(InMemory::new()));
+        let server_id = ServerId::try_from(1).unwrap();
+        let db_name = "delete_rows_test";
+
+        let test_db = TestDb::builder()
+            .object_store(Arc::clone(&object_store))
+            .server_id(server_id)
+            .db_name(db_name)
+            .build()
+            .await;
+        let db = Arc::new(test_db.db);
+
+        // ==================== do: a persisted and an open chunk ====================
+        let partition_key = "1970-01-01T00";
+        let table_name = "cpu";
+        write_lp(
+            db.as_ref(),
+            "cpu,host=a bar=1 10\ncpu,host=b bar=2 10\ncpu,host=a bar=3 20",
+        )
+        .await;
+        let persisted_id = db
+            .rollover_partition(partition_key, table_name)
+            .await
+            .unwrap()
+            .unwrap()
+            .id();
+        db.load_chunk_to_read_buffer(partition_key, table_name, persisted_id, &Default::default())
+            .await
+            .unwrap();
+        db.write_chunk_to_object_store(
+            partition_key,
+            table_name,
+            persisted_id,
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+
+        write_lp(db.as_ref(), "cpu,host=a bar=4 10\ncpu,host=a bar=5 30").await;
+
+        // ==================== do: delete ====================
+        let predicate = DeletePredicate {
+            table_name: table_name.to_string(),
+            range: TimestampRange::new(0, 25),
+            tags: vec![("host".to_string(), "a".to_string())]
+                .into_iter()
+                .collect(),
+        };
+        db.delete(Arc::new(predicate)).await.unwrap();
+
+        let expected = vec![
+            "+-----+------+-------------------------------+",
+            "| bar | host | time                          |",
+            "+-----+------+-------------------------------+",
+            "| 2   | b    | 1970-01-01 00:00:00.000000010 |",
+            "| 5   | a    | 1970-01-01 00:00:00.000000030 |",
+            "+-----+------+-------------------------------+",
+        ];
+        let batches = run_query(Arc::clone(&db), "select * from cpu").await;
+        assert_batches_sorted_eq!(&expected, &batches);
+
+        // the open chunk is still open
+        let chunk_summaries = db.chunk_summaries().unwrap();
+        assert!(chunk_summaries
+            .iter()
+            .any(|summary| summary.storage == ChunkStorage::OpenMutableBuffer));
+
+        // ==================== check: later writes are not masked ====================
+        write_lp(db.as_ref(), "cpu,host=a bar=6 15").await;
+        let expected = vec![
+            "+-----+------+-------------------------------+",
+            "| bar | host | time                          |",
+            "+-----+------+-------------------------------+",
+            "| 2   | b    | 1970-01-01 00:00:00.000000010 |",
+            "| 5   | a    | 1970-01-01 00:00:00.000000030 |",
+            "| 6   | a    | 1970-01-01 00:00:00.000000015 |",
+            "+-----+------+-------------------------------+",
+        ];
+        let batches = run_query(Arc::clone(&db), "select * from cpu").await;
+        assert_batches_sorted_eq!(&expected, &batches);
+
+        // ==================== do: persist the open chunk ====================
+        let closed_id = db
+            .rollover_partition(partition_key, table_name)
+            .await
+            .unwrap()
+            .unwrap()
+            .id();
+        assert_eq!(closed_id, persisted_id + 1);
+        db.load_chunk_to_read_buffer(partition_key, table_name, closed_id, &Default::default())
+            .await
+            .unwrap();
+        db.write_chunk_to_object_store(partition_key, table_name, closed_id, &Default::default())
+            .await
+            .unwrap();
+
+        // ==================== check: persisted chunks stay masked after restart ====================
+        drop(db);
+        let test_db = TestDb::builder()
+            .object_store(Arc::clone(&object_store))
+            .server_id(server_id)
+            .db_name(db_name)
+            .build()
+            .await;
+        let db = Arc::new(test_db.db);
+
+        assert_eq!(
+            read_parquet_file_chunk_ids(&db, partition_key),
+            vec![persisted_id, closed_id]
+        );
+        let batches = run_query(db, "select * from cpu").await;
+        assert_batches_sorted_eq!(&expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn chunk_events() {
+        let db = Arc::new(make_db().await.db);
+        let mut events = db.subscribe_chunk_events();
+
+        write_lp(db.as_ref(), "cpu bar=1 10").await;
+        let partition_key = "1970-01-01T00";
+        let chunk_id = db
+            .rollover_partition(partition_key, "cpu")
+            .await
+            .unwrap()
+            .unwrap()
+            .id();
+        db.load_chunk_to_read_buffer(partition_key, "cpu", chunk_id, &Default::default())
+            .await
+            .unwrap();
+        db.write_chunk_to_object_store(partition_key, "cpu", chunk_id, &Default::default())
+            .await
+            .unwrap();
+        db.unload_read_buffer(partition_key, "cpu", chunk_id)
+            .await
+            .unwrap();
+        db.drop_partition(partition_key, false).await.unwrap();
+
+        let mut states = vec![];
+        while let Ok(event) = events.try_recv() {
+            assert_eq!(event.partition_key.as_ref(), partition_key);
+            assert_eq!(event.table_name.as_ref(), "cpu");
+            assert_eq!(event.id, chunk_id);
+            states.push(event.state);
+        }
+
+        assert_eq!(
+            states,
+            vec![
+                ChunkLifecycleState::Open,
+                ChunkLifecycleState::Closed,
+                ChunkLifecycleState::Moving,
+                ChunkLifecycleState::Moved,
+                ChunkLifecycleState::Persisting,
+                ChunkLifecycleState::Persisted,
+                ChunkLifecycleState::Unloaded,
+                ChunkLifecycleState::Dropped,
+            ]
+        );
+    }
+
     async fn create_parquet_chunk(db: &Db) -> (String, String, u32) {
-        write_lp(db, "cpu bar=1 10");
+        write_lp(db, "cpu bar=1 10").await;
         let partition_key = "1970-01-01T00";
         let table_name = "cpu";
 
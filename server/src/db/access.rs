@@ -7,11 +7,11 @@ use std::{any::Any, sync::Arc};
 use super::{
     catalog::{Catalog, TableNameFilter},
     chunk::DbChunk,
-    Error, Result,
+    field_type_coercions, Error, Result,
 };
 
 use async_trait::async_trait;
-use data_types::{chunk_metadata::ChunkSummary, error::ErrorLogger};
+use data_types::{chunk_metadata::ChunkSummary, database_rules::DatabaseRules, error::ErrorLogger};
 use datafusion::{
     catalog::{catalog::CatalogProvider, schema::SchemaProvider},
     datasource::TableProvider,
@@ -19,6 +19,7 @@ use datafusion::{
 use internal_types::selection::Selection;
 use metrics::{Counter, KeyValue, MetricRegistry};
 use observability_deps::tracing::debug;
+use parking_lot::RwLock;
 use query::{
     predicate::{Predicate, PredicateBuilder},
     provider::{self, ChunkPruner, ProviderBuilder},
@@ -85,6 +86,7 @@ pub(crate) struct QueryCatalogAccess {
 impl QueryCatalogAccess {
     pub fn new(
         db_name: impl Into<String>,
+        rules: Arc<RwLock<DatabaseRules>>,
         catalog: Arc<Catalog>,
         jobs: Arc<JobRegistry>,
         metrics_registry: Arc<MetricRegistry>,
@@ -99,6 +101,7 @@ impl QueryCatalogAccess {
             jobs,
         ));
         let user_tables = Arc::new(DbSchemaProvider::new(
+            rules,
             Arc::clone(&catalog),
             Arc::clone(&chunk_access),
         ));
@@ -224,6 +227,10 @@ impl CatalogProvider for QueryCatalogAccess {
 /// Implement the DataFusion schema provider API
 #[derive(Debug)]
 struct DbSchemaProvider {
+    /// The rules of the database, for the type coercion rules applied to
+    /// the chunks of a table
+    rules: Arc<RwLock<DatabaseRules>>,
+
     /// The catalog to have access to
     catalog: Arc<Catalog>,
 
@@ -232,8 +239,13 @@ struct DbSchemaProvider {
 }
 
 impl DbSchemaProvider {
-    fn new(catalog: Arc<Catalog>, chunk_access: Arc<ChunkAccess>) -> Self {
+    fn new(
+        rules: Arc<RwLock<DatabaseRules>>,
+        catalog: Arc<Catalog>,
+        chunk_access: Arc<ChunkAccess>,
+    ) -> Self {
         Self {
+            rules,
             catalog,
             chunk_access,
         }
@@ -251,7 +263,8 @@ impl SchemaProvider for DbSchemaProvider {
 
     /// Create a table provider for the named table
     fn table(&self, table_name: &str) -> Option<Arc<dyn TableProvider>> {
-        let mut builder = ProviderBuilder::new(table_name);
+        let coercions = field_type_coercions(&self.rules.read().type_coercion_rules);
+        let mut builder = ProviderBuilder::new(table_name).with_coercions(coercions);
         builder.add_pruner(Arc::clone(&self.chunk_access) as Arc<dyn ChunkPruner<DbChunk>>);
 
         let predicate = PredicateBuilder::new().table(table_name).build();
@@ -2,19 +2,26 @@
 use std::collections::BTreeSet;
 use std::{
     collections::{btree_map::Entry, BTreeMap},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
 use snafu::{OptionExt, Snafu};
 
 use crate::db::catalog::metrics::CatalogMetrics;
 use chunk::Chunk;
+use data_types::delete_predicate::DeletePredicate;
 use data_types::partition_metadata::PartitionSummary;
-use data_types::{chunk_metadata::ChunkSummary, partition_metadata::UnaggregatedPartitionSummary};
 use data_types::{
     chunk_metadata::DetailedChunkSummary,
     database_rules::{Order, Sort, SortOrder},
 };
+use data_types::{
+    chunk_metadata::{ChunkLifecycleState, ChunkSummary},
+    partition_metadata::UnaggregatedPartitionSummary,
+};
 use partition::Partition;
 use tracker::RwLock;
 
@@ -168,6 +175,15 @@ pub struct Catalog {
 
     metrics: CatalogMetrics,
 
+    /// Highest sequence number of the oldest unpersisted write buffer entry
+    /// recorded by the parquet files in this catalog
+    min_unpersisted_sequence_number: AtomicU64,
+
+    /// Delete predicates applying to the write buffer entries before the
+    /// sequence number recorded with them, which were not persisted when
+    /// the predicates were added
+    unpersisted_delete_predicates: parking_lot::Mutex<Vec<(Arc<DeletePredicate>, u64)>>,
+
     pub(crate) metrics_registry: Arc<::metrics::MetricRegistry>,
     pub(crate) metric_labels: Vec<::metrics::KeyValue>,
 }
@@ -189,11 +205,53 @@ impl Catalog {
         Self {
             partitions: Default::default(),
             metrics,
+            min_unpersisted_sequence_number: AtomicU64::new(0),
+            unpersisted_delete_predicates: Default::default(),
             metrics_registry,
             metric_labels,
         }
     }
 
+    /// Returns the sequence number of the oldest write buffer entry whose data
+    /// may not be persisted, from which the write buffer is replayed
+    pub fn min_unpersisted_sequence_number(&self) -> u64 {
+        self.min_unpersisted_sequence_number.load(Ordering::SeqCst)
+    }
+
+    /// Records that the write buffer entries before `sequence_number` are
+    /// persisted
+    pub fn record_min_unpersisted_sequence_number(&self, sequence_number: u64) {
+        self.min_unpersisted_sequence_number
+            .fetch_max(sequence_number, Ordering::SeqCst);
+    }
+
+    /// Records that `predicate` applies to the rows of the write buffer
+    /// entries before `sequence_number` when these are replayed
+    pub fn record_unpersisted_delete_predicate(
+        &self,
+        predicate: Arc<DeletePredicate>,
+        sequence_number: u64,
+    ) {
+        self.unpersisted_delete_predicates
+            .lock()
+            .push((predicate, sequence_number));
+    }
+
+    /// Returns the delete predicates applying to some of the write buffer
+    /// entries replayed from `from`, with the sequence number of the first
+    /// entry they do not apply to, ordered by it
+    pub fn unpersisted_delete_predicates(&self, from: u64) -> Vec<(Arc<DeletePredicate>, u64)> {
+        let mut predicates: Vec<_> = self
+            .unpersisted_delete_predicates
+            .lock()
+            .iter()
+            .filter(|(_, sequence_number)| *sequence_number > from)
+            .cloned()
+            .collect();
+        predicates.sort_by_key(|(_, sequence_number)| *sequence_number);
+        predicates
+    }
+
     /// List all partitions in this database
     pub fn partitions(&self) -> impl Iterator<Item = Arc<RwLock<Partition>>> {
         let partitions = self.partitions.read();
@@ -245,6 +303,21 @@ impl Catalog {
             .context(UnknownPartition { partition_key })
     }
 
+    /// Removes the specified partition from the catalog, returning it
+    /// or an error if there is no such partition
+    pub fn drop_partition(&self, partition_key: &str) -> Result<Arc<RwLock<Partition>>> {
+        let mut partitions = self.partitions.write();
+        let partition = partitions
+            .remove(partition_key)
+            .context(UnknownPartition { partition_key })?;
+
+        for chunk in partition.read().chunks() {
+            chunk.read().send_event(ChunkLifecycleState::Dropped);
+        }
+
+        Ok(partition)
+    }
+
     /// Returns a list of summaries for each partition.
     pub fn partition_summaries(&self) -> Vec<PartitionSummary> {
         self.partitions
@@ -376,7 +449,9 @@ mod tests {
             mutable_buffer::chunk::ChunkMetrics::new_unregistered(),
         );
 
-        mb_chunk.write_table_batch(1, 5, batch).unwrap();
+        mb_chunk
+            .write_table_batch(1, 5, batch, Default::default(), &[])
+            .unwrap();
 
         partition.create_open_chunk(mb_chunk).unwrap();
     }
@@ -416,6 +491,27 @@ mod tests {
         assert_eq!(partition_keys, vec!["p1", "p2", "p3"]);
     }
 
+    #[test]
+    fn partition_drop() {
+        let catalog = Catalog::test();
+
+        let p1 = catalog.get_or_create_partition("p1");
+        create_open_chunk(&p1, "table1");
+        let p2 = catalog.get_or_create_partition("p2");
+        create_open_chunk(&p2, "table1");
+
+        assert_eq!(chunk_strings(&catalog).len(), 2);
+
+        let dropped = catalog.drop_partition("p1").unwrap();
+        assert_eq!(dropped.read().key(), "p1");
+        assert!(catalog.partition("p1").is_none());
+        assert_eq!(catalog.partition_keys(), vec!["p2"]);
+        assert_eq!(chunk_strings(&catalog), vec!["Chunk p2:table1:0"]);
+
+        let err = catalog.drop_partition("p1").unwrap_err();
+        assert_eq!(err.to_string(), "unknown partition: p1");
+    }
+
     #[test]
     fn chunk_create() {
         let catalog = Catalog::test();
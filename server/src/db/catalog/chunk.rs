@@ -2,7 +2,11 @@ use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
 use data_types::{
-    chunk_metadata::{ChunkColumnSummary, ChunkStorage, ChunkSummary, DetailedChunkSummary},
+    chunk_metadata::{
+        ChunkColumnSummary, ChunkEvent, ChunkLifecycleState, ChunkStorage, ChunkSummary,
+        DetailedChunkSummary,
+    },
+    delete_predicate::DeletePredicate,
     partition_metadata::TableSummary,
 };
 use internal_types::schema::Schema;
@@ -13,6 +17,7 @@ use read_buffer::Chunk as ReadBufferChunk;
 use super::{ChunkIsEmpty, Error, InternalChunkState, Result};
 use metrics::{Counter, Histogram, KeyValue};
 use snafu::ensure;
+use tokio::sync::broadcast;
 use tracker::{TaskRegistration, TaskTracker};
 
 /// Any lifecycle action currently in progress for this chunk
@@ -47,6 +52,10 @@ pub struct ChunkMetadata {
 
     /// The schema for the table in this Chunk
     pub schema: Arc<Schema>,
+
+    /// The columns the rows of this Chunk are sorted by, in order, if
+    /// they are known to be sorted
+    pub sort_key: Option<Vec<String>>,
 }
 
 /// Different memory representations of a frozen chunk.
@@ -180,9 +189,17 @@ pub struct Chunk {
     /// itself
     time_of_last_write: Option<DateTime<Utc>>,
 
+    /// Lowest sequence number of the entries written into this chunk, used
+    /// to find the write buffer entries whose data is not persisted yet
+    min_sequence_number: Option<u64>,
+
     /// Time at which this chunk was maked as closed. Note this is
     /// not the same as the timestamps on the data itself
     time_closed: Option<DateTime<Utc>>,
+
+    /// Predicates of deletes that apply to this chunk. Rows matching
+    /// any of them are masked at query time
+    delete_predicates: Vec<Arc<DeletePredicate>>,
 }
 
 macro_rules! unexpected_state {
@@ -203,6 +220,7 @@ macro_rules! unexpected_state {
 pub struct ChunkMetrics {
     pub(super) state: Counter,
     pub(super) immutable_chunk_size: Histogram,
+    pub(super) events: broadcast::Sender<ChunkEvent>,
 }
 
 impl ChunkMetrics {
@@ -214,6 +232,7 @@ impl ChunkMetrics {
         Self {
             state: Counter::new_unregistered(),
             immutable_chunk_size: Histogram::new_unregistered(),
+            events: broadcast::channel(1).0,
         }
     }
 }
@@ -256,20 +275,27 @@ impl Chunk {
             metrics,
             time_of_first_write: None,
             time_of_last_write: None,
+            min_sequence_number: None,
             time_closed: None,
+            delete_predicates: vec![],
         };
         chunk.record_write();
+        chunk.send_event(ChunkLifecycleState::Open);
         Ok(chunk)
     }
 
     /// Creates a new chunk that is only registered via an object store reference (= only exists in parquet).
     ///
+    /// The write timestamps are those of the chunk(s) the parquet file was written from, if known.
+    ///
     /// Apart from [`new_open`](Self::new_open) this is the only way to create new chunks.
     pub(crate) fn new_object_store_only(
         chunk_id: u32,
         partition_key: impl AsRef<str>,
         chunk: Arc<parquet_file::chunk::Chunk>,
         metrics: ChunkMetrics,
+        time_of_first_write: Option<DateTime<Utc>>,
+        time_of_last_write: Option<DateTime<Utc>>,
     ) -> Self {
         let table_name = Arc::from(chunk.table_name());
 
@@ -277,6 +303,7 @@ impl Chunk {
         let meta = Arc::new(ChunkMetadata {
             table_summary: Arc::clone(chunk.table_summary()),
             schema: chunk.full_schema(),
+            sort_key: None,
         });
 
         let stage = ChunkStage::Persisted {
@@ -292,9 +319,11 @@ impl Chunk {
             stage,
             lifecycle_action: None,
             metrics,
-            time_of_first_write: None,
-            time_of_last_write: None,
+            time_of_first_write,
+            time_of_last_write,
+            min_sequence_number: None,
             time_closed: None,
+            delete_predicates: vec![],
         }
     }
 
@@ -337,10 +366,26 @@ impl Chunk {
         self.time_of_last_write
     }
 
+    pub fn min_sequence_number(&self) -> Option<u64> {
+        self.min_sequence_number
+    }
+
     pub fn time_closed(&self) -> Option<DateTime<Utc>> {
         self.time_closed
     }
 
+    pub fn delete_predicates(&self) -> &[Arc<DeletePredicate>] {
+        &self.delete_predicates
+    }
+
+    /// Mask the rows matching `predicate` in this chunk. Adding the
+    /// same predicate twice has no effect
+    pub fn add_delete_predicate(&mut self, predicate: Arc<DeletePredicate>) {
+        if !self.delete_predicates.contains(&predicate) {
+            self.delete_predicates.push(predicate);
+        }
+    }
+
     /// Update the write timestamps for this chunk
     pub fn record_write(&mut self) {
         let now = Utc::now();
@@ -350,8 +395,28 @@ impl Chunk {
         self.time_of_last_write = Some(now);
     }
 
+    /// Record that an entry with `sequence_number` was written into this chunk
+    pub fn record_sequence_number(&mut self, sequence_number: u64) {
+        self.min_sequence_number = Some(match self.min_sequence_number {
+            Some(min) => min.min(sequence_number),
+            None => sequence_number,
+        });
+    }
+
     /// Return ChunkSummary metadata for this chunk
     pub fn summary(&self) -> ChunkSummary {
+        let read_buffer = match &self.stage {
+            ChunkStage::Frozen {
+                representation: ChunkStageFrozenRepr::ReadBuffer(repr),
+                ..
+            } => Some(repr.storage_summary()),
+            ChunkStage::Persisted {
+                read_buffer: Some(read_buffer),
+                ..
+            } => Some(read_buffer.storage_summary()),
+            _ => None,
+        };
+
         let (row_count, storage) = match &self.stage {
             ChunkStage::Open { mb_chunk, .. } => (mb_chunk.rows(), ChunkStorage::OpenMutableBuffer),
             ChunkStage::Frozen { representation, .. } => match &representation {
@@ -383,10 +448,12 @@ impl Chunk {
             id: self.id,
             storage,
             estimated_bytes: self.size(),
+            allocated_bytes: self.allocated_bytes(),
             row_count,
             time_of_first_write: self.time_of_first_write,
             time_of_last_write: self.time_of_last_write,
             time_closed: self.time_closed,
+            read_buffer,
         }
     }
 
@@ -398,6 +465,9 @@ impl Chunk {
             ChunkColumnSummary {
                 name: v.0.into(),
                 estimated_bytes: v.1,
+                encoding: None,
+                raw_bytes: None,
+                cardinality: None,
             }
         }
 
@@ -457,6 +527,16 @@ impl Chunk {
         }
     }
 
+    /// Returns the amount of memory allocated by the chunk, including the
+    /// spare capacity of the mutable buffer of open chunks, which is the
+    /// same as [`size`](Self::size) for all other chunks
+    pub fn allocated_bytes(&self) -> usize {
+        match &self.stage {
+            ChunkStage::Open { mb_chunk, .. } => mb_chunk.allocated_bytes(),
+            _ => self.size(),
+        }
+    }
+
     /// Returns a mutable reference to the mutable buffer storage for
     /// chunks in the Open state
     ///
@@ -473,33 +553,39 @@ impl Chunk {
     /// This only works for chunks in the _open_ stage (chunk is converted) and the _frozen_ stage (no-op) and will
     /// fail for other stages.
     pub fn freeze(&mut self) -> Result<()> {
-        match &self.stage {
+        match &mut self.stage {
             ChunkStage::Open { mb_chunk, .. } => {
                 assert!(self.time_closed.is_none());
                 self.time_closed = Some(Utc::now());
-                let s = mb_chunk.snapshot();
+                let size = mb_chunk.size();
+                let table_summary = mb_chunk.table_summary();
+
+                // The chunk is no longer written to, so its data can be
+                // moved into the snapshot instead of being copied
+                let s = mb_chunk.take_snapshot();
                 self.metrics
                     .state
                     .inc_with_labels(&[KeyValue::new("state", "closed")]);
 
-                self.metrics.immutable_chunk_size.observe_with_labels(
-                    mb_chunk.size() as f64,
-                    &[KeyValue::new("state", "closed")],
-                );
+                self.metrics
+                    .immutable_chunk_size
+                    .observe_with_labels(size as f64, &[KeyValue::new("state", "closed")]);
 
                 // Cache table summary + schema
                 let metadata = ChunkMetadata {
-                    table_summary: Arc::new(mb_chunk.table_summary()),
+                    table_summary: Arc::new(table_summary),
                     schema: s.full_schema(),
+                    sort_key: None,
                 };
 
                 self.stage = ChunkStage::Frozen {
                     representation: ChunkStageFrozenRepr::MutableBufferSnapshot(Arc::clone(&s)),
                     meta: Arc::new(metadata),
                 };
+                self.send_event(ChunkLifecycleState::Closed);
                 Ok(())
             }
-            &ChunkStage::Frozen { .. } => {
+            ChunkStage::Frozen { .. } => {
                 // already frozen => no-op
                 Ok(())
             }
@@ -535,6 +621,7 @@ impl Chunk {
                         &[KeyValue::new("state", "moving")],
                     );
 
+                    self.send_event(ChunkLifecycleState::Moving);
                     Ok(chunk)
                 }
                 ChunkStageFrozenRepr::ReadBuffer(_) => InternalChunkState {
@@ -555,10 +642,13 @@ impl Chunk {
 
     /// Set the chunk in the Moved state, setting the underlying
     /// storage handle to db, and discarding the underlying mutable buffer
-    /// storage.
-    pub fn set_moved(&mut self, chunk: Arc<ReadBufferChunk>) -> Result<()> {
+    /// storage. The rows of `chunk` are sorted by the columns of `sort_key`.
+    pub fn set_moved(&mut self, chunk: Arc<ReadBufferChunk>, sort_key: Vec<String>) -> Result<()> {
         match &mut self.stage {
-            ChunkStage::Frozen { representation, .. } => match &representation {
+            ChunkStage::Frozen {
+                representation,
+                meta,
+            } => match &representation {
                 ChunkStageFrozenRepr::MutableBufferSnapshot(_) => {
                     self.metrics
                         .state
@@ -570,7 +660,12 @@ impl Chunk {
                     );
 
                     *representation = ChunkStageFrozenRepr::ReadBuffer(chunk);
+                    *meta = Arc::new(ChunkMetadata {
+                        sort_key: Some(sort_key),
+                        ..meta.as_ref().clone()
+                    });
                     self.finish_lifecycle_action(ChunkLifecycleAction::Moving)?;
+                    self.send_event(ChunkLifecycleState::Moved);
                     Ok(())
                 }
                 ChunkStageFrozenRepr::ReadBuffer(_) => InternalChunkState {
@@ -615,6 +710,7 @@ impl Chunk {
                         self.metrics
                             .state
                             .inc_with_labels(&[KeyValue::new("state", "writing_os")]);
+                        self.send_event(ChunkLifecycleState::Persisting);
                         Ok(db)
                     }
                 }
@@ -666,6 +762,7 @@ impl Chunk {
                             parquet: chunk,
                             read_buffer: Some(db),
                         };
+                        self.send_event(ChunkLifecycleState::Persisted);
                         Ok(())
                     }
                 }
@@ -700,6 +797,7 @@ impl Chunk {
 
                     let rub_chunk = Arc::clone(read_buffer_inner);
                     *read_buffer = None;
+                    self.send_event(ChunkLifecycleState::Unloaded);
                     Ok(rub_chunk)
                 } else {
                     // TODO: do we really need to error here or should unloading an unloaded chunk be a no-op?
@@ -720,6 +818,38 @@ impl Chunk {
         }
     }
 
+    /// Start lifecycle action that compacts this persisted chunk with others of
+    /// its table, returning a handle to its parquet file
+    ///
+    /// The chunk is dropped from the catalog once the compacted file replaced
+    /// it in the preserved catalog
+    pub fn set_compacting(&mut self, registration: &TaskRegistration) -> Result<Arc<ParquetChunk>> {
+        match &self.stage {
+            ChunkStage::Persisted { parquet, .. } => {
+                let parquet = Arc::clone(parquet);
+                self.set_lifecycle_action(ChunkLifecycleAction::Compacting, registration)?;
+                Ok(parquet)
+            }
+            _ => {
+                unexpected_state!(self, "setting compacting", "Persisted", &self.stage)
+            }
+        }
+    }
+
+    /// Notifies chunk event subscribers that this chunk transitioned into `state`
+    pub(super) fn send_event(&self, state: ChunkLifecycleState) {
+        let event = ChunkEvent {
+            partition_key: Arc::clone(&self.partition_key),
+            table_name: Arc::clone(&self.table_name),
+            id: self.id,
+            state,
+            time: Utc::now(),
+        };
+
+        // an error only means that nobody is subscribed
+        let _ = self.metrics.events.send(event);
+    }
+
     /// Set the chunk's in progress lifecycle action or return an error if already in-progress
     fn set_lifecycle_action(
         &mut self,
@@ -879,12 +1009,31 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_compacting() {
+        let registration = TaskRegistration::new();
+
+        // only persisted chunks can be compacted
+        let mut chunk = make_open_chunk();
+        assert_eq!(chunk.set_compacting(&registration).unwrap_err().to_string(), "Internal Error: unexpected chunk state for part1:table1:0  during setting compacting. Expected Persisted, got Open");
+
+        let mut chunk = make_persisted_chunk().await;
+        chunk.set_compacting(&registration).unwrap();
+        assert_eq!(
+            *chunk.lifecycle_action().unwrap().metadata(),
+            ChunkLifecycleAction::Compacting
+        );
+        assert!(chunk.set_compacting(&registration).is_err());
+    }
+
     fn make_mb_chunk(table_name: &str, sequencer_id: u32) -> MBChunk {
         let mut mb_chunk = MBChunk::new(table_name, MBChunkMetrics::new_unregistered());
         let entry = lp_to_entry(&format!("{} bar=1 10", table_name));
         let write = entry.partition_writes().unwrap().remove(0);
         let batch = write.table_batches().remove(0);
-        mb_chunk.write_table_batch(sequencer_id, 1, batch).unwrap();
+        mb_chunk
+            .write_table_batch(sequencer_id, 1, batch, Default::default(), &[])
+            .unwrap();
         mb_chunk
     }
 
@@ -923,6 +1072,8 @@ mod tests {
             partition_key,
             Arc::new(parquet_chunk),
             ChunkMetrics::new_unregistered(),
+            None,
+            None,
         )
     }
 }
@@ -1,7 +1,13 @@
 use crate::db::catalog::chunk::ChunkMetrics;
+use data_types::chunk_metadata::ChunkEvent;
 use metrics::{Counter, GaugeValue, Histogram, KeyValue};
+use tokio::sync::broadcast;
 use tracker::{LockTracker, RwLock};
 
+/// Number of chunk events buffered per subscriber before the oldest ones are
+/// skipped for subscribers that do not keep up
+const CHUNK_EVENTS_CAPACITY: usize = 1024;
+
 #[derive(Debug)]
 pub struct CatalogMetrics {
     /// Metrics domain
@@ -12,6 +18,9 @@ pub struct CatalogMetrics {
 
     /// Lock tracker for partition-level locks
     lock_tracker: LockTracker,
+
+    /// Chunk lifecycle transitions, see [`subscribe_chunk_events`](Self::subscribe_chunk_events)
+    chunk_events: broadcast::Sender<ChunkEvent>,
 }
 
 impl CatalogMetrics {
@@ -23,13 +32,22 @@ impl CatalogMetrics {
             &lock_tracker,
         );
 
+        let (chunk_events, _) = broadcast::channel(CHUNK_EVENTS_CAPACITY);
+
         Self {
             memory_metrics: MemoryMetrics::new(&metrics_domain),
             metrics_domain,
             lock_tracker,
+            chunk_events,
         }
     }
 
+    /// Returns a receiver for all chunk lifecycle transitions in the catalog
+    /// from now on
+    pub fn subscribe_chunk_events(&self) -> broadcast::Receiver<ChunkEvent> {
+        self.chunk_events.subscribe()
+    }
+
     /// Returns the memory metrics for the catalog
     pub fn memory(&self) -> &MemoryMetrics {
         &self.memory_metrics
@@ -68,6 +86,7 @@ impl CatalogMetrics {
                 )
                 .init(),
             lock_tracker,
+            chunk_events: self.chunk_events.clone(),
         }
     }
 }
@@ -80,6 +99,8 @@ pub struct PartitionMetrics {
 
     /// Lock Tracker for chunk-level locks
     lock_tracker: LockTracker,
+
+    chunk_events: broadcast::Sender<ChunkEvent>,
 }
 
 impl PartitionMetrics {
@@ -91,6 +112,7 @@ impl PartitionMetrics {
         ChunkMetrics {
             state: self.chunk_state.clone(),
             immutable_chunk_size: self.immutable_chunk_size.clone(),
+            events: self.chunk_events.clone(),
         }
     }
 }
@@ -98,6 +120,7 @@ impl PartitionMetrics {
 #[derive(Debug)]
 pub struct MemoryMetrics {
     mutable_buffer: GaugeValue,
+    mutable_buffer_allocated: GaugeValue,
     read_buffer: GaugeValue,
     parquet: GaugeValue,
 }
@@ -110,8 +133,16 @@ impl MemoryMetrics {
             "Memory usage by catalog chunks",
         );
 
+        let allocated_gauge = metrics_domain.register_gauge_metric(
+            "chunks_mem_allocated",
+            Some("bytes"),
+            "Memory allocated by catalog chunks, including spare capacity",
+        );
+
         Self {
             mutable_buffer: gauge.gauge_value(&[KeyValue::new("source", "mutable_buffer")]),
+            mutable_buffer_allocated: allocated_gauge
+                .gauge_value(&[KeyValue::new("source", "mutable_buffer")]),
             read_buffer: gauge.gauge_value(&[KeyValue::new("source", "read_buffer")]),
             parquet: gauge.gauge_value(&[KeyValue::new("source", "parquet")]),
         }
@@ -122,6 +153,11 @@ impl MemoryMetrics {
         self.mutable_buffer.clone_empty()
     }
 
+    /// Returns the memory allocated by the mutable buffer
+    pub fn mutable_buffer_allocated(&self) -> GaugeValue {
+        self.mutable_buffer_allocated.clone_empty()
+    }
+
     /// Returns the size of the mutable buffer
     pub fn read_buffer(&self) -> GaugeValue {
         self.read_buffer.clone_empty()
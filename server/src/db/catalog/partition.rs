@@ -5,7 +5,7 @@ use std::{collections::BTreeMap, sync::Arc};
 use chrono::{DateTime, Utc};
 use snafu::OptionExt;
 
-use data_types::chunk_metadata::ChunkSummary;
+use data_types::chunk_metadata::{ChunkLifecycleState, ChunkSummary};
 use data_types::partition_metadata::{
     PartitionSummary, UnaggregatedPartitionSummary, UnaggregatedTableSummary,
 };
@@ -131,6 +131,8 @@ impl Partition {
         &mut self,
         chunk_id: u32,
         chunk: Arc<parquet_file::chunk::Chunk>,
+        time_of_first_write: Option<DateTime<Utc>>,
+        time_of_last_write: Option<DateTime<Utc>>,
     ) -> Result<Arc<RwLock<Chunk>>> {
         let table_name = chunk.table_name().to_string();
 
@@ -139,6 +141,8 @@ impl Partition {
             &self.key,
             chunk,
             self.metrics.new_chunk_metrics(),
+            time_of_first_write,
+            time_of_last_write,
         )));
 
         let table = self
@@ -160,13 +164,32 @@ impl Partition {
         }
     }
 
+    /// Reserve a new chunk ID for the given table without creating a chunk,
+    /// e.g. for a chunk that is first written to object store. The table is
+    /// created if it is unknown.
+    pub fn reserve_chunk_id(&mut self, table_name: impl Into<String>) -> u32 {
+        let table = self
+            .tables
+            .entry(table_name.into())
+            .or_insert_with(PartitionTable::new);
+
+        let chunk_id = table.next_chunk_id;
+        assert_ne!(table.next_chunk_id, u32::MAX, "Chunk ID Overflow");
+        table.next_chunk_id += 1;
+
+        chunk_id
+    }
+
     /// Drop the specified chunk
     pub fn drop_chunk(&mut self, table_name: impl Into<String>, chunk_id: u32) -> Result<()> {
         let table_name = table_name.into();
 
         match self.tables.get_mut(&table_name) {
             Some(table) => match table.chunks.remove(&chunk_id) {
-                Some(_) => Ok(()),
+                Some(chunk) => {
+                    chunk.read().send_event(ChunkLifecycleState::Dropped);
+                    Ok(())
+                }
                 None => UnknownChunk {
                     partition_key: self.key(),
                     table_name,
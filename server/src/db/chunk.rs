@@ -4,7 +4,8 @@ use std::{
 };
 
 use arrow::datatypes::SchemaRef;
-use data_types::partition_metadata;
+use chrono::{DateTime, Utc};
+use data_types::{delete_predicate::DeletePredicate, partition_metadata};
 use partition_metadata::TableSummary;
 use snafu::{ResultExt, Snafu};
 
@@ -24,7 +25,12 @@ use query::{
 use read_buffer::Chunk as ReadBufferChunk;
 
 use super::{
-    catalog::chunk::ChunkMetadata, pred::to_read_buffer_predicate, streams::ReadFilterResultsStream,
+    catalog::chunk::ChunkMetadata,
+    pred::{
+        to_read_buffer_negated_predicate, to_read_buffer_predicate,
+        to_read_buffer_pushdown_predicate,
+    },
+    streams::{DeletedRowsFilterStream, ReadFilterResultsStream},
 };
 
 #[derive(Debug, Snafu)]
@@ -54,9 +60,6 @@ pub enum Error {
         source: internal_types::schema::Error,
     },
 
-    #[snafu(display("Predicate conversion error: {}", source))]
-    PredicateConversion { source: super::pred::Error },
-
     #[snafu(display(
         "Internal error: mutable buffer does not support predicate pushdown, but got: {:?}",
         predicate
@@ -86,6 +89,8 @@ pub struct DbChunk {
     table_name: Arc<str>,
     state: State,
     meta: Arc<ChunkMetadata>,
+    delete_predicates: Vec<Arc<DeletePredicate>>,
+    time_of_last_write: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug)]
@@ -118,6 +123,7 @@ impl DbChunk {
                 let meta = ChunkMetadata {
                     table_summary: Arc::new(mb_chunk.table_summary()),
                     schema: snapshot.full_schema(),
+                    sort_key: None,
                 };
                 (state, Arc::new(meta))
             }
@@ -162,6 +168,8 @@ impl DbChunk {
             table_name: chunk.table_name(),
             state,
             meta,
+            delete_predicates: chunk.delete_predicates().to_vec(),
+            time_of_last_write: chunk.time_of_last_write(),
         })
     }
 
@@ -188,6 +196,8 @@ impl DbChunk {
             table_name: chunk.table_name(),
             meta,
             state,
+            delete_predicates: chunk.delete_predicates().to_vec(),
+            time_of_last_write: chunk.time_of_last_write(),
         })
     }
 
@@ -204,6 +214,58 @@ impl DbChunk {
     pub fn table_name(&self) -> Arc<str> {
         Arc::clone(&self.table_name)
     }
+
+    /// Read the rows of this chunk. The rows masked by deletes are only
+    /// removed from read buffer chunks, which exclude them before
+    /// materialising any values
+    fn read_filter_unmasked(
+        &self,
+        predicate: &Predicate,
+        selection: Selection<'_>,
+    ) -> Result<SendableRecordBatchStream> {
+        let table_name = self.table_name.as_ref();
+        // Predicate is not required to be applied for correctness. We only pushed it down
+        // when possible for performance gain
+
+        debug!(?predicate, "Input Predicate to read_filter");
+
+        match &self.state {
+            State::MutableBuffer { chunk, .. } => {
+                let batch = chunk.read_filter(selection).context(MutableBufferChunk)?;
+
+                Ok(Box::pin(MemoryStream::new(vec![batch])))
+            }
+            State::ReadBuffer { chunk, .. } => {
+                // Only apply pushdownable predicates, including comparisons
+                // of field values, so that only matching rows are materialised
+                let rb_predicate = to_read_buffer_pushdown_predicate(&predicate, &self.meta.schema);
+
+                debug!(?rb_predicate, "Predicate pushed down to RUB");
+
+                let negated_predicates = self
+                    .delete_predicates
+                    .iter()
+                    .map(|predicate| to_read_buffer_negated_predicate(predicate))
+                    .collect();
+
+                let read_results = chunk
+                    .read_filter(table_name, rb_predicate, selection, negated_predicates)
+                    .context(ReadBufferChunkError { chunk_id: self.id })?;
+
+                let schema = chunk
+                    .read_filter_table_schema(table_name, selection)
+                    .context(ReadBufferChunkError { chunk_id: self.id })?;
+
+                Ok(Box::pin(ReadFilterResultsStream::new(
+                    read_results,
+                    schema.into(),
+                )))
+            }
+            State::ParquetFile { chunk, .. } => chunk
+                .read_filter(predicate, selection)
+                .context(ParquetFileChunkError { chunk_id: self.id }),
+        }
+    }
 }
 
 impl PartitionChunk for DbChunk {
@@ -218,10 +280,18 @@ impl PartitionChunk for DbChunk {
     }
 
     fn may_contain_pk_duplicates(&self) -> bool {
-        // Assume that the MUB can contain duplicates as it has the
-        // raw incoming stream of writes, but that all other types of
-        // chunks are deduplicated as part of creation
-        matches!(self.state, State::ReadBuffer { .. })
+        // The MUB has the raw incoming stream of writes, but its data is
+        // deduplicated when loaded into the RUB, and so are the parquet
+        // files written from the RUB and by compaction
+        matches!(self.state, State::MutableBuffer { .. })
+    }
+
+    fn has_delete_predicates(&self) -> bool {
+        !self.delete_predicates.is_empty()
+    }
+
+    fn time_of_last_write(&self) -> Option<DateTime<Utc>> {
+        self.time_of_last_write
     }
 
     fn apply_predicate(&self, predicate: &Predicate) -> Result<PredicateMatch> {
@@ -229,6 +299,11 @@ impl PartitionChunk for DbChunk {
             return Ok(PredicateMatch::Zero);
         }
 
+        if !self.delete_predicates.is_empty() {
+            // the matching rows may all have been deleted
+            return Ok(PredicateMatch::Unknown);
+        }
+
         // TODO apply predicate pruning here...
 
         let pred_result = match &self.state {
@@ -298,58 +373,41 @@ impl PartitionChunk for DbChunk {
         })
     }
 
+    fn sort_key(&self) -> Option<&[String]> {
+        self.meta.sort_key.as_deref()
+    }
+
     fn read_filter(
         &self,
         predicate: &Predicate,
         selection: Selection<'_>,
     ) -> Result<SendableRecordBatchStream, Self::Error> {
-        let table_name = self.table_name.as_ref();
-        // Predicate is not required to be applied for correctness. We only pushed it down
-        // when possible for performance gain
-
-        debug!(?predicate, "Input Predicate to read_filter");
-
-        match &self.state {
-            State::MutableBuffer { chunk, .. } => {
-                let batch = chunk.read_filter(selection).context(MutableBufferChunk)?;
-
-                Ok(Box::pin(MemoryStream::new(vec![batch])))
-            }
-            State::ReadBuffer { chunk, .. } => {
-                // Only apply pushdownable predicates
-                let rb_predicate =
-                    match to_read_buffer_predicate(&predicate).context(PredicateConversion) {
-                        Ok(predicate) => predicate,
-                        Err(_) => read_buffer::Predicate::default(),
-                    };
-
-                debug!(?rb_predicate, "Predicate pushed down to RUB");
-
-                let read_results = chunk
-                    .read_filter(table_name, rb_predicate, selection)
-                    .context(ReadBufferChunkError {
-                        chunk_id: self.id(),
-                    })?;
-
-                let schema = chunk
-                    .read_filter_table_schema(table_name, selection)
-                    .context(ReadBufferChunkError {
-                        chunk_id: self.id(),
-                    })?;
-
-                Ok(Box::pin(ReadFilterResultsStream::new(
-                    read_results,
-                    schema.into(),
-                )))
-            }
-            State::ParquetFile { chunk, .. } => {
-                chunk
-                    .read_filter(predicate, selection)
-                    .context(ParquetFileChunkError {
-                        chunk_id: self.id(),
-                    })
-            }
+        // The read buffer removes the deleted rows itself
+        if self.delete_predicates.is_empty() || matches!(self.state, State::ReadBuffer { .. }) {
+            return self.read_filter_unmasked(predicate, selection);
         }
+
+        // The delete predicates may refer to columns that are not selected,
+        // so read all columns and project the remaining rows afterwards
+        let stream = self.read_filter_unmasked(predicate, Selection::All)?;
+        let selected = self.table_schema(selection)?;
+        let schema = stream.schema();
+        let fields = selected
+            .iter()
+            .map(|(_, field)| {
+                schema
+                    .field_with_name(field.name())
+                    .map(|field| field.clone())
+                    .context(ArrowConversion)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let schema = Arc::new(arrow::datatypes::Schema::new(fields));
+
+        Ok(Box::pin(DeletedRowsFilterStream::new(
+            stream,
+            schema,
+            self.delete_predicates.clone(),
+        )))
     }
 
     fn column_names(
@@ -357,6 +415,11 @@ impl PartitionChunk for DbChunk {
         predicate: &Predicate,
         columns: Selection<'_>,
     ) -> Result<Option<StringSet>, Self::Error> {
+        if !self.delete_predicates.is_empty() {
+            // columns may only contain deleted values
+            return Ok(None);
+        }
+
         let table_name = self.table_name.as_ref();
         match &self.state {
             State::MutableBuffer { chunk, .. } => {
@@ -398,6 +461,11 @@ impl PartitionChunk for DbChunk {
         column_name: &str,
         predicate: &Predicate,
     ) -> Result<Option<StringSet>, Self::Error> {
+        if !self.delete_predicates.is_empty() {
+            // values may only occur in deleted rows
+            return Ok(None);
+        }
+
         let table_name = self.table_name.as_ref();
         match &self.state {
             State::MutableBuffer { .. } => {
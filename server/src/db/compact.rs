@@ -0,0 +1,264 @@
+//! Merging of the data of several chunks of a table into a single sorted and
+//! deduplicated record batch, as done when compacting persisted chunks and
+//! when loading a chunk into the read buffer
+use std::{collections::HashMap, convert::TryInto, sync::Arc};
+
+use arrow::{
+    array::{new_null_array, Array, ArrayRef, StringArray, TimestampNanosecondArray, UInt32Array},
+    compute::{cast, concat, take},
+    datatypes::DataType,
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+use internal_types::{
+    arrow::sort::sort_record_batch_with_key,
+    schema::{merge::SchemaMerger, InfluxColumnType, InfluxFieldType, Schema},
+};
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error merging record batches: {}", source))]
+    MergingBatches { source: ArrowError },
+
+    #[snafu(display("Invalid schema of record batch: {}", source))]
+    InvalidSchema {
+        source: internal_types::schema::Error,
+    },
+
+    #[snafu(display("Error merging schemas of record batches: {}", source))]
+    MergingSchemas {
+        source: internal_types::schema::merge::Error,
+    },
+
+    #[snafu(display("Error sorting merged record batch: {}", source))]
+    SortingBatch {
+        source: internal_types::arrow::sort::Error,
+    },
+
+    #[snafu(display("No record batches to merge"))]
+    NoBatches,
+
+    #[snafu(display("Schema of merged record batches has no time column"))]
+    NoTimeColumn,
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Merges `batches`, in the order their data was written, into a single
+/// record batch with the columns of all batches
+///
+/// Rows with the same tag values and timestamp are merged into one, with
+/// the value of each field written last, the same way queries deduplicate
+/// the data of chunks. The result is sorted using
+/// [`sort_record_batch_with_key`], and returned along with its sort key.
+///
+/// Fields stored with the first type of a pair of `coercions` in some
+/// batches and with the second type in others are converted to the second
+pub fn merge_record_batches(
+    batches: &[RecordBatch],
+    coercions: Vec<(InfluxFieldType, InfluxFieldType)>,
+) -> Result<(RecordBatch, Vec<String>)> {
+    ensure!(!batches.is_empty(), NoBatches);
+
+    let mut merger = SchemaMerger::new().with_coercions(coercions);
+    for batch in batches {
+        let schema: Schema = batch.schema().try_into().context(InvalidSchema)?;
+        merger.merge(&schema).context(MergingSchemas)?;
+    }
+    let schema = merger.build();
+    let arrow_schema = schema.as_arrow();
+
+    // Concatenate the batches column by column, filling in nulls for the
+    // columns a batch doesn't have. Tags are concatenated as plain strings
+    // as the dictionaries of the batches differ.
+    let columns = arrow_schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let data_type = value_type(field.data_type());
+            let arrays = batches
+                .iter()
+                .map(|batch| match batch.schema().index_of(field.name()) {
+                    Ok(idx) => cast(batch.column(idx), &data_type),
+                    Err(_) => Ok(new_null_array(&data_type, batch.num_rows())),
+                })
+                .collect::<Result<Vec<ArrayRef>, _>>()
+                .context(MergingBatches)?;
+            let arrays: Vec<&dyn Array> = arrays.iter().map(|array| array.as_ref()).collect();
+            concat(&arrays).context(MergingBatches)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let (row_keys, num_keys) = primary_keys(&schema, &columns)?;
+
+    let columns = columns
+        .iter()
+        .zip(arrow_schema.fields())
+        .map(|(column, field)| {
+            let indices = last_non_null_rows(column.as_ref(), &row_keys, num_keys);
+            let column = take(column.as_ref(), &indices, None)?;
+            cast(&column, field.data_type())
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .context(MergingBatches)?;
+
+    let batch = RecordBatch::try_new(Arc::clone(&arrow_schema), columns).context(MergingBatches)?;
+    sort_record_batch_with_key(batch).context(SortingBatch)
+}
+
+/// Returns the type the values of a column of type `data_type` are merged as
+fn value_type(data_type: &DataType) -> DataType {
+    match data_type {
+        DataType::Dictionary(_, value_type) => value_type.as_ref().clone(),
+        data_type => data_type.clone(),
+    }
+}
+
+/// Numbers the distinct combinations of tag values and timestamp in the
+/// order they first appear, returning the number of each row and how many
+/// there are
+fn primary_keys(schema: &Schema, columns: &[ArrayRef]) -> Result<(Vec<usize>, usize)> {
+    let mut tags = vec![];
+    let mut time = None;
+    for (idx, (column_type, _)) in schema.iter().enumerate() {
+        match column_type {
+            Some(InfluxColumnType::Tag) => tags.push(
+                columns[idx]
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .expect("tags are merged as strings"),
+            ),
+            Some(InfluxColumnType::Timestamp) => {
+                time = Some(
+                    columns[idx]
+                        .as_any()
+                        .downcast_ref::<TimestampNanosecondArray>()
+                        .expect("time is a nanosecond timestamp"),
+                )
+            }
+            _ => {}
+        }
+    }
+    let time = time.context(NoTimeColumn)?;
+
+    let mut keys: HashMap<(Vec<Option<&str>>, i64), usize> = HashMap::new();
+    let row_keys = (0..time.len())
+        .map(|row| {
+            let tag_values = tags
+                .iter()
+                .map(|tag| {
+                    if tag.is_null(row) {
+                        None
+                    } else {
+                        Some(tag.value(row))
+                    }
+                })
+                .collect();
+            let num_keys = keys.len();
+            *keys
+                .entry((tag_values, time.value(row)))
+                .or_insert(num_keys)
+        })
+        .collect();
+
+    Ok((row_keys, keys.len()))
+}
+
+/// Returns, for each primary key, the index of the last row with a value in
+/// `column`, or null if all its rows are null
+fn last_non_null_rows(column: &dyn Array, row_keys: &[usize], num_keys: usize) -> UInt32Array {
+    let mut indices = vec![None; num_keys];
+    for (row, &key) in row_keys.iter().enumerate() {
+        if column.is_valid(row) {
+            indices[key] = Some(row as u32);
+        }
+    }
+    indices.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{DictionaryArray, Float64Array, Int64Array},
+        datatypes::Int32Type,
+    };
+    use arrow_util::assert_batches_eq;
+    use internal_types::schema::builder::SchemaBuilder;
+
+    use super::*;
+
+    #[test]
+    fn merge() {
+        let schema_1 = SchemaBuilder::new()
+            .tag("host")
+            .field("usage", DataType::Float64)
+            .timestamp()
+            .build()
+            .unwrap();
+        let batch_1 = RecordBatch::try_new(
+            schema_1.as_arrow(),
+            vec![
+                Arc::new(
+                    vec!["b", "a", "a"]
+                        .into_iter()
+                        .collect::<DictionaryArray<Int32Type>>(),
+                ),
+                Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0])),
+                Arc::new(TimestampNanosecondArray::from(vec![10, 20, 10])),
+            ],
+        )
+        .unwrap();
+
+        let schema_2 = SchemaBuilder::new()
+            .tag("host")
+            .tag("region")
+            .field("count", DataType::Int64)
+            .timestamp()
+            .build()
+            .unwrap();
+        let batch_2 = RecordBatch::try_new(
+            schema_2.as_arrow(),
+            vec![
+                Arc::new(
+                    vec!["a", "c"]
+                        .into_iter()
+                        .collect::<DictionaryArray<Int32Type>>(),
+                ),
+                Arc::new(
+                    vec![None, Some("west")]
+                        .into_iter()
+                        .collect::<DictionaryArray<Int32Type>>(),
+                ),
+                Arc::new(Int64Array::from(vec![4, 5])),
+                Arc::new(TimestampNanosecondArray::from(vec![20, 30])),
+            ],
+        )
+        .unwrap();
+
+        let (merged, sort_key) = merge_record_batches(&[batch_1, batch_2], vec![]).unwrap();
+
+        // the count of host a at time 20 is added by the second batch, without
+        // removing the usage written by the first
+        assert_eq!(sort_key, vec!["region", "host", "time"]);
+        assert_batches_eq!(
+            &[
+                "+-------+------+--------+-------------------------------+-------+",
+                "| count | host | region | time                          | usage |",
+                "+-------+------+--------+-------------------------------+-------+",
+                "|       | a    |        | 1970-01-01 00:00:00.000000010 | 3     |",
+                "| 4     | a    |        | 1970-01-01 00:00:00.000000020 | 2     |",
+                "|       | b    |        | 1970-01-01 00:00:00.000000010 | 1     |",
+                "| 5     | c    | west   | 1970-01-01 00:00:00.000000030 |       |",
+                "+-------+------+--------+-------------------------------+-------+",
+            ],
+            &[merged]
+        );
+    }
+
+    #[test]
+    fn merge_nothing() {
+        let err = merge_record_batches(&[], vec![]).unwrap_err();
+        assert!(matches!(err, Error::NoBatches));
+    }
+}
@@ -0,0 +1,226 @@
+//! Scheduled execution of the continuous queries of a database
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use arrow::datatypes::DataType;
+use chrono::Utc;
+use data_types::database_rules::ContinuousQuery;
+use internal_types::schema::TIME_COLUMN_NAME;
+use observability_deps::tracing::{debug, error};
+use query::frontend::sql::SqlQueryPlanner;
+use snafu::{ResultExt, Snafu};
+
+use super::{
+    lines::{batch_to_lines, write_lines},
+    Db,
+};
+
+/// How long to wait between two checks for queries to run
+pub const CONTINUOUS_QUERY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error planning continuous query {}: {}", name, source))]
+    Planning {
+        name: String,
+        source: query::frontend::sql::Error,
+    },
+
+    #[snafu(display("Error executing continuous query {}: {}", name, source))]
+    Execution {
+        name: String,
+        source: query::exec::Error,
+    },
+
+    #[snafu(display("Error converting result of continuous query {}: {}", name, source))]
+    Conversion {
+        name: String,
+        source: arrow::error::ArrowError,
+    },
+
+    #[snafu(display("Error writing result of continuous query {}: {}", name, source))]
+    Writing {
+        name: String,
+        source: super::lines::Error,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Runs the continuous queries of a Db at their scheduled times.
+///
+/// A query first runs at the first scheduled time after the scheduler has
+/// seen it. Runs that were missed, e.g. while the server was down, are not
+/// caught up: only the latest scheduled run is executed.
+pub struct ContinuousQueryScheduler {
+    db: Arc<Db>,
+
+    /// The scheduled time of the last run of each query, by name
+    last_runs: HashMap<String, i64>,
+}
+
+impl ContinuousQueryScheduler {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self {
+            db,
+            last_runs: HashMap::new(),
+        }
+    }
+
+    /// Runs the queries that are due and waits for
+    /// [`CONTINUOUS_QUERY_CHECK_INTERVAL`](CONTINUOUS_QUERY_CHECK_INTERVAL)
+    pub async fn check_for_work(&mut self) {
+        self.run_due(Utc::now().timestamp_nanos()).await;
+
+        tokio::time::sleep(CONTINUOUS_QUERY_CHECK_INTERVAL).await
+    }
+
+    /// Runs every query scheduled at a time after its last run that is not
+    /// after `now`. Returns the number of queries run; failed runs are
+    /// logged and not retried.
+    pub async fn run_due(&mut self, now: i64) -> usize {
+        let queries = self.db.rules.read().continuous_queries.clone();
+
+        // forget the queries that have been deleted
+        self.last_runs
+            .retain(|name, _| queries.iter().any(|query| &query.name == name));
+
+        let mut runs = 0;
+        for query in queries {
+            let scheduled_at = query.schedule.last_run(now);
+            match self.last_runs.insert(query.name.clone(), scheduled_at) {
+                Some(last_run) if last_run < scheduled_at => {}
+                _ => continue,
+            }
+
+            runs += 1;
+            match self.run(&query, scheduled_at).await {
+                Ok(rows) => {
+                    debug!(name=%query.name, scheduled_at, rows, "ran continuous query")
+                }
+                Err(e) => error!(%e, "error running continuous query"),
+            }
+        }
+
+        runs
+    }
+
+    /// Runs `query` and writes its result into the target table. Returns the
+    /// number of rows written.
+    pub async fn run(&self, query: &ContinuousQuery, scheduled_at: i64) -> Result<usize> {
+        let name = &query.name;
+
+        let executor = self.db.executor();
+        let physical_plan = SqlQueryPlanner::default()
+            .query(Arc::clone(&self.db), &query.query, &executor)
+            .context(Planning { name })?;
+        let batches = executor
+            .collect(physical_plan)
+            .await
+            .context(Execution { name })?;
+
+        let mut lines = String::new();
+        for batch in batches {
+            let schema = batch.schema();
+            let tag_columns = schema
+                .fields()
+                .iter()
+                .filter(|field| field.name() != TIME_COLUMN_NAME && is_string(field.data_type()))
+                .map(|field| field.name().as_str())
+                .collect::<Vec<_>>();
+
+            batch_to_lines(&mut lines, &query.target_table, &batch, &tag_columns)
+                .context(Conversion { name })?;
+        }
+
+        write_lines(&self.db, &lines, scheduled_at)
+            .await
+            .context(Writing { name })
+    }
+}
+
+fn is_string(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Utf8 => true,
+        DataType::Dictionary(_, value_type) => value_type.as_ref() == &DataType::Utf8,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::test_helpers::write_lp, utils::make_db};
+    use arrow::record_batch::RecordBatch;
+    use arrow_util::assert_batches_sorted_eq;
+    use data_types::database_rules::Schedule;
+
+    async fn run_query(db: Arc<Db>, query: &str) -> Vec<RecordBatch> {
+        let planner = SqlQueryPlanner::default();
+        let executor = db.executor();
+        let physical_plan = planner.query(db, query, &executor).unwrap();
+        executor.collect(physical_plan).await.unwrap()
+    }
+
+    fn continuous_query() -> ContinuousQuery {
+        ContinuousQuery {
+            name: "max_user".to_string(),
+            query: "select region, max(user) as user from cpu group by region".to_string(),
+            target_table: "cpu_max".to_string(),
+            schedule: Schedule {
+                every: Duration::from_nanos(10),
+                offset: Duration::from_nanos(5),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn run_on_schedule() {
+        let db = Arc::new(make_db().await.db);
+        db.rules.write().continuous_queries = vec![continuous_query()];
+
+        write_lp(
+            &db,
+            "cpu,region=west user=1 10\ncpu,region=west user=3 20\ncpu,region=east user=2 20",
+        )
+        .await;
+
+        let mut scheduler = ContinuousQueryScheduler::new(Arc::clone(&db));
+
+        // the first run is at the next scheduled time
+        assert_eq!(scheduler.run_due(16).await, 0);
+        assert_eq!(scheduler.run_due(24).await, 0);
+        assert_eq!(scheduler.run_due(25).await, 1);
+        assert_eq!(scheduler.run_due(34).await, 0);
+
+        let batches = run_query(Arc::clone(&db), "select * from cpu_max").await;
+        let expected = vec![
+            "+--------+-------------------------------+------+",
+            "| region | time                          | user |",
+            "+--------+-------------------------------+------+",
+            "| east   | 1970-01-01 00:00:00.000000025 | 2    |",
+            "| west   | 1970-01-01 00:00:00.000000025 | 3    |",
+            "+--------+-------------------------------+------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        // deleted queries are not run anymore
+        db.rules.write().continuous_queries = vec![];
+        assert_eq!(scheduler.run_due(100).await, 0);
+    }
+
+    #[tokio::test]
+    async fn run_error() {
+        let db = Arc::new(make_db().await.db);
+        let scheduler = ContinuousQueryScheduler::new(Arc::clone(&db));
+
+        let query = ContinuousQuery {
+            query: "select * from".to_string(),
+            ..continuous_query()
+        };
+        let err = scheduler.run(&query, 0).await.unwrap_err();
+        assert!(matches!(err, Error::Planning { .. }));
+    }
+}
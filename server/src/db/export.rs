@@ -0,0 +1,249 @@
+//! Export of the data of a database as a hive-style partitioned directory of
+//! parquet files, to be read by tools like Spark
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use data_types::timestamp::TimestampRange;
+use datafusion::logical_plan::LogicalPlanBuilder;
+use internal_types::selection::Selection;
+use object_store::{
+    path::{parsed::DirsAndFileName, ObjectStorePath, Path},
+    ObjectStore, ObjectStoreApi,
+};
+use observability_deps::tracing::debug;
+use parquet_file::storage::Storage;
+use query::{predicate::PredicateBuilder, provider::ProviderBuilder, Database, PartitionChunk};
+use snafu::{ResultExt, Snafu};
+
+use super::{field_type_coercions, Db};
+
+/// The name of the parquet file written for every table and partition
+pub const EXPORT_FILE_NAME: &str = "data.parquet";
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error listing chunks: {}", source))]
+    ListingChunks { source: super::Error },
+
+    #[snafu(display(
+        "Error reading schema of chunk {} of table {}: {}",
+        chunk_id,
+        table_name,
+        source
+    ))]
+    ChunkSchema {
+        table_name: String,
+        chunk_id: u32,
+        source: super::chunk::Error,
+    },
+
+    #[snafu(display("Error planning export of table {}: {}", table_name, source))]
+    Planning {
+        table_name: String,
+        source: query::provider::Error,
+    },
+
+    #[snafu(display("Error executing export of table {}: {}", table_name, source))]
+    Execution {
+        table_name: String,
+        source: datafusion::error::DataFusionError,
+    },
+
+    #[snafu(display("Error encoding parquet file of table {}: {}", table_name, source))]
+    Encoding {
+        table_name: String,
+        source: parquet_file::storage::Error,
+    },
+
+    #[snafu(display("Error writing {}: {}", path.display(), source))]
+    Writing {
+        path: Path,
+        source: object_store::Error,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Writes the rows of every table of `db` with a timestamp within `range`,
+/// or all rows without a range, to `store` and returns the paths of the
+/// files written.
+///
+/// Every table and partition of the database gets a single parquet file at
+/// `<prefix>/<table>/partition_key=<key>/data.parquet` with the rows a query
+/// of the table would return. Tables or partitions without rows in `range`
+/// are skipped.
+pub async fn export_to_object_store(
+    db: &Db,
+    store: &ObjectStore,
+    prefix: &DirsAndFileName,
+    range: Option<TimestampRange>,
+) -> Result<Vec<Path>> {
+    let tables: BTreeSet<_> = db
+        .chunk_summaries()
+        .context(ListingChunks)?
+        .into_iter()
+        .map(|summary| (summary.table_name, summary.partition_key))
+        .collect();
+
+    let mut paths = vec![];
+    for (table_name, partition_key) in tables {
+        let data = match export_partition(db, &table_name, &partition_key, range).await? {
+            Some(data) => data,
+            None => continue,
+        };
+
+        let mut path = prefix.clone();
+        path.push_dir(table_name.as_ref());
+        path.push_dir(format!("partition_key={}", partition_key));
+        path.set_file_name(EXPORT_FILE_NAME);
+        let path = store.path_from_dirs_and_filename(path);
+
+        let len = data.len();
+        let data = bytes::Bytes::from(data);
+        store
+            .put(
+                &path,
+                futures::stream::once(async move { std::io::Result::Ok(data) }),
+                Some(len),
+            )
+            .await
+            .context(Writing { path: path.clone() })?;
+
+        debug!(%table_name, %partition_key, bytes=len, "exported partition");
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+/// Returns the rows of a table in a partition as parquet file, or `None`
+/// if there are no rows in `range`
+async fn export_partition(
+    db: &Db,
+    table_name: &str,
+    partition_key: &str,
+    range: Option<TimestampRange>,
+) -> Result<Option<Vec<u8>>> {
+    let predicate = PredicateBuilder::new()
+        .table(table_name)
+        .partition_key(partition_key)
+        .timestamp_range_option(range)
+        .build();
+
+    let chunks = db.chunks(&predicate);
+    if chunks.is_empty() {
+        return Ok(None);
+    }
+
+    let coercions = field_type_coercions(&db.rules.read().type_coercion_rules);
+    let mut builder = ProviderBuilder::new(table_name).with_coercions(coercions);
+    builder.add_no_op_pruner();
+    for chunk in chunks {
+        let chunk_id = chunk.id();
+        let schema = chunk.table_schema(Selection::All).context(ChunkSchema {
+            table_name,
+            chunk_id,
+        })?;
+        builder
+            .add_chunk(chunk, schema)
+            .context(Planning { table_name })?;
+    }
+    let provider = builder.build().context(Planning { table_name })?;
+    let schema = provider.arrow_schema();
+
+    let mut plan_builder = LogicalPlanBuilder::scan(table_name, Arc::new(provider), None)
+        .context(Execution { table_name })?;
+    if let Some(filter_expr) = predicate.filter_expr() {
+        plan_builder = plan_builder
+            .filter(filter_expr)
+            .context(Execution { table_name })?;
+    }
+    let plan = plan_builder.build().context(Execution { table_name })?;
+
+    let ctx = db.executor().new_context();
+    let physical_plan = ctx.prepare_plan(&plan).context(Execution { table_name })?;
+    let batches = ctx
+        .collect(physical_plan)
+        .await
+        .context(Execution { table_name })?;
+
+    if batches.iter().all(|batch| batch.num_rows() == 0) {
+        return Ok(None);
+    }
+
+    Storage::record_batches_to_parquet_bytes(schema, &batches)
+        .context(Encoding { table_name })
+        .map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::test_helpers::write_lp, utils::make_db};
+    use arrow::record_batch::RecordBatch;
+    use arrow_util::assert_batches_sorted_eq;
+    use futures::TryStreamExt;
+    use parquet_file::test_utils::read_data_from_parquet_data;
+
+    async fn read_parquet(db: &Db, store: &ObjectStore, path: &Path) -> Vec<RecordBatch> {
+        let data = store
+            .get(path)
+            .await
+            .unwrap()
+            .map_ok(|bytes| bytes.to_vec())
+            .try_concat()
+            .await
+            .unwrap();
+
+        let predicate = PredicateBuilder::new().table("cpu").build();
+        let schema = db.chunks(&predicate)[0]
+            .table_schema(Selection::All)
+            .unwrap();
+        read_data_from_parquet_data(schema.as_arrow(), data)
+    }
+
+    #[tokio::test]
+    async fn export() {
+        let db = make_db().await.db;
+        write_lp(
+            &db,
+            "cpu,region=west user=1 10\ncpu,region=west user=2 20\ncpu,region=east user=3 20",
+        )
+        .await;
+        write_lp(&db, "mem free=10i 10").await;
+
+        let store = ObjectStore::new_in_memory(object_store::memory::InMemory::new());
+        let mut prefix = DirsAndFileName::default();
+        prefix.push_dir("export");
+
+        let paths = export_to_object_store(&db, &store, &prefix, None)
+            .await
+            .unwrap();
+        let paths: Vec<_> = paths.into_iter().map(|path| path.display()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "export/cpu/partition_key=1970-01-01T00/data.parquet",
+                "export/mem/partition_key=1970-01-01T00/data.parquet",
+            ]
+        );
+
+        // only the rows within the range
+        let paths = export_to_object_store(&db, &store, &prefix, Some(TimestampRange::new(15, 30)))
+            .await
+            .unwrap();
+        assert_eq!(paths.len(), 1);
+
+        let batches = read_parquet(&db, &store, &paths[0]).await;
+        let expected = vec![
+            "+--------+-------------------------------+------+",
+            "| region | time                          | user |",
+            "+--------+-------------------------------+------+",
+            "| east   | 1970-01-01 00:00:00.000000020 | 3    |",
+            "| west   | 1970-01-01 00:00:00.000000020 | 2    |",
+            "+--------+-------------------------------+------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+    }
+}
@@ -5,6 +5,7 @@ use chrono::{DateTime, Utc};
 use observability_deps::tracing::{info, warn};
 
 use data_types::{database_rules::LifecycleRules, error::ErrorLogger, job::Job};
+use metrics::{Counter, KeyValue};
 
 use tracker::{RwLock, TaskTracker};
 
@@ -27,17 +28,29 @@ pub struct LifecycleManager {
     db_name: String,
     move_task: Option<TaskTracker<Job>>,
     write_task: Option<TaskTracker<Job>>,
+
+    /// Chunk transitions started by the lifecycle policy, by action
+    transitions: Counter,
 }
 
 impl LifecycleManager {
     pub fn new(db: Arc<Db>) -> Self {
         let db_name = db.rules.read().name.clone().into();
+        let transitions = db
+            .metrics_registry
+            .register_domain_with_labels("lifecycle", db.metric_labels.clone())
+            .register_counter_metric(
+                "transitions",
+                None,
+                "Chunk transitions started by the lifecycle policy",
+            );
 
         Self {
             db,
             db_name,
             move_task: None,
             write_task: None,
+            transitions,
         }
     }
 
@@ -97,6 +110,9 @@ trait ChunkMover {
     /// Drops a chunk from the database
     fn drop_chunk(&mut self, partition_key: String, table_name: String, chunk_id: u32);
 
+    /// Closes an open chunk, so that further writes go to a new chunk
+    fn rollover_chunk(&mut self, partition_key: String, table_name: String, chunk_id: u32);
+
     /// The core policy logic
     ///
     /// Returns a future that resolves when this method should be called next
@@ -148,6 +164,21 @@ trait ChunkMover {
             }
 
             match chunk_guard.stage() {
+                ChunkStage::Open { .. } if should_rollover(&rules, &*chunk_guard, now) => {
+                    let partition_key = chunk_guard.key().to_string();
+                    let table_name = chunk_guard.table_name().to_string();
+                    let chunk_id = chunk_guard.id();
+
+                    std::mem::drop(chunk_guard);
+
+                    self.rollover_chunk(partition_key.clone(), table_name.clone(), chunk_id);
+
+                    // The closed chunk is moved like any other
+                    if move_tracker.is_none() {
+                        move_tracker =
+                            Some(self.move_to_read_buffer(partition_key, table_name, chunk_id));
+                    }
+                }
                 ChunkStage::Open { .. } => {
                     open_partitions.insert(chunk_guard.key().to_string());
                     if move_tracker.is_none() && would_move {
@@ -172,7 +203,9 @@ trait ChunkMover {
                         move_tracker =
                             Some(self.move_to_read_buffer(partition_key, table_name, chunk_id));
                     }
-                    ChunkStageFrozenRepr::ReadBuffer { .. } if would_write => {
+                    ChunkStageFrozenRepr::ReadBuffer { .. }
+                        if would_write && can_persist(&rules, &*chunk_guard, now) =>
+                    {
                         let partition_key = chunk_guard.key().to_string();
                         let table_name = chunk_guard.table_name().to_string();
                         let chunk_id = chunk_guard.id();
@@ -317,6 +350,8 @@ impl ChunkMover for LifecycleManager {
         chunk_id: u32,
     ) -> TaskTracker<Self::Job> {
         info!(%partition_key, %chunk_id, "moving chunk to read buffer");
+        self.transitions
+            .inc_with_labels(&[KeyValue::new("action", "move")]);
         let tracker =
             self.db
                 .load_chunk_to_read_buffer_in_background(partition_key, table_name, chunk_id);
@@ -331,6 +366,8 @@ impl ChunkMover for LifecycleManager {
         chunk_id: u32,
     ) -> TaskTracker<Self::Job> {
         info!(%partition_key, %chunk_id, "write chunk to object store");
+        self.transitions
+            .inc_with_labels(&[KeyValue::new("action", "persist")]);
         let tracker =
             self.db
                 .write_chunk_to_object_store_in_background(partition_key, table_name, chunk_id);
@@ -340,11 +377,23 @@ impl ChunkMover for LifecycleManager {
 
     fn drop_chunk(&mut self, partition_key: String, table_name: String, chunk_id: u32) {
         info!(%partition_key, %chunk_id, "dropping chunk");
+        self.transitions
+            .inc_with_labels(&[KeyValue::new("action", "drop")]);
         let _ = self
             .db
             .drop_chunk(&partition_key, &table_name, chunk_id)
             .log_if_error("dropping chunk to free up memory");
     }
+
+    fn rollover_chunk(&mut self, partition_key: String, table_name: String, chunk_id: u32) {
+        info!(%partition_key, %chunk_id, "rolling over chunk");
+        self.transitions
+            .inc_with_labels(&[KeyValue::new("action", "rollover")]);
+        let _ = self
+            .db
+            .rollover_chunk(&partition_key, &table_name, chunk_id)
+            .log_if_error("rolling over chunk");
+    }
 }
 
 /// Returns the number of seconds between two times
@@ -359,10 +408,19 @@ fn elapsed_seconds(a: DateTime<Utc>, b: DateTime<Utc>) -> u32 {
     }
 }
 
-/// Returns if the chunk is sufficiently cold and old to move
+/// Returns if the chunk is sufficiently cold and old, or has reached the
+/// maximum age, to move
 ///
 /// Note: Does not check the chunk is the correct state
 fn can_move(rules: &LifecycleRules, chunk: &Chunk, now: DateTime<Utc>) -> bool {
+    if let (Some(max_age), Some(first_write)) =
+        (rules.mutable_max_age_seconds, chunk.time_of_first_write())
+    {
+        if elapsed_seconds(now, first_write) >= max_age.get() {
+            return true;
+        }
+    }
+
     match (rules.mutable_linger_seconds, chunk.time_of_last_write()) {
         (Some(linger), Some(last_write)) if elapsed_seconds(now, last_write) >= linger.get() => {
             match (
@@ -385,6 +443,40 @@ fn can_move(rules: &LifecycleRules, chunk: &Chunk, now: DateTime<Utc>) -> bool {
     }
 }
 
+/// Returns if the chunk has reached the size or row threshold or the maximum
+/// age, so that it should be closed and further writes go to a new chunk
+///
+/// Note: Does not check the chunk is the correct state
+fn should_rollover(rules: &LifecycleRules, chunk: &Chunk, now: DateTime<Utc>) -> bool {
+    let exceeds_size = rules
+        .mutable_size_threshold
+        .map_or(false, |threshold| chunk.allocated_bytes() > threshold.get());
+    let exceeds_rows = rules.mutable_row_threshold.map_or(false, |threshold| {
+        chunk.summary().row_count >= threshold.get()
+    });
+    let exceeds_age = match (rules.mutable_max_age_seconds, chunk.time_of_first_write()) {
+        (Some(max_age), Some(first_write)) => elapsed_seconds(now, first_write) >= max_age.get(),
+        _ => false,
+    };
+
+    exceeds_size || exceeds_rows || exceeds_age
+}
+
+/// Returns if the chunk is old enough to persist
+///
+/// Note: Does not check the chunk is the correct state
+fn can_persist(rules: &LifecycleRules, chunk: &Chunk, now: DateTime<Utc>) -> bool {
+    match (
+        rules.persist_age_threshold_seconds,
+        chunk.time_of_first_write(),
+    ) {
+        (Some(threshold), Some(first_write)) => {
+            elapsed_seconds(now, first_write) >= threshold.get()
+        }
+        _ => true,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -411,7 +503,9 @@ mod tests {
             "table1",
             mutable_buffer::chunk::ChunkMetrics::new_unregistered(),
         );
-        mb_chunk.write_table_batch(1, 5, batch).unwrap();
+        mb_chunk
+            .write_table_batch(1, 5, batch, Default::default(), &[])
+            .unwrap();
 
         let mut chunk =
             Chunk::new_open(id, "", mb_chunk, ChunkMetrics::new_unregistered()).unwrap();
@@ -432,7 +526,7 @@ mod tests {
     /// Transitions a new ("open") chunk into the "moved" state.
     fn transition_to_moved(mut chunk: Chunk, rb: &Arc<read_buffer::Chunk>) -> Chunk {
         chunk = transition_to_moving(chunk);
-        chunk.set_moved(Arc::clone(&rb)).unwrap();
+        chunk.set_moved(Arc::clone(&rb), vec![]).unwrap();
         chunk
     }
 
@@ -489,6 +583,7 @@ mod tests {
         Move(u32),
         Write(u32),
         Drop(u32),
+        Rollover(u32),
     }
 
     /// A dummy mover that is used to test the policy
@@ -591,6 +686,16 @@ mod tests {
                 .collect();
             self.events.push(MoverEvents::Drop(chunk_id))
         }
+
+        fn rollover_chunk(&mut self, _partition_key: String, _table_name: String, chunk_id: u32) {
+            let chunk = self
+                .chunks
+                .iter()
+                .find(|x| x.read().id() == chunk_id)
+                .unwrap();
+            chunk.write().freeze().unwrap();
+            self.events.push(MoverEvents::Rollover(chunk_id));
+        }
     }
 
     #[test]
@@ -630,6 +735,35 @@ mod tests {
         let chunk = new_chunk(0, Some(0), Some(70));
         assert!(!can_move(&rules, &chunk, from_secs(71)));
         assert!(can_move(&rules, &chunk, from_secs(81)));
+
+        // If mutable_max_age_seconds set can move a chunk that is still
+        // receiving writes once it is old enough
+        let rules = LifecycleRules {
+            mutable_max_age_seconds: Some(NonZeroU32::new(100).unwrap()),
+            ..Default::default()
+        };
+        let chunk = new_chunk(0, Some(0), Some(99));
+        assert!(!can_move(&rules, &chunk, from_secs(99)));
+        assert!(can_move(&rules, &chunk, from_secs(100)));
+
+        let chunk = new_chunk(0, None, None);
+        assert!(!can_move(&rules, &chunk, from_secs(100)));
+    }
+
+    #[test]
+    fn test_can_persist() {
+        // Can persist by default
+        let rules = LifecycleRules::default();
+        let chunk = new_chunk(0, Some(0), Some(0));
+        assert!(can_persist(&rules, &chunk, from_secs(0)));
+
+        let rules = LifecycleRules {
+            persist_age_threshold_seconds: Some(NonZeroU32::new(60).unwrap()),
+            ..Default::default()
+        };
+        let chunk = new_chunk(0, Some(10), Some(20));
+        assert!(!can_persist(&rules, &chunk, from_secs(69)));
+        assert!(can_persist(&rules, &chunk, from_secs(70)));
     }
 
     #[test]
@@ -647,6 +781,94 @@ mod tests {
         assert_eq!(mover.events, vec![]);
     }
 
+    #[test]
+    fn test_should_rollover() {
+        // Never rolls over by default
+        let rules = LifecycleRules::default();
+        let chunk = new_chunk(0, Some(0), Some(0));
+        assert!(!should_rollover(&rules, &chunk, from_secs(1_000)));
+
+        let rules = LifecycleRules {
+            mutable_row_threshold: Some(NonZeroUsize::new(2).unwrap()),
+            ..Default::default()
+        };
+        assert!(!should_rollover(&rules, &chunk, from_secs(0)));
+        let rules = LifecycleRules {
+            mutable_row_threshold: Some(NonZeroUsize::new(1).unwrap()),
+            ..Default::default()
+        };
+        assert!(should_rollover(&rules, &chunk, from_secs(0)));
+
+        let rules = LifecycleRules {
+            mutable_size_threshold: Some(NonZeroUsize::new(chunk.allocated_bytes()).unwrap()),
+            ..Default::default()
+        };
+        assert!(!should_rollover(&rules, &chunk, from_secs(0)));
+        let rules = LifecycleRules {
+            mutable_size_threshold: Some(NonZeroUsize::new(chunk.allocated_bytes() - 1).unwrap()),
+            ..Default::default()
+        };
+        assert!(should_rollover(&rules, &chunk, from_secs(0)));
+
+        let rules = LifecycleRules {
+            mutable_max_age_seconds: Some(NonZeroU32::new(10).unwrap()),
+            ..Default::default()
+        };
+        assert!(!should_rollover(&rules, &chunk, from_secs(9)));
+        assert!(should_rollover(&rules, &chunk, from_secs(10)));
+    }
+
+    #[test]
+    fn test_rollover() {
+        let rules = LifecycleRules {
+            mutable_max_age_seconds: Some(NonZeroU32::new(10).unwrap()),
+            ..Default::default()
+        };
+        let chunks = vec![
+            new_chunk(0, Some(5), Some(5)),
+            new_chunk(1, Some(0), Some(0)),
+            new_chunk(2, Some(0), Some(0)),
+        ];
+
+        let mut registry = TaskRegistry::new();
+        let mut mover = DummyMover::new(rules, chunks);
+        mover.check_for_work(from_secs(9), Instant::now());
+        assert_eq!(mover.events, vec![]);
+
+        // Chunks are closed even while another chunk is moved, and moved
+        // once there is no move in progress
+        let (tracker, registration) = registry.register(());
+        mover.move_tracker = Some(tracker);
+        mover.check_for_work(from_secs(10), Instant::now());
+        assert_eq!(
+            mover.events,
+            vec![MoverEvents::Rollover(1), MoverEvents::Rollover(2)]
+        );
+
+        std::mem::drop(registration);
+        mover.check_for_work(from_secs(11), Instant::now());
+        assert_eq!(
+            mover.events,
+            vec![
+                MoverEvents::Rollover(1),
+                MoverEvents::Rollover(2),
+                MoverEvents::Move(1)
+            ]
+        );
+
+        mover.check_for_work(from_secs(15), Instant::now());
+        assert_eq!(
+            mover.events,
+            vec![
+                MoverEvents::Rollover(1),
+                MoverEvents::Rollover(2),
+                MoverEvents::Move(1),
+                MoverEvents::Rollover(0),
+                MoverEvents::Move(0)
+            ]
+        );
+    }
+
     #[test]
     fn test_mutable_linger() {
         let rules = LifecycleRules {
@@ -900,6 +1122,32 @@ mod tests {
         assert_eq!(mover.events, vec![MoverEvents::Write(1)]);
     }
 
+    #[test]
+    fn test_persist_age() {
+        let rules = LifecycleRules {
+            persist: true,
+            persist_age_threshold_seconds: Some(NonZeroU32::new(60).unwrap()),
+            ..Default::default()
+        };
+
+        let rb = Arc::new(read_buffer::Chunk::new(
+            read_buffer::ChunkMetrics::new_unregistered(),
+        ));
+
+        let chunks = vec![
+            // too young to persist
+            transition_to_moved(new_chunk(0, Some(30), Some(30)), &rb),
+            // old enough => write to object store
+            transition_to_moved(new_chunk(1, Some(0), Some(40)), &rb),
+        ];
+
+        let mut mover = DummyMover::new(rules, chunks);
+
+        mover.check_for_work(from_secs(60), Instant::now());
+
+        assert_eq!(mover.events, vec![MoverEvents::Write(1)]);
+    }
+
     #[test]
     fn test_moves_closed() {
         let rules = LifecycleRules {
@@ -0,0 +1,234 @@
+//! Conversion of query results into line protocol, so that they can be
+//! written back into the database like any other write
+
+use std::fmt::Write;
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, BooleanArray, Float64Array, Int64Array, StringArray, UInt64Array},
+    compute::cast,
+    datatypes::DataType,
+    record_batch::RecordBatch,
+};
+use data_types::{database_rules::ShardConfig, timestamp::Precision};
+use entry::lines_to_sharded_entries;
+use influxdb_line_protocol::parse_lines;
+use internal_types::schema::TIME_COLUMN_NAME;
+use snafu::{ResultExt, Snafu};
+
+use super::Db;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error parsing line protocol: {}", source))]
+    LineProtocol {
+        source: influxdb_line_protocol::Error,
+    },
+
+    #[snafu(display("Error converting lines to entries: {}", source))]
+    EntryConversion { source: entry::Error },
+
+    #[snafu(display("Error storing entry: {}", source))]
+    StoringEntry { source: super::Error },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Parses `lines`, partitions them according to the rules of `db` and
+/// stores them. Lines without timestamp get `default_time`. Returns the
+/// number of lines written.
+pub async fn write_lines(db: &Db, lines: &str, default_time: i64) -> Result<usize> {
+    let lines = parse_lines(lines)
+        .collect::<Result<Vec<_>, _>>()
+        .context(LineProtocol)?;
+    if lines.is_empty() {
+        return Ok(0);
+    }
+
+    let sharded_entries = {
+        let rules = db.rules.read();
+        lines_to_sharded_entries(
+            &lines,
+            default_time,
+            Precision::Nanoseconds,
+            None as Option<&ShardConfig>,
+            &*rules,
+        )
+        .context(EntryConversion)?
+    };
+
+    for sharded_entry in sharded_entries {
+        db.store_entry(sharded_entry.entry)
+            .await
+            .context(StoringEntry)?;
+    }
+
+    Ok(lines.len())
+}
+
+/// Appends one line of line protocol to `out` for each row of `batch` that
+/// has at least one non-null field. Every column of `batch` that is neither
+/// a tag nor the time column is a field. If `batch` has no time column, the
+/// lines have no timestamp.
+pub fn batch_to_lines<S: AsRef<str>>(
+    out: &mut String,
+    measurement: &str,
+    batch: &RecordBatch,
+    tag_columns: &[S],
+) -> arrow::error::Result<()> {
+    let schema = batch.schema();
+    let column = |name: &str| -> arrow::error::Result<_> {
+        let idx = schema.index_of(name)?;
+        Ok(Arc::clone(batch.column(idx)))
+    };
+
+    let time = match schema.index_of(TIME_COLUMN_NAME) {
+        Ok(idx) => Some(cast(batch.column(idx), &DataType::Int64)?),
+        Err(_) => None,
+    };
+    let time = time
+        .as_ref()
+        .map(|time| time.as_any().downcast_ref::<Int64Array>().unwrap());
+
+    let tags = tag_columns
+        .iter()
+        .map(|name| {
+            let name = name.as_ref();
+            Ok((name, cast(&column(name)?, &DataType::Utf8)?))
+        })
+        .collect::<arrow::error::Result<Vec<_>>>()?;
+    let fields = schema
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .filter(|(field, _)| {
+            field.name() != TIME_COLUMN_NAME
+                && !tag_columns.iter().any(|tag| tag.as_ref() == field.name())
+        })
+        .map(|(field, values)| (field.name(), values))
+        .collect::<Vec<_>>();
+
+    for row in 0..batch.num_rows() {
+        let mut line = String::new();
+        escape(&mut line, measurement, &[',', ' ']);
+        for (name, values) in &tags {
+            let values = values.as_any().downcast_ref::<StringArray>().unwrap();
+            if values.is_valid(row) {
+                line.push(',');
+                escape(&mut line, name, &[',', '=', ' ']);
+                line.push('=');
+                escape(&mut line, values.value(row), &[',', '=', ' ']);
+            }
+        }
+
+        let mut separator = ' ';
+        for (name, values) in &fields {
+            if !values.is_valid(row) {
+                continue;
+            }
+            line.push(separator);
+            separator = ',';
+            escape(&mut line, name, &[',', '=', ' ']);
+            line.push('=');
+            write_field_value(&mut line, values.as_ref(), row)?;
+        }
+        if separator == ' ' {
+            continue;
+        }
+
+        match time {
+            Some(time) if time.is_valid(row) => writeln!(out, "{} {}", line, time.value(row)),
+            _ => writeln!(out, "{}", line),
+        }
+        .unwrap();
+    }
+
+    Ok(())
+}
+
+/// Appends the line protocol representation of the value at `row`
+fn write_field_value(out: &mut String, values: &dyn Array, row: usize) -> arrow::error::Result<()> {
+    let any = values.as_any();
+    match values.data_type() {
+        DataType::Float64 => {
+            let values = any.downcast_ref::<Float64Array>().unwrap();
+            write!(out, "{:?}", values.value(row)).unwrap();
+        }
+        DataType::Int64 => {
+            let values = any.downcast_ref::<Int64Array>().unwrap();
+            write!(out, "{}i", values.value(row)).unwrap();
+        }
+        DataType::UInt64 => {
+            let values = any.downcast_ref::<UInt64Array>().unwrap();
+            write!(out, "{}u", values.value(row)).unwrap();
+        }
+        DataType::Boolean => {
+            let values = any.downcast_ref::<BooleanArray>().unwrap();
+            write!(out, "{}", values.value(row)).unwrap();
+        }
+        DataType::Utf8 => {
+            let values = any.downcast_ref::<StringArray>().unwrap();
+            out.push('"');
+            escape(out, values.value(row), &['"', '\\']);
+            out.push('"');
+        }
+        data_type => {
+            return Err(arrow::error::ArrowError::InvalidArgumentError(format!(
+                "unsupported field type {:?}",
+                data_type
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// Appends `value` to `out`, escaping `delimiters` with a backslash
+fn escape(out: &mut String, value: &str, delimiters: &[char]) {
+    for c in value.chars() {
+        if delimiters.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{Field, Schema};
+
+    #[test]
+    fn to_lines() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("host", DataType::Utf8, true),
+            Field::new("load", DataType::Float64, true),
+            Field::new("status", DataType::Utf8, true),
+            Field::new("count", DataType::UInt64, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec![Some("a b"), None, Some("c")])),
+                Arc::new(Float64Array::from(vec![Some(1.0), Some(2.5), None])),
+                Arc::new(StringArray::from(vec![Some(r#"o"k"#), None, None])),
+                Arc::new(UInt64Array::from(vec![Some(3), None, None])),
+            ],
+        )
+        .unwrap();
+
+        let mut out = String::new();
+        batch_to_lines(&mut out, "cpu load", &batch, &["host"]).unwrap();
+        assert_eq!(
+            out,
+            "cpu\\ load,host=a\\ b load=1.0,status=\"o\\\"k\",count=3u\n\
+             cpu\\ load load=2.5\n"
+        );
+    }
+
+    #[test]
+    fn escaping() {
+        let mut out = String::new();
+        escape(&mut out, r#"a b,c="d"#, &[',', '=', ' ']);
+        assert_eq!(out, r#"a\ b\,c\="d"#);
+    }
+}
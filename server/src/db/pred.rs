@@ -3,7 +3,10 @@
 
 use std::convert::TryFrom;
 
-use query::predicate::Predicate;
+use data_types::delete_predicate::DeletePredicate;
+use datafusion::{logical_plan::Expr, scalar::ScalarValue};
+use internal_types::schema::{InfluxColumnType, InfluxFieldType, Schema};
+use query::{func::regex::regex_match_parts, predicate::Predicate};
 use snafu::Snafu;
 
 #[derive(Debug, Snafu)]
@@ -22,7 +25,12 @@ pub fn to_read_buffer_predicate(predicate: &Predicate) -> Result<read_buffer::Pr
     match predicate
         .exprs
         .iter()
-        .map(read_buffer::BinaryExpr::try_from)
+        .map(|expr| match regex_match_parts(expr) {
+            Some((column, pattern, matches)) => {
+                read_buffer::BinaryExpr::regex_match(column, pattern, matches)
+            }
+            None => read_buffer::BinaryExpr::try_from(expr),
+        })
         .collect::<Result<Vec<_>, _>>()
     {
         Ok(exprs) => {
@@ -42,13 +50,137 @@ pub fn to_read_buffer_predicate(predicate: &Predicate) -> Result<read_buffer::Pr
     }
 }
 
+/// Converts the expressions of a [`query::predicate::Predicate`] that the
+/// ReadBuffer can evaluate on the columns of `schema` into a
+/// [`read_buffer::Predicate`], so that only the matching rows of a chunk are
+/// materialised.
+///
+/// Comparisons of field columns are pushed down with their literal converted
+/// to the type of the column, when it can be done without loss. Unlike
+/// [`to_read_buffer_predicate`] the expressions that can't be evaluated are
+/// left out, so the result may match more rows than `predicate` and must
+/// only be used where `predicate` is applied again to the rows read.
+pub fn to_read_buffer_pushdown_predicate(
+    predicate: &Predicate,
+    schema: &Schema,
+) -> read_buffer::Predicate {
+    let exprs: Vec<_> = predicate
+        .exprs
+        .iter()
+        .filter_map(|expr| match regex_match_parts(expr) {
+            Some((column, pattern, matches)) => {
+                read_buffer::BinaryExpr::regex_match(column, pattern, matches).ok()
+            }
+            None => {
+                let expr = coerce_literal(expr, schema)?;
+                read_buffer::BinaryExpr::try_from(&expr).ok()
+            }
+        })
+        .collect();
+
+    match predicate.range {
+        Some(range) => read_buffer::Predicate::with_time_range(&exprs, range.start, range.end),
+        None => read_buffer::Predicate::new(exprs),
+    }
+}
+
+/// Converts a [`DeletePredicate`] into the [`read_buffer::Predicate`] matching
+/// the rows it deletes, to be excluded from the rows read from a chunk.
+pub fn to_read_buffer_negated_predicate(predicate: &DeletePredicate) -> read_buffer::Predicate {
+    let exprs: Vec<_> = predicate
+        .tags
+        .iter()
+        .map(|(tag, value)| read_buffer::BinaryExpr::from((tag.as_str(), "=", value.as_str())))
+        .collect();
+
+    read_buffer::Predicate::with_time_range(&exprs, predicate.range.start, predicate.range.end)
+}
+
+/// Returns `expr` with the literal it compares a column to converted to the
+/// type of that column, or `None` if this can't be done without loss
+fn coerce_literal(expr: &Expr, schema: &Schema) -> Option<Expr> {
+    let column_type = |name: &str| {
+        let idx = schema.find_index_of(name)?;
+        schema.field(idx).0
+    };
+
+    match expr {
+        Expr::BinaryExpr { left, op, right } => match (left.as_ref(), right.as_ref()) {
+            (Expr::Column(name), Expr::Literal(value)) => Some(Expr::BinaryExpr {
+                left: left.clone(),
+                op: *op,
+                right: Box::new(Expr::Literal(coerce_scalar(value, column_type(name)?)?)),
+            }),
+            (Expr::Literal(value), Expr::Column(name)) => Some(Expr::BinaryExpr {
+                left: Box::new(Expr::Literal(coerce_scalar(value, column_type(name)?)?)),
+                op: *op,
+                right: right.clone(),
+            }),
+            _ => None,
+        },
+        expr => Some(expr.clone()),
+    }
+}
+
+/// The largest magnitude up to which every integer is exactly representable
+/// as a 64-bit float
+const MAX_EXACT_FLOAT_INTEGER: u64 = 1 << 53;
+
+/// Converts `value` to the type of a column of `column_type`, if it can be
+/// done without loss
+fn coerce_scalar(value: &ScalarValue, column_type: InfluxColumnType) -> Option<ScalarValue> {
+    let exact_integer = |v: f64| v.fract() == 0.0 && v.abs() <= MAX_EXACT_FLOAT_INTEGER as f64;
+
+    match (column_type, value) {
+        (InfluxColumnType::Tag, ScalarValue::Utf8(Some(_)))
+        | (InfluxColumnType::Field(InfluxFieldType::String), ScalarValue::Utf8(Some(_)))
+        | (InfluxColumnType::Field(InfluxFieldType::Boolean), ScalarValue::Boolean(Some(_)))
+        | (InfluxColumnType::Field(InfluxFieldType::Float), ScalarValue::Float64(Some(_)))
+        | (InfluxColumnType::Field(InfluxFieldType::Integer), ScalarValue::Int64(Some(_)))
+        | (InfluxColumnType::Field(InfluxFieldType::UInteger), ScalarValue::UInt64(Some(_)))
+        | (InfluxColumnType::Timestamp, ScalarValue::Int64(Some(_)))
+        | (InfluxColumnType::Timestamp, ScalarValue::TimestampNanosecond(Some(_))) => {
+            Some(value.clone())
+        }
+        (InfluxColumnType::Field(InfluxFieldType::Float), ScalarValue::Int64(Some(v)))
+            if v.unsigned_abs() <= MAX_EXACT_FLOAT_INTEGER =>
+        {
+            Some(ScalarValue::Float64(Some(*v as f64)))
+        }
+        (InfluxColumnType::Field(InfluxFieldType::Float), ScalarValue::UInt64(Some(v)))
+            if *v <= MAX_EXACT_FLOAT_INTEGER =>
+        {
+            Some(ScalarValue::Float64(Some(*v as f64)))
+        }
+        (InfluxColumnType::Field(InfluxFieldType::Integer), ScalarValue::UInt64(Some(v))) => {
+            i64::try_from(*v).ok().map(|v| ScalarValue::Int64(Some(v)))
+        }
+        (InfluxColumnType::Field(InfluxFieldType::Integer), ScalarValue::Float64(Some(v)))
+            if exact_integer(*v) =>
+        {
+            Some(ScalarValue::Int64(Some(*v as i64)))
+        }
+        (InfluxColumnType::Field(InfluxFieldType::UInteger), ScalarValue::Int64(Some(v))) => {
+            u64::try_from(*v).ok().map(|v| ScalarValue::UInt64(Some(v)))
+        }
+        (InfluxColumnType::Field(InfluxFieldType::UInteger), ScalarValue::Float64(Some(v)))
+            if exact_integer(*v) && *v >= 0.0 =>
+        {
+            Some(ScalarValue::UInt64(Some(*v as u64)))
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 pub mod test {
     use super::*;
+    use data_types::timestamp::TimestampRange;
     use datafusion::logical_plan::Expr;
     use datafusion::scalar::ScalarValue;
 
-    use query::predicate::PredicateBuilder;
+    use internal_types::schema::builder::SchemaBuilder;
+    use query::{func::regex::regex_match_expr, predicate::PredicateBuilder};
     use read_buffer::BinaryExpr as RBBinaryExpr;
     use read_buffer::Predicate as RBPredicate;
 
@@ -110,6 +242,27 @@ pub mod test {
                     2000,
                 ),
             ),
+            // regex match expressions
+            (
+                PredicateBuilder::default()
+                    .build_regex_match_expr("track", "^Star")
+                    .build_regex_not_match_expr("track", "Rov")
+                    .build(),
+                RBPredicate::new(vec![
+                    RBBinaryExpr::regex_match("track", "^Star", true).unwrap(),
+                    RBBinaryExpr::regex_match("track", "Rov", false).unwrap(),
+                ]),
+            ),
+            // IN list expressions
+            (
+                PredicateBuilder::default()
+                    .build_in_list_expr("track", vec!["Star Roving", "Intermezzo 1"])
+                    .build(),
+                RBPredicate::new(vec![RBBinaryExpr::in_list(
+                    "track",
+                    vec!["Star Roving", "Intermezzo 1"],
+                )]),
+            ),
         ];
 
         for (predicate, exp) in cases {
@@ -160,6 +313,17 @@ pub mod test {
                     .build(),
                 "NULL literal not supported",
             ),
+            // IN list values must be strings
+            (
+                PredicateBuilder::default()
+                    .add_expr(Expr::InList {
+                        expr: Box::new(Expr::Column("counter".to_owned())),
+                        list: vec![Expr::Literal(ScalarValue::Int64(Some(2992)))],
+                        negated: false,
+                    })
+                    .build(),
+                "unsupported IN list value Int64(2992)",
+            ),
         ];
 
         for (predicate, exp) in cases {
@@ -169,5 +333,112 @@ pub mod test {
                 }
             }
         }
+
+        // regex pattern must be valid
+        let predicate = PredicateBuilder::default()
+            .add_expr(regex_match_expr(
+                Expr::Column("track".to_owned()),
+                "Star(".to_owned(),
+                true,
+            ))
+            .build();
+        match to_read_buffer_predicate(&predicate).unwrap_err() {
+            Error::ReadBufferPredicate { msg, pred: _ } => {
+                assert!(
+                    msg.starts_with("invalid regex pattern \"Star(\""),
+                    "{}",
+                    msg
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn into_read_buffer_pushdown_predicate() {
+        let schema = SchemaBuilder::new()
+            .tag("track")
+            .influx_field("temp", InfluxFieldType::Float)
+            .influx_field("counter", InfluxFieldType::Integer)
+            .influx_field("plays", InfluxFieldType::UInteger)
+            .timestamp()
+            .build()
+            .unwrap();
+
+        let predicate = PredicateBuilder::default()
+            .timestamp_range(100, 2000)
+            .add_expr(
+                Expr::Column("track".to_owned()).eq(Expr::Literal(ScalarValue::Utf8(Some(
+                    "Star Roving".to_owned(),
+                )))),
+            )
+            // literals are converted to the type of the field
+            .add_expr(
+                Expr::Column("temp".to_owned()).gt(Expr::Literal(ScalarValue::Int64(Some(70)))),
+            )
+            .add_expr(
+                Expr::Literal(ScalarValue::Float64(Some(10.0)))
+                    .lt_eq(Expr::Column("counter".to_owned())),
+            )
+            .add_expr(
+                Expr::Column("plays".to_owned()).lt(Expr::Literal(ScalarValue::Int64(Some(3)))),
+            )
+            // not exactly representable in the type of the field
+            .add_expr(
+                Expr::Column("counter".to_owned())
+                    .lt(Expr::Literal(ScalarValue::Float64(Some(20.5)))),
+            )
+            .add_expr(
+                Expr::Column("plays".to_owned()).gt(Expr::Literal(ScalarValue::Int64(Some(-1)))),
+            )
+            // not a column of the chunk
+            .add_expr(
+                Expr::Column("rating".to_owned()).gt(Expr::Literal(ScalarValue::Int64(Some(3)))),
+            )
+            // not supported by the read buffer
+            .add_expr(
+                Expr::Column("track".to_owned())
+                    .like(Expr::Literal(ScalarValue::Utf8(Some("Star%".to_owned())))),
+            )
+            .build();
+
+        assert_eq!(
+            to_read_buffer_pushdown_predicate(&predicate, &schema),
+            RBPredicate::with_time_range(
+                &[
+                    RBBinaryExpr::from(("track", "=", "Star Roving")),
+                    RBBinaryExpr::from(("temp", ">", 70.0_f64)),
+                    RBBinaryExpr::from(("counter", ">=", 10_i64)),
+                    RBBinaryExpr::from(("plays", "<", 3_u64)),
+                ],
+                100,
+                2000,
+            )
+        );
+    }
+
+    #[test]
+    fn to_negated_predicate() {
+        let predicate = DeletePredicate {
+            table_name: "cpu".to_string(),
+            range: TimestampRange::new(100, 2000),
+            tags: vec![
+                ("host".to_string(), "a".to_string()),
+                ("region".to_string(), "west".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        assert_eq!(
+            to_read_buffer_negated_predicate(&predicate),
+            RBPredicate::with_time_range(
+                &[
+                    RBBinaryExpr::from(("host", "=", "a")),
+                    RBBinaryExpr::from(("region", "=", "west")),
+                ],
+                100,
+                2000,
+            )
+        );
     }
 }
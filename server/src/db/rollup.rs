@@ -0,0 +1,262 @@
+//! Periodic aggregation of tables into other tables, as configured by the
+//! rollup rules of a database
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use data_types::database_rules::{RollupAggregate, RollupRule};
+use observability_deps::tracing::{debug, error};
+use query::{
+    frontend::influxrpc::InfluxRpcPlanner,
+    group_by::{Aggregate, WindowDuration},
+    predicate::PredicateBuilder,
+};
+use snafu::{ResultExt, Snafu};
+
+use super::{
+    lines::{batch_to_lines, write_lines},
+    Db,
+};
+
+/// How long to wait between two checks for windows to aggregate
+pub const ROLLUP_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error planning rollup of {}: {}", table_name, source))]
+    Planning {
+        table_name: String,
+        source: query::frontend::influxrpc::Error,
+    },
+
+    #[snafu(display("Error executing rollup of {}: {}", table_name, source))]
+    Execution {
+        table_name: String,
+        source: datafusion::error::DataFusionError,
+    },
+
+    #[snafu(display("Error converting rollup result of {}: {}", table_name, source))]
+    Conversion {
+        table_name: String,
+        source: arrow::error::ArrowError,
+    },
+
+    #[snafu(display("Error writing rollup of {}: {}", table_name, source))]
+    Writing {
+        table_name: String,
+        source: super::lines::Error,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Aggregates the windows of the source tables of the rollup rules of a Db
+/// into their target tables once the windows have ended.
+///
+/// The aggregated rows are written like any other write, so they are
+/// persisted by the lifecycle policy of the Db.
+///
+/// The progress of each rule is only kept in memory: after a restart, all
+/// windows still stored in the source table are aggregated again, replacing
+/// the rows already in the target table.
+pub struct RollupManager {
+    db: Arc<Db>,
+
+    /// The end of the last aggregated window, by source and target table
+    progress: HashMap<(String, String), i64>,
+}
+
+impl RollupManager {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self {
+            db,
+            progress: HashMap::new(),
+        }
+    }
+
+    /// Aggregates the windows that have ended and waits for
+    /// [`ROLLUP_CHECK_INTERVAL`](ROLLUP_CHECK_INTERVAL)
+    pub async fn check_for_work(&mut self) {
+        let now = Utc::now().timestamp_nanos();
+        if let Err(e) = self.rollup(now).await {
+            error!(%e, "error in background rollup task");
+        }
+
+        tokio::time::sleep(ROLLUP_CHECK_INTERVAL).await
+    }
+
+    /// Aggregates every window of every rule that ended at least the rule's
+    /// delay before `now` and has not been aggregated yet. Returns the
+    /// number of rows written.
+    pub async fn rollup(&mut self, now: i64) -> Result<usize> {
+        let rules = self.db.rules.read().rollup_rules.clone();
+
+        let mut rows = 0;
+        for rule in rules {
+            let window = rule.window.as_nanos().min(i64::MAX as u128) as i64;
+            let delay = rule.delay.as_nanos().min(i64::MAX as u128) as i64;
+
+            // only aggregate complete windows
+            let end = now.saturating_sub(delay).div_euclid(window) * window;
+
+            let key = (rule.source_table.clone(), rule.target_table.clone());
+            let start = self.progress.get(&key).copied().unwrap_or(i64::MIN);
+            if start >= end {
+                continue;
+            }
+
+            rows += self.rollup_range(&rule, start, end).await?;
+            self.progress.insert(key, end);
+        }
+
+        Ok(rows)
+    }
+
+    /// Aggregates the rows of the source table of `rule` with a timestamp
+    /// in `[start, end)` and writes the result to the target table
+    async fn rollup_range(&self, rule: &RollupRule, start: i64, end: i64) -> Result<usize> {
+        let table_name = &rule.source_table;
+        let window = WindowDuration::from_nanoseconds(rule.window.as_nanos() as i64);
+
+        let predicate = PredicateBuilder::new()
+            .table(table_name)
+            .timestamp_range(start, end)
+            .build();
+
+        let plans = InfluxRpcPlanner::new()
+            .read_window_aggregate(
+                self.db.as_ref(),
+                predicate,
+                aggregate(rule.aggregate),
+                window,
+                WindowDuration::from_nanoseconds(0),
+            )
+            .context(Planning { table_name })?;
+
+        let executor = self.db.executor();
+        let mut lines = String::new();
+        for plan in plans.plans {
+            let ctx = executor.new_context();
+            let physical_plan = ctx
+                .prepare_plan(&plan.plan)
+                .context(Execution { table_name })?;
+            let batches = ctx
+                .collect(physical_plan)
+                .await
+                .context(Execution { table_name })?;
+
+            for batch in batches {
+                batch_to_lines(&mut lines, &rule.target_table, &batch, &plan.tag_columns)
+                    .context(Conversion { table_name })?;
+            }
+        }
+
+        let rows = write_lines(&self.db, &lines, end)
+            .await
+            .context(Writing { table_name })?;
+
+        debug!(%table_name, target_table=%rule.target_table, start, end, rows, "rolled up table");
+        Ok(rows)
+    }
+}
+
+fn aggregate(aggregate: RollupAggregate) -> Aggregate {
+    match aggregate {
+        RollupAggregate::Mean => Aggregate::Mean,
+        RollupAggregate::Min => Aggregate::Min,
+        RollupAggregate::Max => Aggregate::Max,
+        RollupAggregate::Sum => Aggregate::Sum,
+        RollupAggregate::Count => Aggregate::Count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::test_helpers::write_lp, utils::make_db};
+    use arrow::record_batch::RecordBatch;
+    use arrow_util::assert_batches_sorted_eq;
+    use query::frontend::sql::SqlQueryPlanner;
+
+    async fn run_query(db: Arc<Db>, query: &str) -> Vec<RecordBatch> {
+        let planner = SqlQueryPlanner::default();
+        let executor = db.executor();
+        let physical_plan = planner.query(db, query, &executor).unwrap();
+        executor.collect(physical_plan).await.unwrap()
+    }
+
+    fn rule(aggregate: RollupAggregate) -> RollupRule {
+        RollupRule {
+            source_table: "cpu".to_string(),
+            target_table: "cpu_10s".to_string(),
+            window: Duration::from_nanos(10),
+            aggregate,
+            delay: Duration::from_nanos(0),
+        }
+    }
+
+    #[tokio::test]
+    async fn rollup_mean() {
+        let db = Arc::new(make_db().await.db);
+        db.rules.write().rollup_rules = vec![rule(RollupAggregate::Mean)];
+
+        write_lp(
+            &db,
+            "cpu,region=west user=1,idle=10i 1\n\
+             cpu,region=west user=3,idle=20i 9\n\
+             cpu,region=east user=5 5\n\
+             cpu,region=west user=7 12\n\
+             cpu,region=west user=100 25",
+        )
+        .await;
+
+        let mut manager = RollupManager::new(Arc::clone(&db));
+
+        // only the windows that ended before 20 are aggregated
+        assert_eq!(manager.rollup(21).await.unwrap(), 3);
+
+        let batches = run_query(Arc::clone(&db), "select * from cpu_10s").await;
+        let expected = vec![
+            "+------+--------+-------------------------------+------+",
+            "| idle | region | time                          | user |",
+            "+------+--------+-------------------------------+------+",
+            "|      | east   | 1970-01-01 00:00:00.000000010 | 5    |",
+            "|      | west   | 1970-01-01 00:00:00.000000020 | 7    |",
+            "| 15   | west   | 1970-01-01 00:00:00.000000010 | 2    |",
+            "+------+--------+-------------------------------+------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        // nothing new to aggregate
+        assert_eq!(manager.rollup(29).await.unwrap(), 0);
+
+        assert_eq!(manager.rollup(30).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn rollup_delay() {
+        let db = Arc::new(make_db().await.db);
+        db.rules.write().rollup_rules = vec![RollupRule {
+            delay: Duration::from_nanos(5),
+            ..rule(RollupAggregate::Count)
+        }];
+
+        write_lp(&db, "cpu,region=west user=1 1\ncpu,region=west user=3 9").await;
+
+        let mut manager = RollupManager::new(Arc::clone(&db));
+        assert_eq!(manager.rollup(12).await.unwrap(), 0);
+        assert_eq!(manager.rollup(15).await.unwrap(), 1);
+
+        let batches = run_query(Arc::clone(&db), "select * from cpu_10s").await;
+        let expected = vec![
+            "+--------+-------------------------------+------+",
+            "| region | time                          | user |",
+            "+--------+-------------------------------+------+",
+            "| west   | 1970-01-01 00:00:00.000000010 | 2    |",
+            "+--------+-------------------------------+------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+    }
+}
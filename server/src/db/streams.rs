@@ -1,10 +1,24 @@
 //! Adapter streams for different Chunk types that implement the interface
 //! needed by DataFusion
-use arrow::{datatypes::SchemaRef, error::Result as ArrowResult, record_batch::RecordBatch};
-use datafusion::physical_plan::RecordBatchStream;
+use arrow::{
+    array::{
+        Array, ArrayRef, BooleanArray, DictionaryArray, Int32Array, StringArray,
+        TimestampNanosecondArray,
+    },
+    compute::{cast, filter_record_batch},
+    datatypes::{DataType, Int32Type, SchemaRef},
+    error::{ArrowError, Result as ArrowResult},
+    record_batch::RecordBatch,
+};
+use data_types::delete_predicate::DeletePredicate;
+use datafusion::physical_plan::{RecordBatchStream, SendableRecordBatchStream};
+use futures::StreamExt;
+use internal_types::schema::TIME_COLUMN_NAME;
 use read_buffer::ReadFilterResults;
 
 use std::{
+    collections::HashMap,
+    convert::TryFrom,
     sync::Arc,
     task::{Context, Poll},
 };
@@ -42,3 +56,307 @@ impl futures::Stream for ReadFilterResultsStream {
 
     // TODO is there a useful size_hint to pass?
 }
+
+/// Adapter which removes the rows matching any of the delete predicates from
+/// the batches of another stream and projects them onto `schema`
+///
+/// The batches of the inner stream must contain the time column and all
+/// columns of `schema`.
+pub struct DeletedRowsFilterStream {
+    inner: SendableRecordBatchStream,
+    schema: SchemaRef,
+    delete_predicates: Vec<Arc<DeletePredicate>>,
+}
+
+impl DeletedRowsFilterStream {
+    pub fn new(
+        inner: SendableRecordBatchStream,
+        schema: SchemaRef,
+        delete_predicates: Vec<Arc<DeletePredicate>>,
+    ) -> Self {
+        Self {
+            inner,
+            schema,
+            delete_predicates,
+        }
+    }
+
+    fn filter(&self, batch: RecordBatch) -> ArrowResult<RecordBatch> {
+        let batch = remove_deleted_rows(&batch, &self.delete_predicates)?;
+
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let idx = batch.schema().index_of(field.name())?;
+                Ok(Arc::clone(batch.column(idx)))
+            })
+            .collect::<ArrowResult<Vec<_>>>()?;
+
+        RecordBatch::try_new(Arc::clone(&self.schema), columns)
+    }
+}
+
+impl RecordBatchStream for DeletedRowsFilterStream {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+}
+
+impl futures::Stream for DeletedRowsFilterStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let next = self.inner.poll_next_unpin(cx);
+        next.map(|batch| batch.map(|batch| batch.and_then(|batch| self.filter(batch))))
+    }
+}
+
+/// Returns `batch` without the rows that match any of `delete_predicates`
+///
+/// A tag column missing from the batch is treated as null, so that the
+/// predicates referring to it do not match any row.
+fn remove_deleted_rows(
+    batch: &RecordBatch,
+    delete_predicates: &[Arc<DeletePredicate>],
+) -> ArrowResult<RecordBatch> {
+    if delete_predicates.is_empty() || batch.num_rows() == 0 {
+        return Ok(batch.clone());
+    }
+    let schema = batch.schema();
+
+    let time_idx = schema.index_of(TIME_COLUMN_NAME)?;
+    let time = batch
+        .column(time_idx)
+        .as_any()
+        .downcast_ref::<TimestampNanosecondArray>()
+        .ok_or_else(|| {
+            ArrowError::InvalidArgumentError(format!(
+                "column {} is not a nanosecond timestamp",
+                TIME_COLUMN_NAME
+            ))
+        })?;
+
+    // values of all tag columns referenced by the predicates
+    let mut tags = HashMap::new();
+    for predicate in delete_predicates {
+        for tag in predicate.tags.keys() {
+            if tags.contains_key(tag.as_str()) {
+                continue;
+            }
+            if let Ok(idx) = schema.index_of(tag) {
+                tags.insert(tag.as_str(), TagValues::try_new(batch.column(idx))?);
+            }
+        }
+    }
+
+    // resolve the tag values of each predicate once, rather than for each row
+    let predicates: Vec<_> = delete_predicates
+        .iter()
+        .map(|predicate| {
+            let matchers: Vec<_> = predicate
+                .tags
+                .iter()
+                .map(|(tag, value)| match tags.get(tag.as_str()) {
+                    Some(values) => values.matcher(value),
+                    None => TagMatcher::Never,
+                })
+                .collect();
+            (&predicate.range, matchers)
+        })
+        .collect();
+
+    let keep: BooleanArray = (0..batch.num_rows())
+        .map(|row| {
+            let deleted = predicates.iter().any(|(range, matchers)| {
+                range.contains(time.value(row)) && matchers.iter().all(|m| m.matches(row))
+            });
+            Some(!deleted)
+        })
+        .collect();
+
+    filter_record_batch(batch, &keep)
+}
+
+/// The values of a tag column
+enum TagValues<'a> {
+    Dictionary {
+        keys: &'a Int32Array,
+        values: &'a StringArray,
+    },
+    Strings(StringArray),
+}
+
+impl<'a> TagValues<'a> {
+    fn try_new(array: &'a ArrayRef) -> ArrowResult<Self> {
+        if let Some(dictionary) = array.as_any().downcast_ref::<DictionaryArray<Int32Type>>() {
+            if let Some(values) = dictionary.values().as_any().downcast_ref::<StringArray>() {
+                return Ok(Self::Dictionary {
+                    keys: dictionary.keys(),
+                    values,
+                });
+            }
+        }
+
+        let strings = cast(array, &DataType::Utf8)?;
+        Ok(Self::Strings(StringArray::from(strings.data().clone())))
+    }
+
+    /// Returns a matcher for the rows holding `value`. For dictionaries the
+    /// value is looked up once, so that only keys are compared for each row.
+    fn matcher<'b>(&'b self, value: &'b str) -> TagMatcher<'b> {
+        match self {
+            Self::Dictionary { keys, values } => {
+                let matching_keys = (0..values.len())
+                    .map(|key| values.is_valid(key) && values.value(key) == value)
+                    .collect();
+                TagMatcher::Keys {
+                    keys,
+                    matching_keys,
+                }
+            }
+            Self::Strings(values) => TagMatcher::Value { values, value },
+        }
+    }
+}
+
+/// Finds the rows of a batch that hold a given tag value
+enum TagMatcher<'a> {
+    /// The value is the one of the dictionary keys set in `matching_keys`
+    Keys {
+        keys: &'a Int32Array,
+        matching_keys: Vec<bool>,
+    },
+    Value {
+        values: &'a StringArray,
+        value: &'a str,
+    },
+    /// The column is missing from the batch
+    Never,
+}
+
+impl TagMatcher<'_> {
+    fn matches(&self, row: usize) -> bool {
+        match self {
+            Self::Keys {
+                keys,
+                matching_keys,
+            } => {
+                keys.is_valid(row)
+                    && usize::try_from(keys.value(row))
+                        .ok()
+                        .and_then(|key| matching_keys.get(key).copied())
+                        .unwrap_or(false)
+            }
+            Self::Value { values, value } => values.is_valid(row) && values.value(row) == *value,
+            Self::Never => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::Float64Array;
+    use arrow_util::assert_batches_eq;
+    use data_types::timestamp::TimestampRange;
+    use internal_types::schema::builder::SchemaBuilder;
+
+    use super::*;
+
+    #[test]
+    fn remove_deleted() {
+        let schema = SchemaBuilder::new()
+            .tag("host")
+            .field("usage", DataType::Float64)
+            .timestamp()
+            .build()
+            .unwrap();
+        let batch = RecordBatch::try_new(
+            schema.as_arrow(),
+            vec![
+                Arc::new(
+                    vec![Some("a"), Some("b"), None, Some("a")]
+                        .into_iter()
+                        .collect::<DictionaryArray<Int32Type>>(),
+                ),
+                Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0, 4.0])),
+                Arc::new(TimestampNanosecondArray::from(vec![10, 10, 10, 20])),
+            ],
+        )
+        .unwrap();
+
+        let delete_host_a = Arc::new(DeletePredicate {
+            table_name: "cpu".to_string(),
+            range: TimestampRange::new(0, 15),
+            tags: vec![("host".to_string(), "a".to_string())]
+                .into_iter()
+                .collect(),
+        });
+        let delete_region = Arc::new(DeletePredicate {
+            table_name: "cpu".to_string(),
+            range: TimestampRange::new(0, 100),
+            tags: vec![("region".to_string(), "west".to_string())]
+                .into_iter()
+                .collect(),
+        });
+        let delete_late = Arc::new(DeletePredicate {
+            table_name: "cpu".to_string(),
+            range: TimestampRange::new(20, 30),
+            tags: Default::default(),
+        });
+
+        let result = remove_deleted_rows(&batch, &[]).unwrap();
+        assert_eq!(result.num_rows(), 4);
+
+        let result = remove_deleted_rows(
+            &batch,
+            &[
+                Arc::clone(&delete_host_a),
+                Arc::clone(&delete_region),
+                Arc::clone(&delete_late),
+            ],
+        )
+        .unwrap();
+        let expected = vec![
+            "+------+-------+-------------------------------+",
+            "| host | usage | time                          |",
+            "+------+-------+-------------------------------+",
+            "| b    | 2     | 1970-01-01 00:00:00.000000010 |",
+            "|      | 3     | 1970-01-01 00:00:00.000000010 |",
+            "+------+-------+-------------------------------+",
+        ];
+        assert_batches_eq!(expected, &[result]);
+
+        // tag columns that are not dictionary encoded
+        let batch = RecordBatch::try_new(
+            Arc::new(arrow::datatypes::Schema::new(vec![
+                arrow::datatypes::Field::new("host", DataType::Utf8, true),
+                arrow::datatypes::Field::new(
+                    TIME_COLUMN_NAME,
+                    DataType::Timestamp(arrow::datatypes::TimeUnit::Nanosecond, None),
+                    false,
+                ),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec![Some("a"), None, Some("b")])),
+                Arc::new(TimestampNanosecondArray::from(vec![10, 10, 10])),
+            ],
+        )
+        .unwrap();
+
+        let result = remove_deleted_rows(&batch, &[delete_host_a]).unwrap();
+        let expected = vec![
+            "+------+-------------------------------+",
+            "| host | time                          |",
+            "+------+-------------------------------+",
+            "|      | 1970-01-01 00:00:00.000000010 |",
+            "| b    | 1970-01-01 00:00:00.000000010 |",
+            "+------+-------------------------------+",
+        ];
+        assert_batches_eq!(expected, &[result]);
+    }
+}
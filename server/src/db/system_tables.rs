@@ -1,4 +1,4 @@
-//! Contains implementation of IOx name: (), stats: () system table stats: () stats: ()es (aka tables in the `system` schema)
+//! Contains implementation of IOx system tables (aka tables in the `system` schema)
 //!
 //! For example `SELECT * FROM system.chunks`
 
@@ -19,7 +19,7 @@ use arrow::{
     record_batch::RecordBatch,
 };
 use data_types::{
-    chunk_metadata::{ChunkSummary, DetailedChunkSummary},
+    chunk_metadata::{ChunkColumnSummary, ChunkSummary, DetailedChunkSummary},
     error::ErrorLogger,
     job::Job,
     partition_metadata::{PartitionSummary, UnaggregatedPartitionSummary},
@@ -40,12 +40,14 @@ pub const SYSTEM_SCHEMA: &str = "system";
 
 const CHUNKS: &str = "chunks";
 const COLUMNS: &str = "columns";
+const PARTITIONS: &str = "partitions";
 const CHUNK_COLUMNS: &str = "chunk_columns";
 const OPERATIONS: &str = "operations";
 
 pub struct SystemSchemaProvider {
     chunks: Arc<dyn TableProvider>,
     columns: Arc<dyn TableProvider>,
+    partitions: Arc<dyn TableProvider>,
     chunk_columns: Arc<dyn TableProvider>,
     operations: Arc<dyn TableProvider>,
 }
@@ -67,6 +69,9 @@ impl SystemSchemaProvider {
         let columns = Arc::new(SystemTableProvider {
             inner: ColumnsTable::new(Arc::clone(&catalog)),
         });
+        let partitions = Arc::new(SystemTableProvider {
+            inner: PartitionsTable::new(Arc::clone(&catalog)),
+        });
         let chunk_columns = Arc::new(SystemTableProvider {
             inner: ChunkColumnsTable::new(catalog),
         });
@@ -76,6 +81,7 @@ impl SystemSchemaProvider {
         Self {
             chunks,
             columns,
+            partitions,
             chunk_columns,
             operations,
         }
@@ -91,6 +97,7 @@ impl SchemaProvider for SystemSchemaProvider {
         vec![
             CHUNKS.to_string(),
             COLUMNS.to_string(),
+            PARTITIONS.to_string(),
             CHUNK_COLUMNS.to_string(),
             OPERATIONS.to_string(),
         ]
@@ -100,6 +107,7 @@ impl SchemaProvider for SystemSchemaProvider {
         match name {
             CHUNKS => Some(Arc::clone(&self.chunks)),
             COLUMNS => Some(Arc::clone(&self.columns)),
+            PARTITIONS => Some(Arc::clone(&self.partitions)),
             CHUNK_COLUMNS => Some(Arc::clone(&self.chunk_columns)),
             OPERATIONS => Some(Arc::clone(&self.operations)),
             _ => None,
@@ -313,6 +321,109 @@ fn from_partition_summaries(
     )
 }
 
+/// Implementation of `system.partitions` system table
+#[derive(Debug)]
+struct PartitionsTable {
+    schema: SchemaRef,
+    catalog: Arc<Catalog>,
+}
+
+impl PartitionsTable {
+    fn new(catalog: Arc<Catalog>) -> Self {
+        Self {
+            schema: partitions_schema(),
+            catalog,
+        }
+    }
+}
+
+impl IoxSystemTable for PartitionsTable {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn batch(&self) -> Result<RecordBatch> {
+        let partitions = self
+            .catalog
+            .partitions()
+            .map(|partition| {
+                let partition = partition.read();
+                PartitionRow {
+                    key: partition.key().to_string(),
+                    created_at: partition.created_at(),
+                    last_write_at: partition.last_write_at(),
+                    chunks: partition.chunk_summaries().collect(),
+                }
+            })
+            .collect();
+
+        from_partitions(self.schema(), partitions).log_if_error("system.partitions table")
+    }
+}
+
+/// The state of a partition that is listed in `system.partitions`
+#[derive(Debug)]
+struct PartitionRow {
+    key: String,
+    created_at: DateTime<Utc>,
+    last_write_at: DateTime<Utc>,
+    chunks: Vec<ChunkSummary>,
+}
+
+fn partitions_schema() -> SchemaRef {
+    let ts = DataType::Timestamp(TimeUnit::Nanosecond, None);
+    Arc::new(Schema::new(vec![
+        Field::new("partition_key", DataType::Utf8, false),
+        Field::new("table_count", DataType::UInt64, false),
+        Field::new("chunk_count", DataType::UInt64, false),
+        Field::new("estimated_bytes", DataType::UInt64, false),
+        Field::new("row_count", DataType::UInt64, false),
+        Field::new("created_at", ts.clone(), false),
+        Field::new("last_write_at", ts, false),
+    ]))
+}
+
+fn from_partitions(schema: SchemaRef, partitions: Vec<PartitionRow>) -> Result<RecordBatch> {
+    let partition_key = StringArray::from_iter(partitions.iter().map(|p| Some(p.key.as_str())));
+    let table_count = UInt64Array::from_iter(partitions.iter().map(|p| {
+        let mut table_names: Vec<_> = p.chunks.iter().map(|c| c.table_name.as_ref()).collect();
+        table_names.sort_unstable();
+        table_names.dedup();
+        Some(table_names.len() as u64)
+    }));
+    let chunk_count =
+        UInt64Array::from_iter(partitions.iter().map(|p| Some(p.chunks.len() as u64)));
+    let estimated_bytes = UInt64Array::from_iter(
+        partitions
+            .iter()
+            .map(|p| Some(p.chunks.iter().map(|c| c.estimated_bytes as u64).sum())),
+    );
+    let row_count = UInt64Array::from_iter(
+        partitions
+            .iter()
+            .map(|p| Some(p.chunks.iter().map(|c| c.row_count as u64).sum())),
+    );
+    let created_at = TimestampNanosecondArray::from_iter(
+        partitions.iter().map(|p| time_to_ts(Some(p.created_at))),
+    );
+    let last_write_at = TimestampNanosecondArray::from_iter(
+        partitions.iter().map(|p| time_to_ts(Some(p.last_write_at))),
+    );
+
+    RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(partition_key) as ArrayRef,
+            Arc::new(table_count),
+            Arc::new(chunk_count),
+            Arc::new(estimated_bytes),
+            Arc::new(row_count),
+            Arc::new(created_at),
+            Arc::new(last_write_at),
+        ],
+    )
+}
+
 /// Implementation of system.column_chunks table
 #[derive(Debug)]
 struct ChunkColumnsTable {
@@ -355,6 +466,9 @@ fn chunk_columns_schema() -> SchemaRef {
         Field::new("min_value", DataType::Utf8, true),
         Field::new("max_value", DataType::Utf8, true),
         Field::new("estimated_bytes", DataType::UInt64, true),
+        Field::new("encoding", DataType::Utf8, true),
+        Field::new("raw_bytes", DataType::UInt64, true),
+        Field::new("cardinality", DataType::UInt64, true),
     ]))
 }
 
@@ -376,17 +490,12 @@ fn assemble_chunk_columns(
         })
         .collect();
 
-    /// Builds an index from column_name -> size
-    fn make_column_index(summary: &DetailedChunkSummary) -> HashMap<&str, u64> {
+    /// Builds an index from column_name -> column summary
+    fn make_column_index(summary: &DetailedChunkSummary) -> HashMap<&str, &ChunkColumnSummary> {
         summary
             .columns
             .iter()
-            .map(|column_summary| {
-                (
-                    column_summary.name.as_ref(),
-                    column_summary.estimated_bytes as u64,
-                )
-            })
+            .map(|column_summary| (column_summary.name.as_ref(), column_summary))
             .collect()
     }
 
@@ -402,6 +511,9 @@ fn assemble_chunk_columns(
     let mut min_values = StringBuilder::new(row_estimate);
     let mut max_values = StringBuilder::new(row_estimate);
     let mut estimated_bytes = UInt64Builder::new(row_estimate);
+    let mut encoding = StringBuilder::new(row_estimate);
+    let mut raw_bytes = UInt64Builder::new(row_estimate);
+    let mut cardinality = UInt64Builder::new(row_estimate);
 
     // Note no rows are produced for partitions with no tables, or
     // tables with no columns: There are other tables to list tables
@@ -443,13 +555,19 @@ fn assemble_chunk_columns(
                     max_values.append(false)?;
                 }
 
-                let size = column_index.remove(column.name.as_str());
+                let column_summary = column_index.remove(column.name.as_str());
 
-                estimated_bytes.append_option(size)?;
+                estimated_bytes.append_option(column_summary.map(|c| c.estimated_bytes as u64))?;
+                append_storage_details(
+                    column_summary,
+                    &mut encoding,
+                    &mut raw_bytes,
+                    &mut cardinality,
+                )?;
             }
 
             // now, if there are any left over (special columns, like __dictionary), add them too
-            for (name, size) in column_index {
+            for (name, column_summary) in column_index {
                 partition_key.append_value(&partition.key)?;
                 chunk_id.append_value(chunk_table.chunk_id)?;
                 table_name.append_value(&table.name)?;
@@ -458,7 +576,13 @@ fn assemble_chunk_columns(
                 count.append_null()?;
                 min_values.append(false)?;
                 max_values.append(false)?;
-                estimated_bytes.append_value(size)?;
+                estimated_bytes.append_value(column_summary.estimated_bytes as u64)?;
+                append_storage_details(
+                    Some(column_summary),
+                    &mut encoding,
+                    &mut raw_bytes,
+                    &mut cardinality,
+                )?;
             }
         }
     }
@@ -475,10 +599,30 @@ fn assemble_chunk_columns(
             Arc::new(min_values.finish()),
             Arc::new(max_values.finish()),
             Arc::new(estimated_bytes.finish()),
+            Arc::new(encoding.finish()),
+            Arc::new(raw_bytes.finish()),
+            Arc::new(cardinality.finish()),
         ],
     )
 }
 
+/// Appends the physical storage details of a column, which are only known
+/// for some chunk storage types
+fn append_storage_details(
+    column_summary: Option<&ChunkColumnSummary>,
+    encoding: &mut StringBuilder,
+    raw_bytes: &mut UInt64Builder,
+    cardinality: &mut UInt64Builder,
+) -> Result<()> {
+    match column_summary.and_then(|c| c.encoding.as_ref()) {
+        Some(v) => encoding.append_value(v)?,
+        None => encoding.append(false)?,
+    }
+    raw_bytes.append_option(column_summary.and_then(|c| c.raw_bytes.map(|v| v as u64)))?;
+    cardinality.append_option(column_summary.and_then(|c| c.cardinality))?;
+    Ok(())
+}
+
 /// Implementation of system.operations table
 #[derive(Debug)]
 struct OperationsTable {
@@ -623,6 +767,7 @@ mod tests {
                 id: 0,
                 storage: ChunkStorage::OpenMutableBuffer,
                 estimated_bytes: 23754,
+                allocated_bytes: 23754,
                 row_count: 11,
                 time_of_first_write: Some(DateTime::from_utc(
                     NaiveDateTime::from_timestamp(10, 0),
@@ -630,6 +775,7 @@ mod tests {
                 )),
                 time_of_last_write: None,
                 time_closed: None,
+                read_buffer: None,
             },
             ChunkSummary {
                 partition_key: Arc::from("p1"),
@@ -637,6 +783,7 @@ mod tests {
                 id: 0,
                 storage: ChunkStorage::OpenMutableBuffer,
                 estimated_bytes: 23454,
+                allocated_bytes: 23454,
                 row_count: 22,
                 time_of_first_write: None,
                 time_of_last_write: Some(DateTime::from_utc(
@@ -644,6 +791,7 @@ mod tests {
                     Utc,
                 )),
                 time_closed: None,
+                read_buffer: None,
             },
         ];
 
@@ -722,6 +870,55 @@ mod tests {
         assert_batches_eq!(&expected, &[batch]);
     }
 
+    #[test]
+    fn test_from_partitions() {
+        let chunk = |id, table_name: &str, estimated_bytes, row_count| ChunkSummary {
+            partition_key: Arc::from("p1"),
+            table_name: Arc::from(table_name),
+            id,
+            storage: ChunkStorage::OpenMutableBuffer,
+            estimated_bytes,
+            allocated_bytes: estimated_bytes,
+            row_count,
+            time_of_first_write: None,
+            time_of_last_write: None,
+            time_closed: None,
+            read_buffer: None,
+        };
+        let time = |secs| DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(secs, 0), Utc);
+
+        let partitions = vec![
+            PartitionRow {
+                key: "p1".to_string(),
+                created_at: time(10),
+                last_write_at: time(20),
+                chunks: vec![
+                    chunk(0, "t1", 23754, 11),
+                    chunk(1, "t1", 1000, 2),
+                    chunk(0, "t2", 2000, 5),
+                ],
+            },
+            PartitionRow {
+                key: "p2".to_string(),
+                created_at: time(30),
+                last_write_at: time(30),
+                chunks: vec![],
+            },
+        ];
+
+        let expected = vec![
+            "+---------------+-------------+-------------+-----------------+-----------+---------------------+---------------------+",
+            "| partition_key | table_count | chunk_count | estimated_bytes | row_count | created_at          | last_write_at       |",
+            "+---------------+-------------+-------------+-----------------+-----------+---------------------+---------------------+",
+            "| p1            | 2           | 3           | 26754           | 18        | 1970-01-01 00:00:10 | 1970-01-01 00:00:20 |",
+            "| p2            | 0           | 0           | 0               | 0         | 1970-01-01 00:00:30 | 1970-01-01 00:00:30 |",
+            "+---------------+-------------+-------------+-----------------+-----------+---------------------+---------------------+",
+        ];
+
+        let batch = from_partitions(partitions_schema(), partitions).unwrap();
+        assert_batches_eq!(&expected, &[batch]);
+    }
+
     fn seq_array(start: u64, end: u64) -> ArrayRef {
         Arc::new(UInt64Array::from_iter_values(start..end))
     }
@@ -872,23 +1069,34 @@ mod tests {
                     id: 42,
                     storage: ChunkStorage::ReadBuffer,
                     estimated_bytes: 23754,
+                    allocated_bytes: 23754,
                     row_count: 11,
                     time_of_first_write: None,
                     time_of_last_write: None,
                     time_closed: None,
+                    read_buffer: None,
                 },
                 columns: vec![
                     ChunkColumnSummary {
                         name: "c1".into(),
                         estimated_bytes: 11,
+                        encoding: Some("RLE".into()),
+                        raw_bytes: Some(33),
+                        cardinality: Some(2),
                     },
                     ChunkColumnSummary {
                         name: "c2".into(),
                         estimated_bytes: 12,
+                        encoding: Some("FIXEDN".into()),
+                        raw_bytes: Some(24),
+                        cardinality: None,
                     },
                     ChunkColumnSummary {
                         name: "__other".into(),
                         estimated_bytes: 13,
+                        encoding: Some("RLE".into()),
+                        raw_bytes: Some(14),
+                        cardinality: None,
                     },
                 ],
             },
@@ -899,14 +1107,19 @@ mod tests {
                     id: 43,
                     storage: ChunkStorage::OpenMutableBuffer,
                     estimated_bytes: 23754,
+                    allocated_bytes: 23754,
                     row_count: 11,
                     time_of_first_write: None,
                     time_of_last_write: None,
                     time_closed: None,
+                    read_buffer: None,
                 },
                 columns: vec![ChunkColumnSummary {
                     name: "c1".into(),
                     estimated_bytes: 100,
+                    encoding: None,
+                    raw_bytes: None,
+                    cardinality: None,
                 }],
             },
             DetailedChunkSummary {
@@ -916,29 +1129,34 @@ mod tests {
                     id: 44,
                     storage: ChunkStorage::OpenMutableBuffer,
                     estimated_bytes: 23754,
+                    allocated_bytes: 23754,
                     row_count: 11,
                     time_of_first_write: None,
                     time_of_last_write: None,
                     time_closed: None,
+                    read_buffer: None,
                 },
                 columns: vec![ChunkColumnSummary {
                     name: "c3".into(),
                     estimated_bytes: 200,
+                    encoding: None,
+                    raw_bytes: None,
+                    cardinality: None,
                 }],
             },
         ];
 
         let expected = vec![
-            "+---------------+----------+------------+-------------+-------------------+-------+-----------+-----------+-----------------+",
-            "| partition_key | chunk_id | table_name | column_name | storage           | count | min_value | max_value | estimated_bytes |",
-            "+---------------+----------+------------+-------------+-------------------+-------+-----------+-----------+-----------------+",
-            "| p1            | 42       | t1         | c1          | ReadBuffer        | 55    | bar       | foo       | 11              |",
-            "| p1            | 42       | t1         | c2          | ReadBuffer        | 66    | 11        | 43        | 12              |",
-            "| p1            | 42       | t1         | __other     | ReadBuffer        |       |           |           | 13              |",
-            "| p2            | 43       | t1         | c2          | OpenMutableBuffer | 667   | 110       | 430       |                 |",
-            "| p2            | 43       | t1         | c1          | OpenMutableBuffer |       |           |           | 100             |",
-            "| p2            | 44       | t2         | c3          | OpenMutableBuffer | 4     | -1        | 2         | 200             |",
-            "+---------------+----------+------------+-------------+-------------------+-------+-----------+-----------+-----------------+",
+            "+---------------+----------+------------+-------------+-------------------+-------+-----------+-----------+-----------------+----------+-----------+-------------+",
+            "| partition_key | chunk_id | table_name | column_name | storage           | count | min_value | max_value | estimated_bytes | encoding | raw_bytes | cardinality |",
+            "+---------------+----------+------------+-------------+-------------------+-------+-----------+-----------+-----------------+----------+-----------+-------------+",
+            "| p1            | 42       | t1         | c1          | ReadBuffer        | 55    | bar       | foo       | 11              | RLE      | 33        | 2           |",
+            "| p1            | 42       | t1         | c2          | ReadBuffer        | 66    | 11        | 43        | 12              | FIXEDN   | 24        |             |",
+            "| p1            | 42       | t1         | __other     | ReadBuffer        |       |           |           | 13              | RLE      | 14        |             |",
+            "| p2            | 43       | t1         | c2          | OpenMutableBuffer | 667   | 110       | 430       |                 |          |           |             |",
+            "| p2            | 43       | t1         | c1          | OpenMutableBuffer |       |           |           | 100             |          |           |             |",
+            "| p2            | 44       | t2         | c3          | OpenMutableBuffer | 4     | -1        | 2         | 200             |          |           |             |",
+            "+---------------+----------+------------+-------------+-------------------+-------+-----------+-----------+-----------------+----------+-----------+-------------+",
         ];
 
         let batch =
@@ -0,0 +1,395 @@
+//! Import of the TSM files written by InfluxDB 1.x and 2.x as persisted
+//! chunks, for migrating existing InfluxDB installations
+
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, BooleanArray, Float64Array, Int64Array, StringArray, UInt64Array},
+    datatypes::{DataType, Field, Schema},
+    record_batch::RecordBatch,
+};
+use chrono::Utc;
+use data_types::{database_rules::ShardConfig, timestamp::Precision};
+use datafusion::physical_plan::SendableRecordBatchStream;
+use datafusion_util::MemoryStream;
+use entry::lines_to_sharded_entries;
+use influxdb_line_protocol::parse_lines;
+use influxdb_tsm::{
+    mapper::{ColumnData, TableSection, TsmMeasurementMapper},
+    reader::{TsmBlockReader, TsmIndexReader},
+    TsmError,
+};
+use internal_types::{schema::TIME_COLUMN_NAME, selection::Selection};
+use mutable_buffer::chunk::{
+    Chunk as MutableBufferChunk, ChunkMetrics as MutableBufferChunkMetrics,
+};
+use observability_deps::tracing::{debug, info};
+use parquet_file::{metadata::IoxMetadata, storage::Storage};
+use snafu::{ResultExt, Snafu};
+
+use super::{compact, lines::batch_to_lines, Db};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Error reading TSM file: {}", source))]
+    ReadingTsm { source: TsmError },
+
+    #[snafu(display("Error converting measurement {}: {}", measurement, source))]
+    Conversion {
+        measurement: String,
+        source: arrow::error::ArrowError,
+    },
+
+    #[snafu(display("Error parsing converted measurement {}: {}", measurement, source))]
+    LineProtocol {
+        measurement: String,
+        source: influxdb_line_protocol::Error,
+    },
+
+    #[snafu(display("Error partitioning measurement {}: {}", measurement, source))]
+    Partitioning {
+        measurement: String,
+        source: entry::Error,
+    },
+
+    #[snafu(display(
+        "Error buffering partition {} of table {}: {}",
+        partition_key,
+        table_name,
+        source
+    ))]
+    Buffering {
+        partition_key: String,
+        table_name: String,
+        source: mutable_buffer::chunk::Error,
+    },
+
+    #[snafu(display(
+        "Error deduplicating partition {} of table {}: {}",
+        partition_key,
+        table_name,
+        source
+    ))]
+    Deduplicating {
+        partition_key: String,
+        table_name: String,
+        source: compact::Error,
+    },
+
+    #[snafu(display("Error writing to object store: {}", source))]
+    WritingToObjectStore {
+        source: parquet_file::storage::Error,
+    },
+
+    #[snafu(display("Error in catalog transaction: {}", source))]
+    Transaction {
+        source: parquet_file::catalog::Error,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Imports the TSM file `data` into `db` and returns the number of rows
+/// imported.
+///
+/// Every measurement becomes a table with the tags of its series as tags and
+/// its fields as fields. The rows are partitioned according to the rules of
+/// `db` and every partition of a measurement is written as a parquet file
+/// that is added to the preserved catalog as a persisted chunk, in one
+/// transaction per measurement.
+///
+/// Fields with a type different from the first type seen for the field in
+/// the measurement are skipped, as IOx does not support columns of several
+/// types.
+pub async fn import_tsm(db: &Db, data: Vec<u8>) -> Result<usize> {
+    let index = TsmIndexReader::try_new(Cursor::new(&data[..]), data.len()).context(ReadingTsm)?;
+    let mut block_reader = TsmBlockReader::new(Cursor::new(&data[..]));
+
+    let mut rows = 0;
+    for table in TsmMeasurementMapper::new(index.peekable(), 0) {
+        let mut table = table.context(ReadingTsm)?;
+        let measurement = table.name.clone();
+
+        let mut lines = String::new();
+        let mut conversion = Ok(());
+        table
+            .process(&mut block_reader, |section| {
+                if conversion.is_ok() {
+                    conversion = section_to_lines(&mut lines, &measurement, &section);
+                }
+                Ok(())
+            })
+            .context(ReadingTsm)?;
+        conversion.context(Conversion {
+            measurement: &measurement,
+        })?;
+
+        rows += import_lines(db, &measurement, &lines).await?;
+    }
+
+    Ok(rows)
+}
+
+/// Appends the rows of `section` to `out` as line protocol
+fn section_to_lines(
+    out: &mut String,
+    measurement: &str,
+    section: &TableSection,
+) -> arrow::error::Result<()> {
+    let mut fields = vec![];
+    let mut columns: Vec<ArrayRef> = vec![];
+
+    for (tag_key, tag_value) in &section.tag_cols {
+        fields.push(Field::new(tag_key, DataType::Utf8, false));
+        let values = std::iter::repeat(Some(tag_value.as_str())).take(section.len());
+        columns.push(Arc::new(values.collect::<StringArray>()));
+    }
+
+    for (field_key, values) in &section.field_cols {
+        let values: ArrayRef = match values {
+            ColumnData::Float(values) => Arc::new(Float64Array::from(values.clone())),
+            ColumnData::Integer(values) => Arc::new(Int64Array::from(values.clone())),
+            ColumnData::Bool(values) => Arc::new(BooleanArray::from(values.clone())),
+            ColumnData::Str(values) => Arc::new(
+                values
+                    .iter()
+                    .map(|value| value.as_ref().map(|value| String::from_utf8_lossy(value)))
+                    .collect::<StringArray>(),
+            ),
+            ColumnData::Unsigned(values) => Arc::new(UInt64Array::from(values.clone())),
+        };
+        fields.push(Field::new(field_key, values.data_type().clone(), true));
+        columns.push(values);
+    }
+
+    fields.push(Field::new(TIME_COLUMN_NAME, DataType::Int64, false));
+    columns.push(Arc::new(Int64Array::from(section.ts.clone())));
+
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)?;
+    let tag_columns: Vec<_> = section.tag_cols.iter().map(|(key, _)| key).collect();
+    batch_to_lines(out, measurement, &batch, &tag_columns)
+}
+
+/// Partitions the converted `lines` of a measurement according to the rules
+/// of `db` and adds every partition as a persisted chunk
+async fn import_lines(db: &Db, measurement: &str, lines: &str) -> Result<usize> {
+    let lines = parse_lines(lines)
+        .collect::<Result<Vec<_>, _>>()
+        .context(LineProtocol { measurement })?;
+
+    let (sharded_entries, schema_conflict_policy, type_coercion_rules) = {
+        let rules = db.rules.read();
+        let sharded_entries = lines_to_sharded_entries(
+            &lines,
+            0,
+            Precision::Nanoseconds,
+            None as Option<&ShardConfig>,
+            &*rules,
+        )
+        .context(Partitioning { measurement })?;
+        (
+            sharded_entries,
+            rules.schema_conflict_policy,
+            rules.type_coercion_rules.clone(),
+        )
+    };
+
+    // buffer the rows of every partition in a chunk to convert them to arrow
+    let mut chunks: BTreeMap<(String, String), MutableBufferChunk> = BTreeMap::new();
+    for sharded_entry in &sharded_entries {
+        for partition_write in sharded_entry.entry.partition_writes().unwrap_or_default() {
+            let partition_key = partition_write.key();
+            for table_batch in partition_write.table_batches() {
+                let table_name = table_batch.name();
+                chunks
+                    .entry((partition_key.to_string(), table_name.to_string()))
+                    .or_insert_with(|| {
+                        MutableBufferChunk::new(
+                            table_name,
+                            MutableBufferChunkMetrics::new_unregistered(),
+                        )
+                    })
+                    .write_table_batch(
+                        0,
+                        0,
+                        table_batch,
+                        schema_conflict_policy,
+                        &type_coercion_rules,
+                    )
+                    .context(Buffering {
+                        partition_key,
+                        table_name,
+                    })?;
+            }
+        }
+    }
+
+    let storage = Storage::new(
+        Arc::clone(&db.store),
+        db.server_id,
+        db.rules.read().name.to_string(),
+    );
+    let catalog = db.preserved_catalog.state();
+
+    // the imported data is newer than the data already in the database
+    let now = Utc::now();
+    let mut transaction = db.preserved_catalog.open_transaction().await;
+    for ((partition_key, table_name), chunk) in chunks {
+        let mut batches = vec![];
+        chunk
+            .table_to_arrow(&mut batches, Selection::All)
+            .context(Buffering {
+                partition_key: &partition_key,
+                table_name: &table_name,
+            })?;
+        // the same series may be in several TSM files, and persisted chunks
+        // must not contain duplicates
+        let (batch, _) =
+            compact::merge_record_batches(&batches, vec![]).context(Deduplicating {
+                partition_key: &partition_key,
+                table_name: &table_name,
+            })?;
+        let stream: SendableRecordBatchStream = Box::pin(MemoryStream::new(vec![batch]));
+
+        let chunk_id = catalog
+            .get_or_create_partition(&partition_key)
+            .write()
+            .reserve_chunk_id(&table_name);
+
+        let metadata = IoxMetadata {
+            transaction_revision_counter: transaction.revision_counter(),
+            transaction_uuid: transaction.uuid(),
+            time_of_first_write: Some(now),
+            time_of_last_write: Some(now),
+            min_unpersisted_sequence_number: None,
+        };
+        let (path, parquet_metadata) = storage
+            .write_to_object_store(
+                partition_key.clone(),
+                chunk_id,
+                table_name.clone(),
+                stream,
+                metadata,
+            )
+            .await
+            .context(WritingToObjectStore)?;
+
+        transaction
+            .add_parquet(&path.into(), &parquet_metadata)
+            .context(Transaction)?;
+
+        debug!(%partition_key, %table_name, %chunk_id, rows=chunk.rows(), "imported TSM partition");
+    }
+    transaction.commit().await.context(Transaction)?;
+    db.outdate_quota_usage();
+
+    info!(%measurement, rows=lines.len(), "imported TSM measurement");
+
+    Ok(lines.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::make_db;
+    use arrow_util::assert_batches_sorted_eq;
+    use data_types::chunk_metadata::ChunkStorage;
+    use influxdb_tsm::encoders;
+    use query::{frontend::sql::SqlQueryPlanner, Database};
+
+    /// Returns a TSM file with an integer block for each series key
+    fn tsm_file(series: &[(&[u8], &[i64], &[i64])]) -> Vec<u8> {
+        // magic number and version
+        let mut data = vec![0x16, 0xD1, 0x16, 0xD1, 1];
+
+        let mut index = vec![];
+        for (key, timestamps, values) in series {
+            let mut ts = vec![];
+            encoders::timestamp::encode(timestamps, &mut ts).unwrap();
+            // checksum (not verified), block type and the length of the
+            // timestamps as varint, which is a single byte for short blocks
+            assert!(ts.len() < 128);
+            let mut block = vec![0, 0, 0, 0, 1, ts.len() as u8];
+            block.extend(ts);
+            encoders::integer::encode(values, &mut block).unwrap();
+
+            index.extend(&(key.len() as u16).to_be_bytes());
+            index.extend(*key);
+            index.push(1);
+            index.extend(&1u16.to_be_bytes());
+            index.extend(&timestamps[0].to_be_bytes());
+            index.extend(&timestamps[timestamps.len() - 1].to_be_bytes());
+            index.extend(&(data.len() as u64).to_be_bytes());
+            index.extend(&(block.len() as u32).to_be_bytes());
+
+            data.extend(block);
+        }
+
+        let index_offset = data.len() as u64;
+        data.extend(index);
+        data.extend(&index_offset.to_be_bytes());
+        data
+    }
+
+    #[tokio::test]
+    async fn import() {
+        let db = Arc::new(make_db().await.db);
+
+        // keys as written by InfluxDB 1.x, sorted like in a TSM index
+        let data = tsm_file(&[
+            (b"cpu,host=a#!~#usage", &[10, 20], &[1, 2]),
+            (b"cpu,host=b#!~#usage", &[10], &[3]),
+            (b"mem,host=a#!~#free", &[3_600_000_000_000], &[100]),
+        ]);
+
+        let rows = import_tsm(&db, data).await.unwrap();
+        assert_eq!(rows, 4);
+
+        let mut chunks = db.chunk_summaries().unwrap();
+        chunks.sort_by(|a, b| a.table_name.cmp(&b.table_name));
+        let chunks: Vec<_> = chunks
+            .iter()
+            .map(|chunk| {
+                (
+                    chunk.partition_key.as_ref(),
+                    chunk.table_name.as_ref(),
+                    chunk.storage,
+                )
+            })
+            .collect();
+        assert_eq!(
+            chunks,
+            vec![
+                ("1970-01-01T00", "cpu", ChunkStorage::ObjectStoreOnly),
+                ("1970-01-01T01", "mem", ChunkStorage::ObjectStoreOnly),
+            ]
+        );
+
+        let executor = db.executor();
+        let planner = SqlQueryPlanner::default();
+        let plan = planner
+            .query(Arc::clone(&db), "select * from cpu", &executor)
+            .unwrap();
+        let batches = executor.collect(plan).await.unwrap();
+        let expected = vec![
+            "+------+-------------------------------+-------+",
+            "| host | time                          | usage |",
+            "+------+-------------------------------+-------+",
+            "| a    | 1970-01-01 00:00:00.000000010 | 1     |",
+            "| a    | 1970-01-01 00:00:00.000000020 | 2     |",
+            "| b    | 1970-01-01 00:00:00.000000010 | 3     |",
+            "+------+-------------------------------+-------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn import_invalid() {
+        let db = make_db().await.db;
+
+        let err = import_tsm(&db, vec![0; 4]).await.unwrap_err();
+        assert!(matches!(err, Error::ReadingTsm { .. }));
+    }
+}
@@ -68,49 +68,63 @@
 )]
 
 use std::convert::TryInto;
+use std::num::NonZeroUsize;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use cached::proc_macro::cached;
+use chrono::{DateTime, Utc};
 use db::load_or_create_preserved_catalog;
 use futures::stream::TryStreamExt;
-use object_store::path::Path;
-use observability_deps::tracing::{debug, error, info, warn};
+use object_store::path::{parsed::DirsAndFileName, Path};
+use observability_deps::tracing::{self, debug, error, info, warn};
 use parking_lot::Mutex;
-use snafu::{OptionExt, ResultExt, Snafu};
+use parquet_file::catalog::CatalogDump;
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
 
 use data_types::{
     database_rules::DatabaseRules,
     job::Job,
     server_id::ServerId,
+    timestamp::{Precision, TimestampRange},
     {DatabaseName, DatabaseNameError},
 };
-use entry::{lines_to_sharded_entries, Entry, ShardedEntry};
+use entry::{lines_to_sharded_entries, validate_lines, Entry, ShardedEntry};
 use influxdb_line_protocol::ParsedLine;
 use internal_types::once::OnceNonZeroU32;
 use metrics::{KeyValue, MetricObserverBuilder, MetricRegistry};
 use object_store::{path::ObjectStorePath, ObjectStore, ObjectStoreApi};
-use query::{exec::Executor, DatabaseStore};
+use query::{
+    exec::{Executor, QueryConcurrencyLimits},
+    DatabaseStore,
+};
 use tokio::sync::Semaphore;
 use tracker::{TaskId, TaskRegistration, TaskRegistryWithHistory, TaskTracker, TrackedFutureExt};
 
 pub use crate::config::RemoteTemplate;
 use crate::config::{
-    object_store_path_for_database_config, Config, GRpcConnectionString, DB_RULES_FILE_NAME,
+    object_store_path_for_database_config, Config, GRpcConnectionString, DB_DELETED_FILE_NAME,
+    DB_RULES_FILE_NAME, SERVER_ID_FILE_NAME,
 };
+pub use crate::replication::ReplicationStatus;
+use crate::replication::{ReplicationQueue, ReplicationQueues};
 use cached::Return;
 use data_types::database_rules::{NodeGroup, RoutingRules, Shard, ShardConfig, ShardId};
 pub use db::Db;
 use generated_types::database_rules::{decode_database_rules, encode_database_rules};
 use influxdb_iox_client::{connection::Builder, write};
 use rand::seq::SliceRandom;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+pub mod auth;
 mod config;
 pub mod db;
+pub mod memory;
+pub mod queries;
+mod replication;
 mod write_buffer;
 
 /// Utility modules used by benchmarks and tests
@@ -158,6 +172,9 @@ pub enum Error {
     ))]
     DatabasesNotLoaded,
 
+    #[snafu(display("server is shutting down"))]
+    ShuttingDown,
+
     #[snafu(display("error serializing database rules to protobuf: {}", source))]
     ErrorSerializingRulesProtobuf {
         source: generated_types::database_rules::EncodeError,
@@ -183,9 +200,21 @@ pub enum Error {
     #[snafu(display("database already exists"))]
     DatabaseAlreadyExists { db_name: String },
 
+    #[snafu(display("cannot create write buffer for database {}: {}", db_name, source))]
+    CreatingWriteBuffer {
+        db_name: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
     #[snafu(display("error converting line protocol to flatbuffers: {}", source))]
     LineConversion { source: entry::Error },
 
+    #[snafu(display(
+        "all-or-nothing writes are not supported by database {} because it routes writes",
+        db_name
+    ))]
+    AllOrNothingRouted { db_name: String },
+
     #[snafu(display("error decoding entry flatbuffers: {}", source))]
     DecodingEntry {
         source: flatbuffers::InvalidFlatbuffer,
@@ -197,6 +226,19 @@ pub enum Error {
     #[snafu(display("hard buffer limit reached"))]
     HardLimitReached {},
 
+    #[snafu(display(
+        "resources exhausted: server memory use of {} bytes exceeds the limit of {} bytes",
+        used,
+        limit
+    ))]
+    ResourcesExhausted { used: usize, limit: usize },
+
+    #[snafu(display("quota exceeded: {}", source))]
+    QuotaExceeded { source: db::Error },
+
+    #[snafu(display("{}", source))]
+    SchemaConflict { source: db::Error },
+
     #[snafu(display("no remote configured for node group: {:?}", node_group))]
     NoRemoteConfigured { node_group: NodeGroup },
 
@@ -210,10 +252,129 @@ pub enum Error {
 
     #[snafu(display("cannot load catalog: {}", source))]
     CatalogLoadError { source: DatabaseError },
+
+    #[snafu(display("cannot read preserved catalog: {}", source))]
+    CatalogReadError {
+        source: parquet_file::catalog::Error,
+    },
+
+    #[snafu(display("backup location is not empty: {}", location))]
+    BackupLocationNotEmpty { location: String },
+
+    #[snafu(display("no backup of database {} found at: {}", db_name, location))]
+    BackupNotFound { db_name: String, location: String },
+
+    #[snafu(display("export location is not empty: {}", location))]
+    ExportLocationNotEmpty { location: String },
+
+    #[snafu(display("cannot export database {}: {}", db_name, source))]
+    ExportingDatabase {
+        db_name: String,
+        source: db::export::Error,
+    },
+
+    #[snafu(display("cannot read TSM file {}: {}", path, source))]
+    ReadingTsmFile {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[snafu(display(
+        "cannot import TSM file {} into database {}: {}",
+        path,
+        db_name,
+        source
+    ))]
+    ImportingTsm {
+        db_name: String,
+        path: String,
+        source: db::tsm::Error,
+    },
+
+    #[snafu(display("object store already contains data for database {}", db_name))]
+    DatabaseDataExists { db_name: String },
+
+    #[snafu(display("invalid server ID persisted at {:?}", location))]
+    InvalidPersistedServerId { location: object_store::path::Path },
+
+    #[snafu(display(
+        "not loading the persisted server ID, the object store holds data of the server IDs {:?}",
+        ids
+    ))]
+    AmbiguousPersistedServerId { ids: Vec<ServerId> },
+
+    #[snafu(display(
+        "database {} is deleted, restore it or wait until it is purged",
+        db_name
+    ))]
+    DatabaseDeleted { db_name: String },
+
+    #[snafu(display("invalid deletion time persisted at {:?}", location))]
+    InvalidDeletionMarker { location: object_store::path::Path },
+
+    #[snafu(display("cannot flush database {}: {}", db_name, source))]
+    FlushingDatabase { db_name: String, source: db::Error },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+impl Error {
+    /// Returns true if the written data itself was rejected, e.g. because it
+    /// conflicts with the schema of the database or with other lines of the
+    /// same request, so that other data in the same request may still be
+    /// written
+    pub fn is_rejected_write(&self) -> bool {
+        matches!(
+            self,
+            Self::LineConversion { .. } | Self::SchemaConflict { .. } | Self::QuotaExceeded { .. }
+        )
+    }
+}
+
+/// Converts the error of storing an entry in `Db` into the corresponding
+/// server error
+fn db_write_error(e: db::Error) -> Error {
+    match e {
+        db::Error::HardLimitReached {} => Error::HardLimitReached {},
+        db::Error::TableQuotaExceeded { .. }
+        | db::Error::ColumnQuotaExceeded { .. }
+        | db::Error::RowQuotaExceeded { .. } => Error::QuotaExceeded { source: e },
+        db::Error::SchemaConflict { .. } => Error::SchemaConflict { source: e },
+        _ => Error::UnknownDatabaseError {
+            source: Box::new(e),
+        },
+    }
+}
+
+/// Converts `lines` into entries for `db`, which must not have routing
+/// rules, and checks that they could currently be stored as a whole
+fn validate_write(
+    db: &Db,
+    lines: &[ParsedLine<'_>],
+    default_time: i64,
+    precision: Precision,
+) -> Result<Vec<ShardedEntry>> {
+    let sharded_entries = {
+        let rules = db.rules.read();
+        validate_lines(lines, &rules.line_limits).context(LineConversion)?;
+        lines_to_sharded_entries(
+            lines,
+            default_time,
+            precision,
+            None as Option<&ShardConfig>,
+            &*rules,
+        )
+        .context(LineConversion)?
+    };
+
+    for sharded_entry in &sharded_entries {
+        db.validate_entry(&sharded_entry.entry)
+            .map_err(db_write_error)?;
+    }
+
+    Ok(sharded_entries)
+}
+
 const JOB_HISTORY_SIZE: usize = 1000;
 
 /// The global job registry
@@ -244,10 +405,34 @@ impl JobRegistry {
     pub fn tracked(&self) -> Vec<TaskTracker<Job>> {
         self.inner.lock().tracked()
     }
+
+    /// Returns the Jobs that are still running
+    pub fn running(&self) -> Vec<TaskTracker<Job>> {
+        self.inner.lock().running()
+    }
 }
 
 const STORE_ERROR_PAUSE_SECONDS: u64 = 100;
 
+/// How long to wait before retrying to ship entries to a replication target
+/// that could not be reached
+const REPLICATION_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How long writes are delayed by default while the server is over its memory
+/// budget, before they are rejected
+const DEFAULT_MEMORY_BACKPRESSURE: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How often a delayed write checks whether memory has been freed
+const MEMORY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// How long the data of a deleted database is kept by default before it is
+/// purged
+const DEFAULT_PURGE_DELETED_AFTER: std::time::Duration =
+    std::time::Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How often deleted databases are checked for being due to be purged
+const PURGE_DELETED_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
 /// Used to configure a server instance
 #[derive(Debug)]
 pub struct ServerConfig {
@@ -255,12 +440,38 @@ pub struct ServerConfig {
     // to number of cores on the system.
     num_worker_threads: Option<usize>,
 
+    /// Memory budget of all databases, including running queries. If not
+    /// specified, only the per-database limits apply
+    max_memory: Option<NonZeroUsize>,
+
+    /// How long to delay writes while over `max_memory` before rejecting them
+    memory_backpressure: std::time::Duration,
+
+    /// Memory each query may use for sorts and aggregations. If not
+    /// specified, queries are not limited
+    query_memory_limit: Option<NonZeroUsize>,
+
+    /// The queries each database may run at the same time. If not
+    /// specified, queries are not limited
+    query_concurrency_limits: Option<QueryConcurrencyLimits>,
+
+    /// Memory each database may use for caching query results. If not
+    /// specified, query results are not cached
+    query_result_cache_size: Option<NonZeroUsize>,
+
+    /// How long the data of a deleted database is kept, so that it can be
+    /// restored, before it is purged
+    purge_deleted_after: std::time::Duration,
+
     /// The `ObjectStore` instance to use for persistence
     object_store: Arc<ObjectStore>,
 
     metric_registry: Arc<MetricRegistry>,
 
     remote_template: Option<RemoteTemplate>,
+
+    /// Authorization of API requests, disabled unless an admin token is set
+    auth: auth::Authorizer,
 }
 
 impl ServerConfig {
@@ -272,9 +483,16 @@ impl ServerConfig {
     ) -> Self {
         Self {
             num_worker_threads: None,
+            max_memory: None,
+            memory_backpressure: DEFAULT_MEMORY_BACKPRESSURE,
+            query_memory_limit: None,
+            query_concurrency_limits: None,
+            query_result_cache_size: None,
+            purge_deleted_after: DEFAULT_PURGE_DELETED_AFTER,
             object_store,
             metric_registry,
             remote_template,
+            auth: auth::Authorizer::new(None),
         }
     }
 
@@ -284,6 +502,54 @@ impl ServerConfig {
         self
     }
 
+    /// Limit the memory used by all databases to `max_memory` bytes
+    pub fn with_max_memory(mut self, max_memory: NonZeroUsize) -> Self {
+        self.max_memory = Some(max_memory);
+        self
+    }
+
+    /// Delay writes for up to `backpressure` while the server is over its
+    /// memory budget before rejecting them
+    pub fn with_memory_backpressure(mut self, backpressure: std::time::Duration) -> Self {
+        self.memory_backpressure = backpressure;
+        self
+    }
+
+    /// Fail queries that buffer more than `limit` bytes in sorts and
+    /// aggregations
+    pub fn with_query_memory_limit(mut self, limit: NonZeroUsize) -> Self {
+        self.query_memory_limit = Some(limit);
+        self
+    }
+
+    /// Bound the queries each database may run at the same time by
+    /// `limits`, so that the queries of one database cannot starve the
+    /// queries of the others
+    pub fn with_query_concurrency_limits(mut self, limits: QueryConcurrencyLimits) -> Self {
+        self.query_concurrency_limits = Some(limits);
+        self
+    }
+
+    /// Cache up to `size` bytes of query results per database, answering
+    /// repeated identical queries from memory until their chunks change
+    pub fn with_query_result_cache_size(mut self, size: NonZeroUsize) -> Self {
+        self.query_result_cache_size = Some(size);
+        self
+    }
+
+    /// Keep the data of deleted databases for `purge_after` before purging it
+    pub fn with_purge_deleted_after(mut self, purge_after: std::time::Duration) -> Self {
+        self.purge_deleted_after = purge_after;
+        self
+    }
+
+    /// Require requests to present a token, with `admin_token` granting
+    /// access to everything including the creation of other tokens
+    pub fn with_admin_token(mut self, admin_token: impl Into<String>) -> Self {
+        self.auth = auth::Authorizer::new(Some(admin_token.into()));
+        self
+    }
+
     /// return a reference to the object store in this configuration
     pub fn store(&self) -> Arc<ObjectStore> {
         Arc::clone(&self.object_store)
@@ -307,6 +573,9 @@ pub struct ServerMetrics {
 
     /// The number of Entry bytes ingested
     pub ingest_entries_bytes_total: metrics::Counter,
+
+    /// The number of entries shipped to, or dropped for, replication targets
+    pub replication_entries_total: metrics::Counter,
 }
 
 impl ServerMetrics {
@@ -315,6 +584,7 @@ impl ServerMetrics {
         let http_domain = registry.register_domain("http");
         let ingest_domain = registry.register_domain("ingest");
         let jemalloc_domain = registry.register_domain("jemalloc");
+        let replication_domain = registry.register_domain("replication");
 
         // This isn't really a property of the server, perhaps it should be somewhere else?
         jemalloc_domain.register_observer(None, &[], |observer: MetricObserverBuilder<'_>| {
@@ -369,6 +639,11 @@ impl ServerMetrics {
                 Some("bytes"),
                 "total Entry bytes ingested",
             ),
+            replication_entries_total: replication_domain.register_counter_metric(
+                "entries",
+                None,
+                "total entries shipped to or dropped for replication targets",
+            ),
         }
     }
 }
@@ -409,6 +684,9 @@ pub struct Server<M: ConnectionManager> {
     /// Flags that databases are loaded and server is ready to read/write data.
     initialized: AtomicBool,
 
+    /// Flags that the server is shutting down and no longer accepts writes.
+    shutting_down: AtomicBool,
+
     /// Semaphore that limits the number of jobs that load DBs when the serverID is set.
     ///
     /// Note that this semaphore is more of a "lock" than an arbitrary semaphore. All the other sync structures (mutex,
@@ -417,6 +695,17 @@ pub struct Server<M: ConnectionManager> {
     /// section (in [`maybe_initialize_server`](Self::maybe_initialize_server)) will break apart. So this semaphore
     /// cannot be configured.
     initialize_semaphore: Semaphore,
+
+    /// Entries waiting to be shipped to the replication targets of their
+    /// database
+    replication: Arc<ReplicationQueues>,
+
+    max_memory: Option<NonZeroUsize>,
+    memory_backpressure: std::time::Duration,
+    purge_deleted_after: std::time::Duration,
+
+    /// Validates the tokens presented with API requests
+    pub auth: Arc<auth::Authorizer>,
 }
 
 #[derive(Debug)]
@@ -437,12 +726,54 @@ impl<M: ConnectionManager> Server<M> {
 
         let ServerConfig {
             num_worker_threads,
+            max_memory,
+            memory_backpressure,
+            query_memory_limit,
+            query_concurrency_limits,
+            query_result_cache_size,
+            purge_deleted_after,
             object_store,
             // to test the metrics provide a different registry to the `ServerConfig`.
             metric_registry,
             remote_template,
+            auth,
         } = config;
         let num_worker_threads = num_worker_threads.unwrap_or_else(num_cpus::get);
+        let exec = match query_memory_limit {
+            Some(limit) => Executor::new(num_worker_threads).with_query_memory_limit(limit.get()),
+            None => Executor::new(num_worker_threads),
+        };
+        let exec = match query_concurrency_limits {
+            Some(limits) => exec.with_query_concurrency_limits(limits),
+            None => exec,
+        };
+        let exec = match query_result_cache_size {
+            Some(size) => exec.with_query_result_cache_size(size.get()),
+            None => exec,
+        };
+
+        let replication = Arc::new(ReplicationQueues::default());
+        let queues = Arc::clone(&replication);
+        metric_registry
+            .register_domain("replication")
+            .register_observer(None, &[], move |observer: MetricObserverBuilder<'_>| {
+                observer.register_gauge_u64(
+                    "queue",
+                    None,
+                    "Entries waiting to be shipped to a replication target",
+                    move |observer| {
+                        for status in queues.status() {
+                            observer.observe(
+                                status.queued as u64,
+                                &[
+                                    KeyValue::new("db_name", status.db_name),
+                                    KeyValue::new("target", status.target.to_string()),
+                                ],
+                            );
+                        }
+                    },
+                );
+            });
 
         Self {
             id: Default::default(),
@@ -453,13 +784,19 @@ impl<M: ConnectionManager> Server<M> {
             )),
             store: object_store,
             connection_manager: Arc::new(connection_manager),
-            exec: Arc::new(Executor::new(num_worker_threads)),
+            exec: Arc::new(exec),
             jobs,
             metrics: Arc::new(ServerMetrics::new(Arc::clone(&metric_registry))),
             registry: Arc::clone(&metric_registry),
             initialized: AtomicBool::new(false),
+            shutting_down: AtomicBool::new(false),
             // Always set semaphore permits to `1`, see design comments in `Server::initialize_semaphore`.
             initialize_semaphore: Semaphore::new(1),
+            replication,
+            max_memory,
+            memory_backpressure,
+            purge_deleted_after,
+            auth: Arc::new(auth),
         }
     }
 
@@ -495,11 +832,64 @@ impl<M: ConnectionManager> Server<M> {
         }
     }
 
+    /// Checks that the server is ready to serve reads and writes: its ID is
+    /// set, the databases have been loaded, the object store is reachable and
+    /// the server is not shutting down.
+    pub async fn check_ready(&self) -> Result<()> {
+        self.require_initialized()?;
+        ensure!(!self.is_shutting_down(), ShuttingDown);
+        self.store
+            .list_with_delimiter(&self.root_path()?)
+            .await
+            .context(StoreError)?;
+        Ok(())
+    }
+
+    /// Starts shutting down the server: writes are rejected from now on, while
+    /// queries are still served.
+    pub fn begin_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::Relaxed)
+    }
+
+    /// Prepares every database for the server to shut down, persisting the
+    /// buffered writes if `persist` is set, see [`Db::flush`].
+    /// Every database is attempted, returning the first error encountered
+    pub async fn flush_databases(&self, persist: bool) -> Result<()> {
+        let mut res = Ok(());
+
+        for db_name in self.config.db_names_sorted() {
+            if let Some(db) = self.config.db(&db_name) {
+                info!(%db_name, persist, "flushing database");
+                let flushed = db.flush(persist).await.context(FlushingDatabase {
+                    db_name: db_name.to_string(),
+                });
+                res = res.and(flushed);
+            }
+        }
+
+        res
+    }
+
     /// Tells the server the set of rules for a database.
     pub async fn create_database(&self, rules: DatabaseRules) -> Result<()> {
         // Return an error if this server is not yet ready
         let server_id = self.require_initialized()?;
 
+        let deleted_marker = self.deleted_marker_path(&rules.name)?;
+        if get_deleted_at(&deleted_marker, &self.store)
+            .await?
+            .is_some()
+        {
+            return DatabaseDeleted {
+                db_name: rules.name.to_string(),
+            }
+            .fail();
+        }
+
         let preserved_catalog = load_or_create_preserved_catalog(
             rules.db_name(),
             Arc::clone(&self.store),
@@ -523,97 +913,654 @@ impl<M: ConnectionManager> Server<M> {
         Ok(())
     }
 
-    pub async fn persist_database_rules<'a>(&self, rules: DatabaseRules) -> Result<()> {
-        let location = object_store_path_for_database_config(&self.root_path()?, &rules.name);
+    /// Detaches a database from this server: it stops accepting writes and
+    /// queries and is not reloaded on restart.
+    ///
+    /// When `preserve_data` is set only the database's rules are removed from
+    /// object storage, keeping its preserved catalog and parquet files so it
+    /// can be re-attached later by creating a database with the same name.
+    ///
+    /// Otherwise the database is flagged as deleted in object storage, keeping
+    /// all of its data until it is purged by the background worker once the
+    /// configured purge-after period has passed. Until then it can be brought
+    /// back with [`restore_deleted_database`](Self::restore_deleted_database)
+    /// and its name cannot be used for a new database.
+    pub async fn delete_database(
+        &self,
+        db_name: &DatabaseName<'_>,
+        preserve_data: bool,
+    ) -> Result<()> {
+        self.require_initialized()?;
 
-        let mut data = BytesMut::new();
-        encode_database_rules(rules, &mut data).context(ErrorSerializingRulesProtobuf)?;
+        self.config.remove_db(db_name).await?;
 
-        let len = data.len();
+        if preserve_data {
+            let location = object_store_path_for_database_config(&self.root_path()?, db_name);
+            self.store.delete(&location).await.context(StoreError)?;
+        } else {
+            let data = Bytes::from(Utc::now().to_rfc3339());
+            let len = data.len();
+            self.store
+                .put(
+                    &self.deleted_marker_path(db_name)?,
+                    futures::stream::once(async move { std::io::Result::Ok(data) }),
+                    Some(len),
+                )
+                .await
+                .context(StoreError)?;
+        }
+
+        info!(%db_name, preserve_data, "deleted database");
 
-        let stream_data = std::io::Result::Ok(data.freeze());
-        self.store
-            .put(
-                &location,
-                futures::stream::once(async move { stream_data }),
-                Some(len),
-            )
-            .await
-            .context(StoreError)?;
         Ok(())
     }
 
-    // base location in object store for this writer
-    fn root_path(&self) -> Result<object_store::path::Path> {
-        let id = self.require_id()?;
+    /// Copies a point-in-time snapshot of a database to the object store
+    /// location `destination` (a `/` separated path prefix), returning the
+    /// number of files copied.
+    ///
+    /// The snapshot consists of the database's rules, all transactions of its
+    /// preserved catalog and the parquet files referenced by the catalog. Files
+    /// keep their layout below `destination` because the catalog records the
+    /// full path of every parquet file.
+    pub async fn backup_database(
+        &self,
+        db_name: &DatabaseName<'_>,
+        destination: &str,
+    ) -> Result<usize> {
+        let server_id = self.require_initialized()?;
+        self.config.db(db_name).context(DatabaseNotFound {
+            db_name: db_name.to_string(),
+        })?;
 
-        let mut path = self.store.new_path();
-        path.push_dir(format!("{}", id));
-        Ok(path)
+        let backup_path = self.backup_path(destination, db_name)?;
+        if !self.list_files(&backup_path).await?.is_empty() {
+            return BackupLocationNotEmpty {
+                location: backup_path.display(),
+            }
+            .fail();
+        }
+
+        // Parquet files are immutable and only ever referenced by already
+        // written transactions, so copying the transactions seen here together
+        // with their files gives a consistent snapshot even if the database
+        // keeps persisting chunks in the meantime.
+        let dump = CatalogDump::load(&self.store, server_id, &db_name.to_string())
+            .await
+            .context(CatalogReadError)?;
+
+        let mut locations = vec![object_store_path_for_database_config(
+            &self.root_path()?,
+            db_name,
+        )];
+        locations.extend(dump.transactions.iter().map(|t| t.path.clone()));
+        locations.extend(
+            dump.parquet_files()
+                .into_iter()
+                .map(|path| self.store.path_from_dirs_and_filename(path)),
+        );
+
+        for location in &locations {
+            let target = self.prefixed_path(destination, location);
+            copy_object(&self.store, location, &target).await?;
+        }
+
+        info!(%db_name, %destination, files=locations.len(), "backed up database");
+
+        Ok(locations.len())
     }
 
-    /// Loads the database configurations based on the databases in the
-    /// object store. Any databases in the config already won't be
-    /// replaced.
+    /// Restores a database from a backup written by
+    /// [`backup_database`](Self::backup_database) to `source` and loads it,
+    /// returning the number of files copied.
     ///
-    /// This requires the serverID to be set. It will be a no-op if the configs are already loaded and the server is ready.
-    pub async fn maybe_initialize_server(&self) -> Result<()> {
-        let _guard = self
-            .initialize_semaphore
-            .acquire()
-            .await
-            .expect("semaphore should not be closed");
+    /// The database must neither exist on this server nor have any data left
+    /// in object storage, except for the data of a deleted database that has
+    /// not been purged yet, which is replaced by the backup.
+    pub async fn restore_database(
+        &self,
+        db_name: &DatabaseName<'_>,
+        source: &str,
+    ) -> Result<usize> {
+        let server_id = self.require_initialized()?;
+        if self.config.db(db_name).is_some() {
+            return DatabaseAlreadyExists {
+                db_name: db_name.to_string(),
+            }
+            .fail();
+        }
 
-        // Note that we use Acquire-Release ordering for the atomic within the semaphore to ensure that another thread
-        // that enters this semaphore after we've left actually sees the correct `is_ready` flag.
-        if self.initialized.load(Ordering::Acquire) {
-            // already loaded, so do nothing
-            return Ok(());
+        let backup_path = self.backup_path(source, db_name)?;
+        let files = self.list_files(&backup_path).await?;
+        let rules_path = object_store_path_for_database_config(&self.root_path()?, db_name);
+        let backup_rules_path = self.prefixed_path(source, &rules_path);
+        if !files.contains(&backup_rules_path) {
+            return BackupNotFound {
+                db_name: db_name.to_string(),
+                location: backup_path.display(),
+            }
+            .fail();
         }
 
-        // get the database names from the object store prefixes
-        // TODO: update object store to pull back all common prefixes by
-        //       following the next tokens.
+        let mut db_path = self.root_path()?;
+        db_path.push_dir(db_name.to_string());
+        let deleted_marker = self.deleted_marker_path(db_name)?;
+        if get_deleted_at(&deleted_marker, &self.store)
+            .await?
+            .is_some()
+        {
+            self.purge_database_files(&db_path).await?;
+        }
+        if !self.list_files(&db_path).await?.is_empty() {
+            return DatabaseDataExists {
+                db_name: db_name.to_string(),
+            }
+            .fail();
+        }
+
+        let prefix_len = source.split('/').filter(|p| !p.is_empty()).count();
+        for file in &files {
+            let mut target: DirsAndFileName = file.clone().into();
+            target.directories.drain(..prefix_len);
+            let target = self.store.path_from_dirs_and_filename(target);
+            copy_object(&self.store, file, &target).await?;
+        }
+
+        Self::load_database_config(
+            server_id,
+            Arc::clone(&self.store),
+            Arc::clone(&self.config),
+            Arc::clone(&self.exec),
+            rules_path,
+        )
+        .await?;
+
+        info!(%db_name, %source, files=files.len(), "restored database");
+
+        Ok(files.len())
+    }
+
+    /// Restores a database deleted with
+    /// [`delete_database`](Self::delete_database) whose data has not been
+    /// purged yet and loads it.
+    pub async fn restore_deleted_database(&self, db_name: &DatabaseName<'_>) -> Result<()> {
+        let server_id = self.require_initialized()?;
+
+        let deleted_marker = self.deleted_marker_path(db_name)?;
+        if get_deleted_at(&deleted_marker, &self.store)
+            .await?
+            .is_none()
+        {
+            return match self.config.db(db_name) {
+                Some(_) => DatabaseAlreadyExists {
+                    db_name: db_name.to_string(),
+                }
+                .fail(),
+                None => DatabaseNotFound {
+                    db_name: db_name.to_string(),
+                }
+                .fail(),
+            };
+        }
+
+        self.store
+            .delete(&deleted_marker)
+            .await
+            .context(StoreError)?;
+
+        Self::load_database_config(
+            server_id,
+            Arc::clone(&self.store),
+            Arc::clone(&self.config),
+            Arc::clone(&self.exec),
+            object_store_path_for_database_config(&self.root_path()?, db_name),
+        )
+        .await?;
+
+        info!(%db_name, "restored deleted database");
+
+        Ok(())
+    }
+
+    /// Removes all data of the databases deleted longer than the configured
+    /// purge-after period ago from object storage, returning the number of
+    /// databases purged.
+    pub async fn purge_deleted_databases(&self) -> Result<usize> {
         let list_result = self
             .store
             .list_with_delimiter(&self.root_path()?)
             .await
             .context(StoreError)?;
 
-        let server_id = self.require_id()?;
+        let now = Utc::now();
+        let mut purged = 0;
+        for db_path in list_result.common_prefixes {
+            let mut deleted_marker = db_path.clone();
+            deleted_marker.set_file_name(DB_DELETED_FILE_NAME);
+            let deleted_at = match get_deleted_at(&deleted_marker, &self.store).await? {
+                Some(deleted_at) => deleted_at,
+                None => continue,
+            };
 
-        let handles: Vec<_> = list_result
-            .common_prefixes
-            .into_iter()
-            .map(|mut path| {
-                let store = Arc::clone(&self.store);
-                let config = Arc::clone(&self.config);
-                let exec = Arc::clone(&self.exec);
+            // a deletion time in the future, e.g. due to clock skew, is not due
+            let due = now
+                .signed_duration_since(deleted_at)
+                .to_std()
+                .map(|age| age >= self.purge_deleted_after)
+                .unwrap_or(false);
+            if !due {
+                continue;
+            }
 
-                path.set_file_name(DB_RULES_FILE_NAME);
+            self.purge_database_files(&db_path).await?;
+            info!(location=%db_path.display(), %deleted_at, "purged deleted database");
+            purged += 1;
+        }
 
-                tokio::task::spawn(async move {
-                    if let Err(e) =
-                        Self::load_database_config(server_id, store, config, exec, path).await
-                    {
-                        error!(%e, "cannot load database");
-                    }
-                })
-            })
-            .collect();
+        Ok(purged)
+    }
 
-        futures::future::join_all(handles).await;
+    /// Deletes all files of the deleted database at `db_path`. The deletion
+    /// marker is removed last so that an interrupted purge is picked up again.
+    async fn purge_database_files(&self, db_path: &Path) -> Result<()> {
+        let mut deleted_marker = db_path.clone();
+        deleted_marker.set_file_name(DB_DELETED_FILE_NAME);
+        let marker_location = DirsAndFileName::from(deleted_marker.clone());
 
-        // mark as ready (use correct ordering for Acquire-Release)
-        self.initialized.store(true, Ordering::Release);
-        info!("loaded databases, server is initalized");
+        for location in self.list_files(db_path).await? {
+            if DirsAndFileName::from(location.clone()) != marker_location {
+                self.store.delete(&location).await.context(StoreError)?;
+            }
+        }
 
-        Ok(())
+        self.store.delete(&deleted_marker).await.context(StoreError)
     }
 
-    async fn load_database_config(
-        server_id: ServerId,
-        store: Arc<ObjectStore>,
+    /// Location of the file flagging a database as deleted
+    fn deleted_marker_path(&self, db_name: &DatabaseName<'_>) -> Result<Path> {
+        let mut path = self.root_path()?;
+        path.push_dir(db_name.to_string());
+        path.set_file_name(DB_DELETED_FILE_NAME);
+        Ok(path)
+    }
+
+    /// Creates database `target` with the rules of database `source`,
+    /// returning the number of parquet files copied.
+    ///
+    /// With `with_data` the parquet files referenced by the preserved catalog
+    /// of `source` are copied to `target` too, where they are loaded as
+    /// object store only chunks. Data that has not been persisted yet is not
+    /// copied.
+    pub async fn clone_database(
+        &self,
+        source: &DatabaseName<'_>,
+        target: DatabaseName<'static>,
+        with_data: bool,
+    ) -> Result<usize> {
+        let server_id = self.require_initialized()?;
+        let mut rules = self.db_rules(source).context(DatabaseNotFound {
+            db_name: source.to_string(),
+        })?;
+        if self.config.db(&target).is_some() {
+            return DatabaseAlreadyExists {
+                db_name: target.to_string(),
+            }
+            .fail();
+        }
+
+        let mut target_path = self.root_path()?;
+        target_path.push_dir(target.to_string());
+        if !self.list_files(&target_path).await?.is_empty() {
+            return DatabaseDataExists {
+                db_name: target.to_string(),
+            }
+            .fail();
+        }
+
+        rules.name = target.clone();
+        let db_reservation = self.config.create_db(rules)?;
+
+        let preserved_catalog = load_or_create_preserved_catalog(
+            &target,
+            Arc::clone(&self.store),
+            server_id,
+            self.config.metrics_registry(),
+        )
+        .await
+        .map_err(|e| Box::new(e) as _)
+        .context(CatalogLoadError)?;
+
+        let mut paths = vec![];
+        if with_data {
+            let dump = CatalogDump::load(&self.store, server_id, source)
+                .await
+                .context(CatalogReadError)?;
+
+            for file in dump.parquet_files() {
+                // parquet files are stored below `<server id>/<db name>/data`
+                let mut target_file = file.clone();
+                target_file.directories[1] = target.as_str().into();
+
+                let from = self.store.path_from_dirs_and_filename(file);
+                let to = self.store.path_from_dirs_and_filename(target_file);
+                copy_object(&self.store, &from, &to).await?;
+                paths.push(to);
+            }
+
+            db::import_parquet_files(&preserved_catalog, &paths)
+                .await
+                .map_err(|e| Box::new(e) as _)
+                .context(CatalogLoadError)?;
+        }
+
+        self.persist_database_rules(db_reservation.rules().clone())
+            .await?;
+        db_reservation.commit(
+            server_id,
+            Arc::clone(&self.store),
+            Arc::clone(&self.exec),
+            preserved_catalog,
+        );
+
+        info!(%source, %target, files=paths.len(), "cloned database");
+
+        Ok(paths.len())
+    }
+
+    /// Writes the data of a database with a timestamp within `range`, or all
+    /// data without a range, as a hive-style partitioned directory of parquet
+    /// files to `destination`, returning the number of files written.
+    ///
+    /// A `destination` starting with `file://` is a directory on the local
+    /// filesystem of the server, anything else is a `/` separated path prefix
+    /// in the server's object store. The destination must be empty.
+    pub async fn export_database(
+        &self,
+        db_name: &DatabaseName<'_>,
+        destination: &str,
+        range: Option<TimestampRange>,
+    ) -> Result<usize> {
+        self.require_initialized()?;
+        let db = self.config.db(db_name).context(DatabaseNotFound {
+            db_name: db_name.to_string(),
+        })?;
+
+        let local_store;
+        let (store, prefix) = match destination.strip_prefix("file://") {
+            Some(directory) => {
+                local_store = ObjectStore::new_file(object_store::disk::File::new(directory));
+                (&local_store, "")
+            }
+            None => (self.store.as_ref(), destination),
+        };
+
+        let mut prefix_path = DirsAndFileName::default();
+        prefix_path.push_all_dirs(
+            prefix
+                .split('/')
+                .filter(|p| !p.is_empty())
+                .collect::<Vec<_>>(),
+        );
+
+        let location = store.path_from_dirs_and_filename(prefix_path.clone());
+        let existing = store
+            .list(Some(&location))
+            .await
+            .context(StoreError)?
+            .try_concat()
+            .await
+            .context(StoreError)?;
+        if !existing.is_empty() {
+            return ExportLocationNotEmpty {
+                location: destination,
+            }
+            .fail();
+        }
+
+        let paths = db::export::export_to_object_store(&db, store, &prefix_path, range)
+            .await
+            .context(ExportingDatabase {
+                db_name: db_name.to_string(),
+            })?;
+
+        info!(%db_name, %destination, files=paths.len(), "exported database");
+
+        Ok(paths.len())
+    }
+
+    /// Imports the TSM files at `paths` on the local filesystem of the server
+    /// into a database as persisted chunks, returning the number of rows
+    /// imported. See [`db::tsm::import_tsm`] for how the data is mapped.
+    ///
+    /// The files are imported one after the other; if a file fails, the
+    /// files before it stay imported.
+    pub async fn import_tsm(&self, db_name: &DatabaseName<'_>, paths: &[String]) -> Result<usize> {
+        self.require_initialized()?;
+        let db = self.config.db(db_name).context(DatabaseNotFound {
+            db_name: db_name.to_string(),
+        })?;
+
+        let mut rows = 0;
+        for path in paths {
+            let data = std::fs::read(path).context(ReadingTsmFile { path })?;
+            rows += db::tsm::import_tsm(&db, data).await.context(ImportingTsm {
+                db_name: db_name.to_string(),
+                path,
+            })?;
+        }
+
+        info!(%db_name, files=paths.len(), rows, "imported TSM files");
+
+        Ok(rows)
+    }
+
+    /// Location of the files of a database below the backup prefix `location`
+    fn backup_path(&self, location: &str, db_name: &DatabaseName<'_>) -> Result<Path> {
+        let mut path = self.prefixed_path(location, &self.root_path()?);
+        path.push_dir(db_name.to_string());
+        Ok(path)
+    }
+
+    /// Prepends the `/` separated `prefix` to `path`
+    fn prefixed_path(&self, prefix: &str, path: &Path) -> Path {
+        let path: DirsAndFileName = path.clone().into();
+        let mut prefixed = DirsAndFileName::default();
+        prefixed.push_all_dirs(
+            prefix
+                .split('/')
+                .filter(|p| !p.is_empty())
+                .collect::<Vec<_>>(),
+        );
+        prefixed.directories.extend(path.directories);
+        prefixed.file_name = path.file_name;
+        self.store.path_from_dirs_and_filename(prefixed)
+    }
+
+    async fn list_files(&self, prefix: &Path) -> Result<Vec<Path>> {
+        self.store
+            .list(Some(prefix))
+            .await
+            .context(StoreError)?
+            .try_concat()
+            .await
+            .context(StoreError)
+    }
+
+    pub async fn persist_database_rules<'a>(&self, rules: DatabaseRules) -> Result<()> {
+        let location = object_store_path_for_database_config(&self.root_path()?, &rules.name);
+
+        let mut data = BytesMut::new();
+        encode_database_rules(rules, &mut data).context(ErrorSerializingRulesProtobuf)?;
+
+        let len = data.len();
+
+        let stream_data = std::io::Result::Ok(data.freeze());
+        self.store
+            .put(
+                &location,
+                futures::stream::once(async move { stream_data }),
+                Some(len),
+            )
+            .await
+            .context(StoreError)?;
+        Ok(())
+    }
+
+    // location in object store of the ID of the server using it
+    fn server_id_path(&self) -> object_store::path::Path {
+        let mut path = self.store.new_path();
+        path.set_file_name(SERVER_ID_FILE_NAME);
+        path
+    }
+
+    /// Sets the server ID to the one persisted in the object store when a
+    /// server using it was initialized, so that a restarted server does not
+    /// need to be configured again. Returns `None` if no ID was persisted.
+    ///
+    /// The object store must not be shared with other servers, or the ID of
+    /// the last one initialized is loaded. As a safeguard, this fails if the
+    /// object store holds the data of more than one server ID.
+    pub async fn load_server_id(&self) -> Result<Option<ServerId>> {
+        let location = self.server_id_path();
+        let list_result = self
+            .store
+            .list_with_delimiter(&self.store.new_path())
+            .await
+            .context(StoreError)?;
+        let persisted = list_result.objects.into_iter().any(|object| {
+            DirsAndFileName::from(object.location) == DirsAndFileName::from(location.clone())
+        });
+        if !persisted {
+            return Ok(None);
+        }
+
+        let mut ids: Vec<_> = list_result
+            .common_prefixes
+            .into_iter()
+            .filter_map(|path| {
+                DirsAndFileName::from(path)
+                    .directories
+                    .last()
+                    .and_then(|dir| dir.encoded().parse::<u32>().ok())
+                    .and_then(|id| ServerId::try_from(id).ok())
+            })
+            .collect();
+        ids.sort();
+        ensure!(ids.len() <= 1, AmbiguousPersistedServerId { ids });
+
+        let data = get_store_bytes(&location, &self.store).await?;
+        let id = std::str::from_utf8(&data)
+            .ok()
+            .and_then(|id| id.trim().parse::<u32>().ok())
+            .and_then(|id| ServerId::try_from(id).ok())
+            .context(InvalidPersistedServerId {
+                location: location.clone(),
+            })?;
+
+        self.set_id(id)?;
+        Ok(Some(id))
+    }
+
+    async fn persist_server_id(&self, id: ServerId) -> Result<()> {
+        let data = Bytes::from(format!("{}\n", id));
+        let len = data.len();
+
+        self.store
+            .put(
+                &self.server_id_path(),
+                futures::stream::once(async move { std::io::Result::Ok(data) }),
+                Some(len),
+            )
+            .await
+            .context(StoreError)
+    }
+
+    // base location in object store for this writer
+    fn root_path(&self) -> Result<object_store::path::Path> {
+        let id = self.require_id()?;
+
+        let mut path = self.store.new_path();
+        path.push_dir(format!("{}", id));
+        Ok(path)
+    }
+
+    /// Loads the database configurations based on the databases in the
+    /// object store. Any databases in the config already won't be
+    /// replaced.
+    ///
+    /// This requires the serverID to be set. It will be a no-op if the configs are already loaded and the server is ready.
+    pub async fn maybe_initialize_server(&self) -> Result<()> {
+        let _guard = self
+            .initialize_semaphore
+            .acquire()
+            .await
+            .expect("semaphore should not be closed");
+
+        // Note that we use Acquire-Release ordering for the atomic within the semaphore to ensure that another thread
+        // that enters this semaphore after we've left actually sees the correct `is_ready` flag.
+        if self.initialized.load(Ordering::Acquire) {
+            // already loaded, so do nothing
+            return Ok(());
+        }
+
+        // get the database names from the object store prefixes
+        // TODO: update object store to pull back all common prefixes by
+        //       following the next tokens.
+        let list_result = self
+            .store
+            .list_with_delimiter(&self.root_path()?)
+            .await
+            .context(StoreError)?;
+
+        let server_id = self.require_id()?;
+        self.persist_server_id(server_id).await?;
+
+        let handles: Vec<_> = list_result
+            .common_prefixes
+            .into_iter()
+            .map(|mut path| {
+                let store = Arc::clone(&self.store);
+                let config = Arc::clone(&self.config);
+                let exec = Arc::clone(&self.exec);
+
+                let mut deleted_marker = path.clone();
+                deleted_marker.set_file_name(DB_DELETED_FILE_NAME);
+                path.set_file_name(DB_RULES_FILE_NAME);
+
+                tokio::task::spawn(async move {
+                    match get_deleted_at(&deleted_marker, &store).await {
+                        Ok(None) => {}
+                        Ok(Some(deleted_at)) => {
+                            info!(location=%path.display(), %deleted_at, "skipping deleted database");
+                            return;
+                        }
+                        Err(e) => {
+                            error!(%e, "cannot load database");
+                            return;
+                        }
+                    }
+
+                    if let Err(e) =
+                        Self::load_database_config(server_id, store, config, exec, path).await
+                    {
+                        error!(%e, "cannot load database");
+                    }
+                })
+            })
+            .collect();
+
+        futures::future::join_all(handles).await;
+
+        // mark as ready (use correct ordering for Acquire-Release)
+        self.initialized.store(true, Ordering::Release);
+        info!("loaded databases, server is initalized");
+
+        Ok(())
+    }
+
+    async fn load_database_config(
+        server_id: ServerId,
+        store: Arc<ObjectStore>,
         config: Arc<Config>,
         exec: Arc<Executor>,
         path: Path,
@@ -660,15 +1607,19 @@ impl<M: ConnectionManager> Server<M> {
     /// WriteBuffer and/or the MutableBuffer if configured.
     ///
     /// The provided `default_time` is nanoseconds since the epoch and will be assigned
-    /// to any lines that don't have a timestamp.
+    /// to any lines that don't have a timestamp. The timestamps of the lines are
+    /// in `precision` and converted to nanoseconds.
+    #[tracing::instrument(skip(self, lines), fields(lines = lines.len()))]
     pub async fn write_lines(
         &self,
         db_name: &str,
         lines: &[ParsedLine<'_>],
         default_time: i64,
+        precision: Precision,
     ) -> Result<()> {
         // Return an error if this server is not yet ready
         self.require_initialized()?;
+        ensure!(!self.is_shutting_down(), ShuttingDown);
 
         let db_name = DatabaseName::new(db_name).context(InvalidDatabaseName)?;
         let db = self
@@ -679,10 +1630,13 @@ impl<M: ConnectionManager> Server<M> {
         // need to split this in two blocks because we cannot hold a lock across an async call.
         let routing_config_target = {
             let rules = db.rules.read();
+            validate_lines(lines, &rules.line_limits).context(LineConversion)?;
+
             if let Some(RoutingRules::RoutingConfig(routing_config)) = &rules.routing_rules {
                 let sharded_entries = lines_to_sharded_entries(
                     lines,
                     default_time,
+                    precision,
                     None as Option<&ShardConfig>,
                     &*rules,
                 )
@@ -707,7 +1661,7 @@ impl<M: ConnectionManager> Server<M> {
         // groups. This map is atomically replaced every time the sharding
         // config is updated, hence it's safe to use after we release the shard config
         // lock.
-        let (sharded_entries, shards) = {
+        let (sharded_entries, shards, ignore_errors) = {
             let rules = db.rules.read();
 
             let shard_config = rules.routing_rules.as_ref().map(|cfg| match cfg {
@@ -716,36 +1670,147 @@ impl<M: ConnectionManager> Server<M> {
             });
 
             let sharded_entries =
-                lines_to_sharded_entries(lines, default_time, shard_config, &*rules)
+                lines_to_sharded_entries(lines, default_time, precision, shard_config, &*rules)
                     .context(LineConversion)?;
 
             let shards = shard_config
                 .as_ref()
                 .map(|cfg| Arc::clone(&cfg.shards))
                 .unwrap_or_default();
+            let ignore_errors = shard_config.map_or(false, |cfg| cfg.ignore_errors);
 
-            (sharded_entries, shards)
+            (sharded_entries, shards, ignore_errors)
         };
 
         // Write to all shards in parallel; as soon as one fails return error
         // immediately to the client and abort all other outstanding requests.
         // This can take some time, but we're no longer holding the lock to the shard
         // config.
-        futures_util::future::try_join_all(
-            sharded_entries
-                .into_iter()
-                .map(|e| self.write_sharded_entry(&db_name, &db, Arc::clone(&shards), e)),
-        )
+        futures_util::future::try_join_all(sharded_entries.into_iter().map(|e| {
+            self.write_sharded_entry(&db_name, &db, Arc::clone(&shards), ignore_errors, e)
+        }))
         .await?;
 
         Ok(())
     }
 
+    /// Like [`write_lines`](Self::write_lines), but if the lines are rejected
+    /// because some of them conflict with the schema or exceed the line
+    /// limits or quotas of the database, writes the other lines one by one.
+    /// Returns the index in `lines` and the error of every rejected line
+    pub async fn write_lines_partial(
+        &self,
+        db_name: &str,
+        lines: &[ParsedLine<'_>],
+        default_time: i64,
+        precision: Precision,
+    ) -> Result<Vec<(usize, Error)>> {
+        match self
+            .write_lines(db_name, lines, default_time, precision)
+            .await
+        {
+            Ok(()) => return Ok(vec![]),
+            Err(e) if e.is_rejected_write() && lines.len() == 1 => return Ok(vec![(0, e)]),
+            Err(e) if e.is_rejected_write() => {}
+            Err(e) => return Err(e),
+        }
+
+        // Rejected lines were not written at all, see `Db::store_sequenced_entry`
+        let mut rejected = vec![];
+        for (idx, line) in lines.iter().enumerate() {
+            match self
+                .write_lines(db_name, std::slice::from_ref(line), default_time, precision)
+                .await
+            {
+                Ok(()) => {}
+                Err(e) if e.is_rejected_write() => rejected.push((idx, e)),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(rejected)
+    }
+
+    /// Like [`write_lines`](Self::write_lines), but writes the lines only if
+    /// all of them can be written. Otherwise nothing is written and the index
+    /// in `lines` and the error of every line that would be rejected on its
+    /// own are returned, or if there are none, of the first line that cannot
+    /// be written together with the lines before it.
+    ///
+    /// Not supported for databases with routing rules, whose writes may be
+    /// split into several entries
+    pub async fn write_lines_all_or_nothing(
+        &self,
+        db_name: &str,
+        lines: &[ParsedLine<'_>],
+        default_time: i64,
+        precision: Precision,
+    ) -> Result<Vec<(usize, Error)>> {
+        self.require_initialized()?;
+        ensure!(!self.is_shutting_down(), ShuttingDown);
+
+        let db_name = DatabaseName::new(db_name).context(InvalidDatabaseName)?;
+        let db = self
+            .config
+            .db(&db_name)
+            .context(DatabaseNotFound { db_name: &*db_name })?;
+        ensure!(
+            db.rules.read().routing_rules.is_none(),
+            AllOrNothingRouted { db_name: &*db_name }
+        );
+
+        let sharded_entries = match validate_write(&db, lines, default_time, precision) {
+            Ok(sharded_entries) => sharded_entries,
+            Err(e) if e.is_rejected_write() => {
+                let rejected: Vec<_> = lines
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, line)| {
+                        validate_write(&db, std::slice::from_ref(line), default_time, precision)
+                            .err()
+                            .map(|e| (idx, e))
+                    })
+                    .collect();
+                if !rejected.is_empty() {
+                    return Ok(rejected);
+                }
+
+                // The lines are only rejected together, e.g. because they
+                // conflict with each other or exceed a quota, so search for
+                // the first line that cannot be written after the ones before
+                let (mut valid, mut invalid, mut error) = (1, lines.len(), e);
+                while invalid - valid > 1 {
+                    let mid = valid + (invalid - valid) / 2;
+                    match validate_write(&db, &lines[..mid], default_time, precision) {
+                        Ok(_) => valid = mid,
+                        Err(e) if e.is_rejected_write() => {
+                            invalid = mid;
+                            error = e;
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                return Ok(vec![(invalid - 1, error)]);
+            }
+            Err(e) => return Err(e),
+        };
+
+        // Without routing rules all lines are converted into a single entry,
+        // which is stored as a whole or not at all
+        for sharded_entry in sharded_entries {
+            self.write_entry_local(&db_name, &db, sharded_entry.entry)
+                .await?;
+        }
+
+        Ok(vec![])
+    }
+
     async fn write_sharded_entry(
         &self,
         db_name: &str,
         db: &Db,
         shards: Arc<HashMap<u32, Shard>>,
+        ignore_errors: bool,
         sharded_entry: ShardedEntry,
     ) -> Result<()> {
         match sharded_entry.shard_id {
@@ -753,8 +1818,15 @@ impl<M: ConnectionManager> Server<M> {
                 let shard = shards.get(&shard_id).context(ShardNotFound { shard_id })?;
                 match shard {
                     Shard::Iox(node_group) => {
-                        self.write_entry_downstream(db_name, node_group, sharded_entry.entry)
-                            .await?
+                        let written = self
+                            .write_entry_downstream(db_name, node_group, sharded_entry.entry)
+                            .await;
+                        match written {
+                            Err(e) if ignore_errors => {
+                                warn!(%e, %db_name, shard_id, "ignoring error writing to shard")
+                            }
+                            written => written?,
+                        }
                     }
                 }
             }
@@ -766,6 +1838,7 @@ impl<M: ConnectionManager> Server<M> {
         Ok(())
     }
 
+    #[tracing::instrument(level = "debug", skip(self, entry), fields(bytes = entry.data().len()))]
     async fn write_entry_downstream(
         &self,
         db_name: &str,
@@ -801,9 +1874,11 @@ impl<M: ConnectionManager> Server<M> {
         return NoRemoteReachable { errors }.fail();
     }
 
+    #[tracing::instrument(skip(self, entry_bytes), fields(bytes = entry_bytes.len()))]
     pub async fn write_entry(&self, db_name: &str, entry_bytes: Vec<u8>) -> Result<()> {
         // Return an error if this server is not yet ready
         self.require_initialized()?;
+        ensure!(!self.is_shutting_down(), ShuttingDown);
 
         let db_name = DatabaseName::new(db_name).context(InvalidDatabaseName)?;
         let db = self
@@ -815,9 +1890,43 @@ impl<M: ConnectionManager> Server<M> {
         self.write_entry_local(&db_name, &db, entry).await
     }
 
+    /// Returns the memory used by all databases, see [`Db::memory_used`]
+    pub fn memory_used(&self) -> usize {
+        self.config
+            .db_names_sorted()
+            .iter()
+            .filter_map(|name| self.config.db(name))
+            .map(|db| db.memory_used())
+            .sum()
+    }
+
+    /// Waits for the memory used by all databases to drop below the server's
+    /// budget, e.g. while the lifecycle policy compacts or drops chunks, so
+    /// that writes are delayed before they are rejected
+    async fn wait_for_memory(&self) -> Result<()> {
+        let limit = match self.max_memory {
+            Some(limit) => limit.get(),
+            None => return Ok(()),
+        };
+
+        let deadline = std::time::Instant::now() + self.memory_backpressure;
+        loop {
+            let used = self.memory_used();
+            if used <= limit {
+                return Ok(());
+            }
+            if std::time::Instant::now() >= deadline {
+                return ResourcesExhausted { used, limit }.fail();
+            }
+            tokio::time::sleep(MEMORY_POLL_INTERVAL).await;
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, db, entry), fields(bytes = entry.data().len()))]
     pub async fn write_entry_local(&self, db_name: &str, db: &Db, entry: Entry) -> Result<()> {
         let bytes = entry.data().len() as u64;
-        db.store_entry(entry).map_err(|e| {
+
+        if let Err(e) = self.wait_for_memory().await {
             self.metrics.ingest_entries_bytes_total.add_with_labels(
                 bytes,
                 &[
@@ -825,12 +1934,21 @@ impl<M: ConnectionManager> Server<M> {
                     metrics::KeyValue::new("db_name", db_name.to_string()),
                 ],
             );
-            match e {
-                db::Error::HardLimitReached {} => Error::HardLimitReached {},
-                _ => Error::UnknownDatabaseError {
-                    source: Box::new(e),
-                },
-            }
+            return Err(e);
+        }
+
+        let replication_config = db.rules.read().replication_config.clone();
+        let replicated_entry = replication_config.as_ref().map(|_| entry.clone());
+
+        db.store_entry(entry).await.map_err(|e| {
+            self.metrics.ingest_entries_bytes_total.add_with_labels(
+                bytes,
+                &[
+                    metrics::KeyValue::new("status", "error"),
+                    metrics::KeyValue::new("db_name", db_name.to_string()),
+                ],
+            );
+            db_write_error(e)
         })?;
 
         self.metrics.ingest_entries_bytes_total.add_with_labels(
@@ -841,13 +1959,120 @@ impl<M: ConnectionManager> Server<M> {
             ],
         );
 
+        // Only entries that were written locally are replicated
+        if let (Some(config), Some(entry)) = (replication_config, replicated_entry) {
+            for target in self.replication.enqueue(db_name, &config, &entry) {
+                warn!(%db_name, %target, "replication queue full, dropping entry");
+                self.metrics.replication_entries_total.add_with_labels(
+                    1,
+                    &[
+                        metrics::KeyValue::new("status", "dropped"),
+                        metrics::KeyValue::new("db_name", db_name.to_string()),
+                        metrics::KeyValue::new("target", target.to_string()),
+                    ],
+                );
+            }
+        }
+
         Ok(())
     }
 
+    /// Returns how far each replication target is behind, and how many
+    /// entries it missed because its queue was full
+    pub fn replication_status(&self) -> Vec<ReplicationStatus> {
+        self.replication.status()
+    }
+
+    /// Ships the entries queued for replication to their targets, oldest
+    /// first. Shipping to a target stops at the first error; the failed entry
+    /// is retried on the next call. Returns false if any target failed.
+    async fn ship_replication_queues(&self) -> bool {
+        let queues = self.replication.pending();
+        futures::future::join_all(
+            queues
+                .iter()
+                .map(|queue| self.ship_replication_queue(queue)),
+        )
+        .await
+        .into_iter()
+        .all(|shipped| shipped)
+    }
+
+    async fn ship_replication_queue(&self, queue: &ReplicationQueue) -> bool {
+        let db_name = &queue.db_name;
+        let target = queue.target;
+
+        let shipped = async {
+            let addr = self
+                .config
+                .resolve_remote(target)
+                .context(NoRemoteConfigured {
+                    node_group: vec![target],
+                })?;
+            let remote = self
+                .connection_manager
+                .remote_server(&addr)
+                .await
+                .context(RemoteError)?;
+
+            while let Some(entry) = queue.front() {
+                remote
+                    .write_entry(db_name, entry)
+                    .await
+                    .context(RemoteError)?;
+                queue.pop_front();
+
+                self.metrics.replication_entries_total.add_with_labels(
+                    1,
+                    &[
+                        metrics::KeyValue::new("status", "ok"),
+                        metrics::KeyValue::new("db_name", db_name.to_string()),
+                        metrics::KeyValue::new("target", target.to_string()),
+                    ],
+                );
+            }
+
+            Ok::<_, Error>(())
+        };
+
+        match shipped.await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(%e, %db_name, %target, "error replicating entries, will retry");
+                false
+            }
+        }
+    }
+
     pub fn db(&self, name: &DatabaseName<'_>) -> Option<Arc<Db>> {
         self.config.db(name)
     }
 
+    /// Returns the databases, other than `db_name`, that the SQL `query`
+    /// refers to as `<database>.<table>` and that `token` may read, to
+    /// plan queries across databases
+    pub fn referenced_dbs(
+        &self,
+        db_name: &DatabaseName<'_>,
+        query: &str,
+        token: Option<&str>,
+    ) -> BTreeMap<String, Arc<Db>> {
+        self.config
+            .db_names_sorted()
+            .into_iter()
+            .filter(|name| name != db_name && refers_to(query, name))
+            .filter(|name| {
+                self.auth
+                    .authorize(token, name.as_str(), auth::Scope::Read)
+                    .is_ok()
+            })
+            .filter_map(|name| {
+                let db = self.db(&name)?;
+                Some((name.to_string(), db))
+            })
+            .collect()
+    }
+
     pub fn db_rules(&self, name: &DatabaseName<'_>) -> Option<DatabaseRules> {
         self.config.db(name).map(|d| d.rules.read().clone())
     }
@@ -922,6 +2147,52 @@ impl<M: ConnectionManager> Server<M> {
         Ok(db.load_chunk_to_read_buffer_in_background(partition_key, table_name, chunk_id))
     }
 
+    /// Writes a chunk in the read buffer to object store, as a background
+    /// job, registering the resulting parquet file in the preserved catalog.
+    pub fn persist_chunk(
+        &self,
+        db_name: DatabaseName<'_>,
+        partition_key: impl Into<String>,
+        table_name: impl Into<String>,
+        chunk_id: u32,
+    ) -> Result<TaskTracker<Job>> {
+        let db_name = db_name.to_string();
+        let name = DatabaseName::new(&db_name).context(InvalidDatabaseName)?;
+
+        let partition_key = partition_key.into();
+        let table_name = table_name.into();
+
+        let db = self
+            .config
+            .db(&name)
+            .context(DatabaseNotFound { db_name: &db_name })?;
+
+        Ok(db.write_chunk_to_object_store_in_background(partition_key, table_name, chunk_id))
+    }
+
+    /// Compacts the persisted chunks of a table within a partition into a
+    /// single chunk, as a background job, swapping their parquet files in the
+    /// preserved catalog.
+    pub fn compact_partition(
+        &self,
+        db_name: DatabaseName<'_>,
+        partition_key: impl Into<String>,
+        table_name: impl Into<String>,
+    ) -> Result<TaskTracker<Job>> {
+        let db_name = db_name.to_string();
+        let name = DatabaseName::new(&db_name).context(InvalidDatabaseName)?;
+
+        let partition_key = partition_key.into();
+        let table_name = table_name.into();
+
+        let db = self
+            .config
+            .db(&name)
+            .context(DatabaseNotFound { db_name: &db_name })?;
+
+        Ok(db.compact_partition_in_background(partition_key, table_name))
+    }
+
     /// Returns a list of all jobs tracked by this server
     pub fn tracked_jobs(&self) -> Vec<TaskTracker<Job>> {
         self.jobs.inner.lock().tracked()
@@ -938,20 +2209,54 @@ impl<M: ConnectionManager> Server<M> {
 
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
 
-        while !shutdown.is_cancelled() {
-            if self.require_id().is_ok() {
-                if let Err(e) = self.maybe_initialize_server().await {
-                    error!(%e, "error during DB loading");
-                }
-            }
+        tokio::join!(
+            async {
+                while !shutdown.is_cancelled() {
+                    if self.require_id().is_ok() {
+                        if let Err(e) = self.maybe_initialize_server().await {
+                            error!(%e, "error during DB loading");
+                        }
+                    }
 
-            self.jobs.inner.lock().reclaim();
+                    self.jobs.inner.lock().reclaim();
 
-            tokio::select! {
-                _ = interval.tick() => {},
-                _ = shutdown.cancelled() => break
-            }
-        }
+                    tokio::select! {
+                        _ = interval.tick() => {},
+                        _ = shutdown.cancelled() => break
+                    }
+                }
+            },
+            // replication loop
+            async {
+                while !shutdown.is_cancelled() {
+                    let shipped = tokio::select! {
+                        shipped = self.ship_replication_queues() => shipped,
+                        _ = shutdown.cancelled() => break
+                    };
+
+                    tokio::select! {
+                        _ = self.replication.notified(), if shipped => {},
+                        _ = tokio::time::sleep(REPLICATION_RETRY_INTERVAL), if !shipped => {},
+                        _ = shutdown.cancelled() => break
+                    }
+                }
+            },
+            // purge loop
+            async {
+                while !shutdown.is_cancelled() {
+                    if self.initialized() {
+                        if let Err(e) = self.purge_deleted_databases().await {
+                            error!(%e, "error purging deleted databases");
+                        }
+                    }
+
+                    tokio::select! {
+                        _ = tokio::time::sleep(PURGE_DELETED_INTERVAL) => {},
+                        _ = shutdown.cancelled() => break
+                    }
+                }
+            },
+        );
 
         info!("shutting down background workers");
         self.config.drain().await;
@@ -1121,6 +2426,21 @@ async fn get_store_bytes(
     Ok(b)
 }
 
+// copy the object at `from` to `to` within the same object store
+async fn copy_object(store: &ObjectStore, from: &Path, to: &Path) -> Result<()> {
+    let data = get_store_bytes(from, store).await?.freeze();
+    let len = data.len();
+
+    store
+        .put(
+            to,
+            futures::stream::once(async move { std::io::Result::Ok(data) }),
+            Some(len),
+        )
+        .await
+        .context(StoreError)
+}
+
 // get the bytes for the database rule config file, if it exists,
 // otherwise it returns none.
 async fn get_database_config_bytes(
@@ -1140,6 +2460,44 @@ async fn get_database_config_bytes(
     get_store_bytes(location, store).await
 }
 
+// get the time the database was deleted at from the deletion marker at
+// `location`, or none if the database is not flagged as deleted
+async fn get_deleted_at(
+    location: &object_store::path::Path,
+    store: &ObjectStore,
+) -> Result<Option<DateTime<Utc>>> {
+    let list_result = store
+        .list_with_delimiter(location)
+        .await
+        .context(StoreError)?;
+    if list_result.objects.is_empty() {
+        return Ok(None);
+    }
+
+    let data = get_store_bytes(location, store).await?;
+    let deleted_at = std::str::from_utf8(&data)
+        .ok()
+        .and_then(|s| DateTime::parse_from_rfc3339(s.trim()).ok())
+        .context(InvalidDeletionMarker {
+            location: location.clone(),
+        })?;
+
+    Ok(Some(deleted_at.with_timezone(&Utc)))
+}
+
+/// Returns whether the SQL `query` refers to the schema `name`, plain or
+/// quoted, as in `name.table` or `"name".table`
+fn refers_to(query: &str, name: &str) -> bool {
+    let quoted = format!("\"{}\".", name);
+    let plain = format!("{}.", name);
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+
+    query.contains(&quoted)
+        || query
+            .match_indices(&plain)
+            .any(|(start, _)| !query[..start].ends_with(is_ident) && !query[..start].ends_with('"'))
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
@@ -1155,8 +2513,12 @@ mod tests {
     use tokio_util::sync::CancellationToken;
 
     use arrow_util::assert_batches_eq;
-    use data_types::database_rules::{
-        HashRing, PartitionTemplate, ShardConfig, TemplatePart, NO_SHARD_CONFIG,
+    use data_types::{
+        chunk_metadata::ChunkStorage,
+        database_rules::{
+            HashRing, PartitionTemplate, ReplicationConfig, ShardConfig, TemplatePart,
+            NO_SHARD_CONFIG,
+        },
     };
     use influxdb_line_protocol::parse_lines;
     use metrics::MetricRegistry;
@@ -1216,7 +2578,12 @@ mod tests {
 
         let lines = parsed_lines("cpu foo=1 10");
         let resp = server
-            .write_lines("foo", &lines, ARBITRARY_DEFAULT_TIME)
+            .write_lines(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
             .await
             .unwrap_err();
         assert!(matches!(resp, Error::IdNotSet));
@@ -1235,13 +2602,20 @@ mod tests {
 
         let rules = DatabaseRules {
             name: name.clone(),
-            partition_template: PartitionTemplate {
-                parts: vec![TemplatePart::TimeFormat("YYYY-MM".to_string())],
-            },
+            partition_template: PartitionTemplate::new(vec![TemplatePart::TimeFormat(
+                "YYYY-MM".to_string(),
+            )]),
             lifecycle_rules: Default::default(),
             routing_rules: None,
             worker_cleanup_avg_sleep: Duration::from_secs(2),
             write_buffer_connection_string: None,
+            replication_config: None,
+            quotas: Default::default(),
+            line_limits: Default::default(),
+            schema_conflict_policy: Default::default(),
+            type_coercion_rules: vec![],
+            rollup_rules: vec![],
+            continuous_queries: vec![],
         };
 
         // Create a database
@@ -1288,6 +2662,64 @@ mod tests {
         let _ = server2.db(&name).unwrap();
     }
 
+    #[tokio::test]
+    async fn persists_server_id() {
+        let store = Arc::new(ObjectStore::new_in_memory(InMemory::new()));
+        let config = || {
+            ServerConfig::new(Arc::clone(&store), Arc::new(MetricRegistry::new()), None)
+                .with_num_worker_threads(1)
+        };
+
+        let server = Server::new(TestConnectionManager::new(), config());
+        assert_eq!(server.load_server_id().await.unwrap(), None);
+        server.set_id(ServerId::try_from(42).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+
+        let name = DatabaseName::new("foo").unwrap();
+        server
+            .create_database(DatabaseRules::new(name.clone()))
+            .await
+            .unwrap();
+
+        // A restarted server comes up with the same ID and databases
+        let server = Server::new(TestConnectionManager::new(), config());
+        let id = server.load_server_id().await.unwrap();
+        assert_eq!(id, Some(ServerId::try_from(42).unwrap()));
+        assert_eq!(server.require_id().unwrap(), id.unwrap());
+        server.maybe_initialize_server().await.unwrap();
+        assert_eq!(server.db_names_sorted(), vec!["foo".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn server_id_not_loaded_from_shared_store() {
+        let store = Arc::new(ObjectStore::new_in_memory(InMemory::new()));
+        let config = || {
+            ServerConfig::new(Arc::clone(&store), Arc::new(MetricRegistry::new()), None)
+                .with_num_worker_threads(1)
+        };
+
+        for id in &[1, 2] {
+            let server = Server::new(TestConnectionManager::new(), config());
+            server.set_id(ServerId::try_from(*id).unwrap()).unwrap();
+            server.maybe_initialize_server().await.unwrap();
+            server
+                .create_database(DatabaseRules::new(DatabaseName::new("foo").unwrap()))
+                .await
+                .unwrap();
+        }
+
+        // Loading the ID of the server initialized last would take over the
+        // databases of the other one
+        let server = Server::new(TestConnectionManager::new(), config());
+        let err = server.load_server_id().await.unwrap_err();
+        assert!(
+            matches!(err, Error::AmbiguousPersistedServerId { .. }),
+            "{}",
+            err
+        );
+        assert!(server.require_id().is_err());
+    }
+
     #[tokio::test]
     async fn duplicate_database_name_rejected() {
         // Covers #643
@@ -1324,13 +2756,20 @@ mod tests {
 
         let rules = DatabaseRules {
             name,
-            partition_template: PartitionTemplate {
-                parts: vec![TemplatePart::TimeFormat("YYYY-MM".to_string())],
-            },
+            partition_template: PartitionTemplate::new(vec![TemplatePart::TimeFormat(
+                "YYYY-MM".to_string(),
+            )]),
             lifecycle_rules: Default::default(),
             routing_rules: None,
             worker_cleanup_avg_sleep: Duration::from_secs(2),
             write_buffer_connection_string: None,
+            replication_config: None,
+            quotas: Default::default(),
+            line_limits: Default::default(),
+            schema_conflict_policy: Default::default(),
+            type_coercion_rules: vec![],
+            rollup_rules: vec![],
+            continuous_queries: vec![],
         };
 
         // Create a database
@@ -1388,15 +2827,423 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn db_names_sorted() {
+    async fn delete_database() {
+        let manager = TestConnectionManager::new();
+        let server = Server::new(manager, config());
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+
+        create_simple_database(&server, "apples")
+            .await
+            .expect("failed to create database");
+        create_simple_database(&server, "bananas")
+            .await
+            .expect("failed to create database");
+
+        let bananas = DatabaseName::new("bananas").unwrap();
+        let mut bananas_path = server.store.new_path();
+        bananas_path.push_all_dirs(&["1", "bananas"]);
+
+        async fn list_files(store: &ObjectStore, path: &Path) -> Vec<Path> {
+            store
+                .list(Some(path))
+                .await
+                .unwrap()
+                .try_concat()
+                .await
+                .unwrap()
+        }
+
+        // Preserving the data only removes the rules
+        server.delete_database(&bananas, true).await.unwrap();
+        assert!(server.db(&bananas).is_none());
+        assert_eq!(server.db_names_sorted(), vec!["apples"]);
+
+        let files = list_files(&server.store, &bananas_path).await;
+        assert!(!files.is_empty());
+        assert!(files
+            .iter()
+            .all(|path| !path.display().ends_with(DB_RULES_FILE_NAME)));
+
+        // The database can be re-attached
+        create_simple_database(&server, "bananas")
+            .await
+            .expect("failed to re-create database");
+        assert_eq!(server.db_names_sorted(), vec!["apples", "bananas"]);
+
+        // Otherwise the database is flagged as deleted, keeping its data
+        server.delete_database(&bananas, false).await.unwrap();
+        assert_eq!(server.db_names_sorted(), vec!["apples"]);
+        let files = list_files(&server.store, &bananas_path).await;
+        assert!(files
+            .iter()
+            .any(|path| path.display().ends_with(DB_RULES_FILE_NAME)));
+        assert!(files
+            .iter()
+            .any(|path| path.display().ends_with(DB_DELETED_FILE_NAME)));
+
+        let err = server.delete_database(&bananas, false).await.unwrap_err();
+        assert!(matches!(err, Error::DatabaseNotFound { .. }));
+
+        let err = create_simple_database(&server, "bananas")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::DatabaseDeleted { .. }));
+
+        // The deleted database is not purged before the purge-after period
+        assert_eq!(server.purge_deleted_databases().await.unwrap(), 0);
+
+        server.restore_deleted_database(&bananas).await.unwrap();
+        assert_eq!(server.db_names_sorted(), vec!["apples", "bananas"]);
+        assert!(list_files(&server.store, &bananas_path)
+            .await
+            .iter()
+            .all(|path| !path.display().ends_with(DB_DELETED_FILE_NAME)));
+
+        let err = server.restore_deleted_database(&bananas).await.unwrap_err();
+        assert!(matches!(err, Error::DatabaseAlreadyExists { .. }));
+
+        let cherries = DatabaseName::new("cherries").unwrap();
+        let err = server
+            .restore_deleted_database(&cherries)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::DatabaseNotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn purge_deleted_databases() {
+        let store = Arc::new(ObjectStore::new_in_memory(InMemory::new()));
+        let server_config = || {
+            ServerConfig::new(Arc::clone(&store), Arc::new(MetricRegistry::new()), None)
+                .with_num_worker_threads(1)
+                .with_purge_deleted_after(Duration::from_secs(0))
+        };
+
+        let server = Server::new(TestConnectionManager::new(), server_config());
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+
+        create_simple_database(&server, "apples")
+            .await
+            .expect("failed to create database");
+        create_simple_database(&server, "bananas")
+            .await
+            .expect("failed to create database");
+
+        let bananas = DatabaseName::new("bananas").unwrap();
+        server.delete_database(&bananas, false).await.unwrap();
+
+        // a restarted server does not load the deleted database
+        let restarted = Server::new(TestConnectionManager::new(), server_config());
+        restarted.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        restarted.maybe_initialize_server().await.unwrap();
+        assert_eq!(restarted.db_names_sorted(), vec!["apples"]);
+
+        assert_eq!(server.purge_deleted_databases().await.unwrap(), 1);
+        assert_eq!(server.purge_deleted_databases().await.unwrap(), 0);
+
+        let mut bananas_path = server.store.new_path();
+        bananas_path.push_all_dirs(&["1", "bananas"]);
+        assert!(server.list_files(&bananas_path).await.unwrap().is_empty());
+
+        let err = server.restore_deleted_database(&bananas).await.unwrap_err();
+        assert!(matches!(err, Error::DatabaseNotFound { .. }));
+
+        // the name can be used again once the database is purged
+        create_simple_database(&server, "bananas")
+            .await
+            .expect("failed to create database");
+        assert_eq!(server.db_names_sorted(), vec!["apples", "bananas"]);
+    }
+
+    #[tokio::test]
+    async fn backup_and_restore_database() {
+        let manager = TestConnectionManager::new();
+        let server = Server::new(manager, config());
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+
+        create_simple_database(&server, "bananas")
+            .await
+            .expect("failed to create database");
+        let bananas = DatabaseName::new("bananas").unwrap();
+
+        // persist a chunk so the catalog references a parquet file
+        let db = server.db(&bananas).unwrap();
+        crate::db::test_helpers::write_lp(&db, "cpu bar=1 10").await;
+        db.rollover_partition("1970-01", "cpu").await.unwrap();
+        db.load_chunk_to_read_buffer("1970-01", "cpu", 0, &Default::default())
+            .await
+            .unwrap();
+        db.write_chunk_to_object_store("1970-01", "cpu", 0, &Default::default())
+            .await
+            .unwrap();
+
+        let copied = server.backup_database(&bananas, "backups/1").await.unwrap();
+
+        let mut backup_path = server.store.new_path();
+        backup_path.push_all_dirs(&["backups", "1", "1", "bananas"]);
+        let backup_files = server.list_files(&backup_path).await.unwrap();
+        assert_eq!(backup_files.len(), copied);
+        assert_eq!(
+            backup_files
+                .iter()
+                .filter(|path| path.display().ends_with(".parquet"))
+                .count(),
+            1
+        );
+        assert!(backup_files
+            .iter()
+            .any(|path| path.display().ends_with(DB_RULES_FILE_NAME)));
+
+        let err = server
+            .backup_database(&bananas, "backups/1")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::BackupLocationNotEmpty { .. }));
+
+        let err = server
+            .restore_database(&bananas, "backups/1")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::DatabaseAlreadyExists { .. }));
+
+        // leftover data of a detached database is not overwritten
+        server.delete_database(&bananas, true).await.unwrap();
+        let err = server
+            .restore_database(&bananas, "backups/1")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::DatabaseDataExists { .. }));
+
+        let mut bananas_path = server.store.new_path();
+        bananas_path.push_all_dirs(&["1", "bananas"]);
+        for path in server.list_files(&bananas_path).await.unwrap() {
+            server.store.delete(&path).await.unwrap();
+        }
+
+        let err = server
+            .restore_database(&bananas, "backups/2")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::BackupNotFound { .. }));
+
+        let restored = server
+            .restore_database(&bananas, "backups/1")
+            .await
+            .unwrap();
+        assert_eq!(restored, copied);
+        assert_eq!(server.db_names_sorted(), vec!["bananas"]);
+
+        let db = server.db(&bananas).unwrap();
+        let chunks = db.partition_chunk_summaries("1970-01");
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].storage, ChunkStorage::ObjectStoreOnly);
+    }
+
+    #[tokio::test]
+    async fn export_database() {
+        let manager = TestConnectionManager::new();
+        let server = Server::new(manager, config());
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+
+        create_simple_database(&server, "bananas")
+            .await
+            .expect("failed to create database");
+        let bananas = DatabaseName::new("bananas").unwrap();
+
+        let db = server.db(&bananas).unwrap();
+        crate::db::test_helpers::write_lp(&db, "cpu bar=1 10\nmem foo=2 20").await;
+
+        let exported = server
+            .export_database(&bananas, "exports/1", Some(TimestampRange::new(0, 15)))
+            .await
+            .unwrap();
+        assert_eq!(exported, 1);
+
+        let mut export_path = server.store.new_path();
+        export_path.push_all_dirs(&["exports", "1"]);
+        let export_files: Vec<_> = server
+            .list_files(&export_path)
+            .await
+            .unwrap()
+            .iter()
+            .map(|path| path.display())
+            .collect();
+        assert_eq!(
+            export_files,
+            vec!["exports/1/cpu/partition_key=1970-01/data.parquet"]
+        );
+
+        let err = server
+            .export_database(&bananas, "exports/1", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ExportLocationNotEmpty { .. }));
+
+        // export to the local filesystem
+        let dir = tempfile::tempdir().unwrap();
+        let destination = format!("file://{}", dir.path().display());
+        let exported = server
+            .export_database(&bananas, &destination, None)
+            .await
+            .unwrap();
+        assert_eq!(exported, 2);
+        assert!(dir
+            .path()
+            .join("mem/partition_key=1970-01/data.parquet")
+            .exists());
+
+        let err = server
+            .export_database(&DatabaseName::new("apples").unwrap(), "exports/2", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::DatabaseNotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn import_tsm_errors() {
+        let manager = TestConnectionManager::new();
+        let server = Server::new(manager, config());
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+
+        create_simple_database(&server, "bananas")
+            .await
+            .expect("failed to create database");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.tsm").display().to_string();
+        let err = server
+            .import_tsm(&DatabaseName::new("bananas").unwrap(), &[path])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ReadingTsmFile { .. }));
+
+        let err = server
+            .import_tsm(&DatabaseName::new("apples").unwrap(), &[])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::DatabaseNotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn clone_database() {
+        let manager = TestConnectionManager::new();
+        let server = Server::new(manager, config());
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+
+        create_simple_database(&server, "bananas")
+            .await
+            .expect("failed to create database");
+        let bananas = DatabaseName::new("bananas").unwrap();
+
+        // one persisted chunk and one that only lives in memory
+        let db = server.db(&bananas).unwrap();
+        crate::db::test_helpers::write_lp(&db, "cpu bar=1 10").await;
+        db.rollover_partition("1970-01", "cpu").await.unwrap();
+        db.load_chunk_to_read_buffer("1970-01", "cpu", 0, &Default::default())
+            .await
+            .unwrap();
+        db.write_chunk_to_object_store("1970-01", "cpu", 0, &Default::default())
+            .await
+            .unwrap();
+        crate::db::test_helpers::write_lp(&db, "cpu bar=2 20").await;
+
+        let rules_only = DatabaseName::new("rules_only").unwrap();
+        let copied = server
+            .clone_database(&bananas, rules_only.clone(), false)
+            .await
+            .unwrap();
+        assert_eq!(copied, 0);
+        assert_eq!(
+            server.db_rules(&rules_only).unwrap().partition_template,
+            server.db_rules(&bananas).unwrap().partition_template
+        );
+        assert!(server
+            .db(&rules_only)
+            .unwrap()
+            .chunk_summaries()
+            .unwrap()
+            .is_empty());
+
+        let with_data = DatabaseName::new("with_data").unwrap();
+        let copied = server
+            .clone_database(&bananas, with_data.clone(), true)
+            .await
+            .unwrap();
+        assert_eq!(copied, 1);
+
+        let chunks = server
+            .db(&with_data)
+            .unwrap()
+            .partition_chunk_summaries("1970-01");
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].id, 0);
+        assert_eq!(chunks[0].storage, ChunkStorage::ObjectStoreOnly);
+
+        // the source is unchanged
+        assert_eq!(db.partition_chunk_summaries("1970-01").len(), 2);
+
+        let err = server
+            .clone_database(&bananas, with_data, true)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::DatabaseAlreadyExists { .. }));
+
+        let err = server
+            .clone_database(
+                &DatabaseName::new("unknown").unwrap(),
+                DatabaseName::new("other").unwrap(),
+                false,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::DatabaseNotFound { .. }));
+
+        // the rules of the clones are persisted
+        let mut rules_path = server.store.new_path();
+        rules_path.push_all_dirs(&["1", "with_data"]);
+        rules_path.set_file_name(DB_RULES_FILE_NAME);
+        assert!(server
+            .list_files(&rules_path)
+            .await
+            .unwrap()
+            .contains(&rules_path));
+    }
+
+    #[tokio::test]
+    async fn db_names_sorted() {
+        let manager = TestConnectionManager::new();
+        let server = Server::new(manager, config());
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.expect("load config");
+
+        let names = vec!["bar", "baz"];
+
+        for name in &names {
+            let name = DatabaseName::new(name.to_string()).unwrap();
+            server
+                .create_database(DatabaseRules::new(name))
+                .await
+                .expect("failed to create database");
+        }
+
+        let db_names_sorted = server.db_names_sorted();
+        assert_eq!(names, db_names_sorted);
+    }
+
+    #[tokio::test]
+    async fn referenced_dbs() {
         let manager = TestConnectionManager::new();
-        let server = Server::new(manager, config());
+        let server = Server::new(manager, config().with_admin_token("admin"));
         server.set_id(ServerId::try_from(1).unwrap()).unwrap();
         server.maybe_initialize_server().await.expect("load config");
 
-        let names = vec!["bar", "baz"];
-
-        for name in &names {
+        for name in &["foo", "bar", "baz", "ba"] {
             let name = DatabaseName::new(name.to_string()).unwrap();
             server
                 .create_database(DatabaseRules::new(name))
@@ -1404,8 +3251,28 @@ mod tests {
                 .expect("failed to create database");
         }
 
-        let db_names_sorted = server.db_names_sorted();
-        assert_eq!(names, db_names_sorted);
+        let foo = DatabaseName::new("foo").unwrap();
+        let query = r#"SELECT * FROM cpu UNION ALL SELECT * FROM bar.cpu UNION ALL SELECT * FROM "baz".cpu, foo.cpu, xba.cpu"#;
+        let names = |dbs: BTreeMap<String, Arc<Db>>| dbs.keys().cloned().collect::<Vec<_>>();
+
+        assert_eq!(
+            names(server.referenced_dbs(&foo, query, Some("admin"))),
+            vec!["bar", "baz"]
+        );
+
+        // only databases the token may read are available
+        let (_, secret) = server.auth.create_token(
+            "reader",
+            vec![auth::Permission {
+                db_name: "baz".to_string(),
+                scope: auth::Scope::Read,
+            }],
+        );
+        assert_eq!(
+            names(server.referenced_dbs(&foo, query, Some(&secret))),
+            vec!["baz"]
+        );
+        assert!(server.referenced_dbs(&foo, query, None).is_empty());
     }
 
     #[tokio::test]
@@ -1424,7 +3291,12 @@ mod tests {
         let line = "cpu bar=1 10";
         let lines: Vec<_> = parse_lines(line).map(|l| l.unwrap()).collect();
         server
-            .write_lines("foo", &lines, ARBITRARY_DEFAULT_TIME)
+            .write_lines(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
             .await
             .unwrap();
 
@@ -1448,6 +3320,273 @@ mod tests {
         assert_batches_eq!(expected, &batches);
     }
 
+    #[tokio::test]
+    async fn shutdown() {
+        let manager = TestConnectionManager::new();
+        let server = Server::new(manager, config());
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+        server.check_ready().await.unwrap();
+
+        let name = DatabaseName::new("foo".to_string()).unwrap();
+        server
+            .create_database(DatabaseRules::new(name))
+            .await
+            .unwrap();
+
+        let lines: Vec<_> = parse_lines("cpu bar=1 10").map(|l| l.unwrap()).collect();
+        server
+            .write_lines(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap();
+
+        server.begin_shutdown();
+        let err = server
+            .write_lines(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ShuttingDown));
+        let err = server.check_ready().await.unwrap_err();
+        assert!(matches!(err, Error::ShuttingDown));
+
+        server.flush_databases(true).await.unwrap();
+
+        let db = server.db(&DatabaseName::new("foo").unwrap()).unwrap();
+        let chunks = db.chunk_summaries().unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].storage, ChunkStorage::ReadBufferAndObjectStore);
+    }
+
+    #[tokio::test]
+    async fn writes_partial() {
+        let manager = TestConnectionManager::new();
+        let server = Server::new(manager, config());
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+
+        let name = DatabaseName::new("foo".to_string()).unwrap();
+        let mut rules = DatabaseRules::new(name);
+        rules.quotas.max_tables = NonZeroUsize::new(2);
+        server.create_database(rules).await.unwrap();
+
+        let lines = parsed_lines("cpu bar=1 10");
+        server
+            .write_lines(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap();
+
+        let lines = parsed_lines("cpu bar=2 20\ncpu bar=\"3\" 30\nmem free=4 40\ndisk used=5 50");
+        let rejected = server
+            .write_lines_partial(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(rejected.len(), 2);
+        assert!(matches!(rejected[0], (1, Error::SchemaConflict { .. })));
+        assert!(matches!(rejected[1], (3, Error::QuotaExceeded { .. })));
+
+        let db_name = DatabaseName::new("foo").unwrap();
+        let db = server.db(&db_name).unwrap();
+
+        let planner = SqlQueryPlanner::default();
+        let executor = server.executor();
+        let physical_plan = planner
+            .query(db, "select bar from cpu", executor.as_ref())
+            .unwrap();
+
+        let batches = executor.collect(physical_plan).await.unwrap();
+        let expected = vec![
+            "+-----+", "| bar |", "+-----+", "| 1   |", "| 2   |", "+-----+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        // Errors that are not about the written data fail the whole request
+        let err = server
+            .write_lines_partial(
+                "bar",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::DatabaseNotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn writes_all_or_nothing() {
+        let manager = TestConnectionManager::new();
+        let server = Server::new(manager, config());
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+
+        let name = DatabaseName::new("foo".to_string()).unwrap();
+        let mut rules = DatabaseRules::new(name);
+        rules.quotas.max_tables = NonZeroUsize::new(2);
+        server.create_database(rules).await.unwrap();
+
+        let lines = parsed_lines("cpu bar=1 10");
+        server
+            .write_lines(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap();
+
+        let lines = parsed_lines("cpu bar=2 20\ncpu bar=\"3\" 30\nmem free=4 40");
+        let rejected = server
+            .write_lines_all_or_nothing(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap();
+        assert_eq!(rejected.len(), 1);
+        assert!(matches!(rejected[0], (1, Error::SchemaConflict { .. })));
+
+        // Lines that are only rejected together report the first line that
+        // cannot be written
+        let lines = parsed_lines("mem free=4 40\nmem free=5 50\ndisk used=5 50\nmem free=6 60");
+        let rejected = server
+            .write_lines_all_or_nothing(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap();
+        assert_eq!(rejected.len(), 1);
+        assert!(matches!(rejected[0], (2, Error::QuotaExceeded { .. })));
+
+        let lines = parsed_lines("mem free=4 40\nmem free=\"5\" 50");
+        let rejected = server
+            .write_lines_all_or_nothing(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap();
+        assert_eq!(rejected.len(), 1);
+        assert!(matches!(rejected[0], (1, Error::LineConversion { .. })));
+
+        let lines = parsed_lines("cpu bar=3 30\nmem free=4 40");
+        let rejected = server
+            .write_lines_all_or_nothing(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap();
+        assert!(rejected.is_empty());
+
+        let db_name = DatabaseName::new("foo").unwrap();
+        let db = server.db(&db_name).unwrap();
+
+        let planner = SqlQueryPlanner::default();
+        let executor = server.executor();
+        let physical_plan = planner
+            .query(db, "select bar from cpu", executor.as_ref())
+            .unwrap();
+
+        let batches = executor.collect(physical_plan).await.unwrap();
+        let expected = vec![
+            "+-----+", "| bar |", "+-----+", "| 1   |", "| 3   |", "+-----+",
+        ];
+        assert_batches_eq!(expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn writes_line_limits() {
+        let manager = TestConnectionManager::new();
+        let server = Server::new(manager, config());
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+
+        let name = DatabaseName::new("foo".to_string()).unwrap();
+        let mut rules = DatabaseRules::new(name);
+        rules.line_limits.max_fields_per_line = NonZeroUsize::new(2);
+        rules.line_limits.max_table_name_length = NonZeroUsize::new(4);
+        server.create_database(rules).await.unwrap();
+
+        let lines = parsed_lines("cpu a=1,b=2 10\ncpu a=1,b=2,c=3 20");
+        let err = server
+            .write_lines(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error converting line protocol to flatbuffers: line 2 of table cpu has 3 fields, \
+             exceeding the limit of 2"
+        );
+
+        let lines = parsed_lines("cpu a=1 10\nmemory free=4 40\ndisk used=5 50");
+        let rejected = server
+            .write_lines_partial(
+                "foo",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(rejected.len(), 1);
+        assert!(matches!(
+            rejected[0],
+            (
+                1,
+                Error::LineConversion {
+                    source: entry::Error::TableNameTooLong { .. }
+                }
+            )
+        ));
+
+        let db = server.db(&DatabaseName::new("foo").unwrap()).unwrap();
+        let mut tables: Vec<_> = db
+            .chunk_summaries()
+            .unwrap()
+            .into_iter()
+            .map(|c| c.table_name.to_string())
+            .collect();
+        tables.sort();
+        assert_eq!(tables, vec!["cpu", "disk"]);
+    }
+
     #[tokio::test]
     async fn write_entry_local() {
         let (metric_registry, config) = config_with_metric_registry();
@@ -1470,6 +3609,7 @@ mod tests {
         let sharded_entries = lines_to_sharded_entries(
             &lines,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             NO_SHARD_CONFIG,
             &*db.rules.read(),
         )
@@ -1572,7 +3712,12 @@ mod tests {
         let lines: Vec<_> = parse_lines(line).map(|l| l.unwrap()).collect();
 
         let err = server
-            .write_lines(&db_name, &lines, ARBITRARY_DEFAULT_TIME)
+            .write_lines(
+                &db_name,
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
             .await
             .unwrap_err();
         assert!(
@@ -1582,7 +3727,12 @@ mod tests {
         // one remote is configured but it's down and we'll get connection error
         server.update_remote(bad_remote_id, BAD_REMOTE_ADDR.into());
         let err = server
-            .write_lines(&db_name, &lines, ARBITRARY_DEFAULT_TIME)
+            .write_lines(
+                &db_name,
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
             .await
             .unwrap_err();
         assert!(matches!(
@@ -1604,7 +3754,12 @@ mod tests {
         // probability both the remotes will get hit.
         for _ in 0..100 {
             server
-                .write_lines(&db_name, &lines, ARBITRARY_DEFAULT_TIME)
+                .write_lines(
+                    &db_name,
+                    &lines,
+                    ARBITRARY_DEFAULT_TIME,
+                    Precision::Nanoseconds,
+                )
                 .await
                 .expect("cannot write lines");
         }
@@ -1612,6 +3767,136 @@ mod tests {
         assert_eq!(written_2.load(Ordering::Relaxed), true);
     }
 
+    #[tokio::test]
+    async fn write_entry_downstream_ignore_errors() {
+        const TEST_SHARD_ID: ShardId = 1;
+        let remote_id = ServerId::try_from(2).unwrap();
+
+        let server = Server::new(TestConnectionManager::new(), config());
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+
+        let db_name = DatabaseName::new("foo").unwrap();
+        server
+            .create_database(DatabaseRules::new(db_name.clone()))
+            .await
+            .unwrap();
+
+        let db = server.db(&db_name).unwrap();
+        let set_ignore_errors = |ignore_errors| {
+            let shard_config = ShardConfig {
+                hash_ring: Some(HashRing {
+                    shards: vec![TEST_SHARD_ID].into(),
+                    ..Default::default()
+                }),
+                shards: Arc::new(
+                    vec![(TEST_SHARD_ID, Shard::Iox(vec![remote_id]))]
+                        .into_iter()
+                        .collect(),
+                ),
+                ignore_errors,
+                ..Default::default()
+            };
+            db.rules.write().routing_rules = Some(RoutingRules::ShardConfig(shard_config));
+        };
+
+        // no remote is configured for the shard's node group
+        let lines = parsed_lines("cpu bar=1 10");
+        set_ignore_errors(false);
+        let err = server
+            .write_lines(
+                &db_name,
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NoRemoteConfigured { .. }));
+
+        set_ignore_errors(true);
+        server
+            .write_lines(
+                &db_name,
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn replicate_entries() {
+        const REMOTE_ADDR: &str = "http://localhost:111";
+        let remote_id = ServerId::try_from(2).unwrap();
+
+        let mut manager = TestConnectionManager::new();
+        let written = Arc::new(AtomicBool::new(false));
+        manager.remotes.insert(
+            REMOTE_ADDR.to_owned(),
+            Arc::new(TestRemoteServer {
+                written: Arc::clone(&written),
+            }),
+        );
+
+        let server = Arc::new(Server::new(manager, config()));
+        let cancel_token = CancellationToken::new();
+        let background_handle = spawn_worker(Arc::clone(&server), cancel_token.clone());
+
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+        server.update_remote(remote_id, REMOTE_ADDR.into());
+
+        let db_name = DatabaseName::new("foo").unwrap();
+        let mut rules = DatabaseRules::new(db_name.clone());
+        rules.replication_config = Some(ReplicationConfig {
+            targets: vec![remote_id],
+            ..Default::default()
+        });
+        server.create_database(rules).await.unwrap();
+
+        let lines = parsed_lines("cpu bar=1 10");
+        server
+            .write_lines(
+                &db_name,
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap();
+
+        // The entry is written locally right away and shipped in the background
+        let db = server.db(&db_name).unwrap();
+        let planner = SqlQueryPlanner::default();
+        let executor = server.executor();
+        let physical_plan = planner
+            .query(db, "select * from cpu", executor.as_ref())
+            .unwrap();
+        let batches = executor.collect(physical_plan).await.unwrap();
+        assert_eq!(batches[0].num_rows(), 1);
+
+        let t_0 = Instant::now();
+        while !written.load(Ordering::Relaxed) {
+            assert!(t_0.elapsed() < Duration::from_secs(10));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(server.replication.pending().is_empty());
+        assert_eq!(
+            server.replication_status(),
+            vec![ReplicationStatus {
+                db_name: "foo".to_string(),
+                target: remote_id,
+                queued: 0,
+                dropped: 0,
+            }]
+        );
+
+        cancel_token.cancel();
+        background_handle.await.unwrap();
+    }
+
     #[tokio::test]
     async fn close_chunk() {
         test_helpers::maybe_start_logging();
@@ -1633,7 +3918,12 @@ mod tests {
         let line = "cpu bar=1 10";
         let lines: Vec<_> = parse_lines(line).map(|l| l.unwrap()).collect();
         server
-            .write_lines(&db_name, &lines, ARBITRARY_DEFAULT_TIME)
+            .write_lines(
+                &db_name,
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
             .await
             .unwrap();
 
@@ -1786,8 +4076,7 @@ mod tests {
 
         let db_name = DatabaseName::new("foo").unwrap();
         let db = server.db(&db_name).unwrap();
-        db.rules.write().lifecycle_rules.buffer_size_hard =
-            Some(std::num::NonZeroUsize::new(10).unwrap());
+        db.rules.write().lifecycle_rules.buffer_size_hard = Some(NonZeroUsize::new(10).unwrap());
 
         // inserting first line does not trigger hard buffer limit
         let line_1 = "cpu bar=1 10";
@@ -1795,6 +4084,7 @@ mod tests {
         let sharded_entries_1 = lines_to_sharded_entries(
             &lines_1,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             NO_SHARD_CONFIG,
             &*db.rules.read(),
         )
@@ -1812,6 +4102,7 @@ mod tests {
         let sharded_entries_2 = lines_to_sharded_entries(
             &lines_2,
             ARBITRARY_DEFAULT_TIME,
+            Precision::Nanoseconds,
             NO_SHARD_CONFIG,
             &*db.rules.read(),
         )
@@ -1821,6 +4112,85 @@ mod tests {
         assert!(matches!(res, Err(super::Error::HardLimitReached {})));
     }
 
+    #[tokio::test]
+    async fn server_memory_limit() {
+        let manager = TestConnectionManager::new();
+        let config = config()
+            .with_max_memory(NonZeroUsize::new(1_000_000).unwrap())
+            .with_memory_backpressure(Duration::from_millis(100));
+        let server = Arc::new(Server::new(manager, config));
+        server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        server.maybe_initialize_server().await.unwrap();
+
+        for name in &["foo", "bar"] {
+            server
+                .create_database(DatabaseRules::new(DatabaseName::new(*name).unwrap()))
+                .await
+                .unwrap();
+        }
+        let foo = server.db(&DatabaseName::new("foo").unwrap()).unwrap();
+        let bar = server.db(&DatabaseName::new("bar").unwrap()).unwrap();
+
+        let lines = parsed_lines("cpu bar=1 10");
+        server
+            .write_lines(
+                "bar",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .expect("write under the limit");
+
+        // A query against one database holds back writes to all of them
+        let reservation = foo.query_memory().reserve(1_000_000);
+        assert!(server.memory_used() > 1_000_000);
+        let err = server
+            .write_lines(
+                "bar",
+                &lines,
+                ARBITRARY_DEFAULT_TIME,
+                Precision::Nanoseconds,
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::ResourcesExhausted {
+                    limit: 1_000_000,
+                    ..
+                }
+            ),
+            "{}",
+            err
+        );
+
+        // Writes are delayed until memory is released
+        let write = {
+            let server = Arc::clone(&server);
+            async move {
+                let lines = parsed_lines("cpu bar=1 10");
+                server
+                    .write_lines(
+                        "bar",
+                        &lines,
+                        ARBITRARY_DEFAULT_TIME,
+                        Precision::Nanoseconds,
+                    )
+                    .await
+            }
+        };
+        let release = async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            std::mem::drop(reservation);
+        };
+        let (res, _) = tokio::join!(write, release);
+        res.expect("write after memory was released");
+
+        assert_eq!(bar.memory_used(), server.memory_used());
+    }
+
     #[tokio::test]
     async fn cannot_create_db_until_dbs_are_loaded() {
         let temp_dir = TempDir::new().unwrap();
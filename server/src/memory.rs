@@ -0,0 +1,62 @@
+//! Accounting of the memory held by query execution, which is checked
+//! against the memory budgets together with the size of the catalog chunks
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Memory held by the queries of a database, e.g. results that are buffered
+/// before they are sent to the client
+#[derive(Debug, Default, Clone)]
+pub struct QueryMemory {
+    bytes: Arc<AtomicUsize>,
+}
+
+impl QueryMemory {
+    /// Accounts for `bytes` until the returned reservation is dropped
+    pub fn reserve(&self, bytes: usize) -> MemoryReservation {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+        MemoryReservation {
+            bytes: Arc::clone(&self.bytes),
+            size: bytes,
+        }
+    }
+
+    /// Returns the number of bytes currently reserved
+    pub fn used(&self) -> usize {
+        self.bytes.load(Ordering::Relaxed)
+    }
+}
+
+/// Memory reserved by [`QueryMemory::reserve`], released on drop
+#[derive(Debug)]
+pub struct MemoryReservation {
+    bytes: Arc<AtomicUsize>,
+    size: usize,
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.bytes.fetch_sub(self.size, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve() {
+        let memory = QueryMemory::default();
+        assert_eq!(memory.used(), 0);
+
+        let reservation_1 = memory.reserve(10);
+        let reservation_2 = memory.clone().reserve(5);
+        assert_eq!(memory.used(), 15);
+
+        std::mem::drop(reservation_1);
+        assert_eq!(memory.used(), 5);
+        std::mem::drop(reservation_2);
+        assert_eq!(memory.used(), 0);
+    }
+}
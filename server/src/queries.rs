@@ -0,0 +1,99 @@
+//! Tracking of the queries running against a database, so that they can be
+//! listed and cancelled
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use tracker::{TaskId, TaskRegistration, TaskRegistry, TaskTracker};
+
+/// A query running against a database
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryInfo {
+    /// The text of the query
+    pub text: String,
+
+    /// When the query started
+    pub start_time: DateTime<Utc>,
+}
+
+/// The queries running against a database
+#[derive(Debug, Default, Clone)]
+pub struct RunningQueries {
+    registry: Arc<Mutex<TaskRegistry<QueryInfo>>>,
+}
+
+impl RunningQueries {
+    /// Registers a query. It is listed as running until the futures tracked
+    /// with the returned registration complete, and cancelling it aborts
+    /// these futures
+    pub fn register(&self, text: impl Into<String>) -> (TaskTracker<QueryInfo>, TaskRegistration) {
+        let info = QueryInfo {
+            text: text.into(),
+            start_time: Utc::now(),
+        };
+
+        let mut registry = self.registry.lock();
+        // forget the queries that have completed
+        registry.reclaim().for_each(|_| {});
+        registry.register(info)
+    }
+
+    /// Returns the queries that are still running
+    pub fn running(&self) -> Vec<TaskTracker<QueryInfo>> {
+        let mut running = self.registry.lock().running();
+        running.sort_by_key(|tracker| tracker.id());
+        running
+    }
+
+    /// Cancels the query with the given id, returning `false` if no such
+    /// query is running
+    pub fn cancel(&self, id: TaskId) -> bool {
+        match self.registry.lock().get(id) {
+            Some(tracker) if !tracker.is_complete() => {
+                tracker.cancel();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracker::TrackedFutureExt;
+
+    #[tokio::test]
+    async fn register_and_cancel() {
+        let queries = RunningQueries::default();
+
+        let (tracker, registration) = queries.register("SELECT * from cpu");
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel::<()>();
+        let query = tokio::spawn(done_rx.track(registration));
+
+        let running = queries.running();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].id(), tracker.id());
+        assert_eq!(running[0].metadata().text, "SELECT * from cpu");
+
+        assert!(queries.cancel(tracker.id()));
+        query.await.unwrap().unwrap_err();
+        tracker.join().await;
+
+        assert!(queries.running().is_empty());
+        assert!(!queries.cancel(tracker.id()));
+        std::mem::drop(done_tx);
+    }
+
+    #[tokio::test]
+    async fn completed_queries_are_not_running() {
+        let queries = RunningQueries::default();
+
+        let (tracker, registration) = queries.register("SELECT 1");
+        async {}.track(registration).await.unwrap();
+        tracker.join().await;
+
+        assert!(queries.running().is_empty());
+        assert!(!queries.cancel(tracker.id()));
+    }
+}
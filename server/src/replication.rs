@@ -0,0 +1,203 @@
+//! Queues of entries waiting to be shipped to peer servers by asynchronous
+//! replication
+//!
+//! The queues are only held in memory and bounded. Entries queued when the
+//! server stops, and entries dropped because the queue of a target is full,
+//! are never shipped to it, so the target silently misses their writes. The
+//! [`ReplicationStatus`] of each target tells how far it is behind and
+//! whether it missed entries.
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use data_types::{database_rules::ReplicationConfig, server_id::ServerId};
+use entry::Entry;
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+/// The entries written to one database that have yet to be shipped to one
+/// peer server, oldest first
+#[derive(Debug)]
+pub(crate) struct ReplicationQueue {
+    pub db_name: String,
+    pub target: ServerId,
+    entries: Mutex<VecDeque<Entry>>,
+    /// The number of entries dropped because the queue was full
+    dropped: AtomicU64,
+}
+
+impl ReplicationQueue {
+    fn new(db_name: String, target: ServerId) -> Self {
+        Self {
+            db_name,
+            target,
+            entries: Default::default(),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the oldest entry without removing it from the queue
+    pub fn front(&self) -> Option<Entry> {
+        self.entries.lock().front().cloned()
+    }
+
+    /// Removes the oldest entry, once it has been shipped
+    pub fn pop_front(&self) {
+        self.entries.lock().pop_front();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+
+    fn status(&self) -> ReplicationStatus {
+        ReplicationStatus {
+            db_name: self.db_name.clone(),
+            target: self.target,
+            queued: self.entries.lock().len(),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// How far a replication target is behind the writes to a database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicationStatus {
+    pub db_name: String,
+    pub target: ServerId,
+    /// The number of entries waiting to be shipped to the target
+    pub queued: usize,
+    /// The number of entries the target will never receive, because its
+    /// queue was full when they were written. Not reset when the queue
+    /// drains, as the data of the target stays incomplete
+    pub dropped: u64,
+}
+
+/// The replication queues of all databases, keyed by database name and target
+#[derive(Debug, Default)]
+pub(crate) struct ReplicationQueues {
+    queues: Mutex<BTreeMap<(String, ServerId), Arc<ReplicationQueue>>>,
+    notify: Notify,
+}
+
+impl ReplicationQueues {
+    /// Queues `entry` for every target of `config`. Returns the targets
+    /// whose queue is full and that will therefore never receive `entry`
+    pub fn enqueue(
+        &self,
+        db_name: &str,
+        config: &ReplicationConfig,
+        entry: &Entry,
+    ) -> Vec<ServerId> {
+        let mut queues = self.queues.lock();
+        let mut dropped = vec![];
+
+        for &target in &config.targets {
+            let queue = queues
+                .entry((db_name.to_string(), target))
+                .or_insert_with(|| Arc::new(ReplicationQueue::new(db_name.to_string(), target)));
+
+            let mut entries = queue.entries.lock();
+            if entries.len() < config.queue_size.get() {
+                entries.push_back(entry.clone());
+            } else {
+                queue.dropped.fetch_add(1, Ordering::Relaxed);
+                dropped.push(target);
+            }
+        }
+
+        self.notify.notify_one();
+        dropped
+    }
+
+    /// Returns the queues that have entries waiting to be shipped
+    pub fn pending(&self) -> Vec<Arc<ReplicationQueue>> {
+        self.queues
+            .lock()
+            .values()
+            .filter(|queue| !queue.is_empty())
+            .map(Arc::clone)
+            .collect()
+    }
+
+    /// Returns the status of the replication to every target that entries
+    /// were queued for, ordered by database name and target
+    pub fn status(&self) -> Vec<ReplicationStatus> {
+        self.queues
+            .lock()
+            .values()
+            .map(|queue| queue.status())
+            .collect()
+    }
+
+    /// Completes once entries have been queued since the last time it
+    /// completed
+    pub async fn notified(&self) {
+        self.notify.notified().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entry::test_helpers::lp_to_entry;
+    use std::{convert::TryFrom, num::NonZeroUsize};
+
+    #[test]
+    fn enqueue() {
+        let target_1 = ServerId::try_from(1).unwrap();
+        let target_2 = ServerId::try_from(2).unwrap();
+        let config = ReplicationConfig {
+            targets: vec![target_1, target_2],
+            queue_size: NonZeroUsize::new(2).unwrap(),
+        };
+        let queues = ReplicationQueues::default();
+        assert!(queues.pending().is_empty());
+
+        let entry = lp_to_entry("cpu bar=1 10");
+        assert!(queues.enqueue("foo", &config, &entry).is_empty());
+        assert!(queues.enqueue("foo", &config, &entry).is_empty());
+        assert_eq!(
+            queues.enqueue("foo", &config, &entry),
+            vec![target_1, target_2]
+        );
+
+        let pending = queues.pending();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].db_name, "foo");
+        assert_eq!(pending[0].target, target_1);
+
+        // Shipping the entries to one target does not affect the other
+        pending[0].pop_front();
+        assert!(pending[0].front().is_some());
+        pending[0].pop_front();
+        assert!(pending[0].front().is_none());
+
+        let pending = queues.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].target, target_2);
+
+        // The entry dropped for the first target is still reported
+        assert_eq!(
+            queues.status(),
+            vec![
+                ReplicationStatus {
+                    db_name: "foo".to_string(),
+                    target: target_1,
+                    queued: 0,
+                    dropped: 1,
+                },
+                ReplicationStatus {
+                    db_name: "foo".to_string(),
+                    target: target_2,
+                    queued: 2,
+                    dropped: 1,
+                },
+            ]
+        );
+    }
+}
@@ -35,6 +35,7 @@ pub struct TestDbBuilder {
     db_name: Option<DatabaseName<'static>>,
     worker_cleanup_avg_sleep: Option<Duration>,
     write_buffer: Option<Arc<dyn WriteBuffer>>,
+    query_result_cache_size: Option<usize>,
 }
 
 impl TestDbBuilder {
@@ -53,7 +54,11 @@ impl TestDbBuilder {
             .object_store
             .unwrap_or_else(|| Arc::new(ObjectStore::new_in_memory(InMemory::new())));
 
-        let exec = Arc::new(Executor::new(1));
+        let exec = match self.query_result_cache_size {
+            Some(size) => Executor::new(1).with_query_result_cache_size(size),
+            None => Executor::new(1),
+        };
+        let exec = Arc::new(exec);
         let metrics_registry = Arc::new(metrics::MetricRegistry::new());
 
         let preserved_catalog = load_or_create_preserved_catalog(
@@ -110,6 +115,11 @@ impl TestDbBuilder {
         self.write_buffer = Some(write_buffer);
         self
     }
+
+    pub fn query_result_cache_size(mut self, size: usize) -> Self {
+        self.query_result_cache_size = Some(size);
+        self
+    }
 }
 
 /// Used for testing: create a Database with a local store
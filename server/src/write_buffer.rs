@@ -1,41 +1,44 @@
-use entry::{Entry, Sequence};
+use async_trait::async_trait;
+use entry::{Entry, Sequence, SequencedEntry};
+use futures::stream::BoxStream;
+
+mod file;
+pub use file::{FileBuffer, FILE_BUFFER_SCHEME};
+
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaBuffer;
+
+pub type WriteBufferError = Box<dyn std::error::Error + Sync + Send>;
 
 /// A Write Buffer takes an `Entry` and returns `Sequence` data that facilitates reading entries
 /// from the Write Buffer at a later time.
+#[async_trait]
 pub trait WriteBuffer: Sync + Send + std::fmt::Debug + 'static {
     /// Send an `Entry` to the write buffer and return information that can be used to restore
     /// entries at a later time.
-    fn store_entry(
-        &self,
-        entry: &Entry,
-    ) -> Result<Sequence, Box<dyn std::error::Error + Sync + Send>>;
-
-    // TODO: interface for restoring, will look something like:
-    // fn restore_from(&self, sequence: &Sequence) -> Result<Stream<Entry>, Err>;
-}
-
-#[derive(Debug)]
-pub struct KafkaBuffer {
-    conn: String,
-}
+    async fn store_entry(&self, entry: &Entry) -> Result<Sequence, WriteBufferError>;
 
-impl WriteBuffer for KafkaBuffer {
-    fn store_entry(
+    /// Returns the entries stored in the write buffer with a sequence number of at least
+    /// `from_sequence_number`, oldest first. The stream ends after the last entry that was stored
+    /// when the replay started.
+    fn replay(
         &self,
-        _entry: &Entry,
-    ) -> Result<Sequence, Box<dyn std::error::Error + Sync + Send>> {
-        unimplemented!()
-    }
-}
+        from_sequence_number: u64,
+    ) -> BoxStream<'static, Result<SequencedEntry, WriteBufferError>>;
 
-impl KafkaBuffer {
-    pub fn new(conn: impl Into<String>) -> Self {
-        Self { conn: conn.into() }
+    /// Called once the data of the entries with a sequence number below `sequence_number` is
+    /// persisted, after which they are no longer replayed. Write buffers that don't expire
+    /// entries on their own can delete them.
+    async fn persisted(&self, _sequence_number: u64) -> Result<(), WriteBufferError> {
+        Ok(())
     }
 }
 
 pub mod test_helpers {
     use super::*;
+    use futures::{stream, StreamExt};
     use std::sync::{Arc, Mutex};
 
     #[derive(Debug, Default)]
@@ -43,11 +46,9 @@ pub mod test_helpers {
         pub entries: Arc<Mutex<Vec<Entry>>>,
     }
 
+    #[async_trait]
     impl WriteBuffer for MockBuffer {
-        fn store_entry(
-            &self,
-            entry: &Entry,
-        ) -> Result<Sequence, Box<dyn std::error::Error + Sync + Send>> {
+        async fn store_entry(&self, entry: &Entry) -> Result<Sequence, WriteBufferError> {
             let mut entries = self.entries.lock().unwrap();
             let offset = entries.len() as u64;
             entries.push(entry.clone());
@@ -57,5 +58,29 @@ pub mod test_helpers {
                 number: offset,
             })
         }
+
+        fn replay(
+            &self,
+            from_sequence_number: u64,
+        ) -> BoxStream<'static, Result<SequencedEntry, WriteBufferError>> {
+            let entries: Vec<_> = self
+                .entries
+                .lock()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .skip(from_sequence_number as usize)
+                .map(|(offset, entry)| {
+                    let sequence = Sequence {
+                        id: 0,
+                        number: offset as u64,
+                    };
+                    SequencedEntry::new_from_sequence(sequence, entry.clone())
+                        .map_err(|e| Box::new(e) as _)
+                })
+                .collect();
+
+            stream::iter(entries).boxed()
+        }
     }
 }
@@ -0,0 +1,580 @@
+//! A write buffer in segment files on local disk
+use std::{
+    collections::VecDeque,
+    convert::TryFrom,
+    fs::OpenOptions,
+    io::{self, BufReader, ErrorKind, Read, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use entry::{Entry, Sequence, SequencedEntry};
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use observability_deps::tracing::warn;
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+    sync::Mutex,
+};
+
+use super::{WriteBuffer, WriteBufferError};
+
+/// The prefix of write buffer connection strings that refer to a local directory, in which a
+/// [`FileBuffer`] is created, rather than to a Kafka cluster
+pub const FILE_BUFFER_SCHEME: &str = "file://";
+
+/// The length of the header of each entry in a [`FileBuffer`]: the length of the entry and the
+/// CRC32 checksum of its data, both as little endian `u32`
+const HEADER_LEN: usize = 8;
+
+/// The size up to which entries are appended to a segment before a new one is started
+const SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The extension of the segment files
+const SEGMENT_EXTENSION: &str = "wal";
+
+/// A write-ahead log of the entries of a database on local disk, for deployments that don't run
+/// Kafka but need the writes that haven't been persisted yet to survive a crash.
+///
+/// Every entry is appended to the log and synced to disk before `store_entry` returns. The log
+/// is split into segment files in a directory named after the database, each named after the
+/// sequence number of its first entry. The sequence number of an entry is its offset in the log,
+/// the length of the segments before its own plus its offset in that segment. Segments are
+/// deleted once the data of all their entries is persisted.
+///
+/// An entry that was only partially written when the server crashed is discarded when the log is
+/// opened again. Any other corrupted entry is an error, as skipping it would silently lose the
+/// writes it holds.
+#[derive(Debug)]
+pub struct FileBuffer {
+    dir: PathBuf,
+    segment_size: u64,
+    /// The last segment, which entries are appended to
+    active: Arc<Mutex<ActiveSegment>>,
+}
+
+#[derive(Debug)]
+struct ActiveSegment {
+    /// The file, positioned at its end
+    file: File,
+    /// The sequence number of the first entry of the segment
+    start: u64,
+    /// The length of the file
+    len: u64,
+}
+
+impl ActiveSegment {
+    /// The sequence number of the next entry appended
+    fn end(&self) -> u64 {
+        self.start + self.len
+    }
+}
+
+#[async_trait]
+impl WriteBuffer for FileBuffer {
+    async fn store_entry(&self, entry: &Entry) -> Result<Sequence, WriteBufferError> {
+        let data = entry.data();
+        let mut record = Vec::with_capacity(HEADER_LEN + data.len());
+        record.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        record.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+        record.extend_from_slice(data);
+
+        let mut active = self.active.lock().await;
+        if active.len > 0 && active.len + record.len() as u64 > self.segment_size {
+            let start = active.end();
+            let file = create_segment(&self.dir, start)?;
+            *active = ActiveSegment {
+                file: File::from_std(file),
+                start,
+                len: 0,
+            };
+        }
+
+        let offset = active.len;
+        if let Err(e) = write_record(&mut active.file, &record).await {
+            // Don't leave a partial record behind that later entries would be appended to
+            active.file.set_len(offset).await?;
+            return Err(e.into());
+        }
+        active.len += record.len() as u64;
+
+        Ok(Sequence {
+            id: 0,
+            number: active.start + offset,
+        })
+    }
+
+    fn replay(
+        &self,
+        from_sequence_number: u64,
+    ) -> BoxStream<'static, Result<SequencedEntry, WriteBufferError>> {
+        let replay = Replay {
+            dir: self.dir.clone(),
+            active: Arc::clone(&self.active),
+            from: from_sequence_number,
+            segments: None,
+            reader: None,
+        };
+
+        stream::unfold(Some(replay), |replay| async move {
+            let mut replay = replay?;
+            match replay.next_entry().await {
+                Ok(Some(entry)) => Some((Ok(entry), Some(replay))),
+                Ok(None) => None,
+                // Stop at the first error, the entries after it would be out of order
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+        .boxed()
+    }
+
+    async fn persisted(&self, sequence_number: u64) -> Result<(), WriteBufferError> {
+        // Hold the lock so that the segments don't change while they are deleted
+        let _active = self.active.lock().await;
+
+        // A segment only holds persisted entries if the one after it starts at or before the
+        // sequence number, so the active segment is never deleted
+        let segments = list_segments(&self.dir)?;
+        for segment in segments.windows(2) {
+            let ((_, path), (next_start, _)) = (&segment[0], &segment[1]);
+            if *next_start > sequence_number {
+                break;
+            }
+            tokio::fs::remove_file(path).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FileBuffer {
+    /// Opens or creates the write-ahead log of the database `database_name` in the directory
+    /// `dir`, discarding an incomplete entry at its end, if any.
+    ///
+    /// Fails if an entry before the end of the log is corrupted.
+    pub fn new(dir: impl AsRef<Path>, database_name: impl AsRef<str>) -> io::Result<Self> {
+        let dir = dir.as_ref().join(database_name.as_ref());
+        std::fs::create_dir_all(&dir)?;
+
+        let active = match list_segments(&dir)?.pop() {
+            Some((start, path)) => open_segment(&path, start)?,
+            None => ActiveSegment {
+                file: File::from_std(create_segment(&dir, 0)?),
+                start: 0,
+                len: 0,
+            },
+        };
+
+        Ok(Self {
+            dir,
+            segment_size: SEGMENT_SIZE,
+            active: Arc::new(Mutex::new(active)),
+        })
+    }
+}
+
+/// Returns the sequence numbers of the first entries and the paths of the segments in `dir`,
+/// ordered by sequence number
+fn list_segments(dir: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+    let mut segments = vec![];
+    for dir_entry in std::fs::read_dir(dir)? {
+        let path = dir_entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(SEGMENT_EXTENSION) {
+            continue;
+        }
+        let start = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse().ok());
+        match start {
+            Some(start) => segments.push((start, path)),
+            None => {
+                warn!(path=%path.display(), "ignoring file that is not a write-ahead log segment")
+            }
+        }
+    }
+    segments.sort();
+    Ok(segments)
+}
+
+/// Creates the segment in `dir` starting with the entry with sequence number `start`, making
+/// sure it is still there after a crash
+fn create_segment(dir: &Path, start: u64) -> io::Result<std::fs::File> {
+    let path = dir.join(format!("{:020}.{}", start, SEGMENT_EXTENSION));
+    let file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create_new(true)
+        .open(&path)?;
+    std::fs::File::open(dir)?.sync_all()?;
+    Ok(file)
+}
+
+/// Opens the last segment of the log, discarding an incomplete entry at its end, if any
+fn open_segment(path: &Path, start: u64) -> io::Result<ActiveSegment> {
+    let file = OpenOptions::new().read(true).append(true).open(path)?;
+    let total = file.metadata()?.len();
+
+    let len = complete_len(&file, total).map_err(|e| corrupted(path, e))?;
+    if len < total {
+        warn!(
+            path=%path.display(),
+            valid_bytes=len,
+            total_bytes=total,
+            "discarding incomplete entry at the end of the write-ahead log"
+        );
+        file.set_len(len)?;
+        file.sync_all()?;
+    }
+
+    Ok(ActiveSegment {
+        file: File::from_std(file),
+        start,
+        len,
+    })
+}
+
+/// Returns the length of the complete entries at the start of `file`, `total` bytes long.
+///
+/// An entry that runs up to the end of the file but is cut short or doesn't match its checksum
+/// was being written when the server crashed. A corrupted entry that is followed by other data is
+/// an error.
+fn complete_len(file: &std::fs::File, total: u64) -> io::Result<u64> {
+    let mut reader = BufReader::new(file);
+    let mut offset = 0;
+
+    while total - offset >= HEADER_LEN as u64 {
+        let mut header = [0; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+        let (len, checksum) = parse_header(&header);
+
+        let end = offset + (HEADER_LEN + len) as u64;
+        if end > total {
+            break;
+        }
+
+        let mut data = vec![0; len];
+        reader.read_exact(&mut data)?;
+        if len == 0 || crc32fast::hash(&data) != checksum {
+            if end == total {
+                break;
+            }
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("checksum mismatch of the entry at offset {}", offset),
+            ));
+        }
+
+        offset = end;
+    }
+
+    Ok(offset)
+}
+
+/// Appends `record` to `file` and syncs it to disk
+async fn write_record(file: &mut File, record: &[u8]) -> io::Result<()> {
+    file.write_all(record).await?;
+    file.sync_data().await
+}
+
+/// Returns the length and the checksum of the data of an entry
+fn parse_header(header: &[u8; HEADER_LEN]) -> (usize, u32) {
+    let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let checksum = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    (len, checksum)
+}
+
+fn corrupted(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!(
+            "corrupted write-ahead log segment {}: {}",
+            path.display(),
+            e
+        ),
+    )
+}
+
+/// The state of a replay of a [`FileBuffer`], reading one entry at a time
+struct Replay {
+    dir: PathBuf,
+    active: Arc<Mutex<ActiveSegment>>,
+    from: u64,
+    /// The sequence numbers of the first entry and past the last entry, and the paths of the
+    /// segments left to read, listed when the first entry is read
+    segments: Option<VecDeque<(u64, u64, PathBuf)>>,
+    /// The segment being read, its path and end, and the sequence number of the next entry
+    reader: Option<(tokio::io::BufReader<File>, PathBuf, u64, u64)>,
+}
+
+impl Replay {
+    async fn next_entry(&mut self) -> Result<Option<SequencedEntry>, WriteBufferError> {
+        let from = self.from;
+        let segments = match &mut self.segments {
+            Some(segments) => segments,
+            None => {
+                // Entries stored after the replay started are not replayed
+                let end = self.active.lock().await.end();
+                let segments = list_segments(&self.dir)?;
+                let ends: Vec<_> = segments
+                    .iter()
+                    .skip(1)
+                    .map(|(start, _)| *start)
+                    .chain(std::iter::once(end))
+                    .collect();
+                self.segments.get_or_insert(
+                    segments
+                        .into_iter()
+                        .zip(ends)
+                        .map(|((start, path), end)| (start, end, path))
+                        // Skip the segments that only hold persisted entries
+                        .filter(|(_, end, _)| *end > from)
+                        .collect(),
+                )
+            }
+        };
+
+        loop {
+            let (reader, path, end, number) = match &mut self.reader {
+                Some(reader) => reader,
+                None => match segments.pop_front() {
+                    Some((start, end, path)) => {
+                        let file = File::open(&path).await?;
+                        self.reader.get_or_insert((
+                            tokio::io::BufReader::new(file),
+                            path,
+                            end,
+                            start,
+                        ))
+                    }
+                    None => return Ok(None),
+                },
+            };
+
+            if *number >= *end {
+                self.reader = None;
+                continue;
+            }
+            let path = path.as_path();
+
+            let sequence_number = *number;
+            let mut header = [0; HEADER_LEN];
+            reader
+                .read_exact(&mut header)
+                .await
+                .map_err(|e| corrupted(path, e))?;
+            let (len, checksum) = parse_header(&header);
+            *number += (HEADER_LEN + len) as u64;
+            if *number > *end {
+                return Err(corrupted(
+                    path,
+                    io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "the entry with sequence number {} runs past the end of the segment",
+                            sequence_number
+                        ),
+                    ),
+                )
+                .into());
+            }
+
+            if sequence_number < from {
+                reader.seek(SeekFrom::Current(len as i64)).await?;
+                continue;
+            }
+
+            let mut data = vec![0; len];
+            reader
+                .read_exact(&mut data)
+                .await
+                .map_err(|e| corrupted(path, e))?;
+            if crc32fast::hash(&data) != checksum {
+                return Err(corrupted(
+                    path,
+                    io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "checksum mismatch of the entry with sequence number {}",
+                            sequence_number
+                        ),
+                    ),
+                )
+                .into());
+            }
+
+            let entry = Entry::try_from(data)?;
+            let sequence = Sequence {
+                id: 0,
+                number: sequence_number,
+            };
+            return Ok(Some(SequencedEntry::new_from_sequence(sequence, entry)?));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entry::test_helpers::lp_to_entry;
+    use futures::TryStreamExt;
+
+    use super::*;
+
+    async fn replayed_numbers(buffer: &FileBuffer, from: u64) -> Vec<u64> {
+        let replayed: Vec<_> = buffer.replay(from).try_collect().await.unwrap();
+        replayed
+            .iter()
+            .map(|entry| entry.sequence().number)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn replay() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let buffer = FileBuffer::new(dir.path(), "mydb").unwrap();
+        let first = buffer
+            .store_entry(&lp_to_entry("cpu bar=1 10"))
+            .await
+            .unwrap();
+        let second = buffer
+            .store_entry(&lp_to_entry("cpu bar=2 20"))
+            .await
+            .unwrap();
+        assert_eq!(first.number, 0);
+        assert!(second.number > first.number);
+
+        let replayed: Vec<_> = buffer.replay(0).try_collect().await.unwrap();
+        assert_eq!(replayed.len(), 2);
+        assert_eq!(replayed[1].sequence().number, second.number);
+
+        // Entries before the given sequence number are skipped
+        assert_eq!(
+            replayed_numbers(&buffer, second.number).await,
+            vec![second.number]
+        );
+        assert!(replayed_numbers(&buffer, second.number + 1)
+            .await
+            .is_empty());
+        drop(buffer);
+
+        // Simulate a crash while an entry was written
+        let path = dir.path().join("mydb").join("00000000000000000000.wal");
+        let len = std::fs::metadata(&path).unwrap().len();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        std::io::Write::write_all(&mut file, &[42, 0, 0, 0, 1, 2]).unwrap();
+        drop(file);
+
+        // The incomplete entry is discarded when the log is opened again
+        let buffer = FileBuffer::new(dir.path(), "mydb").unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), len);
+
+        let third = buffer
+            .store_entry(&lp_to_entry("cpu bar=3 30"))
+            .await
+            .unwrap();
+        assert_eq!(third.number, len);
+
+        assert_eq!(
+            replayed_numbers(&buffer, 0).await,
+            vec![first.number, second.number, third.number]
+        );
+    }
+
+    #[tokio::test]
+    async fn segments() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut buffer = FileBuffer::new(dir.path(), "mydb").unwrap();
+        // Start a new segment for every entry
+        buffer.segment_size = 1;
+
+        let mut numbers = vec![];
+        for lp in &["cpu bar=1 10", "cpu bar=2 20", "cpu bar=3 30"] {
+            let sequence = buffer.store_entry(&lp_to_entry(lp)).await.unwrap();
+            numbers.push(sequence.number);
+        }
+        assert_eq!(list_segments(&buffer.dir).unwrap().len(), 3);
+        assert_eq!(replayed_numbers(&buffer, 0).await, numbers);
+        assert_eq!(
+            replayed_numbers(&buffer, numbers[1]).await,
+            numbers[1..].to_vec()
+        );
+
+        // Only the segments whose entries are all persisted are deleted
+        buffer.persisted(numbers[1] + 1).await.unwrap();
+        let segments = list_segments(&buffer.dir).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].0, numbers[1]);
+        assert_eq!(
+            replayed_numbers(&buffer, numbers[2]).await,
+            numbers[2..].to_vec()
+        );
+
+        // The segment entries are appended to is kept
+        buffer.persisted(u64::MAX).await.unwrap();
+        assert_eq!(list_segments(&buffer.dir).unwrap().len(), 1);
+        drop(buffer);
+
+        // Sequence numbers continue after the last segment when the log is opened again
+        let buffer = FileBuffer::new(dir.path(), "mydb").unwrap();
+        let sequence = buffer
+            .store_entry(&lp_to_entry("cpu bar=4 40"))
+            .await
+            .unwrap();
+        assert!(sequence.number > numbers[2]);
+        assert_eq!(
+            replayed_numbers(&buffer, 0).await,
+            vec![numbers[2], sequence.number]
+        );
+    }
+
+    #[tokio::test]
+    async fn corruption() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let buffer = FileBuffer::new(dir.path(), "mydb").unwrap();
+        buffer
+            .store_entry(&lp_to_entry("cpu bar=1 10"))
+            .await
+            .unwrap();
+        let second = buffer
+            .store_entry(&lp_to_entry("cpu bar=2 20"))
+            .await
+            .unwrap();
+        drop(buffer);
+
+        // Flip a byte of the data of the first entry
+        let path = dir.path().join("mydb").join("00000000000000000000.wal");
+        let mut data = std::fs::read(&path).unwrap();
+        data[HEADER_LEN] ^= 0xff;
+        std::fs::write(&path, &data).unwrap();
+
+        // The log is not truncated, which would lose the second entry
+        let err = FileBuffer::new(dir.path(), "mydb").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), data.len() as u64);
+
+        // Corruption of an earlier segment fails the replay
+        data[HEADER_LEN] ^= 0xff;
+        std::fs::write(&path, &data).unwrap();
+        let mut buffer = FileBuffer::new(dir.path(), "mydb").unwrap();
+        buffer.segment_size = 1;
+        buffer
+            .store_entry(&lp_to_entry("cpu bar=3 30"))
+            .await
+            .unwrap();
+        data[HEADER_LEN] ^= 0xff;
+        std::fs::write(&path, &data).unwrap();
+
+        let err = buffer.replay(0).try_collect::<Vec<_>>().await.unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+
+        // Unless the corrupted entry is persisted and skipped
+        assert_eq!(replayed_numbers(&buffer, second.number).await.len(), 2);
+    }
+}
@@ -0,0 +1,178 @@
+//! A write buffer in a Kafka topic, only built with the `kafka` feature
+use std::{convert::TryFrom, time::Duration};
+
+use async_trait::async_trait;
+use entry::{Entry, Sequence, SequencedEntry};
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use rdkafka::{
+    consumer::{Consumer, StreamConsumer},
+    error::KafkaError,
+    producer::{FutureProducer, FutureRecord},
+    util::Timeout,
+    ClientConfig, Message, Offset, TopicPartitionList,
+};
+use tokio::sync::mpsc;
+
+use super::{WriteBuffer, WriteBufferError};
+
+/// All entries of a database are written to a single partition of the topic named after the
+/// database, so that they are replayed in the order they were written.
+const KAFKA_PARTITION: i32 = 0;
+
+/// How long to wait for Kafka to acknowledge a write or to answer a metadata request
+const KAFKA_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Number of replayed entries to buffer before the consumer waits for the database to catch up
+const REPLAY_BUFFER_SIZE: usize = 100;
+
+pub struct KafkaBuffer {
+    conn: String,
+    topic: String,
+    producer: FutureProducer,
+}
+
+impl std::fmt::Debug for KafkaBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaBuffer")
+            .field("conn", &self.conn)
+            .field("topic", &self.topic)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl WriteBuffer for KafkaBuffer {
+    async fn store_entry(&self, entry: &Entry) -> Result<Sequence, WriteBufferError> {
+        let record: FutureRecord<'_, String, [u8]> = FutureRecord::to(&self.topic)
+            .partition(KAFKA_PARTITION)
+            .payload(entry.data());
+
+        let (partition, offset) = self
+            .producer
+            .send(record, Timeout::After(KAFKA_TIMEOUT))
+            .await
+            .map_err(|(e, _message)| e)?;
+
+        Ok(Sequence {
+            id: partition as u32,
+            number: offset as u64,
+        })
+    }
+
+    fn replay(
+        &self,
+        from_sequence_number: u64,
+    ) -> BoxStream<'static, Result<SequencedEntry, WriteBufferError>> {
+        let conn = self.conn.clone();
+        let topic = self.topic.clone();
+        let (tx, rx) = mpsc::channel(REPLAY_BUFFER_SIZE);
+
+        tokio::spawn(async move {
+            if let Err(e) = replay_topic(&conn, &topic, from_sequence_number, &tx).await {
+                // The receiver may have gone away, in which case nobody is interested in the error
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+
+        stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|item| (item, rx))
+        })
+        .boxed()
+    }
+}
+
+impl KafkaBuffer {
+    /// Creates a write buffer for the database `database_name` that writes to the Kafka cluster
+    /// at `conn`, a comma separated list of bootstrap servers.
+    pub fn new(
+        conn: impl Into<String>,
+        database_name: impl Into<String>,
+    ) -> Result<Self, KafkaError> {
+        let conn = conn.into();
+        let topic = database_name.into();
+
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", &conn)
+            .set("message.timeout.ms", &KAFKA_TIMEOUT.as_millis().to_string())
+            .create()?;
+
+        Ok(Self {
+            conn,
+            topic,
+            producer,
+        })
+    }
+}
+
+/// Reads `topic` from the offset `from`, or the oldest offset still retained if that is later,
+/// up to the high watermark fetched before reading and sends the entries to `tx`. Returns early
+/// if the receiver is dropped.
+async fn replay_topic(
+    conn: &str,
+    topic: &str,
+    from: u64,
+    tx: &mpsc::Sender<Result<SequencedEntry, WriteBufferError>>,
+) -> Result<(), WriteBufferError> {
+    // Creating the consumer, fetching the watermarks and assigning the partition block on
+    // librdkafka, so they run on a thread that may block
+    let (conn_captured, topic_captured) = (conn.to_string(), topic.to_string());
+    let assigned = tokio::task::spawn_blocking(move || -> Result<_, WriteBufferError> {
+        let (conn, topic) = (conn_captured.as_str(), topic_captured.as_str());
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", conn)
+            // Offsets are assigned manually and never committed, but librdkafka requires a group
+            .set("group.id", topic)
+            .set("enable.auto.commit", "false")
+            // The offsets just below the high watermark may hold no message, e.g. after log
+            // compaction, so the end of the partition has to be reported to stop the replay
+            .set("enable.partition.eof", "true")
+            .create()?;
+
+        let (low, high) = consumer.fetch_watermarks(topic, KAFKA_PARTITION, KAFKA_TIMEOUT)?;
+        let start = low.max(from as i64);
+        if start >= high {
+            return Ok(None);
+        }
+
+        let mut assignment = TopicPartitionList::new();
+        assignment.add_partition_offset(topic, KAFKA_PARTITION, Offset::Offset(start))?;
+        consumer.assign(&assignment)?;
+
+        Ok(Some((consumer, high)))
+    })
+    .await??;
+    let (consumer, high) = match assigned {
+        Some(assigned) => assigned,
+        None => return Ok(()),
+    };
+
+    let mut messages = consumer.stream();
+    while let Some(message) = messages.next().await {
+        let message = match message {
+            Ok(message) => message,
+            Err(KafkaError::PartitionEOF(_)) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let offset = message.offset();
+        if offset >= high {
+            // Written after the replay started
+            break;
+        }
+
+        let entry = Entry::try_from(message.payload().unwrap_or_default().to_vec())?;
+        let sequence = Sequence {
+            id: message.partition() as u32,
+            number: offset as u64,
+        };
+        let sequenced_entry = SequencedEntry::new_from_sequence(sequence, entry)?;
+
+        if tx.send(Ok(sequenced_entry)).await.is_err() || offset + 1 >= high {
+            break;
+        }
+    }
+
+    Ok(())
+}
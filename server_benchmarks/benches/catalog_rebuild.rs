@@ -0,0 +1,166 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    num::NonZeroU32,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use data_types::server_id::ServerId;
+use datafusion::physical_plan::common::SizedRecordBatchStream;
+use object_store::{memory::InMemory, ObjectStore, ObjectStoreApi};
+use parquet_file::{
+    catalog::{test_helpers::TestCatalogState, PreservedCatalog},
+    metadata::IoxMetadata,
+    rebuild::rebuild_catalog,
+    storage::Storage,
+    test_utils::make_record_batch,
+};
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+// The number of parquet files in the object store
+const FILE_COUNTS: &[usize] = &[1_000, 5_000, 10_000];
+
+// The number of parquet files written by each transaction
+const FILES_PER_TRANSACTION: usize = 10;
+
+const DB_NAME: &str = "db1";
+
+// Tracks the bytes allocated by the benchmark, so that the peak memory used
+// by a rebuild can be reported next to its wall time
+struct TrackingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: TrackingAllocator = TrackingAllocator;
+
+fn server_id() -> ServerId {
+    ServerId::new(NonZeroU32::new(1).unwrap())
+}
+
+// Writes `count` small parquet files into a new in-memory object store, as
+// if they had been persisted by transactions of FILES_PER_TRANSACTION files
+async fn make_store(count: usize) -> Arc<ObjectStore> {
+    let store = Arc::new(ObjectStore::new_in_memory(InMemory::new()));
+    let storage = Storage::new(Arc::clone(&store), server_id(), DB_NAME);
+    let (record_batches, schema, _column_summaries, _num_rows) = make_record_batch("foo");
+    let record_batches: Vec<_> = record_batches.into_iter().map(Arc::new).collect();
+
+    let mut transaction_uuid = Uuid::new_v4();
+    for chunk_id in 0..count {
+        if chunk_id % FILES_PER_TRANSACTION == 0 {
+            transaction_uuid = Uuid::new_v4();
+        }
+        let metadata = IoxMetadata {
+            // revision 0 is always the empty first transaction
+            transaction_revision_counter: (chunk_id / FILES_PER_TRANSACTION) as u64 + 1,
+            transaction_uuid,
+            time_of_first_write: None,
+            time_of_last_write: None,
+            min_unpersisted_sequence_number: None,
+        };
+        let stream = Box::pin(SizedRecordBatchStream::new(
+            Arc::clone(schema.inner()),
+            record_batches.clone(),
+        ));
+
+        storage
+            .write_to_object_store(
+                "part1".to_string(),
+                chunk_id as u32,
+                "table1".to_string(),
+                stream,
+                metadata,
+            )
+            .await
+            .unwrap();
+    }
+
+    store
+}
+
+// Wipes the catalog left behind by a previous rebuild and rebuilds it from
+// the parquet files, returning the wall time of the rebuild
+async fn rebuild(store: &Arc<ObjectStore>, count: usize) -> Duration {
+    PreservedCatalog::<TestCatalogState>::wipe(store, server_id(), DB_NAME)
+        .await
+        .unwrap();
+
+    let start = Instant::now();
+    let catalog = rebuild_catalog::<TestCatalogState, _>(
+        Arc::clone(store),
+        &store.new_path(),
+        server_id(),
+        DB_NAME,
+        (),
+        false,
+    )
+    .await
+    .unwrap();
+    let elapsed = start.elapsed();
+
+    assert_eq!(catalog.state().inner.borrow().parquet_files.len(), count);
+    elapsed
+}
+
+pub fn benchmark_rebuild_catalog(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rebuild_catalog");
+    group.sample_size(10);
+
+    for count in FILE_COUNTS {
+        let runtime = Runtime::new().unwrap();
+        let store = runtime.block_on(make_store(*count));
+
+        // criterion only measures time, so report the memory of a single
+        // rebuild separately
+        let baseline = ALLOCATED.load(Ordering::Relaxed);
+        PEAK.store(baseline, Ordering::Relaxed);
+        runtime.block_on(rebuild(&store, *count));
+        println!(
+            "rebuild_catalog/{}: peak memory {} bytes above baseline",
+            count,
+            PEAK.load(Ordering::Relaxed) - baseline
+        );
+
+        group.throughput(Throughput::Elements(*count as u64));
+        group.bench_function(BenchmarkId::from_parameter(count), |b| {
+            b.to_async(&runtime).iter_custom(|iters| {
+                let store = Arc::clone(&store);
+                async move {
+                    let mut total = Duration::default();
+                    for _ in 0..iters {
+                        total += rebuild(&store, *count).await;
+                    }
+                    total
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_rebuild_catalog);
+criterion_main!(benches);
@@ -6,12 +6,13 @@ use criterion::{criterion_group, criterion_main};
 
 use read_filter::benchmark_read_filter;
 use read_group::benchmark_read_group;
-use tag_values::benchmark_tag_values;
+use tag_values::{benchmark_tag_values, benchmark_tag_values_generated};
 
 // criterion_group!(benches, benchmark_tag_values, benchmark_read_filter);
 criterion_group!(
     benches,
     benchmark_tag_values,
+    benchmark_tag_values_generated,
     benchmark_read_filter,
     benchmark_read_group,
 );
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use data_generator::{Config, FieldType};
+use tokio::runtime::Runtime;
+
+use query::{exec::Executor, frontend::sql::SqlQueryPlanner};
+use query_tests::scenarios::{make_one_chunk_scenarios, DbScenario};
+use server::db::Db;
+
+// Runs the same SQL queries against a single chunk of data at each stage of
+// its lifecycle:
+//
+// - an open mutable buffer chunk;
+// - a closed mutable buffer chunk;
+// - a read buffer chunk;
+// - a read buffer chunk that is also persisted to parquet;
+// - a parquet chunk in object store only.
+//
+// The data is generated by `data_generator` with 100K series of a single
+// measurement `m0`, each with one point. The series have three tag keys:
+//
+//   - tag0, cardinality 10.
+//   - tag1, cardinality 100.
+//   - tag2, cardinality 100.
+//
+// and a float field `v0` and an integer field `v1`. The timespan of the
+// points is around 6m of wall-clock time, all in the same partition.
+async fn setup_scenarios() -> Vec<DbScenario> {
+    let lp = Config {
+        tag_cardinalities: vec![10, 100, 100],
+        field_types: vec![FieldType::Float, FieldType::Integer],
+        ..Default::default()
+    }
+    .line_protocol()
+    .unwrap();
+
+    make_one_chunk_scenarios("2021-04-12T17", &lp).await
+}
+
+const QUERIES: &[(&str, &str)] = &[
+    ("count", "SELECT COUNT(*) FROM m0"),
+    ("select_tag_pred", "SELECT * FROM m0 WHERE tag2 = 'value42'"),
+    (
+        "select_time_pred",
+        "SELECT * FROM m0 WHERE time < to_timestamp('2021-04-12T17:02:00Z')",
+    ),
+    (
+        "aggregate",
+        "SELECT tag0, SUM(v1), MAX(v0) FROM m0 GROUP BY tag0",
+    ),
+    (
+        "aggregate_tag_pred",
+        "SELECT tag0, SUM(v1), MAX(v0) FROM m0 WHERE tag1 = 'value7' GROUP BY tag0",
+    ),
+];
+
+pub fn benchmark_lifecycle_queries(c: &mut Criterion) {
+    let scenarios = Runtime::new().unwrap().block_on(setup_scenarios());
+    let planner = SqlQueryPlanner::default();
+
+    for scenario in scenarios {
+        let DbScenario { scenario_name, db } = scenario;
+        let db = Arc::new(db);
+        let mut group = c.benchmark_group(format!("lifecycle_queries/{}", scenario_name));
+
+        for (name, sql) in QUERIES {
+            group.bench_with_input(BenchmarkId::from_parameter(name), sql, |b, sql| {
+                let executor = db.executor();
+                b.to_async(Runtime::new().unwrap())
+                    .iter(|| run_query(&planner, executor.as_ref(), &db, sql));
+            });
+        }
+
+        group.finish();
+    }
+}
+
+// Plans and runs a SQL query, checking it returned some rows.
+async fn run_query(planner: &SqlQueryPlanner, executor: &Executor, db: &Arc<Db>, sql: &str) {
+    let plan = planner
+        .query(Arc::clone(db), sql, executor)
+        .expect("built plan successfully");
+    let results = executor.collect(plan).await.expect("ran plan successfully");
+    assert!(!results.is_empty());
+}
+
+criterion_group!(benches, benchmark_lifecycle_queries);
+criterion_main!(benches);
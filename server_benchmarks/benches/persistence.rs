@@ -0,0 +1,153 @@
+use std::{num::NonZeroU32, sync::Arc};
+
+use arrow::{datatypes::SchemaRef, record_batch::RecordBatch};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use data_generator::{Config, FieldType};
+use data_types::server_id::ServerId;
+use datafusion::physical_plan::common::{collect, SizedRecordBatchStream};
+use internal_types::selection::Selection;
+use mutable_buffer::chunk::{Chunk, ChunkMetrics};
+use object_store::{memory::InMemory, path::Path, ObjectStore};
+use parquet_file::{metadata::IoxMetadata, storage::Storage};
+use query::predicate::{Predicate, PredicateBuilder};
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+// The number of rows of each chunk written to and read from object store
+const CHUNK_SIZES: &[usize] = &[10_000, 100_000, 1_000_000];
+
+// The number of rows of each record batch, and so of each row group of the
+// parquet files
+const BATCH_SIZE: usize = 10_000;
+
+// The nanoseconds between consecutive rows
+const SPACING: i64 = 1_000_000;
+
+// Generates `rows` rows of a single measurement with three tags, a float and
+// an integer field, and converts them to record batches of BATCH_SIZE rows
+// through mutable buffer chunks, the same way a chunk is persisted
+fn record_batches(rows: usize) -> (SchemaRef, Vec<Arc<RecordBatch>>) {
+    let config = Config {
+        tag_cardinalities: vec![10, 100, rows / 1000],
+        field_types: vec![FieldType::Float, FieldType::Integer],
+        spacing: SPACING,
+        ..Default::default()
+    };
+
+    let batches: Vec<_> = config
+        .entries(BATCH_SIZE)
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            let mut chunk = Chunk::new("m0", ChunkMetrics::new_unregistered());
+            for write in entry.partition_writes().iter().flatten() {
+                for batch in write.table_batches() {
+                    chunk
+                        .write_table_batch(1, 5, batch, Default::default(), &[])
+                        .unwrap();
+                }
+            }
+            Arc::new(chunk.snapshot().read_filter(Selection::All).unwrap())
+        })
+        .collect();
+
+    (batches[0].schema(), batches)
+}
+
+async fn write_chunk(storage: &Storage, schema: SchemaRef, batches: Vec<Arc<RecordBatch>>) -> Path {
+    let stream = Box::pin(SizedRecordBatchStream::new(schema, batches));
+    let metadata = IoxMetadata {
+        transaction_revision_counter: 0,
+        transaction_uuid: Uuid::nil(),
+        time_of_first_write: None,
+        time_of_last_write: None,
+        min_unpersisted_sequence_number: None,
+    };
+
+    let (path, _) = storage
+        .write_to_object_store("part1".to_string(), 1, "m0".to_string(), stream, metadata)
+        .await
+        .unwrap();
+    path
+}
+
+async fn read_chunk(
+    predicate: &Predicate,
+    schema: SchemaRef,
+    path: Path,
+    store: Arc<ObjectStore>,
+) -> usize {
+    let stream = Storage::read_filter(predicate, Selection::All, schema, path, store).unwrap();
+    let batches = collect(stream).await.unwrap();
+    batches.iter().map(|batch| batch.num_rows()).sum()
+}
+
+fn new_storage(store: Arc<ObjectStore>) -> Storage {
+    let server_id = ServerId::new(NonZeroU32::new(1).unwrap());
+    Storage::new(store, server_id, "db1")
+}
+
+fn new_store() -> Arc<ObjectStore> {
+    Arc::new(ObjectStore::new_in_memory(InMemory::new()))
+}
+
+pub fn write_to_object_store(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write_to_object_store");
+    group.sample_size(10);
+
+    for rows in CHUNK_SIZES {
+        let (schema, batches) = record_batches(*rows);
+        let storage = new_storage(new_store());
+
+        group.throughput(Throughput::Elements(*rows as u64));
+        group.bench_function(BenchmarkId::from_parameter(rows), |b| {
+            b.to_async(Runtime::new().unwrap())
+                .iter(|| write_chunk(&storage, Arc::clone(&schema), batches.clone()));
+        });
+    }
+
+    group.finish();
+}
+
+// Reads the chunks back with time range predicates selecting all, a tenth
+// and a hundredth of their rows, which prune row groups using the
+// statistics of the parquet file
+pub fn read_filter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parquet_read_filter");
+    group.sample_size(10);
+
+    let start = Config::default().start_time;
+    for rows in CHUNK_SIZES {
+        let (schema, batches) = record_batches(*rows);
+        let store = new_store();
+        let storage = new_storage(Arc::clone(&store));
+        let path =
+            Runtime::new()
+                .unwrap()
+                .block_on(write_chunk(&storage, Arc::clone(&schema), batches));
+
+        for (selectivity, name) in &[(1, "all"), (10, "tenth"), (100, "hundredth")] {
+            let end = start + (*rows / *selectivity) as i64 * SPACING;
+            let predicate = PredicateBuilder::default()
+                .timestamp_range(start, end)
+                .build();
+
+            group.throughput(Throughput::Elements((*rows / *selectivity) as u64));
+            group.bench_function(BenchmarkId::new(*name, rows), |b| {
+                b.to_async(Runtime::new().unwrap()).iter(|| {
+                    read_chunk(
+                        &predicate,
+                        Arc::clone(&schema),
+                        path.clone(),
+                        Arc::clone(&store),
+                    )
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, write_to_object_store, read_filter);
+criterion_main!(benches);
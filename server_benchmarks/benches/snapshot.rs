@@ -1,5 +1,6 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use entry::test_helpers::lp_to_entries;
+use data_types::timestamp::Precision;
+use entry::{test_helpers::lp_to_entries, Entry};
 use flate2::read::GzDecoder;
 use mutable_buffer::chunk::{Chunk, ChunkMetrics};
 use std::io::Read;
@@ -10,19 +11,26 @@ fn snapshot_chunk(chunk: &Chunk) {
 }
 
 fn chunk(count: usize) -> Chunk {
-    // m0 is hard coded into tag_values.lp.gz
-    let mut chunk = Chunk::new("m0", ChunkMetrics::new_unregistered());
-
     let raw = include_bytes!("../../tests/fixtures/lineproto/tag_values.lp.gz");
     let mut gz = GzDecoder::new(&raw[..]);
     let mut lp = String::new();
     gz.read_to_string(&mut lp).unwrap();
 
+    chunk_from_entries(count, &lp_to_entries(&lp, Precision::Nanoseconds))
+}
+
+fn chunk_from_entries(count: usize, entries: &[Entry]) -> Chunk {
+    // m0 is hard coded into tag_values.lp.gz, and the first measurement of the
+    // generated data
+    let mut chunk = Chunk::new("m0", ChunkMetrics::new_unregistered());
+
     for _ in 0..count {
-        for entry in lp_to_entries(&lp) {
+        for entry in entries {
             for write in entry.partition_writes().iter().flatten() {
                 for batch in write.table_batches() {
-                    chunk.write_table_batch(1, 5, batch).unwrap();
+                    chunk
+                        .write_table_batch(1, 5, batch, Default::default(), &[])
+                        .unwrap();
                 }
             }
         }
@@ -42,5 +50,18 @@ pub fn snapshot_mb(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, snapshot_mb);
+/// Like `snapshot_mb`, with the default data of `data_generator`
+pub fn snapshot_generated_mb(c: &mut Criterion) {
+    let mut group = c.benchmark_group("snapshot_generated_mb");
+    let entries = data_generator::Config::default().entries(10_000).unwrap();
+    for count in &[1, 2, 3, 4, 5] {
+        let chunk = chunk_from_entries(*count as _, &entries);
+        group.bench_function(BenchmarkId::from_parameter(count), |b| {
+            b.iter(|| snapshot_chunk(&chunk));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, snapshot_mb, snapshot_generated_mb);
 criterion_main!(benches);
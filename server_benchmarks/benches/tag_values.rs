@@ -45,16 +45,40 @@ async fn setup_scenarios() -> Vec<DbScenario> {
     db
 }
 
+// The same scenarios with the data of `data_generator`, which has the same
+// tag keys and cardinalities. The points are spread over the hour of the
+// partition instead of a minute.
+async fn setup_generated_scenarios() -> Vec<DbScenario> {
+    let config = data_generator::Config {
+        // 2021-04-12T17:00:00Z
+        start_time: 1_618_246_800_000_000_000,
+        spacing: 3_000_000,
+        ..Default::default()
+    };
+    let lp = config.line_protocol().unwrap();
+
+    query_tests::scenarios::make_two_chunk_scenarios("2021-04-12T17", &lp, &lp).await
+}
+
 // Run all benchmarks for `tag_values`.
 pub fn benchmark_tag_values(c: &mut Criterion) {
     let scenarios = Runtime::new().unwrap().block_on(setup_scenarios());
 
-    execute_benchmark_group(c, scenarios.as_slice());
+    execute_benchmark_group(c, "", scenarios.as_slice());
+}
+
+// Run all benchmarks for `tag_values` against the generated data.
+pub fn benchmark_tag_values_generated(c: &mut Criterion) {
+    let scenarios = Runtime::new()
+        .unwrap()
+        .block_on(setup_generated_scenarios());
+
+    execute_benchmark_group(c, "generated/", scenarios.as_slice());
 }
 
 // Runs an async criterion benchmark against the provided scenarios and
-// predicate.
-fn execute_benchmark_group(c: &mut Criterion, scenarios: &[DbScenario]) {
+// predicate, in groups named after the scenarios with `group_prefix`.
+fn execute_benchmark_group(c: &mut Criterion, group_prefix: &str, scenarios: &[DbScenario]) {
     let planner = InfluxRpcPlanner::new();
 
     let predicates = vec![
@@ -76,7 +100,7 @@ fn execute_benchmark_group(c: &mut Criterion, scenarios: &[DbScenario]) {
 
     for scenario in scenarios {
         let DbScenario { scenario_name, db } = scenario;
-        let mut group = c.benchmark_group(scenario_name);
+        let mut group = c.benchmark_group(format!("{}{}", group_prefix, scenario_name));
 
         for (predicate, pred_name) in &predicates {
             for tag_key in tag_keys {
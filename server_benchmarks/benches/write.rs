@@ -1,4 +1,5 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use data_types::timestamp::Precision;
 use entry::{test_helpers::lp_to_entries, Entry};
 use flate2::read::GzDecoder;
 use mutable_buffer::chunk::{Chunk, ChunkMetrics};
@@ -6,14 +7,17 @@ use std::io::Read;
 
 #[inline]
 fn write_chunk(count: usize, entries: &[Entry]) {
-    // m0 is hard coded into tag_values.lp.gz
+    // m0 is hard coded into tag_values.lp.gz, and the first measurement of the
+    // generated data
     let mut chunk = Chunk::new("m0", ChunkMetrics::new_unregistered());
 
     for _ in 0..count {
         for entry in entries {
             for write in entry.partition_writes().iter().flatten() {
                 for batch in write.table_batches() {
-                    chunk.write_table_batch(1, 5, batch).unwrap();
+                    chunk
+                        .write_table_batch(1, 5, batch, Default::default(), &[])
+                        .unwrap();
                 }
             }
         }
@@ -25,7 +29,7 @@ fn load_entries() -> Vec<Entry> {
     let mut gz = GzDecoder::new(&raw[..]);
     let mut lp = String::new();
     gz.read_to_string(&mut lp).unwrap();
-    lp_to_entries(&lp)
+    lp_to_entries(&lp, Precision::Nanoseconds)
 }
 
 pub fn write_mb(c: &mut Criterion) {
@@ -39,5 +43,17 @@ pub fn write_mb(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, write_mb);
+/// Like `write_mb`, with the default data of `data_generator`
+pub fn write_generated_mb(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write_generated_mb");
+    let entries = data_generator::Config::default().entries(10_000).unwrap();
+    for count in &[1, 2, 3, 4, 5] {
+        group.bench_function(BenchmarkId::from_parameter(count), |b| {
+            b.iter(|| write_chunk(*count, &entries));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, write_mb, write_generated_mb);
 criterion_main!(benches);
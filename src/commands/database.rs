@@ -1,12 +1,27 @@
 //! This module implements the `database` CLI command
-use std::{fs::File, io::Read, path::PathBuf, str::FromStr};
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Write as _},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use data_types::timestamp::Precision;
+use flate2::read::MultiGzDecoder;
 
 use influxdb_iox_client::{
     connection::Builder,
     flight,
-    format::QueryOutputFormat,
+    format::{
+        write_parquet, CsvOptions, PrettyOptions, QueryOutputFormat, StreamingFormatter,
+        TimestampFormat,
+    },
     management::{
-        self, generated_types::*, CreateDatabaseError, GetDatabaseError, ListDatabaseError,
+        self, generated_types::*, BackupDatabaseError, CloneDatabaseError, CreateDatabaseError,
+        DeleteDatabaseError, DeleteError, ExportDatabaseError, GetDatabaseError, ImportTsmError,
+        ListDatabaseError, RestoreDatabaseError, RestoreDeletedDatabaseError,
     },
     write::{self, WriteError},
 };
@@ -14,7 +29,9 @@ use structopt::StructOpt;
 use thiserror::Error;
 
 mod chunk;
+mod continuous_query;
 mod partition;
+mod running_query;
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -27,6 +44,30 @@ pub enum Error {
     #[error("Error listing databases: {0}")]
     ListDatabaseError(#[from] ListDatabaseError),
 
+    #[error("Error deleting database: {0}")]
+    DeleteDatabaseError(#[from] DeleteDatabaseError),
+
+    #[error("Error deleting rows: {0}")]
+    DeleteError(#[from] DeleteError),
+
+    #[error("Error backing up database: {0}")]
+    BackupDatabaseError(#[from] BackupDatabaseError),
+
+    #[error("Error restoring database: {0}")]
+    RestoreDatabaseError(#[from] RestoreDatabaseError),
+
+    #[error("Error restoring deleted database: {0}")]
+    RestoreDeletedDatabaseError(#[from] RestoreDeletedDatabaseError),
+
+    #[error("Error cloning database: {0}")]
+    CloneDatabaseError(#[from] CloneDatabaseError),
+
+    #[error("Error exporting database: {0}")]
+    ExportDatabaseError(#[from] ExportDatabaseError),
+
+    #[error("Error importing TSM files: {0}")]
+    ImportTsmError(#[from] ImportTsmError),
+
     #[error("Error connecting to IOx: {0}")]
     ConnectionError(#[from] influxdb_iox_client::connection::Error),
 
@@ -39,6 +80,9 @@ pub enum Error {
     #[error("Error writing: {0}")]
     WriteError(#[from] WriteError),
 
+    #[error("{} lines were not written", count)]
+    LinesRejected { count: usize },
+
     #[error("Error formatting: {0}")]
     FormattingError(#[from] influxdb_iox_client::format::Error),
 
@@ -51,8 +95,29 @@ pub enum Error {
     #[error("Error in partition subcommand: {0}")]
     Partition(#[from] partition::Error),
 
+    #[error("Error in continuous-query subcommand: {0}")]
+    ContinuousQuery(#[from] continuous_query::Error),
+
+    #[error("Error in running-query subcommand: {0}")]
+    RunningQuery(#[from] running_query::Error),
+
     #[error("JSON Serialization error: {0}")]
     Serde(#[from] serde_json::Error),
+
+    #[error("CSV delimiter must be a single ASCII character, got '{0}'")]
+    InvalidDelimiter(char),
+
+    #[error("The 'parquet' format can only be written to a file, use --output")]
+    ParquetToStdout,
+
+    #[error("Error creating output file {:?}: {}", file_name, source)]
+    CreatingOutput {
+        file_name: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Error writing output: {0}")]
+    WritingOutput(std::io::Error),
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -91,6 +156,28 @@ struct Create {
     #[structopt(long, default_value = "10485760")] // 10485760 = 10*1024*1024
     mutable_size_threshold: usize,
 
+    /// A chunk of data within a partition that received its first write
+    /// this many seconds ago will be frozen and compacted, even if it is
+    /// still receiving writes. 0 disables this trigger
+    #[structopt(long, default_value = "0")]
+    mutable_max_age_seconds: u32,
+
+    /// Once a chunk of data within a partition reaches this number of rows
+    /// writes will be directed to a new chunk and this chunk will be
+    /// compacted. 0 disables this trigger
+    #[structopt(long, default_value = "0")]
+    mutable_row_threshold: u64,
+
+    /// Share the memory for tag values between the open chunks of all
+    /// partitions, instead of storing a copy of them in every chunk
+    #[structopt(long)]
+    mutable_shared_tag_values: bool,
+
+    /// A chunk in the read buffer is only persisted once its first write is
+    /// at least this many seconds old
+    #[structopt(long, default_value = "0")]
+    persist_age_threshold_seconds: u32,
+
     /// Once the total amount of buffered data in memory reaches this size start
     /// dropping data from memory based on the drop_order
     #[structopt(long, default_value = "52428800")] // 52428800 = 50*1024*1024
@@ -113,6 +200,82 @@ struct Create {
     /// Do not allow writing new data to this database
     #[structopt(long)]
     immutable: bool,
+
+    /// Template used to compute the partition key of each row: `strftime`
+    /// formats of the time column combined with tag or field values named in
+    /// angle brackets, e.g. `%Y-%m-%d/region=<region>`. Defaults to hourly
+    /// partitions
+    #[structopt(long, default_value = "%Y-%m-%d %H:00:00")]
+    partition_template: String,
+
+    /// Kafka bootstrap servers (e.g. `localhost:9092`), if the server is
+    /// built with the `kafka` feature. Incoming entries are appended to a
+    /// topic named after the database before they are applied, and replayed
+    /// when the database is loaded again. A local directory given as
+    /// `file:///path/to/dir` stores the entries in write-ahead log segments
+    /// in a subdirectory named after the database, which are deleted once
+    /// the data of their entries is persisted
+    #[structopt(long)]
+    write_buffer: Option<String>,
+
+    /// ID of a peer server that asynchronously receives a copy of every
+    /// entry written to this database, e.g. a warm standby. May be given
+    /// more than once. Entries are queued in memory; those that don't fit in
+    /// the queue of a peer that is unreachable or can't keep up, or that are
+    /// queued when the server stops, are never sent to it
+    #[structopt(long = "replicate-to")]
+    replication_targets: Vec<u32>,
+
+    /// Reject writes that would create more than this many tables. 0 means
+    /// unlimited
+    #[structopt(long, default_value = "0")]
+    max_tables: u64,
+
+    /// Reject writes that would add more than this many columns to a table.
+    /// 0 means unlimited
+    #[structopt(long, default_value = "0")]
+    max_columns_per_table: u64,
+
+    /// Reject writes once the database holds this many rows. 0 means
+    /// unlimited
+    #[structopt(long, default_value = "0")]
+    max_rows: u64,
+
+    /// Reject writes containing a line longer than this many bytes. 0 means
+    /// unlimited
+    #[structopt(long, default_value = "0")]
+    max_line_length: u64,
+
+    /// Reject writes containing a line with more than this many fields. 0
+    /// means unlimited
+    #[structopt(long, default_value = "0")]
+    max_fields_per_line: u64,
+
+    /// Reject writes whose lines have more than this many distinct columns
+    /// for a single table. 0 means unlimited
+    #[structopt(long, default_value = "0")]
+    max_write_columns_per_table: u64,
+
+    /// Reject writes containing a table name longer than this many bytes. 0
+    /// means unlimited
+    #[structopt(long, default_value = "0")]
+    max_table_name_length: u64,
+
+    /// How to handle writes with values whose type differs from the type of
+    /// the existing column: reject the whole table batch, drop the
+    /// conflicting lines, or convert numeric values where that is lossless
+    #[structopt(
+        long,
+        default_value = "reject",
+        possible_values = &["reject", "drop-lines", "coerce"]
+    )]
+    schema_conflict_policy: String,
+
+    /// Convert field values of one numeric type to another when the types
+    /// conflict, even if that loses precision, given as `from:to` with the
+    /// types `i64`, `u64` and `f64`, e.g. `i64:f64`. Can be repeated
+    #[structopt(long = "coerce-type", parse(try_from_str = parse_type_coercion_rule))]
+    type_coercion_rules: Vec<TypeCoercionRule>,
 }
 
 /// Get list of databases
@@ -126,14 +289,139 @@ struct Get {
     name: String,
 }
 
+/// Delete a database, detaching it from the server so it no longer accepts
+/// writes or queries. Unless its data is preserved, the database can be
+/// restored with `restore` until the server purges its data
+#[derive(Debug, StructOpt)]
+struct Delete {
+    /// The name of the database
+    name: String,
+
+    /// Keep the database's data in object storage so it can be re-attached
+    /// later by creating a database with the same name
+    #[structopt(long)]
+    preserve_data: bool,
+}
+
+/// Delete the rows of a table within a time range, optionally only those with
+/// the given tag values. The rows no longer show up in queries right away and
+/// are removed from the data once it is persisted
+#[derive(Debug, StructOpt)]
+struct DeleteRows {
+    /// The name of the database
+    name: String,
+
+    /// The table to delete rows from
+    table_name: String,
+
+    /// Start of the time range (inclusive), in nanoseconds since the epoch.
+    /// Defaults to the earliest possible time
+    #[structopt(long)]
+    start: Option<i64>,
+
+    /// End of the time range (exclusive), in nanoseconds since the epoch.
+    /// Defaults to the latest possible time
+    #[structopt(long)]
+    stop: Option<i64>,
+
+    /// Only delete rows where the tag has this value, given as `tag=value`.
+    /// Can be repeated to match several tags
+    #[structopt(long = "tag", parse(try_from_str = parse_tag_match))]
+    tags: Vec<(String, String)>,
+}
+
+/// Back up a database: copy its rules, preserved catalog and the parquet
+/// files referenced by the catalog to another object store location
+#[derive(Debug, StructOpt)]
+struct Backup {
+    /// The name of the database
+    name: String,
+
+    /// `/` separated object store path prefix to write the backup to
+    destination: String,
+}
+
+/// Restore a database from a backup and load it. The database must not exist
+/// and must not have any data left in object storage. Without a source, a
+/// deleted database whose data has not been purged yet is restored instead
+#[derive(Debug, StructOpt)]
+struct Restore {
+    /// The name of the database
+    name: String,
+
+    /// `/` separated object store path prefix the backup was written to
+    source: Option<String>,
+}
+
+/// Create a new database with the rules of an existing one. The target
+/// database must not exist and must not have any data left in object storage
+#[derive(Debug, StructOpt)]
+struct CloneDatabase {
+    /// The name of the database to copy
+    source: String,
+
+    /// The name of the new database
+    target: String,
+
+    /// Also copy the source database's persisted data and catalog
+    #[structopt(long)]
+    with_data: bool,
+}
+
+/// Export the data of a database as parquet files, one per table and
+/// partition, in a hive-style directory layout:
+/// `<destination>/<table>/partition_key=<key>/data.parquet`
+#[derive(Debug, StructOpt)]
+struct Export {
+    /// The name of the database
+    name: String,
+
+    /// `file://` followed by a directory on the local filesystem of the
+    /// server, or else a `/` separated object store path prefix. Must be
+    /// empty
+    destination: String,
+
+    /// Only export rows from this time on (inclusive), in nanoseconds since
+    /// the epoch. Defaults to the earliest possible time
+    #[structopt(long)]
+    start: Option<i64>,
+
+    /// Only export rows before this time (exclusive), in nanoseconds since
+    /// the epoch. Defaults to the latest possible time
+    #[structopt(long)]
+    stop: Option<i64>,
+}
+
+/// Import TSM files written by InfluxDB 1.x or 2.x into a database. Every
+/// measurement becomes a table, partitioned according to the rules of the
+/// database and stored as persisted chunks
+#[derive(Debug, StructOpt)]
+struct ImportTsm {
+    /// The name of the database
+    name: String,
+
+    /// Paths of the TSM files on the local filesystem of the server
+    #[structopt(required = true)]
+    paths: Vec<String>,
+}
+
 /// Write data into the specified database
 #[derive(Debug, StructOpt)]
 struct Write {
     /// The name of the database
     name: String,
 
-    /// File with data to load. Currently supported formats are .lp
+    /// File with data to load. Currently supported formats are .lp and
+    /// gzip compressed .lp.gz. Use `-` to read from stdin
     file_name: PathBuf,
+
+    /// Maximum number of lines to send per write request
+    #[structopt(long, default_value = "10000")]
+    batch_size: NonZeroUsize,
+
+    /// The unit of the timestamps in the file ('ns', 'us', 'ms' or 's')
+    #[structopt(long, default_value = "ns")]
+    precision: Precision,
 }
 
 /// Query the data with SQL
@@ -145,9 +433,57 @@ struct Query {
     /// The query to run, in SQL format
     query: String,
 
-    /// Optional format ('pretty', 'json', or 'csv')
-    #[structopt(short, long, default_value = "pretty")]
-    format: String,
+    /// Optional format ('pretty', 'json', 'jsonl', 'influxv1', 'csv' or
+    /// 'parquet'). Defaults to the format matching the extension of the
+    /// `--output` file, or 'pretty'
+    #[structopt(short, long)]
+    format: Option<String>,
+
+    /// Write the results to this file instead of stdout
+    #[structopt(short, long)]
+    output: Option<PathBuf>,
+
+    /// Field delimiter to use for the 'csv' format
+    #[structopt(long, default_value = ",")]
+    delimiter: char,
+
+    /// Do not write a header row for the 'csv' format
+    #[structopt(long)]
+    no_header: bool,
+
+    /// Text used to render NULL values in the 'pretty' format
+    #[structopt(long, default_value = "")]
+    null: String,
+
+    /// Maximum width of a cell in the 'pretty' format; longer values are
+    /// truncated
+    #[structopt(long)]
+    max_width: Option<usize>,
+
+    /// Maximum number of rows rendered in the 'pretty' format
+    #[structopt(long)]
+    max_rows: Option<usize>,
+
+    /// How to render timestamps ('rfc3339', 'rfc3339-s', 'rfc3339-ms',
+    /// 'rfc3339-us', 'rfc3339-ns' or 'nanos'). Defaults to the output
+    /// format's native rendering
+    #[structopt(long)]
+    time_format: Option<String>,
+
+    /// Print results in the 'pretty' format as they arrive rather than
+    /// buffering the entire result. Other formats are always streamed.
+    #[structopt(long)]
+    stream: bool,
+
+    /// Print the logical and physical plans of the query instead of its
+    /// results
+    #[structopt(long)]
+    explain: bool,
+
+    /// Run the query and print its physical plan annotated with the number
+    /// of rows and the time spent in each step instead of its results
+    #[structopt(long, conflicts_with = "explain")]
+    analyze: bool,
 }
 
 /// All possible subcommands for database
@@ -156,14 +492,23 @@ enum Command {
     Create(Create),
     List(List),
     Get(Get),
+    Delete(Delete),
+    DeleteRows(DeleteRows),
+    Backup(Backup),
+    Restore(Restore),
+    Clone(CloneDatabase),
+    Export(Export),
+    ImportTsm(ImportTsm),
     Write(Write),
     Query(Query),
     Chunk(chunk::Config),
     Partition(partition::Config),
+    ContinuousQuery(continuous_query::Config),
+    RunningQuery(running_query::Config),
 }
 
-pub async fn command(url: String, config: Config) -> Result<()> {
-    let connection = Builder::default().build(url.clone()).await?;
+pub async fn command(connection_builder: Builder, url: String, config: Config) -> Result<()> {
+    let connection = connection_builder.clone().build(url.clone()).await?;
 
     match config.command {
         Command::Create(command) => {
@@ -174,6 +519,10 @@ pub async fn command(url: String, config: Config) -> Result<()> {
                     mutable_linger_seconds: command.mutable_linger_seconds,
                     mutable_minimum_age_seconds: command.mutable_minimum_age_seconds,
                     mutable_size_threshold: command.mutable_size_threshold as _,
+                    mutable_max_age_seconds: command.mutable_max_age_seconds,
+                    mutable_row_threshold: command.mutable_row_threshold,
+                    mutable_shared_tag_values: command.mutable_shared_tag_values,
+                    persist_age_threshold_seconds: command.persist_age_threshold_seconds,
                     buffer_size_soft: command.buffer_size_soft as _,
                     buffer_size_hard: command.buffer_size_hard as _,
                     sort_order: None, // Server-side default
@@ -183,16 +532,50 @@ pub async fn command(url: String, config: Config) -> Result<()> {
                     worker_backoff_millis: Default::default(),
                 }),
 
-                // Default to hourly partitions
                 partition_template: Some(PartitionTemplate {
                     parts: vec![partition_template::Part {
-                        part: Some(partition_template::part::Part::Time(
-                            "%Y-%m-%d %H:00:00".into(),
+                        part: Some(partition_template::part::Part::Template(
+                            command.partition_template,
                         )),
                     }],
                 }),
 
-                // Note no write buffer config
+                write_buffer_connection_string: command.write_buffer.unwrap_or_default(),
+                replication_config: (!command.replication_targets.is_empty()).then(|| {
+                    ReplicationConfig {
+                        targets: Some(NodeGroup {
+                            nodes: command
+                                .replication_targets
+                                .into_iter()
+                                .map(|id| node_group::Node { id })
+                                .collect(),
+                        }),
+                        // Server-side default
+                        queue_size: 0,
+                    }
+                }),
+
+                quotas: Some(Quotas {
+                    max_tables: command.max_tables,
+                    max_columns_per_table: command.max_columns_per_table,
+                    max_rows: command.max_rows,
+                }),
+
+                line_limits: Some(LineLimits {
+                    max_line_length: command.max_line_length,
+                    max_fields_per_line: command.max_fields_per_line,
+                    max_columns_per_table: command.max_write_columns_per_table,
+                    max_table_name_length: command.max_table_name_length,
+                }),
+
+                schema_conflict_policy: match command.schema_conflict_policy.as_str() {
+                    "drop-lines" => SchemaConflictPolicy::DropLines,
+                    "coerce" => SchemaConflictPolicy::Coerce,
+                    _ => SchemaConflictPolicy::Reject,
+                } as _,
+
+                type_coercion_rules: command.type_coercion_rules,
+
                 ..Default::default()
             };
 
@@ -210,24 +593,139 @@ pub async fn command(url: String, config: Config) -> Result<()> {
             let database = client.get_database(get.name).await?;
             println!("{}", serde_json::to_string_pretty(&database)?);
         }
+        Command::Delete(delete) => {
+            let mut client = management::Client::new(connection);
+            client
+                .delete_database(delete.name, delete.preserve_data)
+                .await?;
+            println!("Ok");
+        }
+        Command::DeleteRows(delete) => {
+            let mut client = management::Client::new(connection);
+            let predicate = DeletePredicate {
+                table_name: delete.table_name,
+                start_time: delete.start.unwrap_or(i64::MIN),
+                stop_time: delete.stop.unwrap_or(i64::MAX),
+                tags: delete.tags.into_iter().collect(),
+            };
+            client.delete(delete.name, predicate).await?;
+            println!("Ok");
+        }
+        Command::Backup(backup) => {
+            let mut client = management::Client::new(connection);
+            let files = client
+                .backup_database(backup.name, backup.destination)
+                .await?;
+            println!("Copied {} files", files);
+        }
+        Command::Restore(restore) => {
+            let mut client = management::Client::new(connection);
+            match restore.source {
+                Some(source) => {
+                    let files = client.restore_database(restore.name, source).await?;
+                    println!("Copied {} files", files);
+                }
+                None => {
+                    client.restore_deleted_database(restore.name).await?;
+                    println!("Ok");
+                }
+            }
+        }
+        Command::Clone(clone) => {
+            let mut client = management::Client::new(connection);
+            let files = client
+                .clone_database(clone.source, clone.target, clone.with_data)
+                .await?;
+            if clone.with_data {
+                println!("Copied {} files", files);
+            } else {
+                println!("Ok");
+            }
+        }
+        Command::Export(export) => {
+            let mut client = management::Client::new(connection);
+            let range = match (export.start, export.stop) {
+                (None, None) => None,
+                (start, stop) => Some(start.unwrap_or(i64::MIN)..stop.unwrap_or(i64::MAX)),
+            };
+            let files = client
+                .export_database(export.name, export.destination, range)
+                .await?;
+            println!("Wrote {} files", files);
+        }
+        Command::ImportTsm(import) => {
+            let mut client = management::Client::new(connection);
+            let rows = client.import_tsm(import.name, import.paths).await?;
+            println!("Imported {} rows", rows);
+        }
         Command::Write(write) => {
             let mut client = write::Client::new(connection);
-
-            let mut file = File::open(&write.file_name).map_err(|e| Error::ReadingFile {
-                file_name: write.file_name.clone(),
-                source: e,
-            })?;
-
-            let mut lp_data = String::new();
-            file.read_to_string(&mut lp_data)
-                .map_err(|e| Error::ReadingFile {
-                    file_name: write.file_name.clone(),
-                    source: e,
-                })?;
-
-            let lines_written = client.write(write.name, lp_data).await?;
+            let Write {
+                name,
+                file_name,
+                batch_size,
+                precision,
+            } = write;
+            let precision = write::generated_types::Precision::from(precision);
+
+            let reading_file = |source| Error::ReadingFile {
+                file_name: file_name.clone(),
+                source,
+            };
+            let reader = open_lp_data(&file_name).map_err(reading_file)?;
+
+            let mut lines_written = 0;
+            let mut rejected = Vec::new();
+            let mut batch = String::new();
+            // The line numbers in the file of the lines in `batch`
+            let mut batch_lines = Vec::new();
+            for (idx, line) in reader.lines().enumerate() {
+                let line = line.map_err(reading_file)?;
+                // Not worth sending, the server reports rejected lines by
+                // their number in `batch`, which `batch_lines` maps back
+                let trimmed = line.trim_start();
+                if trimmed.is_empty() || trimmed.starts_with('#') {
+                    continue;
+                }
+
+                batch.push_str(&line);
+                batch.push('\n');
+                batch_lines.push(idx + 1);
+
+                if batch_lines.len() == batch_size.get() {
+                    lines_written += write_batch(
+                        &mut client,
+                        &name,
+                        std::mem::take(&mut batch),
+                        precision,
+                        &batch_lines,
+                        &mut rejected,
+                    )
+                    .await?;
+                    batch_lines.clear();
+                }
+            }
+            if !batch_lines.is_empty() {
+                lines_written += write_batch(
+                    &mut client,
+                    &name,
+                    batch,
+                    precision,
+                    &batch_lines,
+                    &mut rejected,
+                )
+                .await?;
+            }
 
             println!("{} Lines OK", lines_written);
+            if !rejected.is_empty() {
+                for (line_number, reason) in &rejected {
+                    eprintln!("line {}: {}", line_number, reason);
+                }
+                return Err(Error::LinesRejected {
+                    count: rejected.len(),
+                });
+            }
         }
         Command::Query(query) => {
             let mut client = flight::Client::new(connection);
@@ -235,30 +733,224 @@ pub async fn command(url: String, config: Config) -> Result<()> {
                 name,
                 format,
                 query,
+                delimiter,
+                no_header,
+                null,
+                max_width,
+                max_rows,
+                stream,
+                time_format,
+                output,
+                explain,
+                analyze,
             } = query;
 
-            let format = QueryOutputFormat::from_str(&format)?;
+            let query = match (explain, analyze) {
+                (_, true) => format!("EXPLAIN ANALYZE {}", query),
+                (true, false) => format!("EXPLAIN {}", query),
+                (false, false) => query,
+            };
+
+            let format = match (format, &output) {
+                (Some(format), _) => format,
+                (None, Some(output)) => format_for_file(output),
+                (None, None) => QueryOutputFormat::Pretty.to_string(),
+            };
+            if !delimiter.is_ascii() {
+                return Err(Error::InvalidDelimiter(delimiter));
+            }
+            let time_format = time_format
+                .map(|time_format| TimestampFormat::from_str(&time_format))
+                .transpose()?;
+
+            let pretty_options = PrettyOptions::default()
+                .with_null(null)
+                .with_max_width(max_width)
+                .with_max_rows(max_rows);
+            let csv_options = CsvOptions::default()
+                .with_delimiter(delimiter as u8)
+                .with_header(!no_header);
 
             let mut query_results = client.perform_query(&name, query).await?;
 
-            // It might be nice to do some sort of streaming write
-            // rather than buffering the whole thing.
-            let mut batches = vec![];
-            while let Some(data) = query_results.next().await? {
-                batches.push(data);
+            if format.eq_ignore_ascii_case(PARQUET_FORMAT) {
+                let output = output.ok_or(Error::ParquetToStdout)?;
+
+                let mut batches = vec![];
+                while let Some(data) = query_results.next().await? {
+                    let data = match &time_format {
+                        Some(time_format) => time_format.convert(&data)?,
+                        None => data,
+                    };
+                    batches.push(data);
+                }
+                let schema = batches
+                    .first()
+                    .map(|batch| batch.schema())
+                    .unwrap_or_else(|| query_results.schema());
+
+                write_parquet(schema, &batches, create_output(&output)?)?;
+                return Ok(());
             }
 
-            let formatted_result = format.format(&batches)?;
+            let format = QueryOutputFormat::from_str(&format)?;
+            let mut writer: Box<dyn std::io::Write> = match &output {
+                Some(output) => Box::new(create_output(output)?),
+                None => Box::new(std::io::stdout()),
+            };
 
-            println!("{}", formatted_result);
+            if format == QueryOutputFormat::Pretty && !stream {
+                // Buffer the whole result so the table's columns are sized
+                // to fit all rows
+                let mut batches = vec![];
+                while let Some(data) = query_results.next().await? {
+                    let data = match &time_format {
+                        Some(time_format) => time_format.convert(&data)?,
+                        None => data,
+                    };
+                    batches.push(data);
+                }
+
+                writeln!(writer, "{}", pretty_options.format(&batches)?)
+                    .map_err(Error::WritingOutput)?;
+            } else {
+                let mut formatter = StreamingFormatter::new(format, &mut writer)
+                    .with_pretty_options(pretty_options)
+                    .with_csv_options(csv_options);
+                if let Some(time_format) = time_format {
+                    formatter = formatter.with_timestamp_format(time_format);
+                }
+
+                while let Some(data) = query_results.next().await? {
+                    formatter.write(&data)?;
+                }
+                formatter.finish()?;
+
+                writeln!(writer).map_err(Error::WritingOutput)?;
+            }
+            writer.flush().map_err(Error::WritingOutput)?;
         }
         Command::Chunk(config) => {
-            chunk::command(url, config).await?;
+            chunk::command(connection_builder, url, config).await?;
         }
         Command::Partition(config) => {
-            partition::command(url, config).await?;
+            partition::command(connection_builder, url, config).await?;
+        }
+        Command::ContinuousQuery(config) => {
+            continuous_query::command(connection_builder, url, config).await?;
+        }
+        Command::RunningQuery(config) => {
+            running_query::command(connection_builder, url, config).await?;
         }
     }
 
     Ok(())
 }
+
+/// Name of the query output format that is written with [`write_parquet`]
+/// rather than a [`QueryOutputFormat`]
+const PARQUET_FORMAT: &str = "parquet";
+
+/// Returns the query output format matching the extension of `file_name`, or
+/// the 'pretty' format for unknown extensions
+/// Parses a `tag=value` pair
+fn parse_tag_match(s: &str) -> Result<(String, String), String> {
+    let mut split = s.splitn(2, '=');
+    match (split.next(), split.next()) {
+        (Some(tag), Some(value)) if !tag.is_empty() => Ok((tag.to_string(), value.to_string())),
+        _ => Err(format!("expected `tag=value` but got {:?}", s)),
+    }
+}
+
+/// Parses a `from:to` pair of numeric column types
+fn parse_type_coercion_rule(s: &str) -> Result<TypeCoercionRule, String> {
+    let column_type = |name: &str| match name {
+        "i64" => Ok(ColumnType::I64),
+        "u64" => Ok(ColumnType::U64),
+        "f64" => Ok(ColumnType::F64),
+        _ => Err(format!(
+            "expected one of `i64`, `u64` or `f64` but got {:?}",
+            name
+        )),
+    };
+
+    let mut split = s.splitn(2, ':');
+    match (split.next(), split.next()) {
+        (Some(from), Some(to)) => Ok(TypeCoercionRule {
+            from: column_type(from)? as _,
+            to: column_type(to)? as _,
+        }),
+        _ => Err(format!("expected `from:to` but got {:?}", s)),
+    }
+}
+
+fn format_for_file(file_name: &Path) -> String {
+    match file_name.extension().and_then(OsStr::to_str) {
+        Some(extension)
+            if extension.eq_ignore_ascii_case(PARQUET_FORMAT)
+                || QueryOutputFormat::from_str(extension).is_ok() =>
+        {
+            extension.to_string()
+        }
+        _ => QueryOutputFormat::Pretty.to_string(),
+    }
+}
+
+/// Creates (or truncates) the file query results are written to
+fn create_output(file_name: &Path) -> Result<BufWriter<File>> {
+    let file = File::create(file_name).map_err(|source| Error::CreatingOutput {
+        file_name: file_name.to_path_buf(),
+        source,
+    })?;
+    Ok(BufWriter::new(file))
+}
+
+/// Writes `batch`, whose lines have the numbers `line_numbers` in the input
+/// file and timestamps in `precision`, and returns the number of lines
+/// written. The line number and the reason and start of each rejected line is
+/// added to `rejected`
+async fn write_batch(
+    client: &mut write::Client,
+    name: &str,
+    batch: String,
+    precision: write::generated_types::Precision,
+    line_numbers: &[usize],
+    rejected: &mut Vec<(usize, String)>,
+) -> Result<usize> {
+    match client.write_with_precision(name, batch, precision).await {
+        Ok(lines_written) => Ok(lines_written),
+        Err(WriteError::PartialWrite {
+            lines_written,
+            rejected_lines,
+        }) => {
+            rejected.extend(rejected_lines.into_iter().map(|line| {
+                let line_number = (line.line_number as usize)
+                    .checked_sub(1)
+                    .and_then(|idx| line_numbers.get(idx))
+                    .copied()
+                    .unwrap_or_default();
+                (line_number, format!("{}: '{}'", line.reason, line.snippet))
+            }));
+            Ok(lines_written)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Opens line protocol data for buffered reading. `-` reads from stdin and
+/// files ending in `.gz` are decompressed on the fly
+fn open_lp_data(file_name: &Path) -> std::io::Result<Box<dyn BufRead + Send>> {
+    let reader: Box<dyn Read + Send> = if file_name == Path::new("-") {
+        Box::new(std::io::stdin())
+    } else {
+        Box::new(File::open(file_name)?)
+    };
+
+    let reader: Box<dyn Read + Send> = if file_name.extension() == Some(OsStr::new("gz")) {
+        Box::new(MultiGzDecoder::new(reader))
+    } else {
+        reader
+    };
+
+    Ok(Box::new(BufReader::new(reader)))
+}
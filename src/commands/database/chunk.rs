@@ -1,11 +1,14 @@
 //! This module implements the `chunk` CLI command
-use data_types::chunk_metadata::ChunkSummary;
+use data_types::chunk_metadata::{ChunkEvent, ChunkSummary};
+use data_types::job::Operation;
 use generated_types::google::FieldViolation;
 use influxdb_iox_client::{
     connection::Builder,
-    management::{self, ListChunksError},
+    management::{
+        self, ClosePartitionChunkError, ListChunksError, NewPartitionChunkError, WatchChunksError,
+    },
 };
-use std::convert::TryFrom;
+use std::convert::{TryFrom, TryInto};
 use structopt::StructOpt;
 use thiserror::Error;
 
@@ -14,6 +17,15 @@ pub enum Error {
     #[error("Error listing chunks: {0}")]
     ListChunkError(#[from] ListChunksError),
 
+    #[error("Error closing chunk: {0}")]
+    CloseChunkError(#[from] NewPartitionChunkError),
+
+    #[error("Error moving chunk: {0}")]
+    MoveChunkError(#[from] ClosePartitionChunkError),
+
+    #[error("Error watching chunks: {0}")]
+    WatchChunksError(#[from] WatchChunksError),
+
     #[error("Error interpreting server response: {0}")]
     ConvertingResponse(#[from] FieldViolation),
 
@@ -40,21 +52,67 @@ struct List {
     db_name: String,
 }
 
+/// Closes the open chunk of a table in the Mutable Buffer for writing. New
+/// writes to the table will go to a new open chunk
+#[derive(Debug, StructOpt)]
+struct CloseChunk {
+    /// The name of the database
+    db_name: String,
+
+    /// The partition key
+    partition_key: String,
+
+    /// The table name
+    table_name: String,
+}
+
+/// Moves a chunk from the Mutable Buffer to the Read Buffer, closing it first
+/// if necessary. Prints the operation tracking the move in JSON format
+#[derive(Debug, StructOpt)]
+struct MoveChunk {
+    /// The name of the database
+    db_name: String,
+
+    /// The partition key
+    partition_key: String,
+
+    /// The table name
+    table_name: String,
+
+    /// The chunk id
+    chunk_id: u32,
+}
+
+/// Prints chunk lifecycle state transitions (open, closed, moving, moved,
+/// persisting, persisted, unloaded, dropped) of a database as they happen,
+/// one per line, until interrupted
+#[derive(Debug, StructOpt)]
+struct Watch {
+    /// The name of the database
+    db_name: String,
+
+    /// Print each transition as a JSON object instead of plain text
+    #[structopt(long)]
+    json: bool,
+}
+
 /// All possible subcommands for chunk
 #[derive(Debug, StructOpt)]
 enum Command {
     List(List),
+    CloseChunk(CloseChunk),
+    MoveChunk(MoveChunk),
+    Watch(Watch),
 }
 
-pub async fn command(url: String, config: Config) -> Result<()> {
-    let connection = Builder::default().build(url).await?;
+pub async fn command(connection_builder: Builder, url: String, config: Config) -> Result<()> {
+    let connection = connection_builder.build(url).await?;
+    let mut client = management::Client::new(connection);
 
     match config.command {
         Command::List(get) => {
             let List { db_name } = get;
 
-            let mut client = management::Client::new(connection);
-
             let chunks = client.list_chunks(db_name).await?;
 
             let chunks = chunks
@@ -64,6 +122,55 @@ pub async fn command(url: String, config: Config) -> Result<()> {
 
             serde_json::to_writer_pretty(std::io::stdout(), &chunks)?;
         }
+        Command::CloseChunk(close_chunk) => {
+            let CloseChunk {
+                db_name,
+                partition_key,
+                table_name,
+            } = close_chunk;
+
+            client
+                .new_partition_chunk(db_name, partition_key, table_name)
+                .await?;
+            println!("Ok");
+        }
+        Command::MoveChunk(move_chunk) => {
+            let MoveChunk {
+                db_name,
+                partition_key,
+                table_name,
+                chunk_id,
+            } = move_chunk;
+
+            let operation: Operation = client
+                .close_partition_chunk(db_name, partition_key, table_name, chunk_id)
+                .await?
+                .try_into()?;
+
+            serde_json::to_writer_pretty(std::io::stdout(), &operation)?;
+        }
+        Command::Watch(watch) => {
+            let Watch { db_name, json } = watch;
+
+            let mut events = client.watch_chunks(db_name).await?;
+
+            while let Some(event) = events.next().await? {
+                let event = ChunkEvent::try_from(event)?;
+
+                if json {
+                    println!("{}", serde_json::to_string(&event)?);
+                } else {
+                    println!(
+                        "{} {} {} {} {}",
+                        event.time.to_rfc3339(),
+                        event.partition_key,
+                        event.table_name,
+                        event.id,
+                        event.state.as_str()
+                    );
+                }
+            }
+        }
     }
 
     Ok(())
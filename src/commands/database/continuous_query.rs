@@ -0,0 +1,149 @@
+//! This module implements the `continuous-query` CLI command
+use generated_types::google::protobuf::Duration;
+use influxdb_iox_client::{
+    connection::Builder,
+    management::{
+        self, generated_types::ContinuousQuery, CreateContinuousQueryError,
+        DeleteContinuousQueryError, ListContinuousQueriesError,
+    },
+};
+use structopt::StructOpt;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Error creating continuous query: {0}")]
+    CreateContinuousQueryError(#[from] CreateContinuousQueryError),
+
+    #[error("Error listing continuous queries: {0}")]
+    ListContinuousQueriesError(#[from] ListContinuousQueriesError),
+
+    #[error("Error deleting continuous query: {0}")]
+    DeleteContinuousQueryError(#[from] DeleteContinuousQueryError),
+
+    #[error("Error connecting to IOx: {0}")]
+    ConnectionError(#[from] influxdb_iox_client::connection::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Manage continuous queries, which run SQL on a schedule and write the
+/// result into a table of the same database
+#[derive(Debug, StructOpt)]
+pub struct Config {
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+/// Create a continuous query. The `time` column of the result is written as
+/// the timestamp, the columns with a string type as tags and all other
+/// columns as fields
+#[derive(Debug, StructOpt)]
+struct Create {
+    /// The name of the database
+    db_name: String,
+
+    /// The name of the query, unique within the database
+    name: String,
+
+    /// The table the result is written to
+    target_table: String,
+
+    /// The SQL query to run
+    query: String,
+
+    /// Run the query at every multiple of this many seconds since the epoch
+    #[structopt(long)]
+    every_seconds: u64,
+
+    /// Shift the runs by this many seconds, e.g. every 3600 seconds with an
+    /// offset of 300 seconds runs at 5 minutes past every hour
+    #[structopt(long, default_value = "0")]
+    offset_seconds: u64,
+}
+
+/// List the continuous queries of a database, one per line
+#[derive(Debug, StructOpt)]
+struct List {
+    /// The name of the database
+    db_name: String,
+}
+
+/// Delete a continuous query
+#[derive(Debug, StructOpt)]
+struct Delete {
+    /// The name of the database
+    db_name: String,
+
+    /// The name of the query
+    name: String,
+}
+
+/// All possible subcommands for continuous-query
+#[derive(Debug, StructOpt)]
+enum Command {
+    Create(Create),
+    List(List),
+    Delete(Delete),
+}
+
+pub async fn command(connection_builder: Builder, url: String, config: Config) -> Result<()> {
+    let connection = connection_builder.build(url).await?;
+    let mut client = management::Client::new(connection);
+
+    match config.command {
+        Command::Create(create) => {
+            let Create {
+                db_name,
+                name,
+                target_table,
+                query,
+                every_seconds,
+                offset_seconds,
+            } = create;
+
+            let seconds = |seconds: u64| Duration {
+                seconds: seconds as _,
+                nanos: 0,
+            };
+
+            client
+                .create_continuous_query(
+                    db_name,
+                    ContinuousQuery {
+                        name,
+                        query,
+                        target_table,
+                        every: Some(seconds(every_seconds)),
+                        offset: Some(seconds(offset_seconds)),
+                    },
+                )
+                .await?;
+            println!("Ok");
+        }
+        Command::List(list) => {
+            let List { db_name } = list;
+
+            let queries = client.list_continuous_queries(db_name).await?;
+            for query in queries {
+                let seconds = |d: Option<Duration>| d.map(|d| d.seconds).unwrap_or_default();
+                println!(
+                    "{} {} every={}s offset={}s {}",
+                    query.name,
+                    query.target_table,
+                    seconds(query.every),
+                    seconds(query.offset),
+                    query.query
+                );
+            }
+        }
+        Command::Delete(delete) => {
+            let Delete { db_name, name } = delete;
+
+            client.delete_continuous_query(db_name, name).await?;
+            println!("Ok");
+        }
+    }
+
+    Ok(())
+}
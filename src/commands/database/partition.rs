@@ -1,15 +1,28 @@
 //! This module implements the `partition` CLI command
+use arrow::{
+    array::{ArrayRef, StringArray, UInt64Array},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
 use data_types::chunk_metadata::ChunkSummary;
 use data_types::job::Operation;
+use data_types::partition_metadata::{PartitionStorageSummary, TableStorageSummary};
 use generated_types::google::FieldViolation;
 use influxdb_iox_client::{
     connection::Builder,
+    format::QueryOutputFormat,
     management::{
-        self, ClosePartitionChunkError, GetPartitionError, ListPartitionChunksError,
-        ListPartitionsError, NewPartitionChunkError,
+        self, ClosePartitionChunkError, CompactPartitionError, DropPartitionError,
+        GetPartitionError, ListPartitionChunksError, ListPartitionsError, NewPartitionChunkError,
+        PersistPartitionChunkError,
     },
+    operations,
+};
+use std::{
+    convert::{TryFrom, TryInto},
+    str::FromStr,
+    sync::Arc,
 };
-use std::convert::{TryFrom, TryInto};
 use structopt::StructOpt;
 use thiserror::Error;
 
@@ -30,9 +43,27 @@ pub enum Error {
     #[error("Error closing chunk: {0}")]
     ClosePartitionChunkError(#[from] ClosePartitionChunkError),
 
+    #[error("Error persisting chunk: {0}")]
+    PersistPartitionChunkError(#[from] PersistPartitionChunkError),
+
+    #[error("Error compacting partition: {0}")]
+    CompactPartitionError(#[from] CompactPartitionError),
+
+    #[error("Error waiting for operation: {0}")]
+    OperationError(#[from] operations::Error),
+
+    #[error("Error dropping partition: {0}")]
+    DropPartitionError(#[from] DropPartitionError),
+
     #[error("Error rendering response as JSON: {0}")]
     WritingJson(#[from] serde_json::Error),
 
+    #[error("Error building partition summary batch: {0}")]
+    BuildingBatch(#[from] ArrowError),
+
+    #[error("Error formatting: {0}")]
+    FormattingError(#[from] influxdb_iox_client::format::Error),
+
     #[error("Received invalid response: {0}")]
     InvalidResponse(#[from] FieldViolation),
 
@@ -56,7 +87,8 @@ struct List {
     db_name: String,
 }
 
-/// Get details of a specific partition in JSON format (TODO)
+/// Get details of a specific partition: its tables with their chunk counts per
+/// storage tier, row counts and sizes. Printed in JSON format by default
 #[derive(Debug, StructOpt)]
 struct Get {
     /// The name of the database
@@ -64,6 +96,11 @@ struct Get {
 
     /// The partition key
     partition_key: String,
+
+    /// Optional format ('pretty', 'json', 'jsonl', 'influxv1' or 'csv') to
+    /// render one row per table instead of the JSON summary
+    #[structopt(short, long)]
+    format: Option<String>,
 }
 
 /// lists all chunks in this partition
@@ -107,6 +144,61 @@ struct CloseChunk {
     chunk_id: u32,
 }
 
+/// Writes a chunk in the read buffer to object storage. Prints the operation
+/// tracking the write in JSON format
+#[derive(Debug, StructOpt)]
+struct PersistChunk {
+    /// The name of the database
+    db_name: String,
+
+    /// The partition key
+    partition_key: String,
+
+    /// The table name
+    table_name: String,
+
+    /// The chunk id
+    chunk_id: u32,
+
+    /// Wait for the operation to complete before printing it
+    #[structopt(long)]
+    wait: bool,
+}
+
+/// Compacts the persisted chunks of a table within a partition into a single
+/// sorted and deduplicated chunk. Prints the operation tracking the compaction
+/// in JSON format
+#[derive(Debug, StructOpt)]
+struct Compact {
+    /// The name of the database
+    db_name: String,
+
+    /// The partition key
+    partition_key: String,
+
+    /// The table name
+    table_name: String,
+
+    /// Wait for the operation to complete before printing it
+    #[structopt(long)]
+    wait: bool,
+}
+
+/// Drops a partition and all of its chunks from memory. Persisted chunks are
+/// reloaded on restart unless `--delete-persisted` is given
+#[derive(Debug, StructOpt)]
+struct DropPartition {
+    /// The name of the database
+    db_name: String,
+
+    /// The partition key
+    partition_key: String,
+
+    /// Also remove the partition's parquet files from the preserved catalog
+    #[structopt(long)]
+    delete_persisted: bool,
+}
+
 /// All possible subcommands for partition
 #[derive(Debug, StructOpt)]
 enum Command {
@@ -120,11 +212,17 @@ enum Command {
     NewChunk(NewChunk),
     // Close the chunk and move to read buffer
     CloseChunk(CloseChunk),
+    // Write a chunk in the read buffer to object storage
+    PersistChunk(PersistChunk),
+    // Compact the persisted chunks of a table
+    Compact(Compact),
+    // Drop the partition and all of its chunks
+    Drop(DropPartition),
 }
 
-pub async fn command(url: String, config: Config) -> Result<()> {
-    let connection = Builder::default().build(url).await?;
-    let mut client = management::Client::new(connection);
+pub async fn command(connection_builder: Builder, url: String, config: Config) -> Result<()> {
+    let connection = connection_builder.build(url).await?;
+    let mut client = management::Client::new(connection.clone());
 
     match config.command {
         Command::List(list) => {
@@ -138,22 +236,20 @@ pub async fn command(url: String, config: Config) -> Result<()> {
             let Get {
                 db_name,
                 partition_key,
+                format,
             } = get;
 
-            let management::generated_types::Partition { key } =
-                client.get_partition(db_name, partition_key).await?;
+            let partition = client.get_partition(db_name, partition_key).await?;
+            let summary = PartitionStorageSummary::from(partition);
 
-            // TODO: get more details from the partition, andprint it
-            // out better (i.e. move to using Partition summary that
-            // is already in data_types)
-            #[derive(serde::Serialize)]
-            struct PartitionDetail {
-                key: String,
+            match format {
+                Some(format) => {
+                    let format = QueryOutputFormat::from_str(&format)?;
+                    let batch = tables_to_batch(&summary.tables)?;
+                    println!("{}", format.format(&[batch])?);
+                }
+                None => serde_json::to_writer_pretty(std::io::stdout(), &summary)?,
             }
-
-            let partition_detail = PartitionDetail { key };
-
-            serde_json::to_writer_pretty(std::io::stdout(), &partition_detail)?;
         }
         Command::ListChunks(list_chunks) => {
             let ListChunks {
@@ -198,7 +294,97 @@ pub async fn command(url: String, config: Config) -> Result<()> {
 
             serde_json::to_writer_pretty(std::io::stdout(), &operation)?;
         }
+        Command::PersistChunk(persist_chunk) => {
+            let PersistChunk {
+                db_name,
+                partition_key,
+                table_name,
+                chunk_id,
+                wait,
+            } = persist_chunk;
+
+            let mut operation: Operation = client
+                .persist_partition_chunk(db_name, partition_key, table_name, chunk_id)
+                .await?
+                .try_into()?;
+
+            if wait {
+                operation = operations::Client::new(connection)
+                    .wait_operation(operation.id, None)
+                    .await?
+                    .try_into()?;
+            }
+
+            serde_json::to_writer_pretty(std::io::stdout(), &operation)?;
+        }
+        Command::Compact(compact) => {
+            let Compact {
+                db_name,
+                partition_key,
+                table_name,
+                wait,
+            } = compact;
+
+            let mut operation: Operation = client
+                .compact_partition(db_name, partition_key, table_name)
+                .await?
+                .try_into()?;
+
+            if wait {
+                operation = operations::Client::new(connection)
+                    .wait_operation(operation.id, None)
+                    .await?
+                    .try_into()?;
+            }
+
+            serde_json::to_writer_pretty(std::io::stdout(), &operation)?;
+        }
+        Command::Drop(drop_partition) => {
+            let DropPartition {
+                db_name,
+                partition_key,
+                delete_persisted,
+            } = drop_partition;
+
+            client
+                .drop_partition(db_name, partition_key, delete_persisted)
+                .await?;
+            println!("Ok");
+        }
     }
 
     Ok(())
 }
+
+/// Converts the per-table storage summaries of a partition into a single
+/// [`RecordBatch`] with one row per table
+fn tables_to_batch(tables: &[TableStorageSummary]) -> Result<RecordBatch, ArrowError> {
+    let counter = |f: fn(&TableStorageSummary) -> usize| -> ArrayRef {
+        Arc::new(
+            tables
+                .iter()
+                .map(|t| Some(f(t) as u64))
+                .collect::<UInt64Array>(),
+        )
+    };
+
+    let table_names: ArrayRef = Arc::new(
+        tables
+            .iter()
+            .map(|t| Some(t.name.as_str()))
+            .collect::<StringArray>(),
+    );
+
+    RecordBatch::try_from_iter(vec![
+        ("table_name", table_names),
+        (
+            "mutable_buffer_chunks",
+            counter(|t| t.mutable_buffer_chunks),
+        ),
+        ("read_buffer_chunks", counter(|t| t.read_buffer_chunks)),
+        ("object_store_chunks", counter(|t| t.object_store_chunks)),
+        ("row_count", counter(|t| t.row_count)),
+        ("memory_bytes", counter(|t| t.memory_bytes)),
+        ("object_store_bytes", counter(|t| t.object_store_bytes)),
+    ])
+}
@@ -0,0 +1,87 @@
+//! This module implements the `running-query` CLI command
+use std::convert::TryFrom;
+
+use chrono::{DateTime, Utc};
+use influxdb_iox_client::{
+    connection::Builder,
+    management::{self, CancelQueryError, ListQueriesError},
+};
+use structopt::StructOpt;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Error listing queries: {0}")]
+    ListQueriesError(#[from] ListQueriesError),
+
+    #[error("Error cancelling query: {0}")]
+    CancelQueryError(#[from] CancelQueryError),
+
+    #[error("Error connecting to IOx: {0}")]
+    ConnectionError(#[from] influxdb_iox_client::connection::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Manage the queries running against a database
+#[derive(Debug, StructOpt)]
+pub struct Config {
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+/// List the queries running against a database, one per line
+#[derive(Debug, StructOpt)]
+struct List {
+    /// The name of the database
+    db_name: String,
+}
+
+/// Cancel a query running against a database
+#[derive(Debug, StructOpt)]
+struct Cancel {
+    /// The name of the database
+    db_name: String,
+
+    /// The id of the query, as listed by `running-query list`
+    id: String,
+}
+
+/// All possible subcommands for running-query
+#[derive(Debug, StructOpt)]
+enum Command {
+    List(List),
+    Cancel(Cancel),
+}
+
+pub async fn command(connection_builder: Builder, url: String, config: Config) -> Result<()> {
+    let connection = connection_builder.build(url).await?;
+    let mut client = management::Client::new(connection);
+
+    match config.command {
+        Command::List(list) => {
+            let List { db_name } = list;
+
+            let queries = client.list_queries(db_name).await?;
+            for query in queries {
+                let start_time = query
+                    .start_time
+                    .and_then(|t| DateTime::<Utc>::try_from(t).ok())
+                    .map(|t| t.to_rfc3339())
+                    .unwrap_or_default();
+                println!(
+                    "{} start_time={} cpu_nanos={} {}",
+                    query.id, start_time, query.cpu_nanos, query.text
+                );
+            }
+        }
+        Command::Cancel(cancel) => {
+            let Cancel { db_name, id } = cancel;
+
+            client.cancel_query(db_name, id).await?;
+            println!("Ok");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+//! This module implements the `debug` CLI command
+use structopt::StructOpt;
+use thiserror::Error;
+
+mod catalog;
+mod metrics;
+mod parquet_meta;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Error in catalog subcommand: {0}")]
+    Catalog(#[from] catalog::Error),
+
+    #[error("Error in metrics subcommand: {0}")]
+    Metrics(#[from] metrics::Error),
+
+    #[error("Error in parquet-meta subcommand: {0}")]
+    ParquetMeta(#[from] parquet_meta::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Inspect data persisted by IOx directly in object storage, without going
+/// through a running server, or the metrics of a running server
+#[derive(Debug, StructOpt)]
+pub struct Config {
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+/// All possible subcommands for debug
+#[derive(Debug, StructOpt)]
+enum Command {
+    Catalog(catalog::Config),
+    Metrics(metrics::Config),
+    ParquetMeta(parquet_meta::Config),
+}
+
+pub async fn command(config: Config) -> Result<()> {
+    match config.command {
+        Command::Catalog(catalog) => catalog::command(catalog).await?,
+        Command::Metrics(metrics) => metrics::command(metrics).await?,
+        Command::ParquetMeta(parquet_meta) => parquet_meta::command(parquet_meta).await?,
+    }
+
+    Ok(())
+}
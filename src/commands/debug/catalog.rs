@@ -0,0 +1,148 @@
+//! This module implements the `debug catalog` CLI command
+use std::convert::TryFrom;
+
+use data_types::{server_id::ServerId, DatabaseName, DatabaseNameError};
+use object_store::{
+    path::{parsed::DirsAndFileName, ObjectStorePath},
+    ObjectStore,
+};
+use parquet_file::catalog::{ActionDump, CatalogDump, TransactionDump};
+use structopt::StructOpt;
+use thiserror::Error;
+
+use crate::commands::run::ObjectStoreConfig;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Invalid database name: {0}")]
+    InvalidDatabaseName(#[from] DatabaseNameError),
+
+    #[error("Cannot set up object store: {0}")]
+    ObjectStore(#[from] crate::influxdb_ioxd::Error),
+
+    #[error("Cannot read preserved catalog: {0}")]
+    ReadingCatalog(#[from] parquet_file::catalog::Error),
+
+    #[error("No preserved catalog found for database {0}")]
+    CatalogNotFound(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Inspect the preserved catalog of a database
+#[derive(Debug, StructOpt)]
+pub struct Config {
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+/// Print the revisions, transactions and tracked parquet files of the
+/// preserved catalog of a database. The catalog is only read, never modified
+#[derive(Debug, StructOpt)]
+struct Dump {
+    /// The name of the database
+    db_name: String,
+
+    /// The identifier of the server that owns the database
+    #[structopt(long = "--server-id", env = "INFLUXDB_IOX_ID")]
+    server_id: ServerId,
+
+    #[structopt(flatten)]
+    object_store_config: ObjectStoreConfig,
+}
+
+/// All possible subcommands for catalog
+#[derive(Debug, StructOpt)]
+enum Command {
+    Dump(Dump),
+}
+
+pub async fn command(config: Config) -> Result<()> {
+    match config.command {
+        Command::Dump(dump) => {
+            let Dump {
+                db_name,
+                server_id,
+                object_store_config,
+            } = dump;
+
+            let db_name = DatabaseName::new(db_name)?;
+            let object_store = ObjectStore::try_from(&object_store_config)?;
+
+            let dump = CatalogDump::load(&object_store, server_id, &db_name).await?;
+            if dump.transactions.is_empty() {
+                return Err(Error::CatalogNotFound(db_name.to_string()));
+            }
+
+            for transaction in &dump.transactions {
+                print_transaction(&object_store, transaction);
+            }
+
+            let parquet_files = dump.parquet_files();
+            println!("Parquet files ({}):", parquet_files.len());
+            for path in parquet_files {
+                println!("  {}", display_path(&object_store, &path));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_transaction(object_store: &ObjectStore, transaction: &TransactionDump) {
+    println!(
+        "Revision {} ({})",
+        transaction.revision_counter, transaction.uuid
+    );
+    println!("  file: {}", transaction.path.display());
+
+    let content = match &transaction.content {
+        Ok(content) => content,
+        Err(e) => {
+            println!("  error: {}", e);
+            return;
+        }
+    };
+
+    println!("  version: {}", content.version);
+    match &content.start_timestamp {
+        Ok(ts) => println!("  started: {}", ts.to_rfc3339()),
+        Err(e) => println!("  started: error: {}", e),
+    }
+    match &content.previous_uuid {
+        Some(uuid) => println!("  previous: {}", uuid),
+        None => println!("  previous: none"),
+    }
+
+    for action in &content.actions {
+        match action {
+            ActionDump::Upgrade { format } => println!("  upgrade: {}", format),
+            ActionDump::AddParquet { path, num_rows } => {
+                let path = path_or_error(object_store, path);
+                match num_rows {
+                    Ok(num_rows) => println!("  add: {} ({} rows)", path, num_rows),
+                    Err(e) => println!("  add: {} (error: {})", path, e),
+                }
+            }
+            ActionDump::RemoveParquet { path } => {
+                println!("  remove: {}", path_or_error(object_store, path))
+            }
+        }
+    }
+}
+
+fn path_or_error(
+    object_store: &ObjectStore,
+    path: &parquet_file::catalog::Result<DirsAndFileName>,
+) -> String {
+    match path {
+        Ok(path) => display_path(object_store, path),
+        Err(e) => format!("error: {}", e),
+    }
+}
+
+fn display_path(object_store: &ObjectStore, path: &DirsAndFileName) -> String {
+    object_store
+        .path_from_dirs_and_filename(path.clone())
+        .display()
+}
@@ -0,0 +1,218 @@
+//! This module implements the `debug metrics` CLI command
+use regex::Regex;
+use structopt::StructOpt;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Error fetching metrics from {}: {}", url, source)]
+    Fetching { url: String, source: reqwest::Error },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Fetch the metrics of a running IOx server and print them grouped by metric
+/// family
+#[derive(Debug, StructOpt)]
+pub struct Config {
+    /// Only print metric families whose name matches this regular expression
+    #[structopt(long)]
+    filter: Option<Regex>,
+
+    /// HTTP address of the IOx server to fetch the metrics from
+    #[structopt(long, env = "IOX_HTTP_ADDR", default_value = "http://127.0.0.1:8080")]
+    http_host: String,
+}
+
+pub async fn command(config: Config) -> Result<()> {
+    let Config { filter, http_host } = config;
+
+    let url = format!("{}/metrics", http_host.trim_end_matches('/'));
+    let fetching = |source| Error::Fetching {
+        url: url.clone(),
+        source,
+    };
+
+    let text = reqwest::get(&url)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(fetching)?
+        .text()
+        .await
+        .map_err(fetching)?;
+
+    let families = parse_families(&text)
+        .into_iter()
+        .filter(|family| filter.as_ref().map_or(true, |re| re.is_match(&family.name)));
+
+    for family in families {
+        print!("{}", family);
+    }
+
+    Ok(())
+}
+
+/// A metric family parsed from the Prometheus text exposition format: the
+/// `# HELP` and `# TYPE` comments followed by its samples
+#[derive(Debug, Default, PartialEq)]
+struct MetricFamily {
+    name: String,
+    help: String,
+    kind: String,
+    samples: Vec<Sample>,
+}
+
+/// A single sample line, e.g. `http_requests_total{path="/metrics"} 3`
+#[derive(Debug, PartialEq)]
+struct Sample {
+    name: String,
+    labels: String,
+    value: String,
+}
+
+impl std::fmt::Display for MetricFamily {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.kind.is_empty() {
+            write!(f, " ({})", self.kind)?;
+        }
+        if !self.help.is_empty() {
+            write!(f, ": {}", self.help)?;
+        }
+        writeln!(f)?;
+
+        let keys: Vec<_> = self
+            .samples
+            .iter()
+            .map(|sample| {
+                // Histogram and summary samples carry a suffix such as
+                // `_bucket` or `_sum` that is worth keeping
+                let suffix = sample.name.strip_prefix(&self.name).unwrap_or(&sample.name);
+                match sample.labels.as_str() {
+                    "" => suffix.to_string(),
+                    labels => format!("{}{{{}}}", suffix, labels),
+                }
+            })
+            .collect();
+        let width = keys.iter().map(|key| key.len()).max().unwrap_or_default();
+
+        for (key, sample) in keys.iter().zip(&self.samples) {
+            writeln!(f, "  {:width$}  {}", key, sample.value, width = width)?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Parses the Prometheus text exposition format into metric families. Samples
+/// are attributed to the family of the preceding `# HELP`/`# TYPE` comments
+fn parse_families(text: &str) -> Vec<MetricFamily> {
+    let mut families: Vec<MetricFamily> = vec![];
+
+    // Returns the family named `name`, starting a new one unless it is the
+    // family currently being parsed
+    fn family<'a>(families: &'a mut Vec<MetricFamily>, name: &str) -> &'a mut MetricFamily {
+        if families.last().map_or(true, |family| family.name != name) {
+            families.push(MetricFamily {
+                name: name.to_string(),
+                ..Default::default()
+            });
+        }
+        families.last_mut().unwrap()
+    }
+
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut parts = comment.trim_start().splitn(3, ' ');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("HELP"), Some(name), help) => {
+                    family(&mut families, name).help = help.unwrap_or_default().to_string()
+                }
+                (Some("TYPE"), Some(name), kind) => {
+                    family(&mut families, name).kind = kind.unwrap_or_default().to_string()
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let (series, value) = match line.rfind(|c: char| c.is_whitespace()) {
+            Some(idx) => (line[..idx].trim_end(), &line[idx + 1..]),
+            None => continue,
+        };
+        let (name, labels) = match series.find('{') {
+            Some(idx) => (&series[..idx], series[idx + 1..].trim_end_matches('}')),
+            None => (series, ""),
+        };
+
+        let sample = Sample {
+            name: name.to_string(),
+            labels: labels.to_string(),
+            value: value.to_string(),
+        };
+        match families.last_mut() {
+            Some(family) if name.starts_with(&family.name) => family.samples.push(sample),
+            _ => family(&mut families, name).samples.push(sample),
+        }
+    }
+
+    families
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = r#"# HELP http_requests_total accepted requests
+# TYPE http_requests_total counter
+http_requests_total{path="/metrics",status="ok"} 3
+http_requests_total{path="/api/v2/write",status="error"} 1
+# HELP lp_line_size bytes per line
+# TYPE lp_line_size histogram
+lp_line_size_bucket{le="+Inf"} 2
+lp_line_size_sum 40
+lp_line_size_count 2
+untyped_metric 7
+"#;
+
+    #[test]
+    fn parse() {
+        let families = parse_families(TEXT);
+        let names: Vec<_> = families.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["http_requests_total", "lp_line_size", "untyped_metric"]
+        );
+
+        assert_eq!(families[0].help, "accepted requests");
+        assert_eq!(families[0].kind, "counter");
+        assert_eq!(
+            families[0].samples[1],
+            Sample {
+                name: "http_requests_total".to_string(),
+                labels: r#"path="/api/v2/write",status="error""#.to_string(),
+                value: "1".to_string(),
+            }
+        );
+
+        assert_eq!(families[1].kind, "histogram");
+        assert_eq!(families[1].samples.len(), 3);
+
+        assert_eq!(families[2].kind, "");
+        assert_eq!(families[2].samples[0].value, "7");
+    }
+
+    #[test]
+    fn display() {
+        let families = parse_families(TEXT);
+
+        assert_eq!(
+            families[1].to_string(),
+            r#"lp_line_size (histogram): bytes per line
+  _bucket{le="+Inf"}  2
+  _sum                40
+  _count              2
+
+"#
+        );
+    }
+}
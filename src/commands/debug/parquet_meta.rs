@@ -0,0 +1,159 @@
+//! This module implements the `debug parquet-meta` CLI command
+use std::{convert::TryFrom, path::PathBuf};
+
+use futures::TryStreamExt;
+use object_store::{path::ObjectStorePath, ObjectStore, ObjectStoreApi};
+use parquet::file::metadata::ParquetMetaData;
+use parquet_file::metadata::{
+    read_iox_metadata_from_parquet_metadata, read_parquet_metadata_from_file,
+    read_schema_from_parquet_metadata,
+};
+use structopt::StructOpt;
+use thiserror::Error;
+
+use crate::commands::run::ObjectStoreConfig;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Error reading file {:?}: {}", file_name, source)]
+    ReadingFile {
+        file_name: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Cannot set up object store: {0}")]
+    ObjectStore(#[from] crate::influxdb_ioxd::Error),
+
+    #[error("Error reading {} from object store: {}", location, source)]
+    ReadingObject {
+        location: String,
+        source: object_store::Error,
+    },
+
+    #[error("Cannot read parquet metadata: {0}")]
+    ReadingMetadata(#[from] parquet_file::metadata::Error),
+
+    #[error("Error rendering IOx metadata as JSON: {0}")]
+    WritingJson(#[from] serde_json::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Print the IOx metadata, schema, row group statistics and sizes of a
+/// parquet file persisted by IOx
+#[derive(Debug, StructOpt)]
+pub struct Config {
+    /// Location of the file. A path on the local filesystem unless
+    /// `--object-store` is given, in which case it is the path of the object
+    /// within the object store (e.g.
+    /// `1/mydb/data/cpu/2021-05-01/0/<uuid>.parquet`)
+    location: String,
+
+    #[structopt(flatten)]
+    object_store_config: ObjectStoreConfig,
+}
+
+pub async fn command(config: Config) -> Result<()> {
+    let Config {
+        location,
+        object_store_config,
+    } = config;
+
+    let data = match object_store_config.object_store {
+        Some(_) => {
+            let object_store = ObjectStore::try_from(&object_store_config)?;
+            read_object(&object_store, &location).await?
+        }
+        None => std::fs::read(&location).map_err(|source| Error::ReadingFile {
+            file_name: location.clone().into(),
+            source,
+        })?,
+    };
+
+    println!("File size: {} bytes", data.len());
+
+    let parquet_md = read_parquet_metadata_from_file(data)?;
+    print_metadata(&parquet_md)?;
+
+    Ok(())
+}
+
+async fn read_object(object_store: &ObjectStore, location: &str) -> Result<Vec<u8>> {
+    let mut path = object_store.new_path();
+    let mut parts: Vec<_> = location.split('/').filter(|p| !p.is_empty()).collect();
+    if let Some(file_name) = parts.pop() {
+        path.push_all_dirs(parts);
+        path.set_file_name(file_name);
+    }
+
+    let context = |source: object_store::Error| Error::ReadingObject {
+        location: location.to_string(),
+        source,
+    };
+    let data = object_store
+        .get(&path)
+        .await
+        .map_err(context)?
+        .map_ok(|bytes| bytes.to_vec())
+        .try_concat()
+        .await
+        .map_err(context)?;
+
+    Ok(data)
+}
+
+fn print_metadata(parquet_md: &ParquetMetaData) -> Result<()> {
+    let file_md = parquet_md.file_metadata();
+    println!("Rows: {}", file_md.num_rows());
+    println!("Row groups: {}", parquet_md.num_row_groups());
+    if let Some(created_by) = file_md.created_by() {
+        println!("Created by: {}", created_by);
+    }
+
+    match read_iox_metadata_from_parquet_metadata(parquet_md) {
+        Ok(iox_md) => println!("IOx metadata: {}", serde_json::to_string_pretty(&iox_md)?),
+        Err(e) => println!("IOx metadata: error: {}", e),
+    }
+
+    match read_schema_from_parquet_metadata(parquet_md) {
+        Ok(schema) => {
+            println!("Schema:");
+            for (iox_type, field) in schema.iter() {
+                let iox_type = iox_type
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|| "-".to_string());
+                println!(
+                    "  {}: {} ({:?}, nullable: {})",
+                    field.name(),
+                    iox_type,
+                    field.data_type(),
+                    field.is_nullable()
+                );
+            }
+        }
+        Err(e) => println!("Schema: error: {}", e),
+    }
+
+    for (idx, row_group) in parquet_md.row_groups().iter().enumerate() {
+        println!(
+            "Row group {}: {} rows, {} bytes",
+            idx,
+            row_group.num_rows(),
+            row_group.total_byte_size()
+        );
+        for column in row_group.columns() {
+            println!(
+                "  {}: {} bytes compressed, {} bytes uncompressed",
+                column.column_path().string(),
+                column.compressed_size(),
+                column.uncompressed_size()
+            );
+            match column.statistics() {
+                Some(stats) => println!("    statistics: {}", stats),
+                None => println!("    statistics: none"),
+            }
+        }
+    }
+
+    Ok(())
+}
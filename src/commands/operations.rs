@@ -1,4 +1,5 @@
-use data_types::job::Operation;
+use clap::arg_enum;
+use data_types::job::{Operation, OperationStatus};
 use generated_types::google::FieldViolation;
 use influxdb_iox_client::{
     connection::Builder,
@@ -25,6 +26,9 @@ pub enum Error {
 
     #[error("Output serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
+
+    #[error("Operation {} did not complete successfully: {:?}", id, status)]
+    OperationFailed { id: usize, status: OperationStatus },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -39,7 +43,15 @@ pub struct Config {
 #[derive(Debug, StructOpt)]
 enum Command {
     /// Get list of running operations
-    List,
+    List {
+        /// Only list operations of this database
+        #[structopt(long)]
+        db: Option<String>,
+
+        /// Only list operations with this status
+        #[structopt(long, possible_values = &Status::variants(), case_insensitive = true)]
+        status: Option<Status>,
+    },
 
     /// Get a specific operation
     Get {
@@ -55,6 +67,11 @@ enum Command {
         /// Maximum number of nanoseconds to wait before returning current
         /// status
         nanos: Option<u64>,
+
+        /// Exit with an error unless the operation completed, i.e. if it is
+        /// still running after the timeout, was cancelled or errored
+        #[structopt(long)]
+        check: bool,
     },
 
     /// Cancel a specific operation
@@ -67,11 +84,11 @@ enum Command {
     Test { nanos: Vec<u64> },
 }
 
-pub async fn command(url: String, config: Config) -> Result<()> {
-    let connection = Builder::default().build(url).await?;
+pub async fn command(connection_builder: Builder, url: String, config: Config) -> Result<()> {
+    let connection = connection_builder.build(url).await?;
 
     match config.command {
-        Command::List => {
+        Command::List { db, status } => {
             let result: Result<Vec<Operation>, _> = Client::new(connection)
                 .list_operations()
                 .await?
@@ -79,7 +96,17 @@ pub async fn command(url: String, config: Config) -> Result<()> {
                 .map(|c| c.operation())
                 .map(TryInto::try_into)
                 .collect();
-            let operations = result?;
+            let operations: Vec<_> = result?
+                .into_iter()
+                .filter(|operation| match &db {
+                    Some(db) => operation.job.as_ref().and_then(|job| job.db_name()) == Some(db),
+                    None => true,
+                })
+                .filter(|operation| match status {
+                    Some(status) => operation.status == status.into(),
+                    None => true,
+                })
+                .collect();
             serde_json::to_writer_pretty(std::io::stdout(), &operations)?;
         }
         Command::Get { id } => {
@@ -89,13 +116,20 @@ pub async fn command(url: String, config: Config) -> Result<()> {
                 .try_into()?;
             serde_json::to_writer_pretty(std::io::stdout(), &operation)?;
         }
-        Command::Wait { id, nanos } => {
+        Command::Wait { id, nanos, check } => {
             let timeout = nanos.map(std::time::Duration::from_nanos);
             let operation: Operation = Client::new(connection)
                 .wait_operation(id, timeout)
                 .await?
                 .try_into()?;
             serde_json::to_writer_pretty(std::io::stdout(), &operation)?;
+
+            if check && operation.status != OperationStatus::Complete {
+                return Err(Error::OperationFailed {
+                    id,
+                    status: operation.status,
+                });
+            }
         }
         Command::Cancel { id } => {
             Client::new(connection).cancel_operation(id).await?;
@@ -112,3 +146,25 @@ pub async fn command(url: String, config: Config) -> Result<()> {
 
     Ok(())
 }
+
+arg_enum! {
+    /// Operation status filter of the list command
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    enum Status {
+        Running,
+        Complete,
+        Cancelled,
+        Errored,
+    }
+}
+
+impl From<Status> for OperationStatus {
+    fn from(status: Status) -> Self {
+        match status {
+            Status::Running => Self::Running,
+            Status::Complete => Self::Complete,
+            Status::Cancelled => Self::Cancelled,
+            Status::Errored => Self::Errored,
+        }
+    }
+}
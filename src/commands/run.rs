@@ -3,7 +3,7 @@
 use crate::commands::tracing;
 use crate::influxdb_ioxd::{self, serving_readiness::ServingReadinessState};
 use clap::arg_enum;
-use core::num::NonZeroU16;
+use core::num::{NonZeroU16, NonZeroUsize};
 use data_types::server_id::ServerId;
 use std::{net::SocketAddr, net::ToSocketAddrs, path::PathBuf};
 use structopt::StructOpt;
@@ -104,9 +104,8 @@ pub struct Config {
     ///
     /// json: machine-parseable
     ///
-    ///   {"timestamp":"Oct 24 13:00:00.875","level":"ERROR","fields":{"message":"failed to shave yak","yak":3,"error":"missing yak"},"target":"fmt_json::yak_shave","spans":[{"yaks":3,"name":"shaving_yaks"}]}
-    ///   {"timestamp":"Oct 24 13:00:00.875","level":"TRACE","fields":{"yaks_shaved":2},"target":"fmt_json::yak_shave","spans":[{"yaks":3,"name":"shaving_yaks"}]}
-    ///   {"timestamp":"Oct 24 13:00:00.875","level":"INFO","fields":{"message":"yak shaving completed","all_yaks_shaved":false},"target":"fmt_json"}
+    ///   {"chunk_id":3,"db_name":"mydb","level":"info","location":"server/src/db.rs:1065","message":"chunk marked MOVING, loading tables into read buffer","partition_key":"2021-05-10 13:00:00","span":"load_chunk_to_read_buffer","table_name":"cpu","target":"server::db","time":"2021-05-10T13:00:00.875324000Z","trace_id":"4bf92f3577b34da6a3ce929d0e0e4736"}
+    ///   {"level":"warn","location":"src/influxdb_ioxd.rs:122","message":"NO PERSISTENCE: using Memory for object storage","target":"influxdb_iox::influxdb_ioxd","time":"2021-05-10T13:00:01.104537000Z"}
     ///
     /// logfmt: human-readable and machine-parseable
     ///
@@ -254,6 +253,16 @@ pub struct Config {
     #[structopt(long = "--server-id", env = "INFLUXDB_IOX_ID")]
     pub server_id: Option<ServerId>,
 
+    /// Without `--server-id`, use the ID persisted in the object store by
+    /// the server that last used it.
+    ///
+    /// Only enable this if the object store is not shared with other
+    /// servers, which would otherwise take over the ID and databases of the
+    /// server initialized last. The ID is not loaded if the object store
+    /// holds the data of more than one server ID.
+    #[structopt(long = "--load-server-id", env = "INFLUXDB_IOX_LOAD_SERVER_ID")]
+    pub load_server_id: bool,
+
     /// The address on which IOx will serve HTTP API requests.
     #[structopt(
     long = "--api-bind",
@@ -272,10 +281,6 @@ pub struct Config {
     )]
     pub grpc_bind_address: SocketAddr,
 
-    /// The location InfluxDB IOx will use to store files locally.
-    #[structopt(long = "--data-dir", env = "INFLUXDB_IOX_DB_DIR")]
-    pub database_directory: Option<PathBuf>,
-
     /// The number of threads to use for the query worker pool.
     ///
     /// IOx uses `--num-threads` threads for handling API requests and
@@ -286,6 +291,160 @@ pub struct Config {
     #[structopt(long = "--num-worker-threads", env = "INFLUXDB_IOX_NUM_WORKER_THREADS")]
     pub num_worker_threads: Option<usize>,
 
+    /// Memory budget, in bytes, of all databases: the mutable buffer, the
+    /// read buffer and the results of running queries.
+    ///
+    /// While the budget is exceeded writes are delayed for up to
+    /// `--memory-backpressure-ms` and then rejected with a "resources
+    /// exhausted" error. Per-database limits are set with the
+    /// `buffer_size_hard` lifecycle rule.
+    ///
+    /// If not specified, only the per-database limits apply
+    #[structopt(long = "--max-memory", env = "INFLUXDB_IOX_MAX_MEMORY")]
+    pub max_memory: Option<NonZeroUsize>,
+
+    /// How long, in milliseconds, writes are delayed while the server is over
+    /// its `--max-memory` budget before they are rejected
+    #[structopt(
+        long = "--memory-backpressure-ms",
+        env = "INFLUXDB_IOX_MEMORY_BACKPRESSURE_MS",
+        default_value = "1000"
+    )]
+    pub memory_backpressure_ms: u64,
+
+    /// Memory limit, in bytes, of each query for buffering the data of
+    /// sorts and aggregations. Queries exceeding it fail with an error.
+    ///
+    /// If not specified, queries are not limited
+    #[structopt(long = "--query-memory-limit", env = "INFLUXDB_IOX_QUERY_MEMORY_LIMIT")]
+    pub query_memory_limit: Option<NonZeroUsize>,
+
+    /// How many queries of a single database may execute at the same time.
+    /// Further queries wait for their turn, up to `--max-queued-queries` of
+    /// them for up to `--query-queue-timeout-ms` each, so that a burst of
+    /// queries against one database cannot starve the others.
+    ///
+    /// If not specified, queries are not limited
+    #[structopt(
+        long = "--max-concurrent-queries",
+        env = "INFLUXDB_IOX_MAX_CONCURRENT_QUERIES"
+    )]
+    pub max_concurrent_queries: Option<NonZeroUsize>,
+
+    /// How many queries of a single database may wait for their turn to
+    /// execute when `--max-concurrent-queries` are running. Further queries
+    /// are rejected
+    #[structopt(
+        long = "--max-queued-queries",
+        env = "INFLUXDB_IOX_MAX_QUEUED_QUERIES",
+        default_value = "100"
+    )]
+    pub max_queued_queries: usize,
+
+    /// How long, in milliseconds, a query may wait for its turn to execute
+    /// before it fails
+    #[structopt(
+        long = "--query-queue-timeout-ms",
+        env = "INFLUXDB_IOX_QUERY_QUEUE_TIMEOUT_MS",
+        default_value = "30000"
+    )]
+    pub query_queue_timeout_ms: u64,
+
+    /// Memory, in bytes, each database may use for caching the results of
+    /// queries. Repeated identical queries, such as those of dashboards,
+    /// are answered from the cache until the chunks they read change.
+    ///
+    /// If not specified, query results are not cached
+    #[structopt(
+        long = "--query-result-cache-bytes",
+        env = "INFLUXDB_IOX_QUERY_RESULT_CACHE_BYTES"
+    )]
+    pub query_result_cache_bytes: Option<NonZeroUsize>,
+
+    /// The minimum number of rows of the record batches sent to Arrow Flight
+    /// clients. Query results are streamed to the client as they are
+    /// produced, and smaller batches are coalesced up to this size
+    #[structopt(
+        long = "--flight-batch-rows",
+        env = "INFLUXDB_IOX_FLIGHT_BATCH_ROWS",
+        default_value = "8192"
+    )]
+    pub flight_batch_rows: usize,
+
+    /// How long, in seconds, the data of a deleted database is kept before
+    /// it is purged from object storage. Until then the database can be
+    /// restored with `database restore <name>`
+    #[structopt(
+        long = "--purge-deleted-after-seconds",
+        env = "INFLUXDB_IOX_PURGE_DELETED_AFTER_SECONDS",
+        default_value = "604800"
+    )]
+    pub purge_deleted_after_seconds: u64,
+
+    #[structopt(flatten)]
+    pub object_store_config: ObjectStoreConfig,
+
+    /// When IOx nodes need to talk to remote peers they consult an internal remote address
+    /// mapping. This mapping is populated via API calls. If the mapping doesn't produce
+    /// a result, this config entry allows to generate a hostname from at template:
+    /// occurrences of the "{id}" substring will be replaced with the remote Server ID.
+    ///
+    /// Example: http://node-{id}.ioxmydomain.com:8082
+    #[structopt(long = "--remote-template", env = "INFLUXDB_IOX_REMOTE_TEMPLATE")]
+    pub remote_template: Option<String>,
+
+    /// After startup the IOx server can either accept serving data plane traffic right away
+    /// or require a SetServingReadiness call from the Management API to enable serving.
+    #[structopt(
+        long = "--initial-serving-readiness-state",
+        env = "INFLUXDB_IOX_INITIAL_SERVING_READINESS_STATE",
+        default_value = "serving"
+    )]
+    pub initial_serving_state: ServingReadinessState,
+
+    /// Require every request to present a token, in an `Authorization: Token
+    /// <token>` header.
+    ///
+    /// The admin token grants access to everything, including the management
+    /// API used to create tokens granting read or write access to individual
+    /// databases.
+    ///
+    /// If not specified, authorization is disabled
+    #[structopt(long = "--admin-token", env = "INFLUXDB_IOX_ADMIN_TOKEN")]
+    pub admin_token: Option<String>,
+
+    /// How long, in seconds, the server may take to shut down once it
+    /// received SIGTERM or SIGINT.
+    ///
+    /// Writes are rejected as soon as shutdown starts. Within the timeout,
+    /// in-flight requests and chunk moves and writes are completed (and the
+    /// chunks not yet in object store persisted, with
+    /// `--persist-on-shutdown`), after which the server exits regardless.
+    #[structopt(
+        long = "--drain-timeout-secs",
+        env = "INFLUXDB_IOX_DRAIN_TIMEOUT_SECS",
+        default_value = "30"
+    )]
+    pub drain_timeout_secs: u64,
+
+    /// Write the chunks that are not yet in object store to it when shutting
+    /// down, within `--drain-timeout-secs`. Without it, the data of these
+    /// chunks is lost unless it is replayed from a write buffer
+    #[structopt(
+        long = "--persist-on-shutdown",
+        env = "INFLUXDB_IOX_PERSIST_ON_SHUTDOWN"
+    )]
+    pub persist_on_shutdown: bool,
+}
+
+/// Configuration of the object store used to persist data, shared by
+/// commands that need to access the object store directly.
+#[derive(Debug, StructOpt)]
+pub struct ObjectStoreConfig {
+    /// The location InfluxDB IOx will use to store files locally.
+    #[structopt(long = "--data-dir", env = "INFLUXDB_IOX_DB_DIR")]
+    pub database_directory: Option<PathBuf>,
+
     #[structopt(
     long = "--object-store",
     env = "INFLUXDB_IOX_OBJECT_STORE",
@@ -407,24 +566,6 @@ Possible values (case insensitive):
     /// environments.
     #[structopt(long = "--azure-storage-access-key", env = "AZURE_STORAGE_ACCESS_KEY")]
     pub azure_storage_access_key: Option<String>,
-
-    /// When IOx nodes need to talk to remote peers they consult an internal remote address
-    /// mapping. This mapping is populated via API calls. If the mapping doesn't produce
-    /// a result, this config entry allows to generate a hostname from at template:
-    /// occurrences of the "{id}" substring will be replaced with the remote Server ID.
-    ///
-    /// Example: http://node-{id}.ioxmydomain.com:8082
-    #[structopt(long = "--remote-template", env = "INFLUXDB_IOX_REMOTE_TEMPLATE")]
-    pub remote_template: Option<String>,
-
-    /// After startup the IOx server can either accept serving data plane traffic right away
-    /// or require a SetServingReadiness call from the Management API to enable serving.
-    #[structopt(
-        long = "--initial-serving-readiness-state",
-        env = "INFLUXDB_IOX_INITIAL_SERVING_READINESS_STATE",
-        default_value = "serving"
-    )]
-    pub initial_serving_state: ServingReadinessState,
 }
 
 pub async fn command(config: Config) -> Result<()> {
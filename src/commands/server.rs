@@ -3,7 +3,7 @@
 
 use std::time::{Duration, Instant};
 
-use crate::commands::server_remote;
+use crate::commands::{server_remote, server_token};
 use structopt::StructOpt;
 use thiserror::Error;
 
@@ -12,6 +12,9 @@ pub enum Error {
     #[error("Remote: {0}")]
     RemoteError(#[from] server_remote::Error),
 
+    #[error("Token: {0}")]
+    TokenError(#[from] server_token::Error),
+
     #[error("Error getting server ID: {0}")]
     GetServerIdError(#[from] GetServerIdError),
 
@@ -49,6 +52,8 @@ enum Command {
     WaitServerInitialized(WaitSeverInitialized),
 
     Remote(crate::commands::server_remote::Config),
+
+    Token(crate::commands::server_token::Config),
 }
 
 /// Set server ID
@@ -68,8 +73,8 @@ struct WaitSeverInitialized {
 
 use influxdb_iox_client::{connection::Builder, management::*};
 
-pub async fn command(url: String, config: Config) -> Result<()> {
-    let connection = Builder::default().build(&url).await?;
+pub async fn command(connection_builder: Builder, url: String, config: Config) -> Result<()> {
+    let connection = connection_builder.clone().build(&url).await?;
     let mut client = Client::new(connection);
 
     match config.command {
@@ -103,6 +108,9 @@ pub async fn command(url: String, config: Config) -> Result<()> {
                 tokio::time::sleep(Duration::from_millis(100)).await;
             }
         }
-        Command::Remote(config) => Ok(server_remote::command(url, config).await?),
+        Command::Remote(config) => {
+            Ok(server_remote::command(connection_builder, url, config).await?)
+        }
+        Command::Token(config) => Ok(server_token::command(connection_builder, url, config).await?),
     }
 }
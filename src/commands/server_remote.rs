@@ -14,6 +14,9 @@ pub enum Error {
 
     #[error("List remote error: {0}")]
     ListError(#[from] management::ListRemotesError),
+
+    #[error("Remote {0} not found")]
+    RemoteNotFound(u32),
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -32,8 +35,8 @@ pub enum Config {
     List,
 }
 
-pub async fn command(url: String, config: Config) -> Result<()> {
-    let connection = Builder::default().build(url).await?;
+pub async fn command(connection_builder: Builder, url: String, config: Config) -> Result<()> {
+    let connection = connection_builder.build(url).await?;
 
     match config {
         Config::Set {
@@ -42,10 +45,19 @@ pub async fn command(url: String, config: Config) -> Result<()> {
         } => {
             let mut client = management::Client::new(connection);
             client.update_remote(id, connection_string).await?;
+            println!("Ok");
         }
         Config::Remove { id } => {
             let mut client = management::Client::new(connection);
-            client.delete_remote(id).await?;
+            client.delete_remote(id).await.map_err(|e| match e {
+                management::UpdateRemoteError::ServerError(status)
+                    if status.code() == tonic::Code::NotFound =>
+                {
+                    Error::RemoteNotFound(id)
+                }
+                e => e.into(),
+            })?;
+            println!("Ok");
         }
         Config::List => {
             let mut client = management::Client::new(connection);
@@ -0,0 +1,124 @@
+use influxdb_iox_client::{
+    connection::Builder,
+    management::{self, generated_types::*},
+};
+use structopt::StructOpt;
+use thiserror::Error;
+
+use prettytable::{format, Cell, Row, Table};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Error connecting to IOx: {0}")]
+    ConnectionError(#[from] influxdb_iox_client::connection::Error),
+
+    #[error("Create token error: {0}")]
+    CreateError(#[from] management::CreateTokenError),
+
+    #[error("List tokens error: {0}")]
+    ListError(#[from] management::ListTokensError),
+
+    #[error("Revoke token error: {0}")]
+    RevokeError(#[from] management::RevokeTokenError),
+
+    #[error("Invalid permission '{0}', expected <database>:<read|write>")]
+    InvalidPermission(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "token",
+    about = "Manage the tokens granting access to the databases"
+)]
+pub enum Config {
+    /// Create a token, printing its secret. The secret cannot be retrieved
+    /// afterwards.
+    Create {
+        /// Description of the token, e.g. the team using it
+        description: String,
+
+        /// Access granted by the token, as <database>:<read|write>
+        #[structopt(required = true)]
+        permissions: Vec<String>,
+    },
+    /// Revoke a token.
+    Revoke { id: u64 },
+    /// List the tokens that have not been revoked.
+    List,
+}
+
+fn parse_permission(s: &str) -> Result<Permission> {
+    let invalid = || Error::InvalidPermission(s.to_string());
+    let idx = s.rfind(':').ok_or_else(invalid)?;
+    let (db_name, scope) = (&s[..idx], &s[idx + 1..]);
+    let scope = match scope {
+        "read" => Scope::Read,
+        "write" => Scope::Write,
+        _ => return Err(invalid()),
+    };
+    Ok(Permission {
+        db_name: db_name.to_string(),
+        scope: scope as _,
+    })
+}
+
+fn format_permission(permission: &Permission) -> String {
+    let scope = match permission.scope() {
+        Scope::Read => "read",
+        Scope::Write => "write",
+        Scope::Unspecified => "unspecified",
+    };
+    format!("{}:{}", permission.db_name, scope)
+}
+
+pub async fn command(connection_builder: Builder, url: String, config: Config) -> Result<()> {
+    let connection = connection_builder.build(url).await?;
+    let mut client = management::Client::new(connection);
+
+    match config {
+        Config::Create {
+            description,
+            permissions,
+        } => {
+            let permissions = permissions
+                .iter()
+                .map(|s| parse_permission(s))
+                .collect::<Result<_>>()?;
+            let (token, secret) = client.create_token(description, permissions).await?;
+            println!("Created token {}: {}", token.id, secret);
+        }
+        Config::Revoke { id } => {
+            client.revoke_token(id).await?;
+            println!("Ok");
+        }
+        Config::List => {
+            let tokens = client.list_tokens().await?;
+            if tokens.is_empty() {
+                println!("no tokens");
+            } else {
+                let mut table = Table::new();
+                table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+                table.set_titles(Row::new(vec![
+                    Cell::new("ID"),
+                    Cell::new("Description"),
+                    Cell::new("Permissions"),
+                ]));
+
+                for token in tokens {
+                    let permissions: Vec<_> =
+                        token.permissions.iter().map(format_permission).collect();
+                    table.add_row(Row::new(vec![
+                        Cell::new(&format!("{}", token.id)),
+                        Cell::new(&token.description),
+                        Cell::new(&permissions.join(", ")),
+                    ]));
+                }
+                print!("{}", table);
+            }
+        }
+    };
+
+    Ok(())
+}
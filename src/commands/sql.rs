@@ -12,17 +12,23 @@ mod repl_command;
 
 /// Start IOx interactive SQL REPL loop
 ///
-/// Supports command history and interactive editing. History is
+/// Supports command history and interactive, multi-line editing. History is
 /// stored in $HOME/.iox_sql_history.
 #[derive(Debug, StructOpt)]
 pub struct Config {
-    // TODO add an option to avoid saving history
+    /// Do not load or save command history
+    #[structopt(long)]
+    no_history: bool,
+
+    /// Database to query on startup. Can be changed using the
+    /// `USE DATABASE` command
+    #[structopt(long)]
+    database: Option<String>,
 
-    // TODO add an option to specify the default database (rather than having to set it via USE DATABASE)
     /// Format to use for output. Can be overridden using
     /// `SET FORMAT` command
     ///
-    /// Optional format ('pretty', 'json', or 'csv')
+    /// Optional format ('pretty', 'json', 'jsonl', 'influxv1' or 'csv')
     #[structopt(short, long, default_value = "pretty")]
     format: String,
 }
@@ -47,10 +53,10 @@ pub enum Error {
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 /// Fire up the interactive REPL
-pub async fn command(url: String, config: Config) -> Result<()> {
+pub async fn command(connection_builder: Builder, url: String, config: Config) -> Result<()> {
     debug!("Starting interactive SQL prompt with {:?}", config);
 
-    let connection = Builder::default()
+    let connection = connection_builder
         .build(&url)
         .await
         .context(Connecting { url: &url })?;
@@ -58,10 +64,14 @@ pub async fn command(url: String, config: Config) -> Result<()> {
     println!("Connected to IOx Server at {}", url);
     check_health(connection.clone()).await?;
 
-    let mut repl = repl::Repl::new(connection);
+    let mut repl = repl::Repl::new(connection, !config.no_history);
 
     repl.set_output_format(config.format).context(Repl)?;
 
+    if let Some(db_name) = config.database {
+        repl.use_database(db_name).await.context(Repl)?;
+    }
+
     repl.run().await.context(Repl)
 }
 
@@ -5,7 +5,14 @@ use arrow::{
     record_batch::RecordBatch,
 };
 use observability_deps::tracing::{debug, info};
-use rustyline::{error::ReadlineError, Editor};
+use rustyline::{
+    completion::Completer,
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::{ValidationContext, ValidationResult, Validator},
+    Editor, Helper,
+};
 use snafu::{ResultExt, Snafu};
 
 use super::repl_command::ReplCommand;
@@ -43,6 +50,9 @@ pub enum Error {
 
     #[snafu(display("Error running observer query: {}", source))]
     RunningObserverQuery { source: super::observer::Error },
+
+    #[snafu(display("Database {} not found", db_name))]
+    DatabaseNotFound { db_name: String },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -74,11 +84,41 @@ enum QueryEngine {
     Observer(super::observer::Observer),
 }
 
+/// Rustyline helper that keeps the editor in multi-line mode until the
+/// statement being typed is complete, so that a whole statement can be
+/// edited and is recalled from history as one entry
+struct ReplHelper;
+
+impl Helper for ReplHelper {}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {
+    fn validate(&self, ctx: &mut ValidationContext<'_>) -> rustyline::Result<ValidationResult> {
+        if is_complete_command(ctx.input()) {
+            Ok(ValidationResult::Valid(None))
+        } else {
+            Ok(ValidationResult::Incomplete)
+        }
+    }
+}
+
 /// Captures the state of the repl, gathers commands and executes them
 /// one by one
 pub struct Repl {
     /// Rustyline editor for interacting with user on command line
-    rl: Editor<()>,
+    rl: Editor<ReplHelper>,
+
+    /// Where command history is loaded from and saved to, if at all
+    history_file: Option<PathBuf>,
 
     /// Current prompt
     prompt: String,
@@ -97,6 +137,9 @@ pub struct Repl {
 
     /// Formatter to use to format query results
     output_format: QueryOutputFormat,
+
+    /// Print how long each query took
+    timing: bool,
 }
 
 impl Repl {
@@ -104,16 +147,20 @@ impl Repl {
         print!("{}", ReplCommand::help())
     }
 
-    /// Create a new Repl instance, connected to the specified URL
-    pub fn new(connection: Connection) -> Self {
+    /// Create a new Repl instance, connected to the specified URL. Command
+    /// history is persisted across sessions unless `history` is false
+    pub fn new(connection: Connection, history: bool) -> Self {
         let management_client = influxdb_iox_client::management::Client::new(connection.clone());
         let flight_client = influxdb_iox_client::flight::Client::new(connection.clone());
 
-        let mut rl = Editor::<()>::new();
-        let history_file = history_file();
+        let mut rl = Editor::<ReplHelper>::new();
+        rl.set_helper(Some(ReplHelper));
 
-        if let Err(e) = rl.load_history(&history_file) {
-            debug!(%e, "error loading history file");
+        let history_file = history.then(history_file);
+        if let Some(history_file) = &history_file {
+            if let Err(e) = rl.load_history(history_file) {
+                debug!(%e, "error loading history file");
+            }
         }
 
         let prompt = "> ".to_string();
@@ -122,12 +169,14 @@ impl Repl {
 
         Self {
             rl,
+            history_file,
             prompt,
             connection,
             management_client,
             flight_client,
             query_engine: None,
             output_format,
+            timing: true,
         }
     }
 
@@ -137,7 +186,16 @@ impl Repl {
     pub async fn run(&mut self) -> Result<()> {
         println!("Ready for commands. (Hint: try 'help;')");
         loop {
-            match self.next_command()? {
+            let command = match self.next_command() {
+                Ok(command) => command,
+                Err(e @ Error::ParsingCommand { .. }) => {
+                    println!("{}", e);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            match command {
                 ReplCommand::Help => {
                     self.print_help();
                 }
@@ -153,8 +211,23 @@ impl Repl {
                         .map_err(|e| println!("{}", e))
                         .ok();
                 }
+                ReplCommand::ShowTables => {
+                    self.run_sql("SHOW TABLES".to_string())
+                        .await
+                        .map_err(|e| println!("{}", e))
+                        .ok();
+                }
+                ReplCommand::DescribeTable { table_name } => {
+                    self.run_sql(format!("SHOW COLUMNS FROM {}", table_name))
+                        .await
+                        .map_err(|e| println!("{}", e))
+                        .ok();
+                }
                 ReplCommand::UseDatabase { db_name } => {
-                    self.use_database(db_name);
+                    self.use_database(db_name)
+                        .await
+                        .map_err(|e| println!("{}", e))
+                        .ok();
                 }
                 ReplCommand::SqlCommand { sql } => {
                     self.run_sql(sql).await.map_err(|e| println!("{}", e)).ok();
@@ -166,29 +239,37 @@ impl Repl {
                 ReplCommand::SetFormat { format } => {
                     self.set_output_format(format)?;
                 }
+                ReplCommand::Timing { enabled } => {
+                    self.timing = enabled.unwrap_or(!self.timing);
+                    println!("Timing is {}", if self.timing { "on" } else { "off" });
+                }
             }
         }
     }
 
-    /// Parss the next command;
+    /// Parses the next command, which may span several lines.
+    ///
+    /// When reading from a terminal the [`ReplHelper`] already returns
+    /// complete commands, otherwise lines are gathered here.
     fn next_command(&mut self) -> Result<ReplCommand> {
         let mut request = "".to_owned();
         loop {
             match self.rl.readline(&self.prompt) {
-                Ok(ref line) if is_exit_command(line) && request.is_empty() => {
-                    return Ok(ReplCommand::Exit);
-                }
-                Ok(ref line) if line.trim_end().ends_with(';') => {
+                Ok(line) => {
                     request.push_str(line.trim_end());
-                    self.rl.add_history_entry(request.clone());
-
-                    return request
-                        .try_into()
-                        .map_err(|message| Error::ParsingCommand { message });
-                }
-                Ok(ref line) => {
-                    request.push_str(line);
-                    request.push(' ');
+                    if request.trim().is_empty() {
+                        request.clear();
+                        continue;
+                    }
+
+                    if is_complete_command(&request) {
+                        self.rl.add_history_entry(request.clone());
+
+                        return request
+                            .try_into()
+                            .map_err(|message| Error::ParsingCommand { message });
+                    }
+                    request.push('\n');
                 }
                 Err(ReadlineError::Eof) => {
                     debug!("Received Ctrl-D");
@@ -245,11 +326,15 @@ impl Repl {
         let end = Instant::now();
         self.print_results(&batches)?;
 
-        println!(
-            "Returned {} in {:?}",
-            Self::row_summary(&batches),
-            end - start
-        );
+        if self.timing {
+            println!(
+                "Returned {} in {:?}",
+                Self::row_summary(&batches),
+                end - start
+            );
+        } else {
+            println!("Returned {}", Self::row_summary(&batches));
+        }
         Ok(())
     }
 
@@ -265,10 +350,17 @@ impl Repl {
         }
     }
 
-    fn use_database(&mut self, db_name: String) {
+    /// Switches to querying the remote database `db_name`, which must exist
+    pub async fn use_database(&mut self, db_name: String) -> Result<()> {
+        let state = self.remote_state().await?;
+        if !state.db_names.contains(&db_name) {
+            return DatabaseNotFound { db_name }.fail();
+        }
+
         info!(%db_name, "setting current database");
         println!("You are now in remote mode, querying database {}", db_name);
         self.set_query_engine(QueryEngine::Remote(db_name));
+        Ok(())
     }
 
     async fn use_observer(&mut self) -> Result<()> {
@@ -321,19 +413,26 @@ impl Repl {
 
 impl Drop for Repl {
     fn drop(&mut self) {
-        let history_file = history_file();
-
-        if let Err(e) = self.rl.save_history(&history_file) {
-            debug!(%e, "error saving history file");
+        if let Some(history_file) = &self.history_file {
+            if let Err(e) = self.rl.save_history(history_file) {
+                debug!(%e, "error saving history file");
+            }
         }
     }
 }
 
 fn is_exit_command(line: &str) -> bool {
-    let line = line.trim_end().to_lowercase();
+    let line = line.trim().to_lowercase();
     line == "quit" || line == "exit"
 }
 
+/// Returns true if `input` is a command that can be run: a statement
+/// terminated by `;`, a meta command or an exit command
+fn is_complete_command(input: &str) -> bool {
+    let input = input.trim();
+    input.is_empty() || input.ends_with(';') || input.starts_with('\\') || is_exit_command(input)
+}
+
 /// Return the location of the history file (defaults to $HOME/".iox_sql_history")
 fn history_file() -> PathBuf {
     let mut buf = match std::env::var("HOME") {
@@ -363,3 +462,24 @@ async fn scrape_query(
 
     Ok(batches)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complete_command() {
+        assert!(is_complete_command(""));
+        assert!(is_complete_command("select * from cpu;"));
+        assert!(is_complete_command("select *\nfrom cpu\n;  "));
+        assert!(is_complete_command("\\dt"));
+        assert!(is_complete_command(" \\d cpu"));
+        assert!(is_complete_command("exit"));
+        assert!(is_complete_command("QUIT "));
+
+        assert!(!is_complete_command("select * from cpu"));
+        assert!(!is_complete_command("select *\nfrom cpu"));
+        assert!(!is_complete_command("help"));
+        assert!(!is_complete_command("select exit"));
+    }
+}
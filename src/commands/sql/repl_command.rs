@@ -7,8 +7,11 @@ use observability_deps::tracing::{debug, warn};
 pub enum ReplCommand {
     Help,
     ShowDatabases,
+    ShowTables,
+    DescribeTable { table_name: String },
     Observer,
     SetFormat { format: String },
+    Timing { enabled: Option<bool> },
     UseDatabase { db_name: String },
     SqlCommand { sql: String },
     Exit,
@@ -25,6 +28,10 @@ impl TryInto<ReplCommand> for String {
             return Err("No command specified".to_string());
         }
 
+        if let Some(meta_command) = self.trim().strip_prefix('\\') {
+            return parse_meta_command(meta_command);
+        }
+
         // tokenized commands, normalized whitespace but original case
         let raw_commands = self
             .trim()
@@ -32,9 +39,7 @@ impl TryInto<ReplCommand> for String {
             .strip_suffix(";")
             .unwrap_or(&self)
             // tokenize on whitespace
-            .split(' ')
-            .map(|c| c.trim())
-            .filter(|c| !c.is_empty())
+            .split_whitespace()
             .collect::<Vec<_>>();
 
         // normalized commands (all lower case)
@@ -85,6 +90,41 @@ impl TryInto<ReplCommand> for String {
     }
 }
 
+/// Parses a psql style meta command such as `\\dt` (without the leading
+/// backslash). Meta commands do not need a trailing semicolon
+fn parse_meta_command(meta_command: &str) -> Result<ReplCommand, String> {
+    let tokens = meta_command
+        .strip_suffix(";")
+        .unwrap_or(meta_command)
+        .split_whitespace()
+        .collect::<Vec<_>>();
+
+    match tokens.as_slice() {
+        ["?"] => Ok(ReplCommand::Help),
+        ["q"] => Ok(ReplCommand::Exit),
+        ["l"] => Ok(ReplCommand::ShowDatabases),
+        ["c"] | ["connect"] => Err("name not specified. Usage: \\c <name>".to_string()),
+        ["c", db_name] | ["connect", db_name] => Ok(ReplCommand::UseDatabase {
+            db_name: db_name.to_string(),
+        }),
+        ["d"] | ["dt"] => Ok(ReplCommand::ShowTables),
+        ["d", table_name] => Ok(ReplCommand::DescribeTable {
+            table_name: table_name.to_string(),
+        }),
+        ["timing"] => Ok(ReplCommand::Timing { enabled: None }),
+        ["timing", "on"] => Ok(ReplCommand::Timing {
+            enabled: Some(true),
+        }),
+        ["timing", "off"] => Ok(ReplCommand::Timing {
+            enabled: Some(false),
+        }),
+        _ => Err(format!(
+            "invalid command \\{}. Try \\? for help",
+            meta_command.trim()
+        )),
+    }
+}
+
 impl ReplCommand {
     /// Information for each command
     pub fn help() -> &'static str {
@@ -102,6 +142,17 @@ OBSERVER: Locally query unified queryable views of remote system tables
 
 [EXIT | QUIT]: Quit this session and exit the program
 
+Statements may span multiple lines and are run once terminated by `;`.
+
+Meta commands (case sensitive, no `;` needed):
+\?                 Show this help
+\l                 List databases (same as SHOW DATABASES)
+\c <name>          Switch to database name (same as USE DATABASE)
+\d, \dt            List tables of the current database
+\d <table>         List the columns of table
+\timing [on|off]   Toggle printing how long each query took
+\q                 Quit this session
+
 # Examples: use remote database foo
 SHOW DATABASES;
 USE DATABASE foo;
@@ -246,6 +297,70 @@ mod tests {
         assert_eq!("set format Hmm".try_into(), expected);
     }
 
+    #[test]
+    fn multi_line() {
+        let expected = Ok(ReplCommand::ShowDatabases);
+        assert_eq!("show\ndatabases;".try_into(), expected);
+
+        let expected = Ok(ReplCommand::UseDatabase {
+            db_name: "foo".to_string(),
+        });
+        assert_eq!("use\n  database\n  foo\n;".try_into(), expected);
+
+        let expected = sql_cmd("select *\nfrom foo;");
+        assert_eq!("select *\nfrom foo;".try_into(), expected);
+    }
+
+    #[test]
+    fn meta_commands() {
+        assert_eq!("\\?".try_into(), Ok(ReplCommand::Help));
+        assert_eq!("\\q".try_into(), Ok(ReplCommand::Exit));
+        assert_eq!(" \\l ".try_into(), Ok(ReplCommand::ShowDatabases));
+        assert_eq!("\\d".try_into(), Ok(ReplCommand::ShowTables));
+        assert_eq!("\\dt;".try_into(), Ok(ReplCommand::ShowTables));
+
+        let expected = Ok(ReplCommand::DescribeTable {
+            table_name: "Foo".to_string(),
+        });
+        assert_eq!("\\d Foo".try_into(), expected);
+        assert_eq!("\\d  Foo;".try_into(), expected);
+
+        let expected = Ok(ReplCommand::UseDatabase {
+            db_name: "Foo".to_string(),
+        });
+        assert_eq!("\\c Foo".try_into(), expected);
+        assert_eq!("\\connect Foo".try_into(), expected);
+
+        let expected: Result<ReplCommand, String> =
+            Err("name not specified. Usage: \\c <name>".to_string());
+        assert_eq!("\\c".try_into(), expected);
+
+        assert_eq!(
+            "\\timing".try_into(),
+            Ok(ReplCommand::Timing { enabled: None })
+        );
+        assert_eq!(
+            "\\timing on".try_into(),
+            Ok(ReplCommand::Timing {
+                enabled: Some(true)
+            })
+        );
+        assert_eq!(
+            "\\timing off".try_into(),
+            Ok(ReplCommand::Timing {
+                enabled: Some(false)
+            })
+        );
+
+        let expected: Result<ReplCommand, String> =
+            Err("invalid command \\x foo. Try \\? for help".to_string());
+        assert_eq!("\\x foo".try_into(), expected);
+        // meta commands are case sensitive
+        let expected: Result<ReplCommand, String> =
+            Err("invalid command \\Q. Try \\? for help".to_string());
+        assert_eq!("\\Q".try_into(), expected);
+    }
+
     #[test]
     fn sql_command() {
         let expected = sql_cmd("SELECT * from foo");
@@ -85,12 +85,9 @@ pub fn init_logs_and_tracing(
                             None,
                             None,
                         ),
-                        LogFormat::Json => (
-                            None,
-                            None,
-                            Some(fmt::layer().json().with_writer(log_writer)),
-                            None,
-                        ),
+                        LogFormat::Json => {
+                            (None, None, Some(logfmt::JsonLayer::new(log_writer)), None)
+                        }
                         LogFormat::Logfmt => {
                             (None, None, None, Some(logfmt::LogFmtLayer::new(log_writer)))
                         }
@@ -175,6 +172,9 @@ fn construct_opentelemetry_tracer(config: &crate::commands::run::Config) -> Opti
                 config.traces_exporter_otlp_host.trim(),
                 config.traces_exporter_otlp_port
             );
+            opentelemetry::global::set_text_map_propagator(
+                opentelemetry::sdk::propagation::TraceContextPropagator::new(),
+            );
             Some(
                 opentelemetry_otlp::new_pipeline()
                     .with_trace_config(trace_config)
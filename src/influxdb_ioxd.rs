@@ -1,4 +1,4 @@
-use crate::commands::run::{Config, ObjectStore as ObjStoreOpt};
+use crate::commands::run::{Config, ObjectStore as ObjStoreOpt, ObjectStoreConfig};
 use futures::{future::FusedFuture, pin_mut, FutureExt};
 use hyper::server::conn::AddrIncoming;
 use object_store::{
@@ -6,6 +6,7 @@ use object_store::{
 };
 use observability_deps::tracing::{self, error, info, warn, Instrument};
 use panic_logging::SendPanicsToTracing;
+use query::exec::QueryConcurrencyLimits;
 use server::{
     ConnectionManagerImpl as ConnectionManager, RemoteTemplate, Server as AppServer,
     ServerConfig as AppServerConfig,
@@ -116,7 +117,7 @@ pub async fn main(config: Config) -> Result<()> {
     let f = SendPanicsToTracing::new();
     std::mem::forget(f);
 
-    match config.object_store {
+    match config.object_store_config.object_store {
         Some(ObjStoreOpt::Memory) | None => {
             warn!("NO PERSISTENCE: using Memory for object storage");
         }
@@ -125,7 +126,7 @@ pub async fn main(config: Config) -> Result<()> {
         }
     }
 
-    let object_store = ObjectStore::try_from(&config)?;
+    let object_store = ObjectStore::try_from(&config.object_store_config)?;
     let object_storage = Arc::new(object_store);
     let metric_registry = Arc::new(metrics::MetricRegistry::new());
     let remote_template = config.remote_template.map(RemoteTemplate::new);
@@ -141,6 +142,56 @@ pub async fn main(config: Config) -> Result<()> {
         server_config
     };
 
+    let server_config = if let Some(max_memory) = config.max_memory {
+        info!(max_memory = max_memory.get(), "Using memory budget");
+        server_config.with_max_memory(max_memory)
+    } else {
+        server_config
+    };
+    let server_config = if let Some(limit) = config.query_memory_limit {
+        info!(query_memory_limit = limit.get(), "Using query memory limit");
+        server_config.with_query_memory_limit(limit)
+    } else {
+        server_config
+    };
+    let server_config = if let Some(max_concurrent) = config.max_concurrent_queries {
+        info!(
+            max_concurrent_queries = max_concurrent.get(),
+            max_queued_queries = config.max_queued_queries,
+            query_queue_timeout_ms = config.query_queue_timeout_ms,
+            "Using query concurrency limits"
+        );
+        server_config.with_query_concurrency_limits(QueryConcurrencyLimits {
+            max_concurrent: max_concurrent.get(),
+            max_queued: config.max_queued_queries,
+            queue_timeout: Duration::from_millis(config.query_queue_timeout_ms),
+        })
+    } else {
+        server_config
+    };
+
+    let server_config = if let Some(size) = config.query_result_cache_bytes {
+        info!(
+            query_result_cache_bytes = size.get(),
+            "Using query result cache"
+        );
+        server_config.with_query_result_cache_size(size)
+    } else {
+        server_config
+    };
+
+    let server_config = server_config
+        .with_memory_backpressure(Duration::from_millis(config.memory_backpressure_ms))
+        .with_purge_deleted_after(Duration::from_secs(config.purge_deleted_after_seconds));
+
+    let server_config = if let Some(admin_token) = config.admin_token {
+        info!("Authorization enabled");
+        server_config.with_admin_token(admin_token)
+    } else {
+        info!("Authorization disabled: requests are not required to present a token");
+        server_config
+    };
+
     if config.grpc_bind_address == config.http_bind_address {
         error!(
             %config.grpc_bind_address,
@@ -153,12 +204,18 @@ pub async fn main(config: Config) -> Result<()> {
     let connection_manager = ConnectionManager {};
     let app_server = Arc::new(AppServer::new(connection_manager, server_config));
 
-    // if this ID isn't set the server won't be usable until this is set via an API
-    // call
+    // if this ID isn't set, nor loaded from the object store when requested,
+    // the server won't be usable until this is set via an API call
     if let Some(id) = config.server_id {
         app_server.set_id(id).expect("server id already set");
-    } else {
+    } else if !config.load_server_id {
         warn!("server ID not set. ID must be set via the INFLUXDB_IOX_ID config or API before writing or querying data.");
+    } else {
+        match app_server.load_server_id().await {
+            Ok(Some(id)) => info!(%id, "using server ID persisted in object store"),
+            Ok(None) => warn!("server ID not set. ID must be set via the INFLUXDB_IOX_ID config or API before writing or querying data."),
+            Err(e) => error!(%e, "cannot load server ID from object store. ID must be set via the INFLUXDB_IOX_ID config or API before writing or querying data."),
+        }
     }
 
     // An internal shutdown token for internal workers
@@ -178,6 +235,7 @@ pub async fn main(config: Config) -> Result<()> {
         Arc::clone(&app_server),
         frontend_shutdown.clone(),
         config.initial_serving_state.into(),
+        config.flight_batch_rows,
     )
     .fuse();
 
@@ -228,6 +286,13 @@ pub async fn main(config: Config) -> Result<()> {
     pin_mut!(grpc_server);
     pin_mut!(http_server);
 
+    // Armed once shutdown starts, bounds the time spent draining requests and
+    // flushing the databases
+    let drain_deadline = futures::future::Fuse::<tokio::time::Sleep>::terminated();
+    pin_mut!(drain_deadline);
+    let drain_timeout = Duration::from_secs(config.drain_timeout_secs);
+    let mut shutdown_deadline = None;
+
     // Return the first error encountered
     let mut res = Ok(());
 
@@ -235,14 +300,23 @@ pub async fn main(config: Config) -> Result<()> {
     // process, or by a background task exiting - most likely with an error
     //
     // Graceful shutdown should then proceed in the following order
-    // 1. Stop accepting new HTTP and gRPC requests and drain existing connections
-    // 2. Trigger shutdown of internal background workers loops
+    // 1. Stop accepting writes, new HTTP and gRPC requests and drain existing
+    //    connections
+    // 2. Stop the lifecycle policies of the databases, waiting for the chunk
+    //    moves and writes in flight, and optionally persist the buffered writes
+    // 3. Trigger shutdown of internal background workers loops
     //
     // This is important to ensure background tasks, such as polling the tracker
-    // registry, don't exit before HTTP and gRPC requests dependent on them
+    // registry, don't exit before HTTP and gRPC requests dependent on them.
+    //
+    // Steps 1 and 2 are bounded by the drain timeout
     while !grpc_server.is_terminated() && !http_server.is_terminated() {
         futures::select! {
             _ = signal => info!("Shutdown requested"),
+            _ = drain_deadline => {
+                warn!(?drain_timeout, "drain timeout expired, abandoning in-flight requests");
+                break;
+            },
             _ = server_worker => {
                 info!("server worker shutdown prematurely");
                 internal_shutdown.cancel();
@@ -263,10 +337,30 @@ pub async fn main(config: Config) -> Result<()> {
             },
         }
 
+        if shutdown_deadline.is_none() {
+            app_server.begin_shutdown();
+            let deadline = tokio::time::Instant::now() + drain_timeout;
+            drain_deadline.set(tokio::time::sleep_until(deadline).fuse());
+            shutdown_deadline = Some(deadline);
+        }
+
         frontend_shutdown.cancel()
     }
 
     info!("frontend shutdown completed");
+
+    let shutdown_deadline =
+        shutdown_deadline.unwrap_or_else(|| tokio::time::Instant::now() + drain_timeout);
+    let persist = config.persist_on_shutdown;
+    match tokio::time::timeout_at(shutdown_deadline, app_server.flush_databases(persist)).await {
+        Ok(Ok(())) => info!(persist, "databases flushed"),
+        Ok(Err(e)) => error!(%e, "error flushing databases"),
+        Err(_) => warn!(
+            ?drain_timeout,
+            "drain timeout expired while flushing databases"
+        ),
+    }
+
     internal_shutdown.cancel();
     server_worker.await;
 
@@ -275,10 +369,10 @@ pub async fn main(config: Config) -> Result<()> {
     res
 }
 
-impl TryFrom<&Config> for ObjectStore {
+impl TryFrom<&ObjectStoreConfig> for ObjectStore {
     type Error = Error;
 
-    fn try_from(config: &Config) -> Result<Self, Self::Error> {
+    fn try_from(config: &ObjectStoreConfig) -> Result<Self, Self::Error> {
         match config.object_store {
             Some(ObjStoreOpt::Memory) | None => {
                 Ok(Self::new_in_memory(object_store::memory::InMemory::new()))
@@ -430,7 +524,7 @@ mod tests {
     fn default_object_store_is_memory() {
         let config = Config::from_iter_safe(&["server"]).unwrap();
 
-        let object_store = ObjectStore::try_from(&config).unwrap();
+        let object_store = ObjectStore::try_from(&config.object_store_config).unwrap();
 
         assert!(matches!(
             object_store,
@@ -442,7 +536,7 @@ mod tests {
     fn explicitly_set_object_store_to_memory() {
         let config = Config::from_iter_safe(&["server", "--object-store", "memory"]).unwrap();
 
-        let object_store = ObjectStore::try_from(&config).unwrap();
+        let object_store = ObjectStore::try_from(&config.object_store_config).unwrap();
 
         assert!(matches!(
             object_store,
@@ -465,7 +559,7 @@ mod tests {
         ])
         .unwrap();
 
-        let object_store = ObjectStore::try_from(&config).unwrap();
+        let object_store = ObjectStore::try_from(&config.object_store_config).unwrap();
 
         assert!(matches!(
             object_store,
@@ -477,7 +571,9 @@ mod tests {
     fn s3_config_missing_params() {
         let config = Config::from_iter_safe(&["server", "--object-store", "s3"]).unwrap();
 
-        let err = ObjectStore::try_from(&config).unwrap_err().to_string();
+        let err = ObjectStore::try_from(&config.object_store_config)
+            .unwrap_err()
+            .to_string();
 
         assert_eq!(
             err,
@@ -498,7 +594,7 @@ mod tests {
         ])
         .unwrap();
 
-        let object_store = ObjectStore::try_from(&config).unwrap();
+        let object_store = ObjectStore::try_from(&config.object_store_config).unwrap();
 
         assert!(matches!(
             object_store,
@@ -510,7 +606,9 @@ mod tests {
     fn google_config_missing_params() {
         let config = Config::from_iter_safe(&["server", "--object-store", "google"]).unwrap();
 
-        let err = ObjectStore::try_from(&config).unwrap_err().to_string();
+        let err = ObjectStore::try_from(&config.object_store_config)
+            .unwrap_err()
+            .to_string();
 
         assert_eq!(
             err,
@@ -534,7 +632,7 @@ mod tests {
         ])
         .unwrap();
 
-        let object_store = ObjectStore::try_from(&config).unwrap();
+        let object_store = ObjectStore::try_from(&config.object_store_config).unwrap();
 
         assert!(matches!(
             object_store,
@@ -546,7 +644,9 @@ mod tests {
     fn azure_config_missing_params() {
         let config = Config::from_iter_safe(&["server", "--object-store", "azure"]).unwrap();
 
-        let err = ObjectStore::try_from(&config).unwrap_err().to_string();
+        let err = ObjectStore::try_from(&config.object_store_config)
+            .unwrap_err()
+            .to_string();
 
         assert_eq!(
             err,
@@ -568,7 +668,7 @@ mod tests {
         ])
         .unwrap();
 
-        let object_store = ObjectStore::try_from(&config).unwrap();
+        let object_store = ObjectStore::try_from(&config.object_store_config).unwrap();
 
         assert!(matches!(
             object_store,
@@ -580,7 +680,9 @@ mod tests {
     fn file_config_missing_params() {
         let config = Config::from_iter_safe(&["server", "--object-store", "file"]).unwrap();
 
-        let err = ObjectStore::try_from(&config).unwrap_err().to_string();
+        let err = ObjectStore::try_from(&config.object_store_config)
+            .unwrap_err()
+            .to_string();
 
         assert_eq!(
             err,
@@ -14,32 +14,36 @@
 use super::planner::Planner;
 use data_types::{
     names::{org_and_bucket_to_database, OrgBucketMappingError},
+    timestamp::Precision,
     DatabaseName,
 };
-use influxdb_iox_client::format::QueryOutputFormat;
-use influxdb_line_protocol::parse_lines;
+use influxdb_iox_client::format::{QueryOutputFormat, StreamingFormatter, TimestampFormat};
+use influxdb_line_protocol::{LineBatch, LineLocation, LineSplitter};
 use query::Database;
-use server::{ConnectionManager, Server as AppServer};
+use server::{auth::Scope, ConnectionManager, Server as AppServer};
 
 // External crates
 use bytes::{Bytes, BytesMut};
 use chrono::Utc;
-use futures::{self, StreamExt};
-use http::header::{CONTENT_ENCODING, CONTENT_TYPE};
+use datafusion::physical_plan::SendableRecordBatchStream;
+use futures::{self, Stream, StreamExt};
+use http::header::{ACCEPT, AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE};
 use hyper::{http::HeaderValue, Body, Method, Request, Response, StatusCode};
 use observability_deps::{
-    opentelemetry::KeyValue,
+    opentelemetry::{self, propagation::Extractor, KeyValue},
     tracing::{self, debug, error, info},
 };
 use routerify::{prelude::*, Middleware, RequestInfo, Router, RouterError, RouterService};
-use serde::Deserialize;
-use snafu::{OptionExt, ResultExt, Snafu};
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use hyper::server::conn::AddrIncoming;
 use pprof::protos::Message;
 use std::num::NonZeroI32;
 use std::{
     fmt::Debug,
+    io::Write as _,
     str::{self, FromStr},
     sync::Arc,
 };
@@ -114,9 +118,27 @@ pub enum ApplicationError {
     #[snafu(display("Body exceeds limit of {} bytes", max_body_size))]
     RequestSizeExceeded { max_body_size: usize },
 
+    #[snafu(display("Line exceeds limit of {} bytes", max_line_size))]
+    LineSizeExceeded { max_line_size: usize },
+
     #[snafu(display("Expected query string in request, but none was provided"))]
     ExpectedQueryString {},
 
+    #[snafu(display(
+        "Expected SQL in the 'q' query parameter or the request body, but none was provided"
+    ))]
+    ExpectedSql {},
+
+    #[snafu(display("Unsupported query language '{}'. Expected 'sql' or 'influxql'", lang))]
+    UnsupportedQueryLanguage { lang: String },
+
+    #[snafu(display(
+        "None of the media types in '{}' is supported. Expected one of 'text/plain', \
+         'text/csv', 'application/json' or 'application/x-ndjson'",
+        accept
+    ))]
+    NotAcceptable { accept: String },
+
     /// Error for when we could not parse the http query uri (e.g.
     /// `?foo=bar&bar=baz)`
     #[snafu(display("Invalid query string in HTTP URI '{}': {}", query_string, source))]
@@ -151,9 +173,18 @@ pub enum ApplicationError {
     #[snafu(display("Error reading request body as utf8: {}", source))]
     ReadingBodyAsUtf8 { source: std::str::Utf8Error },
 
-    #[snafu(display("Error parsing line protocol: {}", source))]
-    ParsingLineProtocol {
-        source: influxdb_line_protocol::Error,
+    #[snafu(display(
+        "partial write error ({} written): {}",
+        num_written,
+        rejected_lines
+            .iter()
+            .map(|line| line.reason.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    ))]
+    PartialWrite {
+        num_written: usize,
+        rejected_lines: Vec<RejectedLine>,
     },
 
     #[snafu(display("Error decompressing body as gzip: {}", source))]
@@ -182,6 +213,15 @@ pub enum ApplicationError {
     #[snafu(display("Database {} not found", name))]
     DatabaseNotFound { name: String },
 
+    #[snafu(display("Cannot write to database {}: {}", name, source))]
+    ResourcesExhausted { name: String, source: server::Error },
+
+    #[snafu(display("Cannot write to database {}: {}", name, source))]
+    QuotaExceeded { name: String, source: server::Error },
+
+    #[snafu(display("Cannot write to database {}: {}", name, source))]
+    UnsupportedWriteMode { name: String, source: server::Error },
+
     #[snafu(display("Database {} does not have a Write Buffer", name))]
     WriteBufferNotFound { name: String },
 
@@ -194,15 +234,9 @@ pub enum ApplicationError {
         source: influxdb_iox_client::format::Error,
     },
 
-    #[snafu(display(
-        "Error formatting results of SQL query '{}' using '{:?}': {}",
-        q,
-        format,
-        source
-    ))]
-    FormattingResult {
-        q: String,
-        format: QueryOutputFormat,
+    #[snafu(display("Invalid time format '{}': {}", time_format, source))]
+    ParsingTimeFormat {
+        time_format: String,
         source: influxdb_iox_client::format::Error,
     },
 
@@ -229,6 +263,38 @@ pub enum ApplicationError {
 
     #[snafu(display("Empty flamegraph"))]
     EmptyFlamegraph,
+
+    #[snafu(display("Unauthorized: {}", source))]
+    Unauthorized { source: server::auth::Error },
+
+    #[snafu(display("Server not ready: {}", source))]
+    NotReady { source: server::Error },
+}
+
+/// A point of a write request that was not written, reported in the body of
+/// a partial write error
+#[derive(Debug, Serialize)]
+pub struct RejectedLine {
+    /// 1-based number of the line in the request body, counting empty lines
+    /// and comments
+    line: usize,
+    /// Offset of the start of the line from the start of the request body,
+    /// in bytes
+    byte_offset: usize,
+    /// The start of the line, truncated if it is long
+    snippet: String,
+    reason: String,
+}
+
+impl RejectedLine {
+    fn new(location: &LineLocation<'_>, reason: String) -> Self {
+        Self {
+            line: location.line_number,
+            byte_offset: location.byte_offset,
+            snippet: location.snippet(),
+            reason,
+        }
+    }
 }
 
 impl ApplicationError {
@@ -240,8 +306,12 @@ impl ApplicationError {
             Self::Query { .. } => self.internal_error(),
             Self::QueryError { .. } => self.bad_request(),
             Self::BucketNotFound { .. } => self.not_found(),
-            Self::RequestSizeExceeded { .. } => self.bad_request(),
+            Self::RequestSizeExceeded { .. } => self.payload_too_large(),
+            Self::LineSizeExceeded { .. } => self.payload_too_large(),
             Self::ExpectedQueryString { .. } => self.bad_request(),
+            Self::ExpectedSql { .. } => self.bad_request(),
+            Self::UnsupportedQueryLanguage { .. } => self.bad_request(),
+            Self::NotAcceptable { .. } => self.not_acceptable(),
             Self::InvalidQueryString { .. } => self.bad_request(),
             Self::InvalidRequestBody { .. } => self.bad_request(),
             Self::InternalSerializationError { .. } => self.internal_error(),
@@ -249,7 +319,7 @@ impl ApplicationError {
             Self::ReadingHeaderAsUtf8 { .. } => self.bad_request(),
             Self::ReadingBody { .. } => self.bad_request(),
             Self::ReadingBodyAsUtf8 { .. } => self.bad_request(),
-            Self::ParsingLineProtocol { .. } => self.bad_request(),
+            Self::PartialWrite { .. } => self.bad_request(),
             Self::ReadingBodyAsGzip { .. } => self.bad_request(),
             Self::RouteNotFound { .. } => self.not_found(),
             Self::DatabaseError { .. } => self.internal_error(),
@@ -257,29 +327,77 @@ impl ApplicationError {
             Self::ErrorCreatingDatabase { .. } => self.bad_request(),
             Self::DatabaseNameError { .. } => self.bad_request(),
             Self::DatabaseNotFound { .. } => self.not_found(),
+            Self::ResourcesExhausted { .. } => self.too_many_requests(),
+            Self::QuotaExceeded { .. } => self.bad_request(),
+            Self::UnsupportedWriteMode { .. } => self.bad_request(),
             Self::WriteBufferNotFound { .. } => self.not_found(),
             Self::CreatingResponse { .. } => self.internal_error(),
-            Self::FormattingResult { .. } => self.internal_error(),
             Self::ParsingFormat { .. } => self.bad_request(),
+            Self::ParsingTimeFormat { .. } => self.bad_request(),
             Self::Planning { .. } => self.bad_request(),
             Self::NoSnapshot { .. } => self.not_modified(),
             Self::PProf { .. } => self.internal_error(),
             Self::Prost { .. } => self.internal_error(),
             Self::EmptyFlamegraph => self.no_content(),
+            Self::Unauthorized { source } if source.is_permission_denied() => self.forbidden(),
+            Self::Unauthorized { .. } => self.unauthorized(),
+            Self::NotReady { .. } => self.service_unavailable(),
         }
     }
 
     fn bad_request(&self) -> Response<Body> {
         Response::builder()
             .status(StatusCode::BAD_REQUEST)
-            .body(self.body())
+            .body(self.body("invalid"))
+            .unwrap()
+    }
+
+    fn unauthorized(&self) -> Response<Body> {
+        Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .body(self.body("unauthorized"))
+            .unwrap()
+    }
+
+    fn forbidden(&self) -> Response<Body> {
+        Response::builder()
+            .status(StatusCode::FORBIDDEN)
+            .body(self.body("forbidden"))
+            .unwrap()
+    }
+
+    fn payload_too_large(&self) -> Response<Body> {
+        Response::builder()
+            .status(StatusCode::PAYLOAD_TOO_LARGE)
+            .body(self.body("request too large"))
+            .unwrap()
+    }
+
+    fn not_acceptable(&self) -> Response<Body> {
+        Response::builder()
+            .status(StatusCode::NOT_ACCEPTABLE)
+            .body(self.body("not acceptable"))
+            .unwrap()
+    }
+
+    fn too_many_requests(&self) -> Response<Body> {
+        Response::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .body(self.body("too many requests"))
+            .unwrap()
+    }
+
+    fn service_unavailable(&self) -> Response<Body> {
+        Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .body(self.body("not ready"))
             .unwrap()
     }
 
     fn internal_error(&self) -> Response<Body> {
         Response::builder()
             .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .body(self.body())
+            .body(self.body("internal error"))
             .unwrap()
     }
 
@@ -293,22 +411,31 @@ impl ApplicationError {
     fn not_modified(&self) -> Response<Body> {
         Response::builder()
             .status(StatusCode::NOT_MODIFIED)
-            .body(self.body())
+            .body(self.body("not modified"))
             .unwrap()
     }
 
     fn no_content(&self) -> Response<Body> {
         Response::builder()
             .status(StatusCode::NO_CONTENT)
-            .body(self.body())
+            .body(self.body("empty value"))
             .unwrap()
     }
 
-    fn body(&self) -> Body {
-        let json =
-            serde_json::json!({"error": self.to_string(), "error_code": self.api_error_code()})
-                .to_string();
-        Body::from(json)
+    /// The error as JSON. `code` and `message` follow the InfluxDB 2.x error
+    /// format so that Telegraf and the v2 client libraries can report them
+    fn body(&self, code: &str) -> Body {
+        let message = self.to_string();
+        let mut json = serde_json::json!({
+            "code": code,
+            "message": message,
+            "error": message,
+            "error_code": self.api_error_code(),
+        });
+        if let Self::PartialWrite { rejected_lines, .. } = self {
+            json["rejected_lines"] = serde_json::json!(rejected_lines);
+        }
+        Body::from(json.to_string())
     }
 
     /// Map the error type into an API error code.
@@ -337,7 +464,16 @@ impl ApplicationError {
     }
 }
 
-const MAX_SIZE: usize = 10_485_760; // max write request size of 10MB
+const MAX_SIZE: usize = 10_485_760; // max in-memory request body or line size of 10MB
+
+/// The line protocol of a write request is parsed and written in batches of
+/// at least this many bytes while the rest of the body is received
+const WRITE_BATCH_SIZE: usize = 1_048_576;
+
+/// Gzip compressed write request bodies are decompressed in slices of this
+/// many bytes, so that a single chunk of the body cannot decompress into an
+/// arbitrary amount of memory
+const GZIP_SLICE_SIZE: usize = 4096;
 
 fn router<M>(server: Arc<AppServer<M>>) -> Router<Body, ApplicationError>
 where
@@ -347,8 +483,15 @@ where
     Router::builder()
         .data(server)
         .middleware(Middleware::pre(|mut req| async move {
-            // we don't need the authorization header and we don't want to accidentally log it.
-            req.headers_mut().remove("authorization");
+            // we don't want to accidentally log the authorization header, so
+            // only its token is kept, in the request extensions
+            let token = req.headers_mut().remove(AUTHORIZATION).and_then(|value| {
+                let value = value.to_str().ok()?;
+                server::auth::token_from_header(value).map(ToString::to_string)
+            });
+            if let Some(token) = token {
+                req.extensions_mut().insert(AuthToken(token));
+            }
             debug!(request = ?req,"Processing request");
             Ok(req)
         }))
@@ -358,8 +501,10 @@ where
         })) // this endpoint is for API backward compatibility with InfluxDB 2.x
         .post("/api/v2/write", write::<M>)
         .get("/health", health::<M>)
+        .get("/ready", ready::<M>)
         .get("/metrics", handle_metrics::<M>)
         .get("/iox/api/v1/databases/:name/query", query::<M>)
+        .post("/iox/api/v1/databases/:name/query", query::<M>)
         .get("/api/v1/partitions", list_partitions::<M>)
         .get("/debug/pprof", pprof_home::<M>)
         .get("/debug/pprof/profile", pprof_profile::<M>)
@@ -370,6 +515,59 @@ where
         .unwrap()
 }
 
+/// The token presented with a request
+#[derive(Clone)]
+struct AuthToken(String);
+
+/// Checks that the token presented with `req` grants `scope` access to the
+/// database `db_name`
+fn authorize<M: ConnectionManager>(
+    req: &Request<Body>,
+    server: &AppServer<M>,
+    db_name: &str,
+    scope: Scope,
+) -> Result<(), ApplicationError> {
+    let token = req.extensions().get::<AuthToken>().map(|t| t.0.as_str());
+    server
+        .auth
+        .authorize(token, db_name, scope)
+        .context(Unauthorized)
+}
+
+/// Checks that the token presented with `req` is the admin token, for the
+/// profiling endpoints
+fn authorize_admin<M: ConnectionManager>(
+    req: &Request<Body>,
+    server: &AppServer<M>,
+) -> Result<(), ApplicationError> {
+    let token = req.extensions().get::<AuthToken>().map(|t| t.0.as_str());
+    server.auth.authorize_admin(token).context(Unauthorized)
+}
+
+/// Reads the trace context propagated by a client from the request headers
+struct HeaderExtractor<'a>(&'a http::HeaderMap);
+
+impl<'a> Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+/// Makes the span of the current request a child of the trace propagated
+/// in the headers of `req`, if any, so that the spans of the request are
+/// exported as part of the client's trace and parent based samplers follow
+/// the client's sampling decision
+fn continue_trace(req: &Request<Body>) {
+    let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(req.headers()))
+    });
+    tracing::Span::current().set_parent(parent);
+}
+
 // The API-global error handler, handles ApplicationErrors originating from
 // individual routes and middlewares, along with errors from the router itself
 async fn error_handler(err: RouterError<ApplicationError>, req: RequestInfo) -> Response<Body> {
@@ -399,26 +597,38 @@ async fn error_handler(err: RouterError<ApplicationError>, req: RequestInfo) ->
 struct WriteInfo {
     org: String,
     bucket: String,
+    #[serde(default)]
+    precision: Precision,
+    /// Only write the lines if all of them can be written, see
+    /// [`Server::write_lines_all_or_nothing`](server::Server::write_lines_all_or_nothing)
+    #[serde(default)]
+    all_or_nothing: bool,
 }
 
 /// Parse the request's body into raw bytes, applying size limits and
 /// content encoding as needed.
-async fn parse_body(req: hyper::Request<Body>) -> Result<Bytes, ApplicationError> {
+/// Returns true if the body of `req` is gzip compressed
+fn is_gzip_encoded(req: &hyper::Request<Body>) -> Result<bool, ApplicationError> {
     // clippy says the const needs to be assigned to a local variable:
     // error: a `const` item with interior mutability should not be borrowed
     let header_name = CONTENT_ENCODING;
-    let ungzip = match req.headers().get(&header_name) {
-        None => false,
+    match req.headers().get(&header_name) {
+        None => Ok(false),
         Some(content_encoding) => {
             let content_encoding = content_encoding.to_str().context(ReadingHeaderAsUtf8 {
                 header_name: header_name.as_str(),
             })?;
             match content_encoding {
-                "gzip" => true,
-                _ => InvalidContentEncoding { content_encoding }.fail()?,
+                "identity" => Ok(false),
+                "gzip" => Ok(true),
+                _ => InvalidContentEncoding { content_encoding }.fail(),
             }
         }
-    };
+    }
+}
+
+async fn parse_body(req: hyper::Request<Body>) -> Result<Bytes, ApplicationError> {
+    let ungzip = is_gzip_encoded(&req)?;
 
     let mut payload = req.into_body();
 
@@ -458,6 +668,7 @@ async fn write<M>(req: Request<Body>) -> Result<Response<Body>, ApplicationError
 where
     M: ConnectionManager + Send + Sync + Debug + 'static,
 {
+    continue_trace(&req);
     let path = req.uri().path().to_string();
     let server = Arc::clone(&req.data::<Arc<AppServer<M>>>().expect("server state"));
 
@@ -475,81 +686,244 @@ where
 
     let db_name = org_and_bucket_to_database(&write_info.org, &write_info.bucket)
         .context(BucketMappingError)?;
+    authorize(&req, &server, &db_name, Scope::Write)?;
 
-    let body = parse_body(req).await?;
-
-    let body = str::from_utf8(&body).context(ReadingBodyAsUtf8)?;
+    let ungzip = is_gzip_encoded(&req)?;
 
     // The time, in nanoseconds since the epoch, to assign to any points that don't
     // contain a timestamp
     let default_time = Utc::now().timestamp_nanos();
 
-    let mut num_fields = 0;
-    let mut num_lines = 0;
-
-    let lines = parse_lines(body)
-        .inspect(|line| {
-            if let Ok(line) = line {
-                num_fields += line.field_set.len();
-                num_lines += 1;
-            }
-        })
-        .collect::<Result<Vec<_>, influxdb_line_protocol::Error>>()
-        .context(ParsingLineProtocol)?;
-
-    debug!(num_lines=lines.len(), %db_name, org=%write_info.org, bucket=%write_info.bucket, "inserting lines into database");
-
     let metric_kv = vec![
         KeyValue::new("org", write_info.org.to_string()),
         KeyValue::new("bucket", write_info.bucket.to_string()),
         KeyValue::new("path", path),
     ];
 
-    server
-        .write_lines(&db_name, &lines, default_time)
-        .await
-        .map_err(|e| {
-            let labels = &[
-                metrics::KeyValue::new("status", "error"),
-                metrics::KeyValue::new("db_name", db_name.to_string()),
-            ];
+    // The body is parsed and written in batches of complete lines as it is
+    // received, so that it does not need to fit into memory. If writing a
+    // batch fails, the batches before it remain written. All-or-nothing
+    // writes are written as a single batch once the whole body is received
+    let mut payload = req.into_body();
+    let mut decoder = ungzip.then(|| flate2::write::GzDecoder::new(Vec::new()));
+    let mut splitter = LineSplitter::new();
+    let mut progress = WriteProgress::default();
+    loop {
+        let (batches, last) = match payload.next().await {
+            Some(chunk) => {
+                let chunk = chunk.context(ReadingBody)?;
+                let mut batches = vec![];
+                match decoder.as_mut() {
+                    Some(decoder) => {
+                        for data in chunk.chunks(GZIP_SLICE_SIZE) {
+                            decoder.write_all(data).context(ReadingBodyAsGzip)?;
+                            splitter.push(decoder.get_ref());
+                            decoder.get_mut().clear();
+                            batches.extend(take_write_batch(
+                                &mut splitter,
+                                write_info.all_or_nothing,
+                            )?);
+                        }
+                    }
+                    None => {
+                        splitter.push(&chunk);
+                        batches.extend(take_write_batch(&mut splitter, write_info.all_or_nothing)?);
+                    }
+                }
+                (batches, false)
+            }
+            None => {
+                if let Some(decoder) = decoder.take() {
+                    let data = decoder.finish().context(ReadingBodyAsGzip)?;
+                    splitter.push(&data);
+                }
+                let splitter = std::mem::take(&mut splitter);
+                let batch = splitter.finish().context(ReadingBodyAsUtf8)?;
+                (batch.into_iter().collect(), true)
+            }
+        };
+
+        for batch in batches {
+            write_batch(
+                &server,
+                &db_name,
+                write_info.precision,
+                default_time,
+                &batch,
+                write_info.all_or_nothing,
+                &mut progress,
+            )
+            .await
+            .map_err(|e| {
+                obs.client_error_with_labels(&metric_kv); // user error
+                match e {
+                    server::Error::DatabaseNotFound { .. } => ApplicationError::DatabaseNotFound {
+                        name: db_name.to_string(),
+                    },
+                    server::Error::HardLimitReached { .. }
+                    | server::Error::ResourcesExhausted { .. } => {
+                        ApplicationError::ResourcesExhausted {
+                            name: db_name.to_string(),
+                            source: e,
+                        }
+                    }
+                    server::Error::QuotaExceeded { .. } => ApplicationError::QuotaExceeded {
+                        name: db_name.to_string(),
+                        source: e,
+                    },
+                    server::Error::AllOrNothingRouted { .. } => {
+                        ApplicationError::UnsupportedWriteMode {
+                            name: db_name.to_string(),
+                            source: e,
+                        }
+                    }
+                    server::Error::ShuttingDown => ApplicationError::NotReady { source: e },
+                    _ => ApplicationError::WritingPoints {
+                        org: write_info.org.clone(),
+                        bucket_name: write_info.bucket.clone(),
+                        source: Box::new(e),
+                    },
+                }
+            })?;
+        }
 
-            server
-                .metrics
-                .ingest_lines_total
-                .add_with_labels(num_lines as u64, labels);
+        if last {
+            break;
+        }
+    }
 
-            server
-                .metrics
-                .ingest_fields_total
-                .add_with_labels(num_fields as u64, labels);
-
-            server.metrics.ingest_points_bytes_total.add_with_labels(
-                body.len() as u64,
-                &[
-                    metrics::KeyValue::new("status", "error"),
-                    metrics::KeyValue::new("db_name", db_name.to_string()),
-                ],
-            );
-            debug!(?e, ?db_name, ?num_lines, "error writing lines");
-
-            obs.client_error_with_labels(&metric_kv); // user error
-            match e {
-                server::Error::DatabaseNotFound { .. } => ApplicationError::DatabaseNotFound {
-                    name: db_name.to_string(),
-                },
-                _ => ApplicationError::WritingPoints {
-                    org: write_info.org.clone(),
-                    bucket_name: write_info.bucket.clone(),
-                    source: Box::new(e),
-                },
+    let WriteProgress {
+        num_written,
+        mut rejected_lines,
+    } = progress;
+    rejected_lines.sort_by_key(|rejected| rejected.line);
+
+    if !rejected_lines.is_empty() {
+        obs.client_error_with_labels(&metric_kv); // user error
+        return PartialWrite {
+            num_written,
+            rejected_lines,
+        }
+        .fail();
+    }
+
+    obs.ok_with_labels(&metric_kv); // request completed successfully
+    Ok(Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .body(Body::empty())
+        .unwrap())
+}
+
+/// The outcome of the batches of a write request written so far
+#[derive(Debug, Default)]
+struct WriteProgress {
+    num_written: usize,
+    rejected_lines: Vec<RejectedLine>,
+}
+
+/// Takes the complete lines buffered by `splitter` once there are enough of
+/// them for a batch. All-or-nothing writes are never split into batches, so
+/// their lines are kept buffered up to the in-memory size limit
+fn take_write_batch(
+    splitter: &mut LineSplitter,
+    all_or_nothing: bool,
+) -> Result<Option<LineBatch>, ApplicationError> {
+    if all_or_nothing {
+        ensure!(
+            splitter.buffered() <= MAX_SIZE,
+            RequestSizeExceeded {
+                max_body_size: MAX_SIZE
+            }
+        );
+        return Ok(None);
+    }
+    if splitter.buffered() < WRITE_BATCH_SIZE {
+        return Ok(None);
+    }
+
+    let batch = splitter.take_complete().context(ReadingBodyAsUtf8)?;
+    // What remains is the start of a single line
+    if splitter.buffered() > MAX_SIZE {
+        return LineSizeExceeded {
+            max_line_size: MAX_SIZE,
+        }
+        .fail();
+    }
+    Ok(batch)
+}
+
+/// Writes the lines of `batch` that can be parsed into the database and adds
+/// the others to the rejected lines of `progress`, like InfluxDB does in a
+/// partial write error. With `all_or_nothing`, nothing is written if any line
+/// is rejected
+async fn write_batch<M>(
+    server: &AppServer<M>,
+    db_name: &str,
+    precision: Precision,
+    default_time: i64,
+    batch: &LineBatch,
+    all_or_nothing: bool,
+    progress: &mut WriteProgress,
+) -> Result<(), server::Error>
+where
+    M: ConnectionManager + Send + Sync + Debug + 'static,
+{
+    let mut num_fields = 0;
+
+    let mut lines = Vec::new();
+    let mut locations = Vec::new();
+    for (location, line) in batch.parse() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                progress.rejected_lines.push(RejectedLine::new(
+                    &location,
+                    format!("unable to parse {}: {}", location, e),
+                ));
+                continue;
             }
-        })?;
+        };
+
+        num_fields += line.field_set.len();
+        lines.push(line);
+        locations.push(location);
+    }
+
+    if all_or_nothing && !progress.rejected_lines.is_empty() {
+        return Ok(());
+    }
+
+    debug!(num_lines=lines.len(), %db_name, all_or_nothing, "inserting lines into database");
+
+    let result = match all_or_nothing {
+        true => {
+            server
+                .write_lines_all_or_nothing(db_name, &lines, default_time, precision)
+                .await
+        }
+        false => {
+            server
+                .write_lines_partial(db_name, &lines, default_time, precision)
+                .await
+        }
+    };
 
+    let status = match &result {
+        Ok(_) => "ok",
+        Err(e) => {
+            debug!(?e, ?db_name, num_lines = lines.len(), "error writing lines");
+            "error"
+        }
+    };
     let labels = &[
-        metrics::KeyValue::new("status", "ok"),
+        metrics::KeyValue::new("status", status),
         metrics::KeyValue::new("db_name", db_name.to_string()),
     ];
+    let num_lines = match &result {
+        Ok(rejected) if all_or_nothing && !rejected.is_empty() => 0,
+        Ok(rejected) => lines.len() - rejected.len(),
+        Err(_) => lines.len(),
+    };
 
     server
         .metrics
@@ -561,49 +935,133 @@ where
         .ingest_fields_total
         .add_with_labels(num_fields as u64, labels);
 
-    // line protocol bytes successfully written
+    // line protocol bytes written
     server
         .metrics
         .ingest_points_bytes_total
-        .add_with_labels(body.len() as u64, labels);
-
-    obs.ok_with_labels(&metric_kv); // request completed successfully
-    Ok(Response::builder()
-        .status(StatusCode::NO_CONTENT)
-        .body(Body::empty())
-        .unwrap())
+        .add_with_labels(batch.text().len() as u64, labels);
+
+    let rejected = result?;
+    progress.num_written += num_lines;
+    progress
+        .rejected_lines
+        .extend(rejected.into_iter().map(|(idx, e)| {
+            let location = &locations[idx];
+            RejectedLine::new(location, format!("unable to write {}: {}", location, e))
+        }));
+
+    Ok(())
 }
 
 #[derive(Deserialize, Debug, PartialEq)]
 /// Parsed URI Parameters of the request to the .../query endpoint
 struct QueryParams {
-    q: String,
-    #[serde(default = "default_format")]
-    format: String,
+    /// The SQL query. May instead be sent as the body of a POST request
+    q: Option<String>,
+    /// The output format. Takes precedence over the `Accept` header
+    format: Option<String>,
+    /// The language of the query, `sql` (the default) or `influxql`
+    lang: Option<String>,
+    /// How timestamps are rendered, as the `--time-format` of the CLI.
+    /// Defaults to the output format's native rendering
+    time_format: Option<String>,
 }
 
-fn default_format() -> String {
-    QueryOutputFormat::default().to_string()
+/// Picks the output format for the first media type of an `Accept` header
+/// that query results can be formatted as. Quality values are ignored
+fn format_from_accept(accept: &str) -> Option<QueryOutputFormat> {
+    accept.split(',').find_map(|media_range| {
+        let media_type = media_range.split(';').next().unwrap_or_default().trim();
+        match media_type.to_ascii_lowercase().as_str() {
+            "text/plain" => Some(QueryOutputFormat::Pretty),
+            "text/csv" => Some(QueryOutputFormat::Csv),
+            "application/json" | "application/*" => Some(QueryOutputFormat::Json),
+            "application/x-ndjson" | "application/jsonl" => Some(QueryOutputFormat::JsonLines),
+            "*/*" | "text/*" => Some(QueryOutputFormat::default()),
+            _ => None,
+        }
+    })
+}
+
+/// Formats the batches of `stream` as they are produced, so that results are
+/// sent without buffering them entirely in memory
+fn format_stream(
+    stream: SendableRecordBatchStream,
+    format: QueryOutputFormat,
+    time_format: Option<TimestampFormat>,
+) -> impl Stream<Item = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>> {
+    let mut formatter = StreamingFormatter::new(format, Vec::new());
+    if let Some(time_format) = time_format {
+        formatter = formatter.with_timestamp_format(time_format);
+    }
+
+    futures::stream::unfold(Some((stream, formatter)), |state| async move {
+        let (mut stream, mut formatter) = state?;
+
+        let (output, state): (Result<_, Box<dyn std::error::Error + Send + Sync>>, _) =
+            match stream.next().await {
+                Some(Ok(batch)) => match formatter.write(&batch) {
+                    Ok(()) => {
+                        let output = Bytes::from(std::mem::take(formatter.writer_mut()));
+                        (Ok(output), Some((stream, formatter)))
+                    }
+                    Err(e) => (Err(e.into()), None),
+                },
+                Some(Err(e)) => (Err(e.into()), None),
+                // The stream ends after an error or the trailing output
+                None => (
+                    formatter.finish().map(Bytes::from).map_err(Into::into),
+                    None,
+                ),
+            };
+
+        Some((output, state))
+    })
 }
 
 #[tracing::instrument(level = "debug")]
 async fn query<M: ConnectionManager + Send + Sync + Debug + 'static>(
     req: Request<Body>,
 ) -> Result<Response<Body>, ApplicationError> {
+    continue_trace(&req);
     let path = req.uri().path().to_string();
     let server = Arc::clone(&req.data::<Arc<AppServer<M>>>().expect("server state"));
 
     // TODO(edd): figure out best way of catching all errors in this observation.
     let obs = server.metrics.http_requests.observation(); // instrument request
 
-    let uri_query = req.uri().query().context(ExpectedQueryString {})?;
+    let uri_query = req.uri().query().unwrap_or_default();
+    if req.method() == Method::GET && uri_query.is_empty() {
+        return ExpectedQueryString {}.fail();
+    }
 
-    let QueryParams { q, format } =
-        serde_urlencoded::from_str(uri_query).context(InvalidQueryString {
-            query_string: uri_query,
-        })?;
+    let QueryParams {
+        q,
+        format,
+        lang,
+        time_format,
+    } = serde_urlencoded::from_str(uri_query).context(InvalidQueryString {
+        query_string: uri_query,
+    })?;
 
-    let format = QueryOutputFormat::from_str(&format).context(ParsingFormat { format })?;
+    // An explicit format wins, then content negotiation, then the default
+    let format = match format {
+        Some(format) => QueryOutputFormat::from_str(&format).context(ParsingFormat { format })?,
+        None => match req.headers().get(ACCEPT) {
+            Some(accept) => {
+                let accept = accept.to_str().context(ReadingHeaderAsUtf8 {
+                    header_name: ACCEPT.as_str(),
+                })?;
+                format_from_accept(accept).context(NotAcceptable { accept })?
+            }
+            None => QueryOutputFormat::default(),
+        },
+    };
+    let time_format = time_format
+        .map(|time_format| {
+            TimestampFormat::from_str(&time_format).context(ParsingTimeFormat { time_format })
+        })
+        .transpose()?;
 
     let db_name_str = req
         .param("name")
@@ -616,31 +1074,67 @@ async fn query<M: ConnectionManager + Send + Sync + Debug + 'static>(
     ];
 
     let db_name = DatabaseName::new(&db_name_str).context(DatabaseNameError)?;
-    debug!(uri = ?req.uri(), %q, ?format, %db_name, "running SQL query");
+    authorize(&req, &server, &db_name, Scope::Read)?;
+
+    let token = req.extensions().get::<AuthToken>().map(|t| t.0.clone());
+    let q = match q {
+        Some(q) => q,
+        None if req.method() == Method::POST => {
+            let body = parse_body(req).await?;
+            str::from_utf8(&body)
+                .context(ReadingBodyAsUtf8)?
+                .to_string()
+        }
+        None => return ExpectedQueryString {}.fail(),
+    };
+    if q.trim().is_empty() {
+        return ExpectedSql {}.fail();
+    }
+    debug!(%q, ?format, ?lang, %db_name, "running query");
 
     let db = server
         .db(&db_name)
         .context(DatabaseNotFound { name: &db_name_str })?;
 
-    let executor = db.executor();
-    let physical_plan = Planner::new(Arc::clone(&executor))
-        .sql(db, &q)
-        .await
-        .context(Planning)?;
+    let lang = match lang.as_deref() {
+        None | Some("sql") => "sql",
+        Some("influxql") => "influxql",
+        Some(lang) => return UnsupportedQueryLanguage { lang }.fail(),
+    };
 
-    // TODO: stream read results out rather than rendering the
-    // whole thing in mem
-    let batches = executor
-        .collect(physical_plan)
-        .await
-        .map_err(|e| Box::new(e) as _)
-        .context(Query { db_name })?;
+    // SQL queries may reference the tables of other databases as
+    // `<database>.<table>`
+    let others = match lang {
+        "sql" => server.referenced_dbs(&db_name, &q, token.as_deref()),
+        _ => Default::default(),
+    };
+    let other_dbs: Vec<_> = others.values().cloned().collect();
+    let cache_key = format!("{}\n{}\n{:?}", lang, q, others.keys().collect::<Vec<_>>());
 
-    let results = format
-        .format(&batches)
-        .context(FormattingResult { q, format })?;
+    // Errors that happen once the results are being sent can only abort the
+    // response
+    let executor = db.executor();
+    let stream = db
+        .query_with_cache(&cache_key, &other_dbs, || async {
+            let planner = Planner::new(Arc::clone(&executor)).with_other_databases(others);
+            let physical_plan = match lang {
+                "influxql" => planner.influxql(Arc::clone(&db), &q).await,
+                _ => planner.sql(Arc::clone(&db), &q).await,
+            }
+            .context(Planning)?;
+
+            executor
+                .new_context()
+                .execute(physical_plan)
+                .await
+                .map_err(|e| Box::new(e) as _)
+                .context(Query {
+                    db_name: &db_name_str,
+                })
+        })
+        .await?;
 
-    let body = Body::from(results.into_bytes());
+    let body = Body::wrap_stream(format_stream(stream, format, time_format));
 
     let response = Response::builder()
         .header(CONTENT_TYPE, format.content_type())
@@ -669,6 +1163,26 @@ async fn health<M: ConnectionManager + Send + Sync + Debug + 'static>(
     Ok(Response::new(Body::from(response_body.to_string())))
 }
 
+/// Unlike `/health`, which only checks that the process is up, only passes
+/// once the server can serve reads and writes
+#[tracing::instrument(level = "debug")]
+async fn ready<M: ConnectionManager + Send + Sync + Debug + 'static>(
+    req: Request<Body>,
+) -> Result<Response<Body>, ApplicationError> {
+    let server = Arc::clone(&req.data::<Arc<AppServer<M>>>().expect("server state"));
+    let path = req.uri().path().to_string();
+    let obs = server.metrics.http_requests.observation();
+    let metric_kv = [metrics::KeyValue::new("path", path)];
+
+    if let Err(e) = server.check_ready().await {
+        obs.client_error_with_labels(&metric_kv);
+        return Err(ApplicationError::NotReady { source: e });
+    }
+
+    obs.ok_with_labels(&metric_kv);
+    Ok(Response::new(Body::from("OK")))
+}
+
 #[tracing::instrument(level = "debug")]
 async fn handle_metrics<M: ConnectionManager + Send + Sync + Debug + 'static>(
     req: Request<Body>,
@@ -708,6 +1222,7 @@ async fn list_partitions<M: ConnectionManager + Send + Sync + Debug + 'static>(
 
     let db_name =
         org_and_bucket_to_database(&info.org, &info.bucket).context(BucketMappingError)?;
+    authorize(&req, &server, &db_name, Scope::Read)?;
 
     let metric_kv = vec![
         KeyValue::new("db_name", db_name.to_string()),
@@ -746,6 +1261,9 @@ struct SnapshotInfo {
 async fn pprof_home<M: ConnectionManager + Send + Sync + Debug + 'static>(
     req: Request<Body>,
 ) -> Result<Response<Body>, ApplicationError> {
+    let server = Arc::clone(&req.data::<Arc<AppServer<M>>>().expect("server state"));
+    authorize_admin(&req, &server)?;
+
     let default_host = HeaderValue::from_static("localhost");
     let host = req
         .headers()
@@ -802,6 +1320,9 @@ impl PProfArgs {
 async fn pprof_profile<M: ConnectionManager + Send + Sync + Debug + 'static>(
     req: Request<Body>,
 ) -> Result<Response<Body>, ApplicationError> {
+    let server = Arc::clone(&req.data::<Arc<AppServer<M>>>().expect("server state"));
+    authorize_admin(&req, &server)?;
+
     let query_string = req.uri().query().unwrap_or_default();
     let query: PProfArgs =
         serde_urlencoded::from_str(query_string).context(InvalidQueryString { query_string })?;
@@ -859,13 +1380,16 @@ mod tests {
     };
 
     use arrow::record_batch::RecordBatch;
-    use arrow_util::assert_batches_eq;
+    use arrow_util::{assert_batches_eq, assert_batches_sorted_eq};
     use reqwest::{Client, Response};
 
     use data_types::{database_rules::DatabaseRules, server_id::ServerId, DatabaseName};
     use object_store::{memory::InMemory, ObjectStore};
     use serde::de::DeserializeOwned;
-    use server::{db::Db, ConnectionManagerImpl, ServerConfig as AppServerConfig};
+    use server::{
+        auth::Permission, db::Db, ConnectionManagerImpl, ServerConfig as AppServerConfig,
+    };
+    use test_helpers::assert_contains;
 
     fn config() -> (metrics::TestMetricRegistry, AppServerConfig) {
         let registry = Arc::new(metrics::MetricRegistry::new());
@@ -894,6 +1418,29 @@ mod tests {
         check_response("health", response, StatusCode::OK, Some("OK")).await;
     }
 
+    #[tokio::test]
+    async fn test_ready() {
+        let (_, config) = config();
+        let app_server = Arc::new(AppServer::new(ConnectionManagerImpl {}, config));
+        let server_url = test_server(Arc::clone(&app_server));
+        let client = Client::new();
+
+        // The server is alive but not ready before its ID is set
+        let response = client.get(&format!("{}/health", server_url)).send().await;
+        check_response("health", response, StatusCode::OK, Some("OK")).await;
+        let response = client.get(&format!("{}/ready", server_url)).send().await;
+        check_response("ready", response, StatusCode::SERVICE_UNAVAILABLE, None).await;
+
+        // ... and until the databases are loaded
+        app_server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        let response = client.get(&format!("{}/ready", server_url)).send().await;
+        check_response("ready", response, StatusCode::SERVICE_UNAVAILABLE, None).await;
+
+        app_server.maybe_initialize_server().await.unwrap();
+        let response = client.get(&format!("{}/ready", server_url)).send().await;
+        check_response("ready", response, StatusCode::OK, Some("OK")).await;
+    }
+
     #[tokio::test]
     async fn test_write() {
         let (_, config) = config();
@@ -942,6 +1489,366 @@ mod tests {
         assert_batches_eq!(expected, &batches);
     }
 
+    #[tokio::test]
+    async fn test_write_precision() {
+        let (_, config) = config();
+        let app_server = Arc::new(AppServer::new(ConnectionManagerImpl {}, config));
+        app_server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        app_server.maybe_initialize_server().await.unwrap();
+        app_server
+            .create_database(DatabaseRules::new(
+                DatabaseName::new("MyOrg_MyBucket").unwrap(),
+            ))
+            .await
+            .unwrap();
+        let server_url = test_server(Arc::clone(&app_server));
+
+        let client = Client::new();
+
+        // Timestamps in seconds and milliseconds
+        for (precision, lp_data) in &[
+            (
+                "s",
+                "h2o_temperature,location=santa_monica surface_degrees=65.2 1617286224",
+            ),
+            (
+                "ms",
+                "h2o_temperature,location=boston surface_degrees=50.2 1617286224000",
+            ),
+        ] {
+            let response = client
+                .post(&format!(
+                    "{}/api/v2/write?bucket=MyBucket&org=MyOrg&precision={}",
+                    server_url, precision
+                ))
+                .body(*lp_data)
+                .send()
+                .await;
+
+            check_response("write", response, StatusCode::NO_CONTENT, Some("")).await;
+        }
+
+        let test_db = app_server
+            .db(&DatabaseName::new("MyOrg_MyBucket").unwrap())
+            .expect("Database exists");
+
+        let batches = run_query(test_db, "select * from h2o_temperature").await;
+        let expected = vec![
+            "+--------------+-----------------+---------------------+",
+            "| location     | surface_degrees | time                |",
+            "+--------------+-----------------+---------------------+",
+            "| santa_monica | 65.2            | 2021-04-01 14:10:24 |",
+            "| boston       | 50.2            | 2021-04-01 14:10:24 |",
+            "+--------------+-----------------+---------------------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+
+        // Timestamps that do not fit into nanoseconds are rejected
+        let response = client
+            .post(&format!(
+                "{}/api/v2/write?bucket=MyBucket&org=MyOrg&precision=s",
+                server_url
+            ))
+            .body("h2o_temperature,location=boston surface_degrees=50.2 9223372036854775")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_contains!(
+            body["message"].as_str().unwrap(),
+            "timestamp 9223372036854775 on line 1 is out of range for precision s"
+        );
+
+        // Unknown precisions are rejected
+        let response = client
+            .post(&format!(
+                "{}/api/v2/write?bucket=MyBucket&org=MyOrg&precision=h",
+                server_url
+            ))
+            .body("h2o_temperature,location=boston surface_degrees=50.2 1")
+            .send()
+            .await;
+        check_response("write", response, StatusCode::BAD_REQUEST, None).await;
+    }
+
+    #[tokio::test]
+    async fn test_write_partial() {
+        let (_, config) = config();
+        let app_server = Arc::new(AppServer::new(ConnectionManagerImpl {}, config));
+        app_server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        app_server.maybe_initialize_server().await.unwrap();
+        app_server
+            .create_database(DatabaseRules::new(
+                DatabaseName::new("MyOrg_MyBucket").unwrap(),
+            ))
+            .await
+            .unwrap();
+        let server_url = test_server(Arc::clone(&app_server));
+
+        let client = Client::new();
+
+        let lp_data = vec![
+            "h2o_temperature,location=santa_monica surface_degrees=65.2 1617286224000000000",
+            "arbitrary",
+            "h2o_temperature,location=boston surface_degrees=50.2 1617286224000000000",
+            "h2o_temperature,location=boston surface_degrees=\"warm\" 1617286225000000000",
+        ]
+        .join("\n");
+
+        let response = client
+            .post(&format!(
+                "{}/api/v2/write?bucket=MyBucket&org=MyOrg",
+                server_url
+            ))
+            .body(lp_data)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "invalid");
+        let message = body["message"].as_str().unwrap();
+        assert_contains!(
+            message,
+            "partial write error (2 written): unable to parse line 2 (byte offset 79): \
+             'arbitrary': A generic parsing error occurred: TakeWhile1\n\
+             unable to write line 4 (byte offset 162): "
+        );
+        assert_contains!(message, "Schema conflict");
+
+        let rejected_lines = body["rejected_lines"].as_array().unwrap();
+        assert_eq!(rejected_lines.len(), 2);
+        assert_eq!(rejected_lines[0]["line"], 2);
+        assert_eq!(rejected_lines[0]["byte_offset"], 79);
+        assert_eq!(rejected_lines[0]["snippet"], "arbitrary");
+        assert_eq!(rejected_lines[1]["line"], 4);
+        assert_eq!(rejected_lines[1]["byte_offset"], 162);
+        assert_eq!(
+            rejected_lines[1]["snippet"],
+            "h2o_temperature,location=boston surface_degrees=\"warm\" 161728622..."
+        );
+
+        // The valid points were written
+        let test_db = app_server
+            .db(&DatabaseName::new("MyOrg_MyBucket").unwrap())
+            .expect("Database exists");
+
+        let batches = run_query(test_db, "select location from h2o_temperature").await;
+        let expected = vec![
+            "+--------------+",
+            "| location     |",
+            "+--------------+",
+            "| boston       |",
+            "| santa_monica |",
+            "+--------------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn test_write_all_or_nothing() {
+        let (_, config) = config();
+        let app_server = Arc::new(AppServer::new(ConnectionManagerImpl {}, config));
+        app_server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        app_server.maybe_initialize_server().await.unwrap();
+        app_server
+            .create_database(DatabaseRules::new(
+                DatabaseName::new("MyOrg_MyBucket").unwrap(),
+            ))
+            .await
+            .unwrap();
+        let server_url = test_server(Arc::clone(&app_server));
+
+        let client = Client::new();
+        let url = format!(
+            "{}/api/v2/write?bucket=MyBucket&org=MyOrg&all_or_nothing=true",
+            server_url
+        );
+
+        let lp_data =
+            "h2o_temperature,location=santa_monica surface_degrees=65.2 1617286224000000000";
+        let response = client.post(&url).body(lp_data).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let lp_data = vec![
+            "h2o_temperature,location=boston surface_degrees=50.2 1617286224000000000",
+            "h2o_temperature,location=boston surface_degrees=\"warm\" 1617286225000000000",
+        ]
+        .join("\n");
+        let response = client.post(&url).body(lp_data).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_contains!(
+            body["message"].as_str().unwrap(),
+            "partial write error (0 written): unable to write line 2 (byte offset 73): "
+        );
+        let rejected_lines = body["rejected_lines"].as_array().unwrap();
+        assert_eq!(rejected_lines.len(), 1);
+        assert_eq!(rejected_lines[0]["line"], 2);
+
+        let lp_data = vec![
+            "arbitrary",
+            "h2o_temperature,location=boston surface_degrees=50.2 1617286224000000000",
+        ]
+        .join("\n");
+        let response = client.post(&url).body(lp_data).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = response.json().await.unwrap();
+        let rejected_lines = body["rejected_lines"].as_array().unwrap();
+        assert_eq!(rejected_lines.len(), 1);
+        assert_eq!(rejected_lines[0]["line"], 1);
+
+        // Only the lines of the successful write were written
+        let test_db = app_server
+            .db(&DatabaseName::new("MyOrg_MyBucket").unwrap())
+            .expect("Database exists");
+
+        let batches = run_query(test_db, "select location from h2o_temperature").await;
+        let expected = vec![
+            "+--------------+",
+            "| location     |",
+            "+--------------+",
+            "| santa_monica |",
+            "+--------------+",
+        ];
+        assert_batches_sorted_eq!(expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn test_authorization() {
+        let (_, config) = config();
+        let app_server = Arc::new(AppServer::new(
+            ConnectionManagerImpl {},
+            config.with_admin_token("admin"),
+        ));
+        app_server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        app_server.maybe_initialize_server().await.unwrap();
+        app_server
+            .create_database(DatabaseRules::new(
+                DatabaseName::new("MyOrg_MyBucket").unwrap(),
+            ))
+            .await
+            .unwrap();
+        let (_, reader) = app_server.auth.create_token(
+            "reader",
+            vec![Permission {
+                db_name: "MyOrg_MyBucket".to_string(),
+                scope: Scope::Read,
+            }],
+        );
+        let (_, writer) = app_server.auth.create_token(
+            "writer",
+            vec![Permission {
+                db_name: "MyOrg_MyBucket".to_string(),
+                scope: Scope::Write,
+            }],
+        );
+        let server_url = test_server(Arc::clone(&app_server));
+
+        let client = Client::new();
+        let write = |token: Option<&str>| {
+            let request = client
+                .post(&format!(
+                    "{}/api/v2/write?bucket=MyBucket&org=MyOrg",
+                    server_url
+                ))
+                .body("h2o_temperature,location=santa_monica surface_degrees=65.2 1617286224000000000");
+            let request = match token {
+                Some(token) => request.header("Authorization", format!("Token {}", token)),
+                None => request,
+            };
+            request.send()
+        };
+
+        let response = write(None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "unauthorized");
+
+        let response = write(Some("not a token")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = write(Some(&reader)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "forbidden");
+
+        let response = write(Some(&writer)).await;
+        check_response("write", response, StatusCode::NO_CONTENT, Some("")).await;
+
+        let response = client
+            .get(&format!(
+                "{}/iox/api/v1/databases/MyOrg_MyBucket/query?q={}",
+                server_url, "select%20location%20from%20h2o_temperature"
+            ))
+            .header("Authorization", format!("Bearer {}", reader))
+            .send()
+            .await;
+        check_response("query", response, StatusCode::OK, None).await;
+
+        // Only the admin token grants access to the profiler
+        let response = client
+            .get(&format!("{}/debug/pprof", server_url))
+            .header("Authorization", format!("Token {}", writer))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = client
+            .get(&format!("{}/debug/pprof", server_url))
+            .header("Authorization", "Token admin")
+            .send()
+            .await;
+        check_response("pprof", response, StatusCode::OK, None).await;
+
+        // The health and metrics endpoints are always available
+        let response = client.get(&format!("{}/health", server_url)).send().await;
+        check_response("health", response, StatusCode::OK, Some("OK")).await;
+        let response = client.get(&format!("{}/metrics", server_url)).send().await;
+        check_response("metrics", response, StatusCode::OK, None).await;
+    }
+
+    #[tokio::test]
+    async fn test_write_memory_limit() {
+        let (_, config) = config();
+        let app_server = Arc::new(AppServer::new(ConnectionManagerImpl {}, config));
+        app_server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        app_server.maybe_initialize_server().await.unwrap();
+        app_server
+            .create_database(DatabaseRules::new(
+                DatabaseName::new("MyOrg_MyBucket").unwrap(),
+            ))
+            .await
+            .unwrap();
+        let server_url = test_server(Arc::clone(&app_server));
+
+        let db = app_server
+            .db(&DatabaseName::new("MyOrg_MyBucket").unwrap())
+            .expect("Database exists");
+        db.rules.write().lifecycle_rules.buffer_size_hard =
+            Some(std::num::NonZeroUsize::new(10).unwrap());
+        let _reservation = db.query_memory().reserve(11);
+
+        let client = Client::new();
+        let response = client
+            .post(&format!(
+                "{}/api/v2/write?bucket=MyBucket&org=MyOrg",
+                server_url
+            ))
+            .body("h2o_temperature,location=boston surface_degrees=50.2 1")
+            .send()
+            .await
+            .unwrap();
+
+        // Clients such as Telegraf retry writes rejected with 429
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "too many requests");
+    }
+
     #[tokio::test]
     async fn test_write_metrics() {
         let (metrics_registry, config) = config();
@@ -1119,8 +2026,142 @@ mod tests {
         assert_eq!(get_content_type(&response), "text/csv");
 
         let res = "bottom_degrees,location,state,surface_degrees,time\n\
-                   50.4,santa_monica,CA,65.2,2021-04-01T14:10:24.000000000\n";
+                   50.4,santa_monica,CA,65.2,2021-04-01T14:10:24.000000000Z\n";
+        check_response("query", response, StatusCode::OK, Some(res)).await;
+    }
+
+    #[tokio::test]
+    async fn test_query_time_format() {
+        let (client, server_url) = setup_test_data().await;
+
+        let response = client
+            .get(&format!(
+                "{}/iox/api/v1/databases/MyOrg_MyBucket/query?q={}&format=csv&time_format=nanos",
+                server_url, "select%20*%20from%20h2o_temperature"
+            ))
+            .send()
+            .await;
+
+        let res = "bottom_degrees,location,state,surface_degrees,time\n\
+                   50.4,santa_monica,CA,65.2,1617286224000000000\n";
         check_response("query", response, StatusCode::OK, Some(res)).await;
+
+        let response = client
+            .get(&format!(
+                "{}/iox/api/v1/databases/MyOrg_MyBucket/query?q={}&time_format=unix",
+                server_url, "select%20*%20from%20h2o_temperature"
+            ))
+            .send()
+            .await;
+        assert_eq!(response.unwrap().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_query_influxql() {
+        let (client, server_url) = setup_test_data().await;
+
+        let response = client
+            .get(&format!(
+                "{}/iox/api/v1/databases/MyOrg_MyBucket/query?q={}&lang=influxql&format=csv",
+                server_url, "select%20surface_degrees%20from%20h2o_temperature"
+            ))
+            .send()
+            .await;
+
+        let res = "time,surface_degrees\n\
+                   2021-04-01T14:10:24.000000000Z,65.2\n";
+        check_response("query", response, StatusCode::OK, Some(res)).await;
+
+        let response = client
+            .get(&format!(
+                "{}/iox/api/v1/databases/MyOrg_MyBucket/query?q={}&lang=flux",
+                server_url, "select%20*%20from%20h2o_temperature"
+            ))
+            .send()
+            .await;
+
+        check_response("query", response, StatusCode::BAD_REQUEST, None).await;
+    }
+
+    #[tokio::test]
+    async fn test_query_content_negotiation() {
+        let (client, server_url) = setup_test_data().await;
+        let url = format!(
+            "{}/iox/api/v1/databases/MyOrg_MyBucket/query?q={}",
+            server_url, "select%20location%20from%20h2o_temperature"
+        );
+
+        let response = client
+            .get(&url)
+            .header(ACCEPT, "application/xml, text/csv;q=0.9, */*;q=0.1")
+            .send()
+            .await;
+        assert_eq!(get_content_type(&response), "text/csv");
+        check_response(
+            "query",
+            response,
+            StatusCode::OK,
+            Some("location\nsanta_monica\n"),
+        )
+        .await;
+
+        // An explicit format takes precedence
+        let response = client
+            .get(&format!("{}&format=jsonl", url))
+            .header(ACCEPT, "text/csv")
+            .send()
+            .await;
+        assert_eq!(get_content_type(&response), "application/x-ndjson");
+        check_response(
+            "query",
+            response,
+            StatusCode::OK,
+            Some("{\"location\":\"santa_monica\"}\n"),
+        )
+        .await;
+
+        let response = client
+            .get(&url)
+            .header(ACCEPT, "application/xml")
+            .send()
+            .await;
+        check_response("query", response, StatusCode::NOT_ACCEPTABLE, None).await;
+    }
+
+    #[tokio::test]
+    async fn test_query_post() {
+        let (client, server_url) = setup_test_data().await;
+        let url = format!("{}/iox/api/v1/databases/MyOrg_MyBucket/query", server_url);
+
+        let response = client
+            .post(&url)
+            .header(ACCEPT, "application/json")
+            .body("select location, surface_degrees from h2o_temperature")
+            .send()
+            .await;
+        assert_eq!(get_content_type(&response), "application/json");
+        check_response(
+            "query",
+            response,
+            StatusCode::OK,
+            Some(r#"[{"location":"santa_monica","surface_degrees":65.2}]"#),
+        )
+        .await;
+
+        // The SQL is required
+        let response = client.post(&url).send().await;
+        check_response("query", response, StatusCode::BAD_REQUEST, None).await;
+    }
+
+    #[test]
+    fn test_format_from_accept() {
+        assert_eq!(format_from_accept("text/csv"), Some(QueryOutputFormat::Csv));
+        assert_eq!(
+            format_from_accept("image/png, Application/JSON; charset=utf-8"),
+            Some(QueryOutputFormat::Json)
+        );
+        assert_eq!(format_from_accept("*/*"), Some(QueryOutputFormat::Pretty));
+        assert_eq!(format_from_accept("image/png"), None);
     }
 
     #[tokio::test]
@@ -1218,6 +2259,73 @@ mod tests {
         assert_batches_eq!(expected, &batches);
     }
 
+    #[tokio::test]
+    async fn test_gzip_write_batches() {
+        let (_, config) = config();
+        let app_server = Arc::new(AppServer::new(ConnectionManagerImpl {}, config));
+        app_server.set_id(ServerId::try_from(1).unwrap()).unwrap();
+        app_server.maybe_initialize_server().await.unwrap();
+        app_server
+            .create_database(DatabaseRules::new(
+                DatabaseName::new("MyOrg_MyBucket").unwrap(),
+            ))
+            .await
+            .unwrap();
+        let server_url = test_server(Arc::clone(&app_server));
+
+        // A body larger than a write batch, with an invalid line in a later
+        // batch
+        let mut lp_data: Vec<_> = (0..40_000)
+            .map(|i| {
+                format!(
+                    "h2o_temperature,location=boston surface_degrees={} {}",
+                    i, i
+                )
+            })
+            .collect();
+        lp_data[35_000] = "arbitrary".to_string();
+        let lp_data = lp_data.join("\n");
+        assert!(lp_data.len() > WRITE_BATCH_SIZE);
+        let byte_offset = lp_data.find("arbitrary").unwrap();
+
+        let client = Client::new();
+        let response = client
+            .post(&format!(
+                "{}/api/v2/write?bucket=MyBucket&org=MyOrg",
+                server_url
+            ))
+            .header(CONTENT_ENCODING, "gzip")
+            .body(gzip_str(&lp_data))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_contains!(
+            body["message"].as_str().unwrap(),
+            "partial write error (39999 written): unable to parse line 35001"
+        );
+        let rejected_lines = body["rejected_lines"].as_array().unwrap();
+        assert_eq!(rejected_lines.len(), 1);
+        assert_eq!(rejected_lines[0]["line"], 35_001);
+        assert_eq!(rejected_lines[0]["byte_offset"], byte_offset);
+
+        let test_db = app_server
+            .db(&DatabaseName::new("MyOrg_MyBucket").unwrap())
+            .expect("Database exists");
+
+        let batches = run_query(test_db, "select count(*) as n from h2o_temperature").await;
+        let expected = vec![
+            "+-------+",
+            "| n     |",
+            "+-------+",
+            "| 39999 |",
+            "+-------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+    }
+
     #[tokio::test]
     async fn write_to_invalid_database() {
         let (_, config) = config();
@@ -1,10 +1,14 @@
 //! Query planner wrapper for use in IOx services
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use datafusion::{catalog::catalog::CatalogProvider, physical_plan::ExecutionPlan};
 use query::{
     exec::Executor,
-    frontend::{influxrpc::InfluxRpcPlanner, sql::SqlQueryPlanner},
+    frontend::{
+        influxql::InfluxQlQueryPlanner,
+        influxrpc::InfluxRpcPlanner,
+        sql::{params::QueryParams, SqlQueryPlanner},
+    },
     group_by::{Aggregate, WindowDuration},
     plan::{fieldlist::FieldListPlan, seriesset::SeriesSetPlans, stringset::StringSetPlan},
     predicate::Predicate,
@@ -20,6 +24,12 @@ pub enum Error {
         source: query::frontend::sql::Error,
     },
 
+    #[snafu(display("Error planning InfluxQL query {}", source))]
+    InfluxQl {
+        query: String,
+        source: query::frontend::influxql::Error,
+    },
+
     #[snafu(display("Error planning InfluxRPC query {}", source))]
     InfluxRpc {
         source: query::frontend::influxrpc::Error,
@@ -40,23 +50,43 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 pub struct Planner {
     /// Executors (whose threadpool to use)
     exec: Arc<Executor>,
+    /// Databases whose tables SQL queries may reference as
+    /// `<database>.<table>`
+    other_databases: BTreeMap<String, Arc<dyn CatalogProvider>>,
 }
 
 impl Planner {
     /// Create a new planner that will plan queries using the threadpool of
     /// `exec`
     pub fn new(exec: Arc<Executor>) -> Self {
-        Self { exec }
+        Self {
+            exec,
+            other_databases: Default::default(),
+        }
+    }
+
+    /// Allow SQL queries to reference the tables of `databases`, by name,
+    /// as `<database>.<table>`
+    pub fn with_other_databases<D: CatalogProvider + 'static>(
+        mut self,
+        databases: impl IntoIterator<Item = (String, Arc<D>)>,
+    ) -> Self {
+        self.other_databases.extend(
+            databases
+                .into_iter()
+                .map(|(name, db)| (name, db as Arc<dyn CatalogProvider>)),
+        );
+        self
     }
 
     /// Plan a SQL query against the data in `database`, and return a
     /// DataFusion physical execution plan.
-    pub async fn sql<D: CatalogProvider + 'static>(
+    pub async fn sql<D: CatalogProvider + Database + 'static>(
         &self,
         database: Arc<D>,
         query: impl Into<String>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        let planner = SqlQueryPlanner::new();
+        let planner = SqlQueryPlanner::new().with_other_databases(self.other_databases.clone());
         let q_executor = Arc::clone(&self.exec);
         let query = query.into();
 
@@ -70,6 +100,50 @@ impl Planner {
             .context(InternalExecutionWhilePlanning)?
     }
 
+    /// Plan a SQL query against the data in `database` after binding the
+    /// values of `params` to its parameters, and return a DataFusion
+    /// physical execution plan.
+    pub async fn sql_with_params<D: CatalogProvider + Database + 'static>(
+        &self,
+        database: Arc<D>,
+        query: impl Into<String>,
+        params: QueryParams,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let planner = SqlQueryPlanner::new().with_other_databases(self.other_databases.clone());
+        let q_executor = Arc::clone(&self.exec);
+        let query = query.into();
+
+        self.exec
+            .run(async move {
+                planner
+                    .query_with_params(database, &query, &params, q_executor.as_ref())
+                    .context(Sql { query })
+            })
+            .await
+            .context(InternalExecutionWhilePlanning)?
+    }
+
+    /// Plan an InfluxQL query against the data in `database`, and return a
+    /// DataFusion physical execution plan.
+    pub async fn influxql<D: CatalogProvider + Database + 'static>(
+        &self,
+        database: Arc<D>,
+        query: impl Into<String>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let planner = InfluxQlQueryPlanner::new();
+        let q_executor = Arc::clone(&self.exec);
+        let query = query.into();
+
+        self.exec
+            .run(async move {
+                planner
+                    .query(database, &query, q_executor.as_ref())
+                    .context(InfluxQl { query })
+            })
+            .await
+            .context(InternalExecutionWhilePlanning)?
+    }
+
     /// Creates a plan as described on
     /// [`InfluxRpcPlanner::table_names`], on a separate threadpool
     pub async fn table_names<D>(
@@ -11,6 +11,7 @@ use crate::influxdb_ioxd::serving_readiness::{ServingReadiness, ServingReadiness
 use server::{ConnectionManager, Server};
 use tonic::{Interceptor, Status};
 
+mod auth;
 pub mod error;
 mod flight;
 mod management;
@@ -78,6 +79,7 @@ pub async fn serve<M>(
     server: Arc<Server<M>>,
     shutdown: CancellationToken,
     serving_readiness: ServingReadiness,
+    flight_batch_rows: usize,
 ) -> Result<()>
 where
     M: ConnectionManager + Send + Sync + Debug + 'static,
@@ -104,9 +106,10 @@ where
             storage::make_server(
                 Arc::clone(&server),
                 Arc::clone(&server.registry),
+                Arc::clone(&server.auth),
                 serving_gate.clone(),
             ),
-            flight::make_server(Arc::clone(&server), serving_gate.clone()),
+            flight::make_server(Arc::clone(&server), flight_batch_rows, serving_gate.clone()),
             write::make_server(Arc::clone(&server), serving_gate.clone()),
             management::make_server(Arc::clone(&server), serving_readiness.clone()),
             operations::make_server(Arc::clone(&server)),
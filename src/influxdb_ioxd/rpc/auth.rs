@@ -0,0 +1,58 @@
+//! Authorization of gRPC requests with the token presented in the
+//! `authorization` metadata
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use server::{auth::Scope, ConnectionManager, Server};
+use tonic::{Interceptor, Request, Status};
+
+use super::error::default_auth_error_handler;
+
+/// Returns the token presented with `request`, if any
+pub(crate) fn request_token<T>(request: &Request<T>) -> Option<&str> {
+    request
+        .metadata()
+        .get("authorization")?
+        .to_str()
+        .ok()
+        .and_then(server::auth::token_from_header)
+}
+
+/// Checks that the token presented with `request` grants `scope` access to
+/// the database `db_name`
+pub(crate) fn authorize<M, T>(
+    server: &Server<M>,
+    request: &Request<T>,
+    db_name: &str,
+    scope: Scope,
+) -> Result<(), Status>
+where
+    M: ConnectionManager,
+{
+    server
+        .auth
+        .authorize(request_token(request), db_name, scope)
+        .map_err(default_auth_error_handler)
+}
+
+/// Implements the gRPC interceptor that rejects requests that don't present
+/// the admin token, protecting the management and operations services
+#[derive(Debug)]
+pub struct AdminInterceptor<M: ConnectionManager>(pub Arc<Server<M>>);
+
+impl<M> From<AdminInterceptor<M>> for Interceptor
+where
+    M: ConnectionManager + Send + Sync + Debug + 'static,
+{
+    fn from(admin: AdminInterceptor<M>) -> Self {
+        let interceptor = move |req: Request<()>| {
+            admin
+                .0
+                .auth
+                .authorize_admin(request_token(&req))
+                .map_err(default_auth_error_handler)?;
+            Ok(req)
+        };
+        interceptor.into()
+    }
+}
@@ -17,6 +17,7 @@ pub fn default_server_error_handler(error: server::Error) -> tonic::Status {
         Error::DatabasesNotLoaded => tonic::Status::unavailable(
             "Server ID set but DBs not yet loaded. Server cannot accept reads/writes yet.",
         ),
+        Error::ShuttingDown => tonic::Status::unavailable("Server is shutting down"),
         Error::DatabaseNotFound { db_name } => NotFound {
             resource_type: "database".to_string(),
             resource_name: db_name,
@@ -38,6 +39,20 @@ pub fn default_server_error_handler(error: server::Error) -> tonic::Status {
             description: "hard buffer limit reached".to_string(),
         }
         .into(),
+        Error::ResourcesExhausted { used, limit } => QuotaFailure {
+            subject: "influxdata.com/iox/memory".to_string(),
+            description: format!(
+                "server memory use of {} bytes exceeds the limit of {} bytes",
+                used, limit
+            ),
+        }
+        .into(),
+        Error::QuotaExceeded { source } => QuotaFailure {
+            subject: "influxdata.com/iox/quota".to_string(),
+            description: source.to_string(),
+        }
+        .into(),
+        Error::SchemaConflict { source } => tonic::Status::invalid_argument(source.to_string()),
         Error::NoRemoteConfigured { node_group } => NotFound {
             resource_type: "remote".to_string(),
             resource_name: format!("{:?}", node_group),
@@ -45,6 +60,12 @@ pub fn default_server_error_handler(error: server::Error) -> tonic::Status {
         }
         .into(),
         Error::RemoteError { source } => tonic::Status::unavailable(source.to_string()),
+        Error::AllOrNothingRouted { .. } => PreconditionViolation {
+            category: "Routing rules".to_string(),
+            subject: "influxdata.com/iox".to_string(),
+            description: error.to_string(),
+        }
+        .into(),
         error => {
             error!(?error, "Unexpected error");
             InternalError {}.into()
@@ -99,9 +120,41 @@ pub fn default_db_error_handler(error: server::db::Error) -> tonic::Status {
         }
         .into(),
         Error::RollingOverPartition { source, .. } => default_catalog_error_handler(source),
+        Error::DroppingPartition { source, .. } => default_catalog_error_handler(source),
+        Error::DeletingRows { source, .. } => default_catalog_error_handler(source),
+        Error::DropMovingChunk {
+            partition_key,
+            table_name,
+            chunk_id,
+            action,
+        } => PreconditionViolation {
+            category: "chunk".to_string(),
+            subject: "influxdata.com/iox".to_string(),
+            description: format!(
+                "Cannot drop chunk {}:{}:{} which has an in-progress lifecycle action {}",
+                partition_key, table_name, chunk_id, action
+            ),
+        }
+        .into(),
         error => {
             error!(?error, "Unexpected error");
             InternalError {}.into()
         }
     }
 }
+
+/// map `server::auth::Error` to the appropriate tonic Status
+pub fn default_auth_error_handler(error: server::auth::Error) -> tonic::Status {
+    use server::auth::Error;
+
+    match error {
+        Error::TokenNotFound { id } => NotFound {
+            resource_type: "token".to_string(),
+            resource_name: id.to_string(),
+            ..Default::default()
+        }
+        .into(),
+        e if e.is_permission_denied() => tonic::Status::permission_denied(e.to_string()),
+        e => tonic::Status::unauthenticated(e.to_string()),
+    }
+}
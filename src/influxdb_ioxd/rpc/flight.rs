@@ -1,14 +1,14 @@
 //! Implements the native gRPC IOx query API using Arrow Flight
-use std::{pin::Pin, sync::Arc};
+use std::{collections::BTreeMap, pin::Pin, sync::Arc};
 
-use futures::Stream;
-use observability_deps::tracing::error;
+use futures::{Stream, StreamExt};
+use observability_deps::tracing::{error, info_span, Instrument};
 use serde::Deserialize;
 use snafu::{OptionExt, ResultExt, Snafu};
 use tonic::{Interceptor, Request, Response, Streaming};
 
 use arrow::{
-    array::{make_array, ArrayRef, MutableArrayData},
+    array::{make_array, Array, ArrayRef, MutableArrayData},
     datatypes::{DataType, Field, Schema, SchemaRef},
     error::ArrowError,
     record_batch::RecordBatch,
@@ -19,10 +19,16 @@ use arrow_flight::{
     HandshakeRequest, HandshakeResponse, PutResult, SchemaResult, Ticket,
 };
 use data_types::{DatabaseName, DatabaseNameError};
-use server::{ConnectionManager, Server};
+use datafusion::physical_plan::SendableRecordBatchStream;
+use query::frontend::sql::params::{ParamValue, QueryParams};
+use server::{auth::Scope, memory::QueryMemory, ConnectionManager, Server};
 use std::fmt::Debug;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracker::TrackedFutureExt;
 
 use super::super::planner::Planner;
+use super::auth::request_token;
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -56,13 +62,19 @@ pub enum Error {
     #[snafu(display("Invalid RecordBatch: {}", source))]
     InvalidRecordBatch { source: ArrowError },
 
-    #[snafu(display("Failed to hydrate dictionary: {}", source))]
+    #[snafu(display("Failed to merge dictionaries: {}", source))]
     DictionaryError { source: ArrowError },
 
     #[snafu(display("Error while planning query: {}", source))]
     Planning {
         source: super::super::planner::Error,
     },
+
+    #[snafu(display("Unauthorized: {}", source))]
+    Unauthorized { source: server::auth::Error },
+
+    #[snafu(display("Query against database {} was cancelled", database_name))]
+    Cancelled { database_name: String },
 }
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -89,6 +101,11 @@ impl Error {
             Self::InvalidRecordBatch { .. } => Status::internal(self.to_string()),
             Self::Planning { .. } => Status::invalid_argument(self.to_string()),
             Self::DictionaryError { .. } => Status::internal(self.to_string()),
+            Self::Unauthorized { source } if source.is_permission_denied() => {
+                Status::permission_denied(self.to_string())
+            }
+            Self::Unauthorized { .. } => Status::unauthenticated(self.to_string()),
+            Self::Cancelled { .. } => Status::cancelled(self.to_string()),
         }
     }
 }
@@ -101,22 +118,78 @@ type TonicStream<T> = Pin<Box<dyn Stream<Item = Result<T, tonic::Status>> + Send
 struct ReadInfo {
     database_name: String,
     sql_query: String,
+    /// The values bound to the parameters of `sql_query`, either a list of
+    /// the values of `$1`, `$2`, ... or an object with the values of named
+    /// parameters
+    #[serde(default)]
+    params: Option<ReadParams>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum ReadParams {
+    Positional(Vec<ReadParam>),
+    Named(BTreeMap<String, ReadParam>),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum ReadParam {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+impl From<ReadParams> for QueryParams {
+    fn from(params: ReadParams) -> Self {
+        match params {
+            ReadParams::Positional(values) => Self {
+                positional: values.into_iter().map(Into::into).collect(),
+                ..Default::default()
+            },
+            ReadParams::Named(values) => Self {
+                named: values
+                    .into_iter()
+                    .map(|(name, value)| (name, value.into()))
+                    .collect(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl From<ReadParam> for ParamValue {
+    fn from(param: ReadParam) -> Self {
+        match param {
+            ReadParam::Null => Self::Null,
+            ReadParam::Boolean(value) => Self::Boolean(value),
+            ReadParam::Integer(value) => Self::Integer(value),
+            ReadParam::Float(value) => Self::Float(value),
+            ReadParam::String(value) => Self::String(value),
+        }
+    }
 }
 
 /// Concrete implementation of the gRPC Arrow Flight Service API
 #[derive(Debug)]
 struct FlightService<M: ConnectionManager> {
     server: Arc<Server<M>>,
+    /// The minimum number of rows of the record batches sent to the client;
+    /// smaller batches produced by a query are coalesced up to this size
+    batch_rows: usize,
 }
 
 pub fn make_server<M>(
     server: Arc<Server<M>>,
+    batch_rows: usize,
     interceptor: impl Into<Interceptor>,
 ) -> FlightServer<impl Flight>
 where
     M: ConnectionManager + Send + Sync + Debug + 'static,
 {
-    FlightServer::with_interceptor(FlightService { server }, interceptor)
+    FlightServer::with_interceptor(FlightService { server, batch_rows }, interceptor)
 }
 
 #[tonic::async_trait]
@@ -139,12 +212,11 @@ where
         Err(tonic::Status::unimplemented("Not yet implemented"))
     }
 
-    // TODO: Stream results back directly by using `execute` instead of `collect`
-    // https://docs.rs/datafusion/3.0.0/datafusion/physical_plan/trait.ExecutionPlan.html#tymethod.execute
     async fn do_get(
         &self,
         request: Request<Ticket>,
     ) -> Result<Response<Self::DoGetStream>, tonic::Status> {
+        let token = request_token(&request).map(str::to_string);
         let ticket = request.into_inner();
         let json_str = String::from_utf8(ticket.ticket.to_vec()).context(InvalidTicket {
             ticket: ticket.ticket,
@@ -155,47 +227,101 @@ where
 
         let database = DatabaseName::new(&read_info.database_name).context(InvalidDatabaseName)?;
 
+        self.server
+            .auth
+            .authorize(token.as_deref(), &database, Scope::Read)
+            .context(Unauthorized)?;
+
         let db = self.server.db(&database).context(DatabaseNotFound {
             database_name: &read_info.database_name,
         })?;
 
         let executor = db.executor();
+        let query_memory = db.query_memory().clone();
+        let running_queries = db.running_queries().clone();
+
+        // the parent of the spans created while planning and running the query
+        let span = info_span!(
+            "flight_query",
+            db_name = %read_info.database_name,
+            query = %read_info.sql_query
+        );
 
-        let physical_plan = Planner::new(Arc::clone(&executor))
-            .sql(db, &read_info.sql_query)
-            .await
-            .context(Planning)?;
-
-        // execute the query
-        let results = executor
-            .new_context()
-            .collect(Arc::clone(&physical_plan))
-            .await
-            .map_err(|e| Box::new(e) as _)
-            .context(Query {
-                database_name: &read_info.database_name,
-            })?;
-
-        let options = arrow::ipc::writer::IpcWriteOptions::default();
-        let schema = Arc::new(optimize_schema(&physical_plan.schema()));
-        let schema_flight_data =
-            arrow_flight::utils::flight_data_from_arrow_schema(&schema, &options);
-
-        let mut flights = vec![schema_flight_data];
-
-        for batch in results {
-            let batch = optimize_record_batch(&batch, Arc::clone(&schema))?;
-
-            let (flight_dictionaries, flight_batch) =
-                arrow_flight::utils::flight_data_from_arrow_batch(&batch, &options);
-
-            flights.extend(flight_dictionaries);
-            flights.push(flight_batch);
-        }
-
-        let output = futures::stream::iter(flights.into_iter().map(Ok));
+        let params: QueryParams = read_info.params.map(Into::into).unwrap_or_default();
+        // the tables of other databases are referenced as `<database>.<table>`
+        let others = self
+            .server
+            .referenced_dbs(&database, &read_info.sql_query, token.as_deref());
+        let other_dbs: Vec<_> = others.values().cloned().collect();
+
+        // the same query text with different parameters, or reading from
+        // different databases, is a different query
+        let cache_key = format!(
+            "{}\n{:?}\n{:?}",
+            read_info.sql_query,
+            params,
+            others.keys().collect::<Vec<_>>()
+        );
+        let stream = db
+            .query_with_cache(&cache_key, &other_dbs, || async {
+                let physical_plan = Planner::new(Arc::clone(&executor))
+                    .with_other_databases(others)
+                    .sql_with_params(Arc::clone(&db), &read_info.sql_query, params)
+                    .instrument(span.clone())
+                    .await
+                    .context(Planning)?;
+
+                executor
+                    .new_context()
+                    .execute(physical_plan)
+                    .instrument(span.clone())
+                    .await
+                    .map_err(|e| Box::new(e) as _)
+                    .context(Query {
+                        database_name: &read_info.database_name,
+                    })
+            })
+            .await?;
+
+        // stream the results to the client as they are produced, which runs
+        // until the query completes, it is cancelled or the client
+        // disconnects (which closes the channel)
+        let (tx, rx) = mpsc::channel(2);
+        let (_, registration) = running_queries.register(&read_info.sql_query);
+        let schema = Arc::new(assign_dictionary_ids(&stream.schema()));
+        let batch_rows = self.batch_rows;
+        let database_name = read_info.database_name;
+
+        tokio::spawn(async move {
+            let flights = send_flights(
+                stream,
+                schema,
+                batch_rows,
+                query_memory,
+                database_name.clone(),
+                tx.clone(),
+            )
+            .instrument(span)
+            .track(registration);
+
+            let result = match executor.run(flights).await {
+                Ok(Ok(result)) => result,
+                Ok(Err(_)) => Cancelled { database_name }.fail(),
+                Err(e) => Err(Error::Query {
+                    database_name,
+                    source: Box::new(e),
+                }),
+            };
+
+            if let Err(e) = result {
+                // the client may already be gone
+                let _ = tx.send(Err(e.into())).await;
+            }
+        });
 
-        Ok(Response::new(Box::pin(output) as Self::DoGetStream))
+        Ok(Response::new(
+            Box::pin(ReceiverStream::new(rx)) as Self::DoGetStream
+        ))
     }
 
     async fn handshake(
@@ -248,6 +374,114 @@ where
     }
 }
 
+/// Sends the schema and then the results of a query to the client as
+/// `stream` produces them, coalescing the record batches until they have at
+/// least `batch_rows` rows.
+///
+/// Only the batch being sent counts against the memory budgets, so the
+/// memory used by a query does not grow with the size of its results.
+/// Returns early if the client disconnects.
+async fn send_flights(
+    mut stream: SendableRecordBatchStream,
+    schema: SchemaRef,
+    batch_rows: usize,
+    query_memory: QueryMemory,
+    database_name: String,
+    tx: mpsc::Sender<Result<FlightData, tonic::Status>>,
+) -> Result<()> {
+    let options = arrow::ipc::writer::IpcWriteOptions::default();
+    let schema_flight_data = arrow_flight::utils::flight_data_from_arrow_schema(&schema, &options);
+    if tx.send(Ok(schema_flight_data)).await.is_err() {
+        return Ok(());
+    }
+
+    let mut pending = vec![];
+    let mut pending_rows = 0;
+    loop {
+        let batch = stream
+            .next()
+            .await
+            .transpose()
+            .map_err(|e| Box::new(e) as _)
+            .context(Query {
+                database_name: &database_name,
+            })?;
+
+        let done = batch.is_none();
+        if let Some(batch) = batch {
+            pending_rows += batch.num_rows();
+            pending.push(optimize_record_batch(&batch, Arc::clone(&schema))?);
+        }
+
+        if !pending.is_empty() && (done || pending_rows >= batch_rows) {
+            let batch = coalesce_batches(&schema, &pending)?;
+            pending.clear();
+            pending_rows = 0;
+
+            let (flight_dictionaries, flight_batch) =
+                arrow_flight::utils::flight_data_from_arrow_batch(&batch, &options);
+
+            // The batch counts against the memory budgets until it has been sent
+            let size = flight_dictionaries
+                .iter()
+                .chain(std::iter::once(&flight_batch))
+                .map(|flight| flight.data_header.len() + flight.data_body.len())
+                .sum();
+            let _reservation = query_memory.reserve(size);
+
+            for flight in flight_dictionaries
+                .into_iter()
+                .chain(std::iter::once(flight_batch))
+            {
+                if tx.send(Ok(flight)).await.is_err() {
+                    // the client went away
+                    return Ok(());
+                }
+            }
+        }
+
+        if done {
+            return Ok(());
+        }
+    }
+}
+
+/// Concatenates `batches` into a single record batch
+fn coalesce_batches(schema: &SchemaRef, batches: &[RecordBatch]) -> Result<RecordBatch> {
+    if let [batch] = batches {
+        return Ok(batch.clone());
+    }
+
+    let columns = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| match field.data_type() {
+            // The dictionaries of the batches differ, so they are
+            // concatenated as their values and encoded again
+            DataType::Dictionary(_, value_type) => {
+                let arrays = batches
+                    .iter()
+                    .map(|batch| arrow::compute::cast(batch.column(idx), value_type))
+                    .collect::<Result<Vec<_>, _>>()
+                    .context(DictionaryError)?;
+                let arrays: Vec<&dyn Array> = arrays.iter().map(|array| array.as_ref()).collect();
+                let values = arrow::compute::concat(&arrays).context(InvalidRecordBatch)?;
+                arrow::compute::cast(&values, field.data_type()).context(DictionaryError)
+            }
+            _ => {
+                let arrays: Vec<&dyn Array> = batches
+                    .iter()
+                    .map(|batch| batch.column(idx).as_ref())
+                    .collect();
+                arrow::compute::concat(&arrays).context(InvalidRecordBatch)
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    RecordBatch::try_new(Arc::clone(schema), columns).context(InvalidRecordBatch)
+}
+
 /// Some batches are small slices of the underlying arrays.
 /// At this stage we only know the number of rows in the record batch
 /// and the sizes in bytes of the backing buffers of the column arrays.
@@ -269,6 +503,9 @@ where
 ///
 /// See rationale and discussions about future improvements on
 /// https://github.com/influxdata/influxdb_iox/issues/1133
+///
+/// Dictionary columns are never copied, their values are sent in full with
+/// every batch regardless.
 fn optimize_record_batch(batch: &RecordBatch, schema: SchemaRef) -> Result<RecordBatch, Error> {
     let max_buf_len = batch
         .columns()
@@ -277,21 +514,21 @@ fn optimize_record_batch(batch: &RecordBatch, schema: SchemaRef) -> Result<Recor
         .max()
         .unwrap_or_default();
 
-    let columns: Result<Vec<_>, _> = batch
+    let columns: Vec<_> = batch
         .columns()
         .iter()
         .map(|column| {
-            if matches!(column.data_type(), DataType::Dictionary(_, _)) {
-                hydrate_dictionary(column)
-            } else if max_buf_len > batch.num_rows() * 100 {
-                Ok(deep_clone_array(column))
+            if !matches!(column.data_type(), DataType::Dictionary(_, _))
+                && max_buf_len > batch.num_rows() * 100
+            {
+                deep_clone_array(column)
             } else {
-                Ok(Arc::clone(column))
+                Arc::clone(column)
             }
         })
         .collect();
 
-    RecordBatch::try_new(schema, columns?).context(InvalidRecordBatch)
+    RecordBatch::try_new(schema, columns).context(InvalidRecordBatch)
 }
 
 fn deep_clone_array(array: &ArrayRef) -> ArrayRef {
@@ -301,18 +538,30 @@ fn deep_clone_array(array: &ArrayRef) -> ArrayRef {
     make_array(mutable.freeze())
 }
 
-/// Convert dictionary types to underlying types
-/// See hydrate_dictionary for more information
-fn optimize_schema(schema: &Schema) -> Schema {
+/// Assigns each dictionary column of `schema` its own dictionary ID
+///
+/// An IPC response, streaming or otherwise, defines its schema up front,
+/// which maps the dictionary encoded columns to dictionary IDs. The
+/// dictionaries are then sent over the wire ahead of each record batch
+/// using them, and the client decodes the columns using the dictionary
+/// with the ID of the column. Columns must therefore not share IDs.
+fn assign_dictionary_ids(schema: &Schema) -> Schema {
+    let mut next_dict_id = 0;
     let fields = schema
         .fields()
         .iter()
         .map(|field| match field.data_type() {
-            DataType::Dictionary(_, value_type) => Field::new(
-                field.name(),
-                value_type.as_ref().clone(),
-                field.is_nullable(),
-            ),
+            DataType::Dictionary(_, _) => {
+                let dict_id = next_dict_id;
+                next_dict_id += 1;
+                Field::new_dict(
+                    field.name(),
+                    field.data_type().clone(),
+                    field.is_nullable(),
+                    dict_id,
+                    false,
+                )
+            }
             _ => field.clone(),
         })
         .collect();
@@ -320,27 +569,6 @@ fn optimize_schema(schema: &Schema) -> Schema {
     Schema::new(fields)
 }
 
-/// Hydrates a dictionary to its underlying type
-///
-/// An IPC response, streaming or otherwise, defines its schema up front
-/// which defines the mapping from dictionary IDs. It then sends these
-/// dictionaries over the wire.
-///
-/// This requires identifying the different dictionaries in use, assigning
-/// them IDs, and sending new dictionaries, delta or otherwise, when needed
-///
-/// This is tracked by #1318
-///
-/// For now we just hydrate the dictionaries to their underlying type
-fn hydrate_dictionary(array: &ArrayRef) -> Result<ArrayRef, Error> {
-    match array.data_type() {
-        DataType::Dictionary(_, value) => {
-            arrow::compute::cast(array, &value).context(DictionaryError)
-        }
-        _ => unreachable!("not a dictionary"),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -353,6 +581,41 @@ mod tests {
     use datafusion::physical_plan::limit::truncate_batch;
     use std::sync::Arc;
 
+    #[test]
+    fn test_read_info_params() {
+        let read_info: ReadInfo =
+            serde_json::from_str(r#"{"database_name":"db","sql_query":"select 1"}"#).unwrap();
+        assert!(read_info.params.is_none());
+
+        let read_info: ReadInfo = serde_json::from_str(
+            r#"{"database_name":"db","sql_query":"select $1","params":[null,true,1,1.5,"a"]}"#,
+        )
+        .unwrap();
+        let params: QueryParams = read_info.params.unwrap().into();
+        assert_eq!(
+            params.positional,
+            vec![
+                ParamValue::Null,
+                ParamValue::Boolean(true),
+                ParamValue::Integer(1),
+                ParamValue::Float(1.5),
+                ParamValue::String("a".to_string()),
+            ]
+        );
+        assert!(params.named.is_empty());
+
+        let read_info: ReadInfo = serde_json::from_str(
+            r#"{"database_name":"db","sql_query":"select $host","params":{"host":"a"}}"#,
+        )
+        .unwrap();
+        let params: QueryParams = read_info.params.unwrap().into();
+        assert!(params.positional.is_empty());
+        assert_eq!(
+            params.named.get("host"),
+            Some(&ParamValue::String("a".to_string()))
+        );
+    }
+
     #[test]
     fn test_deep_clone_array() {
         let mut builder = UInt32Array::builder(1000);
@@ -367,6 +630,30 @@ mod tests {
         assert!(sliced.data().get_array_memory_size() > deep_cloned.data().get_array_memory_size());
     }
 
+    #[test]
+    fn test_coalesce_batches() {
+        let batches: Vec<_> = vec![vec![1, 2], vec![3], vec![4, 5, 6]]
+            .into_iter()
+            .map(|values| {
+                let c1 = UInt32Array::from(values);
+                RecordBatch::try_from_iter(vec![("a", Arc::new(c1) as ArrayRef)])
+                    .expect("cannot create record batch")
+            })
+            .collect();
+        let schema = batches[0].schema();
+
+        let coalesced = coalesce_batches(&schema, &batches).unwrap();
+
+        assert_eq!(coalesced.schema(), schema);
+        assert_eq!(coalesced.num_rows(), 6);
+        let array = coalesced
+            .column(0)
+            .as_any()
+            .downcast_ref::<UInt32Array>()
+            .unwrap();
+        assert_eq!(array, &UInt32Array::from(vec![1, 2, 3, 4, 5, 6]));
+    }
+
     #[test]
     fn test_encode_flight_data() {
         let options = arrow::ipc::writer::IpcWriteOptions::default();
@@ -401,6 +688,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_coalesce_batches_dictionary() {
+        let batches: Vec<_> = vec![vec!["foo", "bar"], vec!["fiz", "foo"]]
+            .into_iter()
+            .map(|values| {
+                let c1: DictionaryArray<Int32Type> = values.into_iter().collect();
+                RecordBatch::try_from_iter(vec![("a", Arc::new(c1) as ArrayRef)])
+                    .expect("cannot create record batch")
+            })
+            .collect();
+        let schema = batches[0].schema();
+
+        let coalesced = coalesce_batches(&schema, &batches).unwrap();
+
+        assert_eq!(coalesced.schema(), schema);
+        let array = arrow::compute::cast(coalesced.column(0), &DataType::Utf8).unwrap();
+        let array = array.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(array, &StringArray::from(vec!["foo", "bar", "fiz", "foo"]));
+    }
+
+    #[test]
+    fn test_assign_dictionary_ids() {
+        let dictionary = DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8));
+        let schema = Schema::new(vec![
+            Field::new("a", dictionary.clone(), true),
+            Field::new("b", DataType::UInt32, true),
+            Field::new("c", dictionary, true),
+        ]);
+
+        let schema = assign_dictionary_ids(&schema);
+
+        assert_eq!(schema.field(0).dict_id(), Some(0));
+        assert_eq!(schema.field(1).dict_id(), None);
+        assert_eq!(schema.field(2).dict_id(), Some(1));
+    }
+
     #[test]
     fn test_encode_flight_data_dictionary() {
         let options = arrow::ipc::writer::IpcWriteOptions::default();
@@ -422,24 +745,37 @@ mod tests {
                 .expect("cannot create record batch");
 
         let original_schema = batch.schema();
-        let optimized_schema = Arc::new(optimize_schema(&original_schema));
+        let schema = Arc::new(assign_dictionary_ids(&original_schema));
 
-        let optimized_batch = optimize_record_batch(&batch, Arc::clone(&optimized_schema)).unwrap();
+        let optimized_batch = optimize_record_batch(&batch, Arc::clone(&schema)).unwrap();
 
-        let (_, flight_data) =
+        let (flight_dictionaries, flight_data) =
             arrow_flight::utils::flight_data_from_arrow_batch(&optimized_batch, &options);
+        assert_eq!(flight_dictionaries.len(), 1);
+
+        let mut dictionaries_by_field = vec![None; schema.fields().len()];
+        for flight_dictionary in &flight_dictionaries {
+            let message = arrow::ipc::root_as_message(&flight_dictionary.data_header[..]).unwrap();
+            arrow::ipc::reader::read_dictionary(
+                &flight_dictionary.data_body,
+                message.header_as_dictionary_batch().unwrap(),
+                &schema,
+                &mut dictionaries_by_field,
+            )
+            .unwrap();
+        }
 
         let batch =
-            flight_data_to_arrow_batch(&flight_data, Arc::clone(&optimized_schema), &[None, None])
+            flight_data_to_arrow_batch(&flight_data, Arc::clone(&schema), &dictionaries_by_field)
                 .unwrap();
 
-        // Should hydrate string dictionary for transport
-        assert_eq!(optimized_schema.field(1).data_type(), &DataType::Utf8);
-        let array = batch
-            .column(1)
-            .as_any()
-            .downcast_ref::<StringArray>()
-            .unwrap();
+        // Should keep the string dictionary for transport
+        assert_eq!(
+            batch.column(1).data_type(),
+            original_schema.field(1).data_type()
+        );
+        let array = arrow::compute::cast(batch.column(1), &DataType::Utf8).unwrap();
+        let array = array.as_any().downcast_ref::<StringArray>().unwrap();
 
         let expected = StringArray::from(vec![
             Some("foo"),
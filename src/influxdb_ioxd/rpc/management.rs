@@ -2,22 +2,35 @@ use std::convert::{TryFrom, TryInto};
 use std::fmt::Debug;
 use std::sync::Arc;
 
-use data_types::{database_rules::DatabaseRules, server_id::ServerId, DatabaseName};
+use data_types::{
+    database_rules::{ContinuousQuery, DatabaseRules},
+    delete_predicate::DeletePredicate,
+    server_id::ServerId,
+    timestamp::TimestampRange,
+    DatabaseName,
+};
 use generated_types::google::{
     AlreadyExists, FieldViolation, FieldViolationExt, FromFieldOpt, InternalError, NotFound,
+    PreconditionViolation,
 };
 use generated_types::influxdata::iox::management::v1::*;
-use observability_deps::tracing::info;
+use observability_deps::tracing::{info, warn};
 use query::{Database, DatabaseStore};
-use server::{ConnectionManager, Error, Server};
+use server::{auth, ConnectionManager, Error, Server};
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
+use tracker::TaskId;
 
 struct ManagementService<M: ConnectionManager> {
     server: Arc<Server<M>>,
     serving_readiness: ServingReadiness,
 }
 
-use super::error::{default_db_error_handler, default_server_error_handler};
+use super::auth::AdminInterceptor;
+use super::error::{
+    default_auth_error_handler, default_db_error_handler, default_server_error_handler,
+};
 use crate::influxdb_ioxd::serving_readiness::ServingReadiness;
 
 #[derive(Debug)]
@@ -132,6 +145,19 @@ where
                 }
                 .into())
             }
+            Err(Error::DatabaseDeleted { db_name }) => Err(AlreadyExists {
+                resource_type: "database".to_string(),
+                resource_name: db_name,
+                description: "database is deleted, restore it or wait until it is purged"
+                    .to_string(),
+                ..Default::default()
+            }
+            .into()),
+            Err(e @ Error::CreatingWriteBuffer { .. }) => Err(FieldViolation {
+                field: "rules.write_buffer_connection_string".to_string(),
+                description: e.to_string(),
+            }
+            .into()),
             Err(e) => Err(default_server_error_handler(e)),
         }
     }
@@ -154,6 +180,187 @@ where
         }))
     }
 
+    async fn delete_database(
+        &self,
+        request: Request<DeleteDatabaseRequest>,
+    ) -> Result<Response<DeleteDatabaseResponse>, Status> {
+        let DeleteDatabaseRequest {
+            db_name,
+            preserve_data,
+        } = request.into_inner();
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+
+        self.server
+            .delete_database(&db_name, preserve_data)
+            .await
+            .map_err(default_server_error_handler)?;
+
+        Ok(Response::new(DeleteDatabaseResponse {}))
+    }
+
+    async fn backup_database(
+        &self,
+        request: Request<BackupDatabaseRequest>,
+    ) -> Result<Response<BackupDatabaseResponse>, Status> {
+        let BackupDatabaseRequest {
+            db_name,
+            destination,
+        } = request.into_inner();
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+
+        match self.server.backup_database(&db_name, &destination).await {
+            Ok(files) => Ok(Response::new(BackupDatabaseResponse {
+                files: files as u64,
+            })),
+            Err(Error::BackupLocationNotEmpty { location }) => Err(AlreadyExists {
+                resource_type: "backup".to_string(),
+                resource_name: location,
+                ..Default::default()
+            }
+            .into()),
+            Err(e) => Err(default_server_error_handler(e)),
+        }
+    }
+
+    async fn restore_database(
+        &self,
+        request: Request<RestoreDatabaseRequest>,
+    ) -> Result<Response<RestoreDatabaseResponse>, Status> {
+        let RestoreDatabaseRequest { db_name, source } = request.into_inner();
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+
+        match self.server.restore_database(&db_name, &source).await {
+            Ok(files) => Ok(Response::new(RestoreDatabaseResponse {
+                files: files as u64,
+            })),
+            Err(Error::DatabaseAlreadyExists { db_name })
+            | Err(Error::DatabaseDataExists { db_name }) => Err(AlreadyExists {
+                resource_type: "database".to_string(),
+                resource_name: db_name,
+                ..Default::default()
+            }
+            .into()),
+            Err(Error::BackupNotFound { location, .. }) => Err(NotFound {
+                resource_type: "backup".to_string(),
+                resource_name: location,
+                ..Default::default()
+            }
+            .into()),
+            Err(e) => Err(default_server_error_handler(e)),
+        }
+    }
+
+    async fn restore_deleted_database(
+        &self,
+        request: Request<RestoreDeletedDatabaseRequest>,
+    ) -> Result<Response<RestoreDeletedDatabaseResponse>, Status> {
+        let RestoreDeletedDatabaseRequest { db_name } = request.into_inner();
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+
+        match self.server.restore_deleted_database(&db_name).await {
+            Ok(()) => Ok(Response::new(RestoreDeletedDatabaseResponse {})),
+            Err(Error::DatabaseAlreadyExists { db_name }) => Err(AlreadyExists {
+                resource_type: "database".to_string(),
+                resource_name: db_name,
+                ..Default::default()
+            }
+            .into()),
+            Err(e) => Err(default_server_error_handler(e)),
+        }
+    }
+
+    async fn clone_database(
+        &self,
+        request: Request<CloneDatabaseRequest>,
+    ) -> Result<Response<CloneDatabaseResponse>, Status> {
+        let CloneDatabaseRequest {
+            source_db_name,
+            target_db_name,
+            with_data,
+        } = request.into_inner();
+        let source_db_name = DatabaseName::new(source_db_name).field("source_db_name")?;
+        let target_db_name = DatabaseName::new(target_db_name).field("target_db_name")?;
+
+        match self
+            .server
+            .clone_database(&source_db_name, target_db_name, with_data)
+            .await
+        {
+            Ok(files) => Ok(Response::new(CloneDatabaseResponse {
+                files: files as u64,
+            })),
+            Err(Error::DatabaseAlreadyExists { db_name })
+            | Err(Error::DatabaseDataExists { db_name }) => Err(AlreadyExists {
+                resource_type: "database".to_string(),
+                resource_name: db_name,
+                ..Default::default()
+            }
+            .into()),
+            Err(e) => Err(default_server_error_handler(e)),
+        }
+    }
+
+    async fn export_database(
+        &self,
+        request: Request<ExportDatabaseRequest>,
+    ) -> Result<Response<ExportDatabaseResponse>, Status> {
+        let ExportDatabaseRequest {
+            db_name,
+            destination,
+            range,
+        } = request.into_inner();
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+
+        let range = match range {
+            Some(range) if range.stop_time <= range.start_time => {
+                return Err(FieldViolation {
+                    field: "range.stop_time".to_string(),
+                    description: "Time range must not be empty".to_string(),
+                }
+                .into())
+            }
+            Some(range) => Some(TimestampRange::new(range.start_time, range.stop_time)),
+            None => None,
+        };
+
+        match self
+            .server
+            .export_database(&db_name, &destination, range)
+            .await
+        {
+            Ok(files) => Ok(Response::new(ExportDatabaseResponse {
+                files: files as u64,
+            })),
+            Err(Error::ExportLocationNotEmpty { location }) => Err(AlreadyExists {
+                resource_type: "export".to_string(),
+                resource_name: location,
+                ..Default::default()
+            }
+            .into()),
+            Err(e) => Err(default_server_error_handler(e)),
+        }
+    }
+
+    async fn import_tsm(
+        &self,
+        request: Request<ImportTsmRequest>,
+    ) -> Result<Response<ImportTsmResponse>, Status> {
+        let ImportTsmRequest { db_name, paths } = request.into_inner();
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+
+        match self.server.import_tsm(&db_name, &paths).await {
+            Ok(rows) => Ok(Response::new(ImportTsmResponse { rows: rows as u64 })),
+            Err(e @ Error::ReadingTsmFile { .. }) | Err(e @ Error::ImportingTsm { .. }) => {
+                Err(FieldViolation {
+                    field: "paths".to_string(),
+                    description: e.to_string(),
+                }
+                .into())
+            }
+            Err(e) => Err(default_server_error_handler(e)),
+        }
+    }
+
     async fn list_chunks(
         &self,
         request: Request<ListChunksRequest>,
@@ -185,6 +392,51 @@ where
         Ok(Response::new(ListChunksResponse { chunks }))
     }
 
+    type WatchChunksStream = ReceiverStream<Result<ChunkEvent, Status>>;
+
+    async fn watch_chunks(
+        &self,
+        request: Request<WatchChunksRequest>,
+    ) -> Result<Response<Self::WatchChunksStream>, Status> {
+        let db_name = DatabaseName::new(request.into_inner().db_name).field("db_name")?;
+
+        let db = match self.server.db(&db_name) {
+            Some(db) => db,
+            None => {
+                return Err(NotFound {
+                    resource_type: "database".to_string(),
+                    resource_name: db_name.to_string(),
+                    ..Default::default()
+                }
+                .into())
+            }
+        };
+
+        let mut events = db.subscribe_chunk_events();
+        let (tx, rx) = mpsc::channel(4);
+
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(%db_name, %skipped, "chunk watcher lagging, skipped events");
+                        continue;
+                    }
+                    // the database was dropped
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                if tx.send(Ok(event.into())).await.is_err() {
+                    // the client went away
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
     async fn create_dummy_job(
         &self,
         request: Request<CreateDummyJobRequest>,
@@ -256,7 +508,10 @@ where
         let partition_keys = db.partition_keys().map_err(default_db_error_handler)?;
         let partitions = partition_keys
             .into_iter()
-            .map(|key| Partition { key })
+            .map(|key| Partition {
+                key,
+                ..Default::default()
+            })
             .collect::<Vec<_>>();
 
         Ok(Response::new(ListPartitionsResponse { partitions }))
@@ -278,14 +533,11 @@ where
             ..Default::default()
         })?;
 
-        // TODO: get more actual partition details
-        let partition_keys = db.partition_keys().map_err(default_db_error_handler)?;
-
-        let partition = if partition_keys.contains(&partition_key) {
-            Some(Partition { key: partition_key })
-        } else {
-            None
-        };
+        let partition = db
+            .partition_storage_summary(&partition_key)
+            .await
+            .map_err(default_db_error_handler)?
+            .map(Into::into);
 
         Ok(Response::new(GetPartitionResponse { partition }))
     }
@@ -363,6 +615,261 @@ where
         Ok(Response::new(ClosePartitionChunkResponse { operation }))
     }
 
+    async fn persist_partition_chunk(
+        &self,
+        request: Request<PersistPartitionChunkRequest>,
+    ) -> Result<Response<PersistPartitionChunkResponse>, Status> {
+        let PersistPartitionChunkRequest {
+            db_name,
+            partition_key,
+            table_name,
+            chunk_id,
+        } = request.into_inner();
+
+        // Validate that the database name is legit
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+
+        let tracker = self
+            .server
+            .persist_chunk(db_name, partition_key, table_name, chunk_id)
+            .map_err(default_server_error_handler)?;
+
+        let operation = Some(super::operations::encode_tracker(tracker)?);
+
+        Ok(Response::new(PersistPartitionChunkResponse { operation }))
+    }
+
+    async fn compact_partition(
+        &self,
+        request: Request<CompactPartitionRequest>,
+    ) -> Result<Response<CompactPartitionResponse>, Status> {
+        let CompactPartitionRequest {
+            db_name,
+            partition_key,
+            table_name,
+        } = request.into_inner();
+
+        // Validate that the database name is legit
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+
+        let tracker = self
+            .server
+            .compact_partition(db_name, partition_key, table_name)
+            .map_err(default_server_error_handler)?;
+
+        let operation = Some(super::operations::encode_tracker(tracker)?);
+
+        Ok(Response::new(CompactPartitionResponse { operation }))
+    }
+
+    async fn drop_partition(
+        &self,
+        request: Request<DropPartitionRequest>,
+    ) -> Result<Response<DropPartitionResponse>, Status> {
+        let DropPartitionRequest {
+            db_name,
+            partition_key,
+            delete_persisted,
+        } = request.into_inner();
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+
+        let db = self.server.db(&db_name).ok_or_else(|| NotFound {
+            resource_type: "database".to_string(),
+            resource_name: db_name.to_string(),
+            ..Default::default()
+        })?;
+
+        db.drop_partition(&partition_key, delete_persisted)
+            .await
+            .map_err(default_db_error_handler)?;
+
+        Ok(Response::new(DropPartitionResponse {}))
+    }
+
+    async fn delete(
+        &self,
+        request: Request<DeleteRequest>,
+    ) -> Result<Response<DeleteResponse>, Status> {
+        let DeleteRequest { db_name, predicate } = request.into_inner();
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+        let predicate: DeletePredicate = predicate.required("predicate")?;
+
+        let db = self.server.db(&db_name).ok_or_else(|| NotFound {
+            resource_type: "database".to_string(),
+            resource_name: db_name.to_string(),
+            ..Default::default()
+        })?;
+
+        db.delete(Arc::new(predicate))
+            .await
+            .map_err(default_db_error_handler)?;
+
+        Ok(Response::new(DeleteResponse {}))
+    }
+
+    async fn create_continuous_query(
+        &self,
+        request: Request<CreateContinuousQueryRequest>,
+    ) -> Result<Response<CreateContinuousQueryResponse>, Status> {
+        let CreateContinuousQueryRequest { db_name, query } = request.into_inner();
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+        let query: ContinuousQuery = query.required("query")?;
+        let name = query.name.clone();
+
+        if self.server.db_rules(&db_name).is_none() {
+            return Err(NotFound {
+                resource_type: "database".to_string(),
+                resource_name: db_name.to_string(),
+                ..Default::default()
+            }
+            .into());
+        }
+
+        self.server
+            .update_db_rules(&db_name, |mut rules| {
+                if rules
+                    .continuous_queries
+                    .iter()
+                    .any(|q| q.name == query.name)
+                {
+                    return Err(AlreadyExists {
+                        resource_type: "continuous query".to_string(),
+                        resource_name: query.name,
+                        ..Default::default()
+                    }
+                    .into());
+                }
+                rules.continuous_queries.push(query);
+                Ok(rules)
+            })
+            .await
+            .map_err(UpdateError::from)?;
+
+        info!(%db_name, %name, "created continuous query");
+
+        Ok(Response::new(CreateContinuousQueryResponse {}))
+    }
+
+    async fn list_continuous_queries(
+        &self,
+        request: Request<ListContinuousQueriesRequest>,
+    ) -> Result<Response<ListContinuousQueriesResponse>, Status> {
+        let db_name = DatabaseName::new(request.into_inner().db_name).field("db_name")?;
+
+        let rules = self.server.db_rules(&db_name).ok_or_else(|| NotFound {
+            resource_type: "database".to_string(),
+            resource_name: db_name.to_string(),
+            ..Default::default()
+        })?;
+
+        let queries = rules
+            .continuous_queries
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        Ok(Response::new(ListContinuousQueriesResponse { queries }))
+    }
+
+    async fn delete_continuous_query(
+        &self,
+        request: Request<DeleteContinuousQueryRequest>,
+    ) -> Result<Response<DeleteContinuousQueryResponse>, Status> {
+        let DeleteContinuousQueryRequest { db_name, name } = request.into_inner();
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+
+        if self.server.db_rules(&db_name).is_none() {
+            return Err(NotFound {
+                resource_type: "database".to_string(),
+                resource_name: db_name.to_string(),
+                ..Default::default()
+            }
+            .into());
+        }
+
+        self.server
+            .update_db_rules(&db_name, |mut rules| {
+                let len = rules.continuous_queries.len();
+                rules.continuous_queries.retain(|q| q.name != name);
+                if rules.continuous_queries.len() == len {
+                    return Err(NotFound {
+                        resource_type: "continuous query".to_string(),
+                        resource_name: name.clone(),
+                        ..Default::default()
+                    }
+                    .into());
+                }
+                Ok(rules)
+            })
+            .await
+            .map_err(UpdateError::from)?;
+
+        info!(%db_name, %name, "deleted continuous query");
+
+        Ok(Response::new(DeleteContinuousQueryResponse {}))
+    }
+
+    async fn list_queries(
+        &self,
+        request: Request<ListQueriesRequest>,
+    ) -> Result<Response<ListQueriesResponse>, Status> {
+        let db_name = DatabaseName::new(request.into_inner().db_name).field("db_name")?;
+
+        let db = self.server.db(&db_name).ok_or_else(|| NotFound {
+            resource_type: "database".to_string(),
+            resource_name: db_name.to_string(),
+            ..Default::default()
+        })?;
+
+        let queries = db
+            .running_queries()
+            .running()
+            .into_iter()
+            .map(|tracker| {
+                let info = tracker.metadata();
+                RunningQuery {
+                    id: tracker.id().to_string(),
+                    text: info.text.clone(),
+                    start_time: Some(info.start_time.into()),
+                    cpu_nanos: tracker.get_status().cpu_nanos().unwrap_or_default() as _,
+                }
+            })
+            .collect();
+
+        Ok(Response::new(ListQueriesResponse { queries }))
+    }
+
+    async fn cancel_query(
+        &self,
+        request: Request<CancelQueryRequest>,
+    ) -> Result<Response<CancelQueryResponse>, Status> {
+        let CancelQueryRequest { db_name, id } = request.into_inner();
+        let db_name = DatabaseName::new(db_name).field("db_name")?;
+        let task_id = id.parse::<TaskId>().map_err(|e| FieldViolation {
+            field: "id".to_string(),
+            description: e.to_string(),
+        })?;
+
+        let db = self.server.db(&db_name).ok_or_else(|| NotFound {
+            resource_type: "database".to_string(),
+            resource_name: db_name.to_string(),
+            ..Default::default()
+        })?;
+
+        if !db.running_queries().cancel(task_id) {
+            return Err(NotFound {
+                resource_type: "query".to_string(),
+                resource_name: id,
+                ..Default::default()
+            }
+            .into());
+        }
+
+        info!(%db_name, %id, "cancelled query");
+
+        Ok(Response::new(CancelQueryResponse {}))
+    }
+
     async fn set_serving_readiness(
         &self,
         request: Request<SetServingReadinessRequest>,
@@ -400,6 +907,101 @@ where
             }),
         }))
     }
+
+    async fn create_token(
+        &self,
+        request: Request<CreateTokenRequest>,
+    ) -> Result<Response<CreateTokenResponse>, Status> {
+        if !self.server.auth.is_enabled() {
+            return Err(PreconditionViolation {
+                category: "Authorization".to_string(),
+                subject: "influxdata.com/iox".to_string(),
+                description: "Authorization must be enabled with an admin token".to_string(),
+            }
+            .into());
+        }
+
+        let CreateTokenRequest {
+            description,
+            permissions,
+        } = request.into_inner();
+        let permissions = permissions
+            .into_iter()
+            .map(permission_from_proto)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.scope("permissions"))?;
+
+        let (token, secret) = self.server.auth.create_token(description, permissions);
+        info!(id = token.id, description = %token.description, "created token");
+
+        Ok(Response::new(CreateTokenResponse {
+            token: Some(token_to_proto(token)),
+            secret,
+        }))
+    }
+
+    async fn list_tokens(
+        &self,
+        _: Request<ListTokensRequest>,
+    ) -> Result<Response<ListTokensResponse>, Status> {
+        let tokens = self
+            .server
+            .auth
+            .tokens()
+            .into_iter()
+            .map(token_to_proto)
+            .collect();
+        Ok(Response::new(ListTokensResponse { tokens }))
+    }
+
+    async fn revoke_token(
+        &self,
+        request: Request<RevokeTokenRequest>,
+    ) -> Result<Response<RevokeTokenResponse>, Status> {
+        let RevokeTokenRequest { id } = request.into_inner();
+        self.server
+            .auth
+            .revoke_token(id)
+            .map_err(default_auth_error_handler)?;
+        info!(id, "revoked token");
+
+        Ok(Response::new(RevokeTokenResponse {}))
+    }
+}
+
+fn permission_from_proto(permission: Permission) -> Result<auth::Permission, FieldViolation> {
+    let scope = match permission.scope() {
+        Scope::Unspecified => return Err(FieldViolation::required("scope")),
+        Scope::Read => auth::Scope::Read,
+        Scope::Write => auth::Scope::Write,
+    };
+    let db_name = DatabaseName::new(permission.db_name).field("db_name")?;
+
+    Ok(auth::Permission {
+        db_name: db_name.to_string(),
+        scope,
+    })
+}
+
+fn token_to_proto(token: auth::Token) -> Token {
+    let permissions = token
+        .permissions
+        .into_iter()
+        .map(|permission| Permission {
+            db_name: permission.db_name,
+            scope: match permission.scope {
+                auth::Scope::Read => Scope::Read,
+                auth::Scope::Write => Scope::Write,
+            }
+            .into(),
+        })
+        .collect();
+
+    Token {
+        id: token.id,
+        description: token.description,
+        permissions,
+    }
 }
 
 pub fn make_server<M>(
@@ -411,8 +1013,12 @@ pub fn make_server<M>(
 where
     M: ConnectionManager + Send + Sync + Debug + 'static,
 {
-    management_service_server::ManagementServiceServer::new(ManagementService {
-        server,
-        serving_readiness,
-    })
+    let admin = AdminInterceptor(Arc::clone(&server));
+    management_service_server::ManagementServiceServer::with_interceptor(
+        ManagementService {
+            server,
+            serving_readiness,
+        },
+        admin,
+    )
 }
@@ -20,6 +20,7 @@ use generated_types::{
 };
 use tracker::{TaskId, TaskStatus, TaskTracker};
 
+use super::auth::AdminInterceptor;
 use server::{ConnectionManager, Server};
 use std::convert::TryInto;
 
@@ -211,5 +212,6 @@ pub fn make_server<M>(
 where
     M: ConnectionManager + Send + Sync + Debug + 'static,
 {
-    operations_server::OperationsServer::new(OperationsService { server })
+    let admin = AdminInterceptor(Arc::clone(&server));
+    operations_server::OperationsServer::with_interceptor(OperationsService { server }, admin)
 }
@@ -17,6 +17,7 @@ pub mod service;
 use generated_types::storage_server::{Storage, StorageServer};
 use metrics::{MetricRegistry, RedMetric};
 use query::DatabaseStore;
+use server::auth::Authorizer;
 use std::sync::Arc;
 use tonic::Interceptor;
 
@@ -25,17 +26,20 @@ use tonic::Interceptor;
 struct StorageService<T: DatabaseStore> {
     pub db_store: Arc<T>,
     pub metrics: Metrics,
+    pub auth: Arc<Authorizer>,
 }
 
 pub fn make_server<T: DatabaseStore + 'static>(
     db_store: Arc<T>,
     metrics_registry: Arc<MetricRegistry>,
+    auth: Arc<Authorizer>,
     interceptor: impl Into<Interceptor>,
 ) -> StorageServer<impl Storage> {
     StorageServer::with_interceptor(
         StorageService {
             db_store,
             metrics: Metrics::new(metrics_registry),
+            auth,
         },
         interceptor,
     )
@@ -262,6 +262,14 @@ fn convert_simple_node(builder: PredicateBuilder, node: RPCNode) -> Result<Predi
                 return Ok(builder.tables(value_list));
             } else if tag_name.is_field() {
                 return Ok(builder.field_columns(value_list));
+            } else {
+                // add tag = value1 OR tag = value2 ... as a single
+                // disjunction, which chunks can be pruned with
+                let tag_name = make_tag_name(tag_name)?;
+                let exprs = value_list
+                    .into_iter()
+                    .map(|value| col(&tag_name).eq(lit(value)));
+                return Ok(builder.add_disjunction(exprs));
             }
         }
     }
@@ -1054,6 +1062,40 @@ mod tests {
         assert!(predicate.range.is_none());
     }
 
+    #[test]
+    fn test_convert_predicate_tag_disjunction() {
+        // Test (host = "a" OR host = "b") OR host = "c"
+        let selection = make_or_node(
+            make_or_node(
+                make_tag_ref_node(b"host", "a"),
+                make_tag_ref_node(b"host", "b"),
+            ),
+            make_tag_ref_node(b"host", "c"),
+        );
+
+        let rpc_predicate = RPCPredicate {
+            root: Some(selection),
+        };
+
+        let predicate = PredicateBuilder::default()
+            .rpc_predicate(Some(rpc_predicate))
+            .expect("successfully converting predicate")
+            .build();
+
+        let expected_expr = col("host")
+            .eq(lit("a"))
+            .or(col("host").eq(lit("b")))
+            .or(col("host").eq(lit("c")));
+
+        assert_eq!(predicate.exprs.len(), 1);
+        assert_eq!(
+            format!("{:?}", predicate.exprs[0]),
+            format!("{:?}", expected_expr)
+        );
+        assert!(predicate.table_names.is_none());
+        assert!(predicate.field_columns.is_none());
+    }
+
     #[test]
     fn test_convert_predicate_unsupported_structure() {
         // Test (_f = "foo" and host > 5.0) OR (_m = "bar")
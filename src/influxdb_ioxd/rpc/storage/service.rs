@@ -4,14 +4,18 @@
 
 use crate::influxdb_ioxd::{
     planner::Planner,
-    rpc::storage::{
-        data::{
-            fieldlist_to_measurement_fields_response, series_set_item_to_read_response,
-            tag_keys_to_byte_vecs,
+    rpc::{
+        auth::request_token,
+        error::default_auth_error_handler,
+        storage::{
+            data::{
+                fieldlist_to_measurement_fields_response, series_set_item_to_read_response,
+                tag_keys_to_byte_vecs,
+            },
+            expr::{self, AddRpcNode, GroupByAndAggregate, Loggable, SpecialTagKeys},
+            input::GrpcInputs,
+            StorageService,
         },
-        expr::{self, AddRpcNode, GroupByAndAggregate, Loggable, SpecialTagKeys},
-        input::GrpcInputs,
-        StorageService,
     },
 };
 use data_types::{error::ErrorLogger, names::org_and_bucket_to_database, DatabaseName};
@@ -29,6 +33,7 @@ use query::{
     exec::fieldlist::FieldList, exec::seriesset::Error as SeriesSetError,
     predicate::PredicateBuilder, DatabaseStore,
 };
+use server::auth::Scope;
 use snafu::{OptionExt, ResultExt, Snafu};
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::mpsc;
@@ -219,10 +224,9 @@ where
         &self,
         req: tonic::Request<ReadFilterRequest>,
     ) -> Result<tonic::Response<Self::ReadFilterStream>, Status> {
+        let db_name = self.authorized_database_name(&req)?;
         let read_filter_request = req.into_inner();
 
-        let db_name = get_database_name(&read_filter_request)?;
-
         let ReadFilterRequest {
             read_source: _read_source,
             range,
@@ -261,10 +265,9 @@ where
         &self,
         req: tonic::Request<ReadGroupRequest>,
     ) -> Result<tonic::Response<Self::ReadGroupStream>, Status> {
+        let db_name = self.authorized_database_name(&req)?;
         let read_group_request = req.into_inner();
 
-        let db_name = get_database_name(&read_group_request)?;
-
         let ReadGroupRequest {
             read_source: _read_source,
             range,
@@ -340,10 +343,9 @@ where
         &self,
         req: tonic::Request<ReadWindowAggregateRequest>,
     ) -> Result<tonic::Response<Self::ReadGroupStream>, Status> {
+        let db_name = self.authorized_database_name(&req)?;
         let read_window_aggregate_request = req.into_inner();
 
-        let db_name = get_database_name(&read_window_aggregate_request)?;
-
         let ReadWindowAggregateRequest {
             read_source: _read_source,
             range,
@@ -406,10 +408,9 @@ where
     ) -> Result<tonic::Response<Self::TagKeysStream>, Status> {
         let (tx, rx) = mpsc::channel(4);
 
+        let db_name = self.authorized_database_name(&req)?;
         let tag_keys_request = req.into_inner();
 
-        let db_name = get_database_name(&tag_keys_request)?;
-
         let TagKeysRequest {
             tags_source: _tag_source,
             range,
@@ -459,10 +460,9 @@ where
     ) -> Result<tonic::Response<Self::TagValuesStream>, Status> {
         let (tx, rx) = mpsc::channel(4);
 
+        let db_name = self.authorized_database_name(&req)?;
         let tag_values_request = req.into_inner();
 
-        let db_name = get_database_name(&tag_values_request)?;
-
         let ob = self.metrics.requests.observation();
         let labels = &[
             KeyValue::new("operation", "tag_values"),
@@ -608,10 +608,9 @@ where
     ) -> Result<tonic::Response<Self::MeasurementNamesStream>, Status> {
         let (tx, rx) = mpsc::channel(4);
 
+        let db_name = self.authorized_database_name(&req)?;
         let measurement_names_request = req.into_inner();
 
-        let db_name = get_database_name(&measurement_names_request)?;
-
         let MeasurementNamesRequest {
             source: _source,
             range,
@@ -664,10 +663,9 @@ where
     ) -> Result<tonic::Response<Self::MeasurementTagKeysStream>, Status> {
         let (tx, rx) = mpsc::channel(4);
 
+        let db_name = self.authorized_database_name(&req)?;
         let measurement_tag_keys_request = req.into_inner();
 
-        let db_name = get_database_name(&measurement_tag_keys_request)?;
-
         let MeasurementTagKeysRequest {
             source: _source,
             measurement,
@@ -718,10 +716,9 @@ where
     ) -> Result<tonic::Response<Self::MeasurementTagValuesStream>, Status> {
         let (tx, rx) = mpsc::channel(4);
 
+        let db_name = self.authorized_database_name(&req)?;
         let measurement_tag_values_request = req.into_inner();
 
-        let db_name = get_database_name(&measurement_tag_values_request)?;
-
         let MeasurementTagValuesRequest {
             source: _source,
             measurement,
@@ -774,10 +771,9 @@ where
     ) -> Result<tonic::Response<Self::MeasurementFieldsStream>, Status> {
         let (tx, rx) = mpsc::channel(4);
 
+        let db_name = self.authorized_database_name(&req)?;
         let measurement_fields_request = req.into_inner();
 
-        let db_name = get_database_name(&measurement_fields_request)?;
-
         let MeasurementFieldsRequest {
             source: _source,
             measurement,
@@ -845,6 +841,21 @@ fn get_database_name(input: &impl GrpcInputs) -> Result<DatabaseName<'static>, S
         .map_err(|e| Status::internal(e.to_string()))
 }
 
+impl<T: DatabaseStore> StorageService<T> {
+    /// Returns the database read by `req`, after checking that the token
+    /// presented with the request grants read access to it
+    fn authorized_database_name(
+        &self,
+        req: &tonic::Request<impl GrpcInputs>,
+    ) -> Result<DatabaseName<'static>, Status> {
+        let db_name = get_database_name(req.get_ref())?;
+        self.auth
+            .authorize(request_token(req), &db_name, Scope::Read)
+            .map_err(default_auth_error_handler)?;
+        Ok(db_name)
+    }
+}
+
 // The following code implements the business logic of the requests as
 // methods that return Results with module specific Errors (and thus
 // can use ?, etc). The trait implemententations then handle mapping
@@ -1192,6 +1203,7 @@ mod tests {
     use crate::influxdb_ioxd::serving_readiness::ServingReadinessState;
     use generated_types::google::protobuf::Any;
     use prost::Message;
+    use server::auth::Authorizer;
     use tokio_stream::wrappers::TcpListenerStream;
 
     type IOxTestingClient = i_ox_testing_client::IOxTestingClient<tonic::transport::Channel>;
@@ -2837,6 +2849,7 @@ mod tests {
                 .add_service(crate::influxdb_ioxd::rpc::storage::make_server(
                     Arc::clone(&test_storage),
                     test_storage.metrics_registry.registry(),
+                    Arc::new(Authorizer::new(None)),
                     ServingReadinessInterceptor(ServingReadinessState::Serving.into()),
                 ));
 
@@ -1,13 +1,15 @@
 use std::sync::Arc;
 
 use chrono::Utc;
+use data_types::timestamp::Precision;
 use generated_types::{google::FieldViolation, influxdata::iox::write::v1::*};
-use influxdb_line_protocol::parse_lines;
+use influxdb_line_protocol::{parse_lines_with_location, LineLocation};
 use observability_deps::tracing::debug;
-use server::{ConnectionManager, Server};
+use server::{auth::Scope, ConnectionManager, Server};
 use std::fmt::Debug;
 use tonic::{Interceptor, Response};
 
+use super::auth::authorize;
 use super::error::default_server_error_handler;
 
 /// Implementation of the write service
@@ -24,39 +26,107 @@ where
         &self,
         request: tonic::Request<WriteRequest>,
     ) -> Result<tonic::Response<WriteResponse>, tonic::Status> {
+        authorize(
+            &self.server,
+            &request,
+            &request.get_ref().db_name,
+            Scope::Write,
+        )?;
         let request = request.into_inner();
 
         // The time, in nanoseconds since the epoch, to assign to any points that don't
         // contain a timestamp
         let default_time = Utc::now().timestamp_nanos();
 
+        let precision: Precision = request.precision().into();
         let db_name = request.db_name;
         let lp_data = request.lp_data;
+        let all_or_nothing = request.all_or_nothing;
         let lp_chars = lp_data.len();
 
-        let lines = parse_lines(&lp_data)
-            .collect::<Result<Vec<_>, influxdb_line_protocol::Error>>()
-            .map_err(|e| FieldViolation {
-                field: "lp_data".into(),
-                description: format!("Invalid Line Protocol: {}", e),
-            })?;
+        // Write the lines that can be parsed and report the others, unless
+        // none can be parsed at all
+        let mut lines = Vec::new();
+        let mut locations = Vec::new();
+        let mut rejected_lines = Vec::new();
+        for (location, line) in parse_lines_with_location(&lp_data) {
+            match line {
+                Ok(line) => {
+                    lines.push(line);
+                    locations.push(location);
+                }
+                Err(e) => rejected_lines.push(rejected_line(
+                    &location,
+                    format!("Invalid Line Protocol: {}", e),
+                )),
+            }
+        }
+        if lines.is_empty() {
+            if let Some(rejected) = rejected_lines.first() {
+                return Err(FieldViolation {
+                    field: "lp_data".into(),
+                    description: format!(
+                        "line {} (byte offset {}): {}: '{}'",
+                        rejected.line_number,
+                        rejected.byte_offset,
+                        rejected.reason,
+                        rejected.snippet
+                    ),
+                }
+                .into());
+            }
+        }
+
+        if all_or_nothing && !rejected_lines.is_empty() {
+            return Ok(Response::new(WriteResponse {
+                lines_written: 0,
+                rejected_lines,
+            }));
+        }
 
         let lp_line_count = lines.len();
-        debug!(%db_name, %lp_chars, lp_line_count, "Writing lines into database");
+        debug!(%db_name, %lp_chars, lp_line_count, all_or_nothing, "Writing lines into database");
 
-        self.server
-            .write_lines(&db_name, &lines, default_time)
-            .await
-            .map_err(default_server_error_handler)?;
+        let rejected = match all_or_nothing {
+            true => self
+                .server
+                .write_lines_all_or_nothing(&db_name, &lines, default_time, precision)
+                .await
+                .map_err(default_server_error_handler)?,
+            false => self
+                .server
+                .write_lines_partial(&db_name, &lines, default_time, precision)
+                .await
+                .map_err(default_server_error_handler)?,
+        };
+
+        let lines_written = match all_or_nothing && !rejected.is_empty() {
+            true => 0,
+            false => (lp_line_count - rejected.len()) as u64,
+        };
+        rejected_lines.extend(
+            rejected
+                .into_iter()
+                .map(|(idx, e)| rejected_line(&locations[idx], e.to_string())),
+        );
+        rejected_lines.sort_by_key(|rejected| rejected.line_number);
 
-        let lines_written = lp_line_count as u64;
-        Ok(Response::new(WriteResponse { lines_written }))
+        Ok(Response::new(WriteResponse {
+            lines_written,
+            rejected_lines,
+        }))
     }
 
     async fn write_entry(
         &self,
         request: tonic::Request<WriteEntryRequest>,
     ) -> Result<tonic::Response<WriteEntryResponse>, tonic::Status> {
+        authorize(
+            &self.server,
+            &request,
+            &request.get_ref().db_name,
+            Scope::Write,
+        )?;
         let request = request.into_inner();
         if request.entry.is_empty() {
             return Err(FieldViolation::required("entry").into());
@@ -71,6 +141,15 @@ where
     }
 }
 
+fn rejected_line(location: &LineLocation<'_>, reason: String) -> RejectedLine {
+    RejectedLine {
+        line_number: location.line_number as u64,
+        reason,
+        byte_offset: location.byte_offset as u64,
+        snippet: location.snippet(),
+    }
+}
+
 /// Instantiate the write service
 pub fn make_server<M>(
     server: Arc<Server<M>>,
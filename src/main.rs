@@ -8,6 +8,7 @@
 )]
 
 use dotenv::dotenv;
+use influxdb_iox_client::connection::Builder;
 use structopt::StructOpt;
 use tokio::runtime::Runtime;
 
@@ -20,10 +21,12 @@ use tikv_jemallocator::Jemalloc;
 
 mod commands {
     pub mod database;
+    pub mod debug;
     pub mod operations;
     pub mod run;
     pub mod server;
     pub mod server_remote;
+    pub mod server_token;
     pub mod sql;
     pub mod tracing;
 }
@@ -107,6 +110,10 @@ struct Config {
     )]
     host: String, /* TODO: This must be on the root due to https://github.com/clap-rs/clap/pull/2253 */
 
+    /// Token to present to an IOx server that requires authorization
+    #[structopt(long, global = true, env = "IOX_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
     #[structopt(long)]
     /// Set the maximum number of threads to use. Defaults to the number of
     /// cores on the system
@@ -119,6 +126,7 @@ struct Config {
 #[derive(Debug, StructOpt)]
 enum Command {
     Database(commands::database::Config),
+    Debug(commands::debug::Config),
     // Clippy recommended boxing this variant because it's much larger than the others
     Run(Box<commands::run::Config>),
     Server(commands::server::Config),
@@ -136,6 +144,10 @@ fn main() -> Result<(), std::io::Error> {
     tokio_runtime.block_on(async move {
         let host = config.host;
         let log_verbose_count = config.log_verbose_count;
+        let connection_builder = match config.token {
+            Some(token) => Builder::default().token(token),
+            None => Builder::default(),
+        };
 
         fn handle_init_logs(r: Result<TracingGuard, SetGlobalDefaultError>) -> TracingGuard {
             match r {
@@ -150,21 +162,31 @@ fn main() -> Result<(), std::io::Error> {
         match config.command {
             Command::Database(config) => {
                 let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
-                if let Err(e) = commands::database::command(host, config).await {
+                if let Err(e) = commands::database::command(connection_builder, host, config).await
+                {
+                    eprintln!("{}", e);
+                    std::process::exit(ReturnCode::Failure as _)
+                }
+            }
+            Command::Debug(config) => {
+                let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
+                if let Err(e) = commands::debug::command(config).await {
                     eprintln!("{}", e);
                     std::process::exit(ReturnCode::Failure as _)
                 }
             }
             Command::Operation(config) => {
                 let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
-                if let Err(e) = commands::operations::command(host, config).await {
+                if let Err(e) =
+                    commands::operations::command(connection_builder, host, config).await
+                {
                     eprintln!("{}", e);
                     std::process::exit(ReturnCode::Failure as _)
                 }
             }
             Command::Server(config) => {
                 let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
-                if let Err(e) = commands::server::command(host, config).await {
+                if let Err(e) = commands::server::command(connection_builder, host, config).await {
                     eprintln!("Server command failed: {}", e);
                     std::process::exit(ReturnCode::Failure as _)
                 }
@@ -179,7 +201,7 @@ fn main() -> Result<(), std::io::Error> {
             }
             Command::Sql(config) => {
                 let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
-                if let Err(e) = commands::sql::command(host, config).await {
+                if let Err(e) = commands::sql::command(connection_builder, host, config).await {
                     eprintln!("{}", e);
                     std::process::exit(ReturnCode::Failure as _)
                 }
@@ -11,6 +11,7 @@ use std::{
 };
 
 use futures::prelude::*;
+use influxdb_iox_client::connection::Connection;
 use std::time::Duration;
 use tempfile::TempDir;
 
@@ -69,7 +70,7 @@ use tokio::sync::Mutex;
 /// testing.
 pub struct ServerFixture {
     server: Arc<TestServer>,
-    grpc_channel: tonic::transport::Channel,
+    grpc_channel: Connection,
 }
 
 /// Specifieds should we configure a server initially
@@ -145,9 +146,14 @@ impl ServerFixture {
         }
     }
 
+    /// Return the directory the server uses for its file object store
+    pub fn dir(&self) -> &std::path::Path {
+        self.server.dir.path()
+    }
+
     /// Return a channel connected to the gRPC API. Panics if the
     /// server is not yet up
-    pub fn grpc_channel(&self) -> tonic::transport::Channel {
+    pub fn grpc_channel(&self) -> Connection {
         self.grpc_channel.clone()
     }
 
@@ -450,9 +456,7 @@ impl TestServer {
     }
 
     /// Create a connection channel for the gRPR endpoing
-    async fn grpc_channel(
-        &self,
-    ) -> influxdb_iox_client::connection::Result<tonic::transport::Channel> {
+    async fn grpc_channel(&self) -> influxdb_iox_client::connection::Result<Connection> {
         influxdb_iox_client::connection::Builder::default()
             .build(&self.addrs().grpc_base)
             .await
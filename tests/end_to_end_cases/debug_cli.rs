@@ -0,0 +1,226 @@
+use std::{
+    convert::TryInto,
+    path::{Path, PathBuf},
+};
+
+use assert_cmd::Command;
+use data_types::job::Operation;
+use predicates::prelude::*;
+
+use crate::common::server_fixture::ServerFixture;
+
+use super::scenario::{create_readable_database, rand_name};
+
+#[tokio::test]
+async fn test_dump_catalog() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let db_name = rand_name();
+
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("debug")
+        .arg("catalog")
+        .arg("dump")
+        .arg(&db_name)
+        .arg("--server-id")
+        .arg("42")
+        .arg("--object-store")
+        .arg("file")
+        .arg("--data-dir")
+        .arg(server_fixture.dir())
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Revision 0")
+                .and(predicate::str::contains("previous: none"))
+                .and(predicate::str::contains("Parquet files (0):")),
+        );
+}
+
+#[tokio::test]
+async fn test_dump_catalog_error() {
+    let server_fixture = ServerFixture::create_shared().await;
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("debug")
+        .arg("catalog")
+        .arg("dump")
+        .arg("non_existent_database")
+        .arg("--server-id")
+        .arg("42")
+        .arg("--object-store")
+        .arg("file")
+        .arg("--data-dir")
+        .arg(server_fixture.dir())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No preserved catalog found for database non_existent_database",
+        ));
+}
+
+#[tokio::test]
+async fn test_parquet_meta_non_iox_file() {
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("debug")
+        .arg("parquet-meta")
+        .arg("tests/fixtures/parquet/temperature.parquet")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("File size:")
+                .and(predicate::str::contains("Row group 0:"))
+                .and(predicate::str::contains("IOx metadata: error")),
+        );
+}
+
+#[tokio::test]
+async fn test_parquet_meta_persisted_chunk() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let db_name = rand_name();
+
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    server_fixture
+        .write_client()
+        .write(&db_name, "cpu,region=west user=23.2 100")
+        .await
+        .expect("write succeded");
+
+    let mut management_client = server_fixture.management_client();
+    let mut operations_client = server_fixture.operations_client();
+
+    let operation: Operation = management_client
+        .close_partition_chunk(&db_name, "cpu", "cpu", 0)
+        .await
+        .expect("closing chunk")
+        .try_into()
+        .expect("valid operation");
+    operations_client
+        .wait_operation(operation.id, None)
+        .await
+        .expect("waiting for move");
+
+    let operation: Operation = management_client
+        .persist_partition_chunk(&db_name, "cpu", "cpu", 0)
+        .await
+        .expect("persisting chunk")
+        .try_into()
+        .expect("valid operation");
+    operations_client
+        .wait_operation(operation.id, None)
+        .await
+        .expect("waiting for persist");
+
+    let mut data_dir = server_fixture.dir().to_path_buf();
+    data_dir.push("42");
+    data_dir.push(&db_name);
+    data_dir.push("data");
+    let parquet_files = find_parquet_files(&data_dir);
+    assert_eq!(parquet_files.len(), 1, "{:?}", parquet_files);
+
+    let expected = predicate::str::contains("transaction_revision_counter")
+        .and(predicate::str::contains("Rows: 1"))
+        .and(predicate::str::contains(
+            "user: iox::column_type::field::float",
+        ))
+        .and(predicate::str::contains("Row group 0: 1 rows"));
+
+    // read from the local filesystem
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("debug")
+        .arg("parquet-meta")
+        .arg(&parquet_files[0])
+        .assert()
+        .success()
+        .stdout(expected.clone());
+
+    // read through the object store
+    let location = parquet_files[0]
+        .strip_prefix(server_fixture.dir())
+        .unwrap()
+        .iter()
+        .map(|part| part.to_str().unwrap())
+        .collect::<Vec<_>>()
+        .join("/");
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("debug")
+        .arg("parquet-meta")
+        .arg(location)
+        .arg("--object-store")
+        .arg("file")
+        .arg("--data-dir")
+        .arg(server_fixture.dir())
+        .assert()
+        .success()
+        .stdout(expected);
+}
+
+#[tokio::test]
+async fn test_parquet_meta_error() {
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("debug")
+        .arg("parquet-meta")
+        .arg("non_existent_file.parquet")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Error reading file"));
+}
+
+#[tokio::test]
+async fn test_metrics() {
+    let server_fixture = ServerFixture::create_shared().await;
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("debug")
+        .arg("metrics")
+        .arg("--http-host")
+        .arg(server_fixture.http_base())
+        .arg("--filter")
+        .arg("^jemalloc_")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("jemalloc_memstats_bytes (gauge)")
+                .and(predicate::str::contains(r#"{stat="active"}"#))
+                .and(predicate::str::contains("http_requests").not()),
+        );
+}
+
+#[tokio::test]
+async fn test_metrics_error() {
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("debug")
+        .arg("metrics")
+        .arg("--http-host")
+        .arg("http://127.0.0.1:1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Error fetching metrics"));
+}
+
+fn find_parquet_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(find_parquet_files(&path));
+        } else if path
+            .extension()
+            .map(|ext| ext == "parquet")
+            .unwrap_or(false)
+        {
+            files.push(path);
+        }
+    }
+    files
+}
@@ -1,6 +1,8 @@
 use super::scenario::{collect_query, create_readable_database, rand_name, Scenario};
 use crate::common::server_fixture::ServerFixture;
-use arrow_util::assert_batches_eq;
+use arrow::datatypes::DataType;
+use arrow_util::{assert_batches_eq, assert_batches_sorted_eq};
+use influxdb_iox_client::flight::QueryParams;
 
 #[tokio::test]
 pub async fn test() {
@@ -45,3 +47,166 @@ pub async fn test_no_rows() {
     let batch = query_results.next().await.unwrap();
     assert!(batch.is_none());
 }
+
+#[tokio::test]
+pub async fn test_params() {
+    let server_fixture = ServerFixture::create_shared().await;
+
+    let db_name = rand_name();
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    let mut write_client = server_fixture.write_client();
+    let lp_lines = vec![
+        "cpu,region=west user=23.2 100",
+        "cpu,region=east user=21.0 150",
+    ];
+    write_client
+        .write(&db_name, lp_lines.join("\n"))
+        .await
+        .expect("write succeded");
+
+    let mut client = server_fixture.flight_client();
+    let expected = vec![
+        "+--------+------+",
+        "| region | user |",
+        "+--------+------+",
+        "| west   | 23.2 |",
+        "+--------+------+",
+    ];
+
+    let query_results = client
+        .perform_query_with_params(
+            &db_name,
+            "select region, user from cpu where region = $1 and user > $2",
+            QueryParams::positional(vec!["west".into(), 20.0.into()]),
+        )
+        .await
+        .unwrap();
+    let batches = collect_query(query_results).await;
+    assert_batches_eq!(&expected, &batches);
+
+    let query_results = client
+        .perform_query_with_params(
+            &db_name,
+            "select region, user from cpu where region = $region",
+            QueryParams::named(vec![("region", "west".into())]),
+        )
+        .await
+        .unwrap();
+    let batches = collect_query(query_results).await;
+    assert_batches_eq!(&expected, &batches);
+
+    // values are never interpolated into the query
+    let query_results = client
+        .perform_query_with_params(
+            &db_name,
+            "select region, user from cpu where region = $1",
+            QueryParams::positional(vec!["west' or region = 'east".into()]),
+        )
+        .await
+        .unwrap();
+    let batches = collect_query(query_results).await;
+    assert!(batches.iter().all(|batch| batch.num_rows() == 0));
+
+    let err = client
+        .perform_query_with_params(
+            &db_name,
+            "select region, user from cpu where region = $2",
+            QueryParams::positional(vec!["west".into()]),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("No value was bound to the query parameter $2"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+pub async fn test_dictionary_encoded_tags() {
+    let server_fixture = ServerFixture::create_shared().await;
+
+    let db_name = rand_name();
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    let mut write_client = server_fixture.write_client();
+    let lp_lines = vec![
+        "cpu,region=west,host=a user=23.2 100",
+        "cpu,region=east,host=b user=21.0 150",
+        "cpu,region=west,host=c user=22.1 200",
+    ];
+    write_client
+        .write(&db_name, lp_lines.join("\n"))
+        .await
+        .expect("write succeded");
+
+    let mut client = server_fixture.flight_client();
+    let query_results = client
+        .perform_query(&db_name, "select host, region, user from cpu")
+        .await
+        .unwrap();
+
+    // tags are sent to the client without materializing their dictionaries
+    let schema = query_results.schema();
+    let tag_type = DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8));
+    assert_eq!(
+        schema.field_with_name("host").unwrap().data_type(),
+        &tag_type
+    );
+    assert_eq!(
+        schema.field_with_name("region").unwrap().data_type(),
+        &tag_type
+    );
+
+    let batches = collect_query(query_results).await;
+    let expected = vec![
+        "+------+--------+------+",
+        "| host | region | user |",
+        "+------+--------+------+",
+        "| a    | west   | 23.2 |",
+        "| b    | east   | 21   |",
+        "| c    | west   | 22.1 |",
+        "+------+--------+------+",
+    ];
+    assert_batches_sorted_eq!(&expected, &batches);
+}
+
+#[tokio::test]
+pub async fn test_cross_database() {
+    let server_fixture = ServerFixture::create_shared().await;
+
+    let db_name = rand_name();
+    let other_db_name = rand_name();
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+    create_readable_database(&other_db_name, server_fixture.grpc_channel()).await;
+
+    let mut write_client = server_fixture.write_client();
+    write_client
+        .write(&db_name, "cpu,region=west user=23.2 100")
+        .await
+        .expect("write succeded");
+    write_client
+        .write(&other_db_name, "cpu,region=east user=21.0 150")
+        .await
+        .expect("write succeded");
+
+    let mut client = server_fixture.flight_client();
+    let sql_query = format!(
+        r#"select region, user from cpu union all select region, user from "{}".cpu"#,
+        other_db_name
+    );
+    let query_results = client.perform_query(&db_name, &sql_query).await.unwrap();
+    let batches = collect_query(query_results).await;
+
+    let expected = vec![
+        "+--------+------+",
+        "| region | user |",
+        "+--------+------+",
+        "| east   | 21   |",
+        "| west   | 23.2 |",
+        "+--------+------+",
+    ];
+    assert_batches_sorted_eq!(&expected, &batches);
+}
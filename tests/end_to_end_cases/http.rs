@@ -11,6 +11,19 @@ async fn test_http_error_messages() {
         .await
         .expect_err("Should have errored");
 
-    let expected_error = "HTTP request returned an error: 400 Bad Request, `{\"error\":\"Error parsing line protocol: A generic parsing error occurred: TakeWhile1\",\"error_code\":100}`";
-    assert_eq!(result.to_string(), expected_error);
+    // The body follows the InfluxDB 2.x error format
+    let message = "partial write error (0 written): unable to parse line 1 (byte offset 0): 'arbitrary': A generic parsing error occurred: TakeWhile1";
+    let result = result.to_string();
+    assert!(
+        result.starts_with("HTTP request returned an error: 400 Bad Request"),
+        "{}",
+        result
+    );
+    assert!(result.contains(r#""code":"invalid""#), "{}", result);
+    assert!(
+        result.contains(&format!(r#""message":"{}""#, message)),
+        "{}",
+        result
+    );
+    assert!(result.contains(r#""error_code":100"#), "{}", result);
 }
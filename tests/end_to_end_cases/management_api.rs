@@ -4,7 +4,15 @@ use generated_types::{
     google::protobuf::{Duration, Empty},
     influxdata::iox::management::v1::{database_rules::RoutingRules, *},
 };
-use influxdb_iox_client::{management::CreateDatabaseError, operations, write::WriteError};
+use influxdb_iox_client::{
+    management::{
+        BackupDatabaseError, CancelQueryError, CreateContinuousQueryError, CreateDatabaseError,
+        DeleteContinuousQueryError, DeleteDatabaseError, ListContinuousQueriesError,
+        ListQueriesError, RestoreDatabaseError, RestoreDeletedDatabaseError, WatchChunksError,
+    },
+    operations,
+    write::WriteError,
+};
 
 use test_helpers::assert_contains;
 
@@ -192,6 +200,171 @@ async fn test_list_databases() {
     assert!(names.contains(&name));
 }
 
+#[tokio::test]
+async fn test_delete_database() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let mut client = server_fixture.management_client();
+    let mut write_client = server_fixture.write_client();
+
+    let db_name = rand_name();
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    write_client
+        .write(&db_name, "cpu,region=west user=23.2 100")
+        .await
+        .expect("write succeded");
+
+    // Detach the database but keep its data
+    client
+        .delete_database(&db_name, true)
+        .await
+        .expect("delete database failed");
+
+    let names = client
+        .list_databases()
+        .await
+        .expect("list databases failed");
+    assert!(!names.contains(&db_name));
+
+    let err = write_client
+        .write(&db_name, "cpu,region=west user=21.0 150")
+        .await
+        .expect_err("expected write to fail");
+    assert_contains!(
+        err.to_string(),
+        format!("Resource database/{} not found", db_name)
+    );
+
+    // The name can be used again
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    client
+        .delete_database(&db_name, false)
+        .await
+        .expect("delete database failed");
+
+    let err = client
+        .delete_database(&db_name, false)
+        .await
+        .expect_err("expected delete to fail");
+    assert!(matches!(dbg!(err), DeleteDatabaseError::DatabaseNotFound));
+
+    // The deleted database keeps its name until it is purged
+    let err = client
+        .create_database(DatabaseRules {
+            name: db_name.clone(),
+            ..Default::default()
+        })
+        .await
+        .expect_err("expected create to fail");
+    assert!(matches!(
+        dbg!(err),
+        CreateDatabaseError::DatabaseAlreadyExists
+    ));
+
+    client
+        .restore_deleted_database(&db_name)
+        .await
+        .expect("restore deleted database failed");
+
+    let names = client
+        .list_databases()
+        .await
+        .expect("list databases failed");
+    assert!(names.contains(&db_name));
+
+    let err = client
+        .restore_deleted_database(&db_name)
+        .await
+        .expect_err("expected restore to fail");
+    assert!(matches!(
+        dbg!(err),
+        RestoreDeletedDatabaseError::DatabaseAlreadyExists
+    ));
+}
+
+#[tokio::test]
+async fn test_backup_restore_database() {
+    use influxdb_iox_client::management::generated_types::ChunkStorage;
+
+    let server_fixture = ServerFixture::create_shared().await;
+    let mut client = server_fixture.management_client();
+    let mut write_client = server_fixture.write_client();
+    let mut operations_client = server_fixture.operations_client();
+
+    let db_name = rand_name();
+    let backup = format!("backups/{}", rand_name());
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    write_client
+        .write(&db_name, "cpu,region=west user=23.2 100")
+        .await
+        .expect("write succeded");
+
+    // persist a chunk so that the backup contains a parquet file
+    let operation = client
+        .close_partition_chunk(&db_name, "cpu", "cpu", 0)
+        .await
+        .expect("close chunk failed");
+    operations_client
+        .wait_operation(operation.id(), Some(std::time::Duration::from_secs(1)))
+        .await
+        .expect("failed to wait operation");
+    let operation = client
+        .persist_partition_chunk(&db_name, "cpu", "cpu", 0)
+        .await
+        .expect("persist chunk failed");
+    operations_client
+        .wait_operation(operation.id(), Some(std::time::Duration::from_secs(1)))
+        .await
+        .expect("failed to wait operation");
+
+    let copied = client
+        .backup_database(&db_name, &backup)
+        .await
+        .expect("backup database failed");
+    assert!(copied >= 3, "copied {} files", copied);
+
+    let err = client
+        .backup_database(&db_name, &backup)
+        .await
+        .expect_err("expected backup to fail");
+    assert!(matches!(
+        dbg!(err),
+        BackupDatabaseError::BackupAlreadyExists
+    ));
+
+    let err = client
+        .restore_database(&db_name, &backup)
+        .await
+        .expect_err("expected restore to fail");
+    assert!(matches!(
+        dbg!(err),
+        RestoreDatabaseError::DatabaseAlreadyExists
+    ));
+
+    client
+        .delete_database(&db_name, false)
+        .await
+        .expect("delete database failed");
+
+    let err = client
+        .restore_database(&db_name, "backups/does_not_exist")
+        .await
+        .expect_err("expected restore to fail");
+    assert!(matches!(dbg!(err), RestoreDatabaseError::BackupNotFound));
+
+    let restored = client
+        .restore_database(&db_name, &backup)
+        .await
+        .expect("restore database failed");
+    assert_eq!(restored, copied);
+
+    let chunks = client.list_chunks(&db_name).await.expect("listing chunks");
+    assert_eq!(chunks.len(), 1, "Chunks: {:#?}", chunks);
+    assert_eq!(chunks[0].storage, ChunkStorage::ObjectStoreOnly as i32);
+}
+
 #[tokio::test]
 async fn test_create_get_update_database() {
     let server_fixture = ServerFixture::create_shared().await;
@@ -222,6 +395,38 @@ async fn test_create_get_update_database() {
             nanos: 0,
         }),
         write_buffer_connection_string: "".into(),
+        replication_config: None,
+        quotas: Some(Quotas {
+            max_tables: 100,
+            max_columns_per_table: 0,
+            max_rows: 0,
+        }),
+        line_limits: Some(LineLimits {
+            max_line_length: 0,
+            max_fields_per_line: 100,
+            max_columns_per_table: 0,
+            max_table_name_length: 0,
+        }),
+        schema_conflict_policy: SchemaConflictPolicy::Coerce as _,
+        type_coercion_rules: vec![TypeCoercionRule {
+            from: ColumnType::I64 as _,
+            to: ColumnType::F64 as _,
+        }],
+        rollup_rules: vec![RollupRule {
+            source_table: "cpu".into(),
+            target_table: "cpu_5m".into(),
+            window: Some(Duration {
+                seconds: 300,
+                nanos: 0,
+            }),
+            aggregate: RollupAggregate::Mean as _,
+            delay: Some(Duration {
+                seconds: 60,
+                nanos: 0,
+            }),
+        }],
+
+        continuous_queries: vec![],
     };
 
     client
@@ -240,6 +445,12 @@ async fn test_create_get_update_database() {
         ignore_errors: true,
         ..Default::default()
     }));
+    rules.replication_config = Some(ReplicationConfig {
+        targets: Some(NodeGroup {
+            nodes: vec![node_group::Node { id: 2 }],
+        }),
+        queue_size: 100,
+    });
 
     let updated_rules = client
         .update_database(rules.clone())
@@ -307,10 +518,12 @@ async fn test_chunk_get() {
             id: 0,
             storage: ChunkStorage::OpenMutableBuffer as i32,
             estimated_bytes: 100,
+            allocated_bytes: 0,
             row_count: 2,
             time_of_first_write: None,
             time_of_last_write: None,
             time_closed: None,
+            read_buffer: None,
         },
         Chunk {
             partition_key: "disk".into(),
@@ -318,10 +531,12 @@ async fn test_chunk_get() {
             id: 0,
             storage: ChunkStorage::OpenMutableBuffer as i32,
             estimated_bytes: 82,
+            allocated_bytes: 0,
             row_count: 1,
             time_of_first_write: None,
             time_of_last_write: None,
             time_closed: None,
+            read_buffer: None,
         },
     ];
     assert_eq!(
@@ -369,9 +584,11 @@ async fn test_partition_list() {
     let expected = vec![
         Partition {
             key: "cpu".to_string(),
+            ..Default::default()
         },
         Partition {
             key: "mem".to_string(),
+            ..Default::default()
         },
     ];
 
@@ -397,8 +614,6 @@ async fn test_partition_list_error() {
 
 #[tokio::test]
 async fn test_partition_get() {
-    use generated_types::influxdata::iox::management::v1::Partition;
-
     let fixture = ServerFixture::create_shared().await;
     let mut management_client = fixture.management_client();
 
@@ -411,13 +626,17 @@ async fn test_partition_get() {
         .await
         .expect("getting partition");
 
-    let expected = Partition { key: "cpu".into() };
-
-    assert_eq!(
-        expected, partition,
-        "expected:\n\n{:#?}\n\nactual:{:#?}",
-        expected, partition
-    );
+    assert_eq!(partition.key, "cpu");
+    assert_eq!(partition.tables.len(), 1, "{:#?}", partition);
+
+    let table = &partition.tables[0];
+    assert_eq!(table.table_name, "cpu");
+    assert_eq!(table.mutable_buffer_chunks, 1);
+    assert_eq!(table.read_buffer_chunks, 0);
+    assert_eq!(table.object_store_chunks, 0);
+    assert_eq!(table.row_count, 1);
+    assert!(table.memory_bytes > 0);
+    assert_eq!(table.object_store_bytes, 0);
 }
 
 #[tokio::test]
@@ -486,10 +705,12 @@ async fn test_list_partition_chunks() {
         id: 0,
         storage: ChunkStorage::OpenMutableBuffer as i32,
         estimated_bytes: 100,
+        allocated_bytes: 0,
         row_count: 2,
         time_of_first_write: None,
         time_of_last_write: None,
         time_closed: None,
+        read_buffer: None,
     }];
 
     assert_eq!(
@@ -685,6 +906,74 @@ async fn test_close_partition_chunk() {
     assert_eq!(chunks[0].storage, ChunkStorage::ReadBuffer as i32);
 }
 
+#[tokio::test]
+async fn test_watch_chunks() {
+    let fixture = ServerFixture::create_shared().await;
+    let mut management_client = fixture.management_client();
+    let mut write_client = fixture.write_client();
+    let mut operations_client = fixture.operations_client();
+
+    let db_name = rand_name();
+    create_readable_database(&db_name, fixture.grpc_channel()).await;
+
+    let mut events = management_client
+        .watch_chunks(&db_name)
+        .await
+        .expect("watching chunks");
+
+    write_client
+        .write(&db_name, "cpu,region=west user=23.2 100")
+        .await
+        .expect("write succeded");
+
+    let operation = management_client
+        .close_partition_chunk(&db_name, "cpu", "cpu", 0)
+        .await
+        .expect("close partition chunk");
+
+    operations_client
+        .wait_operation(operation.id(), Some(std::time::Duration::from_secs(1)))
+        .await
+        .expect("failed to wait operation");
+
+    let mut states = vec![];
+    for _ in 0..4 {
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), events.next())
+            .await
+            .expect("timeout waiting for chunk event")
+            .expect("receiving chunk event")
+            .expect("stream ended");
+
+        assert_eq!(event.partition_key, "cpu");
+        assert_eq!(event.table_name, "cpu");
+        assert_eq!(event.id, 0);
+        states.push(event.state());
+    }
+
+    assert_eq!(
+        states,
+        vec![
+            ChunkLifecycleState::Open,
+            ChunkLifecycleState::Closed,
+            ChunkLifecycleState::Moving,
+            ChunkLifecycleState::Moved,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_watch_chunks_error() {
+    let fixture = ServerFixture::create_shared().await;
+    let mut management_client = fixture.management_client();
+
+    let err = management_client
+        .watch_chunks("this_database_does_not_exist")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(dbg!(err), WatchChunksError::DatabaseNotFound));
+}
+
 #[tokio::test]
 async fn test_close_partition_chunk_error() {
     let fixture = ServerFixture::create_shared().await;
@@ -703,6 +992,73 @@ async fn test_close_partition_chunk_error() {
     assert_contains!(err.to_string(), "Database not found");
 }
 
+#[tokio::test]
+async fn test_drop_partition() {
+    let fixture = ServerFixture::create_shared().await;
+    let mut management_client = fixture.management_client();
+    let mut write_client = fixture.write_client();
+
+    let db_name = rand_name();
+    create_readable_database(&db_name, fixture.grpc_channel()).await;
+
+    let lp_lines = vec![
+        "cpu,region=west user=23.2 100",
+        "mem,region=west free=100000 150",
+    ];
+
+    write_client
+        .write(&db_name, lp_lines.join("\n"))
+        .await
+        .expect("write succeded");
+
+    management_client
+        .drop_partition(&db_name, "cpu", false)
+        .await
+        .expect("dropping partition");
+
+    let partitions = management_client
+        .list_partitions(&db_name)
+        .await
+        .expect("listing partition");
+    let partition_keys: Vec<_> = partitions.into_iter().map(|p| p.key).collect();
+    assert_eq!(partition_keys, vec!["mem".to_string()]);
+
+    let chunks = management_client
+        .list_partition_chunks(&db_name, "cpu")
+        .await
+        .expect("listing chunks");
+    assert!(chunks.is_empty(), "Chunks: {:#?}", chunks);
+}
+
+#[tokio::test]
+async fn test_drop_partition_error() {
+    let fixture = ServerFixture::create_shared().await;
+    let mut management_client = fixture.management_client();
+
+    let err = management_client
+        .drop_partition("this database does not exist", "cpu", false)
+        .await
+        .expect_err("expected error");
+
+    assert_contains!(
+        err.to_string(),
+        "Resource database/this database does not exist not found"
+    );
+
+    let db_name = rand_name();
+    create_readable_database(&db_name, fixture.grpc_channel()).await;
+
+    let err = management_client
+        .drop_partition(&db_name, "non_existing_partition", false)
+        .await
+        .expect_err("expected error");
+
+    assert_contains!(
+        err.to_string(),
+        "Resource partition/non_existing_partition not found"
+    );
+}
+
 #[tokio::test]
 async fn test_chunk_lifecycle() {
     use influxdb_iox_client::management::generated_types::ChunkStorage;
@@ -759,7 +1115,9 @@ async fn test_chunk_lifecycle() {
     }
 }
 
-/// Normalizes a set of Chunks for comparison by removing timestamps
+/// Normalizes a set of Chunks for comparison by removing timestamps and
+/// allocated bytes, which depend on how the mutable buffer grows its
+/// allocations
 fn normalize_chunks(chunks: Vec<Chunk>) -> Vec<Chunk> {
     chunks
         .into_iter()
@@ -779,10 +1137,12 @@ fn normalize_chunks(chunks: Vec<Chunk>) -> Vec<Chunk> {
                 id,
                 storage,
                 estimated_bytes,
+                allocated_bytes: 0,
                 row_count,
                 time_of_first_write: None,
                 time_of_last_write: None,
                 time_closed: None,
+                read_buffer: None,
             }
         })
         .collect::<Vec<_>>()
@@ -834,3 +1194,130 @@ async fn test_get_server_status() {
     let names_expected: HashSet<_> = [db_name1, db_name2].iter().cloned().collect();
     assert_eq!(names_actual, names_expected);
 }
+
+#[tokio::test]
+async fn test_continuous_queries() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let mut client = server_fixture.management_client();
+
+    let db_name = rand_name();
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    let query = ContinuousQuery {
+        name: "max_user".to_string(),
+        query: "select max(user) as user from cpu".to_string(),
+        target_table: "cpu_max".to_string(),
+        every: Some(Duration {
+            seconds: 60,
+            nanos: 0,
+        }),
+        offset: Some(Duration {
+            seconds: 0,
+            nanos: 0,
+        }),
+    };
+
+    client
+        .create_continuous_query(&db_name, query.clone())
+        .await
+        .expect("create continuous query failed");
+
+    let err = client
+        .create_continuous_query(&db_name, query.clone())
+        .await
+        .expect_err("expected duplicate name to fail");
+    assert!(matches!(
+        dbg!(err),
+        CreateContinuousQueryError::AlreadyExists(_)
+    ));
+
+    let err = client
+        .create_continuous_query(
+            &db_name,
+            ContinuousQuery {
+                name: "invalid".to_string(),
+                every: None,
+                ..query.clone()
+            },
+        )
+        .await
+        .expect_err("expected invalid query to fail");
+    assert!(matches!(
+        dbg!(err),
+        CreateContinuousQueryError::InvalidQuery(_)
+    ));
+
+    let queries = client
+        .list_continuous_queries(&db_name)
+        .await
+        .expect("list continuous queries failed");
+    assert_eq!(queries, vec![query]);
+
+    // the queries are part of the database rules
+    let rules = client
+        .get_database(&db_name)
+        .await
+        .expect("get database failed");
+    assert_eq!(rules.continuous_queries, queries);
+
+    client
+        .delete_continuous_query(&db_name, "max_user")
+        .await
+        .expect("delete continuous query failed");
+
+    let err = client
+        .delete_continuous_query(&db_name, "max_user")
+        .await
+        .expect_err("expected delete to fail");
+    assert!(matches!(dbg!(err), DeleteContinuousQueryError::NotFound(_)));
+
+    let queries = client
+        .list_continuous_queries(&db_name)
+        .await
+        .expect("list continuous queries failed");
+    assert!(queries.is_empty());
+
+    let err = client
+        .list_continuous_queries(rand_name())
+        .await
+        .expect_err("expected unknown database to fail");
+    assert!(matches!(
+        dbg!(err),
+        ListContinuousQueriesError::DatabaseNotFound(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_running_queries() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let mut client = server_fixture.management_client();
+    let mut flight_client = server_fixture.flight_client();
+
+    let db_name = rand_name();
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    // completed queries are not listed
+    let mut query_results = flight_client
+        .perform_query(&db_name, "select * from system.chunks")
+        .await
+        .expect("query failed");
+    while query_results.next().await.unwrap().is_some() {}
+
+    let queries = client
+        .list_queries(&db_name)
+        .await
+        .expect("list queries failed");
+    assert!(queries.is_empty());
+
+    let err = client
+        .cancel_query(&db_name, "42")
+        .await
+        .expect_err("expected cancelling an unknown query to fail");
+    assert!(matches!(dbg!(err), CancelQueryError::NotFound(_)));
+
+    let err = client
+        .list_queries(rand_name())
+        .await
+        .expect_err("expected unknown database to fail");
+    assert!(matches!(dbg!(err), ListQueriesError::DatabaseNotFound(_)));
+}
@@ -1,6 +1,8 @@
 use assert_cmd::Command;
 use data_types::job::{Job, Operation};
+use flate2::{write::GzEncoder, Compression};
 use predicates::prelude::*;
+use std::{convert::TryInto, io::Write};
 use test_helpers::make_temp_file;
 
 use crate::common::server_fixture::ServerFixture;
@@ -104,6 +106,311 @@ async fn test_create_database() {
         );
 }
 
+#[tokio::test]
+async fn test_delete_database() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+    let db = &db_name;
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("create")
+        .arg(db)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ok"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("delete")
+        .arg(db)
+        .arg("--preserve-data")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ok"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("get")
+        .arg(db)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Database not found"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("delete")
+        .arg(db)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Database not found"));
+}
+
+#[tokio::test]
+async fn test_backup_restore_database() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+    let db = &db_name;
+    let backup = format!("backups/{}", rand_name());
+
+    create_readable_database(db, server_fixture.grpc_channel()).await;
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("backup")
+        .arg(db)
+        .arg(&backup)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Copied"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("backup")
+        .arg(db)
+        .arg(&backup)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Backup already exists"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("delete")
+        .arg(db)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success();
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("restore")
+        .arg(db)
+        .arg(&backup)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Copied"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("restore")
+        .arg(db)
+        .arg(&backup)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Database already exists"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("get")
+        .arg(db)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(db));
+}
+
+#[tokio::test]
+async fn test_clone_database() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+    let db = &db_name;
+    let rules_only = rand_name();
+    let with_data = rand_name();
+
+    create_readable_database(db, server_fixture.grpc_channel()).await;
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("clone")
+        .arg(db)
+        .arg(&rules_only)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ok"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("clone")
+        .arg(db)
+        .arg(&with_data)
+        .arg("--with-data")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Copied"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("get")
+        .arg(&with_data)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(&with_data));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("clone")
+        .arg(db)
+        .arg(&rules_only)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Database already exists"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("clone")
+        .arg(rand_name())
+        .arg(rand_name())
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Database not found"));
+}
+
+#[tokio::test]
+async fn test_export_database() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+    let db = &db_name;
+    let dir = tempfile::tempdir().unwrap();
+    let destination = format!("file://{}", dir.path().display());
+
+    create_readable_database(db, server_fixture.grpc_channel()).await;
+    load_lp(
+        addr,
+        db,
+        vec![
+            "cpu,region=west user=23.2 100",
+            "mem,region=west free=10i 200",
+        ],
+    );
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("export")
+        .arg(db)
+        .arg(&destination)
+        .arg("--stop")
+        .arg("150")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote 1 files"));
+
+    assert!(dir
+        .path()
+        .join("cpu/partition_key=cpu/data.parquet")
+        .exists());
+    assert!(!dir.path().join("mem").exists());
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("export")
+        .arg(db)
+        .arg(&destination)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Export destination is not empty"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("export")
+        .arg(rand_name())
+        .arg(format!("exports/{}", rand_name()))
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Database not found"));
+}
+
+#[tokio::test]
+async fn test_import_tsm() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+    let db = &db_name;
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.tsm");
+
+    create_readable_database(db, server_fixture.grpc_channel()).await;
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("import-tsm")
+        .arg(db)
+        .arg(&missing)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid TSM file"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("import-tsm")
+        .arg(rand_name())
+        .arg(&missing)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Database not found"));
+}
+
 #[tokio::test]
 async fn test_create_database_size() {
     let server_fixture = ServerFixture::create_shared().await;
@@ -234,6 +541,28 @@ async fn test_list_chunks_error() {
         );
 }
 
+#[tokio::test]
+async fn test_watch_chunks_error() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    // note don't make the database, expect error
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("chunk")
+        .arg("watch")
+        .arg(&db_name)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Error watching chunks: Database not found",
+        ));
+}
+
 #[tokio::test]
 async fn test_remotes() {
     let server_fixture = ServerFixture::create_single_use().await;
@@ -259,7 +588,8 @@ async fn test_remotes() {
         .arg("--host")
         .arg(addr)
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("Ok"));
 
     Command::cargo_bin("influxdb_iox")
         .unwrap()
@@ -281,14 +611,27 @@ async fn test_remotes() {
         .arg("--host")
         .arg(addr)
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("Ok"));
 
     Command::cargo_bin("influxdb_iox")
         .unwrap()
         .arg("server")
         .arg("remote")
-        .arg("list")
-        .arg("--host")
+        .arg("remove")
+        .arg("1")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Remote 1 not found"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("server")
+        .arg("remote")
+        .arg("list")
+        .arg("--host")
         .arg(addr)
         .assert()
         .success()
@@ -340,6 +683,160 @@ async fn test_list_partitions_error() {
         .stderr(predicate::str::contains("Database not found"));
 }
 
+#[tokio::test]
+async fn test_create_database_partition_template() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("create")
+        .arg(&db_name)
+        .arg("--partition-template")
+        .arg("%Y-%m-%d/region=<region>")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ok"));
+
+    let lp_data = vec![
+        "cpu,region=west user=23.2 100",
+        "cpu,region=east user=21.0 150",
+    ];
+    load_lp(addr, &db_name, lp_data);
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("partition")
+        .arg("list")
+        .arg(&db_name)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("1970-01-01/region=west")
+                .and(predicate::str::contains("1970-01-01/region=east")),
+        );
+
+    // Invalid templates are rejected by the server
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("create")
+        .arg(rand_name())
+        .arg("--partition-template")
+        .arg("%Y-<region")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("partition_template"));
+}
+
+#[tokio::test]
+async fn test_create_database_quotas() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("create")
+        .arg(&db_name)
+        .arg("--max-tables")
+        .arg("1")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ok"));
+
+    load_lp(addr, &db_name, vec!["cpu,region=west user=23.2 100"]);
+
+    let lp_data_file = make_temp_file("mem,region=west free=100000 150");
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("write")
+        .arg(&db_name)
+        .arg(lp_data_file.as_ref())
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Table quota exceeded"));
+}
+
+#[tokio::test]
+async fn test_create_database_schema_conflict_policy() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("create")
+        .arg(&db_name)
+        .arg("--schema-conflict-policy")
+        .arg("coerce")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ok"));
+
+    load_lp(addr, &db_name, vec!["cpu,region=west user=23.2 100"]);
+    // The integer is converted to a float instead of being rejected
+    load_lp(addr, &db_name, vec!["cpu,region=west user=21i 150"]);
+}
+
+#[tokio::test]
+async fn test_create_database_type_coercion_rules() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("create")
+        .arg(&db_name)
+        .arg("--coerce-type")
+        .arg("i64:f64")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ok"));
+
+    load_lp(addr, &db_name, vec!["cpu,region=west user=23i 100"]);
+    // The integer column is widened to floats instead of rejecting the write
+    load_lp(addr, &db_name, vec!["cpu,region=west user=21.5 150"]);
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("create")
+        .arg(rand_name())
+        .arg("--coerce-type")
+        .arg("i64:string")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "expected one of `i64`, `u64` or `f64`",
+        ));
+}
+
 #[tokio::test]
 async fn test_get_partition() {
     let server_fixture = ServerFixture::create_shared().await;
@@ -367,7 +864,26 @@ async fn test_get_partition() {
         .arg(addr)
         .assert()
         .success()
-        .stdout(predicate::str::contains(expected));
+        .stdout(predicate::str::contains(expected))
+        .stdout(predicate::str::contains(r#""mutable_buffer_chunks": 1"#));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("partition")
+        .arg("get")
+        .arg(&db_name)
+        .arg("cpu")
+        .arg("--format")
+        .arg("csv")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "table_name,mutable_buffer_chunks,read_buffer_chunks,object_store_chunks,row_count",
+        ))
+        .stdout(predicate::str::contains("cpu,1,0,0,1,"));
 }
 
 #[tokio::test]
@@ -582,19 +1098,587 @@ async fn test_close_partition_chunk_error() {
         .stderr(predicate::str::contains("Database not found"));
 }
 
-/// Loads the specified lines into the named database
-fn load_lp(addr: &str, db_name: &str, lp_data: Vec<&str>) {
-    let lp_data_file = make_temp_file(lp_data.join("\n"));
+#[tokio::test]
+async fn test_close_chunk() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    let lp_data = vec!["cpu,region=west user=23.2 100"];
+    load_lp(addr, &db_name, lp_data);
 
     Command::cargo_bin("influxdb_iox")
         .unwrap()
         .arg("database")
-        .arg("write")
+        .arg("chunk")
+        .arg("close-chunk")
         .arg(&db_name)
-        .arg(lp_data_file.as_ref())
+        .arg("cpu")
+        .arg("cpu")
         .arg("--host")
         .arg(addr)
         .assert()
         .success()
-        .stdout(predicate::str::contains("Lines OK"));
+        .stdout(predicate::str::contains("Ok"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("chunk")
+        .arg("list")
+        .arg(&db_name)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ClosedMutableBuffer"));
+}
+
+#[tokio::test]
+async fn test_move_chunk() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    let lp_data = vec!["cpu,region=west user=23.2 100"];
+    load_lp(addr, &db_name, lp_data);
+
+    let stdout: Operation = serde_json::from_slice(
+        &Command::cargo_bin("influxdb_iox")
+            .unwrap()
+            .arg("database")
+            .arg("chunk")
+            .arg("move-chunk")
+            .arg(&db_name)
+            .arg("cpu")
+            .arg("cpu")
+            .arg("0")
+            .arg("--host")
+            .arg(addr)
+            .assert()
+            .success()
+            .get_output()
+            .stdout,
+    )
+    .expect("Expected JSON output");
+
+    let expected_job = Job::CloseChunk {
+        db_name,
+        partition_key: "cpu".into(),
+        table_name: "cpu".into(),
+        chunk_id: 0,
+    };
+
+    assert_eq!(
+        Some(expected_job),
+        stdout.job,
+        "operation was {:#?}",
+        stdout
+    );
+}
+
+#[tokio::test]
+async fn test_move_chunk_error() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("chunk")
+        .arg("move-chunk")
+        .arg("non_existent_database")
+        .arg("non_existent_partition")
+        .arg("non_existent_table")
+        .arg("0")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Database not found"));
+}
+
+#[tokio::test]
+async fn test_persist_partition_chunk() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    let lp_data = vec!["cpu,region=west user=23.2 100"];
+    load_lp(addr, &db_name, lp_data);
+
+    // move the chunk to the read buffer first
+    let operation = server_fixture
+        .management_client()
+        .close_partition_chunk(&db_name, "cpu", "cpu", 0)
+        .await
+        .expect("closing chunk");
+    let operation: Operation = operation.try_into().expect("valid operation");
+    server_fixture
+        .operations_client()
+        .wait_operation(operation.id, None)
+        .await
+        .expect("waiting for move");
+
+    let stdout: Operation = serde_json::from_slice(
+        &Command::cargo_bin("influxdb_iox")
+            .unwrap()
+            .arg("database")
+            .arg("partition")
+            .arg("persist-chunk")
+            .arg(&db_name)
+            .arg("cpu")
+            .arg("cpu")
+            .arg("0")
+            .arg("--wait")
+            .arg("--host")
+            .arg(addr)
+            .assert()
+            .success()
+            .get_output()
+            .stdout,
+    )
+    .expect("Expected JSON output");
+
+    let expected_job = Job::WriteChunk {
+        db_name: db_name.clone(),
+        partition_key: "cpu".into(),
+        table_name: "cpu".into(),
+        chunk_id: 0,
+    };
+
+    assert_eq!(
+        Some(expected_job),
+        stdout.job,
+        "operation was {:#?}",
+        stdout
+    );
+    assert_eq!(stdout.pending_count, 0, "operation was {:#?}", stdout);
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("chunk")
+        .arg("list")
+        .arg(&db_name)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ReadBufferAndObjectStore"));
+}
+
+#[tokio::test]
+async fn test_persist_partition_chunk_error() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("partition")
+        .arg("persist-chunk")
+        .arg("non_existent_database")
+        .arg("non_existent_partition")
+        .arg("non_existent_table")
+        .arg("0")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Database not found"));
+}
+
+#[tokio::test]
+async fn test_compact_partition() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    let lp_data = vec!["cpu,region=west user=23.2 100"];
+    load_lp(addr, &db_name, lp_data);
+
+    let stdout: Operation = serde_json::from_slice(
+        &Command::cargo_bin("influxdb_iox")
+            .unwrap()
+            .arg("database")
+            .arg("partition")
+            .arg("compact")
+            .arg(&db_name)
+            .arg("cpu")
+            .arg("cpu")
+            .arg("--wait")
+            .arg("--host")
+            .arg(addr)
+            .assert()
+            .success()
+            .get_output()
+            .stdout,
+    )
+    .expect("Expected JSON output");
+
+    let expected_job = Job::CompactChunks {
+        db_name: db_name.clone(),
+        partition_key: "cpu".into(),
+        table_name: "cpu".into(),
+    };
+
+    assert_eq!(
+        Some(expected_job),
+        stdout.job,
+        "operation was {:#?}",
+        stdout
+    );
+    assert_eq!(stdout.pending_count, 0, "operation was {:#?}", stdout);
+}
+
+#[tokio::test]
+async fn test_compact_partition_error() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("partition")
+        .arg("compact")
+        .arg("non_existent_database")
+        .arg("non_existent_partition")
+        .arg("non_existent_table")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Database not found"));
+}
+
+#[tokio::test]
+async fn test_drop_partition() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    let lp_data = vec![
+        "cpu,region=west user=23.2 100",
+        "mem,region=west free=100000 150",
+    ];
+    load_lp(addr, &db_name, lp_data);
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("partition")
+        .arg("drop")
+        .arg(&db_name)
+        .arg("cpu")
+        .arg("--delete-persisted")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ok"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("partition")
+        .arg("list")
+        .arg(&db_name)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mem").and(predicate::str::contains("cpu").not()));
+}
+
+#[tokio::test]
+async fn test_drop_partition_error() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("partition")
+        .arg("drop")
+        .arg("non_existent_database")
+        .arg("cpu")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Resource database/non_existent_database not found",
+        ));
+}
+
+#[tokio::test]
+async fn test_delete_rows() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    let lp_data = vec![
+        "cpu,region=west user=23.2 100",
+        "cpu,region=east user=21.5 120",
+        "cpu,region=west user=25.5 200",
+    ];
+    load_lp(addr, &db_name, lp_data);
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("delete-rows")
+        .arg(&db_name)
+        .arg("cpu")
+        .arg("--start")
+        .arg("0")
+        .arg("--stop")
+        .arg("150")
+        .arg("--tag")
+        .arg("region=west")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ok"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("query")
+        .arg(&db_name)
+        .arg("select user from cpu")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("21.5")
+                .and(predicate::str::contains("25.5"))
+                .and(predicate::str::contains("23.2").not()),
+        );
+}
+
+#[tokio::test]
+async fn test_delete_rows_error() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("delete-rows")
+        .arg(&db_name)
+        .arg("cpu")
+        .arg("--start")
+        .arg("100")
+        .arg("--stop")
+        .arg("100")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid predicate"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("delete-rows")
+        .arg("non_existent_database")
+        .arg("cpu")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Resource database/non_existent_database not found",
+        ));
+}
+
+#[tokio::test]
+async fn test_write_stdin_and_gzip() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("write")
+        .arg(&db_name)
+        .arg("-")
+        .arg("--host")
+        .arg(addr)
+        .write_stdin("cpu,region=west user=23.2 100\ncpu,region=west user=21.0 150")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 Lines OK"));
+
+    let dir = test_helpers::tmp_dir().unwrap();
+    let gz_file = dir.path().join("data.lp.gz");
+    let mut encoder = GzEncoder::new(
+        std::fs::File::create(&gz_file).unwrap(),
+        Compression::default(),
+    );
+    encoder
+        .write_all(b"disk,region=east bytes=99i 200\ndisk,region=east bytes=33i 300\ndisk,region=east bytes=11i 400\n")
+        .unwrap();
+    encoder.finish().unwrap();
+
+    // use a small batch size so the file is sent in multiple writes
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("write")
+        .arg(&db_name)
+        .arg(&gz_file)
+        .arg("--batch-size")
+        .arg("2")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3 Lines OK"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("query")
+        .arg(&db_name)
+        .arg("select count(*) as n from disk")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("| 3 |"));
+}
+
+/// Loads the specified lines into the named database
+fn load_lp(addr: &str, db_name: &str, lp_data: Vec<&str>) {
+    let lp_data_file = make_temp_file(lp_data.join("\n"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("write")
+        .arg(&db_name)
+        .arg(lp_data_file.as_ref())
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Lines OK"));
+}
+
+#[tokio::test]
+async fn test_continuous_queries() {
+    let server_fixture = ServerFixture::create_shared().await;
+    let addr = server_fixture.grpc_base();
+    let db_name = rand_name();
+
+    create_readable_database(&db_name, server_fixture.grpc_channel()).await;
+
+    let lp_data = vec![
+        "cpu,region=west user=23.2 100",
+        "cpu,region=west user=25.5 200",
+    ];
+    load_lp(addr, &db_name, lp_data);
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("continuous-query")
+        .arg("create")
+        .arg(&db_name)
+        .arg("max_user")
+        .arg("cpu_max")
+        .arg("select region, max(user) as user from cpu group by region")
+        .arg("--every-seconds")
+        .arg("1")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ok"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("continuous-query")
+        .arg("list")
+        .arg(&db_name)
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "max_user cpu_max every=1s offset=0s",
+        ));
+
+    // wait for the query to run
+    let t_start = std::time::Instant::now();
+    loop {
+        let output = Command::cargo_bin("influxdb_iox")
+            .unwrap()
+            .arg("database")
+            .arg("query")
+            .arg(&db_name)
+            .arg("select user from cpu_max")
+            .arg("--host")
+            .arg(addr)
+            .output()
+            .unwrap();
+        if String::from_utf8_lossy(&output.stdout).contains("25.5") {
+            break;
+        }
+        assert!(
+            t_start.elapsed() < std::time::Duration::from_secs(10),
+            "continuous query did not run"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    }
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("continuous-query")
+        .arg("delete")
+        .arg(&db_name)
+        .arg("max_user")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ok"));
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("continuous-query")
+        .arg("delete")
+        .arg(&db_name)
+        .arg("max_user")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
 }
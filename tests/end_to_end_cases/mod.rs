@@ -1,3 +1,4 @@
+mod debug_cli;
 pub mod flight_api;
 pub mod http;
 pub mod management_api;
@@ -86,3 +86,67 @@ async fn test_start_stop() {
     assert_eq!(completed.status, OperationStatus::Cancelled);
     assert_eq!(&completed.job, &operations[0].job)
 }
+
+#[tokio::test]
+async fn test_list_filter_and_wait_check() {
+    let server_fixture = ServerFixture::create_single_use().await;
+    let addr = server_fixture.grpc_base();
+    let duration = std::time::Duration::from_secs(10).as_nanos() as u64;
+
+    let operation: Operation = serde_json::from_slice(
+        &Command::cargo_bin("influxdb_iox")
+            .unwrap()
+            .arg("operation")
+            .arg("test")
+            .arg(duration.to_string())
+            .arg("--host")
+            .arg(addr)
+            .assert()
+            .success()
+            .get_output()
+            .stdout,
+    )
+    .expect("expected JSON output");
+
+    let list_operations = |args: &[&str]| -> Vec<Operation> {
+        serde_json::from_slice(
+            &Command::cargo_bin("influxdb_iox")
+                .unwrap()
+                .arg("operation")
+                .arg("list")
+                .args(args)
+                .arg("--host")
+                .arg(addr)
+                .assert()
+                .success()
+                .get_output()
+                .stdout,
+        )
+        .expect("expected JSON output")
+    };
+
+    let running = list_operations(&["--status", "running"]);
+    assert_eq!(running.len(), 1);
+    assert_eq!(running[0].id, operation.id);
+    assert!(list_operations(&["--status", "complete"]).is_empty());
+    // dummy jobs don't belong to any database
+    assert!(list_operations(&["--db", "foo"]).is_empty());
+
+    // the operation is still running when the wait times out
+    let timeout = std::time::Duration::from_millis(10).as_nanos() as u64;
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("operation")
+        .arg("wait")
+        .arg(operation.id.to_string())
+        .arg(timeout.to_string())
+        .arg("--check")
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Operation {} did not complete successfully: Running",
+            operation.id
+        )));
+}
@@ -1,6 +1,13 @@
+use std::sync::Arc;
+
+use arrow_util::assert_batches_eq;
 use assert_cmd::Command;
+use parquet::{
+    arrow::{ArrowReader, ParquetFileArrowReader},
+    file::serialized_reader::SerializedFileReader,
+};
 use predicates::prelude::*;
-use test_helpers::make_temp_file;
+use test_helpers::{assert_contains, make_temp_file};
 
 use crate::common::server_fixture::ServerFixture;
 
@@ -19,6 +26,8 @@ pub async fn test() {
     test_read_format_pretty(&db_name, addr).await;
     test_read_format_csv(&db_name, addr).await;
     test_read_format_json(&db_name, addr).await;
+    test_read_output_file(&db_name, addr).await;
+    test_read_explain(&db_name, addr).await;
     test_read_error(&db_name, addr).await;
 }
 
@@ -126,7 +135,7 @@ async fn test_read_format_pretty(db_name: &str, addr: &str) {
 
 async fn test_read_format_csv(db_name: &str, addr: &str) {
     let expected =
-        "west,1970-01-01T00:00:00.000000100,23.2\nwest,1970-01-01T00:00:00.000000150,21.0";
+        "west,1970-01-01T00:00:00.000000100Z,23.2\nwest,1970-01-01T00:00:00.000000150Z,21.0";
 
     Command::cargo_bin("influxdb_iox")
         .unwrap()
@@ -161,6 +170,121 @@ async fn test_read_format_json(db_name: &str, addr: &str) {
         .stdout(predicate::str::contains(expected));
 }
 
+async fn test_read_output_file(db_name: &str, addr: &str) {
+    let dir = test_helpers::tmp_dir().unwrap();
+
+    // the format is inferred from the file extension
+    let csv_file = dir.path().join("cpu.csv");
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("query")
+        .arg(db_name)
+        .arg("select * from cpu")
+        .arg("--host")
+        .arg(addr)
+        .arg("--output")
+        .arg(&csv_file)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    let expected = "region,time,user\n\
+                    west,1970-01-01T00:00:00.000000100Z,23.2\n\
+                    west,1970-01-01T00:00:00.000000150Z,21.0";
+    assert_contains!(std::fs::read_to_string(&csv_file).unwrap(), expected);
+
+    let parquet_file = dir.path().join("cpu.out");
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("query")
+        .arg(db_name)
+        .arg("select * from cpu")
+        .arg("--host")
+        .arg(addr)
+        .arg("--format")
+        .arg("parquet")
+        .arg("--output")
+        .arg(&parquet_file)
+        .assert()
+        .success();
+
+    let file_reader =
+        SerializedFileReader::new(std::fs::File::open(&parquet_file).unwrap()).unwrap();
+    let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
+    let batches: Vec<_> = arrow_reader
+        .get_record_reader(1024)
+        .unwrap()
+        .map(|batch| batch.unwrap())
+        .collect();
+    let expected = vec![
+        "+--------+-------------------------------+------+",
+        "| region | time                          | user |",
+        "+--------+-------------------------------+------+",
+        "| west   | 1970-01-01 00:00:00.000000100 | 23.2 |",
+        "| west   | 1970-01-01 00:00:00.000000150 | 21   |",
+        "+--------+-------------------------------+------+",
+    ];
+    assert_batches_eq!(expected, &batches);
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("query")
+        .arg(db_name)
+        .arg("select * from cpu")
+        .arg("--host")
+        .arg(addr)
+        .arg("--format")
+        .arg("parquet")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The 'parquet' format can only be written to a file, use --output",
+        ));
+}
+
+async fn test_read_explain(db_name: &str, addr: &str) {
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("query")
+        .arg(db_name)
+        .arg("select * from cpu")
+        .arg("--host")
+        .arg(addr)
+        .arg("--explain")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("| logical_plan ")
+                .and(predicate::str::contains("| physical_plan "))
+                .and(predicate::str::contains(
+                    "IOxReadFilterNode: table_name=cpu",
+                )),
+        );
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("query")
+        .arg(db_name)
+        .arg("select * from cpu")
+        .arg("--host")
+        .arg(addr)
+        .arg("--analyze")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("| analyzed_plan |")
+                .and(predicate::str::contains("metrics=[output_rows=2,"))
+                .and(predicate::str::contains(
+                    "| summary       | output_rows=2, elapsed=",
+                )),
+        );
+}
+
 async fn test_read_error(db_name: &str, addr: &str) {
     Command::cargo_bin("influxdb_iox")
         .unwrap()
@@ -189,6 +313,6 @@ async fn test_read_error(db_name: &str, addr: &str) {
         .assert()
         .failure()
         .stderr(predicate::str::contains(
-            "Unknown format type: not_a_valid_format. Expected one of 'pretty', 'csv' or 'json'",
+            "Unknown format type: not_a_valid_format. Expected one of 'pretty', 'csv', 'json', 'jsonl' or 'influxv1'",
         ));
 }
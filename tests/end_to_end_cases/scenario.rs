@@ -2,7 +2,7 @@ use std::{sync::Arc, time::SystemTime};
 
 use generated_types::google::protobuf::Empty;
 use generated_types::influxdata::iox::management::v1::*;
-use influxdb_iox_client::flight::PerformQuery;
+use influxdb_iox_client::{connection::Connection, flight::PerformQuery};
 use rand::{
     distributions::{Alphanumeric, Standard},
     thread_rng, Rng,
@@ -290,10 +290,7 @@ pub fn rand_id() -> String {
 /// given a channel to talk with the management api, create a new
 /// database with the specified name configured with a 10MB mutable
 /// buffer, partitioned on table
-pub async fn create_readable_database(
-    db_name: impl Into<String>,
-    channel: tonic::transport::Channel,
-) {
+pub async fn create_readable_database(db_name: impl Into<String>, channel: Connection) {
     let mut management_client = influxdb_iox_client::management::Client::new(channel);
 
     let rules = DatabaseRules {
@@ -318,10 +315,7 @@ pub async fn create_readable_database(
 
 /// given a channel to talk with the managment api, create a new
 /// database with no mutable buffer configured, no partitioning rules
-pub async fn create_unreadable_database(
-    db_name: impl Into<String>,
-    channel: tonic::transport::Channel,
-) {
+pub async fn create_unreadable_database(db_name: impl Into<String>, channel: Connection) {
     let mut management_client = influxdb_iox_client::management::Client::new(channel);
 
     let rules = DatabaseRules {
@@ -338,10 +332,7 @@ pub async fn create_unreadable_database(
 /// given a channel to talk with the managment api, create a new
 /// database with the specified name configured with a 10MB mutable
 /// buffer, partitioned on table, with some data written into two partitions
-pub async fn create_two_partition_database(
-    db_name: impl Into<String>,
-    channel: tonic::transport::Channel,
-) {
+pub async fn create_two_partition_database(db_name: impl Into<String>, channel: Connection) {
     let mut write_client = influxdb_iox_client::write::Client::new(channel.clone());
 
     let db_name = db_name.into();
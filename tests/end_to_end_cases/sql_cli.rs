@@ -353,3 +353,98 @@ order by
         .success()
         .stdout(predicate::str::contains(expected_output));
 }
+
+#[tokio::test]
+async fn test_sql_use_unknown_database() {
+    let fixture = ServerFixture::create_shared().await;
+    let addr = fixture.grpc_base();
+
+    let db_name = rand_name();
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("sql")
+        .arg("--host")
+        .arg(addr)
+        .write_stdin(format!("use {};\n\nshow databases;", db_name))
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains(format!("Database {} not found", db_name))
+                .and(predicate::str::contains("| db_name")),
+        );
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("sql")
+        .arg("--host")
+        .arg(addr)
+        .arg("--database")
+        .arg(&db_name)
+        .write_stdin("exit")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Database {} not found",
+            db_name
+        )));
+}
+
+#[tokio::test]
+async fn test_sql_database_option() {
+    let fixture = ServerFixture::create_shared().await;
+    let addr = fixture.grpc_base();
+
+    let db_name = rand_name();
+    create_two_partition_database(&db_name, fixture.grpc_channel()).await;
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("sql")
+        .arg("--host")
+        .arg(addr)
+        .arg("--no-history")
+        .arg("--database")
+        .arg(&db_name)
+        .write_stdin("select\n  count(*)\nfrom\n  cpu\n;")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("You are now in remote mode")
+                .and(predicate::str::contains("Returned 1 row in")),
+        );
+}
+
+#[tokio::test]
+async fn test_sql_meta_commands() {
+    let fixture = ServerFixture::create_shared().await;
+    let addr = fixture.grpc_base();
+
+    let db_name = rand_name();
+    create_two_partition_database(&db_name, fixture.grpc_channel()).await;
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("sql")
+        .arg("--host")
+        .arg(addr)
+        .arg("--no-history")
+        .write_stdin(format!(
+            "\\c {}\n\\dt\n\\d cpu\n\\timing off\nselect * from cpu;\n\\x\n\\q\nhelp;",
+            db_name
+        ))
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("You are now in remote mode")
+                // \dt
+                .and(predicate::str::contains("| mem"))
+                // \d cpu
+                .and(predicate::str::contains("| sleeping"))
+                .and(predicate::str::contains("Timing is off"))
+                .and(predicate::str::contains("Returned 1 row\n"))
+                .and(predicate::str::contains("invalid command \\x"))
+                // help is not run after \q
+                .and(predicate::str::contains("# Basic IOx SQL Primer").not()),
+        );
+}
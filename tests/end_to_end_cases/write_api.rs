@@ -1,10 +1,11 @@
-use influxdb_iox_client::write::WriteError;
+use influxdb_iox_client::write::{self, WriteError};
 use test_helpers::assert_contains;
 
 use crate::common::server_fixture::ServerFixture;
 
 use super::scenario::{create_readable_database, rand_name};
 use arrow_util::assert_batches_sorted_eq;
+use data_types::timestamp::Precision;
 use entry::{
     lines_to_sharded_entries,
     test_helpers::{partitioner, sharder},
@@ -77,13 +78,174 @@ async fn test_write() {
     }
     assert!(maybe_err.is_some());
     let err = maybe_err.unwrap();
-    let WriteError::ServerError(status) = dbg!(err);
+    let status = match dbg!(err) {
+        WriteError::ServerError(status) => status,
+        err => panic!("unexpected error: {}", err),
+    };
     assert_eq!(status.code(), tonic::Code::ResourceExhausted);
 
     // IMPORTANT: At this point, the database is flooded and pretty much
     // useless. Don't append any tests after the "hard limit" test!
 }
 
+#[tokio::test]
+async fn test_write_partial() {
+    let fixture = ServerFixture::create_shared().await;
+    let mut write_client = fixture.write_client();
+
+    let db_name = rand_name();
+    create_readable_database(&db_name, fixture.grpc_channel()).await;
+
+    let lp_lines = vec![
+        "cpu,region=west user=23.2 100",
+        "XXX",
+        "cpu,region=west user=\"high\" 150",
+        "cpu,region=east user=21.0 200",
+    ];
+
+    let err = write_client
+        .write(&db_name, lp_lines.join("\n"))
+        .await
+        .expect_err("expected write to be partial");
+
+    assert_eq!(
+        err.to_string(),
+        "Partial write: 2 lines written, 2 lines rejected"
+    );
+    let rejected_lines = match err {
+        WriteError::PartialWrite {
+            lines_written: 2,
+            rejected_lines,
+        } => rejected_lines,
+        err => panic!("unexpected error: {}", err),
+    };
+
+    let line_numbers: Vec<_> = rejected_lines.iter().map(|l| l.line_number).collect();
+    assert_eq!(line_numbers, vec![2, 3]);
+    assert_contains!(&rejected_lines[0].reason, "Invalid Line Protocol");
+    assert_contains!(&rejected_lines[1].reason, "Schema conflict");
+
+    let byte_offsets: Vec<_> = rejected_lines.iter().map(|l| l.byte_offset).collect();
+    assert_eq!(byte_offsets, vec![30, 34]);
+    assert_eq!(rejected_lines[0].snippet, "XXX");
+    assert_eq!(rejected_lines[1].snippet, lp_lines[2]);
+
+    let mut query_results = fixture
+        .flight_client()
+        .perform_query(&db_name, "select region, user from cpu")
+        .await
+        .unwrap();
+
+    let mut batches = Vec::new();
+    while let Some(data) = query_results.next().await.unwrap() {
+        batches.push(data);
+    }
+
+    let expected = vec![
+        "+--------+------+",
+        "| region | user |",
+        "+--------+------+",
+        "| east   | 21   |",
+        "| west   | 23.2 |",
+        "+--------+------+",
+    ];
+    assert_batches_sorted_eq!(&expected, &batches);
+}
+
+#[tokio::test]
+async fn test_write_precision() {
+    let fixture = ServerFixture::create_shared().await;
+    let mut write_client = fixture.write_client();
+
+    let db_name = rand_name();
+    create_readable_database(&db_name, fixture.grpc_channel()).await;
+
+    let lines_written = write_client
+        .write_with_precision(
+            &db_name,
+            "cpu,region=west user=23.2 1617286224",
+            write::generated_types::Precision::Seconds,
+        )
+        .await
+        .expect("write succeeded");
+    assert_eq!(lines_written, 1);
+
+    let mut query_results = fixture
+        .flight_client()
+        .perform_query(&db_name, "select region, time from cpu")
+        .await
+        .unwrap();
+
+    let mut batches = Vec::new();
+    while let Some(data) = query_results.next().await.unwrap() {
+        batches.push(data);
+    }
+
+    let expected = vec![
+        "+--------+---------------------+",
+        "| region | time                |",
+        "+--------+---------------------+",
+        "| west   | 2021-04-01 14:10:24 |",
+        "+--------+---------------------+",
+    ];
+    assert_batches_sorted_eq!(&expected, &batches);
+}
+
+#[tokio::test]
+async fn test_write_all_or_nothing() {
+    let fixture = ServerFixture::create_shared().await;
+    let mut write_client = fixture.write_client();
+
+    let db_name = rand_name();
+    create_readable_database(&db_name, fixture.grpc_channel()).await;
+
+    let lines_written = write_client
+        .write_all_or_nothing(&db_name, "cpu,region=west user=23.2 100")
+        .await
+        .expect("write succeeded");
+    assert_eq!(lines_written, 1);
+
+    let lp_lines = vec![
+        "cpu,region=east user=21.0 200",
+        "cpu,region=west user=\"high\" 150",
+        "cpu,region=east user=22.0 300",
+    ];
+
+    let err = write_client
+        .write_all_or_nothing(&db_name, lp_lines.join("\n"))
+        .await
+        .expect_err("expected write to be rejected");
+
+    assert_eq!(err.to_string(), "Write rejected: 1 lines rejected");
+    let rejected_lines = match err {
+        WriteError::RejectedWrite { rejected_lines } => rejected_lines,
+        err => panic!("unexpected error: {}", err),
+    };
+    assert_eq!(rejected_lines.len(), 1);
+    assert_eq!(rejected_lines[0].line_number, 2);
+    assert_contains!(&rejected_lines[0].reason, "Schema conflict");
+
+    let mut query_results = fixture
+        .flight_client()
+        .perform_query(&db_name, "select region, user from cpu")
+        .await
+        .unwrap();
+
+    let mut batches = Vec::new();
+    while let Some(data) = query_results.next().await.unwrap() {
+        batches.push(data);
+    }
+
+    let expected = vec![
+        "+--------+------+",
+        "| region | user |",
+        "+--------+------+",
+        "| west   | 23.2 |",
+        "+--------+------+",
+    ];
+    assert_batches_sorted_eq!(&expected, &batches);
+}
+
 #[tokio::test]
 async fn test_write_entry() {
     let fixture = ServerFixture::create_shared().await;
@@ -96,9 +258,14 @@ async fn test_write_entry() {
 
     let lines: Vec<_> = parse_lines(&lp_data).map(|l| l.unwrap()).collect();
     let default_time = 456;
-    let sharded_entries =
-        lines_to_sharded_entries(&lines, default_time, sharder(1).as_ref(), &partitioner(1))
-            .unwrap();
+    let sharded_entries = lines_to_sharded_entries(
+        &lines,
+        default_time,
+        Precision::Nanoseconds,
+        sharder(1).as_ref(),
+        &partitioner(1),
+    )
+    .unwrap();
 
     let entry: Vec<u8> = sharded_entries.into_iter().next().unwrap().entry.into();
 
@@ -49,6 +49,33 @@ async fn test_write(db_name: &str, addr: &str) {
         .success()
         .stdout(predicate::str::contains("2 Lines OK"));
 
+    // rejected lines are reported with their number in the file
+    let lp_data_file = make_temp_file(
+        vec![
+            "cpu,region=west user=23.2 200",
+            "# a comment",
+            "",
+            "cpu,region=west user= 250",
+        ]
+        .join("\n"),
+    );
+
+    Command::cargo_bin("influxdb_iox")
+        .unwrap()
+        .arg("database")
+        .arg("write")
+        .arg(db_name)
+        .arg(lp_data_file.as_ref())
+        .arg("--host")
+        .arg(addr)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("1 Lines OK"))
+        .stderr(
+            predicate::str::contains("line 4: Invalid Line Protocol")
+                .and(predicate::str::contains("'cpu,region=west user= 250'")),
+        );
+
     // try reading a non existent file
     Command::cargo_bin("influxdb_iox")
         .unwrap()